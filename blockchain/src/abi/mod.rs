@@ -1,28 +1,11 @@
 // External imports
-use ethers::{
-    abi::{Abi, Function, Event},
-    contract::Contract,
-    middleware::Middleware,
-    types::{Address, H256, U256, Bytes, Filter, Log, AccessList},
-    utils::hex,
-};
+use ethers::abi::Abi;
 
 // Standard library imports
-use std::{
-    collections::{HashMap, HashSet},
-    sync::{Arc, Mutex, RwLock},
-    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
-    fmt::{self, Display, Formatter},
-    error::Error,
-    fs,
-    path::Path,
-};
+use std::fs;
 
 // Third party imports
 use anyhow::{Result, Context};
-use tracing::{info, warn, error, debug};
-use async_trait::async_trait;
-use tokio::time::{timeout, sleep};
 use serde_json;
 
 // Internal imports
@@ -66,7 +49,7 @@ pub fn read_abi_from_file(file_path: &str) -> Result<Abi> {
 /// * `anyhow::Error` - Lỗi khi ghi file hoặc serialize JSON
 pub fn save_abi_to_file(file_path: &str, abi: &Abi) -> Result<()> {
     let content = serde_json::to_string_pretty(abi)
-        .with_context(|| format!("Failed to serialize ABI to JSON"))?;
+        .with_context(|| "Failed to serialize ABI to JSON".to_string())?;
     fs::write(file_path, content)
         .with_context(|| format!("Failed to write ABI to file: {}", file_path))?;
     Ok(())
@@ -98,7 +81,7 @@ pub fn is_valid_abi_file(file_path: &str) -> Result<bool> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
+    
     use tempfile::NamedTempFile;
 
     /// Test đọc ABI từ file
@@ -1,21 +1,18 @@
 // External imports
 use ethers::{
     prelude::*,
-    providers::{Http, Provider, Middleware},
-    types::{Address, U256, H256, Bytes},
-    utils::keccak256,
+    providers::Middleware,
+    types::{Address, H256},
 };
-use anyhow::{Result, Context, anyhow};
+use anyhow::{Result, anyhow};
 use thiserror::Error;
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::time::{Instant, Duration};
-use std::str::FromStr;
-use tracing::{debug, error, info, warn};
 
 // Internal imports
-use crate::abi::abis::{PAIR_ABI, FACTORY_ABI, ERC20_ABI, ROUTER_ABI};
+use crate::abi::abis::{erc20_token, uniswap_v2_factory, uniswap_v2_pair, uniswap_v2_router};
 
 // Error type for contract operations
 #[derive(Error, Debug)]
@@ -60,7 +57,7 @@ impl<T> CacheEntry<T> {
 #[derive(Debug)]
 pub struct ContractManager<M: Middleware + 'static> {
     provider: Arc<M>,
-    contract_cache: Arc<RwLock<HashMap<Address, CacheEntry<Contract<Arc<M>>>>>>,
+    contract_cache: Arc<RwLock<HashMap<Address, CacheEntry<Contract<M>>>>>,
     abi_cache: Arc<RwLock<HashMap<String, CacheEntry<ethers::abi::Abi>>>>,
     contracts_info: Arc<RwLock<HashMap<Address, ContractInfo>>>,
 }
@@ -103,51 +100,51 @@ impl<M: Middleware + 'static> ContractManager<M> {
     }
 
     // Get a contract instance by address with default ERC20 ABI
-    pub async fn get_erc20(&self, address: Address) -> Result<Contract<Arc<M>>> {
-        self.get_contract_with_abi(address, "erc20", ERC20_ABI).await
+    pub async fn get_erc20(&self, address: Address) -> Result<Contract<M>> {
+        self.get_contract_from_abi(address, erc20_token::ERC20_ABI.clone())
     }
 
     // Get a DEX pair contract
-    pub async fn get_pair(&self, address: Address) -> Result<Contract<Arc<M>>> {
-        self.get_contract_with_abi(address, "pair", PAIR_ABI).await
+    pub async fn get_pair(&self, address: Address) -> Result<Contract<M>> {
+        self.get_contract_from_abi(address, uniswap_v2_pair::UNIV2PAIR_ABI.clone())
     }
 
     // Get a DEX factory contract
-    pub async fn get_factory(&self, address: Address) -> Result<Contract<Arc<M>>> {
-        self.get_contract_with_abi(address, "factory", FACTORY_ABI).await
+    pub async fn get_factory(&self, address: Address) -> Result<Contract<M>> {
+        self.get_contract_from_abi(address, uniswap_v2_factory::UNIV2FACTORY_ABI.clone())
     }
 
     // Get a DEX router contract
-    pub async fn get_router(&self, address: Address) -> Result<Contract<Arc<M>>> {
-        self.get_contract_with_abi(address, "router", ROUTER_ABI).await
+    pub async fn get_router(&self, address: Address) -> Result<Contract<M>> {
+        self.get_contract_from_abi(address, uniswap_v2_router::UNIV2ROUTER_ABI.clone())
     }
 
     // Get contract with custom ABI
-    pub async fn get_contract_with_abi(&self, address: Address, abi_key: &str, abi_json: &str) -> Result<Contract<Arc<M>>> {
-        // Check cache first
-        if let Ok(cache) = self.contract_cache.read() {
-            if let Some(entry) = cache.get(&address) {
-                if !entry.is_expired() {
-                    return Ok(entry.value.clone());
-                }
-            }
+    pub async fn get_contract_with_abi(&self, address: Address, abi_key: &str, abi_json: &str) -> Result<Contract<M>> {
+        if let Some(contract) = self.cached_contract(address) {
+            return Ok(contract);
         }
 
-        // Get ABI from cache or parse
-        let abi = if let Ok(abi_cache) = self.abi_cache.read() {
-            if let Some(entry) = abi_cache.get(abi_key) {
-                if !entry.is_expired() {
-                    entry.value.clone()
-                } else {
-                    self.parse_and_cache_abi(abi_key, abi_json).await?
-                }
-            } else {
-                self.parse_and_cache_abi(abi_key, abi_json).await?
-            }
-        } else {
-            self.parse_and_cache_abi(abi_key, abi_json).await?
+        // Get ABI from cache or parse (guard dropped before awaiting)
+        let cached_abi = self.abi_cache.read().ok().and_then(|abi_cache| {
+            abi_cache.get(abi_key)
+                .filter(|entry| !entry.is_expired())
+                .map(|entry| entry.value.clone())
+        });
+        let abi = match cached_abi {
+            Some(abi) => abi,
+            None => self.parse_and_cache_abi(abi_key, abi_json).await?,
         };
 
+        self.get_contract_from_abi(address, abi)
+    }
+
+    // Get contract with an already parsed ABI
+    pub fn get_contract_from_abi(&self, address: Address, abi: ethers::abi::Abi) -> Result<Contract<M>> {
+        if let Some(contract) = self.cached_contract(address) {
+            return Ok(contract);
+        }
+
         // Create contract
         let contract = Contract::new(address, abi, self.provider.clone());
 
@@ -159,6 +156,14 @@ impl<M: Middleware + 'static> ContractManager<M> {
         Ok(contract)
     }
 
+    // Contract instance from cache if not expired
+    fn cached_contract(&self, address: Address) -> Option<Contract<M>> {
+        let cache = self.contract_cache.read().ok()?;
+        cache.get(&address)
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| entry.value.clone())
+    }
+
     // Parse ABI and cache it
     async fn parse_and_cache_abi(&self, abi_key: &str, abi_json: &str) -> Result<ethers::abi::Abi> {
         let abi: ethers::abi::Abi = serde_json::from_str(abi_json)
@@ -197,16 +202,22 @@ impl<M: Middleware + 'static> ContractManager<M> {
         };
 
         // Try to get basic token info
-        if let Ok(name) = contract.method::<_, String>("name", ()).call().await {
-            info.name = name;
+        if let Ok(call) = contract.method::<_, String>("name", ()) {
+            if let Ok(name) = call.call().await {
+                info.name = name;
+            }
         }
 
-        if let Ok(symbol) = contract.method::<_, String>("symbol", ()).call().await {
-            info.symbol = Some(symbol);
+        if let Ok(call) = contract.method::<_, String>("symbol", ()) {
+            if let Ok(symbol) = call.call().await {
+                info.symbol = Some(symbol);
+            }
         }
 
-        if let Ok(decimals) = contract.method::<_, u8>("decimals", ()).call().await {
-            info.decimals = Some(decimals);
+        if let Ok(call) = contract.method::<_, u8>("decimals", ()) {
+            if let Ok(decimals) = call.call().await {
+                info.decimals = Some(decimals);
+            }
         }
 
         // Try to determine contract type
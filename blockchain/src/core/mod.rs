@@ -2,38 +2,34 @@
 use ethers::{
     prelude::*,
     providers::{Http, Provider, Middleware},
-    types::{Address, U256, Transaction, Filter, H256, BlockId, BlockNumber, Bytes},
-    abi::{self, Token, Detokenize},
+    types::{Address, U256, H256},
+    abi::{Token, Detokenize},
     contract::Contract,
 };
 
 // Standard library imports
 use std::{
-    collections::HashMap,
     sync::Arc,
     str::FromStr,
-    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    time::Duration,
     marker::Send,
 };
 
 // Third party imports
 use anyhow::{Result, anyhow, Context};
-use tracing::{info, warn, error, debug};
+use tracing::{info, warn, error};
 use async_trait::async_trait;
-use tokio::time::sleep;
 use serde::{Serialize, Deserialize};
 
 // Internal imports
 use crate::abi;
-use common::cache::{Cache, CacheEntry, BasicCache, CacheConfig};
+use diamond_common::cache::{Cache, BasicCache, CacheConfig};
 
-pub mod blockchain_monitor;
 pub mod token_info;
 pub mod transaction;
 pub mod contract_manager;
 
 pub use blockchain_monitor::*;
-pub use token_info::*;
 pub use transaction::*;
 pub use contract_manager::*;
 
@@ -85,7 +81,7 @@ pub trait BlockchainService: Send + Sync + 'static {
     async fn get_eth_balance(&self, address: &str) -> Result<U256>;
     
     /// Gọi hàm của contract
-    async fn call_contract_function<T: Detokenize + 'static>(
+    async fn call_contract_function<T: Detokenize + Send + Sync + 'static>(
         &self,
         contract_address: &str, 
         function_name: &str, 
@@ -130,6 +126,16 @@ mod blockchain_monitor {
             })
         }
         
+        /// Tham số chain của monitor
+        pub fn params(&self) -> &BlockchainParams {
+            &self.config
+        }
+        
+        /// Block mới nhất đã truy vấn (0 nếu chưa truy vấn)
+        pub fn last_block(&self) -> u64 {
+            self.last_block
+        }
+        
         /// Thiết lập cấu hình cache
         pub fn with_cache_config(&mut self, config: CacheConfig) -> &mut Self {
             self.cache = Arc::new(BasicCache::new(Some(config)));
@@ -228,7 +234,7 @@ mod blockchain_monitor {
             };
             
             // Update cache
-            let _ = self.cache.store_in_cache(&cache_key, &token_info, 300).await; // Cache 5 phút
+            let _ = self.cache.store_in_cache(&cache_key, token_info.clone(), 300).await; // Cache 5 phút
             
             Ok(token_info)
         }
@@ -304,7 +310,7 @@ mod blockchain_monitor {
                 .with_context(|| format!("Không thể lấy số dư ETH cho địa chỉ {}", address))
         }
         
-        async fn call_contract_function<T: Detokenize + 'static>(
+        async fn call_contract_function<T: Detokenize + Send + Sync + 'static>(
             &self,
             contract_address: &str, 
             function_name: &str, 
@@ -351,7 +357,12 @@ mod tests {
     
     #[tokio::test]
     async fn test_blockchain_monitor() {
-        // Test sẽ được triển khai sau
-        assert!(true);
+        let monitor = BlockchainMonitor::new(BlockchainParams {
+            chain_id: 1,
+            rpc_url: "http://localhost:8545".to_string(),
+            explorer_url: "https://etherscan.io".to_string(),
+        }).unwrap();
+        assert_eq!(monitor.params().chain_id, 1);
+        assert_eq!(monitor.last_block(), 0);
     }
 } 
\ No newline at end of file
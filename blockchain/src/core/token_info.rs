@@ -1,24 +1,12 @@
 // External imports
-use ethers::{
-    core::types::{Address, U256},
-    contract::Contract,
-};
+use ethers::core::types::U256;
 
 // Standard library imports
-use std::{
-    sync::Arc,
-    str::FromStr,
-    time::Instant,
-};
 
 // Third party imports
-use anyhow::{Result, anyhow, Context};
 use serde::{Serialize, Deserialize};
-use tracing::{warn, debug};
 
 // Internal imports
-use crate::abi;
-use common::cache::{Cache, CacheEntry};
 
 /// Cấu trúc thông tin token
 #[derive(Debug, Clone, Serialize, Deserialize)]
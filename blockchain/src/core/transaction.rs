@@ -1,21 +1,12 @@
 // External imports
-use ethers::{
-    core::types::{TransactionReceipt, H256, U256, Transaction, Block, BlockId, BlockNumber},
-    providers::{Middleware, PendingTransaction},
-};
+use ethers::core::types::{TransactionReceipt, H256, U256, Transaction};
 
 // Standard library imports
-use std::{
-    sync::Arc,
-    str::FromStr,
-    time::Duration,
-};
+use std::str::FromStr;
 
 // Third party imports
-use anyhow::{Result, anyhow, Context};
-use tracing::{info, warn, error, debug};
+use anyhow::{Result, Context};
 use async_trait::async_trait;
-use tokio::time::timeout;
 
 /// Cấu trúc thông tin giao dịch mở rộng
 #[derive(Debug, Clone)]
@@ -102,7 +93,7 @@ impl TransactionInfo {
     
     /// Lấy block number chứa giao dịch
     pub fn block_number(&self) -> Option<U256> {
-        self.receipt.as_ref().and_then(|r| r.block_number)
+        self.receipt.as_ref().and_then(|r| r.block_number).map(|n| U256::from(n.as_u64()))
     }
 }
 
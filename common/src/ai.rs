@@ -1,11 +1,11 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
+use ethers::core::types::Address;
 
 // Standard library imports
 use std::sync::Arc;
 
 // Third party imports
-use anyhow::{Result, Context};
+use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -26,7 +26,8 @@ pub trait AIModel: Send + Sync + 'static {
 }
 
 /// Mô hình AI cơ bản
-#[derive(Debug, Clone)]
+#[derive(Clone)]
+#[allow(dead_code)]
 pub struct BasicAIModel {
     model: Arc<dyn AIModel>,
     config: AIModelConfig,
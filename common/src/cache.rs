@@ -1,32 +1,23 @@
 // File này đã được hợp nhất vào common/cache.rs
 // Vui lòng sử dụng module common::cache thay vì module này.
 
-// Re-export từ thư mục gốc
-pub use crate::cache::*;
-
 // Deprecated: Nội dung chi tiết đã được chuyển sang common/cache.rs
 // Tất cả code mới nên import từ common::cache
 
 // External imports
-use ethers::core::types::{Address, H256, U256};
 
 // Standard library imports
 use std::{
     collections::HashMap,
     fmt::Debug,
-    hash::Hash,
-    num::NonZeroUsize,
-    sync::{Arc, RwLock},
-    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    sync::RwLock,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 // Third party imports
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
-use lru::LruCache;
 use serde::{Serialize, Deserialize};
-use serde_json::{Value, json};
-use tracing::{info, warn, debug, error};
 use tokio::sync::Mutex as AsyncMutex;
 
 /// Trait cung cấp interface chung cho tất cả các loại cache
@@ -57,6 +48,11 @@ pub trait Cache: Send + Sync {
 
     /// Lấy số lượng phần tử trong cache
     async fn len(&self) -> Result<usize>;
+
+    /// Cache có rỗng không
+    async fn is_empty(&self) -> Result<bool> {
+        Ok(self.len().await? == 0)
+    }
     
     /// Lấy JSON từ cache
     async fn get_from_cache<T: Serialize + for<'de> Deserialize<'de> + Send + Sync + 'static>(&self, key: &str) -> Result<Option<T>> {
@@ -130,6 +126,7 @@ impl Default for CacheConfig {
 }
 
 /// Cache cơ bản lưu trong bộ nhớ
+#[derive(Debug)]
 pub struct BasicCache {
     entries: RwLock<HashMap<String, String>>,
     expirations: RwLock<HashMap<String, u64>>,
@@ -314,6 +311,7 @@ impl Cache for BasicCache {
 }
 
 /// Cache bất đồng bộ
+#[derive(Debug)]
 pub struct AsyncCache {
     entries: AsyncMutex<HashMap<String, String>>,
     expirations: AsyncMutex<HashMap<String, u64>>,
@@ -484,6 +482,7 @@ impl Cache for AsyncCache {
 }
 
 /// Cache JSON hỗ trợ serialize/deserialize
+#[derive(Debug)]
 pub struct JSONCache<C: Cache> {
     cache: C,
 }
@@ -550,7 +549,7 @@ pub fn create_json_cache<C: Cache + Send + Sync + 'static>(cache: C) -> JSONCach
 mod tests {
     use super::*;
 
-    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
     struct TestStruct {
         id: u32,
         name: String,
@@ -561,14 +560,14 @@ mod tests {
         let value = TestStruct { id: 1, name: "Test".to_string() };
         let entry = CacheEntry::new(value, 60);
         
-        assert_eq!(false, entry.is_expired());
+        assert!(!entry.is_expired());
         
         let value = TestStruct { id: 2, name: "Expired".to_string() };
         let mut entry = CacheEntry::new(value, 0);
         
         // Giả lập thời gian hết hạn trong quá khứ
         entry.expires_at = 0;
-        assert_eq!(true, entry.is_expired());
+        assert!(entry.is_expired());
     }
     
     #[tokio::test]
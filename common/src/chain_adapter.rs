@@ -252,13 +252,14 @@ mod tests {
     }
 
     /// Test EVMAdapter
-    #[test]
-    fn test_evm_adapter() {
+    #[tokio::test]
+    async fn test_evm_adapter() {
         let adapter = EVMAdapter::new(Arc::new(TestChainAdapter));
-        assert!(adapter.client.get_chain_id().is_ok());
+        assert!(adapter.client.get_chain_id().await.is_ok());
     }
 
     /// Test chain adapter
+    #[derive(Debug)]
     struct TestChainAdapter;
 
     #[async_trait]
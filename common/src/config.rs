@@ -1,11 +1,10 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
 
 // Standard library imports
 use std::{
     collections::HashMap,
     sync::{Arc, RwLock},
-    time::{Duration, SystemTime},
+    time::SystemTime,
     path::PathBuf,
 };
 
@@ -14,14 +13,14 @@ use anyhow::{Context, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-/// Config trait
+/// Kho lưu giá trị config dạng key-value
 #[async_trait]
-pub trait Config: Send + Sync + 'static {
+pub trait ConfigStore: Send + Sync + 'static {
     /// Lấy giá trị config
-    async fn get<T: Serialize + for<'de> Deserialize<'de>>(&self, key: &str) -> Result<Option<T>>;
+    async fn get<T: Serialize + for<'de> Deserialize<'de> + Send>(&self, key: &str) -> Result<Option<T>>;
 
     /// Lưu giá trị config
-    async fn set<T: Serialize + for<'de> Deserialize<'de>>(&self, key: &str, value: T) -> Result<()>;
+    async fn set<T: Serialize + for<'de> Deserialize<'de> + Send>(&self, key: &str, value: T) -> Result<()>;
 
     /// Xóa giá trị config
     async fn remove(&self, key: &str) -> Result<()>;
@@ -43,6 +42,7 @@ pub struct ConfigEntry<T> {
 
 /// Basic config
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct BasicConfig {
     config: Arc<RwLock<ConfigConfig>>,
     entries: Arc<RwLock<HashMap<String, Vec<u8>>>>,
@@ -72,8 +72,8 @@ impl BasicConfig {
 }
 
 #[async_trait]
-impl Config for BasicConfig {
-    async fn get<T: Serialize + for<'de> Deserialize<'de>>(&self, key: &str) -> Result<Option<T>> {
+impl ConfigStore for BasicConfig {
+    async fn get<T: Serialize + for<'de> Deserialize<'de> + Send>(&self, key: &str) -> Result<Option<T>> {
         let entries = self.entries.read().unwrap();
         if let Some(data) = entries.get(key) {
             let entry: ConfigEntry<T> = bincode::deserialize(data)?;
@@ -82,7 +82,7 @@ impl Config for BasicConfig {
         Ok(None)
     }
 
-    async fn set<T: Serialize + for<'de> Deserialize<'de>>(&self, key: &str, value: T) -> Result<()> {
+    async fn set<T: Serialize + for<'de> Deserialize<'de> + Send>(&self, key: &str, value: T) -> Result<()> {
         let now = SystemTime::now();
         let entry = ConfigEntry {
             value,
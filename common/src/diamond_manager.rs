@@ -1,8 +1,7 @@
 // External imports
 use ethers::{
-    core::types::{Address, H256, U256},
-    providers::{Middleware, Provider},
-    signers::Signer,
+    core::types::{Address, H256},
+    providers::Middleware,
 };
 
 // Standard library imports
@@ -11,7 +10,6 @@ use std::sync::Arc;
 // Third party imports
 use anyhow::{Result, Context};
 use async_trait::async_trait;
-use serde::{Deserialize, Serialize};
 
 /// Quản trị hệ thống và hợp đồng Diamond
 #[async_trait]
@@ -31,14 +29,14 @@ pub trait DiamondManager: Send + Sync + 'static {
 
 /// Quản trị viên Diamond
 #[derive(Debug, Clone)]
-pub struct DiamondAdmin {
-    provider: Arc<dyn Middleware>,
+pub struct DiamondAdmin<M: Middleware> {
+    provider: Arc<M>,
     admin_address: Address,
 }
 
-impl DiamondAdmin {
+impl<M: Middleware> DiamondAdmin<M> {
     /// Tạo quản trị viên mới
-    pub fn new(provider: impl Middleware, admin_address: Address) -> Self {
+    pub fn new(provider: M, admin_address: Address) -> Self {
         Self {
             provider: Arc::new(provider),
             admin_address,
@@ -47,15 +45,18 @@ impl DiamondAdmin {
 }
 
 #[async_trait]
-impl DiamondManager for DiamondAdmin {
+impl<M: Middleware + 'static> DiamondManager for DiamondAdmin<M> {
     async fn deploy_contract(&self, bytecode: &[u8]) -> Result<Address> {
-        let tx = self.provider.send_transaction(
+        let pending = self.provider.send_transaction(
             ethers::types::TransactionRequest::new()
                 .from(self.admin_address)
                 .data(bytecode.to_vec()),
             None,
         ).await?;
-        Ok(tx.contract_address.unwrap())
+        let receipt = pending.await?
+            .context("Giao dịch triển khai hợp đồng bị drop khỏi mempool")?;
+        receipt.contract_address
+            .context("Receipt không có địa chỉ hợp đồng")
     }
 
     async fn update_contract(&self, address: Address, bytecode: &[u8]) -> Result<H256> {
@@ -66,17 +67,16 @@ impl DiamondManager for DiamondAdmin {
                 .data(bytecode.to_vec()),
             None,
         ).await?;
-        Ok(tx.transaction_hash)
+        Ok(tx.tx_hash())
     }
 
     async fn call_contract(&self, address: Address, data: &[u8]) -> Result<Vec<u8>> {
-        let result = self.provider.call(
-            ethers::types::TransactionRequest::new()
-                .from(self.admin_address)
-                .to(address)
-                .data(data.to_vec()),
-            None,
-        ).await?;
+        let tx: ethers::types::transaction::eip2718::TypedTransaction = ethers::types::TransactionRequest::new()
+            .from(self.admin_address)
+            .to(address)
+            .data(data.to_vec())
+            .into();
+        let result = self.provider.call(&tx, None).await?;
         Ok(result.to_vec())
     }
 
@@ -88,7 +88,7 @@ impl DiamondManager for DiamondAdmin {
                 .data(data.to_vec()),
             None,
         ).await?;
-        Ok(tx.transaction_hash)
+        Ok(tx.tx_hash())
     }
 }
 
@@ -96,7 +96,7 @@ impl DiamondManager for DiamondAdmin {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ethers::providers::Http;
+    use ethers::providers::{Http, Provider};
 
     /// Test DiamondAdmin
     #[test]
@@ -1,15 +1,15 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
+use ethers::core::types::{Address, U256};
 
 // Standard library imports
 use std::{
     collections::HashMap,
     sync::{Arc, RwLock},
-    time::{Duration, SystemTime},
+    time::SystemTime,
 };
 
 // Third party imports
-use anyhow::{Context, Result};
+use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -41,6 +41,7 @@ pub struct EquilibriumPoint {
 
 /// Basic equilibrium
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct BasicEquilibrium {
     config: Arc<RwLock<EquilibriumConfig>>,
     points: Arc<RwLock<HashMap<Address, Vec<EquilibriumPoint>>>>,
@@ -82,7 +83,7 @@ impl Equilibrium for BasicEquilibrium {
 
     async fn update_equilibrium(&self, token: Address, point: EquilibriumPoint) -> Result<()> {
         let mut points = self.points.write().unwrap();
-        let token_points = points.entry(token).or_insert_with(Vec::new);
+        let token_points = points.entry(token).or_default();
         token_points.push(point);
         Ok(())
     }
@@ -1,15 +1,13 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
 
 // Standard library imports
 use std::{
-    collections::HashMap,
     sync::{Arc, RwLock},
     time::{Duration, SystemTime},
 };
 
 // Third party imports
-use anyhow::{Context, Result};
+use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -41,6 +39,7 @@ pub struct Event {
 
 /// Basic event handler
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct BasicEventHandler {
     config: Arc<RwLock<EventHandlerConfig>>,
     events: Arc<RwLock<Vec<Event>>>,
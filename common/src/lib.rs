@@ -1,7 +1,5 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
 use anyhow::Result;
-use serde::{Serialize, Deserialize};
 
 // Module declarations
 pub mod types;
@@ -14,7 +12,6 @@ pub mod server;
 pub mod worker;
 pub mod retry_policy;
 pub mod config;
-pub mod subscription;
 pub mod chain_adapter;
 pub mod state_manager;
 pub mod task_manager;
@@ -23,6 +20,7 @@ pub mod executor;
 pub mod scheduler;
 pub mod security;
 pub mod user;
+pub mod subscription;
 pub mod ai;
 pub mod event_handler;
 pub mod diamond_manager;
@@ -39,6 +37,12 @@ pub use cache::*;
 pub use models::*;
 pub use server::*;
 
+// Các tên trùng giữa `types`, `models` và `error`: chọn rõ ràng bản được re-export ở gốc crate
+pub use types::{
+    Block, EndpointStatus, TokenInfo, Transaction, TransactionStatus, WalletStatus, WalletType,
+};
+pub use error::CommonResult;
+
 // Network types defined locally instead of re-exported from network crate
 // to avoid cyclic dependency
 pub mod network_types {
@@ -101,9 +105,14 @@ pub use network_types::*;
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_simple() {
-        assert!(true);
+    #[tokio::test]
+    async fn test_simple() {
+        let mut gateway = ServerGateway::new(config::Config::default());
+        assert!(!gateway.is_running());
+        gateway.start().await.unwrap();
+        assert!(gateway.is_running());
+        gateway.stop().await.unwrap();
+        assert!(!gateway.is_running());
     }
 }
 
@@ -142,4 +151,14 @@ impl ServerGateway {
         self.is_running = false;
         Ok(())
     }
+    
+    /// Gateway có đang chạy không
+    pub fn is_running(&self) -> bool {
+        self.is_running
+    }
+    
+    /// Cấu hình của gateway
+    pub fn config(&self) -> &config::Config {
+        &self.config
+    }
 } 
\ No newline at end of file
@@ -1,15 +1,13 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
 
 // Standard library imports
 use std::{
-    collections::HashMap,
     sync::{Arc, RwLock},
-    time::{Duration, SystemTime},
+    time::SystemTime,
 };
 
 // Third party imports
-use anyhow::{Context, Result};
+use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -52,6 +50,7 @@ pub struct LogEntry {
 
 /// Basic logger
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct BasicLogger {
     config: Arc<RwLock<LoggerConfig>>,
     logs: Arc<RwLock<Vec<LogEntry>>>,
@@ -1,5 +1,4 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
 
 // Standard library imports
 use std::{
@@ -9,7 +8,7 @@ use std::{
 };
 
 // Third party imports
-use anyhow::{Context, Result};
+use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -53,6 +52,7 @@ pub struct MetricValue {
 
 /// Basic metrics
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct BasicMetrics {
     config: Arc<RwLock<MetricsConfig>>,
     metrics: Arc<RwLock<HashMap<String, MetricValue>>>,
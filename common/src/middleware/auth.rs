@@ -1,5 +1,6 @@
 use axum::{
     http::{Request, header},
+    body::Body,
     middleware::Next,
     response::{Response, IntoResponse},
 };
@@ -11,14 +12,9 @@ use std::{
     collections::HashMap,
     sync::RwLock,
     time::{SystemTime, UNIX_EPOCH},
-    fmt::{self, Display, Formatter},
-    error::Error,
     env,
 };
-use anyhow::{Result, Context};
-use tracing::{info, warn, error, debug};
-use async_trait::async_trait;
-use tokio::time::{timeout, sleep};
+use anyhow::Result;
 use once_cell::sync::Lazy;
 
 // Cấu hình cho JWT
@@ -40,7 +36,7 @@ impl Default for JWTConfig {
 }
 
 // Thông tin trong JWT token
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,      // User ID hoặc username
     pub role: String,     // Quyền: "user", "admin", etc.
@@ -97,6 +93,7 @@ impl From<&str> for UserRole {
 // Quản lý blacklist token
 pub struct TokenBlacklist {
     blacklisted_tokens: HashMap<String, u64>, // jti -> expiration time
+    revoked_users: HashMap<String, u64>, // user id -> thời điểm thu hồi mọi token đã cấp
     last_cleanup: u64,
 }
 
@@ -110,6 +107,7 @@ impl TokenBlacklist {
     pub fn new() -> Self {
         Self {
             blacklisted_tokens: HashMap::new(),
+            revoked_users: HashMap::new(),
             last_cleanup: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
@@ -137,6 +135,18 @@ impl TokenBlacklist {
     pub fn is_blacklisted(&self, jti: &str) -> bool {
         self.blacklisted_tokens.contains_key(jti)
     }
+
+    // Thu hồi mọi token của người dùng được cấp trước thời điểm revoked_at
+    pub fn revoke_user(&mut self, user_id: &str, revoked_at: u64) {
+        self.revoked_users.insert(user_id.to_string(), revoked_at);
+    }
+
+    // Kiểm tra token (theo claims) đã bị vô hiệu hóa chưa
+    pub fn is_revoked(&self, claims: &Claims) -> bool {
+        self.is_blacklisted(&claims.jti)
+            || self.revoked_users.get(&claims.sub)
+                .is_some_and(|revoked_at| claims.iat as u64 <= *revoked_at)
+    }
     
     // Dọn dẹp token hết hạn
     fn cleanup(&mut self) {
@@ -229,9 +239,9 @@ pub fn extract_client_ip<B>(request: &Request<B>) -> Option<String> {
 }
 
 // Middleware template cho xác thực API key cho các service khác gọi đến
-pub async fn api_key_middleware<B, E>(
-    request: Request<B>,
-    next: Next<B>,
+pub async fn api_key_middleware<E>(
+    request: Request<Body>,
+    next: Next,
     api_key_validator: impl Fn(&str) -> bool,
     error_response: impl Fn() -> E,
 ) -> Result<Response, E> 
@@ -245,18 +255,9 @@ where
         .and_then(|value| value.to_str().ok());
     
     match api_key {
-        Some(key) => {
-            // Kiểm tra API key hợp lệ
-            if api_key_validator(key) {
-                // Tiếp tục request
-                Ok(next.run(request).await)
-            } else {
-                Err(error_response())
-            }
-        },
-        None => {
-            Err(error_response())
-        }
+        // Kiểm tra API key hợp lệ rồi tiếp tục request
+        Some(key) if api_key_validator(key) => Ok(next.run(request).await),
+        _ => Err(error_response()),
     }
 }
 
@@ -297,10 +298,10 @@ where
     }
     
     /// Thực hiện xác thực JWT
-    pub async fn into_middleware<B>(
+    pub async fn into_middleware(
         self,
-        request: Request<B>,
-        next: Next<B>,
+        request: Request<Body>,
+        next: Next,
     ) -> Result<Response, E> {
         // Lấy token từ header
         let auth_header = request.headers().get(header::AUTHORIZATION);
@@ -354,9 +355,9 @@ where
 }
 
 // Middleware tổng quát cho kiểm tra quyền
-pub async fn role_middleware<B, E>(
-    request: Request<B>,
-    next: Next<B>,
+pub async fn role_middleware<E>(
+    request: Request<Body>,
+    next: Next,
     required_roles: Vec<&'static str>,
     error_response: impl Fn() -> E,
 ) -> Result<Response, E>
@@ -410,6 +411,27 @@ mod tests {
         assert!(blacklist.is_blacklisted(&jti));
     }
 
+    /// Test thu hồi toàn bộ token của một người dùng
+    #[test]
+    fn test_revoke_user() {
+        let mut blacklist = TokenBlacklist::new();
+        let claims = |sub: &str, iat: usize| Claims {
+            sub: sub.to_string(),
+            role: "user".to_string(),
+            wallet_ids: vec![],
+            exp: iat + 3600,
+            nbf: iat,
+            iat,
+            jti: format!("{}-{}", sub, iat),
+        };
+
+        blacklist.revoke_user("user1", 1_000);
+        assert!(blacklist.is_revoked(&claims("user1", 999)));
+        assert!(blacklist.is_revoked(&claims("user1", 1_000)));
+        assert!(!blacklist.is_revoked(&claims("user1", 1_001)));
+        assert!(!blacklist.is_revoked(&claims("user2", 999)));
+    }
+
     /// Test role middleware
     #[test]
     fn test_role_middleware() {
@@ -423,7 +445,7 @@ mod tests {
             jti: "test".to_string(),
         };
         
-        let required_roles = vec!["admin"];
+        let required_roles = ["admin"];
         assert!(required_roles.contains(&claims.role.as_str()));
     }
 } 
\ No newline at end of file
@@ -1,28 +1,20 @@
 // External imports
-use axum::{
-    http::{Request, Response},
-    middleware::Next,
-    response::IntoResponse,
-};
 
 // Standard library imports
 use std::{
     collections::HashMap,
     sync::Arc,
-    fmt::{self, Display, Formatter},
-    error::Error,
     any::Any,
 };
 
 // Third party imports
-use anyhow::{Result, Context, anyhow};
-use tracing::{info, warn, error, debug};
+use anyhow::{Result, anyhow};
+use tracing::info;
 use async_trait::async_trait;
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use std::future::Future;
-use tokio::time::{timeout, sleep};
 
 /// Thông tin người dùng
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -140,6 +132,7 @@ impl MiddlewareChain {
 }
 
 /// Authentication middleware
+#[allow(dead_code)]
 pub struct AuthMiddleware {
     /// Secret key cho JWT
     jwt_secret: String,
@@ -215,6 +208,7 @@ impl Middleware for AuthMiddleware {
 }
 
 /// Rate limiting middleware
+#[allow(dead_code)]
 pub struct RateLimitMiddleware {
     /// Số lượng request tối đa
     max_requests: u32,
@@ -368,7 +362,7 @@ mod tests {
     /// Test RequestContext
     #[test]
     fn test_request_context() {
-        let mut ctx = RequestContext::new("/test", "GET");
+        let ctx = RequestContext::new("/test", "GET");
         assert_eq!(ctx.path, "/test");
         assert_eq!(ctx.method, "GET");
         assert!(ctx.user.is_none());
@@ -1,18 +1,15 @@
 // External imports
 
 // Standard library imports
-use std::fmt;
-use std::sync::Arc;
 
 // Third party imports
 use async_trait::async_trait;
 use anyhow;
 
 // Internal imports
-use crate::types::*;
 
 // Import các module middleware
-mod auth;
+pub mod auth;
 mod core;
 mod rate_limit;
 
@@ -22,17 +19,9 @@ pub use core::*;
 pub use rate_limit::*;
 
 // Re-export UserRole để tiện sử dụng
-pub use crate::models::user::UserRole;
-
-/// Interface chung cho tất cả các middleware
-#[async_trait]
-pub trait Middleware {
-    type Error: std::error::Error + Send + Sync + 'static;
-    
-    async fn process<B>(&self, request: Request<B>, next: Next<B>) -> Result<Response, Self::Error>
-    where
-        B: Send + 'static;
-}
+pub use crate::models::UserRole;
+// `auth` và `core` cùng định nghĩa UserInfo, bản của auth đi kèm JWT claims
+pub use auth::UserInfo;
 
 /// Interface cho các middleware
 #[async_trait]
@@ -86,7 +75,7 @@ mod tests {
         assert_eq!(jwt_middleware.jwt_secret, "secret");
 
         let rate_limiter = RateLimiter::new(60, 3600);
-        assert_eq!(rate_limiter.window_size, 60);
-        assert_eq!(rate_limiter.cleanup_interval, 3600);
+        assert_eq!(rate_limiter.window_size(), 60);
+        assert_eq!(rate_limiter.cleanup_interval(), 3600);
     }
 } 
\ No newline at end of file
@@ -1,6 +1,7 @@
 // External imports
 use axum::{
     http::Request,
+    body::Body,
     middleware::Next,
     response::{Response, IntoResponse},
 };
@@ -9,17 +10,12 @@ use axum::{
 use std::{
     collections::HashMap,
     time::{SystemTime, UNIX_EPOCH},
-    fmt::{self, Display, Formatter},
-    error::Error,
 };
 
 // Third party imports
-use anyhow::{Result, Context};
-use tracing::{info, warn, error, debug};
-use async_trait::async_trait;
+use anyhow::Result;
 use tokio::sync::RwLock;
 use once_cell::sync::Lazy;
-use tokio::time::{timeout, sleep};
 
 /// Cấu trúc dữ liệu cho rate limiting
 #[derive(Debug, Clone)]
@@ -113,6 +109,16 @@ impl RateLimiter {
         }
     }
     
+    /// Kích thước window (giây)
+    pub fn window_size(&self) -> u64 {
+        self.window_size
+    }
+    
+    /// Khoảng thời gian dọn dẹp (giây)
+    pub fn cleanup_interval(&self) -> u64 {
+        self.cleanup_interval
+    }
+    
     /// Kiểm tra rate limit
     pub async fn check_limit(&mut self, key: &str, limit: usize) -> bool {
         let result = check_rate_limit(key, limit, self.window_size).await;
@@ -151,16 +157,16 @@ impl RateLimiter {
 /// # Returns
 ///
 /// * `Result<Response, E>` - Response hoặc lỗi
-pub async fn rate_limit_middleware<B, F, E>(
-    request: Request<B>,
-    next: Next<B>,
+pub async fn rate_limit_middleware<F, E>(
+    request: Request<Body>,
+    next: Next,
     key_extractor: F,
     limit: usize,
     window_size: u64,
     error_response: impl Fn() -> E,
 ) -> Result<Response, E>
 where
-    F: FnOnce(&Request<B>) -> String,
+    F: FnOnce(&Request<Body>) -> String,
     E: IntoResponse,
 {
     let key = key_extractor(&request);
@@ -185,9 +191,9 @@ where
 /// # Returns
 ///
 /// * `Result<Response, E>` - Response hoặc lỗi
-pub async fn ip_rate_limit_middleware<B, E>(
-    request: Request<B>,
-    next: Next<B>,
+pub async fn ip_rate_limit_middleware<E>(
+    request: Request<Body>,
+    next: Next,
     limit: usize,
     window_size: u64,
     error_response: impl Fn() -> E,
@@ -212,9 +218,9 @@ mod tests {
     use super::*;
 
     /// Test rate limit
-    #[test]
-    fn test_rate_limit() {
-        let key = "test_key";
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let key = "test_rate_limit_key";
         let limit = 5;
         let window_size = 60;
         
@@ -228,10 +234,10 @@ mod tests {
     }
 
     /// Test rate limiter
-    #[test]
-    fn test_rate_limiter() {
+    #[tokio::test]
+    async fn test_rate_limiter() {
         let mut limiter = RateLimiter::new(60, 3600);
-        let key = "test_key";
+        let key = "test_rate_limiter_key";
         let limit = 5;
         
         // Test trong cùng một window
@@ -244,33 +250,34 @@ mod tests {
     }
 
     /// Test rate limit middleware
-    #[test]
-    fn test_rate_limit_middleware() {
-        let request = Request::new(());
-        let key = "test_key";
+    #[tokio::test]
+    async fn test_rate_limit_middleware() {
+        use axum::{http::StatusCode, routing::get, Router};
+        use tower::ServiceExt;
+
         let limit = 5;
         let window_size = 60;
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(move |request: Request<Body>, next: Next| {
+                rate_limit_middleware(
+                    request,
+                    next,
+                    |_| "test_rate_limit_middleware_key".to_string(),
+                    limit,
+                    window_size,
+                    || StatusCode::TOO_MANY_REQUESTS,
+                )
+            }));
         
         // Test trong cùng một window
         for _ in 0..limit {
-            assert!(rate_limit_middleware(
-                request.clone(),
-                Next::new(|| async { Response::new(()) }),
-                |_| key.to_string(),
-                limit,
-                window_size,
-                || Response::new(())
-            ).await.is_ok());
+            let response = app.clone().oneshot(Request::new(Body::empty())).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
         }
         
         // Test vượt quá limit
-        assert!(rate_limit_middleware(
-            request,
-            Next::new(|| async { Response::new(()) }),
-            |_| key.to_string(),
-            limit,
-            window_size,
-            || Response::new(())
-        ).await.is_err());
+        let response = app.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
     }
-} 
\ No newline at end of file
+}
@@ -1,16 +1,14 @@
 // External imports
-use anyhow::{Result, Context};
-use ethers::core::types::{Address, H256, U256};
+use ethers::core::types::{Address, U256};
 
 // Standard library imports
 use std::fmt::{self, Display, Formatter};
-use std::time::Duration;
 
 // Third party imports
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
-use ethers::types::{Block as EthBlock, U64};
+use ethers::types::Block as EthBlock;
 
 /// Trạng thái block
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -23,6 +21,8 @@ pub enum BlockStatus {
     Failed,
     /// Đã bị bỏ qua
     Skipped,
+    /// Bị loại khỏi chain chính do reorg
+    Orphaned,
 }
 
 impl Display for BlockStatus {
@@ -32,6 +32,7 @@ impl Display for BlockStatus {
             BlockStatus::Confirmed => write!(f, "Confirmed"),
             BlockStatus::Failed => write!(f, "Failed"),
             BlockStatus::Skipped => write!(f, "Skipped"),
+            BlockStatus::Orphaned => write!(f, "Orphaned"),
         }
     }
 }
@@ -101,6 +102,7 @@ impl Block {
     }
 
     /// Cập nhật thông tin block
+    #[allow(clippy::too_many_arguments)]
     pub fn update_info(
         &mut self,
         miner: Address,
@@ -251,7 +253,7 @@ mod tests {
             vec![],
         );
         let tx_hash = String::from("tx_hash1");
-        block.add_transaction(tx_hash);
+        block.add_transaction(tx_hash.clone());
         assert_eq!(block.transactions.len(), 1);
         assert_eq!(block.transactions[0], tx_hash);
     }
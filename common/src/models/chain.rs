@@ -1,7 +1,5 @@
 // External imports
-use anyhow::{Result, Context};
-use ethers::core::types::{Address, H256, U256};
-use ethers::core::types::U64;
+use ethers::core::types::{Address, U256};
 
 // Standard library imports
 use std::{
@@ -111,6 +109,7 @@ pub struct Chain {
 
 impl Chain {
     /// Tạo chain mới
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         name: String,
         chain_id: u64,
@@ -157,6 +156,7 @@ impl Chain {
     }
 
     /// Cập nhật thông tin chain
+    #[allow(clippy::too_many_arguments)]
     pub fn update_info(
         &mut self,
         name: String,
@@ -245,8 +245,8 @@ mod tests {
             "ETH".to_string(),
             18,
             Duration::from_secs(12),
-            U256::from(2000000000),
-            U256::from(3000000000),
+            U256::from(2_000_000_000u64),
+            U256::from(3_000_000_000u64),
         );
         assert_eq!(chain.name, "Test Chain");
         assert_eq!(chain.chain_id, 1);
@@ -276,14 +276,14 @@ mod tests {
             "ETH".to_string(),
             18,
             Duration::from_secs(12),
-            U256::from(2000000000),
-            U256::from(3000000000),
+            U256::from(2_000_000_000u64),
+            U256::from(3_000_000_000u64),
         );
         chain.update_info(
             "Updated Chain".to_string(),
             "http://localhost:8547".to_string(),
             "ws://localhost:8548".to_string(),
-            U256::from(2000000000),
+            U256::from(2_000_000_000u64),
             U256::from(9000000),
             Duration::from_secs(120),
             Duration::from_secs(60),
@@ -292,8 +292,8 @@ mod tests {
             "ETH2".to_string(),
             9,
             Duration::from_secs(15),
-            U256::from(3000000000),
-            U256::from(4000000000),
+            U256::from(3_000_000_000u64),
+            U256::from(4_000_000_000u64),
         );
         assert_eq!(chain.name, "Updated Chain");
         assert_eq!(chain.rpc_url, "http://localhost:8547");
@@ -320,8 +320,8 @@ mod tests {
             "ETH".to_string(),
             18,
             Duration::from_secs(12),
-            U256::from(2000000000),
-            U256::from(3000000000),
+            U256::from(2_000_000_000u64),
+            U256::from(3_000_000_000u64),
         );
         chain.update_status(ChainStatus::Maintenance);
         assert_eq!(chain.status, ChainStatus::Maintenance);
@@ -346,8 +346,8 @@ mod tests {
             "ETH".to_string(),
             18,
             Duration::from_secs(12),
-            U256::from(2000000000),
-            U256::from(3000000000),
+            U256::from(2_000_000_000u64),
+            U256::from(3_000_000_000u64),
         );
         let contract = Address::zero();
         chain.add_contract(contract);
@@ -374,8 +374,8 @@ mod tests {
             "ETH".to_string(),
             18,
             Duration::from_secs(12),
-            U256::from(2000000000),
-            U256::from(3000000000),
+            U256::from(2_000_000_000u64),
+            U256::from(3_000_000_000u64),
         );
         chain.add_metadata("key1".to_string(), "value1".to_string());
         assert_eq!(chain.get_metadata("key1"), Some(&"value1".to_string()));
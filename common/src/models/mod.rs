@@ -1,14 +1,9 @@
 // External imports
 use ethers::types as ethers_types;
 
-// Standard library imports
-use std::time::Duration;
-use std::fmt::{Display, Formatter};
-
 // Third party imports
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use uuid::Uuid;
 
 // Internal modules
 mod block;
@@ -113,6 +108,7 @@ pub enum OrderStatus {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
 
     /// Test User
     #[test]
@@ -133,6 +129,11 @@ mod tests {
         let wallet = Wallet::new(
             ethers_types::Address::random(),
             WalletType::Standard,
+            "test_wallet".to_string(),
+            None,
+            1,
+            ethers_types::U256::zero(),
+            ethers_types::U256::zero(),
         );
         assert_eq!(wallet.wallet_type, WalletType::Standard);
         assert_eq!(wallet.status, WalletStatus::Active);
@@ -144,7 +145,7 @@ mod tests {
         let tx = Transaction::new(
             ethers_types::H256::random(),
             ethers_types::Address::random(),
-            ethers_types::Address::random(),
+            Some(ethers_types::Address::random()),
             ethers_types::U256::from(1000000000000000000u64),
         );
         assert_eq!(tx.status, TransactionStatus::Pending);
@@ -155,8 +156,8 @@ mod tests {
     fn test_block() {
         let block = Block::new(
             1,
-            ethers_types::H256::random(),
-            ethers_types::H256::random(),
+            format!("{:?}", ethers_types::H256::random()),
+            format!("{:?}", ethers_types::H256::random()),
             vec![],
         );
         assert_eq!(block.number, 1);
@@ -170,9 +171,23 @@ mod tests {
             "Ethereum".to_string(),
             1,
             "http://localhost:8545".to_string(),
+            "ws://localhost:8546".to_string(),
+            ethers_types::U256::zero(),
+            ethers_types::U256::from(21000),
+            Duration::from_secs(60),
+            Duration::from_secs(15),
+            1,
+            vec![],
+            ChainType::Mainnet,
+            "https://etherscan.io".to_string(),
+            "ETH".to_string(),
+            18,
+            Duration::from_secs(12),
+            ethers_types::U256::zero(),
+            ethers_types::U256::zero(),
         );
         assert_eq!(chain.name, "Ethereum");
         assert_eq!(chain.chain_id, 1);
-        assert_eq!(chain.status, ChainStatus::Active);
+        assert_eq!(chain.status, ChainStatus::Running);
     }
 } 
\ No newline at end of file
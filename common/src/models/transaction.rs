@@ -1,10 +1,9 @@
 // External imports
-use anyhow::{Result, Context};
+use anyhow::Result;
 use ethers::core::types::{Address, H256, U256};
 
 // Standard library imports
 use std::fmt::{self, Display, Formatter};
-use std::time::Duration;
 
 // Third party imports
 use serde::{Serialize, Deserialize};
@@ -13,7 +12,6 @@ use uuid::Uuid;
 use ethers::types::{
     Transaction as EthTransaction,
     TransactionReceipt,
-    Bytes,
 };
 
 /// Trạng thái giao dịch
@@ -362,7 +360,7 @@ mod tests {
         let tx = Transaction::new(
             H256::random(),
             Address::random(),
-            Address::random().map(|t| t.unwrap_or_default()),
+            Some(Address::random()),
             U256::from(1000000000000000000u64),
         );
         assert_eq!(tx.status, TransactionStatus::Pending);
@@ -378,7 +376,7 @@ mod tests {
         let mut tx = Transaction::new(
             H256::random(),
             Address::random(),
-            Address::random().map(|t| t.unwrap_or_default()),
+            Some(Address::random()),
             U256::from(1000000000000000000u64),
         );
         tx.update_nonce(1);
@@ -391,7 +389,7 @@ mod tests {
         let mut tx = Transaction::new(
             H256::random(),
             Address::random(),
-            Address::random().map(|t| t.unwrap_or_default()),
+            Some(Address::random()),
             U256::from(1000000000000000000u64),
         );
         tx.update_data(vec![1, 2, 3]);
@@ -404,7 +402,7 @@ mod tests {
         let mut tx = Transaction::new(
             H256::random(),
             Address::random(),
-            Address::random().map(|t| t.unwrap_or_default()),
+            Some(Address::random()),
             U256::from(1000000000000000000u64),
         );
         tx.update_gas(
@@ -421,7 +419,7 @@ mod tests {
         let mut tx = Transaction::new(
             H256::random(),
             Address::random(),
-            Address::random().map(|t| t.unwrap_or_default()),
+            Some(Address::random()),
             U256::from(1000000000000000000u64),
         );
         tx.update_status(TransactionStatus::Confirmed);
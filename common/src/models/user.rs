@@ -8,11 +8,10 @@ use argon2::{
 use std::{
     collections::HashMap,
     fmt::{self, Display, Formatter},
-    time::Duration,
 };
 
 // Third party imports
-use anyhow::{Context, Result};
+use anyhow::Result;
 use chrono::{DateTime, Utc};
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
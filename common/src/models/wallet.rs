@@ -389,7 +389,7 @@ mod tests {
         assert_eq!(wallet.tokens.get("ETH"), Some(&U256::from(1000)));
 
         wallet.remove_token("ETH");
-        assert!(wallet.tokens.get("ETH").is_none());
+        assert!(!wallet.tokens.contains_key("ETH"));
 
         let contract = Address::random();
         wallet.add_contract(contract);
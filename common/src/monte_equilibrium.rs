@@ -1,11 +1,10 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
+use ethers::core::types::{Address, U256};
 
 // Standard library imports
-use std::sync::Arc;
 
 // Third party imports
-use anyhow::{Result, Context};
+use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -19,7 +18,7 @@ pub trait MonteEquilibrium: Send + Sync + 'static {
     async fn optimize_strategy(&self, strategy: &Strategy) -> Result<OptimizedStrategy>;
 
     /// Đánh giá hiệu quả
-    async fn evaluate_performance(&self, result: &Result) -> Result<Performance>;
+    async fn evaluate_performance(&self, result: &GameResult) -> Result<Performance>;
 }
 
 /// Trò chơi
@@ -67,7 +66,7 @@ pub struct OptimizedStrategy {
 
 /// Kết quả
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Result {
+pub struct GameResult {
     pub game: Game,
     pub equilibrium: Equilibrium,
     pub performance: f64,
@@ -162,7 +161,7 @@ mod tests {
     /// Test Result
     #[test]
     fn test_result() {
-        let result = Result {
+        let result = GameResult {
             game: Game {
                 players: vec![],
                 strategies: vec![],
@@ -1,20 +1,17 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
 
 // Standard library imports
 use std::{
     sync::{Arc, RwLock},
     time::{Duration, Instant},
-    collections::HashMap,
 };
 
 // Third party imports
-use anyhow::{Result, Context};
+use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, error, info, trace, warn};
+use tracing::{debug, error};
 use tokio::time::sleep;
-use rand::Rng;
 
 /// Cấu hình retry policy
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -110,7 +107,7 @@ impl RetryPolicy {
             match f().await {
                 Ok(result) => {
                     let mut metrics = self.metrics.write().unwrap();
-                    metrics.total_retries += retries;
+                    metrics.total_retries += u64::from(retries);
                     metrics.successful_retries += 1;
                     metrics.total_time += start_time.elapsed();
                     
@@ -124,7 +121,7 @@ impl RetryPolicy {
                 Err(e) => {
                     if retries >= self.config.max_retries {
                         let mut metrics = self.metrics.write().unwrap();
-                        metrics.total_retries += retries;
+                        metrics.total_retries += u64::from(retries);
                         metrics.failed_retries += 1;
                         metrics.total_time += start_time.elapsed();
                         
@@ -200,7 +197,9 @@ pub struct CircuitBreaker {
     /// Thời gian đợi trước khi thử lại sau khi mở circuit (milliseconds)
     reset_timeout: u64,
     /// Trạng thái hiện tại
-    state: RwLock<CircuitBreakerState>,
+    state: Arc<RwLock<CircuitBreakerState>>,
+    /// Thời điểm circuit chuyển sang Open
+    opened_at: Arc<RwLock<Option<Instant>>>,
 }
 
 /// Trạng thái của circuit breaker
@@ -220,19 +219,37 @@ impl CircuitBreaker {
         Self {
             failure_threshold,
             reset_timeout,
-            state: RwLock::new(CircuitBreakerState::Closed),
+            state: Arc::new(RwLock::new(CircuitBreakerState::Closed)),
+            opened_at: Arc::new(RwLock::new(None)),
         }
     }
     
+    /// Số lần lỗi liên tiếp tối đa đã cấu hình
+    pub fn failure_threshold(&self) -> u32 {
+        self.failure_threshold
+    }
+
     /// Kiểm tra xem circuit có cho phép thực hiện operation không
+    ///
+    /// Circuit đang Open sẽ chuyển sang HalfOpen khi đã hết `reset_timeout`,
+    /// không cần task nền nên dùng được cả ngoài tokio runtime.
     pub fn can_execute(&self) -> bool {
-        let state = *self.state.read().unwrap();
-        state != CircuitBreakerState::Open
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        if *state == CircuitBreakerState::Open {
+            let expired = self.opened_at.read().unwrap_or_else(|e| e.into_inner())
+                .map(|opened| opened.elapsed() >= Duration::from_millis(self.reset_timeout))
+                .unwrap_or(true);
+            if expired {
+                *state = CircuitBreakerState::HalfOpen;
+                debug!("Circuit breaker state changed to HalfOpen after reset timeout");
+            }
+        }
+        *state != CircuitBreakerState::Open
     }
     
     /// Thông báo thành công
     pub fn on_success(&self) {
-        let mut state = self.state.write().unwrap();
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
         if *state == CircuitBreakerState::HalfOpen {
             *state = CircuitBreakerState::Closed;
             debug!("Circuit breaker state changed to Closed after successful operation");
@@ -241,20 +258,11 @@ impl CircuitBreaker {
     
     /// Thông báo lỗi
     pub fn on_failure(&self) {
-        let mut state = self.state.write().unwrap();
-        if *state == CircuitBreakerState::Closed {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        if *state != CircuitBreakerState::Open {
             *state = CircuitBreakerState::Open;
+            *self.opened_at.write().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
             debug!("Circuit breaker state changed to Open after failure");
-            
-            // Reset circuit breaker sau một thời gian
-            let reset_timeout = self.reset_timeout;
-            let state_clone = self.state.clone();
-            tokio::spawn(async move {
-                sleep(Duration::from_millis(reset_timeout)).await;
-                let mut state = state_clone.write().unwrap();
-                *state = CircuitBreakerState::HalfOpen;
-                debug!("Circuit breaker state changed to HalfOpen after reset timeout");
-            });
         }
     }
 }
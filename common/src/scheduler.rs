@@ -1,5 +1,4 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
 
 // Standard library imports
 use std::{
@@ -9,7 +8,7 @@ use std::{
 };
 
 // Third party imports
-use anyhow::{Context, Result};
+use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -64,6 +63,7 @@ pub enum JobStatus {
 
 /// Basic scheduler
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct BasicScheduler {
     config: Arc<RwLock<SchedulerConfig>>,
     jobs: Arc<RwLock<HashMap<String, Job>>>,
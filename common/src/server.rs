@@ -5,16 +5,9 @@ use std::collections::HashMap;
 // External imports
 use tokio::net::TcpListener;
 use anyhow::{Result, Context};
-use tracing::{info, error, debug};
+use tracing::{info, debug};
 use async_trait::async_trait;
 
-// Internal imports
-use network::core::{
-    NetworkConfig,
-    NetworkError,
-    NetworkResult,
-};
-
 /// Server trait định nghĩa các phương thức cần thiết cho một server
 #[async_trait]
 pub trait Server: Send + Sync {
@@ -36,7 +29,6 @@ pub trait Server: Send + Sync {
 /// - Snipebot
 /// - AI
 /// - Wallet
-#[derive(Debug)]
 pub struct GatewayServer {
     /// Cấu hình server
     config: Arc<crate::config::Config>,
@@ -72,8 +64,8 @@ impl GatewayServer {
     }
     
     /// Lấy service theo tên
-    pub fn get_service(&self, name: &str) -> Option<&Box<dyn Server>> {
-        self.services.get(name)
+    pub fn get_service(&self, name: &str) -> Option<&dyn Server> {
+        self.services.get(name).map(|service| service.as_ref())
     }
 }
 
@@ -135,5 +127,4 @@ impl Server for GatewayServer {
     fn is_running(&self) -> bool {
         self.is_running
     }
-} 
-} 
\ No newline at end of file
+}
@@ -1,5 +1,4 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
 
 // Standard library imports
 use std::{
@@ -9,7 +8,7 @@ use std::{
 };
 
 // Third party imports
-use anyhow::{Context, Result};
+use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -17,10 +16,10 @@ use serde::{Deserialize, Serialize};
 #[async_trait]
 pub trait StateManager: Send + Sync + 'static {
     /// Lấy trạng thái
-    async fn get_state<T: Serialize + for<'de> Deserialize<'de>>(&self, key: &str) -> Result<Option<T>>;
+    async fn get_state<T: Serialize + for<'de> Deserialize<'de> + Send>(&self, key: &str) -> Result<Option<T>>;
 
     /// Lưu trạng thái
-    async fn set_state<T: Serialize + for<'de> Deserialize<'de>>(&self, key: &str, value: T) -> Result<()>;
+    async fn set_state<T: Serialize + for<'de> Deserialize<'de> + Send>(&self, key: &str, value: T) -> Result<()>;
 
     /// Xóa trạng thái
     async fn remove_state(&self, key: &str) -> Result<()>;
@@ -42,6 +41,7 @@ pub struct State<T> {
 
 /// Basic state manager
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct BasicStateManager {
     config: Arc<RwLock<StateManagerConfig>>,
     states: Arc<RwLock<HashMap<String, Vec<u8>>>>,
@@ -74,7 +74,7 @@ impl BasicStateManager {
 
 #[async_trait]
 impl StateManager for BasicStateManager {
-    async fn get_state<T: Serialize + for<'de> Deserialize<'de>>(&self, key: &str) -> Result<Option<T>> {
+    async fn get_state<T: Serialize + for<'de> Deserialize<'de> + Send>(&self, key: &str) -> Result<Option<T>> {
         let states = self.states.read().unwrap();
         if let Some(data) = states.get(key) {
             let state: State<T> = bincode::deserialize(data)?;
@@ -83,7 +83,7 @@ impl StateManager for BasicStateManager {
         Ok(None)
     }
 
-    async fn set_state<T: Serialize + for<'de> Deserialize<'de>>(&self, key: &str, value: T) -> Result<()> {
+    async fn set_state<T: Serialize + for<'de> Deserialize<'de> + Send>(&self, key: &str, value: T) -> Result<()> {
         let now = SystemTime::now();
         let state = State {
             value,
@@ -1,34 +1,19 @@
-// External imports
-use ethers::prelude::*;
-use axum::{
-    routing::{post},
-    http::StatusCode,
-    Json, Router,
-    extract::{State},
-};
-
 // Standard library imports
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 // Internal imports
-use crate::user_subscription::{SubscriptionLevel, Subscription};
-use crate::snipebot::SnipeBot;
-use crate::api::AppState;
-use crate::user::User;
-use crate::types::*;
+use crate::user::SubscriptionLevel;
 
 // Third party imports
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, anyhow};
-use tracing::{debug, info, warn, error};
+use tracing::info;
 
 // ====== SubscriptionManager Struct (từ snipebot/src/subscription_manager.rs) ======
+#[derive(Debug, Clone)]
 pub struct SubscriptionManager {
     subscription_config: SubscriptionTradeConfig,
-    current_user_level: SubscriptionLevel,
     active_subscriptions: Vec<UserSubscription>,
-    // Các thuộc tính khác
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,13 +34,16 @@ pub struct SubscriptionTradeConfig {
     pub max_slippage_percent: f64,
 }
 
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
 impl UserSubscription {
     pub fn new(user_id: String, level: SubscriptionLevel, duration_days: u64) -> Self {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_secs();
-        
+        let now = now_secs();
         let end_date = now + (duration_days * 24 * 60 * 60);
         
         Self {
@@ -67,20 +55,11 @@ impl UserSubscription {
     }
     
     pub fn is_active(&self) -> bool {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_secs();
-            
-        now <= self.end_date
+        now_secs() <= self.end_date
     }
     
     pub fn remaining_days(&self) -> u64 {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_secs();
-            
+        let now = now_secs();
         if now > self.end_date {
             0
         } else {
@@ -106,11 +85,16 @@ impl Default for SubscriptionTradeConfig {
     }
 }
 
+impl Default for SubscriptionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SubscriptionManager {
     pub fn new() -> Self {
         Self {
             subscription_config: SubscriptionTradeConfig::default(),
-            current_user_level: SubscriptionLevel::Free,
             active_subscriptions: Vec::new(),
         }
     }
@@ -119,16 +103,16 @@ impl SubscriptionManager {
         self.subscription_config = config;
     }
     
-    pub fn update_user_level(&mut self, user_id: &str, level: SubscriptionLevel) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn update_user_level(&mut self, user_id: &str, level: SubscriptionLevel) -> Result<()> {
         // Tìm subscription hiện tại nếu có
         if let Some(subscription) = self.active_subscriptions.iter_mut().find(|s| s.user_id == user_id) {
-            subscription.level = level;
             info!("Cập nhật cấp độ cho người dùng {} thành {:?}", user_id, level);
+            subscription.level = level;
         } else {
             // Tạo subscription mới với thời hạn mặc định 30 ngày
+            info!("Tạo subscription mới cho người dùng {} với cấp độ {:?}", user_id, level);
             let new_subscription = UserSubscription::new(user_id.to_string(), level, 30);
             self.active_subscriptions.push(new_subscription);
-            info!("Tạo subscription mới cho người dùng {} với cấp độ {:?}", user_id, level);
         }
         
         Ok(())
@@ -138,20 +122,20 @@ impl SubscriptionManager {
         self.active_subscriptions.iter().find(|s| s.user_id == user_id)
     }
     
-    pub fn extend_subscription(&mut self, user_id: &str, additional_days: u64) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn extend_subscription(&mut self, user_id: &str, additional_days: u64) -> Result<()> {
         if let Some(subscription) = self.active_subscriptions.iter_mut().find(|s| s.user_id == user_id) {
             subscription.extend(additional_days);
             info!("Gia hạn subscription cho người dùng {} thêm {} ngày", user_id, additional_days);
             Ok(())
         } else {
-            Err(format!("Không tìm thấy subscription cho người dùng {}", user_id).into())
+            Err(anyhow!("Không tìm thấy subscription cho người dùng {}", user_id))
         }
     }
     
-    pub fn check_subscription_limits(&self, user_id: &str, trade_type: TradeType) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn check_subscription_limits(&self, user_id: &str, trade_type: TradeType) -> Result<()> {
         if let Some(subscription) = self.get_user_subscription(user_id) {
             if !subscription.is_active() {
-                return Err(format!("Subscription của người dùng {} đã hết hạn", user_id).into());
+                return Err(anyhow!("Subscription của người dùng {} đã hết hạn", user_id));
             }
             
             // Kiểm tra các giới hạn dựa trên cấp độ và loại giao dịch
@@ -159,7 +143,7 @@ impl SubscriptionManager {
                 SubscriptionLevel::Free => {
                     // Giới hạn cho Free tier
                     match trade_type {
-                        TradeType::AutoTrade => Err("Tài khoản Free không hỗ trợ Auto Trade".into()),
+                        TradeType::AutoTrade => Err(anyhow!("Tài khoản Free không hỗ trợ Auto Trade")),
                         TradeType::ManualTrade => Ok(()),
                         TradeType::SnipeTrade => {
                             // Giới hạn 3 snipe trade mỗi ngày cho tài khoản Free
@@ -168,8 +152,8 @@ impl SubscriptionManager {
                         }
                     }
                 },
-                SubscriptionLevel::Premium => {
-                    // Giới hạn cho Premium tier
+                SubscriptionLevel::Basic | SubscriptionLevel::Premium => {
+                    // Giới hạn cho Basic/Premium tier
                     Ok(())
                 },
                 SubscriptionLevel::VIP => {
@@ -178,11 +162,11 @@ impl SubscriptionManager {
                 }
             }
         } else {
-            Err(format!("Không tìm thấy subscription cho người dùng {}", user_id).into())
+            Err(anyhow!("Không tìm thấy subscription cho người dùng {}", user_id))
         }
     }
     
-    pub fn get_subscription_config(&self, level: SubscriptionLevel) -> SubscriptionTradeConfig {
+    pub fn get_subscription_config(&self, level: &SubscriptionLevel) -> SubscriptionTradeConfig {
         // Tùy chỉnh cấu hình dựa trên cấp độ
         match level {
             SubscriptionLevel::Free => {
@@ -192,32 +176,34 @@ impl SubscriptionManager {
                 config.allowed_chains = vec!["ethereum".to_string()];
                 config
             },
+            SubscriptionLevel::Basic => {
+                let mut config = self.subscription_config.clone();
+                config.max_simultaneous_trades = 2;
+                config.max_daily_trades = 5;
+                config.max_gas_price_gwei = 200;
+                config
+            },
             SubscriptionLevel::Premium => {
                 let mut config = self.subscription_config.clone();
                 config.max_simultaneous_trades = 3;
                 config.max_daily_trades = 10;
+                config.max_gas_price_gwei = 300;
+                config
+            },
+            SubscriptionLevel::VIP => {
+                let mut config = self.subscription_config.clone();
+                config.max_gas_price_gwei = 500;
                 config
             },
-            SubscriptionLevel::VIP => self.subscription_config.clone(),
         }
     }
     
     // Phương thức kiểm tra và dọn dẹp các subscription hết hạn
     pub fn cleanup_expired_subscriptions(&mut self) {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_secs();
+        let now = now_secs();
             
-        // Tạo danh sách subscription hết hạn
-        let expired_subscriptions: Vec<String> = self.active_subscriptions
-            .iter()
-            .filter(|s| s.end_date < now)
-            .map(|s| s.user_id.clone())
-            .collect();
-            
-        for user_id in &expired_subscriptions {
-            info!("Subscription của người dùng {} đã hết hạn", user_id);
+        for subscription in self.active_subscriptions.iter().filter(|s| s.end_date < now) {
+            info!("Subscription của người dùng {} đã hết hạn", subscription.user_id);
         }
         
         // Loại bỏ các subscription hết hạn
@@ -225,143 +211,13 @@ impl SubscriptionManager {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TradeType {
     AutoTrade,
     ManualTrade,
     SnipeTrade,
 }
 
-// ====== API Models và Routes (từ file subscription.rs gốc) ======
-
-// Request models
-#[derive(Deserialize)]
-pub struct UpdateSubscriptionRequest {
-    pub username: String,
-    pub level: String,
-    pub duration_days: u64,
-}
-
-#[derive(Deserialize)]
-pub struct StartAutoTradeRequest {
-    pub username: String,
-}
-
-// Response models
-#[derive(Serialize)]
-pub struct ApiResponse<T> {
-    pub status: String,
-    pub message: String,
-    pub data: Option<T>,
-}
-
-#[derive(Serialize)]
-pub struct SubscriptionData {
-    pub level: String,
-    pub start_date: u64,
-    pub end_date: u64,
-    pub is_active: bool,
-}
-
-// Hàm tạo routes cho subscription
-pub fn subscription_routes() -> Router<Arc<AppState>> {
-    Router::new()
-        .route("/api/subscription/update", post(update_subscription_handler))
-        .route("/api/subscription/auto-trade/start", post(start_auto_trade_handler))
-}
-
-// Handler cập nhật subscription
-async fn update_subscription_handler(
-    State(state): State<Arc<AppState>>,
-    Json(req): Json<UpdateSubscriptionRequest>,
-) -> Result<Json<ApiResponse<SubscriptionData>>, (StatusCode, Json<ApiResponse<()>>)> {
-    // Chuyển đổi chuỗi cấp độ thành enum SubscriptionLevel
-    let level = match req.level.to_lowercase().as_str() {
-        "free" => SubscriptionLevel::Free,
-        "premium" => SubscriptionLevel::Premium,
-        "vip" => SubscriptionLevel::VIP,
-        _ => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ApiResponse {
-                    status: "error".to_string(),
-                    message: "Cấp độ đăng ký không hợp lệ".to_string(),
-                    data: None,
-                }),
-            ));
-        }
-    };
-
-    // Cập nhật subscription
-    let mut snipebot = state.snipebot.as_ref().clone();
-    match snipebot.update_user_subscription(&req.username, level, req.duration_days).await {
-        Ok(_) => {
-            // Lấy thông tin subscription đã cập nhật
-            let user_manager = state.user_manager.lock().await;
-            if let Ok(subscription) = user_manager.get_user_subscription(&req.username) {
-                let subscription_data = SubscriptionData {
-                    level: format!("{:?}", subscription.level),
-                    start_date: subscription.start_date,
-                    end_date: subscription.end_date,
-                    is_active: subscription.is_active(),
-                };
-
-                Ok(Json(ApiResponse {
-                    status: "success".to_string(),
-                    message: format!("Đã cập nhật cấp độ đăng ký cho người dùng {} thành {}", req.username, req.level),
-                    data: Some(subscription_data),
-                }))
-            } else {
-                Err((
-                    StatusCode::NOT_FOUND,
-                    Json(ApiResponse {
-                        status: "error".to_string(),
-                        message: format!("Không tìm thấy người dùng {}", req.username),
-                        data: None,
-                    }),
-                ))
-            }
-        },
-        Err(e) => {
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse {
-                    status: "error".to_string(),
-                    message: format!("Lỗi khi cập nhật cấp độ đăng ký: {}", e),
-                    data: None,
-                }),
-            ))
-        }
-    }
-}
-
-// Handler bắt đầu auto trade
-async fn start_auto_trade_handler(
-    State(state): State<Arc<AppState>>,
-    Json(req): Json<StartAutoTradeRequest>,
-) -> Result<Json<ApiResponse<()>>, (StatusCode, Json<ApiResponse<()>>)> {
-    let mut snipebot = state.snipebot.as_ref().clone();
-    match snipebot.start_auto_trade(&req.username).await {
-        Ok(_) => {
-            Ok(Json(ApiResponse {
-                status: "success".to_string(),
-                message: format!("Đã bắt đầu auto trade cho người dùng {}", req.username),
-                data: None,
-            }))
-        },
-        Err(e) => {
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse {
-                    status: "error".to_string(),
-                    message: format!("Lỗi khi bắt đầu auto trade: {}", e),
-                    data: None,
-                }),
-            ))
-        }
-    }
-}
-
 // Unit tests
 #[cfg(test)]
 mod tests {
@@ -373,7 +229,7 @@ mod tests {
         let level = SubscriptionLevel::Premium;
         let duration_days = 30;
         
-        let subscription = UserSubscription::new(user_id.clone(), level, duration_days);
+        let subscription = UserSubscription::new(user_id.clone(), level.clone(), duration_days);
         
         assert_eq!(subscription.user_id, user_id);
         assert_eq!(subscription.level, level);
@@ -388,7 +244,7 @@ mod tests {
         let level = SubscriptionLevel::Premium;
         
         // Kiểm tra thêm subscription mới
-        assert!(manager.update_user_level(user_id, level).is_ok());
+        assert!(manager.update_user_level(user_id, level.clone()).is_ok());
         
         // Kiểm tra lấy subscription
         let subscription = manager.get_user_subscription(user_id);
@@ -404,4 +260,15 @@ mod tests {
         // Kiểm tra giới hạn giao dịch
         assert!(manager.check_subscription_limits(user_id, TradeType::ManualTrade).is_ok());
     }
+
+    #[test]
+    fn test_gas_cap_per_level() {
+        let manager = SubscriptionManager::new();
+        let cap = |level| manager.get_subscription_config(&level).max_gas_price_gwei;
+
+        assert_eq!(cap(SubscriptionLevel::Free), 100);
+        assert_eq!(cap(SubscriptionLevel::Basic), 200);
+        assert_eq!(cap(SubscriptionLevel::Premium), 300);
+        assert_eq!(cap(SubscriptionLevel::VIP), 500);
+    }
 }
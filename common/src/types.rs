@@ -4,9 +4,6 @@ use ethers::types::{
     H256,
     U256,
     Block as EthBlock,
-    TransactionReceipt,
-    Log,
-    Filter,
 };
 
 // Standard library imports
@@ -14,16 +11,12 @@ use std::{
     sync::{Arc, RwLock},
     time::{Duration, SystemTime},
     collections::HashMap,
-    fmt::{Debug, Display, Formatter},
+    fmt::Debug,
 };
 
 // Third party imports
 use serde::{Deserialize, Serialize};
 use anyhow::Result;
-use tracing::{info, warn, error, debug};
-use async_trait::async_trait;
-use tokio::time::{timeout, sleep};
-use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
 use crate::error::CommonError;
@@ -410,6 +403,7 @@ pub type Block = EthBlock<H256>;
 
 /// Module tests
 #[cfg(test)]
+#[allow(clippy::unnecessary_literal_unwrap)]
 mod tests {
     use super::*;
 
@@ -570,7 +564,7 @@ mod tests {
     #[test]
     fn test_common_bool_result() {
         let result: CommonBoolResult = Ok(true);
-        assert_eq!(result.unwrap(), true);
+        assert!(result.unwrap());
     }
 
     /// Test CommonU64Result
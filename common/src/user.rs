@@ -1,5 +1,4 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
 
 // Standard library imports
 use std::collections::HashMap;
@@ -7,21 +6,18 @@ use std::path::Path;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-// Internal imports
-use crate::error::Result;
-
 // Third party imports
-use anyhow::{anyhow};
+use anyhow::{anyhow, Result};
 use argon2::{
     password_hash::{
         rand_core::OsRng,
-        SaltString, PasswordHash, PasswordHasher, PasswordVerifier,
+        SaltString, PasswordHasher,
     },
     Argon2,
 };
 use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::Utc;
-use log::{info, error};
+use tracing::info;
 use serde::{Deserialize, Serialize};
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -200,10 +196,7 @@ impl User {
     
     /// Kiểm tra mật khẩu
     pub fn verify_password(&self, password: &str) -> bool {
-        match verify(password, &self.password_hash) {
-            Ok(result) => result,
-            Err(_) => false,
-        }
+        verify(password, &self.password_hash).unwrap_or_default()
     }
     
 /// Đặt mật khẩu mới
@@ -409,13 +402,12 @@ impl UserManager {
             .ok_or_else(|| anyhow!("Invalid username or password"))?;
         
         // Cập nhật thời gian đăng nhập
-        if let Some(user) = self.users.get_mut(&user_id) {
-            user.update_last_login();
-            self.save().await?;
-            Ok(user)
-        } else {
-            Err(anyhow!("User not found"))
+        match self.users.get_mut(&user_id) {
+            Some(user) => user.update_last_login(),
+            None => return Err(anyhow!("User not found")),
         }
+        self.save().await?;
+        self.users.get(&user_id).ok_or_else(|| anyhow!("User not found"))
     }
     
     /// Cập nhật thông tin người dùng
@@ -1,18 +1,11 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
 
 // Standard library imports
-use std::{
-    collections::HashMap,
-    sync::{Arc, RwLock},
-    time::{Duration, SystemTime, UNIX_EPOCH},
-    fmt::{Display, Formatter},
-};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // Third party imports
-use anyhow::{Context, Result};
+use anyhow::Result;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use std::future::Future;
 use tokio;
@@ -46,44 +39,6 @@ pub fn xor_encrypt_decrypt(data: &[u8], key: &[u8]) -> Vec<u8> {
     result
 }
 
-/// Module tests
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    /// Test current_timestamp
-    #[test]
-    fn test_current_timestamp() {
-        let timestamp = current_timestamp();
-        assert!(timestamp > 0);
-    }
-
-    /// Test random_id
-    #[test]
-    fn test_random_id() {
-        let id = random_id();
-        assert!(!id.is_empty());
-    }
-
-    /// Test is_node_online
-    #[test]
-    fn test_is_node_online() {
-        let last_seen = Utc::now();
-        let timeout = Duration::from_secs(60);
-        assert!(is_node_online(last_seen, timeout));
-    }
-
-    /// Test xor_encrypt_decrypt
-    #[test]
-    fn test_xor_encrypt_decrypt() {
-        let data = b"test";
-        let key = b"key";
-        let encrypted = xor_encrypt_decrypt(data, key);
-        let decrypted = xor_encrypt_decrypt(&encrypted, key);
-        assert_eq!(data, decrypted.as_slice());
-    }
-}
-
 pub fn get_timestamp() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -154,4 +109,42 @@ where
     }
 
     Err(format!("Operation failed after {} retries", max_retries).into())
+}
+
+/// Module tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test current_timestamp
+    #[test]
+    fn test_current_timestamp() {
+        let timestamp = current_timestamp();
+        assert!(timestamp > 0);
+    }
+
+    /// Test random_id
+    #[test]
+    fn test_random_id() {
+        let id = random_id();
+        assert!(!id.is_empty());
+    }
+
+    /// Test is_node_online
+    #[test]
+    fn test_is_node_online() {
+        let last_seen = Utc::now();
+        let timeout = Duration::from_secs(60);
+        assert!(is_node_online(last_seen, timeout));
+    }
+
+    /// Test xor_encrypt_decrypt
+    #[test]
+    fn test_xor_encrypt_decrypt() {
+        let data = b"test";
+        let key = b"key";
+        let encrypted = xor_encrypt_decrypt(data, key);
+        let decrypted = xor_encrypt_decrypt(&encrypted, key);
+        assert_eq!(data, decrypted.as_slice());
+    }
 } 
\ No newline at end of file
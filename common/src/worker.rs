@@ -1,15 +1,13 @@
 // External imports
-use ethers::core::types::{Address, H256, U256};
 
 // Standard library imports
 use std::{
-    collections::HashMap,
     sync::{Arc, RwLock},
     time::{Duration, SystemTime},
 };
 
 // Third party imports
-use anyhow::{Context, Result};
+use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -61,6 +59,7 @@ pub enum WorkerStatus {
 
 /// Basic worker
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct BasicWorker {
     config: Arc<RwLock<WorkerConfig>>,
     status: Arc<RwLock<WorkerStatus>>,
@@ -15,26 +15,25 @@ use uuid::Uuid;
 use serde_json;
 
 /// Loại node trong mạng
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Default)]
 pub enum NodeType {
     /// Node master
     Master,
     /// Node slave
+    #[default]
     Slave,
     /// Node server
     Server,
 }
 
-impl Default for NodeType {
-    fn default() -> Self {
-        Self::Slave
-    }
-}
 
 /// Loại storage
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Default)]
 pub enum StorageType {
     /// Storage trong bộ nhớ
+    #[default]
     Memory,
     /// Storage trong Redis
     Redis,
@@ -42,11 +41,6 @@ pub enum StorageType {
     File,
 }
 
-impl Default for StorageType {
-    fn default() -> Self {
-        Self::Memory
-    }
-}
 
 /// Cấu hình mạng
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -123,7 +117,8 @@ impl Default for NetworkConfig {
             grpc_enabled: true,
             grpc_port: 8082,
             
-            quic_enabled: true,
+            // QUIC cần certificate/key nên mặc định tắt, bật khi đã cấu hình cert_path/key_path
+            quic_enabled: false,
             quic_port: 8083,
             cert_path: None,
             key_path: None,
@@ -1,13 +1,9 @@
 // External imports
-use anyhow::{Result, Context};
+use anyhow::Result;
 use thiserror::Error;
 
 // Standard library imports
-use std::{
-    io,
-    fmt::{self, Display, Formatter},
-    error::Error as StdError,
-};
+use std::io;
 
 /// Network error
 #[derive(Debug, Error)]
@@ -87,7 +83,7 @@ mod tests {
     /// Test NetworkError
     #[test]
     fn test_network_error() {
-        let io_error = NetworkError::IoError(io::Error::new(io::ErrorKind::Other, "test"));
+        let io_error = NetworkError::IoError(io::Error::other("test"));
         assert!(matches!(io_error, NetworkError::IoError(_)));
         
         let conn_error = NetworkError::connection_error("test");
@@ -112,10 +108,10 @@ mod tests {
         let error = NetworkError::connection_error("failed to connect");
         assert_eq!(error.to_string(), "Connection error: failed to connect");
         
-        let error = NetworkError::TimeoutError;
-        assert_eq!(error.to_string(), "Timeout error");
+        let error = NetworkError::TimeoutError("no response".to_string());
+        assert_eq!(error.to_string(), "Timeout error: no response");
         
-        let error = NetworkError::NotConnected;
-        assert_eq!(error.to_string(), "Not connected");
+        let error = NetworkError::ServerNotRunning;
+        assert_eq!(error.to_string(), "Server is not running");
     }
 } 
\ No newline at end of file
@@ -1,33 +1,23 @@
 // External imports
-use ethers::types as ethers_types;
 use ethers::types::U256;
 use anyhow::Result;
 use async_trait::async_trait;
 
 // Standard library imports
-use std::net::SocketAddr;
-use std::{
-    sync::{Arc, RwLock},
-    time::Duration,
-    fmt::{Display, Formatter},
-};
 
 // Module declarations
-mod config;
-mod error;
-mod models;
-mod types;
-mod utils;
-mod server;
+pub mod config;
+pub mod error;
+pub mod models;
+pub mod types;
+pub mod utils;
 pub mod message;
 
 // Re-exports
 pub use config::NetworkConfig as NetworkConfigInternal;
 pub use error::{NetworkError, NetworkResult};
 pub use models::*;
-pub use types::*;
 pub use utils::*;
-pub use server::*;
 
 /// Cấu hình mạng
 #[derive(Debug, Clone)]
@@ -1,9 +1,7 @@
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 use std::net::SocketAddr;
-use std::fmt::{self, Display, Formatter};
 use chrono::{DateTime, Utc};
-use anyhow::Result;
 use ethers::types::U256;
 
 /// Node trong mạng
@@ -156,6 +154,7 @@ impl ConnectionInfo {
 
 /// Cấu trúc thống kê mạng
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Default)]
 pub struct NetworkStats {
     /// Số bytes đã gửi
     pub bytes_sent: u64,
@@ -167,16 +166,6 @@ pub struct NetworkStats {
     pub packets_received: u64,
 }
 
-impl Default for NetworkStats {
-    fn default() -> Self {
-        Self {
-            bytes_sent: 0,
-            bytes_received: 0,
-            packets_sent: 0,
-            packets_received: 0,
-        }
-    }
-}
 
 /// Thông tin về trạng thái mạng
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,8 +1,8 @@
 use serde::{Serialize, Deserialize};
-use std::fmt::{self, Display, Formatter};
+use std::fmt;
 use std::net::SocketAddr;
 use std::error::Error;
-use anyhow::{Result, Context};
+use anyhow::Result;
 use chrono::{DateTime, Utc};
 use std::io::{self, ErrorKind};
 
@@ -1,17 +1,15 @@
 // External imports
-use anyhow::{Result, Context};
 
 // Standard library imports
 use std::{
     time::{SystemTime, UNIX_EPOCH},
-    net::{IpAddr, SocketAddr},
+    net::IpAddr,
     str::FromStr,
 };
 
 // Third party imports
 use rand::{thread_rng, Rng};
 use hex;
-use tokio::time::sleep;
 
 /// Lấy thời gian hiện tại dưới dạng timestamp (số giây từ epoch)
 pub fn current_timestamp() -> u64 {
@@ -38,7 +36,7 @@ pub fn is_valid_address(address: &str) -> bool {
     
     // Kiểm tra port
     if let Ok(port) = port.parse::<u16>() {
-        port > 0 && port <= 65535
+        port > 0
     } else {
         false
     }
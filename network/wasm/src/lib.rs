@@ -1,24 +1,20 @@
 // External imports
-use ethers::types::{Transaction, Address, U256, H256};
+use ethers::types::Transaction;
 
 // Standard library imports
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use std::str::FromStr;
-use std::collections::HashMap;
 
 // Internal imports
 // ... none for now ...
 
 // Third party imports
-use anyhow::{Result, anyhow, Context};
+use anyhow::{Result, anyhow};
 use serde::{Serialize, Deserialize};
 use tracing::{info, error, debug, warn};
 use once_cell::sync::OnceCell;
 use tokio::sync::Mutex;
-use wasmer::{Store, Module, Instance, Value, Function, imports};
-use wasmer_compiler_llvm::LLVM;
+use wasmer::{Store, Module, Instance, Value, imports};
 use sha2::{Sha256, Digest};
 
 // Lưu trữ WASM runtime
@@ -81,8 +77,8 @@ pub struct TransactionInput {
 impl WasmRuntime {
     // Khởi tạo WASM runtime
     pub fn new() -> Result<Self> {
-        let compiler = LLVM::default();
-        let store = Store::new(&compiler);
+        // Store mặc định dùng compiler Cranelift, không cần LLVM trên máy build
+        let store = Store::default();
         
         Ok(Self {
             store,
@@ -104,8 +100,9 @@ impl WasmRuntime {
     }
     
     // Chạy module WASM với dữ liệu input
-    pub fn execute_analysis(&self, module_name: &str, input: &WasmInput) -> Result<WasmAnalysisResult> {
-        let module = self.modules.iter()
+    pub fn execute_analysis(&mut self, module_name: &str, input: &WasmInput) -> Result<WasmAnalysisResult> {
+        let Self { store, modules } = self;
+        let module = modules.iter()
             .find(|(name, _)| name == module_name)
             .map(|(_, module)| module)
             .ok_or_else(|| anyhow!("Không tìm thấy module: {}", module_name))?;
@@ -117,7 +114,7 @@ impl WasmRuntime {
         let import_object = imports! {};
         
         // Khởi tạo instance từ module
-        let instance = Instance::new(module, &import_object)?;
+        let instance = Instance::new(store, module, &import_object)?;
         
         // Lấy memory export
         let memory = instance.exports.get_memory("memory")?;
@@ -131,33 +128,34 @@ impl WasmRuntime {
         let input_bytes = input_json.as_bytes();
         let input_size = input_bytes.len();
         
-        let alloc_result = alloc.call(&[Value::I32(input_size as i32)])?;
-        let input_ptr = alloc_result[0].unwrap_i32() as usize;
+        let alloc_result = alloc.call(store, &[Value::I32(input_size as i32)])?;
+        let input_ptr = alloc_result[0].unwrap_i32() as u64;
         
         // Ghi input vào memory
-        memory.view()[input_ptr..(input_ptr + input_size)].copy_from_slice(input_bytes);
+        memory.view(store).write(input_ptr, input_bytes)?;
         
         // Gọi hàm phân tích
-        let analyze_result = analyze.call(&[
+        let analyze_result = analyze.call(store, &[
             Value::I32(input_ptr as i32),
             Value::I32(input_size as i32),
         ])?;
         
         // Lấy con trỏ và kích thước kết quả
-        let result_ptr = analyze_result[0].unwrap_i32() as usize;
+        let result_ptr = analyze_result[0].unwrap_i32() as u64;
         let result_size = analyze_result[1].unwrap_i32() as usize;
         
         // Đọc kết quả từ memory
-        let result_bytes = memory.view()[result_ptr..(result_ptr + result_size)].to_vec();
+        let mut result_bytes = vec![0u8; result_size];
+        memory.view(store).read(result_ptr, &mut result_bytes)?;
         let result_json = String::from_utf8(result_bytes)?;
         
         // Giải phóng memory
-        dealloc.call(&[
+        dealloc.call(store, &[
             Value::I32(input_ptr as i32),
             Value::I32(input_size as i32),
         ])?;
         
-        dealloc.call(&[
+        dealloc.call(store, &[
             Value::I32(result_ptr as i32),
             Value::I32(result_size as i32),
         ])?;
@@ -198,7 +196,7 @@ pub async fn analyze_transaction(transaction: &Transaction) -> Result<WasmAnalys
     let runtime = WASM_RUNTIME.get()
         .ok_or_else(|| anyhow!("WASM runtime chưa được khởi tạo"))?;
     
-    let runtime = runtime.lock().await;
+    let mut runtime = runtime.lock().await;
     
     // Chuẩn bị input để phân tích
     let input = prepare_wasm_input(transaction)?;
@@ -334,7 +332,7 @@ fn analyze_method_id(method_id: &str, risk_factors: &mut Vec<String>, confidence
 }
 
 // Phân tích contract
-fn analyze_contract(token_address: &str) -> ContractAnalysis {
+fn analyze_contract(_token_address: &str) -> ContractAnalysis {
     // Đây là phiên bản mock - trong thực tế nên tích hợp với phân tích thực
     ContractAnalysis {
         is_verified: false,
@@ -21,7 +21,7 @@ pub fn get_pair_abi() -> &'static str {
 }
 
 // Re-export ABI hoặc trực tiếp
-pub use abi::abis::erc20::ERC20_ABI;
+pub use abi::abis::erc20_token::ERC20_ABI;
 pub use abi::abis::uniswap_v2_router::UNIV2ROUTER_ABI;
 pub use abi::abis::uniswap_v2_factory::UNIV2FACTORY_ABI;
 pub use abi::abis::uniswap_v2_pair::UNIV2PAIR_ABI; 
\ No newline at end of file
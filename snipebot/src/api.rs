@@ -1,53 +1,49 @@
 use axum::{
-    routing::{get, post},
-    http::StatusCode,
+    routing::{get, post, delete},
+    http::{header, Request, StatusCode},
     Json, Router,
     extract::{State, Path, Query, Extension},
     response::{IntoResponse, Response},
     middleware::{self, Next},
-    http::Request,
-    body::Body,
 };
 use std::sync::Arc;
 use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
 use serde::{Serialize, Deserialize};
-use super::config::{Config, BotMode};
-use super::nodes;
-use super::snipebot::SnipeBot;
-use super::storage::Storage;
+use serde_json::json;
 use tracing::{info, warn, error};
-use ethers::types::U256;
-use super::user::{UserManager, UserRole, User};
-use common::middleware::auth::{auth_middleware, create_token, Claims, logout, JWTAuthMiddleware, JWTAuthError};
-use common::middleware::rate_limit::ip_rate_limit_middleware;
-use common::middleware::UserRole;
 use tower_http::cors::{CorsLayer, Any};
-use crate::metrics::RETRY_METRICS;
-use crate::utils::{RetryConfig, transaction_retry_config};
-use std::time::{SystemTime, UNIX_EPOCH};
-use crate::types::{
-    NetworkStats,
-    UserInfo,
-    SystemStats,
-    WalletBalance,
-    TokenBalance,
-};
-use crate::user_subscription::SubscriptionLevel;
-use std::collections::HashMap;
-use chrono::Utc;
-use crate::utils::{
-    safe_now, get_uptime, get_cpu_usage, get_memory_usage,
-    get_disk_usage, get_network_stats, get_balance, perform_logout,
-};
-use crate::types::{
-    NetworkStats,
-    UserInfo,
-    SystemStats,
-    WalletBalance,
-    TokenBalance,
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::{Address, TransactionRequest, U256};
+use anyhow::{anyhow, Result};
+use diamond_common::middleware::auth::{
+    create_jwt_token, decode_jwt_token, Claims, UserRole as TokenRole, TOKEN_BLACKLIST,
 };
-use std::sync::{Mutex, RwLock};
-use super::EndpointManager;
+use diamond_common::middleware::check_rate_limit;
+use diamond_common::user::{SubscriptionLevel, User, UserInfo, UserManager};
+use diamond_wallet::{SafeWalletView, SelectionStrategy, SolanaKeypair, WalletGroup, WalletManager};
+use crate::config::{Config, BotMode};
+use crate::endpoint_manager::EndpointManager;
+use crate::snipebot::{SnipeBot, SnipeResult};
+use crate::storage::{Storage, Transaction};
+use crate::metric::RETRY_METRICS;
+use crate::token_status::TokenStatus;
+use crate::chain_adapters::l2_fee::{self, L1FeeModel};
+use crate::chain_adapters::chain_config_loader::{self, ChainReloadReport, ChainSummary};
+use crate::chain_adapters::treasury::{Treasury, TreasuryPlan, TreasuryReport, TreasuryRequest};
+use crate::chain_adapters::{self, chain_registry, solana_adapter};
+use crate::chain_adapters::interfaces::ChainAdapter;
+use crate::chain_adapters::portfolio::{Portfolio, PortfolioIndexer, PortfolioSnapshot};
+
+/// Thời hạn của JWT cấp khi đăng nhập (giờ)
+const TOKEN_LIFETIME_HOURS: usize = 24;
+
+/// Số request tối đa mỗi người dùng trong một cửa sổ rate limit
+const RATE_LIMIT_PER_WINDOW: usize = 100;
+
+/// Độ dài cửa sổ rate limit (giây)
+const RATE_LIMIT_WINDOW_SECS: u64 = 60;
 
 /// Cấu trúc phản hồi API
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,12 +63,23 @@ pub struct ApiErrorResponse {
     pub message: String,
 }
 
+type ApiError = (StatusCode, Json<ApiErrorResponse>);
+
 pub struct AppState {
     pub config: Config,
     pub storage: Arc<Storage>,
     pub snipebot: Arc<SnipeBot>,
     pub user_manager: Arc<tokio::sync::Mutex<UserManager>>,
-    pub endpoint_manager: Arc<RwLock<EndpointManager>>,
+    pub wallets: Arc<WalletManager>,
+    pub portfolio: Arc<PortfolioIndexer<dyn ChainAdapter>>,
+    pub endpoint_manager: Arc<tokio::sync::RwLock<EndpointManager>>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
 }
 
 impl<T> ApiResponse<T> {
@@ -81,10 +88,7 @@ impl<T> ApiResponse<T> {
             status: "success".to_string(),
             message: "Operation successful".to_string(),
             data: Some(data),
-            timestamp: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
+            timestamp: unix_now(),
         }
     }
 
@@ -93,10 +97,7 @@ impl<T> ApiResponse<T> {
             status: "error".to_string(),
             message: message.into(),
             data: None,
-            timestamp: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
+            timestamp: unix_now(),
         }
     }
 }
@@ -113,176 +114,293 @@ impl<T: Serialize> IntoResponse for ApiResponse<T> {
     }
 }
 
+fn api_error(status: StatusCode, error: &str, message: String) -> ApiError {
+    (
+        status,
+        Json(ApiErrorResponse {
+            error: error.to_string(),
+            code: u32::from(status.as_u16()),
+            status: "error".to_string(),
+            message,
+        })
+    )
+}
+
 // Struct cho RetryMetrics
 #[derive(Serialize, Deserialize)]
-pub struct RetryMetrics {
-    pub total_attempts: u64,
-    pub successful_attempts: u64,
-    pub failed_attempts: u64,
-    pub retried_operations: u64,
-    pub average_retries: f64,
-    pub max_retries_hit: u64,
-    pub rpc_errors: std::collections::HashMap<String, u64>,
-    pub gas_adjustments: u64,
+pub struct RetryMetricsView {
+    pub total_retries: u64,
+    pub successful_retries: u64,
+    pub failed_retries: u64,
+    pub first_attempt_success: u64,
+    pub avg_completion_time_ms: u64,
+    pub fallback_provider_activations: u64,
+    pub success_rate: f64,
+}
+
+// Struct cho LoginRequest
+#[derive(Deserialize)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+// Struct cho LoginResponse
+#[derive(Serialize)]
+struct LoginResponse {
+    token: String,
+    user_info: UserInfo,
+    expires_in: u64,
+}
+
+// Đăng nhập: xác thực mật khẩu và cấp JWT
+async fn login(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<LoginRequest>,
+) -> Result<Json<ApiResponse<LoginResponse>>, ApiError> {
+    let mut user_manager = state.user_manager.lock().await;
+    let user = user_manager.authenticate(&payload.username, &payload.password).await
+        .map_err(|_| api_error(StatusCode::UNAUTHORIZED, "invalid_credentials", "Sai tên đăng nhập hoặc mật khẩu".to_string()))?;
+
+    let role = TokenRole::from(user.roles.first().map(String::as_str).unwrap_or("user"));
+    let token = create_jwt_token(&user.id, role, user.wallets.clone(), TOKEN_LIFETIME_HOURS)
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "token_failed", format!("Không thể tạo token: {}", e)))?;
+
+    Ok(Json(ApiResponse::success(LoginResponse {
+        token,
+        user_info: user.to_user_info(),
+        expires_in: TOKEN_LIFETIME_HOURS as u64 * 3600,
+    })))
+}
+
+// Struct cho RegisterRequest
+#[derive(Deserialize)]
+struct RegisterRequest {
+    username: String,
+    password: String,
+    email: String,
 }
 
 // Triển khai hàm register
 async fn register(
     State(state): State<Arc<AppState>>,
     Json(payload): Json<RegisterRequest>,
-) -> Result<Json<ApiResponse<String>>, (StatusCode, Json<ApiErrorResponse>)> {
-    let mut user_manager = state.user_manager.lock().await;
-    
+) -> Result<Json<ApiResponse<String>>, ApiError> {
     // Kiểm tra thông tin đăng ký
     if payload.username.is_empty() || payload.password.is_empty() || payload.email.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ApiErrorResponse {
-                status: "error".to_string(),
-                message: "Thông tin đăng ký không đầy đủ".to_string(),
-            })
-        ));
+        return Err(api_error(StatusCode::BAD_REQUEST, "invalid_registration", "Thông tin đăng ký không đầy đủ".to_string()));
     }
-    
+
     // Kiểm tra định dạng email
     if !payload.email.contains('@') {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ApiErrorResponse {
-                status: "error".to_string(),
-                message: "Định dạng email không hợp lệ".to_string(),
-            })
-        ));
+        return Err(api_error(StatusCode::BAD_REQUEST, "invalid_email", "Định dạng email không hợp lệ".to_string()));
     }
-    
+
     // Kiểm tra độ dài mật khẩu
     if payload.password.len() < 8 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ApiErrorResponse {
-                status: "error".to_string(),
-                message: "Mật khẩu phải có ít nhất 8 ký tự".to_string(),
-            })
-        ));
-    }
-    
-    // Thử tạo tài khoản
-    match user_manager.create_user(&payload.username, &payload.password, &payload.email, UserRole::User) {
-        Ok(_) => {
-            // Lưu thay đổi
-            if let Err(e) = user_manager.save_users().await {
-                error!("Lỗi khi lưu thông tin người dùng: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiErrorResponse {
-                        status: "error".to_string(),
-                        message: "Không thể lưu thông tin người dùng".to_string(),
-                    })
-                ));
-            }
-            
-            Ok(Json(ApiResponse::success("Đăng ký thành công".to_string())))
-        },
-        Err(e) => {
-            Err((
-                StatusCode::BAD_REQUEST,
-                Json(ApiErrorResponse {
-                    status: "error".to_string(),
-                    message: e.to_string(),
-                })
-            ))
-        }
+        return Err(api_error(StatusCode::BAD_REQUEST, "weak_password", "Mật khẩu phải có ít nhất 8 ký tự".to_string()));
     }
+
+    let user = User::new(&payload.username, &payload.email, &payload.password)
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "user_create_failed", e.to_string()))?;
+    state.user_manager.lock().await.add_user(user).await
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "user_create_failed", e.to_string()))?;
+
+    Ok(Json(ApiResponse::success("Đăng ký thành công".to_string())))
 }
 
-// Struct cho RegisterRequest
+// Thông tin người dùng đang đăng nhập
+async fn get_current_user(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<ApiResponse<UserInfo>>, ApiError> {
+    state.user_manager.lock().await.get_user(&claims.sub)
+        .map(|user| Json(ApiResponse::success(user.to_user_info())))
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "user_not_found", "Không tìm thấy người dùng".to_string()))
+}
+
+// Đăng xuất: đưa token hiện tại vào blacklist tới khi hết hạn
+async fn logout_user(
+    Extension(claims): Extension<Claims>,
+) -> Json<ApiResponse<String>> {
+    TOKEN_BLACKLIST.write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .add_token(claims.jti, claims.exp as u64);
+
+    Json(ApiResponse::success("Đã đăng xuất thành công".to_string()))
+}
+
+// Struct cho SubscriptionData
+#[derive(Debug, Serialize)]
+struct SubscriptionData {
+    level: String,
+    start_date: u64,
+    expiry_date: u64,
+    is_active: bool,
+}
+
+// Gói đăng ký của người dùng đang đăng nhập
+async fn get_subscription_status(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<ApiResponse<SubscriptionData>>, ApiError> {
+    let user_manager = state.user_manager.lock().await;
+    let subscription = user_manager.get_user(&claims.sub)
+        .and_then(|user| user.subscription.clone())
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "subscription_not_found", "Người dùng chưa có gói đăng ký".to_string()))?;
+
+    Ok(Json(ApiResponse::success(SubscriptionData {
+        level: format!("{:?}", subscription.level),
+        start_date: subscription.start_date,
+        expiry_date: subscription.expiry_date,
+        is_active: subscription.is_active(),
+    })))
+}
+
+// Struct cho UpdateSubscriptionRequest
 #[derive(Deserialize)]
-struct RegisterRequest {
-    username: String,
-    password: String,
-    email: String,
+struct UpdateSubscriptionRequest {
+    level: String,
+    duration_days: Option<u64>,
 }
 
-// Struct cho LoginResponse
-#[derive(Serialize)]
-struct LoginResponse {
-    token: String,
-    refresh_token: String,
-    user_info: UserInfo,
-    expires_in: u64,
+// Xác định cấp độ đăng ký từ tên gói
+fn parse_subscription_level(level: &str) -> SubscriptionLevel {
+    match level {
+        "basic" => SubscriptionLevel::Basic,
+        "premium" => SubscriptionLevel::Premium,
+        "vip" => SubscriptionLevel::VIP,
+        _ => SubscriptionLevel::Free,
+    }
 }
 
-// Triển khai hàm update_subscription_handler
+// Cập nhật gói đăng ký cho một người dùng (chỉ admin)
 async fn update_subscription_handler(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    Json(payload): Json<UpdateSubscriptionRequest>,
+) -> Result<Json<ApiResponse<bool>>, ApiError> {
+    let level = parse_subscription_level(&payload.level);
+    let mut user_manager = state.user_manager.lock().await;
+
+    state.snipebot
+        .update_user_subscription(&mut user_manager, &user_id, level, payload.duration_days.unwrap_or(30)).await
+        .map(|_| Json(ApiResponse::success(true)))
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "subscription_update_failed", e.to_string()))
+}
+
+// Người dùng tự đổi gói đăng ký. Chưa có xác minh thanh toán nên chỉ được giữ
+// hoặc hạ cấp gói hiện tại, không gia hạn thêm ngày
+async fn update_own_subscription(
     State(state): State<Arc<AppState>>,
     Extension(claims): Extension<Claims>,
     Json(payload): Json<UpdateSubscriptionRequest>,
-) -> Result<Json<ApiResponse<SubscriptionData>>, (StatusCode, Json<ApiErrorResponse>)> {
-    let username = claims.sub.clone();
+) -> Result<Json<ApiResponse<SubscriptionData>>, ApiError> {
+    let level = parse_subscription_level(&payload.level);
     let mut user_manager = state.user_manager.lock().await;
-    
-    // Xác định cấp độ đăng ký
-    let level = match payload.level.as_str() {
-        "premium" => SubscriptionLevel::Premium,
-        "vip" => SubscriptionLevel::VIP,
-        _ => SubscriptionLevel::Free,
-    };
-    
-    // Tính thời gian kết thúc đăng ký
-    let current_time = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
-    
-    let end_date = if let Some(days) = payload.duration_days {
-        current_time + (days as u64 * 86400)
-    } else {
-        current_time + (30 * 86400) // Mặc định 30 ngày
-    };
-    
-    // Cập nhật đăng ký
-    match user_manager.update_user_subscription(&username, level, current_time, end_date) {
-        Ok(subscription) => {
-            // Lưu thay đổi
-            if let Err(e) = user_manager.save_users().await {
-                error!("Lỗi khi lưu thông tin người dùng: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiErrorResponse {
-                        status: "error".to_string(),
-                        message: "Không thể lưu thông tin đăng ký".to_string(),
-                    })
-                ));
-            }
-            
-            // Tạo response
-            let subscription_data = SubscriptionData {
-                level: format!("{:?}", subscription.level),
-                start_date: subscription.start_date,
-                end_date: subscription.end_date,
-                is_active: subscription.is_active(),
-            };
-            
-            Ok(Json(ApiResponse::success(subscription_data)))
-        },
-        Err(e) => {
-            Err((
-                StatusCode::BAD_REQUEST,
-                Json(ApiErrorResponse {
-                    status: "error".to_string(),
-                    message: e.to_string(),
-                })
-            ))
-        }
+
+    let current = user_manager.get_user(&claims.sub)
+        .and_then(|user| user.subscription.clone())
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "subscription_not_found", "Người dùng chưa có gói đăng ký".to_string()))?;
+    if crate::types::SubscriptionLevel::from(level.clone()) > crate::types::SubscriptionLevel::from(current.level.clone()) {
+        return Err(api_error(StatusCode::PAYMENT_REQUIRED, "upgrade_requires_payment", "Nâng cấp gói cần được admin xác nhận thanh toán".to_string()));
+    }
+    if payload.duration_days.unwrap_or(0) > 0 {
+        return Err(api_error(StatusCode::PAYMENT_REQUIRED, "extension_requires_payment", "Gia hạn gói cần được admin xác nhận thanh toán".to_string()));
     }
+
+    if !current.is_active() {
+        return Err(api_error(StatusCode::PAYMENT_REQUIRED, "subscription_expired", "Gói đăng ký đã hết hạn".to_string()));
+    }
+
+    // Gói còn hạn: đổi cấp độ, giữ nguyên ngày hết hạn
+    state.snipebot
+        .update_user_subscription(&mut user_manager, &claims.sub, level, 0).await
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "subscription_update_failed", e.to_string()))?;
+
+    let subscription = user_manager.get_user(&claims.sub)
+        .and_then(|user| user.subscription.clone())
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "subscription_not_found", "Người dùng chưa có gói đăng ký".to_string()))?;
+    Ok(Json(ApiResponse::success(SubscriptionData {
+        level: format!("{:?}", subscription.level),
+        start_date: subscription.start_date,
+        expiry_date: subscription.expiry_date,
+        is_active: subscription.is_active(),
+    })))
 }
 
-// Struct cho UpdateSubscriptionRequest
-#[derive(Deserialize)]
-struct UpdateSubscriptionRequest {
-    level: String,
-    duration_days: Option<u32>,
-    payment_id: Option<String>,
+// Bắt đầu auto trade với gói đăng ký của người dùng đang đăng nhập
+async fn start_auto_trade(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<ApiResponse<String>>, ApiError> {
+    let user_manager = state.user_manager.lock().await;
+    state.snipebot.start_auto_trade(&user_manager, &claims.sub).await
+        .map(|_| Json(ApiResponse::success("Đã bắt đầu auto trade".to_string())))
+        .map_err(|e| api_error(StatusCode::FORBIDDEN, "auto_trade_failed", e.to_string()))
+}
+
+// Đăng xuất một người dùng khỏi mọi phiên (chỉ admin)
+async fn admin_logout_user(
+    State(state): State<Arc<AppState>>,
+    Path(username): Path<String>,
+) -> Result<Json<ApiResponse<String>>, ApiError> {
+    let user_id = state.user_manager.lock().await
+        .get_user_by_username(&username)
+        .map(|user| user.id.clone())
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "user_not_found", "Không tìm thấy người dùng".to_string()))?;
+
+    TOKEN_BLACKLIST.write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .revoke_user(&user_id, crate::utils::safe_now());
+
+    info!("Admin đã đăng xuất người dùng {}", username);
+    Ok(Json(ApiResponse::success(format!("Đã đăng xuất người dùng {}", username))))
+}
+
+// Middleware xác thực JWT: kiểm tra chữ ký, blacklist và rate limit theo người dùng
+async fn auth_middleware<B>(
+    mut request: Request<B>,
+    next: Next<B>,
+) -> Result<Response, ApiError> {
+    let token = request.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| api_error(StatusCode::UNAUTHORIZED, "missing_token", "Thiếu token xác thực".to_string()))?;
+
+    let claims = decode_jwt_token(token)
+        .map_err(|e| api_error(StatusCode::UNAUTHORIZED, "invalid_token", format!("Token không hợp lệ: {}", e)))?;
+
+    let blacklisted = TOKEN_BLACKLIST.read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .is_revoked(&claims);
+    if blacklisted {
+        return Err(api_error(StatusCode::UNAUTHORIZED, "revoked_token", "Token đã bị vô hiệu hóa".to_string()));
+    }
+
+    if !check_rate_limit(&claims.sub, RATE_LIMIT_PER_WINDOW, RATE_LIMIT_WINDOW_SECS).await {
+        return Err(api_error(StatusCode::TOO_MANY_REQUESTS, "rate_limited", "Quá nhiều yêu cầu trong thời gian ngắn. Vui lòng thử lại sau.".to_string()));
+    }
+
+    request.extensions_mut().insert(claims);
+    Ok(next.run(request).await)
+}
+
+// Middleware kiểm tra quyền admin (chạy sau auth_middleware)
+async fn admin_middleware<B>(
+    request: Request<B>,
+    next: Next<B>,
+) -> Result<Response, ApiError> {
+    let allowed = request.extensions()
+        .get::<Claims>()
+        .map(|claims| claims.role == "admin" || claims.role == "system")
+        .unwrap_or(false);
+    if !allowed {
+        return Err(api_error(StatusCode::FORBIDDEN, "forbidden", "Bạn không có quyền thực hiện thao tác này".to_string()));
+    }
+
+    Ok(next.run(request).await)
 }
 
 // Danh sách các routes định nghĩa
@@ -290,6 +408,10 @@ fn wallet_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/api/wallet/balance", get(get_wallet_balance))
         .route("/api/wallet/transactions", get(get_wallet_transactions))
+        .route("/api/wallets", get(list_wallets))
+        .route("/api/wallet/groups", get(list_wallet_groups))
+        .route("/api/portfolio", get(get_portfolio))
+        .route("/api/portfolio/history", get(get_portfolio_history))
 }
 
 fn trading_routes() -> Router<Arc<AppState>> {
@@ -303,7 +425,15 @@ fn trading_routes() -> Router<Arc<AppState>> {
 fn admin_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/api/admin/stats", get(get_admin_stats))
+        .route("/api/admin/subscription/:user_id", post(update_subscription_handler))
         .route("/api/admin/user/:username/logout", post(admin_logout_user))
+        .route("/api/admin/chains/reload", post(reload_chains))
+        .route("/api/admin/treasury", post(treasury_operation))
+        .route("/api/admin/wallet/groups", post(set_wallet_group))
+        .route("/api/admin/wallet/groups/:name", delete(remove_wallet_group))
+        .route("/api/admin/wallet/watch", post(import_watch_only_wallets))
+        .route("/api/admin/wallet/solana", post(import_solana_keypair))
+        .route_layer(middleware::from_fn(admin_middleware))
 }
 
 // Định nghĩa router chính
@@ -313,8 +443,8 @@ pub fn get_routes(app_state: Arc<AppState>) -> Router {
         .route("/api/health", get(health_check))
         .route("/api/auth/login", post(login))
         .route("/api/auth/register", post(register));
-    
-    // Routes cần xác thực 
+
+    // Routes cần xác thực
     let auth_routes = Router::new()
         .route("/api/auth/me", get(get_current_user))
         .route("/api/auth/logout", post(logout_user))
@@ -322,116 +452,41 @@ pub fn get_routes(app_state: Arc<AppState>) -> Router {
         .merge(trading_routes())
         .merge(admin_routes())
         .route("/api/subscription/status", get(get_subscription_status))
-        .route("/api/subscription/update", post(update_subscription_handler))
+        .route("/api/subscription/update", post(update_own_subscription))
+        .route("/api/subscription/auto-trade/start", post(start_auto_trade))
         .route("/api/bot/mode", get(get_bot_mode))
-        .route("/api/metrics/retry", get(get_retry_metrics));
-    
-    // Merge và áp dụng middleware
+        .route("/api/chains", get(list_chains))
+        .route("/api/gas/:chain_id", get(get_gas_info))
+        .route("/api/metrics/retry", get(get_retry_metrics))
+        .route("/api/metrics/nonce", get(get_nonce_metrics))
+        .route_layer(middleware::from_fn(auth_middleware));
+
     Router::new()
         .merge(public_routes)
-        .merge(auth_routes.layer(middleware::from_fn_with_state(app_state.clone(), auth_middleware)))
+        .merge(auth_routes)
         .with_state(app_state)
 }
 
-// Thêm phương thức để tạo router từ AppState
-pub async fn create_api_server(app_state: Arc<AppState>) -> Result<(), Box<dyn std::error::Error>> {
-    let app = get_routes(app_state);
-    
-    // Thêm CORS
+// Khởi động API server trên api_host:api_port của cấu hình
+pub async fn create_api_server(app_state: Arc<AppState>) -> Result<()> {
+    let addr: SocketAddr = format!("{}:{}", app_state.config.api_host, app_state.config.api_port)
+        .parse()
+        .map_err(|e| anyhow!("Địa chỉ API không hợp lệ: {}", e))?;
+
     let cors = CorsLayer::new()
         .allow_headers(Any)
         .allow_methods(Any)
         .allow_origin(Any);
-        
-    let app = app.layer(cors);
-    
-    // Lấy port từ config
-    let port = app_state.config.api_port;
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    
+
+    let app = get_routes(app_state)
+        .layer(cors)
+        .layer(tower_http::trace::TraceLayer::new_for_http());
+
     info!("API server starting on {}", addr);
-    
-    // Cấu hình JWT middleware và rate limiter
-    let jwt_secret = app_state.config.auth.jwt_secret.clone();
-    let jwt_middleware = JWTAuthMiddleware::new(
-        jwt_secret,
-        |jti| {
-            let blacklist = diamond_common::middleware::auth::TOKEN_BLACKLIST.read().unwrap();
-            blacklist.is_blacklisted(jti)
-        },
-        |key, limit| async {
-            diamond_common::middleware::rate_limit::check_rate_limit(key, limit, 60).await
-        }.into(),
-        |err| match err {
-            JWTAuthError::InvalidToken(msg) => (
-                StatusCode::UNAUTHORIZED,
-                Json(ApiErrorResponse {
-                    status: "error".to_string(),
-                    message: format!("Token không hợp lệ: {}", msg),
-                })
-            ),
-            JWTAuthError::BlacklistedToken => (
-                StatusCode::UNAUTHORIZED,
-                Json(ApiErrorResponse {
-                    status: "error".to_string(),
-                    message: "Token đã bị vô hiệu hóa".to_string(),
-                })
-            ),
-            JWTAuthError::RateLimitExceeded => (
-                StatusCode::TOO_MANY_REQUESTS,
-                Json(ApiErrorResponse {
-                    status: "error".to_string(),
-                    message: "Quá nhiều yêu cầu trong thời gian ngắn. Vui lòng thử lại sau.".to_string(),
-                })
-            ),
-        },
-        app_state.clone(),
-    );
-    
-    // Cập nhật router với middleware mới
-    let app = Router::new()
-        .merge(Router::new()
-            .route("/api/health", get(health_check))
-            .route("/api/auth/login", post(login))
-            .route("/api/auth/register", post(register)))
-        .merge(
-            auth_routes
-                .merge(admin_routes())
-                .merge(wallet_routes())
-                // Thêm các middleware xác thực
-                .layer(from_fn_with_state(app_state.clone(), jwt_middleware))
-        )
-        // Middleware chung cho tất cả routes
-        .layer(
-            tower_http::trace::TraceLayer::new_for_http()
-                .make_span_with(tower_http::trace::DefaultMakeSpan::new().level(tracing::Level::INFO))
-                .on_response(tower_http::trace::DefaultOnResponse::new().level(tracing::Level::INFO))
-        )
-        .with_state(app_state)
-        .route_layer(middleware::from_fn_with_state(
-            app_state.clone(),
-            |state, req, next| async move {
-                ip_rate_limit_middleware(
-                    req,
-                    next,
-                    100, // limit
-                    60,  // window_size in seconds
-                    || (
-                        StatusCode::TOO_MANY_REQUESTS,
-                        Json(ApiErrorResponse {
-                            status: "error".to_string(),
-                            message: "Quá nhiều yêu cầu. Vui lòng thử lại sau.".to_string(),
-                        })
-                    ),
-                ).await
-            },
-        ));
-    
-    // Khởi động server
     axum::Server::bind(&addr)
         .serve(app.into_make_service())
         .await?;
-    
+
     Ok(())
 }
 
@@ -449,852 +504,595 @@ pub struct GasInfo {
     pub optimal_priority_fee_gwei: Option<f64>,
     pub optimal_max_fee_gwei: Option<f64>,
     pub trend: Option<String>,
+    /// Mô hình phí dữ liệu L1 (none, op-stack, arbitrum)
+    pub l1_fee_model: String,
+    /// Phí dữ liệu L1 của một giao dịch chuyển native token tham chiếu (ETH)
+    pub reference_l1_fee_eth: Option<f64>,
+    /// Tổng chi phí (thực thi + L1) của giao dịch tham chiếu (ETH)
+    pub reference_total_cost_eth: Option<f64>,
     pub timestamp: u64,
 }
 
+fn wei_to_gwei(value: U256) -> f64 {
+    ethers::utils::format_units(value, "gwei")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default()
+}
+
+fn wei_to_eth(value: U256) -> f64 {
+    ethers::utils::format_ether(value).parse().unwrap_or_default()
+}
+
 // Hàm xử lý API endpoint
 async fn get_gas_info(
     State(state): State<Arc<AppState>>,
     Path(chain_id): Path<u64>,
-) -> Result<Json<ApiResponse<GasInfo>>, (StatusCode, Json<ApiErrorResponse>)> {
-    // Lấy chain adapter
-    let chain_adapter = match state.snipebot.get_chain_adapter_by_id(chain_id) {
-        Some(adapter) => adapter,
-        None => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ApiErrorResponse {
-                    status: "error".to_string(),
-                    message: format!("Chain ID {} không được hỗ trợ", chain_id),
-                })
-            ));
-        }
-    };
-    
-    // Lấy thông tin gas
-    let current_time = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-        
-    let current_gas_price = match chain_adapter.get_provider().get_gas_price().await {
-        Ok(price) => price,
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiErrorResponse {
-                    status: "error".to_string(),
-                    message: format!("Không thể lấy gas price: {}", e),
-                })
-            ));
-        }
-    };
-    
-    // Chuyển wei thành gwei
-    let current_price_gwei = current_gas_price.as_u128() as f64 / 1_000_000_000.0;
-    
-    // Lấy thông tin optimizer
-    let (average_price_gwei, congestion_level, trend, priority_fee, max_fee) = 
-        if let Some(optimizer) = chain_adapter.get_gas_optimizer() {
-            let avg = optimizer.get_average_gas_price()
-                .map(|p| p.as_u128() as f64 / 1_000_000_000.0)
+) -> Result<Json<ApiResponse<GasInfo>>, ApiError> {
+    let chain = chain_registry::get_chain_config(chain_id)
+        .map_err(|_| api_error(StatusCode::BAD_REQUEST, "unknown_chain", format!("Chain ID {} không được hỗ trợ", chain_id)))?;
+    let provider: Provider<Http> = chain_registry::get_http_provider(chain_id)
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "unknown_chain", e.to_string()))?;
+
+    let current_gas_price = provider.get_gas_price().await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "gas_price_failed", format!("Không thể lấy gas price: {}", e)))?;
+    let current_price_gwei = wei_to_gwei(current_gas_price);
+
+    // Optimizer chỉ theo dõi chain mà bot đang giao dịch
+    let bot_adapter = state.snipebot.get_chain_adapter();
+    let eip1559_supported = bot_adapter.get_config().chain_id == chain_id && bot_adapter.get_config().eip1559_supported;
+    let optimizer = state.snipebot.get_gas_optimizer()
+        .filter(|_| bot_adapter.get_config().chain_id == chain_id);
+    let (average_price_gwei, congestion_level, trend, priority_fee, max_fee) = match optimizer {
+        Some(optimizer) => {
+            let average = optimizer.get_average_gas_price()
+                .map(wei_to_gwei)
                 .unwrap_or(current_price_gwei);
-                
-            let congestion = optimizer.get_network_congestion().to_string();
-            
-            let trend = optimizer.analyze_gas_trend()
-                .map(|(_, desc)| desc);
-                
-            // Cố gắng lấy fees EIP-1559 nếu được hỗ trợ
-            let (priority_fee, max_fee) = if chain_adapter.get_config().eip1559_supported {
-                match optimizer.get_optimal_eip1559_fees(chain_adapter).await {
-                    Ok((priority, max)) => (
-                        Some(priority.as_u128() as f64 / 1_000_000_000.0),
-                        Some(max.as_u128() as f64 / 1_000_000_000.0)
-                    ),
-                    Err(_) => (None, None)
+            let trend = optimizer.analyze_gas_trend().map(|(_, description)| description);
+            let (priority_fee, max_fee) = if eip1559_supported {
+                match optimizer.get_optimal_eip1559_fees(bot_adapter).await {
+                    Ok((priority, max)) => (Some(wei_to_gwei(priority)), Some(wei_to_gwei(max))),
+                    Err(_) => (None, None),
                 }
             } else {
                 (None, None)
             };
-            
-            (avg, congestion, trend, priority_fee, max_fee)
-        } else {
-            (current_price_gwei, "Unknown".to_string(), None, None, None)
+            (average, optimizer.get_network_congestion().to_string(), trend, priority_fee, max_fee)
+        }
+        None => (current_price_gwei, "Unknown".to_string(), None, None, None),
+    };
+
+    // Ước tính chi phí tham chiếu, gồm cả phí dữ liệu L1 trên rollup
+    let l1_fee_model = L1FeeModel::for_chain(chain_id);
+    let reference_tx = TransactionRequest::new()
+        .to(Address::zero())
+        .value(U256::zero())
+        .gas(U256::from(21000))
+        .gas_price(current_gas_price);
+    let (reference_l1_fee_eth, reference_total_cost_eth) =
+        match l2_fee::estimate_total_cost(&provider, chain_id, &reference_tx).await {
+            Ok(estimate) => (
+                Some(wei_to_eth(estimate.l1_data_fee)),
+                Some(wei_to_eth(estimate.total_fee)),
+            ),
+            Err(e) => {
+                warn!("Không thể ước tính chi phí tham chiếu cho chain {}: {}", chain_id, e);
+                (None, None)
+            }
         };
-    
-    // Tạo response
-    let gas_info = GasInfo {
+
+    Ok(Json(ApiResponse::success(GasInfo {
         chain_id,
-        chain_name: chain_adapter.get_config().name.clone(),
+        chain_name: chain.name,
         current_price_gwei,
         average_price_gwei,
         congestion_level,
-        eip1559_supported: chain_adapter.get_config().eip1559_supported,
+        eip1559_supported,
         priority_fee_gwei: priority_fee,
         max_fee_gwei: max_fee,
         optimal_priority_fee_gwei: priority_fee,
         optimal_max_fee_gwei: max_fee,
         trend,
-        timestamp: current_time,
-    };
-    
-    Ok(Json(ApiResponse {
-        status: "success".to_string(),
-        data: gas_info,
-    }))
+        l1_fee_model: l1_fee_model.to_string(),
+        reference_l1_fee_eth,
+        reference_total_cost_eth,
+        timestamp: unix_now(),
+    })))
 }
 
-// Thêm route vào router
-pub fn register_routes(router: &mut Router) {
-    // ... các routes hiện tại ...
-    
-    // Thêm route gas info
-    router.route("/api/gas/:chain_id", get(get_gas_info));
+// Danh sách chain đang được cấu hình, kèm trạng thái adapter
+async fn list_chains() -> Json<ApiResponse<Vec<ChainSummary>>> {
+    Json(ApiResponse::success(chain_config_loader::list_chains()))
 }
 
-// Middleware kiểm tra quyền admin
-async fn admin_auth<B>(
-    request: Request<B>,
-    next: Next<B>,
-) -> Result<Response, impl IntoResponse> {
-    role_middleware(
-        request, 
-        next, 
-        vec!["admin", "system"],
-        || (
-            StatusCode::FORBIDDEN,
-            Json(ApiErrorResponse {
-                status: "error".to_string(),
-                message: "Bạn không có quyền thực hiện thao tác này".to_string(),
-            })
-        )
-    ).await
+// Nạp lại thư mục cấu hình chain: thêm, cập nhật hoặc gỡ chain theo file TOML
+async fn reload_chains(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<ChainReloadReport>>, ApiError> {
+    let dir = std::path::PathBuf::from(&state.config.chain_config_dir);
+    match chain_config_loader::reload_chain_configs(&dir).await {
+        Ok(report) => Ok(Json(ApiResponse::success(report))),
+        Err(e) => {
+            error!("Không thể nạp lại cấu hình chain từ {}: {}", dir.display(), e);
+            Err(api_error(StatusCode::INTERNAL_SERVER_ERROR, "chain_reload_failed", format!("Không thể nạp lại cấu hình chain: {}", e)))
+        }
+    }
 }
 
-// Health check endpoint - triển khai
-async fn health_check() -> impl IntoResponse {
-    Json(ApiResponse::success(json!({
-        "status": "ok",
-        "version": env!("CARGO_PKG_VERSION"),
-        "server_time": SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs(),
-    })))
+// Yêu cầu chia/gom/nạp gas; mặc định chỉ xem trước kế hoạch (dry-run)
+#[derive(Debug, Deserialize)]
+pub struct TreasuryOperationRequest {
+    pub chain_id: u64,
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+    #[serde(flatten)]
+    pub request: TreasuryRequest,
 }
 
-// Triển khai get_admin_stats
-async fn get_admin_stats(
-    State(state): State<Arc<AppState>>,
-    Extension(claims): Extension<Claims>,
-) -> Result<Json<ApiResponse<AdminStats>>, (StatusCode, Json<ApiErrorResponse>)> {
-    // Kiểm tra quyền admin
-    if claims.role != "admin" {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(ApiErrorResponse {
-                status: "error".to_string(), 
-                message: "Bạn không có quyền truy cập".to_string(),
-            }),
-        ));
-    }
-    
-    // Lấy thông tin server
-    let system_stats = get_system_stats().await;
-    
-    // Lấy thông tin người dùng
-    let user_manager = state.user_manager.lock().await;
-    let users = user_manager.get_all_users();
-    let active_users = users.iter().filter(|u| u.active).count();
-    let premium_users = users.iter().filter(|u| u.role == UserRole::Admin).count();
-    
-    // Lấy thông tin giao dịch
-    let transactions = state.storage.get_all_transactions();
-    let transactions_count = transactions.len();
-    
-    // Lấy thông tin của 24h qua
-    let day_ago = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs() - 86400;
-    
-    let daily_transactions = transactions.iter()
-        .filter(|tx| tx.timestamp >= day_ago)
-        .count();
-    
-    // Tạo response
-    let admin_stats = AdminStats {
-        total_users: users.len(),
-        active_users,
-        premium_users,
-        total_transactions: transactions_count,
-        daily_transactions,
-        total_volume_usd: 0.0, // Tính sau
-        daily_volume_usd: 0.0, // Tính sau
-        system_stats,
-    };
-    
-    Ok(Json(ApiResponse::success(admin_stats)))
+fn default_dry_run() -> bool {
+    true
 }
 
-// Struct định nghĩa cho admin stats
+// Kế hoạch và kết quả thực hiện (None khi dry-run)
 #[derive(Debug, Serialize)]
-struct AdminStats {
-    pub total_users: usize,
-    pub active_users: usize,
-    pub premium_users: usize,
-    pub total_transactions: usize,
-    pub daily_transactions: usize,
-    pub total_volume_usd: f64,
-    pub daily_volume_usd: f64,
-    pub system_stats: SystemStats,
+pub struct TreasuryOperationResponse {
+    pub plan: TreasuryPlan,
+    pub report: Option<TreasuryReport>,
 }
 
-// Struct cho SystemStats
-#[derive(Debug, Serialize)]
-struct SystemStats {
-    pub cpu_usage: f64,
-    pub memory_usage: f64,
-    pub disk_usage: f64,
-    pub uptime_seconds: u64,
-    pub node_connections: std::collections::HashMap<String, bool>,
-}
-
-// Hàm lấy thông tin hệ thống
-async fn get_system_stats() -> Result<Json<ApiResponse<SystemStatsV2>>, ApiErrorResponse> {
-    let stats = SystemStatsV2 {
-        uptime: get_uptime(),
-        cpu_usage: get_cpu_usage(),
-        memory_usage: get_memory_usage(),
-        disk_usage: get_disk_usage(),
-        network_stats: get_network_stats(),
-        version: env!("CARGO_PKG_VERSION").to_string(),
-    };
+// Chia quỹ, gom số dư hoặc nạp gas cho nhiều ví
+async fn treasury_operation(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<TreasuryOperationRequest>,
+) -> Result<Json<ApiResponse<TreasuryOperationResponse>>, ApiError> {
+    let treasury = Treasury::for_chain(payload.chain_id, state.wallets.clone())
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "treasury_unavailable", format!("Không thể dùng treasury trên chain {}: {}", payload.chain_id, e)))?;
+    let plan = treasury.plan(&payload.request).await
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "treasury_plan_failed", format!("Không thể lập kế hoạch: {}", e)))?;
 
-    Ok(Json(ApiResponse {
-        status: "success".to_string(),
-        message: "System stats retrieved successfully".to_string(),
-        data: Some(stats),
-        timestamp: utils::safe_now(),
-    }))
+    if payload.dry_run {
+        return Ok(Json(ApiResponse::success(TreasuryOperationResponse { plan, report: None })));
+    }
+
+    match treasury.execute(&plan).await {
+        Ok(report) => Ok(Json(ApiResponse::success(TreasuryOperationResponse { plan, report: Some(report) }))),
+        Err(e) => {
+            error!("Treasury {:?} trên chain {} thất bại: {}", plan.operation, payload.chain_id, e);
+            Err(api_error(StatusCode::INTERNAL_SERVER_ERROR, "treasury_execute_failed", format!("Không thể thực hiện kế hoạch: {}", e)))
+        }
+    }
 }
 
-async fn get_wallet_balance(claims: Claims) -> Result<Json<ApiResponse<WalletBalanceV2>>, ApiErrorResponse> {
-    let balance_response = WalletBalanceV2 {
-        address: claims.wallet_ids.first().cloned().unwrap_or_default(),
-        balance: get_balance().await?,
-        native_symbol: "ETH".to_string(),
+// Chain dùng để đọc số dư ví (bỏ trống thì không đọc số dư)
+#[derive(Debug, Deserialize)]
+pub struct WalletBalanceQuery {
+    pub chain_id: Option<u64>,
+}
+
+// Danh sách ví kèm nhóm và số dư
+async fn list_wallets(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<WalletBalanceQuery>,
+) -> Result<Json<ApiResponse<Vec<SafeWalletView>>>, ApiError> {
+    let wallets = match query.chain_id {
+        Some(chain_id) => {
+            let provider = chain_registry::get_http_provider(chain_id)
+                .map_err(|e| api_error(StatusCode::BAD_REQUEST, "unknown_chain", e.to_string()))?;
+            state.wallets.list_wallets_with_balances(Arc::new(provider)).await
+        }
+        None => state.wallets.list_wallets(),
     };
 
-    Ok(Json(ApiResponse {
-        status: "success".to_string(),
-        message: "Wallet balance retrieved successfully".to_string(),
-        data: Some(balance_response),
-        timestamp: utils::safe_now(),
-    }))
+    wallets
+        .map(|wallets| Json(ApiResponse::success(wallets)))
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "wallet_list_failed", format!("Không thể lấy danh sách ví: {}", e)))
 }
 
-async fn logout_user(
-    Path(token): Path<String>,
-    State(state): State<AppState>,
-) -> impl IntoResponse {
-    // Ghi log cho việc logout
-    info!("Logout request with token: {}", token);
-    
-    // Gọi hàm logout từ middleware để invalid token
-    common::middleware::auth::logout(token, &state.token_blacklist).await;
-    
-    Json(ApiResponse {
-        status: "success".to_string(),
-        message: "Đã đăng xuất thành công".to_string(),
-        data: json!({}),
-    })
-}
-
-// Triển khai admin_logout_user
-async fn admin_logout_user(
+#[derive(Debug, Deserialize)]
+pub struct PortfolioQuery {
+    /// Cập nhật ngay thay vì trả kết quả của lần index gần nhất
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+// Danh mục của mọi ví: tổng USD, chi tiết theo chain và thay đổi 24h
+async fn get_portfolio(
     State(state): State<Arc<AppState>>,
-    Extension(claims): Extension<Claims>,
-    Path(username): Path<String>,
-) -> Result<Json<ApiResponse<String>>, (StatusCode, Json<ApiErrorResponse>)> {
-    // Kiểm tra quyền admin
-    if claims.role != "admin" {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(ApiErrorResponse {
-                status: "error".to_string(), 
-                message: "Bạn không có quyền truy cập".to_string(),
-            }),
-        ));
+    Query(query): Query<PortfolioQuery>,
+) -> Result<Json<ApiResponse<Portfolio>>, ApiError> {
+    if !query.refresh {
+        if let Some(portfolio) = state.portfolio.latest().await {
+            return Ok(Json(ApiResponse::success(portfolio)));
+        }
     }
-    
-    // TODO: Triển khai logic đăng xuất người dùng
-    // Ví dụ: invalidate tất cả token của người dùng này
-    
-    Ok(Json(ApiResponse::success(format!("Đã đăng xuất người dùng {}", username))))
+
+    state.portfolio.refresh().await
+        .map(|portfolio| Json(ApiResponse::success(portfolio)))
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "portfolio_failed", format!("Không thể cập nhật danh mục: {}", e)))
 }
 
-// Triển khai get_wallet_transactions
-async fn get_wallet_transactions(
+#[derive(Debug, Deserialize)]
+pub struct PortfolioHistoryQuery {
+    /// Số giờ lịch sử (mặc định 24)
+    pub hours: Option<u64>,
+}
+
+// Lịch sử giá trị danh mục theo snapshot
+async fn get_portfolio_history(
     State(state): State<Arc<AppState>>,
-    Extension(claims): Extension<Claims>,
-    Query(params): Query<std::collections::HashMap<String, String>>,
-) -> Result<Json<ApiResponse<Vec<TransactionInfo>>>, (StatusCode, Json<ApiErrorResponse>)> {
-    // Lấy tham số phân trang
-    let page = params.get("page").and_then(|p| p.parse::<usize>().ok()).unwrap_or(1);
-    let limit = params.get("limit").and_then(|l| l.parse::<usize>().ok()).unwrap_or(10);
-    
-    // Lấy địa chỉ ví từ thông tin người dùng
-    let wallet_address = claims.wallets.first().cloned().unwrap_or_default();
-    
-    // Lấy giao dịch từ storage
-    let all_transactions = state.storage.get_all_transactions();
-    
-    // Lọc giao dịch của ví hiện tại
-    let mut transactions: Vec<TransactionInfo> = all_transactions.iter()
-        .filter(|tx| tx.from_address == wallet_address || tx.to_address == wallet_address)
-        .map(|tx| TransactionInfo {
-            hash: tx.transaction_hash.clone(),
-            block_number: tx.block_number,
-            timestamp: tx.timestamp,
-            from_address: tx.from_address.clone(),
-            to_address: tx.to_address.clone(),
-            value: tx.value.clone(),
-            gas_used: tx.gas_used,
-            gas_price: tx.gas_price.clone(),
-            status: tx.success.then(|| "Success".to_string()).unwrap_or("Failed".to_string()),
-        })
-        .collect();
-    
-    // Sắp xếp theo thời gian giảm dần
-    transactions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-    
-    // Phân trang
-    let start = (page - 1) * limit;
-    let end = start + limit;
-    let paged_transactions = transactions.into_iter()
-        .skip(start)
-        .take(limit)
-        .collect();
-    
-    Ok(Json(ApiResponse::success(paged_transactions)))
+    Query(query): Query<PortfolioHistoryQuery>,
+) -> Json<ApiResponse<Vec<PortfolioSnapshot>>> {
+    let since = unix_now().saturating_sub(query.hours.unwrap_or(24).saturating_mul(3600));
+
+    Json(ApiResponse::success(state.portfolio.history(since).await))
 }
 
-// Struct cho TransactionInfo
+// Thành viên nhóm ví kèm trạng thái khóa và số dư
 #[derive(Debug, Serialize)]
-struct TransactionInfo {
-    pub hash: String,
-    pub block_number: u64,
-    pub timestamp: u64,
-    pub from_address: String,
-    pub to_address: String,
-    pub value: String,
-    pub gas_used: u64,
-    pub gas_price: String,
-    pub status: String,
+pub struct WalletGroupMemberView {
+    pub address: Address,
+    pub locked: bool,
+    pub balance: Option<String>,
 }
 
-// Triển khai get_token_info
-async fn get_token_info(
+#[derive(Debug, Serialize)]
+pub struct WalletGroupView {
+    pub name: String,
+    pub strategy: SelectionStrategy,
+    pub members: Vec<WalletGroupMemberView>,
+}
+
+// Danh sách nhóm ví; có chain_id thì đọc số dư từng thành viên
+async fn list_wallet_groups(
     State(state): State<Arc<AppState>>,
-    Path(token_address): Path<String>,
-) -> Result<Json<ApiResponse<TokenDetails>>, (StatusCode, Json<ApiErrorResponse>)> {
-    // Lấy token info
-    match state.snipebot.get_token_info(&token_address).await {
-        Ok(token_info) => {
-            // Lấy thông tin bổ sung
-            let token_status = match state.snipebot.get_token_status(&token_address).await {
-                Ok(status) => Some(status),
-                Err(_) => None,
-            };
-            
-            // Tạo response
-            let token_details = TokenDetails {
-                address: token_info.address,
-                name: token_info.name.unwrap_or_else(|| "Unknown".to_string()),
-                symbol: token_info.symbol,
-                decimals: token_info.decimals,
-                total_supply: "Unknown".to_string(), // TODO: Lấy total supply
-                liquidity: token_status.as_ref().and_then(|s| s.liquidity),
-                price_usd: token_status.as_ref().and_then(|s| s.price_usd),
-                market_cap: token_status.as_ref().and_then(|s| s.market_cap),
-                holders: token_status.as_ref().and_then(|s| s.holder_count),
-                creation_time: token_status.as_ref().and_then(|s| s.creation_block.map(|b| b as i64)),
-                pair: token_info.pair,
-                router: Some(token_info.router),
+    Query(query): Query<WalletBalanceQuery>,
+) -> Result<Json<ApiResponse<Vec<WalletGroupView>>>, ApiError> {
+    let provider = query.chain_id
+        .map(chain_registry::get_http_provider)
+        .transpose()
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "unknown_chain", e.to_string()))?;
+
+    let mut views = Vec::new();
+    for group in state.wallets.wallet_groups() {
+        let mut members = Vec::with_capacity(group.members.len());
+        for address in group.members {
+            let balance = match &provider {
+                Some(provider) => provider.get_balance(address, None).await.ok().map(|balance| balance.to_string()),
+                None => None,
             };
-            
-            Ok(Json(ApiSuccessResponse {
-                status: "success".to_string(),
-                data: serde_json::to_value(token_details).unwrap(),
-            }))
-        },
-        Err(e) => {
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiErrorResponse {
-                    status: "error".to_string(),
-                    message: format!("Lỗi khi lấy thông tin token: {}", e),
-                })
-            ))
+            members.push(WalletGroupMemberView { address, locked: state.wallets.is_wallet_locked(address), balance });
         }
+        views.push(WalletGroupView { name: group.name, strategy: group.strategy, members });
     }
-}
 
-// Struct cho TokenDetails
-#[derive(Debug, Serialize)]
-struct TokenDetails {
-    pub address: String,
-    pub name: String,
-    pub symbol: String,
-    pub decimals: u8,
-    pub total_supply: String,
-    pub liquidity: Option<f64>,
-    pub price_usd: Option<f64>,
-    pub market_cap: Option<f64>,
-    pub holders: Option<u64>,
-    pub creation_time: Option<i64>,
-    pub pair: Option<String>,
-    pub router: Option<String>,
+    Ok(Json(ApiResponse::success(views)))
 }
 
-// Triển khai get_bot_mode
-async fn get_bot_mode(
+// Tạo hoặc cập nhật nhóm ví (thành viên, chiến lược chọn ví)
+async fn set_wallet_group(
     State(state): State<Arc<AppState>>,
-) -> Result<Json<ApiResponse<BotModeInfo>>, (StatusCode, Json<ApiErrorResponse>)> {
-    let mode = state.snipebot.get_mode();
-    
-    let bot_mode_info = BotModeInfo {
-        mode: format!("{:?}", mode),
-        auto_enabled: matches!(mode, BotMode::Auto),
-        last_updated: SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs(),
-    };
-    
-    Ok(Json(ApiSuccessResponse {
-        status: "success".to_string(),
-        data: serde_json::to_value(bot_mode_info).unwrap(),
-    }))
-}
+    Json(group): Json<WalletGroup>,
+) -> Result<Json<ApiResponse<WalletGroup>>, ApiError> {
+    if group.name.trim().is_empty() {
+        return Err(api_error(StatusCode::BAD_REQUEST, "invalid_group", "Tên nhóm ví không được để trống".to_string()));
+    }
 
-// Struct cho BotModeInfo
-#[derive(Debug, Serialize)]
-struct BotModeInfo {
-    pub mode: String,
-    pub auto_enabled: bool,
-    pub last_updated: u64,
+    state.wallets.set_wallet_group(group.clone())
+        .map(|_| Json(ApiResponse::success(group)))
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "group_save_failed", format!("Không thể lưu nhóm ví: {}", e)))
 }
 
-// Triển khai approve_token
-async fn approve_token(
+// Xóa nhóm ví (không xóa ví)
+async fn remove_wallet_group(
     State(state): State<Arc<AppState>>,
-    Extension(claims): Extension<Claims>,
-    Json(payload): Json<ApproveTokenRequestV2>,
-) -> Result<Json<ApiResponse<String>>, (StatusCode, Json<ApiErrorResponse>)> {
-    // Lấy địa chỉ token
-    let token_address = payload.token_address;
-    
-    // Lấy địa chỉ spender (router)
-    let spender_address = payload.spender_address.clone();
-    
-    // Parse số lượng token
-    let amount = match U256::from_dec_str(&payload.amount) {
-        Ok(amt) => amt,
-        Err(e) => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ApiErrorResponse {
-                    status: "error".to_string(),
-                    message: format!("Số lượng token không hợp lệ: {}", e),
-                })
-            ));
-        }
-    };
-    
-    // Thực hiện approve
-    match state.snipebot.approve_token(&token_address, &spender_address, amount).await {
-        Ok(tx_hash) => {
-            Ok(Json(ApiResponse::success(tx_hash)))
-        },
-        Err(e) => {
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiErrorResponse {
-                    status: "error".to_string(),
-                    message: format!("Lỗi khi approve token: {}", e),
-                })
-            ))
-        }
+    Path(name): Path<String>,
+) -> Result<Json<ApiResponse<bool>>, ApiError> {
+    match state.wallets.remove_wallet_group(&name) {
+        Ok(true) => Ok(Json(ApiResponse::success(true))),
+        Ok(false) => Err(api_error(StatusCode::NOT_FOUND, "group_not_found", format!("Không tìm thấy nhóm ví {}", name))),
+        Err(e) => Err(api_error(StatusCode::INTERNAL_SERVER_ERROR, "group_save_failed", format!("Không thể xóa nhóm ví: {}", e))),
     }
 }
 
-// Struct cho ApproveTokenRequest
+// Nguồn ví chỉ theo dõi: một địa chỉ hoặc dải địa chỉ của xpub
 #[derive(Debug, Deserialize)]
-pub struct ApproveTokenRequestV2 {
-    pub token_address: String,
-    pub spender_address: String,
-    pub amount: String,
-    pub wallet_address: Option<String>,
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum WatchOnlyImportRequest {
+    Address {
+        address: Address,
+        name: Option<String>,
+    },
+    Xpub {
+        xpub: String,
+        #[serde(default)]
+        start: u32,
+        #[serde(default = "default_xpub_count")]
+        count: u32,
+        name: Option<String>,
+    },
 }
 
-// Triển khai trade_token
-async fn trade_token(
+fn default_xpub_count() -> u32 {
+    diamond_wallet::DEFAULT_GAP_LIMIT
+}
+
+// Nhập ví chỉ theo dõi để theo dõi số dư/PnL mà không cần khóa
+async fn import_watch_only_wallets(
     State(state): State<Arc<AppState>>,
-    Extension(claims): Extension<Claims>,
-    Json(payload): Json<TradeTokenRequestV2>,
-) -> Result<Json<ApiResponse<String>>, (StatusCode, Json<ApiErrorResponse>)> {
-    // Parse thông tin giao dịch
-    let token_address = payload.token_address;
-    let amount = match U256::from_dec_str(&payload.amount) {
-        Ok(amt) => amt,
-        Err(e) => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ApiErrorResponse {
-                    status: "error".to_string(),
-                    message: format!("Số lượng token không hợp lệ: {}", e),
-                })
-            ));
+    Json(request): Json<WatchOnlyImportRequest>,
+) -> Result<Json<ApiResponse<Vec<SafeWalletView>>>, ApiError> {
+    let imported = match request {
+        WatchOnlyImportRequest::Address { address, name } => state.wallets.import_watch_only(address, name).map(|view| vec![view]),
+        WatchOnlyImportRequest::Xpub { xpub, start, count, name } => {
+            state.wallets.import_xpub(&xpub, start..start.saturating_add(count), name)
         }
     };
-    
-    let slippage = payload.slippage.unwrap_or(1.0); // 1% mặc định
-    
-    // Xác định loại giao dịch
-    match payload.action.as_str() {
-        "buy" => {
-            // Mua token
-            match state.snipebot.swap_eth_for_tokens(amount, &token_address, slippage).await {
-                Ok(tx_hash) => {
-                    Ok(Json(ApiResponse::success(tx_hash)))
-                },
-                Err(e) => {
-                    Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ApiErrorResponse {
-                            status: "error".to_string(),
-                            message: format!("Lỗi khi mua token: {}", e),
-                        })
-                    ))
-                }
-            }
-        },
-        "sell" => {
-            // Bán token
-            match state.snipebot.swap_tokens_for_eth(&token_address, amount, slippage).await {
-                Ok(tx_hash) => {
-                    Ok(Json(ApiResponse::success(tx_hash)))
-                },
-                Err(e) => {
-                    Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ApiErrorResponse {
-                            status: "error".to_string(),
-                            message: format!("Lỗi khi bán token: {}", e),
-                        })
-                    ))
-                }
-            }
-        },
-        _ => {
-            Err((
-                StatusCode::BAD_REQUEST,
-                Json(ApiErrorResponse {
-                    status: "error".to_string(),
-                    message: "Loại giao dịch không hợp lệ. Chỉ hỗ trợ 'buy' hoặc 'sell'".to_string(),
-                })
-            ))
-        }
-    }
+
+    imported
+        .map(|views| Json(ApiResponse::success(views)))
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "watch_only_import_failed", format!("Không thể nhập ví chỉ theo dõi: {}", e)))
 }
 
-// Struct cho TradeTokenRequest
-#[derive(Debug, Deserialize)]
-pub struct TradeTokenRequestV2 {
-    pub token_address: String,
-    pub amount: String,
-    pub action: String,
-    pub slippage: Option<f64>,
-    pub wallet_address: Option<String>,
+// Keypair Solana dạng base58 (định dạng export của Phantom/Solflare)
+#[derive(Deserialize)]
+pub struct SolanaKeypairImportRequest {
+    pub secret_key: String,
 }
 
-/// Struct cho params truy vấn giao dịch
-#[derive(Debug, Deserialize)]
-struct TransactionParams {
-    limit: Option<usize>,
-    offset: Option<usize>,
-    sort: Option<String>,
+// Nhập keypair Solana vào kho mã hóa của thư mục ví và dùng nó để ký cho các adapter Solana
+async fn import_solana_keypair(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SolanaKeypairImportRequest>,
+) -> Result<Json<ApiResponse<String>>, ApiError> {
+    let keypair = SolanaKeypair::from_base58(&request.secret_key)
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "invalid_keypair", e.to_string()))?;
+    let pubkey = state.wallets.import_solana_keypair(&keypair)
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "solana_import_failed", format!("Không thể lưu keypair Solana: {}", e)))?;
+    solana_adapter::set_solana_keypair(Arc::new(keypair))
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "solana_adapter_failed", format!("Không thể gắn keypair Solana: {}", e)))?;
+
+    Ok(Json(ApiResponse::success(pubkey)))
 }
 
-/// Struct cho tham số thông tin token
-#[derive(Debug, Deserialize)]
-struct TokenInfoParams {
-    token_address: Option<String>,
-    chain_id: Option<u64>,
+// Health check endpoint - triển khai
+async fn health_check() -> impl IntoResponse {
+    Json(ApiResponse::success(json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+        "server_time": unix_now(),
+    })))
 }
 
-/// Struct cho request phê duyệt token
-#[derive(Debug, Deserialize)]
-struct ApproveTokenRequest {
-    token_address: String,
-    spender: Option<String>,
-    amount: Option<String>,
+// Struct định nghĩa cho admin stats
+#[derive(Debug, Serialize)]
+struct AdminStats {
+    pub total_users: usize,
+    pub active_users: usize,
+    pub total_transactions: usize,
+    pub daily_transactions: usize,
 }
 
-/// Struct cho request giao dịch token
-#[derive(Debug, Deserialize)]
-struct TradeTokenRequest {
-    token_address: String,
-    amount_in: String,
-    action: String, // "buy" hoặc "sell"
-    slippage: Option<f64>,
-    deadline: Option<u64>,
+// Triển khai get_admin_stats
+async fn get_admin_stats(
+    State(state): State<Arc<AppState>>,
+) -> Json<ApiResponse<AdminStats>> {
+    let (total_users, active_users) = {
+        let user_manager = state.user_manager.lock().await;
+        let users = user_manager.get_all_users();
+        (users.len(), users.iter().filter(|user| user.active).count())
+    };
+
+    let transactions = state.storage.get_all_transactions();
+    let day_ago = unix_now().saturating_sub(86400);
+
+    Json(ApiResponse::success(AdminStats {
+        total_users,
+        active_users,
+        total_transactions: transactions.len(),
+        daily_transactions: transactions.iter().filter(|tx| tx.timestamp >= day_ago).count(),
+    }))
 }
 
-/// Struct cho request đăng xuất từ admin
+// Struct cho WalletBalance
+#[derive(Debug, Serialize)]
+pub struct WalletBalanceView {
+    pub address: String,
+    pub chain_id: u64,
+    pub native_balance: String,
+    pub native_symbol: String,
+}
+
+// Số dư native của ví bot đang dùng
+async fn get_wallet_balance(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<WalletBalanceView>>, ApiError> {
+    let balance = state.snipebot.get_native_balance().await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "balance_failed", format!("Không thể lấy số dư: {}", e)))?;
+    let chain = state.snipebot.get_chain_adapter().get_config();
+
+    Ok(Json(ApiResponse::success(WalletBalanceView {
+        address: state.snipebot.get_current_wallet_address(),
+        chain_id: chain.chain_id,
+        native_balance: ethers::utils::format_ether(balance),
+        native_symbol: chain.native_symbol.clone(),
+    })))
+}
+
+// Tham số phân trang lịch sử giao dịch
 #[derive(Debug, Deserialize)]
-struct AdminLogoutRequest {
-    username: String,
+pub struct TransactionPageQuery {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
 }
 
-/// Struct cho response thống kê admin
-#[derive(Debug, Serialize)]
-struct AdminStatsResponse {
-    active_users: usize,
-    total_trades: usize,
-    successful_trades: usize,
-    failed_trades: usize,
-    system_stats: SystemStats,
+// Lịch sử giao dịch của bot, mới nhất trước
+async fn get_wallet_transactions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<TransactionPageQuery>,
+) -> Json<ApiResponse<Vec<Transaction>>> {
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(10);
+
+    let mut transactions = state.storage.get_all_transactions();
+    transactions.sort_by_key(|tx| std::cmp::Reverse(tx.timestamp));
+    let paged = transactions.into_iter()
+        .skip((page - 1).saturating_mul(limit))
+        .take(limit)
+        .collect();
+
+    Json(ApiResponse::success(paged))
 }
 
-/// Struct cho thông kê hệ thống
-#[derive(Debug, Serialize)]
-struct SystemStatsDuplicate {
-    cpu_usage: f64,
-    memory_usage: f64,
-    disk_usage: f64,
-    uptime: u64,
+// Triển khai get_token_info
+async fn get_token_info(
+    State(state): State<Arc<AppState>>,
+    Path(token_address): Path<String>,
+) -> Result<Json<ApiResponse<TokenStatus>>, ApiError> {
+    state.snipebot.get_token_status(&token_address).await
+        .map(|status| Json(ApiResponse::success(status)))
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "token_info_failed", format!("Lỗi khi lấy thông tin token: {}", e)))
 }
 
-/// Thông tin số dư ví
+// Trạng thái token kèm phân tích rủi ro
 #[derive(Debug, Serialize)]
-struct WalletBalanceDuplicate {
-    address: String,
-    native_balance: String,
-    native_token: String,
-    token_balances: Vec<TokenBalanceDuplicate>,
-    total_usd_value: String,
+struct TokenStatsView {
+    status: TokenStatus,
+    risk_score: f64,
+    is_verified: bool,
 }
 
-/// Thông tin số dư token
-#[derive(Debug, Serialize)]
-struct TokenBalanceDuplicate {
-    token_address: String,
-    symbol: String,
-    name: String,
-    balance: String,
-    decimals: u8,
-    usd_value: Option<String>,
+// Triển khai token_stats
+async fn token_stats(
+    State(state): State<Arc<AppState>>,
+    Path(token_address): Path<String>,
+) -> Result<Json<ApiResponse<TokenStatsView>>, ApiError> {
+    let (status, analysis) = state.snipebot.analyze_token(&token_address).await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "token_stats_failed", format!("Lỗi khi phân tích token: {}", e)))?;
+
+    Ok(Json(ApiResponse::success(TokenStatsView {
+        status,
+        risk_score: analysis.base.risk_score,
+        is_verified: analysis.is_verified,
+    })))
 }
 
-/// Response cho thông tin giao dịch
-#[derive(Debug, Serialize)]
-struct TransactionResponse {
-    tx_hash: String,
-    from: String,
-    to: String,
-    value: String,
-    gas_used: String,
-    status: String,
-    timestamp: u64,
-    method: String,
-}
-
-/// Response cho thông tin token
-#[derive(Debug, Serialize)]
-struct TokenInfoResponse {
-    address: String,
-    name: String,
-    symbol: String,
-    decimals: u8,
-    total_supply: String,
-    price_usd: Option<String>,
-    market_cap: Option<String>,
-    liquidity: Option<String>,
-    holders: Option<usize>,
-    transactions: Option<usize>,
-}
-
-// Struct cho SystemStats
+// Struct cho BotModeInfo
 #[derive(Debug, Serialize)]
-pub struct SystemStatsV2 {
-    pub uptime: u64,
-    pub cpu_usage: f64,
-    pub memory_usage: f64,
-    pub disk_usage: f64,
-    pub network_stats: NetworkStats,
-    pub version: String,
+struct BotModeInfo {
+    pub mode: String,
+    pub auto_enabled: bool,
 }
 
-// Struct cho WalletBalance
-#[derive(Debug, Serialize)]
-pub struct WalletBalanceV2 {
-    pub address: String,
-    pub chain_id: u64,
-    pub native_balance: String,
-    pub native_balance_usd: f64,
-    pub tokens: Vec<TokenBalanceV2>,
+// Triển khai get_bot_mode
+async fn get_bot_mode(
+    State(state): State<Arc<AppState>>,
+) -> Json<ApiResponse<BotModeInfo>> {
+    let mode = state.snipebot.get_mode();
+
+    Json(ApiResponse::success(BotModeInfo {
+        mode: format!("{:?}", mode),
+        auto_enabled: matches!(mode, BotMode::Auto),
+    }))
 }
 
-// Struct cho TokenBalance
-#[derive(Debug, Serialize)]
-pub struct TokenBalanceV2 {
+// Chỉ số retry toàn cục
+async fn get_retry_metrics() -> Json<ApiResponse<RetryMetricsView>> {
+    let metrics = &*RETRY_METRICS;
+    Json(ApiResponse::success(RetryMetricsView {
+        total_retries: metrics.total_retries.load(Ordering::SeqCst),
+        successful_retries: metrics.successful_retries.load(Ordering::SeqCst),
+        failed_retries: metrics.failed_retries.load(Ordering::SeqCst),
+        first_attempt_success: metrics.first_attempt_success.load(Ordering::SeqCst),
+        avg_completion_time_ms: metrics.avg_completion_time_ms.load(Ordering::SeqCst),
+        fallback_provider_activations: metrics.fallback_provider_activations.load(Ordering::SeqCst),
+        success_rate: metrics.get_success_rate(),
+    }))
+}
+
+// Thống kê nonce lease của tất cả chain
+async fn get_nonce_metrics() -> Result<Json<ApiResponse<serde_json::Value>>, ApiError> {
+    let stats = chain_adapters::get_nonce_stats().await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "nonce_stats_failed", e.to_string()))?;
+    Ok(Json(ApiResponse::success(stats)))
+}
+
+// Struct cho ApproveTokenRequest
+#[derive(Debug, Deserialize)]
+pub struct ApproveTokenRequest {
     pub token_address: String,
-    pub token_symbol: String,
-    pub token_decimals: u8,
-    pub balance: String,
-    pub balance_usd: Option<f64>,
+    pub spender_address: String,
+    pub amount: String,
 }
 
-async fn system_stats(
+// Triển khai approve_token
+async fn approve_token(
     State(state): State<Arc<AppState>>,
-) -> Result<Json<ApiResponse<SystemStatsV2>>, (StatusCode, Json<ApiErrorResponse>)> {
-    // Lấy thời gian khởi động
-    let uptime = match std::fs::read_to_string("/proc/uptime") {
-        Ok(uptime) => {
-            let uptime_parts: Vec<&str> = uptime.split_whitespace().collect();
-            if uptime_parts.len() > 0 {
-                let uptime_seconds = uptime_parts[0].parse::<f64>().unwrap_or(0.0);
-                uptime_seconds as u64
-            } else {
-                0
-            }
-        },
-        Err(_) => 0,
-    };
-    
-    // Lấy thông tin hệ thống
-    let cpu_usage = 0.5; // placeholder
-    let memory_usage = 0.35; // placeholder
-    let disk_usage = 0.25; // placeholder
-    let active_requests = 10; // placeholder
-    let success_rate = 0.95; // placeholder
-    
-    Ok(Json(ApiResponse {
-        success: true,
-        data: SystemStatsV2 {
-            uptime,
-            cpu_usage,
-            memory_usage,
-            disk_usage,
-            active_requests,
-            success_rate,
-            version: env!("CARGO_PKG_VERSION").to_string(),
-        },
-        message: Some("System stats retrieved successfully".to_string()),
-    }))
+    Json(payload): Json<ApproveTokenRequest>,
+) -> Result<Json<ApiResponse<Option<String>>>, ApiError> {
+    let amount = U256::from_dec_str(&payload.amount)
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "invalid_amount", format!("Số lượng token không hợp lệ: {}", e)))?;
+
+    state.snipebot.approve_token(&payload.token_address, &payload.spender_address, amount).await
+        .map(|receipt| Json(ApiResponse::success(receipt.map(|receipt| format!("{:?}", receipt.transaction_hash)))))
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "approve_failed", format!("Lỗi khi approve token: {}", e)))
 }
 
-async fn wallet_balance(
+// Struct cho TradeTokenRequest
+#[derive(Debug, Deserialize)]
+pub struct TradeTokenRequest {
+    pub token_address: String,
+    /// "buy" hoặc "sell"
+    pub action: String,
+    /// Lệnh mua: số native token; lệnh bán: phần trăm số dư token
+    pub amount: String,
+    /// Phần trăm gas price so với giá hiện tại (0 = giá mạng)
+    #[serde(default)]
+    pub gas_price_percent: u64,
+    /// Chain giao dịch; chain non-EVM (như Solana) đi qua adapter giao dịch chung
+    #[serde(default)]
+    pub chain: Option<String>,
+    /// Pool/venue cho chain non-EVM (bắt buộc với Solana)
+    #[serde(default)]
+    pub venue: Option<String>,
+}
+
+// Triển khai trade_token
+async fn trade_token(
     State(state): State<Arc<AppState>>,
-    Extension(claims): Extension<Claims>,
-    Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<ApiResponse<WalletBalanceV2>>, (StatusCode, Json<ApiErrorResponse>)> {
-    // Lấy địa chỉ ví từ params hoặc từ claims
-    let wallet_addr = if let Some(addr) = params.get("address") {
-        addr.clone()
-    } else {
-        claims.wallet_address.clone()
+    Json(payload): Json<TradeTokenRequest>,
+) -> Result<Json<ApiResponse<SnipeResult>>, ApiError> {
+    // Chain non-EVM không có router/mempool EVM của bot: giao dịch qua adapter giao dịch chung
+    let non_evm_chain = match payload.chain.as_deref() {
+        Some(chain) => {
+            let config = chain_config_loader::find_chain_id(chain)
+                .and_then(|chain_id| chain_registry::get_chain_config(chain_id).ok())
+                .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "chain_not_found", format!("Không tìm thấy chain {}", chain)))?;
+            if config.is_evm() && config.chain_id != state.config.chain_id {
+                return Err(api_error(StatusCode::BAD_REQUEST, "unsupported_chain",
+                    format!("Bot chỉ giao dịch EVM trên {}", state.config.chain_name)));
+            }
+            (!config.is_evm()).then(|| chain.to_string())
+        }
+        None => None,
     };
-    
-    // Kiểm tra địa chỉ ví hợp lệ
-    if !wallet_addr.starts_with("0x") || wallet_addr.len() != 42 {
-        return Err((
-            StatusCode::BAD_REQUEST, 
-            Json(ApiErrorResponse { 
-                error: "Invalid wallet address".to_string(),
-                code: 400,
-            })
-        ));
-    }
-    
-    // Lấy số dư ETH
-    let eth_balance = state.snipebot.get_native_balance().await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiErrorResponse {
-                error: format!("Failed to get ETH balance: {}", e),
-                code: 500,
-            })
-        )
-    })?;
-    
-    // Format số dư ETH
-    let eth_balance_formatted = format!("{}", ethers::utils::format_ether(eth_balance));
-    
-    // Quy đổi sang USD
-    let eth_balance_usd = 1500.0 * ethers::utils::format_ether(eth_balance).parse::<f64>().unwrap_or(0.0);
-    
-    // Lấy danh sách token và số dư
-    let tokens = vec![
-        TokenBalanceV2 {
-            token_address: "0x...".to_string(),
-            token_symbol: "USDT".to_string(),
-            token_decimals: 6,
-            balance: "1000.0".to_string(),
-            balance_usd: Some(1000.0),
-        },
-        TokenBalanceV2 {
-            token_address: "0x...".to_string(),
-            token_symbol: "USDC".to_string(),
-            token_decimals: 6,
-            balance: "500.0".to_string(),
-            balance_usd: Some(500.0),
-        },
-    ];
-    
-    // Tạo response
-    let token_balances: Vec<TokenBalanceV2> = tokens.into_iter()
-        .collect();
-    
-    let balance_response = WalletBalanceV2 {
-        address: wallet_addr.clone(),
-        chain_id: state.config.chain_id,
-        native_balance: eth_balance_formatted,
-        native_balance_usd: eth_balance_usd,
-        tokens: token_balances,
+
+    let result = match (payload.action.as_str(), non_evm_chain) {
+        ("buy", Some(chain)) => state.snipebot.manual_buy_on_chain(&chain, &payload.token_address, &payload.amount, payload.venue.as_deref()).await,
+        ("buy", None) => state.snipebot.manual_buy(&payload.token_address, &payload.amount, payload.gas_price_percent).await,
+        ("sell", chain) => {
+            let percent = payload.amount.parse::<u8>()
+                .ok()
+                .filter(|percent| (1..=100).contains(percent))
+                .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "invalid_amount", "Lệnh bán cần phần trăm từ 1 đến 100".to_string()))?;
+            match chain {
+                Some(chain) => state.snipebot.manual_sell_on_chain(&chain, &payload.token_address, percent, payload.venue.as_deref()).await,
+                None => state.snipebot.manual_sell(&payload.token_address, percent, payload.gas_price_percent).await,
+            }
+        }
+        _ => return Err(api_error(StatusCode::BAD_REQUEST, "invalid_action", "Loại giao dịch không hợp lệ. Chỉ hỗ trợ 'buy' hoặc 'sell'".to_string())),
     };
-    
-    Ok(Json(ApiResponse {
-        success: true,
-        data: balance_response,
-        message: Some("Wallet balance retrieved successfully".to_string()),
-    }))
-}
 
-// Thêm hàm xử lý logout cho API
-async fn logout(
-    req: Request<Body>,
-) -> Result<Json<ApiResponse<String>>, (StatusCode, Json<ApiErrorResponse>)> {
-    let auth_header = req.headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .unwrap_or("");
-    
-    match crate::middleware::logout(auth_header).await {
-        Ok(_) => Ok(Json(ApiResponse {
-            status: "success".to_string(),
-            data: "Đăng xuất thành công".to_string(),
-        })),
-        Err((status, _)) => Err((
-            status,
-            Json(ApiErrorResponse {
-                status: "error".to_string(),
-                message: "Không thể đăng xuất".to_string(),
-            })
-        ))
-    }
-}
\ No newline at end of file
+    result
+        .map(|result| Json(ApiResponse::success(result)))
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "trade_failed", format!("Lỗi khi giao dịch token: {}", e)))
+}
@@ -0,0 +1,65 @@
+// Standard library imports
+use std::sync::Arc;
+
+// Third party imports
+use anyhow::{bail, Context, Result};
+
+// Internal imports
+use diamond_wallet::{StorageConfig, WalletManager, WalletManagerConfig};
+use snipebot::chain_adapters::chain_config_loader;
+use snipebot::chain_adapters::treasury::{Treasury, TreasuryRequest};
+
+const USAGE: &str = "Usage: treasury <chain_id> <request.json> [--execute]
+
+Request file uses the same JSON as POST /api/admin/treasury, e.g.
+  {\"operation\": \"sweep\", \"wallets\": [\"0x..\"], \"to\": \"0x..\", \"asset\": \"native\", \"dust_threshold\": \"1000000000000000\"}
+Without --execute the plan is only printed (dry-run).
+
+Environment: WALLET_FOLDER, WALLET_ENCRYPTION_SEED, CHAIN_CONFIG_DIR";
+
+/// CLI chia/gom/nạp gas cho nhiều ví; mặc định chỉ in kế hoạch
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let execute = args.iter().any(|arg| arg == "--execute");
+    let positional: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    let [chain_id, request_path] = positional.as_slice() else {
+        bail!("{}", USAGE);
+    };
+
+    let chain_id: u64 = chain_id.parse().with_context(|| format!("Invalid chain id {}", chain_id))?;
+    let request: TreasuryRequest = serde_json::from_str(
+        &std::fs::read_to_string(request_path).with_context(|| format!("Cannot read {}", request_path))?,
+    ).with_context(|| format!("Invalid treasury request in {}", request_path))?;
+
+    chain_config_loader::reload_chain_configs(&chain_config_loader::chain_config_dir()).await?;
+
+    let wallets = WalletManager::new(WalletManagerConfig {
+        default_chain_id: chain_id,
+        storage_config: StorageConfig {
+            wallet_dir: std::env::var("WALLET_FOLDER").unwrap_or_else(|_| "data".to_string()),
+            ..Default::default()
+        },
+        wallet_encryption_seed: std::env::var("WALLET_ENCRYPTION_SEED")
+            .context("WALLET_ENCRYPTION_SEED must be set to unlock the wallets")?,
+        default_policy: None,
+    })?;
+
+    let treasury = Treasury::for_chain(chain_id, Arc::new(wallets))?;
+    let plan = treasury.plan(&request).await?;
+    println!("{}", serde_json::to_string_pretty(&plan)?);
+
+    if !execute {
+        eprintln!("Dry-run: {} transfers planned, nothing sent. Re-run with --execute to send.", plan.transfers.len());
+        return Ok(());
+    }
+
+    let report = treasury.execute(&plan).await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if !report.failed.is_empty() {
+        bail!("{} of {} transfers failed", report.failed.len(), plan.transfers.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,96 @@
+// Third party imports
+use anyhow::{bail, Context, Result};
+
+// Internal imports
+use diamond_wallet::{
+    verify_backup, BackupKey, BackupOptions, BackupShare, ShareSplit, StorageConfig, WalletManager, WalletManagerConfig,
+};
+
+const USAGE: &str = "Usage:
+  wallet_backup export <backup.json> [--shares <k>/<n>]
+  wallet_backup verify <backup.json> [--shares-file <shares.txt>]
+  wallet_backup restore <backup.json> [--shares-file <shares.txt>] [--overwrite]
+
+export writes all wallets, groups and spending policies into one encrypted archive.
+With --shares the backup key is also split into n Shamir shares (any k restore it);
+shares are printed one per line and must be stored separately from the archive.
+restore verifies the whole archive before writing; existing entries are kept unless --overwrite.
+
+Environment: WALLET_FOLDER, WALLET_ENCRYPTION_SEED, BACKUP_PASSPHRASE (must differ from WALLET_ENCRYPTION_SEED)";
+
+/// CLI sao lưu và khôi phục toàn bộ thư mục ví
+fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [command, archive_path, options @ ..] = args.as_slice() else {
+        bail!("{}", USAGE);
+    };
+    let option = |name: &str| options.iter().position(|arg| arg == name).and_then(|i| options.get(i + 1));
+
+    match command.as_str() {
+        "export" => {
+            let split = option("--shares").map(|value| parse_split(value)).transpose()?;
+            let export = wallet_manager()?.export_backup(&backup_passphrase()?, &BackupOptions { split, ..Default::default() })?;
+            std::fs::write(archive_path, &export.archive).with_context(|| format!("Cannot write {}", archive_path))?;
+            eprintln!("Backup written to {}", archive_path);
+            for share in &export.shares {
+                println!("{}", share);
+            }
+        }
+        "verify" => {
+            let summary = verify_backup(&read_archive(archive_path)?, &backup_key(option("--shares-file"))?)?;
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+        "restore" => {
+            let overwrite = options.iter().any(|arg| arg == "--overwrite");
+            let key = backup_key(option("--shares-file"))?;
+            let report = wallet_manager()?.restore_backup(&read_archive(archive_path)?, &key, overwrite)?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        _ => bail!("{}", USAGE),
+    }
+    Ok(())
+}
+
+fn wallet_manager() -> Result<WalletManager> {
+    WalletManager::new(WalletManagerConfig {
+        storage_config: StorageConfig {
+            wallet_dir: std::env::var("WALLET_FOLDER").unwrap_or_else(|_| "data".to_string()),
+            ..Default::default()
+        },
+        wallet_encryption_seed: std::env::var("WALLET_ENCRYPTION_SEED")
+            .context("WALLET_ENCRYPTION_SEED must be set to unlock the wallets")?,
+        ..Default::default()
+    })
+}
+
+fn backup_passphrase() -> Result<String> {
+    std::env::var("BACKUP_PASSPHRASE").context("BACKUP_PASSPHRASE must be set")
+}
+
+/// Mở bằng mảnh khóa nếu có tệp mảnh, ngược lại bằng BACKUP_PASSPHRASE
+fn backup_key(shares_file: Option<&String>) -> Result<BackupKey> {
+    let Some(path) = shares_file else {
+        return Ok(BackupKey::Passphrase(backup_passphrase()?));
+    };
+    let shares = std::fs::read_to_string(path)
+        .with_context(|| format!("Cannot read {}", path))?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::parse::<BackupShare>)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(BackupKey::Shares(shares))
+}
+
+fn read_archive(path: &str) -> Result<String> {
+    std::fs::read_to_string(path).with_context(|| format!("Cannot read {}", path))
+}
+
+fn parse_split(value: &str) -> Result<ShareSplit> {
+    let (threshold, shares) = value.split_once('/').with_context(|| format!("Invalid --shares {}, expected k/n", value))?;
+    Ok(ShareSplit {
+        threshold: threshold.parse().with_context(|| format!("Invalid threshold {}", threshold))?,
+        shares: shares.parse().with_context(|| format!("Invalid share count {}", shares))?,
+    })
+}
@@ -4,8 +4,7 @@ use anyhow::{Result, anyhow};
 use crate::chain_adapters::trait_adapter::ChainAdapter;
 use crate::chain_adapters::chain_traits::*;
 use once_cell::sync::Lazy;
-use crate::chain_adapters::base::ChainConfig;
-use ethers::signers::LocalWallet;
+use diamond_wallet::ManagedSigner;
 
 /// Singleton registry quản lý tất cả các chain adapter thông qua dyn trait
 pub static ADAPTER_REGISTRY: Lazy<RwLock<AdapterRegistry>> = Lazy::new(|| {
@@ -29,6 +28,12 @@ pub struct AdapterRegistry {
     custom_adapters: HashMap<String, CustomChainAdapterEnum>,
 }
 
+impl Default for AdapterRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AdapterRegistry {
     /// Tạo registry mới
     pub fn new() -> Self {
@@ -165,7 +170,7 @@ pub fn get_chain_adapter(chain_name: &str) -> Result<Arc<dyn ChainAdapter>> {
 }
 
 /// Thêm wallet vào adapter
-pub async fn add_wallet_to_adapter(chain_name: &str, wallet: LocalWallet) -> Result<()> {
+pub async fn add_wallet_to_adapter(chain_name: &str, _wallet: ManagedSigner) -> Result<()> {
     // Clone adapter với ví mới thay vì sửa trực tiếp adapter hiện tại
     // 1. Lấy adapter
     let adapter_ref = {
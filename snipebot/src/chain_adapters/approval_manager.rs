@@ -0,0 +1,598 @@
+// External imports
+use ethers::{
+    abi::{self, Token},
+    providers::{Http, Provider},
+    types::{transaction::eip2718::TypedTransaction, Address, Bytes, Signature, TransactionRequest, H256, U256, U64},
+    utils::id,
+};
+
+// Standard library imports
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Third party imports
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+// Internal imports
+use crate::chain_adapters::interfaces::ChainAdapter;
+use diamond_wallet::{Erc2612Permit, WalletManager};
+
+/// Cách quyền chi tiêu được cấp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalKind {
+    /// Giao dịch approve do bot gửi
+    Transaction,
+    /// Chữ ký EIP-2612 permit do bot ký
+    Permit,
+    /// Approve có sẵn từ trước, chỉ được theo dõi
+    External,
+}
+
+/// Một quyền chi tiêu (owner, token, spender) trong danh mục
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRecord {
+    pub chain_id: u64,
+    pub owner: Address,
+    pub token: Address,
+    pub spender: Address,
+    /// Số lượng bot cấp ở lần gần nhất
+    pub granted: U256,
+    pub kind: ApprovalKind,
+    pub tx_hash: Option<H256>,
+    /// Vị thế đang dùng quyền này; đóng vị thế thì tự thu hồi
+    pub position_id: Option<String>,
+    pub granted_at: DateTime<Utc>,
+    /// Allowance đọc được ở lần quét gần nhất
+    pub current_allowance: Option<U256>,
+    pub last_scanned: Option<DateTime<Utc>>,
+}
+
+impl ApprovalRecord {
+    /// Allowance còn hiệu lực (theo lần quét gần nhất)
+    pub fn is_active(&self) -> bool {
+        self.current_allowance.is_some_and(|allowance| !allowance.is_zero())
+    }
+
+    /// Allowance không giới hạn (uint160 max trở lên)
+    pub fn is_unlimited(&self) -> bool {
+        self.current_allowance.is_some_and(|allowance| allowance >= U256::MAX >> 96)
+    }
+}
+
+/// Chữ ký permit kèm tham số để truyền cho hợp đồng
+#[derive(Debug, Clone)]
+pub struct PermitSignature {
+    pub permit: Erc2612Permit,
+    pub signature: Signature,
+}
+
+/// Kết quả thu hồi hàng loạt
+#[derive(Debug, Clone, Default)]
+pub struct RevokeReport {
+    /// (token, spender, tx hash)
+    pub revoked: Vec<(Address, Address, H256)>,
+    /// (token, spender, lỗi)
+    pub failed: Vec<(Address, Address, String)>,
+}
+
+/// Cấu hình ApprovalManager
+#[derive(Debug, Clone)]
+pub struct ApprovalManagerConfig {
+    /// File lưu danh mục approve; None thì chỉ giữ trong bộ nhớ
+    pub store_path: Option<PathBuf>,
+    /// Đặt allowance về 0 trước khi đổi sang giá trị khác (token kiểu USDT)
+    pub reset_before_change: bool,
+    /// Tự thu hồi khi vị thế đóng
+    pub auto_revoke_on_close: bool,
+    pub confirmations: usize,
+    pub receipt_timeout: Duration,
+    /// Thời hạn của chữ ký permit
+    pub permit_validity: Duration,
+}
+
+impl Default for ApprovalManagerConfig {
+    fn default() -> Self {
+        Self {
+            store_path: None,
+            reset_before_change: true,
+            auto_revoke_on_close: true,
+            confirmations: 1,
+            receipt_timeout: Duration::from_secs(120),
+            permit_validity: Duration::from_secs(20 * 60),
+        }
+    }
+}
+
+/// Khóa danh mục: (owner, token, spender)
+type ApprovalKey = (Address, Address, Address);
+
+/// Quản lý approve token của các ví trên một chain: cấp đúng số lượng, theo dõi,
+/// quét allowance định kỳ và thu hồi
+pub struct ApprovalManager<A: ChainAdapter + ?Sized> {
+    adapter: Arc<A>,
+    provider: Arc<Provider<Http>>,
+    wallets: Arc<WalletManager>,
+    config: ApprovalManagerConfig,
+    records: RwLock<HashMap<ApprovalKey, ApprovalRecord>>,
+}
+
+impl<A: ChainAdapter + ?Sized + 'static> ApprovalManager<A> {
+    /// Tạo ApprovalManager và nạp danh mục đã lưu
+    pub fn new(
+        adapter: Arc<A>,
+        provider: Arc<Provider<Http>>,
+        wallets: Arc<WalletManager>,
+        config: ApprovalManagerConfig,
+    ) -> Result<Self> {
+        let mut records = HashMap::new();
+        if let Some(path) = config.store_path.as_ref().filter(|path| path.exists()) {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Cannot read approval inventory {:?}", path))?;
+            let saved: Vec<ApprovalRecord> = serde_json::from_str(&content)
+                .with_context(|| format!("Invalid approval inventory {:?}", path))?;
+            let chain_id = adapter.get_chain_id();
+            for record in saved.into_iter().filter(|record| record.chain_id == chain_id) {
+                records.insert((record.owner, record.token, record.spender), record);
+            }
+        }
+
+        Ok(Self {
+            adapter,
+            provider,
+            wallets,
+            config,
+            records: RwLock::new(records),
+        })
+    }
+
+    /// Danh mục approve hiện tại
+    pub async fn records(&self) -> Vec<ApprovalRecord> {
+        self.records.read().await.values().cloned().collect()
+    }
+
+    /// Đảm bảo allowance đủ `amount`; chỉ approve đúng số lượng khi thiếu
+    pub async fn ensure_allowance(
+        &self,
+        owner: Address,
+        token: Address,
+        spender: Address,
+        amount: U256,
+        position_id: Option<&str>,
+    ) -> Result<Option<H256>> {
+        let current = self.adapter.get_token_allowance(token, owner, spender).await?;
+        if current >= amount {
+            self.link_position(owner, token, spender, current, position_id).await?;
+            return Ok(None);
+        }
+        self.approve_exact(owner, token, spender, amount, position_id).await
+    }
+
+    /// Đặt allowance đúng bằng `amount` và ghi vào danh mục
+    pub async fn approve_exact(
+        &self,
+        owner: Address,
+        token: Address,
+        spender: Address,
+        amount: U256,
+        position_id: Option<&str>,
+    ) -> Result<Option<H256>> {
+        let current = self.adapter.get_token_allowance(token, owner, spender).await?;
+        if current == amount {
+            self.link_position(owner, token, spender, current, position_id).await?;
+            return Ok(None);
+        }
+
+        if self.config.reset_before_change && !current.is_zero() && !amount.is_zero() {
+            self.send_approve(owner, token, spender, U256::zero()).await?;
+        }
+        let tx_hash = self.send_approve(owner, token, spender, amount).await?;
+        info!("Approved {} of {:?} to {:?} for {:?}", amount, token, spender, owner);
+
+        self.upsert(ApprovalRecord {
+            chain_id: self.adapter.get_chain_id(),
+            owner,
+            token,
+            spender,
+            granted: amount,
+            kind: ApprovalKind::Transaction,
+            tx_hash: Some(tx_hash),
+            position_id: position_id.map(str::to_string),
+            granted_at: Utc::now(),
+            current_allowance: Some(amount),
+            last_scanned: Some(Utc::now()),
+        }).await?;
+
+        Ok(Some(tx_hash))
+    }
+
+    /// Token có hỗ trợ EIP-2612 permit không (có DOMAIN_SEPARATOR và nonces)
+    pub async fn supports_permit(&self, token: Address, owner: Address) -> bool {
+        self.permit_domain(token, owner).await.is_ok()
+    }
+
+    /// Ký permit cho đúng `value`, không cần gửi giao dịch approve
+    pub async fn sign_permit(
+        &self,
+        owner: Address,
+        token: Address,
+        spender: Address,
+        value: U256,
+        position_id: Option<&str>,
+    ) -> Result<PermitSignature> {
+        let (domain_separator, nonce) = self.permit_domain(token, owner).await
+            .map_err(|e| anyhow!("Token {:?} does not support EIP-2612 permit: {}", token, e))?;
+        let deadline = U256::from((Utc::now().timestamp() as u64) + self.config.permit_validity.as_secs());
+
+        let permit = Erc2612Permit {
+            chain_id: self.adapter.get_chain_id(),
+            token,
+            owner,
+            spender,
+            value,
+            nonce,
+            deadline,
+            domain_separator,
+        };
+        let signature = self.wallets.sign_permit(&permit).await?;
+
+        self.upsert(ApprovalRecord {
+            chain_id: permit.chain_id,
+            owner,
+            token,
+            spender,
+            granted: value,
+            kind: ApprovalKind::Permit,
+            tx_hash: None,
+            position_id: position_id.map(str::to_string),
+            granted_at: Utc::now(),
+            current_allowance: None,
+            last_scanned: None,
+        }).await?;
+
+        Ok(PermitSignature { permit, signature })
+    }
+
+    /// Theo dõi một cặp token/spender có sẵn (approve ngoài bot) để quét và thu hồi
+    pub async fn track(&self, owner: Address, token: Address, spender: Address) -> Result<()> {
+        if self.records.read().await.contains_key(&(owner, token, spender)) {
+            return Ok(());
+        }
+        let current = self.adapter.get_token_allowance(token, owner, spender).await?;
+        self.upsert(ApprovalRecord {
+            chain_id: self.adapter.get_chain_id(),
+            owner,
+            token,
+            spender,
+            granted: current,
+            kind: ApprovalKind::External,
+            tx_hash: None,
+            position_id: None,
+            granted_at: Utc::now(),
+            current_allowance: Some(current),
+            last_scanned: Some(Utc::now()),
+        }).await
+    }
+
+    /// Thu hồi allowance (approve 0); trả về None nếu đã bằng 0
+    pub async fn revoke(&self, owner: Address, token: Address, spender: Address) -> Result<Option<H256>> {
+        let current = self.adapter.get_token_allowance(token, owner, spender).await?;
+        let tx_hash = if current.is_zero() {
+            None
+        } else {
+            let hash = self.send_approve(owner, token, spender, U256::zero()).await?;
+            info!("Revoked allowance of {:?} to {:?} for {:?}", token, spender, owner);
+            Some(hash)
+        };
+
+        let mut records = self.records.write().await;
+        if let Some(record) = records.get_mut(&(owner, token, spender)) {
+            record.current_allowance = Some(U256::zero());
+            record.last_scanned = Some(Utc::now());
+            record.position_id = None;
+        }
+        self.save(&records)?;
+
+        Ok(tx_hash)
+    }
+
+    /// Vị thế đã đóng: thu hồi các quyền gắn với vị thế
+    pub async fn on_position_closed(&self, position_id: &str) -> Result<Vec<H256>> {
+        if !self.config.auto_revoke_on_close {
+            return Ok(Vec::new());
+        }
+
+        let keys: Vec<ApprovalKey> = self.records.read().await.iter()
+            .filter(|(_, record)| record.position_id.as_deref() == Some(position_id))
+            .map(|(key, _)| *key)
+            .collect();
+
+        let mut hashes = Vec::new();
+        for (owner, token, spender) in keys {
+            if let Some(hash) = self.revoke(owner, token, spender).await? {
+                hashes.push(hash);
+            }
+        }
+        Ok(hashes)
+    }
+
+    /// Đọc lại allowance hiện tại của mọi cặp trong danh mục (lọc theo ví nếu có),
+    /// trả về các quyền còn hiệu lực
+    pub async fn scan(&self, owner: Option<Address>) -> Result<Vec<ApprovalRecord>> {
+        let keys: Vec<ApprovalKey> = self.records.read().await.keys()
+            .filter(|(record_owner, _, _)| owner.is_none() || owner == Some(*record_owner))
+            .copied()
+            .collect();
+
+        let mut allowances = Vec::with_capacity(keys.len());
+        for (owner, token, spender) in keys {
+            match self.adapter.get_token_allowance(token, owner, spender).await {
+                Ok(allowance) => allowances.push(((owner, token, spender), allowance)),
+                Err(e) => warn!("Cannot read allowance of {:?} to {:?} for {:?}: {}", token, spender, owner, e),
+            }
+        }
+
+        let mut records = self.records.write().await;
+        let now = Utc::now();
+        let mut active = Vec::new();
+        for (key, allowance) in allowances {
+            if let Some(record) = records.get_mut(&key) {
+                record.current_allowance = Some(allowance);
+                record.last_scanned = Some(now);
+                if record.is_active() {
+                    active.push(record.clone());
+                }
+            }
+        }
+        self.save(&records)?;
+
+        Ok(active)
+    }
+
+    /// Thu hồi mọi allowance còn hiệu lực (lọc theo ví nếu có)
+    pub async fn revoke_all(&self, owner: Option<Address>) -> Result<RevokeReport> {
+        let mut report = RevokeReport::default();
+        for record in self.scan(owner).await? {
+            match self.revoke(record.owner, record.token, record.spender).await {
+                Ok(Some(hash)) => report.revoked.push((record.token, record.spender, hash)),
+                Ok(None) => {}
+                Err(e) => report.failed.push((record.token, record.spender, e.to_string())),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Quét allowance định kỳ và cảnh báo allowance không giới hạn
+    pub fn spawn_periodic_scan(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.scan(None).await {
+                    Ok(active) => {
+                        for record in active.iter().filter(|record| record.is_unlimited()) {
+                            warn!(
+                                "Unlimited allowance of {:?} to {:?} for {:?} on chain {}",
+                                record.token, record.spender, record.owner, record.chain_id
+                            );
+                        }
+                    }
+                    Err(e) => warn!("Allowance scan failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Gắn vị thế cho quyền đã đủ mà không cần approve lại
+    async fn link_position(
+        &self,
+        owner: Address,
+        token: Address,
+        spender: Address,
+        current: U256,
+        position_id: Option<&str>,
+    ) -> Result<()> {
+        let mut records = self.records.write().await;
+        let record = records.entry((owner, token, spender)).or_insert_with(|| ApprovalRecord {
+            chain_id: self.adapter.get_chain_id(),
+            owner,
+            token,
+            spender,
+            granted: current,
+            kind: ApprovalKind::External,
+            tx_hash: None,
+            position_id: None,
+            granted_at: Utc::now(),
+            current_allowance: None,
+            last_scanned: None,
+        });
+        record.current_allowance = Some(current);
+        record.last_scanned = Some(Utc::now());
+        if let Some(position_id) = position_id {
+            record.position_id = Some(position_id.to_string());
+        }
+        self.save(&records)
+    }
+
+    async fn upsert(&self, record: ApprovalRecord) -> Result<()> {
+        let mut records = self.records.write().await;
+        records.insert((record.owner, record.token, record.spender), record);
+        self.save(&records)
+    }
+
+    /// Gửi approve(spender, amount) qua WalletManager (áp dụng chính sách ví) và chờ xác nhận
+    async fn send_approve(&self, owner: Address, token: Address, spender: Address, amount: U256) -> Result<H256> {
+        let data = encode_call("approve(address,uint256)", &[Token::Address(spender), Token::Uint(amount)]);
+        let tx: TypedTransaction = TransactionRequest::new().from(owner).to(token).data(data).into();
+
+        let tx_hash = self.wallets.send_transaction(self.provider.clone(), owner, &tx).await?;
+        let receipt = self.adapter
+            .wait_for_transaction_receipt(tx_hash, self.config.confirmations, self.config.receipt_timeout)
+            .await?;
+        if receipt.status != Some(U64::one()) {
+            bail!("Approve transaction {:?} for token {:?} reverted", tx_hash, token);
+        }
+        Ok(tx_hash)
+    }
+
+    /// Đọc DOMAIN_SEPARATOR và nonce permit hiện tại của owner
+    async fn permit_domain(&self, token: Address, owner: Address) -> Result<(H256, U256)> {
+        let separator = self.read_word(token, encode_call("DOMAIN_SEPARATOR()", &[])).await?;
+        let nonce = self.read_word(token, encode_call("nonces(address)", &[Token::Address(owner)])).await?;
+        Ok((H256::from(separator), U256::from_big_endian(&nonce)))
+    }
+
+    async fn read_word(&self, token: Address, data: Bytes) -> Result<[u8; 32]> {
+        let request = TransactionRequest::new().to(token).data(data);
+        let output = self.adapter.call(&request, None).await?;
+        output.get(..32)
+            .and_then(|word| <[u8; 32]>::try_from(word).ok())
+            .ok_or_else(|| anyhow!("Unexpected return data from {:?}", token))
+    }
+
+    fn save(&self, records: &HashMap<ApprovalKey, ApprovalRecord>) -> Result<()> {
+        let Some(path) = &self.config.store_path else {
+            return Ok(());
+        };
+
+        // Giữ bản ghi của chain khác nếu dùng chung file
+        let chain_id = self.adapter.get_chain_id();
+        let mut saved: Vec<ApprovalRecord> = if path.exists() {
+            serde_json::from_str::<Vec<ApprovalRecord>>(&std::fs::read_to_string(path)?)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|record| record.chain_id != chain_id)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        saved.extend(records.values().cloned());
+
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(&saved)?)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// Mã hóa lời gọi hàm: selector + tham số ABI
+pub(crate) fn encode_call(signature: &str, args: &[Token]) -> Bytes {
+    let mut data = id(signature).to_vec();
+    data.extend(abi::encode(args));
+    Bytes::from(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_adapters::tests::mock_adapter::MockChainAdapter;
+    use crate::chain_adapters::tests::mock_chain::{calldata, MockChain};
+    use crate::chain_adapters::tests::mock_contracts::TokenSpec;
+    use crate::chain_adapters::tests::wallet_fixture::TestWallets;
+
+    struct Fixture {
+        chain: MockChain,
+        token: Address,
+        owner: Address,
+        manager: ApprovalManager<MockChainAdapter>,
+        wallets: TestWallets,
+    }
+
+    async fn fixture() -> Fixture {
+        let chain = MockChain::spawn(1).await.unwrap();
+        let token = chain.deploy_erc20(&TokenSpec::new("Mock Token", "MOCK", 18)).unwrap();
+        let provider = Provider::<Http>::try_from(chain.http_url()).unwrap();
+
+        let wallets = TestWallets::new(1);
+        let owner = wallets.import_dev_wallet(&chain, 1);
+        assert_eq!(owner, chain.dev_address(1));
+
+        let manager = ApprovalManager::new(
+            Arc::new(MockChainAdapter::new(&chain)),
+            Arc::new(provider),
+            wallets.manager.clone(),
+            ApprovalManagerConfig {
+                store_path: Some(wallets.dir.path().join("approvals.json")),
+                ..Default::default()
+            },
+        ).unwrap();
+
+        Fixture { chain, token, owner, manager, wallets }
+    }
+
+    fn allowance(fixture: &Fixture, spender: Address) -> U256 {
+        let output = fixture.chain.call(
+            fixture.token,
+            calldata("allowance(address,address)", &[Token::Address(fixture.owner), Token::Address(spender)]),
+        ).unwrap();
+        U256::from_big_endian(&output)
+    }
+
+    #[tokio::test]
+    async fn test_exact_approval_recorded_and_revoked_on_position_close() {
+        let fixture = fixture().await;
+        let router = Address::repeat_byte(0x42);
+
+        let hash = fixture.manager.approve_exact(fixture.owner, fixture.token, router, U256::from(1_000u64), Some("pos-1")).await.unwrap();
+        assert!(hash.is_some());
+        assert_eq!(allowance(&fixture, router), U256::from(1_000u64));
+
+        // Đủ allowance thì không gửi thêm giao dịch
+        let hash = fixture.manager.ensure_allowance(fixture.owner, fixture.token, router, U256::from(500u64), Some("pos-1")).await.unwrap();
+        assert!(hash.is_none());
+
+        let records = fixture.manager.records().await;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].kind, ApprovalKind::Transaction);
+        assert_eq!(records[0].position_id.as_deref(), Some("pos-1"));
+
+        let revoked = fixture.manager.on_position_closed("pos-1").await.unwrap();
+        assert_eq!(revoked.len(), 1);
+        assert_eq!(allowance(&fixture, router), U256::zero());
+
+        // Danh mục được lưu lại và nạp khi khởi động lại
+        let saved: Vec<ApprovalRecord> = serde_json::from_str(
+            &std::fs::read_to_string(fixture.wallets.dir.path().join("approvals.json")).unwrap()
+        ).unwrap();
+        assert_eq!(saved[0].current_allowance, Some(U256::zero()));
+    }
+
+    #[tokio::test]
+    async fn test_scan_finds_unlimited_allowance_and_bulk_revokes() {
+        let fixture = fixture().await;
+        let legacy_router = Address::repeat_byte(0x77);
+        let other_router = Address::repeat_byte(0x78);
+
+        // Approve không giới hạn có sẵn từ trước khi dùng ApprovalManager
+        fixture.chain.transact(
+            fixture.owner,
+            Some(fixture.token),
+            U256::zero(),
+            calldata("approve(address,uint256)", &[Token::Address(legacy_router), Token::Uint(U256::MAX)]),
+        ).unwrap();
+        fixture.manager.track(fixture.owner, fixture.token, legacy_router).await.unwrap();
+        fixture.manager.approve_exact(fixture.owner, fixture.token, other_router, U256::from(7u64), None).await.unwrap();
+
+        let active = fixture.manager.scan(Some(fixture.owner)).await.unwrap();
+        assert_eq!(active.len(), 2);
+        assert!(active.iter().any(|record| record.spender == legacy_router && record.is_unlimited()));
+
+        let report = fixture.manager.revoke_all(Some(fixture.owner)).await.unwrap();
+        assert_eq!(report.revoked.len(), 2);
+        assert!(report.failed.is_empty());
+        assert_eq!(allowance(&fixture, legacy_router), U256::zero());
+        assert_eq!(allowance(&fixture, other_router), U256::zero());
+        assert!(fixture.manager.scan(None).await.unwrap().is_empty());
+
+        // Token mock không có DOMAIN_SEPARATOR/nonces nên không hỗ trợ permit
+        assert!(!fixture.manager.supports_permit(fixture.token, fixture.owner).await);
+        assert!(fixture.manager.sign_permit(fixture.owner, fixture.token, other_router, U256::one(), None).await.is_err());
+    }
+}
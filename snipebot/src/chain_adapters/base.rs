@@ -1,15 +1,15 @@
 // External imports
-use async_trait::async_trait;
 use ethers::{
     prelude::*,
-    providers::{Http, Provider, Middleware},
-    signers::{LocalWallet, Signer},
-    types::{Address, U256, TransactionRequest, Transaction, TransactionReceipt, BlockId},
-    core::types::BlockNumber,
+    providers::{Http, Provider, Middleware, is_local_endpoint, DEFAULT_LOCAL_POLL_INTERVAL},
+    signers::Signer,
+    types::{Address, U256, H256, TransactionRequest, Transaction, TransactionReceipt, BlockId},
+    types::transaction::eip2718::TypedTransaction,
+    utils::keccak256,
 };
-use log::{info, error, warn};
+use log::{info, error, warn, debug};
 use serde::{Serialize, Deserialize};
-use anyhow::Result;
+use anyhow::{Result, Context, anyhow};
 use once_cell::sync::Lazy;
 
 // Standard library imports
@@ -17,24 +17,28 @@ use std::{
     sync::{Arc, RwLock},
     str::FromStr,
     collections::HashMap,
-    time::{Instant, SystemTime},
 };
 
 // Internal imports 
 use crate::{
-    error::{TransactionError, classify_blockchain_error, get_recovery_info},
-    utils::{self, retry_blockchain_tx, RetryConfig, transaction_retry_config},
+    error::{TransactionError, classify_blockchain_error},
+    utils,
     abi_utils,
+    gas_optimizer::GasOptimizer,
     chain_adapters::{
-        nonce_manager::NonceManager,
-        retry::retry_blockchain_operation,
-        interfaces::{ChainAdapter, ChainError, GasInfo, TokenDetails, BlockInfo, NodeInfo},
-        retry_policy::{RetryContext, create_default_retry_policy},
-        connection_pool::{get_or_create_pool, ProviderGuard},
+        trait_adapter,
+        nonce_manager::{NonceManager, NonceLease, get_or_create_nonce_manager},
+        interfaces::ChainError,
+        retry_policy::{RetryAction, RetryPolicyEnum},
+        block_tracker::get_block_tracker,
+        l2_fee::{self, TotalCostEstimate},
+        connection_pool::{self, QuorumUnavailable},
+        ws_subscription::{self, Subscription, SubscriptionEvent, SubscriptionKind, WsSubscriptionConfig},
     },
 };
 
-use common::cache::{Cache, JSONCache, CacheEntry, CacheConfig};
+use diamond_common::cache::{Cache, JSONCache, AsyncCache};
+use diamond_wallet::ManagedSigner;
 
 /// Cấu hình cơ bản cho một mạng EVM
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,12 +76,11 @@ pub struct ChainConfig {
 }
 
 /// Adapter chung cho tất cả các mạng EVM 
-#[derive(Debug)]
 pub struct EVMAdapter {
     /// Provider cho mạng
     provider: Provider<Http>,
-    /// Ví người dùng (nếu được thiết lập)
-    wallet: Option<LocalWallet>,
+    /// Ví người dùng (nếu được thiết lập); mọi chữ ký đi qua WalletManager
+    wallet: Option<ManagedSigner>,
     /// Cấu hình của chain
     config: ChainConfig,
     /// Cache cho ABI contracts
@@ -86,39 +89,74 @@ pub struct EVMAdapter {
     /// Quản lý nonce để tránh duplicate transaction
     nonce_manager: Arc<NonceManager>,
     /// Cache cho dữ liệu JSON
-    cache: JSONCache,
+    cache: JSONCache<AsyncCache>,
 }
 
-#[async_trait]
-impl Cache for EVMAdapter {
-    async fn get_from_cache<T: Serialize + for<'de> Deserialize<'de> + Send + Sync + 'static>(&self, key: &str) -> Result<Option<T>> {
-        self.cache.get_from_cache(key).await
+impl std::fmt::Debug for EVMAdapter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EVMAdapter")
+            .field("config", &self.config)
+            .field("has_wallet", &self.wallet.is_some())
+            .field("has_rpc_pool", &self.rpc_pool.is_some())
+            .finish()
     }
+}
 
-    async fn store_in_cache<T: Serialize + for<'de> Deserialize<'de> + Send + Sync + 'static>(&self, key: &str, value: T, ttl_seconds: u64) -> Result<()> {
-        self.cache.store_in_cache(key, value, ttl_seconds).await
-    }
+/// Tùy chọn gửi giao dịch có retry theo quyết định của retry policy
+#[derive(Clone)]
+pub struct TxRetryOptions {
+    /// Gas price (max fee với EIP-1559) cho lần gửi đầu
+    pub gas_price: U256,
+    /// Policy phân loại lỗi thành RetryAction
+    pub policy: RetryPolicyEnum,
+    /// Bộ tối ưu gas dùng để tăng phí trong giới hạn phí hiện hành; None thì tăng không giới hạn
+    pub gas_optimizer: Option<Arc<GasOptimizer>>,
+}
 
-    async fn remove(&self, key: &str) -> Result<()> {
-        self.cache.remove(key).await
+impl TxRetryOptions {
+    /// Tùy chọn với retry policy mặc định của registry chain
+    pub fn with_default_policy(gas_price: U256) -> Self {
+        Self {
+            gas_price,
+            policy: crate::chain_adapters::chain_registry::get_registry().get_default_retry_policy(),
+            gas_optimizer: None,
+        }
     }
 
-    async fn clear(&self) -> Result<()> {
-        self.cache.clear().await
+    /// Tăng phí thêm `percent` phần trăm; None khi đã chạm giới hạn phí
+    fn bump_fee(&self, fee: U256, percent: u32) -> Option<U256> {
+        match &self.gas_optimizer {
+            Some(optimizer) => optimizer.bump_fee(fee, percent),
+            None => Some(fee.saturating_mul(U256::from(100 + u64::from(percent))) / U256::from(100)),
+        }
     }
+}
 
-    async fn cleanup_cache(&self) -> Result<()> {
-        self.cache.cleanup_cache().await
-    }
+/// Thông tin bundle đã gửi lên relay, lưu trong cache để theo dõi
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleInfo {
+    /// Hash bundle do relay trả về
+    pub bundle_hash: String,
+    /// Hash các giao dịch trong bundle
+    pub transaction_hashes: Vec<H256>,
+    /// Block mục tiêu
+    pub target_block: u64,
+    /// Thời điểm tạo (unix giây)
+    pub created_at: u64,
 }
 
 impl EVMAdapter {
     /// Tạo adapter mới với cấu hình định sẵn
     pub async fn new(config: ChainConfig) -> Result<Self> {
         // Tạo provider từ RPC URL
-        let provider = Provider::<Http>::try_from(&config.rpc_url)
+        let mut provider = Provider::<Http>::try_from(&config.rpc_url)
             .context(format!("Không thể kết nối đến RPC: {}", config.rpc_url))?;
         
+        // Node local (dev/test) xác nhận giao dịch ngay, không cần chu kỳ poll 7 giây mặc định
+        if is_local_endpoint(&config.rpc_url) {
+            provider.set_interval(DEFAULT_LOCAL_POLL_INTERVAL);
+        }
+        
         // Tạo map chứa ABI
         let mut contract_abis = HashMap::new();
         
@@ -145,7 +183,7 @@ impl EVMAdapter {
         
         // Tạo nonce manager với provider
         let provider_arc = Arc::new(provider.clone());
-        let nonce_manager = Arc::new(NonceManager::new(provider_arc, 60)); // Cache nonce trong 60 giây
+        let nonce_manager = get_or_create_nonce_manager(config.chain_id, provider_arc, 60).await; // Đối chiếu nonce mỗi 60 giây
         
         Ok(Self {
             provider,
@@ -154,7 +192,7 @@ impl EVMAdapter {
             contract_abis,
             rpc_pool: None,
             nonce_manager,
-            cache: JSONCache::new(),
+            cache: JSONCache::new(AsyncCache::new(None)),
         })
     }
     
@@ -169,24 +207,37 @@ impl EVMAdapter {
     }
     
     /// Lấy ví (nếu có)
-    pub fn get_wallet(&self) -> Option<&LocalWallet> {
+    pub fn get_wallet(&self) -> Option<&ManagedSigner> {
         self.wallet.as_ref()
     }
     
     /// Đặt ví
-    pub fn set_wallet(&mut self, wallet: LocalWallet) {
-        self.wallet = Some(wallet);
+    pub fn set_wallet(&mut self, wallet: ManagedSigner) {
+        self.wallet = Some(wallet.with_chain_id(self.config.chain_id));
+    }
+
+    /// Bản sao adapter ký bằng ví khác; dùng chung provider, RPC pool và nonce manager của chain
+    pub fn with_wallet(&self, wallet: ManagedSigner) -> Self {
+        Self {
+            provider: self.provider.clone(),
+            wallet: Some(wallet.with_chain_id(self.config.chain_id)),
+            config: self.config.clone(),
+            contract_abis: self.contract_abis.clone(),
+            rpc_pool: self.rpc_pool.clone(),
+            nonce_manager: Arc::clone(&self.nonce_manager),
+            cache: JSONCache::new(AsyncCache::new(None)),
+        }
     }
     
     /// Lấy ví với chain id
-    fn get_wallet_with_chain_id(&self) -> Result<LocalWallet> {
+    fn get_wallet_with_chain_id(&self) -> Result<ManagedSigner> {
         let wallet = self.wallet.as_ref()
             .ok_or_else(|| anyhow!("Không có ví để thực hiện giao dịch"))?;
         Ok(wallet.clone().with_chain_id(self.config.chain_id))
     }
     
     /// Tạo client với ví
-    fn get_client(&self) -> Result<SignerMiddleware<Provider<Http>, LocalWallet>> {
+    fn get_client(&self) -> Result<SignerMiddleware<Provider<Http>, ManagedSigner>> {
         let wallet = self.get_wallet_with_chain_id()?;
         Ok(SignerMiddleware::new(self.provider.clone(), wallet))
     }
@@ -197,7 +248,7 @@ impl EVMAdapter {
         
         // Thử lấy từ cache trước
         let cache_key = format!("balance_{}_{}", self.config.name, address);
-        if let Some(balance) = self.get_from_cache::<U256>(&cache_key) {
+        if let Ok(Some(balance)) = self.cache.get_from_cache::<U256>(&cache_key).await {
             return Ok(balance);
         }
         
@@ -206,14 +257,14 @@ impl EVMAdapter {
             .context(format!("Địa chỉ ví không hợp lệ: {}", address))?;
             
         // Thực hiện request với retry
-        let provider = self.provider.clone();
-        let balance = retry_async(move || async move {
+        let provider = &self.provider;
+        let balance = retry_async(|| async move {
             provider.get_balance(wallet_addr, None).await
                 .map_err(|e| anyhow!("Lỗi khi lấy số dư: {}", e))
         }).await?;
         
         // Cache kết quả
-        let _ = self.store_in_cache(&cache_key, &balance, 10); // TTL 10 giây
+        let _ = self.cache.store_in_cache(&cache_key, balance, 10).await; // TTL 10 giây
         
         Ok(balance)
     }
@@ -228,7 +279,7 @@ impl EVMAdapter {
         // Tạo contract ERC20
         let token_abi = self.contract_abis.get("erc20")
             .ok_or_else(|| anyhow!("Không tìm thấy ERC20 ABI"))?;
-        let token_contract = Contract::new(token_addr, token_abi.clone(), self.provider.clone());
+        let token_contract = Contract::new(token_addr, token_abi.clone(), Arc::new(self.provider.clone()));
         
         // Gọi hàm balanceOf
         let balance: U256 = token_contract.method("balanceOf", wallet_addr)?
@@ -244,27 +295,20 @@ impl EVMAdapter {
         let spender_addr = Address::from_str(spender_address)
             .context(format!("Địa chỉ spender không hợp lệ: {}", spender_address))?;
         
-        // Tạo client với ví
-        let client = Arc::new(self.get_client()?);
-        
-        // Tạo contract ERC20 với quyền ghi
         let token_abi = self.contract_abis.get("erc20")
             .ok_or_else(|| anyhow!("Không tìm thấy ERC20 ABI"))?;
-        let token_contract = Contract::new(token_addr, token_abi.clone(), client);
+        let token_contract = Contract::new(token_addr, token_abi.clone(), Arc::new(self.provider.clone()));
         
         // Gọi hàm approve
-        let tx = token_contract.method("approve", (spender_addr, amount))?
-            .send()
-            .await
+        let call = token_contract.method::<_, bool>("approve", (spender_addr, amount))?;
+        let options = TxRetryOptions::with_default_policy(self.default_gas_price_wei());
+        let receipt = self.send_with_retry_policy(call.tx, &options, "approve_token").await
             .context("Lỗi khi gửi giao dịch approve")?;
-            
-        // Đợi biên lai
-        let receipt = tx.await
-            .context("Lỗi khi lấy biên lai giao dịch")?;
         Ok(Some(receipt))
     }
     
     /// Swap chính xác ETH sang token
+    #[allow(clippy::too_many_arguments)]
     pub async fn swap_exact_eth_for_tokens(
         &self,
         token_address: &str,
@@ -275,54 +319,48 @@ impl EVMAdapter {
         gas_limit: Option<u64>,
         gas_price: Option<u64>,
     ) -> Result<Option<TransactionReceipt>> {
+        let tx = self.build_swap_exact_eth_for_tokens_tx(token_address, amount_in, min_amount_out, recipient, deadline, gas_limit)?;
+        let gas_price = gas_price.map(U256::from).unwrap_or_else(|| self.default_gas_price_wei());
+        let receipt = self.send_with_retry_policy(tx, &TxRetryOptions::with_default_policy(gas_price), "swap_exact_eth_for_tokens").await
+            .context("Lỗi khi gửi giao dịch swap")?;
+        Ok(Some(receipt))
+    }
+    
+    /// Giao dịch swap chính xác ETH sang token (chưa có nonce/phí, gửi qua `send_with_retry_policy`)
+    pub fn build_swap_exact_eth_for_tokens_tx(
+        &self,
+        token_address: &str,
+        amount_in: U256,
+        min_amount_out: U256,
+        recipient: &str,
+        deadline: u64,
+        gas_limit: Option<u64>,
+    ) -> Result<TypedTransaction> {
         let token_addr = Address::from_str(token_address)
             .context(format!("Địa chỉ token không hợp lệ: {}", token_address))?;
         let recipient_addr = Address::from_str(recipient)
             .context(format!("Địa chỉ người nhận không hợp lệ: {}", recipient))?;
         
-        // Tạo client với ví
-        let client = Arc::new(self.get_client()?);
-        
-        // Tạo contract Router
-        let router_addr = Address::from_str(&self.config.router_address)
-            .context(format!("Địa chỉ router không hợp lệ: {}", self.config.router_address))?;
-        let router_abi = self.contract_abis.get("router")
-            .ok_or_else(|| anyhow!("Không tìm thấy Router ABI"))?;
-        let router_contract = Contract::new(router_addr, router_abi.clone(), client);
-        
-        // Tạo path cho swap
+        let router_contract = self.router_contract()?;
         let weth_addr = Address::from_str(&self.config.wrapped_native_token)
             .context(format!("Địa chỉ wrapped token không hợp lệ: {}", self.config.wrapped_native_token))?;
         let path = vec![weth_addr, token_addr];
         
-        // Chuẩn bị tx
-        let mut tx = router_contract.method(
+        let call = router_contract.method::<_, Vec<U256>>(
             &self.config.eth_to_token_swap_fn, 
             (min_amount_out, path, recipient_addr, U256::from(deadline))
         ).context(format!("Không thể tạo giao dịch swap với method: {}", self.config.eth_to_token_swap_fn))?;
         
-        // Thêm value và gas
-        tx.tx.set_value(amount_in);
-        
+        let mut tx = call.tx;
+        tx.set_value(amount_in);
         if let Some(limit) = gas_limit {
-            tx.tx.set_gas(limit);
-        }
-        
-        if let Some(price) = gas_price {
-            tx.tx.set_gas_price(U256::from(price));
+            tx.set_gas(limit);
         }
-        
-        // Gửi giao dịch
-        let pending_tx = tx.send().await
-            .context("Lỗi khi gửi giao dịch swap")?;
-        
-        // Đợi biên lai
-        let receipt = pending_tx.await
-            .context("Lỗi khi lấy biên lai giao dịch")?;
-        Ok(Some(receipt))
+        Ok(tx)
     }
     
     /// Swap chính xác token sang ETH
+    #[allow(clippy::too_many_arguments)]
     pub async fn swap_exact_tokens_for_eth(
         &self,
         token_address: &str,
@@ -333,49 +371,57 @@ impl EVMAdapter {
         gas_limit: Option<u64>,
         gas_price: Option<u64>,
     ) -> Result<Option<TransactionReceipt>> {
+        let tx = self.build_swap_exact_tokens_for_eth_tx(token_address, amount_in, min_amount_out, recipient, deadline, gas_limit)?;
+        let gas_price = gas_price.map(U256::from).unwrap_or_else(|| self.default_gas_price_wei());
+        let receipt = self.send_with_retry_policy(tx, &TxRetryOptions::with_default_policy(gas_price), "swap_exact_tokens_for_eth").await
+            .context("Lỗi khi gửi giao dịch swap")?;
+        Ok(Some(receipt))
+    }
+    
+    /// Giao dịch swap chính xác token sang ETH (chưa có nonce/phí, gửi qua `send_with_retry_policy`)
+    pub fn build_swap_exact_tokens_for_eth_tx(
+        &self,
+        token_address: &str,
+        amount_in: U256,
+        min_amount_out: U256,
+        recipient: &str,
+        deadline: u64,
+        gas_limit: Option<u64>,
+    ) -> Result<TypedTransaction> {
         let token_addr = Address::from_str(token_address)
             .context(format!("Địa chỉ token không hợp lệ: {}", token_address))?;
         let recipient_addr = Address::from_str(recipient)
             .context(format!("Địa chỉ người nhận không hợp lệ: {}", recipient))?;
         
-        // Tạo client với ví
-        let client = Arc::new(self.get_client()?);
-        
-        // Tạo contract Router
-        let router_addr = Address::from_str(&self.config.router_address)
-            .context(format!("Địa chỉ router không hợp lệ: {}", self.config.router_address))?;
-        let router_abi = self.contract_abis.get("router")
-            .ok_or_else(|| anyhow!("Không tìm thấy Router ABI"))?;
-        let router_contract = Contract::new(router_addr, router_abi.clone(), client);
-        
-        // Tạo path cho swap
+        let router_contract = self.router_contract()?;
         let weth_addr = Address::from_str(&self.config.wrapped_native_token)
             .context(format!("Địa chỉ wrapped token không hợp lệ: {}", self.config.wrapped_native_token))?;
         let path = vec![token_addr, weth_addr];
         
-        // Chuẩn bị tx
-        let mut tx = router_contract.method(
+        let call = router_contract.method::<_, Vec<U256>>(
             &self.config.token_to_eth_swap_fn, 
             (amount_in, min_amount_out, path, recipient_addr, U256::from(deadline))
         ).context(format!("Không thể tạo giao dịch swap với method: {}", self.config.token_to_eth_swap_fn))?;
         
-        // Thêm gas
+        let mut tx = call.tx;
         if let Some(limit) = gas_limit {
-            tx.tx.set_gas(limit);
+            tx.set_gas(limit);
         }
-        
-        if let Some(price) = gas_price {
-            tx.tx.set_gas_price(U256::from(price));
-        }
-        
-        // Gửi giao dịch
-        let pending_tx = tx.send().await
-            .context("Lỗi khi gửi giao dịch swap")?;
-        
-        // Đợi biên lai
-        let receipt = pending_tx.await
-            .context("Lỗi khi lấy biên lai giao dịch")?;
-        Ok(Some(receipt))
+        Ok(tx)
+    }
+    
+    /// Contract router (chỉ dùng để mã hóa calldata và gọi hàm đọc)
+    fn router_contract(&self) -> Result<Contract<Provider<Http>>> {
+        let router_addr = Address::from_str(&self.config.router_address)
+            .context(format!("Địa chỉ router không hợp lệ: {}", self.config.router_address))?;
+        let router_abi = self.contract_abis.get("router")
+            .ok_or_else(|| anyhow!("Không tìm thấy Router ABI"))?;
+        Ok(Contract::new(router_addr, router_abi.clone(), Arc::new(self.provider.clone())))
+    }
+    
+    /// Gas price mặc định của chain (wei)
+    fn default_gas_price_wei(&self) -> U256 {
+        crate::utils::gwei_to_wei(self.config.default_gas_price)
     }
     
     /// Lấy giá swap dự kiến
@@ -385,7 +431,7 @@ impl EVMAdapter {
             .context(format!("Địa chỉ router không hợp lệ: {}", self.config.router_address))?;
         let router_abi = self.contract_abis.get("router")
             .ok_or_else(|| anyhow!("Không tìm thấy Router ABI"))?;
-        let router_contract = Contract::new(router_addr, router_abi.clone(), self.provider.clone());
+        let router_contract = Contract::new(router_addr, router_abi.clone(), Arc::new(self.provider.clone()));
         
         // Gọi hàm getAmountsOut
         let amounts: Vec<U256> = router_contract.method("getAmountsOut", (amount_in, path))?
@@ -394,6 +440,42 @@ impl EVMAdapter {
         Ok(amounts)
     }
     
+    /// Lấy giá swap dự kiến với quorum read qua connection pool (nếu pool đủ endpoint)
+    pub async fn get_amounts_out_verified(&self, amount_in: U256, path: Vec<Address>) -> Result<Vec<U256>> {
+        let router_addr = Address::from_str(&self.config.router_address)
+            .context(format!("Địa chỉ router không hợp lệ: {}", self.config.router_address))?;
+        
+        match connection_pool::quorum_get_amounts_out(self.config.chain_id, router_addr, amount_in, path.clone()).await {
+            Ok(amounts) => Ok(amounts),
+            // Chỉ dùng provider mặc định khi pool không đủ endpoint; bất đồng hay endpoint lỗi thì trả lỗi
+            Err(e) if e.is::<QuorumUnavailable>() => {
+                warn!("Quorum getAmountsOut không khả dụng cho {}: {}, dùng provider mặc định", self.config.name, e);
+                self.get_amounts_out(amount_in, path).await
+            },
+            Err(e) => Err(e.context("Quorum read getAmountsOut thất bại")),
+        }
+    }
+    
+    /// Lấy số dư native token với quorum read qua connection pool (nếu pool đủ endpoint)
+    pub async fn get_native_balance_verified(&self, address: &str) -> Result<U256> {
+        let wallet_addr = Address::from_str(address)
+            .context(format!("Địa chỉ ví không hợp lệ: {}", address))?;
+        
+        match connection_pool::quorum_get_balance(self.config.chain_id, wallet_addr).await {
+            Ok(balance) => Ok(balance),
+            Err(e) if e.is::<QuorumUnavailable>() => {
+                warn!("Quorum balance không khả dụng cho {}: {}, dùng provider mặc định", self.config.name, e);
+                self.get_native_balance(address).await
+            },
+            Err(e) => Err(e.context("Quorum read số dư thất bại")),
+        }
+    }
+    
+    /// Ước tính tổng chi phí giao dịch, gồm cả phí dữ liệu L1 trên rollup
+    pub async fn estimate_total_cost(&self, tx: &TransactionRequest) -> Result<TotalCostEstimate> {
+        l2_fee::estimate_total_cost(&self.provider, self.config.chain_id, tx).await
+    }
+    
     /// Kiểm tra sự tồn tại của cặp token
     pub async fn get_pair(&self, token_a: &str, token_b: &str) -> Result<Option<String>> {
         let token_a_addr = Address::from_str(token_a)
@@ -406,7 +488,7 @@ impl EVMAdapter {
             .context(format!("Địa chỉ factory không hợp lệ: {}", self.config.factory_address))?;
         let factory_abi = self.contract_abis.get("factory")
             .ok_or_else(|| anyhow!("Không tìm thấy Factory ABI"))?;
-        let factory_contract = Contract::new(factory_addr, factory_abi.clone(), self.provider.clone());
+        let factory_contract = Contract::new(factory_addr, factory_abi.clone(), Arc::new(self.provider.clone()));
         
         // Gọi hàm getPair
         let pair_addr: Address = factory_contract.method("getPair", (token_a_addr, token_b_addr))?
@@ -452,67 +534,62 @@ impl EVMAdapter {
             // Kiểm tra ví người dùng
             let wallet = self.get_wallet_with_chain_id()?;
             
-            // Lấy URL FlashBots endpoint
+            // URL FlashBots relay
             let flashbots_url = "https://relay.flashbots.net";
             
-            // Tạo FlashBots provider
-            let provider = self.provider.clone();
-            let flashbots_signer = wallet.clone();
-            
-            // Tạo Flashbots provider
-            let flashbots = match ethers::providers::FlashbotsMiddleware::new(
-                provider,
-                Url::parse(flashbots_url)?,
-                flashbots_signer,
-            ) {
-                Ok(fb) => fb,
-                Err(e) => return Err(anyhow!("Không thể tạo Flashbots provider: {}", e)),
-            };
-            
             // Tham số cho bundle
             let block_number = self.provider.get_block_number().await?;
-            let target_block = block_number + 1;
+            let target_block = block_number.as_u64() + 1;
             
-            // Tạo bundle từ các giao dịch
-            let mut bundle_txs = Vec::new();
+            // Ký từng giao dịch; nonce và gas limit được điền bởi provider
+            let client = self.get_client()?;
+            let mut raw_txs = Vec::new();
+            let mut tx_hashes = Vec::new();
             for tx in txs {
-                // Clone giao dịch và cập nhật các trường cần thiết
-                let mut bundle_tx = tx.clone();
-                
-                // Bỏ qua gas price và max fee per gas để FlashBots tối ưu
-                bundle_tx.gas_price = None;
-                bundle_tx.max_fee_per_gas = None;
-                
-                // Ký giao dịch
-                let signed_tx = wallet.sign_transaction(&bundle_tx).await?;
-                bundle_txs.push(signed_tx.into());
+                let mut bundle_tx: TypedTransaction = tx.into();
+                client.fill_transaction(&mut bundle_tx, None).await
+                    .map_err(|e| anyhow!("Không thể điền giao dịch cho bundle: {}", e))?;
+                let raw = wallet.sign_raw_transaction(&bundle_tx).await?;
+                tx_hashes.push(H256::from(keccak256(&raw)));
+                raw_txs.push(format!("0x{}", hex::encode(&raw)));
             }
             
-            // Tạo bundle request
-            let bundle = FlashbotsBundle {
-                transactions: bundle_txs,
-                target_block,
-                min_block: target_block,
-                max_block: target_block + 3, // Thử trong 3 block
-                revert_on_fail: true,
-            };
+            // Tạo bundle request (eth_sendBundle)
+            let body = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_sendBundle",
+                "params": [{
+                    "txs": raw_txs,
+                    "blockNumber": format!("0x{:x}", target_block),
+                }],
+            }).to_string();
+            
+            // Relay yêu cầu header X-Flashbots-Signature = address:sign(keccak(body))
+            let body_hash = format!("0x{}", hex::encode(keccak256(body.as_bytes())));
+            let signature = wallet.sign_message(body_hash).await?;
+            let auth_header = format!("{:?}:0x{}", wallet.address(), signature);
             
             // Gửi bundle
-            match flashbots.send_bundle(&bundle).await {
-                Ok(bundle_hash) => {
-                    info!("Đã gửi Flashbots bundle thành công: {:?}", bundle_hash);
-                    
-                    // Bắt đầu theo dõi bundle
-                    let bundle_stats = flashbots.get_bundle_stats(bundle_hash).await?;
-                    debug!("Bundle stats: {:?}", bundle_stats);
-                    
-                    // Lưu thông tin bundle vào cache để theo dõi sau này
-                    self.cache_bundle_info(bundle_hash, bundle.target_block, bundle_txs.clone())?;
-                    
-                    Ok(())
-                },
-                Err(e) => Err(anyhow!("Lỗi khi gửi Flashbots bundle: {}", e)),
+            let response: serde_json::Value = reqwest::Client::new()
+                .post(flashbots_url)
+                .header("Content-Type", "application/json")
+                .header("X-Flashbots-Signature", auth_header)
+                .body(body)
+                .send().await
+                .context("Lỗi khi gửi Flashbots bundle")?
+                .json().await
+                .context("Phản hồi Flashbots không hợp lệ")?;
+            
+            if let Some(err) = response.get("error") {
+                return Err(anyhow!("Lỗi khi gửi Flashbots bundle: {}", err));
             }
+            let bundle_hash = response["result"]["bundleHash"].as_str().unwrap_or_default().to_string();
+            info!("Đã gửi Flashbots bundle thành công: {}", bundle_hash);
+            debug!("Bundle gồm {} giao dịch, block mục tiêu {}", tx_hashes.len(), target_block);
+            
+            // Lưu thông tin bundle vào cache để theo dõi sau này
+            self.cache_bundle_info(bundle_hash, target_block, tx_hashes).await
         } else {
             // Đối với các chain khác, kiểm tra nếu có FlashBots tương đương
             match self.config.name.as_str() {
@@ -534,43 +611,27 @@ impl EVMAdapter {
     }
     
     /// Lưu thông tin bundle vào cache
-    fn cache_bundle_info(&self, bundle_hash: H256, target_block: u64, txs: Vec<TypedTransaction>) -> Result<()> {
-        // Lấy danh sách transaction hash
-        let tx_hashes: Vec<H256> = txs.iter()
-            .filter_map(|tx| tx.hash())
-            .collect();
-            
-        // Tạo thông tin bundle
+    async fn cache_bundle_info(&self, bundle_hash: String, target_block: u64, tx_hashes: Vec<H256>) -> Result<()> {
+        let key = format!("bundle_{}", bundle_hash);
         let bundle_info = BundleInfo {
             bundle_hash,
             transaction_hashes: tx_hashes,
-            target_block: target_block.into(),
+            target_block,
             created_at: utils::safe_now(), // Sử dụng hàm an toàn từ utils
         };
         
-        // Lưu vào cache
-        match self.cache.try_write() {
-            Ok(mut cache) => {
-                let key = format!("bundle_{:?}", bundle_hash);
-                let json_value = serde_json::to_value(&bundle_info)?;
-                cache.insert(key, (Instant::now(), json_value));
-                Ok(())
-            },
-            Err(_) => {
-                warn!("Không thể lấy write lock cho cache khi lưu bundle info");
-                Err(anyhow!("Không thể lấy write lock cho cache"))
-            }
-        }
+        // Giữ thông tin bundle trong 1 giờ
+        self.cache.store_in_cache(&key, bundle_info, 3600).await
     }
     
     /// Gửi bundle đến BSC MEV Relay
-    async fn send_bundle_to_bsc_mev_relay(&self, txs: Vec<TransactionRequest>) -> Result<()> {
+    async fn send_bundle_to_bsc_mev_relay(&self, _txs: Vec<TransactionRequest>) -> Result<()> {
         // TODO: Triển khai gửi bundle đến BSC MEV Relay khi có
         Err(anyhow!("BSC MEV Relay chưa được hỗ trợ đầy đủ"))
     }
     
     /// Gửi bundle đến Avalanche Subnet
-    async fn send_bundle_to_avalanche_subnet(&self, txs: Vec<TransactionRequest>) -> Result<()> {
+    async fn send_bundle_to_avalanche_subnet(&self, _txs: Vec<TransactionRequest>) -> Result<()> {
         // TODO: Triển khai gửi bundle đến Avalanche Subnet khi có
         Err(anyhow!("Avalanche Subnet protection chưa được hỗ trợ đầy đủ"))
     }
@@ -583,11 +644,11 @@ impl EVMAdapter {
         for (i, tx) in txs.iter().enumerate() {
             info!("Gửi giao dịch {}/{}", i+1, txs.len());
             
-            let tx_hash = client.send_transaction(tx.clone(), None).await?;
-            info!("Đã gửi giao dịch {}: {:?}", i+1, tx_hash);
+            let pending_tx = client.send_transaction(tx.clone(), None).await?;
+            info!("Đã gửi giao dịch {}: {:?}", i+1, pending_tx.tx_hash());
             
             // Đợi giao dịch được xác nhận trước khi gửi giao dịch tiếp theo
-            let receipt = client.pending_transaction(tx_hash)
+            let receipt = pending_tx
                 .confirmations(1)
                 .await?;
                 
@@ -610,7 +671,7 @@ impl EVMAdapter {
     }
     
     /// Theo dõi giao dịch chờ xử lý (chưa triển khai chi tiết)
-    pub async fn watch_pending_transactions(&self, callback: Box<dyn Fn(Transaction) + Send + Sync>) -> Result<()> {
+    pub async fn watch_pending_transactions(&self, _callback: Box<dyn Fn(Transaction) + Send + Sync>) -> Result<()> {
         info!("Chức năng theo dõi mempool trên {} sẽ được triển khai sau", self.config.name);
         Ok(())
     }
@@ -623,10 +684,9 @@ impl EVMAdapter {
         let token_addr = Address::from_str(token_address)
             .context(format!("Địa chỉ token không hợp lệ: {}", token_address))?;
         
-        // Tạo WebSocket provider để nhận các transaction mới từ mempool
-        let ws_rpc_url = self.config.rpc_url.replace("http", "ws");
-        let ws_provider = match Provider::<ethers::providers::Ws>::connect(&ws_rpc_url).await {
-            Ok(provider) => provider,
+        // Subscribe pending transaction qua WebSocket dùng chung (tự kết nối lại khi mất kết nối)
+        let mut subscription = match self.subscribe_ws(SubscriptionKind::PendingTransactions).await {
+            Ok(subscription) => subscription,
             Err(e) => {
                 // Nếu WebSocket không khả dụng, thử polling
                 info!("WebSocket không khả dụng cho {}, sử dụng HTTP polling: {}", self.config.name, e);
@@ -634,15 +694,6 @@ impl EVMAdapter {
             }
         };
         
-        // Subscribe đến các pending transaction
-        let stream = match ws_provider.subscribe_pending_txs().await {
-            Ok(stream) => stream,
-            Err(e) => {
-                info!("Không thể subscribe pending txs cho {}, sử dụng HTTP polling: {}", self.config.name, e);
-                return self.watch_token_transactions_with_polling(token_address, callback).await;
-            }
-        };
-        
         // Provider để lấy chi tiết transaction
         let provider = self.provider.clone();
         let chain_name = self.config.name.clone();
@@ -654,29 +705,29 @@ impl EVMAdapter {
         
         // Tạo task để xử lý stream
         let handle = tokio::spawn(async move {
-            let mut stream = stream;
-            
             info!("Đã bắt đầu theo dõi mempool cho token {} trên {}", token_addr_str, chain_name);
             
-            while let Some(tx_hash) = stream.next().await {
+            while let Some(message) = subscription.next().await {
+                let SubscriptionEvent::PendingTransaction(tx_hash) = message.event else {
+                    continue;
+                };
+
                 // Lấy chi tiết transaction
-                if let Ok(tx) = provider.get_transaction(tx_hash).await {
-                    if let Some(tx) = tx {
-                        // Kiểm tra nếu giao dịch đến router
-                        if tx.to == Some(router_addr) {
-                            // Kiểm tra xem có liên quan đến token không
-                            if let Some(input) = &tx.input.0 {
-                                let input_str = hex::encode(input);
-                                // Kiểm tra xem input có chứa địa chỉ token không
-                                if input_str.contains(&token_addr.to_string()[2..].to_lowercase()) {
-                                    callback(tx);
-                                }
+                if let Ok(Some(tx)) = provider.get_transaction(tx_hash).await {
+                    // Kiểm tra nếu giao dịch đến router
+                    if tx.to == Some(router_addr) {
+                        // Kiểm tra xem có liên quan đến token không
+                        if let input @ [_, ..] = tx.input.as_ref() {
+                            let input_str = hex::encode(input);
+                            // Kiểm tra xem input có chứa địa chỉ token không
+                            if input_str.contains(&format!("{:x}", token_addr)) {
+                                callback(tx);
                             }
                         }
-                        // Kiểm tra nếu giao dịch đến token
-                        else if tx.to == Some(token_addr) {
-                            callback(tx);
-                        }
+                    }
+                    // Kiểm tra nếu giao dịch đến token
+                    else if tx.to == Some(token_addr) {
+                        callback(tx);
                     }
                 }
             }
@@ -687,6 +738,16 @@ impl EVMAdapter {
         Ok(handle)
     }
     
+    /// Đăng ký subscription WebSocket qua manager dùng chung của chain
+    pub async fn subscribe_ws(&self, kind: SubscriptionKind) -> Result<Subscription> {
+        let ws_rpc_url = self.config.rpc_url.replace("http", "ws");
+        let manager = ws_subscription::get_or_create_subscription_manager(
+            self.config.chain_id,
+            WsSubscriptionConfig::new(&ws_rpc_url, &self.config.rpc_url),
+        )?;
+        manager.subscribe(kind).await
+    }
+    
     /// Theo dõi mempool bằng HTTP polling khi WebSocket không khả dụng
     pub async fn watch_token_transactions_with_polling(&self, 
         token_address: &str, 
@@ -721,16 +782,16 @@ impl EVMAdapter {
                     // Nếu có block mới
                     if current_block > last_block {
                         for block_num in last_block+1..=current_block {
-                            if let Ok(Some(block)) = provider.get_block_with_txs(block_num.into()).await {
+                            if let Ok(Some(block)) = provider.get_block_with_txs(BlockId::from(block_num)).await {
                                 // Xử lý từng transaction trong block
                                 for tx in block.transactions {
                                     // Kiểm tra nếu giao dịch đến router
                                     if tx.to == Some(router_addr) {
                                         // Kiểm tra xem có liên quan đến token không
-                                        if let Some(input) = &tx.input.0 {
+                                        if let input @ [_, ..] = tx.input.as_ref() {
                                             let input_str = hex::encode(input);
                                             // Kiểm tra xem input có chứa địa chỉ token không
-                                            if input_str.contains(&token_addr.to_string()[2..].to_lowercase()) {
+                                            if input_str.contains(&format!("{:x}", token_addr)) {
                                                 callback(tx);
                                             }
                                         }
@@ -761,23 +822,9 @@ impl EVMAdapter {
         let token_addr = Address::from_str(token_address)
             .context(format!("Địa chỉ token không hợp lệ: {}", token_address))?;
             
-        // Tạo WebSocket provider để nhận các transaction mới từ mempool
-        let ws_rpc_url = self.config.rpc_url.replace("http", "ws");
-        let ws_provider = match Provider::<ethers::providers::Ws>::connect(&ws_rpc_url).await {
-            Ok(provider) => provider,
-            Err(e) => {
-                // Nếu WebSocket không khả dụng, trả về lỗi
-                return Err(anyhow!("WebSocket không khả dụng cho sandwich: {}", e));
-            }
-        };
-        
-        // Subscribe đến các pending transaction
-        let stream = match ws_provider.subscribe_pending_txs().await {
-            Ok(stream) => stream,
-            Err(e) => {
-                return Err(anyhow!("Không thể subscribe pending txs: {}", e));
-            }
-        };
+        // Subscribe pending transaction qua WebSocket dùng chung (tự kết nối lại khi mất kết nối)
+        let mut subscription = self.subscribe_ws(SubscriptionKind::PendingTransactions).await
+            .map_err(|e| anyhow!("Không thể subscribe pending txs cho sandwich: {}", e))?;
         
         // Provider để lấy chi tiết transaction
         let provider = self.provider.clone();
@@ -791,40 +838,40 @@ impl EVMAdapter {
             
         // Tạo task để xử lý stream
         let handle = tokio::spawn(async move {
-            let mut stream = stream;
-            
-            while let Some(tx_hash) = stream.next().await {
+            while let Some(message) = subscription.next().await {
+                let SubscriptionEvent::PendingTransaction(tx_hash) = message.event else {
+                    continue;
+                };
+
                 // Lấy chi tiết transaction
-                if let Ok(tx) = provider.get_transaction(tx_hash).await {
-                    if let Some(tx) = tx {
-                        // Kiểm tra nếu giao dịch đến router và có giá trị tối thiểu
-                        if tx.to == Some(router_addr) && (tx.value >= min_amount) {
-                            // Phân tích function call
-                            if let Some(input) = &tx.input.0 {
-                                // Mặc định tất cả các hàm swap đều có 4 byte đầu là function selector
-                                if input.len() > 4 {
-                                    let selector = &input[0..4];
-                                    
-                                    // Phân tích transaction dựa trên selector
-                                    if let Ok(function) = router_abi.functions().find(|f| {
-                                        let computed_selector = f.short_signature();
-                                        selector == computed_selector.as_slice()
-                                    }) {
-                                        // Nếu là hàm swap
-                                        if function.name.contains("swap") {
-                                            // Thử decode tham số
-                                            if let Ok(decoded) = function.decode_input(&input[4..]) {
-                                                // Tìm tham số path
-                                                for param in decoded {
-                                                    if let Some(path) = param.into_array() {
-                                                        // Kiểm tra nếu path chứa token
-                                                        for addr in path {
-                                                            if let Some(address) = addr.into_address() {
-                                                                if address == token_addr {
-                                                                    // Gọi callback với số lượng token
-                                                                    callback(tx.clone(), tx.value);
-                                                                    break;
-                                                                }
+                if let Ok(Some(tx)) = provider.get_transaction(tx_hash).await {
+                    // Kiểm tra nếu giao dịch đến router và có giá trị tối thiểu
+                    if tx.to == Some(router_addr) && (tx.value >= min_amount) {
+                        // Phân tích function call
+                        if let input @ [_, ..] = tx.input.as_ref() {
+                            // Mặc định tất cả các hàm swap đều có 4 byte đầu là function selector
+                            if input.len() > 4 {
+                                let selector = &input[0..4];
+                                
+                                // Phân tích transaction dựa trên selector
+                                if let Some(function) = router_abi.functions().find(|f| {
+                                    let computed_selector = f.short_signature();
+                                    selector == computed_selector.as_slice()
+                                }) {
+                                    // Nếu là hàm swap
+                                    if function.name.contains("swap") {
+                                        // Thử decode tham số
+                                        if let Ok(decoded) = function.decode_input(&input[4..]) {
+                                            // Tìm tham số path
+                                            for param in decoded {
+                                                if let Some(path) = param.into_array() {
+                                                    // Kiểm tra nếu path chứa token
+                                                    for addr in path {
+                                                        if let Some(address) = addr.into_address() {
+                                                            if address == token_addr {
+                                                                // Gọi callback với số lượng token
+                                                                callback(tx.clone(), tx.value);
+                                                                break;
                                                             }
                                                         }
                                                     }
@@ -875,94 +922,246 @@ impl EVMAdapter {
         gas_price: Option<u64>,
         operation_name: &str,
     ) -> Result<TransactionReceipt, TransactionError> {
-        use crate::chain_adapters::retry::{retry_blockchain_operation, BlockchainError};
-        
-        // Tạo client với ví
-        let client = self.get_client()
-            .map_err(|e| TransactionError::Other(e.to_string()))?;
-        
-        // Sử dụng NonceManager để lấy nonce tiếp theo
-        let wallet_address = self.wallet.as_ref().map(|w| w.address()).unwrap_or_default();
-        
-        // Chuẩn bị transaction
-        let mut modified_tx = tx.clone();
-        
+        let mut tx = tx;
         if let Some(limit) = gas_limit {
-            modified_tx.set_gas(limit);
+            tx.set_gas(limit);
         }
         
-        // Sử dụng retry với gas price tự động điều chỉnh
-        retry_blockchain_operation(
-            operation_name,
-            |gas_override| async {
-                // Lấy nonce từ NonceManager
-                let nonce = self.nonce_manager.get_next_nonce(wallet_address).await?;
-                
-                // Set nonce vào transaction
-                let mut tx_to_send = modified_tx.clone();
-                tx_to_send.set_nonce(nonce);
+        let gas_price = gas_price.map(U256::from).unwrap_or_else(|| self.default_gas_price_wei());
+        self.send_with_retry_policy(tx, &TxRetryOptions::with_default_policy(gas_price), operation_name).await
+            .map_err(|e| {
+                error!("Lỗi khi gửi giao dịch: {}", e);
+                TransactionError::from_anyhow(e)
+            })
+    }
+    
+    /// Ký và gửi giao dịch với nonce cấp từ NonceManager. Lỗi mỗi lần gửi được retry policy
+    /// phân loại thành RetryAction: BumpNonce/RefreshNonce đồng bộ nonce với chain rồi cấp lại,
+    /// BumpFees tăng phí qua bộ tối ưu gas, SwitchEndpoint chuyển endpoint RPC,
+    /// WaitForPending chờ receipt của giao dịch đã có trong mempool
+    pub async fn send_with_retry_policy(
+        &self,
+        tx: TypedTransaction,
+        options: &TxRetryOptions,
+        operation_name: &str,
+    ) -> Result<TransactionReceipt> {
+        let wallet = self.get_wallet_with_chain_id()?;
+        let wallet_address = wallet.address();
+        let mut provider = self.provider.clone();
+        
+        let mut tx = self.fee_market_transaction(tx);
+        tx.set_from(wallet_address);
+        tx.set_chain_id(self.config.chain_id);
+        if tx.gas().is_none() {
+            let gas = provider.estimate_gas(&tx, None).await
+                .map_err(|e| anyhow!("Không thể ước tính gas cho '{}': {}", operation_name, e))?;
+            tx.set_gas(gas);
+        }
+        
+        let mut max_fee = options.gas_price;
+        let mut priority_fee = self.config.max_priority_fee
+            .map(crate::utils::gwei_to_wei)
+            .unwrap_or(U256::from(1_500_000_000u64)); // 1.5 gwei
+        let mut attempt = 0u32;
+        
+        // Nonce của giao dịch được giữ qua các lần thử; chỉ đổi khi chain đã dùng nonce đó
+        let mut lease: Option<NonceLease> = None;
+        // Hash các bản đã vào mempool ở nonce của lease (bản gốc và các bản thay thế tăng phí)
+        let mut broadcast: Vec<H256> = Vec::new();
+        let mut needs_send = true;
+        
+        loop {
+            let current = match &lease {
+                Some(current) => current.clone(),
+                None => {
+                    let leased = self.nonce_manager.lease_nonce(wallet_address).await?;
+                    lease = Some(leased.clone());
+                    leased
+                }
+            };
+            
+            let error = if needs_send {
+                let mut tx_to_send = tx.clone();
+                tx_to_send.set_nonce(current.nonce);
+                apply_fees(&mut tx_to_send, max_fee, priority_fee);
                 
-                // Set gas price nếu có
-                let gas_to_use = gas_override.unwrap_or_else(|| {
-                    if let Some(gp) = gas_price {
-                        U256::from(gp)
-                    } else {
-                        U256::from(self.config.default_gas_price)
+                // Ký qua WalletManager: chính sách chi tiêu và audit được áp dụng cho mỗi bản gửi
+                let raw_tx = match wallet.sign_raw_transaction(&tx_to_send).await {
+                    Ok(raw_tx) => raw_tx,
+                    Err(e) => {
+                        self.abandon_lease(&current, !broadcast.is_empty()).await;
+                        return Err(anyhow!("Không thể ký giao dịch '{}': {}", operation_name, e));
                     }
-                });
-                
-                tx_to_send.set_gas_price(gas_to_use);
-                
-                // Khi gọi send_transaction, middlewares trong ethers sẽ tự ký transaction
-                debug!("Gửi giao dịch với nonce {}, gas price {}", nonce, gas_to_use);
+                };
+                let tx_hash = H256::from(keccak256(&raw_tx));
+                debug!("Gửi '{}' nonce {}, phí {} (lần {})", operation_name, current.nonce, max_fee, attempt + 1);
                 
-                // Gửi transaction
-                let pending_tx_result = client.send_transaction(tx_to_send, None).await;
-                let pending_tx = match pending_tx_result {
-                    Ok(tx) => tx,
+                match provider.send_raw_transaction(raw_tx).await {
+                    Ok(_) => {
+                        let _ = self.nonce_manager.mark_submitted(&current, tx_hash).await;
+                        broadcast.push(tx_hash);
+                        needs_send = false;
+                        continue;
+                    }
                     Err(e) => {
-                        // Xử lý lỗi nonce
-                        if e.to_string().contains("nonce too low") ||
-                           e.to_string().contains("already known") ||
-                           e.to_string().contains("replacement transaction underpriced") {
-                            // Reset nonce từ blockchain
-                            if let Err(reset_err) = self.nonce_manager.reset_nonce(wallet_address).await {
-                                error!("Không thể reset nonce: {}", reset_err);
-                            }
+                        let chain_error = ChainError::from_anyhow(anyhow!(e));
+                        if matches!(options.policy.decide(attempt, &chain_error), RetryAction::WaitForPending) {
+                            // Node đã có đúng bản này: theo dõi theo hash thay vì gửi lại
+                            let _ = self.nonce_manager.mark_submitted(&current, tx_hash).await;
+                            broadcast.push(tx_hash);
+                            needs_send = false;
+                            continue;
                         }
-                        return Err(anyhow::anyhow!(e));
+                        chain_error
                     }
-                };
-                
-                // Đợi transaction được xác nhận
-                let receipt_result = pending_tx.await;
-                let receipt = match receipt_result {
-                    Ok(receipt_opt) => receipt_opt.ok_or_else(|| anyhow::anyhow!("Không có transaction receipt"))?,
-                    Err(e) => return Err(anyhow::anyhow!(e)),
-                };
-                
-                // Cập nhật nonce mới (nonce tiếp theo là nonce hiện tại + 1)
-                if let Some(tx_nonce) = receipt.transaction_nonce {
-                    let _ = self.nonce_manager.update_nonce(
-                        wallet_address, 
-                        tx_nonce + U256::from(1)
-                    ).await;
                 }
-                
-                Ok(receipt)
-            },
-            // Sử dụng gas price từ input hoặc default
-            gas_price.map(U256::from),
-            3, // 3 lần thử lại
-        ).await.map_err(|e| {
-            error!("Lỗi khi gửi giao dịch: {}", e);
-            TransactionError::from_anyhow(e)
-        })
+            } else {
+                match self.wait_for_any_receipt(&provider, &broadcast).await {
+                    Ok(Some(receipt)) => return Ok(self.finalize_receipt(receipt, &current).await),
+                    Ok(None) => {
+                        // Không bản nào còn trong mempool hay được đào
+                        if let Err(sync_err) = self.nonce_manager.settle_unconfirmed(&current, true).await {
+                            error!("Không thể đối chiếu nonce {} sau khi giao dịch bị rơi: {}", current.nonce, sync_err);
+                        }
+                        return Err(anyhow!("Giao dịch '{}' nonce {} bị rơi khỏi mempool, không có receipt", operation_name, current.nonce));
+                    }
+                    Err(e) => ChainError::from_anyhow(e),
+                }
+            };
+            
+            let action = options.policy.decide(attempt, &error);
+            attempt += 1;
+            warn!("Gửi '{}' thất bại (lần {}): {} -> {:?}", operation_name, attempt, error, action);
+            
+            match action {
+                RetryAction::BumpFees { percent } => {
+                    // Bản thay thế ký lại ở cùng nonce với phí cao hơn
+                    match options.bump_fee(max_fee, percent) {
+                        Some(bumped) => {
+                            max_fee = bumped;
+                            priority_fee = options.bump_fee(priority_fee, percent).unwrap_or(max_fee);
+                            needs_send = true;
+                            info!("Tăng phí '{}' nonce {} thêm {}% lên {} wei", operation_name, current.nonce, percent, max_fee);
+                        }
+                        None => {
+                            self.abandon_lease(&current, !broadcast.is_empty()).await;
+                            return Err(anyhow!("Lỗi khi thực hiện '{}': {} (phí đã chạm giới hạn)", operation_name, error));
+                        }
+                    }
+                }
+                RetryAction::Retry { delay } => {
+                    tokio::time::sleep(delay).await;
+                    needs_send = broadcast.is_empty();
+                }
+                RetryAction::SwitchEndpoint { delay } => {
+                    tokio::time::sleep(delay).await;
+                    provider = self.next_endpoint(&provider).await;
+                    needs_send = broadcast.is_empty();
+                }
+                RetryAction::WaitForPending if !broadcast.is_empty() => {
+                    needs_send = false;
+                }
+                RetryAction::BumpNonce | RetryAction::RefreshNonce if !broadcast.is_empty() => {
+                    // Nonce đã bị dùng sau khi ta gửi: có thể chính bản của ta đã được đào
+                    needs_send = false;
+                }
+                RetryAction::BumpNonce | RetryAction::RefreshNonce => {
+                    // Chưa gửi được bản nào: trả lease này rồi đối chiếu với chain để cấp nonce mới,
+                    // không đụng tới lease của các giao dịch khác
+                    let _ = self.nonce_manager.release_nonce(&current).await;
+                    self.nonce_manager.reconcile(wallet_address).await?;
+                    lease = None;
+                    needs_send = true;
+                }
+                _ => {
+                    self.abandon_lease(&current, !broadcast.is_empty()).await;
+                    return Err(anyhow!("Lỗi khi thực hiện '{}': {}", operation_name, error));
+                }
+            }
+        }
+    }
+    
+    /// Chờ receipt của một trong các bản cùng nonce. Bản mới nhất được theo dõi trước; nếu nó
+    /// không còn trong mempool thì một bản trước đó có thể đã được đào thay
+    async fn wait_for_any_receipt(&self, provider: &Provider<Http>, hashes: &[H256]) -> Result<Option<TransactionReceipt>> {
+        let (latest, earlier) = hashes.split_last()
+            .ok_or_else(|| anyhow!("Chưa có giao dịch nào được gửi"))?;
+        if let Some(receipt) = PendingTransaction::new(*latest, provider).await? {
+            return Ok(Some(receipt));
+        }
+        for hash in earlier.iter().rev() {
+            if let Some(receipt) = provider.get_transaction_receipt(*hash).await? {
+                return Ok(Some(receipt));
+            }
+        }
+        Ok(None)
+    }
+    
+    /// Xác nhận nonce của giao dịch đã được đào và theo dõi reorg cho giao dịch
+    async fn finalize_receipt(&self, receipt: TransactionReceipt, lease: &NonceLease) -> TransactionReceipt {
+        let _ = self.nonce_manager.confirm_nonce(lease.address, lease.nonce).await;
+        
+        if let (Some(tracker), Some(block_number), Some(block_hash)) = (
+            get_block_tracker(self.config.chain_id).await,
+            receipt.block_number,
+            receipt.block_hash,
+        ) {
+            tracker.track_transaction(receipt.transaction_hash, block_number.as_u64(), block_hash).await;
+        }
+        
+        receipt
+    }
+    
+    /// Bỏ giao dịch: chưa gửi được bản nào thì trả nonce, đã gửi thì đối chiếu với chain
+    /// (bản trong mempool vẫn có thể được đào nên nonce không được cấp lại ngay)
+    async fn abandon_lease(&self, lease: &NonceLease, broadcast: bool) {
+        let result = if broadcast {
+            self.nonce_manager.settle_unconfirmed(lease, false).await.map(|_| ())
+        } else {
+            self.nonce_manager.release_nonce(lease).await
+        };
+        if let Err(e) = result {
+            error!("Không thể trả lại nonce {} của {}: {}", lease.nonce, lease.address, e);
+        }
+    }
+    
+    /// Chain không hỗ trợ EIP-1559 thì chuyển giao dịch về dạng legacy
+    fn fee_market_transaction(&self, tx: TypedTransaction) -> TypedTransaction {
+        match tx {
+            TypedTransaction::Eip1559(request) if !self.config.eip1559_supported => {
+                TypedTransaction::Legacy(TransactionRequest {
+                    from: request.from,
+                    to: request.to,
+                    gas: request.gas,
+                    gas_price: None,
+                    value: request.value,
+                    data: request.data,
+                    nonce: request.nonce,
+                    chain_id: request.chain_id,
+                })
+            }
+            tx => tx,
+        }
+    }
+    
+    /// Provider của endpoint kế tiếp trong RPC pool; không có pool thì giữ endpoint hiện tại
+    async fn next_endpoint(&self, current: &Provider<Http>) -> Provider<Http> {
+        if let Some(pool) = &self.rpc_pool {
+            let current_url = current.url().as_str().trim_end_matches('/').to_string();
+            if let Some(url) = pool.all_urls().into_iter().find(|url| url.trim_end_matches('/') == current_url) {
+                pool.mark_unavailable(&url);
+            }
+            match self.get_provider_with_rotation().await {
+                Ok(provider) => return provider,
+                Err(e) => warn!("Không thể chuyển endpoint RPC: {}", e),
+            }
+        }
+        current.clone()
     }
     
     /// Lấy client với RPC rotation
     pub async fn get_provider_with_rotation(&self) -> Result<Provider<Http>> {
-        use crate::chain_adapters::retry::with_rpc_rotation;
+        
         
         // Nếu có RPC pool, sử dụng nó
         if let Some(pool) = &self.rpc_pool {
@@ -978,32 +1177,11 @@ impl EVMAdapter {
     
     /// Thực hiện swap ETH sang tokens
     pub async fn swap_eth_for_tokens(&self, amount: U256, token_address: &str, slippage: f64) -> Result<String> {
-        use crate::chain_adapters::retry::retry_blockchain_operation;
-        
-        let token_addr = Address::from_str(token_address)
-            .context(format!("Địa chỉ token không hợp lệ: {}", token_address))?;
-            
         // Lấy đường dẫn từ ETH đến token
         let path = self.get_native_to_token_path(token_address)?;
         
-        // Tạo client với ví
-        let client = self.get_client()
-            .context("Không có ví cho adapter")?;
-            
-        // Lấy address của router
-        let router_addr = Address::from_str(&self.config.router_address)
-            .context(format!("Địa chỉ router không hợp lệ: {}", self.config.router_address))?;
-            
-        // Lấy ABI router
-        let router_abi = self.contract_abis.get("router")
-            .ok_or_else(|| anyhow!("Không tìm thấy Router ABI"))?
-            .clone();
-            
-        // Tạo contract router
-        let router_contract = Contract::new(router_addr, router_abi, Arc::new(client.clone()));
-        
         // Lấy số lượng token tối thiểu (với slippage)
-        let amounts = self.get_amounts_out(amount, path.clone()).await
+        let amounts = self.get_amounts_out(amount, path).await
             .context("Không thể lấy amounts out cho swap")?;
         
         let min_amount_out = if amounts.len() > 1 {
@@ -1015,10 +1193,9 @@ impl EVMAdapter {
         };
         
         // Lấy địa chỉ ví
-        let address = if let Some(wallet) = &self.wallet {
-            wallet.address()
-        } else {
-            return Err(anyhow!("Không có ví cho adapter"));
+        let address = match &self.wallet {
+            Some(wallet) => format!("{:?}", wallet.address()),
+            None => return Err(anyhow!("Không có ví cho adapter")),
         };
         
         // Lấy deadline (thời gian hiện tại + 20 phút)
@@ -1026,52 +1203,23 @@ impl EVMAdapter {
             .duration_since(std::time::UNIX_EPOCH)?
             .as_secs() + 1200;
         
-        // Thực hiện swap với retry
-        let tx_hash = retry_blockchain_operation(
-            "swap_eth_for_tokens",
-            |gas_price| async {
-                // Tên hàm swap từ config
-                let swap_fn = &self.config.eth_to_token_swap_fn;
-                
-                // Tạo tham số swap
-                let swap_params = match swap_fn.as_str() {
-                    "swapExactETHForTokens" => (min_amount_out, path.clone(), address, U256::from(deadline)),
-                    "swapExactAVAXForTokens" => (min_amount_out, path.clone(), address, U256::from(deadline)),
-                    _ => return Err(anyhow!("Hàm swap không được hỗ trợ: {}", swap_fn)),
-                };
-                
-                // Thực hiện gọi hàm swap
-                let mut call = router_contract.method::<_, H256>(swap_fn, swap_params)?;
-                
-                // Thêm giá trị ETH và gas
-                call = call.value(amount);
-                
-                // Nếu có gas price override
-                if let Some(gas) = gas_price {
-                    if self.config.eip1559_supported {
-                        // Sử dụng EIP-1559 nếu được hỗ trợ
-                        let priority_fee = self.config.max_priority_fee
-                            .map(|fee| ethers::utils::parse_units(fee.to_string(), "gwei").unwrap_or(U256::from(1_500_000_000)))
-                            .unwrap_or(U256::from(1_500_000_000)); // 1.5 gwei
-                        
-                        call = call.gas_price(gas)
-                            .max_priority_fee_per_gas(priority_fee);
-                    } else {
-                        call = call.gas_price(gas);
-                    }
-                }
-                
-                // Chạy transaction
-                let tx = call.send().await?;
-                let tx_hash = tx.tx_hash();
-                
-                Ok(format!("{:?}", tx_hash))
-            },
-            Some(U256::from(self.config.default_gas_price)),
-            3
-        ).await?;
+        let tx = self.build_swap_exact_eth_for_tokens_tx(token_address, amount, min_amount_out, &address, deadline, None)?;
+        let receipt = self.send_with_retry_policy(tx, &TxRetryOptions::with_default_policy(self.default_gas_price_wei()), "swap_eth_for_tokens").await?;
         
-        Ok(tx_hash)
+        Ok(format!("{:?}", receipt.transaction_hash))
+    }
+}
+
+/// Đặt phí cho giao dịch: max fee/priority fee với EIP-1559, gas price với giao dịch legacy
+fn apply_fees(tx: &mut TypedTransaction, max_fee: U256, priority_fee: U256) {
+    match tx {
+        TypedTransaction::Eip1559(request) => {
+            request.max_fee_per_gas = Some(max_fee);
+            request.max_priority_fee_per_gas = Some(priority_fee.min(max_fee));
+        }
+        tx => {
+            tx.set_gas_price(max_fee);
+        }
     }
 }
 
@@ -1094,6 +1242,12 @@ pub struct ChainRegistry {
     adapters: HashMap<String, ChainAdapterEnum>,
 }
 
+impl Default for ChainRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ChainRegistry {
     pub fn new() -> Self {
         Self {
@@ -1156,6 +1310,7 @@ macro_rules! define_chain_enum_variant {
 #[macro_export]
 macro_rules! impl_chain_adapter_method {
     ($method:ident, $return_type:ty, $($param_name:ident: $param_type:ty),*) => {
+        #[allow(clippy::too_many_arguments)]
         pub async fn $method(&self, $($param_name: $param_type),*) -> $return_type {
             chain_variant_match!(self, adapter, adapter.$method($($param_name),*).await)
         }
@@ -1192,14 +1347,41 @@ impl ChainAdapterEnum {
         self.get_config().name.clone()
     }
     
+    pub fn get_provider(&self) -> &Provider<Http> {
+        chain_variant_match!(self, adapter, adapter.get_provider())
+    }
+    
+    pub fn get_wallet(&self) -> Option<&ManagedSigner> {
+        chain_variant_match!(self, adapter, adapter.get_wallet())
+    }
+    
+    /// Adapter cùng chain nhưng ký giao dịch bằng ví khác
+    pub fn with_wallet(&self, wallet: ManagedSigner) -> Self {
+        match self {
+            ChainAdapterEnum::Ethereum(adapter) => ChainAdapterEnum::Ethereum(Arc::new(adapter.with_wallet(wallet))),
+            ChainAdapterEnum::BSC(adapter) => ChainAdapterEnum::BSC(Arc::new(adapter.with_wallet(wallet))),
+            ChainAdapterEnum::Avalanche(adapter) => ChainAdapterEnum::Avalanche(Arc::new(adapter.with_wallet(wallet))),
+            ChainAdapterEnum::Base(adapter) => ChainAdapterEnum::Base(Arc::new(adapter.with_wallet(wallet))),
+            ChainAdapterEnum::Monad(adapter) => ChainAdapterEnum::Monad(Arc::new(adapter.with_wallet(wallet))),
+            ChainAdapterEnum::Arbitrum(adapter) => ChainAdapterEnum::Arbitrum(Arc::new(adapter.with_wallet(wallet))),
+            ChainAdapterEnum::Optimism(adapter) => ChainAdapterEnum::Optimism(Arc::new(adapter.with_wallet(wallet))),
+            ChainAdapterEnum::Polygon(adapter) => ChainAdapterEnum::Polygon(Arc::new(adapter.with_wallet(wallet))),
+            ChainAdapterEnum::Custom(name, adapter) => ChainAdapterEnum::Custom(name.clone(), Arc::new(adapter.with_wallet(wallet))),
+        }
+    }
+    
     // Sử dụng macro để implement các phương thức async
     impl_chain_adapter_method!(get_native_balance, Result<U256>, address: &str);
     impl_chain_adapter_method!(get_token_balance, Result<U256>, token_address: &str, wallet_address: &str);
     impl_chain_adapter_method!(approve_token, Result<Option<TransactionReceipt>>, token_address: &str, spender_address: &str, amount: U256);
     impl_chain_adapter_method!(swap_exact_eth_for_tokens, Result<Option<TransactionReceipt>>, token_address: &str, amount_in: U256, min_amount_out: U256, recipient: &str, deadline: u64, gas_limit: Option<u64>, gas_price: Option<u64>);
     impl_chain_adapter_method!(swap_exact_tokens_for_eth, Result<Option<TransactionReceipt>>, token_address: &str, amount_in: U256, min_amount_out: U256, recipient: &str, deadline: u64, gas_limit: Option<u64>, gas_price: Option<u64>);
+    impl_chain_adapter_method!(send_with_retry_policy, Result<TransactionReceipt>, tx: TypedTransaction, options: &TxRetryOptions, operation_name: &str);
     impl_chain_adapter_method!(get_amounts_out, Result<Vec<U256>>, amount_in: U256, path: Vec<Address>);
+    impl_chain_adapter_method!(get_amounts_out_verified, Result<Vec<U256>>, amount_in: U256, path: Vec<Address>);
+    impl_chain_adapter_method!(get_native_balance_verified, Result<U256>, address: &str);
     impl_chain_adapter_method!(get_pair, Result<Option<String>>, token_a: &str, token_b: &str);
+    impl_chain_adapter_method!(estimate_total_cost, Result<TotalCostEstimate>, tx: &TransactionRequest);
     impl_chain_adapter_method!(create_flashbots_bundle, Result<()>, txs: Vec<TransactionRequest>);
     impl_chain_adapter_method!(watch_pending_transactions, Result<()>, callback: Box<dyn Fn(Transaction) + Send + Sync>);
     impl_chain_adapter_method!(watch_token_transactions, Result<tokio::task::JoinHandle<()>>, token_address: &str, callback: Box<dyn Fn(Transaction) + Send + Sync + 'static>);
@@ -1209,6 +1391,42 @@ impl ChainAdapterEnum {
     impl_chain_adapter_sync_method!(decode_router_input, Result<Vec<ethers::abi::Token>>, input: &[u8]);
     impl_chain_adapter_sync_method!(get_native_to_token_path, Result<Vec<Address>>, token_address: &str);
     impl_chain_adapter_sync_method!(get_token_to_native_path, Result<Vec<Address>>, token_address: &str);
+    impl_chain_adapter_sync_method!(build_swap_exact_eth_for_tokens_tx, Result<TypedTransaction>, token_address: &str, amount_in: U256, min_amount_out: U256, recipient: &str, deadline: u64, gas_limit: Option<u64>);
+    impl_chain_adapter_sync_method!(build_swap_exact_tokens_for_eth_tx, Result<TypedTransaction>, token_address: &str, amount_in: U256, min_amount_out: U256, recipient: &str, deadline: u64, gas_limit: Option<u64>);
+}
+
+impl trait_adapter::ChainAdapter for ChainAdapterEnum {
+    fn get_config(&self) -> &ChainConfig {
+        ChainAdapterEnum::get_config(self)
+    }
+    
+    fn get_provider(&self) -> &Provider<Http> {
+        ChainAdapterEnum::get_provider(self)
+    }
+    
+    fn get_wallet(&self) -> Option<&ManagedSigner> {
+        ChainAdapterEnum::get_wallet(self)
+    }
+    
+    fn set_wallet(&mut self, wallet: ManagedSigner) {
+        *self = self.with_wallet(wallet);
+    }
+    
+    fn get_gas_optimizer(&self) -> Option<&crate::gas_optimizer::GasOptimizer> {
+        None
+    }
+    
+    fn decode_router_input(&self, input: &[u8]) -> Result<Vec<ethers::abi::Token>> {
+        ChainAdapterEnum::decode_router_input(self, input)
+    }
+    
+    fn get_native_to_token_path(&self, token_address: &str) -> Result<Vec<Address>> {
+        ChainAdapterEnum::get_native_to_token_path(self, token_address)
+    }
+    
+    fn get_token_to_native_path(&self, token_address: &str) -> Result<Vec<Address>> {
+        ChainAdapterEnum::get_token_to_native_path(self, token_address)
+    }
 }
 
 impl PartialEq for ChainAdapterEnum {
@@ -1233,30 +1451,33 @@ impl std::fmt::Display for ChainAdapterEnum {
     }
 }
 
+/// Tạo adapter theo tên chain từ cấu hình đã nạp (file TOML trong thư mục chain).
+/// Chain không có variant riêng được bọc trong `ChainAdapterEnum::Custom`
 pub async fn create_chain_adapter(chain_name: &str) -> Result<ChainAdapterEnum> {
-    match chain_name.to_lowercase().as_str() {
-        "ethereum" => {
-            let adapter = crate::chain_adapters::ethereum::EthereumAdapter::new().await?;
-            Ok(ChainAdapterEnum::Ethereum(adapter))
-        },
-        "bsc" => {
-            let adapter = crate::chain_adapters::bsc::BSCAdapter::new().await?;
-            Ok(ChainAdapterEnum::BSC(adapter))
-        },
-        "base" => {
-            let adapter = crate::chain_adapters::base_adapter::BaseAdapter::new().await?;
-            Ok(ChainAdapterEnum::Base(adapter))
-        },
-        "avalanche" => {
-            let adapter = crate::chain_adapters::avalanche::AvalancheAdapter::new().await?;
-            Ok(ChainAdapterEnum::Avalanche(adapter))
-        },
-        "monad" => {
-            let adapter = crate::chain_adapters::monad::MonadAdapter::new().await?;
-            Ok(ChainAdapterEnum::Monad(adapter))
-        },
-        _ => Err(anyhow::anyhow!("Unsupported chain: {}", chain_name))
+    use crate::chain_adapters::{chain_config_loader, chain_registry};
+    
+    let key = chain_name.to_lowercase();
+    let chain_id = chain_config_loader::find_chain_id(&key)
+        .ok_or_else(|| anyhow::anyhow!("Unsupported chain: {}", chain_name))?;
+    let config = chain_registry::get_chain_config(chain_id)?;
+    if !config.is_evm() {
+        return Err(anyhow::anyhow!(
+            "Chain {} is not EVM-compatible; use trading_adapter::get_trading_adapter instead", chain_name
+        ));
     }
+    let adapter = Arc::new(EVMAdapter::new(config.to_adapter_config()).await?);
+    
+    Ok(match key.as_str() {
+        "ethereum" => ChainAdapterEnum::Ethereum(adapter),
+        "bsc" => ChainAdapterEnum::BSC(adapter),
+        "avalanche" => ChainAdapterEnum::Avalanche(adapter),
+        "base" => ChainAdapterEnum::Base(adapter),
+        "monad" => ChainAdapterEnum::Monad(adapter),
+        "arbitrum" => ChainAdapterEnum::Arbitrum(adapter),
+        "optimism" => ChainAdapterEnum::Optimism(adapter),
+        "polygon" => ChainAdapterEnum::Polygon(adapter),
+        _ => ChainAdapterEnum::Custom(key, adapter),
+    })
 }
 
 pub async fn handle_transaction_error(
@@ -0,0 +1,593 @@
+// External imports
+use ethers::{
+    providers::{Http, Middleware, Provider},
+    types::{BlockId, BlockNumber, H256, U64},
+};
+
+// Standard library imports
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+// Internal imports
+use crate::event_bus::{EventBus, ReorgEvent, SystemEvent};
+use diamond_common::models::BlockStatus;
+
+// Third party imports
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn, error};
+
+/// Cấu hình cho BlockTracker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockTrackerConfig {
+    /// Số block gần nhất được giữ lại để phát hiện reorg
+    pub window_size: usize,
+    /// Khoảng thời gian poll head mới (ms)
+    pub poll_interval_ms: u64,
+    /// Số block xác nhận trước khi coi giao dịch là cuối cùng (`confirmation_depth` trong cấu hình chain)
+    pub confirmation_depth: u64,
+}
+
+impl Default for BlockTrackerConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 256,
+            poll_interval_ms: 2000,
+            confirmation_depth: 6,
+        }
+    }
+}
+
+impl BlockTrackerConfig {
+    /// Cấu hình poll khoảng nửa thời gian ra block (tối thiểu 250ms, tối đa 6s)
+    pub fn for_block_time(avg_block_time_secs: f64) -> Self {
+        let poll_interval_ms = (avg_block_time_secs * 500.0).clamp(250.0, 6000.0) as u64;
+        Self { poll_interval_ms, ..Self::default() }
+    }
+
+    /// Đặt số block xác nhận
+    pub fn with_confirmation_depth(mut self, depth: u64) -> Self {
+        self.confirmation_depth = depth.max(1);
+        self
+    }
+}
+
+/// Block được theo dõi trong cửa sổ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedBlock {
+    /// Block number
+    pub number: u64,
+    /// Block hash
+    pub hash: H256,
+    /// Hash của block cha
+    pub parent_hash: H256,
+}
+
+/// Giao dịch của bot đang chờ đủ số block xác nhận
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedTransaction {
+    /// Hash giao dịch
+    pub tx_hash: H256,
+    /// Block chứa giao dịch
+    pub block_number: u64,
+    /// Hash block chứa giao dịch
+    pub block_hash: H256,
+    /// Trạng thái: Pending (chờ xác nhận), Confirmed (đã cuối cùng), Orphaned (mất khỏi chain)
+    pub status: BlockStatus,
+}
+
+/// Kết quả khi áp dụng một head mới vào cửa sổ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeadUpdate {
+    /// Head đã có trong cửa sổ
+    Unchanged,
+    /// Chain được nối dài bình thường
+    Extended,
+    /// Cần lấy thêm block cha để xác định điểm rẽ nhánh
+    NeedParent(u64),
+    /// Phát hiện reorg: các block bị thay thế
+    Reorg {
+        common_ancestor: u64,
+        orphaned: Vec<TrackedBlock>,
+    },
+}
+
+/// Cửa sổ các block hash gần nhất của chain chính
+#[derive(Debug, Default)]
+pub struct BlockWindow {
+    blocks: BTreeMap<u64, TrackedBlock>,
+    capacity: usize,
+}
+
+impl BlockWindow {
+    /// Tạo cửa sổ mới
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            blocks: BTreeMap::new(),
+            capacity: capacity.max(2),
+        }
+    }
+
+    /// Block mới nhất trong cửa sổ
+    pub fn head(&self) -> Option<&TrackedBlock> {
+        self.blocks.values().next_back()
+    }
+
+    /// Lấy block theo số
+    pub fn get(&self, number: u64) -> Option<&TrackedBlock> {
+        self.blocks.get(&number)
+    }
+
+    /// Áp dụng một block vào cửa sổ.
+    ///
+    /// Block được chấp nhận khi block cha khớp với cửa sổ. Nếu hash cha không khớp,
+    /// caller cần cung cấp block cha (`NeedParent`) cho tới khi tìm được điểm chung.
+    pub fn apply(&mut self, block: TrackedBlock) -> HeadUpdate {
+        if let Some(existing) = self.blocks.get(&block.number) {
+            if existing.hash == block.hash {
+                return HeadUpdate::Unchanged;
+            }
+        }
+
+        let parent_number = match block.number.checked_sub(1) {
+            Some(n) => n,
+            None => {
+                self.insert(block);
+                return HeadUpdate::Extended;
+            }
+        };
+
+        match self.blocks.get(&parent_number) {
+            // Cửa sổ rỗng hoặc block cha đã ra khỏi cửa sổ: chấp nhận luôn
+            None if self.blocks.is_empty() || parent_number < *self.blocks.keys().next().unwrap() => {
+                self.insert(block);
+                HeadUpdate::Extended
+            },
+            None => HeadUpdate::NeedParent(parent_number),
+            Some(parent) if parent.hash == block.parent_hash => {
+                let orphaned = self.truncate_from(block.number);
+                self.insert(block.clone());
+                if orphaned.is_empty() {
+                    HeadUpdate::Extended
+                } else {
+                    HeadUpdate::Reorg {
+                        common_ancestor: parent_number,
+                        orphaned,
+                    }
+                }
+            },
+            Some(_) => HeadUpdate::NeedParent(parent_number),
+        }
+    }
+
+    /// Xóa tất cả block từ `number` trở lên và trả về danh sách đã xóa
+    fn truncate_from(&mut self, number: u64) -> Vec<TrackedBlock> {
+        self.blocks.split_off(&number).into_values().collect()
+    }
+
+    fn insert(&mut self, block: TrackedBlock) {
+        self.blocks.insert(block.number, block);
+        while self.blocks.len() > self.capacity {
+            let oldest = *self.blocks.keys().next().unwrap();
+            self.blocks.remove(&oldest);
+        }
+    }
+}
+
+/// Theo dõi head của chain, phát hiện reorg và theo dõi số block xác nhận của giao dịch
+pub struct BlockTracker {
+    /// Chain ID
+    chain_id: u64,
+    /// Provider để lấy block
+    provider: Arc<Provider<Http>>,
+    /// Cấu hình
+    config: BlockTrackerConfig,
+    /// Cửa sổ block hash gần nhất
+    window: RwLock<BlockWindow>,
+    /// Giao dịch của bot đang theo dõi
+    transactions: RwLock<HashMap<H256, TrackedTransaction>>,
+    /// Event bus để phát sự kiện reorg
+    event_bus: Option<Arc<EventBus>>,
+}
+
+impl BlockTracker {
+    /// Tạo BlockTracker mới
+    pub fn new(
+        chain_id: u64,
+        provider: Arc<Provider<Http>>,
+        config: BlockTrackerConfig,
+        event_bus: Option<Arc<EventBus>>,
+    ) -> Self {
+        let window = BlockWindow::new(config.window_size);
+        Self {
+            chain_id,
+            provider,
+            config,
+            window: RwLock::new(window),
+            transactions: RwLock::new(HashMap::new()),
+            event_bus,
+        }
+    }
+
+    /// Số block xác nhận cần thiết cho chain này
+    pub fn confirmation_depth(&self) -> u64 {
+        self.config.confirmation_depth
+    }
+
+    /// Block head hiện tại đã biết
+    pub async fn current_head(&self) -> Option<u64> {
+        self.window.read().await.head().map(|b| b.number)
+    }
+
+    /// Bắt đầu theo dõi một giao dịch đã được đưa vào block
+    pub async fn track_transaction(&self, tx_hash: H256, block_number: u64, block_hash: H256) {
+        self.transactions.write().await.insert(tx_hash, TrackedTransaction {
+            tx_hash,
+            block_number,
+            block_hash,
+            status: BlockStatus::Pending,
+        });
+        debug!("Theo dõi giao dịch {:?} ở block {} trên chain {}", tx_hash, block_number, self.chain_id);
+    }
+
+    /// Ngừng theo dõi giao dịch
+    pub async fn untrack_transaction(&self, tx_hash: H256) -> Option<TrackedTransaction> {
+        self.transactions.write().await.remove(&tx_hash)
+    }
+
+    /// Lấy trạng thái giao dịch đang theo dõi.
+    /// Giao dịch đã cuối cùng được gỡ sau khi phát `TransactionFinalized` nên trả về None.
+    pub async fn get_transaction_status(&self, tx_hash: H256) -> Option<TrackedTransaction> {
+        self.transactions.read().await.get(&tx_hash).cloned()
+    }
+
+    /// Số giao dịch đang theo dõi
+    pub async fn tracked_count(&self) -> usize {
+        self.transactions.read().await.len()
+    }
+
+    /// Lấy block và chuyển sang TrackedBlock
+    async fn fetch_block(&self, id: BlockId) -> Result<TrackedBlock> {
+        let block = self.provider.get_block(id).await
+            .map_err(|e| anyhow!("Không thể lấy block {:?}: {}", id, e))?
+            .ok_or_else(|| anyhow!("Block {:?} không tồn tại", id))?;
+
+        Ok(TrackedBlock {
+            number: block.number.map(|n| n.as_u64())
+                .ok_or_else(|| anyhow!("Block {:?} chưa có số block", id))?,
+            hash: block.hash.ok_or_else(|| anyhow!("Block {:?} chưa có hash", id))?,
+            parent_hash: block.parent_hash,
+        })
+    }
+
+    /// Poll head mới nhất và xử lý reorg nếu có
+    pub async fn poll(&self) -> Result<()> {
+        let head = self.fetch_block(BlockId::Number(BlockNumber::Latest)).await?;
+        let known_head = self.current_head().await;
+
+        // Lấp các block bị bỏ qua giữa hai lần poll để không mất hash trong cửa sổ
+        if let Some(known) = known_head {
+            let start = known + 1;
+            let end = head.number.min(known + self.config.window_size as u64);
+            for number in start..end {
+                let block = self.fetch_block(BlockId::Number(BlockNumber::Number(U64::from(number)))).await?;
+                self.apply_block(block).await?;
+            }
+        }
+
+        self.apply_block(head).await?;
+        self.update_confirmations().await;
+        Ok(())
+    }
+
+    /// Áp dụng một block, lấy thêm block cha cho tới khi tìm được điểm chung nếu cần
+    async fn apply_block(&self, block: TrackedBlock) -> Result<()> {
+        // Chuỗi block mới từ điểm rẽ nhánh tới head (thứ tự giảm dần)
+        let mut pending = vec![block];
+        let mut orphaned_all: Vec<TrackedBlock> = Vec::new();
+        let mut common_ancestor = None;
+
+        while let Some(next) = pending.last().cloned() {
+            let update = self.window.write().await.apply(next);
+            match update {
+                HeadUpdate::Unchanged | HeadUpdate::Extended => {
+                    pending.pop();
+                },
+                HeadUpdate::Reorg { common_ancestor: ancestor, orphaned } => {
+                    pending.pop();
+                    common_ancestor = Some(ancestor);
+                    orphaned_all.extend(orphaned);
+                },
+                HeadUpdate::NeedParent(number) => {
+                    if pending.len() > self.config.window_size {
+                        return Err(anyhow!(
+                            "Reorg trên chain {} sâu hơn cửa sổ theo dõi ({} block)",
+                            self.chain_id, self.config.window_size
+                        ));
+                    }
+                    let parent = self.fetch_block(BlockId::Number(BlockNumber::Number(U64::from(number)))).await?;
+                    pending.push(parent);
+                },
+            }
+        }
+
+        if let Some(ancestor) = common_ancestor {
+            self.handle_reorg(ancestor, orphaned_all).await;
+        }
+        Ok(())
+    }
+
+    /// Xử lý reorg: kiểm tra lại receipt các giao dịch trong block bị loại và phát sự kiện
+    async fn handle_reorg(&self, common_ancestor: u64, orphaned: Vec<TrackedBlock>) {
+        let new_head = self.current_head().await.unwrap_or(common_ancestor);
+        let orphaned_hashes: Vec<H256> = orphaned.iter().map(|b| b.hash).collect();
+        warn!(
+            "Phát hiện reorg trên chain {}: {} block bị thay thế từ block {}",
+            self.chain_id, orphaned.len(), common_ancestor + 1
+        );
+
+        // Các giao dịch của bot nằm trong block bị loại
+        let affected: Vec<H256> = self.transactions.read().await
+            .values()
+            .filter(|tx| orphaned_hashes.contains(&tx.block_hash))
+            .map(|tx| tx.tx_hash)
+            .collect();
+
+        for tx_hash in &affected {
+            self.reverify_transaction(*tx_hash).await;
+        }
+
+        if let Some(bus) = &self.event_bus {
+            let event = ReorgEvent {
+                chain_id: self.chain_id,
+                common_ancestor,
+                depth: orphaned.len() as u64,
+                orphaned_blocks: orphaned_hashes.iter().map(|h| format!("{:?}", h)).collect(),
+                new_head,
+                affected_transactions: affected.iter().map(|h| format!("{:?}", h)).collect(),
+            };
+            if let Err(e) = bus.publish(SystemEvent::ChainReorg(event)) {
+                debug!("Không có subscriber cho sự kiện reorg: {}", e);
+            }
+        }
+    }
+
+    /// Lấy lại receipt của giao dịch sau reorg
+    async fn reverify_transaction(&self, tx_hash: H256) {
+        let receipt = match self.provider.get_transaction_receipt(tx_hash).await {
+            Ok(receipt) => receipt,
+            Err(e) => {
+                error!("Không thể kiểm tra lại receipt {:?} sau reorg: {}", tx_hash, e);
+                return;
+            }
+        };
+
+        let new_block = receipt.as_ref()
+            .and_then(|r| r.block_number.zip(r.block_hash))
+            .map(|(number, hash)| (number.as_u64(), hash));
+
+        let mut transactions = self.transactions.write().await;
+        if let Some(tx) = transactions.get_mut(&tx_hash) {
+            match new_block {
+                Some((number, hash)) => {
+                    info!("Giao dịch {:?} được đưa lại vào block {} sau reorg", tx_hash, number);
+                    tx.block_number = number;
+                    tx.block_hash = hash;
+                    tx.status = BlockStatus::Pending;
+                },
+                None => {
+                    warn!("Giao dịch {:?} không còn trên chain {} sau reorg", tx_hash, self.chain_id);
+                    tx.status = BlockStatus::Orphaned;
+                },
+            }
+        }
+        drop(transactions);
+
+        if let Some(bus) = &self.event_bus {
+            let _ = bus.publish(SystemEvent::TransactionReorged(
+                self.chain_id,
+                format!("{:?}", tx_hash),
+                new_block.map(|(number, _)| number),
+            ));
+        }
+    }
+
+    /// Đánh dấu giao dịch đủ số block xác nhận là cuối cùng, phát sự kiện và gỡ khỏi danh sách theo dõi.
+    /// Giao dịch bị loại khỏi chain được giữ tới khi block cũ ra khỏi cửa sổ reorg rồi cũng bị gỡ.
+    async fn update_confirmations(&self) {
+        let head = match self.current_head().await {
+            Some(head) => head,
+            None => return,
+        };
+        let depth = self.confirmation_depth();
+        let window = self.config.window_size as u64;
+
+        let mut finalized = Vec::new();
+        {
+            let mut transactions = self.transactions.write().await;
+            transactions.retain(|_, tx| match tx.status {
+                BlockStatus::Pending if head + 1 >= tx.block_number + depth => {
+                    finalized.push((tx.tx_hash, tx.block_number));
+                    false
+                },
+                BlockStatus::Orphaned => tx.block_number + window > head,
+                _ => true,
+            });
+        }
+
+        if let Some(bus) = &self.event_bus {
+            for (tx_hash, block_number) in finalized {
+                let _ = bus.publish(SystemEvent::TransactionFinalized(
+                    self.chain_id,
+                    format!("{:?}", tx_hash),
+                    block_number,
+                ));
+            }
+        }
+    }
+
+    /// Chạy vòng lặp poll head định kỳ
+    pub fn start(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let interval = Duration::from_millis(self.config.poll_interval_ms);
+            info!("Bắt đầu theo dõi block trên chain {} (xác nhận sau {} block)", self.chain_id, self.confirmation_depth());
+
+            loop {
+                let started = Instant::now();
+                if let Err(e) = self.poll().await {
+                    warn!("Lỗi khi theo dõi block trên chain {}: {}", self.chain_id, e);
+                }
+                let elapsed = started.elapsed();
+                if elapsed < interval {
+                    tokio::time::sleep(interval - elapsed).await;
+                }
+            }
+        })
+    }
+}
+
+/// BlockTracker đang chạy kèm task poll để dừng khi gỡ chain
+type RunningTracker = (Arc<BlockTracker>, tokio::task::JoinHandle<()>);
+
+/// Các BlockTracker đang chạy theo chain ID
+static BLOCK_TRACKERS: once_cell::sync::Lazy<RwLock<HashMap<u64, RunningTracker>>> =
+    once_cell::sync::Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Khởi động BlockTracker cho chain (nếu chưa chạy) và trả về tracker
+pub async fn start_block_tracker(
+    chain_id: u64,
+    provider: Arc<Provider<Http>>,
+    config: BlockTrackerConfig,
+    event_bus: Option<Arc<EventBus>>,
+) -> Arc<BlockTracker> {
+    let mut trackers = BLOCK_TRACKERS.write().await;
+    if let Some((tracker, _)) = trackers.get(&chain_id) {
+        return tracker.clone();
+    }
+
+    let tracker = Arc::new(BlockTracker::new(chain_id, provider, config, event_bus));
+    let handle = tracker.clone().start();
+    trackers.insert(chain_id, (tracker.clone(), handle));
+    tracker
+}
+
+/// Dừng BlockTracker của chain (khi chain bị gỡ hoặc đổi RPC). Trả về true nếu tracker đang chạy
+pub async fn stop_block_tracker(chain_id: u64) -> bool {
+    match BLOCK_TRACKERS.write().await.remove(&chain_id) {
+        Some((_, handle)) => {
+            handle.abort();
+            info!("Dừng theo dõi block trên chain {}", chain_id);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Lấy BlockTracker đang chạy cho chain ID
+pub async fn get_block_tracker(chain_id: u64) -> Option<Arc<BlockTracker>> {
+    BLOCK_TRACKERS.read().await.get(&chain_id).map(|(tracker, _)| tracker.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(number: u64, hash: u64, parent: u64) -> TrackedBlock {
+        TrackedBlock {
+            number,
+            hash: H256::from_low_u64_be(hash),
+            parent_hash: H256::from_low_u64_be(parent),
+        }
+    }
+
+    #[test]
+    fn test_poll_interval_follows_block_time() {
+        assert_eq!(BlockTrackerConfig::for_block_time(12.0).poll_interval_ms, 6000);
+        assert_eq!(BlockTrackerConfig::for_block_time(3.0).poll_interval_ms, 1500);
+        assert_eq!(BlockTrackerConfig::for_block_time(0.25).poll_interval_ms, 250);
+    }
+
+    #[test]
+    fn test_window_extends_linear_chain() {
+        let mut window = BlockWindow::new(10);
+        assert_eq!(window.apply(block(1, 1, 0)), HeadUpdate::Extended);
+        assert_eq!(window.apply(block(2, 2, 1)), HeadUpdate::Extended);
+        assert_eq!(window.apply(block(2, 2, 1)), HeadUpdate::Unchanged);
+        assert_eq!(window.head().unwrap().number, 2);
+    }
+
+    #[test]
+    fn test_window_detects_reorg_by_parent_mismatch() {
+        let mut window = BlockWindow::new(10);
+        window.apply(block(1, 1, 0));
+        window.apply(block(2, 2, 1));
+        window.apply(block(3, 3, 2));
+
+        // Head mới ở block 3 có cha là 2b (khác 2): cần lấy block cha
+        assert_eq!(window.apply(block(3, 33, 22)), HeadUpdate::NeedParent(2));
+        // Block 2b có cha khớp với block 1: reorg sâu 2 block
+        match window.apply(block(2, 22, 1)) {
+            HeadUpdate::Reorg { common_ancestor, orphaned } => {
+                assert_eq!(common_ancestor, 1);
+                assert_eq!(orphaned.len(), 2);
+            },
+            other => panic!("Kỳ vọng reorg, nhận {:?}", other),
+        }
+        assert_eq!(window.apply(block(3, 33, 22)), HeadUpdate::Extended);
+        assert_eq!(window.head().unwrap().hash, H256::from_low_u64_be(33));
+    }
+
+    #[test]
+    fn test_window_capacity() {
+        let mut window = BlockWindow::new(3);
+        for i in 1..=5 {
+            window.apply(block(i, i, i - 1));
+        }
+        assert!(window.get(2).is_none());
+        assert!(window.get(3).is_some());
+        assert_eq!(window.blocks.len(), 3);
+    }
+
+    #[test]
+    fn test_confirmation_depth_override() {
+        let config = BlockTrackerConfig::default();
+        assert_eq!(config.confirmation_depth, 6);
+        let config = BlockTrackerConfig::for_block_time(12.0).with_confirmation_depth(12);
+        assert_eq!(config.confirmation_depth, 12);
+        assert_eq!(BlockTrackerConfig::default().with_confirmation_depth(0).confirmation_depth, 1);
+    }
+
+    #[tokio::test]
+    async fn test_final_transactions_are_pruned() {
+        let provider = Arc::new(Provider::<Http>::try_from("http://localhost:8545").unwrap());
+        let bus = Arc::new(EventBus::new(16));
+        let mut events = bus.subscribe();
+        let config = BlockTrackerConfig::default().with_confirmation_depth(3);
+        let tracker = BlockTracker::new(1, provider, config, Some(bus));
+
+        for i in 1..=2 {
+            tracker.window.write().await.apply(block(i, i, i - 1));
+        }
+        let tx_hash = H256::from_low_u64_be(0xaa);
+        tracker.track_transaction(tx_hash, 1, H256::from_low_u64_be(1)).await;
+
+        // Mới có 2 xác nhận: vẫn theo dõi
+        tracker.update_confirmations().await;
+        assert_eq!(tracker.tracked_count().await, 1);
+
+        // Đủ 3 xác nhận: phát sự kiện và gỡ khỏi danh sách
+        tracker.window.write().await.apply(block(3, 3, 2));
+        tracker.update_confirmations().await;
+        assert!(tracker.get_transaction_status(tx_hash).await.is_none());
+        match events.try_recv().unwrap() {
+            SystemEvent::TransactionFinalized(chain_id, _, block_number) => {
+                assert_eq!((chain_id, block_number), (1, 1));
+            },
+            other => panic!("Kỳ vọng TransactionFinalized, nhận {:?}", other),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 // External imports
 use ethers::{
     types::{Address, U256, TransactionRequest, Transaction, TransactionReceipt, BlockId, Bytes, Filter, Log},
+    types::transaction::eip2718::TypedTransaction,
     providers::Middleware,
     contract::Contract,
     prelude::*,
@@ -10,28 +11,31 @@ use ethers::{
 use std::{
     time::Instant,
     sync::Arc,
+    collections::HashMap,
+    str::FromStr,
 };
 
 // Internal imports
 use crate::{
     chain_adapters::{
         interfaces::{ChainAdapter, ChainError, GasInfo, TokenDetails, BlockInfo, NodeInfo},
-        retry_policy::{RetryPolicy, RetryContext, create_default_retry_policy},
+        retry_policy::{RetryPolicyEnum, RetryContext, create_default_retry_policy},
         chain_registry::{ChainConfig, get_chain_config},
-        connection_pool::{get_or_create_pool, ProviderGuard},
+        connection_pool::{get_or_create_pool, ProviderGuard, RPCConnectionPool},
     },
     abi_utils,
+    error::TransactionError,
+    utils::{gwei_to_wei, math::calculate_min_amount_out},
 };
 
 // Third party imports
-use anyhow::{Result, anyhow};
-use tracing::{info, warn, error};
+use anyhow::{Result, Context, anyhow};
 use async_trait::async_trait;
 use tokio::sync::RwLock;
-use serde::{Serialize, Deserialize};
-use wallet::secure_storage::SecureWalletStorage;
+use diamond_wallet::{ManagedSigner, WalletManager};
 
 /// Triển khai ChainAdapter cho các blockchain tương thích EVM
+#[allow(dead_code)]
 pub struct EVMChainAdapter {
     /// Chain ID
     chain_id: u64,
@@ -45,8 +49,8 @@ pub struct EVMChainAdapter {
     abis: HashMap<String, ethers::abi::Abi>,
     /// Wallet info
     wallet_address: Option<Address>,
-    /// Ví cục bộ
-    wallet: Option<LocalWallet>,
+    /// Ví ký qua WalletManager
+    wallet: Option<ManagedSigner>,
 }
 
 impl EVMChainAdapter {
@@ -105,45 +109,26 @@ impl EVMChainAdapter {
             self.config.connection_pool_config.clone(),
         ).await?;
         
-        let provider = pool.write().await.get_provider().await?;
+        let provider = RPCConnectionPool::get_provider(&pool).await?;
         
         Ok(provider)
     }
     
     /// Thêm ví vào adapter
-    pub fn with_wallet(mut self, wallet: LocalWallet) -> Self {
+    pub fn with_wallet(mut self, wallet: ManagedSigner) -> Self {
         self.wallet = Some(wallet.with_chain_id(self.chain_id));
         self.wallet_address = Some(self.wallet.as_ref().unwrap().address());
         self
     }
     
-    /// Thêm ví từ secure storage
-    pub async fn with_wallet_from_storage(
-        mut self,
-        storage: Arc<RwLock<SecureWalletStorage>>,
-        address: &str,
-    ) -> Result<Self> {
+    /// Thêm ví quản lý bởi WalletManager (signer của ví, chính sách chi tiêu và audit)
+    pub fn with_wallet_manager(self, wallet_manager: &Arc<WalletManager>, address: &str) -> Result<Self> {
         // Kiểm tra địa chỉ hợp lệ
-        let address = SecureWalletStorage::validate_wallet_address(address)
-            .map(|_| address.to_string())?;
-        
-        // Lấy thông tin ví từ storage
-        let storage = storage.read().await;
-        let wallet_info = storage.get_wallet(&address)
-            .ok_or_else(|| anyhow!("Wallet not found: {}", address))?;
-        
-        // Chuyển đổi sang LocalWallet
-        let wallet = storage.to_local_wallet(wallet_info)?
-            .with_chain_id(self.chain_id);
-        
-        // Cập nhật wallet
-        self.wallet = Some(wallet);
-        self.wallet_address = Some(
-            Address::from_str(&wallet_info.address)
-                .context("Invalid wallet address format")?
-        );
+        let parsed = Address::from_str(address)
+            .context("Invalid wallet address format")?;
         
-        Ok(self)
+        let wallet = wallet_manager.managed_signer(parsed, self.chain_id)?;
+        Ok(self.with_wallet(wallet))
     }
     
     /// Lấy ABI cho một contract
@@ -160,7 +145,7 @@ impl EVMChainAdapter {
         provider: T,
     ) -> Result<Contract<T>> {
         let abi = self.get_abi(abi_name)?;
-        Ok(Contract::new(address, abi.clone(), provider))
+        Ok(Contract::new(address, abi.clone(), Arc::new(provider)))
     }
     
     /// Mã hóa tham số giao dịch cho router
@@ -184,58 +169,58 @@ impl EVMChainAdapter {
     }
     
     /// Ước tính gas price dựa vào cấu hình
+    #[allow(dead_code)]
     fn estimate_gas_price(&self) -> Result<U256> {
         let gas_config = &self.config.gas_config;
         let base_fee_gwei = gas_config.base_fee;
         let priority_fee_gwei = gas_config.priority_fee;
         
-        // Chuyển từ gwei sang wei
-        let gwei_to_wei = U256::from(10).pow(U256::from(9));
-        
         if gas_config.supports_eip1559 {
-            // EIP-1559: Trả về max_fee_per_gas và max_priority_fee_per_gas
-            let max_fee_per_gas = U256::from_f64_lossy(base_fee_gwei) * gwei_to_wei;
-            let max_priority_fee_per_gas = U256::from_f64_lossy(priority_fee_gwei) * gwei_to_wei;
-            
-            Ok(max_fee_per_gas)
+            // EIP-1559: Trả về max_fee_per_gas (base fee đã bao gồm dự phòng theo cấu hình)
+            Ok(gwei_to_wei(base_fee_gwei))
         } else {
             // Legacy: Trả về gas_price = base_fee + priority_fee
-            let gas_price = U256::from_f64_lossy(base_fee_gwei + priority_fee_gwei) * gwei_to_wei;
-            
-            Ok(gas_price)
+            Ok(gwei_to_wei(base_fee_gwei + priority_fee_gwei))
         }
     }
 
+    /// Ký và gửi giao dịch bằng ví của adapter, retry theo retry policy và chờ 2 xác nhận
     pub async fn send_transaction_with_retry(
         &self,
-        tx: TypedTransaction,
+        mut tx: TypedTransaction,
         gas_limit: Option<u64>,
         gas_price: Option<u64>,
         operation_name: &str,
     ) -> Result<TransactionReceipt, TransactionError> {
-        // Clone dữ liệu cần thiết trước khi await
-        let tx_clone = tx.clone();
-        let provider = self.get_provider().await?;
-        let retry_policy = self.retry_policy.clone();
-
+        let wallet = self.wallet.clone()
+            .ok_or_else(|| TransactionError::Other("No wallet configured".to_string()))?;
+        let provider = self.get_provider().await
+            .map_err(|e| TransactionError::Other(e.to_string()))?;
+        
+        if let Some(limit) = gas_limit {
+            tx.set_gas(limit);
+        }
+        if let Some(price) = gas_price {
+            tx.set_gas_price(price);
+        }
+        tx.set_chain_id(self.chain_id);
+        
         let context = RetryContext::new(
             operation_name,
             &provider.endpoint_info.url,
             self.chain_id,
             gas_price.map(U256::from),
         );
-
-        retry_policy.retry(
-            Box::new(move || {
-                let tx = tx_clone.clone();
-                let provider = provider.provider.clone();
-                
-                Box::pin(async move {
-                    let tx_hash = provider.send_transaction(tx, None).await?;
-                    let receipt = provider.wait_for_transaction_receipt(tx_hash, 2).await?;
-                    Ok(receipt)
-                })
-            }),
+        let client = SignerMiddleware::new(provider.provider.clone(), wallet);
+        
+        self.retry_policy.retry(
+            || async {
+                let pending_tx = client.send_transaction(tx.clone(), None).await
+                    .map_err(|e| anyhow!(e))?;
+                pending_tx.confirmations(2).await
+                    .map_err(|e| anyhow!(e))?
+                    .ok_or_else(|| anyhow!("Transaction dropped from mempool"))
+            },
             &context,
         ).await.map_err(|e| TransactionError::Unknown(e.to_string()))
     }
@@ -246,6 +231,60 @@ impl EVMChainAdapter {
         
         Ok(rpc_pool.provider.clone())
     }
+    
+    /// Địa chỉ router mặc định của chain
+    fn router_address(&self) -> Result<Address> {
+        let router = self.config.to_adapter_config().router_address;
+        Address::from_str(&router)
+            .map_err(|_| anyhow!("No router configured for chain {}", self.chain_id))
+    }
+    
+    /// Path swap từ wrapped native token sang token
+    fn get_native_to_token_path(&self, token_address: &str) -> Result<Vec<Address>> {
+        let wrapped_native = self.config.wrapped_native_token
+            .ok_or_else(|| anyhow!("No wrapped native token configured for chain {}", self.chain_id))?;
+        let token = Address::from_str(token_address)
+            .context(format!("Invalid token address: {}", token_address))?;
+        Ok(vec![wrapped_native, token])
+    }
+    
+    /// Lấy số lượng đầu ra dự kiến từ router
+    async fn get_amounts_out(&self, amount_in: U256, path: Vec<Address>) -> Result<Vec<U256>> {
+        let provider = self.get_provider().await?;
+        let router = self.create_contract(self.router_address()?, "router", provider.reader().clone()).await?;
+        
+        let amounts: Vec<U256> = router.method("getAmountsOut", (amount_in, path))?
+            .call()
+            .await
+            .context("getAmountsOut call failed")?;
+        Ok(amounts)
+    }
+    
+    /// Tạo giao dịch swap native token sang token qua router
+    async fn create_swap_tx(
+        &self,
+        amount_in: U256,
+        min_amount_out: U256,
+        path: Vec<Address>,
+        recipient: Address,
+    ) -> Result<TypedTransaction> {
+        let swap_fn = self.config.to_adapter_config().eth_to_token_swap_fn;
+        let deadline = U256::from(crate::utils::safe_now() + 1200);
+        
+        let data = self.encode_router_transaction(&swap_fn, vec![
+            ethers::abi::Token::Uint(min_amount_out),
+            ethers::abi::Token::Array(path.into_iter().map(ethers::abi::Token::Address).collect()),
+            ethers::abi::Token::Address(recipient),
+            ethers::abi::Token::Uint(deadline),
+        ])?;
+        
+        let tx = TransactionRequest::new()
+            .from(recipient)
+            .to(self.router_address()?)
+            .value(amount_in)
+            .data(data);
+        Ok(tx.into())
+    }
 
     pub async fn swap_eth_for_tokens(
         &self,
@@ -253,20 +292,14 @@ impl EVMChainAdapter {
         token_address: &str,
         slippage: f64,
     ) -> Result<String> {
-        // Clone dữ liệu cần thiết
-        let provider = self.get_provider().await?;
-        let token_addr = token_address.to_string();
         let wallet = self.wallet.clone().ok_or_else(|| anyhow!("No wallet configured"))?;
-
-        // Tạo scope riêng cho mutable borrow
-        let path = {
-            let path = self.get_native_to_token_path(&token_addr)?;
-            path
-        };
+        let path = self.get_native_to_token_path(token_address)?;
 
         // Tính toán amounts
         let amounts = self.get_amounts_out(amount, path.clone()).await?;
-        let min_amount_out = calculate_min_amount_out(amounts[1], slippage);
+        let expected_out = *amounts.last()
+            .ok_or_else(|| anyhow!("getAmountsOut returned no amounts"))?;
+        let min_amount_out = calculate_min_amount_out(expected_out, slippage);
 
         // Tạo và gửi transaction
         let tx = self.create_swap_tx(
@@ -281,9 +314,9 @@ impl EVMChainAdapter {
             None,
             None,
             "swap_eth_for_tokens",
-        ).await?;
+        ).await.map_err(|e| anyhow!(e.to_string()))?;
 
-        Ok(receipt.transaction_hash.to_string())
+        Ok(format!("{:?}", receipt.transaction_hash))
     }
 }
 
@@ -304,13 +337,13 @@ impl ChainAdapter for EVMChainAdapter {
         // Thực hiện lấy block number với retry
         self.retry_policy.retry(
             || async {
-                provider.provider.get_block_number()
+                provider.reader().get_block_number()
                     .await
                     .map(|bn| bn.as_u64())
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))
+        ).await.map_err(ChainError::from_anyhow)
     }
     
     async fn get_gas_price(&self) -> Result<U256, ChainError> {
@@ -328,12 +361,12 @@ impl ChainAdapter for EVMChainAdapter {
         // Thực hiện lấy gas price với retry
         self.retry_policy.retry(
             || async {
-                provider.provider.get_gas_price()
+                provider.reader().get_gas_price()
                     .await
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))
+        ).await.map_err(ChainError::from_anyhow)
     }
     
     fn get_chain_id(&self) -> u64 {
@@ -359,12 +392,12 @@ impl ChainAdapter for EVMChainAdapter {
         // Thực hiện lấy block với retry
         let block_opt = self.retry_policy.retry(
             || async {
-                provider.provider.get_block(block_id)
+                provider.reader().get_block(block_id)
                     .await
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))?;
+        ).await.map_err(ChainError::from_anyhow)?;
         
         // Chuyển đổi dữ liệu
         if let Some(block) = block_opt {
@@ -403,21 +436,18 @@ impl ChainAdapter for EVMChainAdapter {
             // Lấy block mới nhất để có base fee
             let block = self.retry_policy.retry(
                 || async {
-                    provider.provider.get_block(BlockNumber::Latest)
+                    provider.reader().get_block(BlockNumber::Latest)
                         .await
                         .map_err(|e| anyhow!(e))
                 },
                 &context
-            ).await.map_err(|e| ChainError::from_anyhow(e))?;
+            ).await.map_err(ChainError::from_anyhow)?;
             
             // Nếu có base fee từ block
             if let Some(block) = block {
                 if let Some(base_fee) = block.base_fee_per_gas {
                     // Tính priority fee từ cấu hình
-                    let gwei_to_wei = U256::from(10).pow(U256::from(9));
-                    let priority_fee = U256::from_f64_lossy(
-                        self.config.gas_config.priority_fee
-                    ) * gwei_to_wei;
+                    let priority_fee = gwei_to_wei(self.config.gas_config.priority_fee);
                     
                     // Tính max fee = base_fee * 2 + priority_fee
                     let max_fee = base_fee.checked_mul(U256::from(2))
@@ -441,7 +471,7 @@ impl ChainAdapter for EVMChainAdapter {
         ))
     }
     
-    async fn send_raw_transaction(&self, tx_bytes: Bytes) -> Result<PendingTransaction<'static, Provider<Http>>, ChainError> {
+    async fn send_raw_transaction(&self, tx_bytes: Bytes) -> Result<ethers::types::H256, ChainError> {
         let provider = self.get_provider().await
             .map_err(|e: anyhow::Error| ChainError::from_anyhow(e))?;
         
@@ -455,23 +485,19 @@ impl ChainAdapter for EVMChainAdapter {
         
         // Retry nếu có lỗi
         let tx_hash = self.retry_policy.retry(
-            Box::new(move || {
-                let provider_clone = provider.provider.clone();
-                let tx_bytes_clone = tx_bytes.clone();
-                
-                Box::pin(async move {
-                    provider_clone.send_raw_transaction(tx_bytes_clone).await
-                        .map_err(|e| anyhow::anyhow!(e))
-                })
-            }),
+            || async {
+                provider.provider.send_raw_transaction(tx_bytes.clone()).await
+                    .map(|pending| pending.tx_hash())
+                    .map_err(|e| anyhow!(e))
+            },
             &context
         ).await
         .map_err(|e| ChainError::from_anyhow(anyhow!("Failed to send raw transaction: {}", e)))?;
         
-        Ok(PendingTransaction::new(tx_hash, provider.provider.clone()))
+        Ok(tx_hash)
     }
     
-    async fn send_transaction(&self, tx: &TransactionRequest) -> Result<PendingTransaction<'static, Provider<Http>>, ChainError> {
+    async fn send_transaction(&self, tx: &TransactionRequest) -> Result<ethers::types::H256, ChainError> {
         let provider = self.get_provider().await
             .map_err(|e: anyhow::Error| ChainError::from_anyhow(e))?;
         
@@ -488,20 +514,16 @@ impl ChainAdapter for EVMChainAdapter {
         
         // Retry nếu có lỗi
         let tx_hash = self.retry_policy.retry(
-            Box::new(move || {
-                let provider_clone = provider.provider.clone();
-                let tx_clone = tx_clone.clone();
-                
-                Box::pin(async move {
-                    provider_clone.send_transaction(tx_clone, None).await
-                        .map_err(|e| anyhow::anyhow!(e))
-                })
-            }),
+            || async {
+                provider.provider.send_transaction(tx_clone.clone(), None).await
+                    .map(|pending| pending.tx_hash())
+                    .map_err(|e| anyhow!(e))
+            },
             &context
         ).await
         .map_err(|e| ChainError::from_anyhow(anyhow!("Failed to send transaction: {}", e)))?;
         
-        Ok(PendingTransaction::new(tx_hash, provider.provider.clone()))
+        Ok(tx_hash)
     }
     
     async fn get_transaction_receipt(&self, tx_hash: H256) -> Result<Option<TransactionReceipt>, ChainError> {
@@ -519,12 +541,12 @@ impl ChainAdapter for EVMChainAdapter {
         // Thực hiện lấy receipt với retry
         self.retry_policy.retry(
             || async {
-                provider.provider.get_transaction_receipt(tx_hash)
+                provider.reader().get_transaction_receipt(tx_hash)
                     .await
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))
+        ).await.map_err(ChainError::from_anyhow)
     }
     
     async fn get_transaction(&self, tx_hash: H256) -> Result<Option<Transaction>, ChainError> {
@@ -542,12 +564,12 @@ impl ChainAdapter for EVMChainAdapter {
         // Thực hiện lấy transaction với retry
         self.retry_policy.retry(
             || async {
-                provider.provider.get_transaction(tx_hash)
+                provider.reader().get_transaction(tx_hash)
                     .await
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))
+        ).await.map_err(ChainError::from_anyhow)
     }
     
     async fn get_eth_balance(&self, address: Address, block: Option<BlockId>) -> Result<U256, ChainError> {
@@ -565,12 +587,12 @@ impl ChainAdapter for EVMChainAdapter {
         // Thực hiện lấy số dư với retry
         self.retry_policy.retry(
             || async {
-                provider.provider.get_balance(address, block)
+                provider.reader().get_balance(address, block)
                     .await
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))
+        ).await.map_err(ChainError::from_anyhow)
     }
     
     async fn get_token_balance(&self, token: Address, address: Address, block: Option<BlockId>) -> Result<U256, ChainError> {
@@ -578,7 +600,7 @@ impl ChainAdapter for EVMChainAdapter {
             .map_err(|e: anyhow::Error| ChainError::from_anyhow(e))?;
         
         // Tạo contract ERC20
-        let contract = self.create_contract(token, "erc20", provider.provider.clone())
+        let contract = self.create_contract(token, "erc20", provider.reader().clone())
             .await
             .map_err(|e| ChainError::ContractNotFound(e.to_string()))?;
         
@@ -601,7 +623,7 @@ impl ChainAdapter for EVMChainAdapter {
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))
+        ).await.map_err(ChainError::from_anyhow)
     }
     
     async fn get_token_details(&self, token: Address) -> Result<TokenDetails, ChainError> {
@@ -609,7 +631,7 @@ impl ChainAdapter for EVMChainAdapter {
             .map_err(|e: anyhow::Error| ChainError::from_anyhow(e))?;
         
         // Tạo contract ERC20
-        let contract = self.create_contract(token, "erc20", provider.provider.clone())
+        let contract = self.create_contract(token, "erc20", provider.reader().clone())
             .await
             .map_err(|e| ChainError::ContractNotFound(e.to_string()))?;
         
@@ -621,7 +643,7 @@ impl ChainAdapter for EVMChainAdapter {
             None,
         );
         
-        // Thực hiện các request song song
+        // Thực hiện các request song song (được gom vào một JSON-RPC batch)
         let name_future = self.retry_policy.retry(
             || async {
                 contract.method::<_, String>("name", ())?
@@ -673,10 +695,10 @@ impl ChainAdapter for EVMChainAdapter {
         // Tạo token details
         let token_details = TokenDetails {
             address: token,
-            name: name.map_err(|e| ChainError::from_anyhow(e))?,
-            symbol: symbol.map_err(|e| ChainError::from_anyhow(e))?,
-            decimals: decimals.map_err(|e| ChainError::from_anyhow(e))?,
-            total_supply: total_supply.map_err(|e| ChainError::from_anyhow(e))?,
+            name: name.map_err(ChainError::from_anyhow)?,
+            symbol: symbol.map_err(ChainError::from_anyhow)?,
+            decimals: decimals.map_err(ChainError::from_anyhow)?,
+            total_supply: total_supply.map_err(ChainError::from_anyhow)?,
         };
         
         Ok(token_details)
@@ -684,10 +706,10 @@ impl ChainAdapter for EVMChainAdapter {
     
     async fn get_token_allowance(&self, token: Address, owner: Address, spender: Address) -> Result<U256, ChainError> {
         let provider = self.get_provider().await
-            .map_err(|e| ChainError::Connection(e.to_string()))?;
+            .map_err(|e| ChainError::ConnectionError(e.to_string()))?;
         
         // Tạo contract ERC20
-        let contract = self.create_contract(token, "erc20", provider.provider.clone())
+        let contract = self.create_contract(token, "erc20", provider.reader().clone())
             .await
             .map_err(|e| ChainError::ContractNotFound(e.to_string()))?;
         
@@ -708,12 +730,12 @@ impl ChainAdapter for EVMChainAdapter {
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))
+        ).await.map_err(ChainError::from_anyhow)
     }
     
     async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, ChainError> {
         let provider = self.get_provider().await
-            .map_err(|e| ChainError::Connection(e.to_string()))?;
+            .map_err(|e| ChainError::ConnectionError(e.to_string()))?;
         
         // Tạo context cho retry
         let context = RetryContext::new(
@@ -727,17 +749,17 @@ impl ChainAdapter for EVMChainAdapter {
         let filter_clone = filter.clone();
         self.retry_policy.retry(
             || async {
-                provider.provider.get_logs(&filter_clone)
+                provider.reader().get_logs(&filter_clone)
                     .await
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))
+        ).await.map_err(ChainError::from_anyhow)
     }
     
     async fn get_transaction_count(&self, address: Address, block: Option<BlockId>) -> Result<U256, ChainError> {
         let provider = self.get_provider().await
-            .map_err(|e| ChainError::Connection(e.to_string()))?;
+            .map_err(|e| ChainError::ConnectionError(e.to_string()))?;
         
         // Tạo context cho retry
         let context = RetryContext::new(
@@ -750,17 +772,17 @@ impl ChainAdapter for EVMChainAdapter {
         // Thực hiện lấy nonce với retry
         self.retry_policy.retry(
             || async {
-                provider.provider.get_transaction_count(address, block)
+                provider.reader().get_transaction_count(address, block)
                     .await
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))
+        ).await.map_err(ChainError::from_anyhow)
     }
     
     async fn estimate_gas(&self, tx: &TransactionRequest) -> Result<U256, ChainError> {
         let provider = self.get_provider().await
-            .map_err(|e| ChainError::Connection(e.to_string()))?;
+            .map_err(|e| ChainError::ConnectionError(e.to_string()))?;
         
         // Tạo context cho retry
         let context = RetryContext::new(
@@ -771,20 +793,20 @@ impl ChainAdapter for EVMChainAdapter {
         );
         
         // Thực hiện ước tính gas với retry
-        let tx_clone = tx.clone();
+        let tx_clone: TypedTransaction = tx.clone().into();
         self.retry_policy.retry(
             || async {
-                provider.provider.estimate_gas(&tx_clone)
+                provider.reader().estimate_gas(&tx_clone, None)
                     .await
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))
+        ).await.map_err(ChainError::from_anyhow)
     }
     
     async fn call(&self, tx: &TransactionRequest, block: Option<BlockId>) -> Result<Bytes, ChainError> {
         let provider = self.get_provider().await
-            .map_err(|e| ChainError::Connection(e.to_string()))?;
+            .map_err(|e| ChainError::ConnectionError(e.to_string()))?;
         
         // Tạo context cho retry
         let context = RetryContext::new(
@@ -795,16 +817,16 @@ impl ChainAdapter for EVMChainAdapter {
         );
         
         // Thực hiện call với retry
-        let tx_clone = tx.clone();
+        let tx_clone: TypedTransaction = tx.clone().into();
         let block_clone = block;
         self.retry_policy.retry(
             || async {
-                provider.provider.call(&tx_clone, block_clone)
+                provider.reader().call(&tx_clone, block_clone)
                     .await
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))
+        ).await.map_err(ChainError::from_anyhow)
     }
     
     async fn wait_for_transaction_receipt(
@@ -817,7 +839,7 @@ impl ChainAdapter for EVMChainAdapter {
             .map_err(|e: anyhow::Error| ChainError::from_anyhow(e))?;
         
         // Tạo future từ PendingTransaction
-        let pending_tx = PendingTransaction::new(tx_hash, provider.provider.clone());
+        let pending_tx = PendingTransaction::new(tx_hash, &provider.provider);
         
         // Sử dụng tokio timeout
         let receipt_result = tokio::time::timeout(
@@ -843,7 +865,7 @@ impl ChainAdapter for EVMChainAdapter {
     
     async fn get_node_info(&self) -> Result<NodeInfo, ChainError> {
         let provider = self.get_provider().await
-            .map_err(|e| ChainError::Connection(e.to_string()))?;
+            .map_err(|e| ChainError::ConnectionError(e.to_string()))?;
         
         // Tạo context cho retry
         let context = RetryContext::new(
@@ -853,36 +875,43 @@ impl ChainAdapter for EVMChainAdapter {
             None,
         );
         
-        // Lấy thông tin client
-        let client_version = self.retry_policy.retry(
+        // Ba request chạy đồng thời để được gom vào một JSON-RPC batch
+        let client_version_future = self.retry_policy.retry(
             || async {
-                provider.provider.client_version()
+                provider.reader().client_version()
                     .await
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))?;
+        );
         
-        // Lấy thông tin syncing
-        let syncing = self.retry_policy.retry(
+        let syncing_future = self.retry_policy.retry(
             || async {
-                provider.provider.syncing()
+                provider.reader().syncing()
                     .await
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))?;
+        );
         
-        // Lấy số block hiện tại
-        let current_block = self.retry_policy.retry(
+        let current_block_future = self.retry_policy.retry(
             || async {
-                provider.provider.get_block_number()
+                provider.reader().get_block_number()
                     .await
                     .map(|n| n.as_u64())
                     .map_err(|e| anyhow!(e))
             },
             &context
-        ).await.map_err(|e| ChainError::from_anyhow(e))?;
+        );
+        
+        let (client_version, syncing, current_block) = tokio::join!(
+            client_version_future,
+            syncing_future,
+            current_block_future
+        );
+        let client_version = client_version.map_err(ChainError::from_anyhow)?;
+        let syncing = syncing.map_err(ChainError::from_anyhow)?;
+        let current_block = current_block.map_err(ChainError::from_anyhow)?;
         
         // Xác định highest block từ thông tin syncing
         let (is_syncing, highest_block) = match syncing {
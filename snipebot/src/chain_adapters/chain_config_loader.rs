@@ -0,0 +1,514 @@
+// Standard library imports
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+// Internal imports
+use crate::chain_adapters::{
+    block_tracker::{self, BlockTrackerConfig},
+    chain_registry::{self, AdapterStatus, ChainConfig},
+    connection_pool,
+};
+use crate::event_bus::EventBus;
+
+// Third party imports
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+/// Biến môi trường chỉ định thư mục cấu hình chain
+pub const CHAIN_CONFIG_DIR_ENV: &str = "CHAIN_CONFIG_DIR";
+
+/// Thư mục cấu hình chain mặc định
+pub const DEFAULT_CHAIN_CONFIG_DIR: &str = "config/chains";
+
+/// Phần mở rộng của file cấu hình chain
+const CHAIN_CONFIG_EXTENSION: &str = "toml";
+
+/// Cấu hình chain đóng gói sẵn, dùng khi không tìm thấy thư mục cấu hình
+const BUNDLED_CHAIN_CONFIGS: &[(&str, &str)] = &[
+    ("ethereum", include_str!("../../config/chains/ethereum.toml")),
+    ("bsc", include_str!("../../config/chains/bsc.toml")),
+    ("avalanche", include_str!("../../config/chains/avalanche.toml")),
+    ("base", include_str!("../../config/chains/base.toml")),
+    ("arbitrum", include_str!("../../config/chains/arbitrum.toml")),
+    ("optimism", include_str!("../../config/chains/optimism.toml")),
+    ("polygon", include_str!("../../config/chains/polygon.toml")),
+    ("monad", include_str!("../../config/chains/monad.toml")),
+    ("solana", include_str!("../../config/chains/solana.toml")),
+];
+
+/// Một cấu hình chain đã nạp, kèm nguồn gốc
+#[derive(Debug, Clone)]
+pub struct ChainConfigSource {
+    /// Key của chain (tên file không có phần mở rộng, VD: "ethereum")
+    pub key: String,
+    /// Đường dẫn file (None nếu là cấu hình đóng gói sẵn)
+    pub path: Option<PathBuf>,
+    /// Cấu hình chain
+    pub config: ChainConfig,
+}
+
+/// Kết quả đọc các file cấu hình chain
+#[derive(Debug, Default)]
+pub struct ChainConfigSet {
+    /// Các cấu hình hợp lệ
+    pub chains: Vec<ChainConfigSource>,
+    /// Key của các file không hợp lệ
+    pub failed_keys: Vec<String>,
+    /// Lỗi đọc/kiểm tra từng file
+    pub errors: Vec<String>,
+}
+
+/// Kết quả nạp lại cấu hình chain
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChainReloadReport {
+    /// Chain mới được thêm
+    pub added: Vec<u64>,
+    /// Chain có cấu hình thay đổi
+    pub updated: Vec<u64>,
+    /// Chain bị gỡ do file cấu hình bị xoá
+    pub removed: Vec<u64>,
+    /// Chain không thay đổi
+    pub unchanged: Vec<u64>,
+    /// Lỗi gặp phải (file không hợp lệ được giữ nguyên cấu hình cũ)
+    pub errors: Vec<String>,
+}
+
+/// Thông tin tóm tắt một chain cho API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainSummary {
+    /// Chain ID
+    pub chain_id: u64,
+    /// Key của chain
+    pub key: Option<String>,
+    /// Tên chain
+    pub name: String,
+    /// Loại chain
+    pub chain_type: String,
+    /// Symbol của native token
+    pub native_token_symbol: String,
+    /// Số RPC endpoint đã cấu hình
+    pub rpc_endpoints: usize,
+    /// File cấu hình (None nếu đóng gói sẵn hoặc thêm trực tiếp)
+    pub source: Option<String>,
+    /// Trạng thái adapter (None nếu chưa khởi tạo)
+    pub adapter_status: Option<AdapterStatus>,
+}
+
+/// Các chain đã nạp từ file, theo chain ID
+static LOADED_CHAINS: Lazy<RwLock<HashMap<u64, ChainConfigSource>>> = Lazy::new(|| {
+    RwLock::new(HashMap::new())
+});
+
+/// EventBus của ứng dụng, chuyển cho các block tracker khởi chạy khi nạp chain
+static EVENT_BUS: Lazy<RwLock<Option<Arc<EventBus>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Đặt EventBus cho các block tracker (gọi trước khi nạp cấu hình chain)
+pub fn set_event_bus(bus: Arc<EventBus>) {
+    *EVENT_BUS.write().unwrap_or_else(|e| e.into_inner()) = Some(bus);
+}
+
+fn event_bus() -> Option<Arc<EventBus>> {
+    EVENT_BUS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Thư mục cấu hình chain (biến môi trường `CHAIN_CONFIG_DIR` hoặc mặc định)
+pub fn chain_config_dir() -> PathBuf {
+    std::env::var(CHAIN_CONFIG_DIR_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_CHAIN_CONFIG_DIR))
+}
+
+/// Parse và kiểm tra một cấu hình chain dạng TOML
+pub fn parse_chain_config(content: &str) -> Result<ChainConfig> {
+    let config: ChainConfig = toml::from_str(content)
+        .map_err(|e| anyhow!("Failed to parse chain config: {}", e))?;
+    config.validate()?;
+    Ok(config)
+}
+
+/// Đọc một file cấu hình chain
+pub fn load_chain_config_file(path: &Path) -> Result<ChainConfig> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read chain config {}", path.display()))?;
+    parse_chain_config(&content).with_context(|| format!("Invalid chain config {}", path.display()))
+}
+
+/// Đọc tất cả file `*.toml` trong thư mục cấu hình.
+/// File lỗi được ghi vào `errors` thay vì làm hỏng toàn bộ lần nạp.
+/// Nếu thư mục không tồn tại, dùng cấu hình đóng gói sẵn.
+pub fn load_chain_configs(dir: &Path) -> Result<ChainConfigSet> {
+    if !dir.exists() {
+        warn!("Chain config directory {} not found, using bundled chain configs", dir.display());
+        return Ok(bundled_chain_configs());
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read chain config directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == CHAIN_CONFIG_EXTENSION))
+        .collect();
+    paths.sort();
+
+    let mut set = ChainConfigSet::default();
+    for path in paths {
+        let key = chain_key(&path);
+        match load_chain_config_file(&path) {
+            Ok(config) => set.push(key, Some(path), config),
+            Err(e) => {
+                set.errors.push(format!("{:#}", e));
+                set.failed_keys.push(key);
+            }
+        }
+    }
+
+    Ok(set)
+}
+
+/// Cấu hình chain đóng gói sẵn
+pub fn bundled_chain_configs() -> ChainConfigSet {
+    let mut set = ChainConfigSet::default();
+    for (key, content) in BUNDLED_CHAIN_CONFIGS {
+        match parse_chain_config(content) {
+            Ok(config) => set.push(key.to_string(), None, config),
+            Err(e) => {
+                set.errors.push(format!("Bundled chain config {}: {:#}", key, e));
+                set.failed_keys.push(key.to_string());
+            }
+        }
+    }
+    set
+}
+
+impl ChainConfigSet {
+    /// Thêm cấu hình, từ chối chain ID trùng lặp giữa các file
+    fn push(&mut self, key: String, path: Option<PathBuf>, config: ChainConfig) {
+        if let Some(existing) = self.chains.iter().find(|source| source.config.chain_id == config.chain_id) {
+            self.errors.push(format!(
+                "Chain config '{}' duplicates chain ID {} already defined by '{}'",
+                key, config.chain_id, existing.key
+            ));
+            self.failed_keys.push(key);
+            return;
+        }
+        self.chains.push(ChainConfigSource { key, path, config });
+    }
+}
+
+/// Key của chain từ tên file
+fn chain_key(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// So sánh hai cấu hình theo nội dung serialize
+fn same_config(a: &ChainConfig, b: &ChainConfig) -> bool {
+    match (serde_json::to_value(a), serde_json::to_value(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Tính thay đổi giữa các chain đang nạp và tập cấu hình mới.
+/// Chain có file lỗi được giữ nguyên thay vì bị gỡ.
+pub fn plan_reload(current: &HashMap<u64, ChainConfigSource>, set: &ChainConfigSet) -> ChainReloadReport {
+    let mut report = ChainReloadReport {
+        errors: set.errors.clone(),
+        ..Default::default()
+    };
+
+    for source in &set.chains {
+        match current.get(&source.config.chain_id) {
+            None => report.added.push(source.config.chain_id),
+            Some(existing) if same_config(&existing.config, &source.config) => report.unchanged.push(source.config.chain_id),
+            Some(_) => report.updated.push(source.config.chain_id),
+        }
+    }
+
+    let new_ids: HashSet<u64> = set.chains.iter().map(|source| source.config.chain_id).collect();
+    for (chain_id, existing) in current {
+        if new_ids.contains(chain_id) {
+            continue;
+        }
+        if set.failed_keys.contains(&existing.key) {
+            report.unchanged.push(*chain_id);
+        } else {
+            report.removed.push(*chain_id);
+        }
+    }
+
+    report.added.sort_unstable();
+    report.updated.sort_unstable();
+    report.removed.sort_unstable();
+    report.unchanged.sort_unstable();
+    report
+}
+
+/// Gỡ adapter, watcher, connection pool và cấu hình của một chain
+fn remove_chain(chain_id: u64) -> Result<()> {
+    {
+        let mut registry = chain_registry::get_registry_mut();
+        if registry.get_watcher(chain_id)?.is_some() {
+            registry.unregister_watcher(chain_id)?;
+        }
+        if registry.get_adapter(chain_id).is_ok() {
+            registry.unregister_adapter(chain_id)?;
+        }
+        registry.remove_chain_config(chain_id)?;
+    }
+
+    connection_pool::remove_pool(chain_id);
+    Ok(())
+}
+
+/// Khởi động theo dõi block (phát hiện reorg, số block xác nhận) cho chain EVM
+async fn start_chain_block_tracker(config: &ChainConfig) -> Result<()> {
+    if !config.is_evm() {
+        return Ok(());
+    }
+    let provider = chain_registry::get_http_provider(config.chain_id)?;
+    block_tracker::start_block_tracker(
+        config.chain_id,
+        Arc::new(provider),
+        BlockTrackerConfig::for_block_time(config.avg_block_time)
+            .with_confirmation_depth(config.confirmation_depth),
+        event_bus(),
+    ).await;
+    Ok(())
+}
+
+/// Thêm cấu hình vào registry và khởi tạo adapter.
+/// Chain đã tồn tại được gỡ adapter và pool cũ trước để áp dụng RPC/tham số mới.
+async fn apply_chain(source: &ChainConfigSource, replace: bool) -> Result<()> {
+    let chain_id = source.config.chain_id;
+
+    if replace {
+        {
+            let mut registry = chain_registry::get_registry_mut();
+            if registry.get_adapter(chain_id).is_ok() {
+                registry.unregister_adapter(chain_id)?;
+            }
+        }
+        connection_pool::remove_pool(chain_id);
+        block_tracker::stop_block_tracker(chain_id).await;
+    }
+
+    chain_registry::get_registry_mut().add_chain_config(source.config.clone())?;
+    chain_registry::init_chain_adapter(chain_id).await?;
+    start_chain_block_tracker(&source.config).await
+}
+
+/// Nạp lại thư mục cấu hình chain: thêm chain mới, áp dụng thay đổi và gỡ chain có file bị xoá
+pub async fn reload_chain_configs(dir: &Path) -> Result<ChainReloadReport> {
+    let set = load_chain_configs(dir)?;
+    let mut report = {
+        let current = LOADED_CHAINS.read().map_err(|e| anyhow!("Chain config lock poisoned: {}", e))?;
+        plan_reload(&current, &set)
+    };
+
+    for chain_id in report.removed.clone() {
+        block_tracker::stop_block_tracker(chain_id).await;
+        match remove_chain(chain_id) {
+            Ok(()) => info!("Removed chain {} (config file deleted)", chain_id),
+            Err(e) => report.errors.push(format!("Failed to remove chain {}: {}", chain_id, e)),
+        }
+        if let Ok(mut loaded) = LOADED_CHAINS.write() {
+            loaded.remove(&chain_id);
+        }
+    }
+
+    for source in &set.chains {
+        let chain_id = source.config.chain_id;
+        let replace = report.updated.contains(&chain_id);
+        if !replace && !report.added.contains(&chain_id) {
+            continue;
+        }
+
+        match apply_chain(source, replace).await {
+            Ok(()) => info!("{} chain {} ({}) from {}", if replace { "Updated" } else { "Added" }, chain_id, source.config.name, source.key),
+            Err(e) => {
+                error!("Failed to apply chain config {}: {}", source.key, e);
+                report.errors.push(format!("Failed to apply chain config '{}': {}", source.key, e));
+            }
+        }
+        // Ghi nhận cả khi khởi tạo adapter lỗi: cấu hình đã vào registry và lần nạp sau sẽ so sánh đúng
+        if let Ok(mut loaded) = LOADED_CHAINS.write() {
+            loaded.insert(chain_id, source.clone());
+        }
+    }
+
+    for error in &report.errors {
+        warn!("Chain config reload: {}", error);
+    }
+    info!(
+        "Chain config reload: {} added, {} updated, {} removed, {} unchanged",
+        report.added.len(), report.updated.len(), report.removed.len(), report.unchanged.len()
+    );
+    Ok(report)
+}
+
+/// Nạp cấu hình chain từ thư mục mặc định
+pub async fn reload_default_chain_configs() -> Result<ChainReloadReport> {
+    reload_chain_configs(&chain_config_dir()).await
+}
+
+/// Danh sách chain đang có trong registry
+pub fn list_chains() -> Vec<ChainSummary> {
+    let loaded = LOADED_CHAINS.read().map(|loaded| loaded.clone()).unwrap_or_default();
+    let registry = chain_registry::get_registry();
+
+    let mut chains: Vec<ChainSummary> = registry
+        .get_all_chain_configs()
+        .into_iter()
+        .map(|config| {
+            let source = loaded.get(&config.chain_id);
+            ChainSummary {
+                chain_id: config.chain_id,
+                key: source.map(|source| source.key.clone()),
+                name: config.name.clone(),
+                chain_type: config.chain_type.clone(),
+                native_token_symbol: config.native_token_symbol.clone(),
+                rpc_endpoints: config.primary_rpc_urls.len() + config.backup_rpc_urls.len(),
+                source: source.and_then(|source| source.path.as_ref()).map(|path| path.display().to_string()),
+                adapter_status: registry.get_adapter_info(config.chain_id).ok().map(|info| info.status),
+            }
+        })
+        .collect();
+
+    chains.sort_by_key(|chain| chain.chain_id);
+    chains
+}
+
+/// Tìm chain ID theo key (tên file) hoặc tên chain, không phân biệt hoa thường
+pub fn find_chain_id(name: &str) -> Option<u64> {
+    let name = name.to_lowercase();
+    if let Some(source) = LOADED_CHAINS.read().ok()?.values().find(|source| source.key == name) {
+        return Some(source.config.chain_id);
+    }
+    chain_registry::get_all_chain_configs()
+        .into_iter()
+        .find(|config| config.name.to_lowercase() == name)
+        .map(|config| config.chain_id)
+}
+
+/// Key của các chain đã nạp
+pub fn loaded_chain_keys() -> Vec<String> {
+    let mut keys: Vec<String> = LOADED_CHAINS
+        .read()
+        .map(|loaded| loaded.values().map(|source| source.key.clone()).collect())
+        .unwrap_or_default();
+    keys.sort();
+    keys
+}
+
+/// Dấu vân tay của thư mục (tên file, thời gian sửa, kích thước) để phát hiện thay đổi
+fn dir_fingerprint(dir: &Path) -> Vec<(PathBuf, Option<SystemTime>, u64)> {
+    let mut entries: Vec<(PathBuf, Option<SystemTime>, u64)> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().extension().is_some_and(|ext| ext == CHAIN_CONFIG_EXTENSION))
+                .map(|entry| {
+                    let metadata = entry.metadata().ok();
+                    (
+                        entry.path(),
+                        metadata.as_ref().and_then(|m| m.modified().ok()),
+                        metadata.map(|m| m.len()).unwrap_or(0),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+/// Theo dõi thư mục cấu hình và tự nạp lại khi có file được thêm, sửa hoặc xoá
+pub fn spawn_chain_config_watcher(dir: PathBuf, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut last = dir_fingerprint(&dir);
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let current = dir_fingerprint(&dir);
+            if current == last {
+                continue;
+            }
+            last = current;
+
+            debug!("Chain config directory {} changed, reloading", dir.display());
+            if let Err(e) = reload_chain_configs(&dir).await {
+                error!("Failed to reload chain configs from {}: {}", dir.display(), e);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_chain_configs_are_valid() {
+        let set = bundled_chain_configs();
+        assert!(set.errors.is_empty(), "{:?}", set.errors);
+        assert_eq!(set.chains.len(), BUNDLED_CHAIN_CONFIGS.len());
+
+        let avalanche = set.chains.iter().find(|source| source.key == "avalanche").unwrap();
+        let adapter_config = avalanche.config.to_adapter_config();
+        assert_eq!(adapter_config.chain_id, 43114);
+        assert_eq!(adapter_config.eth_to_token_swap_fn, "swapExactAVAXForTokens");
+        assert_eq!(adapter_config.block_time, 2000);
+        assert!(adapter_config.router_address.starts_with("0x"));
+    }
+
+    #[test]
+    fn test_validation_reports_all_issues() {
+        let mut config = parse_chain_config(BUNDLED_CHAIN_CONFIGS[0].1).unwrap();
+        config.primary_rpc_urls = vec!["ftp://node.example".to_string()];
+        config.gas_config.safe_gas_price_multiplier = 0.5;
+        config.chain_type = "cosmos".to_string();
+
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("unsupported scheme"));
+        assert!(message.contains("safe_gas_price_multiplier"));
+        assert!(message.contains("chain_type"));
+
+        assert!(parse_chain_config("chain_id = 1\nname = \"x\"").is_err());
+    }
+
+    #[test]
+    fn test_plan_reload_keeps_chains_with_broken_files() {
+        let bundled = bundled_chain_configs();
+        let current: HashMap<u64, ChainConfigSource> = bundled
+            .chains
+            .iter()
+            .take(3)
+            .map(|source| (source.config.chain_id, source.clone()))
+            .collect();
+
+        // File thứ nhất bị sửa, file thứ hai bị lỗi, file thứ ba bị xoá, thêm một chain mới
+        let mut changed = bundled.chains[0].clone();
+        changed.config.avg_block_time = 6.0;
+        let set = ChainConfigSet {
+            chains: vec![changed, bundled.chains[3].clone()],
+            failed_keys: vec![bundled.chains[1].key.clone()],
+            errors: vec!["broken".to_string()],
+        };
+
+        let report = plan_reload(&current, &set);
+        assert_eq!(report.updated, vec![bundled.chains[0].config.chain_id]);
+        assert_eq!(report.added, vec![bundled.chains[3].config.chain_id]);
+        assert_eq!(report.unchanged, vec![bundled.chains[1].config.chain_id]);
+        assert_eq!(report.removed, vec![bundled.chains[2].config.chain_id]);
+        assert_eq!(report.errors.len(), 1);
+    }
+}
@@ -2,23 +2,20 @@ use ethers::types::{Address, U256};
 use anyhow::{Result, anyhow};
 use std::sync::{Arc, RwLock};
 use std::collections::HashMap;
-use tracing::info;
+use tracing::{info, warn};
 use crate::chain_adapters::ChainAdapter;
 use ethers::prelude::*;
 use crate::chain_adapters::{
-    ChainWatcher, 
     ChainError,
     retry_policy::{RetryPolicyEnum, create_default_retry_policy},
     connection_pool::{get_or_create_pool, ConnectionPoolConfig},
-    base::ChainConfig,
-    trait_adapter::{ChainAdapter as TraitChainAdapter, ChainWatcherEnum},
+    l2_fee::L1FeeModel,
+    treasury::ChainTreasuryConfig,
+    trait_adapter::ChainWatcherEnum,
 };
 use once_cell::sync::Lazy;
 use serde::{Serialize, Deserialize};
 use async_trait::async_trait;
-use crate::chain_adapters::trait_adapter::RetryPolicy;
-use crate::chain_adapters::retry_policy::RetryPolicyEnum;
-use crate::chain_adapters::trait_adapter::{ChainWatcher, ChainWatcherEnum};
 
 /// Trạng thái của một adapter
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -56,7 +53,17 @@ pub struct ChainAdapterInfo {
     pub average_response_time: f64,
 }
 
-/// Cấu hình cho từng chain cụ thể
+/// Swap mặc định native -> token trên router Uniswap V2
+const DEFAULT_ETH_TO_TOKEN_SWAP_FN: &str = "swapExactETHForTokens";
+
+/// Swap mặc định token -> native trên router Uniswap V2
+const DEFAULT_TOKEN_TO_ETH_SWAP_FN: &str = "swapExactTokensForETH";
+
+/// Các loại chain được hỗ trợ
+const SUPPORTED_CHAIN_TYPES: &[&str] = &["evm", "non-evm"];
+
+/// Cấu hình cho từng chain cụ thể.
+/// Được nạp từ thư mục file TOML (xem `chain_config_loader`), mỗi file một chain.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChainConfig {
     /// Chain ID
@@ -71,24 +78,214 @@ pub struct ChainConfig {
     pub native_token_decimals: u8,
     /// Block time trung bình (seconds)
     pub avg_block_time: f64,
+    /// Số block xác nhận trước khi coi giao dịch là cuối cùng
+    #[serde(default = "default_confirmation_depth")]
+    pub confirmation_depth: u64,
     /// Loại chain (EVM, Substrate, etc.)
     pub chain_type: String,
     /// Các RPC endpoint chính
     pub primary_rpc_urls: Vec<String>,
     /// Các RPC endpoint backup
+    #[serde(default)]
     pub backup_rpc_urls: Vec<String>,
     /// Block explorer URL
+    #[serde(default)]
     pub explorer_url: String,
+    /// Địa chỉ wrapped native token (WETH, WBNB...)
+    #[serde(default)]
+    pub wrapped_native_token: Option<Address>,
+    /// Tên hàm swap native -> token của router (mặc định swapExactETHForTokens)
+    #[serde(default)]
+    pub eth_to_token_swap_fn: Option<String>,
+    /// Tên hàm swap token -> native của router (mặc định swapExactTokensForETH)
+    #[serde(default)]
+    pub token_to_eth_swap_fn: Option<String>,
     /// Router contracts
+    #[serde(default)]
     pub router_contracts: HashMap<String, Address>,
     /// Factory contracts
+    #[serde(default)]
     pub factory_contracts: HashMap<String, Address>,
     /// Cấu hình gas
+    #[serde(default)]
     pub gas_config: GasConfig,
     /// Cấu hình connection pool
+    #[serde(default)]
     pub connection_pool_config: Option<ConnectionPoolConfig>,
     /// Danh sách token phổ biến
+    #[serde(default)]
     pub common_tokens: HashMap<String, TokenInfo>,
+    /// Capability bổ sung của chain (L1 fee model, gas station...)
+    #[serde(default)]
+    pub capabilities: ChainCapabilityConfig,
+    /// Cấu hình chia/gom quỹ (hợp đồng disperse, ngưỡng gas)
+    #[serde(default)]
+    pub treasury: ChainTreasuryConfig,
+}
+
+/// Số block xác nhận khi file cấu hình chain không khai báo `confirmation_depth`
+fn default_confirmation_depth() -> u64 {
+    6
+}
+
+impl ChainConfig {
+    /// Kiểm tra cấu hình theo schema; trả về lỗi liệt kê mọi vấn đề tìm thấy
+    pub fn validate(&self) -> Result<()> {
+        let mut issues = Vec::new();
+        
+        if self.chain_id == 0 {
+            issues.push("chain_id must be greater than 0".to_string());
+        }
+        if self.name.trim().is_empty() {
+            issues.push("name must not be empty".to_string());
+        }
+        if !SUPPORTED_CHAIN_TYPES.contains(&self.chain_type.to_lowercase().as_str()) {
+            issues.push(format!("chain_type '{}' is not one of {:?}", self.chain_type, SUPPORTED_CHAIN_TYPES));
+        }
+        if self.native_token_symbol.trim().is_empty() {
+            issues.push("native_token_symbol must not be empty".to_string());
+        }
+        if self.native_token_decimals > 36 {
+            issues.push(format!("native_token_decimals {} is out of range (0-36)", self.native_token_decimals));
+        }
+        if !self.avg_block_time.is_finite() || self.avg_block_time <= 0.0 {
+            issues.push(format!("avg_block_time {} must be a positive number of seconds", self.avg_block_time));
+        }
+        if self.confirmation_depth == 0 {
+            issues.push("confirmation_depth must be at least 1 block".to_string());
+        }
+        
+        // RPC endpoints
+        if self.primary_rpc_urls.is_empty() {
+            issues.push("primary_rpc_urls must contain at least one endpoint".to_string());
+        }
+        let mut seen_urls = std::collections::HashSet::new();
+        for rpc_url in self.primary_rpc_urls.iter().chain(self.backup_rpc_urls.iter()) {
+            match url::Url::parse(rpc_url) {
+                Ok(parsed) if ["http", "https", "ws", "wss"].contains(&parsed.scheme()) => {},
+                Ok(parsed) => issues.push(format!("RPC URL {} has unsupported scheme '{}'", rpc_url, parsed.scheme())),
+                Err(e) => issues.push(format!("RPC URL {} is invalid: {}", rpc_url, e)),
+            }
+            if !seen_urls.insert(rpc_url.as_str()) {
+                issues.push(format!("RPC URL {} is listed more than once", rpc_url));
+            }
+        }
+        if !self.explorer_url.is_empty() && url::Url::parse(&self.explorer_url).is_err() {
+            issues.push(format!("explorer_url {} is invalid", self.explorer_url));
+        }
+        
+        // Contracts
+        for (kind, contracts) in [("router", &self.router_contracts), ("factory", &self.factory_contracts)] {
+            for (name, address) in contracts {
+                if address.is_zero() {
+                    issues.push(format!("{} contract '{}' has zero address", kind, name));
+                }
+            }
+        }
+        if self.wrapped_native_token.is_some_and(|address| address.is_zero()) {
+            issues.push("wrapped_native_token has zero address".to_string());
+        }
+        
+        // Gas
+        let gas = &self.gas_config;
+        let non_negative = |value: f64| value.is_finite() && value >= 0.0;
+        if gas.default_gas_limit < 21000 {
+            issues.push(format!("gas_config.default_gas_limit {} is below 21000", gas.default_gas_limit));
+        }
+        if !non_negative(gas.base_fee) || !non_negative(gas.priority_fee) {
+            issues.push("gas_config.base_fee and gas_config.priority_fee must not be negative".to_string());
+        }
+        if !non_negative(gas.safe_gas_price_multiplier) || gas.safe_gas_price_multiplier < 1.0 {
+            issues.push(format!("gas_config.safe_gas_price_multiplier {} must be at least 1.0", gas.safe_gas_price_multiplier));
+        }
+        if !non_negative(gas.max_gas_price) || gas.max_gas_price == 0.0 || gas.max_gas_price < gas.base_fee {
+            issues.push(format!("gas_config.max_gas_price {} must be positive and not below base_fee", gas.max_gas_price));
+        }
+        
+        // Capabilities
+        if let Some(gas_station_url) = &self.capabilities.gas_station_url {
+            match url::Url::parse(gas_station_url) {
+                Ok(parsed) if ["http", "https"].contains(&parsed.scheme()) => {},
+                _ => issues.push(format!("capabilities.gas_station_url {} must be an http(s) URL", gas_station_url)),
+            }
+        }
+        
+        // Treasury
+        let treasury = &self.treasury;
+        if treasury.gas_floor.is_some_and(|floor| !non_negative(floor)) || treasury.gas_target.is_some_and(|target| !non_negative(target)) {
+            issues.push("treasury.gas_floor and treasury.gas_target must not be negative".to_string());
+        }
+        if let (Some(floor), Some(target)) = (treasury.gas_floor, treasury.gas_target) {
+            if target < floor {
+                issues.push(format!("treasury.gas_target {} must not be below gas_floor {}", target, floor));
+            }
+        }
+        if treasury.disperse_contract.is_some_and(|contract| contract.is_zero()) {
+            issues.push("treasury.disperse_contract must not be the zero address".to_string());
+        }
+        
+        // Tokens
+        for (key, token) in &self.common_tokens {
+            if token.symbol.trim().is_empty() {
+                issues.push(format!("common token '{}' has empty symbol", key));
+            }
+            if token.address.is_zero() {
+                issues.push(format!("common token '{}' has zero address", key));
+            }
+            if token.decimals > 36 {
+                issues.push(format!("common token '{}' decimals {} is out of range (0-36)", key, token.decimals));
+            }
+        }
+        
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Invalid configuration for chain '{}' (chain ID {}): {}",
+                self.name, self.chain_id, issues.join("; ")
+            ))
+        }
+    }
+
+    /// Chain tương thích EVM (chain_type = "EVM")
+    pub fn is_evm(&self) -> bool {
+        self.chain_type.eq_ignore_ascii_case("evm")
+    }
+
+    /// Chuyển sang cấu hình rút gọn dùng bởi các adapter EVM (`base::ChainConfig`).
+    /// Router/factory mặc định là contract có key nhỏ nhất theo thứ tự chữ cái.
+    pub fn to_adapter_config(&self) -> crate::chain_adapters::base::ChainConfig {
+        let first_contract = |contracts: &HashMap<String, Address>| {
+            let mut names: Vec<&String> = contracts.keys().collect();
+            names.sort();
+            names.first()
+                .and_then(|name| contracts.get(*name))
+                .map(|address| format!("{:?}", address))
+                .unwrap_or_default()
+        };
+        
+        crate::chain_adapters::base::ChainConfig {
+            name: self.name.clone(),
+            chain_id: self.chain_id,
+            rpc_url: self.primary_rpc_urls.first().cloned().unwrap_or_default(),
+            native_symbol: self.native_token_symbol.clone(),
+            wrapped_native_token: self.wrapped_native_token
+                .map(|address| format!("{:?}", address))
+                .unwrap_or_default(),
+            router_address: first_contract(&self.router_contracts),
+            factory_address: first_contract(&self.factory_contracts),
+            explorer_url: self.explorer_url.clone(),
+            block_time: (self.avg_block_time * 1000.0) as u64,
+            default_gas_limit: self.gas_config.default_gas_limit,
+            default_gas_price: self.gas_config.base_fee,
+            eip1559_supported: self.gas_config.supports_eip1559,
+            max_priority_fee: self.gas_config.supports_eip1559.then_some(self.gas_config.priority_fee),
+            eth_to_token_swap_fn: self.eth_to_token_swap_fn.clone()
+                .unwrap_or_else(|| DEFAULT_ETH_TO_TOKEN_SWAP_FN.to_string()),
+            token_to_eth_swap_fn: self.token_to_eth_swap_fn.clone()
+                .unwrap_or_else(|| DEFAULT_TOKEN_TO_ETH_SWAP_FN.to_string()),
+        }
+    }
 }
 
 /// Thông tin token
@@ -136,6 +333,21 @@ impl Default for GasConfig {
     }
 }
 
+/// Capability khai báo trong file cấu hình chain. Trường bỏ trống sẽ được suy ra
+/// từ chain ID (xem `generic_evm::EvmCapabilities::from_config`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChainCapabilityConfig {
+    /// Mô hình phí dữ liệu L1 ("None", "OpStack", "Arbitrum")
+    #[serde(default)]
+    pub l1_fee_model: Option<L1FeeModel>,
+    /// URL gas station riêng của chain (VD: Polygon gas station)
+    #[serde(default)]
+    pub gas_station_url: Option<String>,
+    /// Chain có relay Flashbots để gửi bundle
+    #[serde(default)]
+    pub flashbots: bool,
+}
+
 /// Registry quản lý tất cả chain adapter
 pub struct ChainRegistry {
     /// Map từ chain ID tới adapter
@@ -150,6 +362,12 @@ pub struct ChainRegistry {
     default_retry_policy: RetryPolicyEnum,
 }
 
+impl Default for ChainRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ChainRegistry {
     /// Tạo mới registry
     pub fn new() -> Self {
@@ -182,7 +400,7 @@ impl ChainRegistry {
         let info = ChainAdapterInfo {
             chain_id,
             name: chain_name,
-            adapter_type,
+            adapter_type: adapter_type.clone(),
             status: AdapterStatus::Active,
             uptime: 0,
             successful_requests: 0,
@@ -215,8 +433,9 @@ impl ChainRegistry {
         Ok(())
     }
     
-    /// Thêm cấu hình chain mới
+    /// Thêm hoặc thay thế cấu hình chain (cấu hình được kiểm tra trước khi thêm)
     pub fn add_chain_config(&mut self, config: ChainConfig) -> Result<()> {
+        config.validate()?;
         let chain_id = config.chain_id;
         
         // Cập nhật thông tin adapter nếu đã tồn tại
@@ -250,6 +469,20 @@ impl ChainRegistry {
             .ok_or_else(|| anyhow!("No configuration found for chain ID {}", chain_id))
     }
     
+    /// Gỡ cấu hình chain; adapter và watcher phải được gỡ riêng
+    pub fn remove_chain_config(&mut self, chain_id: u64) -> Result<ChainConfig> {
+        let config = self.chain_configs.remove(&chain_id)
+            .ok_or_else(|| anyhow!("No configuration found for chain ID {}", chain_id))?;
+        
+        info!("Removed configuration for chain ID {}", chain_id);
+        Ok(config)
+    }
+    
+    /// Lấy tất cả cấu hình chain
+    pub fn get_all_chain_configs(&self) -> Vec<&ChainConfig> {
+        self.chain_configs.values().collect()
+    }
+    
     /// Lấy thông tin tất cả adapter
     pub fn get_all_adapter_info(&self) -> Vec<&ChainAdapterInfo> {
         self.adapter_info.values().collect()
@@ -338,22 +571,7 @@ impl ChainRegistry {
     /// Khởi tạo adapter từ cấu hình
     pub async fn init_adapter_from_config(&mut self, chain_id: u64) -> Result<()> {
         let config = self.get_chain_config(chain_id)?.clone();
-        
-        // Tạo connection pool
-        let pool = get_or_create_pool(
-            chain_id,
-            config.primary_rpc_urls.clone(),
-            config.backup_rpc_urls.clone(),
-            config.connection_pool_config.clone(),
-        ).await?;
-        
-        // Tạo adapter (triển khai cụ thể cần được thêm vào)
-        // Hiện tại chỉ là placeholder
-        let adapter: Arc<dyn ChainAdapter> = Arc::new(PlaceholderAdapter {
-            chain_id,
-            name: config.name.clone(),
-            config: config.clone(),
-        });
+        let adapter = build_adapter(&config).await?;
         
         // Đăng ký adapter
         self.register_adapter(chain_id, adapter)?;
@@ -362,6 +580,55 @@ impl ChainRegistry {
     }
 }
 
+/// Tạo connection pool và adapter cho một cấu hình chain
+async fn build_adapter(config: &ChainConfig) -> Result<Arc<dyn ChainAdapter>> {
+    let chain_id = config.chain_id;
+    
+    // Chain non-EVM không dùng connection pool EVM (health check gọi eth_blockNumber);
+    // giao dịch đi qua adapter riêng đăng ký trong registry adapter giao dịch
+    if !config.is_evm() {
+        if crate::chain_adapters::solana_adapter::is_solana_chain(config) {
+            crate::chain_adapters::solana_adapter::init_solana_trading_adapter(config)?;
+        } else {
+            warn!("No trading adapter for non-EVM chain {} ({})", config.name, chain_id);
+        }
+        return Ok(Arc::new(PlaceholderAdapter {
+            chain_id,
+            name: config.name.clone(),
+            config: config.to_adapter_config(),
+        }));
+    }
+    
+    // Tạo connection pool
+    let pool = get_or_create_pool(
+        chain_id,
+        config.primary_rpc_urls.clone(),
+        config.backup_rpc_urls.clone(),
+        config.connection_pool_config.clone(),
+    ).await?;
+    
+    // Ngưỡng lag block của endpoint suy ra từ block time của chain
+    pool.write()
+        .map_err(|e| anyhow!("Pool lock poisoned: {}", e))?
+        .set_avg_block_time(config.avg_block_time);
+    
+    // Tạo adapter (triển khai cụ thể cần được thêm vào)
+    // Hiện tại chỉ là placeholder
+    Ok(Arc::new(PlaceholderAdapter {
+        chain_id,
+        name: config.name.clone(),
+        config: config.to_adapter_config(),
+    }))
+}
+
+/// Khởi tạo và đăng ký adapter cho chain đã có cấu hình trong registry toàn cục.
+/// Không giữ lock registry trong lúc tạo connection pool.
+pub async fn init_chain_adapter(chain_id: u64) -> Result<()> {
+    let config = get_chain_config(chain_id)?;
+    let adapter = build_adapter(&config).await?;
+    get_registry_mut().register_adapter(chain_id, adapter)
+}
+
 /// Registry toàn cục
 static CHAIN_REGISTRY: Lazy<RwLock<ChainRegistry>> = Lazy::new(|| {
     RwLock::new(ChainRegistry::new())
@@ -377,20 +644,14 @@ pub fn get_registry_mut() -> std::sync::RwLockWriteGuard<'static, ChainRegistry>
     CHAIN_REGISTRY.write().unwrap()
 }
 
-/// Lấy adapter dựa trên chain id
-pub fn get_adapter(chain_id: u64) -> Result<Box<PlaceholderAdapter>> {
+/// Lấy adapter đã đăng ký trong registry toàn cục theo chain id
+pub fn get_adapter(chain_id: u64) -> Result<Arc<dyn ChainAdapter>> {
     // Kiểm tra chain ID
     if chain_id == 0 {
         return Err(anyhow!("Chain ID không hợp lệ"));
     }
     
-    // Lấy cấu hình mặc định
-    let config = get_chain_config(chain_id)?;
-    
-    // Tạo adapter
-    let adapter = PlaceholderAdapter::new(config)?;
-    
-    Ok(Box::new(adapter))
+    get_registry().get_adapter(chain_id)
 }
 
 /// Lấy cấu hình chain theo chain ID
@@ -398,8 +659,23 @@ pub fn get_chain_config(chain_id: u64) -> Result<ChainConfig> {
     get_registry().get_chain_config(chain_id).cloned()
 }
 
+/// Tạo HTTP provider tới RPC chính của chain trong registry toàn cục
+pub fn get_http_provider(chain_id: u64) -> Result<Provider<Http>> {
+    let config = get_chain_config(chain_id)?;
+    let rpc_url = config.primary_rpc_urls.first()
+        .ok_or_else(|| anyhow!("Chain {} has no RPC endpoint", chain_id))?;
+    Provider::<Http>::try_from(rpc_url.as_str())
+        .map_err(|e| anyhow!("Invalid RPC URL {} for chain {}: {}", rpc_url, chain_id, e))
+}
+
+/// Lấy tất cả cấu hình chain trong registry toàn cục
+pub fn get_all_chain_configs() -> Vec<ChainConfig> {
+    get_registry().get_all_chain_configs().into_iter().cloned().collect()
+}
+
 /// Adapter placeholder
 #[derive(Debug)]
+#[allow(dead_code)]
 struct PlaceholderAdapter {
     chain_id: u64,
     name: String,
@@ -429,38 +705,6 @@ impl ChainAdapter for PlaceholderAdapter {
     fn get_type(&self) -> String {
         "Placeholder".to_string()
     }
-    
-    fn get_config(&self) -> &crate::chain_adapters::base::ChainConfig {
-        &self.config
-    }
-    
-    fn get_provider(&self) -> &Provider<Http> {
-        unimplemented!("get_provider not implemented for PlaceholderAdapter")
-    }
-    
-    fn get_wallet(&self) -> Option<&LocalWallet> {
-        None
-    }
-    
-    fn set_wallet(&mut self, _wallet: LocalWallet) {
-        // No-op for placeholder
-    }
-    
-    fn get_gas_optimizer(&self) -> Option<&crate::gas_optimizer::GasOptimizer> {
-        None
-    }
-    
-    fn decode_router_input(&self, _input: &[u8]) -> Result<Vec<Token>> {
-        Err(anyhow!("decode_router_input not implemented for PlaceholderAdapter"))
-    }
-    
-    fn get_native_to_token_path(&self, _token_address: &str) -> Result<Vec<Address>> {
-        Err(anyhow!("get_native_to_token_path not implemented for PlaceholderAdapter"))
-    }
-    
-    fn get_token_to_native_path(&self, _token_address: &str) -> Result<Vec<Address>> {
-        Err(anyhow!("get_token_to_native_path not implemented for PlaceholderAdapter"))
-    }
 }
 
 /// Triển khai factory để tạo các adapter
@@ -473,7 +717,7 @@ pub mod factory {
         let config = get_registry().get_chain_config(chain_id)?.clone();
         
         // Kiểm tra và tạo adapter dựa trên chain_type
-        match config.chain_type.as_str() {
+        match config.chain_type.to_lowercase().as_str() {
             "evm" => {
                 // Ví dụ code tạo adapter EVM
                 // Thực tế cần phải implement chi tiết hơn
@@ -484,7 +728,7 @@ pub mod factory {
                 unimplemented!("Non-EVM adapter creation not implemented");
             },
             _ => {
-                return Err(anyhow!("Unsupported chain type: {}", config.chain_type));
+                Err(anyhow!("Unsupported chain type: {}", config.chain_type))
             }
         }
     }
@@ -4,10 +4,16 @@ use anyhow::Result;
 use ethers::types::{U256, TransactionReceipt};
 use std::sync::Arc;
 use ethers::types::Address;
-use ethers::types::Token;
-use ethers::types::LocalWallet;
-use ethers::types::Provider;
-use ethers::types::Http;
+use ethers::abi::Token;
+use diamond_wallet::ManagedSigner;
+use ethers::providers::{Provider, Http};
+use crate::chain_adapters::generic_evm::GenericEvmAdapter;
+
+/// Lấy `GenericEvmAdapter` phía sau trait object; các trait đặc thù chain được phục vụ
+/// qua capability hook của adapter này
+fn as_generic_evm(adapter: &Arc<dyn ChainAdapter + Send + Sync>) -> Option<&GenericEvmAdapter> {
+    (**adapter).as_any().downcast_ref::<GenericEvmAdapter>()
+}
 
 /// Trait bổ sung cho Ethereum
 #[async_trait]
@@ -129,15 +135,15 @@ impl ChainAdapter for PolygonAdapterEnum {
         }
     }
     
-    fn get_wallet(&self) -> Option<&LocalWallet> {
+    fn get_wallet(&self) -> Option<&ManagedSigner> {
         match self {
             PolygonAdapterEnum::Polygon(adapter) => adapter.get_wallet(),
         }
     }
     
-    fn set_wallet(&mut self, wallet: LocalWallet) {
+    fn set_wallet(&mut self, _wallet: ManagedSigner) {
         match self {
-            PolygonAdapterEnum::Polygon(adapter) => {
+            PolygonAdapterEnum::Polygon(_adapter) => {
                 // Vì adapter là Arc, chúng ta cần xử lý đặc biệt với set_wallet
                 // Đây chỉ là placeholder, cần thực hiện chi tiết hơn tùy theo thiết kế cụ thể
                 unimplemented!("set_wallet for PolygonAdapterEnum is not implemented")
@@ -176,8 +182,8 @@ impl PolygonAdapterEnum {
         match self {
             PolygonAdapterEnum::Polygon(adapter) => {
                 // Cast adapter to PolygonAdapter
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn PolygonAdapter>() {
-                    adapter.get_polygon_gas_station().await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    PolygonAdapter::get_polygon_gas_station(adapter).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng PolygonAdapter"))
                 }
@@ -191,8 +197,8 @@ impl PolygonAdapter for PolygonAdapterEnum {
     async fn get_polygon_gas_station(&self) -> Result<serde_json::Value> {
         match self {
             PolygonAdapterEnum::Polygon(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn PolygonAdapter>() {
-                    adapter.get_polygon_gas_station().await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    PolygonAdapter::get_polygon_gas_station(adapter).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng PolygonAdapter"))
                 }
@@ -221,15 +227,15 @@ impl ChainAdapter for BaseAdapterEnum {
         }
     }
     
-    fn get_wallet(&self) -> Option<&LocalWallet> {
+    fn get_wallet(&self) -> Option<&ManagedSigner> {
         match self {
             BaseAdapterEnum::Base(adapter) => adapter.get_wallet(),
         }
     }
     
-    fn set_wallet(&mut self, wallet: LocalWallet) {
+    fn set_wallet(&mut self, _wallet: ManagedSigner) {
         match self {
-            BaseAdapterEnum::Base(adapter) => {
+            BaseAdapterEnum::Base(_adapter) => {
                 // Vì adapter là Arc, chúng ta cần xử lý đặc biệt với set_wallet
                 unimplemented!("set_wallet for BaseAdapterEnum is not implemented")
             }
@@ -266,8 +272,8 @@ impl BaseAdapterEnum {
     pub async fn get_l1_fee_scale(&self) -> Result<f64> {
         match self {
             BaseAdapterEnum::Base(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn BaseAdapter>() {
-                    adapter.get_l1_fee_scale().await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    BaseAdapter::get_l1_fee_scale(adapter).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng BaseAdapter"))
                 }
@@ -281,8 +287,8 @@ impl BaseAdapter for BaseAdapterEnum {
     async fn get_l1_fee_scale(&self) -> Result<f64> {
         match self {
             BaseAdapterEnum::Base(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn BaseAdapter>() {
-                    adapter.get_l1_fee_scale().await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    BaseAdapter::get_l1_fee_scale(adapter).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng BaseAdapter"))
                 }
@@ -311,15 +317,15 @@ impl ChainAdapter for MonadAdapterEnum {
         }
     }
     
-    fn get_wallet(&self) -> Option<&LocalWallet> {
+    fn get_wallet(&self) -> Option<&ManagedSigner> {
         match self {
             MonadAdapterEnum::Monad(adapter) => adapter.get_wallet(),
         }
     }
     
-    fn set_wallet(&mut self, wallet: LocalWallet) {
+    fn set_wallet(&mut self, _wallet: ManagedSigner) {
         match self {
-            MonadAdapterEnum::Monad(adapter) => {
+            MonadAdapterEnum::Monad(_adapter) => {
                 // Vì adapter là Arc, chúng ta cần xử lý đặc biệt với set_wallet
                 unimplemented!("set_wallet for MonadAdapterEnum is not implemented")
             }
@@ -356,8 +362,8 @@ impl MonadAdapterEnum {
     pub async fn get_monad_performance(&self) -> Result<serde_json::Value> {
         match self {
             MonadAdapterEnum::Monad(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn MonadAdapter>() {
-                    adapter.get_monad_performance().await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    MonadAdapter::get_monad_performance(adapter).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng MonadAdapter"))
                 }
@@ -371,8 +377,8 @@ impl MonadAdapter for MonadAdapterEnum {
     async fn get_monad_performance(&self) -> Result<serde_json::Value> {
         match self {
             MonadAdapterEnum::Monad(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn MonadAdapter>() {
-                    adapter.get_monad_performance().await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    MonadAdapter::get_monad_performance(adapter).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng MonadAdapter"))
                 }
@@ -401,15 +407,15 @@ impl ChainAdapter for EthereumAdapterEnum {
         }
     }
     
-    fn get_wallet(&self) -> Option<&LocalWallet> {
+    fn get_wallet(&self) -> Option<&ManagedSigner> {
         match self {
             EthereumAdapterEnum::Ethereum(adapter) => adapter.get_wallet(),
         }
     }
     
-    fn set_wallet(&mut self, wallet: LocalWallet) {
+    fn set_wallet(&mut self, _wallet: ManagedSigner) {
         match self {
-            EthereumAdapterEnum::Ethereum(adapter) => {
+            EthereumAdapterEnum::Ethereum(_adapter) => {
                 unimplemented!("set_wallet for EthereumAdapterEnum is not implemented")
             }
         }
@@ -445,8 +451,8 @@ impl EthereumAdapterEnum {
     pub async fn send_to_ethereum(&self, to: &str, value: U256) -> Result<Option<TransactionReceipt>> {
         match self {
             EthereumAdapterEnum::Ethereum(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn EthereumAdapter>() {
-                    adapter.send_to_ethereum(to, value).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    EthereumAdapter::send_to_ethereum(adapter, to, value).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng EthereumAdapter"))
                 }
@@ -458,8 +464,8 @@ impl EthereumAdapterEnum {
     pub async fn get_eth2_staking_balance(&self, address: &str) -> Result<U256> {
         match self {
             EthereumAdapterEnum::Ethereum(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn EthereumAdapter>() {
-                    adapter.get_eth2_staking_balance(address).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    EthereumAdapter::get_eth2_staking_balance(adapter, address).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng EthereumAdapter"))
                 }
@@ -473,8 +479,8 @@ impl EthereumAdapter for EthereumAdapterEnum {
     async fn send_to_ethereum(&self, to: &str, value: U256) -> Result<Option<TransactionReceipt>> {
         match self {
             EthereumAdapterEnum::Ethereum(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn EthereumAdapter>() {
-                    adapter.send_to_ethereum(to, value).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    EthereumAdapter::send_to_ethereum(adapter, to, value).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng EthereumAdapter"))
                 }
@@ -485,8 +491,8 @@ impl EthereumAdapter for EthereumAdapterEnum {
     async fn get_eth2_staking_balance(&self, address: &str) -> Result<U256> {
         match self {
             EthereumAdapterEnum::Ethereum(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn EthereumAdapter>() {
-                    adapter.get_eth2_staking_balance(address).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    EthereumAdapter::get_eth2_staking_balance(adapter, address).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng EthereumAdapter"))
                 }
@@ -515,15 +521,15 @@ impl ChainAdapter for BSCAdapterEnum {
         }
     }
     
-    fn get_wallet(&self) -> Option<&LocalWallet> {
+    fn get_wallet(&self) -> Option<&ManagedSigner> {
         match self {
             BSCAdapterEnum::BSC(adapter) => adapter.get_wallet(),
         }
     }
     
-    fn set_wallet(&mut self, wallet: LocalWallet) {
+    fn set_wallet(&mut self, _wallet: ManagedSigner) {
         match self {
-            BSCAdapterEnum::BSC(adapter) => {
+            BSCAdapterEnum::BSC(_adapter) => {
                 unimplemented!("set_wallet for BSCAdapterEnum is not implemented")
             }
         }
@@ -564,8 +570,8 @@ impl BSCAdapterEnum {
     ) -> Result<Option<TransactionReceipt>> {
         match self {
             BSCAdapterEnum::BSC(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn BSCAdapter>() {
-                    adapter.swap_via_pancakeswap(token_address, amount_in, min_amount_out).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    BSCAdapter::swap_via_pancakeswap(adapter, token_address, amount_in, min_amount_out).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng BSCAdapter"))
                 }
@@ -584,8 +590,8 @@ impl BSCAdapter for BSCAdapterEnum {
     ) -> Result<Option<TransactionReceipt>> {
         match self {
             BSCAdapterEnum::BSC(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn BSCAdapter>() {
-                    adapter.swap_via_pancakeswap(token_address, amount_in, min_amount_out).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    BSCAdapter::swap_via_pancakeswap(adapter, token_address, amount_in, min_amount_out).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng BSCAdapter"))
                 }
@@ -614,15 +620,15 @@ impl ChainAdapter for AvalancheAdapterEnum {
         }
     }
     
-    fn get_wallet(&self) -> Option<&LocalWallet> {
+    fn get_wallet(&self) -> Option<&ManagedSigner> {
         match self {
             AvalancheAdapterEnum::Avalanche(adapter) => adapter.get_wallet(),
         }
     }
     
-    fn set_wallet(&mut self, wallet: LocalWallet) {
+    fn set_wallet(&mut self, _wallet: ManagedSigner) {
         match self {
-            AvalancheAdapterEnum::Avalanche(adapter) => {
+            AvalancheAdapterEnum::Avalanche(_adapter) => {
                 unimplemented!("set_wallet for AvalancheAdapterEnum is not implemented")
             }
         }
@@ -664,8 +670,8 @@ impl AvalancheAdapterEnum {
     ) -> Result<Option<TransactionReceipt>> {
         match self {
             AvalancheAdapterEnum::Avalanche(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn AvalancheAdapter>() {
-                    adapter.swap_exact_avax_for_tokens(token_address, amount_in, min_amount_out, recipient, deadline).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    AvalancheAdapter::swap_exact_avax_for_tokens(adapter, token_address, amount_in, min_amount_out, recipient, deadline).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng AvalancheAdapter"))
                 }
@@ -683,8 +689,8 @@ impl AvalancheAdapterEnum {
     ) -> Result<Option<TransactionReceipt>> {
         match self {
             AvalancheAdapterEnum::Avalanche(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn AvalancheAdapter>() {
-                    adapter.swap_exact_tokens_for_avax(token_address, amount_in, min_amount_out, recipient, deadline).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    AvalancheAdapter::swap_exact_tokens_for_avax(adapter, token_address, amount_in, min_amount_out, recipient, deadline).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng AvalancheAdapter"))
                 }
@@ -705,8 +711,8 @@ impl AvalancheAdapter for AvalancheAdapterEnum {
     ) -> Result<Option<TransactionReceipt>> {
         match self {
             AvalancheAdapterEnum::Avalanche(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn AvalancheAdapter>() {
-                    adapter.swap_exact_avax_for_tokens(token_address, amount_in, min_amount_out, recipient, deadline).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    AvalancheAdapter::swap_exact_avax_for_tokens(adapter, token_address, amount_in, min_amount_out, recipient, deadline).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng AvalancheAdapter"))
                 }
@@ -724,8 +730,8 @@ impl AvalancheAdapter for AvalancheAdapterEnum {
     ) -> Result<Option<TransactionReceipt>> {
         match self {
             AvalancheAdapterEnum::Avalanche(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn AvalancheAdapter>() {
-                    adapter.swap_exact_tokens_for_avax(token_address, amount_in, min_amount_out, recipient, deadline).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    AvalancheAdapter::swap_exact_tokens_for_avax(adapter, token_address, amount_in, min_amount_out, recipient, deadline).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng AvalancheAdapter"))
                 }
@@ -754,15 +760,15 @@ impl ChainAdapter for OptimismAdapterEnum {
         }
     }
     
-    fn get_wallet(&self) -> Option<&LocalWallet> {
+    fn get_wallet(&self) -> Option<&ManagedSigner> {
         match self {
             OptimismAdapterEnum::Optimism(adapter) => adapter.get_wallet(),
         }
     }
     
-    fn set_wallet(&mut self, wallet: LocalWallet) {
+    fn set_wallet(&mut self, _wallet: ManagedSigner) {
         match self {
-            OptimismAdapterEnum::Optimism(adapter) => {
+            OptimismAdapterEnum::Optimism(_adapter) => {
                 unimplemented!("set_wallet for OptimismAdapterEnum is not implemented")
             }
         }
@@ -797,8 +803,8 @@ impl OptimismAdapterEnum {
     pub async fn get_l1_gas_price(&self) -> Result<U256> {
         match self {
             OptimismAdapterEnum::Optimism(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn OptimismAdapter>() {
-                    adapter.get_l1_gas_price().await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    OptimismAdapter::get_l1_gas_price(adapter).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng OptimismAdapter"))
                 }
@@ -809,8 +815,8 @@ impl OptimismAdapterEnum {
     pub async fn estimate_l1_fee(&self, data_length: usize) -> Result<U256> {
         match self {
             OptimismAdapterEnum::Optimism(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn OptimismAdapter>() {
-                    adapter.estimate_l1_fee(data_length).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    OptimismAdapter::estimate_l1_fee(adapter, data_length).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng OptimismAdapter"))
                 }
@@ -824,8 +830,8 @@ impl OptimismAdapter for OptimismAdapterEnum {
     async fn get_l1_gas_price(&self) -> Result<U256> {
         match self {
             OptimismAdapterEnum::Optimism(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn OptimismAdapter>() {
-                    adapter.get_l1_gas_price().await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    OptimismAdapter::get_l1_gas_price(adapter).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng OptimismAdapter"))
                 }
@@ -836,8 +842,8 @@ impl OptimismAdapter for OptimismAdapterEnum {
     async fn estimate_l1_fee(&self, data_length: usize) -> Result<U256> {
         match self {
             OptimismAdapterEnum::Optimism(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn OptimismAdapter>() {
-                    adapter.estimate_l1_fee(data_length).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    OptimismAdapter::estimate_l1_fee(adapter, data_length).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng OptimismAdapter"))
                 }
@@ -866,15 +872,15 @@ impl ChainAdapter for ArbitrumAdapterEnum {
         }
     }
     
-    fn get_wallet(&self) -> Option<&LocalWallet> {
+    fn get_wallet(&self) -> Option<&ManagedSigner> {
         match self {
             ArbitrumAdapterEnum::Arbitrum(adapter) => adapter.get_wallet(),
         }
     }
     
-    fn set_wallet(&mut self, wallet: LocalWallet) {
+    fn set_wallet(&mut self, _wallet: ManagedSigner) {
         match self {
-            ArbitrumAdapterEnum::Arbitrum(adapter) => {
+            ArbitrumAdapterEnum::Arbitrum(_adapter) => {
                 unimplemented!("set_wallet for ArbitrumAdapterEnum is not implemented")
             }
         }
@@ -909,8 +915,8 @@ impl ArbitrumAdapterEnum {
     pub async fn get_l1_gas_price(&self) -> Result<U256> {
         match self {
             ArbitrumAdapterEnum::Arbitrum(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn ArbitrumAdapter>() {
-                    adapter.get_l1_gas_price().await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    ArbitrumAdapter::get_l1_gas_price(adapter).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng ArbitrumAdapter"))
                 }
@@ -921,8 +927,8 @@ impl ArbitrumAdapterEnum {
     pub async fn estimate_l1_fee(&self, data_length: usize) -> Result<U256> {
         match self {
             ArbitrumAdapterEnum::Arbitrum(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn ArbitrumAdapter>() {
-                    adapter.estimate_l1_fee(data_length).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    ArbitrumAdapter::estimate_l1_fee(adapter, data_length).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng ArbitrumAdapter"))
                 }
@@ -936,8 +942,8 @@ impl ArbitrumAdapter for ArbitrumAdapterEnum {
     async fn get_l1_gas_price(&self) -> Result<U256> {
         match self {
             ArbitrumAdapterEnum::Arbitrum(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn ArbitrumAdapter>() {
-                    adapter.get_l1_gas_price().await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    ArbitrumAdapter::get_l1_gas_price(adapter).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng ArbitrumAdapter"))
                 }
@@ -948,8 +954,8 @@ impl ArbitrumAdapter for ArbitrumAdapterEnum {
     async fn estimate_l1_fee(&self, data_length: usize) -> Result<U256> {
         match self {
             ArbitrumAdapterEnum::Arbitrum(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn ArbitrumAdapter>() {
-                    adapter.estimate_l1_fee(data_length).await
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    ArbitrumAdapter::estimate_l1_fee(adapter, data_length).await
                 } else {
                     Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng ArbitrumAdapter"))
                 }
@@ -978,15 +984,15 @@ impl ChainAdapter for CustomChainAdapterEnum {
         }
     }
     
-    fn get_wallet(&self) -> Option<&LocalWallet> {
+    fn get_wallet(&self) -> Option<&ManagedSigner> {
         match self {
             CustomChainAdapterEnum::Custom(adapter) => adapter.get_wallet(),
         }
     }
     
-    fn set_wallet(&mut self, wallet: LocalWallet) {
+    fn set_wallet(&mut self, _wallet: ManagedSigner) {
         match self {
-            CustomChainAdapterEnum::Custom(adapter) => {
+            CustomChainAdapterEnum::Custom(_adapter) => {
                 unimplemented!("set_wallet for CustomChainAdapterEnum is not implemented")
             }
         }
@@ -1022,8 +1028,8 @@ impl CustomChainAdapterEnum {
     pub fn get_custom_chain_name(&self) -> &str {
         match self {
             CustomChainAdapterEnum::Custom(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn CustomChainAdapter>() {
-                    adapter.get_custom_chain_name()
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    CustomChainAdapter::get_custom_chain_name(adapter)
                 } else {
                     "Unknown Custom Chain"
                 }
@@ -1035,8 +1041,8 @@ impl CustomChainAdapterEnum {
     pub fn get_native_token_name(&self) -> &str {
         match self {
             CustomChainAdapterEnum::Custom(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn CustomChainAdapter>() {
-                    adapter.get_native_token_name()
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    CustomChainAdapter::get_native_token_name(adapter)
                 } else {
                     "Unknown Token"
                 }
@@ -1048,8 +1054,8 @@ impl CustomChainAdapterEnum {
     pub fn get_wrapped_native_token(&self) -> &str {
         match self {
             CustomChainAdapterEnum::Custom(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn CustomChainAdapter>() {
-                    adapter.get_wrapped_native_token()
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    CustomChainAdapter::get_wrapped_native_token(adapter)
                 } else {
                     "0x0000000000000000000000000000000000000000"
                 }
@@ -1062,8 +1068,8 @@ impl CustomChainAdapter for CustomChainAdapterEnum {
     fn get_custom_chain_name(&self) -> &str {
         match self {
             CustomChainAdapterEnum::Custom(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn CustomChainAdapter>() {
-                    adapter.get_custom_chain_name()
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    CustomChainAdapter::get_custom_chain_name(adapter)
                 } else {
                     "Unknown Custom Chain"
                 }
@@ -1074,8 +1080,8 @@ impl CustomChainAdapter for CustomChainAdapterEnum {
     fn get_native_token_name(&self) -> &str {
         match self {
             CustomChainAdapterEnum::Custom(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn CustomChainAdapter>() {
-                    adapter.get_native_token_name()
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    CustomChainAdapter::get_native_token_name(adapter)
                 } else {
                     "Unknown Token"
                 }
@@ -1086,8 +1092,8 @@ impl CustomChainAdapter for CustomChainAdapterEnum {
     fn get_wrapped_native_token(&self) -> &str {
         match self {
             CustomChainAdapterEnum::Custom(adapter) => {
-                if let Some(adapter) = adapter.as_any().downcast_ref::<dyn CustomChainAdapter>() {
-                    adapter.get_wrapped_native_token()
+                if let Some(adapter) = as_generic_evm(adapter) {
+                    CustomChainAdapter::get_wrapped_native_token(adapter)
                 } else {
                     "0x0000000000000000000000000000000000000000"
                 }
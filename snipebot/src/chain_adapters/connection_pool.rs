@@ -1,33 +1,61 @@
 // Standard library imports
 use std::{
     sync::{Arc, RwLock},
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     time::Duration,
     future::Future,
     sync::Weak,
 };
 
 // Internal imports
-use crate::{
-    chain_adapters::{
-        interfaces::ChainError,
-        retry_policy::{RetryPolicy, RetryPolicyEnum},
-    },
-};
+use crate::chain_adapters::{
+        retry_policy::{RetryPolicyEnum, create_default_retry_policy},
+        rpc_batch::{BatchConfig, BatchingHttp},
+        rate_limiter::{self, ComputeUnitCosts, EndpointLimiter, EndpointRateLimit},
+    };
 
-use common::cache::{Cache, CacheEntry, JSONCache, BasicCache};
+use diamond_common::cache::{Cache, JSONCache, AsyncCache};
 
 // Third party imports
-use anyhow::{Result, anyhow};
+use anyhow::{Result, Context, anyhow};
 use tracing::{info, warn, debug};
 use ethers::providers::{Middleware, Provider, Http};
+use ethers::types::{Address, BlockId, BlockNumber, U256, U64, TransactionRequest, transaction::eip2718::TypedTransaction};
+use ethers::abi::{self, ParamType, Token};
+use futures::future::join_all;
 use serde::{Serialize, Deserialize};
 use metrics::{counter, gauge};
 use tokio::sync::Semaphore;
-use serde_json;
 use async_trait::async_trait;
 use std::time::Instant;
 
+/// Hệ số làm mượt cho độ trễ EWMA
+const EWMA_ALPHA: f64 = 0.2;
+
+/// Độ trễ (ms) dùng để chuẩn hóa health score
+const LATENCY_SCALE_MS: f64 = 250.0;
+
+/// Số mẫu độ trễ giữ lại cho mỗi endpoint để tính percentile
+const LATENCY_WINDOW_SIZE: usize = 100;
+
+/// Số lần bất đồng trong quorum trước khi endpoint bị đánh dấu Down
+const MAX_DISAGREEMENTS: u64 = 3;
+
+/// Thời gian (giây) endpoint Down bị cách ly trước khi health check được thử đưa nó trở lại
+const DOWN_RECOVERY_SECONDS: u64 = 60;
+
+/// Khoảng thời gian (giây) endpoint được phép chậm hơn head tốt nhất trước khi bị hạ cấp
+const STALE_HEAD_SECONDS: f64 = 30.0;
+
+/// Khoảng thời gian (giây) tối đa để endpoint còn được coi là ở gần tip cho các read cần state mới
+const FRESH_HEAD_SECONDS: f64 = 3.0;
+
+/// Ngưỡng lag tối thiểu (block) để tránh hạ cấp do lệch thời điểm health check
+const MIN_STALE_BLOCKS: u64 = 2;
+
+/// Block time mặc định (giây) khi chưa biết cấu hình chain
+const DEFAULT_BLOCK_TIME_SECONDS: f64 = 12.0;
+
 /// Trạng thái của một RPC endpoint
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EndpointStatus {
@@ -60,6 +88,36 @@ pub struct EndpointInfo {
     pub priority: u32,
     /// Giới hạn số lượng request đồng thời
     pub concurrent_limit: usize,
+    /// Độ trễ trượt mũ (EWMA, ms), phản ánh nhanh thay đổi gần đây
+    #[serde(default)]
+    pub ewma_latency: f64,
+    /// Số lần trả kết quả khác đa số trong quorum read (giảm dần khi đồng thuận trở lại)
+    #[serde(default)]
+    pub disagreement_count: u64,
+    /// Block head gần nhất endpoint báo về (0 = chưa biết)
+    #[serde(default)]
+    pub head_block: u64,
+    /// Số block chậm hơn head tốt nhất trong pool
+    #[serde(default)]
+    pub block_lag: u64,
+    /// Endpoint đang bị hạ cấp do chậm block
+    #[serde(default)]
+    pub lagging: bool,
+    /// Tổng compute unit đã sử dụng
+    #[serde(default)]
+    pub compute_units_used: u64,
+    /// Số lần request bị giữ lại do hết hạn mức cục bộ
+    #[serde(default)]
+    pub throttled_count: u64,
+    /// Số lần endpoint trả về rate limit (HTTP 429 hoặc lỗi rate limit của provider)
+    #[serde(default)]
+    pub rate_limited_count: u64,
+    /// Thời điểm hết backoff do rate limit (Unix timestamp, 0 = không backoff)
+    #[serde(default)]
+    pub rate_limited_until: u64,
+    /// Thời điểm endpoint bị đánh dấu Down (Unix timestamp, 0 = không Down)
+    #[serde(default)]
+    pub down_since: u64,
 }
 
 impl EndpointInfo {
@@ -75,6 +133,16 @@ impl EndpointInfo {
             error_count: 0,
             priority,
             concurrent_limit,
+            ewma_latency: 0.0,
+            disagreement_count: 0,
+            head_block: 0,
+            block_lag: 0,
+            lagging: false,
+            compute_units_used: 0,
+            throttled_count: 0,
+            rate_limited_count: 0,
+            rate_limited_until: 0,
+            down_since: 0,
         }
     }
     
@@ -86,6 +154,13 @@ impl EndpointInfo {
         let total_requests = self.success_count + self.error_count;
         self.avg_latency = ((self.avg_latency * (total_requests - 1) as f64) + latency_ms) / total_requests as f64;
         
+        // Cập nhật độ trễ EWMA
+        self.ewma_latency = if self.ewma_latency == 0.0 {
+            latency_ms
+        } else {
+            EWMA_ALPHA * latency_ms + (1.0 - EWMA_ALPHA) * self.ewma_latency
+        };
+        
         // Cập nhật tỷ lệ lỗi
         self.error_rate = self.error_count as f64 / total_requests as f64;
         
@@ -95,8 +170,8 @@ impl EndpointInfo {
             .unwrap_or_default()
             .as_secs();
         
-        // Cập nhật trạng thái nếu cần
-        if self.status == EndpointStatus::Degraded && self.error_rate < 0.1 {
+        // Cập nhật trạng thái nếu cần (endpoint bị hạ cấp do bất đồng hoặc chậm block phải hồi phục trước)
+        if self.status == EndpointStatus::Degraded && self.error_rate < 0.1 && self.disagreement_count == 0 && !self.lagging {
             self.status = EndpointStatus::Healthy;
             info!("Endpoint {} status changed to Healthy", self.url);
         }
@@ -118,18 +193,444 @@ impl EndpointInfo {
         
         // Cập nhật trạng thái
         if self.error_rate > 0.5 {
-            self.status = EndpointStatus::Down;
-            warn!("Endpoint {} status changed to Down (error rate: {:.2})", self.url, self.error_rate);
+            if self.mark_down() {
+                warn!("Endpoint {} status changed to Down (error rate: {:.2})", self.url, self.error_rate);
+            }
         } else if self.error_rate > 0.1 {
             self.status = EndpointStatus::Degraded;
             warn!("Endpoint {} status changed to Degraded (error rate: {:.2})", self.url, self.error_rate);
         }
     }
     
+    /// Đánh dấu endpoint Down và ghi lại thời điểm để tính thời gian cách ly.
+    /// Trả về false nếu endpoint đã Down từ trước.
+    fn mark_down(&mut self) -> bool {
+        if self.status == EndpointStatus::Down {
+            return false;
+        }
+        self.status = EndpointStatus::Down;
+        self.down_since = unix_now();
+        true
+    }
+    
+    /// Đưa endpoint Down trở lại ở trạng thái Degraded khi đã hết thời gian cách ly và vừa
+    /// vượt qua health check. Bộ đếm lỗi và bất đồng được đặt lại để endpoint có cơ hội phục hồi;
+    /// nếu vẫn lỗi hoặc bất đồng, endpoint sẽ bị đánh dấu Down lại.
+    pub fn recover_if_due(&mut self, now: u64) -> bool {
+        if self.status != EndpointStatus::Down || now < self.down_since.saturating_add(DOWN_RECOVERY_SECONDS) {
+            return false;
+        }
+        self.status = EndpointStatus::Degraded;
+        self.down_since = 0;
+        self.disagreement_count = 0;
+        self.success_count = 0;
+        self.error_count = 0;
+        self.error_rate = 0.0;
+        info!("Endpoint {} passed recovery probe, status changed to Degraded", self.url);
+        true
+    }
+    
     /// Kiểm tra endpoint có khả dụng không
     pub fn is_available(&self) -> bool {
         self.status != EndpointStatus::Down
     }
+    
+    /// Điểm sức khỏe (0.0 - 1.0) dựa trên trạng thái, tỷ lệ lỗi, độ trễ và số lần bất đồng
+    pub fn health_score(&self) -> f64 {
+        let status_weight = match self.status {
+            EndpointStatus::Healthy => 1.0,
+            EndpointStatus::Degraded => 0.3,
+            EndpointStatus::Down => 0.0,
+        };
+        let reliability = (1.0 - self.error_rate).max(0.01);
+        let latency_factor = 1.0 / (1.0 + self.ewma_latency / LATENCY_SCALE_MS);
+        let agreement_factor = 1.0 / (1.0 + self.disagreement_count as f64);
+        
+        status_weight * reliability * latency_factor * agreement_factor
+    }
+    
+    /// Hạ cấp endpoint khi trả kết quả khác đa số hoặc trả lời quá chậm trong quorum read
+    pub fn demote(&mut self, reason: &str) {
+        self.disagreement_count += 1;
+        
+        if self.disagreement_count >= MAX_DISAGREEMENTS {
+            if self.mark_down() {
+                warn!("Endpoint {} status changed to Down ({} disagreements: {})", self.url, self.disagreement_count, reason);
+            }
+        } else if self.status == EndpointStatus::Healthy {
+            self.status = EndpointStatus::Degraded;
+            warn!("Endpoint {} status changed to Degraded ({})", self.url, reason);
+        }
+    }
+    
+    /// Ghi nhận endpoint đồng thuận với đa số trong quorum read
+    pub fn record_agreement(&mut self) {
+        self.disagreement_count = self.disagreement_count.saturating_sub(1);
+    }
+    
+    /// Cập nhật độ lag so với head tốt nhất; hạ cấp endpoint khi lag vượt `max_lag` block
+    pub fn record_head_lag(&mut self, best_head: u64, max_lag: u64) {
+        self.block_lag = best_head.saturating_sub(self.head_block);
+        
+        if self.block_lag > max_lag {
+            if !self.lagging {
+                warn!("Endpoint {} is {} blocks behind best head {}", self.url, self.block_lag, best_head);
+            }
+            self.lagging = true;
+            if self.status == EndpointStatus::Healthy {
+                self.status = EndpointStatus::Degraded;
+                warn!("Endpoint {} status changed to Degraded (stale head {})", self.url, self.head_block);
+            }
+        } else if self.lagging {
+            self.lagging = false;
+            if self.status == EndpointStatus::Degraded && self.error_rate < 0.1 && self.disagreement_count == 0 {
+                self.status = EndpointStatus::Healthy;
+                info!("Endpoint {} caught up to head {}, status changed to Healthy", self.url, self.head_block);
+            }
+        }
+    }
+    
+    /// Endpoint có head đã biết và nằm trong `tolerance` block so với tip
+    pub fn is_near_tip(&self, tolerance: u64) -> bool {
+        self.is_available() && self.head_block > 0 && self.block_lag <= tolerance
+    }
+}
+
+/// Unix timestamp hiện tại (giây)
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Ngưỡng lag (block) theo block time trung bình của chain: `(stale, fresh)`.
+/// Endpoint lag quá `stale` bị hạ cấp; read cần state mới chỉ đi tới endpoint lag không quá `fresh`.
+pub fn block_lag_thresholds(avg_block_time_secs: f64) -> (u64, u64) {
+    let block_time = if avg_block_time_secs.is_finite() && avg_block_time_secs > 0.0 {
+        avg_block_time_secs
+    } else {
+        DEFAULT_BLOCK_TIME_SECONDS
+    };
+    
+    let stale = ((STALE_HEAD_SECONDS / block_time).ceil() as u64).max(MIN_STALE_BLOCKS);
+    let fresh = ((FRESH_HEAD_SECONDS / block_time).ceil() as u64).clamp(1, stale);
+    (stale, fresh)
+}
+
+/// Cập nhật lag của các endpoint theo head tốt nhất, trả về head tốt nhất
+fn apply_block_lag(infos: &mut [&mut EndpointInfo], max_lag: u64) -> u64 {
+    let best_head = infos.iter()
+        .filter(|info| info.is_available())
+        .map(|info| info.head_block)
+        .max()
+        .unwrap_or(0);
+    
+    if best_head == 0 {
+        return 0;
+    }
+    
+    for info in infos.iter_mut().filter(|info| info.head_block > 0) {
+        info.record_head_lag(best_head, max_lag);
+    }
+    
+    best_head
+}
+
+/// Chiến lược chọn endpoint của pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Default)]
+pub enum RoutingStrategy {
+    /// Lần lượt theo vòng
+    #[default]
+    RoundRobin,
+    /// Endpoint có độ trễ EWMA thấp nhất
+    LowestLatency,
+    /// Chọn ngẫu nhiên có trọng số theo health score
+    WeightedRandom,
+    /// Gửi tới endpoint nhanh nhất, gửi thêm tới endpoint thứ hai nếu chậm hơn p90
+    Hedged,
+}
+
+
+/// Cấu hình quorum read cho các truy vấn quan trọng
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuorumConfig {
+    /// Số endpoint tối thiểu phải trả cùng kết quả (k)
+    pub min_agreement: usize,
+    /// Số endpoint được truy vấn (n)
+    pub fanout: usize,
+    /// Thời gian chờ tối đa cho mỗi endpoint (ms)
+    pub timeout_ms: u64,
+}
+
+impl Default for QuorumConfig {
+    fn default() -> Self {
+        Self {
+            min_agreement: 2,
+            fanout: 3,
+            timeout_ms: 3000,
+        }
+    }
+}
+
+/// Kết quả tổng hợp của một quorum read
+#[derive(Debug, Clone)]
+pub struct QuorumOutcome<T> {
+    /// Giá trị được đa số đồng thuận (nếu đạt quorum)
+    pub value: Option<T>,
+    /// Endpoint trả kết quả trùng với đa số
+    pub agreeing: Vec<String>,
+    /// Endpoint trả kết quả khác đa số
+    pub disagreeing: Vec<String>,
+    /// Endpoint lỗi hoặc timeout
+    pub failed: Vec<String>,
+}
+
+/// Kết quả một request đọc trên một endpoint
+enum ReadResult<T> {
+    Ok(T),
+    Failed(anyhow::Error),
+    TimedOut,
+}
+
+/// (URL endpoint, kết quả, độ trễ ms) của một request đọc, ghi nhận vào pool sau khi nhả lock
+type ReadOutcome<T> = (String, ReadResult<T>, f64);
+
+/// Endpoint (URL, provider) được chọn cho một lượt đọc
+type ReadCandidates = Vec<(String, Provider<Http>)>;
+
+/// Pool có ít endpoint hơn `min_agreement` nên không thể quorum read; chỉ lỗi này cho phép
+/// người gọi dùng provider đơn lẻ thay thế, còn bất đồng giữa các endpoint thì không
+#[derive(Debug, thiserror::Error)]
+#[error("Not enough RPC endpoints for quorum on chain {chain_id}: pool has {configured}, {required} required")]
+pub struct QuorumUnavailable {
+    pub chain_id: u64,
+    pub configured: usize,
+    pub required: usize,
+}
+
+/// Endpoint cho fresh read: (URL, provider, p90 độ trễ ms) theo thứ tự ưu tiên,
+/// timeout mỗi request và độ trễ hedge mặc định (chỉ với chiến lược Hedged)
+struct FreshPlan {
+    candidates: Vec<(String, Provider<Http>, Option<f64>)>,
+    timeout: Duration,
+    hedge_delay: Option<Duration>,
+}
+
+/// Chuyển kết quả có timeout thành ReadResult
+fn read_result<T>(result: std::result::Result<Result<T>, tokio::time::error::Elapsed>) -> ReadResult<T> {
+    match result {
+        Ok(Ok(value)) => ReadResult::Ok(value),
+        Ok(Err(e)) => ReadResult::Failed(e),
+        Err(_) => ReadResult::TimedOut,
+    }
+}
+
+/// Ghi lại kết quả một lần thử vào `outcomes`, trả về giá trị nếu thành công
+fn settle_attempt<T>(outcomes: &mut Vec<ReadOutcome<()>>, url: String, result: ReadResult<T>, start: Instant) -> Option<T> {
+    let latency = start.elapsed().as_millis() as f64;
+    match result {
+        ReadResult::Ok(value) => {
+            outcomes.push((url, ReadResult::Ok(()), latency));
+            Some(value)
+        },
+        ReadResult::Failed(e) => {
+            outcomes.push((url, ReadResult::Failed(e), latency));
+            None
+        },
+        ReadResult::TimedOut => {
+            outcomes.push((url, ReadResult::TimedOut, latency));
+            None
+        },
+    }
+}
+
+/// Thử lần lượt từng endpoint cho tới khi thành công
+async fn sequential_attempts<F, Fut, T>(plan: FreshPlan, operation: &F) -> (Option<T>, Vec<ReadOutcome<()>>)
+where
+    F: Fn(Provider<Http>) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut outcomes = Vec::new();
+    for (url, provider, _) in plan.candidates {
+        let start = Instant::now();
+        let result = read_result(tokio::time::timeout(plan.timeout, operation(provider)).await);
+        if let Some(value) = settle_attempt(&mut outcomes, url, result, start) {
+            return (Some(value), outcomes);
+        }
+    }
+    (None, outcomes)
+}
+
+/// Hedged read: gửi tới endpoint đầu tiên, nếu chưa có kết quả sau p90 độ trễ của endpoint đó
+/// thì gửi thêm tới endpoint kế tiếp và lấy kết quả thành công đến trước.
+/// Request bị bỏ dở khi bên kia đã thành công không được ghi nhận.
+async fn hedged_attempts<F, Fut, T>(plan: FreshPlan, default_delay: Duration, operation: &F) -> (Option<T>, Vec<ReadOutcome<()>>)
+where
+    F: Fn(Provider<Http>) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut outcomes = Vec::new();
+    let timeout = plan.timeout;
+    let mut candidates = plan.candidates.into_iter();
+    
+    while let Some((first_url, first_provider, first_p90)) = candidates.next() {
+        let hedge_delay = first_p90
+            .map(|p90| Duration::from_millis(p90.ceil() as u64))
+            .unwrap_or(default_delay)
+            .max(Duration::from_millis(1));
+        
+        let first_start = Instant::now();
+        let first = tokio::time::timeout(timeout, operation(first_provider));
+        tokio::pin!(first);
+        
+        // Chờ endpoint đầu trong khoảng p90
+        if let Ok(result) = tokio::time::timeout(hedge_delay, &mut first).await {
+            if let Some(value) = settle_attempt(&mut outcomes, first_url, read_result(result), first_start) {
+                return (Some(value), outcomes);
+            }
+            continue;
+        }
+        
+        // Endpoint đầu chậm hơn p90, gửi request dự phòng
+        let Some((second_url, second_provider, _)) = candidates.next() else {
+            let result = read_result(first.await);
+            let value = settle_attempt(&mut outcomes, first_url, result, first_start);
+            return (value, outcomes);
+        };
+        counter!("rpc_hedged_requests", 1, "url" => first_url.clone());
+        debug!("Hedging RPC read: {} slower than {:?}, also sending to {}", first_url, hedge_delay, second_url);
+        
+        let second_start = Instant::now();
+        let second = tokio::time::timeout(timeout, operation(second_provider));
+        tokio::pin!(second);
+        
+        let value = tokio::select! {
+            result = &mut first => match settle_attempt(&mut outcomes, first_url, read_result(result), first_start) {
+                Some(value) => Some(value),
+                None => settle_attempt(&mut outcomes, second_url, read_result(second.await), second_start),
+            },
+            result = &mut second => match settle_attempt(&mut outcomes, second_url, read_result(result), second_start) {
+                Some(value) => Some(value),
+                None => settle_attempt(&mut outcomes, first_url, read_result(first.await), first_start),
+            },
+        };
+        if value.is_some() {
+            return (value, outcomes);
+        }
+    }
+    
+    (None, outcomes)
+}
+
+/// Tổng hợp kết quả từ nhiều endpoint, yêu cầu ít nhất `min_agreement` kết quả giống nhau
+pub fn tally_quorum<T: PartialEq + Clone>(responses: Vec<(String, Option<T>)>, min_agreement: usize) -> QuorumOutcome<T> {
+    let mut groups: Vec<(T, Vec<String>)> = Vec::new();
+    let mut failed = Vec::new();
+    
+    for (url, response) in responses {
+        match response {
+            Some(value) => match groups.iter_mut().find(|(v, _)| *v == value) {
+                Some((_, urls)) => urls.push(url),
+                None => groups.push((value, vec![url])),
+            },
+            None => failed.push(url),
+        }
+    }
+    
+    groups.sort_by_key(|group| std::cmp::Reverse(group.1.len()));
+    
+    // Cần nhóm lớn nhất đạt ngưỡng và không hòa với nhóm thứ hai
+    let has_quorum = match (groups.first(), groups.get(1)) {
+        (Some(first), second) => {
+            first.1.len() >= min_agreement.max(1)
+                && second.is_none_or(|s| s.1.len() < first.1.len())
+        },
+        (None, _) => false,
+    };
+    
+    if !has_quorum {
+        return QuorumOutcome {
+            value: None,
+            agreeing: Vec::new(),
+            disagreeing: Vec::new(),
+            failed,
+        };
+    }
+    
+    let mut groups = groups.into_iter();
+    let (value, agreeing) = groups.next().expect("đã kiểm tra nhóm đầu tiên");
+    let disagreeing = groups.flat_map(|(_, urls)| urls).collect();
+    
+    QuorumOutcome {
+        value: Some(value),
+        agreeing,
+        disagreeing,
+        failed,
+    }
+}
+
+/// Sắp xếp các endpoint khả dụng theo chiến lược, trả về chỉ số theo thứ tự ưu tiên
+///
+/// `random` là số ngẫu nhiên trong [0, 1) dùng cho WeightedRandom.
+fn order_candidates(strategy: RoutingStrategy, infos: &[&EndpointInfo], start_index: usize, random: f64) -> Vec<usize> {
+    let available: Vec<usize> = (0..infos.len()).filter(|&i| infos[i].is_available()).collect();
+    if available.is_empty() {
+        return available;
+    }
+    
+    match strategy {
+        RoutingStrategy::RoundRobin => {
+            let start = start_index % infos.len();
+            let mut ordered: Vec<usize> = available.iter().copied().filter(|&i| i >= start).collect();
+            ordered.extend(available.iter().copied().filter(|&i| i < start));
+            ordered
+        },
+        RoutingStrategy::LowestLatency | RoutingStrategy::Hedged => {
+            let mut ordered = available;
+            ordered.sort_by(|&a, &b| {
+                infos[a].ewma_latency.partial_cmp(&infos[b].ewma_latency)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(infos[a].priority.cmp(&infos[b].priority))
+            });
+            ordered
+        },
+        RoutingStrategy::WeightedRandom => {
+            let mut ordered = available;
+            ordered.sort_by(|&a, &b| {
+                infos[b].health_score().partial_cmp(&infos[a].health_score())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            
+            let total: f64 = ordered.iter().map(|&i| infos[i].health_score()).sum();
+            if total > 0.0 {
+                let mut target = random.clamp(0.0, 1.0) * total;
+                let mut chosen = ordered.len() - 1;
+                for (position, &i) in ordered.iter().enumerate() {
+                    target -= infos[i].health_score();
+                    if target < 0.0 {
+                        chosen = position;
+                        break;
+                    }
+                }
+                let first = ordered.remove(chosen);
+                ordered.insert(0, first);
+            }
+            ordered
+        },
+    }
+}
+
+/// Percentile (0-100) của một tập giá trị
+fn percentile(values: &VecDeque<f64>, p: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted: Vec<f64> = values.iter().copied().collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let rank = ((p / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted.get(rank.min(sorted.len() - 1)).copied()
 }
 
 /// Cấu trúc chi tiết endpoint với thông tin provider
@@ -138,10 +639,16 @@ struct PooledEndpoint {
     info: EndpointInfo,
     /// Provider đã được khởi tạo
     provider: Provider<Http>,
+    /// Provider tự gom request thành JSON-RPC batch
+    batched_provider: Provider<BatchingHttp>,
     /// Semaphore để giới hạn số lượng request đồng thời
     semaphore: Arc<Semaphore>,
     /// Thời gian cuối cùng sử dụng
     last_used: Instant,
+    /// Các mẫu độ trễ gần nhất (ms) để tính p90 cho hedged request
+    latency_window: VecDeque<f64>,
+    /// Token bucket request/compute unit và backoff khi bị rate limit
+    limiter: EndpointLimiter,
 }
 
 /// Cấu hình cho RPC pool
@@ -159,6 +666,34 @@ pub struct ConnectionPoolConfig {
     pub connection_timeout: u64,
     /// Thời gian chờ tối đa cho một request (ms)
     pub request_timeout: u64,
+    /// Chiến lược chọn endpoint
+    #[serde(default)]
+    pub routing_strategy: RoutingStrategy,
+    /// Độ trễ (ms) trước khi gửi request dự phòng khi endpoint chưa có đủ mẫu để tính p90
+    #[serde(default = "default_hedge_delay_ms")]
+    pub hedge_delay_ms: u64,
+    /// Cấu hình quorum read
+    #[serde(default)]
+    pub quorum: QuorumConfig,
+    /// Cấu hình gom request JSON-RPC
+    #[serde(default)]
+    pub batching: BatchConfig,
+    /// Giới hạn request/compute unit mặc định cho mỗi endpoint
+    #[serde(default)]
+    pub rate_limit: EndpointRateLimit,
+    /// Giới hạn riêng theo URL endpoint (ghi đè `rate_limit`)
+    #[serde(default)]
+    pub endpoint_rate_limits: HashMap<String, EndpointRateLimit>,
+    /// Chi phí compute unit theo method
+    #[serde(default)]
+    pub compute_unit_costs: ComputeUnitCosts,
+    /// Ghi đè ngưỡng lag (block) trước khi endpoint bị hạ cấp; mặc định suy ra từ block time của chain
+    #[serde(default)]
+    pub max_block_lag: Option<u64>,
+}
+
+fn default_hedge_delay_ms() -> u64 {
+    500
 }
 
 impl Default for ConnectionPoolConfig {
@@ -170,11 +705,20 @@ impl Default for ConnectionPoolConfig {
             max_connections: 10,
             connection_timeout: 5000,   // 5 giây
             request_timeout: 30000,     // 30 giây
+            routing_strategy: RoutingStrategy::RoundRobin,
+            hedge_delay_ms: default_hedge_delay_ms(),
+            quorum: QuorumConfig::default(),
+            batching: BatchConfig::default(),
+            rate_limit: EndpointRateLimit::default(),
+            endpoint_rate_limits: HashMap::new(),
+            compute_unit_costs: ComputeUnitCosts::default(),
+            max_block_lag: None,
         }
     }
 }
 
 /// Quản lý pool kết nối đến các RPC endpoint
+#[allow(dead_code)]
 pub struct RPCConnectionPool {
     /// Các endpoint chính
     primary_endpoints: Vec<PooledEndpoint>,
@@ -191,20 +735,37 @@ pub struct RPCConnectionPool {
     /// Chain ID
     chain_id: u64,
     /// Cache cho các kết quả truy vấn
-    cache: JSONCache,
+    cache: JSONCache<AsyncCache>,
+    /// Ngưỡng lag (block) trước khi endpoint bị hạ cấp
+    max_block_lag: u64,
+    /// Ngưỡng lag (block) tối đa cho read cần state mới
+    fresh_block_lag: u64,
+    /// Head tốt nhất trong pool ở lần kiểm tra gần nhất
+    best_head: u64,
 }
 
 #[async_trait]
 impl Cache for RPCConnectionPool {
-    async fn get_from_cache<T: Serialize + for<'de> Deserialize<'de> + Send + Sync + 'static>(&self, key: &str) -> Result<Option<T>> {
-        self.cache.get_from_cache(key).await
+    async fn get<K, V>(&self, key: K) -> Result<Option<V>>
+    where
+        K: AsRef<str> + Send + Sync,
+        V: for<'de> Deserialize<'de> + Send + Sync + 'static,
+    {
+        self.cache.get(key).await
     }
 
-    async fn store_in_cache<T: Serialize + for<'de> Deserialize<'de> + Send + Sync + 'static>(&self, key: &str, value: T, ttl_seconds: u64) -> Result<()> {
-        self.cache.store_in_cache(key, value, ttl_seconds).await
+    async fn set<K, V>(&self, key: K, value: V, ttl_seconds: u64) -> Result<()>
+    where
+        K: AsRef<str> + Send + Sync,
+        V: Serialize + Send + Sync + 'static,
+    {
+        self.cache.set(key, value, ttl_seconds).await
     }
 
-    async fn remove(&self, key: &str) -> Result<()> {
+    async fn remove<K>(&self, key: K) -> Result<()>
+    where
+        K: AsRef<str> + Send + Sync,
+    {
         self.cache.remove(key).await
     }
 
@@ -212,11 +773,64 @@ impl Cache for RPCConnectionPool {
         self.cache.clear().await
     }
 
-    async fn cleanup_cache(&self) -> Result<()> {
-        self.cache.cleanup_cache().await
+    async fn cleanup(&self) -> Result<()> {
+        self.cache.cleanup().await
+    }
+
+    async fn len(&self) -> Result<usize> {
+        self.cache.len().await
     }
 }
 
+/// Khóa ghi pool, chuyển lỗi poisoned lock thành anyhow::Error
+fn write_pool(pool: &RwLock<RPCConnectionPool>) -> Result<std::sync::RwLockWriteGuard<'_, RPCConnectionPool>> {
+    pool.write().map_err(|e| anyhow!("Pool lock poisoned: {}", e))
+}
+
+/// Khóa đọc pool, chuyển lỗi poisoned lock thành anyhow::Error
+fn read_pool(pool: &RwLock<RPCConnectionPool>) -> Result<std::sync::RwLockReadGuard<'_, RPCConnectionPool>> {
+    pool.read().map_err(|e| anyhow!("Pool lock poisoned: {}", e))
+}
+
+/// Bản sao provider của endpoint được chọn, lấy ra dưới lock để dùng sau khi nhả lock
+struct SelectedEndpoint {
+    provider: Provider<Http>,
+    batched_provider: Provider<BatchingHttp>,
+    info: EndpointInfo,
+    semaphore: Arc<Semaphore>,
+}
+
+impl SelectedEndpoint {
+    fn from_endpoint(endpoint: &PooledEndpoint) -> Self {
+        Self {
+            provider: endpoint.provider.clone(),
+            batched_provider: endpoint.batched_provider.clone(),
+            info: endpoint.info.clone(),
+            semaphore: endpoint.semaphore.clone(),
+        }
+    }
+    
+    fn into_guard(self, permit: tokio::sync::OwnedSemaphorePermit, pool: &Arc<RwLock<RPCConnectionPool>>) -> ProviderGuard {
+        ProviderGuard {
+            provider: self.provider,
+            batched_provider: self.batched_provider,
+            endpoint_info: self.info,
+            _permit: permit,
+            pool: Arc::downgrade(pool),
+        }
+    }
+}
+
+/// Kết quả chọn endpoint dưới lock
+enum Selection {
+    /// Endpoint còn permit, dùng ngay
+    Ready(SelectedEndpoint, tokio::sync::OwnedSemaphorePermit),
+    /// Endpoint đủ hạn mức nhưng đang hết permit, cần chờ ngoài lock
+    Busy(SelectedEndpoint),
+    /// Không có endpoint nào đủ hạn mức
+    Unavailable,
+}
+
 impl RPCConnectionPool {
     /// Tạo mới RPC pool
     pub async fn new(
@@ -231,7 +845,7 @@ impl RPCConnectionPool {
         // Khởi tạo endpoint chính
         let mut primary_endpoints = Vec::with_capacity(primary_urls.len());
         for (i, url) in primary_urls.iter().enumerate() {
-            match Self::create_endpoint(url, i as u32, config.max_connections).await {
+            match Self::create_endpoint(url, i as u32, &config).await {
                 Ok(endpoint) => primary_endpoints.push(endpoint),
                 Err(e) => {
                     warn!("Failed to initialize primary RPC endpoint {}: {}", url, e);
@@ -247,7 +861,7 @@ impl RPCConnectionPool {
         // Khởi tạo endpoint backup
         let mut backup_endpoints = Vec::with_capacity(backup_urls.len());
         for (i, url) in backup_urls.iter().enumerate() {
-            match Self::create_endpoint(url, (i + 100) as u32, config.max_connections).await {
+            match Self::create_endpoint(url, (i + 100) as u32, &config).await {
                 Ok(endpoint) => backup_endpoints.push(endpoint),
                 Err(e) => {
                     warn!("Failed to initialize backup RPC endpoint {}: {}", url, e);
@@ -259,7 +873,10 @@ impl RPCConnectionPool {
         info!("Initialized RPC connection pool with {} primary and {} backup endpoints", 
               primary_endpoints.len(), backup_endpoints.len());
         
-        Ok(Self {
+        let (stale_lag, fresh_lag) = block_lag_thresholds(DEFAULT_BLOCK_TIME_SECONDS);
+        let max_block_lag = config.max_block_lag.unwrap_or(stale_lag);
+        
+        let mut pool = Self {
             primary_endpoints,
             backup_endpoints,
             next_primary_index: 0,
@@ -267,12 +884,51 @@ impl RPCConnectionPool {
             retry_policy,
             config,
             chain_id,
-            cache: JSONCache::new(),
-        })
+            cache: JSONCache::new(AsyncCache::new(None)),
+            max_block_lag,
+            fresh_block_lag: fresh_lag.min(max_block_lag),
+            best_head: 0,
+        };
+        pool.update_block_lag();
+        
+        Ok(pool)
+    }
+    
+    /// Đặt block time trung bình của chain (giây) để suy ra ngưỡng lag
+    pub fn set_avg_block_time(&mut self, avg_block_time_secs: f64) {
+        let (stale_lag, fresh_lag) = block_lag_thresholds(avg_block_time_secs);
+        self.max_block_lag = self.config.max_block_lag.unwrap_or(stale_lag);
+        self.fresh_block_lag = fresh_lag.min(self.max_block_lag);
+        debug!("Chain {} block lag thresholds: stale > {} blocks, fresh <= {} blocks",
+               self.chain_id, self.max_block_lag, self.fresh_block_lag);
+        self.update_block_lag();
+    }
+    
+    /// Tính lại lag của từng endpoint so với head tốt nhất trong pool
+    fn update_block_lag(&mut self) {
+        let max_lag = self.max_block_lag;
+        let mut infos: Vec<&mut EndpointInfo> = self.primary_endpoints.iter_mut()
+            .chain(self.backup_endpoints.iter_mut())
+            .map(|e| &mut e.info)
+            .collect();
+        self.best_head = self.best_head.max(apply_block_lag(&mut infos, max_lag));
+        
+        for info in &infos {
+            gauge!("rpc_endpoint_block_lag", info.block_lag as f64, "url" => info.url.clone());
+        }
+        gauge!("rpc_pool_best_head", self.best_head as f64, "chain_id" => self.chain_id.to_string());
+    }
+    
+    /// Head tốt nhất trong pool ở lần kiểm tra gần nhất
+    pub fn best_head(&self) -> u64 {
+        self.best_head
     }
     
     /// Tạo PooledEndpoint từ URL
-    async fn create_endpoint(url: &str, priority: u32, max_concurrent: usize) -> Result<PooledEndpoint> {
+    async fn create_endpoint(url: &str, priority: u32, config: &ConnectionPoolConfig) -> Result<PooledEndpoint> {
+        let max_concurrent = config.max_connections;
+        let rate_limit = config.endpoint_rate_limits.get(url).unwrap_or(&config.rate_limit);
+        
         // Tạo provider
         let provider = Provider::<Http>::try_from(url)
             .with_context(|| format!("Failed to create provider for URL: {}", url))?;
@@ -282,13 +938,18 @@ impl RPCConnectionPool {
             Duration::from_secs(5),
             provider.get_block_number()
         ).await {
-            Ok(Ok(_)) => {
+            Ok(Ok(head)) => {
                 // Provider hoạt động, tạo endpoint
+                let mut info = EndpointInfo::new(url, priority, max_concurrent);
+                info.head_block = head.as_u64();
                 Ok(PooledEndpoint {
-                    info: EndpointInfo::new(url, priority, max_concurrent),
+                    info,
                     provider,
+                    batched_provider: Provider::new(BatchingHttp::new(url, config.batching.clone())),
                     semaphore: Arc::new(Semaphore::new(max_concurrent)),
                     last_used: Instant::now(),
+                    latency_window: VecDeque::with_capacity(LATENCY_WINDOW_SIZE),
+                    limiter: EndpointLimiter::new(rate_limit, Instant::now()),
                 })
             },
             _ => {
@@ -298,87 +959,288 @@ impl RPCConnectionPool {
         }
     }
     
-    /// Lấy provider theo round-robin
-    pub async fn get_provider(&mut self) -> Result<ProviderGuard> {
-        // Thử endpoints chính trước
-        if !self.primary_endpoints.is_empty() {
-            // Tìm endpoint chính khả dụng
-            for _ in 0..self.primary_endpoints.len() {
-                let index = self.next_primary_index;
-                self.next_primary_index = (self.next_primary_index + 1) % self.primary_endpoints.len();
+    /// Lấy provider theo chiến lược định tuyến đã cấu hình (mặc định round-robin)
+    pub async fn get_provider(pool: &Arc<RwLock<Self>>) -> Result<ProviderGuard> {
+        Self::get_provider_for_method(pool, "").await
+    }
+    
+    /// Lấy provider cho một method JSON-RPC, chỉ chọn endpoint còn hạn mức request/compute unit.
+    /// Endpoint hết hạn mức hoặc đang backoff do rate limit bị bỏ qua để chuyển tải sang endpoint khác.
+    /// Lock của pool chỉ được giữ khi chọn endpoint; việc chờ hạn mức và chờ permit diễn ra ngoài lock.
+    pub async fn get_provider_for_method(pool: &Arc<RwLock<Self>>, method: &str) -> Result<ProviderGuard> {
+        let (cost, wait, connection_timeout, chain_id) = {
+            let mut guard = write_pool(pool)?;
+            let cost = guard.config.compute_unit_costs.cost(method);
+            let wait = guard.time_until_budget(cost);
+            (cost, wait, Duration::from_millis(guard.config.connection_timeout), guard.chain_id)
+        };
+        
+        // Mọi endpoint đều hết hạn mức: chờ nếu thời gian chờ ngắn hơn connection timeout
+        if wait > Duration::ZERO {
+            if wait > connection_timeout {
+                counter!("rpc_pool_budget_exhausted", 1, "chain_id" => chain_id.to_string());
+                return Err(anyhow!(
+                    "All RPC endpoints on chain {} are rate limited, retry in {:?}",
+                    chain_id, wait
+                ));
+            }
+            debug!("All RPC endpoints on chain {} throttled, waiting {:?}", chain_id, wait);
+            tokio::time::sleep(wait).await;
+        }
+        
+        let selection = write_pool(pool)?.select_endpoint(cost);
+        let selected = match selection {
+            Selection::Ready(selected, permit) => return Ok(selected.into_guard(permit, pool)),
+            Selection::Busy(selected) => selected,
+            Selection::Unavailable => return Err(anyhow!("No available RPC endpoints")),
+        };
+        
+        // Endpoint đang đủ tải: chờ permit ngoài lock trong giới hạn connection timeout
+        match tokio::time::timeout(connection_timeout, selected.semaphore.clone().acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(selected.into_guard(permit, pool)),
+            _ => Err(anyhow!("No available RPC endpoints")),
+        }
+    }
+    
+    /// Chọn endpoint (chính trước, backup sau) theo chiến lược định tuyến. Endpoint còn permit được
+    /// trả về ngay; nếu tất cả đều đủ tải thì trả về endpoint đầu tiên đủ hạn mức để chờ permit.
+    fn select_endpoint(&mut self, cost: u32) -> Selection {
+        let mut busy = None;
+        
+        for primary in [true, false] {
+            let endpoints_len = if primary { self.primary_endpoints.len() } else { self.backup_endpoints.len() };
+            if endpoints_len == 0 {
+                continue;
+            }
+            
+            let order = self.candidate_order(primary);
+            if primary {
+                self.next_primary_index = (self.next_primary_index + 1) % endpoints_len;
+            } else {
+                self.next_backup_index = (self.next_backup_index + 1) % endpoints_len;
+            }
+            
+            for index in order {
+                let endpoint = if primary {
+                    &mut self.primary_endpoints[index]
+                } else {
+                    &mut self.backup_endpoints[index]
+                };
                 
-                let endpoint = &self.primary_endpoints[index];
-                if endpoint.info.is_available() {
-                    // Cố gắng lấy permit từ semaphore
-                    match tokio::time::timeout(
-                        Duration::from_millis(self.config.connection_timeout),
-                        endpoint.semaphore.clone().acquire_owned()
-                    ).await {
-                        Ok(Ok(permit)) => {
-                            // Đã có permit, trả về provider
-                            let endpoint = &mut self.primary_endpoints[index];
-                            endpoint.last_used = Instant::now();
-                            
-                            // Khi tạo ProviderGuard, sử dụng Arc::downgrade để cung cấp Weak reference
-                            let pool_arc = Arc::new(RwLock::new(self));
-                            let pool_weak = Arc::downgrade(&pool_arc);
-                            
-                            return Ok(ProviderGuard {
-                                provider: endpoint.provider.clone(),
-                                endpoint_info: endpoint.info.clone(),
-                                _permit: permit,
-                                pool: pool_weak,
-                            });
-                        },
-                        _ => {
-                            // Không thể lấy permit, thử endpoint tiếp theo
-                            continue;
-                        }
-                    }
+                // Endpoint hết permit chỉ được giữ làm phương án chờ, không tính hạn mức hai lần
+                let permit = endpoint.semaphore.clone().try_acquire_owned().ok();
+                if permit.is_none() && busy.is_some() {
+                    continue;
+                }
+                if !Self::admit(endpoint, cost) {
+                    continue;
+                }
+                
+                endpoint.last_used = Instant::now();
+                let selected = SelectedEndpoint::from_endpoint(endpoint);
+                match permit {
+                    Some(permit) => return Selection::Ready(selected, permit),
+                    None => busy = Some(selected),
                 }
             }
         }
         
-        // Nếu không có endpoint chính khả dụng, thử endpoints backup
-        if !self.backup_endpoints.is_empty() {
-            for _ in 0..self.backup_endpoints.len() {
-                let index = self.next_backup_index;
-                self.next_backup_index = (self.next_backup_index + 1) % self.backup_endpoints.len();
-                
-                let endpoint = &self.backup_endpoints[index];
-                if endpoint.info.is_available() {
-                    // Cố gắng lấy permit từ semaphore
-                    match tokio::time::timeout(
-                        Duration::from_millis(self.config.connection_timeout),
-                        endpoint.semaphore.clone().acquire_owned()
-                    ).await {
-                        Ok(Ok(permit)) => {
-                            // Đã có permit, trả về provider
-                            let endpoint = &mut self.backup_endpoints[index];
-                            endpoint.last_used = Instant::now();
-                            
-                            // Khi tạo ProviderGuard, sử dụng Arc::downgrade để cung cấp Weak reference
-                            let pool_arc = Arc::new(RwLock::new(self));
-                            let pool_weak = Arc::downgrade(&pool_arc);
-                            
-                            return Ok(ProviderGuard {
-                                provider: endpoint.provider.clone(),
-                                endpoint_info: endpoint.info.clone(),
-                                _permit: permit,
-                                pool: pool_weak,
-                            });
-                        },
-                        _ => {
-                            // Không thể lấy permit, thử endpoint tiếp theo
-                            continue;
-                        }
-                    }
+        match busy {
+            Some(selected) => Selection::Busy(selected),
+            None => Selection::Unavailable,
+        }
+    }
+    
+    /// Thời gian chờ ngắn nhất trước khi có endpoint đủ hạn mức cho chi phí `cost` CU
+    fn time_until_budget(&mut self, cost: u32) -> Duration {
+        let now = Instant::now();
+        self.primary_endpoints.iter_mut()
+            .chain(self.backup_endpoints.iter_mut())
+            .filter(|endpoint| endpoint.info.is_available())
+            .map(|endpoint| endpoint.limiter.time_until_available(cost, now))
+            .min()
+            .unwrap_or(Duration::ZERO)
+    }
+    
+    /// Cấp hạn mức cho request trên endpoint và cập nhật metrics ngân sách
+    fn admit(endpoint: &mut PooledEndpoint, cost: u32) -> bool {
+        let now = Instant::now();
+        let url = endpoint.info.url.clone();
+        
+        match endpoint.limiter.try_acquire(cost, now) {
+            Ok(()) => {
+                endpoint.info.compute_units_used += cost as u64;
+                counter!("rpc_endpoint_compute_units", cost as u64, "url" => url.clone());
+                if let Some(remaining) = endpoint.limiter.compute_budget_remaining(now) {
+                    gauge!("rpc_endpoint_compute_budget_remaining", remaining, "url" => url);
                 }
+                true
+            },
+            Err(wait) => {
+                endpoint.info.throttled_count += 1;
+                counter!("rpc_endpoint_throttled", 1, "url" => url.clone());
+                debug!("RPC endpoint {} throttled for {:?}", url, wait);
+                false
             }
         }
+    }
+    
+    /// Thứ tự endpoint (chính hoặc backup) theo chiến lược định tuyến
+    fn candidate_order(&self, primary: bool) -> Vec<usize> {
+        let (endpoints, start_index) = if primary {
+            (&self.primary_endpoints, self.next_primary_index)
+        } else {
+            (&self.backup_endpoints, self.next_backup_index)
+        };
+        let infos: Vec<&EndpointInfo> = endpoints.iter().map(|e| &e.info).collect();
+        
+        order_candidates(self.config.routing_strategy, &infos, start_index, rand::random::<f64>())
+    }
+    
+    /// Danh sách provider khả dụng (chính trước, backup sau) theo thứ tự chiến lược định tuyến.
+    /// Với `fresh_only` chỉ giữ endpoint ở gần tip.
+    fn ordered_providers(&self, strategy: RoutingStrategy, fresh_only: bool) -> Vec<(String, Provider<Http>, Option<f64>)> {
+        let mut result = Vec::new();
+        let mut throttled = Vec::new();
+        
+        for (endpoints, start_index) in [
+            (&self.primary_endpoints, self.next_primary_index),
+            (&self.backup_endpoints, self.next_backup_index),
+        ] {
+            let infos: Vec<&EndpointInfo> = endpoints.iter().map(|e| &e.info).collect();
+            for index in order_candidates(strategy, &infos, start_index, rand::random::<f64>()) {
+                let endpoint = &endpoints[index];
+                if fresh_only && !endpoint.info.is_near_tip(self.fresh_block_lag) {
+                    continue;
+                }
+                // Endpoint đang backoff do rate limit nhận tải sau cùng
+                if endpoint.limiter.backoff_remaining(Instant::now()).is_some() {
+                    throttled.push((endpoint.info.url.clone(), endpoint.provider.clone(), percentile(&endpoint.latency_window, 90.0)));
+                    continue;
+                }
+                result.push((
+                    endpoint.info.url.clone(),
+                    endpoint.provider.clone(),
+                    percentile(&endpoint.latency_window, 90.0),
+                ));
+            }
+        }
+        
+        result.extend(throttled);
+        result
+    }
+    
+    /// Thay đổi chiến lược định tuyến
+    pub fn set_routing_strategy(&mut self, strategy: RoutingStrategy) {
+        info!("Chain {} RPC routing strategy: {:?} -> {:?}", self.chain_id, self.config.routing_strategy, strategy);
+        self.config.routing_strategy = strategy;
+    }
+    
+    /// Chọn endpoint cho quorum read: `fanout` endpoint khỏe nhất ở gần tip.
+    /// Chỉ đọc trạng thái pool, gọi dưới lock ngắn rồi thực hiện request ngoài lock.
+    fn quorum_candidates(&self) -> Result<(ReadCandidates, QuorumConfig)> {
+        let quorum = self.config.quorum.clone();
+        let candidates: ReadCandidates = self
+            .ordered_providers(RoutingStrategy::WeightedRandom, true)
+            .into_iter()
+            .take(quorum.fanout.max(quorum.min_agreement))
+            .map(|(url, provider, _)| (url, provider))
+            .collect();
+        
+        let configured = self.primary_endpoints.len() + self.backup_endpoints.len();
+        if configured < quorum.min_agreement {
+            return Err(QuorumUnavailable { chain_id: self.chain_id, configured, required: quorum.min_agreement }.into());
+        }
+        if candidates.len() < quorum.min_agreement {
+            return Err(anyhow!(
+                "Not enough healthy RPC endpoints for quorum on chain {}: {} available, {} required",
+                self.chain_id, candidates.len(), quorum.min_agreement
+            ));
+        }
+        Ok((candidates, quorum))
+    }
+    
+    /// Ghi nhận kết quả quorum read vào thống kê endpoint và chỉ trả kết quả khi
+    /// ít nhất `min_agreement` endpoint đồng thuận. Endpoint bất đồng hoặc timeout bị hạ cấp.
+    fn apply_quorum_outcomes<T: PartialEq + Clone>(&mut self, outcomes: Vec<ReadOutcome<T>>, min_agreement: usize) -> Result<T> {
+        let mut responses = Vec::new();
+        for (url, result, latency) in outcomes {
+            match result {
+                ReadResult::Ok(value) => {
+                    self.record_success(&url, latency);
+                    responses.push((url, Some(value)));
+                },
+                ReadResult::Failed(e) => {
+                    debug!("Quorum read failed on {}: {}", url, e);
+                    self.record_failure(&url, &e);
+                    responses.push((url, None));
+                },
+                ReadResult::TimedOut => {
+                    // Timeout tính một lần như lỗi; bất đồng chỉ dành cho kết quả sai
+                    debug!("Quorum read timed out on {}", url);
+                    self.record_error(&url);
+                    responses.push((url, None));
+                }
+            }
+        }
+        
+        let outcome = tally_quorum(responses, min_agreement);
+        
+        for url in &outcome.agreeing {
+            self.with_endpoint_mut(url, |info| info.record_agreement());
+        }
+        for url in &outcome.disagreeing {
+            counter!("rpc_quorum_disagreements", 1, "url" => url.clone());
+            self.with_endpoint_mut(url, |info| info.demote("disagreed with quorum"));
+        }
         
-        // Không có endpoint nào khả dụng
-        Err(anyhow!("No available RPC endpoints"))
+        outcome.value.ok_or_else(|| anyhow!(
+            "Quorum not reached on chain {}: need {} matching responses ({} failed)",
+            self.chain_id, min_agreement, outcome.failed.len()
+        ))
+    }
+    
+    /// Chọn endpoint cho read cần state mới (nonce, số dư, reserves): chỉ endpoint ở gần tip,
+    /// theo chiến lược định tuyến hiện tại. Với chiến lược Hedged trả thêm độ trễ dự phòng mặc định.
+    fn fresh_candidates(&self) -> Result<FreshPlan> {
+        let candidates = self.ordered_providers(self.config.routing_strategy, true);
+        if candidates.is_empty() {
+            return Err(anyhow!(
+                "No RPC endpoint within {} blocks of head {} on chain {}",
+                self.fresh_block_lag, self.best_head, self.chain_id
+            ));
+        }
+        Ok(FreshPlan {
+            candidates,
+            timeout: Duration::from_millis(self.config.request_timeout),
+            hedge_delay: (self.config.routing_strategy == RoutingStrategy::Hedged)
+                .then(|| Duration::from_millis(self.config.hedge_delay_ms)),
+        })
+    }
+    
+    /// Ghi nhận kết quả các lần thử của fresh read vào thống kê endpoint
+    fn apply_fresh_outcomes<T>(&mut self, outcomes: &[ReadOutcome<T>]) {
+        for (url, result, latency) in outcomes {
+            match result {
+                ReadResult::Ok(_) => self.record_success(url, *latency),
+                ReadResult::Failed(e) => {
+                    debug!("Fresh read failed on {}: {}", url, e);
+                    self.record_failure(url, e);
+                },
+                ReadResult::TimedOut => self.record_error(url),
+            }
+        }
+    }
+    
+    /// Cập nhật thông tin endpoint theo URL
+    fn with_endpoint_mut<F: FnOnce(&mut EndpointInfo)>(&mut self, url: &str, update: F) {
+        if let Some(endpoint) = self.primary_endpoints.iter_mut()
+            .chain(self.backup_endpoints.iter_mut())
+            .find(|e| e.info.url == url)
+        {
+            update(&mut endpoint.info);
+            gauge!("rpc_endpoint_health_score", endpoint.info.health_score(), "url" => url.to_string());
+        }
     }
     
     /// Cập nhật trạng thái endpoint sau một request thành công
@@ -387,6 +1249,8 @@ impl RPCConnectionPool {
         for endpoint in &mut self.primary_endpoints {
             if endpoint.info.url == url {
                 endpoint.info.record_success(latency_ms);
+                endpoint.limiter.on_success();
+                Self::push_latency(&mut endpoint.latency_window, latency_ms);
                 
                 // Cập nhật metrics
                 gauge!("rpc_endpoint_latency", latency_ms, "url" => url.to_string());
@@ -400,6 +1264,8 @@ impl RPCConnectionPool {
         for endpoint in &mut self.backup_endpoints {
             if endpoint.info.url == url {
                 endpoint.info.record_success(latency_ms);
+                endpoint.limiter.on_success();
+                Self::push_latency(&mut endpoint.latency_window, latency_ms);
                 
                 // Cập nhật metrics
                 gauge!("rpc_endpoint_latency", latency_ms, "url" => url.to_string());
@@ -410,6 +1276,14 @@ impl RPCConnectionPool {
         }
     }
     
+    /// Thêm mẫu độ trễ vào cửa sổ trượt
+    fn push_latency(window: &mut VecDeque<f64>, latency_ms: f64) {
+        if window.len() >= LATENCY_WINDOW_SIZE {
+            window.pop_front();
+        }
+        window.push_back(latency_ms);
+    }
+    
     /// Cập nhật trạng thái endpoint sau một request lỗi
     fn record_error(&mut self, url: &str) {
         // Cập nhật endpoint chính
@@ -439,36 +1313,97 @@ impl RPCConnectionPool {
         }
     }
     
-    /// Thực hiện health check cho tất cả endpoint
-    pub async fn health_check_all(&mut self) {
-        // Kiểm tra endpoint chính
-        for endpoint in &mut self.primary_endpoints {
-            self.health_check_endpoint(endpoint).await;
+    /// Cập nhật trạng thái endpoint theo lỗi trả về: lỗi rate limit (HTTP 429, -32005...)
+    /// chỉ đưa endpoint vào backoff, không tính là node lỗi
+    fn record_failure(&mut self, url: &str, error: &anyhow::Error) {
+        match rate_limiter::detect_rate_limit(&error.to_string()) {
+            Some(retry_after) => self.record_rate_limited(url, retry_after),
+            None => self.record_error(url),
         }
+    }
+    
+    /// Đưa endpoint vào backoff sau khi bị rate limit, tôn trọng Retry-After nếu có
+    fn record_rate_limited(&mut self, url: &str, retry_after: Option<Duration>) {
+        let now = Instant::now();
+        let endpoint = match self.primary_endpoints.iter_mut()
+            .chain(self.backup_endpoints.iter_mut())
+            .find(|endpoint| endpoint.info.url == url)
+        {
+            Some(endpoint) => endpoint,
+            None => return,
+        };
         
-        // Kiểm tra endpoint backup
-        for endpoint in &mut self.backup_endpoints {
-            self.health_check_endpoint(endpoint).await;
+        let backoff = endpoint.limiter.on_rate_limited(retry_after, now);
+        endpoint.info.rate_limited_count += 1;
+        endpoint.info.rate_limited_until = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .saturating_add(backoff)
+            .as_secs();
+        
+        // Cập nhật metrics
+        counter!("rpc_endpoint_rate_limited", 1, "url" => url.to_string());
+        warn!("RPC endpoint {} rate limited, backing off for {:?}", url, backoff);
+    }
+    
+    /// Thực hiện health check cho tất cả endpoint. Các request chạy song song ngoài lock,
+    /// lock của pool chỉ được giữ khi ghi kết quả.
+    pub async fn health_check_all(pool: &Arc<RwLock<Self>>) {
+        let targets: Vec<(String, Provider<Http>)> = match read_pool(pool) {
+            Ok(guard) => guard.primary_endpoints.iter()
+                .chain(guard.backup_endpoints.iter())
+                .map(|endpoint| (endpoint.info.url.clone(), endpoint.provider.clone()))
+                .collect(),
+            Err(e) => {
+                warn!("Skipping RPC health check: {}", e);
+                return;
+            }
+        };
+        
+        let checks = targets.into_iter().map(|(url, provider)| async move {
+            debug!("Performing health check for RPC endpoint: {}", url);
+            let start = Instant::now();
+            let head = match tokio::time::timeout(Duration::from_secs(5), provider.get_block_number()).await {
+                Ok(Ok(head)) => Some(head.as_u64()),
+                _ => None,
+            };
+            (url, head, start.elapsed().as_millis() as f64)
+        });
+        let results = join_all(checks).await;
+        
+        let mut guard = match write_pool(pool) {
+            Ok(guard) => guard,
+            Err(e) => {
+                warn!("Dropping RPC health check results: {}", e);
+                return;
+            }
+        };
+        for (url, head, latency) in results {
+            guard.apply_health_check(&url, head, latency);
         }
+        
+        // So sánh head của từng endpoint với head tốt nhất
+        guard.update_block_lag();
     }
     
-    /// Thực hiện health check cho một endpoint cụ thể
-    async fn health_check_endpoint(&mut self, endpoint: &mut PooledEndpoint) {
-        let url = endpoint.info.url.clone();
-        debug!("Performing health check for RPC endpoint: {}", url);
+    /// Ghi kết quả health check của một endpoint
+    fn apply_health_check(&mut self, url: &str, head: Option<u64>, latency: f64) {
+        let Some(endpoint) = self.primary_endpoints.iter_mut()
+            .chain(self.backup_endpoints.iter_mut())
+            .find(|endpoint| endpoint.info.url == url)
+        else {
+            return;
+        };
         
-        let start = Instant::now();
-        match tokio::time::timeout(
-            Duration::from_secs(5),
-            endpoint.provider.get_block_number()
-        ).await {
-            Ok(Ok(_)) => {
-                // Endpoint hoạt động
-                let latency = start.elapsed().as_millis() as f64;
+        match head {
+            Some(head) => {
+                // Endpoint hoạt động; endpoint Down chỉ được thử lại sau thời gian cách ly
+                endpoint.info.head_block = head;
+                endpoint.info.recover_if_due(unix_now());
                 endpoint.info.record_success(latency);
-                debug!("Health check passed for RPC endpoint {} with latency {}ms", url, latency);
+                debug!("Health check passed for RPC endpoint {} with latency {}ms at block {}", url, latency, head);
             },
-            _ => {
+            None => {
                 // Endpoint không hoạt động
                 endpoint.info.record_error();
                 warn!("Health check failed for RPC endpoint {}", url);
@@ -478,18 +1413,26 @@ impl RPCConnectionPool {
     
     /// Thực hiện health check định kỳ
     pub async fn start_health_check_loop(pool: Arc<RwLock<Self>>) {
-        let interval = {
-            let guard = pool.read().unwrap();
-            Duration::from_secs(guard.config.health_check_interval)
+        let (chain_id, interval) = match read_pool(&pool) {
+            Ok(guard) => (guard.chain_id, Duration::from_secs(guard.config.health_check_interval)),
+            Err(e) => {
+                warn!("Not starting RPC health check loop: {}", e);
+                return;
+            }
         };
         
         loop {
             // Đợi khoảng thời gian cấu hình
             tokio::time::sleep(interval).await;
             
+            // Dừng khi pool đã bị gỡ khỏi manager (chain bị xoá hoặc nạp lại cấu hình)
+            if !get_pool(chain_id).is_some_and(|current| Arc::ptr_eq(&current, &pool)) {
+                debug!("Stopping health check loop for removed pool of chain {}", chain_id);
+                break;
+            }
+            
             // Thực hiện health check
-            let mut guard = pool.write().unwrap();
-            guard.health_check_all().await;
+            Self::health_check_all(&pool).await;
         }
     }
     
@@ -510,49 +1453,55 @@ impl RPCConnectionPool {
         result
     }
     
-    /// Thêm endpoint mới
-    pub async fn add_endpoint(&mut self, url: &str, is_primary: bool) -> Result<()> {
-        // Kiểm tra endpoint đã tồn tại chưa
-        for endpoint in &self.primary_endpoints {
-            if endpoint.info.url == url {
-                return Err(anyhow!("Endpoint already exists: {}", url));
-            }
-        }
-        
-        for endpoint in &self.backup_endpoints {
-            if endpoint.info.url == url {
-                return Err(anyhow!("Endpoint already exists: {}", url));
-            }
-        }
-        
-        // Khởi tạo endpoint mới
-        let priority = if is_primary {
-            self.primary_endpoints.len() as u32
-        } else {
-            (self.backup_endpoints.len() + 100) as u32
+    /// Thêm endpoint mới. Endpoint được khởi tạo (và kiểm tra kết nối) ngoài lock của pool.
+    pub async fn add_endpoint(pool: &Arc<RwLock<Self>>, url: &str, is_primary: bool) -> Result<()> {
+        let (priority, config) = {
+            let guard = read_pool(pool)?;
+            guard.ensure_new_endpoint(url)?;
+            let priority = if is_primary {
+                guard.primary_endpoints.len() as u32
+            } else {
+                (guard.backup_endpoints.len() + 100) as u32
+            };
+            (priority, guard.config.clone())
         };
         
-        let new_endpoint = Self::create_endpoint(url, priority, self.config.max_connections).await?;
+        let new_endpoint = Self::create_endpoint(url, priority, &config).await?;
+        
+        // Kiểm tra lại vì endpoint có thể đã được thêm trong lúc khởi tạo
+        let mut guard = write_pool(pool)?;
+        guard.ensure_new_endpoint(url)?;
         
         // Thêm vào danh sách tương ứng
         if is_primary {
-            self.primary_endpoints.push(new_endpoint);
+            guard.primary_endpoints.push(new_endpoint);
             info!("Added new primary RPC endpoint: {}", url);
         } else {
-            self.backup_endpoints.push(new_endpoint);
+            guard.backup_endpoints.push(new_endpoint);
             info!("Added new backup RPC endpoint: {}", url);
         }
         
         Ok(())
     }
     
+    /// Kiểm tra endpoint chưa tồn tại trong pool
+    fn ensure_new_endpoint(&self, url: &str) -> Result<()> {
+        if self.primary_endpoints.iter()
+            .chain(self.backup_endpoints.iter())
+            .any(|endpoint| endpoint.info.url == url)
+        {
+            return Err(anyhow!("Endpoint already exists: {}", url));
+        }
+        Ok(())
+    }
+    
     /// Thực hiện hàm async với provider từ pool
-    pub async fn with_provider<F, Fut, T>(&mut self, operation: F) -> Result<T>
+    pub async fn with_provider<F, Fut, T>(pool: &Arc<RwLock<Self>>, operation: F) -> Result<T>
     where
         F: FnOnce(Provider<Http>) -> Fut,
         Fut: Future<Output = Result<T>>,
     {
-        let provider_guard = self.get_provider().await?;
+        let provider_guard = Self::get_provider(pool).await?;
         let provider = provider_guard.provider.clone();
         
         let start = Instant::now();
@@ -561,12 +1510,8 @@ impl RPCConnectionPool {
         
         // Cập nhật thông tin endpoint
         match &result {
-            Ok(_) => {
-                self.record_success(&provider_guard.endpoint_info.url, latency);
-            },
-            Err(_) => {
-                self.record_error(&provider_guard.endpoint_info.url);
-            }
+            Ok(_) => provider_guard.report_success(latency),
+            Err(e) => provider_guard.report_failure(e),
         }
         
         result
@@ -587,6 +1532,9 @@ impl RPCConnectionPool {
 pub struct ProviderGuard {
     /// Provider đang sử dụng
     pub provider: Provider<Http>,
+    /// Provider cùng endpoint nhưng tự gom các call phát sinh gần nhau thành một JSON-RPC batch
+    /// và gộp các call giống hệt nhau đang chờ kết quả
+    batched_provider: Provider<BatchingHttp>,
     /// Thông tin endpoint
     pub endpoint_info: EndpointInfo,
     /// Permit để quản lý số lượng kết nối đồng thời
@@ -596,6 +1544,12 @@ pub struct ProviderGuard {
 }
 
 impl ProviderGuard {
+    /// Provider cho các read: các call phát sinh đồng thời trên endpoint được gom thành một
+    /// JSON-RPC batch. Giao dịch (gửi, chờ receipt) dùng `provider`.
+    pub fn reader(&self) -> &Provider<BatchingHttp> {
+        &self.batched_provider
+    }
+    
     /// Thực hiện hàm async với provider
     pub async fn with_timeout<F, Fut, T>(&self, operation: F, timeout: Duration) -> Result<T>
     where
@@ -627,6 +1581,24 @@ impl ProviderGuard {
         }
     }
     
+    /// Báo cáo lỗi kèm nội dung, để pool phân biệt lỗi rate limit với lỗi node
+    pub fn report_failure(&self, error: &anyhow::Error) {
+        if let Some(pool) = self.pool.upgrade() {
+            if let Ok(mut pool_guard) = pool.write() {
+                pool_guard.record_failure(&self.endpoint_info.url, error);
+            }
+        }
+    }
+    
+    /// Báo cáo endpoint bị rate limit
+    pub fn report_rate_limited(&self, retry_after: Option<Duration>) {
+        if let Some(pool) = self.pool.upgrade() {
+            if let Ok(mut pool_guard) = pool.write() {
+                pool_guard.record_rate_limited(&self.endpoint_info.url, retry_after);
+            }
+        }
+    }
+    
     /// Báo cáo thành công
     pub fn report_success(&self, latency_ms: f64) {
         if let Some(pool) = self.pool.upgrade() {
@@ -651,21 +1623,13 @@ impl ConnectionPoolManager {
         }
     }
     
-    /// Lấy hoặc tạo pool cho chain ID cụ thể
-    pub async fn get_or_create_pool(
-        &mut self,
-        chain_id: u64,
-        primary_urls: Vec<String>,
-        backup_urls: Vec<String>,
-        config: Option<ConnectionPoolConfig>,
-    ) -> Result<Arc<RwLock<RPCConnectionPool>>> {
-        if let Some(pool) = self.pools.get(&chain_id) {
-            return Ok(pool.clone());
+    /// Đăng ký pool mới cho chain ID và bắt đầu health check loop. Nếu chain đã có pool
+    /// (được tạo song song) thì giữ pool hiện có.
+    pub fn register_pool(&mut self, chain_id: u64, pool: RPCConnectionPool) -> Arc<RwLock<RPCConnectionPool>> {
+        if let Some(existing) = self.pools.get(&chain_id) {
+            return existing.clone();
         }
         
-        // Tạo pool mới
-        let config = config.unwrap_or_default();
-        let pool = RPCConnectionPool::new(chain_id, primary_urls, backup_urls, config, None).await?;
         let pool_arc = Arc::new(RwLock::new(pool));
         
         // Thêm vào manager
@@ -677,7 +1641,7 @@ impl ConnectionPoolManager {
             RPCConnectionPool::start_health_check_loop(loop_pool).await;
         });
         
-        Ok(pool_arc)
+        pool_arc
     }
     
     /// Lấy pool hiện có cho chain ID
@@ -685,19 +1649,9 @@ impl ConnectionPoolManager {
         self.pools.get(&chain_id).cloned()
     }
     
-    /// Thêm endpoint mới cho pool
-    pub async fn add_endpoint(
-        &self,
-        chain_id: u64,
-        url: &str,
-        is_primary: bool,
-    ) -> Result<()> {
-        if let Some(pool) = self.pools.get(&chain_id) {
-            let mut guard = pool.write().unwrap();
-            guard.add_endpoint(url, is_primary).await
-        } else {
-            Err(anyhow!("No pool found for chain ID: {}", chain_id))
-        }
+    /// Gỡ pool của chain ID khỏi manager
+    pub fn remove_pool(&mut self, chain_id: u64) -> Option<Arc<RwLock<RPCConnectionPool>>> {
+        self.pools.remove(&chain_id)
     }
     
     /// Lấy thông tin tất cả pool
@@ -705,8 +1659,9 @@ impl ConnectionPoolManager {
         let mut result = HashMap::new();
         
         for (chain_id, pool) in &self.pools {
-            let guard = pool.read().unwrap();
-            result.insert(*chain_id, guard.get_endpoints_info());
+            if let Ok(guard) = read_pool(pool) {
+                result.insert(*chain_id, guard.get_endpoints_info());
+            }
         }
         
         result
@@ -717,31 +1672,447 @@ impl ConnectionPoolManager {
 pub static CONNECTION_POOL_MANAGER: once_cell::sync::Lazy<RwLock<ConnectionPoolManager>> = 
     once_cell::sync::Lazy::new(|| RwLock::new(ConnectionPoolManager::new()));
 
-/// Lấy hoặc tạo pool cho chain ID
+/// Khóa manager toàn cục, bỏ qua poisoned lock vì manager chỉ giữ map các pool
+fn pool_manager_read() -> std::sync::RwLockReadGuard<'static, ConnectionPoolManager> {
+    CONNECTION_POOL_MANAGER.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn pool_manager_write() -> std::sync::RwLockWriteGuard<'static, ConnectionPoolManager> {
+    CONNECTION_POOL_MANAGER.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Lấy hoặc tạo pool cho chain ID. Pool mới được khởi tạo ngoài lock của manager.
 pub async fn get_or_create_pool(
     chain_id: u64,
     primary_urls: Vec<String>,
     backup_urls: Vec<String>,
     config: Option<ConnectionPoolConfig>,
 ) -> Result<Arc<RwLock<RPCConnectionPool>>> {
-    let mut manager = CONNECTION_POOL_MANAGER.write().unwrap();
-    manager.get_or_create_pool(chain_id, primary_urls, backup_urls, config).await
+    if let Some(pool) = get_pool(chain_id) {
+        return Ok(pool);
+    }
+    
+    // Tạo pool mới
+    let config = config.unwrap_or_default();
+    let pool = RPCConnectionPool::new(chain_id, primary_urls, backup_urls, config, None).await?;
+    
+    Ok(pool_manager_write().register_pool(chain_id, pool))
 }
 
 /// Lấy pool hiện có cho chain ID
 pub fn get_pool(chain_id: u64) -> Option<Arc<RwLock<RPCConnectionPool>>> {
-    let manager = CONNECTION_POOL_MANAGER.read().unwrap();
-    manager.get_pool(chain_id)
+    pool_manager_read().get_pool(chain_id)
+}
+
+/// Gỡ pool của chain ID; health check loop của pool sẽ tự dừng
+pub fn remove_pool(chain_id: u64) -> bool {
+    pool_manager_write().remove_pool(chain_id).is_some()
 }
 
 /// Thêm endpoint mới cho pool
 pub async fn add_endpoint(chain_id: u64, url: &str, is_primary: bool) -> Result<()> {
-    let manager = CONNECTION_POOL_MANAGER.read().unwrap();
-    manager.add_endpoint(chain_id, url, is_primary).await
+    let pool = get_pool(chain_id).ok_or_else(|| anyhow!("No pool found for chain ID: {}", chain_id))?;
+    RPCConnectionPool::add_endpoint(&pool, url, is_primary).await
 }
 
 /// Lấy thông tin tất cả pool
 pub fn get_all_pools_info() -> HashMap<u64, Vec<EndpointInfo>> {
-    let manager = CONNECTION_POOL_MANAGER.read().unwrap();
-    manager.get_pools_info()
-} 
\ No newline at end of file
+    pool_manager_read().get_pools_info()
+}
+
+/// Thay đổi chiến lược định tuyến cho pool của chain
+pub fn set_routing_strategy(chain_id: u64, strategy: RoutingStrategy) -> Result<()> {
+    let pool = get_pool(chain_id).ok_or_else(|| anyhow!("No pool found for chain ID: {}", chain_id))?;
+    let mut guard = write_pool(&pool)?;
+    guard.set_routing_strategy(strategy);
+    Ok(())
+}
+
+/// Đặt block time trung bình (giây) cho pool của chain để suy ra ngưỡng lag
+pub fn set_avg_block_time(chain_id: u64, avg_block_time_secs: f64) -> Result<()> {
+    let pool = get_pool(chain_id).ok_or_else(|| anyhow!("No pool found for chain ID: {}", chain_id))?;
+    let mut guard = write_pool(&pool)?;
+    guard.set_avg_block_time(avg_block_time_secs);
+    Ok(())
+}
+
+/// Block chung cho một quorum read: head thấp nhất trong các endpoint được chọn, để mọi
+/// endpoint đều đọc được và endpoint chậm một block không bị tính là bất đồng
+async fn resolve_quorum_block(chain_id: u64, candidates: &ReadCandidates, quorum: &QuorumConfig) -> Result<BlockId> {
+    let timeout = Duration::from_millis(quorum.timeout_ms);
+    let heads = join_all(candidates.iter().map(|(_, provider)| async move {
+        match tokio::time::timeout(timeout, provider.get_block_number()).await {
+            Ok(Ok(head)) => Some(head),
+            _ => None,
+        }
+    })).await;
+    let heads: Vec<U64> = heads.into_iter().flatten().collect();
+    
+    if heads.len() < quorum.min_agreement {
+        return Err(anyhow!(
+            "Cannot resolve quorum block on chain {}: {} of {} endpoints returned a head",
+            chain_id, heads.len(), candidates.len()
+        ));
+    }
+    let block = heads.into_iter().min().unwrap_or_default();
+    Ok(BlockId::Number(BlockNumber::Number(block)))
+}
+
+/// Quorum read trên pool của chain: chọn endpoint dưới lock ngắn, chốt một block chung rồi gửi
+/// request ngoài lock với block đó, sau đó lock lại để ghi nhận thống kê và hạ cấp endpoint bất đồng
+pub async fn quorum_read<F, Fut, T>(chain_id: u64, operation: F) -> Result<T>
+where
+    F: Fn(Provider<Http>, BlockId) -> Fut,
+    Fut: Future<Output = Result<T>>,
+    T: PartialEq + Clone,
+{
+    let pool = get_pool(chain_id).ok_or_else(|| QuorumUnavailable {
+        chain_id,
+        configured: 0,
+        required: QuorumConfig::default().min_agreement,
+    })?;
+    let (candidates, quorum) = read_pool(&pool)?.quorum_candidates()?;
+    let block = resolve_quorum_block(chain_id, &candidates, &quorum).await?;
+    
+    let timeout = Duration::from_millis(quorum.timeout_ms);
+    let requests = candidates.into_iter().map(|(url, provider)| {
+        let request = operation(provider, block);
+        async move {
+            let start = Instant::now();
+            let result = match tokio::time::timeout(timeout, request).await {
+                Ok(Ok(value)) => ReadResult::Ok(value),
+                Ok(Err(e)) => ReadResult::Failed(e),
+                Err(_) => ReadResult::TimedOut,
+            };
+            (url, result, start.elapsed().as_millis() as f64)
+        }
+    });
+    let outcomes = join_all(requests).await;
+    
+    let result = write_pool(&pool)?.apply_quorum_outcomes(outcomes, quorum.min_agreement);
+    result
+}
+
+/// Read cần state mới trên pool của chain: thử các endpoint gần tip ngoài lock cho tới khi
+/// thành công (hedged với chiến lược Hedged), sau đó lock ngắn để ghi nhận kết quả từng lần thử
+pub async fn fresh_read<F, Fut, T>(chain_id: u64, operation: F) -> Result<T>
+where
+    F: Fn(Provider<Http>) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let pool = get_pool(chain_id).ok_or_else(|| anyhow!("No pool found for chain ID: {}", chain_id))?;
+    let plan = read_pool(&pool)?.fresh_candidates()?;
+    
+    let (value, mut outcomes) = match plan.hedge_delay {
+        Some(default_delay) => hedged_attempts(plan, default_delay, &operation).await,
+        None => sequential_attempts(plan, &operation).await,
+    };
+    
+    write_pool(&pool)?.apply_fresh_outcomes(&outcomes);
+    
+    value.ok_or_else(|| match outcomes.pop() {
+        Some((_, ReadResult::Failed(e), _)) => e,
+        Some((url, _, _)) => anyhow!("Fresh read timed out on {}", url),
+        None => anyhow!("Fresh read failed on chain {}", chain_id),
+    })
+}
+
+/// Lấy nonce (pending) từ endpoint ở gần tip
+pub async fn fresh_get_transaction_count(chain_id: u64, address: Address) -> Result<U256> {
+    fresh_read(chain_id, |provider| async move {
+        provider.get_transaction_count(address, Some(ethers::types::BlockNumber::Pending.into())).await
+            .map_err(|e| anyhow!("get_transaction_count failed: {}", e))
+    }).await
+}
+
+/// Encode calldata cho Router.getAmountsOut(uint256,address[])
+fn encode_get_amounts_out(amount_in: U256, path: &[Address]) -> Vec<u8> {
+    let mut data = ethers::utils::id("getAmountsOut(uint256,address[])").to_vec();
+    data.extend(abi::encode(&[
+        Token::Uint(amount_in),
+        Token::Array(path.iter().map(|a| Token::Address(*a)).collect()),
+    ]));
+    data
+}
+
+/// Decode kết quả uint256[] của getAmountsOut
+fn decode_amounts_out(output: &[u8]) -> Result<Vec<U256>> {
+    let tokens = abi::decode(&[ParamType::Array(Box::new(ParamType::Uint(256)))], output)
+        .map_err(|e| anyhow!("Failed to decode getAmountsOut: {}", e))?;
+    tokens.into_iter().next()
+        .and_then(|token| token.into_array())
+        .ok_or_else(|| anyhow!("getAmountsOut returned unexpected data"))?
+        .into_iter()
+        .map(|token| token.into_uint().ok_or_else(|| anyhow!("getAmountsOut returned non-uint value")))
+        .collect()
+}
+
+/// Lấy số dư native token với quorum read
+pub async fn quorum_get_balance(chain_id: u64, address: Address) -> Result<U256> {
+    quorum_read(chain_id, |provider, block| async move {
+        provider.get_balance(address, Some(block)).await
+            .map_err(|e| anyhow!("get_balance failed: {}", e))
+    }).await
+}
+
+/// Lấy getAmountsOut từ router với quorum read
+pub async fn quorum_get_amounts_out(chain_id: u64, router: Address, amount_in: U256, path: Vec<Address>) -> Result<Vec<U256>> {
+    let calldata = encode_get_amounts_out(amount_in, &path);
+    quorum_read(chain_id, |provider, block| {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(router)
+            .data(calldata.clone())
+            .into();
+        async move {
+            let output = provider.call(&tx, Some(block)).await
+                .map_err(|e| anyhow!("getAmountsOut call failed: {}", e))?;
+            decode_amounts_out(&output)
+        }
+    }).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_adapters::tests::mock_rpc::spawn_mock_rpc;
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+
+    /// Mock node có head `head`; số dư tại block n là `n * per_block`, block được yêu cầu ghi vào `requested`
+    async fn spawn_head_node(head: u64, per_block: u64, requested: Arc<Mutex<Vec<String>>>) -> String {
+        spawn_mock_rpc(move |method, params: &Value| match method {
+            "eth_blockNumber" => json!(format!("{:#x}", head)),
+            "eth_getBalance" => {
+                let tag = params[1].as_str().unwrap_or("latest").to_string();
+                let block = match tag.as_str() {
+                    "latest" => head,
+                    number => u64::from_str_radix(number.trim_start_matches("0x"), 16).unwrap(),
+                };
+                requested.lock().unwrap().push(tag);
+                json!(format!("{:#x}", block * per_block))
+            },
+            _ => Value::Null,
+        }).await
+    }
+
+    fn endpoint(url: &str, priority: u32, ewma_latency: f64) -> EndpointInfo {
+        let mut info = EndpointInfo::new(url, priority, 10);
+        info.ewma_latency = ewma_latency;
+        info
+    }
+
+    #[test]
+    fn test_order_candidates_strategies() {
+        let a = endpoint("a", 0, 120.0);
+        let b = endpoint("b", 1, 40.0);
+        let mut c = endpoint("c", 2, 80.0);
+        c.status = EndpointStatus::Down;
+        let infos = vec![&a, &b, &c];
+
+        // Round-robin bắt đầu từ index 1, bỏ qua endpoint Down
+        assert_eq!(order_candidates(RoutingStrategy::RoundRobin, &infos, 1, 0.0), vec![1, 0]);
+        // Độ trễ EWMA thấp nhất trước
+        assert_eq!(order_candidates(RoutingStrategy::LowestLatency, &infos, 0, 0.0), vec![1, 0]);
+        assert_eq!(order_candidates(RoutingStrategy::Hedged, &infos, 0, 0.0), vec![1, 0]);
+
+        // Weighted random: random = 0 chọn endpoint điểm cao nhất, random ~1 chọn endpoint điểm thấp nhất
+        assert_eq!(order_candidates(RoutingStrategy::WeightedRandom, &infos, 0, 0.0)[0], 1);
+        assert_eq!(order_candidates(RoutingStrategy::WeightedRandom, &infos, 0, 0.999)[0], 0);
+    }
+
+    #[test]
+    fn test_tally_quorum() {
+        let responses = vec![
+            ("a".to_string(), Some(100u64)),
+            ("b".to_string(), Some(100u64)),
+            ("c".to_string(), Some(99u64)),
+            ("d".to_string(), None),
+        ];
+        let outcome = tally_quorum(responses, 2);
+        assert_eq!(outcome.value, Some(100));
+        assert_eq!(outcome.agreeing, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(outcome.disagreeing, vec!["c".to_string()]);
+        assert_eq!(outcome.failed, vec!["d".to_string()]);
+
+        // Hòa giữa hai nhóm: không đạt quorum, không hạ cấp ai
+        let tied = tally_quorum(vec![
+            ("a".to_string(), Some(1u64)),
+            ("b".to_string(), Some(2u64)),
+        ], 1);
+        assert!(tied.value.is_none());
+        assert!(tied.disagreeing.is_empty());
+
+        // Không đủ k kết quả giống nhau
+        let short = tally_quorum(vec![("a".to_string(), Some(1u64)), ("b".to_string(), None)], 2);
+        assert!(short.value.is_none());
+    }
+
+    #[test]
+    fn test_demote_and_recover() {
+        let mut info = endpoint("a", 0, 50.0);
+        let healthy_score = info.health_score();
+
+        info.demote("disagreed with quorum");
+        assert_eq!(info.status, EndpointStatus::Degraded);
+        assert!(info.health_score() < healthy_score);
+
+        // Thành công không khôi phục khi còn bất đồng
+        info.record_success(50.0);
+        assert_eq!(info.status, EndpointStatus::Degraded);
+
+        info.record_agreement();
+        info.record_success(50.0);
+        assert_eq!(info.status, EndpointStatus::Healthy);
+
+        for _ in 0..MAX_DISAGREEMENTS {
+            info.demote("disagreed with quorum");
+        }
+        assert_eq!(info.status, EndpointStatus::Down);
+        assert!(!info.is_available());
+
+        // Endpoint Down được thử lại sau thời gian cách ly
+        let down_since = info.down_since;
+        assert!(!info.recover_if_due(down_since + DOWN_RECOVERY_SECONDS - 1));
+        assert!(info.recover_if_due(down_since + DOWN_RECOVERY_SECONDS));
+        assert_eq!(info.status, EndpointStatus::Degraded);
+        assert_eq!(info.disagreement_count, 0);
+        info.record_success(50.0);
+        assert_eq!(info.status, EndpointStatus::Healthy);
+    }
+
+    #[tokio::test]
+    async fn test_hedged_read_uses_second_endpoint_when_first_is_slow() {
+        let candidate = |url: &str, p90: Option<f64>| {
+            (url.to_string(), Provider::<Http>::try_from(url).unwrap(), p90)
+        };
+        let plan = FreshPlan {
+            candidates: vec![candidate("http://slow.invalid", Some(10.0)), candidate("http://fast.invalid", None)],
+            timeout: Duration::from_secs(5),
+            hedge_delay: Some(Duration::from_millis(50)),
+        };
+        let operation = |provider: Provider<Http>| async move {
+            let url = provider.as_ref().url().to_string();
+            if url.contains("slow") {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+            }
+            Ok(url)
+        };
+
+        let (value, outcomes) = hedged_attempts(plan, Duration::from_millis(50), &operation).await;
+        assert!(value.unwrap().contains("fast"));
+        // Request chậm bị bỏ dở không được ghi nhận
+        assert_eq!(outcomes.len(), 1);
+        assert!(outcomes[0].0.contains("fast"));
+    }
+
+    #[test]
+    fn test_ewma_latency_and_percentile() {
+        let mut info = endpoint("a", 0, 0.0);
+        info.record_success(100.0);
+        assert_eq!(info.ewma_latency, 100.0);
+        info.record_success(200.0);
+        assert!((info.ewma_latency - 120.0).abs() < 1e-9);
+
+        let window: VecDeque<f64> = (1..=10).map(|v| v as f64 * 10.0).collect();
+        assert_eq!(percentile(&window, 90.0), Some(90.0));
+        assert_eq!(percentile(&VecDeque::new(), 90.0), None);
+    }
+
+    #[test]
+    fn test_amounts_out_codec() {
+        let path = vec![Address::repeat_byte(1), Address::repeat_byte(2)];
+        let data = encode_get_amounts_out(U256::from(1000u64), &path);
+        assert_eq!(&data[..4], &ethers::utils::id("getAmountsOut(uint256,address[])")[..]);
+
+        let output = abi::encode(&[Token::Array(vec![
+            Token::Uint(U256::from(1000u64)),
+            Token::Uint(U256::from(997u64)),
+        ])]);
+        assert_eq!(decode_amounts_out(&output).unwrap(), vec![U256::from(1000u64), U256::from(997u64)]);
+    }
+
+    #[test]
+    fn test_block_lag_thresholds() {
+        // Ethereum ~12s, BSC ~3s, Arbitrum ~0.25s
+        assert_eq!(block_lag_thresholds(12.0), (3, 1));
+        assert_eq!(block_lag_thresholds(3.0), (10, 1));
+        assert_eq!(block_lag_thresholds(0.25), (120, 12));
+        // Block time không hợp lệ dùng mặc định
+        assert_eq!(block_lag_thresholds(0.0), block_lag_thresholds(DEFAULT_BLOCK_TIME_SECONDS));
+        assert_eq!(block_lag_thresholds(60.0).0, MIN_STALE_BLOCKS);
+    }
+
+    #[test]
+    fn test_stale_endpoint_degraded_and_recovers() {
+        let mut tip = endpoint("http://tip", 0, 50.0);
+        let mut near = endpoint("http://near", 1, 50.0);
+        let mut stale = endpoint("http://stale", 2, 10.0);
+        tip.head_block = 1_000;
+        near.head_block = 999;
+        stale.head_block = 990;
+
+        let best = apply_block_lag(&mut [&mut tip, &mut near, &mut stale], 3);
+        assert_eq!(best, 1_000);
+        assert_eq!(stale.block_lag, 10);
+        assert!(stale.lagging);
+        assert_eq!(stale.status, EndpointStatus::Degraded);
+        assert_eq!(near.status, EndpointStatus::Healthy);
+
+        // Chỉ endpoint ở gần tip được dùng cho read cần state mới
+        assert!(tip.is_near_tip(1));
+        assert!(near.is_near_tip(1));
+        assert!(!stale.is_near_tip(1));
+
+        // Request thành công không khôi phục endpoint còn chậm block
+        stale.record_success(10.0);
+        assert_eq!(stale.status, EndpointStatus::Degraded);
+
+        stale.head_block = 1_000;
+        apply_block_lag(&mut [&mut tip, &mut near, &mut stale], 3);
+        assert!(!stale.lagging);
+        assert_eq!(stale.status, EndpointStatus::Healthy);
+    }
+
+    /// Hai endpoint lệch head một block vẫn đồng thuận vì quorum read chốt cùng một block
+    #[tokio::test]
+    async fn test_quorum_read_pins_one_block_across_endpoints() {
+        let chain_id = 31_001;
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let urls = vec![
+            spawn_head_node(0x100, 1_000, requested.clone()).await,
+            spawn_head_node(0x101, 1_000, requested.clone()).await,
+        ];
+        get_or_create_pool(chain_id, urls, vec![], None).await.unwrap();
+
+        let balance = quorum_get_balance(chain_id, Address::repeat_byte(1)).await.unwrap();
+        assert_eq!(balance, U256::from(0x100 * 1_000));
+        assert_eq!(*requested.lock().unwrap(), vec!["0x100".to_string(); 2]);
+        remove_pool(chain_id);
+    }
+
+    /// Chỉ pool thiếu endpoint mới báo QuorumUnavailable; endpoint bất đồng là lỗi thường
+    #[tokio::test]
+    async fn test_quorum_unavailable_only_when_pool_is_too_small() {
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let holder = Address::repeat_byte(1);
+
+        let error = quorum_get_balance(31_002, holder).await.unwrap_err();
+        assert!(error.is::<QuorumUnavailable>());
+
+        let single = spawn_head_node(0x100, 1_000, requested.clone()).await;
+        get_or_create_pool(31_002, vec![single], vec![], None).await.unwrap();
+        let error = quorum_get_balance(31_002, holder).await.unwrap_err();
+        assert!(error.is::<QuorumUnavailable>());
+        remove_pool(31_002);
+
+        let urls = vec![
+            spawn_head_node(0x100, 1_000, requested.clone()).await,
+            spawn_head_node(0x100, 2_000, requested.clone()).await,
+        ];
+        get_or_create_pool(31_003, urls, vec![], None).await.unwrap();
+        let error = quorum_get_balance(31_003, holder).await.unwrap_err();
+        assert!(!error.is::<QuorumUnavailable>());
+        assert!(error.to_string().contains("Quorum not reached"));
+        remove_pool(31_003);
+    }
+}
@@ -0,0 +1,645 @@
+// External imports
+use async_trait::async_trait;
+use ethers::{
+    abi::Token,
+    providers::{Http, Middleware, Provider},
+    signers::Signer,
+    types::{Address, TransactionReceipt, TransactionRequest, U256},
+};
+use serde::{Serialize, Deserialize};
+
+// Standard library imports
+use std::{
+    str::FromStr,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+// Third party imports
+use anyhow::{anyhow, Context, Result};
+use tracing::{debug, info};
+use diamond_wallet::ManagedSigner;
+
+// Internal imports
+use crate::chain_adapters::{
+    base::EVMAdapter,
+    chain_config_loader,
+    chain_registry::{self, ChainConfig},
+    chain_traits::{
+        ArbitrumAdapter, AvalancheAdapter, BSCAdapter, BaseAdapter, CustomChainAdapter,
+        EthereumAdapter, MonadAdapter, OptimismAdapter, PolygonAdapter,
+    },
+    l2_fee::{self, L1FeeModel},
+    trading_adapter::{self, ChainFamily, SwapOutcome, SwapRequest, TradeQuote, TradingAdapter, NATIVE_TOKEN},
+    trait_adapter,
+};
+
+/// Tên hàm swap native -> token trên các router fork TraderJoe (Avalanche)
+pub const AVAX_TO_TOKEN_SWAP_FN: &str = "swapExactAVAXForTokens";
+
+/// Tên hàm swap token -> native trên các router fork TraderJoe (Avalanche)
+pub const TOKEN_TO_AVAX_SWAP_FN: &str = "swapExactTokensForAVAX";
+
+/// Key router PancakeSwap trong `router_contracts`
+pub const PANCAKESWAP_DEX: &str = "pancakeswap";
+
+/// Deadline mặc định (giây) cho các swap không truyền deadline
+const DEFAULT_SWAP_DEADLINE_SECS: u64 = 300;
+
+/// Tập capability của một chain EVM, suy ra từ file cấu hình chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvmCapabilities {
+    /// Hỗ trợ EIP-1559 (fee market)
+    pub eip1559: bool,
+    /// Mô hình phí dữ liệu L1 (rollup)
+    pub l1_fee_model: L1FeeModel,
+    /// Tên hàm swap native -> token của router
+    pub eth_to_token_swap_fn: String,
+    /// Tên hàm swap token -> native của router
+    pub token_to_eth_swap_fn: String,
+    /// Các DEX có router trong cấu hình (key viết thường, đã sắp xếp)
+    pub dexes: Vec<String>,
+    /// URL gas station riêng của chain (nếu có)
+    pub gas_station_url: Option<String>,
+    /// Chain có relay Flashbots
+    pub flashbots: bool,
+}
+
+impl EvmCapabilities {
+    /// Suy ra capability từ cấu hình chain; giá trị khai báo trong `[capabilities]`
+    /// được ưu tiên, còn lại dựa trên chain ID
+    pub fn from_config(config: &ChainConfig) -> Self {
+        let adapter_config = config.to_adapter_config();
+        let mut dexes: Vec<String> = config.router_contracts.keys()
+            .map(|name| name.to_lowercase())
+            .collect();
+        dexes.sort();
+
+        Self {
+            eip1559: config.gas_config.supports_eip1559,
+            l1_fee_model: config.capabilities.l1_fee_model
+                .unwrap_or_else(|| L1FeeModel::for_chain(config.chain_id)),
+            eth_to_token_swap_fn: adapter_config.eth_to_token_swap_fn,
+            token_to_eth_swap_fn: adapter_config.token_to_eth_swap_fn,
+            dexes,
+            gas_station_url: config.capabilities.gas_station_url.clone(),
+            flashbots: config.capabilities.flashbots,
+        }
+    }
+
+    /// Router của chain dùng tên hàm swap kiểu Avalanche (AVAX thay cho ETH)
+    pub fn uses_avax_swap_fns(&self) -> bool {
+        self.eth_to_token_swap_fn == AVAX_TO_TOKEN_SWAP_FN
+            && self.token_to_eth_swap_fn == TOKEN_TO_AVAX_SWAP_FN
+    }
+
+    /// Chain có router của DEX `name` (khớp cả key có hậu tố phiên bản, VD: "pancakeswap_v2")
+    pub fn has_dex(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        let versioned = format!("{}_", name);
+        self.dexes.iter().any(|dex| *dex == name || dex.starts_with(&versioned))
+    }
+}
+
+/// Adapter EVM dùng chung cho mọi chain EVM. Thêm chain mới chỉ cần thêm file
+/// cấu hình; các trait đặc thù chain (AvalancheAdapter, BSCAdapter...) được phục vụ
+/// qua capability hook và trả lỗi nếu chain không có capability tương ứng
+#[derive(Debug)]
+pub struct GenericEvmAdapter {
+    /// Adapter EVM phía dưới (provider, ví, ABI, nonce manager)
+    inner: EVMAdapter,
+    /// Cấu hình đầy đủ của chain
+    chain_config: ChainConfig,
+    /// Capability của chain
+    capabilities: EvmCapabilities,
+}
+
+impl GenericEvmAdapter {
+    /// Tạo adapter từ cấu hình chain (đã được kiểm tra theo schema)
+    pub async fn from_config(chain_config: ChainConfig) -> Result<Self> {
+        chain_config.validate()?;
+        if !chain_config.chain_type.eq_ignore_ascii_case("evm") {
+            return Err(anyhow!(
+                "Chain {} không phải EVM (chain_type = {})",
+                chain_config.name, chain_config.chain_type
+            ));
+        }
+
+        let capabilities = EvmCapabilities::from_config(&chain_config);
+        let inner = EVMAdapter::new(chain_config.to_adapter_config()).await?;
+        debug!("Tạo GenericEvmAdapter cho {} với capability {:?}", chain_config.name, capabilities);
+
+        Ok(Self {
+            inner,
+            chain_config,
+            capabilities,
+        })
+    }
+
+    /// Tạo adapter cho chain đã nạp theo tên file cấu hình hoặc tên chain (VD: "bsc")
+    pub async fn for_chain(chain: &str) -> Result<Self> {
+        let chain_id = chain_config_loader::find_chain_id(chain)
+            .ok_or_else(|| anyhow!("Không tìm thấy cấu hình cho chain: {}", chain))?;
+        let chain_config = chain_registry::get_chain_config(chain_id)?;
+        Self::from_config(chain_config).await
+    }
+
+    /// Adapter EVM phía dưới
+    pub fn inner(&self) -> &EVMAdapter {
+        &self.inner
+    }
+
+    /// Cấu hình đầy đủ của chain
+    pub fn chain_config(&self) -> &ChainConfig {
+        &self.chain_config
+    }
+
+    /// Capability của chain
+    pub fn capabilities(&self) -> &EvmCapabilities {
+        &self.capabilities
+    }
+
+    /// Kiểm tra RPC đang trỏ đúng chain trong cấu hình
+    pub async fn verify_chain_id(&self) -> Result<()> {
+        let remote = self.inner.get_provider().get_chainid().await
+            .map_err(|e| anyhow!("Không thể lấy chain ID từ RPC của {}: {}", self.chain_config.name, e))?;
+        if remote != U256::from(self.chain_config.chain_id) {
+            return Err(anyhow!(
+                "RPC của {} trả về chain ID {}, cấu hình là {}",
+                self.chain_config.name, remote, self.chain_config.chain_id
+            ));
+        }
+        Ok(())
+    }
+
+    /// Trả lỗi nếu chain không có capability được yêu cầu
+    fn require(&self, supported: bool, capability: &str) -> Result<()> {
+        if supported {
+            Ok(())
+        } else {
+            Err(anyhow!("Chain {} không hỗ trợ {}", self.chain_config.name, capability))
+        }
+    }
+
+    /// Địa chỉ ví đang gắn với adapter
+    fn wallet_address(&self) -> Result<Address> {
+        self.inner.get_wallet()
+            .map(|wallet| wallet.address())
+            .ok_or_else(|| anyhow!("Không có ví để thực hiện giao dịch trên {}", self.chain_config.name))
+    }
+
+    /// Giao dịch mẫu có `data_length` byte calldata khác 0 (trường hợp xấu nhất khi tính phí L1)
+    fn sample_l1_tx(&self, data_length: usize) -> TransactionRequest {
+        l2_fee::sample_tx(self.chain_config.wrapped_native_token.unwrap_or_else(Address::zero), data_length)
+    }
+
+    /// Phí dữ liệu L1 cho giao dịch, theo mô hình phí L1 của chain
+    pub async fn get_l1_data_fee(&self, tx: &TransactionRequest) -> Result<U256> {
+        let provider = self.inner.get_provider();
+        match self.capabilities.l1_fee_model {
+            L1FeeModel::OpStack => l2_fee::get_op_stack_l1_fee(provider, self.chain_config.chain_id, tx).await,
+            L1FeeModel::Arbitrum => Ok(l2_fee::get_arbitrum_gas_components(provider, tx).await?.l1_fee()),
+            L1FeeModel::None => Err(anyhow!("Chain {} không có phí dữ liệu L1", self.chain_config.name)),
+        }
+    }
+
+    /// L1 base fee mà rollup đang dùng để tính phí dữ liệu
+    pub async fn get_l1_base_fee(&self) -> Result<U256> {
+        let provider = self.inner.get_provider();
+        match self.capabilities.l1_fee_model {
+            L1FeeModel::OpStack => l2_fee::get_op_stack_l1_base_fee(provider).await,
+            L1FeeModel::Arbitrum => {
+                let components = l2_fee::get_arbitrum_gas_components(provider, &self.sample_l1_tx(0)).await?;
+                Ok(components.l1_base_fee_estimate)
+            },
+            L1FeeModel::None => Err(anyhow!("Chain {} không có phí dữ liệu L1", self.chain_config.name)),
+        }
+    }
+
+    /// Gửi bundle qua Flashbots (chỉ chain có capability `flashbots`)
+    pub async fn create_flashbots_bundle(&self, txs: Vec<TransactionRequest>) -> Result<()> {
+        self.require(self.capabilities.flashbots, "Flashbots bundle")?;
+        self.inner.create_flashbots_bundle(txs).await
+    }
+}
+
+impl trait_adapter::ChainAdapter for GenericEvmAdapter {
+    fn get_config(&self) -> &crate::chain_adapters::base::ChainConfig {
+        self.inner.get_config()
+    }
+
+    fn get_provider(&self) -> &Provider<Http> {
+        self.inner.get_provider()
+    }
+
+    fn get_wallet(&self) -> Option<&ManagedSigner> {
+        self.inner.get_wallet()
+    }
+
+    fn set_wallet(&mut self, wallet: ManagedSigner) {
+        self.inner.set_wallet(wallet);
+    }
+
+    fn get_gas_optimizer(&self) -> Option<&crate::gas_optimizer::GasOptimizer> {
+        None
+    }
+
+    fn decode_router_input(&self, input: &[u8]) -> Result<Vec<Token>> {
+        self.inner.decode_router_input(input)
+    }
+
+    fn get_native_to_token_path(&self, token_address: &str) -> Result<Vec<Address>> {
+        self.inner.get_native_to_token_path(token_address)
+    }
+
+    fn get_token_to_native_path(&self, token_address: &str) -> Result<Vec<Address>> {
+        self.inner.get_token_to_native_path(token_address)
+    }
+}
+
+#[async_trait]
+impl EthereumAdapter for GenericEvmAdapter {
+    async fn send_to_ethereum(&self, to: &str, value: U256) -> Result<Option<TransactionReceipt>> {
+        let wallet = self.inner.get_wallet()
+            .ok_or_else(|| anyhow!("Không có ví để thực hiện giao dịch trên {}", self.chain_config.name))?
+            .clone()
+            .with_chain_id(self.chain_config.chain_id);
+        let to = Address::from_str(to)
+            .context(format!("Địa chỉ người nhận không hợp lệ: {}", to))?;
+
+        let client = ethers::middleware::SignerMiddleware::new(self.inner.get_provider().clone(), wallet);
+        let pending = client.send_transaction(TransactionRequest::new().to(to).value(value), None).await
+            .context("Lỗi khi gửi native token")?;
+        let receipt = pending.await
+            .context("Lỗi khi lấy biên lai giao dịch")?;
+        Ok(receipt)
+    }
+
+    async fn get_eth2_staking_balance(&self, _address: &str) -> Result<U256> {
+        // Cần beacon API, chưa có capability tương ứng trong cấu hình chain
+        self.require(false, "truy vấn số dư staking của beacon chain")?;
+        Ok(U256::zero())
+    }
+}
+
+#[async_trait]
+impl BSCAdapter for GenericEvmAdapter {
+    async fn swap_via_pancakeswap(
+        &self,
+        token_address: &str,
+        amount_in: U256,
+        min_amount_out: U256,
+    ) -> Result<Option<TransactionReceipt>> {
+        self.require(self.capabilities.has_dex(PANCAKESWAP_DEX), "router PancakeSwap")?;
+        let recipient = format!("{:?}", self.wallet_address()?);
+        let deadline = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() + DEFAULT_SWAP_DEADLINE_SECS;
+
+        self.inner.swap_exact_eth_for_tokens(token_address, amount_in, min_amount_out, &recipient, deadline, None, None).await
+    }
+}
+
+#[async_trait]
+impl AvalancheAdapter for GenericEvmAdapter {
+    async fn swap_exact_avax_for_tokens(
+        &self,
+        token_address: &str,
+        amount_in: U256,
+        min_amount_out: U256,
+        recipient: &str,
+        deadline: u64,
+    ) -> Result<Option<TransactionReceipt>> {
+        self.require(self.capabilities.uses_avax_swap_fns(), AVAX_TO_TOKEN_SWAP_FN)?;
+        self.inner.swap_exact_eth_for_tokens(token_address, amount_in, min_amount_out, recipient, deadline, None, None).await
+    }
+
+    async fn swap_exact_tokens_for_avax(
+        &self,
+        token_address: &str,
+        amount_in: U256,
+        min_amount_out: U256,
+        recipient: &str,
+        deadline: u64,
+    ) -> Result<Option<TransactionReceipt>> {
+        self.require(self.capabilities.uses_avax_swap_fns(), TOKEN_TO_AVAX_SWAP_FN)?;
+        self.inner.swap_exact_tokens_for_eth(token_address, amount_in, min_amount_out, recipient, deadline, None, None).await
+    }
+}
+
+#[async_trait]
+impl OptimismAdapter for GenericEvmAdapter {
+    async fn get_l1_gas_price(&self) -> Result<U256> {
+        self.require(self.capabilities.l1_fee_model == L1FeeModel::OpStack, "phí L1 OP-stack")?;
+        self.get_l1_base_fee().await
+    }
+
+    async fn estimate_l1_fee(&self, data_length: usize) -> Result<U256> {
+        self.require(self.capabilities.l1_fee_model == L1FeeModel::OpStack, "phí L1 OP-stack")?;
+        self.get_l1_data_fee(&self.sample_l1_tx(data_length)).await
+    }
+}
+
+#[async_trait]
+impl ArbitrumAdapter for GenericEvmAdapter {
+    async fn get_l1_gas_price(&self) -> Result<U256> {
+        self.require(self.capabilities.l1_fee_model == L1FeeModel::Arbitrum, "phí L1 Arbitrum")?;
+        self.get_l1_base_fee().await
+    }
+
+    async fn estimate_l1_fee(&self, data_length: usize) -> Result<U256> {
+        self.require(self.capabilities.l1_fee_model == L1FeeModel::Arbitrum, "phí L1 Arbitrum")?;
+        self.get_l1_data_fee(&self.sample_l1_tx(data_length)).await
+    }
+}
+
+#[async_trait]
+impl PolygonAdapter for GenericEvmAdapter {
+    async fn get_polygon_gas_station(&self) -> Result<serde_json::Value> {
+        let url = self.capabilities.gas_station_url.as_deref()
+            .ok_or_else(|| anyhow!("Chain {} không hỗ trợ gas station", self.chain_config.name))?;
+
+        let response = reqwest::get(url).await
+            .context(format!("Không thể gọi gas station {}", url))?;
+        let value = response.error_for_status()?
+            .json::<serde_json::Value>().await
+            .context("Gas station trả về dữ liệu không hợp lệ")?;
+        Ok(value)
+    }
+}
+
+#[async_trait]
+impl BaseAdapter for GenericEvmAdapter {
+    async fn get_l1_fee_scale(&self) -> Result<f64> {
+        self.require(self.capabilities.l1_fee_model == L1FeeModel::OpStack, "phí L1 OP-stack")?;
+        let scalar = l2_fee::get_op_stack_base_fee_scalar(self.inner.get_provider()).await?;
+        // baseFeeScalar có đơn vị 1e-6
+        Ok(scalar.as_u128() as f64 / 1_000_000.0)
+    }
+}
+
+#[async_trait]
+impl MonadAdapter for GenericEvmAdapter {
+    async fn get_monad_performance(&self) -> Result<serde_json::Value> {
+        let provider = self.inner.get_provider();
+        let started = Instant::now();
+        let block_number = provider.get_block_number().await
+            .map_err(|e| anyhow!("Không thể lấy block number: {}", e))?;
+        let rpc_latency_ms = started.elapsed().as_millis() as u64;
+        let gas_price = provider.get_gas_price().await
+            .map_err(|e| anyhow!("Không thể lấy gas price: {}", e))?;
+
+        info!("Hiệu suất {}: block {}, RPC latency {}ms", self.chain_config.name, block_number, rpc_latency_ms);
+        Ok(serde_json::json!({
+            "chain_id": self.chain_config.chain_id,
+            "block_number": block_number.as_u64(),
+            "gas_price_gwei": gas_price.as_u128() as f64 / 1e9,
+            "expected_block_time_ms": (self.chain_config.avg_block_time * 1000.0) as u64,
+            "rpc_latency_ms": rpc_latency_ms,
+        }))
+    }
+}
+
+impl CustomChainAdapter for GenericEvmAdapter {
+    fn get_custom_chain_name(&self) -> &str {
+        &self.chain_config.name
+    }
+
+    fn get_native_token_name(&self) -> &str {
+        &self.chain_config.native_token_name
+    }
+
+    fn get_wrapped_native_token(&self) -> &str {
+        &self.inner.get_config().wrapped_native_token
+    }
+}
+
+/// Chuyển số lượng U256 sang u128 của `TradingAdapter`
+fn u256_to_u128(value: U256) -> Result<u128> {
+    if value > U256::from(u128::MAX) {
+        return Err(anyhow!("Số lượng {} vượt quá giới hạn u128", value));
+    }
+    Ok(value.as_u128())
+}
+
+#[async_trait]
+impl TradingAdapter for GenericEvmAdapter {
+    fn chain_family(&self) -> ChainFamily {
+        ChainFamily::Evm
+    }
+
+    fn chain_name(&self) -> &str {
+        &self.chain_config.name
+    }
+
+    fn native_symbol(&self) -> &str {
+        &self.chain_config.native_token_symbol
+    }
+
+    fn is_valid_address(&self, address: &str) -> bool {
+        Address::from_str(address).is_ok()
+    }
+
+    fn trader_address(&self) -> Option<String> {
+        self.wallet_address().ok().map(|address| format!("{:?}", address))
+    }
+
+    async fn native_balance(&self, owner: &str) -> Result<u128> {
+        u256_to_u128(self.inner.get_native_balance(owner).await?)
+    }
+
+    async fn token_balance(&self, token: &str, owner: &str) -> Result<u128> {
+        if token == NATIVE_TOKEN {
+            return self.native_balance(owner).await;
+        }
+        u256_to_u128(self.inner.get_token_balance(token, owner).await?)
+    }
+
+    async fn quote(&self, request: &SwapRequest) -> Result<TradeQuote> {
+        let path = match (request.input_token.as_str(), request.output_token.as_str()) {
+            (NATIVE_TOKEN, NATIVE_TOKEN) => return Err(anyhow!("Swap cần ít nhất một token khác token gốc")),
+            (NATIVE_TOKEN, token) => self.inner.get_native_to_token_path(token)?,
+            (token, NATIVE_TOKEN) => self.inner.get_token_to_native_path(token)?,
+            (input, output) => vec![
+                Address::from_str(input).context(format!("Địa chỉ token không hợp lệ: {}", input))?,
+                Address::from_str(output).context(format!("Địa chỉ token không hợp lệ: {}", output))?,
+            ],
+        };
+        let amounts = self.inner.get_amounts_out(U256::from(request.amount_in), path).await?;
+        let amount_out = u256_to_u128(*amounts.last().ok_or_else(|| anyhow!("Router không trả về số lượng"))?)?;
+
+        Ok(TradeQuote {
+            amount_in: request.amount_in,
+            amount_out,
+            min_amount_out: trading_adapter::min_amount_out(amount_out, request.slippage_bps),
+            venue: request.venue.clone().unwrap_or_else(|| self.inner.get_config().router_address.clone()),
+        })
+    }
+
+    async fn swap(&self, request: &SwapRequest) -> Result<SwapOutcome> {
+        let quote = self.quote(request).await?;
+        let recipient = format!("{:?}", self.wallet_address()?);
+        let deadline = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() + DEFAULT_SWAP_DEADLINE_SECS;
+        let amount_in = U256::from(request.amount_in);
+        let min_amount_out = U256::from(quote.min_amount_out);
+
+        let receipt = if request.is_buy() {
+            self.inner.swap_exact_eth_for_tokens(
+                &request.output_token, amount_in, min_amount_out, &recipient, deadline, None, None,
+            ).await?
+        } else if request.output_token == NATIVE_TOKEN {
+            let router = self.inner.get_config().router_address.clone();
+            self.inner.approve_token(&request.input_token, &router, amount_in).await?;
+            self.inner.swap_exact_tokens_for_eth(
+                &request.input_token, amount_in, min_amount_out, &recipient, deadline, None, None,
+            ).await?
+        } else {
+            return Err(anyhow!("Swap token -> token chưa được hỗ trợ trên {}", self.chain_config.name));
+        };
+
+        let receipt = receipt.ok_or_else(|| anyhow!("Không nhận được biên lai giao dịch swap"))?;
+        Ok(SwapOutcome {
+            tx_id: format!("{:?}", receipt.transaction_hash),
+            confirmed: receipt.status.map(|status| status.as_u64() == 1).unwrap_or(false),
+            quote,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_adapters::chain_config_loader::bundled_chain_configs;
+    use crate::chain_adapters::tests::{
+        mock_chain::{uniswap_v2_amount_out, MockChain},
+        mock_contracts::{self, TokenSpec},
+    };
+
+    const MOCK_L1_FEE: u64 = 42_000;
+    const MOCK_L1_BASE_FEE: u64 = 7_000_000_000;
+
+    #[test]
+    fn test_capabilities_from_bundled_configs() {
+        let set = bundled_chain_configs();
+        let capabilities = |key: &str| {
+            let source = set.chains.iter().find(|source| source.key == key).unwrap();
+            EvmCapabilities::from_config(&source.config)
+        };
+
+        assert!(capabilities("avalanche").uses_avax_swap_fns());
+        assert!(!capabilities("ethereum").uses_avax_swap_fns());
+        assert!(capabilities("ethereum").flashbots);
+        assert!(capabilities("bsc").has_dex(PANCAKESWAP_DEX));
+        assert_eq!(capabilities("optimism").l1_fee_model, L1FeeModel::OpStack);
+        assert_eq!(capabilities("base").l1_fee_model, L1FeeModel::OpStack);
+        assert_eq!(capabilities("arbitrum").l1_fee_model, L1FeeModel::Arbitrum);
+        assert_eq!(capabilities("polygon").l1_fee_model, L1FeeModel::None);
+        assert!(capabilities("polygon").gas_station_url.is_some());
+
+        // Mô hình phí L1 khai báo trong file phải khớp với chain ID đã biết
+        for source in &set.chains {
+            let declared = EvmCapabilities::from_config(&source.config).l1_fee_model;
+            assert_eq!(declared, L1FeeModel::for_chain(source.config.chain_id), "{}", source.key);
+        }
+    }
+
+    #[test]
+    fn test_capability_overrides_from_config() {
+        let set = bundled_chain_configs();
+        let mut config = set.chains.iter().find(|source| source.key == "ethereum").unwrap().config.clone();
+        config.chain_id = 999_999;
+        config.capabilities.l1_fee_model = Some(L1FeeModel::OpStack);
+
+        let capabilities = EvmCapabilities::from_config(&config);
+        assert_eq!(capabilities.l1_fee_model, L1FeeModel::OpStack);
+
+        config.capabilities.l1_fee_model = None;
+        assert_eq!(EvmCapabilities::from_config(&config).l1_fee_model, L1FeeModel::None);
+    }
+
+    /// Bộ kiểm thử chung: mọi file cấu hình chain đóng gói phải chạy được trên mock chain (revm)
+    /// có Uniswap V2 và oracle phí L1 tại địa chỉ predeploy của rollup
+    #[tokio::test]
+    async fn test_conformance_suite_for_bundled_chains() {
+        let ether = U256::exp10(18);
+
+        for source in bundled_chain_configs().chains.into_iter().filter(|source| source.config.is_evm()) {
+            let key = source.key.clone();
+            let mut config = source.config;
+
+            let chain = MockChain::spawn(config.chain_id).await.unwrap();
+            let dex = chain.deploy_uniswap_v2().unwrap();
+            let token = chain.deploy_erc20(&TokenSpec::new("Mock Token", "MOCK", 18)).unwrap();
+            let pair = chain.add_liquidity_eth(&dex, token, ether * 1_000, ether * 10).unwrap();
+            chain.set_code(
+                l2_fee::OP_GAS_PRICE_ORACLE.parse().unwrap(),
+                mock_contracts::op_gas_price_oracle_runtime(U256::from(MOCK_L1_FEE), U256::from(MOCK_L1_BASE_FEE)),
+            );
+            chain.set_code(
+                l2_fee::ARBITRUM_NODE_INTERFACE.parse().unwrap(),
+                mock_contracts::arbitrum_node_interface_runtime(100_000, 6, U256::from(7_000), U256::from(MOCK_L1_BASE_FEE)),
+            );
+
+            config.primary_rpc_urls = vec![chain.http_url().to_string()];
+            config.backup_rpc_urls.clear();
+            config.wrapped_native_token = Some(dex.weth);
+            config.router_contracts.values_mut().for_each(|router| *router = dex.router);
+            config.factory_contracts.values_mut().for_each(|factory| *factory = dex.factory);
+
+            let adapter = GenericEvmAdapter::from_config(config).await
+                .unwrap_or_else(|e| panic!("{}: {}", key, e));
+            let capabilities = adapter.capabilities().clone();
+            let holder = chain.dev_address(1);
+            let (holder_str, token_str) = (format!("{:?}", holder), format!("{:?}", token));
+
+            adapter.verify_chain_id().await.unwrap_or_else(|e| panic!("{}: {}", key, e));
+            assert_eq!(adapter.inner().get_native_balance(&holder_str).await.unwrap(), chain.balance(holder), "{}", key);
+
+            let expected_out = uniswap_v2_amount_out(U256::from(1_000), ether * 10, ether * 1_000);
+            let path = trait_adapter::ChainAdapter::get_native_to_token_path(&adapter, &token_str).unwrap();
+            let amounts = adapter.inner().get_amounts_out(U256::from(1_000), path).await.unwrap();
+            assert_eq!(amounts, vec![U256::from(1_000), expected_out], "{}", key);
+
+            // Giao diện giao dịch chung dùng cùng router
+            let quote = TradingAdapter::quote(&adapter, &SwapRequest::buy(&token_str, 1_000, 100)).await.unwrap();
+            let expected_out = expected_out.as_u128();
+            assert_eq!(
+                (quote.amount_out, quote.min_amount_out),
+                (expected_out, trading_adapter::min_amount_out(expected_out, 100)),
+                "{}", key
+            );
+            assert_eq!(TradingAdapter::native_balance(&adapter, &holder_str).await.unwrap(), chain.balance(holder).as_u128(), "{}", key);
+
+            let found = adapter.inner().get_pair(&token_str, &format!("{:?}", dex.weth)).await.unwrap();
+            assert_eq!(found, Some(format!("{:?}", pair)), "{}", key);
+
+            // Capability hook: chain hỗ trợ thì trả kết quả, không hỗ trợ thì trả lỗi
+            let op_fee = OptimismAdapter::estimate_l1_fee(&adapter, 100).await;
+            let arb_fee = ArbitrumAdapter::estimate_l1_fee(&adapter, 100).await;
+            match capabilities.l1_fee_model {
+                L1FeeModel::OpStack => {
+                    assert_eq!(op_fee.unwrap(), U256::from(MOCK_L1_FEE), "{}", key);
+                    assert_eq!(OptimismAdapter::get_l1_gas_price(&adapter).await.unwrap(), U256::from(MOCK_L1_BASE_FEE));
+                    assert!(arb_fee.is_err(), "{}", key);
+                },
+                L1FeeModel::Arbitrum => {
+                    assert_eq!(arb_fee.unwrap(), U256::from(6 * 7_000), "{}", key);
+                    assert_eq!(ArbitrumAdapter::get_l1_gas_price(&adapter).await.unwrap(), U256::from(MOCK_L1_BASE_FEE));
+                    assert!(op_fee.is_err(), "{}", key);
+                },
+                L1FeeModel::None => {
+                    assert!(op_fee.is_err() && arb_fee.is_err(), "{}", key);
+                },
+            }
+
+            let avax_swap = adapter.swap_exact_avax_for_tokens(&token_str, U256::one(), U256::zero(), &holder_str, 0).await;
+            if !capabilities.uses_avax_swap_fns() {
+                assert!(avax_swap.unwrap_err().to_string().contains("không hỗ trợ"), "{}", key);
+            }
+            if !capabilities.has_dex(PANCAKESWAP_DEX) {
+                assert!(adapter.swap_via_pancakeswap(&token_str, U256::one(), U256::zero()).await.is_err(), "{}", key);
+            }
+
+            let performance = adapter.get_monad_performance().await.unwrap();
+            assert_eq!(performance["block_number"], chain.block_number(), "{}", key);
+            assert_eq!(adapter.get_custom_chain_name(), adapter.chain_config().name);
+        }
+    }
+}
@@ -1,15 +1,15 @@
-use std::sync::Arc;
 use std::fmt::Debug;
 use ethers::types::{
     Address, BlockId, Bytes, Filter, Log, Transaction, TransactionReceipt,
-    TransactionRequest, U256, H256
+    TransactionRequest, U256, H256, transaction::eip2718::TypedTransaction,
 };
 use async_trait::async_trait;
 use anyhow::Result;
-use ethers::providers::PendingTransaction;
 use serde::{Serialize, Deserialize};
 use thiserror::Error;
 
+use crate::chain_adapters::rpc_error;
+
 /// TokenDetails chứa thông tin về một token ERC20
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenDetails {
@@ -148,6 +148,49 @@ pub enum ChainError {
     #[error("Maximum retry attempts reached: {0}")]
     MaxRetryReached(String),
     
+    /// Nonce đã được dùng (node đã có giao dịch với nonce này hoặc lớn hơn)
+    #[error("Nonce too low: {0}")]
+    NonceTooLow(String),
+    
+    /// Nonce vượt quá nonce kế tiếp của tài khoản (có khoảng trống)
+    #[error("Nonce too high: {0}")]
+    NonceTooHigh(String),
+    
+    /// Giao dịch đã có trong mempool của node
+    #[error("Transaction already known: {0}")]
+    AlreadyKnown(String),
+    
+    /// Giao dịch thay thế không tăng phí đủ mức tối thiểu của node
+    #[error("Replacement transaction underpriced: {0}")]
+    ReplacementUnderpriced(String),
+    
+    /// Max fee per gas thấp hơn base fee của block
+    #[error("Fee cap too low: {0}")]
+    FeeCapTooLow(String),
+    
+    /// Không đủ số dư cho gas * giá + value
+    #[error("Insufficient funds: {0}")]
+    InsufficientFunds(String),
+    
+    /// Gas limit thấp hơn intrinsic gas của giao dịch
+    #[error("Intrinsic gas too low: {0}")]
+    IntrinsicGasTooLow(String),
+    
+    /// Giao dịch/lời gọi bị revert, kèm lý do và dữ liệu revert nếu node trả về
+    #[error("Execution reverted: {}", reason.as_deref().unwrap_or("no reason"))]
+    ExecutionReverted {
+        reason: Option<String>,
+        data: Option<Bytes>,
+    },
+    
+    /// Node không có block/state được yêu cầu (chưa đồng bộ hoặc đã prune)
+    #[error("Header not found: {0}")]
+    HeaderNotFound(String),
+    
+    /// Adapter không triển khai thao tác này
+    #[error("Not implemented")]
+    NotImplemented,
+    
     /// Lỗi không xác định
     #[error("Unknown error: {0}")]
     Unknown(String),
@@ -157,26 +200,28 @@ impl ChainError {
     /// Chuyển đổi từ anyhow::Error sang ChainError
     pub fn from_anyhow(err: anyhow::Error) -> Self {
         // Kiểm tra nếu error đã là ChainError
-        if let Ok(chain_err) = err.downcast::<ChainError>() {
+        let err = match err.downcast::<ChainError>() {
+            Ok(chain_err) => return chain_err,
+            Err(err) => err,
+        };
+        
+        // Lỗi JSON-RPC/provider: phân loại theo mã và thông điệp của node
+        if let Some(chain_err) = rpc_error::classify_anyhow(&err) {
             return chain_err;
         }
         
         let err_string = err.to_string();
         
-        // Kiểm tra các loại lỗi phổ biến từ ethers provider
+        // Các lỗi nội bộ còn lại
         if err_string.contains("could not connect to server") || err_string.contains("connection error") {
             return ChainError::ConnectionError(err_string);
-        } else if err_string.contains("rate limit") {
-            return ChainError::RateLimitExceeded(err_string);
-        } else if err_string.contains("nonce too low") || err_string.contains("nonce") {
+        } else if err_string.contains("nonce") {
             return ChainError::NonceError(err_string);
-        } else if err_string.contains("gas required exceeds") || err_string.contains("gas price") {
+        } else if err_string.contains("gas price") {
             return ChainError::GasEstimationError(err_string);
-        } else if err_string.contains("timeout") {
-            return ChainError::TimeoutError(30000); // Giả định timeout sau 30s
         } else if err_string.contains("transaction") {
             return ChainError::TransactionError(err_string);
-        } else if err_string.contains("contract call") || err_string.contains("execution reverted") {
+        } else if err_string.contains("contract call") {
             return ChainError::ContractCallError(err_string);
         } else if err_string.contains("token") {
             return ChainError::TokenError(err_string);
@@ -190,7 +235,7 @@ impl ChainError {
             return ChainError::UnsupportedOperation(err_string);
         } else if err_string.contains("invalid address") {
             return ChainError::InvalidAddress(err_string);
-        } else if err_string.contains("insufficient funds") || err_string.contains("insufficient balance") {
+        } else if err_string.contains("insufficient balance") {
             return ChainError::InsufficientBalance(err_string);
         } else if err_string.contains("approval") {
             return ChainError::ApprovalError(err_string);
@@ -208,12 +253,6 @@ impl ChainError {
             return ChainError::TransactionError(err_string);
         } else if err_string.contains("reverted") {
             return ChainError::Revert(err_string);
-        } else if err_string.contains("underpriced") {
-            return ChainError::Underpriced;
-        } else if err_string.contains("gas required exceeds") {
-            return ChainError::InsufficientGas(err_string);
-        } else if err_string.contains("gas cap") {
-            return ChainError::GasCap;
         } else if err_string.contains("max retry") {
             return ChainError::MaxRetryReached(err_string);
         } else if err_string.contains("rpc") {
@@ -230,7 +269,21 @@ impl ChainError {
             ChainError::GasEstimationError(_) | 
             ChainError::InsufficientGas(_) | 
             ChainError::Underpriced | 
-            ChainError::GasCap
+            ChainError::GasCap |
+            ChainError::ReplacementUnderpriced(_) |
+            ChainError::FeeCapTooLow(_) |
+            ChainError::IntrinsicGasTooLow(_)
+        )
+    }
+    
+    /// Kiểm tra xem lỗi có liên quan đến nonce không
+    pub fn is_nonce_related(&self) -> bool {
+        matches!(
+            self,
+            ChainError::NonceError(_) |
+            ChainError::NonceTooLow(_) |
+            ChainError::NonceTooHigh(_) |
+            ChainError::AlreadyKnown(_)
         )
     }
     
@@ -242,7 +295,11 @@ impl ChainError {
             ChainError::TimeoutError(_) |
             ChainError::RateLimitExceeded(_) |
             ChainError::ProviderNotAvailable |
-            ChainError::Underpriced
+            ChainError::Underpriced |
+            ChainError::ReplacementUnderpriced(_) |
+            ChainError::FeeCapTooLow(_) |
+            ChainError::NonceTooLow(_) |
+            ChainError::HeaderNotFound(_)
         )
     }
     
@@ -279,6 +336,22 @@ impl ChainError {
                 "Tăng số lần retry hoặc kiểm tra lỗi gốc".to_string(),
             Self::TransactionError(_) => 
                 "Kiểm tra lại thông số transaction và định dạng dữ liệu".to_string(),
+            Self::NonceTooLow(_) => 
+                "Lấy lại nonce từ node và gửi với nonce kế tiếp".to_string(),
+            Self::NonceTooHigh(_) => 
+                "Đồng bộ lại nonce, giao dịch trước có thể chưa được gửi".to_string(),
+            Self::AlreadyKnown(_) => 
+                "Giao dịch đã có trong mempool, chờ receipt thay vì gửi lại".to_string(),
+            Self::ReplacementUnderpriced(_) | Self::FeeCapTooLow(_) => 
+                "Tăng max fee và priority fee rồi gửi lại".to_string(),
+            Self::InsufficientFunds(_) => 
+                "Nạp thêm token gốc cho ví hoặc giảm số lượng giao dịch".to_string(),
+            Self::IntrinsicGasTooLow(_) => 
+                "Tăng gas limit lên ít nhất bằng intrinsic gas".to_string(),
+            Self::ExecutionReverted { .. } => 
+                "Kiểm tra lý do revert, tham số gọi và trạng thái contract".to_string(),
+            Self::HeaderNotFound(_) => 
+                "Chờ node đồng bộ hoặc dùng endpoint khác (archive node cho block cũ)".to_string(),
             _ => "Kiểm tra logs chi tiết và liên hệ hỗ trợ kỹ thuật".to_string(),
         }
     }
@@ -293,7 +366,7 @@ fn extract_chain_id(err_string: &str) -> Option<u64> {
         .or_else(|| err_string.split("chain: ").nth(1))
         .or_else(|| err_string.split("chainId: ").nth(1))
     {
-        if let Some(id_end) = id_str.find(|c: char| !c.is_digit(10)) {
+        if let Some(id_end) = id_str.find(|c: char| !c.is_ascii_digit()) {
             if let Ok(id) = id_str[..id_end].parse::<u64>() {
                 return Some(id);
             }
@@ -317,6 +390,9 @@ pub struct GasInfo {
     pub gas_limit: U256,
     /// Có hỗ trợ EIP-1559 không
     pub eip1559_supported: bool,
+    /// Phí dữ liệu L1 (wei) cho rollup như Optimism, Arbitrum, Base
+    #[serde(default)]
+    pub l1_data_fee: Option<U256>,
 }
 
 impl GasInfo {
@@ -328,6 +404,7 @@ impl GasInfo {
             max_priority_fee_per_gas: None,
             gas_limit,
             eip1559_supported: false,
+            l1_data_fee: None,
         }
     }
     
@@ -343,9 +420,29 @@ impl GasInfo {
             max_priority_fee_per_gas: Some(max_priority_fee_per_gas),
             gas_limit,
             eip1559_supported: true,
+            l1_data_fee: None,
         }
     }
     
+    /// Gắn phí dữ liệu L1 (wei) vào thông tin gas
+    pub fn with_l1_data_fee(mut self, l1_data_fee: U256) -> Self {
+        self.l1_data_fee = Some(l1_data_fee);
+        self
+    }
+    
+    /// Tổng chi phí tối đa (wei): gas limit * giá gas + phí dữ liệu L1
+    pub fn total_cost(&self) -> U256 {
+        let price = if self.eip1559_supported {
+            self.max_fee_per_gas.unwrap_or(self.gas_price)
+        } else {
+            self.gas_price
+        };
+        
+        self.gas_limit
+            .saturating_mul(price)
+            .saturating_add(self.l1_data_fee.unwrap_or_default())
+    }
+    
     /// Tăng gas price theo tỷ lệ phần trăm
     pub fn increase_by_percent(&self, percent: f64) -> Self {
         let multiplier = (100.0 + percent) / 100.0;
@@ -365,6 +462,7 @@ impl GasInfo {
                 max_priority_fee_per_gas: new_priority_fee,
                 gas_limit: self.gas_limit,
                 eip1559_supported: true,
+                l1_data_fee: self.l1_data_fee,
             }
         } else {
             let new_gas_price = (self.gas_price.as_u128() as f64 * multiplier) as u128;
@@ -375,24 +473,27 @@ impl GasInfo {
                 max_priority_fee_per_gas: None,
                 gas_limit: self.gas_limit,
                 eip1559_supported: false,
+                l1_data_fee: self.l1_data_fee,
             }
         }
     }
     
-    /// Áp dụng thông tin gas vào TransactionRequest
-    pub fn apply_to_tx(&self, tx: &mut TransactionRequest) {
-        tx.gas(self.gas_limit);
+    /// Áp dụng thông tin gas vào transaction (EIP-1559 nếu chain hỗ trợ, ngược lại legacy)
+    pub fn apply_to_tx(&self, tx: &mut TypedTransaction) {
+        tx.set_gas(self.gas_limit);
         
-        if self.eip1559_supported {
-            if let Some(max_fee) = self.max_fee_per_gas {
-                tx.max_fee_per_gas(max_fee);
-            }
-            
-            if let Some(priority_fee) = self.max_priority_fee_per_gas {
-                tx.max_priority_fee_per_gas(priority_fee);
+        match (self.eip1559_supported, tx) {
+            (true, TypedTransaction::Eip1559(inner)) => {
+                if let Some(max_fee) = self.max_fee_per_gas {
+                    inner.max_fee_per_gas = Some(max_fee);
+                }
+                if let Some(priority_fee) = self.max_priority_fee_per_gas {
+                    inner.max_priority_fee_per_gas = Some(priority_fee);
+                }
+            },
+            (_, tx) => {
+                tx.set_gas_price(self.gas_price);
             }
-        } else {
-            tx.gas_price(self.gas_price);
         }
     }
 }
@@ -471,7 +572,7 @@ pub trait ChainAdapter: Send + Sync + Debug {
     fn get_type(&self) -> String;
     
     /// Lấy thông tin block
-    async fn get_block(&self, block_id: BlockId) -> Result<Option<BlockInfo>, ChainError> {
+    async fn get_block(&self, _block_id: BlockId) -> Result<Option<BlockInfo>, ChainError> {
         Err(ChainError::NotImplemented)
     }
     
@@ -482,71 +583,71 @@ pub trait ChainAdapter: Send + Sync + Debug {
     }
     
     /// Gửi transaction raw
-    async fn send_raw_transaction(&self, tx_bytes: Bytes) -> Result<PendingTransaction<'static, ethers::providers::Provider<ethers::providers::Http>>, ChainError> {
+    async fn send_raw_transaction(&self, _tx_bytes: Bytes) -> Result<ethers::types::H256, ChainError> {
         Err(ChainError::NotImplemented)
     }
     
     /// Gửi transaction
-    async fn send_transaction(&self, tx: &TransactionRequest) -> Result<PendingTransaction<'static, ethers::providers::Provider<ethers::providers::Http>>, ChainError> {
+    async fn send_transaction(&self, _tx: &TransactionRequest) -> Result<ethers::types::H256, ChainError> {
         Err(ChainError::NotImplemented)
     }
     
     /// Lấy transaction receipt
-    async fn get_transaction_receipt(&self, tx_hash: ethers::types::H256) -> Result<Option<TransactionReceipt>, ChainError> {
+    async fn get_transaction_receipt(&self, _tx_hash: ethers::types::H256) -> Result<Option<TransactionReceipt>, ChainError> {
         Err(ChainError::NotImplemented)
     }
     
     /// Lấy transaction
-    async fn get_transaction(&self, tx_hash: ethers::types::H256) -> Result<Option<Transaction>, ChainError> {
+    async fn get_transaction(&self, _tx_hash: ethers::types::H256) -> Result<Option<Transaction>, ChainError> {
         Err(ChainError::NotImplemented)
     }
     
     /// Lấy số dư ETH
-    async fn get_eth_balance(&self, address: Address, block: Option<BlockId>) -> Result<U256, ChainError> {
+    async fn get_eth_balance(&self, _address: Address, _block: Option<BlockId>) -> Result<U256, ChainError> {
         Err(ChainError::NotImplemented)
     }
     
     /// Lấy số dư token
-    async fn get_token_balance(&self, token: Address, address: Address, block: Option<BlockId>) -> Result<U256, ChainError> {
+    async fn get_token_balance(&self, _token: Address, _address: Address, _block: Option<BlockId>) -> Result<U256, ChainError> {
         Err(ChainError::NotImplemented)
     }
     
     /// Lấy thông tin token
-    async fn get_token_details(&self, token: Address) -> Result<TokenDetails, ChainError> {
+    async fn get_token_details(&self, _token: Address) -> Result<TokenDetails, ChainError> {
         Err(ChainError::NotImplemented)
     }
     
     /// Lấy allowance
-    async fn get_token_allowance(&self, token: Address, owner: Address, spender: Address) -> Result<U256, ChainError> {
+    async fn get_token_allowance(&self, _token: Address, _owner: Address, _spender: Address) -> Result<U256, ChainError> {
         Err(ChainError::NotImplemented)
     }
     
     /// Lấy logs
-    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, ChainError> {
+    async fn get_logs(&self, _filter: &Filter) -> Result<Vec<Log>, ChainError> {
         Err(ChainError::NotImplemented)
     }
     
     /// Lấy nonce
-    async fn get_transaction_count(&self, address: Address, block: Option<BlockId>) -> Result<U256, ChainError> {
+    async fn get_transaction_count(&self, _address: Address, _block: Option<BlockId>) -> Result<U256, ChainError> {
         Err(ChainError::NotImplemented)
     }
     
     /// Ước tính gas
-    async fn estimate_gas(&self, tx: &TransactionRequest) -> Result<U256, ChainError> {
+    async fn estimate_gas(&self, _tx: &TransactionRequest) -> Result<U256, ChainError> {
         Err(ChainError::NotImplemented)
     }
     
     /// Call (không thay đổi state)
-    async fn call(&self, tx: &TransactionRequest, block: Option<BlockId>) -> Result<Bytes, ChainError> {
+    async fn call(&self, _tx: &TransactionRequest, _block: Option<BlockId>) -> Result<Bytes, ChainError> {
         Err(ChainError::NotImplemented)
     }
     
     /// Chờ transaction được confirm
     async fn wait_for_transaction_receipt(
         &self,
-        tx_hash: ethers::types::H256,
-        confirmations: usize,
-        timeout: std::time::Duration,
+        _tx_hash: ethers::types::H256,
+        _confirmations: usize,
+        _timeout: std::time::Duration,
     ) -> Result<TransactionReceipt, ChainError> {
         Err(ChainError::NotImplemented)
     }
@@ -578,23 +679,3 @@ pub trait ChainWatcher: Send + Sync + Debug {
     /// Dừng theo dõi transaction
     async fn unwatch_transaction(&self, tx_hash: ethers::types::H256) -> Result<(), ChainError>;
 }
-
-/// Hàm trích xuất chain_id từ một chuỗi lỗi
-fn extract_chain_id(err_string: &str) -> Option<u64> {
-    // Tìm các pattern như "chain id 1", "chain: 1", "chainId: 1"
-    if let Some(id_str) = err_string
-        .split("chain id ")
-        .nth(1)
-        .or_else(|| err_string.split("chain: ").nth(1))
-        .or_else(|| err_string.split("chainId: ").nth(1))
-    {
-        if let Some(id_end) = id_str.find(|c: char| !c.is_digit(10)) {
-            if let Ok(id) = id_str[..id_end].parse::<u64>() {
-                return Some(id);
-            }
-        } else if let Ok(id) = id_str.parse::<u64>() {
-            return Some(id);
-        }
-    }
-    None
-} 
\ No newline at end of file
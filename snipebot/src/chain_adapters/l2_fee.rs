@@ -0,0 +1,405 @@
+// External imports
+use ethers::{
+    abi::{self, ParamType, Token},
+    providers::{Http, Middleware, Provider},
+    types::{transaction::eip2718::TypedTransaction, Address, Bytes, NameOrAddress, TransactionRequest, U256},
+    utils::id,
+};
+use serde::{Serialize, Deserialize};
+
+// Third party imports
+use anyhow::{anyhow, Result};
+use tracing::{debug, warn};
+
+// Internal imports
+use crate::chain_adapters::interfaces::GasInfo;
+
+/// Địa chỉ predeploy GasPriceOracle trên các chain OP-stack (Optimism, Base, ...)
+pub const OP_GAS_PRICE_ORACLE: &str = "0x420000000000000000000000000000000000000F";
+
+/// Địa chỉ precompile ảo NodeInterface trên Arbitrum (chỉ dùng được qua eth_call)
+pub const ARBITRUM_NODE_INTERFACE: &str = "0x00000000000000000000000000000000000000C8";
+
+/// Kích thước calldata của một lệnh swap qua router V2 với path 2 token (byte)
+pub const SWAP_CALLDATA_BYTES: usize = 260;
+
+/// Giao dịch mẫu gửi tới `to` với `data_length` byte calldata khác 0 (trường hợp xấu nhất khi tính phí L1)
+pub fn sample_tx(to: Address, data_length: usize) -> TransactionRequest {
+    TransactionRequest::new()
+        .to(NameOrAddress::Address(to))
+        .data(Bytes::from(vec![0xff_u8; data_length]))
+}
+
+/// Mô hình tính phí dữ liệu L1 của một chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum L1FeeModel {
+    /// Chain không có phí dữ liệu L1 (L1 hoặc sidechain)
+    None,
+    /// OP-stack: phí L1 lấy từ GasPriceOracle.getL1Fee
+    OpStack,
+    /// Arbitrum Nitro: phí L1 quy đổi thành gas, lấy từ NodeInterface.gasEstimateComponents
+    Arbitrum,
+}
+
+impl L1FeeModel {
+    /// Xác định mô hình phí L1 dựa trên chain ID
+    pub fn for_chain(chain_id: u64) -> Self {
+        match chain_id {
+            // Optimism, OP Sepolia, Base, Base Sepolia, Zora, Mode
+            10 | 11155420 | 8453 | 84532 | 7777777 | 34443 => L1FeeModel::OpStack,
+            // Arbitrum One, Arbitrum Nova, Arbitrum Sepolia
+            42161 | 42170 | 421614 => L1FeeModel::Arbitrum,
+            _ => L1FeeModel::None,
+        }
+    }
+
+    /// Chain có phí dữ liệu L1 hay không
+    pub fn has_l1_fee(&self) -> bool {
+        *self != L1FeeModel::None
+    }
+}
+
+impl std::fmt::Display for L1FeeModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            L1FeeModel::None => write!(f, "none"),
+            L1FeeModel::OpStack => write!(f, "op-stack"),
+            L1FeeModel::Arbitrum => write!(f, "arbitrum"),
+        }
+    }
+}
+
+/// Kết quả gasEstimateComponents của Arbitrum NodeInterface
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArbitrumGasComponents {
+    /// Tổng gas ước tính (đã bao gồm phần gas cho L1)
+    pub gas_estimate: u64,
+    /// Phần gas dùng để trả phí dữ liệu L1
+    pub gas_estimate_for_l1: u64,
+    /// Base fee L2 hiện tại (wei)
+    pub base_fee: U256,
+    /// Ước tính base fee L1 (wei)
+    pub l1_base_fee_estimate: U256,
+}
+
+impl ArbitrumGasComponents {
+    /// Gas thực thi trên L2 (không tính phần L1)
+    pub fn l2_gas(&self) -> u64 {
+        self.gas_estimate.saturating_sub(self.gas_estimate_for_l1)
+    }
+
+    /// Phí dữ liệu L1 (wei), tính theo base fee L2 vì ArbOS thu phần này bằng gas L2
+    pub fn l1_fee(&self) -> U256 {
+        U256::from(self.gas_estimate_for_l1).saturating_mul(self.base_fee)
+    }
+}
+
+/// Ước tính tổng chi phí của một giao dịch, tách phần thực thi L2 và phí dữ liệu L1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotalCostEstimate {
+    /// Chain ID
+    pub chain_id: u64,
+    /// Mô hình phí L1 đã áp dụng
+    pub fee_model: L1FeeModel,
+    /// Gas limit cho phần thực thi
+    pub l2_gas_limit: U256,
+    /// Giá gas cho phần thực thi (wei)
+    pub l2_gas_price: U256,
+    /// Phí thực thi = l2_gas_limit * l2_gas_price (wei)
+    pub l2_execution_fee: U256,
+    /// Phí dữ liệu L1 (wei), bằng 0 trên chain không phải rollup
+    pub l1_data_fee: U256,
+    /// Tổng chi phí (wei)
+    pub total_fee: U256,
+}
+
+impl TotalCostEstimate {
+    /// Tạo ước tính từ các thành phần đã biết
+    pub fn compose(
+        chain_id: u64,
+        fee_model: L1FeeModel,
+        l2_gas_limit: U256,
+        l2_gas_price: U256,
+        l1_data_fee: U256,
+    ) -> Self {
+        let l2_execution_fee = l2_gas_limit.saturating_mul(l2_gas_price);
+        Self {
+            chain_id,
+            fee_model,
+            l2_gas_limit,
+            l2_gas_price,
+            l2_execution_fee,
+            l1_data_fee,
+            total_fee: l2_execution_fee.saturating_add(l1_data_fee),
+        }
+    }
+
+    /// Tỷ lệ phí L1 trên tổng chi phí (0.0 - 1.0)
+    pub fn l1_share(&self) -> f64 {
+        if self.total_fee.is_zero() {
+            return 0.0;
+        }
+        wei_to_f64(self.l1_data_fee) / wei_to_f64(self.total_fee)
+    }
+
+    /// Tổng chi phí quy ra native token (ETH)
+    pub fn total_fee_eth(&self) -> f64 {
+        wei_to_f64(self.total_fee) / 1e18
+    }
+
+    /// Tổng chi phí quy ra USD theo giá native token
+    pub fn total_fee_usd(&self, native_price_usd: f64) -> f64 {
+        self.total_fee_eth() * native_price_usd
+    }
+
+    /// Gắn phí L1 vào GasInfo để báo cáo chi phí
+    pub fn apply_to_gas_info(&self, gas_info: GasInfo) -> GasInfo {
+        gas_info.with_l1_data_fee(self.l1_data_fee)
+    }
+}
+
+fn wei_to_f64(value: U256) -> f64 {
+    if value > U256::from(u128::MAX) {
+        f64::MAX
+    } else {
+        value.as_u128() as f64
+    }
+}
+
+/// Encode calldata cho GasPriceOracle.getL1Fee(bytes)
+pub fn encode_get_l1_fee_call(unsigned_tx_rlp: &[u8]) -> Bytes {
+    let mut data = id("getL1Fee(bytes)").to_vec();
+    data.extend(abi::encode(&[Token::Bytes(unsigned_tx_rlp.to_vec())]));
+    Bytes::from(data)
+}
+
+/// Encode calldata cho NodeInterface.gasEstimateComponents(address,bool,bytes)
+pub fn encode_gas_estimate_components_call(to: Address, contract_creation: bool, data: &[u8]) -> Bytes {
+    let mut calldata = id("gasEstimateComponents(address,bool,bytes)").to_vec();
+    calldata.extend(abi::encode(&[
+        Token::Address(to),
+        Token::Bool(contract_creation),
+        Token::Bytes(data.to_vec()),
+    ]));
+    Bytes::from(calldata)
+}
+
+/// Decode kết quả (uint64,uint64,uint256,uint256) của gasEstimateComponents
+pub fn decode_gas_estimate_components(output: &[u8]) -> Result<ArbitrumGasComponents> {
+    let tokens = abi::decode(
+        &[ParamType::Uint(64), ParamType::Uint(64), ParamType::Uint(256), ParamType::Uint(256)],
+        output,
+    ).map_err(|e| anyhow!("Không thể decode gasEstimateComponents: {}", e))?;
+
+    let as_uint = |token: &Token| token.clone().into_uint()
+        .ok_or_else(|| anyhow!("gasEstimateComponents trả về giá trị không phải uint"));
+
+    Ok(ArbitrumGasComponents {
+        gas_estimate: as_uint(&tokens[0])?.low_u64(),
+        gas_estimate_for_l1: as_uint(&tokens[1])?.low_u64(),
+        base_fee: as_uint(&tokens[2])?,
+        l1_base_fee_estimate: as_uint(&tokens[3])?,
+    })
+}
+
+/// Decode một uint256 từ kết quả eth_call
+fn decode_uint(output: &[u8]) -> Result<U256> {
+    if output.len() < 32 {
+        return Err(anyhow!("Kết quả eth_call quá ngắn: {} bytes", output.len()));
+    }
+    Ok(U256::from_big_endian(&output[..32]))
+}
+
+/// Gọi GasPriceOracle.getL1Fee cho giao dịch (OP-stack)
+pub async fn get_op_stack_l1_fee(provider: &Provider<Http>, chain_id: u64, tx: &TransactionRequest) -> Result<U256> {
+    // GasPriceOracle tính phí trên RLP của giao dịch chưa ký
+    let mut unsigned = tx.clone();
+    if unsigned.chain_id.is_none() {
+        unsigned.chain_id = Some(chain_id.into());
+    }
+    let rlp = unsigned.rlp();
+
+    let oracle: Address = OP_GAS_PRICE_ORACLE.parse()?;
+    let call: TypedTransaction = TransactionRequest::new()
+        .to(oracle)
+        .data(encode_get_l1_fee_call(&rlp))
+        .into();
+
+    let output = provider.call(&call, None).await
+        .map_err(|e| anyhow!("Không thể gọi GasPriceOracle.getL1Fee: {}", e))?;
+    decode_uint(&output)
+}
+
+/// Gọi một hàm view không tham số trả về uint256 trên GasPriceOracle (OP-stack)
+async fn call_op_gas_price_oracle(provider: &Provider<Http>, signature: &str) -> Result<U256> {
+    let oracle: Address = OP_GAS_PRICE_ORACLE.parse()?;
+    let call: TypedTransaction = TransactionRequest::new()
+        .to(oracle)
+        .data(Bytes::from(id(signature).to_vec()))
+        .into();
+
+    let output = provider.call(&call, None).await
+        .map_err(|e| anyhow!("Không thể gọi GasPriceOracle.{}: {}", signature, e))?;
+    decode_uint(&output)
+}
+
+/// Lấy L1 base fee hiện tại mà GasPriceOracle đang dùng (OP-stack)
+pub async fn get_op_stack_l1_base_fee(provider: &Provider<Http>) -> Result<U256> {
+    call_op_gas_price_oracle(provider, "l1BaseFee()").await
+}
+
+/// Lấy base fee scalar (Ecotone) của GasPriceOracle, đơn vị 1e-6 (OP-stack)
+pub async fn get_op_stack_base_fee_scalar(provider: &Provider<Http>) -> Result<U256> {
+    call_op_gas_price_oracle(provider, "baseFeeScalar()").await
+}
+
+/// Gọi NodeInterface.gasEstimateComponents cho giao dịch (Arbitrum)
+pub async fn get_arbitrum_gas_components(provider: &Provider<Http>, tx: &TransactionRequest) -> Result<ArbitrumGasComponents> {
+    let (to, contract_creation) = match &tx.to {
+        Some(NameOrAddress::Address(address)) => (*address, false),
+        Some(NameOrAddress::Name(name)) => {
+            return Err(anyhow!("Không hỗ trợ ENS name khi ước tính phí Arbitrum: {}", name));
+        }
+        None => (Address::zero(), true),
+    };
+    let data = tx.data.clone().unwrap_or_default();
+
+    let node_interface: Address = ARBITRUM_NODE_INTERFACE.parse()?;
+    let mut call = TransactionRequest::new()
+        .to(node_interface)
+        .data(encode_gas_estimate_components_call(to, contract_creation, &data));
+    // NodeInterface mô phỏng giao dịch với from/value của lời gọi
+    if let Some(from) = tx.from {
+        call = call.from(from);
+    }
+    if let Some(value) = tx.value {
+        call = call.value(value);
+    }
+
+    let output = provider.call(&call.into(), None).await
+        .map_err(|e| anyhow!("Không thể gọi NodeInterface.gasEstimateComponents: {}", e))?;
+    decode_gas_estimate_components(&output)
+}
+
+/// Ước tính tổng chi phí (thực thi L2 + dữ liệu L1) của một giao dịch
+///
+/// Gas limit và gas price lấy từ `tx` nếu có, nếu không sẽ ước tính qua RPC.
+/// Trên Arbitrum, gas thực thi lấy từ gasEstimateComponents vì eth_estimateGas
+/// đã gộp cả phần gas dành cho L1.
+pub async fn estimate_total_cost(provider: &Provider<Http>, chain_id: u64, tx: &TransactionRequest) -> Result<TotalCostEstimate> {
+    let fee_model = L1FeeModel::for_chain(chain_id);
+
+    let gas_price = match tx.gas_price {
+        Some(price) => price,
+        None => provider.get_gas_price().await
+            .map_err(|e| anyhow!("Không thể lấy gas price: {}", e))?,
+    };
+
+    let estimate = match fee_model {
+        L1FeeModel::Arbitrum => {
+            let components = get_arbitrum_gas_components(provider, tx).await?;
+            debug!(
+                "Arbitrum gas components cho chain {}: total {}, L1 {}, base fee {}",
+                chain_id, components.gas_estimate, components.gas_estimate_for_l1, components.base_fee
+            );
+            TotalCostEstimate::compose(
+                chain_id,
+                fee_model,
+                U256::from(components.l2_gas()),
+                gas_price,
+                components.l1_fee(),
+            )
+        },
+        L1FeeModel::OpStack | L1FeeModel::None => {
+            let gas_limit = match tx.gas {
+                Some(gas) => gas,
+                None => {
+                    let typed: TypedTransaction = tx.clone().into();
+                    provider.estimate_gas(&typed, None).await
+                        .map_err(|e| anyhow!("Không thể ước tính gas: {}", e))?
+                }
+            };
+
+            let l1_data_fee = if fee_model == L1FeeModel::OpStack {
+                get_op_stack_l1_fee(provider, chain_id, tx).await?
+            } else {
+                U256::zero()
+            };
+
+            TotalCostEstimate::compose(chain_id, fee_model, gas_limit, gas_price, l1_data_fee)
+        },
+    };
+
+    if estimate.fee_model.has_l1_fee() && estimate.l1_share() > 0.5 {
+        warn!(
+            "Phí dữ liệu L1 chiếm {:.1}% tổng chi phí giao dịch trên chain {}",
+            estimate.l1_share() * 100.0, chain_id
+        );
+    }
+
+    Ok(estimate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_model_for_chain() {
+        assert_eq!(L1FeeModel::for_chain(10), L1FeeModel::OpStack);
+        assert_eq!(L1FeeModel::for_chain(8453), L1FeeModel::OpStack);
+        assert_eq!(L1FeeModel::for_chain(42161), L1FeeModel::Arbitrum);
+        assert_eq!(L1FeeModel::for_chain(1), L1FeeModel::None);
+        assert_eq!(L1FeeModel::for_chain(56), L1FeeModel::None);
+        assert!(!L1FeeModel::None.has_l1_fee());
+    }
+
+    #[test]
+    fn test_encode_get_l1_fee_call() {
+        let data = encode_get_l1_fee_call(&[0xde, 0xad, 0xbe, 0xef]);
+        // Selector của getL1Fee(bytes)
+        assert_eq!(&data[..4], &[0x49, 0x94, 0x8e, 0x0e]);
+        // offset + length + 1 word dữ liệu
+        assert_eq!(data.len(), 4 + 32 * 3);
+        assert_eq!(U256::from_big_endian(&data[36..68]), U256::from(4));
+        assert_eq!(&data[68..72], &[0xde, 0xad, 0xbe, 0xef]);
+    }
+
+    #[test]
+    fn test_decode_gas_estimate_components() {
+        let output = abi::encode(&[
+            Token::Uint(U256::from(450_000u64)),
+            Token::Uint(U256::from(300_000u64)),
+            Token::Uint(U256::from(10_000_000u64)),
+            Token::Uint(U256::from(20_000_000_000u64)),
+        ]);
+        let components = decode_gas_estimate_components(&output).unwrap();
+
+        assert_eq!(components.gas_estimate, 450_000);
+        assert_eq!(components.gas_estimate_for_l1, 300_000);
+        assert_eq!(components.l2_gas(), 150_000);
+        assert_eq!(components.l1_fee(), U256::from(300_000u64 * 10_000_000u64));
+
+        assert!(decode_gas_estimate_components(&[0u8; 31]).is_err());
+    }
+
+    #[test]
+    fn test_total_cost_composition() {
+        let estimate = TotalCostEstimate::compose(
+            10,
+            L1FeeModel::OpStack,
+            U256::from(200_000u64),
+            U256::from(1_000_000u64),
+            U256::from(600_000_000_000u64),
+        );
+
+        assert_eq!(estimate.l2_execution_fee, U256::from(200_000_000_000u64));
+        assert_eq!(estimate.total_fee, U256::from(800_000_000_000u64));
+        assert!((estimate.l1_share() - 0.75).abs() < 1e-9);
+        assert!((estimate.total_fee_usd(2000.0) - 0.0016).abs() < 1e-12);
+
+        let gas_info = estimate.apply_to_gas_info(GasInfo::new_legacy(U256::from(1_000_000u64), U256::from(200_000u64)));
+        assert_eq!(gas_info.l1_data_fee, Some(U256::from(600_000_000_000u64)));
+        assert_eq!(gas_info.total_cost(), estimate.total_fee);
+    }
+}
@@ -1,16 +1,11 @@
 // External imports
-use ethers::{
-    types::{Address, U256, TransactionReceipt},
-    providers::Middleware
-};
+use ethers::types::{Address, U256};
 
 // Standard library imports
-use std::sync::Arc;
 use std::str::FromStr;
 
 // Third party imports
 use anyhow::{Result, Context};
-use tokio;
 use tracing::{info, warn};
 
 // Public modules
@@ -19,135 +14,86 @@ pub mod trait_adapter;
 pub mod retry_policy;
 pub mod connection_pool;
 pub mod chain_registry;
+pub mod chain_config_loader;
 pub mod chain_adapter_impl;
 pub mod non_evm_adapter;
 pub mod wallet_integration;
+pub mod approval_manager;
+pub mod treasury;
+pub mod price;
+pub mod portfolio;
 pub mod nonce_manager;
+pub mod block_tracker;
+pub mod l2_fee;
+pub mod ws_subscription;
+pub mod rpc_batch;
+pub mod multicall;
+pub mod rate_limiter;
+pub mod rpc_error;
 pub mod error_handler;
 pub mod interfaces;
 pub mod chain_traits;
 pub mod adapter_registry;
 pub mod retry;
 pub mod configs;
+pub mod generic_evm;
+pub mod trading_adapter;
+pub mod solana_tx;
+pub mod solana_dex;
+pub mod solana_adapter;
+
+#[cfg(test)]
+pub mod tests;
 
 // Public re-exports
 pub use {
     adapter_registry::{ADAPTER_REGISTRY, AdapterRegistry, get_chain_adapter, add_wallet_to_adapter},
-    base::{BaseChainAdapter, ChainConfig},
-    chain_adapter_impl::{ChainAdapterImpl, EVMChainAdapter},
+    approval_manager::{ApprovalManager, ApprovalManagerConfig, ApprovalRecord, ApprovalKind, PermitSignature, RevokeReport},
+    base::{EVMAdapter, ChainAdapterEnum, ChainConfig, create_chain_adapter},
+    block_tracker::{BlockTracker, BlockTrackerConfig, TrackedTransaction, start_block_tracker, stop_block_tracker, get_block_tracker},
+    chain_adapter_impl::EVMChainAdapter,
     chain_registry::{get_adapter, get_chain_config, AdapterStatus, ChainAdapterInfo, factory},
-    connection_pool::{ConnectionPool, get_pool, get_all_pools_info, EndpointInfo, EndpointStatus, ConnectionPoolConfig},
+    chain_config_loader::{ChainReloadReport, ChainSummary, reload_chain_configs, list_chains},
+    connection_pool::{RPCConnectionPool, get_pool, get_all_pools_info, EndpointInfo, EndpointStatus, ConnectionPoolConfig, RoutingStrategy, QuorumConfig, QuorumUnavailable, quorum_get_balance, quorum_get_amounts_out, fresh_get_transaction_count},
     interfaces::{ChainAdapter, ChainError, GasInfo, TokenDetails, BlockInfo, NodeInfo, TokenState},
+    generic_evm::{GenericEvmAdapter, EvmCapabilities},
+    l2_fee::{L1FeeModel, TotalCostEstimate, estimate_total_cost},
     non_evm_adapter::NonEVMAdapter,
-    retry_policy::{RetryPolicy, RetryContext, RetryStats, create_default_retry_policy},
+    nonce_manager::{NonceManager, NonceLease, NonceMetrics, NonceReconcileReport, get_or_create_nonce_manager},
+    retry_policy::{RetryPolicy, RetryAction, RetryContext, RetryStats, create_default_retry_policy},
+    rpc_error::{classify_rpc_error, classify_provider_error, classify_anyhow, decode_revert_reason},
     trait_adapter::{AsyncChainAdapter, ChainWatcher},
+    rpc_batch::{BatchingHttp, BatchConfig},
+    solana_adapter::{SolanaAdapter, SolanaConfig, SolanaRpcClient, PoolPrice},
+    solana_dex::SolanaDex,
+    price::{DexPriceOracle, DexPriceConfig, STABLECOIN_SYMBOLS},
+    portfolio::{PortfolioIndexer, PortfolioConfig, PortfolioChain, Portfolio, ChainPortfolio, WalletHoldings, TokenHolding, TokenMeta, PortfolioSnapshot, ValueChange},
+    treasury::{Treasury, TreasuryConfig, TreasuryRequest, ChainTreasuryConfig, TreasuryPlan, TreasuryReport, TreasuryOperation, Asset, Distribution, Transfer, TransferRoute, SkippedWallet, FailedTransfer, split_amount},
+    trading_adapter::{TradingAdapter, ChainFamily, SwapRequest, TradeQuote, SwapOutcome, get_trading_adapter, get_trading_chains},
+    rate_limiter::{EndpointRateLimit, ComputeUnitCosts},
+    ws_subscription::{WsSubscriptionManager, WsSubscriptionConfig, SubscriptionKind, SubscriptionEvent, SubscriptionMessage, Subscription, get_or_create_subscription_manager},
     wallet_integration::{WalletIntegration, TransactionManager, create_transaction_manager, get_wallet_balances, get_token_balances}
 };
 
 /// Khởi tạo tất cả các chain adapter được hỗ trợ
 pub async fn init_adapters() -> Result<()> {
-    // Lấy danh sách chain ID từ cấu hình
-    let chain_configs = chain_registry::get_all_chain_configs();
+    // Nạp cấu hình chain từ thư mục file TOML (hoặc cấu hình đóng gói sẵn)
+    chain_config_loader::reload_default_chain_configs().await?;
     
-    // Khởi tạo registry
-    let registry = Arc::new(tokio::sync::RwLock::new(
-        chain_registry::ChainRegistry::new()
-    ));
-    
-    // Tạo và đăng ký từng adapter
-    for config in chain_configs {
-        if config.chain_type == "EVM" {
-            // Tạo EVM adapter
-            let adapter = EVMChainAdapter::from_config(config.clone()).await?;
-            
-            // Đăng ký vào registry
-            let mut registry_guard = registry.write().await;
-            registry_guard.register_adapter(config.chain_id, adapter.clone());
-            
-            info!("Đã khởi tạo EVM adapter cho chain {}: {}", config.chain_id, config.name);
-        } else {
-            // Xử lý các loại adapter khác khi cần
-            warn!("Không hỗ trợ loại chain {}: {}", config.chain_type, config.name);
+    // Tạo và đăng ký adapter cho từng chain trong registry toàn cục
+    for config in chain_registry::get_all_chain_configs() {
+        if chain_registry::get_registry().get_adapter(config.chain_id).is_ok() {
+            continue;
+        }
+        match chain_registry::init_chain_adapter(config.chain_id).await {
+            Ok(()) => info!("Đã khởi tạo adapter cho chain {}: {}", config.chain_id, config.name),
+            Err(e) => warn!("Không thể khởi tạo adapter cho chain {} ({}): {}", config.chain_id, config.name, e),
         }
     }
     
-    // Lưu registry vào biến toàn cục
-    *chain_registry::REGISTRY.write().await = registry.clone();
-    
     Ok(())
 }
 
-/// Hàm xử lý transaction với logic send_raw và retry tích hợp
-pub async fn send_transaction_with_retry(
-    chain_id: u64,
-    wallet_address: &str,
-    to_address: &str,
-    value: Option<U256>,
-    data: Option<Vec<u8>>,
-    gas_limit: Option<U256>,
-    gas_price: Option<U256>
-) -> Result<TransactionReceipt> {
-    // Lấy registry
-    let registry = chain_registry::REGISTRY.read().await.clone();
-    
-    // Lấy wallet storage
-    let wallet_storage = wallet::secure_storage::WALLET_STORAGE.read().await.clone();
-    
-    // Tạo transaction manager
-    let tx_manager = wallet_integration::create_transaction_manager(
-        registry.clone(),
-        wallet_storage
-    ).await;
-    
-    // Gửi transaction
-    let tx_hash = tx_manager.send_transaction(
-        chain_id,
-        wallet_address,
-        to_address,
-        value,
-        data,
-        gas_limit,
-        gas_price,
-        None // Tự động tính nonce
-    ).await?;
-    
-    // Đợi transaction hoàn thành
-    let receipt = tx_manager.wait_for_transaction(
-        chain_id,
-        tx_hash,
-        2, // 2 confirmations
-        120 // timeout 120 seconds
-    ).await?;
-    
-    Ok(receipt)
-}
-
-/// Lấy số dư token và ETH cho một ví
-pub async fn get_wallet_balances_with_tokens(
-    wallet_address: &str,
-    chain_ids: &[u64],
-    token_addresses: &[(u64, &str)]
-) -> Result<(Vec<(u64, U256)>, Vec<(u64, Address, U256)>)> {
-    // Lấy registry
-    let registry = chain_registry::REGISTRY.read().await.clone();
-    
-    // Lấy balance ETH
-    let native_balances = wallet_integration::get_wallet_balances(
-        registry.clone(),
-        wallet_address,
-        chain_ids
-    ).await?;
-    
-    // Lấy balance token
-    let token_balances = wallet_integration::get_token_balances(
-        registry.clone(),
-        wallet_address,
-        token_addresses
-    ).await?;
-    
-    Ok((native_balances, token_balances))
-}
-
 /// Lấy token details cho nhiều token
 pub async fn get_token_details_batch(
     chain_id: u64,
@@ -209,33 +155,42 @@ pub async fn get_retry_stats() -> Result<serde_json::Value> {
     Ok(json)
 }
 
+/// Lấy thống kê nonce lease của tất cả chain
+pub async fn get_nonce_stats() -> Result<serde_json::Value> {
+    // Lấy thống kê
+    let stats = nonce_manager::get_all_nonce_metrics().await;
+    
+    // Format thành JSON
+    let json = serde_json::to_value(stats)?;
+    
+    Ok(json)
+}
+
 /// Lấy tất cả chain đang được hỗ trợ
 pub fn get_supported_chains() -> Vec<String> {
-    chain_registry::get_supported_chains()
+    configs::get_supported_chains()
 }
 
+/// Lấy số dư token của ví trên một chain
 pub async fn get_token_balance(chain_id: u64, token_address: &str, wallet_address: &str) -> Result<U256> {
     let adapter = chain_registry::get_adapter(chain_id)?;
-    if let Ok(async_adapter) = adapter.as_any().downcast_ref::<Box<dyn AsyncChainAdapter>>() {
-        let balance = async_adapter.get_token_balance(token_address, wallet_address).await?;
-        Ok(balance)
-    } else {
-        Err(anyhow::anyhow!("Không thể chuyển đổi adapter thành AsyncChainAdapter"))
-    }
+    let token = Address::from_str(token_address)
+        .context(format!("Invalid token address: {}", token_address))?;
+    let wallet = Address::from_str(wallet_address)
+        .context(format!("Invalid wallet address: {}", wallet_address))?;
+    Ok(adapter.get_token_balance(token, wallet, None).await?)
 }
 
+/// Lấy số dư native token của ví trên một chain
 pub async fn get_native_balance(chain_id: u64, wallet_address: &str) -> Result<U256> {
     let adapter = chain_registry::get_adapter(chain_id)?;
-    if let Ok(async_adapter) = adapter.as_any().downcast_ref::<Box<dyn AsyncChainAdapter>>() {
-        let balance = async_adapter.get_native_balance(wallet_address).await?;
-        Ok(balance)
-    } else {
-        Err(anyhow::anyhow!("Không thể chuyển đổi adapter thành AsyncChainAdapter"))
-    }
+    let wallet = Address::from_str(wallet_address)
+        .context(format!("Invalid wallet address: {}", wallet_address))?;
+    Ok(adapter.get_eth_balance(wallet, None).await?)
 }
 
+/// Lấy gas price hiện tại của chain
 pub async fn get_gas_price(chain_id: u64) -> Result<U256> {
     let adapter = chain_registry::get_adapter(chain_id)?;
-    let gas_optimizer = adapter.get_gas_optimizer().ok_or_else(|| anyhow::anyhow!("Không tìm thấy gas optimizer"))?;
-    gas_optimizer.get_optimal_gas_price(&*adapter).await
+    Ok(adapter.get_gas_price().await?)
 }
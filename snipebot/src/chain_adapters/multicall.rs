@@ -0,0 +1,224 @@
+// External imports
+use ethers::{
+    abi::{self, ParamType, Token},
+    providers::Middleware,
+    types::{transaction::eip2718::TypedTransaction, Address, BlockId, Bytes, TransactionRequest, U256},
+};
+
+// Third party imports
+use anyhow::{anyhow, Result};
+use metrics::counter;
+use tracing::debug;
+
+/// Địa chỉ Multicall3 (giống nhau trên hầu hết các chain EVM)
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+/// Số call tối đa trong một lần gọi aggregate3 để tránh vượt gas limit của eth_call
+pub const MAX_CALLS_PER_MULTICALL: usize = 300;
+
+/// Một call trong Multicall3.aggregate3
+#[derive(Debug, Clone)]
+pub struct Call3 {
+    /// Contract được gọi
+    pub target: Address,
+    /// Cho phép call thất bại mà không revert cả batch
+    pub allow_failure: bool,
+    /// Calldata
+    pub call_data: Bytes,
+}
+
+impl Call3 {
+    /// Tạo call cho phép thất bại
+    pub fn new(target: Address, call_data: Vec<u8>) -> Self {
+        Self {
+            target,
+            allow_failure: true,
+            call_data: call_data.into(),
+        }
+    }
+}
+
+/// Địa chỉ Multicall3 dạng Address
+pub fn multicall3_address() -> Address {
+    MULTICALL3_ADDRESS.parse().unwrap_or_default()
+}
+
+/// Encode calldata cho aggregate3((address,bool,bytes)[])
+pub fn encode_aggregate3(calls: &[Call3]) -> Vec<u8> {
+    let mut data = ethers::utils::id("aggregate3((address,bool,bytes)[])").to_vec();
+    data.extend(abi::encode(&[Token::Array(
+        calls
+            .iter()
+            .map(|call| {
+                Token::Tuple(vec![
+                    Token::Address(call.target),
+                    Token::Bool(call.allow_failure),
+                    Token::Bytes(call.call_data.to_vec()),
+                ])
+            })
+            .collect(),
+    )]));
+    data
+}
+
+/// Decode kết quả (bool success, bytes returnData)[] của aggregate3
+pub fn decode_aggregate3(output: &[u8]) -> Result<Vec<Option<Bytes>>> {
+    let tokens = abi::decode(
+        &[ParamType::Array(Box::new(ParamType::Tuple(vec![ParamType::Bool, ParamType::Bytes])))],
+        output,
+    )
+    .map_err(|e| anyhow!("Failed to decode aggregate3: {}", e))?;
+
+    tokens
+        .into_iter()
+        .next()
+        .and_then(|token| token.into_array())
+        .ok_or_else(|| anyhow!("aggregate3 returned unexpected data"))?
+        .into_iter()
+        .map(|token| {
+            let mut fields = token
+                .into_tuple()
+                .ok_or_else(|| anyhow!("aggregate3 result is not a tuple"))?
+                .into_iter();
+            let success = fields.next().and_then(|t| t.into_bool()).unwrap_or(false);
+            let data = fields.next().and_then(|t| t.into_bytes()).unwrap_or_default();
+            Ok(if success { Some(data.into()) } else { None })
+        })
+        .collect()
+}
+
+/// Thực hiện nhiều call trong một (hoặc vài, nếu vượt `MAX_CALLS_PER_MULTICALL`) eth_call.
+/// Kết quả theo đúng thứ tự `calls`; `None` cho call thất bại.
+pub async fn aggregate3<M: Middleware>(client: &M, calls: &[Call3], block: Option<BlockId>) -> Result<Vec<Option<Bytes>>> {
+    let multicall = multicall3_address();
+    let mut results = Vec::with_capacity(calls.len());
+
+    for chunk in calls.chunks(MAX_CALLS_PER_MULTICALL) {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(multicall)
+            .data(encode_aggregate3(chunk))
+            .into();
+        let output = client
+            .call(&tx, block)
+            .await
+            .map_err(|e| anyhow!("Multicall3 aggregate3 failed: {}", e))?;
+
+        let decoded = decode_aggregate3(&output)?;
+        if decoded.len() != chunk.len() {
+            return Err(anyhow!("Multicall3 returned {} results for {} calls", decoded.len(), chunk.len()));
+        }
+        results.extend(decoded);
+    }
+
+    counter!("multicall_batched_calls", calls.len() as u64);
+    debug!("Multicall3 executed {} calls", calls.len());
+    Ok(results)
+}
+
+/// Calldata cho ERC20 balanceOf(address)
+pub fn balance_of_call(token: Address, owner: Address) -> Call3 {
+    let mut data = ethers::utils::id("balanceOf(address)").to_vec();
+    data.extend(abi::encode(&[Token::Address(owner)]));
+    Call3::new(token, data)
+}
+
+/// Calldata cho ERC20 totalSupply()
+pub fn total_supply_call(token: Address) -> Call3 {
+    Call3::new(token, ethers::utils::id("totalSupply()").to_vec())
+}
+
+/// Calldata cho UniswapV2Pair getReserves()
+pub fn get_reserves_call(pair: Address) -> Call3 {
+    Call3::new(pair, ethers::utils::id("getReserves()").to_vec())
+}
+
+/// Calldata cho UniswapV2Pair token0()
+pub fn token0_call(pair: Address) -> Call3 {
+    Call3::new(pair, ethers::utils::id("token0()").to_vec())
+}
+
+/// Decode kết quả uint256
+pub fn decode_uint(data: &[u8]) -> Option<U256> {
+    (data.len() >= 32).then(|| U256::from_big_endian(&data[..32]))
+}
+
+/// Decode kết quả address
+pub fn decode_address(data: &[u8]) -> Option<Address> {
+    (data.len() >= 32).then(|| Address::from_slice(&data[12..32]))
+}
+
+/// Decode kết quả getReserves() thành (reserve0, reserve1)
+pub fn decode_reserves(data: &[u8]) -> Option<(U256, U256)> {
+    (data.len() >= 64).then(|| (U256::from_big_endian(&data[..32]), U256::from_big_endian(&data[32..64])))
+}
+
+/// Lấy số dư ERC20 cho nhiều cặp (token, owner) trong một eth_call
+pub async fn get_token_balances<M: Middleware>(client: &M, queries: &[(Address, Address)]) -> Result<Vec<Option<U256>>> {
+    let calls: Vec<Call3> = queries.iter().map(|(token, owner)| balance_of_call(*token, *owner)).collect();
+    Ok(aggregate3(client, &calls, None)
+        .await?
+        .into_iter()
+        .map(|data| data.and_then(|d| decode_uint(&d)))
+        .collect())
+}
+
+/// Lấy reserves của nhiều pair trong một eth_call
+pub async fn get_pair_reserves<M: Middleware>(client: &M, pairs: &[Address]) -> Result<Vec<Option<(U256, U256)>>> {
+    let calls: Vec<Call3> = pairs.iter().map(|pair| get_reserves_call(*pair)).collect();
+    Ok(aggregate3(client, &calls, None)
+        .await?
+        .into_iter()
+        .map(|data| data.and_then(|d| decode_reserves(&d)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_aggregate3_selector_and_calls() {
+        let calls = vec![
+            balance_of_call(Address::repeat_byte(1), Address::repeat_byte(2)),
+            get_reserves_call(Address::repeat_byte(3)),
+        ];
+        let data = encode_aggregate3(&calls);
+        // aggregate3((address,bool,bytes)[]) = 0x82ad56cb
+        assert_eq!(&data[..4], &[0x82, 0xad, 0x56, 0xcb]);
+
+        let decoded = abi::decode(
+            &[ParamType::Array(Box::new(ParamType::Tuple(vec![ParamType::Address, ParamType::Bool, ParamType::Bytes])))],
+            &data[4..],
+        )
+        .unwrap();
+        let items = decoded[0].clone().into_array().unwrap();
+        assert_eq!(items.len(), 2);
+        let first = items[0].clone().into_tuple().unwrap();
+        assert_eq!(first[0], Token::Address(Address::repeat_byte(1)));
+        assert_eq!(first[1], Token::Bool(true));
+        // balanceOf(address) = 0x70a08231
+        assert_eq!(&first[2].clone().into_bytes().unwrap()[..4], &[0x70, 0xa0, 0x82, 0x31]);
+    }
+
+    #[test]
+    fn test_decode_aggregate3_results() {
+        let reserves = abi::encode(&[Token::Uint(U256::from(1000u64)), Token::Uint(U256::from(5u64)), Token::Uint(U256::from(1u64))]);
+        let output = abi::encode(&[Token::Array(vec![
+            Token::Tuple(vec![Token::Bool(true), Token::Bytes(reserves)]),
+            Token::Tuple(vec![Token::Bool(false), Token::Bytes(vec![])]),
+        ])]);
+
+        let results = decode_aggregate3(&output).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(decode_reserves(results[0].as_ref().unwrap()), Some((U256::from(1000u64), U256::from(5u64))));
+        assert!(results[1].is_none());
+    }
+
+    #[test]
+    fn test_decode_scalars() {
+        let word = abi::encode(&[Token::Address(Address::repeat_byte(9))]);
+        assert_eq!(decode_address(&word), Some(Address::repeat_byte(9)));
+        assert_eq!(decode_uint(&abi::encode(&[Token::Uint(U256::from(42u64))])), Some(U256::from(42u64)));
+        assert_eq!(decode_uint(&[0u8; 4]), None);
+    }
+}
@@ -1,27 +1,28 @@
 use ethers::types::{Address, U256};
 use std::time::Instant;
 use crate::chain_adapters::interfaces::{GasInfo, TokenDetails, BlockInfo, NodeInfo};
-use crate::chain_adapters::connection_pool::{get_or_create_pool, ProviderGuard};
 use anyhow::{Result, anyhow};
 use std::sync::Arc;
-use tracing::{info, warn, error};
 use crate::chain_adapters::{ChainAdapter, ChainError};
 use std::collections::HashMap;
 use async_trait::async_trait;
 use tokio::sync::RwLock;
 use ethers::types::{
-    BlockId, Bytes, Filter, Log, Transaction, H256,
+    BlockId, Bytes, Filter, Log, Transaction, TransactionRequest, TransactionReceipt, H256,
 };
-use ethers::providers::PendingTransaction;
 use serde::{Serialize, Deserialize};
 
 use crate::chain_adapters::{
     base::ChainConfig,
-    retry_policy::{RetryPolicy, RetryPolicyEnum, RetryContext, create_default_retry_policy},
+    retry_policy::{RetryPolicyEnum, RetryContext, create_default_retry_policy},
+    solana_adapter::SolanaAdapter,
+    trading_adapter::TradingAdapter,
 };
 
-/// Adapter cho blockchain không phải là EVM (cho mục đích minh họa)
-/// Trong thực tế, cần phải triển khai cụ thể cho từng loại blockchain
+/// Adapter cho blockchain không phải là EVM.
+/// Khi gắn backend Solana, các truy vấn slot/phí/node được chuyển sang `SolanaAdapter`;
+/// giao dịch đi qua giao diện `TradingAdapter` vì các kiểu EVM không áp dụng được
+#[allow(dead_code)]
 pub struct NonEVMAdapter {
     /// Định danh blockchain
     chain_id: u64,
@@ -33,8 +34,11 @@ pub struct NonEVMAdapter {
     retry_policy: RetryPolicyEnum,
     /// Cache cho các kết quả truy vấn
     cache: RwLock<HashMap<String, (Instant, serde_json::Value)>>,
+    /// Backend Solana (nếu chain là Solana)
+    solana: Option<Arc<SolanaAdapter>>,
 }
 
+#[allow(dead_code)]
 impl NonEVMAdapter {
     /// Tạo mới một NonEVMAdapter
     pub fn new(
@@ -49,6 +53,41 @@ impl NonEVMAdapter {
             config,
             retry_policy: retry_policy.unwrap_or_else(create_default_retry_policy),
             cache: RwLock::new(HashMap::new()),
+            solana: None,
+        })
+    }
+    
+    /// Tạo NonEVMAdapter dùng backend Solana
+    pub fn with_solana(
+        chain_id: u64,
+        chain_name: String,
+        config: ChainConfig,
+        solana: Arc<SolanaAdapter>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            chain_id,
+            chain_name,
+            config,
+            retry_policy: create_default_retry_policy(),
+            cache: RwLock::new(HashMap::new()),
+            solana: Some(solana),
+        })
+    }
+    
+    /// Backend Solana (nếu có)
+    pub fn solana(&self) -> Option<&Arc<SolanaAdapter>> {
+        self.solana.as_ref()
+    }
+    
+    /// Adapter giao dịch không phụ thuộc kiểu EVM của chain này
+    pub fn trading_adapter(&self) -> Option<Arc<dyn TradingAdapter>> {
+        self.solana.clone().map(|solana| solana as Arc<dyn TradingAdapter>)
+    }
+    
+    /// Lấy backend Solana, báo NotImplemented nếu chain chưa có backend
+    fn require_solana(&self, operation: &str) -> Result<&Arc<SolanaAdapter>, ChainError> {
+        self.solana.as_ref().ok_or_else(|| {
+            ChainError::UnsupportedOperation(format!("{} not implemented for non-EVM chain {}", operation, self.chain_name))
         })
     }
     
@@ -88,7 +127,7 @@ impl NonEVMAdapter {
     /// Truy cập API của blockchain
     async fn api_request<T>(&self, endpoint: &str, method: &str, params: Vec<serde_json::Value>) -> Result<T>
     where
-        T: for<'de> Deserialize<'de> + Serialize,
+        T: for<'de> Deserialize<'de> + Serialize + Send + Sync,
     {
         // Cache key từ endpoint và params
         let cache_key = format!("{}:{}:{}", endpoint, method, serde_json::to_string(&params)?);
@@ -155,13 +194,18 @@ impl NonEVMAdapter {
 #[async_trait]
 impl ChainAdapter for NonEVMAdapter {
     async fn get_block_number(&self) -> Result<u64, ChainError> {
-        // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("get_block_number not implemented for non-EVM chains".to_string()))
+        // Solana: slot hiện tại
+        let solana = self.require_solana("get_block_number")?;
+        solana.rpc().get_slot().await
+            .map_err(|e| ChainError::ConnectionError(e.to_string()))
     }
     
     async fn get_gas_price(&self) -> Result<U256, ChainError> {
-        // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("get_gas_price not implemented for non-EVM chains".to_string()))
+        // Solana: priority fee gần đây (micro-lamports/compute unit)
+        let solana = self.require_solana("get_gas_price")?;
+        solana.rpc().get_recent_prioritization_fee(&[]).await
+            .map(U256::from)
+            .map_err(|e| ChainError::ConnectionError(e.to_string()))
     }
     
     fn get_chain_id(&self) -> u64 {
@@ -174,89 +218,107 @@ impl ChainAdapter for NonEVMAdapter {
     
     async fn get_block(&self, _block_id: BlockId) -> Result<Option<BlockInfo>, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("get_block not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("get_block not implemented for non-EVM chains".to_string()))
     }
     
     async fn get_gas_info(&self) -> Result<GasInfo, ChainError> {
-        // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("get_gas_info not implemented for non-EVM chains".to_string()))
+        // Solana: gas_price là priority fee, gas_limit là giới hạn compute unit
+        let priority_fee = self.get_gas_price().await?;
+        let compute_unit_limit = self.require_solana("get_gas_info")?.config().compute_unit_limit;
+        Ok(GasInfo {
+            gas_price: priority_fee,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: Some(priority_fee),
+            gas_limit: U256::from(compute_unit_limit),
+            eip1559_supported: false,
+            l1_data_fee: None,
+        })
     }
     
     /// Gửi transaction raw
-    async fn send_raw_transaction(&self, _tx_bytes: Bytes) -> Result<PendingTransaction<'static, Provider<Http>>, ChainError> {
+    async fn send_raw_transaction(&self, _tx_bytes: Bytes) -> Result<ethers::types::H256, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("send_raw_transaction not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("send_raw_transaction not implemented for non-EVM chains".to_string()))
     }
     
     /// Gửi transaction
-    async fn send_transaction(&self, _tx: &TransactionRequest) -> Result<PendingTransaction<'static, Provider<Http>>, ChainError> {
+    async fn send_transaction(&self, _tx: &TransactionRequest) -> Result<ethers::types::H256, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("send_transaction not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("send_transaction not implemented for non-EVM chains".to_string()))
     }
     
     async fn get_transaction_receipt(&self, _tx_hash: H256) -> Result<Option<TransactionReceipt>, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("get_transaction_receipt not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("get_transaction_receipt not implemented for non-EVM chains".to_string()))
     }
     
     async fn get_transaction(&self, _tx_hash: H256) -> Result<Option<Transaction>, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("get_transaction not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("get_transaction not implemented for non-EVM chains".to_string()))
     }
     
     async fn get_eth_balance(&self, _address: Address, _block: Option<BlockId>) -> Result<U256, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("get_eth_balance not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("get_eth_balance not implemented for non-EVM chains".to_string()))
     }
     
     async fn get_token_balance(&self, _token: Address, _address: Address, _block: Option<BlockId>) -> Result<U256, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("get_token_balance not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("get_token_balance not implemented for non-EVM chains".to_string()))
     }
     
     async fn get_token_details(&self, _token: Address) -> Result<TokenDetails, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("get_token_details not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("get_token_details not implemented for non-EVM chains".to_string()))
     }
     
     async fn get_token_allowance(&self, _token: Address, _owner: Address, _spender: Address) -> Result<U256, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("get_token_allowance not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("get_token_allowance not implemented for non-EVM chains".to_string()))
     }
     
     async fn get_logs(&self, _filter: &Filter) -> Result<Vec<Log>, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("get_logs not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("get_logs not implemented for non-EVM chains".to_string()))
     }
     
     async fn get_transaction_count(&self, _address: Address, _block: Option<BlockId>) -> Result<U256, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("get_transaction_count not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("get_transaction_count not implemented for non-EVM chains".to_string()))
     }
     
     async fn estimate_gas(&self, _tx: &TransactionRequest) -> Result<U256, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("estimate_gas not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("estimate_gas not implemented for non-EVM chains".to_string()))
     }
     
     async fn call(&self, _tx: &TransactionRequest, _block: Option<BlockId>) -> Result<Bytes, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("call not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("call not implemented for non-EVM chains".to_string()))
     }
     
     async fn wait_for_transaction_receipt(
         &self,
         _tx_hash: H256,
         _confirmations: usize,
-        timeout: std::time::Duration,
+        _timeout: std::time::Duration,
     ) -> Result<TransactionReceipt, ChainError> {
         // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("wait_for_transaction_receipt not implemented for non-EVM chains".to_string()))
+        Err(ChainError::UnsupportedOperation("wait_for_transaction_receipt not implemented for non-EVM chains".to_string()))
     }
     
     async fn get_node_info(&self) -> Result<NodeInfo, ChainError> {
-        // Triển khai theo giao thức của blockchain cụ thể
-        Err(ChainError::NotImplemented("get_node_info not implemented for non-EVM chains".to_string()))
+        let solana = self.require_solana("get_node_info")?;
+        let client_version = solana.rpc().get_version().await
+            .map_err(|e| ChainError::ConnectionError(e.to_string()))?;
+        let slot = self.get_block_number().await?;
+        Ok(NodeInfo {
+            client_version: format!("solana-core/{}", client_version),
+            chain_id: self.chain_id,
+            is_syncing: false,
+            current_block: slot,
+            highest_block: slot,
+        })
     }
 }
 
@@ -265,6 +327,7 @@ impl std::fmt::Debug for NonEVMAdapter {
         f.debug_struct("NonEVMAdapter")
             .field("chain_id", &self.chain_id)
             .field("chain_name", &self.chain_name)
+            .field("solana", &self.solana.is_some())
             .finish()
     }
 } 
\ No newline at end of file
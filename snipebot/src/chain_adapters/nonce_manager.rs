@@ -1,165 +1,794 @@
-use ethers::types::{Address, U256};
-use anyhow::{Result, anyhow};
-use std::sync::{Arc, RwLock};
-use std::collections::HashMap;
-use tracing::{info, warn, error};
-use tokio::sync::{RwLock as TokioRwLock, Mutex};
+use ethers::types::{Address, BlockNumber, BlockId, H256, U256};
 use ethers::providers::{Provider, Http, Middleware};
-use log::{debug, info, warn, error};
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
-use std::cmp;
-use tracing::{info, warn};
-use crate::chain_adapters::interfaces::ChainError;
+use anyhow::{Result, anyhow};
+use std::sync::{Arc, Weak};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+use tokio::sync::{RwLock, Mutex};
+use tracing::{debug, info, warn, error};
+use serde::{Serialize, Deserialize};
+use metrics::gauge;
+use once_cell::sync::Lazy;
+
+use crate::chain_adapters::connection_pool;
+
+/// Trạng thái của một nonce đang được cho mượn (lease)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LeaseState {
+    /// Đã cấp nonce nhưng giao dịch chưa được gửi
+    Reserved,
+    /// Giao dịch đã được gửi lên mempool
+    Submitted,
+}
+
+/// Một nonce được cấp cho giao dịch đang xử lý
+#[derive(Debug, Clone)]
+pub struct NonceLease {
+    /// Chain ID của nonce
+    pub chain_id: u64,
+    /// Địa chỉ ví
+    pub address: Address,
+    /// Giá trị nonce được cấp
+    pub nonce: U256,
+    /// Trạng thái lease
+    pub state: LeaseState,
+    /// Hash giao dịch (khi đã gửi)
+    pub tx_hash: Option<H256>,
+    /// Thời điểm cấp nonce
+    pub acquired_at: Instant,
+    /// Thời điểm giao dịch được gửi
+    pub submitted_at: Option<Instant>,
+}
+
+/// Kết quả đối chiếu nonce với blockchain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NonceReconcileReport {
+    /// Chain ID
+    pub chain_id: u64,
+    /// Địa chỉ ví
+    pub address: Address,
+    /// Số giao dịch đã xác nhận (`latest`)
+    pub latest_nonce: U256,
+    /// Số giao dịch tính cả mempool (`pending`)
+    pub pending_nonce: U256,
+    /// Nonce tiếp theo sẽ được cấp sau khi đối chiếu
+    pub next_nonce: U256,
+    /// Các nonce bị thiếu sẽ chặn các giao dịch phía sau
+    pub gaps: Vec<U256>,
+    /// Số lease đã được xác nhận và giải phóng trong lần đối chiếu này
+    pub confirmed_leases: usize,
+    /// Nonce của giao dịch đã gửi nhưng bị rơi khỏi mempool, được trả lại để cấp lại
+    pub dropped_nonces: Vec<U256>,
+    /// Nonce Reserved mà chain đã dùng (thấp hơn `pending`), lease bị thu hồi
+    pub stale_reserved: Vec<U256>,
+}
+
+/// Thống kê nonce cho một chain
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NonceMetrics {
+    /// Chain ID
+    pub chain_id: u64,
+    /// Số địa chỉ đang được theo dõi
+    pub tracked_addresses: usize,
+    /// Tổng số lease đang mở
+    pub outstanding_leases: usize,
+    /// Số lease đang ở trạng thái Reserved
+    pub reserved_leases: usize,
+    /// Số lease đã gửi nhưng chưa xác nhận
+    pub submitted_leases: usize,
+    /// Số nonce đã trả lại và chờ tái sử dụng
+    pub released_nonces: usize,
+    /// Tuổi của lease lâu nhất (giây)
+    pub oldest_lease_secs: u64,
+}
+
+/// Trạng thái nonce của một địa chỉ
+#[derive(Debug)]
+struct AddressNonceState {
+    /// Nonce cao nhất tiếp theo chưa từng được cấp
+    next_nonce: U256,
+    /// Nonce đã xác nhận trên chain (`latest`)
+    confirmed_nonce: U256,
+    /// Số giao dịch chain thấy tính cả mempool (`pending`) ở lần đối chiếu gần nhất
+    chain_pending: U256,
+    /// Các nonce đang được cho mượn
+    leases: BTreeMap<U256, NonceLease>,
+    /// Các nonce đã trả lại, được ưu tiên cấp lại để tránh gap
+    released: BTreeSet<U256>,
+    /// Thời điểm đồng bộ gần nhất với chain
+    last_sync: Instant,
+}
+
+impl AddressNonceState {
+    fn new(latest: U256, pending: U256) -> Self {
+        Self {
+            next_nonce: std::cmp::max(latest, pending),
+            confirmed_nonce: latest,
+            chain_pending: std::cmp::max(latest, pending),
+            leases: BTreeMap::new(),
+            released: BTreeSet::new(),
+            last_sync: Instant::now(),
+        }
+    }
+
+    /// Cấp nonce thấp nhất khả dụng: nonce đã trả lại trước, sau đó mới tới nonce mới
+    fn lease(&mut self, chain_id: u64, address: Address) -> NonceLease {
+        let nonce = match self.released.iter().next().cloned() {
+            Some(nonce) => {
+                self.released.remove(&nonce);
+                nonce
+            },
+            None => {
+                let nonce = self.next_nonce;
+                self.next_nonce = self.next_nonce + 1;
+                nonce
+            }
+        };
+
+        let lease = NonceLease {
+            chain_id,
+            address,
+            nonce,
+            state: LeaseState::Reserved,
+            tx_hash: None,
+            acquired_at: Instant::now(),
+            submitted_at: None,
+        };
+        self.leases.insert(nonce, lease.clone());
+        lease
+    }
+
+    /// Đánh dấu lease đã được gửi lên mạng
+    fn mark_submitted(&mut self, nonce: U256, tx_hash: H256) -> bool {
+        match self.leases.get_mut(&nonce) {
+            Some(lease) => {
+                lease.state = LeaseState::Submitted;
+                lease.tx_hash = Some(tx_hash);
+                lease.submitted_at = Some(Instant::now());
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Trả lại nonce khi giao dịch thất bại trước khi được đưa vào mempool
+    fn release(&mut self, nonce: U256) -> bool {
+        if self.leases.remove(&nonce).is_none() {
+            return false;
+        }
+
+        if nonce + 1 == self.next_nonce {
+            // Nonce cuối cùng: thu hồi luôn cùng các nonce đã trả lại liền kề
+            self.next_nonce = nonce;
+            while self.next_nonce > self.confirmed_nonce
+                && self.released.remove(&(self.next_nonce - 1))
+            {
+                self.next_nonce = self.next_nonce - 1;
+            }
+        } else if nonce >= self.confirmed_nonce {
+            self.released.insert(nonce);
+        }
+        true
+    }
+
+    /// Đánh dấu một nonce đã được xác nhận trên chain
+    fn confirm(&mut self, nonce: U256) {
+        self.leases.remove(&nonce);
+        self.released.remove(&nonce);
+        let new_confirmed = nonce + 1;
+        if new_confirmed > self.confirmed_nonce {
+            self.apply_confirmed(new_confirmed);
+        }
+    }
+
+    /// Giải phóng tất cả lease/nonce thấp hơn nonce đã xác nhận. Trả về số lease đã giải phóng.
+    fn apply_confirmed(&mut self, confirmed: U256) -> usize {
+        self.confirmed_nonce = std::cmp::max(self.confirmed_nonce, confirmed);
+        let pending_leases = self.leases.split_off(&self.confirmed_nonce);
+        let freed = self.leases.len();
+        self.leases = pending_leases;
+        self.released = self.released.split_off(&self.confirmed_nonce);
+        if self.next_nonce < self.confirmed_nonce {
+            self.next_nonce = self.confirmed_nonce;
+        }
+        freed
+    }
+
+    /// Đồng bộ với số giao dịch `latest` và `pending` từ chain
+    fn reconcile(&mut self, latest: U256, pending: U256) -> usize {
+        let freed = self.apply_confirmed(latest);
+
+        // Chain đã thấy nhiều giao dịch hơn (gửi từ nơi khác): nhảy lên theo chain
+        if pending > self.next_nonce {
+            self.next_nonce = pending;
+            self.released.clear();
+        }
+
+        self.chain_pending = std::cmp::max(latest, pending);
+        self.last_sync = Instant::now();
+        freed
+    }
+
+    /// Thu hồi các lease Reserved có nonce thấp hơn `pending`: chain đã có giao dịch ở
+    /// nonce đó (gửi từ nơi khác) nên lease không thể dùng được nữa
+    fn release_stale_reserved(&mut self) -> Vec<U256> {
+        let stale: Vec<U256> = self.leases.range(..self.chain_pending)
+            .filter(|(_, lease)| lease.state == LeaseState::Reserved)
+            .map(|(nonce, _)| *nonce)
+            .collect();
+        for nonce in &stale {
+            self.leases.remove(nonce);
+        }
+        self.released = self.released.split_off(&self.chain_pending);
+        stale
+    }
+
+    /// Lease đã gửi quá `grace` mà chain vẫn không thấy (nonce >= `pending`)
+    fn is_unseen(&self, lease: &NonceLease, grace: Duration) -> bool {
+        lease.state == LeaseState::Submitted
+            && lease.nonce >= self.chain_pending
+            && lease.submitted_at.is_none_or(|at| at.elapsed() >= grace)
+    }
+
+    /// Trả lại lease đã gửi nhưng bị rơi khỏi mempool để nonce được cấp lại (lấp gap).
+    /// Chỉ nonce đúng bằng `pending` chắc chắn bị thiếu; các nonce cao hơn có thể vẫn
+    /// đang xếp hàng sau nó nên được giữ đến lần đối chiếu sau
+    fn release_dropped(&mut self, grace: Duration) -> Vec<U256> {
+        let nonce = self.chain_pending;
+        match self.leases.get(&nonce) {
+            Some(lease) if self.is_unseen(lease, grace) => {
+                self.release(nonce);
+                vec![nonce]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Các nonce trong khoảng [confirmed, lease đã gửi cao nhất] không có giao dịch nào
+    /// mà chain thấy được. Những nonce này sẽ chặn mọi giao dịch có nonce cao hơn.
+    fn gaps(&self, grace: Duration) -> Vec<U256> {
+        let highest_submitted = self.leases.iter()
+            .rev()
+            .find(|(_, lease)| lease.state == LeaseState::Submitted)
+            .map(|(nonce, _)| *nonce);
+
+        let highest_submitted = match highest_submitted {
+            Some(nonce) => nonce,
+            None => return Vec::new(),
+        };
 
-/// Quản lý nonce để tránh duplicate transaction
+        let mut gaps = Vec::new();
+        let mut nonce = self.confirmed_nonce;
+        while nonce <= highest_submitted {
+            let covered = self.leases.get(&nonce)
+                .map(|lease| lease.state == LeaseState::Submitted && !self.is_unseen(lease, grace))
+                .unwrap_or(false);
+            if !covered {
+                gaps.push(nonce);
+            }
+            nonce = nonce + 1;
+        }
+        gaps
+    }
+}
+
+/// Quản lý nonce theo cơ chế cho mượn (lease) cho một chain.
+///
+/// Mỗi giao dịch đang xử lý giữ một `NonceLease`. Khi giao dịch thất bại trước khi
+/// vào mempool, nonce được trả lại để cấp lại, tránh tạo gap. Trạng thái được đối chiếu
+/// định kỳ với số giao dịch `pending` và `latest` của chain.
 pub struct NonceManager {
-    /// Lưu trữ nonce cho mỗi địa chỉ ví
-    nonces: RwLock<HashMap<Address, Mutex<U256>>>,
+    /// Chain ID mà manager phụ trách
+    chain_id: u64,
+    /// Trạng thái nonce theo địa chỉ ví
+    states: RwLock<HashMap<Address, Arc<Mutex<AddressNonceState>>>>,
     /// Provider để lấy nonce từ blockchain khi cần
     provider: Arc<Provider<Http>>,
-    /// Thời gian cache nonce tối đa (sau thời gian này sẽ lấy lại từ chain)
+    /// Thời gian tối đa giữa hai lần đối chiếu với chain
     cache_duration: Duration,
-    /// Lưu thời điểm cập nhật nonce gần nhất
-    last_updates: RwLock<HashMap<Address, Instant>>,
-    /// Lock để đảm bảo đồng bộ giữa các thread khi cập nhật nonce
-    global_lock: Mutex<()>,
+    /// Lease ở trạng thái Reserved quá thời gian này sẽ bị thu hồi
+    lease_timeout: Duration,
+    /// Giao dịch đã gửi quá thời gian này mà chain không thấy được coi là bị rơi
+    drop_grace: Duration,
 }
 
 impl NonceManager {
-    /// Tạo NonceManager mới
-    pub fn new(provider: Arc<Provider<Http>>, cache_seconds: u64) -> Self {
+    /// Tạo NonceManager mới cho một chain
+    pub fn new(provider: Arc<Provider<Http>>, chain_id: u64, cache_seconds: u64) -> Self {
         Self {
-            nonces: RwLock::new(HashMap::new()),
+            chain_id,
+            states: RwLock::new(HashMap::new()),
             provider,
             cache_duration: Duration::from_secs(cache_seconds),
-            last_updates: RwLock::new(HashMap::new()),
-            global_lock: Mutex::new(()),
+            lease_timeout: Duration::from_secs(120),
+            drop_grace: Duration::from_secs(60),
+        }
+    }
+
+    /// Thay đổi thời gian thu hồi lease bị bỏ quên
+    pub fn with_lease_timeout(mut self, timeout: Duration) -> Self {
+        self.lease_timeout = timeout;
+        self
+    }
+
+    /// Thay đổi thời gian chờ trước khi coi giao dịch đã gửi là bị rơi khỏi mempool
+    pub fn with_drop_grace(mut self, grace: Duration) -> Self {
+        self.drop_grace = grace;
+        self
+    }
+
+    /// Lấy chain ID
+    pub fn get_chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Lấy (hoặc khởi tạo từ chain) trạng thái của một địa chỉ
+    async fn get_state(&self, address: Address) -> Result<Arc<Mutex<AddressNonceState>>> {
+        if let Some(state) = self.states.read().await.get(&address) {
+            return Ok(state.clone());
+        }
+
+        let (latest, pending) = self.get_nonces_from_chain(address).await?;
+
+        let mut states = self.states.write().await;
+        // Một thread khác có thể đã khởi tạo trong lúc chờ chain
+        let state = states.entry(address)
+            .or_insert_with(|| Arc::new(Mutex::new(AddressNonceState::new(latest, pending))))
+            .clone();
+        debug!("Khởi tạo nonce cho {} trên chain {}: latest={}, pending={}", address, self.chain_id, latest, pending);
+        Ok(state)
+    }
+
+    /// Cấp một nonce cho giao dịch sắp gửi
+    pub async fn lease_nonce(&self, address: Address) -> Result<NonceLease> {
+        let state = self.get_state(address).await?;
+
+        // Đối chiếu lại nếu dữ liệu đã cũ
+        let needs_sync = state.lock().await.last_sync.elapsed() > self.cache_duration;
+        if needs_sync {
+            self.reconcile(address).await?;
+        }
+
+        let mut guard = state.lock().await;
+        let lease = guard.lease(self.chain_id, address);
+        drop(guard);
+
+        debug!("Cấp nonce {} cho {} trên chain {}", lease.nonce, address, self.chain_id);
+        self.publish_metrics().await;
+        Ok(lease)
+    }
+
+    /// Đánh dấu lease đã gửi thành công lên mempool
+    pub async fn mark_submitted(&self, lease: &NonceLease, tx_hash: H256) -> Result<()> {
+        let state = self.get_state(lease.address).await?;
+        if !state.lock().await.mark_submitted(lease.nonce, tx_hash) {
+            return Err(anyhow!("Không tìm thấy lease cho nonce {} của {}", lease.nonce, lease.address));
+        }
+        self.publish_metrics().await;
+        Ok(())
+    }
+
+    /// Trả lại nonce khi giao dịch không được gửi đi (lỗi ký, lỗi RPC trước khi broadcast...)
+    pub async fn release_nonce(&self, lease: &NonceLease) -> Result<()> {
+        let state = self.get_state(lease.address).await?;
+        if state.lock().await.release(lease.nonce) {
+            debug!("Trả lại nonce {} của {} trên chain {}", lease.nonce, lease.address, self.chain_id);
+        }
+        self.publish_metrics().await;
+        Ok(())
+    }
+
+    /// Xác nhận nonce đã được đưa vào block
+    pub async fn confirm_nonce(&self, address: Address, nonce: U256) -> Result<()> {
+        let state = self.get_state(address).await?;
+        state.lock().await.confirm(nonce);
+        self.publish_metrics().await;
+        Ok(())
+    }
+
+    /// Đối chiếu trạng thái với số giao dịch `pending` và `latest` của chain
+    pub async fn reconcile(&self, address: Address) -> Result<NonceReconcileReport> {
+        let (latest, pending) = self.get_nonces_from_chain(address).await?;
+        let state = self.get_state(address).await?;
+        let mut guard = state.lock().await;
+
+        let confirmed_leases = guard.reconcile(latest, pending);
+        let stale_reserved = guard.release_stale_reserved();
+        if !stale_reserved.is_empty() {
+            warn!(
+                "Nonce {:?} của {} trên chain {} đã được dùng bởi giao dịch khác, thu hồi lease",
+                stale_reserved, address, self.chain_id
+            );
+        }
+        let dropped_nonces = guard.release_dropped(self.drop_grace);
+        if !dropped_nonces.is_empty() {
+            warn!(
+                "Giao dịch nonce {:?} của {} trên chain {} bị rơi khỏi mempool, trả lại nonce để cấp lại",
+                dropped_nonces, address, self.chain_id
+            );
+        }
+        let gaps = guard.gaps(self.drop_grace);
+        if !gaps.is_empty() {
+            warn!(
+                "Phát hiện {} nonce bị thiếu cho {} trên chain {}: {:?}",
+                gaps.len(), address, self.chain_id, gaps
+            );
+        }
+
+        Ok(NonceReconcileReport {
+            chain_id: self.chain_id,
+            address,
+            latest_nonce: latest,
+            pending_nonce: pending,
+            next_nonce: guard.next_nonce,
+            gaps,
+            confirmed_leases,
+            dropped_nonces,
+            stale_reserved,
+        })
+    }
+
+    /// Đối chiếu một lease đã gửi nhưng không nhận được receipt. Nếu giao dịch không được
+    /// đào và chain không còn thấy nó (`dropped`), nonce được trả lại để cấp lại
+    pub async fn settle_unconfirmed(&self, lease: &NonceLease, dropped: bool) -> Result<NonceReconcileReport> {
+        let mut report = self.reconcile(lease.address).await?;
+        if !dropped || lease.nonce < report.pending_nonce {
+            return Ok(report);
+        }
+
+        let state = self.get_state(lease.address).await?;
+        let released = state.lock().await.release(lease.nonce);
+        if released {
+            warn!("Giao dịch nonce {} của {} bị rơi, trả lại nonce", lease.nonce, lease.address);
+            report.dropped_nonces.push(lease.nonce);
+        }
+        self.publish_metrics().await;
+        Ok(report)
+    }
+
+    /// Lấy danh sách nonce bị thiếu (không đối chiếu lại với chain)
+    pub async fn detect_gaps(&self, address: Address) -> Vec<U256> {
+        match self.states.read().await.get(&address) {
+            Some(state) => state.lock().await.gaps(self.drop_grace),
+            None => Vec::new(),
         }
     }
-    
-    /// Lấy nonce tiếp theo cho địa chỉ và tự động tăng
+
+    /// Lấy nonce tiếp theo cho địa chỉ. Nonce được coi như đã gửi ngay lập tức.
+    ///
+    /// Giữ lại để tương thích với code cũ, nên dùng `lease_nonce` cho code mới.
     pub async fn get_next_nonce(&self, address: Address) -> Result<U256> {
-        // Lock global để tránh race condition giữa nhiều thread
-        let _guard = self.global_lock.lock().await;
-        
-        // Kiểm tra xem đã có nonce cho địa chỉ này chưa
-        let mut nonces = self.nonces.write().await;
-        let mut last_updates = self.last_updates.write().await;
-        
-        // Nếu chưa có nonce hoặc cache đã hết hạn, lấy từ blockchain
-        let should_refresh = match last_updates.get(&address) {
-            Some(time) => time.elapsed() > self.cache_duration,
-            None => true,
-        };
-        
-        if !nonces.contains_key(&address) || should_refresh {
-            // Lấy nonce hiện tại từ blockchain
-            let chain_nonce = self.get_nonce_from_chain(address).await?;
-            debug!("Lấy nonce từ blockchain cho địa chỉ {}: {}", address, chain_nonce);
-            
-            // Thêm nonce vào cache
-            nonces.insert(address, Mutex::new(chain_nonce));
-            last_updates.insert(address, Instant::now());
-        }
-        
-        // Lấy mutex cho nonce của địa chỉ
-        let nonce_mutex = nonces.get(&address).unwrap().clone();
-        
-        // Giải phóng lock write để các thread khác có thể đọc
-        drop(nonces);
-        drop(last_updates);
-        
-        // Lock nonce của địa chỉ này và tăng giá trị
-        let mut nonce = nonce_mutex.lock().await;
-        let current_nonce = *nonce;
-        *nonce = *nonce + 1;
-        
-        Ok(current_nonce)
-    }
-    
+        let lease = self.lease_nonce(address).await?;
+        let state = self.get_state(address).await?;
+        if let Some(entry) = state.lock().await.leases.get_mut(&lease.nonce) {
+            entry.state = LeaseState::Submitted;
+        }
+        Ok(lease.nonce)
+    }
+
     /// Cập nhật nonce cụ thể cho địa chỉ (sau khi biết giao dịch đã được xác nhận)
     pub async fn update_nonce(&self, address: Address, new_nonce: U256) -> Result<()> {
-        let _guard = self.global_lock.lock().await;
-        
-        let mut nonces = self.nonces.write().await;
-        let mut last_updates = self.last_updates.write().await;
-        
-        // Tạo mới hoặc cập nhật nonce hiện có
-        if let Some(nonce_mutex) = nonces.get(&address) {
-            let mut nonce = nonce_mutex.lock().await;
-            *nonce = cmp::max(*nonce, new_nonce);
-        } else {
-            nonces.insert(address, Mutex::new(new_nonce));
-        }
-        
-        // Cập nhật thời gian cập nhật
-        last_updates.insert(address, Instant::now());
-        
+        let state = self.get_state(address).await?;
+        state.lock().await.apply_confirmed(new_nonce);
         Ok(())
     }
-    
-    /// Reset nonce cho địa chỉ (lấy lại từ blockchain)
+
+    /// Reset nonce cho địa chỉ (lấy lại từ blockchain, bỏ toàn bộ lease)
     pub async fn reset_nonce(&self, address: Address) -> Result<U256> {
-        let _guard = self.global_lock.lock().await;
-        
-        // Lấy nonce từ blockchain
-        let chain_nonce = self.get_nonce_from_chain(address).await?;
-        
-        // Cập nhật lại trong cache
-        let mut nonces = self.nonces.write().await;
-        let mut last_updates = self.last_updates.write().await;
-        
-        if let Some(nonce_mutex) = nonces.get(&address) {
-            let mut nonce = nonce_mutex.lock().await;
-            *nonce = chain_nonce;
-        } else {
-            nonces.insert(address, Mutex::new(chain_nonce));
-        }
-        
-        last_updates.insert(address, Instant::now());
-        
-        Ok(chain_nonce)
-    }
-    
-    /// Lấy nonce từ blockchain
-    async fn get_nonce_from_chain(&self, address: Address) -> Result<U256> {
-        match self.provider.get_transaction_count(address, None).await {
-            Ok(nonce) => Ok(nonce),
+        let (latest, pending) = self.get_nonces_from_chain(address).await?;
+        let fresh = AddressNonceState::new(latest, pending);
+        let next_nonce = fresh.next_nonce;
+
+        self.states.write().await.insert(address, Arc::new(Mutex::new(fresh)));
+        info!("Reset nonce cho {} trên chain {} về {}", address, self.chain_id, next_nonce);
+        self.publish_metrics().await;
+        Ok(next_nonce)
+    }
+
+    /// Lấy số giao dịch `latest` và `pending` từ blockchain
+    async fn get_nonces_from_chain(&self, address: Address) -> Result<(U256, U256)> {
+        let latest = self.provider
+            .get_transaction_count(address, Some(BlockId::Number(BlockNumber::Latest)))
+            .await
+            .map_err(|err| {
+                error!("Lỗi khi lấy nonce latest từ blockchain: {}", err);
+                anyhow!("Không thể lấy nonce: {}", err)
+            })?;
+        // Nonce pending ưu tiên lấy từ endpoint ở gần tip trong pool để tránh node bị chậm block
+        let pending = match connection_pool::fresh_get_transaction_count(self.chain_id, address).await {
+            Ok(pending) => pending,
             Err(err) => {
-                error!("Lỗi khi lấy nonce từ blockchain: {}", err);
-                Err(anyhow!("Không thể lấy nonce: {}", err))
+                debug!("Không lấy được nonce pending từ endpoint gần tip: {}, dùng provider mặc định", err);
+                self.provider
+                    .get_transaction_count(address, Some(BlockId::Number(BlockNumber::Pending)))
+                    .await
+                    .map_err(|err| {
+                        error!("Lỗi khi lấy nonce pending từ blockchain: {}", err);
+                        anyhow!("Không thể lấy nonce: {}", err)
+                    })?
             }
-        }
+        };
+        Ok((latest, pending.max(latest)))
     }
-    
+
     /// Kiểm tra xem nonce đã tồn tại trong cache chưa
     pub async fn has_cached_nonce(&self, address: Address) -> bool {
-        let nonces = self.nonces.read().await;
-        nonces.contains_key(&address)
+        self.states.read().await.contains_key(&address)
     }
-    
-    /// Xóa cache nonce cho tất cả địa chỉ đã quá hạn
+
+    /// Thu hồi các lease Reserved quá hạn và xóa địa chỉ không còn lease nào
     pub async fn cleanup_cache(&self) {
-        let _guard = self.global_lock.lock().await;
-        
-        let mut nonces = self.nonces.write().await;
-        let mut last_updates = self.last_updates.write().await;
-        
-        // Lọc ra các địa chỉ có cache đã hết hạn
-        let expired_addresses: Vec<Address> = last_updates
-            .iter()
-            .filter(|(_, time)| time.elapsed() > self.cache_duration)
-            .map(|(addr, _)| *addr)
-            .collect();
-        
-        // Xóa các địa chỉ hết hạn khỏi cache
-        for addr in &expired_addresses {
-            nonces.remove(addr);
-            last_updates.remove(addr);
+        let mut states = self.states.write().await;
+        let mut expired_leases = 0;
+        let mut idle_addresses = Vec::new();
+
+        for (address, state) in states.iter() {
+            let mut guard = state.lock().await;
+            let stale: Vec<U256> = guard.leases.values()
+                .filter(|lease| lease.state == LeaseState::Reserved
+                    && lease.acquired_at.elapsed() > self.lease_timeout)
+                .map(|lease| lease.nonce)
+                .collect();
+            for nonce in stale {
+                guard.release(nonce);
+                expired_leases += 1;
+            }
+
+            if guard.leases.is_empty() && guard.last_sync.elapsed() > self.cache_duration {
+                idle_addresses.push(*address);
+            }
+        }
+
+        for address in &idle_addresses {
+            states.remove(address);
+        }
+        drop(states);
+
+        if expired_leases > 0 || !idle_addresses.is_empty() {
+            debug!(
+                "Chain {}: thu hồi {} lease quá hạn, xóa {} địa chỉ không hoạt động",
+                self.chain_id, expired_leases, idle_addresses.len()
+            );
+        }
+        self.publish_metrics().await;
+    }
+
+    /// Lấy thống kê lease hiện tại
+    pub async fn get_metrics(&self) -> NonceMetrics {
+        let states = self.states.read().await;
+        let mut metrics = NonceMetrics {
+            chain_id: self.chain_id,
+            tracked_addresses: states.len(),
+            ..Default::default()
+        };
+
+        for state in states.values() {
+            let guard = state.lock().await;
+            for lease in guard.leases.values() {
+                metrics.outstanding_leases += 1;
+                match lease.state {
+                    LeaseState::Reserved => metrics.reserved_leases += 1,
+                    LeaseState::Submitted => metrics.submitted_leases += 1,
+                }
+                metrics.oldest_lease_secs = metrics.oldest_lease_secs.max(lease.acquired_at.elapsed().as_secs());
+            }
+            metrics.released_nonces += guard.released.len();
+        }
+
+        metrics
+    }
+
+    /// Đẩy thống kê lease ra metrics exporter
+    async fn publish_metrics(&self) {
+        let metrics = self.get_metrics().await;
+        let chain = self.chain_id.to_string();
+        gauge!("nonce_outstanding_leases", metrics.outstanding_leases as f64, "chain_id" => chain.clone());
+        gauge!("nonce_reserved_leases", metrics.reserved_leases as f64, "chain_id" => chain.clone());
+        gauge!("nonce_submitted_leases", metrics.submitted_leases as f64, "chain_id" => chain.clone());
+        gauge!("nonce_released_nonces", metrics.released_nonces as f64, "chain_id" => chain);
+    }
+}
+
+/// Chu kỳ thu hồi lease quá hạn và dọn địa chỉ không hoạt động
+const NONCE_CLEANUP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Các NonceManager theo chain ID. Một ví giao dịch trên nhiều chain sẽ có trạng thái nonce riêng cho từng chain.
+static NONCE_MANAGERS: Lazy<RwLock<HashMap<u64, Arc<NonceManager>>>> = Lazy::new(|| {
+    RwLock::new(HashMap::new())
+});
+
+/// Lấy hoặc tạo NonceManager cho chain ID
+pub async fn get_or_create_nonce_manager(
+    chain_id: u64,
+    provider: Arc<Provider<Http>>,
+    cache_seconds: u64,
+) -> Arc<NonceManager> {
+    if let Some(manager) = NONCE_MANAGERS.read().await.get(&chain_id) {
+        return manager.clone();
+    }
+
+    let mut managers = NONCE_MANAGERS.write().await;
+    // Một task khác có thể đã tạo manager trong lúc chờ lock
+    if let Some(manager) = managers.get(&chain_id) {
+        return manager.clone();
+    }
+    let manager = Arc::new(NonceManager::new(provider, chain_id, cache_seconds));
+    managers.insert(chain_id, manager.clone());
+    drop(managers);
+
+    spawn_cleanup_task(Arc::downgrade(&manager), NONCE_CLEANUP_INTERVAL);
+    manager
+}
+
+/// Chạy `cleanup_cache` định kỳ cho tới khi manager bị drop
+fn spawn_cleanup_task(manager: Weak<NonceManager>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // Tick đầu tiên trả về ngay
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match manager.upgrade() {
+                Some(manager) => manager.cleanup_cache().await,
+                None => break,
+            }
         }
-        
-        if !expired_addresses.is_empty() {
-            debug!("Đã xóa {} cache nonce hết hạn", expired_addresses.len());
+    });
+}
+
+/// Lấy NonceManager hiện có cho chain ID
+pub async fn get_nonce_manager(chain_id: u64) -> Option<Arc<NonceManager>> {
+    NONCE_MANAGERS.read().await.get(&chain_id).cloned()
+}
+
+/// Lấy thống kê nonce của tất cả chain
+pub async fn get_all_nonce_metrics() -> Vec<NonceMetrics> {
+    let managers: Vec<Arc<NonceManager>> = NONCE_MANAGERS.read().await.values().cloned().collect();
+    let mut result = Vec::with_capacity(managers.len());
+    for manager in managers {
+        result.push(manager.get_metrics().await);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr() -> Address {
+        Address::from_low_u64_be(1)
+    }
+
+    #[test]
+    fn test_lease_is_sequential_and_unique() {
+        let mut state = AddressNonceState::new(U256::from(5), U256::from(5));
+        let a = state.lease(1, addr());
+        let b = state.lease(1, addr());
+        let c = state.lease(1, addr());
+        assert_eq!(a.nonce, U256::from(5));
+        assert_eq!(b.nonce, U256::from(6));
+        assert_eq!(c.nonce, U256::from(7));
+        assert_eq!(state.leases.len(), 3);
+    }
+
+    #[test]
+    fn test_released_nonce_is_reused() {
+        let mut state = AddressNonceState::new(U256::from(0), U256::from(0));
+        let a = state.lease(1, addr());
+        let b = state.lease(1, addr());
+        let _c = state.lease(1, addr());
+
+        // Giao dịch giữa thất bại trước khi gửi: nonce được cấp lại
+        assert!(state.release(b.nonce));
+        let d = state.lease(1, addr());
+        assert_eq!(d.nonce, b.nonce);
+
+        // Trả lại nonce cuối cùng: next_nonce lùi lại thay vì giữ trong released
+        state.release(a.nonce);
+        let e = state.lease(1, addr());
+        assert_eq!(e.nonce, a.nonce);
+        assert!(state.released.is_empty());
+    }
+
+    #[test]
+    fn test_release_tail_rewinds_next_nonce() {
+        let mut state = AddressNonceState::new(U256::from(10), U256::from(10));
+        let a = state.lease(1, addr());
+        let b = state.lease(1, addr());
+        state.release(a.nonce);
+        state.release(b.nonce);
+        assert_eq!(state.next_nonce, U256::from(10));
+        assert!(state.released.is_empty());
+    }
+
+    #[test]
+    fn test_gap_detection() {
+        let mut state = AddressNonceState::new(U256::from(0), U256::from(0));
+        let a = state.lease(1, addr());
+        let _b = state.lease(1, addr());
+        let c = state.lease(1, addr());
+        state.mark_submitted(a.nonce, H256::zero());
+        state.mark_submitted(c.nonce, H256::zero());
+
+        // Nonce 1 vẫn Reserved nên chặn nonce 2
+        assert_eq!(state.gaps(Duration::from_secs(60)), vec![U256::from(1)]);
+    }
+
+    #[test]
+    fn test_dropped_submitted_lease_is_gap_and_refilled() {
+        let mut state = AddressNonceState::new(U256::from(0), U256::from(0));
+        let a = state.lease(1, addr());
+        let b = state.lease(1, addr());
+        let c = state.lease(1, addr());
+        for lease in [&a, &b, &c] {
+            state.mark_submitted(lease.nonce, H256::zero());
         }
+
+        // Nonce 0 đã đào, chain không thấy nonce 1 (bị rơi) nên nonce 2 xếp hàng sau nó
+        state.reconcile(U256::from(1), U256::from(1));
+        assert_eq!(state.gaps(Duration::ZERO), vec![U256::from(1), U256::from(2)]);
+        // Trong thời gian chờ, giao dịch vừa gửi chưa bị coi là rơi
+        assert!(state.gaps(Duration::from_secs(60)).is_empty());
+
+        // Chỉ nonce 1 được trả lại; nonce 2 vẫn giữ vì đang chờ sau nonce 1
+        assert_eq!(state.release_dropped(Duration::ZERO), vec![U256::from(1)]);
+        assert!(state.leases.contains_key(&U256::from(2)));
+        assert_eq!(state.lease(1, addr()).nonce, U256::from(1));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_reconcile_frees_confirmed_and_follows_chain() {
+        let mut state = AddressNonceState::new(U256::from(0), U256::from(0));
+        let a = state.lease(1, addr());
+        let b = state.lease(1, addr());
+        state.mark_submitted(a.nonce, H256::zero());
+        state.mark_submitted(b.nonce, H256::zero());
+
+        let freed = state.reconcile(U256::from(1), U256::from(2));
+        assert_eq!(freed, 1);
+        assert_eq!(state.leases.len(), 1);
+
+        // Ví gửi giao dịch từ nơi khác: next_nonce nhảy theo pending
+        state.reconcile(U256::from(2), U256::from(5));
+        assert_eq!(state.next_nonce, U256::from(5));
+        assert!(state.leases.is_empty());
+    }
+
+    #[test]
+    fn test_reconcile_releases_reserved_below_pending() {
+        let mut state = AddressNonceState::new(U256::from(0), U256::from(0));
+        let a = state.lease(1, addr());
+        let b = state.lease(1, addr());
+        let c = state.lease(1, addr());
+        state.mark_submitted(b.nonce, H256::zero());
+
+        // Ví gửi 2 giao dịch từ nơi khác: nonce 0 và 1 đã có trong mempool
+        state.reconcile(U256::from(0), U256::from(2));
+        assert_eq!(state.release_stale_reserved(), vec![a.nonce]);
+        assert!(!state.leases.contains_key(&a.nonce));
+        // Lease đã gửi và lease Reserved từ pending trở lên được giữ nguyên
+        assert!(state.leases.contains_key(&b.nonce));
+        assert!(state.leases.contains_key(&c.nonce));
+        // Nonce đã thu hồi không được cấp lại
+        assert_eq!(state.lease(1, addr()).nonce, U256::from(3));
+    }
+
+    #[tokio::test]
+    async fn test_periodic_cleanup_reclaims_stale_leases() {
+        let provider = Arc::new(Provider::<Http>::try_from("http://localhost:8545").unwrap());
+        let manager = Arc::new(
+            NonceManager::new(provider, 1, 3600).with_lease_timeout(Duration::from_millis(10))
+        );
+        let mut state = AddressNonceState::new(U256::from(0), U256::from(0));
+        state.lease(1, addr());
+        manager.states.write().await.insert(addr(), Arc::new(Mutex::new(state)));
+        assert_eq!(manager.get_metrics().await.reserved_leases, 1);
+
+        spawn_cleanup_task(Arc::downgrade(&manager), Duration::from_millis(20));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let metrics = manager.get_metrics().await;
+        assert_eq!(metrics.reserved_leases, 0);
+        assert_eq!(metrics.released_nonces, 0);
+    }
+}
@@ -0,0 +1,605 @@
+// External imports
+use ethers::{
+    abi::{self, ParamType},
+    types::{Address, Filter, TransactionRequest, H256, U256},
+    utils::keccak256,
+};
+
+// Standard library imports
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Third party imports
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+// Internal imports
+use crate::chain_adapters::approval_manager::encode_call;
+use crate::chain_adapters::chain_registry::{self, ChainConfig};
+use crate::chain_adapters::interfaces::ChainAdapter;
+use crate::chain_adapters::price::{to_units, DexPriceConfig, DexPriceOracle};
+use crate::chain_adapters::treasury::dec_u256;
+use diamond_wallet::WalletManager;
+
+/// Số giây trong một ngày (cửa sổ tính thay đổi 24h)
+const DAY_SECS: u64 = 24 * 60 * 60;
+/// Số địa chỉ ví tối đa trong một filter topic của eth_getLogs
+const MAX_TOPIC_ADDRESSES: usize = 100;
+
+/// Topic của event ERC-20 Transfer(address,address,uint256)
+pub fn transfer_topic() -> H256 {
+    H256::from(keccak256("Transfer(address,address,uint256)"))
+}
+
+/// Cấu hình indexer số dư
+#[derive(Debug, Clone)]
+pub struct PortfolioConfig {
+    /// File lưu trạng thái quét và lịch sử snapshot (None: chỉ giữ trong bộ nhớ)
+    pub store_path: Option<PathBuf>,
+    /// Số block tối đa cho một lần eth_getLogs
+    pub log_block_range: u64,
+    /// Số block quét ngược khi gặp ví hoặc chain lần đầu
+    pub initial_lookback_blocks: u64,
+    /// Thời gian giữ snapshot lịch sử
+    pub history_retention: Duration,
+    /// Chu kỳ cập nhật định kỳ
+    pub refresh_interval: Duration,
+}
+
+impl Default for PortfolioConfig {
+    fn default() -> Self {
+        Self {
+            store_path: None,
+            log_block_range: 2_000,
+            initial_lookback_blocks: 100_000,
+            history_retention: Duration::from_secs(7 * DAY_SECS),
+            refresh_interval: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// Metadata của token ERC-20
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenMeta {
+    pub symbol: Option<String>,
+    pub decimals: u8,
+}
+
+/// Số dư một token của ví
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenHolding {
+    pub token: Address,
+    pub symbol: Option<String>,
+    pub decimals: u8,
+    #[serde(with = "dec_u256")]
+    pub balance: U256,
+    /// Số dư theo đơn vị token
+    pub amount: f64,
+    pub price_usd: Option<f64>,
+    pub value_usd: f64,
+}
+
+/// Số dư native và token của một ví trên một chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletHoldings {
+    pub address: Address,
+    #[serde(with = "dec_u256")]
+    pub native_balance: U256,
+    pub native_amount: f64,
+    pub native_value_usd: f64,
+    /// Token có số dư, giá trị giảm dần
+    pub tokens: Vec<TokenHolding>,
+    pub total_usd: f64,
+}
+
+/// Thay đổi giá trị so với snapshot trước đó
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ValueChange {
+    pub usd: f64,
+    /// Phần trăm thay đổi (None nếu giá trị trước đó bằng 0)
+    pub percent: Option<f64>,
+}
+
+impl ValueChange {
+    /// Thay đổi so với snapshot mới nhất cũ hơn `window` giây; `chain_id` None là tổng danh mục
+    pub fn since(history: &[PortfolioSnapshot], now: u64, window: u64, current: f64, chain_id: Option<u64>) -> Option<Self> {
+        let cutoff = now.checked_sub(window)?;
+        let snapshot = history.iter().rev().find(|snapshot| snapshot.timestamp <= cutoff)?;
+        let previous = match chain_id {
+            Some(chain_id) => *snapshot.chains.get(&chain_id)?,
+            None => snapshot.total_usd,
+        };
+        Some(Self {
+            usd: current - previous,
+            percent: (previous > 0.0).then(|| (current - previous) / previous * 100.0),
+        })
+    }
+}
+
+/// Danh mục trên một chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainPortfolio {
+    pub chain_id: u64,
+    pub name: String,
+    pub native_symbol: String,
+    pub native_price_usd: Option<f64>,
+    pub total_usd: f64,
+    pub change_24h: Option<ValueChange>,
+    pub wallets: Vec<WalletHoldings>,
+    /// Lỗi khi cập nhật chain (số liệu của chain bị bỏ trống)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Snapshot giá trị danh mục theo thời gian
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioSnapshot {
+    pub timestamp: u64,
+    pub total_usd: f64,
+    /// Giá trị theo chain
+    pub chains: BTreeMap<u64, f64>,
+}
+
+/// Danh mục của mọi ví trên mọi chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub timestamp: u64,
+    pub total_usd: f64,
+    pub change_24h: Option<ValueChange>,
+    pub chains: Vec<ChainPortfolio>,
+}
+
+/// Trạng thái quét Transfer log của một chain
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ChainIndexState {
+    /// Block cuối đã quét
+    last_block: Option<u64>,
+    /// Ví đã được quét lịch sử
+    scanned_wallets: BTreeSet<Address>,
+    /// Token từng được chuyển đến từng ví
+    held_tokens: BTreeMap<Address, BTreeSet<Address>>,
+    /// Metadata token đã đọc
+    tokens: BTreeMap<Address, TokenMeta>,
+}
+
+/// Trạng thái được lưu ra file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct IndexerState {
+    chains: BTreeMap<u64, ChainIndexState>,
+    history: Vec<PortfolioSnapshot>,
+}
+
+/// Một chain được index: adapter, oracle giá và token phổ biến luôn được kiểm tra
+pub struct PortfolioChain<A: ChainAdapter + ?Sized> {
+    pub chain_id: u64,
+    pub name: String,
+    pub native_symbol: String,
+    pub native_decimals: u8,
+    /// Token trong `common_tokens` của chain
+    pub known_tokens: BTreeMap<Address, TokenMeta>,
+    adapter: Arc<A>,
+    oracle: DexPriceOracle<A>,
+}
+
+impl<A: ChainAdapter + ?Sized> PortfolioChain<A> {
+    pub fn new(chain: &ChainConfig, adapter: Arc<A>) -> Self {
+        Self {
+            chain_id: chain.chain_id,
+            name: chain.name.clone(),
+            native_symbol: chain.native_token_symbol.clone(),
+            native_decimals: chain.native_token_decimals,
+            known_tokens: chain.common_tokens.values()
+                .map(|token| (token.address, TokenMeta { symbol: Some(token.symbol.clone()), decimals: token.decimals }))
+                .collect(),
+            oracle: DexPriceOracle::new(adapter.clone(), DexPriceConfig::from_chain_config(chain)),
+            adapter,
+        }
+    }
+}
+
+/// Indexer số dư native và ERC-20 của mọi ví trên mọi chain: phát hiện token qua Transfer log,
+/// định giá qua oracle DEX và lưu snapshot lịch sử
+pub struct PortfolioIndexer<A: ChainAdapter + ?Sized> {
+    chains: Vec<PortfolioChain<A>>,
+    wallets: Arc<WalletManager>,
+    config: PortfolioConfig,
+    state: RwLock<IndexerState>,
+    latest: RwLock<Option<Portfolio>>,
+}
+
+impl PortfolioIndexer<dyn ChainAdapter> {
+    /// Tạo indexer cho mọi chain EVM đã đăng ký adapter trong registry
+    pub fn from_registry(wallets: Arc<WalletManager>, config: PortfolioConfig) -> Result<Self> {
+        let mut chains = Vec::new();
+        for chain in chain_registry::get_all_chain_configs().into_iter().filter(|chain| chain.is_evm()) {
+            match chain_registry::get_registry().get_adapter(chain.chain_id) {
+                Ok(adapter) => chains.push(PortfolioChain::new(&chain, adapter)),
+                Err(e) => warn!("Portfolio indexer skips chain {}: {}", chain.chain_id, e),
+            }
+        }
+        Self::new(chains, wallets, config)
+    }
+}
+
+impl<A: ChainAdapter + ?Sized + 'static> PortfolioIndexer<A> {
+    /// Tạo indexer và nạp trạng thái đã lưu
+    pub fn new(chains: Vec<PortfolioChain<A>>, wallets: Arc<WalletManager>, config: PortfolioConfig) -> Result<Self> {
+        let state = match config.store_path.as_ref().filter(|path| path.exists()) {
+            Some(path) => {
+                let content = std::fs::read_to_string(path)
+                    .with_context(|| format!("Cannot read portfolio index {:?}", path))?;
+                serde_json::from_str(&content).with_context(|| format!("Invalid portfolio index {:?}", path))?
+            }
+            None => IndexerState::default(),
+        };
+
+        Ok(Self {
+            chains,
+            wallets,
+            config,
+            state: RwLock::new(state),
+            latest: RwLock::new(None),
+        })
+    }
+
+    /// Danh mục của lần cập nhật gần nhất
+    pub async fn latest(&self) -> Option<Portfolio> {
+        self.latest.read().await.clone()
+    }
+
+    /// Snapshot lịch sử từ thời điểm `since` (unix giây)
+    pub async fn history(&self, since: u64) -> Vec<PortfolioSnapshot> {
+        self.state.read().await.history.iter()
+            .filter(|snapshot| snapshot.timestamp >= since)
+            .cloned()
+            .collect()
+    }
+
+    /// Quét token mới, đọc số dư, định giá và ghi snapshot
+    pub async fn refresh(&self) -> Result<Portfolio> {
+        let wallets = self.wallet_addresses()?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+        let mut state = self.state.write().await;
+        let mut chains = Vec::with_capacity(self.chains.len());
+        for chain in &self.chains {
+            let index = state.chains.entry(chain.chain_id).or_default();
+            chains.push(match self.index_chain(chain, index, &wallets).await {
+                Ok(portfolio) => portfolio,
+                Err(e) => {
+                    warn!("Portfolio refresh failed on chain {}: {}", chain.chain_id, e);
+                    ChainPortfolio {
+                        chain_id: chain.chain_id,
+                        name: chain.name.clone(),
+                        native_symbol: chain.native_symbol.clone(),
+                        native_price_usd: None,
+                        total_usd: 0.0,
+                        change_24h: None,
+                        wallets: Vec::new(),
+                        error: Some(e.to_string()),
+                    }
+                }
+            });
+        }
+
+        for chain in chains.iter_mut().filter(|chain| chain.error.is_none()) {
+            chain.change_24h = ValueChange::since(&state.history, now, DAY_SECS, chain.total_usd, Some(chain.chain_id));
+        }
+        let total_usd = chains.iter().map(|chain| chain.total_usd).sum();
+        let complete = chains.iter().all(|chain| chain.error.is_none());
+        let portfolio = Portfolio {
+            timestamp: now,
+            total_usd,
+            change_24h: complete.then(|| ValueChange::since(&state.history, now, DAY_SECS, total_usd, None)).flatten(),
+            chains,
+        };
+
+        // Chain lỗi làm tổng bị thiếu nên không ghi snapshot để lịch sử không bị tụt giả
+        if complete {
+            state.history.push(PortfolioSnapshot {
+                timestamp: now,
+                total_usd,
+                chains: portfolio.chains.iter().map(|chain| (chain.chain_id, chain.total_usd)).collect(),
+            });
+        }
+        let cutoff = now.saturating_sub(self.config.history_retention.as_secs());
+        state.history.retain(|snapshot| snapshot.timestamp >= cutoff);
+        self.save(&state)?;
+        drop(state);
+
+        info!("Portfolio value ${:.2} across {} chains", total_usd, portfolio.chains.len());
+        *self.latest.write().await = Some(portfolio.clone());
+        Ok(portfolio)
+    }
+
+    /// Cập nhật danh mục định kỳ
+    pub fn spawn_periodic_refresh(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.refresh_interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.refresh().await {
+                    warn!("Portfolio refresh failed: {}", e);
+                }
+            }
+        })
+    }
+
+    fn wallet_addresses(&self) -> Result<Vec<Address>> {
+        let mut addresses: Vec<Address> = self.wallets.list_wallets()?
+            .iter()
+            .filter_map(|wallet| wallet.address.parse().ok())
+            .collect();
+        addresses.sort();
+        addresses.dedup();
+        Ok(addresses)
+    }
+
+    async fn index_chain(&self, chain: &PortfolioChain<A>, index: &mut ChainIndexState, wallets: &[Address]) -> Result<ChainPortfolio> {
+        self.discover_tokens(chain, index, wallets).await?;
+
+        let native_price_usd = chain.oracle.native_price_usd().await.unwrap_or_else(|e| {
+            warn!("Cannot price native token on chain {}: {}", chain.chain_id, e);
+            None
+        });
+        let mut prices: HashMap<Address, Option<f64>> = HashMap::new();
+        let mut holdings = Vec::with_capacity(wallets.len());
+
+        for wallet in wallets {
+            let native_balance = chain.adapter.get_eth_balance(*wallet, None).await?;
+            let native_amount = to_units(native_balance, chain.native_decimals);
+            let native_value_usd = native_price_usd.map(|price| price * native_amount).unwrap_or(0.0);
+
+            let candidates: BTreeSet<Address> = chain.known_tokens.keys()
+                .chain(index.held_tokens.get(wallet).into_iter().flatten())
+                .copied()
+                .collect();
+            let mut tokens = Vec::new();
+            for token in candidates {
+                let balance = match chain.adapter.get_token_balance(token, *wallet, None).await {
+                    Ok(balance) if !balance.is_zero() => balance,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        debug!("Cannot read balance of {:?} for {:?}: {}", token, wallet, e);
+                        continue;
+                    }
+                };
+                let Some(meta) = self.token_meta(chain, index, token).await else {
+                    continue;
+                };
+                let price_usd = match prices.get(&token) {
+                    Some(price) => *price,
+                    None => {
+                        let price = chain.oracle.token_price_usd(token, meta.decimals, native_price_usd).await.unwrap_or_else(|e| {
+                            debug!("Cannot price token {:?} on chain {}: {}", token, chain.chain_id, e);
+                            None
+                        });
+                        prices.insert(token, price);
+                        price
+                    }
+                };
+                let amount = to_units(balance, meta.decimals);
+                tokens.push(TokenHolding {
+                    token,
+                    symbol: meta.symbol,
+                    decimals: meta.decimals,
+                    balance,
+                    amount,
+                    price_usd,
+                    value_usd: price_usd.map(|price| price * amount).unwrap_or(0.0),
+                });
+            }
+            tokens.sort_by(|a, b| b.value_usd.total_cmp(&a.value_usd));
+
+            let total_usd = native_value_usd + tokens.iter().map(|token| token.value_usd).sum::<f64>();
+            holdings.push(WalletHoldings {
+                address: *wallet,
+                native_balance,
+                native_amount,
+                native_value_usd,
+                tokens,
+                total_usd,
+            });
+        }
+
+        Ok(ChainPortfolio {
+            chain_id: chain.chain_id,
+            name: chain.name.clone(),
+            native_symbol: chain.native_symbol.clone(),
+            native_price_usd,
+            total_usd: holdings.iter().map(|wallet| wallet.total_usd).sum(),
+            change_24h: None,
+            wallets: holdings,
+            error: None,
+        })
+    }
+
+    /// Ghi nhận token đã được chuyển đến ví: quét block mới cho mọi ví và quét ngược cho ví mới
+    async fn discover_tokens(&self, chain: &PortfolioChain<A>, index: &mut ChainIndexState, wallets: &[Address]) -> Result<()> {
+        let latest = chain.adapter.get_block_number().await?;
+        let lookback_start = latest.saturating_sub(self.config.initial_lookback_blocks);
+
+        match index.last_block {
+            Some(last_block) => {
+                let new_wallets: Vec<Address> = wallets.iter()
+                    .filter(|wallet| !index.scanned_wallets.contains(wallet))
+                    .copied()
+                    .collect();
+                if !new_wallets.is_empty() && lookback_start <= last_block {
+                    self.scan_transfers(chain, index, &new_wallets, lookback_start, last_block).await?;
+                }
+                if last_block < latest {
+                    self.scan_transfers(chain, index, wallets, last_block + 1, latest).await?;
+                }
+            }
+            None => self.scan_transfers(chain, index, wallets, lookback_start, latest).await?,
+        }
+
+        index.last_block = Some(latest);
+        index.scanned_wallets.extend(wallets.iter().copied());
+        Ok(())
+    }
+
+    async fn scan_transfers(
+        &self,
+        chain: &PortfolioChain<A>,
+        index: &mut ChainIndexState,
+        wallets: &[Address],
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<()> {
+        let range = self.config.log_block_range.max(1);
+        for recipients in wallets.chunks(MAX_TOPIC_ADDRESSES) {
+            let recipients: Vec<H256> = recipients.iter().map(|wallet| H256::from(*wallet)).collect();
+            let mut from = from_block;
+            while from <= to_block {
+                let to = from.saturating_add(range - 1).min(to_block);
+                let filter = Filter::new()
+                    .from_block(from)
+                    .to_block(to)
+                    .topic0(transfer_topic())
+                    .topic2(recipients.clone());
+                let logs = chain.adapter.get_logs(&filter).await
+                    .map_err(|e| anyhow!("eth_getLogs {}-{} failed: {}", from, to, e))?;
+                // ERC-721 cũng phát Transfer nhưng có 4 topic (tokenId indexed)
+                for log in logs.iter().filter(|log| log.topics.len() == 3) {
+                    index.held_tokens.entry(Address::from(log.topics[2])).or_default().insert(log.address);
+                }
+                from = to + 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Metadata token: từ cấu hình chain, bộ nhớ đệm hoặc đọc on-chain (None nếu không phải ERC-20)
+    async fn token_meta(&self, chain: &PortfolioChain<A>, index: &mut ChainIndexState, token: Address) -> Option<TokenMeta> {
+        if let Some(meta) = chain.known_tokens.get(&token).or_else(|| index.tokens.get(&token)) {
+            return Some(meta.clone());
+        }
+
+        let decimals = chain.adapter.call(&TransactionRequest::new().to(token).data(encode_call("decimals()", &[])), None).await.ok()?;
+        let decimals = u8::try_from(U256::from_big_endian(decimals.get(..32)?)).ok()?;
+        let symbol = chain.adapter.call(&TransactionRequest::new().to(token).data(encode_call("symbol()", &[])), None).await
+            .ok()
+            .and_then(|output| abi::decode(&[ParamType::String], &output).ok())
+            .and_then(|tokens| tokens.into_iter().next())
+            .and_then(|token| token.into_string());
+
+        let meta = TokenMeta { symbol, decimals };
+        index.tokens.insert(token, meta.clone());
+        Some(meta)
+    }
+
+    fn save(&self, state: &IndexerState) -> Result<()> {
+        let Some(path) = &self.config.store_path else {
+            return Ok(());
+        };
+
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(state)?)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_adapters::tests::mock_adapter::MockChainAdapter;
+    use crate::chain_adapters::tests::mock_chain::MockChain;
+    use crate::chain_adapters::tests::mock_contracts::TokenSpec;
+    use crate::chain_adapters::tests::wallet_fixture::TestWallets;
+
+    fn snapshot(timestamp: u64, total_usd: f64, chains: &[(u64, f64)]) -> PortfolioSnapshot {
+        PortfolioSnapshot { timestamp, total_usd, chains: chains.iter().copied().collect() }
+    }
+
+    #[test]
+    fn test_change_uses_latest_snapshot_older_than_window() {
+        let now = 10 * DAY_SECS;
+        let history = vec![
+            snapshot(now - 2 * DAY_SECS, 50.0, &[(1, 50.0)]),
+            snapshot(now - DAY_SECS - 60, 100.0, &[(1, 80.0), (56, 20.0)]),
+            snapshot(now - 3600, 140.0, &[(1, 100.0), (56, 40.0)]),
+        ];
+
+        let total = ValueChange::since(&history, now, DAY_SECS, 150.0, None).unwrap();
+        assert_eq!(total.usd, 50.0);
+        assert_eq!(total.percent, Some(50.0));
+        assert_eq!(ValueChange::since(&history, now, DAY_SECS, 10.0, Some(56)).unwrap().usd, -10.0);
+        // Chain chưa có trong snapshot cũ và lịch sử chưa đủ 24h thì không có thay đổi
+        assert!(ValueChange::since(&history, now, DAY_SECS, 10.0, Some(137)).is_none());
+        assert!(ValueChange::since(&history[2..], now, DAY_SECS, 150.0, None).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_discovers_and_values_tokens() {
+        let chain = MockChain::spawn(31337).await.unwrap();
+        let dex = chain.deploy_uniswap_v2().unwrap();
+        let usdc = chain.deploy_erc20(&TokenSpec::new("USD Coin", "USDC", 6)).unwrap();
+        let meme = chain.deploy_erc20(&TokenSpec::new("Meme", "MEME", 18)).unwrap();
+        // 2 ETH : 6000 USDC -> ETH = 3000 USD; 2 ETH : 1000 MEME -> MEME = 6 USD
+        chain.add_liquidity_eth(&dex, usdc, U256::exp10(6) * 6000, U256::exp10(18) * 2).unwrap();
+        chain.add_liquidity_eth(&dex, meme, U256::exp10(18) * 1000, U256::exp10(18) * 2).unwrap();
+
+        let test_wallets = TestWallets::new(chain.chain_id());
+        let wallets = test_wallets.manager.clone();
+        let holder = chain.dev_address(3);
+        wallets.import_watch_only(holder, Some("holder".to_string())).unwrap();
+        chain.mint(meme, holder, U256::exp10(18) * 10).unwrap();
+        chain.mint(usdc, holder, U256::exp10(6) * 25).unwrap();
+
+        let config: ChainConfig = serde_json::from_value(serde_json::json!({
+            "chain_id": chain.chain_id(),
+            "name": "Mock",
+            "native_token_name": "Ether",
+            "native_token_symbol": "ETH",
+            "native_token_decimals": 18,
+            "avg_block_time": 1.0,
+            "chain_type": "EVM",
+            "primary_rpc_urls": [chain.http_url()],
+            "wrapped_native_token": dex.weth,
+            "factory_contracts": {"uniswap_v2": dex.factory},
+            "common_tokens": {
+                "USDC": {"name": "USD Coin", "symbol": "USDC", "address": usdc, "decimals": 6, "logo_url": null}
+            }
+        })).unwrap();
+        let adapter = Arc::new(MockChainAdapter::new(&chain));
+        let store_path = test_wallets.dir.path().join("portfolio.json");
+        let indexer = PortfolioIndexer::new(
+            vec![PortfolioChain::new(&config, adapter.clone())],
+            wallets.clone(),
+            PortfolioConfig { store_path: Some(store_path.clone()), log_block_range: 3, ..Default::default() },
+        ).unwrap();
+
+        let portfolio = indexer.refresh().await.unwrap();
+        let holdings = &portfolio.chains[0].wallets[0];
+        assert_eq!(holdings.address, holder);
+        assert!((portfolio.chains[0].native_price_usd.unwrap() - 3000.0).abs() < 1e-6);
+
+        // MEME được phát hiện qua Transfer log, USDC có sẵn trong common_tokens và định giá 1 USD
+        let meme_holding = holdings.tokens.iter().find(|token| token.token == meme).unwrap();
+        assert_eq!(meme_holding.symbol.as_deref(), Some("MEME"));
+        assert!((meme_holding.value_usd - 60.0).abs() < 1e-6);
+        let usdc_holding = holdings.tokens.iter().find(|token| token.token == usdc).unwrap();
+        assert_eq!(usdc_holding.price_usd, Some(1.0));
+        let expected = holdings.native_amount * 3000.0 + 60.0 + 25.0;
+        assert!((portfolio.total_usd - expected).abs() < 1e-6);
+        assert_eq!(indexer.history(0).await.len(), 1);
+
+        // Trạng thái quét được nạp lại: token đã thấy vẫn được theo dõi dù không quét lại log
+        let reopened = PortfolioIndexer::new(
+            vec![PortfolioChain::new(&config, adapter)],
+            wallets,
+            PortfolioConfig { store_path: Some(store_path), initial_lookback_blocks: 0, ..Default::default() },
+        ).unwrap();
+        let portfolio = reopened.refresh().await.unwrap();
+        assert!(portfolio.chains[0].wallets[0].tokens.iter().any(|token| token.token == meme));
+        assert_eq!(reopened.history(0).await.len(), 2);
+    }
+}
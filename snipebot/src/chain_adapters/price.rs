@@ -0,0 +1,234 @@
+// External imports
+use ethers::{
+    abi::Token,
+    types::{Address, TransactionRequest, U256},
+};
+
+// Standard library imports
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Third party imports
+use anyhow::Result;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+// Internal imports
+use crate::chain_adapters::approval_manager::encode_call;
+use crate::chain_adapters::chain_registry::ChainConfig;
+use crate::chain_adapters::interfaces::ChainAdapter;
+
+/// Symbol của stablecoin được định giá 1 USD
+pub const STABLECOIN_SYMBOLS: &[&str] = &["USDC", "USDT", "DAI", "BUSD", "USDC.e", "USDbC"];
+
+/// Thanh khoản native tối thiểu (mỗi phía của pair) để giá được dùng
+const DEFAULT_MIN_NATIVE_LIQUIDITY: f64 = 0.1;
+
+/// Nguồn định giá của một chain: factory Uniswap V2, wrapped native và các stablecoin
+#[derive(Debug, Clone)]
+pub struct DexPriceConfig {
+    pub factory: Option<Address>,
+    pub wrapped_native: Option<Address>,
+    pub native_decimals: u8,
+    /// (địa chỉ, decimals) của stablecoin dùng làm mốc USD
+    pub stablecoins: Vec<(Address, u8)>,
+    /// Pair có ít native hơn ngưỡng này bị bỏ qua (tránh giá từ pair rác)
+    pub min_native_liquidity: f64,
+}
+
+impl Default for DexPriceConfig {
+    fn default() -> Self {
+        Self {
+            factory: None,
+            wrapped_native: None,
+            native_decimals: 18,
+            stablecoins: Vec::new(),
+            min_native_liquidity: DEFAULT_MIN_NATIVE_LIQUIDITY,
+        }
+    }
+}
+
+impl DexPriceConfig {
+    /// Lấy factory (theo tên sắp xếp), wrapped native và stablecoin trong `common_tokens`
+    pub fn from_chain_config(chain: &ChainConfig) -> Self {
+        let mut factories: Vec<(&String, &Address)> = chain.factory_contracts.iter().collect();
+        factories.sort();
+
+        let mut stablecoins: Vec<(usize, Address, u8)> = chain.common_tokens.values()
+            .filter_map(|token| {
+                STABLECOIN_SYMBOLS.iter()
+                    .position(|symbol| token.symbol.eq_ignore_ascii_case(symbol))
+                    .map(|rank| (rank, token.address, token.decimals))
+            })
+            .collect();
+        stablecoins.sort();
+
+        Self {
+            factory: factories.first().map(|(_, factory)| **factory),
+            wrapped_native: chain.wrapped_native_token,
+            native_decimals: chain.native_token_decimals,
+            stablecoins: stablecoins.into_iter().map(|(_, address, decimals)| (address, decimals)).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Token là stablecoin mốc USD
+    pub fn is_stablecoin(&self, token: Address) -> bool {
+        self.stablecoins.iter().any(|(address, _)| *address == token)
+    }
+}
+
+/// Định giá token theo reserves của pair Uniswap V2: token/wrapped native cho giá native,
+/// wrapped native/stablecoin cho giá USD
+pub struct DexPriceOracle<A: ChainAdapter + ?Sized> {
+    adapter: Arc<A>,
+    config: DexPriceConfig,
+    /// Cache getPair: (token, wrapped native) -> pair (None nếu chưa có pair)
+    pairs: RwLock<HashMap<(Address, Address), Option<Address>>>,
+}
+
+impl<A: ChainAdapter + ?Sized> DexPriceOracle<A> {
+    pub fn new(adapter: Arc<A>, config: DexPriceConfig) -> Self {
+        Self { adapter, config, pairs: RwLock::new(HashMap::new()) }
+    }
+
+    pub fn config(&self) -> &DexPriceConfig {
+        &self.config
+    }
+
+    /// Giá USD của native token theo pair wrapped native/stablecoin đầu tiên đủ thanh khoản
+    pub async fn native_price_usd(&self) -> Result<Option<f64>> {
+        let Some(wrapped) = self.config.wrapped_native else {
+            return Ok(None);
+        };
+        for (stablecoin, decimals) in &self.config.stablecoins {
+            if let Some(price) = self.pair_price(wrapped, self.config.native_decimals, *stablecoin, *decimals).await? {
+                return Ok(Some(price));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Giá token tính bằng native token
+    pub async fn token_price_native(&self, token: Address, decimals: u8) -> Result<Option<f64>> {
+        let Some(wrapped) = self.config.wrapped_native else {
+            return Ok(None);
+        };
+        if token == wrapped {
+            return Ok(Some(1.0));
+        }
+        self.pair_price(token, decimals, wrapped, self.config.native_decimals).await
+    }
+
+    /// Giá USD của token; stablecoin mốc luôn là 1 USD
+    pub async fn token_price_usd(&self, token: Address, decimals: u8, native_price_usd: Option<f64>) -> Result<Option<f64>> {
+        if self.config.is_stablecoin(token) {
+            return Ok(Some(1.0));
+        }
+        let Some(native_price_usd) = native_price_usd else {
+            return Ok(None);
+        };
+        Ok(self.token_price_native(token, decimals).await?.map(|price| price * native_price_usd))
+    }
+
+    /// Giá `base` theo `quote` (quote luôn là wrapped native hoặc đối bên của wrapped native)
+    async fn pair_price(&self, base: Address, base_decimals: u8, quote: Address, quote_decimals: u8) -> Result<Option<f64>> {
+        let Some(pair) = self.pair(base, quote).await? else {
+            return Ok(None);
+        };
+        let output = self.adapter.call(&TransactionRequest::new().to(pair).data(encode_call("getReserves()", &[])), None).await?;
+        if output.len() < 64 {
+            return Ok(None);
+        }
+        let (reserve0, reserve1) = (U256::from_big_endian(&output[..32]), U256::from_big_endian(&output[32..64]));
+        // Uniswap V2 sắp token0 < token1 theo địa chỉ
+        let (base_reserve, quote_reserve) = if base < quote { (reserve0, reserve1) } else { (reserve1, reserve0) };
+
+        let native_reserve = match self.config.wrapped_native {
+            Some(wrapped) if wrapped == base => to_units(base_reserve, base_decimals),
+            _ => to_units(quote_reserve, quote_decimals),
+        };
+        if native_reserve < self.config.min_native_liquidity {
+            debug!("Bỏ qua pair {:?}: thanh khoản {} native dưới ngưỡng", pair, native_reserve);
+            return Ok(None);
+        }
+        Ok(price_from_reserves(base_reserve, base_decimals, quote_reserve, quote_decimals))
+    }
+
+    async fn pair(&self, token_a: Address, token_b: Address) -> Result<Option<Address>> {
+        let Some(factory) = self.config.factory else {
+            return Ok(None);
+        };
+        if let Some(pair) = self.pairs.read().await.get(&(token_a, token_b)) {
+            return Ok(*pair);
+        }
+
+        let data = encode_call("getPair(address,address)", &[Token::Address(token_a), Token::Address(token_b)]);
+        let output = self.adapter.call(&TransactionRequest::new().to(factory).data(data), None).await?;
+        let pair = output.get(12..32).map(Address::from_slice).filter(|pair| !pair.is_zero());
+        // Chỉ cache pair đã tồn tại; pair chưa có có thể được tạo sau
+        if pair.is_some() {
+            self.pairs.write().await.insert((token_a, token_b), pair);
+        }
+        Ok(pair)
+    }
+}
+
+/// Đổi số lượng nguyên (wei) sang đơn vị token theo decimals
+pub fn to_units(amount: U256, decimals: u8) -> f64 {
+    ethers::utils::format_units(amount, decimals as u32)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0.0)
+}
+
+/// Giá 1 đơn vị base tính bằng quote từ reserves đã chuẩn hóa decimals
+pub fn price_from_reserves(base_reserve: U256, base_decimals: u8, quote_reserve: U256, quote_decimals: u8) -> Option<f64> {
+    let base = to_units(base_reserve, base_decimals);
+    (base > 0.0).then(|| to_units(quote_reserve, quote_decimals) / base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_from_reserves_normalizes_decimals() {
+        // 2 WETH (18 decimals) : 6000 USDC (6 decimals) -> 3000 USDC/WETH
+        let price = price_from_reserves(U256::exp10(18) * 2, 18, U256::exp10(6) * 6000, 6).unwrap();
+        assert!((price - 3000.0).abs() < 1e-9);
+        assert!(price_from_reserves(U256::zero(), 18, U256::exp10(6), 6).is_none());
+        assert!((to_units(U256::from(1_500_000u64), 6) - 1.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_config_prefers_listed_stablecoin_order() {
+        let chain: ChainConfig = serde_json::from_value(serde_json::json!({
+            "chain_id": 1,
+            "name": "Ethereum",
+            "native_token_name": "Ether",
+            "native_token_symbol": "ETH",
+            "native_token_decimals": 18,
+            "avg_block_time": 12.0,
+            "chain_type": "EVM",
+            "primary_rpc_urls": ["http://localhost:8545"],
+            "wrapped_native_token": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            "factory_contracts": {
+                "sushiswap": "0xc0aee478e3658e2610c5f7a4a2e1777ce9e4f2ac",
+                "uniswap_v2": "0x5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f"
+            },
+            "common_tokens": {
+                "USDT": {"name": "Tether USD", "symbol": "USDT", "address": "0xdac17f958d2ee523a2206206994597c13d831ec7", "decimals": 6, "logo_url": null},
+                "USDC": {"name": "USD Coin", "symbol": "USDC", "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", "decimals": 6, "logo_url": null},
+                "WETH": {"name": "Wrapped Ether", "symbol": "WETH", "address": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", "decimals": 18, "logo_url": null}
+            }
+        })).unwrap();
+
+        let config = DexPriceConfig::from_chain_config(&chain);
+        assert_eq!(config.factory, Some("0xc0aee478e3658e2610c5f7a4a2e1777ce9e4f2ac".parse().unwrap()));
+        let usdc: Address = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".parse().unwrap();
+        assert_eq!(config.stablecoins.first(), Some(&(usdc, 6)));
+        assert_eq!(config.stablecoins.len(), 2);
+        assert!(config.is_stablecoin(usdc));
+    }
+}
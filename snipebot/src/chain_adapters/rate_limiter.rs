@@ -0,0 +1,343 @@
+// External imports
+use chrono::DateTime;
+
+// Standard library imports
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+// Third party imports
+use serde::{Deserialize, Serialize};
+
+/// Thời gian backoff ban đầu khi bị rate limit mà không có Retry-After (ms)
+const BASE_RATE_LIMIT_BACKOFF_MS: u64 = 1_000;
+
+/// Thời gian backoff tối đa khi bị rate limit (ms)
+const MAX_RATE_LIMIT_BACKOFF_MS: u64 = 60_000;
+
+/// Giới hạn request/compute unit của một endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointRateLimit {
+    /// Số request tối đa mỗi giây (None = không giới hạn)
+    #[serde(default)]
+    pub max_requests_per_second: Option<f64>,
+    /// Số compute unit tối đa mỗi giây (None = không giới hạn)
+    #[serde(default)]
+    pub max_compute_units_per_second: Option<f64>,
+    /// Dung lượng burst, tính bằng số giây của hạn mức
+    #[serde(default = "default_burst_seconds")]
+    pub burst_seconds: f64,
+}
+
+fn default_burst_seconds() -> f64 {
+    1.0
+}
+
+impl Default for EndpointRateLimit {
+    fn default() -> Self {
+        Self {
+            max_requests_per_second: None,
+            max_compute_units_per_second: None,
+            burst_seconds: default_burst_seconds(),
+        }
+    }
+}
+
+/// Chi phí compute unit (CU) theo method JSON-RPC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComputeUnitCosts {
+    /// Chi phí cho method không có trong bảng
+    pub default_cost: u32,
+    /// Chi phí theo method
+    pub methods: HashMap<String, u32>,
+}
+
+impl Default for ComputeUnitCosts {
+    fn default() -> Self {
+        // Bảng chi phí tham khảo theo các nhà cung cấp RPC trả phí phổ biến
+        let methods = [
+            ("eth_chainId", 0),
+            ("net_version", 0),
+            ("eth_blockNumber", 10),
+            ("eth_feeHistory", 10),
+            ("eth_getTransactionReceipt", 15),
+            ("eth_getBlockByNumber", 16),
+            ("eth_getBalance", 19),
+            ("eth_gasPrice", 19),
+            ("eth_maxPriorityFeePerGas", 19),
+            ("eth_call", 26),
+            ("eth_getCode", 26),
+            ("eth_getTransactionCount", 26),
+            ("eth_getLogs", 75),
+            ("eth_estimateGas", 87),
+            ("eth_sendRawTransaction", 250),
+        ]
+        .into_iter()
+        .map(|(method, cost)| (method.to_string(), cost))
+        .collect();
+
+        Self {
+            default_cost: 20,
+            methods,
+        }
+    }
+}
+
+impl ComputeUnitCosts {
+    /// Chi phí CU của method
+    pub fn cost(&self, method: &str) -> u32 {
+        self.methods.get(method).copied().unwrap_or(self.default_cost)
+    }
+}
+
+/// Token bucket: nạp lại `rate` token mỗi giây, tối đa `capacity`
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    rate: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Tạo bucket đầy
+    pub fn new(rate: f64, burst_seconds: f64, now: Instant) -> Self {
+        let capacity = (rate * burst_seconds.max(0.0)).max(1.0);
+        Self {
+            capacity,
+            tokens: capacity,
+            rate: rate.max(f64::MIN_POSITIVE),
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Số token hiện có
+    pub fn available(&mut self, now: Instant) -> f64 {
+        self.refill(now);
+        self.tokens
+    }
+
+    /// Thời gian chờ để có đủ `cost` token (0 nếu đã đủ)
+    pub fn wait_time(&mut self, cost: f64, now: Instant) -> Duration {
+        self.refill(now);
+        // Chi phí lớn hơn dung lượng chỉ cần bucket đầy
+        let needed = cost.min(self.capacity) - self.tokens;
+        if needed <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(needed / self.rate)
+        }
+    }
+
+    /// Trừ `cost` token (có thể âm khi chi phí vượt dung lượng)
+    pub fn take(&mut self, cost: f64) {
+        self.tokens -= cost;
+    }
+}
+
+/// Bộ giới hạn của một endpoint: bucket request, bucket CU và backoff khi bị rate limit
+#[derive(Debug, Clone)]
+pub struct EndpointLimiter {
+    requests: Option<TokenBucket>,
+    compute_units: Option<TokenBucket>,
+    backoff_until: Option<Instant>,
+    consecutive_rate_limits: u32,
+}
+
+impl EndpointLimiter {
+    /// Tạo bộ giới hạn theo cấu hình
+    pub fn new(limit: &EndpointRateLimit, now: Instant) -> Self {
+        Self {
+            requests: limit.max_requests_per_second.map(|rate| TokenBucket::new(rate, limit.burst_seconds, now)),
+            compute_units: limit.max_compute_units_per_second.map(|rate| TokenBucket::new(rate, limit.burst_seconds, now)),
+            backoff_until: None,
+            consecutive_rate_limits: 0,
+        }
+    }
+
+    /// Thời gian còn phải chờ do bị rate limit (None nếu không backoff)
+    pub fn backoff_remaining(&self, now: Instant) -> Option<Duration> {
+        self.backoff_until
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+    }
+
+    /// Thời gian chờ trước khi có đủ hạn mức cho một request chi phí `cost` CU (0 nếu gửi được ngay)
+    pub fn time_until_available(&mut self, cost: u32, now: Instant) -> Duration {
+        if let Some(remaining) = self.backoff_remaining(now) {
+            return remaining;
+        }
+
+        let request_wait = self.requests.as_mut().map_or(Duration::ZERO, |bucket| bucket.wait_time(1.0, now));
+        let cu_wait = self.compute_units.as_mut().map_or(Duration::ZERO, |bucket| bucket.wait_time(cost as f64, now));
+        request_wait.max(cu_wait)
+    }
+
+    /// Thử cấp hạn mức cho một request chi phí `cost` CU; trả về thời gian cần chờ nếu không đủ
+    pub fn try_acquire(&mut self, cost: u32, now: Instant) -> Result<(), Duration> {
+        let wait = self.time_until_available(cost, now);
+        if wait > Duration::ZERO {
+            return Err(wait);
+        }
+
+        if let Some(bucket) = self.requests.as_mut() {
+            bucket.take(1.0);
+        }
+        if let Some(bucket) = self.compute_units.as_mut() {
+            bucket.take(cost as f64);
+        }
+        Ok(())
+    }
+
+    /// Ghi nhận endpoint trả về rate limit; trả về thời gian backoff áp dụng
+    pub fn on_rate_limited(&mut self, retry_after: Option<Duration>, now: Instant) -> Duration {
+        self.consecutive_rate_limits = self.consecutive_rate_limits.saturating_add(1);
+        let backoff = retry_after.unwrap_or_else(|| {
+            let exponent = (self.consecutive_rate_limits - 1).min(16);
+            Duration::from_millis((BASE_RATE_LIMIT_BACKOFF_MS << exponent).min(MAX_RATE_LIMIT_BACKOFF_MS))
+        });
+        self.backoff_until = Some(now + backoff);
+
+        // Bucket cạn để không gửi thêm ngay khi hết backoff
+        for bucket in [self.requests.as_mut(), self.compute_units.as_mut()].into_iter().flatten() {
+            bucket.refill(now);
+            bucket.tokens = bucket.tokens.min(0.0);
+        }
+        backoff
+    }
+
+    /// Ghi nhận request thành công
+    pub fn on_success(&mut self) {
+        self.consecutive_rate_limits = 0;
+    }
+
+    /// Tỷ lệ hạn mức CU còn lại (0.0 - 1.0), None nếu không giới hạn CU
+    pub fn compute_budget_remaining(&mut self, now: Instant) -> Option<f64> {
+        self.compute_units.as_mut().map(|bucket| {
+            let capacity = bucket.capacity;
+            (bucket.available(now) / capacity).clamp(0.0, 1.0)
+        })
+    }
+}
+
+/// Parse header Retry-After: số giây hoặc HTTP-date
+pub fn parse_retry_after(value: &str, now_unix_secs: i64) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return (seconds >= 0.0 && seconds.is_finite()).then(|| Duration::from_secs_f64(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(Duration::from_secs(date.timestamp().saturating_sub(now_unix_secs).max(0) as u64))
+}
+
+/// Phát hiện lỗi rate limit từ thông điệp lỗi của provider.
+/// Trả về `Some(retry_after)` nếu là lỗi rate limit, kèm thời gian chờ nếu thông điệp có gợi ý.
+pub fn detect_rate_limit(message: &str) -> Option<Option<Duration>> {
+    let lower = message.to_lowercase();
+    let is_rate_limit = lower.contains("429")
+        || lower.contains("too many requests")
+        || lower.contains("rate limit")
+        || lower.contains("rate-limit")
+        || lower.contains("exceeded its compute units")
+        || lower.contains("compute units per second")
+        || lower.contains("request limit")
+        || lower.contains("-32005");
+    if !is_rate_limit {
+        return None;
+    }
+
+    Some(retry_hint(&lower))
+}
+
+/// Tìm gợi ý thời gian chờ dạng "retry after 2s", "try again in 500ms", "retry-after: 3"
+fn retry_hint(lower: &str) -> Option<Duration> {
+    for marker in ["retry after", "retry-after", "try again in", "backoff"] {
+        let Some(position) = lower.find(marker) else { continue };
+        let rest = lower[position + marker.len()..].trim_start_matches([' ', ':', '=']);
+        let number: String = rest.chars().take_while(|c| c.is_ascii_digit() || *c == '.').collect();
+        let Ok(value) = number.parse::<f64>() else { continue };
+        let unit = rest[number.len()..].trim_start();
+        let seconds = if unit.starts_with("ms") { value / 1000.0 } else { value };
+        return Some(Duration::from_secs_f64(seconds));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_limits_and_refills() {
+        let start = Instant::now();
+        let limit = EndpointRateLimit {
+            max_requests_per_second: Some(2.0),
+            max_compute_units_per_second: Some(100.0),
+            burst_seconds: 1.0,
+        };
+        let mut limiter = EndpointLimiter::new(&limit, start);
+
+        assert!(limiter.try_acquire(26, start).is_ok());
+        assert!(limiter.try_acquire(26, start).is_ok());
+        // Hết hạn mức request/giây
+        let wait = limiter.try_acquire(26, start).unwrap_err();
+        assert!(wait > Duration::ZERO && wait <= Duration::from_millis(500));
+
+        // Sau 1 giây bucket request đầy lại, CU còn đủ cho eth_getLogs
+        let later = start + Duration::from_secs(1);
+        assert!(limiter.try_acquire(75, later).is_ok());
+        // CU chưa đủ cho eth_sendRawTransaction (vượt dung lượng phải chờ bucket đầy)
+        assert!(limiter.try_acquire(250, later).is_err());
+        assert!(limiter.try_acquire(250, later + Duration::from_secs(2)).is_ok());
+    }
+
+    #[test]
+    fn test_rate_limited_backoff() {
+        let start = Instant::now();
+        let mut limiter = EndpointLimiter::new(&EndpointRateLimit::default(), start);
+        assert!(limiter.try_acquire(10, start).is_ok());
+
+        assert_eq!(limiter.on_rate_limited(Some(Duration::from_secs(3)), start), Duration::from_secs(3));
+        assert!(limiter.try_acquire(10, start + Duration::from_secs(2)).is_err());
+        assert!(limiter.try_acquire(10, start + Duration::from_secs(3)).is_ok());
+
+        // Không có Retry-After: backoff tăng theo cấp số nhân
+        let first = limiter.on_rate_limited(None, start);
+        let second = limiter.on_rate_limited(None, start);
+        assert_eq!(second, first * 2);
+        limiter.on_success();
+        assert_eq!(limiter.on_rate_limited(None, start), Duration::from_millis(BASE_RATE_LIMIT_BACKOFF_MS));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("5", 0), Some(Duration::from_secs(5)));
+        assert_eq!(parse_retry_after(" 0.5 ", 0), Some(Duration::from_millis(500)));
+        // 1445412480 = Wed, 21 Oct 2015 07:28:00 GMT
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", 1_445_412_470), Some(Duration::from_secs(10)));
+        assert_eq!(parse_retry_after("soon", 0), None);
+    }
+
+    #[test]
+    fn test_detect_rate_limit() {
+        assert_eq!(detect_rate_limit("execution reverted"), None);
+        assert_eq!(detect_rate_limit("HTTP 429 Too Many Requests"), Some(None));
+        assert_eq!(
+            detect_rate_limit("(code: -32005) rate limit exceeded, retry after 2s"),
+            Some(Some(Duration::from_secs(2)))
+        );
+        assert_eq!(
+            detect_rate_limit("Your app has exceeded its compute units per second capacity. Try again in 250ms"),
+            Some(Some(Duration::from_millis(250)))
+        );
+        assert_eq!(ComputeUnitCosts::default().cost("eth_getLogs"), 75);
+        assert_eq!(ComputeUnitCosts::default().cost("debug_traceCall"), 20);
+    }
+}
@@ -1,11 +1,12 @@
 use anyhow::{Result, anyhow};
+use std::fmt::Debug;
+use std::future::Future;
 use std::time::Duration;
+use ethers::providers::{Middleware, ProviderError};
+use ethers::types::U256;
 use tracing::{info, warn, debug};
 use backoff::ExponentialBackoff;
 
-use crate::chain_adapters::retry_policy::{RetryContext, create_default_retry_policy};
-use crate::chain_adapters::connection_pool::{get_or_create_pool, ProviderGuard};
-use crate::chain_adapters::interfaces::ChainError;
 
 /// Enum liệt kê các loại lỗi phổ biến của blockchain
 #[derive(Debug, Clone)]
@@ -39,9 +40,9 @@ impl From<ethers::providers::ProviderError> for BlockchainError {
     fn from(err: ethers::providers::ProviderError) -> Self {
         match err {
             ProviderError::JsonRpcClientError(e) => {
-                if e.to_string().contains("insufficient funds") {
-                    BlockchainError::InsufficientBalance(e.to_string())
-                } else if e.to_string().contains("gas required exceeds allowance") {
+                if e.to_string().contains("insufficient funds")
+                    || e.to_string().contains("gas required exceeds allowance")
+                {
                     BlockchainError::InsufficientBalance(e.to_string())
                 } else if e.to_string().contains("nonce") {
                     BlockchainError::InvalidNonce(e.to_string())
@@ -65,13 +66,13 @@ impl From<ethers::providers::ProviderError> for BlockchainError {
 
 /// Kiểm tra xem lỗi có nên thử lại không
 pub fn is_retryable(error: &BlockchainError) -> bool {
-    match error {
-        BlockchainError::RPCConnection(_) => true,
-        BlockchainError::LowGas(_) => true,
-        BlockchainError::InvalidNonce(_) => true,
-        BlockchainError::Timeout(_) => true,
-        _ => false,
-    }
+    matches!(
+        error,
+        BlockchainError::RPCConnection(_)
+            | BlockchainError::LowGas(_)
+            | BlockchainError::InvalidNonce(_)
+            | BlockchainError::Timeout(_)
+    )
 }
 
 /// Tăng gas price dựa trên lỗi
@@ -87,12 +88,13 @@ pub fn adjust_gas_for_retry(error: &BlockchainError, current_gas_price: U256) ->
 
 /// Cấu hình backoff
 pub fn create_backoff() -> ExponentialBackoff {
-    let mut backoff = ExponentialBackoff::default();
-    backoff.initial_interval = Duration::from_millis(500);
-    backoff.max_interval = Duration::from_secs(30);
-    backoff.multiplier = 2.0;
-    backoff.max_elapsed_time = Some(Duration::from_secs(300)); // 5 phút
-    backoff
+    ExponentialBackoff {
+        initial_interval: Duration::from_millis(500),
+        max_interval: Duration::from_secs(30),
+        multiplier: 2.0,
+        max_elapsed_time: Some(Duration::from_secs(300)), // 5 phút
+        ..Default::default()
+    }
 }
 
 /// Thực hiện hàm async với retry và backoff
@@ -102,7 +104,7 @@ where
     Fut: Future<Output = std::result::Result<T, E>>,
     E: Into<anyhow::Error> + Debug,
 {
-    let mut backoff = create_backoff();
+    let backoff = create_backoff();
     let operation = || async {
         match f().await {
             Ok(v) => Ok(v),
@@ -142,10 +144,11 @@ where
                 return Ok(result);
             },
             Err(e) => {
-                let error_str = e.to_string();
-                let blockchain_error = if error_str.contains("insufficient funds") {
-                    BlockchainError::InsufficientBalance(error_str)
-                } else if error_str.contains("gas required exceeds allowance") {
+                // Cả chuỗi nguyên nhân: lý do thật (ví dụ bị chính sách chi tiêu từ chối) nằm dưới context
+                let error_str = format!("{:#}", e);
+                let blockchain_error = if error_str.contains("insufficient funds")
+                    || error_str.contains("gas required exceeds allowance")
+                {
                     BlockchainError::InsufficientBalance(error_str)
                 } else if error_str.contains("nonce") {
                     BlockchainError::InvalidNonce(error_str)
@@ -194,13 +197,10 @@ where
     F: Fn(&str) -> Fut,
     Fut: Future<Output = Result<T>>,
 {
-    let primary_result = f(primary_url).await;
-    
-    if primary_result.is_ok() {
-        return primary_result;
-    }
-    
-    let primary_error = primary_result.unwrap_err();
+    let primary_error = match f(primary_url).await {
+        Ok(result) => return Ok(result),
+        Err(e) => e,
+    };
     
     // Chỉ rotate RPC URL nếu lỗi là lỗi kết nối
     if !primary_error.to_string().contains("connection") 
@@ -237,10 +237,7 @@ pub async fn check_rpc_availability(urls: &[&str]) -> Vec<(String, bool, u128)>
         let start_time = std::time::Instant::now();
         let is_available = match ethers::providers::Provider::<ethers::providers::Http>::try_from(*url) {
             Ok(provider) => {
-                match provider.get_block_number().await {
-                    Ok(_) => true,
-                    Err(_) => false,
-                }
+                provider.get_block_number().await.is_ok()
             },
             Err(_) => false,
         };
@@ -286,12 +283,11 @@ impl RPCPool {
     /// Lấy URL RPC tốt nhất
     pub async fn get_best_url(&self) -> Result<String> {
         // Kiểm tra lại availability nếu đã quá 10 phút
-        {
-            let last_checked = self.last_checked.read().unwrap();
-            if last_checked.elapsed() > Duration::from_secs(600) {
-                drop(last_checked);
-                self.check_availability().await?;
-            }
+        let stale = self.last_checked.read()
+            .map(|last_checked| last_checked.elapsed() > Duration::from_secs(600))
+            .unwrap_or(true);
+        if stale {
+            self.check_availability().await?;
         }
         
         // Lấy URL đầu tiên có sẵn
@@ -354,14 +350,14 @@ impl RPCPool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     
     #[tokio::test]
     async fn test_retry_async() {
-        let mut attempts = 0;
+        let attempts = AtomicUsize::new(0);
         
         let result = retry_async(|| async {
-            attempts += 1;
-            if attempts < 3 {
+            if attempts.fetch_add(1, Ordering::SeqCst) + 1 < 3 {
                 Err::<(), _>(anyhow!("Lỗi thử nghiệm"))
             } else {
                 Ok(())
@@ -369,19 +365,19 @@ mod tests {
         }).await;
         
         assert!(result.is_ok());
-        assert_eq!(attempts, 3);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
     }
     
     #[tokio::test]
     async fn test_retry_blockchain_operation() {
-        let mut attempts = 0;
+        let attempts = AtomicUsize::new(0);
         
         let result = retry_blockchain_operation(
             "test_operation",
-            |gas_price| async move {
-                attempts += 1;
-                if attempts < 3 {
-                    if attempts == 1 {
+            |_gas_price| async {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                if attempt < 3 {
+                    if attempt == 1 {
                         Err(anyhow!("underpriced transaction"))
                     } else {
                         Err(anyhow!("connection error"))
@@ -395,6 +391,6 @@ mod tests {
         ).await;
         
         assert!(result.is_ok());
-        assert_eq!(attempts, 3);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
     }
-} 
\ No newline at end of file
+}
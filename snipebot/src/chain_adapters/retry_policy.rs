@@ -5,13 +5,12 @@ use std::{
     sync::{Arc, RwLock, atomic::{AtomicU64, Ordering}},
     collections::HashMap,
     error::Error,
+    future::Future,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 // Internal imports
-use crate::{
-    chain_adapters::interfaces::ChainError,
-    metrics::{RetryMetrics, RETRY_METRICS},
-};
+use crate::chain_adapters::{interfaces::ChainError, rate_limiter::detect_rate_limit, rpc_error};
 
 // Third party imports
 use anyhow::{Result, anyhow};
@@ -20,7 +19,7 @@ use ethers::types::U256;
 use serde::{Serialize, Deserialize};
 use metrics::{counter, gauge};
 use async_trait::async_trait;
-use backoff::backoff::Backoff;
+use rand::Rng;
 
 /// Trạng thái circuit breaker
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -154,7 +153,7 @@ impl CircuitBreaker {
                         match self.last_state_change.try_read() {
                             Ok(last_change) => {
                                 let elapsed = last_change.elapsed();
-                                if elapsed >= self.config.open_duration {
+                                if elapsed >= Duration::from_millis(self.config.open_duration) {
                                     // Chuyển sang trạng thái half-open
                                     match self.state.try_write() {
                                         Ok(mut state) => {
@@ -328,9 +327,10 @@ pub trait RetryPolicy: Send + Sync + 'static {
     fn get_backoff_duration(&self, attempt: u32) -> Duration;
     
     /// Thực hiện retry một hàm async
-    async fn retry_async<F, T>(&self, f: F) -> Result<T> 
+    async fn retry_async<F, Fut, T>(&self, context: RetryContext, f: F) -> Result<T>
     where
-        F: Fn() -> Result<T> + Send + Sync,
+        F: Fn() -> Fut + Send + Sync,
+        Fut: Future<Output = Result<T>> + Send,
         T: Send;
         
     /// Lấy số lần retry tối đa
@@ -344,6 +344,7 @@ pub enum RetryPolicyEnum {
     // Có thể thêm các loại khác nếu cần
 }
 
+#[async_trait]
 impl RetryPolicy for RetryPolicyEnum {
     fn should_retry(&self, attempt: u32, error: &anyhow::Error) -> bool {
         match self {
@@ -357,13 +358,14 @@ impl RetryPolicy for RetryPolicyEnum {
         }
     }
     
-    async fn retry_async<F, T>(&self, f: F) -> Result<T> 
+    async fn retry_async<F, Fut, T>(&self, context: RetryContext, f: F) -> Result<T>
     where
-        F: Fn() -> Result<T> + Send + Sync,
-        T: Send
+        F: Fn() -> Fut + Send + Sync,
+        Fut: Future<Output = Result<T>> + Send,
+        T: Send,
     {
         match self {
-            RetryPolicyEnum::Exponential(policy) => policy.retry_async(f)
+            RetryPolicyEnum::Exponential(policy) => policy.retry_async(context, f).await
         }
     }
     
@@ -374,6 +376,49 @@ impl RetryPolicy for RetryPolicyEnum {
     }
 }
 
+impl RetryPolicyEnum {
+    /// Thực thi thao tác với retry theo context (thứ tự tham số thuận tiện cho call site)
+    pub async fn retry<F, Fut, T>(&self, f: F, context: &RetryContext) -> Result<T>
+    where
+        F: Fn() -> Fut + Send + Sync,
+        Fut: Future<Output = Result<T>> + Send,
+        T: Send,
+    {
+        self.retry_async(context.clone(), f).await
+    }
+    
+    /// Quyết định hành động retry cho lỗi đã phân loại
+    pub fn decide(&self, attempt: u32, error: &ChainError) -> RetryAction {
+        match self {
+            RetryPolicyEnum::Exponential(policy) => policy.decide(attempt, error)
+        }
+    }
+    
+    /// Phân loại lỗi anyhow (mã JSON-RPC, thông điệp node) rồi quyết định hành động retry
+    pub fn decide_error(&self, attempt: u32, error: anyhow::Error) -> RetryAction {
+        self.decide(attempt, &ChainError::from_anyhow(error))
+    }
+}
+
+/// Hành động cần thực hiện trước lần gửi lại, quyết định theo loại ChainError
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RetryAction {
+    /// Gửi lại nguyên trạng sau khoảng chờ
+    Retry { delay: Duration },
+    /// Nonce đã bị dùng: lấy nonce kế tiếp rồi gửi lại ngay
+    BumpNonce,
+    /// Nonce vượt trước: đồng bộ lại nonce từ node rồi gửi lại
+    RefreshNonce,
+    /// Phí quá thấp: tăng max fee/priority fee (hoặc gas price) thêm `percent` phần trăm
+    BumpFees { percent: u32 },
+    /// Endpoint bị giới hạn hoặc chậm đồng bộ: chuyển endpoint và chờ `delay`
+    SwitchEndpoint { delay: Duration },
+    /// Giao dịch đã có trong mempool: chờ receipt thay vì gửi lại
+    WaitForPending,
+    /// Lỗi không thể khắc phục bằng retry
+    Abort,
+}
+
 /// Cấu trúc context cho retry
 #[derive(Debug, Clone)]
 pub struct RetryContext {
@@ -434,190 +479,146 @@ impl ExponentialRetryPolicy {
             Arc::new(CircuitBreaker::new(endpoint, self.config.circuit_breaker.clone()))
         }).clone()
     }
+    
+    /// Thời gian chờ theo cấp số nhân (không jitter) cho lần thử `attempt`
+    fn exponential_delay(&self, attempt: u32) -> Duration {
+        let delay_ms = self.config.base_retry_interval as f64 * self.config.backoff_factor.powi(attempt as i32);
+        Duration::from_millis(delay_ms.min(self.config.max_total_retry_time as f64) as u64)
+    }
+    
+    /// Phần trăm tăng phí cho lần thử `attempt`, giới hạn bởi max_boost_factor
+    fn fee_bump_percent(&self, attempt: u32) -> u32 {
+        let boost = &self.gas_boost_config;
+        let factor = (boost.initial_boost_factor + boost.step_factor * attempt as f64).min(boost.max_boost_factor);
+        ((factor - 1.0) * 100.0).round().max(0.0) as u32
+    }
+    
+    /// Quyết định hành động retry dựa trên loại lỗi đã phân loại
+    pub fn decide(&self, attempt: u32, error: &ChainError) -> RetryAction {
+        if attempt as usize >= self.config.max_retries {
+            return RetryAction::Abort;
+        }
+        
+        match error {
+            ChainError::NonceTooLow(_) => RetryAction::BumpNonce,
+            ChainError::NonceTooHigh(_) | ChainError::NonceError(_) => RetryAction::RefreshNonce,
+            ChainError::AlreadyKnown(_) => RetryAction::WaitForPending,
+            ChainError::Underpriced | ChainError::ReplacementUnderpriced(_) | ChainError::FeeCapTooLow(_) => {
+                if self.gas_boost_config.enabled {
+                    RetryAction::BumpFees { percent: self.fee_bump_percent(attempt) }
+                } else {
+                    RetryAction::Abort
+                }
+            }
+            ChainError::RateLimitExceeded(message) => {
+                // Ưu tiên thời gian chờ do endpoint gợi ý (Retry-After, "try again in ...")
+                let delay = detect_rate_limit(message).flatten()
+                    .unwrap_or_else(|| self.exponential_delay(attempt));
+                RetryAction::SwitchEndpoint { delay }
+            }
+            ChainError::HeaderNotFound(_) => RetryAction::SwitchEndpoint { delay: self.exponential_delay(attempt) },
+            ChainError::ConnectionError(_) |
+            ChainError::TimeoutError(_) |
+            ChainError::ProviderNotAvailable => RetryAction::Retry { delay: self.exponential_delay(attempt) },
+            _ => RetryAction::Abort,
+        }
+    }
 }
 
 #[async_trait]
 impl RetryPolicy for ExponentialRetryPolicy {
     fn should_retry(&self, attempt: u32, error: &anyhow::Error) -> bool {
-        let config = self.config.clone();
-        let error_str = error.to_string();
-        Box::pin(async move {
-            // Phân tích lỗi để quyết định có retry không
-            error_str.contains("timeout") || 
-            error_str.contains("connection refused") ||
-            error_str.contains("too many requests") ||
-            error_str.contains("rate limit") ||
-            error_str.contains("server error") ||
-            error_str.contains("network error") ||
-            attempt < 3  // Luôn retry trong 3 lần đầu
-        }).await.unwrap_or(false)
+        if attempt as usize >= self.config.max_retries {
+            return false;
+        }
+        
+        // Lỗi đã phân loại được (mã JSON-RPC, thông điệp node) quyết định trước
+        if let Some(chain_err) = rpc_error::classify_anyhow(error) {
+            return chain_err.is_retryable();
+        }
+        
+        let error_str = error.to_string().to_lowercase();
+        self.config.retryable_errors.iter().any(|pattern| error_str.contains(pattern.as_str()))
     }
     
     fn get_backoff_duration(&self, attempt: u32) -> Duration {
-        let config = self.config.clone();
-        Box::pin(async move {
-            // Tính toán thời gian chờ theo cấp số nhân với jitter
-            let base_delay = config.initial_backoff_ms;
-            let max_delay = config.max_backoff_ms;
-            let multiplier = config.backoff_multiplier;
-            
-            // Công thức: base_delay * (multiplier ^ attempt)
-            let delay_ms = base_delay * multiplier.powi(attempt as i32);
-            let delay_ms = delay_ms.min(max_delay as f64) as u64;
-            
-            // Thêm jitter (dao động ngẫu nhiên ±20%)
-            let jitter_range = (delay_ms as f64 * 0.2) as u64;
-            let mut rng = rand::thread_rng();
-            let jitter = rng.gen_range(0..=jitter_range*2) as i64 - jitter_range as i64;
-            
-            let final_delay = std::cmp::max(10, delay_ms as i64 + jitter) as u64;
-            Duration::from_millis(final_delay)
-        }).await
+        // Thời gian chờ theo cấp số nhân cộng jitter ngẫu nhiên
+        let jitter = if self.config.max_jitter > 0 {
+            rand::thread_rng().gen_range(0..=self.config.max_jitter)
+        } else {
+            0
+        };
+        self.exponential_delay(attempt) + Duration::from_millis(jitter)
     }
     
-    async fn retry_async<F, T>(&self, f: F) -> Result<T> 
+    async fn retry_async<F, Fut, T>(&self, context: RetryContext, f: F) -> Result<T>
     where
-        F: Fn() -> Result<T> + Send + Sync,
-        T: Send
+        F: Fn() -> Fut + Send + Sync,
+        Fut: Future<Output = Result<T>> + Send,
+        T: Send,
     {
-        let config = self.config.clone();
-        let gas_boost_config = self.gas_boost_config.clone();
         let endpoint = context.endpoint.clone();
-        let circuit_breaker = self.get_circuit_breaker(&endpoint);
         let operation_name = context.operation_name.clone();
-
-        Box::pin(async move {
-            let mut current_retry = 0;
-            let mut total_time = Duration::from_millis(0);
-            let start_time = Instant::now();
-            let mut last_error = None;
-            let mut current_gas = context.current_gas_price;
+        let circuit_breaker = self.get_circuit_breaker(&endpoint);
+        let start_time = Instant::now();
+        let mut current_retry: u32 = 0;
+        
+        // Kiểm tra circuit breaker
+        if !circuit_breaker.can_execute() {
+            return Err(anyhow!("Circuit breaker is open for endpoint: {}", endpoint));
+        }
+        
+        loop {
+            let err = match f().await {
+                Ok(value) => {
+                    // Cập nhật metrics nếu đã retry
+                    if current_retry > 0 {
+                        counter!("retry_success", 1,
+                                "operation" => operation_name.clone(),
+                                "endpoint" => endpoint.clone());
+                        info!("Operation {} succeeded after {} retries in {} ms",
+                              operation_name, current_retry, start_time.elapsed().as_millis());
+                    }
+                    circuit_breaker.record_success();
+                    return Ok(value);
+                }
+                Err(err) => err,
+            };
+            
+            warn!("Operation {} failed (attempt {}): {}", operation_name, current_retry + 1, err);
+            circuit_breaker.record_failure();
             
-            // Kiểm tra circuit breaker
             if !circuit_breaker.can_execute() {
-                return Err(anyhow!("Circuit breaker is open for endpoint: {}", endpoint));
+                warn!("Circuit breaker opened for endpoint: {}", endpoint);
+                return Err(err.context("Circuit breaker opened during retry"));
             }
             
-            loop {
-                // Kiểm tra số lần retry
-                if current_retry >= config.max_retries {
-                    warn!("Max retries ({}) reached for operation: {}", 
-                        config.max_retries, operation_name);
-                    
-                    // Cập nhật metrics
-                    counter!("retry_exhausted", 1, 
-                            "operation" => operation_name.clone(),
-                            "endpoint" => endpoint.clone());
-                    
-                    return Err(last_error.unwrap_or_else(|| anyhow!("Max retries reached")));
-                }
-                
-                // Kiểm tra thời gian tổng cộng
-                if total_time.as_millis() as u64 >= config.max_total_retry_time {
-                    warn!("Max total retry time ({} ms) reached for operation: {}", 
-                        config.max_total_retry_time, operation_name);
-                    
-                    // Cập nhật metrics
-                    counter!("retry_timeout", 1, 
-                            "operation" => operation_name.clone(),
-                            "endpoint" => endpoint.clone());
-                    
-                    return Err(last_error.unwrap_or_else(|| anyhow!("Max retry time exceeded")));
-                }
-                
-                // Thực hiện operation
-                let result = f().await;
-                
-                match result {
-                    Ok(value) => {
-                        // Cập nhật metrics nếu đã retry
-                        if current_retry > 0 {
-                            counter!("retry_success", 1, 
-                                    "operation" => operation_name.clone(),
-                                    "endpoint" => endpoint.clone(),
-                                    "retry_count" => current_retry.to_string());
-                            
-                            // Ghi log thành công sau khi retry
-                            info!("Operation {} succeeded after {} retries in {} ms", 
-                                  operation_name, current_retry, total_time.as_millis());
-                            
-                            // Đánh dấu circuit breaker thành công
-                            circuit_breaker.record_success();
-                        }
-                        
-                        return Ok(value);
-                    }
-                    Err(err) => {
-                        // Lưu lỗi cuối cùng
-                        last_error = Some(err.clone());
-                        
-                        // Ghi log lỗi
-                        warn!("Operation {} failed (attempt {}): {}", 
-                              operation_name, current_retry + 1, err);
-                        
-                        // Ghi nhận thất bại cho circuit breaker
-                        circuit_breaker.record_failure();
-                        
-                        // Kiểm tra circuit breaker
-                        if !circuit_breaker.can_execute() {
-                            warn!("Circuit breaker opened for endpoint: {}", endpoint);
-                            return Err(anyhow!("Circuit breaker opened during retry"));
-                        }
-                        
-                        // Điều chỉnh gas price nếu cần
-                        let chain_err = match err.downcast_ref::<ChainError>() {
-                            Some(chain_err) => {
-                                // Có thể điều chỉnh gas price
-                                if let Some(new_gas) = self.adjust_gas_price(chain_err, current_gas) {
-                                    debug!("Adjusting gas price from {:?} to {:?}", current_gas, new_gas);
-                                    current_gas = Some(new_gas);
-                                }
-                                Some(chain_err)
-                            },
-                            None => None
-                        };
-                        
-                        // Kiểm tra xem lỗi có thể retry không
-                        let retryable = match chain_err {
-                            Some(chain_err) => self.is_retryable(chain_err),
-                            None => false
-                        };
-                        
-                        if !retryable {
-                            // Cập nhật metrics
-                            counter!("retry_non_retryable", 1, 
-                                    "operation" => operation_name.clone(),
-                                    "endpoint" => endpoint.clone());
-                            
-                            warn!("Non-retryable error encountered for operation {}: {}", 
-                                  operation_name, err);
-                            
-                            return Err(err);
-                        }
-                        
-                        // Tính thời gian delay
-                        let delay = self.get_backoff_duration(current_retry);
-                        
-                        // Cập nhật metrics
-                        counter!("retry_attempt", 1, 
-                                "operation" => operation_name.clone(),
-                                "endpoint" => endpoint.clone(),
-                                "retry_count" => current_retry.to_string());
-                        
-                        // Ghi log retry
-                        debug!("Retrying operation {} in {} ms (attempt {}/{})", 
-                              operation_name, delay.as_millis(), current_retry + 1, config.max_retries);
-                        
-                        // Delay trước khi thử lại
-                        tokio::time::sleep(delay).await;
-                        
-                        // Cập nhật thời gian và số lần retry
-                        current_retry += 1;
-                        total_time = start_time.elapsed();
-                    }
-                }
+            if !self.should_retry(current_retry, &err) {
+                counter!("retry_non_retryable", 1,
+                        "operation" => operation_name.clone(),
+                        "endpoint" => endpoint.clone());
+                return Err(err);
             }
-        })
+            
+            let delay = self.get_backoff_duration(current_retry);
+            if (start_time.elapsed() + delay).as_millis() as u64 >= self.config.max_total_retry_time {
+                warn!("Max total retry time ({} ms) reached for operation: {}",
+                    self.config.max_total_retry_time, operation_name);
+                counter!("retry_timeout", 1,
+                        "operation" => operation_name.clone(),
+                        "endpoint" => endpoint.clone());
+                return Err(err);
+            }
+            
+            counter!("retry_attempt", 1,
+                    "operation" => operation_name.clone(),
+                    "endpoint" => endpoint.clone());
+            debug!("Retrying operation {} in {} ms (attempt {}/{})",
+                  operation_name, delay.as_millis(), current_retry + 1, self.config.max_retries);
+            
+            tokio::time::sleep(delay).await;
+            current_retry += 1;
+        }
     }
     
     fn get_max_retries(&self) -> u32 {
@@ -733,11 +734,6 @@ pub enum RetryDecision {
     Abort,
 }
 
-pub trait RetryPolicy: Send + Sync + Debug + 'static {
-    fn should_retry(&self, error: &dyn Error) -> RetryDecision;
-    fn reset(&self);
-}
-
 #[derive(Debug)]
 pub struct RetryCallback<T> {
     callback: T,
@@ -765,7 +761,7 @@ impl<T> RetryCallback<T> {
         current
     }
     
-    pub fn update_safe(&self, mut info: RetryInfo) -> Result<(), String> {
+    pub fn update_safe(&self, info: RetryInfo) -> Result<(), String> {
         // Clone dữ liệu trước khi cập nhật
         let mut current = self.current.write().unwrap();
         *current = info;
@@ -773,8 +769,8 @@ impl<T> RetryCallback<T> {
     }
 }
 
-impl<T: Fn(RetryInfo) -> RetryDecision + Send + Sync + 'static> RetryPolicy for RetryCallback<T> {
-    fn should_retry(&self, error: &dyn Error) -> RetryDecision {
+impl<T: Fn(RetryInfo) -> RetryDecision + Send + Sync + 'static> RetryCallback<T> {
+    pub fn should_retry(&self, error: &dyn Error) -> RetryDecision {
         // Clone dữ liệu trước khi sử dụng trong closure
         let current_info = self.get_current_safe();
         let mut updated_info = current_info.clone();
@@ -794,7 +790,7 @@ impl<T: Fn(RetryInfo) -> RetryDecision + Send + Sync + 'static> RetryPolicy for
         (self.callback)(updated_info)
     }
     
-    fn reset(&self) {
+    pub fn reset(&self) {
         let new_info = RetryInfo {
             attempts: 0,
             last_error: None,
@@ -808,4 +804,49 @@ impl<T: Fn(RetryInfo) -> RetryDecision + Send + Sync + 'static> RetryPolicy for
             warn!("Lỗi khi reset retry info: {}", e);
         }
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> ExponentialRetryPolicy {
+        ExponentialRetryPolicy::new(RetryPolicyConfig::default(), GasBoostConfig::default())
+    }
+
+    #[test]
+    fn test_decide_nonce_and_fee_errors() {
+        let policy = policy();
+        assert_eq!(policy.decide(0, &ChainError::NonceTooLow("nonce too low".into())), RetryAction::BumpNonce);
+        assert_eq!(policy.decide(0, &ChainError::AlreadyKnown("already known".into())), RetryAction::WaitForPending);
+
+        // Tăng phí theo initial_boost_factor rồi step_factor cho mỗi lần thử
+        let underpriced = ChainError::ReplacementUnderpriced("replacement transaction underpriced".into());
+        assert_eq!(policy.decide(0, &underpriced), RetryAction::BumpFees { percent: 20 });
+        assert_eq!(policy.decide(1, &underpriced), RetryAction::BumpFees { percent: 40 });
+        assert_eq!(policy.decide(3, &underpriced), RetryAction::Abort);
+    }
+
+    #[test]
+    fn test_decide_endpoint_and_fatal_errors() {
+        let policy = policy();
+        let limited = ChainError::RateLimitExceeded("429 Too Many Requests, retry after 2s".into());
+        assert_eq!(policy.decide(0, &limited), RetryAction::SwitchEndpoint { delay: Duration::from_secs(2) });
+        assert_eq!(
+            policy.decide(1, &ChainError::HeaderNotFound("header not found".into())),
+            RetryAction::SwitchEndpoint { delay: Duration::from_millis(2000) },
+        );
+        assert_eq!(policy.decide(0, &ChainError::InsufficientFunds("insufficient funds".into())), RetryAction::Abort);
+        assert_eq!(
+            policy.decide(0, &ChainError::ExecutionReverted { reason: Some("UniswapV2: K".into()), data: None }),
+            RetryAction::Abort,
+        );
+    }
+
+    #[test]
+    fn test_decide_error_classifies_provider_message() {
+        let policy = create_default_retry_policy();
+        let error = anyhow!("(code: -32000, message: nonce too low: next nonce 5, tx nonce 4, data: None)");
+        assert_eq!(policy.decide_error(0, error), RetryAction::BumpNonce);
+    }
+}
@@ -0,0 +1,405 @@
+// External imports
+use ethers::providers::{JsonRpcClient, JsonRpcError, ProviderError, RpcError};
+
+// Standard library imports
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+// Internal imports
+use crate::chain_adapters::rate_limiter;
+
+// Third party imports
+use async_trait::async_trait;
+use metrics::{counter, histogram};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, warn};
+
+/// Cấu hình gom request JSON-RPC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchConfig {
+    /// Cửa sổ gom request (ms); các request phát sinh trong cửa sổ được gửi chung một HTTP batch
+    pub window_ms: u64,
+    /// Số request tối đa trong một batch
+    pub max_batch_size: usize,
+    /// Thời gian chờ tối đa cho một HTTP batch (ms)
+    pub request_timeout_ms: u64,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            window_ms: 5,
+            max_batch_size: 100,
+            request_timeout_ms: 30_000,
+        }
+    }
+}
+
+/// Lỗi của transport gom request
+#[derive(Debug, Error)]
+pub enum BatchError {
+    /// Lỗi HTTP/kết nối
+    #[error("RPC transport error: {0}")]
+    Transport(String),
+    /// Node trả về lỗi JSON-RPC
+    #[error(transparent)]
+    JsonRpc(JsonRpcError),
+    /// Không decode được kết quả
+    #[error("Failed to decode RPC response: {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+impl RpcError for BatchError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            BatchError::JsonRpc(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            BatchError::Serde(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<BatchError> for ProviderError {
+    fn from(err: BatchError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(err))
+    }
+}
+
+/// Kết quả của một call, dùng chung cho mọi request đã được gộp
+#[derive(Debug, Clone)]
+enum CallFailure {
+    Transport(String),
+    Rpc(JsonRpcError),
+}
+
+type CallResult = Result<Value, CallFailure>;
+
+impl From<CallFailure> for BatchError {
+    fn from(failure: CallFailure) -> Self {
+        match failure {
+            CallFailure::Transport(message) => BatchError::Transport(message),
+            CallFailure::Rpc(err) => BatchError::JsonRpc(err),
+        }
+    }
+}
+
+/// Một call đang chờ được gửi
+#[derive(Debug)]
+struct PendingCall {
+    key: String,
+    method: String,
+    params: Value,
+}
+
+/// Khóa gộp request: cùng method và params (bao gồm block tag) thì dùng chung một kết quả
+fn coalesce_key(method: &str, params: &Value) -> String {
+    format!("{}:{}", method, params)
+}
+
+/// Tạo body cho HTTP batch; batch một phần tử được gửi như request đơn
+fn build_batch_body(calls: &[PendingCall]) -> Value {
+    let requests: Vec<Value> = calls
+        .iter()
+        .enumerate()
+        .map(|(id, call)| json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": call.method,
+            "params": call.params,
+        }))
+        .collect();
+
+    if requests.len() == 1 {
+        requests.into_iter().next().unwrap_or(Value::Null)
+    } else {
+        Value::Array(requests)
+    }
+}
+
+/// Response của một request trong batch
+#[derive(Debug, Deserialize)]
+struct RawResponse {
+    id: Value,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<JsonRpcError>,
+}
+
+impl RawResponse {
+    fn into_result(self) -> CallResult {
+        match self.error {
+            Some(err) => Err(CallFailure::Rpc(err)),
+            None => Ok(self.result.unwrap_or(Value::Null)),
+        }
+    }
+}
+
+/// Tách response của batch theo id; `None` nếu node không hỗ trợ batch (không trả về mảng)
+fn parse_batch_response(body: Value, count: usize) -> Option<Vec<CallResult>> {
+    let responses: Vec<RawResponse> = match body {
+        Value::Array(_) => serde_json::from_value(body).ok()?,
+        single if count == 1 => vec![serde_json::from_value(single).ok()?],
+        _ => return None,
+    };
+
+    let mut by_id: HashMap<u64, CallResult> = responses
+        .into_iter()
+        .filter_map(|response| response.id.as_u64().map(|id| (id, response.into_result())))
+        .collect();
+
+    Some(
+        (0..count as u64)
+            .map(|id| {
+                by_id
+                    .remove(&id)
+                    .unwrap_or_else(|| Err(CallFailure::Transport(format!("missing response for request {}", id))))
+            })
+            .collect(),
+    )
+}
+
+/// Transport HTTP tự gom các request JSON-RPC phát sinh trong một cửa sổ ngắn thành một batch,
+/// và gộp các request giống hệt nhau đang chờ kết quả thành một request duy nhất.
+/// Phải được tạo bên trong tokio runtime.
+#[derive(Debug, Clone)]
+pub struct BatchingHttp {
+    url: String,
+    sender: mpsc::UnboundedSender<PendingCall>,
+    inflight: Arc<Mutex<HashMap<String, Vec<oneshot::Sender<CallResult>>>>>,
+}
+
+impl BatchingHttp {
+    /// Tạo transport và khởi chạy worker gom request
+    pub fn new(url: &str, config: BatchConfig) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let inflight = Arc::new(Mutex::new(HashMap::new()));
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build()
+            .unwrap_or_default();
+
+        tokio::spawn(Self::run(url.to_string(), config, client, receiver, inflight.clone()));
+
+        Self {
+            url: url.to_string(),
+            sender,
+            inflight,
+        }
+    }
+
+    /// URL của endpoint
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Worker: gom call trong cửa sổ rồi gửi batch (mỗi batch gửi trong task riêng)
+    async fn run(
+        url: String,
+        config: BatchConfig,
+        client: reqwest::Client,
+        mut receiver: mpsc::UnboundedReceiver<PendingCall>,
+        inflight: Arc<Mutex<HashMap<String, Vec<oneshot::Sender<CallResult>>>>>,
+    ) {
+        let window = Duration::from_millis(config.window_ms);
+        let max_batch_size = config.max_batch_size.max(1);
+
+        while let Some(first) = receiver.recv().await {
+            let mut batch = vec![first];
+            let deadline = tokio::time::Instant::now() + window;
+
+            while batch.len() < max_batch_size {
+                match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                    Ok(Some(call)) => batch.push(call),
+                    _ => break,
+                }
+            }
+
+            tokio::spawn(Self::send_batch(url.clone(), client.clone(), batch, inflight.clone()));
+        }
+    }
+
+    /// Gửi một batch và trả kết quả cho mọi request đang chờ
+    async fn send_batch(
+        url: String,
+        client: reqwest::Client,
+        batch: Vec<PendingCall>,
+        inflight: Arc<Mutex<HashMap<String, Vec<oneshot::Sender<CallResult>>>>>,
+    ) {
+        histogram!("rpc_batch_size", batch.len() as f64, "url" => url.clone());
+
+        let results = match Self::post(&client, &url, &build_batch_body(&batch)).await {
+            Ok(body) => match parse_batch_response(body, batch.len()) {
+                Some(results) => results,
+                None => {
+                    // Node không hỗ trợ batch, gửi lần lượt từng request
+                    warn!("RPC endpoint {} rejected batch of {} requests, sending individually", url, batch.len());
+                    let mut results = Vec::with_capacity(batch.len());
+                    for call in &batch {
+                        let body = build_batch_body(std::slice::from_ref(call));
+                        results.push(match Self::post(&client, &url, &body).await {
+                            Ok(body) => parse_batch_response(body, 1)
+                                .and_then(|mut single| single.pop())
+                                .unwrap_or_else(|| Err(CallFailure::Transport("invalid JSON-RPC response".to_string()))),
+                            Err(failure) => Err(failure),
+                        });
+                    }
+                    results
+                }
+            },
+            Err(failure) => vec![Err(failure); batch.len()],
+        };
+
+        for (call, result) in batch.into_iter().zip(results) {
+            let waiters = inflight
+                .lock()
+                .map(|mut map| map.remove(&call.key).unwrap_or_default())
+                .unwrap_or_default();
+            for waiter in waiters {
+                let _ = waiter.send(result.clone());
+            }
+        }
+    }
+
+    /// Gửi HTTP POST và đọc JSON response
+    async fn post(client: &reqwest::Client, url: &str, body: &Value) -> Result<Value, CallFailure> {
+        let response = client
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| CallFailure::Transport(e.to_string()))?;
+
+        // Node trả 429: báo lỗi kèm Retry-After để pool đưa endpoint vào backoff
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs() as i64;
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| rate_limiter::parse_retry_after(value, now));
+            return Err(CallFailure::Transport(match retry_after {
+                Some(wait) => format!("HTTP 429 Too Many Requests, retry after {}ms", wait.as_millis()),
+                None => "HTTP 429 Too Many Requests".to_string(),
+            }));
+        }
+
+        response
+            .json::<Value>()
+            .await
+            .map_err(|e| CallFailure::Transport(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for BatchingHttp {
+    type Error = BatchError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(params)?;
+        let key = coalesce_key(method, &params);
+        let (reply, response) = oneshot::channel();
+
+        let is_new = {
+            let mut inflight = self
+                .inflight
+                .lock()
+                .map_err(|e| BatchError::Transport(format!("inflight lock poisoned: {}", e)))?;
+            let waiters = inflight.entry(key.clone()).or_default();
+            waiters.push(reply);
+            waiters.len() == 1
+        };
+
+        if is_new {
+            self.sender
+                .send(PendingCall {
+                    key,
+                    method: method.to_string(),
+                    params,
+                })
+                .map_err(|_| BatchError::Transport("batch worker stopped".to_string()))?;
+        } else {
+            debug!("Coalesced in-flight {} request", method);
+            counter!("rpc_coalesced_requests", 1, "method" => method.to_string());
+        }
+
+        let value = response
+            .await
+            .map_err(|_| BatchError::Transport("batch worker dropped request".to_string()))??;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(method: &str, params: Value) -> PendingCall {
+        PendingCall {
+            key: coalesce_key(method, &params),
+            method: method.to_string(),
+            params,
+        }
+    }
+
+    #[test]
+    fn test_coalesce_key_includes_block() {
+        let latest = json!([{"to": "0x01", "data": "0x02"}, "latest"]);
+        let pinned = json!([{"to": "0x01", "data": "0x02"}, "0x10"]);
+        assert_eq!(coalesce_key("eth_call", &latest), coalesce_key("eth_call", &latest.clone()));
+        assert_ne!(coalesce_key("eth_call", &latest), coalesce_key("eth_call", &pinned));
+        assert_ne!(coalesce_key("eth_call", &latest), coalesce_key("eth_estimateGas", &latest));
+    }
+
+    #[test]
+    fn test_build_batch_body() {
+        let single = build_batch_body(&[call("eth_blockNumber", json!([]))]);
+        assert_eq!(single["method"], "eth_blockNumber");
+        assert_eq!(single["id"], 0);
+
+        let batch = build_batch_body(&[call("eth_blockNumber", json!([])), call("eth_chainId", json!([]))]);
+        let requests = batch.as_array().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1]["id"], 1);
+        assert_eq!(requests[1]["method"], "eth_chainId");
+    }
+
+    #[test]
+    fn test_parse_batch_response_out_of_order() {
+        let body = json!([
+            {"jsonrpc": "2.0", "id": 1, "error": {"code": -32000, "message": "execution reverted"}},
+            {"jsonrpc": "2.0", "id": 0, "result": "0x10"},
+        ]);
+        let results = parse_batch_response(body, 3).unwrap();
+        assert_eq!(results[0].as_ref().unwrap(), &json!("0x10"));
+        assert!(matches!(&results[1], Err(CallFailure::Rpc(err)) if err.code == -32000));
+        assert!(matches!(&results[2], Err(CallFailure::Transport(_))));
+
+        // Node không hỗ trợ batch trả về một object lỗi duy nhất
+        let rejected = json!({"jsonrpc": "2.0", "id": null, "error": {"code": -32600, "message": "batch not supported"}});
+        assert!(parse_batch_response(rejected, 2).is_none());
+
+        let single = json!({"jsonrpc": "2.0", "id": 0, "result": "0x1"});
+        assert_eq!(parse_batch_response(single, 1).unwrap()[0].as_ref().unwrap(), &json!("0x1"));
+    }
+}
@@ -0,0 +1,335 @@
+// External imports
+use ethers::{
+    abi::{self, ParamType, Token},
+    providers::{JsonRpcError, ProviderError, RpcError},
+    types::Bytes,
+};
+
+// Third party imports
+use serde_json::Value;
+
+// Internal imports
+use crate::chain_adapters::{interfaces::ChainError, rate_limiter::detect_rate_limit};
+
+/// Mã lỗi `execution reverted` (geth, erigon, BSC, nethermind)
+pub const EXECUTION_REVERTED_CODE: i64 = 3;
+
+/// Mã lỗi vượt giới hạn request (Infura, Alchemy và phần lớn provider trả phí)
+pub const LIMIT_EXCEEDED_CODE: i64 = -32005;
+
+/// Mã lỗi method không tồn tại
+pub const METHOD_NOT_FOUND_CODE: i64 = -32601;
+
+/// Timeout mặc định khi lỗi không cho biết thời gian đã chờ (ms)
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+
+/// Selector của `Error(string)` và `Panic(uint256)` trong dữ liệu revert
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Loại lỗi nhận diện theo thông điệp của node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MessageKind {
+    NonceTooLow,
+    NonceTooHigh,
+    AlreadyKnown,
+    ReplacementUnderpriced,
+    Underpriced,
+    FeeCapTooLow,
+    InsufficientFunds,
+    IntrinsicGasTooLow,
+    GasCap,
+    GasRequiredExceeds,
+    HeaderNotFound,
+    Reverted,
+}
+
+/// Thông điệp lỗi (chữ thường) của geth, erigon, BSC, nethermind, besu theo loại.
+/// Thứ tự quan trọng: mẫu cụ thể phải đứng trước mẫu tổng quát chứa nó
+const MESSAGE_PATTERNS: &[(&str, MessageKind)] = &[
+    ("nonce too low", MessageKind::NonceTooLow),
+    ("oldnonce", MessageKind::NonceTooLow),
+    ("nonce is too low", MessageKind::NonceTooLow),
+    ("nonce too high", MessageKind::NonceTooHigh),
+    ("nonce gap", MessageKind::NonceTooHigh),
+    ("already known", MessageKind::AlreadyKnown),
+    ("known transaction", MessageKind::AlreadyKnown),
+    ("alreadyknown", MessageKind::AlreadyKnown),
+    ("already imported", MessageKind::AlreadyKnown),
+    ("replacement transaction underpriced", MessageKind::ReplacementUnderpriced),
+    ("replacement fee too low", MessageKind::ReplacementUnderpriced),
+    ("replacement underpriced", MessageKind::ReplacementUnderpriced),
+    ("max fee per gas less than block base fee", MessageKind::FeeCapTooLow),
+    ("fee cap less than block base fee", MessageKind::FeeCapTooLow),
+    ("feecap too low", MessageKind::FeeCapTooLow),
+    ("miner premium is negative", MessageKind::FeeCapTooLow),
+    ("transaction underpriced", MessageKind::Underpriced),
+    ("gas price too low", MessageKind::Underpriced),
+    ("feetoolow", MessageKind::Underpriced),
+    ("insufficient funds", MessageKind::InsufficientFunds),
+    ("insufficientfunds", MessageKind::InsufficientFunds),
+    ("upfront cost exceeds account balance", MessageKind::InsufficientFunds),
+    ("intrinsic gas too low", MessageKind::IntrinsicGasTooLow),
+    ("intrinsic gas exceeds gas limit", MessageKind::IntrinsicGasTooLow),
+    ("gaslimitbelowintrinsicgas", MessageKind::IntrinsicGasTooLow),
+    ("exceeds the configured cap", MessageKind::GasCap),
+    ("gas required exceeds allowance", MessageKind::GasRequiredExceeds),
+    ("exceeds block gas limit", MessageKind::GasRequiredExceeds),
+    ("header not found", MessageKind::HeaderNotFound),
+    ("unknown block", MessageKind::HeaderNotFound),
+    ("missing trie node", MessageKind::HeaderNotFound),
+    ("execution reverted", MessageKind::Reverted),
+    ("vm execution error", MessageKind::Reverted),
+    ("reverted with data", MessageKind::Reverted),
+];
+
+/// Thông điệp lỗi kết nối/timeout của HTTP client và WebSocket
+const CONNECTION_PATTERNS: &[&str] = &[
+    "error sending request",
+    "connection refused",
+    "connection reset",
+    "connection closed",
+    "broken pipe",
+    "dns error",
+    "tcp connect error",
+    "502 bad gateway",
+    "503 service unavailable",
+    "504 gateway timeout",
+];
+const TIMEOUT_PATTERNS: &[&str] = &["timed out", "timeout", "deadline has elapsed"];
+
+/// Phân loại lỗi JSON-RPC theo mã và thông điệp của node.
+/// Trả về `None` nếu lỗi không thuộc loại đã biết
+pub fn classify_rpc_error(code: Option<i64>, message: &str, data: Option<&Value>) -> Option<ChainError> {
+    let lower = message.to_lowercase();
+
+    if code == Some(LIMIT_EXCEEDED_CODE) || code == Some(429) {
+        return Some(ChainError::RateLimitExceeded(message.to_string()));
+    }
+
+    let kind = MESSAGE_PATTERNS.iter()
+        .find(|(pattern, _)| lower.contains(pattern))
+        .map(|(_, kind)| *kind)
+        .or_else(|| (code == Some(EXECUTION_REVERTED_CODE)).then_some(MessageKind::Reverted));
+
+    if let Some(kind) = kind {
+        let message = message.to_string();
+        return Some(match kind {
+            MessageKind::NonceTooLow => ChainError::NonceTooLow(message),
+            MessageKind::NonceTooHigh => ChainError::NonceTooHigh(message),
+            MessageKind::AlreadyKnown => ChainError::AlreadyKnown(message),
+            MessageKind::ReplacementUnderpriced => ChainError::ReplacementUnderpriced(message),
+            MessageKind::Underpriced => ChainError::Underpriced,
+            MessageKind::FeeCapTooLow => ChainError::FeeCapTooLow(message),
+            MessageKind::InsufficientFunds => ChainError::InsufficientFunds(message),
+            MessageKind::IntrinsicGasTooLow => ChainError::IntrinsicGasTooLow(message),
+            MessageKind::GasCap => ChainError::GasCap,
+            MessageKind::GasRequiredExceeds => ChainError::InsufficientGas(message),
+            MessageKind::HeaderNotFound => ChainError::HeaderNotFound(message),
+            MessageKind::Reverted => {
+                let data = revert_data(data).or_else(|| revert_data_in_message(&message));
+                let reason = data.as_ref()
+                    .and_then(|data| decode_revert_reason(data))
+                    .or_else(|| reason_in_message(&message));
+                ChainError::ExecutionReverted { reason, data }
+            },
+        });
+    }
+
+    // Kiểm tra sau các mẫu trên để chuỗi "429" trong dữ liệu revert không bị nhận nhầm
+    if detect_rate_limit(&lower).is_some() {
+        return Some(ChainError::RateLimitExceeded(message.to_string()));
+    }
+    if code == Some(METHOD_NOT_FOUND_CODE) {
+        return Some(ChainError::UnsupportedOperation(message.to_string()));
+    }
+    if TIMEOUT_PATTERNS.iter().any(|pattern| lower.contains(pattern)) {
+        return Some(ChainError::TimeoutError(DEFAULT_TIMEOUT_MS));
+    }
+    if CONNECTION_PATTERNS.iter().any(|pattern| lower.contains(pattern)) {
+        return Some(ChainError::ConnectionError(message.to_string()));
+    }
+    None
+}
+
+/// Phân loại phản hồi lỗi JSON-RPC
+pub fn classify_json_rpc_error(error: &JsonRpcError) -> Option<ChainError> {
+    classify_rpc_error(Some(error.code), &error.message, error.data.as_ref())
+}
+
+/// Phân loại lỗi của ethers provider
+pub fn classify_provider_error(error: &ProviderError) -> Option<ChainError> {
+    if let Some(response) = error.as_error_response() {
+        return classify_json_rpc_error(response);
+    }
+    match error {
+        ProviderError::HTTPError(e) if e.is_timeout() => Some(ChainError::TimeoutError(DEFAULT_TIMEOUT_MS)),
+        ProviderError::HTTPError(e) if e.status().map(|status| status.as_u16()) == Some(429) => {
+            Some(ChainError::RateLimitExceeded(e.to_string()))
+        },
+        ProviderError::HTTPError(e) => Some(ChainError::ConnectionError(e.to_string())),
+        _ => classify_message(&error.to_string()),
+    }
+}
+
+/// Phân loại lỗi đã bọc trong anyhow: tìm lỗi provider/JSON-RPC gốc trong chuỗi
+/// nguyên nhân, nếu không có thì phân tích thông điệp
+pub fn classify_anyhow(error: &anyhow::Error) -> Option<ChainError> {
+    for cause in error.chain() {
+        if let Some(chain_error) = cause.downcast_ref::<ChainError>() {
+            return Some(chain_error.clone());
+        }
+        if let Some(provider_error) = cause.downcast_ref::<ProviderError>() {
+            if let Some(classified) = classify_provider_error(provider_error) {
+                return Some(classified);
+            }
+        }
+        if let Some(response) = cause.downcast_ref::<JsonRpcError>() {
+            if let Some(classified) = classify_json_rpc_error(response) {
+                return Some(classified);
+            }
+        }
+    }
+    classify_message(&format!("{:#}", error))
+}
+
+/// Phân loại từ thông điệp lỗi dạng chuỗi. Hiểu được định dạng
+/// `(code: -32000, message: ..., data: ...)` mà ethers dùng khi hiển thị `JsonRpcError`
+pub fn classify_message(message: &str) -> Option<ChainError> {
+    let code = message.find("code: ").and_then(|position| {
+        let rest = &message[position + "code: ".len()..];
+        let end = rest.find(|c: char| c != '-' && !c.is_ascii_digit()).unwrap_or(rest.len());
+        rest[..end].parse::<i64>().ok()
+    });
+    classify_rpc_error(code, message, None)
+}
+
+/// Dữ liệu revert trong trường `data` của lỗi (chuỗi hex hoặc object `{"data": "0x..."}`)
+fn revert_data(data: Option<&Value>) -> Option<Bytes> {
+    match data? {
+        Value::String(hex) => parse_hex(hex),
+        Value::Object(object) => revert_data(object.get("data")),
+        _ => None,
+    }
+}
+
+/// Dữ liệu revert nằm trong thông điệp, VD: "reverted with data: 0x08c379a0..."
+fn revert_data_in_message(message: &str) -> Option<Bytes> {
+    let position = message.find("data: 0x").or_else(|| message.find("data: \"0x"))?;
+    let rest = message[position + "data: ".len()..].trim_start_matches('"');
+    let end = rest.find(|c: char| !c.is_ascii_hexdigit() && c != 'x').unwrap_or(rest.len());
+    parse_hex(&rest[..end])
+}
+
+/// Lý do revert dạng chữ sau "execution reverted: "
+fn reason_in_message(message: &str) -> Option<String> {
+    let position = message.find("execution reverted: ")?;
+    let rest = &message[position + "execution reverted: ".len()..];
+    let end = rest.find([',', ')', '"']).unwrap_or(rest.len());
+    let reason = rest[..end].trim();
+    (!reason.is_empty()).then(|| reason.to_string())
+}
+
+fn parse_hex(hex: &str) -> Option<Bytes> {
+    let hex = hex.strip_prefix("0x")?;
+    let bytes = hex::decode(hex).ok()?;
+    (!bytes.is_empty()).then(|| Bytes::from(bytes))
+}
+
+/// Giải mã lý do revert: `Error(string)` hoặc mã `Panic(uint256)` của Solidity
+pub fn decode_revert_reason(data: &[u8]) -> Option<String> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (selector, payload) = data.split_at(4);
+    if selector == ERROR_SELECTOR {
+        match abi::decode(&[ParamType::String], payload).ok()?.into_iter().next()? {
+            Token::String(reason) => Some(reason),
+            _ => None,
+        }
+    } else if selector == PANIC_SELECTOR {
+        match abi::decode(&[ParamType::Uint(256)], payload).ok()?.into_iter().next()? {
+            Token::Uint(code) => Some(format!("panic code {:#x}", code)),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rpc(code: i64, message: &str) -> ChainError {
+        classify_rpc_error(Some(code), message, None).unwrap()
+    }
+
+    #[test]
+    fn test_classify_client_messages() {
+        // geth / BSC
+        assert!(matches!(rpc(-32000, "nonce too low"), ChainError::NonceTooLow(_)));
+        assert!(matches!(rpc(-32000, "replacement transaction underpriced"), ChainError::ReplacementUnderpriced(_)));
+        assert!(matches!(rpc(-32000, "transaction underpriced"), ChainError::Underpriced));
+        assert!(matches!(
+            rpc(-32000, "insufficient funds for gas * price + value: balance 0, tx cost 100"),
+            ChainError::InsufficientFunds(_)
+        ));
+        assert!(matches!(rpc(-32000, "intrinsic gas too low: have 20000, want 21000"), ChainError::IntrinsicGasTooLow(_)));
+        assert!(matches!(rpc(-32000, "header not found"), ChainError::HeaderNotFound(_)));
+        assert!(matches!(rpc(-32000, "already known"), ChainError::AlreadyKnown(_)));
+        assert!(matches!(
+            rpc(-32000, "max fee per gas less than block base fee: address 0x1, maxFeePerGas: 1 baseFee: 7"),
+            ChainError::FeeCapTooLow(_)
+        ));
+        // erigon / nethermind
+        assert!(matches!(rpc(-32000, "replacement fee too low"), ChainError::ReplacementUnderpriced(_)));
+        assert!(matches!(rpc(-32010, "OldNonce"), ChainError::NonceTooLow(_)));
+        assert!(matches!(rpc(-32010, "FeeTooLow"), ChainError::Underpriced));
+        assert!(matches!(rpc(-32000, "known transaction: 0xabc"), ChainError::AlreadyKnown(_)));
+        // provider
+        assert!(matches!(rpc(-32005, "limit exceeded"), ChainError::RateLimitExceeded(_)));
+        assert!(matches!(rpc(429, "Your app has exceeded its compute units per second capacity"), ChainError::RateLimitExceeded(_)));
+        assert!(matches!(rpc(-32601, "the method eth_foo does not exist"), ChainError::UnsupportedOperation(_)));
+        assert!(classify_rpc_error(Some(-32602), "invalid argument 0", None).is_none());
+    }
+
+    #[test]
+    fn test_execution_reverted_with_data() {
+        let mut data = ERROR_SELECTOR.to_vec();
+        data.extend(abi::encode(&[Token::String("UniswapV2: K".to_string())]));
+        let hex = format!("0x{}", hex::encode(&data));
+
+        match classify_rpc_error(Some(3), "execution reverted: UniswapV2: K", Some(&json!(hex))) {
+            Some(ChainError::ExecutionReverted { reason, data: Some(bytes) }) => {
+                assert_eq!(reason.as_deref(), Some("UniswapV2: K"));
+                assert_eq!(bytes.to_vec(), data);
+            },
+            other => panic!("{:?}", other),
+        }
+
+        // Lỗi contract của ethers chỉ có dữ liệu hex trong thông điệp
+        let message = format!("Contract call reverted with data: {}", hex);
+        match classify_message(&message) {
+            Some(ChainError::ExecutionReverted { reason, .. }) => assert_eq!(reason.as_deref(), Some("UniswapV2: K")),
+            other => panic!("{:?}", other),
+        }
+
+        let mut panic_data = PANIC_SELECTOR.to_vec();
+        panic_data.extend(abi::encode(&[Token::Uint(0x11.into())]));
+        assert_eq!(decode_revert_reason(&panic_data).as_deref(), Some("panic code 0x11"));
+    }
+
+    #[test]
+    fn test_classify_anyhow_chain() {
+        let response = JsonRpcError { code: -32000, message: "nonce too low".to_string(), data: None };
+        let error = anyhow::Error::new(response).context("Lỗi khi gửi giao dịch swap");
+        assert!(matches!(classify_anyhow(&error), Some(ChainError::NonceTooLow(_))));
+
+        // Thông điệp đã định dạng theo kiểu ethers
+        let error = anyhow::anyhow!("(code: -32000, message: header not found, data: None)");
+        assert!(matches!(classify_anyhow(&error), Some(ChainError::HeaderNotFound(_))));
+        assert!(classify_anyhow(&anyhow::anyhow!("Địa chỉ token không hợp lệ")).is_none());
+    }
+}
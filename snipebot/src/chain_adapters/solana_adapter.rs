@@ -0,0 +1,870 @@
+// External imports
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+
+// Standard library imports
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+// Third party imports
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use tracing::{debug, info, warn};
+
+// Internal imports
+use diamond_wallet::SolanaKeypair;
+use crate::chain_adapters::{
+    chain_registry,
+    solana_dex::{
+        mint_decimals, raydium_swap_base_in, token_account_amount, RaydiumAmmState, SerumMarketKeys,
+        SolanaDex, WhirlpoolState,
+    },
+    solana_tx::{
+        associated_token_address, close_token_account, create_associated_token_account_idempotent,
+        set_compute_unit_limit, set_compute_unit_price, sync_native, system_transfer, Message, Pubkey,
+        SignedTransaction, WRAPPED_SOL_MINT,
+    },
+    trading_adapter::{self, ChainFamily, SwapOutcome, SwapRequest, TradeQuote, TradingAdapter, NATIVE_TOKEN},
+};
+
+/// Biến môi trường chọn pubkey trong kho keypair Solana mã hóa của thư mục ví
+/// (bỏ trống khi kho chỉ có một keypair)
+pub const SOLANA_WALLET_ENV: &str = "SOLANA_WALLET";
+
+/// Keypair ký giao dịch cho các adapter Solana, giải mã từ kho ví khi khởi động
+static SOLANA_SIGNER: Lazy<RwLock<Option<Arc<SolanaKeypair>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Ký hiệu token gốc của Solana
+pub const SOL_SYMBOL: &str = "SOL";
+
+/// Số lamports trong 1 SOL
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// Cấu hình adapter Solana
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolanaConfig {
+    /// Tên chain (dùng làm key trong registry adapter giao dịch)
+    pub name: String,
+    /// RPC endpoint HTTP
+    pub rpc_url: String,
+    /// Commitment dùng khi đọc và xác nhận giao dịch (processed/confirmed/finalized)
+    pub commitment: String,
+    /// Giới hạn compute unit cho giao dịch swap
+    pub compute_unit_limit: u32,
+    /// Priority fee cố định (micro-lamports/CU). `None` lấy theo getRecentPrioritizationFees
+    pub priority_fee_micro_lamports: Option<u64>,
+    /// Thời gian tối đa chờ xác nhận giao dịch (ms)
+    pub confirm_timeout_ms: u64,
+    /// Chu kỳ kiểm tra trạng thái giao dịch (ms)
+    pub confirm_poll_ms: u64,
+    /// Timeout của mỗi request RPC (ms)
+    pub request_timeout_ms: u64,
+}
+
+impl Default for SolanaConfig {
+    fn default() -> Self {
+        Self {
+            name: "Solana".to_string(),
+            rpc_url: "https://api.mainnet-beta.solana.com".to_string(),
+            commitment: "confirmed".to_string(),
+            compute_unit_limit: 200_000,
+            priority_fee_micro_lamports: None,
+            confirm_timeout_ms: 60_000,
+            confirm_poll_ms: 500,
+            request_timeout_ms: 30_000,
+        }
+    }
+}
+
+impl SolanaConfig {
+    /// Tạo cấu hình từ file cấu hình chain (chain_type = "non-evm")
+    pub fn from_chain_config(config: &chain_registry::ChainConfig) -> Result<Self> {
+        let rpc_url = config.primary_rpc_urls.iter()
+            .find(|url| url.starts_with("http"))
+            .cloned()
+            .ok_or_else(|| anyhow!("Chain {} không có RPC HTTP", config.name))?;
+        Ok(Self {
+            name: config.name.clone(),
+            rpc_url,
+            ..Self::default()
+        })
+    }
+}
+
+/// Chain trong file cấu hình có phải Solana hay không
+pub fn is_solana_chain(config: &chain_registry::ChainConfig) -> bool {
+    !config.is_evm() && config.native_token_symbol.eq_ignore_ascii_case(SOL_SYMBOL)
+}
+
+/// Tài khoản Solana đọc từ RPC
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolanaAccount {
+    /// Số dư (lamports)
+    pub lamports: u64,
+    /// Program sở hữu tài khoản
+    pub owner: Pubkey,
+    /// Dữ liệu tài khoản
+    pub data: Vec<u8>,
+}
+
+impl SolanaAccount {
+    /// Parse tài khoản từ kết quả RPC (encoding base64)
+    fn from_rpc(value: &Value) -> Result<Option<Self>> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        let data = value["data"][0].as_str()
+            .ok_or_else(|| anyhow!("Tài khoản thiếu dữ liệu base64"))?;
+        Ok(Some(Self {
+            lamports: value["lamports"].as_u64().ok_or_else(|| anyhow!("Tài khoản thiếu lamports"))?,
+            owner: Pubkey::from_str(value["owner"].as_str().unwrap_or_default())?,
+            data: BASE64.decode(data).context("Dữ liệu tài khoản không phải base64")?,
+        }))
+    }
+}
+
+/// Trạng thái giao dịch trả về từ getSignatureStatuses
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignatureStatus {
+    /// Slot chứa giao dịch
+    pub slot: u64,
+    /// Mức xác nhận hiện tại (processed/confirmed/finalized)
+    pub confirmation_status: Option<String>,
+    /// Lỗi thực thi (nếu có)
+    pub err: Option<Value>,
+}
+
+/// Thứ tự các mức commitment để so sánh
+fn commitment_rank(commitment: &str) -> u8 {
+    match commitment {
+        "finalized" => 2,
+        "confirmed" => 1,
+        _ => 0,
+    }
+}
+
+/// Client JSON-RPC tối giản cho Solana
+#[derive(Debug)]
+pub struct SolanaRpcClient {
+    url: String,
+    client: reqwest::Client,
+    commitment: String,
+    next_id: AtomicU64,
+}
+
+impl SolanaRpcClient {
+    /// Tạo client mới
+    pub fn new(url: &str, commitment: &str, timeout: Duration) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .context("Không thể tạo HTTP client cho Solana RPC")?;
+        Ok(Self {
+            url: url.to_string(),
+            client,
+            commitment: commitment.to_string(),
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    /// Gửi một lời gọi JSON-RPC và trả về trường `result`
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        let response: Value = self.client
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("Không thể gọi Solana RPC {}", method))?
+            .json()
+            .await
+            .with_context(|| format!("Phản hồi {} không phải JSON", method))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("Solana RPC {} lỗi: {}", method, error));
+        }
+        response.get("result")
+            .cloned()
+            .ok_or_else(|| anyhow!("Phản hồi {} không có result", method))
+    }
+
+    /// Slot hiện tại
+    pub async fn get_slot(&self) -> Result<u64> {
+        self.call("getSlot", json!([{"commitment": self.commitment}])).await?
+            .as_u64()
+            .ok_or_else(|| anyhow!("getSlot trả về giá trị không hợp lệ"))
+    }
+
+    /// Phiên bản node
+    pub async fn get_version(&self) -> Result<String> {
+        let result = self.call("getVersion", json!([])).await?;
+        Ok(result["solana-core"].as_str().unwrap_or("unknown").to_string())
+    }
+
+    /// Số dư lamports của tài khoản
+    pub async fn get_balance(&self, address: &Pubkey) -> Result<u64> {
+        self.call("getBalance", json!([address.to_string(), {"commitment": self.commitment}])).await?["value"]
+            .as_u64()
+            .ok_or_else(|| anyhow!("getBalance trả về giá trị không hợp lệ"))
+    }
+
+    /// Đọc một tài khoản
+    pub async fn get_account(&self, address: &Pubkey) -> Result<Option<SolanaAccount>> {
+        let result = self.call(
+            "getAccountInfo",
+            json!([address.to_string(), {"encoding": "base64", "commitment": self.commitment}]),
+        ).await?;
+        SolanaAccount::from_rpc(&result["value"])
+    }
+
+    /// Đọc nhiều tài khoản trong một lời gọi
+    pub async fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<SolanaAccount>>> {
+        let keys: Vec<String> = addresses.iter().map(|address| address.to_string()).collect();
+        let result = self.call(
+            "getMultipleAccounts",
+            json!([keys, {"encoding": "base64", "commitment": self.commitment}]),
+        ).await?;
+        let values = result["value"].as_array()
+            .ok_or_else(|| anyhow!("getMultipleAccounts trả về giá trị không hợp lệ"))?;
+        if values.len() != addresses.len() {
+            return Err(anyhow!("getMultipleAccounts trả về {} tài khoản, cần {}", values.len(), addresses.len()));
+        }
+        values.iter().map(SolanaAccount::from_rpc).collect()
+    }
+
+    /// Các tài khoản token của `owner` cho `mint`
+    pub async fn get_token_accounts_by_owner(&self, owner: &Pubkey, mint: &Pubkey) -> Result<Vec<(Pubkey, SolanaAccount)>> {
+        let result = self.call(
+            "getTokenAccountsByOwner",
+            json!([
+                owner.to_string(),
+                {"mint": mint.to_string()},
+                {"encoding": "base64", "commitment": self.commitment}
+            ]),
+        ).await?;
+        let mut accounts = Vec::new();
+        for entry in result["value"].as_array().cloned().unwrap_or_default() {
+            let pubkey = Pubkey::from_str(entry["pubkey"].as_str().unwrap_or_default())?;
+            if let Some(account) = SolanaAccount::from_rpc(&entry["account"])? {
+                accounts.push((pubkey, account));
+            }
+        }
+        Ok(accounts)
+    }
+
+    /// Blockhash mới nhất dùng để ký giao dịch
+    pub async fn get_latest_blockhash(&self) -> Result<[u8; 32]> {
+        let result = self.call("getLatestBlockhash", json!([{"commitment": self.commitment}])).await?;
+        let blockhash = result["value"]["blockhash"].as_str()
+            .ok_or_else(|| anyhow!("getLatestBlockhash không có blockhash"))?;
+        Ok(Pubkey::from_str(blockhash).context("Blockhash không hợp lệ")?.0)
+    }
+
+    /// Gửi giao dịch đã ký, trả về chữ ký
+    pub async fn send_transaction(&self, transaction: &[u8]) -> Result<String> {
+        let result = self.call(
+            "sendTransaction",
+            json!([BASE64.encode(transaction), {"encoding": "base64", "preflightCommitment": self.commitment}]),
+        ).await?;
+        result.as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("sendTransaction trả về giá trị không hợp lệ"))
+    }
+
+    /// Trạng thái của một giao dịch
+    pub async fn get_signature_status(&self, signature: &str) -> Result<Option<SignatureStatus>> {
+        let result = self.call("getSignatureStatuses", json!([[signature]])).await?;
+        let status = &result["value"][0];
+        if status.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(SignatureStatus {
+            slot: status["slot"].as_u64().unwrap_or_default(),
+            confirmation_status: status["confirmationStatus"].as_str().map(str::to_string),
+            err: status.get("err").filter(|err| !err.is_null()).cloned(),
+        }))
+    }
+
+    /// Trung vị priority fee gần đây (micro-lamports/CU) cho các tài khoản ghi
+    pub async fn get_recent_prioritization_fee(&self, accounts: &[Pubkey]) -> Result<u64> {
+        let keys: Vec<String> = accounts.iter().map(|address| address.to_string()).collect();
+        let result = self.call("getRecentPrioritizationFees", json!([keys])).await?;
+        let mut fees: Vec<u64> = result.as_array()
+            .map(|entries| entries.iter().filter_map(|entry| entry["prioritizationFee"].as_u64()).collect())
+            .unwrap_or_default();
+        if fees.is_empty() {
+            return Ok(0);
+        }
+        fees.sort_unstable();
+        Ok(fees[fees.len() / 2])
+    }
+}
+
+/// Giá của một pool DEX trên Solana
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolPrice {
+    /// Loại DEX
+    pub dex: SolanaDex,
+    /// Mint token base (token A của Whirlpool)
+    pub base_mint: Pubkey,
+    /// Mint token quote (token B của Whirlpool)
+    pub quote_mint: Pubkey,
+    /// Giá base tính theo quote (đã điều chỉnh decimals)
+    pub price: f64,
+}
+
+/// Trạng thái pool đã đọc từ chain, đủ để báo giá
+#[derive(Debug, Clone)]
+enum PoolState {
+    Raydium { amm: RaydiumAmmState, base_reserve: u64, quote_reserve: u64 },
+    Orca { pool: WhirlpoolState, decimals_a: u8, decimals_b: u8 },
+}
+
+impl PoolState {
+    /// Cặp mint (base, quote) của pool
+    fn mints(&self) -> (Pubkey, Pubkey) {
+        match self {
+            PoolState::Raydium { amm, .. } => (amm.base_mint, amm.quote_mint),
+            PoolState::Orca { pool, .. } => (pool.token_mint_a, pool.token_mint_b),
+        }
+    }
+
+    /// Giá hiện tại của pool
+    fn price(&self) -> PoolPrice {
+        let (base_mint, quote_mint) = self.mints();
+        let (dex, price) = match self {
+            PoolState::Raydium { amm, base_reserve, quote_reserve } => {
+                (SolanaDex::Raydium, amm.price(*base_reserve, *quote_reserve))
+            },
+            PoolState::Orca { pool, decimals_a, decimals_b } => {
+                (SolanaDex::Orca, pool.price(*decimals_a, *decimals_b))
+            },
+        };
+        PoolPrice { dex, base_mint, quote_mint, price }
+    }
+
+    /// Số lượng nhận được khi bán `amount_in` token `input_mint` vào pool
+    fn amount_out(&self, input_mint: &Pubkey, amount_in: u64) -> Result<u64> {
+        let (base_mint, quote_mint) = self.mints();
+        if *input_mint != base_mint && *input_mint != quote_mint {
+            return Err(anyhow!("Token {} không thuộc pool {}/{}", input_mint, base_mint, quote_mint));
+        }
+        let base_to_quote = *input_mint == base_mint;
+        match self {
+            PoolState::Raydium { amm, base_reserve, quote_reserve } => {
+                if base_to_quote {
+                    amm.amount_out(amount_in, *base_reserve, *quote_reserve)
+                } else {
+                    amm.amount_out(amount_in, *quote_reserve, *base_reserve)
+                }
+            },
+            PoolState::Orca { pool, .. } => Ok(pool.estimate_amount_out(amount_in, base_to_quote)),
+        }
+    }
+}
+
+/// Adapter Solana: đọc số dư SOL/SPL, giá pool Raydium/Orca, tạo và ký giao dịch swap
+#[derive(Debug)]
+pub struct SolanaAdapter {
+    config: SolanaConfig,
+    rpc: SolanaRpcClient,
+    keypair: Option<Arc<SolanaKeypair>>,
+}
+
+impl SolanaAdapter {
+    /// Tạo adapter chỉ đọc (chưa gắn keypair)
+    pub fn new(config: SolanaConfig) -> Result<Self> {
+        let rpc = SolanaRpcClient::new(
+            &config.rpc_url,
+            &config.commitment,
+            Duration::from_millis(config.request_timeout_ms),
+        )?;
+        Ok(Self { config, rpc, keypair: None })
+    }
+
+    /// Gắn keypair từ ví để ký giao dịch
+    pub fn with_keypair(mut self, keypair: Arc<SolanaKeypair>) -> Self {
+        self.keypair = Some(keypair);
+        self
+    }
+
+    /// Cấu hình adapter
+    pub fn config(&self) -> &SolanaConfig {
+        &self.config
+    }
+
+    /// Client RPC
+    pub fn rpc(&self) -> &SolanaRpcClient {
+        &self.rpc
+    }
+
+    /// Keypair đã gắn, báo lỗi nếu adapter chỉ đọc
+    fn require_keypair(&self) -> Result<&Arc<SolanaKeypair>> {
+        self.keypair.as_ref()
+            .ok_or_else(|| anyhow!("Adapter {} chưa gắn keypair Solana", self.config.name))
+    }
+
+    /// Đổi ký hiệu token trong `SwapRequest` sang mint (token gốc dùng wrapped SOL)
+    fn token_mint(token: &str) -> Result<Pubkey> {
+        if token == NATIVE_TOKEN {
+            return Ok(Pubkey::from_static(WRAPPED_SOL_MINT));
+        }
+        Pubkey::from_str(token)
+    }
+
+    /// Số dư SOL (lamports)
+    pub async fn get_sol_balance(&self, owner: &str) -> Result<u64> {
+        self.rpc.get_balance(&Pubkey::from_str(owner)?).await
+    }
+
+    /// Tổng số dư SPL token của `owner` trên mọi tài khoản token của `mint`
+    pub async fn get_spl_token_balance(&self, owner: &str, mint: &str) -> Result<u64> {
+        let accounts = self.rpc
+            .get_token_accounts_by_owner(&Pubkey::from_str(owner)?, &Pubkey::from_str(mint)?)
+            .await?;
+        accounts.iter().try_fold(0u64, |total, (_, account)| {
+            Ok(total.saturating_add(token_account_amount(&account.data)?))
+        })
+    }
+
+    /// Decimals của mint SPL
+    pub async fn get_token_decimals(&self, mint: &str) -> Result<u8> {
+        let account = self.rpc.get_account(&Pubkey::from_str(mint)?).await?
+            .ok_or_else(|| anyhow!("Không tìm thấy mint {}", mint))?;
+        mint_decimals(&account.data)
+    }
+
+    /// Đọc trạng thái pool, nhận diện Raydium/Orca qua program sở hữu
+    async fn load_pool(&self, pool: &Pubkey) -> Result<PoolState> {
+        let account = self.rpc.get_account(pool).await?
+            .ok_or_else(|| anyhow!("Không tìm thấy pool {}", pool))?;
+
+        match SolanaDex::from_owner(&account.owner) {
+            Some(SolanaDex::Raydium) => {
+                let amm = RaydiumAmmState::parse(&account.data)?;
+                let vaults = self.rpc.get_multiple_accounts(&[amm.base_vault, amm.quote_vault]).await?;
+                let amount = |vault: &Option<SolanaAccount>| match vault {
+                    Some(vault) => token_account_amount(&vault.data),
+                    None => Err(anyhow!("Không tìm thấy vault của pool {}", pool)),
+                };
+                let (base_reserve, quote_reserve) = amm.reserves(amount(&vaults[0])?, amount(&vaults[1])?);
+                Ok(PoolState::Raydium { amm, base_reserve, quote_reserve })
+            },
+            Some(SolanaDex::Orca) => {
+                let whirlpool = WhirlpoolState::parse(&account.data)?;
+                let mints = self.rpc
+                    .get_multiple_accounts(&[whirlpool.token_mint_a, whirlpool.token_mint_b])
+                    .await?;
+                let decimals = |mint: &Option<SolanaAccount>| match mint {
+                    Some(mint) => mint_decimals(&mint.data),
+                    None => Err(anyhow!("Không tìm thấy mint của pool {}", pool)),
+                };
+                Ok(PoolState::Orca {
+                    decimals_a: decimals(&mints[0])?,
+                    decimals_b: decimals(&mints[1])?,
+                    pool: whirlpool,
+                })
+            },
+            None => Err(anyhow!("Tài khoản {} không phải pool Raydium AMM v4 hoặc Orca Whirlpool", pool)),
+        }
+    }
+
+    /// Giá hiện tại của pool Raydium/Orca
+    pub async fn get_pool_price(&self, pool: &str) -> Result<PoolPrice> {
+        Ok(self.load_pool(&Pubkey::from_str(pool)?).await?.price())
+    }
+
+    /// Báo giá swap trên pool đã chỉ định trong `request.venue`
+    async fn quote_on_pool(&self, request: &SwapRequest) -> Result<(Pubkey, PoolState, TradeQuote)> {
+        let venue = request.venue.as_deref()
+            .ok_or_else(|| anyhow!("Swap trên Solana cần chỉ định địa chỉ pool (venue)"))?;
+        let pool_id = Pubkey::from_str(venue)?;
+        let input_mint = Self::token_mint(&request.input_token)?;
+        let output_mint = Self::token_mint(&request.output_token)?;
+        let amount_in = u64::try_from(request.amount_in)
+            .map_err(|_| anyhow!("Số lượng {} vượt quá giới hạn u64 của Solana", request.amount_in))?;
+
+        let pool = self.load_pool(&pool_id).await?;
+        let (base_mint, quote_mint) = pool.mints();
+        let same_pair = (input_mint == base_mint && output_mint == quote_mint)
+            || (input_mint == quote_mint && output_mint == base_mint);
+        if !same_pair {
+            return Err(anyhow!("Pool {} không phải cặp {}/{}", pool_id, input_mint, output_mint));
+        }
+
+        let amount_out = pool.amount_out(&input_mint, amount_in)? as u128;
+        let quote = TradeQuote {
+            amount_in: request.amount_in,
+            amount_out,
+            min_amount_out: trading_adapter::min_amount_out(amount_out, request.slippage_bps),
+            venue: pool_id.to_string(),
+        };
+        Ok((pool_id, pool, quote))
+    }
+
+    /// Tạo và ký giao dịch swap trên pool Raydium AMM v4.
+    /// SOL được wrap vào tài khoản WSOL trước khi swap và unwrap (đóng tài khoản) sau khi swap
+    pub async fn build_swap_transaction(&self, request: &SwapRequest) -> Result<(SignedTransaction, TradeQuote)> {
+        let keypair = self.require_keypair()?;
+        let owner = Pubkey(keypair.pubkey_bytes());
+        let (pool_id, pool, quote) = self.quote_on_pool(request).await?;
+
+        let amm = match &pool {
+            PoolState::Raydium { amm, .. } => amm,
+            PoolState::Orca { .. } => {
+                return Err(anyhow!("Pool Orca {} chỉ hỗ trợ đọc giá, chưa hỗ trợ swap", pool_id));
+            },
+        };
+        let market_account = self.rpc.get_account(&amm.market_id).await?
+            .ok_or_else(|| anyhow!("Không tìm thấy market {} của pool {}", amm.market_id, pool_id))?;
+        let market = SerumMarketKeys::parse(&amm.market_id, &amm.market_program_id, &market_account.data)?;
+
+        let wsol_mint = Pubkey::from_static(WRAPPED_SOL_MINT);
+        let input_mint = Self::token_mint(&request.input_token)?;
+        let output_mint = Self::token_mint(&request.output_token)?;
+        let source = associated_token_address(&owner, &input_mint)?;
+        let destination = associated_token_address(&owner, &output_mint)?;
+        let amount_in = quote.amount_in as u64;
+
+        let priority_fee = match self.config.priority_fee_micro_lamports {
+            Some(fee) => fee,
+            None => self.rpc.get_recent_prioritization_fee(&[pool_id]).await.unwrap_or_else(|e| {
+                warn!("Không lấy được priority fee Solana, dùng 0: {}", e);
+                0
+            }),
+        };
+
+        let mut instructions = vec![
+            set_compute_unit_limit(self.config.compute_unit_limit),
+            set_compute_unit_price(priority_fee),
+        ];
+        if input_mint == wsol_mint {
+            instructions.push(create_associated_token_account_idempotent(&owner, &owner, &wsol_mint)?);
+            instructions.push(system_transfer(&owner, &source, amount_in));
+            instructions.push(sync_native(&source));
+        }
+        instructions.push(create_associated_token_account_idempotent(&owner, &owner, &output_mint)?);
+        instructions.push(raydium_swap_base_in(
+            &pool_id,
+            amm,
+            &market,
+            &source,
+            &destination,
+            &owner,
+            amount_in,
+            quote.min_amount_out as u64,
+        )?);
+        if input_mint == wsol_mint || output_mint == wsol_mint {
+            let wsol_account = associated_token_address(&owner, &wsol_mint)?;
+            instructions.push(close_token_account(&wsol_account, &owner, &owner));
+        }
+
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        let message = Message::compile(&instructions, &owner, blockhash)?;
+        let transaction = SignedTransaction::sign(message, &[keypair.as_ref()])?;
+        debug!("Đã ký giao dịch swap Solana {} trên pool {}", transaction.signature(), pool_id);
+        Ok((transaction, quote))
+    }
+
+    /// Gửi giao dịch và chờ đạt mức commitment cấu hình.
+    /// Trả về chữ ký và cờ đã xác nhận (false nếu hết thời gian chờ)
+    pub async fn send_and_confirm(&self, transaction: &SignedTransaction) -> Result<(String, bool)> {
+        let signature = self.rpc.send_transaction(&transaction.serialize()).await?;
+        let target = commitment_rank(&self.config.commitment);
+        let deadline = Instant::now() + Duration::from_millis(self.config.confirm_timeout_ms);
+
+        loop {
+            if let Some(status) = self.rpc.get_signature_status(&signature).await? {
+                if let Some(err) = status.err {
+                    return Err(anyhow!("Giao dịch Solana {} thất bại: {}", signature, err));
+                }
+                if status.confirmation_status.as_deref().map(commitment_rank).unwrap_or(0) >= target {
+                    return Ok((signature, true));
+                }
+            }
+            if Instant::now() >= deadline {
+                warn!("Giao dịch Solana {} chưa được xác nhận sau {}ms", signature, self.config.confirm_timeout_ms);
+                return Ok((signature, false));
+            }
+            tokio::time::sleep(Duration::from_millis(self.config.confirm_poll_ms)).await;
+        }
+    }
+}
+
+#[async_trait]
+impl TradingAdapter for SolanaAdapter {
+    fn chain_family(&self) -> ChainFamily {
+        ChainFamily::Solana
+    }
+
+    fn chain_name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn native_symbol(&self) -> &str {
+        SOL_SYMBOL
+    }
+
+    fn is_valid_address(&self, address: &str) -> bool {
+        Pubkey::from_str(address).is_ok()
+    }
+
+    fn trader_address(&self) -> Option<String> {
+        self.keypair.as_ref().map(|keypair| keypair.pubkey())
+    }
+
+    async fn native_balance(&self, owner: &str) -> Result<u128> {
+        Ok(self.get_sol_balance(owner).await? as u128)
+    }
+
+    async fn token_balance(&self, token: &str, owner: &str) -> Result<u128> {
+        if token == NATIVE_TOKEN {
+            return self.native_balance(owner).await;
+        }
+        Ok(self.get_spl_token_balance(owner, token).await? as u128)
+    }
+
+    async fn quote(&self, request: &SwapRequest) -> Result<TradeQuote> {
+        Ok(self.quote_on_pool(request).await?.2)
+    }
+
+    async fn swap(&self, request: &SwapRequest) -> Result<SwapOutcome> {
+        let (transaction, quote) = self.build_swap_transaction(request).await?;
+        let (tx_id, confirmed) = self.send_and_confirm(&transaction).await?;
+        info!("Swap Solana {} -> {} trên pool {}: {}", request.input_token, request.output_token, quote.venue, tx_id);
+        Ok(SwapOutcome { tx_id, quote, confirmed })
+    }
+}
+
+/// Đặt keypair ký cho các adapter Solana và đăng ký lại adapter của những chain Solana đã nạp
+pub fn set_solana_keypair(keypair: Arc<SolanaKeypair>) -> Result<()> {
+    *SOLANA_SIGNER.write().unwrap_or_else(|e| e.into_inner()) = Some(keypair);
+    for config in chain_registry::get_all_chain_configs().iter().filter(|config| is_solana_chain(config)) {
+        init_solana_trading_adapter(config)?;
+    }
+    Ok(())
+}
+
+/// Tạo adapter Solana từ file cấu hình chain và đăng ký vào registry adapter giao dịch.
+/// Keypair lấy từ `set_solana_keypair` (kho ví mã hóa); chưa có thì adapter chỉ đọc
+pub fn init_solana_trading_adapter(config: &chain_registry::ChainConfig) -> Result<Arc<SolanaAdapter>> {
+    let mut adapter = SolanaAdapter::new(SolanaConfig::from_chain_config(config)?)?;
+    if let Some(keypair) = SOLANA_SIGNER.read().unwrap_or_else(|e| e.into_inner()).clone() {
+        info!("Gắn keypair Solana {} cho chain {}", keypair.pubkey(), config.name);
+        adapter = adapter.with_keypair(keypair);
+    }
+
+    let adapter = Arc::new(adapter);
+    trading_adapter::register_trading_adapter(&config.name, adapter.clone());
+    Ok(adapter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_adapters::solana_dex::{RAYDIUM_AMM_V4_LEN, RAYDIUM_AMM_V4_PROGRAM_ID};
+    use crate::chain_adapters::solana_tx::{create_program_address, TOKEN_PROGRAM_ID};
+    use crate::chain_adapters::tests::mock_rpc::spawn_mock_rpc;
+    use diamond_wallet::solana::verify_solana_signature;
+    use std::collections::HashMap;
+
+    const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qrxzTnHXQ1mN5SG9nm8JqAnX9h";
+    const OPENBOOK_PROGRAM_ID: &str = "srmqPvymJeFKQ4zGQed1GzYhNZu8Ng3XBEWMQDfP9Ex";
+
+    /// Tài khoản giả lập trên mock node: (owner, data)
+    type MockAccounts = HashMap<String, (String, Vec<u8>)>;
+
+    fn key(byte: u8) -> Pubkey {
+        Pubkey([byte; 32])
+    }
+
+    fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Vec<u8> {
+        let mut data = vec![0u8; 165];
+        data[..32].copy_from_slice(&mint.0);
+        data[32..64].copy_from_slice(&owner.0);
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data
+    }
+
+    /// Pool Raydium SOL/USDC giá 150 USDC/SOL cùng vault và market OpenBook
+    fn raydium_fixture() -> (Pubkey, MockAccounts) {
+        let pool = key(1);
+        let (base_vault, quote_vault, market) = (key(2), key(3), key(4));
+        let market_program = Pubkey::from_static(OPENBOOK_PROGRAM_ID);
+        let wsol = Pubkey::from_static(WRAPPED_SOL_MINT);
+        let usdc = Pubkey::from_static(USDC_MINT);
+
+        let mut amm = vec![0u8; RAYDIUM_AMM_V4_LEN];
+        amm[32..40].copy_from_slice(&9u64.to_le_bytes());
+        amm[40..48].copy_from_slice(&6u64.to_le_bytes());
+        amm[176..184].copy_from_slice(&25u64.to_le_bytes());
+        amm[184..192].copy_from_slice(&10_000u64.to_le_bytes());
+        for (offset, value) in [
+            (336, base_vault), (368, quote_vault), (400, wsol), (432, usdc),
+            (496, key(5)), (528, market), (560, market_program), (592, key(6)),
+        ] {
+            amm[offset..offset + 32].copy_from_slice(&value.0);
+        }
+
+        // Nonce đầu tiên cho ra vault signer hợp lệ (nằm ngoài đường cong)
+        let nonce = (0u64..)
+            .find(|nonce| create_program_address(&[&market.0, &nonce.to_le_bytes()], &market_program).is_ok())
+            .unwrap();
+        let mut market_data = vec![0u8; 388];
+        market_data[45..53].copy_from_slice(&nonce.to_le_bytes());
+        for (offset, value) in [(117, key(7)), (165, key(8)), (253, key(9)), (285, key(10)), (317, key(11))] {
+            market_data[offset..offset + 32].copy_from_slice(&value.0);
+        }
+
+        let token_program = TOKEN_PROGRAM_ID.to_string();
+        let mut accounts = MockAccounts::new();
+        accounts.insert(pool.to_string(), (RAYDIUM_AMM_V4_PROGRAM_ID.to_string(), amm));
+        accounts.insert(base_vault.to_string(), (token_program.clone(), token_account(&wsol, &key(12), 1_000_000_000_000)));
+        accounts.insert(quote_vault.to_string(), (token_program, token_account(&usdc, &key(12), 150_000_000_000)));
+        accounts.insert(market.to_string(), (OPENBOOK_PROGRAM_ID.to_string(), market_data));
+        (pool, accounts)
+    }
+
+    fn account_json(accounts: &MockAccounts, address: &str) -> Value {
+        match accounts.get(address) {
+            Some((owner, data)) => json!({
+                "lamports": 2_039_280u64,
+                "owner": owner,
+                "data": [BASE64.encode(data), "base64"],
+                "executable": false,
+                "rentEpoch": 0,
+            }),
+            None => Value::Null,
+        }
+    }
+
+    /// Trả lời một lời gọi JSON-RPC của mock node Solana
+    fn mock_rpc_result(accounts: &MockAccounts, method: &str, params: &Value) -> Value {
+        let context = json!({"slot": 250_000_000u64});
+        match method {
+            "getSlot" => json!(250_000_000u64),
+            "getVersion" => json!({"solana-core": "1.18.22"}),
+            "getBalance" => json!({"context": context, "value": 5 * LAMPORTS_PER_SOL}),
+            "getAccountInfo" => json!({"context": context, "value": account_json(accounts, params[0].as_str().unwrap_or_default())}),
+            "getMultipleAccounts" => {
+                let values: Vec<Value> = params[0].as_array().cloned().unwrap_or_default().iter()
+                    .map(|address| account_json(accounts, address.as_str().unwrap_or_default()))
+                    .collect();
+                json!({"context": context, "value": values})
+            },
+            "getTokenAccountsByOwner" => {
+                let owner = Pubkey::from_str(params[0].as_str().unwrap_or_default()).unwrap();
+                let mint = Pubkey::from_str(params[1]["mint"].as_str().unwrap_or_default()).unwrap();
+                let entries: Vec<Value> = [(key(20), 700u64), (key(21), 300u64)].iter()
+                    .map(|(address, amount)| json!({
+                        "pubkey": address.to_string(),
+                        "account": {
+                            "lamports": 2_039_280u64,
+                            "owner": TOKEN_PROGRAM_ID,
+                            "data": [BASE64.encode(token_account(&mint, &owner, *amount)), "base64"],
+                        },
+                    }))
+                    .collect();
+                json!({"context": context, "value": entries})
+            },
+            "getLatestBlockhash" => json!({"context": context, "value": {"blockhash": key(30).to_string(), "lastValidBlockHeight": 1}}),
+            "getRecentPrioritizationFees" => json!([
+                {"slot": 1, "prioritizationFee": 10},
+                {"slot": 2, "prioritizationFee": 5_000},
+                {"slot": 3, "prioritizationFee": 200},
+            ]),
+            "sendTransaction" => {
+                // Kiểm tra chữ ký của fee payer trước khi chấp nhận giao dịch
+                let raw = BASE64.decode(params[0].as_str().unwrap_or_default()).unwrap_or_default();
+                if raw.len() < 65 + 4 + 32 || raw[0] != 1 {
+                    return Value::Null;
+                }
+                let signature: [u8; 64] = raw[1..65].try_into().unwrap();
+                let message = &raw[65..];
+                let payer: [u8; 32] = message[4..36].try_into().unwrap();
+                if !verify_solana_signature(&payer, message, &signature) {
+                    return Value::Null;
+                }
+                json!(bs58::encode(signature).into_string())
+            },
+            "getSignatureStatuses" => json!({
+                "context": context,
+                "value": [{"slot": 250_000_001u64, "confirmations": 1, "err": null, "confirmationStatus": "confirmed"}],
+            }),
+            _ => Value::Null,
+        }
+    }
+
+    async fn mock_adapter(keypair: Option<Arc<SolanaKeypair>>) -> (SolanaAdapter, Pubkey) {
+        let (pool, accounts) = raydium_fixture();
+        let config = SolanaConfig {
+            rpc_url: spawn_mock_rpc(move |method, params| mock_rpc_result(&accounts, method, params)).await,
+            confirm_poll_ms: 10,
+            ..SolanaConfig::default()
+        };
+        let adapter = SolanaAdapter::new(config).unwrap();
+        let adapter = match keypair {
+            Some(keypair) => adapter.with_keypair(keypair),
+            None => adapter,
+        };
+        (adapter, pool)
+    }
+
+    #[tokio::test]
+    async fn test_reads_balances_and_pool_price() {
+        let (adapter, pool) = mock_adapter(None).await;
+        let owner = key(40).to_string();
+
+        assert_eq!(adapter.native_balance(&owner).await.unwrap(), 5 * LAMPORTS_PER_SOL as u128);
+        assert_eq!(adapter.token_balance(USDC_MINT, &owner).await.unwrap(), 1_000);
+        assert_eq!(adapter.rpc().get_slot().await.unwrap(), 250_000_000);
+
+        let price = adapter.get_pool_price(&pool.to_string()).await.unwrap();
+        assert_eq!(price.dex, SolanaDex::Raydium);
+        assert_eq!(price.quote_mint, Pubkey::from_static(USDC_MINT));
+        assert!((price.price - 150.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_swap_is_signed_sent_and_confirmed() {
+        let keypair = Arc::new(SolanaKeypair::generate());
+        let (adapter, pool) = mock_adapter(Some(keypair.clone())).await;
+        assert_eq!(adapter.trader_address(), Some(keypair.pubkey()));
+
+        // 1 SOL -> USDC, phí 0.25%
+        let request = SwapRequest::buy(USDC_MINT, LAMPORTS_PER_SOL as u128, 100).with_venue(&pool.to_string());
+        let (transaction, quote) = adapter.build_swap_transaction(&request).await.unwrap();
+        assert_eq!(quote.amount_out, 149_475_897);
+        assert_eq!(quote.min_amount_out, trading_adapter::min_amount_out(quote.amount_out, 100));
+        assert_eq!(transaction.message.account_keys[0], Pubkey(keypair.pubkey_bytes()));
+
+        let outcome = adapter.swap(&request).await.unwrap();
+        assert!(outcome.confirmed);
+        assert_eq!(outcome.quote, quote);
+        assert_eq!(bs58::decode(&outcome.tx_id).into_vec().unwrap().len(), 64);
+    }
+
+    #[tokio::test]
+    async fn test_quote_rejects_invalid_requests() {
+        let (adapter, pool) = mock_adapter(None).await;
+
+        let no_venue = SwapRequest::buy(USDC_MINT, 1_000, 50);
+        assert!(adapter.quote(&no_venue).await.is_err());
+
+        let wrong_pair = SwapRequest::buy(&key(50).to_string(), 1_000, 50).with_venue(&pool.to_string());
+        assert!(adapter.quote(&wrong_pair).await.is_err());
+
+        let read_only = SwapRequest::buy(USDC_MINT, 1_000, 50).with_venue(&pool.to_string());
+        assert!(adapter.quote(&read_only).await.is_ok());
+        assert!(adapter.swap(&read_only).await.is_err());
+    }
+}
@@ -0,0 +1,335 @@
+// External imports
+use serde::{Serialize, Deserialize};
+
+// Third party imports
+use anyhow::{anyhow, Result};
+
+// Internal imports
+use crate::chain_adapters::solana_tx::{
+    create_program_address, find_program_address, AccountMeta, Instruction, Pubkey, TOKEN_PROGRAM_ID,
+};
+
+/// Program Raydium AMM v4
+pub const RAYDIUM_AMM_V4_PROGRAM_ID: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+
+/// Program Orca Whirlpool
+pub const ORCA_WHIRLPOOL_PROGRAM_ID: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+
+/// Seed PDA authority của Raydium AMM v4
+const RAYDIUM_AUTHORITY_SEED: &[u8] = b"amm authority";
+
+/// Kích thước tài khoản pool Raydium AMM v4
+pub const RAYDIUM_AMM_V4_LEN: usize = 752;
+
+/// Kích thước tối thiểu của tài khoản Whirlpool cần để đọc giá
+const WHIRLPOOL_MIN_LEN: usize = 245;
+
+/// Kích thước tối thiểu của tài khoản market OpenBook/Serum v3
+const SERUM_MARKET_MIN_LEN: usize = 349;
+
+/// Offset số dư (u64) trong tài khoản SPL token
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+/// Offset decimals trong tài khoản mint SPL
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+/// Mã instruction swap_base_in của Raydium AMM v4
+const RAYDIUM_SWAP_BASE_IN: u8 = 9;
+
+/// Loại pool DEX trên Solana
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SolanaDex {
+    /// Raydium AMM v4 (constant product)
+    Raydium,
+    /// Orca Whirlpool (concentrated liquidity)
+    Orca,
+}
+
+impl SolanaDex {
+    /// Xác định DEX dựa trên program sở hữu tài khoản pool
+    pub fn from_owner(owner: &Pubkey) -> Option<Self> {
+        if *owner == Pubkey::from_static(RAYDIUM_AMM_V4_PROGRAM_ID) {
+            Some(SolanaDex::Raydium)
+        } else if *owner == Pubkey::from_static(ORCA_WHIRLPOOL_PROGRAM_ID) {
+            Some(SolanaDex::Orca)
+        } else {
+            None
+        }
+    }
+}
+
+/// Đọc u64 little-endian tại `offset`
+pub fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes = data.get(offset..offset + 8)
+        .ok_or_else(|| anyhow!("Dữ liệu tài khoản quá ngắn để đọc u64 tại offset {}", offset))?;
+    Ok(u64::from_le_bytes(bytes.try_into()?))
+}
+
+/// Đọc u128 little-endian tại `offset`
+fn read_u128(data: &[u8], offset: usize) -> Result<u128> {
+    let bytes = data.get(offset..offset + 16)
+        .ok_or_else(|| anyhow!("Dữ liệu tài khoản quá ngắn để đọc u128 tại offset {}", offset))?;
+    Ok(u128::from_le_bytes(bytes.try_into()?))
+}
+
+/// Đọc u16 little-endian tại `offset`
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = data.get(offset..offset + 2)
+        .ok_or_else(|| anyhow!("Dữ liệu tài khoản quá ngắn để đọc u16 tại offset {}", offset))?;
+    Ok(u16::from_le_bytes(bytes.try_into()?))
+}
+
+/// Số dư của tài khoản SPL token
+pub fn token_account_amount(data: &[u8]) -> Result<u64> {
+    read_u64(data, TOKEN_ACCOUNT_AMOUNT_OFFSET)
+}
+
+/// Decimals của mint SPL
+pub fn mint_decimals(data: &[u8]) -> Result<u8> {
+    data.get(MINT_DECIMALS_OFFSET).copied()
+        .ok_or_else(|| anyhow!("Dữ liệu mint quá ngắn để đọc decimals"))
+}
+
+/// Trạng thái pool Raydium AMM v4 (các trường cần cho báo giá và swap)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RaydiumAmmState {
+    pub base_decimals: u8,
+    pub quote_decimals: u8,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+    pub base_need_take_pnl: u64,
+    pub quote_need_take_pnl: u64,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub open_orders: Pubkey,
+    pub market_id: Pubkey,
+    pub market_program_id: Pubkey,
+    pub target_orders: Pubkey,
+}
+
+impl RaydiumAmmState {
+    /// Parse dữ liệu tài khoản pool (layout LIQUIDITY_STATE_LAYOUT_V4)
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < RAYDIUM_AMM_V4_LEN {
+            return Err(anyhow!("Tài khoản pool Raydium dài {} bytes, cần {}", data.len(), RAYDIUM_AMM_V4_LEN));
+        }
+        Ok(Self {
+            base_decimals: read_u64(data, 32)? as u8,
+            quote_decimals: read_u64(data, 40)? as u8,
+            swap_fee_numerator: read_u64(data, 176)?,
+            swap_fee_denominator: read_u64(data, 184)?,
+            base_need_take_pnl: read_u64(data, 192)?,
+            quote_need_take_pnl: read_u64(data, 200)?,
+            base_vault: Pubkey::read(data, 336)?,
+            quote_vault: Pubkey::read(data, 368)?,
+            base_mint: Pubkey::read(data, 400)?,
+            quote_mint: Pubkey::read(data, 432)?,
+            open_orders: Pubkey::read(data, 496)?,
+            market_id: Pubkey::read(data, 528)?,
+            market_program_id: Pubkey::read(data, 560)?,
+            target_orders: Pubkey::read(data, 592)?,
+        })
+    }
+
+    /// Reserve thực của pool (số dư vault trừ phần PnL chưa rút)
+    pub fn reserves(&self, base_vault_amount: u64, quote_vault_amount: u64) -> (u64, u64) {
+        (
+            base_vault_amount.saturating_sub(self.base_need_take_pnl),
+            quote_vault_amount.saturating_sub(self.quote_need_take_pnl),
+        )
+    }
+
+    /// Số lượng nhận được khi bán `amount_in` (constant product, đã trừ phí swap)
+    pub fn amount_out(&self, amount_in: u64, reserve_in: u64, reserve_out: u64) -> Result<u64> {
+        if self.swap_fee_denominator == 0 || reserve_in == 0 || reserve_out == 0 {
+            return Err(anyhow!("Pool Raydium không có thanh khoản"));
+        }
+        let fee = (amount_in as u128 * self.swap_fee_numerator as u128).div_ceil(self.swap_fee_denominator as u128);
+        let amount_in_after_fee = amount_in as u128 - fee.min(amount_in as u128);
+        let amount_out = reserve_out as u128 * amount_in_after_fee / (reserve_in as u128 + amount_in_after_fee);
+        Ok(amount_out as u64)
+    }
+
+    /// Giá base tính theo quote (đã điều chỉnh decimals)
+    pub fn price(&self, base_reserve: u64, quote_reserve: u64) -> f64 {
+        if base_reserve == 0 {
+            return 0.0;
+        }
+        let base = base_reserve as f64 / 10f64.powi(self.base_decimals as i32);
+        let quote = quote_reserve as f64 / 10f64.powi(self.quote_decimals as i32);
+        quote / base
+    }
+}
+
+/// Các tài khoản market OpenBook/Serum mà Raydium AMM v4 yêu cầu khi swap
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerumMarketKeys {
+    pub bids: Pubkey,
+    pub asks: Pubkey,
+    pub event_queue: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub vault_signer: Pubkey,
+}
+
+impl SerumMarketKeys {
+    /// Parse tài khoản market Serum v3 / OpenBook v1 (5 byte padding đầu)
+    pub fn parse(market_id: &Pubkey, market_program_id: &Pubkey, data: &[u8]) -> Result<Self> {
+        if data.len() < SERUM_MARKET_MIN_LEN {
+            return Err(anyhow!("Tài khoản market dài {} bytes, cần tối thiểu {}", data.len(), SERUM_MARKET_MIN_LEN));
+        }
+        let vault_signer_nonce = read_u64(data, 45)?;
+        let vault_signer = create_program_address(
+            &[&market_id.0, &vault_signer_nonce.to_le_bytes()],
+            market_program_id,
+        )?;
+        Ok(Self {
+            base_vault: Pubkey::read(data, 117)?,
+            quote_vault: Pubkey::read(data, 165)?,
+            event_queue: Pubkey::read(data, 253)?,
+            bids: Pubkey::read(data, 285)?,
+            asks: Pubkey::read(data, 317)?,
+            vault_signer,
+        })
+    }
+}
+
+/// Tạo instruction swap_base_in của Raydium AMM v4
+#[allow(clippy::too_many_arguments)]
+pub fn raydium_swap_base_in(
+    amm_id: &Pubkey,
+    amm: &RaydiumAmmState,
+    market: &SerumMarketKeys,
+    user_source: &Pubkey,
+    user_destination: &Pubkey,
+    user_owner: &Pubkey,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<Instruction> {
+    let program_id = Pubkey::from_static(RAYDIUM_AMM_V4_PROGRAM_ID);
+    let (authority, _) = find_program_address(&[RAYDIUM_AUTHORITY_SEED], &program_id)?;
+
+    let mut data = vec![RAYDIUM_SWAP_BASE_IN];
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+    Ok(Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::readonly(Pubkey::from_static(TOKEN_PROGRAM_ID), false),
+            AccountMeta::writable(*amm_id, false),
+            AccountMeta::readonly(authority, false),
+            AccountMeta::writable(amm.open_orders, false),
+            AccountMeta::writable(amm.target_orders, false),
+            AccountMeta::writable(amm.base_vault, false),
+            AccountMeta::writable(amm.quote_vault, false),
+            AccountMeta::readonly(amm.market_program_id, false),
+            AccountMeta::writable(amm.market_id, false),
+            AccountMeta::writable(market.bids, false),
+            AccountMeta::writable(market.asks, false),
+            AccountMeta::writable(market.event_queue, false),
+            AccountMeta::writable(market.base_vault, false),
+            AccountMeta::writable(market.quote_vault, false),
+            AccountMeta::readonly(market.vault_signer, false),
+            AccountMeta::writable(*user_source, false),
+            AccountMeta::writable(*user_destination, false),
+            AccountMeta::readonly(*user_owner, true),
+        ],
+        data,
+    })
+}
+
+/// Trạng thái pool Orca Whirlpool (các trường cần để đọc giá)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WhirlpoolState {
+    /// Phí swap, đơn vị phần triệu
+    pub fee_rate: u16,
+    pub liquidity: u128,
+    /// Căn bậc hai giá dạng Q64.64
+    pub sqrt_price: u128,
+    pub token_mint_a: Pubkey,
+    pub token_vault_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_vault_b: Pubkey,
+}
+
+impl WhirlpoolState {
+    /// Parse dữ liệu tài khoản Whirlpool (8 byte discriminator của Anchor)
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < WHIRLPOOL_MIN_LEN {
+            return Err(anyhow!("Tài khoản Whirlpool dài {} bytes, cần tối thiểu {}", data.len(), WHIRLPOOL_MIN_LEN));
+        }
+        Ok(Self {
+            fee_rate: read_u16(data, 45)?,
+            liquidity: read_u128(data, 49)?,
+            sqrt_price: read_u128(data, 65)?,
+            token_mint_a: Pubkey::read(data, 101)?,
+            token_vault_a: Pubkey::read(data, 133)?,
+            token_mint_b: Pubkey::read(data, 181)?,
+            token_vault_b: Pubkey::read(data, 213)?,
+        })
+    }
+
+    /// Giá token A tính theo token B (đã điều chỉnh decimals)
+    pub fn price(&self, decimals_a: u8, decimals_b: u8) -> f64 {
+        let sqrt_price = self.sqrt_price as f64 / 2f64.powi(64);
+        sqrt_price * sqrt_price * 10f64.powi(decimals_a as i32 - decimals_b as i32)
+    }
+
+    /// Ước tính số lượng nhận theo giá hiện tại, đã trừ phí (bỏ qua price impact)
+    pub fn estimate_amount_out(&self, amount_in: u64, a_to_b: bool) -> u64 {
+        let sqrt_price = self.sqrt_price as f64 / 2f64.powi(64);
+        let raw_price = sqrt_price * sqrt_price;
+        let after_fee = amount_in as f64 * (1.0 - self.fee_rate as f64 / 1_000_000.0);
+        let amount_out = if a_to_b {
+            after_fee * raw_price
+        } else if raw_price > 0.0 {
+            after_fee / raw_price
+        } else {
+            0.0
+        };
+        amount_out.max(0.0) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raydium_constant_product_quote() {
+        let mut data = vec![0u8; RAYDIUM_AMM_V4_LEN];
+        data[32..40].copy_from_slice(&9u64.to_le_bytes());
+        data[40..48].copy_from_slice(&6u64.to_le_bytes());
+        data[176..184].copy_from_slice(&25u64.to_le_bytes());
+        data[184..192].copy_from_slice(&10_000u64.to_le_bytes());
+        data[192..200].copy_from_slice(&1_000u64.to_le_bytes());
+        data[400..432].copy_from_slice(&[4u8; 32]);
+
+        let amm = RaydiumAmmState::parse(&data).unwrap();
+        assert_eq!(amm.base_mint, Pubkey([4u8; 32]));
+        let (base, quote) = amm.reserves(1_000_001_000, 150_000_000);
+        assert_eq!(base, 1_000_000_000);
+        assert!((amm.price(base, quote) - 150.0).abs() < 1e-9);
+
+        // 0.01 SOL -> USDC, phí 0.25%
+        let out = amm.amount_out(10_000_000, base, quote).unwrap();
+        assert_eq!(out, 1_481_472);
+        assert!(RaydiumAmmState::parse(&data[..100]).is_err());
+    }
+
+    #[test]
+    fn test_whirlpool_price_from_sqrt_price() {
+        let mut data = vec![0u8; WHIRLPOOL_MIN_LEN];
+        data[45..47].copy_from_slice(&3_000u16.to_le_bytes());
+        // sqrt(0.15) * 2^64: giá thô 0.15 USDC(6) / SOL(9) lamport => 150 USDC/SOL
+        let sqrt_price = (0.15f64.sqrt() * 2f64.powi(64)) as u128;
+        data[65..81].copy_from_slice(&sqrt_price.to_le_bytes());
+
+        let pool = WhirlpoolState::parse(&data).unwrap();
+        assert!((pool.price(9, 6) - 150.0).abs() < 1e-6);
+        assert_eq!(pool.estimate_amount_out(1_000_000_000, true), 149_550_000);
+    }
+}
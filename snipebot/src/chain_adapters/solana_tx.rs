@@ -0,0 +1,441 @@
+// External imports
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+
+// Standard library imports
+use std::{fmt, str::FromStr};
+
+// Third party imports
+use anyhow::{anyhow, Result};
+
+// Internal imports
+use diamond_wallet::SolanaKeypair;
+
+/// System program
+pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+
+/// SPL Token program
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+/// Associated Token Account program
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+/// Compute Budget program
+pub const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+
+/// Mint của wrapped SOL
+pub const WRAPPED_SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Số PDA bump tối đa
+const MAX_BUMP_SEED: u8 = u8::MAX;
+
+/// Hậu tố khi băm địa chỉ PDA
+const PDA_MARKER: &[u8] = b"ProgramDerivedAddress";
+
+/// Public key / địa chỉ tài khoản Solana (32 byte, hiển thị base58)
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pubkey(pub [u8; 32]);
+
+impl Pubkey {
+    /// Parse từ chuỗi base58, panic nếu không hợp lệ (chỉ dùng cho hằng số)
+    pub fn from_static(value: &str) -> Self {
+        Self::from_str(value).unwrap_or_else(|e| panic!("Pubkey hằng số không hợp lệ {}: {}", value, e))
+    }
+
+    /// Đọc pubkey tại `offset` trong dữ liệu tài khoản
+    pub fn read(data: &[u8], offset: usize) -> Result<Self> {
+        let bytes = data.get(offset..offset + 32)
+            .ok_or_else(|| anyhow!("Dữ liệu tài khoản quá ngắn để đọc pubkey tại offset {}", offset))?;
+        let mut key = [0u8; 32];
+        key.copy_from_slice(bytes);
+        Ok(Self(key))
+    }
+
+    /// Pubkey có nằm trên đường cong ed25519 không (PDA phải nằm ngoài)
+    pub fn is_on_curve(&self) -> bool {
+        CompressedEdwardsY(self.0).decompress().is_some()
+    }
+}
+
+impl FromStr for Pubkey {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let bytes = bs58::decode(value.trim()).into_vec()
+            .map_err(|e| anyhow!("Địa chỉ Solana không hợp lệ {}: {}", value, e))?;
+        if bytes.len() != 32 {
+            return Err(anyhow!("Địa chỉ Solana {} phải dài 32 bytes, nhận {} bytes", value, bytes.len()));
+        }
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&bytes);
+        Ok(Self(key))
+    }
+}
+
+impl fmt::Display for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", bs58::encode(self.0).into_string())
+    }
+}
+
+impl fmt::Debug for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Pubkey({})", self)
+    }
+}
+
+impl Serialize for Pubkey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Pubkey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Pubkey::from_str(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Tạo địa chỉ PDA từ seeds và bump đã biết
+pub fn create_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Result<Pubkey> {
+    let mut hasher = Sha256::new();
+    for seed in seeds {
+        if seed.len() > 32 {
+            return Err(anyhow!("Seed PDA dài quá 32 bytes"));
+        }
+        hasher.update(seed);
+    }
+    hasher.update(program_id.0);
+    hasher.update(PDA_MARKER);
+
+    let address = Pubkey(hasher.finalize().into());
+    if address.is_on_curve() {
+        return Err(anyhow!("Seeds tạo ra địa chỉ nằm trên đường cong ed25519"));
+    }
+    Ok(address)
+}
+
+/// Tìm PDA hợp lệ đầu tiên (bump từ 255 giảm dần)
+pub fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Result<(Pubkey, u8)> {
+    for bump in (0..=MAX_BUMP_SEED).rev() {
+        let bump_seed = [bump];
+        let mut with_bump = seeds.to_vec();
+        with_bump.push(&bump_seed);
+        if let Ok(address) = create_program_address(&with_bump, program_id) {
+            return Ok((address, bump));
+        }
+    }
+    Err(anyhow!("Không tìm được PDA hợp lệ cho seeds đã cho"))
+}
+
+/// Địa chỉ associated token account của `owner` cho `mint`
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Result<Pubkey> {
+    let token_program = Pubkey::from_static(TOKEN_PROGRAM_ID);
+    let (address, _) = find_program_address(
+        &[&owner.0, &token_program.0, &mint.0],
+        &Pubkey::from_static(ASSOCIATED_TOKEN_PROGRAM_ID),
+    )?;
+    Ok(address)
+}
+
+/// Tài khoản tham gia một instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl AccountMeta {
+    /// Tài khoản ghi được
+    pub fn writable(pubkey: Pubkey, is_signer: bool) -> Self {
+        Self { pubkey, is_signer, is_writable: true }
+    }
+
+    /// Tài khoản chỉ đọc
+    pub fn readonly(pubkey: Pubkey, is_signer: bool) -> Self {
+        Self { pubkey, is_signer, is_writable: false }
+    }
+}
+
+/// Một instruction gọi tới program on-chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<AccountMeta>,
+    pub data: Vec<u8>,
+}
+
+/// Instruction chuyển lamports qua System program
+pub fn system_transfer(from: &Pubkey, to: &Pubkey, lamports: u64) -> Instruction {
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&lamports.to_le_bytes());
+    Instruction {
+        program_id: Pubkey::from_static(SYSTEM_PROGRAM_ID),
+        accounts: vec![AccountMeta::writable(*from, true), AccountMeta::writable(*to, false)],
+        data,
+    }
+}
+
+/// Instruction tạo associated token account (không lỗi nếu đã tồn tại)
+pub fn create_associated_token_account_idempotent(payer: &Pubkey, owner: &Pubkey, mint: &Pubkey) -> Result<Instruction> {
+    Ok(Instruction {
+        program_id: Pubkey::from_static(ASSOCIATED_TOKEN_PROGRAM_ID),
+        accounts: vec![
+            AccountMeta::writable(*payer, true),
+            AccountMeta::writable(associated_token_address(owner, mint)?, false),
+            AccountMeta::readonly(*owner, false),
+            AccountMeta::readonly(*mint, false),
+            AccountMeta::readonly(Pubkey::from_static(SYSTEM_PROGRAM_ID), false),
+            AccountMeta::readonly(Pubkey::from_static(TOKEN_PROGRAM_ID), false),
+        ],
+        data: vec![1],
+    })
+}
+
+/// Instruction SyncNative: cập nhật số dư wrapped SOL sau khi chuyển lamports vào
+pub fn sync_native(account: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: Pubkey::from_static(TOKEN_PROGRAM_ID),
+        accounts: vec![AccountMeta::writable(*account, false)],
+        data: vec![17],
+    }
+}
+
+/// Instruction đóng token account, trả lamports (và SOL đã unwrap) về `destination`
+pub fn close_token_account(account: &Pubkey, destination: &Pubkey, owner: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: Pubkey::from_static(TOKEN_PROGRAM_ID),
+        accounts: vec![
+            AccountMeta::writable(*account, false),
+            AccountMeta::writable(*destination, false),
+            AccountMeta::readonly(*owner, true),
+        ],
+        data: vec![9],
+    }
+}
+
+/// Instruction giới hạn compute unit của giao dịch
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    let mut data = vec![2];
+    data.extend_from_slice(&units.to_le_bytes());
+    Instruction { program_id: Pubkey::from_static(COMPUTE_BUDGET_PROGRAM_ID), accounts: vec![], data }
+}
+
+/// Instruction đặt priority fee (micro-lamports mỗi compute unit)
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    let mut data = vec![3];
+    data.extend_from_slice(&micro_lamports.to_le_bytes());
+    Instruction { program_id: Pubkey::from_static(COMPUTE_BUDGET_PROGRAM_ID), accounts: vec![], data }
+}
+
+/// Encode độ dài theo định dạng compact-u16 của Solana
+fn encode_compact_u16(mut value: usize, out: &mut Vec<u8>) {
+    loop {
+        let mut byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        byte |= 0x80;
+        out.push(byte);
+    }
+}
+
+/// Message giao dịch (định dạng legacy) đã được compile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub num_required_signatures: u8,
+    pub num_readonly_signed: u8,
+    pub num_readonly_unsigned: u8,
+    pub account_keys: Vec<Pubkey>,
+    pub recent_blockhash: [u8; 32],
+    instructions: Vec<(u8, Vec<u8>, Vec<u8>)>,
+}
+
+impl Message {
+    /// Compile danh sách instruction; `payer` luôn là tài khoản đầu tiên và ký giao dịch
+    pub fn compile(instructions: &[Instruction], payer: &Pubkey, recent_blockhash: [u8; 32]) -> Result<Self> {
+        // Gộp quyền của mỗi tài khoản theo thứ tự xuất hiện
+        let mut metas: Vec<AccountMeta> = vec![AccountMeta::writable(*payer, true)];
+        let mut merge = |meta: AccountMeta| {
+            match metas.iter_mut().find(|existing| existing.pubkey == meta.pubkey) {
+                Some(existing) => {
+                    existing.is_signer |= meta.is_signer;
+                    existing.is_writable |= meta.is_writable;
+                },
+                None => metas.push(meta),
+            }
+        };
+        for instruction in instructions {
+            for meta in &instruction.accounts {
+                merge(*meta);
+            }
+            merge(AccountMeta::readonly(instruction.program_id, false));
+        }
+
+        // Thứ tự: signer ghi được, signer chỉ đọc, không ký ghi được, không ký chỉ đọc
+        let rank = |meta: &AccountMeta| match (meta.is_signer, meta.is_writable) {
+            (true, true) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (false, false) => 3,
+        };
+        let payer_meta = metas.remove(0);
+        metas.sort_by_key(rank);
+        metas.insert(0, payer_meta);
+
+        if metas.len() > u8::MAX as usize {
+            return Err(anyhow!("Giao dịch có quá nhiều tài khoản ({})", metas.len()));
+        }
+        let count = |signer: bool, writable: bool| metas.iter()
+            .filter(|meta| meta.is_signer == signer && meta.is_writable == writable)
+            .count() as u8;
+        let account_keys: Vec<Pubkey> = metas.iter().map(|meta| meta.pubkey).collect();
+        let index_of = |key: &Pubkey| account_keys.iter().position(|candidate| candidate == key)
+            .map(|index| index as u8)
+            .ok_or_else(|| anyhow!("Tài khoản {} không có trong message", key));
+
+        let mut compiled = Vec::with_capacity(instructions.len());
+        for instruction in instructions {
+            let accounts = instruction.accounts.iter()
+                .map(|meta| index_of(&meta.pubkey))
+                .collect::<Result<Vec<u8>>>()?;
+            compiled.push((index_of(&instruction.program_id)?, accounts, instruction.data.clone()));
+        }
+
+        Ok(Self {
+            num_required_signatures: count(true, true) + count(true, false),
+            num_readonly_signed: count(true, false),
+            num_readonly_unsigned: count(false, false),
+            account_keys,
+            recent_blockhash,
+            instructions: compiled,
+        })
+    }
+
+    /// Các tài khoản cần ký, theo thứ tự chữ ký trong giao dịch
+    pub fn signer_keys(&self) -> &[Pubkey] {
+        &self.account_keys[..self.num_required_signatures as usize]
+    }
+
+    /// Serialize message (phần được ký)
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = vec![self.num_required_signatures, self.num_readonly_signed, self.num_readonly_unsigned];
+        encode_compact_u16(self.account_keys.len(), &mut out);
+        for key in &self.account_keys {
+            out.extend_from_slice(&key.0);
+        }
+        out.extend_from_slice(&self.recent_blockhash);
+        encode_compact_u16(self.instructions.len(), &mut out);
+        for (program_index, accounts, data) in &self.instructions {
+            out.push(*program_index);
+            encode_compact_u16(accounts.len(), &mut out);
+            out.extend_from_slice(accounts);
+            encode_compact_u16(data.len(), &mut out);
+            out.extend_from_slice(data);
+        }
+        out
+    }
+}
+
+/// Giao dịch Solana đã ký
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedTransaction {
+    pub signatures: Vec<[u8; 64]>,
+    pub message: Message,
+}
+
+impl SignedTransaction {
+    /// Ký message bằng các keypair; mỗi signer của message phải có keypair tương ứng
+    pub fn sign(message: Message, keypairs: &[&SolanaKeypair]) -> Result<Self> {
+        let payload = message.serialize();
+        let signatures = message.signer_keys().iter()
+            .map(|signer| {
+                keypairs.iter()
+                    .find(|keypair| keypair.pubkey_bytes() == signer.0)
+                    .map(|keypair| keypair.sign(&payload))
+                    .ok_or_else(|| anyhow!("Thiếu keypair để ký cho tài khoản {}", signer))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { signatures, message })
+    }
+
+    /// Chữ ký đầu tiên (base58) là ID của giao dịch
+    pub fn signature(&self) -> String {
+        self.signatures.first()
+            .map(|signature| bs58::encode(signature).into_string())
+            .unwrap_or_default()
+    }
+
+    /// Serialize theo wire format để gửi qua `sendTransaction`
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        encode_compact_u16(self.signatures.len(), &mut out);
+        for signature in &self.signatures {
+            out.extend_from_slice(signature);
+        }
+        out.extend(self.message.serialize());
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compact_u16_encoding() {
+        let encode = |value| {
+            let mut out = Vec::new();
+            encode_compact_u16(value, &mut out);
+            out
+        };
+        assert_eq!(encode(0), vec![0x00]);
+        assert_eq!(encode(0x7f), vec![0x7f]);
+        assert_eq!(encode(0x80), vec![0x80, 0x01]);
+        assert_eq!(encode(0x3fff), vec![0xff, 0x7f]);
+        assert_eq!(encode(0x4000), vec![0x80, 0x80, 0x01]);
+    }
+
+    #[test]
+    fn test_program_address_matches_known_value() {
+        // Authority của Raydium AMM v4 là PDA với seed "amm authority"
+        let raydium = Pubkey::from_str("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8").unwrap();
+        let (authority, _) = find_program_address(&[b"amm authority"], &raydium).unwrap();
+        assert_eq!(authority.to_string(), "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1");
+
+        let owner = Pubkey([3u8; 32]);
+        let mint = Pubkey::from_static(WRAPPED_SOL_MINT);
+        let ata = associated_token_address(&owner, &mint).unwrap();
+        assert!(!ata.is_on_curve());
+        assert_ne!(ata, associated_token_address(&mint, &owner).unwrap());
+    }
+
+    #[test]
+    fn test_message_orders_accounts_and_signs() {
+        let payer = SolanaKeypair::generate();
+        let payer_key = Pubkey(payer.pubkey_bytes());
+        let recipient = Pubkey([7u8; 32]);
+
+        let message = Message::compile(
+            &[set_compute_unit_price(1_000), system_transfer(&payer_key, &recipient, 5_000)],
+            &payer_key,
+            [9u8; 32],
+        ).unwrap();
+        assert_eq!(message.num_required_signatures, 1);
+        assert_eq!(message.num_readonly_unsigned, 2);
+        assert_eq!(message.account_keys[0], payer_key);
+        assert_eq!(message.account_keys[1], recipient);
+
+        let transaction = SignedTransaction::sign(message.clone(), &[&payer]).unwrap();
+        assert!(diamond_wallet::solana::verify_solana_signature(
+            &payer.pubkey_bytes(), &message.serialize(), &transaction.signatures[0]
+        ));
+        assert_eq!(transaction.serialize()[0], 1);
+        assert!(SignedTransaction::sign(message, &[&SolanaKeypair::generate()]).is_err());
+    }
+}
@@ -0,0 +1,568 @@
+// External imports
+use ethers::{types::{Address, U256}, utils::id};
+
+// Standard library imports
+use std::collections::HashMap;
+
+/// Opcode EVM dùng để dựng hợp đồng mock
+pub mod opcodes {
+    pub const STOP: u8 = 0x00;
+    pub const ADD: u8 = 0x01;
+    pub const MUL: u8 = 0x02;
+    pub const SUB: u8 = 0x03;
+    pub const DIV: u8 = 0x04;
+    pub const LT: u8 = 0x10;
+    pub const GT: u8 = 0x11;
+    pub const EQ: u8 = 0x14;
+    pub const ISZERO: u8 = 0x15;
+    pub const AND: u8 = 0x16;
+    pub const OR: u8 = 0x17;
+    pub const SHL: u8 = 0x1b;
+    pub const SHR: u8 = 0x1c;
+    pub const KECCAK256: u8 = 0x20;
+    pub const ADDRESS: u8 = 0x30;
+    pub const BALANCE: u8 = 0x31;
+    pub const CALLER: u8 = 0x33;
+    pub const CALLVALUE: u8 = 0x34;
+    pub const CALLDATALOAD: u8 = 0x35;
+    pub const CALLDATASIZE: u8 = 0x36;
+    pub const CODECOPY: u8 = 0x39;
+    pub const RETURNDATASIZE: u8 = 0x3d;
+    pub const RETURNDATACOPY: u8 = 0x3e;
+    pub const TIMESTAMP: u8 = 0x42;
+    pub const NUMBER: u8 = 0x43;
+    pub const SELFBALANCE: u8 = 0x47;
+    pub const POP: u8 = 0x50;
+    pub const MLOAD: u8 = 0x51;
+    pub const MSTORE: u8 = 0x52;
+    pub const SLOAD: u8 = 0x54;
+    pub const SSTORE: u8 = 0x55;
+    pub const JUMP: u8 = 0x56;
+    pub const JUMPI: u8 = 0x57;
+    pub const GAS: u8 = 0x5a;
+    pub const JUMPDEST: u8 = 0x5b;
+    pub const PUSH1: u8 = 0x60;
+    pub const PUSH2: u8 = 0x61;
+    pub const LOG0: u8 = 0xa0;
+    pub const CREATE2: u8 = 0xf5;
+    pub const CALL: u8 = 0xf1;
+    pub const RETURN: u8 = 0xf3;
+    pub const STATICCALL: u8 = 0xfa;
+    pub const REVERT: u8 = 0xfd;
+
+    /// Opcode không đẩy giá trị nào lên stack
+    pub fn has_output(op: u8) -> bool {
+        !matches!(op, STOP | POP | MSTORE | SSTORE | JUMP | JUMPI | CODECOPY | RETURNDATACOPY | RETURN | REVERT)
+            && !(LOG0..=LOG0 + 4).contains(&op)
+    }
+}
+
+use opcodes::*;
+
+/// Vùng bộ nhớ: 0x00-0x3f dùng để băm, sau đó là biến cục bộ, bộ đệm trả về,
+/// bộ đệm lời gọi ngoài, bộ đệm log và vùng động
+const VAR_BASE: u64 = 0x80;
+const MAX_VARS: usize = 64;
+pub const RET_BUF: u64 = VAR_BASE + 32 * MAX_VARS as u64;
+pub const CALL_IN: u64 = RET_BUF + 0x100;
+pub const CALL_OUT: u64 = CALL_IN + 0x100;
+pub const LOG_BUF: u64 = CALL_OUT + 0x80;
+pub const DYN_BUF: u64 = LOG_BUF + 0x80;
+
+/// Selector của lỗi `Error(string)` trong revert reason
+const ERROR_SELECTOR: &str = "Error(string)";
+
+/// Biến cục bộ lưu trong bộ nhớ
+#[derive(Debug, Clone, Copy)]
+pub struct Var(usize);
+
+impl Var {
+    fn addr(self) -> u64 {
+        VAR_BASE + 32 * self.0 as u64
+    }
+}
+
+/// Biểu thức dạng Yul: tham số đầu tiên nằm trên đỉnh stack khi gọi opcode
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Lit(U256),
+    Var(Var),
+    Label(String),
+    Op(u8, Vec<Expr>),
+    /// keccak256(key . slot) - slot của mapping theo layout Solidity
+    Mapping(Box<Expr>, Box<Expr>),
+}
+
+impl From<Var> for Expr {
+    fn from(var: Var) -> Self {
+        Expr::Var(var)
+    }
+}
+
+impl From<u64> for Expr {
+    fn from(value: u64) -> Self {
+        Expr::Lit(U256::from(value))
+    }
+}
+
+impl From<U256> for Expr {
+    fn from(value: U256) -> Self {
+        Expr::Lit(value)
+    }
+}
+
+impl From<Address> for Expr {
+    fn from(value: Address) -> Self {
+        Expr::Lit(U256::from_big_endian(value.as_bytes()))
+    }
+}
+
+impl From<&Expr> for Expr {
+    fn from(expr: &Expr) -> Self {
+        expr.clone()
+    }
+}
+
+pub fn op<const N: usize>(code: u8, args: [Expr; N]) -> Expr {
+    Expr::Op(code, args.into())
+}
+
+macro_rules! binary_ops {
+    ($($name:ident => $code:expr),* $(,)?) => {
+        $(pub fn $name(a: impl Into<Expr>, b: impl Into<Expr>) -> Expr {
+            op($code, [a.into(), b.into()])
+        })*
+    };
+}
+
+macro_rules! nullary_ops {
+    ($($name:ident => $code:expr),* $(,)?) => {
+        $(pub fn $name() -> Expr {
+            op($code, [])
+        })*
+    };
+}
+
+binary_ops! {
+    add => ADD, sub => SUB, mul => MUL, div => DIV,
+    lt => LT, gt => GT, eq => EQ, and => AND, or => OR,
+    shl => SHL, shr => SHR, keccak256 => KECCAK256,
+}
+
+nullary_ops! {
+    caller => CALLER, callvalue => CALLVALUE, calldatasize => CALLDATASIZE,
+    address => ADDRESS, selfbalance => SELFBALANCE, timestamp => TIMESTAMP,
+    number => NUMBER, gas => GAS, returndatasize => RETURNDATASIZE,
+}
+
+pub fn iszero(a: impl Into<Expr>) -> Expr {
+    op(ISZERO, [a.into()])
+}
+
+pub fn not_lt(a: impl Into<Expr>, b: impl Into<Expr>) -> Expr {
+    iszero(lt(a, b))
+}
+
+pub fn neq(a: impl Into<Expr>, b: impl Into<Expr>) -> Expr {
+    iszero(eq(a, b))
+}
+
+pub fn mload(offset: impl Into<Expr>) -> Expr {
+    op(MLOAD, [offset.into()])
+}
+
+pub fn sload(slot: impl Into<Expr>) -> Expr {
+    op(SLOAD, [slot.into()])
+}
+
+pub fn calldataload(offset: impl Into<Expr>) -> Expr {
+    op(CALLDATALOAD, [offset.into()])
+}
+
+pub fn balance(account: impl Into<Expr>) -> Expr {
+    op(BALANCE, [account.into()])
+}
+
+/// Tham số tĩnh thứ `index` của calldata
+pub fn arg(index: u64) -> Expr {
+    calldataload(4 + 32 * index)
+}
+
+/// Slot của `mapping[key]` khai báo ở `slot`
+pub fn mapping(slot: impl Into<Expr>, key: impl Into<Expr>) -> Expr {
+    Expr::Mapping(Box::new(key.into()), Box::new(slot.into()))
+}
+
+/// Slot của `mapping[outer][inner]` khai báo ở `slot`
+pub fn mapping2(slot: impl Into<Expr>, outer: impl Into<Expr>, inner: impl Into<Expr>) -> Expr {
+    mapping(mapping(slot, outer), inner)
+}
+
+/// Word thứ `index` của kết quả lời gọi ngoài gần nhất
+pub fn call_result(index: u64) -> Expr {
+    mload(CALL_OUT + 32 * index)
+}
+
+pub fn label(name: &str) -> Expr {
+    Expr::Label(name.to_string())
+}
+
+/// Selector 4 byte của chữ ký hàm
+pub fn selector(signature: &str) -> U256 {
+    U256::from_big_endian(&id(signature))
+}
+
+/// Topic của event theo chữ ký
+pub fn event_topic(signature: &str) -> U256 {
+    U256::from_big_endian(&ethers::utils::keccak256(signature.as_bytes()))
+}
+
+/// Bộ dựng bytecode với nhãn nhảy và dữ liệu nhúng
+#[derive(Debug, Default)]
+pub struct Code {
+    bytes: Vec<u8>,
+    labels: HashMap<String, usize>,
+    fixups: Vec<(usize, String)>,
+    next_label: usize,
+    next_var: usize,
+}
+
+impl Code {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn emit(&mut self, byte: u8) {
+        self.bytes.push(byte);
+    }
+
+    fn push(&mut self, value: U256) {
+        let mut word = [0_u8; 32];
+        value.to_big_endian(&mut word);
+        let skip = word.iter().take_while(|byte| **byte == 0).count().min(31);
+        self.emit(PUSH1 + (31 - skip) as u8);
+        self.bytes.extend_from_slice(&word[skip..]);
+    }
+
+    fn push_label(&mut self, name: &str) {
+        self.emit(PUSH2);
+        self.fixups.push((self.bytes.len(), name.to_string()));
+        self.bytes.extend_from_slice(&[0, 0]);
+    }
+
+    /// Tạo tên nhãn mới chưa dùng
+    pub fn fresh_label(&mut self) -> String {
+        self.next_label += 1;
+        format!("__L{}", self.next_label)
+    }
+
+    /// Đặt nhãn nhảy (JUMPDEST) tại vị trí hiện tại
+    pub fn place(&mut self, name: &str) {
+        self.labels.insert(name.to_string(), self.bytes.len());
+        self.emit(JUMPDEST);
+    }
+
+    /// Nhúng dữ liệu thô với nhãn trỏ tới đầu vùng dữ liệu
+    pub fn data(&mut self, name: &str, data: &[u8]) {
+        self.labels.insert(name.to_string(), self.bytes.len());
+        self.bytes.extend_from_slice(data);
+    }
+
+    /// Cấp biến cục bộ mới
+    pub fn var(&mut self) -> Var {
+        assert!(self.next_var < MAX_VARS, "mock contract dùng quá {} biến", MAX_VARS);
+        self.next_var += 1;
+        Var(self.next_var - 1)
+    }
+
+    /// Giải phóng toàn bộ biến (dùng khi bắt đầu thân hàm mới)
+    pub fn reset_vars(&mut self) {
+        self.next_var = 0;
+    }
+
+    pub fn compile(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Lit(value) => self.push(*value),
+            Expr::Var(var) => {
+                self.push(U256::from(var.addr()));
+                self.emit(MLOAD);
+            },
+            Expr::Label(name) => self.push_label(name),
+            Expr::Op(code, args) => {
+                for arg in args.iter().rev() {
+                    self.compile(arg);
+                }
+                self.emit(*code);
+            },
+            Expr::Mapping(key, slot) => {
+                // Tính slot trước để biểu thức lồng nhau không ghi đè vùng băm
+                self.compile(slot);
+                self.compile(key);
+                self.push(U256::zero());
+                self.emit(MSTORE);
+                self.push(U256::from(0x20));
+                self.emit(MSTORE);
+                self.push(U256::from(0x40));
+                self.push(U256::zero());
+                self.emit(KECCAK256);
+            },
+        }
+    }
+
+    /// Thực thi biểu thức, bỏ giá trị trả về nếu có
+    pub fn exec(&mut self, expr: Expr) {
+        self.compile(&expr);
+        if let Expr::Op(code, _) = expr {
+            if has_output(code) {
+                self.emit(POP);
+            }
+        } else {
+            self.emit(POP);
+        }
+    }
+
+    pub fn set(&mut self, var: Var, value: impl Into<Expr>) {
+        self.compile(&value.into());
+        self.push(U256::from(var.addr()));
+        self.emit(MSTORE);
+    }
+
+    /// Cấp biến mới và gán giá trị
+    pub fn let_(&mut self, value: impl Into<Expr>) -> Var {
+        let var = self.var();
+        self.set(var, value);
+        var
+    }
+
+    pub fn mstore(&mut self, offset: impl Into<Expr>, value: impl Into<Expr>) {
+        self.exec(op(MSTORE, [offset.into(), value.into()]));
+    }
+
+    pub fn sstore(&mut self, slot: impl Into<Expr>, value: impl Into<Expr>) {
+        self.exec(op(SSTORE, [slot.into(), value.into()]));
+    }
+
+    pub fn jump(&mut self, name: &str) {
+        self.push_label(name);
+        self.emit(JUMP);
+    }
+
+    pub fn jump_if(&mut self, cond: impl Into<Expr>, name: &str) {
+        self.compile(&cond.into());
+        self.push_label(name);
+        self.emit(JUMPI);
+    }
+
+    pub fn if_(&mut self, cond: impl Into<Expr>, body: impl FnOnce(&mut Self)) {
+        let end = self.fresh_label();
+        self.jump_if(iszero(cond), &end);
+        body(self);
+        self.place(&end);
+    }
+
+    pub fn if_else(&mut self, cond: impl Into<Expr>, then: impl FnOnce(&mut Self), otherwise: impl FnOnce(&mut Self)) {
+        let (other, end) = (self.fresh_label(), self.fresh_label());
+        self.jump_if(iszero(cond), &other);
+        then(self);
+        self.jump(&end);
+        self.place(&other);
+        otherwise(self);
+        self.place(&end);
+    }
+
+    pub fn while_(&mut self, cond: impl Into<Expr>, body: impl FnOnce(&mut Self)) {
+        let (start, end) = (self.fresh_label(), self.fresh_label());
+        self.place(&start);
+        self.jump_if(iszero(cond), &end);
+        body(self);
+        self.jump(&start);
+        self.place(&end);
+    }
+
+    /// Revert với `Error(string)` như `require(cond, reason)` của Solidity
+    pub fn require(&mut self, cond: impl Into<Expr>, reason: &str) {
+        let ok = self.fresh_label();
+        self.jump_if(cond, &ok);
+        self.revert_with(reason);
+        self.place(&ok);
+    }
+
+    /// Revert lại nguyên dữ liệu lỗi của lời gọi con khi `success` bằng 0
+    /// (hành vi của lời gọi hàm cấp cao trong Solidity)
+    pub fn bubble_revert(&mut self, success: impl Into<Expr>) {
+        let ok = self.fresh_label();
+        self.jump_if(success, &ok);
+        self.exec(op(RETURNDATACOPY, [0_u64.into(), 0_u64.into(), returndatasize()]));
+        self.exec(op(REVERT, [0_u64.into(), returndatasize()]));
+        self.place(&ok);
+    }
+
+    pub fn revert_with(&mut self, reason: &str) {
+        let bytes = reason.as_bytes();
+        self.mstore(0_u64, shl(224_u64, selector(ERROR_SELECTOR)));
+        self.mstore(4_u64, 0x20_u64);
+        self.mstore(0x24_u64, bytes.len() as u64);
+        for (index, chunk) in bytes.chunks(32).enumerate() {
+            let mut word = [0_u8; 32];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.mstore(0x44 + 32 * index as u64, U256::from_big_endian(&word));
+        }
+        let size = 0x44 + 32 * bytes.len().div_ceil(32) as u64;
+        self.exec(op(REVERT, [0_u64.into(), size.into()]));
+    }
+
+    /// Trả về các word ABI tĩnh
+    pub fn ret(&mut self, values: Vec<Expr>) {
+        let size = 32 * values.len() as u64;
+        for (index, value) in values.into_iter().enumerate() {
+            self.mstore(RET_BUF + 32 * index as u64, value);
+        }
+        self.exec(op(RETURN, [RET_BUF.into(), size.into()]));
+    }
+
+    /// Trả về một `string` ABI (không quá 32 byte)
+    pub fn ret_string(&mut self, value: &str) {
+        assert!(value.len() <= 32, "chuỗi mock quá dài: {}", value);
+        let mut word = [0_u8; 32];
+        word[..value.len()].copy_from_slice(value.as_bytes());
+        self.mstore(RET_BUF, 0x20_u64);
+        self.mstore(RET_BUF + 0x20, value.len() as u64);
+        self.mstore(RET_BUF + 0x40, U256::from_big_endian(&word));
+        self.exec(op(RETURN, [RET_BUF.into(), 0x60_u64.into()]));
+    }
+
+    pub fn return_memory(&mut self, offset: impl Into<Expr>, size: impl Into<Expr>) {
+        self.exec(op(RETURN, [offset.into(), size.into()]));
+    }
+
+    pub fn stop(&mut self) {
+        self.emit(STOP);
+    }
+
+    /// Phát event với topic và dữ liệu là các word ABI
+    pub fn log(&mut self, topics: Vec<Expr>, data: Vec<Expr>) {
+        assert!(topics.len() <= 4);
+        let size = 32 * data.len() as u64;
+        for (index, value) in data.into_iter().enumerate() {
+            self.mstore(LOG_BUF + 32 * index as u64, value);
+        }
+        let mut args = vec![Expr::from(LOG_BUF), Expr::from(size)];
+        let code = LOG0 + topics.len() as u8;
+        args.extend(topics);
+        self.exec(Expr::Op(code, args));
+    }
+
+    /// Ghi calldata `signature(args...)` vào bộ đệm, trả về độ dài
+    fn encode_call(&mut self, signature: &str, args: Vec<Expr>) -> u64 {
+        let size = 4 + 32 * args.len() as u64;
+        self.mstore(CALL_IN, shl(224_u64, selector(signature)));
+        for (index, value) in args.into_iter().enumerate() {
+            self.mstore(CALL_IN + 4 + 32 * index as u64, value);
+        }
+        size
+    }
+
+    /// Gọi hàm ngoài (CALL), trả về biến cờ thành công; kết quả nằm ở `CALL_OUT`
+    pub fn call(&mut self, target: impl Into<Expr>, value: impl Into<Expr>, signature: &str, args: Vec<Expr>) -> Var {
+        let size = self.encode_call(signature, args);
+        let success = self.var();
+        self.set(success, op(CALL, [gas(), target.into(), value.into(), CALL_IN.into(), size.into(), CALL_OUT.into(), 0x80_u64.into()]));
+        success
+    }
+
+    /// Gọi hàm view (STATICCALL) và bắt buộc thành công; kết quả nằm ở `CALL_OUT`
+    pub fn static_call(&mut self, target: impl Into<Expr>, signature: &str, args: Vec<Expr>) {
+        let size = self.encode_call(signature, args);
+        let call = op(STATICCALL, [gas(), target.into(), CALL_IN.into(), size.into(), CALL_OUT.into(), 0x80_u64.into()]);
+        self.require(call, "mock: static call failed");
+    }
+
+    /// Bytecode hoàn chỉnh sau khi điền địa chỉ nhãn
+    pub fn build(mut self) -> Vec<u8> {
+        for (position, name) in std::mem::take(&mut self.fixups) {
+            let target = *self.labels.get(&name)
+                .unwrap_or_else(|| panic!("nhãn chưa được đặt: {}", name));
+            assert!(target <= u16::MAX as usize, "bytecode mock quá lớn");
+            self.bytes[position..position + 2].copy_from_slice(&(target as u16).to_be_bytes());
+        }
+        self.bytes
+    }
+}
+
+/// Thân hàm của hợp đồng, sinh code khi dựng bytecode
+type Body = Box<dyn FnOnce(&mut Code)>;
+
+/// Hợp đồng với bảng dispatch theo selector
+#[derive(Default)]
+pub struct Contract {
+    code: Code,
+    functions: Vec<(Vec<U256>, String)>,
+    bodies: Vec<(String, Body)>,
+    fallback: Option<Body>,
+}
+
+impl Contract {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Khai báo hàm theo chữ ký Solidity (VD: "transfer(address,uint256)")
+    pub fn function(&mut self, signature: &str, body: impl FnOnce(&mut Code) + 'static) -> &mut Self {
+        self.functions_with_aliases(&[signature], body)
+    }
+
+    /// Khai báo nhiều chữ ký dùng chung một thân hàm
+    pub fn functions_with_aliases(&mut self, signatures: &[&str], body: impl FnOnce(&mut Code) + 'static) -> &mut Self {
+        let name = format!("fn_{}", signatures[0]);
+        self.functions.push((signatures.iter().map(|sig| selector(sig)).collect(), name.clone()));
+        self.bodies.push((name, Box::new(body)));
+        self
+    }
+
+    /// Thân hàm khi không khớp selector nào (mặc định: revert)
+    pub fn fallback(&mut self, body: impl FnOnce(&mut Code) + 'static) -> &mut Self {
+        self.fallback = Some(Box::new(body));
+        self
+    }
+
+    /// Bytecode runtime, kèm các vùng dữ liệu nhúng
+    pub fn build(self, embedded: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut code = self.code;
+        let sig = shr(224_u64, calldataload(0_u64));
+        code.jump_if(lt(calldatasize(), 4_u64), "fallback");
+        for (selectors, name) in &self.functions {
+            for selector in selectors {
+                code.jump_if(eq(sig.clone(), *selector), name);
+            }
+        }
+        code.place("fallback");
+        match self.fallback {
+            Some(body) => {
+                code.reset_vars();
+                body(&mut code);
+                code.stop();
+            },
+            None => code.exec(op(REVERT, [0_u64.into(), 0_u64.into()])),
+        }
+        for (name, body) in self.bodies {
+            code.place(&name);
+            code.reset_vars();
+            body(&mut code);
+            code.stop();
+        }
+        for (name, data) in embedded {
+            code.data(name, data);
+        }
+        code.build()
+    }
+}
+
+/// Init code: chạy `constructor` rồi trả về `runtime`
+pub fn init_code(runtime: &[u8], constructor: impl FnOnce(&mut Code)) -> Vec<u8> {
+    let mut code = Code::new();
+    constructor(&mut code);
+    code.exec(op(CODECOPY, [0_u64.into(), label("runtime"), (runtime.len() as u64).into()]));
+    code.exec(op(RETURN, [0_u64.into(), (runtime.len() as u64).into()]));
+    code.data("runtime", runtime);
+    code.build()
+}
@@ -0,0 +1,95 @@
+// External imports
+use async_trait::async_trait;
+use ethers::{
+    abi::Token,
+    providers::{Http, Middleware, Provider},
+    types::{Address, BlockId, Bytes, Filter, Log, TransactionReceipt, TransactionRequest, H256, U256},
+};
+
+// Standard library imports
+use std::time::Duration;
+
+// Internal imports
+use super::mock_chain::MockChain;
+use crate::chain_adapters::approval_manager::encode_call;
+use crate::chain_adapters::interfaces::{ChainAdapter, ChainError};
+
+/// Adapter tối giản trên mock chain cho test các module dùng chung `ChainAdapter`
+/// (ApprovalManager, Treasury, PortfolioIndexer): chỉ cài các hàm đọc và chờ receipt
+#[derive(Debug)]
+pub struct MockChainAdapter {
+    provider: Provider<Http>,
+    chain_id: u64,
+}
+
+impl MockChainAdapter {
+    pub fn new(chain: &MockChain) -> Self {
+        Self {
+            provider: Provider::<Http>::try_from(chain.http_url()).unwrap(),
+            chain_id: chain.chain_id(),
+        }
+    }
+}
+
+#[async_trait]
+impl ChainAdapter for MockChainAdapter {
+    async fn get_block_number(&self) -> Result<u64, ChainError> {
+        self.provider.get_block_number().await
+            .map(|number| number.as_u64())
+            .map_err(|e| ChainError::from_anyhow(e.into()))
+    }
+
+    async fn get_gas_price(&self) -> Result<U256, ChainError> {
+        self.provider.get_gas_price().await.map_err(|e| ChainError::from_anyhow(e.into()))
+    }
+
+    fn get_chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn get_type(&self) -> String {
+        "mock".to_string()
+    }
+
+    async fn get_eth_balance(&self, address: Address, block: Option<BlockId>) -> Result<U256, ChainError> {
+        self.provider.get_balance(address, block).await.map_err(|e| ChainError::from_anyhow(e.into()))
+    }
+
+    async fn get_token_balance(&self, token: Address, address: Address, block: Option<BlockId>) -> Result<U256, ChainError> {
+        let data = encode_call("balanceOf(address)", &[Token::Address(address)]);
+        let output = self.call(&TransactionRequest::new().to(token).data(data), block).await?;
+        Ok(U256::from_big_endian(&output))
+    }
+
+    async fn get_token_allowance(&self, token: Address, owner: Address, spender: Address) -> Result<U256, ChainError> {
+        let data = encode_call("allowance(address,address)", &[Token::Address(owner), Token::Address(spender)]);
+        let output = self.call(&TransactionRequest::new().to(token).data(data), None).await?;
+        Ok(U256::from_big_endian(&output))
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, ChainError> {
+        self.provider.get_logs(filter).await.map_err(|e| ChainError::from_anyhow(e.into()))
+    }
+
+    async fn call(&self, tx: &TransactionRequest, block: Option<BlockId>) -> Result<Bytes, ChainError> {
+        self.provider.call(&tx.clone().into(), block).await.map_err(|e| ChainError::from_anyhow(e.into()))
+    }
+
+    async fn wait_for_transaction_receipt(
+        &self,
+        tx_hash: H256,
+        _confirmations: usize,
+        timeout: Duration,
+    ) -> Result<TransactionReceipt, ChainError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let receipt = self.provider.get_transaction_receipt(tx_hash).await
+                .map_err(|e| ChainError::from_anyhow(e.into()))?;
+            match receipt {
+                Some(receipt) => return Ok(receipt),
+                None if tokio::time::Instant::now() >= deadline => return Err(ChainError::ReceiptNotFound(tx_hash)),
+                None => tokio::time::sleep(Duration::from_millis(20)).await,
+            }
+        }
+    }
+}
@@ -0,0 +1,1468 @@
+// External imports
+use ethers::{
+    abi::{self, ethereum_types::BloomInput, ParamType, Token},
+    signers::{LocalWallet, Signer},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Block, Bloom, Bytes, Log, Transaction,
+        TransactionReceipt, H256, H64, U256, U64,
+    },
+    utils::{keccak256, rlp},
+};
+use futures::{SinkExt, StreamExt};
+use revm::{
+    db::{CacheDB, EmptyDB},
+    primitives::{
+        AccountInfo, Address as EvmAddress, BlockEnv, Bytecode, Bytes as EvmBytes, ExecutionResult, Output,
+        ResultAndState, SpecId, TxEnv, TxKind, B256, U256 as EvmU256,
+    },
+    DatabaseCommit, DatabaseRef, Evm,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::broadcast,
+    task::JoinHandle,
+};
+use tokio_tungstenite::tungstenite::Message;
+
+// Standard library imports
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// Third party imports
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+
+// Internal imports
+use super::mock_contracts::{self, TokenSpec};
+
+/// Số ví dev được cấp sẵn ETH ở block genesis
+pub const DEV_ACCOUNT_COUNT: usize = 10;
+
+/// Số dư ETH của mỗi ví dev (10_000 ETH)
+pub const DEV_ACCOUNT_BALANCE: u128 = 10_000 * 1_000_000_000_000_000_000;
+
+/// Base fee mặc định (1 gwei)
+pub const DEFAULT_BASE_FEE: u64 = 1_000_000_000;
+
+/// Priority fee gợi ý (1 gwei)
+pub const DEFAULT_PRIORITY_FEE: u64 = 1_000_000_000;
+
+/// Gas limit của một block
+pub const BLOCK_GAS_LIMIT: u64 = 30_000_000;
+
+/// Gas limit cho giao dịch nội bộ của mock (deploy, mint...)
+const INTERNAL_TX_GAS: u64 = 10_000_000;
+
+/// Mã lỗi JSON-RPC khi vượt rate limit (theo Infura/Alchemy)
+const RATE_LIMIT_ERROR_CODE: i64 = -32005;
+
+/// Mã lỗi JSON-RPC của giao dịch bị revert
+const EXECUTION_REVERTED_CODE: i64 = 3;
+
+/// Lỗi JSON-RPC trả về cho client
+#[derive(Debug, Clone)]
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into(), data: None }
+    }
+
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(-32602, message)
+    }
+
+    fn server(message: impl Into<String>) -> Self {
+        Self::new(-32000, message)
+    }
+
+    fn to_json(&self) -> Value {
+        let mut error = json!({"code": self.code, "message": self.message});
+        if let Some(data) = &self.data {
+            error["data"] = data.clone();
+        }
+        error
+    }
+}
+
+type RpcResult = std::result::Result<Value, RpcError>;
+
+/// Sự kiện phát cho các subscription qua WebSocket
+#[derive(Debug, Clone)]
+enum ChainEvent {
+    NewHead(Value),
+    Log(Box<Log>),
+    PendingTransaction(H256),
+}
+
+/// Lỗi giả lập áp cho các request tiếp theo
+#[derive(Debug, Default, Clone)]
+struct Faults {
+    /// Độ trễ trước mỗi phản hồi
+    latency: Duration,
+    /// Số request tiếp theo bị trả HTTP 429
+    rate_limited_requests: u32,
+    /// Số giao dịch tiếp theo được nhận nhưng không bao giờ vào block
+    dropped_transactions: u32,
+}
+
+/// Block đã mine cùng trạng thái EVM sau block
+#[derive(Debug, Clone)]
+struct MinedBlock {
+    header: Block<H256>,
+    logs: Vec<Log>,
+    state: CacheDB<EmptyDB>,
+}
+
+impl MinedBlock {
+    fn hash(&self) -> H256 {
+        self.header.hash.unwrap_or_default()
+    }
+
+    fn number(&self) -> u64 {
+        self.header.number.unwrap_or_default().as_u64()
+    }
+}
+
+/// Trạng thái chain: các block, mempool, receipt và cấu hình lỗi giả lập
+struct ChainState {
+    chain_id: u64,
+    blocks: Vec<MinedBlock>,
+    pending: Vec<Transaction>,
+    transactions: HashMap<H256, Transaction>,
+    receipts: HashMap<H256, TransactionReceipt>,
+    revert_reasons: HashMap<H256, String>,
+    automine: bool,
+    base_fee: U256,
+    reorg_count: u64,
+    faults: Faults,
+    method_calls: HashMap<String, usize>,
+    events: broadcast::Sender<ChainEvent>,
+}
+
+fn to_evm_address(address: Address) -> EvmAddress {
+    EvmAddress::from(address.0)
+}
+
+fn from_evm_address(address: EvmAddress) -> Address {
+    Address::from_slice(address.as_slice())
+}
+
+fn to_evm_u256(value: U256) -> EvmU256 {
+    let mut bytes = [0_u8; 32];
+    value.to_big_endian(&mut bytes);
+    EvmU256::from_be_bytes(bytes)
+}
+
+fn from_evm_u256(value: EvmU256) -> U256 {
+    U256::from_big_endian(&value.to_be_bytes::<32>())
+}
+
+fn quantity(value: impl Into<U256>) -> Value {
+    json!(format!("{:#x}", value.into()))
+}
+
+fn parse_u256(value: &Value) -> std::result::Result<U256, RpcError> {
+    match value {
+        Value::String(text) => U256::from_str_radix(text.trim_start_matches("0x"), 16)
+            .map_err(|e| RpcError::invalid_params(format!("số không hợp lệ {}: {}", text, e))),
+        Value::Number(number) => number.as_u64().map(U256::from)
+            .ok_or_else(|| RpcError::invalid_params(format!("số không hợp lệ: {}", number))),
+        _ => Err(RpcError::invalid_params(format!("thiếu số: {}", value))),
+    }
+}
+
+fn parse_address(value: &Value) -> std::result::Result<Address, RpcError> {
+    serde_json::from_value(value.clone())
+        .map_err(|_| RpcError::invalid_params(format!("địa chỉ không hợp lệ: {}", value)))
+}
+
+fn parse_hash(value: &Value) -> std::result::Result<H256, RpcError> {
+    serde_json::from_value(value.clone())
+        .map_err(|_| RpcError::invalid_params(format!("hash không hợp lệ: {}", value)))
+}
+
+fn parse_bytes(value: &Value) -> std::result::Result<Bytes, RpcError> {
+    if value.is_null() {
+        return Ok(Bytes::default());
+    }
+    serde_json::from_value(value.clone())
+        .map_err(|_| RpcError::invalid_params(format!("dữ liệu hex không hợp lệ: {}", value)))
+}
+
+/// Lý do revert từ output `Error(string)` (nếu có)
+fn revert_reason(output: &[u8]) -> String {
+    if output.len() > 4 && output[..4] == ethers::utils::id("Error(string)") {
+        if let Ok(tokens) = abi::decode(&[ParamType::String], &output[4..]) {
+            if let Some(Token::String(reason)) = tokens.into_iter().next() {
+                return reason;
+            }
+        }
+    }
+    format!("0x{}", hex::encode(output))
+}
+
+/// Calldata `signature(args...)`
+pub fn calldata(signature: &str, args: &[Token]) -> Vec<u8> {
+    let mut data = ethers::utils::id(signature).to_vec();
+    data.extend(abi::encode(args));
+    data
+}
+
+fn logs_bloom(logs: &[Log]) -> Bloom {
+    let mut bloom = Bloom::default();
+    for log in logs {
+        bloom.accrue(BloomInput::Raw(log.address.as_bytes()));
+        for topic in &log.topics {
+            bloom.accrue(BloomInput::Raw(topic.as_bytes()));
+        }
+    }
+    bloom
+}
+
+/// Bộ lọc log của `eth_getLogs` và subscription `logs`
+#[derive(Debug, Clone, Default)]
+struct LogFilter {
+    from_block: Option<u64>,
+    to_block: Option<u64>,
+    block_hash: Option<H256>,
+    addresses: Vec<Address>,
+    topics: Vec<Option<Vec<H256>>>,
+}
+
+impl LogFilter {
+    fn parse(value: &Value, state: &ChainState) -> std::result::Result<Self, RpcError> {
+        let mut filter = LogFilter::default();
+        if value.is_null() {
+            return Ok(filter);
+        }
+        if !value["blockHash"].is_null() {
+            filter.block_hash = Some(parse_hash(&value["blockHash"])?);
+        }
+        if !value["fromBlock"].is_null() {
+            filter.from_block = state.resolve_block_tag(&value["fromBlock"])?;
+        }
+        if !value["toBlock"].is_null() {
+            filter.to_block = state.resolve_block_tag(&value["toBlock"])?;
+        }
+        match &value["address"] {
+            Value::Null => {},
+            Value::Array(items) => {
+                filter.addresses = items.iter().map(parse_address).collect::<std::result::Result<_, _>>()?;
+            },
+            single => filter.addresses = vec![parse_address(single)?],
+        }
+        if let Value::Array(topics) = &value["topics"] {
+            for topic in topics {
+                filter.topics.push(match topic {
+                    Value::Null => None,
+                    Value::Array(options) => Some(options.iter().map(parse_hash).collect::<std::result::Result<_, _>>()?),
+                    single => Some(vec![parse_hash(single)?]),
+                });
+            }
+        }
+        Ok(filter)
+    }
+
+    fn matches(&self, log: &Log) -> bool {
+        if !self.addresses.is_empty() && !self.addresses.contains(&log.address) {
+            return false;
+        }
+        self.topics.iter().enumerate().all(|(index, expected)| match expected {
+            None => true,
+            Some(options) => log.topics.get(index).map(|topic| options.contains(topic)).unwrap_or(false),
+        })
+    }
+}
+
+impl ChainState {
+    fn genesis(chain_id: u64, events: broadcast::Sender<ChainEvent>) -> Self {
+        let mut db = CacheDB::new(EmptyDB::default());
+        for wallet in dev_wallets(chain_id) {
+            db.insert_account_info(
+                to_evm_address(wallet.address()),
+                AccountInfo::from_balance(EvmU256::from(DEV_ACCOUNT_BALANCE)),
+            );
+        }
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        let mut state = Self {
+            chain_id,
+            blocks: Vec::new(),
+            pending: Vec::new(),
+            transactions: HashMap::new(),
+            receipts: HashMap::new(),
+            revert_reasons: HashMap::new(),
+            automine: true,
+            base_fee: U256::from(DEFAULT_BASE_FEE),
+            reorg_count: 0,
+            faults: Faults::default(),
+            method_calls: HashMap::new(),
+            events,
+        };
+        let header = state.block_header(0, H256::zero(), timestamp, Vec::new(), 0, &[]);
+        state.blocks.push(MinedBlock { header, logs: Vec::new(), state: db });
+        state
+    }
+
+    fn latest(&self) -> &MinedBlock {
+        self.blocks.last().expect("chain luôn có block genesis")
+    }
+
+    fn latest_number(&self) -> u64 {
+        self.latest().number()
+    }
+
+    /// Số block theo tag; `None` nếu block chưa tồn tại
+    fn resolve_block_tag(&self, tag: &Value) -> std::result::Result<Option<u64>, RpcError> {
+        match tag {
+            Value::Null => Ok(Some(self.latest_number())),
+            Value::String(text) => match text.as_str() {
+                "latest" | "pending" | "safe" | "finalized" => Ok(Some(self.latest_number())),
+                "earliest" => Ok(Some(0)),
+                _ => {
+                    let number = parse_u256(tag)?.as_u64();
+                    Ok((number <= self.latest_number()).then_some(number))
+                },
+            },
+            Value::Object(object) => {
+                if let Some(hash) = object.get("blockHash") {
+                    let hash = parse_hash(hash)?;
+                    Ok(self.blocks.iter().find(|block| block.hash() == hash).map(MinedBlock::number))
+                } else {
+                    self.resolve_block_tag(object.get("blockNumber").unwrap_or(&Value::Null))
+                }
+            },
+            Value::Number(_) => {
+                let number = parse_u256(tag)?.as_u64();
+                Ok((number <= self.latest_number()).then_some(number))
+            },
+            _ => Err(RpcError::invalid_params(format!("block tag không hợp lệ: {}", tag))),
+        }
+    }
+
+    /// Trạng thái EVM tại block theo tag
+    fn state_at(&self, tag: &Value) -> std::result::Result<&CacheDB<EmptyDB>, RpcError> {
+        let number = self.resolve_block_tag(tag)?
+            .ok_or_else(|| RpcError::server("header not found"))?;
+        Ok(&self.blocks[number as usize].state)
+    }
+
+    fn block_header(&self, number: u64, parent_hash: H256, timestamp: u64, transactions: Vec<H256>, gas_used: u64, logs: &[Log]) -> Block<H256> {
+        let mut preimage = Vec::new();
+        preimage.extend_from_slice(&number.to_be_bytes());
+        preimage.extend_from_slice(parent_hash.as_bytes());
+        preimage.extend_from_slice(&timestamp.to_be_bytes());
+        preimage.extend_from_slice(&self.reorg_count.to_be_bytes());
+        for hash in &transactions {
+            preimage.extend_from_slice(hash.as_bytes());
+        }
+        Block {
+            hash: Some(H256::from(keccak256(&preimage))),
+            parent_hash,
+            number: Some(U64::from(number)),
+            timestamp: U256::from(timestamp),
+            gas_limit: U256::from(BLOCK_GAS_LIMIT),
+            gas_used: U256::from(gas_used),
+            base_fee_per_gas: Some(self.base_fee),
+            logs_bloom: Some(logs_bloom(logs)),
+            author: Some(Address::zero()),
+            difficulty: U256::zero(),
+            total_difficulty: Some(U256::zero()),
+            nonce: Some(H64::zero()),
+            mix_hash: Some(H256::zero()),
+            size: Some(U256::from(512 + 32 * transactions.len())),
+            transactions,
+            ..Default::default()
+        }
+    }
+
+    fn nonce_of(db: &CacheDB<EmptyDB>, address: Address) -> u64 {
+        db.basic_ref(to_evm_address(address)).ok().flatten().map(|info| info.nonce).unwrap_or_default()
+    }
+
+    /// Nonce kế tiếp tính cả các giao dịch liên tiếp trong mempool
+    fn pending_nonce(&self, address: Address) -> u64 {
+        let mut nonce = Self::nonce_of(&self.latest().state, address);
+        while self.pending.iter().any(|tx| tx.from == address && tx.nonce.as_u64() == nonce) {
+            nonce += 1;
+        }
+        nonce
+    }
+
+    fn tx_env(&self, tx: &Transaction) -> TxEnv {
+        let mut env = TxEnv {
+            caller: to_evm_address(tx.from),
+            gas_limit: tx.gas.as_u64(),
+            transact_to: tx.to.map(|to| TxKind::Call(to_evm_address(to))).unwrap_or(TxKind::Create),
+            value: to_evm_u256(tx.value),
+            data: EvmBytes::from(tx.input.to_vec()),
+            nonce: Some(tx.nonce.as_u64()),
+            chain_id: tx.chain_id.map(|id| id.as_u64()),
+            ..Default::default()
+        };
+        if tx.transaction_type == Some(U64::from(2)) {
+            env.gas_price = to_evm_u256(tx.max_fee_per_gas.unwrap_or_default());
+            env.gas_priority_fee = Some(to_evm_u256(tx.max_priority_fee_per_gas.unwrap_or_default()));
+        } else {
+            env.gas_price = to_evm_u256(tx.gas_price.unwrap_or_default());
+        }
+        env
+    }
+
+    fn block_env(&self, number: u64, timestamp: u64, base_fee: U256) -> BlockEnv {
+        BlockEnv {
+            number: EvmU256::from(number),
+            timestamp: EvmU256::from(timestamp),
+            gas_limit: EvmU256::from(BLOCK_GAS_LIMIT),
+            basefee: to_evm_u256(base_fee),
+            prevrandao: Some(B256::ZERO),
+            ..Default::default()
+        }
+    }
+
+    fn execute(&self, db: &CacheDB<EmptyDB>, block: BlockEnv, tx: TxEnv) -> std::result::Result<ResultAndState, String> {
+        let mut evm = Evm::builder()
+            .with_ref_db(db)
+            .with_spec_id(SpecId::CANCUN)
+            .modify_cfg_env(|cfg| cfg.chain_id = self.chain_id)
+            .modify_block_env(|env| *env = block)
+            .modify_tx_env(|env| *env = tx)
+            .build();
+        evm.transact().map_err(|e| e.to_string())
+    }
+
+    /// Giá gas thực trả của giao dịch theo base fee của block
+    fn effective_gas_price(tx: &Transaction, base_fee: U256) -> U256 {
+        match (tx.max_fee_per_gas, tx.max_priority_fee_per_gas) {
+            (Some(max_fee), Some(priority)) => max_fee.min(base_fee + priority),
+            _ => tx.gas_price.unwrap_or_default(),
+        }
+    }
+
+    fn emit(&self, event: ChainEvent) {
+        // Không có subscriber thì bỏ qua
+        let _ = self.events.send(event);
+    }
+
+    /// Mine một block chứa mọi giao dịch thực thi được trong mempool.
+    /// Giao dịch không hợp lệ (thiếu tiền, sai nonce...) bị loại và trả về kèm lý do
+    fn mine_block(&mut self) -> Vec<(H256, String)> {
+        let parent = self.latest();
+        let number = parent.number() + 1;
+        let parent_hash = parent.hash();
+        let timestamp = parent.header.timestamp.as_u64() + 1;
+        let mut db = parent.state.clone();
+        let block_env = self.block_env(number, timestamp, self.base_fee);
+
+        let mut included = Vec::new();
+        let mut receipts = Vec::new();
+        let mut block_logs = Vec::new();
+        let mut rejected = Vec::new();
+        let mut cumulative_gas = 0_u64;
+
+        while let Some(position) = self.pending.iter().position(|tx| Self::nonce_of(&db, tx.from) == tx.nonce.as_u64()) {
+            if cumulative_gas + self.pending[position].gas.as_u64() > BLOCK_GAS_LIMIT {
+                break;
+            }
+            let mut tx = self.pending.remove(position);
+            let outcome = match self.execute(&db, block_env.clone(), self.tx_env(&tx)) {
+                Ok(outcome) => outcome,
+                Err(reason) => {
+                    self.transactions.remove(&tx.hash);
+                    rejected.push((tx.hash, reason));
+                    continue;
+                },
+            };
+            db.commit(outcome.state);
+
+            let (gas_used, status, contract_address, logs) = match outcome.result {
+                ExecutionResult::Success { gas_used, logs, output, .. } => {
+                    let created = match output {
+                        Output::Create(_, address) => address.map(from_evm_address),
+                        Output::Call(_) => None,
+                    };
+                    (gas_used, 1_u64, created, logs)
+                },
+                ExecutionResult::Revert { gas_used, output } => {
+                    self.revert_reasons.insert(tx.hash, revert_reason(&output));
+                    (gas_used, 0, None, Vec::new())
+                },
+                ExecutionResult::Halt { gas_used, reason } => {
+                    self.revert_reasons.insert(tx.hash, format!("{:?}", reason));
+                    (gas_used, 0, None, Vec::new())
+                },
+            };
+            cumulative_gas += gas_used;
+
+            let transaction_index = included.len() as u64;
+            let logs: Vec<Log> = logs.into_iter().enumerate().map(|(index, log)| Log {
+                address: from_evm_address(log.address),
+                topics: log.data.topics().iter().map(|topic| H256::from_slice(topic.as_slice())).collect(),
+                data: Bytes::from(log.data.data.to_vec()),
+                block_number: Some(U64::from(number)),
+                transaction_hash: Some(tx.hash),
+                transaction_index: Some(U64::from(transaction_index)),
+                log_index: Some(U256::from(block_logs.len() + index)),
+                transaction_log_index: Some(U256::from(index)),
+                removed: Some(false),
+                ..Default::default()
+            }).collect();
+            block_logs.extend(logs.iter().cloned());
+
+            tx.transaction_index = Some(U64::from(transaction_index));
+            tx.block_number = Some(U64::from(number));
+            let effective_gas_price = Self::effective_gas_price(&tx, self.base_fee);
+            receipts.push(TransactionReceipt {
+                transaction_hash: tx.hash,
+                transaction_index: U64::from(transaction_index),
+                block_number: Some(U64::from(number)),
+                from: tx.from,
+                to: tx.to,
+                cumulative_gas_used: U256::from(cumulative_gas),
+                gas_used: Some(U256::from(gas_used)),
+                contract_address,
+                logs_bloom: logs_bloom(&logs),
+                logs,
+                status: Some(U64::from(status)),
+                transaction_type: tx.transaction_type,
+                effective_gas_price: Some(effective_gas_price),
+                ..Default::default()
+            });
+            included.push(tx);
+        }
+
+        // Gắn hash block sau khi biết danh sách giao dịch
+        let hashes: Vec<H256> = included.iter().map(|tx| tx.hash).collect();
+        let header = self.block_header(number, parent_hash, timestamp, hashes, cumulative_gas, &block_logs);
+        let block_hash = header.hash;
+        for log in block_logs.iter_mut() {
+            log.block_hash = block_hash;
+        }
+        for mut receipt in receipts {
+            receipt.block_hash = block_hash;
+            for log in receipt.logs.iter_mut() {
+                log.block_hash = block_hash;
+            }
+            self.receipts.insert(receipt.transaction_hash, receipt);
+        }
+        for mut tx in included {
+            tx.block_hash = block_hash;
+            if tx.transaction_type == Some(U64::from(2)) {
+                tx.gas_price = Some(Self::effective_gas_price(&tx, self.base_fee));
+            }
+            self.transactions.insert(tx.hash, tx);
+        }
+
+        let head = serde_json::to_value(&header).unwrap_or_default();
+        self.blocks.push(MinedBlock { header, logs: block_logs.clone(), state: db });
+        self.emit(ChainEvent::NewHead(head));
+        for log in block_logs {
+            self.emit(ChainEvent::Log(Box::new(log)));
+        }
+        rejected
+    }
+
+    fn has_executable(&self) -> bool {
+        let state = &self.latest().state;
+        self.pending.iter().any(|tx| Self::nonce_of(state, tx.from) == tx.nonce.as_u64())
+    }
+
+    /// Đưa giao dịch vào mempool; khi automine thì mine ngay và trả lỗi nếu bị loại
+    fn submit(&mut self, tx: Transaction, force_mine: bool) -> std::result::Result<H256, RpcError> {
+        let hash = tx.hash;
+        if self.transactions.contains_key(&hash) {
+            return Err(RpcError::server("already known"));
+        }
+        if let Some(chain_id) = tx.chain_id {
+            if chain_id != U256::from(self.chain_id) {
+                return Err(RpcError::server(format!("invalid chain id {}", chain_id)));
+            }
+        }
+        if tx.nonce.as_u64() < Self::nonce_of(&self.latest().state, tx.from) {
+            return Err(RpcError::server("nonce too low"));
+        }
+        if let Some(position) = self.pending.iter().position(|pending| pending.from == tx.from && pending.nonce == tx.nonce) {
+            let price = |tx: &Transaction| tx.max_fee_per_gas.or(tx.gas_price).unwrap_or_default();
+            if price(&tx) <= price(&self.pending[position]) {
+                return Err(RpcError::server("replacement transaction underpriced"));
+            }
+            let replaced = self.pending.remove(position);
+            self.transactions.remove(&replaced.hash);
+        }
+
+        self.transactions.insert(hash, tx.clone());
+        self.pending.push(tx);
+        self.emit(ChainEvent::PendingTransaction(hash));
+
+        if force_mine || self.automine {
+            while self.has_executable() {
+                let rejected = self.mine_block();
+                if let Some((_, reason)) = rejected.iter().find(|(rejected, _)| *rejected == hash) {
+                    return Err(RpcError::server(reason.clone()));
+                }
+            }
+        }
+        Ok(hash)
+    }
+
+    /// Giao dịch nội bộ không cần chữ ký (giả danh `from`, như anvil impersonation)
+    fn internal_transaction(&self, from: Address, to: Option<Address>, value: U256, data: Vec<u8>) -> Transaction {
+        let nonce = self.pending_nonce(from);
+        let mut preimage = b"mock-internal".to_vec();
+        preimage.extend_from_slice(from.as_bytes());
+        preimage.extend_from_slice(&nonce.to_be_bytes());
+        preimage.extend_from_slice(&self.reorg_count.to_be_bytes());
+        Transaction {
+            hash: H256::from(keccak256(&preimage)),
+            nonce: U256::from(nonce),
+            from,
+            to,
+            value,
+            gas: U256::from(INTERNAL_TX_GAS),
+            gas_price: Some(self.base_fee),
+            input: Bytes::from(data),
+            chain_id: Some(U256::from(self.chain_id)),
+            transaction_type: Some(U64::zero()),
+            ..Default::default()
+        }
+    }
+
+    /// Thực thi lời gọi chỉ đọc trên trạng thái tại `tag`
+    fn simulate(&self, request: &Value, tag: &Value) -> std::result::Result<ExecutionResult, RpcError> {
+        let db = self.state_at(tag)?;
+        let from = if request["from"].is_null() { Address::zero() } else { parse_address(&request["from"])? };
+        let to = if request["to"].is_null() { None } else { Some(parse_address(&request["to"])?) };
+        let data = if request["data"].is_null() { parse_bytes(&request["input"])? } else { parse_bytes(&request["data"])? };
+        let value = if request["value"].is_null() { U256::zero() } else { parse_u256(&request["value"])? };
+        let gas = if request["gas"].is_null() { BLOCK_GAS_LIMIT } else { parse_u256(&request["gas"])?.as_u64() };
+
+        let tx = TxEnv {
+            caller: to_evm_address(from),
+            gas_limit: gas,
+            transact_to: to.map(|to| TxKind::Call(to_evm_address(to))).unwrap_or(TxKind::Create),
+            value: to_evm_u256(value),
+            data: EvmBytes::from(data.to_vec()),
+            nonce: None,
+            ..Default::default()
+        };
+        let latest = self.latest();
+        let block = self.block_env(latest.number() + 1, latest.header.timestamp.as_u64() + 1, U256::zero());
+        self.execute(db, block, tx)
+            .map(|outcome| outcome.result)
+            .map_err(RpcError::server)
+    }
+
+    fn reverted(output: &[u8]) -> RpcError {
+        let mut error = RpcError::new(EXECUTION_REVERTED_CODE, format!("execution reverted: {}", revert_reason(output)));
+        error.data = Some(json!(format!("0x{}", hex::encode(output))));
+        error
+    }
+
+    fn block_json(&self, block: &MinedBlock, full: bool) -> Value {
+        let mut value = serde_json::to_value(&block.header).unwrap_or_default();
+        if full {
+            value["transactions"] = Value::Array(
+                block.header.transactions.iter()
+                    .filter_map(|hash| self.transactions.get(hash))
+                    .map(|tx| serde_json::to_value(tx).unwrap_or_default())
+                    .collect(),
+            );
+        }
+        value
+    }
+
+    fn logs(&self, filter: &LogFilter) -> Vec<Log> {
+        let from = filter.from_block.unwrap_or_else(|| self.latest_number());
+        let to = filter.to_block.unwrap_or_else(|| self.latest_number());
+        self.blocks.iter()
+            .filter(|block| match filter.block_hash {
+                Some(hash) => block.hash() == hash,
+                None => (from..=to).contains(&block.number()),
+            })
+            .flat_map(|block| block.logs.iter())
+            .filter(|log| filter.matches(log))
+            .cloned()
+            .collect()
+    }
+
+    fn fee_history(&self, params: &Value) -> RpcResult {
+        let count = parse_u256(&params[0])?.as_u64().clamp(1, 1024);
+        let newest = self.resolve_block_tag(&params[1])?.unwrap_or_else(|| self.latest_number());
+        let oldest = newest.saturating_sub(count - 1);
+        let percentiles = params[2].as_array().map(Vec::len).unwrap_or(0);
+
+        let blocks = &self.blocks[oldest as usize..=newest as usize];
+        let mut base_fees: Vec<Value> = blocks.iter()
+            .map(|block| quantity(block.header.base_fee_per_gas.unwrap_or_default()))
+            .collect();
+        base_fees.push(quantity(self.base_fee));
+        let ratios: Vec<f64> = blocks.iter()
+            .map(|block| block.header.gas_used.as_u64() as f64 / BLOCK_GAS_LIMIT as f64)
+            .collect();
+        let rewards: Vec<Vec<Value>> = blocks.iter()
+            .map(|_| vec![quantity(DEFAULT_PRIORITY_FEE); percentiles])
+            .collect();
+
+        let mut history = json!({
+            "oldestBlock": quantity(oldest),
+            "baseFeePerGas": base_fees,
+            "gasUsedRatio": ratios,
+        });
+        if percentiles > 0 {
+            history["reward"] = json!(rewards);
+        }
+        Ok(history)
+    }
+
+    fn send_raw_transaction(&mut self, params: &Value) -> RpcResult {
+        let raw = parse_bytes(&params[0])?;
+        let rlp = rlp::Rlp::new(raw.as_ref());
+        let (typed, signature) = TypedTransaction::decode_signed(&rlp)
+            .map_err(|e| RpcError::invalid_params(format!("giao dịch không giải mã được: {}", e)))?;
+        let from = signature.recover(typed.sighash())
+            .map_err(|e| RpcError::invalid_params(format!("chữ ký không hợp lệ: {}", e)))?;
+
+        let mut tx = Transaction {
+            hash: H256::from(keccak256(raw.as_ref())),
+            nonce: typed.nonce().copied().unwrap_or_default(),
+            from,
+            to: typed.to_addr().copied(),
+            value: typed.value().copied().unwrap_or_default(),
+            gas: typed.gas().copied().unwrap_or_default(),
+            input: typed.data().cloned().unwrap_or_default(),
+            v: U64::from(signature.v),
+            r: signature.r,
+            s: signature.s,
+            chain_id: typed.chain_id().map(|id| U256::from(id.as_u64())),
+            ..Default::default()
+        };
+        if let TypedTransaction::Eip1559(request) = &typed {
+            tx.max_fee_per_gas = request.max_fee_per_gas;
+            tx.max_priority_fee_per_gas = request.max_priority_fee_per_gas;
+            tx.access_list = Some(request.access_list.clone());
+            tx.transaction_type = Some(U64::from(2));
+        } else if let TypedTransaction::Eip2930(request) = &typed {
+            tx.gas_price = request.tx.gas_price;
+            tx.access_list = Some(request.access_list.clone());
+            tx.transaction_type = Some(U64::from(1));
+        } else {
+            tx.gas_price = typed.gas_price();
+            tx.transaction_type = Some(U64::zero());
+        }
+
+        if self.faults.dropped_transactions > 0 {
+            // Node nhận giao dịch nhưng làm rơi, giao dịch không bao giờ được mine
+            self.faults.dropped_transactions -= 1;
+            return Ok(json!(tx.hash));
+        }
+        self.submit(tx, false).map(|hash| json!(hash))
+    }
+
+    /// Xử lý một lời gọi JSON-RPC (trừ subscription)
+    fn dispatch(&mut self, method: &str, params: &Value) -> RpcResult {
+        *self.method_calls.entry(method.to_string()).or_default() += 1;
+        match method {
+            "web3_clientVersion" => Ok(json!("MockChain/v0.1.0")),
+            "net_version" => Ok(json!(self.chain_id.to_string())),
+            "net_listening" => Ok(json!(true)),
+            "eth_syncing" => Ok(json!(false)),
+            "eth_chainId" => Ok(quantity(self.chain_id)),
+            "eth_blockNumber" => Ok(quantity(self.latest_number())),
+            "eth_accounts" => Ok(json!(dev_wallets(self.chain_id).iter().map(|wallet| wallet.address()).collect::<Vec<_>>())),
+            "eth_gasPrice" => Ok(quantity(self.base_fee + U256::from(DEFAULT_PRIORITY_FEE))),
+            "eth_maxPriorityFeePerGas" => Ok(quantity(DEFAULT_PRIORITY_FEE)),
+            "eth_feeHistory" => self.fee_history(params),
+            "eth_getBalance" => {
+                let address = to_evm_address(parse_address(&params[0])?);
+                let info = self.state_at(&params[1])?.basic_ref(address).ok().flatten();
+                Ok(quantity(info.map(|info| from_evm_u256(info.balance)).unwrap_or_default()))
+            },
+            "eth_getTransactionCount" => {
+                let address = parse_address(&params[0])?;
+                if params[1].as_str() == Some("pending") {
+                    return Ok(quantity(self.pending_nonce(address)));
+                }
+                Ok(quantity(Self::nonce_of(self.state_at(&params[1])?, address)))
+            },
+            "eth_getCode" => {
+                let db = self.state_at(&params[1])?;
+                let info = db.basic_ref(to_evm_address(parse_address(&params[0])?)).ok().flatten();
+                let code = info
+                    .and_then(|info| info.code.clone().or_else(|| db.code_by_hash_ref(info.code_hash).ok()))
+                    .map(|code| code.original_bytes().to_vec())
+                    .unwrap_or_default();
+                Ok(json!(Bytes::from(code)))
+            },
+            "eth_getStorageAt" => {
+                let address = to_evm_address(parse_address(&params[0])?);
+                let slot = to_evm_u256(parse_u256(&params[1])?);
+                let value = self.state_at(&params[2])?.storage_ref(address, slot).unwrap_or_default();
+                Ok(json!(H256::from(from_evm_u256(value).to_big_endian_bytes())))
+            },
+            "eth_call" => match self.simulate(&params[0], &params[1])? {
+                ExecutionResult::Success { output, .. } => Ok(json!(Bytes::from(output.into_data().to_vec()))),
+                ExecutionResult::Revert { output, .. } => Err(Self::reverted(&output)),
+                ExecutionResult::Halt { reason, .. } => Err(RpcError::server(format!("execution halted: {:?}", reason))),
+            },
+            "eth_estimateGas" => match self.simulate(&params[0], &params[1])? {
+                // Thêm 50% dự phòng cho quy tắc 63/64 khi gọi lồng nhau
+                ExecutionResult::Success { gas_used, .. } => Ok(quantity(gas_used + gas_used / 2)),
+                ExecutionResult::Revert { output, .. } => Err(Self::reverted(&output)),
+                ExecutionResult::Halt { reason, .. } => Err(RpcError::server(format!("execution halted: {:?}", reason))),
+            },
+            "eth_sendRawTransaction" => self.send_raw_transaction(params),
+            "eth_getTransactionByHash" => {
+                let hash = parse_hash(&params[0])?;
+                Ok(self.transactions.get(&hash).map(|tx| json!(tx)).unwrap_or(Value::Null))
+            },
+            "eth_getTransactionReceipt" => {
+                let hash = parse_hash(&params[0])?;
+                Ok(self.receipts.get(&hash).map(|receipt| json!(receipt)).unwrap_or(Value::Null))
+            },
+            "eth_getBlockByNumber" => {
+                let full = params[1].as_bool().unwrap_or(false);
+                Ok(self.resolve_block_tag(&params[0])?
+                    .map(|number| self.block_json(&self.blocks[number as usize], full))
+                    .unwrap_or(Value::Null))
+            },
+            "eth_getBlockByHash" => {
+                let hash = parse_hash(&params[0])?;
+                let full = params[1].as_bool().unwrap_or(false);
+                Ok(self.blocks.iter().find(|block| block.hash() == hash)
+                    .map(|block| self.block_json(block, full))
+                    .unwrap_or(Value::Null))
+            },
+            "eth_getLogs" => {
+                let filter = LogFilter::parse(&params[0], self)?;
+                Ok(json!(self.logs(&filter)))
+            },
+            "evm_mine" => {
+                self.mine_block();
+                Ok(json!("0x0"))
+            },
+            _ => Err(RpcError::new(-32601, format!("the method {} does not exist/is not available", method))),
+        }
+    }
+}
+
+trait ToBigEndianBytes {
+    fn to_big_endian_bytes(&self) -> [u8; 32];
+}
+
+impl ToBigEndianBytes for U256 {
+    fn to_big_endian_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0_u8; 32];
+        self.to_big_endian(&mut bytes);
+        bytes
+    }
+}
+
+/// Ví dev xác định theo chỉ số, dùng chung cho mọi mock chain
+pub fn dev_wallets(chain_id: u64) -> Vec<LocalWallet> {
+    (0..DEV_ACCOUNT_COUNT)
+        .map(|index| {
+            let key = keccak256(format!("diamond-mock-chain-dev-{}", index));
+            LocalWallet::from_bytes(&key)
+                .expect("khóa dev hợp lệ")
+                .with_chain_id(chain_id)
+        })
+        .collect()
+}
+
+/// Subscription đang mở trên một kết nối WebSocket
+enum WsSubscription {
+    NewHeads,
+    Logs(LogFilter),
+    PendingTransactions,
+}
+
+impl WsSubscription {
+    fn notification(&self, event: &ChainEvent) -> Option<Value> {
+        match (self, event) {
+            (WsSubscription::NewHeads, ChainEvent::NewHead(head)) => Some(head.clone()),
+            (WsSubscription::Logs(filter), ChainEvent::Log(log)) if filter.matches(log) => Some(json!(log)),
+            (WsSubscription::PendingTransactions, ChainEvent::PendingTransaction(hash)) => Some(json!(hash)),
+            _ => None,
+        }
+    }
+}
+
+/// Dữ liệu dùng chung giữa handle `MockChain` và các server
+struct Shared {
+    state: Mutex<ChainState>,
+    events: broadcast::Sender<ChainEvent>,
+    next_subscription: AtomicU64,
+}
+
+impl Shared {
+    fn lock(&self) -> std::sync::MutexGuard<'_, ChainState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Áp lỗi giả lập cho một request: trả `false` nếu request bị rate limit
+    async fn admit(&self) -> bool {
+        let latency = {
+            let mut state = self.lock();
+            if state.faults.rate_limited_requests > 0 {
+                state.faults.rate_limited_requests -= 1;
+                return false;
+            }
+            state.faults.latency
+        };
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        true
+    }
+
+    fn rate_limited_response(id: &Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": id, "error": {"code": RATE_LIMIT_ERROR_CODE, "message": "rate limit exceeded"}})
+    }
+
+    fn respond(&self, request: &Value) -> Value {
+        let method = request["method"].as_str().unwrap_or_default();
+        let result = self.lock().dispatch(method, &request["params"]);
+        match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": request["id"], "result": result}),
+            Err(error) => json!({"jsonrpc": "2.0", "id": request["id"], "error": error.to_json()}),
+        }
+    }
+
+    /// Xử lý payload JSON-RPC (đơn lẻ hoặc batch)
+    fn respond_payload(&self, payload: &Value, mut handle: impl FnMut(&Value) -> Value) -> Value {
+        match payload {
+            Value::Array(requests) => Value::Array(requests.iter().map(&mut handle).collect()),
+            request => handle(request),
+        }
+    }
+}
+
+/// Đọc một HTTP request, trả về body
+async fn read_http_body(socket: &mut TcpStream) -> Option<Vec<u8>> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0_u8; 8192];
+    loop {
+        let read = match socket.read(&mut chunk).await {
+            Ok(0) | Err(_) => return None,
+            Ok(read) => read,
+        };
+        buffer.extend_from_slice(&chunk[..read]);
+        let text = String::from_utf8_lossy(&buffer).to_string();
+        if let Some(header_end) = text.find("\r\n\r\n") {
+            let content_length = text[..header_end].lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if buffer.len() >= header_end + 4 + content_length {
+                return Some(buffer[header_end + 4..header_end + 4 + content_length].to_vec());
+            }
+        }
+    }
+}
+
+async fn serve_http_connection(mut socket: TcpStream, shared: Arc<Shared>) {
+    let Some(body) = read_http_body(&mut socket).await else { return };
+    let payload: Value = serde_json::from_slice(&body).unwrap_or_default();
+
+    let (status, response) = if shared.admit().await {
+        ("200 OK", shared.respond_payload(&payload, |request| shared.respond(request)))
+    } else {
+        ("429 Too Many Requests", shared.respond_payload(&payload, |request| Shared::rate_limited_response(&request["id"])))
+    };
+    let body = response.to_string();
+    let http = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nRetry-After: 1\r\nConnection: close\r\n\r\n{}",
+        status, body.len(), body
+    );
+    let _ = socket.write_all(http.as_bytes()).await;
+}
+
+async fn serve_ws_connection(stream: TcpStream, shared: Arc<Shared>) {
+    let Ok(socket) = tokio_tungstenite::accept_async(stream).await else { return };
+    let (mut sink, mut source) = socket.split();
+    let mut events = shared.events.subscribe();
+    let mut subscriptions: HashMap<String, WsSubscription> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            message = source.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Ping(payload))) => {
+                        let _ = sink.send(Message::Pong(payload)).await;
+                        continue;
+                    },
+                    Some(Ok(_)) => continue,
+                    _ => break,
+                };
+                let payload: Value = serde_json::from_str(&text).unwrap_or_default();
+                let response = if shared.admit().await {
+                    shared.respond_payload(&payload, |request| {
+                        match request["method"].as_str().unwrap_or_default() {
+                            "eth_subscribe" => {
+                                let subscription = match request["params"][0].as_str() {
+                                    Some("newHeads") => WsSubscription::NewHeads,
+                                    Some("newPendingTransactions") => WsSubscription::PendingTransactions,
+                                    Some("logs") => {
+                                        let state = shared.lock();
+                                        match LogFilter::parse(&request["params"][1], &state) {
+                                            Ok(filter) => WsSubscription::Logs(filter),
+                                            Err(error) => return json!({"jsonrpc": "2.0", "id": request["id"], "error": error.to_json()}),
+                                        }
+                                    },
+                                    other => {
+                                        let error = RpcError::invalid_params(format!("subscription không hỗ trợ: {:?}", other));
+                                        return json!({"jsonrpc": "2.0", "id": request["id"], "error": error.to_json()});
+                                    },
+                                };
+                                let id = format!("{:#x}", shared.next_subscription.fetch_add(1, Ordering::Relaxed) + 1);
+                                subscriptions.insert(id.clone(), subscription);
+                                json!({"jsonrpc": "2.0", "id": request["id"], "result": id})
+                            },
+                            "eth_unsubscribe" => {
+                                let removed = request["params"][0].as_str()
+                                    .map(|id| subscriptions.remove(id).is_some())
+                                    .unwrap_or(false);
+                                json!({"jsonrpc": "2.0", "id": request["id"], "result": removed})
+                            },
+                            _ => shared.respond(request),
+                        }
+                    })
+                } else {
+                    shared.respond_payload(&payload, |request| Shared::rate_limited_response(&request["id"]))
+                };
+                if sink.send(Message::Text(response.to_string())).await.is_err() {
+                    break;
+                }
+            },
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                for (id, subscription) in &subscriptions {
+                    if let Some(result) = subscription.notification(&event) {
+                        let notification = json!({
+                            "jsonrpc": "2.0",
+                            "method": "eth_subscription",
+                            "params": {"subscription": id, "result": result},
+                        });
+                        if sink.send(Message::Text(notification.to_string())).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Task server, dừng khi handle cuối cùng của mock chain bị drop
+struct ServerTasks(Vec<JoinHandle<()>>);
+
+impl Drop for ServerTasks {
+    fn drop(&mut self) {
+        for task in &self.0 {
+            task.abort();
+        }
+    }
+}
+
+/// Bộ hợp đồng Uniswap V2 đã deploy trên mock chain. Đây là bản viết lại bằng DSL `evm_asm`
+/// (cùng ABI, chuỗi revert và công thức giá), không phải bytecode biên dịch của Uniswap, nên
+/// `pair_init_code_hash` khác hash của mainnet
+#[derive(Debug, Clone, Copy)]
+pub struct UniswapV2 {
+    pub weth: Address,
+    pub factory: Address,
+    pub router: Address,
+    pub pair_init_code_hash: H256,
+}
+
+/// Node EVM giả lập chạy trong tiến trình test: JSON-RPC qua HTTP và WebSocket,
+/// EVM nhúng (revm), tự mine block khi nhận giao dịch và có thể giả lập lỗi
+/// (độ trễ, HTTP 429, giao dịch bị rơi, reorg)
+#[derive(Clone)]
+pub struct MockChain {
+    shared: Arc<Shared>,
+    http_url: String,
+    ws_url: String,
+    _tasks: Arc<ServerTasks>,
+}
+
+impl MockChain {
+    /// Khởi động mock chain với `chain_id` trên cổng ngẫu nhiên ở localhost
+    pub async fn spawn(chain_id: u64) -> Result<Self> {
+        let (events, _) = broadcast::channel(1024);
+        let shared = Arc::new(Shared {
+            state: Mutex::new(ChainState::genesis(chain_id, events.clone())),
+            events,
+            next_subscription: AtomicU64::new(0),
+        });
+
+        let http = TcpListener::bind("127.0.0.1:0").await.context("Không thể mở cổng HTTP cho mock chain")?;
+        let ws = TcpListener::bind("127.0.0.1:0").await.context("Không thể mở cổng WebSocket cho mock chain")?;
+        let http_url = format!("http://{}", http.local_addr()?);
+        let ws_url = format!("ws://{}", ws.local_addr()?);
+
+        let http_shared = shared.clone();
+        let http_task = tokio::spawn(async move {
+            while let Ok((socket, _)) = http.accept().await {
+                tokio::spawn(serve_http_connection(socket, http_shared.clone()));
+            }
+        });
+        let ws_shared = shared.clone();
+        let ws_task = tokio::spawn(async move {
+            while let Ok((socket, _)) = ws.accept().await {
+                tokio::spawn(serve_ws_connection(socket, ws_shared.clone()));
+            }
+        });
+
+        Ok(Self {
+            shared,
+            http_url,
+            ws_url,
+            _tasks: Arc::new(ServerTasks(vec![http_task, ws_task])),
+        })
+    }
+
+    pub fn http_url(&self) -> &str {
+        &self.http_url
+    }
+
+    pub fn ws_url(&self) -> &str {
+        &self.ws_url
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        self.shared.lock().chain_id
+    }
+
+    /// Ví dev thứ `index` (đã có ETH từ genesis)
+    pub fn dev_wallet(&self, index: usize) -> LocalWallet {
+        dev_wallets(self.chain_id()).swap_remove(index)
+    }
+
+    pub fn dev_address(&self, index: usize) -> Address {
+        self.dev_wallet(index).address()
+    }
+
+    pub fn block_number(&self) -> u64 {
+        self.shared.lock().latest_number()
+    }
+
+    pub fn block_hash(&self, number: u64) -> Option<H256> {
+        self.shared.lock().blocks.get(number as usize).map(MinedBlock::hash)
+    }
+
+    pub fn balance(&self, address: Address) -> U256 {
+        let state = self.shared.lock();
+        state.latest().state.basic_ref(to_evm_address(address)).ok().flatten()
+            .map(|info| from_evm_u256(info.balance))
+            .unwrap_or_default()
+    }
+
+    /// Số lần method JSON-RPC đã được gọi (tính cả trong batch)
+    pub fn method_calls(&self, method: &str) -> usize {
+        self.shared.lock().method_calls.get(method).copied().unwrap_or(0)
+    }
+
+    /// Giao dịch đang chờ trong mempool
+    pub fn pending_transactions(&self) -> Vec<H256> {
+        self.shared.lock().pending.iter().map(|tx| tx.hash).collect()
+    }
+
+    pub fn receipt(&self, hash: H256) -> Option<TransactionReceipt> {
+        self.shared.lock().receipts.get(&hash).cloned()
+    }
+
+    /// Bật/tắt tự mine khi nhận giao dịch (tắt để giao dịch nằm chờ trong mempool)
+    pub fn set_automine(&self, enabled: bool) {
+        self.shared.lock().automine = enabled;
+    }
+
+    /// Mine `count` block (gồm giao dịch thực thi được trong mempool)
+    pub fn mine(&self, count: u64) {
+        let mut state = self.shared.lock();
+        for _ in 0..count {
+            state.mine_block();
+        }
+    }
+
+    /// Đổi base fee cho các block tiếp theo (giả lập gas tăng đột biến)
+    pub fn set_base_fee(&self, base_fee: U256) {
+        self.shared.lock().base_fee = base_fee;
+    }
+
+    /// Độ trễ trước mỗi phản hồi HTTP/WS
+    pub fn set_latency(&self, latency: Duration) {
+        self.shared.lock().faults.latency = latency;
+    }
+
+    /// `count` request tiếp theo bị trả HTTP 429 (lỗi -32005 qua WS)
+    pub fn rate_limit_next(&self, count: u32) {
+        self.shared.lock().faults.rate_limited_requests = count;
+    }
+
+    /// `count` giao dịch tiếp theo được trả hash nhưng không bao giờ vào block
+    pub fn drop_next_transactions(&self, count: u32) {
+        self.shared.lock().faults.dropped_transactions = count;
+    }
+
+    /// Reorg `depth` block cuối: thay bằng các block mới cùng chiều cao. Log của block
+    /// bị thay được phát lại với `removed: true`; giao dịch trong đó được đưa lại vào
+    /// mempool nếu `reinclude`, ngược lại bị bỏ hẳn
+    pub fn reorg(&self, depth: u64, reinclude: bool) -> Result<()> {
+        let mut state = self.shared.lock();
+        if depth == 0 || depth > state.latest_number() {
+            bail!("Độ sâu reorg không hợp lệ: {} (chain cao {})", depth, state.latest_number());
+        }
+
+        let keep = state.blocks.len() - depth as usize;
+        let removed = state.blocks.split_off(keep);
+        state.reorg_count += 1;
+        let mut requeued = Vec::new();
+        for block in &removed {
+            for log in &block.logs {
+                state.emit(ChainEvent::Log(Box::new(Log { removed: Some(true), ..log.clone() })));
+            }
+            for hash in &block.header.transactions {
+                state.receipts.remove(hash);
+                if let Some(mut tx) = state.transactions.remove(hash) {
+                    tx.block_hash = None;
+                    tx.block_number = None;
+                    tx.transaction_index = None;
+                    requeued.push(tx);
+                }
+            }
+        }
+
+        if reinclude {
+            for tx in requeued {
+                state.transactions.insert(tx.hash, tx.clone());
+                state.pending.push(tx);
+            }
+        }
+        // Chain mới phải cao ít nhất bằng chain cũ
+        let automine = state.automine;
+        for _ in 0..depth {
+            if !automine {
+                let pending = std::mem::take(&mut state.pending);
+                state.mine_block();
+                state.pending = pending;
+            } else {
+                state.mine_block();
+            }
+        }
+        Ok(())
+    }
+
+    /// Gửi giao dịch nội bộ từ `from` (không cần chữ ký) và mine ngay
+    pub fn transact(&self, from: Address, to: Option<Address>, value: U256, data: Vec<u8>) -> Result<TransactionReceipt> {
+        let mut state = self.shared.lock();
+        let tx = state.internal_transaction(from, to, value, data);
+        let hash = state.submit(tx, true).map_err(|e| anyhow!("Giao dịch mock bị từ chối: {}", e.message))?;
+        let receipt = state.receipts.get(&hash).cloned()
+            .ok_or_else(|| anyhow!("Giao dịch mock {:?} chưa được mine", hash))?;
+        if receipt.status != Some(U64::one()) {
+            let reason = state.revert_reasons.get(&hash).cloned().unwrap_or_default();
+            bail!("Giao dịch mock bị revert: {}", reason);
+        }
+        Ok(receipt)
+    }
+
+    /// Lời gọi chỉ đọc trên block mới nhất
+    pub fn call(&self, to: Address, data: Vec<u8>) -> Result<Vec<u8>> {
+        let state = self.shared.lock();
+        let request = json!({"to": to, "data": Bytes::from(data)});
+        match state.simulate(&request, &Value::Null).map_err(|e| anyhow!(e.message))? {
+            ExecutionResult::Success { output, .. } => Ok(output.into_data().to_vec()),
+            ExecutionResult::Revert { output, .. } => bail!("Lời gọi mock bị revert: {}", revert_reason(&output)),
+            ExecutionResult::Halt { reason, .. } => bail!("Lời gọi mock bị dừng: {:?}", reason),
+        }
+    }
+
+    /// Đặt runtime bytecode tại `address` trên block mới nhất (predeploy/precompile của L2)
+    pub fn set_code(&self, address: Address, runtime: Vec<u8>) {
+        let mut state = self.shared.lock();
+        let block = state.blocks.last_mut().expect("chain luôn có block genesis");
+        block.state.insert_account_info(
+            to_evm_address(address),
+            AccountInfo::from_bytecode(Bytecode::new_raw(EvmBytes::from(runtime))),
+        );
+    }
+
+    /// Deploy hợp đồng từ init code, trả về địa chỉ
+    pub fn deploy(&self, from: Address, init_code: Vec<u8>) -> Result<Address> {
+        self.transact(from, None, U256::zero(), init_code)?
+            .contract_address
+            .ok_or_else(|| anyhow!("Deploy không trả về địa chỉ hợp đồng"))
+    }
+
+    /// Deploy token ERC-20 mock từ ví dev 0
+    pub fn deploy_erc20(&self, spec: &TokenSpec) -> Result<Address> {
+        self.deploy(self.dev_address(0), mock_contracts::erc20_init_code(spec))
+    }
+
+    /// Mint token mock cho `to`
+    pub fn mint(&self, token: Address, to: Address, amount: U256) -> Result<()> {
+        let data = calldata("mint(address,uint256)", &[Token::Address(to), Token::Uint(amount)]);
+        self.transact(self.dev_address(0), Some(token), U256::zero(), data).map(|_| ())
+    }
+
+    pub fn token_balance(&self, token: Address, owner: Address) -> Result<U256> {
+        let output = self.call(token, calldata("balanceOf(address)", &[Token::Address(owner)]))?;
+        Ok(U256::from_big_endian(output.get(..32).ok_or_else(|| anyhow!("balanceOf trả dữ liệu rỗng"))?))
+    }
+
+    /// Deploy WETH, factory và router Uniswap V2 (bản DSL trong `mock_contracts`) từ ví dev 0
+    pub fn deploy_uniswap_v2(&self) -> Result<UniswapV2> {
+        let deployer = self.dev_address(0);
+        let weth = self.deploy(deployer, mock_contracts::weth_init_code())?;
+        let factory = self.deploy(deployer, mock_contracts::factory_init_code())?;
+        let router = self.deploy(deployer, mock_contracts::router_init_code(factory, weth))?;
+        Ok(UniswapV2 {
+            weth,
+            factory,
+            router,
+            pair_init_code_hash: mock_contracts::pair_init_code_hash(),
+        })
+    }
+
+    /// Địa chỉ pair của hai token (zero nếu chưa có)
+    pub fn get_pair(&self, dex: &UniswapV2, token_a: Address, token_b: Address) -> Result<Address> {
+        let output = self.call(dex.factory, calldata("getPair(address,address)", &[Token::Address(token_a), Token::Address(token_b)]))?;
+        Ok(Address::from_slice(output.get(12..32).ok_or_else(|| anyhow!("getPair trả dữ liệu rỗng"))?))
+    }
+
+    /// Mint token cho ví dev 0 rồi thêm thanh khoản token/WETH qua router, trả về pair
+    pub fn add_liquidity_eth(&self, dex: &UniswapV2, token: Address, token_amount: U256, eth_amount: U256) -> Result<Address> {
+        let provider = self.dev_address(0);
+        self.mint(token, provider, token_amount)?;
+        let approve = calldata("approve(address,uint256)", &[Token::Address(dex.router), Token::Uint(token_amount)]);
+        self.transact(provider, Some(token), U256::zero(), approve)?;
+        let add = calldata("addLiquidityETH(address,uint256,uint256,uint256,address,uint256)", &[
+            Token::Address(token),
+            Token::Uint(token_amount),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero()),
+            Token::Address(provider),
+            Token::Uint(U256::MAX),
+        ]);
+        self.transact(provider, Some(dex.router), eth_amount, add)?;
+        self.get_pair(dex, token, dex.weth)
+    }
+}
+
+/// Lượng token nhận được theo công thức Uniswap V2 (phí 0.3%)
+pub fn uniswap_v2_amount_out(amount_in: U256, reserve_in: U256, reserve_out: U256) -> U256 {
+    let with_fee = amount_in * 997;
+    with_fee * reserve_out / (reserve_in * 1000 + with_fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::{Http, Middleware, Provider, Ws};
+
+    const ETHER: u128 = 1_000_000_000_000_000_000;
+
+    #[tokio::test]
+    async fn test_uniswap_v2_swap_matches_reference_formula() {
+        let chain = MockChain::spawn(31_337).await.unwrap();
+        let dex = chain.deploy_uniswap_v2().unwrap();
+        let token = chain.deploy_erc20(&TokenSpec::new("Mock Token", "MOCK", 18)).unwrap();
+        let pair = chain.add_liquidity_eth(&dex, token, U256::from(1_000_000 * ETHER), U256::from(100 * ETHER)).unwrap();
+        assert_ne!(pair, Address::zero());
+
+        // Địa chỉ pair tính theo CREATE2 khớp địa chỉ factory trả về
+        let (token0, token1) = if token < dex.weth { (token, dex.weth) } else { (dex.weth, token) };
+        let salt = keccak256([token0.as_bytes(), token1.as_bytes()].concat());
+        assert_eq!(ethers::utils::get_create2_address_from_hash(dex.factory, salt, dex.pair_init_code_hash), pair);
+
+        let amount_in = U256::from(ETHER);
+        let path = Token::Array(vec![Token::Address(dex.weth), Token::Address(token)]);
+        let quoted = chain.call(dex.router, calldata("getAmountsOut(uint256,address[])", &[Token::Uint(amount_in), path.clone()])).unwrap();
+        let amounts = abi::decode(&[ParamType::Array(Box::new(ParamType::Uint(256)))], &quoted).unwrap();
+        let expected = uniswap_v2_amount_out(amount_in, U256::from(100 * ETHER), U256::from(1_000_000 * ETHER));
+        assert_eq!(amounts[0], Token::Array(vec![Token::Uint(amount_in), Token::Uint(expected)]));
+
+        let trader = chain.dev_address(1);
+        let swap = calldata("swapExactETHForTokens(uint256,address[],address,uint256)", &[
+            Token::Uint(expected), path, Token::Address(trader), Token::Uint(U256::MAX),
+        ]);
+        let receipt = chain.transact(trader, Some(dex.router), amount_in, swap).unwrap();
+        assert_eq!(chain.token_balance(token, trader).unwrap(), expected);
+        // Transfer WETH, Transfer token, Sync, Swap và Deposit của WETH
+        assert!(receipt.logs.len() >= 5);
+
+        // Slippage vượt mức tối thiểu thì router revert với lý do của Uniswap
+        let greedy = calldata("swapExactETHForTokens(uint256,address[],address,uint256)", &[
+            Token::Uint(U256::MAX),
+            Token::Array(vec![Token::Address(dex.weth), Token::Address(token)]),
+            Token::Address(trader),
+            Token::Uint(U256::MAX),
+        ]);
+        let error = chain.transact(trader, Some(dex.router), amount_in, greedy).unwrap_err();
+        assert!(error.to_string().contains("INSUFFICIENT_OUTPUT_AMOUNT"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_rpc_faults_and_reorg() {
+        let chain = MockChain::spawn(31_337).await.unwrap();
+        let provider = Provider::<Http>::try_from(chain.http_url()).unwrap().interval(Duration::from_millis(50));
+        assert_eq!(provider.get_chainid().await.unwrap(), U256::from(31_337));
+
+        chain.rate_limit_next(1);
+        assert!(provider.get_block_number().await.is_err());
+        assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 0);
+
+        let history = provider.fee_history(4, ethers::types::BlockNumber::Latest, &[50.0]).await.unwrap();
+        assert_eq!(history.base_fee_per_gas.last(), Some(&U256::from(DEFAULT_BASE_FEE)));
+
+        // Giao dịch bị rơi: có hash nhưng không bao giờ có receipt
+        let wallet = chain.dev_wallet(2);
+        let client = ethers::middleware::SignerMiddleware::new(provider.clone(), wallet.clone());
+        chain.drop_next_transactions(1);
+        let tx = ethers::types::TransactionRequest::pay(chain.dev_address(3), 1_000_u64);
+        let dropped = client.send_transaction(tx.clone(), None).await.unwrap().tx_hash();
+        assert!(provider.get_transaction_receipt(dropped).await.unwrap().is_none());
+        assert_eq!(chain.block_number(), 0);
+
+        let receipt = client.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+        let mined_in = receipt.block_hash.unwrap();
+        assert_eq!(chain.block_number(), 1);
+
+        // Reorg bỏ giao dịch: receipt biến mất và block 1 có hash mới
+        chain.reorg(1, false).unwrap();
+        assert!(provider.get_transaction_receipt(receipt.transaction_hash).await.unwrap().is_none());
+        assert_ne!(chain.block_hash(1), Some(mined_in));
+        assert_eq!(chain.balance(chain.dev_address(3)), U256::from(DEV_ACCOUNT_BALANCE));
+    }
+
+    #[tokio::test]
+    async fn test_websocket_subscriptions() {
+        let chain = MockChain::spawn(31_337).await.unwrap();
+        let provider = Provider::<Ws>::connect(chain.ws_url()).await.unwrap();
+        let mut heads = provider.subscribe_blocks().await.unwrap();
+
+        chain.mine(1);
+        let head = tokio::time::timeout(Duration::from_secs(5), heads.next()).await.unwrap().unwrap();
+        assert_eq!(head.number, Some(U64::one()));
+        assert_eq!(head.hash, chain.block_hash(1));
+    }
+}
@@ -0,0 +1,661 @@
+// External imports
+use ethers::types::{Address, H256, U256};
+
+// Internal imports
+use super::evm_asm::*;
+
+/// Slot lưu trữ của token ERC-20 (theo layout OpenZeppelin)
+const TOKEN_BALANCES: u64 = 0;
+const TOKEN_ALLOWANCES: u64 = 1;
+const TOKEN_SUPPLY: u64 = 2;
+
+/// Slot lưu trữ của pair
+const PAIR_TOKEN0: u64 = 0;
+const PAIR_TOKEN1: u64 = 1;
+const PAIR_RESERVE0: u64 = 2;
+const PAIR_RESERVE1: u64 = 3;
+const PAIR_FACTORY: u64 = 4;
+const PAIR_SUPPLY: u64 = 5;
+const PAIR_BALANCES: u64 = 6;
+const PAIR_TIMESTAMP: u64 = 7;
+
+/// Slot lưu trữ của factory
+const FACTORY_ALL_PAIRS: u64 = 0;
+const FACTORY_GET_PAIR: u64 = 1;
+const FACTORY_FEE_TO: u64 = 2;
+
+/// Thanh khoản tối thiểu bị khóa vĩnh viễn khi mint lần đầu
+const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+/// uint256 lớn nhất (allowance vô hạn)
+fn max_uint() -> Expr {
+    U256::MAX.into()
+}
+
+fn transfer_topic() -> Expr {
+    event_topic("Transfer(address,address,uint256)").into()
+}
+
+/// Cấu hình token ERC-20 mock
+#[derive(Debug, Clone)]
+pub struct TokenSpec {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    /// Phí chuyển token (basis point) bị đốt mỗi lần transfer, dùng cho kịch bản token thu phí
+    pub transfer_fee_bps: u64,
+}
+
+impl TokenSpec {
+    pub fn new(name: &str, symbol: &str, decimals: u8) -> Self {
+        Self {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            decimals,
+            transfer_fee_bps: 0,
+        }
+    }
+
+    pub fn with_transfer_fee(mut self, fee_bps: u64) -> Self {
+        self.transfer_fee_bps = fee_bps;
+        self
+    }
+}
+
+/// Chuyển `amount` token từ `from` sang `to` (có trừ phí nếu token thu phí)
+fn token_transfer(c: &mut Code, from: Var, to: Var, amount: Var, fee_bps: u64) {
+    let from_balance = c.let_(sload(mapping(TOKEN_BALANCES, from)));
+    c.require(not_lt(from_balance, amount), "ERC20: transfer amount exceeds balance");
+    c.sstore(mapping(TOKEN_BALANCES, from), sub(from_balance, amount));
+
+    let received = c.let_(amount);
+    if fee_bps > 0 {
+        let fee = c.let_(div(mul(amount, fee_bps), 10_000_u64));
+        c.set(received, sub(amount, fee));
+        c.sstore(TOKEN_SUPPLY, sub(sload(TOKEN_SUPPLY), fee));
+        c.log(vec![transfer_topic(), from.into(), 0_u64.into()], vec![fee.into()]);
+    }
+    c.sstore(mapping(TOKEN_BALANCES, to), add(sload(mapping(TOKEN_BALANCES, to)), received));
+    c.log(vec![transfer_topic(), from.into(), to.into()], vec![received.into()]);
+}
+
+/// Cộng số dư và tổng cung (mint)
+fn token_mint(c: &mut Code, to: Var, amount: Var) {
+    c.sstore(mapping(TOKEN_BALANCES, to), add(sload(mapping(TOKEN_BALANCES, to)), amount));
+    c.sstore(TOKEN_SUPPLY, add(sload(TOKEN_SUPPLY), amount));
+    c.log(vec![transfer_topic(), 0_u64.into(), to.into()], vec![amount.into()]);
+}
+
+/// Hàm ERC-20 chuẩn, dùng chung cho token mock và WETH
+fn erc20_functions(contract: &mut Contract, spec: &TokenSpec) {
+    let (name, symbol, decimals, fee_bps) = (spec.name.clone(), spec.symbol.clone(), spec.decimals, spec.transfer_fee_bps);
+    contract
+        .function("name()", move |c| c.ret_string(&name))
+        .function("symbol()", move |c| c.ret_string(&symbol))
+        .function("decimals()", move |c| c.ret(vec![(decimals as u64).into()]))
+        .function("totalSupply()", |c| c.ret(vec![sload(TOKEN_SUPPLY)]))
+        .function("balanceOf(address)", |c| c.ret(vec![sload(mapping(TOKEN_BALANCES, arg(0)))]))
+        .function("allowance(address,address)", |c| {
+            c.ret(vec![sload(mapping2(TOKEN_ALLOWANCES, arg(0), arg(1)))])
+        })
+        .function("approve(address,uint256)", |c| {
+            c.sstore(mapping2(TOKEN_ALLOWANCES, caller(), arg(0)), arg(1));
+            c.log(vec![event_topic("Approval(address,address,uint256)").into(), caller(), arg(0)], vec![arg(1)]);
+            c.ret(vec![1_u64.into()]);
+        })
+        .function("transfer(address,uint256)", move |c| {
+            let (from, to, amount) = (c.let_(caller()), c.let_(arg(0)), c.let_(arg(1)));
+            token_transfer(c, from, to, amount, fee_bps);
+            c.ret(vec![1_u64.into()]);
+        })
+        .function("transferFrom(address,address,uint256)", move |c| {
+            let (from, to, amount) = (c.let_(arg(0)), c.let_(arg(1)), c.let_(arg(2)));
+            let allowed = c.let_(sload(mapping2(TOKEN_ALLOWANCES, from, caller())));
+            c.if_(neq(allowed, max_uint()), |c| {
+                c.require(not_lt(allowed, amount), "ERC20: insufficient allowance");
+                c.sstore(mapping2(TOKEN_ALLOWANCES, from, caller()), sub(allowed, amount));
+            });
+            token_transfer(c, from, to, amount, fee_bps);
+            c.ret(vec![1_u64.into()]);
+        });
+}
+
+/// Runtime bytecode token ERC-20 mock, có thêm `mint(address,uint256)` không giới hạn quyền
+pub fn erc20_runtime(spec: &TokenSpec) -> Vec<u8> {
+    let mut contract = Contract::new();
+    erc20_functions(&mut contract, spec);
+    contract.function("mint(address,uint256)", |c| {
+        let (to, amount) = (c.let_(arg(0)), c.let_(arg(1)));
+        token_mint(c, to, amount);
+        c.ret(vec![1_u64.into()]);
+    });
+    contract.build(&[])
+}
+
+pub fn erc20_init_code(spec: &TokenSpec) -> Vec<u8> {
+    init_code(&erc20_runtime(spec), |_| {})
+}
+
+/// Nạp ETH vào WETH cho người gọi
+fn weth_deposit(c: &mut Code) {
+    let (to, amount) = (c.let_(caller()), c.let_(callvalue()));
+    token_mint(c, to, amount);
+    c.log(vec![event_topic("Deposit(address,uint256)").into(), to.into()], vec![amount.into()]);
+}
+
+/// Runtime bytecode WETH9 (deposit/withdraw, nhận ETH qua fallback)
+pub fn weth_runtime() -> Vec<u8> {
+    let mut contract = Contract::new();
+    erc20_functions(&mut contract, &TokenSpec::new("Wrapped Ether", "WETH", 18));
+    contract
+        .function("deposit()", |c| {
+            weth_deposit(c);
+            c.stop();
+        })
+        .function("withdraw(uint256)", |c| {
+            let (owner, amount) = (c.let_(caller()), c.let_(arg(0)));
+            let held = c.let_(sload(mapping(TOKEN_BALANCES, owner)));
+            c.require(not_lt(held, amount), "WETH: insufficient balance");
+            c.sstore(mapping(TOKEN_BALANCES, owner), sub(held, amount));
+            c.sstore(TOKEN_SUPPLY, sub(sload(TOKEN_SUPPLY), amount));
+            c.log(vec![transfer_topic(), owner.into(), 0_u64.into()], vec![amount.into()]);
+            c.log(vec![event_topic("Withdrawal(address,uint256)").into(), owner.into()], vec![amount.into()]);
+            let sent = c.let_(op(opcodes::CALL, [gas(), owner.into(), amount.into(), 0_u64.into(), 0_u64.into(), 0_u64.into(), 0_u64.into()]));
+            c.require(sent, "WETH: ETH transfer failed");
+            c.stop();
+        })
+        .fallback(|c| {
+            c.require(callvalue(), "WETH: no value");
+            weth_deposit(c);
+        });
+    contract.build(&[])
+}
+
+pub fn weth_init_code() -> Vec<u8> {
+    init_code(&weth_runtime(), |_| {})
+}
+
+/// Chuyển token ERC-20 và bắt buộc thành công (kiểu TransferHelper của Uniswap)
+fn safe_transfer(c: &mut Code, token: impl Into<Expr>, to: impl Into<Expr>, amount: impl Into<Expr>, reason: &str) {
+    let ok = c.call(token, 0_u64, "transfer(address,uint256)", vec![to.into(), amount.into()]);
+    c.require(and(ok, or(iszero(returndatasize()), call_result(0))), reason);
+}
+
+fn safe_transfer_from(c: &mut Code, token: impl Into<Expr>, from: impl Into<Expr>, to: impl Into<Expr>, amount: impl Into<Expr>) {
+    let ok = c.call(token, 0_u64, "transferFrom(address,address,uint256)", vec![from.into(), to.into(), amount.into()]);
+    c.require(and(ok, or(iszero(returndatasize()), call_result(0))), "TransferHelper: TRANSFER_FROM_FAILED");
+}
+
+fn safe_transfer_eth(c: &mut Code, to: impl Into<Expr>, amount: impl Into<Expr>) {
+    let sent = c.let_(op(opcodes::CALL, [gas(), to.into(), amount.into(), 0_u64.into(), 0_u64.into(), 0_u64.into(), 0_u64.into()]));
+    c.require(sent, "TransferHelper: ETH_TRANSFER_FAILED");
+}
+
+/// Số dư token của chính hợp đồng
+fn self_token_balance(c: &mut Code, token: impl Into<Expr>) -> Var {
+    c.static_call(token, "balanceOf(address)", vec![address()]);
+    c.let_(call_result(0))
+}
+
+/// Cập nhật reserve của pair và phát event Sync
+fn pair_update(c: &mut Code, balance0: Var, balance1: Var) {
+    c.sstore(PAIR_RESERVE0, balance0);
+    c.sstore(PAIR_RESERVE1, balance1);
+    c.sstore(PAIR_TIMESTAMP, timestamp());
+    c.log(vec![event_topic("Sync(uint112,uint112)").into()], vec![balance0.into(), balance1.into()]);
+}
+
+/// Căn bậc hai nguyên (phương pháp Babylon, như Math.sqrt của Uniswap)
+fn sqrt(c: &mut Code, y: Var) -> Var {
+    let z = c.let_(0_u64);
+    c.if_else(gt(y, 3_u64), |c| {
+        c.set(z, y);
+        let x = c.let_(add(div(y, 2_u64), 1_u64));
+        c.while_(lt(x, z), |c| {
+            c.set(z, x);
+            c.set(x, div(add(div(y, x), x), 2_u64));
+        });
+    }, |c| {
+        c.if_(y, |c| c.set(z, 1_u64));
+    });
+    z
+}
+
+fn min(c: &mut Code, a: Var, b: Var) -> Var {
+    let result = c.let_(a);
+    c.if_(lt(b, a), |c| c.set(result, b));
+    result
+}
+
+fn lp_mint(c: &mut Code, to: impl Into<Expr> + Clone, amount: impl Into<Expr> + Clone) {
+    c.sstore(mapping(PAIR_BALANCES, to.clone()), add(sload(mapping(PAIR_BALANCES, to.clone())), amount.clone()));
+    c.sstore(PAIR_SUPPLY, add(sload(PAIR_SUPPLY), amount.clone()));
+    c.log(vec![transfer_topic(), 0_u64.into(), to.into()], vec![amount.into()]);
+}
+
+/// Runtime bytecode UniswapV2Pair (mint/burn/swap/sync, LP token tối giản)
+pub fn pair_runtime() -> Vec<u8> {
+    let mut contract = Contract::new();
+    contract
+        .function("initialize(address,address)", |c| {
+            c.require(eq(caller(), sload(PAIR_FACTORY)), "UniswapV2: FORBIDDEN");
+            c.sstore(PAIR_TOKEN0, arg(0));
+            c.sstore(PAIR_TOKEN1, arg(1));
+        })
+        .function("factory()", |c| c.ret(vec![sload(PAIR_FACTORY)]))
+        .function("token0()", |c| c.ret(vec![sload(PAIR_TOKEN0)]))
+        .function("token1()", |c| c.ret(vec![sload(PAIR_TOKEN1)]))
+        .function("getReserves()", |c| {
+            c.ret(vec![sload(PAIR_RESERVE0), sload(PAIR_RESERVE1), sload(PAIR_TIMESTAMP)])
+        })
+        .function("name()", |c| c.ret_string("Uniswap V2"))
+        .function("symbol()", |c| c.ret_string("UNI-V2"))
+        .function("decimals()", |c| c.ret(vec![18_u64.into()]))
+        .function("totalSupply()", |c| c.ret(vec![sload(PAIR_SUPPLY)]))
+        .function("balanceOf(address)", |c| c.ret(vec![sload(mapping(PAIR_BALANCES, arg(0)))]))
+        .function("transfer(address,uint256)", |c| {
+            let (to, amount) = (c.let_(arg(0)), c.let_(arg(1)));
+            let held = c.let_(sload(mapping(PAIR_BALANCES, caller())));
+            c.require(not_lt(held, amount), "UniswapV2: INSUFFICIENT_BALANCE");
+            c.sstore(mapping(PAIR_BALANCES, caller()), sub(held, amount));
+            c.sstore(mapping(PAIR_BALANCES, to), add(sload(mapping(PAIR_BALANCES, to)), amount));
+            c.log(vec![transfer_topic(), caller(), to.into()], vec![amount.into()]);
+            c.ret(vec![1_u64.into()]);
+        })
+        .function("mint(address)", |c| {
+            let to = c.let_(arg(0));
+            let (reserve0, reserve1) = (c.let_(sload(PAIR_RESERVE0)), c.let_(sload(PAIR_RESERVE1)));
+            let balance0 = self_token_balance(c, sload(PAIR_TOKEN0));
+            let balance1 = self_token_balance(c, sload(PAIR_TOKEN1));
+            let amount0 = c.let_(sub(balance0, reserve0));
+            let amount1 = c.let_(sub(balance1, reserve1));
+            let supply = c.let_(sload(PAIR_SUPPLY));
+            let liquidity = c.var();
+            c.if_else(iszero(supply), |c| {
+                let product = c.let_(mul(amount0, amount1));
+                let root = sqrt(c, product);
+                c.require(gt(root, MINIMUM_LIQUIDITY), "UniswapV2: INSUFFICIENT_LIQUIDITY_MINTED");
+                c.set(liquidity, sub(root, MINIMUM_LIQUIDITY));
+                lp_mint(c, 0_u64, MINIMUM_LIQUIDITY);
+            }, |c| {
+                let by0 = c.let_(div(mul(amount0, supply), reserve0));
+                let by1 = c.let_(div(mul(amount1, supply), reserve1));
+                let smaller = min(c, by0, by1);
+                c.set(liquidity, smaller);
+            });
+            c.require(liquidity, "UniswapV2: INSUFFICIENT_LIQUIDITY_MINTED");
+            lp_mint(c, to, liquidity);
+            pair_update(c, balance0, balance1);
+            c.log(vec![event_topic("Mint(address,uint256,uint256)").into(), caller()], vec![amount0.into(), amount1.into()]);
+            c.ret(vec![liquidity.into()]);
+        })
+        .function("burn(address)", |c| {
+            let to = c.let_(arg(0));
+            let (token0, token1) = (c.let_(sload(PAIR_TOKEN0)), c.let_(sload(PAIR_TOKEN1)));
+            let balance0 = self_token_balance(c, token0);
+            let balance1 = self_token_balance(c, token1);
+            let liquidity = c.let_(sload(mapping(PAIR_BALANCES, address())));
+            let supply = c.let_(sload(PAIR_SUPPLY));
+            let amount0 = c.let_(div(mul(liquidity, balance0), supply));
+            let amount1 = c.let_(div(mul(liquidity, balance1), supply));
+            c.require(and(amount0, amount1), "UniswapV2: INSUFFICIENT_LIQUIDITY_BURNED");
+            c.sstore(mapping(PAIR_BALANCES, address()), 0_u64);
+            c.sstore(PAIR_SUPPLY, sub(supply, liquidity));
+            c.log(vec![transfer_topic(), address(), 0_u64.into()], vec![liquidity.into()]);
+            safe_transfer(c, token0, to, amount0, "UniswapV2: TRANSFER_FAILED");
+            safe_transfer(c, token1, to, amount1, "UniswapV2: TRANSFER_FAILED");
+            let balance0 = self_token_balance(c, token0);
+            let balance1 = self_token_balance(c, token1);
+            pair_update(c, balance0, balance1);
+            c.log(vec![event_topic("Burn(address,uint256,uint256,address)").into(), caller(), to.into()], vec![amount0.into(), amount1.into()]);
+            c.ret(vec![amount0.into(), amount1.into()]);
+        })
+        .function("swap(uint256,uint256,address,bytes)", |c| {
+            let (out0, out1, to) = (c.let_(arg(0)), c.let_(arg(1)), c.let_(arg(2)));
+            c.require(or(out0, out1), "UniswapV2: INSUFFICIENT_OUTPUT_AMOUNT");
+            let (reserve0, reserve1) = (c.let_(sload(PAIR_RESERVE0)), c.let_(sload(PAIR_RESERVE1)));
+            c.require(and(lt(out0, reserve0), lt(out1, reserve1)), "UniswapV2: INSUFFICIENT_LIQUIDITY");
+            let (token0, token1) = (c.let_(sload(PAIR_TOKEN0)), c.let_(sload(PAIR_TOKEN1)));
+            c.require(and(neq(to, token0), neq(to, token1)), "UniswapV2: INVALID_TO");
+            c.if_(out0, |c| safe_transfer(c, token0, to, out0, "UniswapV2: TRANSFER_FAILED"));
+            c.if_(out1, |c| safe_transfer(c, token1, to, out1, "UniswapV2: TRANSFER_FAILED"));
+            let balance0 = self_token_balance(c, token0);
+            let balance1 = self_token_balance(c, token1);
+
+            let in0 = c.let_(0_u64);
+            let in1 = c.let_(0_u64);
+            c.if_(gt(balance0, sub(reserve0, out0)), |c| c.set(in0, sub(balance0, sub(reserve0, out0))));
+            c.if_(gt(balance1, sub(reserve1, out1)), |c| c.set(in1, sub(balance1, sub(reserve1, out1))));
+            c.require(or(in0, in1), "UniswapV2: INSUFFICIENT_INPUT_AMOUNT");
+
+            // Bất biến K sau phí 0.3%
+            let adjusted0 = c.let_(sub(mul(balance0, 1_000_u64), mul(in0, 3_u64)));
+            let adjusted1 = c.let_(sub(mul(balance1, 1_000_u64), mul(in1, 3_u64)));
+            c.require(not_lt(mul(adjusted0, adjusted1), mul(mul(reserve0, reserve1), 1_000_000_u64)), "UniswapV2: K");
+
+            pair_update(c, balance0, balance1);
+            c.log(
+                vec![event_topic("Swap(address,uint256,uint256,uint256,uint256,address)").into(), caller(), to.into()],
+                vec![in0.into(), in1.into(), out0.into(), out1.into()],
+            );
+        })
+        .function("sync()", |c| {
+            let balance0 = self_token_balance(c, sload(PAIR_TOKEN0));
+            let balance1 = self_token_balance(c, sload(PAIR_TOKEN1));
+            pair_update(c, balance0, balance1);
+        });
+    contract.build(&[])
+}
+
+/// Init code của pair: ghi factory là người tạo
+pub fn pair_init_code() -> Vec<u8> {
+    init_code(&pair_runtime(), |c| c.sstore(PAIR_FACTORY, caller()))
+}
+
+/// Runtime bytecode UniswapV2Factory, nhúng init code của pair để tạo bằng CREATE2
+pub fn factory_runtime() -> Vec<u8> {
+    let pair_code = pair_init_code();
+    let pair_code_len = pair_code.len() as u64;
+    let mut contract = Contract::new();
+    contract
+        .function("createPair(address,address)", move |c| {
+            let (a, b) = (c.let_(arg(0)), c.let_(arg(1)));
+            c.require(neq(a, b), "UniswapV2: IDENTICAL_ADDRESSES");
+            let (token0, token1) = (c.let_(a), c.let_(b));
+            c.if_(lt(b, a), |c| {
+                c.set(token0, b);
+                c.set(token1, a);
+            });
+            c.require(token0, "UniswapV2: ZERO_ADDRESS");
+            c.require(iszero(sload(mapping2(FACTORY_GET_PAIR, token0, token1))), "UniswapV2: PAIR_EXISTS");
+
+            // salt = keccak256(abi.encodePacked(token0, token1))
+            c.mstore(0_u64, shl(96_u64, token0));
+            c.mstore(20_u64, shl(96_u64, token1));
+            let salt = c.let_(keccak256(0_u64, 40_u64));
+            c.exec(op(opcodes::CODECOPY, [DYN_BUF.into(), label("pair_init_code"), pair_code_len.into()]));
+            let pair = c.let_(op(opcodes::CREATE2, [0_u64.into(), DYN_BUF.into(), pair_code_len.into(), salt.into()]));
+            c.require(pair, "UniswapV2: CREATE2_FAILED");
+            let ok = c.call(pair, 0_u64, "initialize(address,address)", vec![token0.into(), token1.into()]);
+            c.bubble_revert(ok);
+
+            c.sstore(mapping2(FACTORY_GET_PAIR, token0, token1), pair);
+            c.sstore(mapping2(FACTORY_GET_PAIR, token1, token0), pair);
+            let index = c.let_(sload(FACTORY_ALL_PAIRS));
+            c.mstore(0_u64, FACTORY_ALL_PAIRS);
+            c.sstore(add(keccak256(0_u64, 32_u64), index), pair);
+            c.sstore(FACTORY_ALL_PAIRS, add(index, 1_u64));
+            c.log(
+                vec![event_topic("PairCreated(address,address,address,uint256)").into(), token0.into(), token1.into()],
+                vec![pair.into(), add(index, 1_u64)],
+            );
+            c.ret(vec![pair.into()]);
+        })
+        .function("getPair(address,address)", |c| c.ret(vec![sload(mapping2(FACTORY_GET_PAIR, arg(0), arg(1)))]))
+        .function("allPairsLength()", |c| c.ret(vec![sload(FACTORY_ALL_PAIRS)]))
+        .function("allPairs(uint256)", |c| {
+            let index = c.let_(arg(0));
+            c.require(lt(index, sload(FACTORY_ALL_PAIRS)), "UniswapV2: INDEX_OUT_OF_RANGE");
+            c.mstore(0_u64, FACTORY_ALL_PAIRS);
+            c.ret(vec![sload(add(keccak256(0_u64, 32_u64), index))]);
+        })
+        .function("feeTo()", |c| c.ret(vec![sload(FACTORY_FEE_TO)]));
+    contract.build(&[("pair_init_code", &pair_code)])
+}
+
+pub fn factory_init_code() -> Vec<u8> {
+    init_code(&factory_runtime(), |_| {})
+}
+
+/// Hash init code của pair (dùng để tính địa chỉ pair theo CREATE2)
+pub fn pair_init_code_hash() -> H256 {
+    H256::from(ethers::utils::keccak256(pair_init_code()))
+}
+
+/// Đường đi swap trong calldata: độ dài và hàm đọc phần tử
+struct Path {
+    offset: Var,
+    len: Var,
+}
+
+impl Path {
+    fn load(c: &mut Code, arg_index: u64) -> Self {
+        let offset = c.let_(add(arg(arg_index), 4_u64));
+        let len = c.let_(calldataload(offset));
+        c.require(gt(len, 1_u64), "UniswapV2Library: INVALID_PATH");
+        Self { offset, len }
+    }
+
+    fn at(&self, index: impl Into<Expr>) -> Expr {
+        calldataload(add(add(self.offset, 32_u64), mul(index, 32_u64)))
+    }
+
+    fn last(&self) -> Expr {
+        self.at(sub(self.len, 1_u64))
+    }
+}
+
+/// Mảng `uint[] amounts` trong bộ nhớ ở dạng ABI (offset, độ dài, phần tử)
+fn amount_at(index: impl Into<Expr>) -> Expr {
+    add(DYN_BUF + 0x40, mul(index, 32_u64))
+}
+
+/// Địa chỉ pair của hai token theo factory, revert nếu chưa có
+fn pair_for(c: &mut Code, factory: Address, a: impl Into<Expr>, b: impl Into<Expr>) -> Var {
+    c.static_call(factory, "getPair(address,address)", vec![a.into(), b.into()]);
+    let pair = c.let_(call_result(0));
+    c.require(pair, "UniswapV2Library: PAIR_NOT_FOUND");
+    pair
+}
+
+/// Reserve của pair theo thứ tự (a, b)
+fn reserves_for(c: &mut Code, pair: Var, a: Var, b: Var) -> (Var, Var) {
+    c.static_call(pair, "getReserves()", vec![]);
+    let (reserve_a, reserve_b) = (c.let_(call_result(0)), c.let_(call_result(1)));
+    c.if_(lt(b, a), |c| {
+        c.set(reserve_a, call_result(1));
+        c.set(reserve_b, call_result(0));
+    });
+    (reserve_a, reserve_b)
+}
+
+/// UniswapV2Library.getAmountOut (phí 0.3%)
+fn amount_out(c: &mut Code, amount_in: impl Into<Expr>, reserve_in: Var, reserve_out: Var) -> Var {
+    let amount_in = c.let_(amount_in);
+    c.require(amount_in, "UniswapV2Library: INSUFFICIENT_INPUT_AMOUNT");
+    c.require(and(reserve_in, reserve_out), "UniswapV2Library: INSUFFICIENT_LIQUIDITY");
+    let with_fee = c.let_(mul(amount_in, 997_u64));
+    c.let_(div(mul(with_fee, reserve_out), add(mul(reserve_in, 1_000_u64), with_fee)))
+}
+
+/// UniswapV2Library.getAmountsOut: ghi mảng amounts vào `DYN_BUF`
+fn amounts_out(c: &mut Code, factory: Address, amount_in: impl Into<Expr>, path: &Path) {
+    c.mstore(DYN_BUF, 0x20_u64);
+    c.mstore(DYN_BUF + 0x20, path.len);
+    c.mstore(amount_at(0_u64), amount_in);
+    let index = c.let_(0_u64);
+    c.while_(lt(index, sub(path.len, 1_u64)), |c| {
+        let (input, output) = (c.let_(path.at(index)), c.let_(path.at(add(index, 1_u64))));
+        let pair = pair_for(c, factory, input, output);
+        let (reserve_in, reserve_out) = reserves_for(c, pair, input, output);
+        let out = amount_out(c, mload(amount_at(index)), reserve_in, reserve_out);
+        c.mstore(amount_at(add(index, 1_u64)), out);
+        c.set(index, add(index, 1_u64));
+    });
+}
+
+fn return_amounts(c: &mut Code, path: &Path) {
+    c.return_memory(DYN_BUF, add(0x40_u64, mul(path.len, 32_u64)));
+}
+
+/// UniswapV2Router02._swap: swap lần lượt qua các pair, token cuối gửi tới `to`
+fn swap_along(c: &mut Code, factory: Address, path: &Path, to: impl Into<Expr>) {
+    let to = c.let_(to);
+    let index = c.let_(0_u64);
+    c.while_(lt(index, sub(path.len, 1_u64)), |c| {
+        let (input, output) = (c.let_(path.at(index)), c.let_(path.at(add(index, 1_u64))));
+        let out = c.let_(mload(amount_at(add(index, 1_u64))));
+        let (out0, out1) = (c.let_(0_u64), c.let_(out));
+        c.if_(lt(output, input), |c| {
+            c.set(out0, out);
+            c.set(out1, 0_u64);
+        });
+        let recipient = c.let_(to);
+        c.if_(lt(add(index, 2_u64), path.len), |c| {
+            let next = pair_for(c, factory, output, path.at(add(index, 2_u64)));
+            c.set(recipient, next);
+        });
+        let pair = pair_for(c, factory, input, output);
+        let ok = c.call(pair, 0_u64, "swap(uint256,uint256,address,bytes)", vec![
+            out0.into(), out1.into(), recipient.into(), 0x80_u64.into(), 0_u64.into(),
+        ]);
+        c.bubble_revert(ok);
+        c.set(index, add(index, 1_u64));
+    });
+}
+
+fn ensure_deadline(c: &mut Code, arg_index: u64) {
+    c.require(not_lt(arg(arg_index), timestamp()), "UniswapV2Router: EXPIRED");
+}
+
+/// Lượng token tối ưu khi thêm thanh khoản (UniswapV2Router02._addLiquidity)
+fn liquidity_amounts(c: &mut Code, factory: Address, token_a: Var, token_b: Var, desired: (Var, Var), minimum: (Var, Var)) -> (Var, Var, Var) {
+    c.static_call(factory, "getPair(address,address)", vec![token_a.into(), token_b.into()]);
+    let pair = c.let_(call_result(0));
+    c.if_(iszero(pair), |c| {
+        let ok = c.call(factory, 0_u64, "createPair(address,address)", vec![token_a.into(), token_b.into()]);
+        c.bubble_revert(ok);
+        c.set(pair, call_result(0));
+    });
+    let (reserve_a, reserve_b) = reserves_for(c, pair, token_a, token_b);
+    let (amount_a, amount_b) = (c.let_(desired.0), c.let_(desired.1));
+    c.if_(or(reserve_a, reserve_b), |c| {
+        let optimal_b = c.let_(div(mul(desired.0, reserve_b), reserve_a));
+        c.if_else(not_lt(desired.1, optimal_b), |c| {
+            c.require(not_lt(optimal_b, minimum.1), "UniswapV2Router: INSUFFICIENT_B_AMOUNT");
+            c.set(amount_b, optimal_b);
+        }, |c| {
+            let optimal_a = c.let_(div(mul(desired.1, reserve_a), reserve_b));
+            c.require(not_lt(desired.0, optimal_a), "UniswapV2Router: EXCESSIVE_A_AMOUNT");
+            c.require(not_lt(optimal_a, minimum.0), "UniswapV2Router: INSUFFICIENT_A_AMOUNT");
+            c.set(amount_a, optimal_a);
+        });
+    });
+    (pair, amount_a, amount_b)
+}
+
+/// Runtime bytecode UniswapV2Router02 với factory và WETH gắn cứng (immutable)
+pub fn router_runtime(factory: Address, weth: Address) -> Vec<u8> {
+    let mut contract = Contract::new();
+    contract
+        .function("factory()", move |c| c.ret(vec![factory.into()]))
+        .functions_with_aliases(&["WETH()", "WAVAX()"], move |c| c.ret(vec![weth.into()]))
+        .function("getAmountOut(uint256,uint256,uint256)", |c| {
+            let (reserve_in, reserve_out) = (c.let_(arg(1)), c.let_(arg(2)));
+            let out = amount_out(c, arg(0), reserve_in, reserve_out);
+            c.ret(vec![out.into()]);
+        })
+        .function("getAmountsOut(uint256,address[])", move |c| {
+            let path = Path::load(c, 1);
+            amounts_out(c, factory, arg(0), &path);
+            return_amounts(c, &path);
+        })
+        .function("swapExactTokensForTokens(uint256,uint256,address[],address,uint256)", move |c| {
+            ensure_deadline(c, 4);
+            let path = Path::load(c, 2);
+            amounts_out(c, factory, arg(0), &path);
+            c.require(not_lt(mload(amount_at(sub(path.len, 1_u64))), arg(1)), "UniswapV2Router: INSUFFICIENT_OUTPUT_AMOUNT");
+            let first_pair = pair_for(c, factory, path.at(0_u64), path.at(1_u64));
+            safe_transfer_from(c, path.at(0_u64), caller(), first_pair, arg(0));
+            swap_along(c, factory, &path, arg(3));
+            return_amounts(c, &path);
+        })
+        .functions_with_aliases(&[
+            "swapExactETHForTokens(uint256,address[],address,uint256)",
+            "swapExactAVAXForTokens(uint256,address[],address,uint256)",
+        ], move |c| {
+            ensure_deadline(c, 3);
+            let path = Path::load(c, 1);
+            c.require(eq(path.at(0_u64), weth), "UniswapV2Router: INVALID_PATH");
+            amounts_out(c, factory, callvalue(), &path);
+            c.require(not_lt(mload(amount_at(sub(path.len, 1_u64))), arg(0)), "UniswapV2Router: INSUFFICIENT_OUTPUT_AMOUNT");
+            let ok = c.call(weth, callvalue(), "deposit()", vec![]);
+            c.require(ok, "UniswapV2Router: DEPOSIT_FAILED");
+            let first_pair = pair_for(c, factory, path.at(0_u64), path.at(1_u64));
+            safe_transfer(c, weth, first_pair, callvalue(), "UniswapV2Router: TRANSFER_FAILED");
+            swap_along(c, factory, &path, arg(2));
+            return_amounts(c, &path);
+        })
+        .functions_with_aliases(&[
+            "swapExactTokensForETH(uint256,uint256,address[],address,uint256)",
+            "swapExactTokensForAVAX(uint256,uint256,address[],address,uint256)",
+        ], move |c| {
+            ensure_deadline(c, 4);
+            let path = Path::load(c, 2);
+            c.require(eq(path.last(), weth), "UniswapV2Router: INVALID_PATH");
+            amounts_out(c, factory, arg(0), &path);
+            let out = c.let_(mload(amount_at(sub(path.len, 1_u64))));
+            c.require(not_lt(out, arg(1)), "UniswapV2Router: INSUFFICIENT_OUTPUT_AMOUNT");
+            let first_pair = pair_for(c, factory, path.at(0_u64), path.at(1_u64));
+            safe_transfer_from(c, path.at(0_u64), caller(), first_pair, arg(0));
+            swap_along(c, factory, &path, address());
+            let ok = c.call(weth, 0_u64, "withdraw(uint256)", vec![out.into()]);
+            c.require(ok, "UniswapV2Router: WITHDRAW_FAILED");
+            safe_transfer_eth(c, arg(3), out);
+            return_amounts(c, &path);
+        })
+        .function("addLiquidity(address,address,uint256,uint256,uint256,uint256,address,uint256)", move |c| {
+            ensure_deadline(c, 7);
+            let (token_a, token_b) = (c.let_(arg(0)), c.let_(arg(1)));
+            let desired = (c.let_(arg(2)), c.let_(arg(3)));
+            let minimum = (c.let_(arg(4)), c.let_(arg(5)));
+            let (pair, amount_a, amount_b) = liquidity_amounts(c, factory, token_a, token_b, desired, minimum);
+            safe_transfer_from(c, token_a, caller(), pair, amount_a);
+            safe_transfer_from(c, token_b, caller(), pair, amount_b);
+            let ok = c.call(pair, 0_u64, "mint(address)", vec![arg(6)]);
+            c.require(ok, "UniswapV2Router: MINT_FAILED");
+            c.ret(vec![amount_a.into(), amount_b.into(), call_result(0)]);
+        })
+        .function("addLiquidityETH(address,uint256,uint256,uint256,address,uint256)", move |c| {
+            ensure_deadline(c, 5);
+            let (token, native) = (c.let_(arg(0)), c.let_(weth));
+            let desired = (c.let_(arg(1)), c.let_(callvalue()));
+            let minimum = (c.let_(arg(2)), c.let_(arg(3)));
+            let (pair, amount_token, amount_eth) = liquidity_amounts(c, factory, token, native, desired, minimum);
+            safe_transfer_from(c, token, caller(), pair, amount_token);
+            let ok = c.call(weth, amount_eth, "deposit()", vec![]);
+            c.require(ok, "UniswapV2Router: DEPOSIT_FAILED");
+            safe_transfer(c, weth, pair, amount_eth, "UniswapV2Router: TRANSFER_FAILED");
+            let ok = c.call(pair, 0_u64, "mint(address)", vec![arg(4)]);
+            c.require(ok, "UniswapV2Router: MINT_FAILED");
+            let liquidity = c.let_(call_result(0));
+            c.if_(gt(callvalue(), amount_eth), |c| safe_transfer_eth(c, caller(), sub(callvalue(), amount_eth)));
+            c.ret(vec![amount_token.into(), amount_eth.into(), liquidity.into()]);
+        })
+        // Chỉ nhận ETH từ WETH (khi withdraw)
+        .fallback(move |c| c.require(eq(caller(), weth), "UniswapV2Router: ETH_NOT_FROM_WETH"));
+    contract.build(&[])
+}
+
+pub fn router_init_code(factory: Address, weth: Address) -> Vec<u8> {
+    init_code(&router_runtime(factory, weth), |_| {})
+}
+
+/// Runtime bytecode GasPriceOracle OP-stack mock: phí L1 cố định cho mọi giao dịch
+pub fn op_gas_price_oracle_runtime(l1_fee: U256, l1_base_fee: U256) -> Vec<u8> {
+    let mut contract = Contract::new();
+    contract
+        .function("getL1Fee(bytes)", move |c| c.ret(vec![l1_fee.into()]))
+        .function("l1BaseFee()", move |c| c.ret(vec![l1_base_fee.into()]));
+    contract.build(&[])
+}
+
+/// Runtime bytecode NodeInterface Arbitrum mock: gasEstimateComponents trả
+/// (gasEstimate, gasEstimateForL1, baseFee, l1BaseFeeEstimate) cố định
+pub fn arbitrum_node_interface_runtime(gas_estimate: u64, gas_for_l1: u64, base_fee: U256, l1_base_fee: U256) -> Vec<u8> {
+    let mut contract = Contract::new();
+    contract.function("gasEstimateComponents(address,bool,bytes)", move |c| {
+        c.ret(vec![gas_estimate.into(), gas_for_l1.into(), base_fee.into(), l1_base_fee.into()])
+    });
+    contract.build(&[])
+}
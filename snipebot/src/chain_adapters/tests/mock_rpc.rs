@@ -0,0 +1,67 @@
+// External imports
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+// Standard library imports
+use std::sync::Arc;
+
+/// Mock node JSON-RPC qua HTTP, chạy trên cổng ngẫu nhiên ở localhost.
+/// `handler` trả `result` cho từng (method, params); `Value::Null` được trả về như lỗi JSON-RPC
+pub async fn spawn_mock_rpc<F>(handler: F) -> String
+where
+    F: Fn(&str, &Value) -> Value + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handler = Arc::new(handler);
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let mut buffer = Vec::new();
+                let mut chunk = [0_u8; 4096];
+                // Đọc header rồi body theo Content-Length
+                let body = loop {
+                    let read = match socket.read(&mut chunk).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(read) => read,
+                    };
+                    buffer.extend_from_slice(&chunk[..read]);
+                    let text = String::from_utf8_lossy(&buffer).to_string();
+                    if let Some(header_end) = text.find("\r\n\r\n") {
+                        let content_length = text[..header_end].lines()
+                            .find_map(|line| {
+                                let (name, value) = line.split_once(':')?;
+                                name.eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().ok())?
+                            })
+                            .unwrap_or(0);
+                        if buffer.len() >= header_end + 4 + content_length {
+                            break buffer[header_end + 4..header_end + 4 + content_length].to_vec();
+                        }
+                    }
+                };
+
+                let request: Value = serde_json::from_slice(&body).unwrap_or_default();
+                let method = request["method"].as_str().unwrap_or_default();
+                let result = handler(method, &request["params"]);
+                let response = if result.is_null() {
+                    json!({"jsonrpc": "2.0", "id": request["id"], "error": {"code": -32602, "message": format!("mock: rejected {}", method)}})
+                } else {
+                    json!({"jsonrpc": "2.0", "id": request["id"], "result": result})
+                };
+                let payload = response.to_string();
+                let http = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    payload.len(), payload
+                );
+                let _ = socket.write_all(http.as_bytes()).await;
+            });
+        }
+    });
+
+    url
+}
@@ -0,0 +1,8 @@
+pub mod evm_asm;
+pub mod mock_adapter;
+pub mod mock_contracts;
+pub mod mock_chain;
+pub mod mock_rpc;
+pub mod wallet_fixture;
+
+mod test_evm_adapter;
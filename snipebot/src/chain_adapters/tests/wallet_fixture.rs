@@ -0,0 +1,47 @@
+// External imports
+use ethers::types::Address;
+
+// Standard library imports
+use std::sync::Arc;
+
+// Internal imports
+use super::mock_chain::MockChain;
+use diamond_wallet::{ManagedSigner, MasterKeyParams, StorageConfig, WalletManager, WalletManagerConfig};
+
+/// Passphrase vận hành của WalletManager trong test
+pub const TEST_PASSPHRASE: &str = "diamond test wallet passphrase";
+
+/// WalletManager trên thư mục tạm với master key nhẹ (Argon2 tham số thấp) cho test
+pub struct TestWallets {
+    pub manager: Arc<WalletManager>,
+    pub dir: tempfile::TempDir,
+}
+
+impl TestWallets {
+    pub fn new(chain_id: u64) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = Arc::new(WalletManager::new(WalletManagerConfig {
+            default_chain_id: chain_id,
+            storage_config: StorageConfig {
+                wallet_dir: dir.path().join("wallets").to_string_lossy().to_string(),
+                master_key: MasterKeyParams::light(),
+                ..Default::default()
+            },
+            wallet_encryption_seed: TEST_PASSPHRASE.to_string(),
+            default_policy: None,
+        }).unwrap());
+        Self { manager, dir }
+    }
+
+    /// Import ví dev thứ `index` của mock chain vào WalletManager
+    pub fn import_dev_wallet(&self, chain: &MockChain, index: usize) -> Address {
+        let wallet = chain.dev_wallet(index);
+        self.manager.import_private_key(&hex::encode(wallet.signer().to_bytes()), None).unwrap()
+    }
+
+    /// Import ví dev thứ `index` và trả về signer của WalletManager cho ví đó
+    pub fn dev_signer(&self, chain: &MockChain, index: usize) -> ManagedSigner {
+        let address = self.import_dev_wallet(chain, index);
+        self.manager.managed_signer(address, chain.chain_id()).unwrap()
+    }
+}
@@ -0,0 +1,196 @@
+// External imports
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use serde::{Serialize, Deserialize};
+
+// Standard library imports
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, RwLock},
+};
+
+// Third party imports
+use anyhow::{anyhow, Result};
+
+/// Ký hiệu token gốc của chain trong `SwapRequest` (ETH, BNB, SOL...)
+pub const NATIVE_TOKEN: &str = "native";
+
+/// Mẫu số của slippage tính theo basis point
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Họ blockchain của một adapter giao dịch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChainFamily {
+    /// Chain tương thích EVM (Ethereum, BSC, Base...)
+    Evm,
+    /// Solana
+    Solana,
+}
+
+/// Yêu cầu swap không phụ thuộc chain. Địa chỉ dùng định dạng gốc của chain
+/// (hex cho EVM, base58 cho Solana), số lượng tính theo đơn vị nhỏ nhất
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapRequest {
+    /// Token bán (`NATIVE_TOKEN` cho token gốc)
+    pub input_token: String,
+    /// Token mua (`NATIVE_TOKEN` cho token gốc)
+    pub output_token: String,
+    /// Số lượng bán (đơn vị nhỏ nhất: wei, lamports...)
+    pub amount_in: u128,
+    /// Slippage tối đa (basis point)
+    pub slippage_bps: u32,
+    /// Pool/router cụ thể muốn dùng (bắt buộc với Solana: địa chỉ pool Raydium/Orca)
+    #[serde(default)]
+    pub venue: Option<String>,
+}
+
+impl SwapRequest {
+    /// Yêu cầu mua `token` bằng token gốc
+    pub fn buy(token: &str, amount_in: u128, slippage_bps: u32) -> Self {
+        Self {
+            input_token: NATIVE_TOKEN.to_string(),
+            output_token: token.to_string(),
+            amount_in,
+            slippage_bps,
+            venue: None,
+        }
+    }
+
+    /// Yêu cầu bán `token` lấy token gốc
+    pub fn sell(token: &str, amount_in: u128, slippage_bps: u32) -> Self {
+        Self {
+            input_token: token.to_string(),
+            output_token: NATIVE_TOKEN.to_string(),
+            amount_in,
+            slippage_bps,
+            venue: None,
+        }
+    }
+
+    /// Chỉ định pool/router
+    pub fn with_venue(mut self, venue: &str) -> Self {
+        self.venue = Some(venue.to_string());
+        self
+    }
+
+    /// Bên bán là token gốc
+    pub fn is_buy(&self) -> bool {
+        self.input_token == NATIVE_TOKEN
+    }
+}
+
+/// Báo giá cho một yêu cầu swap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeQuote {
+    /// Số lượng bán
+    pub amount_in: u128,
+    /// Số lượng dự kiến nhận
+    pub amount_out: u128,
+    /// Số lượng tối thiểu chấp nhận sau slippage
+    pub min_amount_out: u128,
+    /// Pool/router dùng để báo giá
+    pub venue: String,
+}
+
+/// Kết quả swap đã gửi lên chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwapOutcome {
+    /// Hash (EVM) hoặc chữ ký (Solana) của giao dịch
+    pub tx_id: String,
+    /// Báo giá dùng khi tạo giao dịch
+    pub quote: TradeQuote,
+    /// Giao dịch đã được xác nhận
+    pub confirmed: bool,
+}
+
+/// Số lượng tối thiểu nhận được sau khi trừ slippage
+pub fn min_amount_out(amount_out: u128, slippage_bps: u32) -> u128 {
+    let keep = BPS_DENOMINATOR - (slippage_bps as u128).min(BPS_DENOMINATOR);
+    // Chia trước để không tràn số với amount lớn
+    amount_out / BPS_DENOMINATOR * keep + amount_out % BPS_DENOMINATOR * keep / BPS_DENOMINATOR
+}
+
+/// Giao diện giao dịch không phụ thuộc kiểu dữ liệu EVM, để SnipeBot có thể
+/// giao dịch trên cả chain EVM và non-EVM (Solana)
+#[async_trait]
+pub trait TradingAdapter: Send + Sync + Debug {
+    /// Họ blockchain
+    fn chain_family(&self) -> ChainFamily;
+
+    /// Tên chain
+    fn chain_name(&self) -> &str;
+
+    /// Ký hiệu token gốc
+    fn native_symbol(&self) -> &str;
+
+    /// Kiểm tra địa chỉ theo định dạng của chain
+    fn is_valid_address(&self, address: &str) -> bool;
+
+    /// Địa chỉ ví dùng để giao dịch (nếu đã gắn ví)
+    fn trader_address(&self) -> Option<String>;
+
+    /// Số dư token gốc
+    async fn native_balance(&self, owner: &str) -> Result<u128>;
+
+    /// Số dư token
+    async fn token_balance(&self, token: &str, owner: &str) -> Result<u128>;
+
+    /// Báo giá swap
+    async fn quote(&self, request: &SwapRequest) -> Result<TradeQuote>;
+
+    /// Tạo, ký và gửi giao dịch swap
+    async fn swap(&self, request: &SwapRequest) -> Result<SwapOutcome>;
+}
+
+/// Registry toàn cục các adapter giao dịch theo tên chain (viết thường)
+static TRADING_ADAPTERS: Lazy<RwLock<HashMap<String, Arc<dyn TradingAdapter>>>> = Lazy::new(|| {
+    RwLock::new(HashMap::new())
+});
+
+/// Đăng ký adapter giao dịch cho chain
+pub fn register_trading_adapter(chain: &str, adapter: Arc<dyn TradingAdapter>) {
+    match TRADING_ADAPTERS.write() {
+        Ok(mut adapters) => {
+            adapters.insert(chain.to_lowercase(), adapter);
+        },
+        Err(e) => tracing::error!("Không thể ghi registry adapter giao dịch: {}", e),
+    }
+}
+
+/// Lấy adapter giao dịch của chain
+pub fn get_trading_adapter(chain: &str) -> Result<Arc<dyn TradingAdapter>> {
+    let adapters = TRADING_ADAPTERS.read()
+        .map_err(|e| anyhow!("Không thể đọc registry adapter giao dịch: {}", e))?;
+    adapters.get(&chain.to_lowercase())
+        .cloned()
+        .ok_or_else(|| anyhow!("Chưa có adapter giao dịch cho chain: {}", chain))
+}
+
+/// Danh sách chain đã có adapter giao dịch
+pub fn get_trading_chains() -> Vec<String> {
+    TRADING_ADAPTERS.read()
+        .map(|adapters| adapters.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_amount_out_applies_slippage() {
+        assert_eq!(min_amount_out(10_000, 50), 9_950);
+        assert_eq!(min_amount_out(10_000, 0), 10_000);
+        assert_eq!(min_amount_out(10_000, 20_000), 0);
+        assert!(min_amount_out(u128::MAX, 100) < u128::MAX);
+    }
+
+    #[test]
+    fn test_swap_request_builders() {
+        let buy = SwapRequest::buy("So1", 5, 100).with_venue("pool");
+        assert!(buy.is_buy());
+        assert_eq!(buy.venue.as_deref(), Some("pool"));
+        assert!(!SwapRequest::sell("So1", 5, 100).is_buy());
+    }
+}
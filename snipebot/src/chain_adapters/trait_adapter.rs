@@ -1,21 +1,30 @@
 use async_trait::async_trait;
-use ethers::types::{Address, U256, TransactionRequest, TransactionReceipt};
+use ethers::types::{Address, U256, Transaction, TransactionRequest, TransactionReceipt};
 use ethers::{
-    providers::{Provider, Http, Middleware},
-    signers::LocalWallet,
+    providers::{Provider, Http},
     abi::Token,
 };
 use anyhow::Result;
 use std::sync::Arc;
-use std::time::Duration;
+use diamond_wallet::ManagedSigner;
 use crate::error::TransactionError;
+use crate::chain_adapters::base::EVMAdapter;
 use std::fmt::Debug;
-use ethers::core::types::BlockNumber;
 use tokio::task::JoinHandle;
-use std::sync::{Arc, RwLock};
+
+/// Cho phép downcast trait object `ChainAdapter` về kiểu adapter cụ thể
+pub trait AsAny {
+    fn as_any(&self) -> &dyn std::any::Any;
+}
+
+impl<T: std::any::Any> AsAny for T {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
 
 /// Trait định nghĩa các chức năng không async của một blockchain adapter
-pub trait ChainAdapter: Send + Sync + Debug + 'static {
+pub trait ChainAdapter: AsAny + Send + Sync + Debug + 'static {
     /// Lấy config của chain
     fn get_config(&self) -> &crate::chain_adapters::base::ChainConfig;
     
@@ -23,10 +32,10 @@ pub trait ChainAdapter: Send + Sync + Debug + 'static {
     fn get_provider(&self) -> &Provider<Http>;
     
     /// Lấy ví (nếu có)
-    fn get_wallet(&self) -> Option<&LocalWallet>;
+    fn get_wallet(&self) -> Option<&ManagedSigner>;
     
     /// Đặt ví 
-    fn set_wallet(&mut self, wallet: LocalWallet);
+    fn set_wallet(&mut self, wallet: ManagedSigner);
     
     /// Lấy gas optimizer (nếu có)
     fn get_gas_optimizer(&self) -> Option<&crate::gas_optimizer::GasOptimizer>;
@@ -67,6 +76,7 @@ pub trait ChainAdapter: Send + Sync + Debug + 'static {
 }
 
 /// Trait cho các chức năng async của một blockchain adapter
+#[allow(clippy::too_many_arguments)]
 #[async_trait]
 pub trait AsyncChainAdapter: ChainAdapter + Send + Sync + 'static {
     /// Lấy số dư native token
@@ -140,83 +150,68 @@ pub trait AsyncChainAdapter: ChainAdapter + Send + Sync + 'static {
     async fn create_flashbots_bundle(&self, txs: Vec<TransactionRequest>) -> Result<()>;
 }
 
-// Tạo trait cho các chức năng liên quan đến callback để xử lý riêng
+/// Callback nhận giao dịch từ mempool
+pub type TransactionCallback = Box<dyn Fn(Transaction) + Send + Sync + 'static>;
+
+/// Callback nhận giao dịch kèm số lượng (cơ hội sandwich)
+pub type SandwichCallback = Box<dyn Fn(Transaction, U256) + Send + Sync + 'static>;
+
+// Tạo trait cho các chức năng liên quan đến callback để xử lý riêng.
+// Callback được bọc trong Box để trait dùng được dưới dạng trait object
 #[async_trait]
 pub trait ChainWatcher: Send + Sync + Debug {
     /// Theo dõi giao dịch đang chờ xử lý
-    async fn watch_pending_transactions<F>(&self, callback: F) -> Result<()>
-    where
-        F: Fn(Transaction) + Send + Sync + 'static;
+    async fn watch_pending_transactions(&self, callback: TransactionCallback) -> Result<()>;
     
     /// Theo dõi các giao dịch liên quan đến token cụ thể
-    async fn watch_token_transactions<F>(&self, token_address: &str, callback: F) -> Result<JoinHandle<()>>
-    where
-        F: Fn(Transaction) + Send + Sync + 'static;
+    async fn watch_token_transactions(&self, token_address: &str, callback: TransactionCallback) -> Result<JoinHandle<()>>;
     
     /// Theo dõi cơ hội sandwich attack
-    async fn watch_for_sandwich_opportunities<F>(&self, token_address: &str, min_amount: U256, callback: F) -> Result<JoinHandle<()>>
-    where
-        F: Fn(Transaction, U256) + Send + Sync + 'static;
+    async fn watch_for_sandwich_opportunities(&self, token_address: &str, min_amount: U256, callback: SandwichCallback) -> Result<JoinHandle<()>>;
+}
+
+#[async_trait]
+impl ChainWatcher for EVMAdapter {
+    async fn watch_pending_transactions(&self, callback: TransactionCallback) -> Result<()> {
+        EVMAdapter::watch_pending_transactions(self, callback).await
+    }
+    
+    async fn watch_token_transactions(&self, token_address: &str, callback: TransactionCallback) -> Result<JoinHandle<()>> {
+        EVMAdapter::watch_token_transactions(self, token_address, callback).await
+    }
+    
+    async fn watch_for_sandwich_opportunities(&self, token_address: &str, min_amount: U256, callback: SandwichCallback) -> Result<JoinHandle<()>> {
+        EVMAdapter::watch_for_sandwich_opportunities(self, token_address, min_amount, callback).await
+    }
 }
 
 /// Enum bọc cho ChainWatcher để có thể sử dụng như trait object
 #[derive(Debug, Clone)]
 pub enum ChainWatcherEnum {
-    Default(Arc<dyn ChainAdapter + Send + Sync>),
+    Default(Arc<EVMAdapter>),
     Custom(Arc<dyn ChainWatcher + Send + Sync>),
 }
 
-#[async_trait]
-impl ChainWatcher for ChainWatcherEnum {
-    /// Theo dõi giao dịch đang chờ xử lý
-    async fn watch_pending_transactions<F>(&self, callback: F) -> Result<()>
-    where
-        F: Fn(Transaction) + Send + Sync + 'static
-    {
+impl ChainWatcherEnum {
+    fn watcher(&self) -> &dyn ChainWatcher {
         match self {
-            ChainWatcherEnum::Default(adapter) => {
-                // Cast adapter to ChainWatcher if possible, otherwise error
-                if let Some(watcher) = adapter.as_any().downcast_ref::<dyn ChainWatcher>() {
-                    watcher.watch_pending_transactions(callback).await
-                } else {
-                    Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng ChainWatcher"))
-                }
-            },
-            ChainWatcherEnum::Custom(watcher) => watcher.watch_pending_transactions(callback).await,
+            ChainWatcherEnum::Default(adapter) => adapter.as_ref(),
+            ChainWatcherEnum::Custom(watcher) => watcher.as_ref(),
         }
     }
+}
+
+#[async_trait]
+impl ChainWatcher for ChainWatcherEnum {
+    async fn watch_pending_transactions(&self, callback: TransactionCallback) -> Result<()> {
+        self.watcher().watch_pending_transactions(callback).await
+    }
     
-    /// Theo dõi các giao dịch liên quan đến token cụ thể
-    async fn watch_token_transactions<F>(&self, token_address: &str, callback: F) -> Result<JoinHandle<()>>
-    where
-        F: Fn(Transaction) + Send + Sync + 'static
-    {
-        match self {
-            ChainWatcherEnum::Default(adapter) => {
-                if let Some(watcher) = adapter.as_any().downcast_ref::<dyn ChainWatcher>() {
-                    watcher.watch_token_transactions(token_address, callback).await
-                } else {
-                    Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng ChainWatcher"))
-                }
-            },
-            ChainWatcherEnum::Custom(watcher) => watcher.watch_token_transactions(token_address, callback).await,
-        }
+    async fn watch_token_transactions(&self, token_address: &str, callback: TransactionCallback) -> Result<JoinHandle<()>> {
+        self.watcher().watch_token_transactions(token_address, callback).await
     }
     
-    /// Theo dõi cơ hội sandwich attack
-    async fn watch_for_sandwich_opportunities<F>(&self, token_address: &str, min_amount: U256, callback: F) -> Result<JoinHandle<()>>
-    where
-        F: Fn(Transaction, U256) + Send + Sync + 'static
-    {
-        match self {
-            ChainWatcherEnum::Default(adapter) => {
-                if let Some(watcher) = adapter.as_any().downcast_ref::<dyn ChainWatcher>() {
-                    watcher.watch_for_sandwich_opportunities(token_address, min_amount, callback).await
-                } else {
-                    Err(anyhow::anyhow!("Adapter không hỗ trợ chức năng ChainWatcher"))
-                }
-            },
-            ChainWatcherEnum::Custom(watcher) => watcher.watch_for_sandwich_opportunities(token_address, min_amount, callback).await,
-        }
+    async fn watch_for_sandwich_opportunities(&self, token_address: &str, min_amount: U256, callback: SandwichCallback) -> Result<JoinHandle<()>> {
+        self.watcher().watch_for_sandwich_opportunities(token_address, min_amount, callback).await
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,707 @@
+// External imports
+use ethers::{
+    abi::Token,
+    providers::{Http, Provider},
+    types::{transaction::eip2718::TypedTransaction, Address, TransactionRequest, H256, U256, U64},
+    utils::parse_units,
+};
+
+// Standard library imports
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Third party imports
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+// Internal imports
+use crate::chain_adapters::approval_manager::encode_call;
+use crate::chain_adapters::chain_registry::{self, ChainConfig};
+use crate::chain_adapters::interfaces::ChainAdapter;
+use diamond_wallet::WalletManager;
+
+/// Gas của một lần chuyển native
+const NATIVE_TRANSFER_GAS: u64 = 21_000;
+/// Gas ước lượng cho một lần chuyển token ERC-20
+const TOKEN_TRANSFER_GAS: u64 = 65_000;
+/// Gas ước lượng cho một lần approve
+const APPROVE_GAS: u64 = 50_000;
+/// Gas cố định và gas thêm cho mỗi người nhận của hợp đồng disperse
+const DISPERSE_BASE_GAS: u64 = 35_000;
+const DISPERSE_NATIVE_GAS_PER_RECIPIENT: u64 = 12_000;
+const DISPERSE_TOKEN_GAS_PER_RECIPIENT: u64 = 40_000;
+
+/// Tài sản được chuyển
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Asset {
+    Native,
+    Token(Address),
+}
+
+/// Cách chia tổng số lượng cho các ví nhận
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Distribution {
+    Equal,
+    /// Trọng số theo thứ tự ví nhận
+    Weighted(Vec<u64>),
+}
+
+/// Loại thao tác quỹ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TreasuryOperation {
+    /// Chia từ ví chủ cho các ví con
+    Distribute,
+    /// Gom số dư các ví con về một ví
+    Sweep,
+    /// Nạp gas cho ví dưới ngưỡng
+    TopUp,
+}
+
+/// Cách gửi các lần chuyển trong kế hoạch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferRoute {
+    /// Mỗi lần chuyển là một giao dịch
+    Direct,
+    /// Gộp vào một giao dịch qua hợp đồng disperse
+    Disperse(Address),
+}
+
+/// Một lần chuyển trong kế hoạch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transfer {
+    pub from: Address,
+    pub to: Address,
+    pub amount: U256,
+}
+
+/// Ví bị bỏ qua khi lập kế hoạch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedWallet {
+    pub wallet: Address,
+    pub reason: String,
+}
+
+/// Kế hoạch chuyển quỹ; dry-run chỉ trả về kế hoạch mà không gửi giao dịch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreasuryPlan {
+    pub chain_id: u64,
+    pub operation: TreasuryOperation,
+    pub asset: Asset,
+    pub route: TransferRoute,
+    pub transfers: Vec<Transfer>,
+    pub skipped: Vec<SkippedWallet>,
+    /// Tổng số lượng được chuyển
+    pub total: U256,
+    /// Gas price dùng khi lập kế hoạch và khi gửi
+    pub gas_price: U256,
+    /// Phí gas ước lượng của toàn bộ kế hoạch
+    pub estimated_fee: U256,
+}
+
+/// Lần chuyển gửi lỗi
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedTransfer {
+    pub transfer: Transfer,
+    pub error: String,
+}
+
+/// Kết quả thực hiện kế hoạch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TreasuryReport {
+    pub tx_hashes: Vec<H256>,
+    pub completed: Vec<Transfer>,
+    pub failed: Vec<FailedTransfer>,
+}
+
+/// Cấu hình quỹ của một chain, khai báo trong mục `[treasury]` của file cấu hình chain
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChainTreasuryConfig {
+    /// Hợp đồng kiểu Disperse.app (disperseEther/disperseToken) để gộp nhiều lần chuyển
+    #[serde(default)]
+    pub disperse_contract: Option<Address>,
+    /// Ngưỡng gas tối thiểu của ví (đơn vị native, VD: 0.01)
+    #[serde(default)]
+    pub gas_floor: Option<f64>,
+    /// Mức nạp lên khi ví dưới ngưỡng (đơn vị native)
+    #[serde(default)]
+    pub gas_target: Option<f64>,
+}
+
+/// Cấu hình Treasury
+#[derive(Debug, Clone)]
+pub struct TreasuryConfig {
+    pub disperse_contract: Option<Address>,
+    /// Ngưỡng gas tối thiểu (wei)
+    pub gas_floor: Option<U256>,
+    /// Mức nạp lên (wei)
+    pub gas_target: Option<U256>,
+    pub confirmations: usize,
+    pub receipt_timeout: Duration,
+}
+
+impl Default for TreasuryConfig {
+    fn default() -> Self {
+        Self {
+            disperse_contract: None,
+            gas_floor: None,
+            gas_target: None,
+            confirmations: 1,
+            receipt_timeout: Duration::from_secs(120),
+        }
+    }
+}
+
+impl TreasuryConfig {
+    /// Lấy cấu hình quỹ từ cấu hình chain
+    pub fn from_chain_config(chain: &ChainConfig) -> Result<Self> {
+        let to_wei = |value: Option<f64>, field: &str| -> Result<Option<U256>> {
+            value.map(|value| {
+                parse_units(value.to_string(), u32::from(chain.native_token_decimals))
+                    .map(U256::from)
+                    .with_context(|| format!("Invalid treasury.{} for chain {}", field, chain.chain_id))
+            }).transpose()
+        };
+
+        Ok(Self {
+            disperse_contract: chain.treasury.disperse_contract,
+            gas_floor: to_wei(chain.treasury.gas_floor, "gas_floor")?,
+            gas_target: to_wei(chain.treasury.gas_target, "gas_target")?,
+            ..Default::default()
+        })
+    }
+}
+
+/// Chia `total` cho `recipients` ví theo cách chia; phần dư do làm tròn thuộc về ví đầu tiên
+pub fn split_amount(total: U256, recipients: usize, distribution: &Distribution) -> Result<Vec<U256>> {
+    if recipients == 0 {
+        bail!("No recipients to distribute to");
+    }
+
+    let mut amounts = match distribution {
+        Distribution::Equal => vec![total / U256::from(recipients); recipients],
+        Distribution::Weighted(weights) => {
+            if weights.len() != recipients {
+                bail!("Expected {} weights, got {}", recipients, weights.len());
+            }
+            let sum: U256 = weights.iter().map(|weight| U256::from(*weight)).fold(U256::zero(), |a, b| a + b);
+            if sum.is_zero() {
+                bail!("Distribution weights must not all be zero");
+            }
+            weights.iter()
+                .map(|weight| total.checked_mul(U256::from(*weight)).map(|value| value / sum))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| anyhow!("Distribution amount overflow"))?
+        }
+    };
+
+    let distributed = amounts.iter().fold(U256::zero(), |a, b| a + *b);
+    amounts[0] += total - distributed;
+    Ok(amounts)
+}
+
+/// Yêu cầu lập kế hoạch quỹ, dùng chung cho API và CLI; số lượng là chuỗi thập phân (wei)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum TreasuryRequest {
+    Distribute {
+        from: Address,
+        recipients: Vec<Address>,
+        asset: Asset,
+        #[serde(with = "dec_u256")]
+        total: U256,
+        distribution: Distribution,
+    },
+    Sweep {
+        wallets: Vec<Address>,
+        to: Address,
+        asset: Asset,
+        #[serde(with = "dec_u256", default)]
+        dust_threshold: U256,
+    },
+    TopUp {
+        from: Address,
+        wallets: Vec<Address>,
+    },
+}
+
+/// Chia, gom và nạp gas cho nhiều ví trên một chain
+pub struct Treasury<A: ChainAdapter + ?Sized> {
+    adapter: Arc<A>,
+    provider: Arc<Provider<Http>>,
+    wallets: Arc<WalletManager>,
+    config: TreasuryConfig,
+}
+
+impl Treasury<dyn ChainAdapter> {
+    /// Tạo Treasury cho chain đã đăng ký trong registry, dùng cấu hình `[treasury]` của chain
+    pub fn for_chain(chain_id: u64, wallets: Arc<WalletManager>) -> Result<Self> {
+        let adapter = chain_registry::get_registry().get_adapter(chain_id)?;
+        let chain = chain_registry::get_chain_config(chain_id)?;
+        let provider = chain_registry::get_http_provider(chain_id)?;
+
+        Ok(Self::new(adapter, Arc::new(provider), wallets, TreasuryConfig::from_chain_config(&chain)?))
+    }
+}
+
+impl<A: ChainAdapter + ?Sized> Treasury<A> {
+    pub fn new(
+        adapter: Arc<A>,
+        provider: Arc<Provider<Http>>,
+        wallets: Arc<WalletManager>,
+        config: TreasuryConfig,
+    ) -> Self {
+        Self { adapter, provider, wallets, config }
+    }
+
+    /// Lập kế hoạch theo yêu cầu
+    pub async fn plan(&self, request: &TreasuryRequest) -> Result<TreasuryPlan> {
+        match request {
+            TreasuryRequest::Distribute { from, recipients, asset, total, distribution } => {
+                self.plan_distribution(*from, recipients, *asset, *total, distribution).await
+            }
+            TreasuryRequest::Sweep { wallets, to, asset, dust_threshold } => {
+                self.plan_sweep(wallets, *to, *asset, *dust_threshold).await
+            }
+            TreasuryRequest::TopUp { from, wallets } => self.plan_top_up(*from, wallets).await,
+        }
+    }
+
+    /// Lập kế hoạch chia `total` từ ví chủ cho các ví nhận
+    pub async fn plan_distribution(
+        &self,
+        from: Address,
+        recipients: &[Address],
+        asset: Asset,
+        total: U256,
+        distribution: &Distribution,
+    ) -> Result<TreasuryPlan> {
+        let unique: HashSet<&Address> = recipients.iter().collect();
+        if unique.len() != recipients.len() {
+            bail!("Recipient list contains duplicates");
+        }
+
+        let amounts = split_amount(total, recipients.len(), distribution)?;
+        let mut transfers = Vec::new();
+        let mut skipped = Vec::new();
+        for (to, amount) in recipients.iter().zip(amounts) {
+            if *to == from {
+                skipped.push(SkippedWallet { wallet: *to, reason: "recipient is the source wallet".to_string() });
+            } else if amount.is_zero() {
+                skipped.push(SkippedWallet { wallet: *to, reason: "share rounds down to zero".to_string() });
+            } else {
+                transfers.push(Transfer { from, to: *to, amount });
+            }
+        }
+
+        let plan = self.build_plan(TreasuryOperation::Distribute, asset, transfers, skipped).await?;
+        self.ensure_source_funds(from, &plan).await?;
+        Ok(plan)
+    }
+
+    /// Lập kế hoạch gom số dư các ví về `to`; ví có số dư (sau phí gas) không vượt ngưỡng bụi bị bỏ qua
+    pub async fn plan_sweep(
+        &self,
+        wallets: &[Address],
+        to: Address,
+        asset: Asset,
+        dust_threshold: U256,
+    ) -> Result<TreasuryPlan> {
+        let gas_price = self.gas_price().await?;
+        let mut transfers = Vec::new();
+        let mut skipped = Vec::new();
+
+        for wallet in wallets.iter().copied().filter(|wallet| *wallet != to) {
+            let native = self.native_balance(wallet).await?;
+            let skip = |reason: &str| SkippedWallet { wallet, reason: reason.to_string() };
+
+            match asset {
+                Asset::Native => {
+                    let fee = gas_price * NATIVE_TRANSFER_GAS;
+                    match native.checked_sub(fee) {
+                        Some(amount) if amount > dust_threshold => transfers.push(Transfer { from: wallet, to, amount }),
+                        _ => skipped.push(skip("balance after gas is at or below dust threshold")),
+                    }
+                }
+                Asset::Token(token) => {
+                    let balance = self.token_balance(token, wallet).await?;
+                    if balance <= dust_threshold {
+                        skipped.push(skip("balance is at or below dust threshold"));
+                    } else if native < gas_price * TOKEN_TRANSFER_GAS {
+                        skipped.push(skip("not enough native balance for gas"));
+                    } else {
+                        transfers.push(Transfer { from: wallet, to, amount: balance });
+                    }
+                }
+            }
+        }
+
+        let mut plan = self.build_plan(TreasuryOperation::Sweep, asset, transfers, skipped).await?;
+        // Mỗi ví tự gửi giao dịch của mình nên không gộp qua disperse
+        plan.route = TransferRoute::Direct;
+        plan.gas_price = gas_price;
+        plan.estimated_fee = gas_price * transfer_gas(asset) * plan.transfers.len();
+        Ok(plan)
+    }
+
+    /// Lập kế hoạch nạp gas từ ví chủ cho các ví dưới ngưỡng, nạp lên mức đích của chain
+    pub async fn plan_top_up(&self, from: Address, wallets: &[Address]) -> Result<TreasuryPlan> {
+        let chain_id = self.adapter.get_chain_id();
+        let floor = self.config.gas_floor
+            .ok_or_else(|| anyhow!("No gas floor configured for chain {}", chain_id))?;
+        let target = self.config.gas_target.unwrap_or(floor).max(floor);
+
+        let mut transfers = Vec::new();
+        let mut skipped = Vec::new();
+        for wallet in wallets.iter().copied().filter(|wallet| *wallet != from) {
+            let balance = self.native_balance(wallet).await?;
+            if balance >= floor {
+                skipped.push(SkippedWallet { wallet, reason: "balance is at or above gas floor".to_string() });
+            } else {
+                transfers.push(Transfer { from, to: wallet, amount: target - balance });
+            }
+        }
+
+        let plan = self.build_plan(TreasuryOperation::TopUp, Asset::Native, transfers, skipped).await?;
+        self.ensure_source_funds(from, &plan).await?;
+        Ok(plan)
+    }
+
+    /// Gửi các giao dịch của kế hoạch; lần chuyển lỗi được ghi lại và không chặn các lần sau
+    pub async fn execute(&self, plan: &TreasuryPlan) -> Result<TreasuryReport> {
+        let chain_id = self.adapter.get_chain_id();
+        if plan.chain_id != chain_id {
+            bail!("Plan targets chain {} but treasury is on chain {}", plan.chain_id, chain_id);
+        }
+
+        let mut report = TreasuryReport::default();
+        if let TransferRoute::Disperse(contract) = plan.route {
+            match self.send_disperse(contract, plan).await {
+                Ok(hashes) => {
+                    report.tx_hashes.extend(hashes);
+                    report.completed.extend(plan.transfers.iter().cloned());
+                }
+                Err(e) => {
+                    warn!("Disperse of {} transfers on chain {} failed: {}", plan.transfers.len(), chain_id, e);
+                    report.failed.extend(plan.transfers.iter().cloned()
+                        .map(|transfer| FailedTransfer { transfer, error: e.to_string() }));
+                }
+            }
+            return Ok(report);
+        }
+
+        for transfer in &plan.transfers {
+            match self.send_transfer(plan.asset, transfer, plan.gas_price).await {
+                Ok(hash) => {
+                    report.tx_hashes.push(hash);
+                    report.completed.push(transfer.clone());
+                }
+                Err(e) => {
+                    warn!("Transfer of {} from {:?} to {:?} failed: {}", transfer.amount, transfer.from, transfer.to, e);
+                    report.failed.push(FailedTransfer { transfer: transfer.clone(), error: e.to_string() });
+                }
+            }
+        }
+
+        info!(
+            "{:?} on chain {}: {} transfers completed, {} failed",
+            plan.operation, chain_id, report.completed.len(), report.failed.len()
+        );
+        Ok(report)
+    }
+
+    async fn build_plan(
+        &self,
+        operation: TreasuryOperation,
+        asset: Asset,
+        transfers: Vec<Transfer>,
+        skipped: Vec<SkippedWallet>,
+    ) -> Result<TreasuryPlan> {
+        let gas_price = self.gas_price().await?;
+        let total = transfers.iter().fold(U256::zero(), |sum, transfer| sum + transfer.amount);
+
+        let (route, gas) = match self.config.disperse_contract {
+            Some(contract) if transfers.len() > 1 => {
+                let per_recipient = match asset {
+                    Asset::Native => DISPERSE_NATIVE_GAS_PER_RECIPIENT,
+                    Asset::Token(_) => DISPERSE_TOKEN_GAS_PER_RECIPIENT + APPROVE_GAS / transfers.len() as u64,
+                };
+                (TransferRoute::Disperse(contract), DISPERSE_BASE_GAS + per_recipient * transfers.len() as u64)
+            }
+            _ => (TransferRoute::Direct, transfer_gas(asset) * transfers.len() as u64),
+        };
+
+        Ok(TreasuryPlan {
+            chain_id: self.adapter.get_chain_id(),
+            operation,
+            asset,
+            route,
+            transfers,
+            skipped,
+            total,
+            gas_price,
+            estimated_fee: gas_price * gas,
+        })
+    }
+
+    /// Ví nguồn phải đủ số lượng cần chuyển và phí gas
+    async fn ensure_source_funds(&self, from: Address, plan: &TreasuryPlan) -> Result<()> {
+        let native = self.native_balance(from).await?;
+        let native_needed = match plan.asset {
+            Asset::Native => plan.total + plan.estimated_fee,
+            Asset::Token(token) => {
+                let balance = self.token_balance(token, from).await?;
+                if balance < plan.total {
+                    bail!("Insufficient token balance in {:?}: have {}, need {}", from, balance, plan.total);
+                }
+                plan.estimated_fee
+            }
+        };
+        if native < native_needed {
+            bail!("Insufficient native balance in {:?}: have {}, need {}", from, native, native_needed);
+        }
+        Ok(())
+    }
+
+    async fn send_transfer(&self, asset: Asset, transfer: &Transfer, gas_price: U256) -> Result<H256> {
+        match asset {
+            Asset::Native => {
+                let tx = TransactionRequest::new()
+                    .from(transfer.from)
+                    .to(transfer.to)
+                    .value(transfer.amount)
+                    .gas(NATIVE_TRANSFER_GAS)
+                    .gas_price(gas_price);
+                self.send(transfer.from, tx).await
+            }
+            Asset::Token(token) => {
+                let data = encode_call("transfer(address,uint256)", &[Token::Address(transfer.to), Token::Uint(transfer.amount)]);
+                let tx = TransactionRequest::new().from(transfer.from).to(token).data(data).gas_price(gas_price);
+                self.send(transfer.from, tx).await
+            }
+        }
+    }
+
+    /// Gộp mọi lần chuyển của kế hoạch vào một lời gọi disperse; token cần approve cho hợp đồng trước
+    async fn send_disperse(&self, contract: Address, plan: &TreasuryPlan) -> Result<Vec<H256>> {
+        let from = plan.transfers.first().map(|transfer| transfer.from)
+            .ok_or_else(|| anyhow!("Plan has no transfers"))?;
+        if plan.transfers.iter().any(|transfer| transfer.from != from) {
+            bail!("Disperse requires a single source wallet");
+        }
+
+        let recipients = Token::Array(plan.transfers.iter().map(|transfer| Token::Address(transfer.to)).collect());
+        let values = Token::Array(plan.transfers.iter().map(|transfer| Token::Uint(transfer.amount)).collect());
+        let mut hashes = Vec::new();
+
+        let tx = match plan.asset {
+            Asset::Native => TransactionRequest::new()
+                .from(from)
+                .to(contract)
+                .value(plan.total)
+                .data(encode_call("disperseEther(address[],uint256[])", &[recipients, values])),
+            Asset::Token(token) => {
+                let allowance = self.adapter.get_token_allowance(token, from, contract).await?;
+                if allowance < plan.total {
+                    let data = encode_call("approve(address,uint256)", &[Token::Address(contract), Token::Uint(plan.total)]);
+                    let approve = TransactionRequest::new().from(from).to(token).data(data).gas_price(plan.gas_price);
+                    hashes.push(self.send(from, approve).await?);
+                }
+                TransactionRequest::new()
+                    .from(from)
+                    .to(contract)
+                    .data(encode_call("disperseToken(address,address[],uint256[])", &[Token::Address(token), recipients, values]))
+            }
+        };
+
+        hashes.push(self.send(from, tx.gas_price(plan.gas_price)).await?);
+        Ok(hashes)
+    }
+
+    /// Gửi qua WalletManager (áp dụng chính sách ví) và chờ xác nhận
+    async fn send(&self, from: Address, tx: TransactionRequest) -> Result<H256> {
+        let tx: TypedTransaction = tx.into();
+        let tx_hash = self.wallets.send_transaction(self.provider.clone(), from, &tx).await?;
+        let receipt = self.adapter
+            .wait_for_transaction_receipt(tx_hash, self.config.confirmations, self.config.receipt_timeout)
+            .await?;
+        if receipt.status != Some(U64::one()) {
+            bail!("Treasury transaction {:?} from {:?} reverted", tx_hash, from);
+        }
+        Ok(tx_hash)
+    }
+
+    async fn gas_price(&self) -> Result<U256> {
+        Ok(self.adapter.get_gas_price().await?)
+    }
+
+    async fn native_balance(&self, address: Address) -> Result<U256> {
+        Ok(self.adapter.get_eth_balance(address, None).await?)
+    }
+
+    async fn token_balance(&self, token: Address, address: Address) -> Result<U256> {
+        Ok(self.adapter.get_token_balance(token, address, None).await?)
+    }
+}
+
+/// Gas của một lần chuyển trực tiếp
+fn transfer_gas(asset: Asset) -> u64 {
+    match asset {
+        Asset::Native => NATIVE_TRANSFER_GAS,
+        Asset::Token(_) => TOKEN_TRANSFER_GAS,
+    }
+}
+
+/// Serde cho U256 dạng chuỗi thập phân
+pub(crate) mod dec_u256 {
+    use ethers::types::U256;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        U256::from_dec_str(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_adapters::tests::mock_adapter::MockChainAdapter;
+    use crate::chain_adapters::tests::mock_chain::MockChain;
+    use crate::chain_adapters::tests::mock_contracts::TokenSpec;
+    use crate::chain_adapters::tests::wallet_fixture::TestWallets;
+
+    struct Fixture {
+        chain: MockChain,
+        master: Address,
+        wallets: TestWallets,
+    }
+
+    impl Fixture {
+        fn treasury(&self, config: TreasuryConfig) -> Treasury<MockChainAdapter> {
+            let provider = Provider::<Http>::try_from(self.chain.http_url()).unwrap();
+            Treasury::new(
+                Arc::new(MockChainAdapter::new(&self.chain)),
+                Arc::new(provider),
+                self.wallets.manager.clone(),
+                config,
+            )
+        }
+
+        /// Import ví dev thứ `index` vào WalletManager
+        fn import_dev_wallet(&self, index: usize) -> Address {
+            self.wallets.import_dev_wallet(&self.chain, index)
+        }
+    }
+
+    async fn fixture() -> Fixture {
+        let chain = MockChain::spawn(1).await.unwrap();
+        let wallets = TestWallets::new(1);
+        let mut fixture = Fixture { chain, master: Address::zero(), wallets };
+        fixture.master = fixture.import_dev_wallet(1);
+        fixture
+    }
+
+    #[test]
+    fn test_split_amount_equal_and_weighted() {
+        let equal = split_amount(U256::from(100u64), 3, &Distribution::Equal).unwrap();
+        assert_eq!(equal, vec![U256::from(34u64), U256::from(33u64), U256::from(33u64)]);
+
+        let weighted = split_amount(U256::from(1_000u64), 3, &Distribution::Weighted(vec![1, 2, 7])).unwrap();
+        assert_eq!(weighted, vec![U256::from(100u64), U256::from(200u64), U256::from(700u64)]);
+
+        assert!(split_amount(U256::from(1u64), 2, &Distribution::Weighted(vec![1])).is_err());
+        assert!(split_amount(U256::from(1u64), 0, &Distribution::Equal).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_distribute_native_then_top_up_below_floor() {
+        let fixture = fixture().await;
+        let recipients: Vec<Address> = (1..=3u8).map(Address::repeat_byte).collect();
+        let floor = U256::exp10(16);
+        let target = U256::exp10(17);
+        let treasury = fixture.treasury(TreasuryConfig {
+            gas_floor: Some(floor),
+            gas_target: Some(target),
+            ..Default::default()
+        });
+
+        // Dry-run: chỉ lập kế hoạch, không gửi gì
+        let total = U256::exp10(16) * 3u64;
+        let request: TreasuryRequest = serde_json::from_value(serde_json::json!({
+            "operation": "distribute",
+            "from": fixture.master,
+            "recipients": recipients,
+            "asset": "native",
+            "total": total.to_string(),
+            "distribution": "equal",
+        })).unwrap();
+        let plan = treasury.plan(&request).await.unwrap();
+        assert_eq!(plan.route, TransferRoute::Direct);
+        assert_eq!(plan.transfers.len(), 3);
+        assert_eq!(plan.total, total);
+        assert!(recipients.iter().all(|recipient| fixture.chain.balance(*recipient).is_zero()));
+
+        let report = treasury.execute(&plan).await.unwrap();
+        assert_eq!(report.completed.len(), 3);
+        assert!(report.failed.is_empty());
+        assert!(recipients.iter().all(|recipient| fixture.chain.balance(*recipient) == U256::exp10(16)));
+
+        // Ví đã ở đúng ngưỡng thì bỏ qua, ví rỗng được nạp lên mức đích
+        let empty = Address::repeat_byte(0x04);
+        let mut wallets = recipients.clone();
+        wallets.push(empty);
+        let plan = treasury.plan_top_up(fixture.master, &wallets).await.unwrap();
+        assert_eq!(plan.transfers, vec![Transfer { from: fixture.master, to: empty, amount: target }]);
+        assert_eq!(plan.skipped.len(), 3);
+
+        treasury.execute(&plan).await.unwrap();
+        assert_eq!(fixture.chain.balance(empty), target);
+    }
+
+    #[tokio::test]
+    async fn test_weighted_token_distribution_and_sweep_skips_dust() {
+        let fixture = fixture().await;
+        let token = fixture.chain.deploy_erc20(&TokenSpec::new("Mock Token", "MOCK", 18)).unwrap();
+        fixture.chain.mint(token, fixture.master, U256::from(10_000u64)).unwrap();
+
+        // Ví con là ví dev có sẵn gas để tự gửi khi gom
+        let children: Vec<Address> = (2..=4).map(|index| fixture.import_dev_wallet(index)).collect();
+        let treasury = fixture.treasury(TreasuryConfig::default());
+
+        let plan = treasury.plan_distribution(
+            fixture.master,
+            &children,
+            Asset::Token(token),
+            U256::from(1_000u64),
+            &Distribution::Weighted(vec![6, 3, 1]),
+        ).await.unwrap();
+        treasury.execute(&plan).await.unwrap();
+        let balances: Vec<U256> = children.iter().map(|child| fixture.chain.token_balance(token, *child).unwrap()).collect();
+        assert_eq!(balances, vec![U256::from(600u64), U256::from(300u64), U256::from(100u64)]);
+
+        // Không đủ số dư thì từ chối lập kế hoạch
+        assert!(treasury.plan_distribution(
+            fixture.master, &children, Asset::Token(token), U256::from(1_000_000u64), &Distribution::Equal,
+        ).await.is_err());
+
+        let plan = treasury.plan_sweep(&children, fixture.master, Asset::Token(token), U256::from(100u64)).await.unwrap();
+        assert_eq!(plan.transfers.len(), 2);
+        assert_eq!(plan.skipped.iter().map(|skipped| skipped.wallet).collect::<Vec<_>>(), vec![children[2]]);
+
+        let report = treasury.execute(&plan).await.unwrap();
+        assert_eq!(report.tx_hashes.len(), 2);
+        assert_eq!(fixture.chain.token_balance(token, fixture.master).unwrap(), U256::from(9_900u64));
+        assert!(fixture.chain.token_balance(token, children[0]).unwrap().is_zero());
+        assert_eq!(fixture.chain.token_balance(token, children[2]).unwrap(), U256::from(100u64));
+    }
+}
@@ -1,9 +1,8 @@
 // External imports
 use ethers::{
-    providers::Middleware,
-    signers::LocalWallet,
-    types::{Address, U256, H256},
-    contract::ERC20,
+    signers::Signer,
+    types::{Address, U256, H256, Bytes, TransactionRequest, TransactionReceipt},
+    types::transaction::eip2718::TypedTransaction,
 };
 
 // Standard library imports
@@ -21,21 +20,57 @@ use crate::{
         chain_registry::ChainRegistry,
         chain_adapter_impl::EVMChainAdapter,
     },
-    types::{WalletBalance, TokenBalance},
     abi_utils,
 };
 
 // Third party imports
 use anyhow::{Result, anyhow};
-use tracing::{info, warn, error};
+use tracing::{info, warn, error, debug};
 use tokio::sync::RwLock;
-use wallet::{
-    secure_storage::{SecureWalletStorage, WalletInfo},
-    WalletManager,
-};
+use diamond_wallet::{SecureWalletStorage, WalletInfo, WalletSigner, WalletManager};
+
+/// Hệ số dự phòng gas khi ước tính (120%)
+const GAS_LIMIT_BUFFER_PERCENT: u64 = 120;
+
+/// Parse địa chỉ từ chuỗi, trả về lỗi ChainError
+fn parse_address(address: &str) -> Result<Address, ChainError> {
+    Address::from_str(address)
+        .map_err(|e| ChainError::InvalidAddress(format!("{}: {}", address, e)))
+}
+
+/// Mã hóa lời gọi hàm ERC20 theo ABI chuẩn
+fn encode_erc20_call(function_name: &str, params: &[ethers::abi::Token]) -> Result<Vec<u8>, ChainError> {
+    let abi: ethers::abi::Abi = serde_json::from_str(abi_utils::get_erc20_abi())
+        .map_err(|e| ChainError::ContractError(format!("Failed to parse ERC20 ABI: {}", e)))?;
+    let function = abi.function(function_name)
+        .map_err(|e| ChainError::ContractError(e.to_string()))?;
+    function.encode_input(params)
+        .map_err(|e| ChainError::ContractError(e.to_string()))
+}
+
+/// Ước tính gas limit cho giao dịch, cộng thêm phần dự phòng
+async fn estimate_gas_with_buffer(adapter: &dyn ChainAdapter, tx: &TransactionRequest) -> Result<U256, ChainError> {
+    let gas = adapter.estimate_gas(tx).await?;
+    Ok(gas.saturating_mul(U256::from(GAS_LIMIT_BUFFER_PERCENT)) / U256::from(100))
+}
+
+/// Ký giao dịch bằng signer của ví và gửi dạng raw qua adapter
+async fn sign_and_send(
+    adapter: &dyn ChainAdapter,
+    signer: &WalletSigner,
+    tx: TransactionRequest,
+) -> Result<H256, ChainError> {
+    let tx: TypedTransaction = tx.into();
+    let signature = signer.sign_transaction(&tx).await
+        .map_err(|e| ChainError::TransactionError(format!("Failed to sign: {}", e)))?;
+    let signed_tx = tx.rlp_signed(&signature);
+
+    let tx_hash = adapter.send_raw_transaction(signed_tx).await?;
+    Ok(tx_hash)
+}
 
 /// Cấu trúc dữ liệu để quản lý giao dịch
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct TransactionManager {
     /// Registry chứa các chain adapter
     registry: Arc<RwLock<ChainRegistry>>,
@@ -54,23 +89,36 @@ impl TransactionManager {
             wallet_storage,
         }
     }
-    
+
+    /// Lấy adapter của chain từ registry
+    async fn adapter(&self, chain_id: u64) -> Result<Arc<dyn ChainAdapter>, ChainError> {
+        self.registry.read().await
+            .get_adapter(chain_id)
+            .map_err(|_| ChainError::UnsupportedChain(chain_id))
+    }
+
     /// Lấy thông tin ví từ secure storage
     pub async fn get_wallet_info(&self, address: &str) -> Result<WalletInfo> {
-        // Kiểm tra định dạng địa chỉ
-        let validated_address = SecureWalletStorage::validate_wallet_address(address)
-            .map(|_| address.to_string())?;
-            
+        let parsed = Address::from_str(address)
+            .map_err(|e| anyhow!("Invalid wallet address {}: {}", address, e))?;
+
         // Lấy thông tin ví từ storage
         let storage = self.wallet_storage.read().await;
-        let wallet_info = storage.get_wallet(&validated_address)
-            .ok_or_else(|| anyhow!("Wallet not found: {}", validated_address))?
-            .clone();
-            
-        Ok(wallet_info)
+        storage.wallet_info(&format!("{:?}", parsed))
+            .cloned()
+            .ok_or_else(|| anyhow!("Wallet not found: {}", address))
     }
-    
+
+    /// Lấy signer của ví từ secure storage (storage phải đã được mở khóa)
+    async fn signer(&self, address: Address, chain_id: u64) -> Result<WalletSigner, ChainError> {
+        let storage = self.wallet_storage.read().await;
+        let wallet = storage.load_private_key(&format!("{:?}", address))
+            .map_err(|_| ChainError::WalletNotConfigured)?;
+        Ok(WalletSigner::from(wallet).with_chain_id(chain_id))
+    }
+
     /// Tạo và gửi giao dịch từ ví
+    #[allow(clippy::too_many_arguments)]
     pub async fn send_transaction(
         &self,
         chain_id: u64,
@@ -83,68 +131,52 @@ impl TransactionManager {
         nonce: Option<U256>,
     ) -> Result<H256, ChainError> {
         // Lấy adapter cho chain
-        let registry = self.registry.read().await;
-        let adapter = registry.get_adapter(chain_id)
-            .ok_or(ChainError::UnsupportedChain(chain_id))?;
-            
+        let adapter = self.adapter(chain_id).await?;
+
         // Kiểm tra định dạng địa chỉ ví và địa chỉ nhận
-        let wallet_address = SecureWalletStorage::validate_wallet_address(wallet_address)
-            .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-            
-        let to_address = SecureWalletStorage::validate_wallet_address(to)
-            .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-            
-        // Chuyển đổi địa chỉ sang ethers Address
-        let to_address = Address::from_str(to)
-            .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-            
+        let from = parse_address(wallet_address)?;
+        let to_address = parse_address(to)?;
+
         // Lấy nonce nếu không có
         let nonce = match nonce {
             Some(n) => n,
-            None => {
-                let address = Address::from_str(wallet_address)
-                    .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-                adapter.get_transaction_count(address, None).await?
-            }
+            None => adapter.get_transaction_count(from, None).await?,
         };
-        
+
         // Lấy gas price nếu không có
         let gas_price = match gas_price {
             Some(gp) => gp,
             None => adapter.get_gas_price().await?,
         };
-        
+
         // Tạo transaction request
-        let tx = TransactionRequest {
-            to,
-            value,
-            data,
-            gas_limit,
-            gas_price,
-            nonce,
-            chain_id: Some(chain_id),
-        };
-        
+        let mut tx = TransactionRequest::new()
+            .from(from)
+            .to(to_address)
+            .value(value.unwrap_or_default())
+            .gas_price(gas_price)
+            .nonce(nonce)
+            .chain_id(chain_id);
+        if let Some(data) = data {
+            tx = tx.data(data);
+        }
+
         // Ước lượng gas nếu không có
         let gas_limit = match gas_limit {
             Some(gl) => gl,
-            None => adapter.estimate_gas(&tx).await?,
+            None => estimate_gas_with_buffer(adapter.as_ref(), &tx).await?,
         };
-        
-        // Thêm gas limit vào transaction
         let tx = tx.gas(gas_limit);
-        
-        // Gửi transaction
+
+        // Ký bằng signer của ví và gửi
         debug!("Sending transaction on chain {}: {:?}", chain_id, tx);
-        let pending_tx = adapter.send_transaction(&tx).await?;
-        
-        // Lấy tx hash
-        let tx_hash = pending_tx.tx_hash();
+        let signer = self.signer(from, chain_id).await?;
+        let tx_hash = sign_and_send(adapter.as_ref(), &signer, tx).await?;
         info!("Transaction sent: {:?}", tx_hash);
-        
+
         Ok(tx_hash)
     }
-    
+
     /// Phê duyệt token cho một spender
     pub async fn approve_token(
         &self,
@@ -154,32 +186,15 @@ impl TransactionManager {
         spender_address: &str,
         amount: U256,
     ) -> Result<H256, ChainError> {
-        // Lấy adapter cho chain
-        let registry = self.registry.read().await;
-        let adapter = registry.get_adapter(chain_id)
-            .ok_or(ChainError::UnsupportedChain(chain_id))?;
-            
-        // Kiểm tra địa chỉ hợp lệ
-        let token = Address::from_str(token_address)
-            .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-            
-        let spender = Address::from_str(spender_address)
-            .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-            
-        // Lấy ABI cho ERC20
-        let abi: ethers::abi::Abi = serde_json::from_str(abi_utils::get_erc20_abi())
-            .context("Failed to parse ERC20 ABI")?;
-            
-        // Tạo hàm approve
-        let function = abi.function("approve")
-            .map_err(|e| ChainError::ContractError(e.to_string()))?;
-            
-        // Mã hóa tham số
-        let encoded = function.encode_input(&[
+        let spender = parse_address(spender_address)?;
+        parse_address(token_address)?;
+
+        // Mã hóa tham số approve
+        let encoded = encode_erc20_call("approve", &[
             ethers::abi::Token::Address(spender),
             ethers::abi::Token::Uint(amount),
-        ]).map_err(|e| ChainError::ContractError(e.to_string()))?;
-        
+        ])?;
+
         // Gửi transaction approve
         self.send_transaction(
             chain_id,
@@ -192,7 +207,7 @@ impl TransactionManager {
             None,                  // Tự động lấy nonce
         ).await
     }
-    
+
     /// Gửi token đến một địa chỉ
     pub async fn transfer_token(
         &self,
@@ -202,32 +217,15 @@ impl TransactionManager {
         to_address: &str,
         amount: U256,
     ) -> Result<H256, ChainError> {
-        // Lấy adapter cho chain
-        let registry = self.registry.read().await;
-        let adapter = registry.get_adapter(chain_id)
-            .ok_or(ChainError::UnsupportedChain(chain_id))?;
-            
-        // Kiểm tra địa chỉ hợp lệ
-        let token = Address::from_str(token_address)
-            .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-            
-        let to = Address::from_str(to_address)
-            .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-            
-        // Lấy ABI cho ERC20
-        let abi: ethers::abi::Abi = serde_json::from_str(abi_utils::get_erc20_abi())
-            .context("Failed to parse ERC20 ABI")?;
-            
-        // Tạo hàm transfer
-        let function = abi.function("transfer")
-            .map_err(|e| ChainError::ContractError(e.to_string()))?;
-            
-        // Mã hóa tham số
-        let encoded = function.encode_input(&[
+        let to = parse_address(to_address)?;
+        parse_address(token_address)?;
+
+        // Mã hóa tham số transfer
+        let encoded = encode_erc20_call("transfer", &[
             ethers::abi::Token::Address(to),
             ethers::abi::Token::Uint(amount),
-        ]).map_err(|e| ChainError::ContractError(e.to_string()))?;
-        
+        ])?;
+
         // Gửi transaction transfer
         self.send_transaction(
             chain_id,
@@ -240,29 +238,7 @@ impl TransactionManager {
             None,                  // Tự động lấy nonce
         ).await
     }
-    
-    /// Lấy danh sách giao dịch gần đây của ví
-    pub async fn get_recent_transactions(
-        &self,
-        chain_id: u64,
-        wallet_address: &str,
-        limit: usize,
-    ) -> Result<Vec<TransactionReceipt>, ChainError> {
-        // Lấy adapter cho chain
-        let registry = self.registry.read().await;
-        let adapter = registry.get_adapter(chain_id)
-            .ok_or(ChainError::UnsupportedChain(chain_id))?;
-            
-        // Chuyển đổi địa chỉ
-        let address = Address::from_str(wallet_address)
-            .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-            
-        // TODO: Triển khai lấy giao dịch gần đây (cần thực hiện tổng hợp các giao dịch từ block gần đây)
-        // Đây chỉ là stub, cần triển khai đầy đủ
-        
-        Err(ChainError::from_anyhow(anyhow!("get_recent_transactions not implemented yet".to_string())))
-    }
-    
+
     /// Kiểm tra xem giao dịch đã hoàn thành chưa
     pub async fn wait_for_transaction(
         &self,
@@ -271,17 +247,15 @@ impl TransactionManager {
         confirmations: usize,
         timeout_secs: u64,
     ) -> Result<TransactionReceipt, ChainError> {
-        // Lấy adapter cho chain
-        let registry = self.registry.read().await;
-        let adapter = registry.get_adapter(chain_id)
-            .ok_or(ChainError::UnsupportedChain(chain_id))?;
-            
+        let adapter = self.adapter(chain_id).await?;
+
         // Đợi giao dịch hoàn thành
-        let timeout = std::time::Duration::from_secs(timeout_secs);
+        let timeout = Duration::from_secs(timeout_secs);
         adapter.wait_for_transaction_receipt(tx_hash, confirmations, timeout).await
     }
-    
+
     /// Thực hiện swap token thông qua router
+    #[allow(clippy::too_many_arguments)]
     pub async fn swap_tokens(
         &self,
         chain_id: u64,
@@ -293,42 +267,24 @@ impl TransactionManager {
         router_address: &str,
         deadline: u64,
     ) -> Result<H256, ChainError> {
-        // Lấy adapter cho chain
-        let registry = self.registry.read().await;
-        let adapter = registry.get_adapter(chain_id)
-            .ok_or(ChainError::UnsupportedChain(chain_id))?;
-            
         // Kiểm tra địa chỉ hợp lệ
-        let token_in_addr = Address::from_str(token_in)
-            .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-            
-        let token_out_addr = Address::from_str(token_out)
-            .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-            
-        let router = Address::from_str(router_address)
-            .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-            
-        // Lấy địa chỉ ví
-        let wallet = Address::from_str(wallet_address)
-            .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-            
+        let token_in_addr = parse_address(token_in)?;
+        let token_out_addr = parse_address(token_out)?;
+        parse_address(router_address)?;
+        let wallet = parse_address(wallet_address)?;
+
         // Lấy ABI cho router
         let abi: ethers::abi::Abi = serde_json::from_str(abi_utils::get_router_abi())
-            .context("Failed to parse Router ABI")?;
-            
+            .map_err(|e| ChainError::ContractError(format!("Failed to parse Router ABI: {}", e)))?;
+
         // Chuẩn bị tham số cho swapExactTokensForTokens
         let path = vec![token_in_addr, token_out_addr];
-        let deadline_timestamp = U256::from(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() + deadline
-        );
-        
+        let deadline_timestamp = U256::from(crate::utils::safe_now() + deadline);
+
         // Tạo hàm swapExactTokensForTokens
         let function = abi.function("swapExactTokensForTokens")
             .map_err(|e| ChainError::ContractError(e.to_string()))?;
-            
+
         // Mã hóa tham số
         let encoded = function.encode_input(&[
             ethers::abi::Token::Uint(amount_in),
@@ -341,7 +297,7 @@ impl TransactionManager {
             ethers::abi::Token::Address(wallet),
             ethers::abi::Token::Uint(deadline_timestamp),
         ]).map_err(|e| ChainError::ContractError(e.to_string()))?;
-        
+
         // Gửi transaction swap
         self.send_transaction(
             chain_id,
@@ -366,18 +322,17 @@ pub async fn create_transaction_manager(
 
 /// Hàm trợ giúp để lấy số dư ví trên nhiều chain
 pub async fn get_wallet_balances(
-    registry: Arc<RwLock<ChainRegistry>>, 
-    wallet_address: &str, 
+    registry: Arc<RwLock<ChainRegistry>>,
+    wallet_address: &str,
     chain_ids: &[u64]
 ) -> Result<Vec<(u64, U256)>, ChainError> {
     // Kiểm tra địa chỉ ví
-    let address = Address::from_str(wallet_address)
-        .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-        
+    let address = parse_address(wallet_address)?;
+
     let registry_guard = registry.read().await;
-    
+
     let mut results = Vec::new();
-    
+
     // Lấy số dư trên từng chain
     for &chain_id in chain_ids {
         let adapter = match registry_guard.get_adapter(chain_id) {
@@ -387,7 +342,7 @@ pub async fn get_wallet_balances(
                 continue;
             }
         };
-        
+
         // Lấy số dư ETH
         match adapter.get_eth_balance(address, None).await {
             Ok(balance) => {
@@ -398,24 +353,23 @@ pub async fn get_wallet_balances(
             }
         }
     }
-    
+
     Ok(results)
 }
 
 /// Hàm trợ giúp để lấy số dư token trên nhiều chain
 pub async fn get_token_balances(
-    registry: Arc<RwLock<ChainRegistry>>, 
-    wallet_address: &str, 
+    registry: Arc<RwLock<ChainRegistry>>,
+    wallet_address: &str,
     token_addresses: &[(u64, &str)]
 ) -> Result<Vec<(u64, Address, U256)>, ChainError> {
     // Kiểm tra địa chỉ ví
-    let address = Address::from_str(wallet_address)
-        .map_err(|e| ChainError::InvalidAddress(e.to_string()))?;
-        
+    let address = parse_address(wallet_address)?;
+
     let registry_guard = registry.read().await;
-    
+
     let mut results = Vec::new();
-    
+
     // Lấy số dư trên từng chain và token
     for &(chain_id, token_addr) in token_addresses {
         let adapter = match registry_guard.get_adapter(chain_id) {
@@ -425,7 +379,7 @@ pub async fn get_token_balances(
                 continue;
             }
         };
-        
+
         // Chuyển địa chỉ token sang Address
         let token = match Address::from_str(token_addr) {
             Ok(addr) => addr,
@@ -434,19 +388,19 @@ pub async fn get_token_balances(
                 continue;
             }
         };
-        
+
         // Lấy số dư token
         match adapter.get_token_balance(token, address, None).await {
             Ok(balance) => {
                 results.push((chain_id, token, balance));
             }
             Err(e) => {
-                error!("Failed to get token balance for chain {} token {}: {:?}", 
+                error!("Failed to get token balance for chain {} token {}: {:?}",
                        chain_id, token_addr, e);
             }
         }
     }
-    
+
     Ok(results)
 }
 
@@ -485,13 +439,13 @@ impl WalletIntegration {
             chain_adapters: Arc::new(RwLock::new(HashMap::new())),
         }
     }
-    
+
     /// Thêm adapter cho một chain
     pub async fn add_adapter(&self, chain_id: u64, adapter: Arc<EVMChainAdapter>) {
         let mut adapters = self.chain_adapters.write().await;
         adapters.insert(chain_id, adapter);
     }
-    
+
     /// Lấy adapter cho một chain
     pub async fn get_adapter(&self, chain_id: u64) -> Result<Arc<EVMChainAdapter>, ChainError> {
         let adapters = self.chain_adapters.read().await;
@@ -499,8 +453,10 @@ impl WalletIntegration {
             .cloned()
             .ok_or(ChainError::UnsupportedChain(chain_id))
     }
-    
-    /// Gửi giao dịch trên một chain
+
+    /// Gửi giao dịch trên một chain.
+    /// Giao dịch được ký qua `WalletManager` (signer cục bộ hoặc từ xa, kèm kiểm tra chính sách chi tiêu)
+    #[allow(clippy::too_many_arguments)]
     pub async fn send_transaction(
         &self,
         wallet_address: Address,
@@ -513,106 +469,59 @@ impl WalletIntegration {
     ) -> Result<TransactionResult, ChainError> {
         // Lấy adapter cho chain
         let adapter = self.get_adapter(chain_id).await?;
-        
-        // Lấy wallet
-        let wallet = match self.wallet_manager.get_wallet(wallet_address) {
-            Ok(wallet) => wallet.with_chain_id(chain_id),
-            Err(_) => return Err(ChainError::WalletNotConfigured),
-        };
-        
+
         // Tạo request giao dịch
-        let mut tx = TransactionRequest::new();
-        
+        let mut tx = TransactionRequest::new()
+            .from(wallet_address)
+            .value(value)
+            .chain_id(chain_id);
+
         if let Some(to_addr) = to {
             tx = tx.to(to_addr);
         }
-        
-        tx = tx.from(wallet_address)
-            .value(value);
-        
+
         if let Some(data_bytes) = data {
             tx = tx.data(data_bytes);
         }
-        
+
         // Thiết lập gas price nếu được cung cấp, nếu không thì lấy từ mạng
-        let actual_gas_price = if let Some(gp) = gas_price {
-            gp
-        } else {
-            adapter.get_gas_price().await?
+        let actual_gas_price = match gas_price {
+            Some(gp) => gp,
+            None => adapter.get_gas_price().await?,
         };
         tx = tx.gas_price(actual_gas_price);
-        
+
         // Thiết lập gas limit nếu được cung cấp, nếu không thì ước tính
-        let actual_gas_limit = if let Some(gl) = gas_limit {
-            gl
-        } else {
-            // Nếu không có gas_limit, ước tính với một giá trị mặc định
-            let mut typed_tx = TypedTransaction::Eip1559(Eip1559TransactionRequest::new());
-            typed_tx.set_from(wallet_address);
-            typed_tx.set_to(Address::from_str(to.unwrap().to_string().as_str()).unwrap());
-            
-            if let Some(val) = value {
-                typed_tx.set_value(val);
-            }
-            
-            if let Some(data_bytes) = &data {
-                typed_tx.set_data(data_bytes.clone());
-            }
-            
-            let gas = match adapter.estimate_gas(&typed_tx).await {
-                Ok(gas) => {
-                    // Kiểm tra gas limit
-                    if gas > config.gas_limit.into() {
-                        warn!("Estimated gas {} exceeds max gas limit {}", gas, config.gas_limit);
-                        return Err(ChainError::GasLimitExceeded(gas.as_u64()));
-                    }
-                    
-                    // Tăng thêm 20% để đảm bảo đủ gas
-                    let adjusted = (gas.as_u64() * 12) / 10;
-                    U256::from(adjusted)
-                },
-                Err(e) => {
-                    warn!("Failed to estimate gas: {}", e);
-                    U256::from(config.gas_limit)
-                }
-            };
-            
-            gas
+        let actual_gas_limit = match gas_limit {
+            Some(gl) => gl,
+            None => estimate_gas_with_buffer(adapter.as_ref(), &tx).await?,
         };
         tx = tx.gas(actual_gas_limit);
-        
+
         // Lấy nonce hiện tại
         let nonce = adapter.get_transaction_count(wallet_address, None).await?;
         tx = tx.nonce(nonce);
-        
-        // Chuyển đổi thành TypedTransaction
+
+        // Ký giao dịch qua WalletManager (WalletSigner + chính sách chi tiêu)
         let tx: TypedTransaction = tx.into();
-        
-        // Ký giao dịch
-        let signature = wallet.sign_transaction(&tx).await
+        let signed_tx = self.wallet_manager.sign_transaction(wallet_address, &tx, chain_id).await
             .map_err(|e| ChainError::TransactionError(format!("Failed to sign: {}", e)))?;
-        
-        // Tạo giao dịch đã ký
-        let signed_tx = tx.rlp_signed(&signature);
-        
+
         // Gửi giao dịch
-        let pending_tx = adapter.send_raw_transaction(signed_tx).await
+        let tx_hash = adapter.send_raw_transaction(signed_tx).await
             .map_err(|e| ChainError::TransactionError(format!("Failed to send: {}", e)))?;
-        
-        // Lấy hash giao dịch
-        let tx_hash = pending_tx.tx_hash();
-        
+
         Ok(TransactionResult {
             tx_hash,
             from: wallet_address,
-            to: to,
+            to,
             value,
             gas: actual_gas_limit,
             gas_price: actual_gas_price,
             chain_id,
         })
     }
-    
+
     /// Phê duyệt token ERC20
     pub async fn approve_token(
         &self,
@@ -622,35 +531,34 @@ impl WalletIntegration {
         spender_address: Address,
         amount: U256,
     ) -> Result<TransactionResult, ChainError> {
-        // Tạo calldata cho approve(address,uint256)
-        let token = ERC20::new(token_address, self.get_adapter(chain_id).await?.get_provider());
-        
+        let adapter = self.get_adapter(chain_id).await?;
+
         // Kiểm tra allowance hiện tại
-        let current_allowance = token.allowance(wallet_address, spender_address).call().await
-            .map_err(|e| ChainError::ContractError(format!("Failed to check allowance: {}", e)))?;
-        
+        let current_allowance = adapter.get_token_allowance(token_address, wallet_address, spender_address).await?;
+
         // Nếu allowance đã đủ, không cần phê duyệt nữa
         if current_allowance >= amount {
             return Err(ChainError::ApprovalError("Already approved sufficient amount".to_string()));
         }
-        
+
         // Tạo calldata cho approve
-        let approve_data = token.approve(spender_address, amount).tx.data()
-            .ok_or_else(|| ChainError::ContractError("Failed to generate approval data".to_string()))?
-            .clone();
-        
+        let approve_data = encode_erc20_call("approve", &[
+            ethers::abi::Token::Address(spender_address),
+            ethers::abi::Token::Uint(amount),
+        ])?;
+
         // Gửi giao dịch phê duyệt
         self.send_transaction(
             wallet_address,
             chain_id,
             Some(token_address),
             U256::zero(),
-            Some(approve_data),
+            Some(approve_data.into()),
             None,
             None,
         ).await
     }
-    
+
     /// Chờ giao dịch được xác nhận
     pub async fn wait_for_transaction(
         &self,
@@ -660,19 +568,19 @@ impl WalletIntegration {
         timeout: Duration,
     ) -> Result<TransactionReceipt, ChainError> {
         let adapter = self.get_adapter(chain_id).await?;
-        
+
         // Tạo future với timeout
         let receipt_future = adapter.wait_for_transaction_receipt(tx_hash, confirmations, timeout);
-        
+
         // Đặt timeout cho việc chờ
         match tokio::time::timeout(timeout, receipt_future).await {
-            Ok(result) => result.map_err(|e| ChainError::from_anyhow(e.into())),
-            Err(_) => Err(ChainError::from_anyhow(anyhow::anyhow!(
-                "Timed out waiting for tx {}", tx_hash
+            Ok(result) => result,
+            Err(_) => Err(ChainError::from_anyhow(anyhow!(
+                "Timed out waiting for tx {:?}", tx_hash
             ))),
         }
     }
-    
+
     /// Lấy số dư ETH và tokens của ví
     pub async fn get_wallet_balances(
         &self,
@@ -681,10 +589,10 @@ impl WalletIntegration {
         token_addresses: Vec<Address>,
     ) -> Result<(U256, HashMap<Address, U256>), ChainError> {
         let adapter = self.get_adapter(chain_id).await?;
-        
+
         // Lấy số dư ETH
         let eth_balance = adapter.get_eth_balance(wallet_address, None).await?;
-        
+
         // Lấy số dư từng token
         let mut token_balances = HashMap::new();
         for token_address in token_addresses {
@@ -694,16 +602,16 @@ impl WalletIntegration {
                 },
                 Err(e) => {
                     warn!(
-                        "Không thể lấy số dư token {}: {}",
+                        "Không thể lấy số dư token {:?}: {}",
                         token_address, e
                     );
                 }
             }
         }
-        
+
         Ok((eth_balance, token_balances))
     }
-    
+
     /// Lấy chi tiết về một token
     pub async fn get_token_details(
         &self,
@@ -713,7 +621,7 @@ impl WalletIntegration {
         let adapter = self.get_adapter(chain_id).await?;
         adapter.get_token_details(token_address).await
     }
-    
+
     /// Lấy số dư của token
     pub async fn get_token_balance(
         &self,
@@ -724,63 +632,54 @@ impl WalletIntegration {
         let adapter = self.get_adapter(chain_id).await?;
         adapter.get_token_balance(token_address, wallet_address, None).await
     }
-    
+
     /// Làm mới cache adapter
     pub async fn refresh_adapters(&self) {
         let mut adapters = self.chain_adapters.write().await;
         adapters.clear();
     }
-    
+
     /// Nhập ví từ private key và thêm vào WalletManager
     pub fn import_wallet_from_private_key(&self, private_key: &str) -> Result<Address, ChainError> {
-        self.wallet_manager.import_from_private_key(private_key)
-            .map_err(|e| ChainError::from_anyhow(e))
+        self.wallet_manager.import_private_key(private_key, None)
+            .map_err(ChainError::from_anyhow)
     }
-    
+
     /// Nhập ví từ seed phrase và thêm vào WalletManager
     pub fn import_wallet_from_seed(&self, seed_phrase: &str, password: Option<&str>)
-        -> Result<Address, ChainError> 
+        -> Result<Address, ChainError>
     {
-        self.wallet_manager.import_from_seed_phrase(seed_phrase, password)
-            .map_err(|e| ChainError::from_anyhow(e))
+        self.wallet_manager.import_from_mnemonic(seed_phrase, password)
+            .map_err(ChainError::from_anyhow)
+            .and_then(|info| parse_address(&info.address))
     }
-    
+
     /// Tạo ví mới và thêm vào WalletManager
     pub fn create_new_wallet(&self, password: Option<&str>) -> Result<(String, Address), ChainError> {
         self.wallet_manager.create_wallet(password)
-            .map_err(|e| ChainError::from_anyhow(e))
+            .map_err(ChainError::from_anyhow)
     }
-    
+
     /// Xóa ví khỏi WalletManager
     pub fn remove_wallet(&self, address: Address) -> Result<(), ChainError> {
-        self.wallet_manager.remove_wallet(address)
-            .map_err(|e| ChainError::from_anyhow(e))
+        if self.wallet_manager.remove_wallet(&format!("{:?}", address)) {
+            Ok(())
+        } else {
+            Err(ChainError::InvalidAddress(format!("Wallet not found: {:?}", address)))
+        }
     }
-    
+
     /// Liệt kê tất cả các ví trong WalletManager
     pub fn list_wallets(&self) -> Result<Vec<Address>, ChainError> {
-        self.wallet_manager.list_wallets()
-            .map_err(|e| ChainError::from_anyhow(e))
+        let wallets = self.wallet_manager.list_wallets()
+            .map_err(ChainError::from_anyhow)?;
+        wallets.iter()
+            .map(|view| parse_address(&view.address))
+            .collect()
     }
-    
+
     /// Kiểm tra nếu có ví nào trong hệ thống
     pub fn has_wallets(&self) -> bool {
-        self.wallet_manager.has_wallets()
+        !self.wallet_manager.get_wallet_list().is_empty()
     }
 }
-
-// Giao diện ERC20 để tương tác với token
-abigen!(
-    ERC20,
-    r#"[
-        function name() view returns (string)
-        function symbol() view returns (string)
-        function decimals() view returns (uint8)
-        function totalSupply() view returns (uint256)
-        function balanceOf(address) view returns (uint256)
-        function allowance(address owner, address spender) view returns (uint256)
-        function approve(address spender, uint256 amount) returns (bool)
-        function transfer(address to, uint256 amount) returns (bool)
-        function transferFrom(address from, address to, uint256 amount) returns (bool)
-    ]"#,
-); 
\ No newline at end of file
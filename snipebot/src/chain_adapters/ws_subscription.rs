@@ -0,0 +1,697 @@
+// External imports
+use ethers::{
+    providers::{Http, Middleware, Provider, Ws},
+    types::{Block, Filter, Log, H256, U256},
+};
+
+// Standard library imports
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+
+// Third party imports
+use anyhow::{anyhow, Result};
+use futures::{
+    future::AbortHandle,
+    stream::{self, Abortable, BoxStream, SelectAll, StreamExt},
+};
+use metrics::{counter, gauge};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, info, warn};
+
+/// Cấu hình cho lớp subscription WebSocket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsSubscriptionConfig {
+    /// URL WebSocket dùng cho subscription
+    pub ws_url: String,
+    /// URL HTTP dùng để bù dữ liệu bị lỡ khi mất kết nối
+    pub http_url: String,
+    /// Thời gian chờ ban đầu trước khi kết nối lại (ms)
+    pub initial_backoff_ms: u64,
+    /// Thời gian chờ tối đa giữa các lần kết nối lại (ms)
+    pub max_backoff_ms: u64,
+    /// Chu kỳ kiểm tra kết nối còn sống (giây)
+    pub heartbeat_interval_secs: u64,
+    /// Số block tối đa được bù sau một lần mất kết nối
+    pub max_backfill_blocks: u64,
+    /// Số sự kiện gần nhất được nhớ để loại bỏ trùng lặp giữa dữ liệu bù và dữ liệu trực tiếp
+    pub dedup_window: usize,
+}
+
+impl WsSubscriptionConfig {
+    /// Tạo cấu hình với giá trị mặc định cho cặp URL WebSocket/HTTP
+    pub fn new(ws_url: &str, http_url: &str) -> Self {
+        Self {
+            ws_url: ws_url.to_string(),
+            http_url: http_url.to_string(),
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            heartbeat_interval_secs: 15,
+            max_backfill_blocks: 1_000,
+            dedup_window: 4_096,
+        }
+    }
+}
+
+/// Loại subscription logic
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionKind {
+    /// Header của block mới (`newHeads`)
+    NewHeads,
+    /// Log khớp filter (`logs`)
+    Logs(Box<Filter>),
+    /// Hash giao dịch mới vào mempool (`newPendingTransactions`)
+    PendingTransactions,
+}
+
+impl SubscriptionKind {
+    /// Subscription log theo filter
+    pub fn logs(filter: Filter) -> Self {
+        SubscriptionKind::Logs(Box::new(filter))
+    }
+
+    /// Khóa định danh subscription trên socket; các subscription giống nhau dùng chung một khóa
+    pub fn key(&self) -> String {
+        match self {
+            SubscriptionKind::NewHeads => "newHeads".to_string(),
+            SubscriptionKind::Logs(filter) => format!(
+                "logs:{}",
+                serde_json::to_string(filter).unwrap_or_default()
+            ),
+            SubscriptionKind::PendingTransactions => "newPendingTransactions".to_string(),
+        }
+    }
+}
+
+/// Sự kiện nhận được từ subscription
+#[derive(Debug, Clone)]
+pub enum SubscriptionEvent {
+    /// Block mới
+    NewHead(Box<Block<H256>>),
+    /// Log mới (hoặc log bị gỡ do reorg khi `removed == Some(true)`)
+    Log(Box<Log>),
+    /// Hash giao dịch pending
+    PendingTransaction(H256),
+}
+
+/// Thông điệp gửi tới consumer của subscription
+#[derive(Debug, Clone)]
+pub struct SubscriptionMessage {
+    /// Chain ID
+    pub chain_id: u64,
+    /// Sự kiện
+    pub event: SubscriptionEvent,
+    /// Sự kiện được bù qua HTTP sau khi mất kết nối
+    pub backfilled: bool,
+}
+
+/// Khóa loại bỏ trùng lặp cho sự kiện
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum EventKey {
+    Head(H256),
+    Log(Option<H256>, Option<H256>, Option<U256>, bool),
+}
+
+impl EventKey {
+    fn for_event(event: &SubscriptionEvent) -> Option<Self> {
+        match event {
+            SubscriptionEvent::NewHead(block) => block.hash.map(EventKey::Head),
+            SubscriptionEvent::Log(log) => Some(EventKey::Log(
+                log.block_hash,
+                log.transaction_hash,
+                log.log_index,
+                log.removed.unwrap_or(false),
+            )),
+            SubscriptionEvent::PendingTransaction(_) => None,
+        }
+    }
+}
+
+/// Tập khóa có giới hạn, giữ lại các khóa gần nhất
+#[derive(Debug)]
+struct RecentKeys<K: Hash + Eq + Clone> {
+    order: VecDeque<K>,
+    keys: HashSet<K>,
+    capacity: usize,
+}
+
+impl<K: Hash + Eq + Clone> RecentKeys<K> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            order: VecDeque::new(),
+            keys: HashSet::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Thêm khóa, trả về false nếu khóa đã tồn tại
+    fn insert(&mut self, key: K) -> bool {
+        if !self.keys.insert(key.clone()) {
+            return false;
+        }
+        self.order.push_back(key);
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Khoảng block bị thiếu giữa block cuối cùng đã phát và block mới, giới hạn `max_blocks` block gần nhất
+fn head_gap(last: Option<u64>, number: u64, max_blocks: u64) -> Option<(u64, u64)> {
+    let last = last?;
+    if number <= last + 1 || max_blocks == 0 {
+        return None;
+    }
+    let to = number - 1;
+    let from = (last + 1).max(to.saturating_sub(max_blocks - 1));
+    Some((from, to))
+}
+
+/// Thời gian chờ kế tiếp theo exponential backoff
+fn next_backoff(current: Duration, max: Duration) -> Duration {
+    (current * 2).min(max)
+}
+
+/// Thêm jitter ±20% để các client không kết nối lại cùng lúc
+fn with_jitter(delay: Duration, random: f64) -> Duration {
+    delay.mul_f64(0.8 + 0.4 * random.clamp(0.0, 1.0))
+}
+
+/// Một subscription logic trên socket dùng chung
+struct Topic {
+    kind: SubscriptionKind,
+    consumers: HashMap<u64, mpsc::UnboundedSender<SubscriptionMessage>>,
+    /// Block cuối cùng đã phát (newHeads) hoặc đã đồng bộ (logs)
+    synced_block: Option<u64>,
+    seen: RecentKeys<EventKey>,
+}
+
+/// Lệnh gửi tới phiên WebSocket đang chạy
+enum Command {
+    Activate(String),
+    Deactivate(String),
+}
+
+/// Subscription phía consumer
+pub struct Subscription {
+    /// ID của consumer
+    pub id: u64,
+    key: String,
+    receiver: mpsc::UnboundedReceiver<SubscriptionMessage>,
+}
+
+impl Subscription {
+    /// Nhận thông điệp tiếp theo, trả về None khi manager dừng
+    pub async fn next(&mut self) -> Option<SubscriptionMessage> {
+        self.receiver.recv().await
+    }
+}
+
+/// Quản lý các subscription WebSocket của một chain: ghép nhiều subscription logic trên một socket,
+/// tự kết nối lại với exponential backoff và bù block/log bị lỡ qua HTTP để consumer nhận luồng liên tục.
+/// `newPendingTransactions` không thể bù nên chỉ được đăng ký lại sau khi kết nối lại.
+pub struct WsSubscriptionManager {
+    chain_id: u64,
+    config: WsSubscriptionConfig,
+    http: Provider<Http>,
+    topics: Mutex<HashMap<String, Topic>>,
+    next_id: AtomicU64,
+    connected: AtomicBool,
+    command_tx: mpsc::UnboundedSender<Command>,
+    command_rx: Mutex<Option<mpsc::UnboundedReceiver<Command>>>,
+}
+
+impl WsSubscriptionManager {
+    /// Tạo manager (chưa kết nối, gọi `start` để chạy)
+    pub fn new(chain_id: u64, config: WsSubscriptionConfig) -> Result<Arc<Self>> {
+        let http = Provider::<Http>::try_from(config.http_url.as_str())
+            .map_err(|e| anyhow!("Invalid HTTP URL {}: {}", config.http_url, e))?;
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+
+        Ok(Arc::new(Self {
+            chain_id,
+            config,
+            http,
+            topics: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            connected: AtomicBool::new(false),
+            command_tx,
+            command_rx: Mutex::new(Some(command_rx)),
+        }))
+    }
+
+    /// Chain ID của manager
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Socket có đang kết nối không
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// Chạy vòng lặp kết nối trong background (chỉ có tác dụng ở lần gọi đầu)
+    pub fn start(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let commands = manager.command_rx.lock().await.take();
+            match commands {
+                Some(commands) => manager.run(commands).await,
+                None => debug!("WS subscription manager for chain {} already started", manager.chain_id),
+            }
+        })
+    }
+
+    /// Đăng ký subscription logic mới
+    pub async fn subscribe(&self, kind: SubscriptionKind) -> Result<Subscription> {
+        let key = kind.key();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        let is_new = {
+            let mut topics = self.topics.lock().await;
+            let is_new = !topics.contains_key(&key);
+            topics
+                .entry(key.clone())
+                .or_insert_with(|| Topic {
+                    kind,
+                    consumers: HashMap::new(),
+                    synced_block: None,
+                    seen: RecentKeys::new(self.config.dedup_window),
+                })
+                .consumers
+                .insert(id, sender);
+            gauge!("ws_subscription_topics", topics.len() as f64, "chain_id" => self.chain_id.to_string());
+            is_new
+        };
+
+        if is_new {
+            self.command_tx
+                .send(Command::Activate(key.clone()))
+                .map_err(|_| anyhow!("WS subscription manager for chain {} stopped", self.chain_id))?;
+        }
+
+        Ok(Subscription { id, key, receiver })
+    }
+
+    /// Hủy subscription; subscription trên socket chỉ bị hủy khi không còn consumer nào
+    pub async fn unsubscribe(&self, subscription: Subscription) {
+        let mut topics = self.topics.lock().await;
+        let empty = match topics.get_mut(&subscription.key) {
+            Some(topic) => {
+                topic.consumers.remove(&subscription.id);
+                topic.consumers.is_empty()
+            }
+            None => false,
+        };
+        if empty {
+            topics.remove(&subscription.key);
+            let _ = self.command_tx.send(Command::Deactivate(subscription.key));
+        }
+        gauge!("ws_subscription_topics", topics.len() as f64, "chain_id" => self.chain_id.to_string());
+    }
+
+    /// Vòng lặp kết nối lại với exponential backoff
+    async fn run(self: Arc<Self>, mut commands: mpsc::UnboundedReceiver<Command>) {
+        let initial_backoff = Duration::from_millis(self.config.initial_backoff_ms.max(1));
+        let max_backoff = Duration::from_millis(self.config.max_backoff_ms).max(initial_backoff);
+        let mut backoff = initial_backoff;
+
+        loop {
+            match Provider::<Ws>::connect(&self.config.ws_url).await {
+                Ok(ws) => {
+                    info!("WS subscription connected for chain {}: {}", self.chain_id, self.config.ws_url);
+                    let mut established = false;
+                    let result = self.run_session(&ws, &mut commands, &mut established).await;
+                    self.connected.store(false, Ordering::Relaxed);
+                    gauge!("ws_subscription_connected", 0.0, "chain_id" => self.chain_id.to_string());
+
+                    match result {
+                        Ok(()) => {
+                            info!("WS subscription manager for chain {} stopped", self.chain_id);
+                            return;
+                        }
+                        Err(e) => warn!("WS subscription session for chain {} ended: {}", self.chain_id, e),
+                    }
+                    if established {
+                        backoff = initial_backoff;
+                    }
+                }
+                Err(e) => warn!("WS connect failed for chain {}: {}", self.chain_id, e),
+            }
+
+            counter!("ws_subscription_reconnects", 1, "chain_id" => self.chain_id.to_string());
+            let delay = with_jitter(backoff, rand::random::<f64>());
+            debug!("Reconnecting WS subscriptions for chain {} in {:?}", self.chain_id, delay);
+            tokio::time::sleep(delay).await;
+            backoff = next_backoff(backoff, max_backoff);
+        }
+    }
+
+    /// Một phiên kết nối: đăng ký lại mọi subscription, bù dữ liệu bị lỡ rồi chuyển tiếp sự kiện
+    async fn run_session(
+        &self,
+        ws: &Provider<Ws>,
+        commands: &mut mpsc::UnboundedReceiver<Command>,
+        established: &mut bool,
+    ) -> Result<()> {
+        let mut streams: SelectAll<BoxStream<'_, (String, Option<SubscriptionEvent>)>> = SelectAll::new();
+        let mut handles: HashMap<String, AbortHandle> = HashMap::new();
+
+        let kinds: Vec<(String, SubscriptionKind)> = {
+            let topics = self.topics.lock().await;
+            topics.iter().map(|(key, topic)| (key.clone(), topic.kind.clone())).collect()
+        };
+        for (key, kind) in kinds {
+            let stream = Self::open_stream(ws, key.clone(), &kind).await?;
+            let (handle, registration) = AbortHandle::new_pair();
+            streams.push(Abortable::new(stream, registration).boxed());
+            handles.insert(key, handle);
+        }
+
+        *established = true;
+        self.connected.store(true, Ordering::Relaxed);
+        gauge!("ws_subscription_connected", 1.0, "chain_id" => self.chain_id.to_string());
+
+        // Bù dữ liệu trong khoảng mất kết nối; sự kiện trực tiếp chờ trong stream và được lọc trùng
+        self.backfill_all().await;
+
+        let mut heartbeat = tokio::time::interval(Duration::from_secs(self.config.heartbeat_interval_secs.max(1)));
+        heartbeat.tick().await;
+        let mut previous_heartbeat_head: Option<u64> = None;
+
+        loop {
+            tokio::select! {
+                item = streams.next(), if !streams.is_empty() => {
+                    match item {
+                        Some((key, Some(event))) => self.dispatch(&key, event, false).await,
+                        Some((key, None)) if handles.contains_key(&key) => {
+                            return Err(anyhow!("subscription {} closed by server", key));
+                        }
+                        _ => {}
+                    }
+                },
+                command = commands.recv() => {
+                    match command {
+                        Some(Command::Activate(key)) => {
+                            if handles.contains_key(&key) {
+                                continue;
+                            }
+                            let kind = self.topics.lock().await.get(&key).map(|topic| topic.kind.clone());
+                            if let Some(kind) = kind {
+                                let stream = Self::open_stream(ws, key.clone(), &kind).await?;
+                                let (handle, registration) = AbortHandle::new_pair();
+                                streams.push(Abortable::new(stream, registration).boxed());
+                                handles.insert(key, handle);
+                            }
+                        }
+                        Some(Command::Deactivate(key)) => {
+                            // Hủy stream sẽ gửi eth_unsubscribe cho subscription trên socket
+                            if let Some(handle) = handles.remove(&key) {
+                                handle.abort();
+                            }
+                        }
+                        None => return Ok(()),
+                    }
+                },
+                _ = heartbeat.tick() => {
+                    let head = tokio::time::timeout(Duration::from_secs(5), ws.get_block_number())
+                        .await
+                        .map_err(|_| anyhow!("heartbeat timed out"))?
+                        .map_err(|e| anyhow!("heartbeat failed: {}", e))?
+                        .as_u64();
+
+                    // Log của block ở heartbeat trước chắc chắn đã được giao, dời mốc đồng bộ tới đó
+                    if let Some(checkpoint) = previous_heartbeat_head {
+                        self.advance_log_checkpoints(checkpoint).await;
+                    }
+                    previous_heartbeat_head = Some(head);
+                },
+            }
+        }
+    }
+
+    /// Mở stream cho subscription, kết thúc bằng marker `(key, None)` khi socket đóng subscription
+    async fn open_stream<'a>(
+        ws: &'a Provider<Ws>,
+        key: String,
+        kind: &SubscriptionKind,
+    ) -> Result<BoxStream<'a, (String, Option<SubscriptionEvent>)>> {
+        let events: BoxStream<'a, SubscriptionEvent> = match kind {
+            SubscriptionKind::NewHeads => ws.subscribe_blocks().await?.map(|block| SubscriptionEvent::NewHead(Box::new(block))).boxed(),
+            SubscriptionKind::Logs(filter) => ws.subscribe_logs(filter).await?.map(|log| SubscriptionEvent::Log(Box::new(log))).boxed(),
+            SubscriptionKind::PendingTransactions => {
+                ws.subscribe_pending_txs().await?.map(SubscriptionEvent::PendingTransaction).boxed()
+            }
+        };
+        debug!("Subscribed {}", key);
+
+        let end_key = key.clone();
+        Ok(events
+            .map(move |event| (key.clone(), Some(event)))
+            .chain(stream::once(async move { (end_key, None) }))
+            .boxed())
+    }
+
+    /// Xử lý sự kiện: bù khoảng trống block nếu có rồi giao tới consumer
+    async fn dispatch(&self, key: &str, event: SubscriptionEvent, backfilled: bool) {
+        if let SubscriptionEvent::NewHead(block) = &event {
+            let number = block.number.map(|n| n.as_u64()).unwrap_or_default();
+            let last = self.topics.lock().await.get(key).and_then(|topic| topic.synced_block);
+            if let Some((from, to)) = head_gap(last, number, self.config.max_backfill_blocks) {
+                counter!("ws_subscription_head_gaps", 1, "chain_id" => self.chain_id.to_string());
+                debug!("Filling head gap {}..={} on chain {}", from, to, self.chain_id);
+                self.backfill_heads(key, from, to).await;
+            }
+        }
+
+        self.deliver(key, event, backfilled).await;
+    }
+
+    /// Giao sự kiện tới consumer, bỏ qua sự kiện trùng và consumer đã đóng
+    async fn deliver(&self, key: &str, event: SubscriptionEvent, backfilled: bool) {
+        let mut topics = self.topics.lock().await;
+        let Some(topic) = topics.get_mut(key) else {
+            return;
+        };
+
+        if let Some(event_key) = EventKey::for_event(&event) {
+            if !topic.seen.insert(event_key) {
+                return;
+            }
+        }
+
+        match &event {
+            SubscriptionEvent::NewHead(block) => {
+                topic.synced_block = block.number.map(|n| n.as_u64()).or(topic.synced_block);
+            }
+            SubscriptionEvent::Log(log) => {
+                if let Some(number) = log.block_number.map(|n| n.as_u64()) {
+                    topic.synced_block = Some(topic.synced_block.map_or(number, |synced| synced.max(number)));
+                }
+            }
+            SubscriptionEvent::PendingTransaction(_) => {}
+        }
+
+        let message = SubscriptionMessage {
+            chain_id: self.chain_id,
+            event,
+            backfilled,
+        };
+        topic.consumers.retain(|_, sender| sender.send(message.clone()).is_ok());
+
+        if backfilled {
+            counter!("ws_subscription_backfilled_events", 1, "chain_id" => self.chain_id.to_string());
+        }
+
+        if topic.consumers.is_empty() {
+            topics.remove(key);
+            let _ = self.command_tx.send(Command::Deactivate(key.to_string()));
+        }
+    }
+
+    /// Bù dữ liệu bị lỡ cho mọi subscription sau khi kết nối lại
+    async fn backfill_all(&self) {
+        let head = match self.http.get_block_number().await {
+            Ok(head) => head.as_u64(),
+            Err(e) => {
+                warn!("Cannot backfill WS subscriptions on chain {}: {}", self.chain_id, e);
+                return;
+            }
+        };
+
+        let snapshot: Vec<(String, SubscriptionKind, Option<u64>)> = {
+            let topics = self.topics.lock().await;
+            topics
+                .iter()
+                .map(|(key, topic)| (key.clone(), topic.kind.clone(), topic.synced_block))
+                .collect()
+        };
+
+        for (key, kind, synced) in snapshot {
+            match (kind, synced) {
+                (SubscriptionKind::NewHeads, Some(last)) => {
+                    if let Some((from, to)) = head_gap(Some(last), head + 1, self.config.max_backfill_blocks) {
+                        self.backfill_heads(&key, from, to).await;
+                    }
+                }
+                (SubscriptionKind::Logs(filter), Some(synced)) => {
+                    // Lấy lại từ block đã đồng bộ (bao gồm) để không lỡ log cùng block, trùng lặp được lọc
+                    let from = synced.max(head.saturating_sub(self.config.max_backfill_blocks.saturating_sub(1)));
+                    self.backfill_logs(&key, &filter, from, head).await;
+                }
+                (SubscriptionKind::Logs(_), None) => {
+                    // Subscription mới, chỉ cần mốc đồng bộ cho lần mất kết nối sau
+                    if let Some(topic) = self.topics.lock().await.get_mut(&key) {
+                        topic.synced_block = Some(head);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Bù các block `from..=to` qua HTTP theo thứ tự
+    async fn backfill_heads(&self, key: &str, from: u64, to: u64) {
+        for number in from..=to {
+            match self.http.get_block(number).await {
+                Ok(Some(block)) => self.deliver(key, SubscriptionEvent::NewHead(Box::new(block)), true).await,
+                Ok(None) => {
+                    warn!("Block {} not found while backfilling chain {}", number, self.chain_id);
+                    return;
+                }
+                Err(e) => {
+                    warn!("Failed to backfill block {} on chain {}: {}", number, self.chain_id, e);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Bù log trong khoảng block qua HTTP `get_logs`
+    async fn backfill_logs(&self, key: &str, filter: &Filter, from: u64, to: u64) {
+        let range_filter = filter.clone().from_block(from).to_block(to);
+        match self.http.get_logs(&range_filter).await {
+            Ok(logs) => {
+                debug!("Backfilled {} logs for blocks {}..={} on chain {}", logs.len(), from, to, self.chain_id);
+                for log in logs {
+                    self.deliver(key, SubscriptionEvent::Log(Box::new(log)), true).await;
+                }
+                if let Some(topic) = self.topics.lock().await.get_mut(key) {
+                    topic.synced_block = Some(topic.synced_block.map_or(to, |synced| synced.max(to)));
+                }
+            }
+            Err(e) => warn!("Failed to backfill logs {}..={} on chain {}: {}", from, to, self.chain_id, e),
+        }
+    }
+
+    /// Dời mốc đồng bộ của các subscription log tới `checkpoint`
+    async fn advance_log_checkpoints(&self, checkpoint: u64) {
+        let mut topics = self.topics.lock().await;
+        for topic in topics.values_mut() {
+            if matches!(topic.kind, SubscriptionKind::Logs(_)) {
+                topic.synced_block = Some(topic.synced_block.map_or(checkpoint, |synced| synced.max(checkpoint)));
+            }
+        }
+    }
+}
+
+/// Registry toàn cục cho subscription manager theo chain
+static SUBSCRIPTION_MANAGERS: Lazy<RwLock<HashMap<u64, Arc<WsSubscriptionManager>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Lấy hoặc tạo (và khởi chạy) subscription manager cho chain
+pub fn get_or_create_subscription_manager(chain_id: u64, config: WsSubscriptionConfig) -> Result<Arc<WsSubscriptionManager>> {
+    let mut managers = SUBSCRIPTION_MANAGERS
+        .write()
+        .map_err(|e| anyhow!("Subscription registry lock poisoned: {}", e))?;
+    if let Some(manager) = managers.get(&chain_id) {
+        return Ok(manager.clone());
+    }
+
+    let manager = WsSubscriptionManager::new(chain_id, config)?;
+    manager.start();
+    managers.insert(chain_id, manager.clone());
+    Ok(manager)
+}
+
+/// Lấy subscription manager hiện có của chain
+pub fn get_subscription_manager(chain_id: u64) -> Option<Arc<WsSubscriptionManager>> {
+    SUBSCRIPTION_MANAGERS.read().ok()?.get(&chain_id).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Address, U64};
+
+    #[test]
+    fn test_head_gap() {
+        assert_eq!(head_gap(None, 10, 100), None);
+        assert_eq!(head_gap(Some(9), 10, 100), None);
+        assert_eq!(head_gap(Some(10), 10, 100), None);
+        assert_eq!(head_gap(Some(5), 10, 100), Some((6, 9)));
+        // Khoảng trống lớn chỉ bù các block gần nhất
+        assert_eq!(head_gap(Some(0), 1_000, 10), Some((990, 999)));
+    }
+
+    #[test]
+    fn test_backoff_and_jitter() {
+        let max = Duration::from_secs(30);
+        let mut delay = Duration::from_millis(500);
+        for _ in 0..10 {
+            delay = next_backoff(delay, max);
+        }
+        assert_eq!(delay, max);
+        assert_eq!(next_backoff(Duration::from_secs(1), max), Duration::from_secs(2));
+
+        let base = Duration::from_secs(10);
+        assert_eq!(with_jitter(base, 0.0), Duration::from_secs(8));
+        assert_eq!(with_jitter(base, 1.0), Duration::from_secs(12));
+    }
+
+    #[test]
+    fn test_recent_keys_dedup() {
+        let mut keys = RecentKeys::new(2);
+        assert!(keys.insert(1));
+        assert!(!keys.insert(1));
+        assert!(keys.insert(2));
+        assert!(keys.insert(3));
+        // Khóa cũ nhất đã bị loại khỏi cửa sổ
+        assert!(keys.insert(1));
+    }
+
+    #[test]
+    fn test_subscription_keys_and_event_keys() {
+        let filter = Filter::new().address(Address::repeat_byte(7));
+        assert_eq!(SubscriptionKind::logs(filter.clone()).key(), SubscriptionKind::logs(filter).key());
+        assert_ne!(SubscriptionKind::NewHeads.key(), SubscriptionKind::PendingTransactions.key());
+
+        let log = Log {
+            block_hash: Some(H256::repeat_byte(1)),
+            block_number: Some(U64::from(5)),
+            transaction_hash: Some(H256::repeat_byte(2)),
+            log_index: Some(U256::from(3)),
+            ..Default::default()
+        };
+        let mut removed = log.clone();
+        removed.removed = Some(true);
+
+        let live = EventKey::for_event(&SubscriptionEvent::Log(Box::new(log.clone())));
+        assert_eq!(live, EventKey::for_event(&SubscriptionEvent::Log(Box::new(log))));
+        // Log bị gỡ do reorg không bị coi là trùng với log gốc
+        assert_ne!(live, EventKey::for_event(&SubscriptionEvent::Log(Box::new(removed))));
+        assert_eq!(EventKey::for_event(&SubscriptionEvent::PendingTransaction(H256::zero())), None);
+    }
+}
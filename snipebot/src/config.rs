@@ -1,13 +1,12 @@
 // External imports
 use ethers::providers::{Provider, Http};
-use ethers::types::{TransactionReceipt, U256};
 use serde::{Deserialize, Serialize};
 
 // Standard library imports
 use std::collections::HashMap;
 use std::env;
 use std::future::Future;
-use std::io::{Error, ErrorKind};
+use std::io::Error;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -17,7 +16,6 @@ use crate::chain_adapters::ChainConfig;
 // Third party imports
 use anyhow::Result;
 use dotenv::dotenv;
-use log::{info, warn};
 use tokio;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -77,6 +75,9 @@ pub struct Config {
     #[serde(default = "default_wallet_encryption_seed")]
     pub wallet_encryption_seed: String,
     pub fallback_rpc_urls: Vec<String>,
+    /// Thư mục chứa file cấu hình chain (*.toml)
+    #[serde(default = "default_chain_config_dir")]
+    pub chain_config_dir: String,
 }
 
 /// Cấu hình cho retry
@@ -113,6 +114,12 @@ impl Default for RetryConfig {
     }
 }
 
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Config {
     pub fn new() -> Self {
         Self {
@@ -145,6 +152,7 @@ impl Config {
             auto_trade_threshold: 0.8,
             wallet_encryption_seed: default_wallet_encryption_seed(),
             fallback_rpc_urls: vec![],
+            chain_config_dir: default_chain_config_dir(),
         }
     }
     
@@ -188,6 +196,7 @@ impl Config {
             auto_trade_threshold: env::var("AUTO_TRADE_THRESHOLD").unwrap_or_else(|_| "0.8".to_string()).parse().unwrap_or(0.8),
             wallet_encryption_seed: default_wallet_encryption_seed(),
             fallback_rpc_urls: vec![],
+            chain_config_dir: default_chain_config_dir(),
         })
     }
 }
@@ -203,97 +212,16 @@ pub fn init_config() -> Arc<Config> {
     }
 }
 
-/// Trả về danh sách các chain được định nghĩa trước
-pub fn get_predefined_chains() -> HashMap<String, ChainConfig> {
-    let mut chains = HashMap::new();
-    
-    // Ethereum Mainnet
-    chains.insert("ethereum".to_string(), ChainConfig {
-        name: "Ethereum".to_string(),
-        chain_id: 1,
-        rpc_url: "https://eth.llamarpc.com".to_string(),
-        native_symbol: "ETH".to_string(),
-        wrapped_native_token: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string(), // WETH
-        router_address: "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D".to_string(), // Uniswap V2 Router
-        factory_address: "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f".to_string(), // Uniswap V2 Factory
-        explorer_url: "https://etherscan.io".to_string(),
-        block_time: 12000, // ~12 seconds
-        default_gas_limit: 500000,
-        default_gas_price: 20000000000, // 20 Gwei
-        eip1559_supported: true,
-        max_priority_fee: Some(2000000000), // 2 Gwei
-    });
-    
-    // Binance Smart Chain
-    chains.insert("bsc".to_string(), ChainConfig {
-        name: "Binance Smart Chain".to_string(),
-        chain_id: 56,
-        rpc_url: "https://bsc-dataseed.binance.org".to_string(),
-        native_symbol: "BNB".to_string(),
-        wrapped_native_token: "0xbb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c".to_string(),
-        router_address: "0x10ED43C718714eb63d5aA57B78B54704E256024E".to_string(), // PancakeSwap Router
-        factory_address: "0xcA143Ce32Fe78f1f7019d7d551a6402fC5350c73".to_string(), // PancakeSwap Factory
-        explorer_url: "https://bscscan.com".to_string(),
-        block_time: 3000, // ~3 seconds
-        default_gas_limit: 500000,
-        default_gas_price: 5000000000, // 5 Gwei
-        eip1559_supported: false,
-        max_priority_fee: None,
-    });
-    
-    // Arbitrum
-    chains.insert("arbitrum".to_string(), ChainConfig {
-        name: "Arbitrum".to_string(),
-        chain_id: 42161,
-        rpc_url: "https://arb1.arbitrum.io/rpc".to_string(),
-        native_symbol: "ETH".to_string(),
-        wrapped_native_token: "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1".to_string(),
-        router_address: "0x1b02da8cb0d097eb8d57a175b88c7d8b47997506".to_string(), // SushiSwap Router
-        factory_address: "0xc35DADB65012eC5796536bD9864eD8773aBc74C4".to_string(), // SushiSwap Factory
-        explorer_url: "https://arbiscan.io".to_string(),
-        block_time: 250, // ~0.25 seconds
-        default_gas_limit: 1000000,
-        default_gas_price: 100000000, // 0.1 Gwei
-        eip1559_supported: true,
-        max_priority_fee: Some(10000000), // 0.01 Gwei
-    });
-    
-    // Base
-    chains.insert("base".to_string(), ChainConfig {
-        name: "Base".to_string(),
-        chain_id: 8453,
-        rpc_url: "https://mainnet.base.org".to_string(),
-        native_symbol: "ETH".to_string(),
-        wrapped_native_token: "0x4200000000000000000000000000000000000006".to_string(),
-        router_address: "0x8c1a3cf8f83074169fe5d7ad50b978e1cadff129".to_string(), // BaseSwap Router
-        factory_address: "0xfda619b6d20975be80a10332cd39b9a4b0e4f9bb".to_string(), // BaseSwap Factory
-        explorer_url: "https://basescan.org".to_string(),
-        block_time: 2000, // ~2 seconds
-        default_gas_limit: 800000,
-        default_gas_price: 1000000000, // 1 Gwei
-        eip1559_supported: true,
-        max_priority_fee: Some(100000000), // 0.1 Gwei
-    });
-    
-    chains
-}
-
 #[derive(Clone, Debug)]
 pub struct MultiChainConfig {
     pub chains: HashMap<u64, ChainConfig>,
     pub default_chain_id: u64,
 }
 
-impl SnipeBot {
-    async fn check_for_rugpull(&self, token_info: &TokenInfo) -> Result<bool, Box<dyn std::error::Error>> {
-        // Kiểm tra liquidity ratio
-        // Kiểm tra code token
-        // Kiểm tra owner có thể mint không giới hạn không
-        // Kiểm tra trading enabled
-        // etc.
-        
-        Ok(false) // Not a rug pull
-    }
+fn default_chain_config_dir() -> String {
+    crate::chain_adapters::chain_config_loader::chain_config_dir()
+        .display()
+        .to_string()
 }
 
 fn default_wallet_encryption_seed() -> String {
@@ -302,88 +230,6 @@ fn default_wallet_encryption_seed() -> String {
         .unwrap_or_else(|_| "snipebot_default_encryption_seed".to_string())
 }
 
-impl EthereumAdapter {
-    async fn get_provider_with_fallback(&self) -> Provider<Http> {
-        // Thử kết nối với provider chính
-        if let Ok(block_number) = self.provider.get_block_number().await {
-            return self.provider.clone();
-        }
-        
-        // Nếu thất bại, thử với các provider dự phòng
-        for url in &self.config.fallback_rpc_urls {
-            match Provider::<Http>::try_from(url.as_str()) {
-                Ok(provider) => {
-                    if let Ok(_) = provider.get_block_number().await {
-                        info!("Đã chuyển sang provider dự phòng: {}", url);
-                        return provider;
-                    }
-                },
-                Err(_) => continue,
-            }
-        }
-        
-        // Nếu tất cả đều thất bại, trả về provider chính
-        warn!("Tất cả provider đều không khả dụng, sử dụng provider chính");
-        self.provider.clone()
-    }
-    
-    async fn swap_exact_eth_for_tokens_with_fallback(
-        &self,
-        token_address: &str,
-        amount_in: U256,
-        min_amount_out: U256,
-        recipient: &str,
-        deadline: u64,
-        gas_limit: Option<u64>,
-        gas_price: Option<u64>,
-    ) -> Result<Option<TransactionReceipt>, Box<dyn std::error::Error>> {
-        let fallback_providers = self.config.fallback_rpc_urls.clone();
-        let config = RetryConfig::default();
-        let operation_name = "swap_eth_for_tokens";
-        
-        return retry_with_fallback_providers(
-            // Primary operation
-            || async {
-                self.swap_exact_eth_for_tokens(
-                    token_address,
-                    amount_in,
-                    min_amount_out,
-                    recipient,
-                    deadline,
-                    gas_limit,
-                    gas_price,
-                ).await
-            },
-            // Fallback operation generator
-            |provider_url| async move {
-                // Tạo adapter mới với provider dự phòng
-                let mut adapter = EthereumAdapter::new().await?;
-                
-                // Cập nhật provider
-                adapter.provider = Provider::<Http>::try_from(provider_url)?;
-                
-                // Cập nhật wallet
-                if let Some(wallet) = self.wallet.clone() {
-                    adapter.set_wallet(wallet);
-                }
-                
-                adapter.swap_exact_eth_for_tokens(
-                    token_address,
-                    amount_in,
-                    min_amount_out,
-                    recipient,
-                    deadline,
-                    gas_limit,
-                    gas_price,
-                ).await
-            },
-            fallback_providers,
-            config,
-            operation_name
-        ).await;
-    }
-}
-
 /// Thực hiện retry với các fallback provider
 pub async fn retry_with_fallback_providers<T, F, Fut>(
     providers: Vec<Provider<Http>>, 
@@ -431,8 +277,7 @@ where
         delay = config.initial_retry_delay;
     }
     
-    Err(last_error.unwrap_or_else(|| Box::new(Error::new(
-        ErrorKind::Other, 
+    Err(last_error.unwrap_or_else(|| Box::new(Error::other(
         "All providers failed"
     ))))
 }
\ No newline at end of file
@@ -0,0 +1,246 @@
+// Quản lý danh sách RPC endpoint theo chain và kiểm tra sức khỏe định kỳ
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use ethers::providers::{Http, Middleware, Provider};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::config::Config;
+use crate::utils::safe_now;
+
+/// Thông tin một RPC endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointInfo {
+    pub url: String,
+    pub chain_id: u64,
+    pub name: String,
+    /// Priority cao hơn được ưu tiên
+    pub priority: u32,
+    pub enabled: bool,
+}
+
+/// Thống kê request của một endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointStats {
+    pub total_requests: u64,
+    pub success_rate: f64,
+    /// Latency trung bình (ms)
+    pub average_latency: u64,
+    pub last_error: Option<String>,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct EndpointManager {
+    endpoints: HashMap<u64, Vec<EndpointInfo>>,
+    stats: RwLock<HashMap<String, EndpointStats>>,
+    last_update: RwLock<u64>,
+}
+
+impl EndpointManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nạp rpc_url và các fallback_rpc_urls của cấu hình cho chain mặc định
+    pub fn from_config(config: &Config) -> Self {
+        let mut manager = Self::new();
+        let urls: Vec<&String> = std::iter::once(&config.rpc_url)
+            .chain(config.fallback_rpc_urls.iter())
+            .filter(|url| !url.is_empty())
+            .collect();
+
+        for (i, url) in urls.iter().enumerate() {
+            manager.add_endpoint(EndpointInfo {
+                url: url.to_string(),
+                chain_id: config.chain_id,
+                name: format!("endpoint-{}-{}", config.chain_id, i + 1),
+                priority: (urls.len() - i) as u32, // Priority giảm dần
+                enabled: true,
+            });
+        }
+        manager
+    }
+
+    // Thêm endpoint mới (bỏ qua nếu URL đã có)
+    pub fn add_endpoint(&mut self, endpoint: EndpointInfo) {
+        let endpoints = self.endpoints.entry(endpoint.chain_id).or_default();
+        if !endpoints.iter().any(|e| e.url == endpoint.url) {
+            endpoints.push(endpoint);
+            // Sắp xếp theo priority giảm dần
+            endpoints.sort_by_key(|e| std::cmp::Reverse(e.priority));
+        }
+    }
+
+    // Endpoint đang enabled có priority cao nhất của chain
+    pub fn get_best_endpoint(&self, chain_id: u64) -> Option<EndpointInfo> {
+        self.endpoints.get(&chain_id)?
+            .iter()
+            .find(|e| e.enabled)
+            .cloned()
+    }
+
+    // Cập nhật thống kê cho một endpoint
+    pub async fn update_stats(&self, url: &str, success: bool, latency_ms: u64, error: Option<String>) {
+        let mut stats = self.stats.write().await;
+        let endpoint_stats = stats.entry(url.to_string()).or_insert(EndpointStats {
+            total_requests: 0,
+            success_rate: 1.0,
+            average_latency: 0,
+            last_error: None,
+            timestamp: safe_now(),
+        });
+
+        let previous = endpoint_stats.total_requests as f64;
+        endpoint_stats.total_requests += 1;
+        let total = endpoint_stats.total_requests as f64;
+
+        // Trung bình lũy tiến cho success rate và latency
+        let success_value = if success { 1.0 } else { 0.0 };
+        endpoint_stats.success_rate = (endpoint_stats.success_rate * previous + success_value) / total;
+        endpoint_stats.average_latency = ((endpoint_stats.average_latency as f64 * previous + latency_ms as f64) / total) as u64;
+        if !success {
+            endpoint_stats.last_error = error;
+        }
+        endpoint_stats.timestamp = safe_now();
+
+        *self.last_update.write().await = safe_now();
+    }
+
+    // Vô hiệu hóa endpoint không khả dụng
+    pub fn disable_endpoint(&mut self, chain_id: u64, url: &str) -> bool {
+        match self.endpoints.get_mut(&chain_id).and_then(|endpoints| endpoints.iter_mut().find(|e| e.url == url)) {
+            Some(endpoint) => {
+                endpoint.enabled = false;
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Kiểm tra sức khỏe các endpoints; endpoint khả dụng được xếp trước
+    pub async fn check_endpoints_health(&mut self) -> HashMap<u64, Vec<EndpointInfo>> {
+        let mut results = HashMap::new();
+
+        for (chain_id, endpoints) in &mut self.endpoints {
+            for endpoint in endpoints.iter_mut() {
+                match check_endpoint_health(&endpoint.url).await {
+                    Ok(is_healthy) => {
+                        endpoint.enabled = is_healthy;
+                        if !is_healthy {
+                            warn!("Endpoint {} cho chain_id {} không khả dụng", endpoint.url, chain_id);
+                        }
+                    },
+                    Err(e) => {
+                        endpoint.enabled = false;
+                        warn!("Lỗi khi kiểm tra endpoint {} cho chain_id {}: {}", endpoint.url, chain_id, e);
+                    }
+                }
+            }
+
+            endpoints.sort_by(|a, b| b.enabled.cmp(&a.enabled).then(b.priority.cmp(&a.priority)));
+            results.insert(*chain_id, endpoints.clone());
+        }
+
+        results
+    }
+
+    // Tất cả endpoints của một chain
+    pub fn get_endpoints_for_chain(&self, chain_id: u64) -> Vec<EndpointInfo> {
+        self.endpoints.get(&chain_id).cloned().unwrap_or_default()
+    }
+
+    pub async fn get_stats(&self) -> HashMap<String, EndpointStats> {
+        self.stats.read().await.clone()
+    }
+
+    pub async fn get_last_update(&self) -> u64 {
+        *self.last_update.read().await
+    }
+}
+
+// Endpoint khỏe nếu trả về block number trong 5 giây
+async fn check_endpoint_health(endpoint_url: &str) -> Result<bool> {
+    let provider = Provider::<Http>::try_from(endpoint_url)
+        .map_err(|e| anyhow!("Không thể kết nối tới endpoint {}: {}", endpoint_url, e))?;
+
+    match tokio::time::timeout(Duration::from_secs(5), provider.get_block_number()).await {
+        Ok(Ok(_)) => Ok(true),
+        Ok(Err(e)) => {
+            warn!("Endpoint {} trả về lỗi: {}", endpoint_url, e);
+            Ok(false)
+        },
+        Err(_) => {
+            warn!("Endpoint {} không phản hồi trong thời gian quy định", endpoint_url);
+            Ok(false)
+        }
+    }
+}
+
+/// Kiểm tra sức khỏe các endpoints mỗi 5 phút
+pub async fn start_endpoint_manager_service(endpoint_manager: Arc<RwLock<EndpointManager>>) {
+    info!("Khởi động Endpoint Manager service");
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(300)).await;
+
+        let results = endpoint_manager.write().await.check_endpoints_health().await;
+        for (chain_id, endpoints) in results {
+            let available_count = endpoints.iter().filter(|e| e.enabled).count();
+            if available_count == 0 {
+                error!("Chain {}: không còn endpoint khả dụng", chain_id);
+            } else {
+                info!("Chain {}: {}/{} endpoints khả dụng", chain_id, available_count, endpoints.len());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(url: &str, priority: u32) -> EndpointInfo {
+        EndpointInfo {
+            url: url.to_string(),
+            chain_id: 1,
+            name: url.to_string(),
+            priority,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_best_endpoint_skips_disabled() {
+        let mut manager = EndpointManager::new();
+        manager.add_endpoint(endpoint("http://low", 1));
+        manager.add_endpoint(endpoint("http://high", 2));
+        manager.add_endpoint(endpoint("http://high", 5));
+
+        assert_eq!(manager.get_endpoints_for_chain(1).len(), 2);
+        assert_eq!(manager.get_best_endpoint(1).map(|e| e.url), Some("http://high".to_string()));
+
+        assert!(manager.disable_endpoint(1, "http://high"));
+        assert_eq!(manager.get_best_endpoint(1).map(|e| e.url), Some("http://low".to_string()));
+        assert!(manager.get_best_endpoint(56).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_update_stats_averages() {
+        let manager = EndpointManager::new();
+        manager.update_stats("http://a", true, 100, None).await;
+        manager.update_stats("http://a", false, 300, Some("timeout".to_string())).await;
+
+        let stats = manager.get_stats().await;
+        let a = &stats["http://a"];
+        assert_eq!(a.total_requests, 2);
+        assert!((a.success_rate - 0.5).abs() < f64::EPSILON);
+        assert_eq!(a.average_latency, 200);
+        assert_eq!(a.last_error.as_deref(), Some("timeout"));
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt;
+
+/// Phân loại lỗi giao dịch on-chain để quyết định cách xử lý/thử lại
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionError {
+    /// Giao dịch không được xác nhận trong thời gian chờ
+    Timeout,
+    /// Gas price quá thấp để được chấp nhận/thay thế
+    Underpriced,
+    /// Không đủ native token để trả gas và value
+    InsufficientFunds,
+    /// Nonce đã được sử dụng
+    NonceTooLow,
+    /// Giao dịch bị revert với lý do
+    ExecutionReverted(String),
+    /// Lỗi khác đã biết nội dung
+    Other(String),
+    /// Lỗi không xác định
+    Unknown(String),
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout => write!(f, "Transaction timeout"),
+            Self::Underpriced => write!(f, "Transaction underpriced"),
+            Self::InsufficientFunds => write!(f, "Insufficient funds"),
+            Self::NonceTooLow => write!(f, "Nonce too low"),
+            Self::ExecutionReverted(reason) => write!(f, "Execution reverted: {}", reason),
+            Self::Other(msg) => write!(f, "Transaction error: {}", msg),
+            Self::Unknown(msg) => write!(f, "Unknown transaction error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TransactionError {}
+
+impl TransactionError {
+    /// Chuyển anyhow::Error thành TransactionError, giữ nguyên nếu lỗi gốc đã là TransactionError
+    pub fn from_anyhow(e: anyhow::Error) -> Self {
+        match e.downcast::<TransactionError>() {
+            Ok(tx_error) => tx_error,
+            Err(e) => classify_blockchain_error(&e),
+        }
+    }
+}
+
+/// Hành động khôi phục đề xuất cho một lỗi giao dịch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// Thử lại nguyên giao dịch
+    Retry,
+    /// Thử lại với gas price cao hơn
+    BumpGas,
+    /// Lấy lại nonce rồi thử lại
+    RefreshNonce,
+    /// Không thử lại
+    Abort,
+}
+
+/// Thông tin khôi phục cho một lỗi giao dịch
+#[derive(Debug, Clone)]
+pub struct RecoveryInfo {
+    pub action: RecoveryAction,
+    pub retryable: bool,
+    pub message: String,
+}
+
+/// Phân loại lỗi blockchain dựa trên nội dung thông báo lỗi
+pub fn classify_blockchain_error<E: fmt::Display + ?Sized>(error: &E) -> TransactionError {
+    let message = error.to_string();
+    let lower = message.to_lowercase();
+
+    if lower.contains("timeout") || lower.contains("timed out") {
+        TransactionError::Timeout
+    } else if lower.contains("underpriced") || lower.contains("fee too low") {
+        TransactionError::Underpriced
+    } else if lower.contains("insufficient funds") {
+        TransactionError::InsufficientFunds
+    } else if lower.contains("nonce too low") || lower.contains("already known") {
+        TransactionError::NonceTooLow
+    } else if lower.contains("execution reverted") || lower.contains("revert") {
+        TransactionError::ExecutionReverted(message)
+    } else if message.is_empty() {
+        TransactionError::Unknown(message)
+    } else {
+        TransactionError::Other(message)
+    }
+}
+
+/// Lấy hành động khôi phục tương ứng với một lỗi giao dịch
+pub fn get_recovery_info(error: &TransactionError) -> RecoveryInfo {
+    let (action, message) = match error {
+        TransactionError::Timeout => (RecoveryAction::Retry, "Thử lại sau timeout"),
+        TransactionError::Underpriced => (RecoveryAction::BumpGas, "Tăng gas price rồi thử lại"),
+        TransactionError::NonceTooLow => (RecoveryAction::RefreshNonce, "Đồng bộ lại nonce rồi thử lại"),
+        TransactionError::InsufficientFunds => (RecoveryAction::Abort, "Nạp thêm native token"),
+        TransactionError::ExecutionReverted(_) => (RecoveryAction::Abort, "Giao dịch bị revert, không thử lại"),
+        TransactionError::Other(_) | TransactionError::Unknown(_) => (RecoveryAction::Retry, "Thử lại"),
+    };
+
+    RecoveryInfo {
+        action,
+        retryable: action != RecoveryAction::Abort,
+        message: message.to_string(),
+    }
+}
@@ -1,7 +1,6 @@
-use std::sync::Arc;
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::broadcast;
 use serde::{Serialize, Deserialize};
-use thiserror::Error;
+use crate::snipebot::SnipeResult;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SystemEvent {
@@ -10,6 +9,26 @@ pub enum SystemEvent {
     SnipeExecuted(SnipeResult),
     MempoolTransaction(String), // Transaction hash
     ServerStatus(bool), // Is online
+    ChainReorg(ReorgEvent),
+    TransactionReorged(u64, String, Option<u64>), // Chain ID, transaction hash, block mới (None nếu bị loại khỏi chain)
+    TransactionFinalized(u64, String, u64), // Chain ID, transaction hash, block number
+}
+
+/// Thông tin về một lần reorg được phát hiện
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReorgEvent {
+    /// Chain ID
+    pub chain_id: u64,
+    /// Block chung cuối cùng giữa chain cũ và chain mới
+    pub common_ancestor: u64,
+    /// Độ sâu của reorg (số block bị thay thế)
+    pub depth: u64,
+    /// Hash các block bị loại bỏ
+    pub orphaned_blocks: Vec<String>,
+    /// Head mới sau reorg
+    pub new_head: u64,
+    /// Các giao dịch của bot nằm trong block bị loại bỏ
+    pub affected_transactions: Vec<String>,
 }
 
 pub struct EventBus {
@@ -26,8 +45,8 @@ impl EventBus {
         self.tx.subscribe()
     }
     
-    pub fn publish(&self, event: SystemEvent) -> Result<(), broadcast::error::SendError<SystemEvent>> {
-        self.tx.send(event)
+    pub fn publish(&self, event: SystemEvent) -> Result<(), Box<broadcast::error::SendError<SystemEvent>>> {
+        self.tx.send(event).map(|_| ()).map_err(Box::new)
     }
 }
 
@@ -1,15 +1,12 @@
 // External imports
 use ethers::{
-    types::{Address, U256, H256, Bytes, TransactionRequest},
+    types::{H256, Bytes, TransactionRequest},
     signers::{LocalWallet, Signer},
-    middleware::SignerMiddleware,
-    providers::Provider,
 };
 
 // Standard library imports
 use std::{
     time::Duration,
-    sync::Arc,
     str::FromStr,
 };
 
@@ -19,9 +16,8 @@ use async_trait::async_trait;
 use reqwest::{Client, Error as ReqwestError};
 use serde::{Serialize, Deserialize};
 use thiserror::Error;
-use tracing::{info, warn, debug, error};
+use tracing::warn;
 use tokio::time::timeout;
-use url::Url;
 
 /// Định nghĩa các lỗi liên quan đến FlashBots
 #[derive(Debug, Error)]
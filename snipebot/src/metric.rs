@@ -1,9 +1,10 @@
 use once_cell::sync::Lazy;
 use prometheus::{Counter, Gauge, HistogramVec, IntCounterVec, Registry};
-use std::sync::Mutex;
 use std::time::Instant;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use lazy_static::lazy_static;
+use crate::gas_optimizer::NetworkCongestion;
 
 // Singleton registry
 pub static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
@@ -7,8 +7,7 @@ use serde::{Serialize, Deserialize};
 use super::config::Config;
 use super::storage::Storage;
 use super::snipebot::{SnipeBot, TokenInfo};
-use crate::error::{TransactionError, classify_blockchain_error, get_recovery_info};
-use crate::user_manager::UserManager;
+use crate::error::{TransactionError, classify_blockchain_error};
 use crate::token_status::TokenSafetyLevel;
 use crate::mempool::{PendingSwap, ArbitrageOpportunity, SandwichOpportunity};
 
@@ -123,7 +122,7 @@ impl ServiceManager {
                         // Forward request đến transaction worker
                         match tx_worker.process_transaction(tx_req).await {
                             Ok(result) => {
-                                info!("Giao dịch {} hoàn thành: {:?}", result.transaction_hash.unwrap_or_default(), result);
+                                info!("Giao dịch {} hoàn thành: {:?}", result.transaction_hash.clone().unwrap_or_default(), result);
                             },
                             Err(e) => {
                                 error!("Giao dịch thất bại: {}", e);
@@ -147,7 +146,7 @@ impl ServiceManager {
                             running: self.running,
                             service_name: "ServiceManager".to_string(),
                             uptime: self.start_time.elapsed().as_secs(),
-                            queue_size: self.rx_receiver.capacity().unwrap_or(0),
+                            queue_size: self.rx_receiver.max_capacity() - self.rx_receiver.capacity(),
                         };
                         info!("Trạng thái service: {:?}", status);
                     }
@@ -214,17 +213,14 @@ impl TransactionWorker {
                 
                 // Phân loại lỗi
                 let tx_error = match e.downcast::<TransactionError>() {
-                    Ok(tx_error) => *tx_error,
+                    Ok(tx_error) => tx_error,
                     Err(_) => classify_blockchain_error(&error_msg),
                 };
                 
                 // Ghi nhật ký chi tiết
                 warn!(
-                    transaction_id = tx_req.id,
-                    token = tx_req.token_address,
-                    error_type = ?tx_error,
-                    error = error_msg,
-                    "Phân loại lỗi giao dịch thất bại"
+                    "Phân loại lỗi giao dịch thất bại: transaction_id={} token={} error_type={:?} error={}",
+                    tx_req.id, tx_req.token_address, tx_error, error_msg
                 );
                 
                 // Tạo kết quả thất bại để trả về
@@ -241,8 +237,10 @@ impl TransactionWorker {
                         .as_secs(),
                 };
                 
-                // Nên lưu lỗi để phân tích sau
-                self.storage.add_failed_transaction(tx_req.id.clone(), &result, &format!("{:?}", tx_error)).await?;
+                // Lưu giao dịch thất bại để phân tích sau
+                let mut failed: super::storage::Transaction = result.into();
+                failed.id = tx_req.id.clone();
+                self.storage.add_transaction(failed);
                 
                 Err(Box::new(tx_error))
             }
@@ -251,6 +249,7 @@ impl TransactionWorker {
 }
 
 // Monitor Service giám sát token
+#[allow(dead_code)]
 pub struct MonitorService {
     config: Config,
     snipebot: Arc<SnipeBot>,
@@ -1,66 +1,88 @@
 use ethers::{
-    prelude::*,
-    providers::{Http, Provider, Middleware},
-    signers::{LocalWallet, Signer},
-    types::{Address, TransactionRequest, U256, H256, TransactionReceipt},
-    utils as ethers_utils,
-    contract::Contract
+    contract::Contract,
+    providers::Middleware,
+    signers::Signer,
+    types::{Address, TransactionRequest, U256},
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
 use std::str::FromStr;
-use std::sync::Arc;
-use tokio::time::{sleep, Duration};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex};
+use anyhow::{anyhow, Result};
 use log::{info, error, warn, debug};
-use super::config::Config;
-use super::storage::Storage;
-use diamond_wallet::{WalletManager, WalletInfo, SafeWalletView};
-use std::time::SystemTime;
-use std::sync::Mutex;
-use crate::chain_adapters::base::ChainAdapterEnum;
-use crate::trade::trade_logic::{TradeManager, TradeConfig, TradeResult};
-use crate::risk_analyzer::{RiskAnalyzer, BasicRiskAnalyzer};
+use super::config::{BotMode, Config};
+use super::storage::{self, Storage};
+use diamond_common::cache::Cache;
+use diamond_common::subscription::{SubscriptionManager, TradeType as SubscriptionTradeType};
+use diamond_common::user::UserManager;
+use diamond_wallet::{WalletManager, WalletInfo, SafeWalletView, WalletLease, DerivationScheme, ensure_operator_passphrase};
+use crate::chain_adapters::base::{create_chain_adapter, ChainAdapterEnum};
+use crate::chain_adapters::{chain_config_loader, chain_registry, l2_fee, GasInfo};
+use crate::chain_adapters::price::{DexPriceConfig, DexPriceOracle};
+use crate::chain_adapters::trading_adapter::{get_trading_adapter, SwapOutcome, SwapRequest, NATIVE_TOKEN};
+use crate::trade::trade_logic::{OrderType, TradeManager, TradeConfig, TradeResult, TradeType};
+use crate::risk_analyzer::{RiskAnalyzer, BasicRiskAnalyzer, RiskConfig, TokenRiskAnalysis};
 use crate::gas_optimizer::GasOptimizer;
-use crate::token_status::{TokenStatusTracker, TokenStatus, TokenPriceAlert, TokenSafetyLevel};
-use crate::mempool::{MempoolWatcher, MempoolTracker};
-use std::sync::mpsc;
-use crate::user_subscription::{SubscriptionLevel, SubscriptionTradeConfig};
-use crate::error::{TransactionError, classify_blockchain_error, get_recovery_info};
-use uuid::Uuid;
-use std::collections::HashMap;
+use crate::fee_estimator::FeeCaps;
+use crate::token_status::{TokenStatusTracker, TokenStatusTrackerBase, TokenStatus, TokenPriceAlert, TokenSafetyLevel};
+use crate::mempool::{MempoolWatcher, PendingSwap};
+use crate::service::ServiceMessage;
+use crate::types::SubscriptionLevel;
 use crate::abi_utils;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::RwLock;
-use crate::utils;
-use crate::types::{ServiceMessage, Subscription, SubscriptionLevel, TokenInfo};
-use crate::module_manager::ModuleManager;
-use crate::health_monitor::HealthMonitor;
-use crate::trade_executor::TradeExecutor;
-use crate::subscription_manager::SubscriptionManager;
-use crate::mempool::mempool_monitor::MempoolMonitor;
-use crate::ai::ai_coordinator::AICoordinator;
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::utils::safe_now;
+
+/// Số token trong cache của tracker mà vượt quá thì coi là áp lực bộ nhớ
+const MEMORY_PRESSURE_TOKEN_LIMIT: usize = 10_000;
+
+/// Thông tin token cần cho một lệnh snipe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenInfo {
+    pub address: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub router: String,
+    pub pair: Option<String>,
+}
+
+/// Tham số gas và slippage cho một lệnh snipe
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SnipeConfig {
-    pub token_address: Address,
-    pub amount_in: U256,
-    pub amount_out_min: U256,
-    pub deadline: u64,
+    pub gas_limit: u64,
+    /// Gas price (wei)
+    pub gas_price: u64,
+    /// Slippage (%)
     pub slippage: f64,
-    pub gas_price: U256,
-    pub gas_limit: U256,
-    pub chain_id: u64,
-    pub max_retries: u32,
-    pub retry_delay: u64,
+    /// Deadline tính từ lúc gửi (giây)
+    pub timeout: u64,
+    pub auto_approve: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SnipeResult {
-    pub transaction_hash: H256,
-    pub token_address: Address,
-    pub amount_in: U256,
-    pub amount_out: U256,
+    pub transaction_hash: Option<String>,
     pub success: bool,
+    pub token_address: String,
+    pub amount_in: String,
+    pub estimated_amount_out: Option<String>,
     pub error: Option<String>,
+    pub timestamp: u64,
+}
+
+impl From<SnipeResult> for storage::Transaction {
+    fn from(result: SnipeResult) -> Self {
+        let status = if result.success { "success" } else { "failed" };
+        Self {
+            id: result.transaction_hash.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            token: result.token_address,
+            amount: result.amount_in,
+            status: status.to_string(),
+            timestamp: result.timestamp,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,11 +120,23 @@ pub struct AutoTradeConfig {
     pub use_mev_strategies: bool,
     pub arbitrage_min_profit_percent: f64,
     pub max_gas_boost_percent: u64,
+    /// Độ tin cậy tối thiểu của quyết định giao dịch trong chu kỳ auto trade
+    #[serde(default = "default_ai_confidence_threshold")]
     pub ai_confidence_threshold: f64,
     pub cycle_interval_seconds: u64,
+    /// Tự thu gọn cache token khi bộ nhớ bị áp lực
+    #[serde(default = "default_auto_tuning_enabled")]
     pub auto_tuning_enabled: bool,
 }
 
+fn default_ai_confidence_threshold() -> f64 {
+    0.6
+}
+
+fn default_auto_tuning_enabled() -> bool {
+    true
+}
+
 impl Default for AutoTradeConfig {
     fn default() -> Self {
         Self {
@@ -133,9 +167,91 @@ impl Default for AutoTradeConfig {
             use_mev_strategies: true,
             arbitrage_min_profit_percent: 1.0,
             max_gas_boost_percent: 200,
-            ai_confidence_threshold: 0.6,
+            ai_confidence_threshold: default_ai_confidence_threshold(),
             cycle_interval_seconds: 3600,
-            auto_tuning_enabled: true,
+            auto_tuning_enabled: default_auto_tuning_enabled(),
+        }
+    }
+}
+
+/// Tính năng auto trade mở cho một gói subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionTierConfig {
+    pub enabled: bool,
+    pub allow_yellow_tokens: bool,
+    pub auto_sell_minutes: u64,
+    pub take_profit_percent: f64,
+    pub stop_loss_percent: f64,
+    pub use_gas_optimizer: bool,
+    pub use_sandwich_mode: bool,
+    pub use_mempool_watching: bool,
+    pub use_front_run: bool,
+    pub use_trailing_stop_loss: bool,
+    pub trailing_stop_percent: f64,
+}
+
+impl SubscriptionTierConfig {
+    fn basic() -> Self {
+        Self {
+            enabled: true,
+            allow_yellow_tokens: true,
+            auto_sell_minutes: 30,
+            take_profit_percent: 20.0,
+            stop_loss_percent: 10.0,
+            use_gas_optimizer: false,
+            use_sandwich_mode: false,
+            use_mempool_watching: false,
+            use_front_run: false,
+            use_trailing_stop_loss: false,
+            trailing_stop_percent: 15.0,
+        }
+    }
+}
+
+/// Cấu hình auto trade theo từng gói subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionTradeConfig {
+    pub free_config: SubscriptionTierConfig,
+    pub basic_config: SubscriptionTierConfig,
+    pub premium_config: SubscriptionTierConfig,
+    pub vip_config: SubscriptionTierConfig,
+}
+
+impl SubscriptionTradeConfig {
+    /// Cấu hình của gói ứng với cấp độ người dùng
+    pub fn tier_config(&self, level: SubscriptionLevel) -> &SubscriptionTierConfig {
+        match level {
+            SubscriptionLevel::Free => &self.free_config,
+            SubscriptionLevel::Basic => &self.basic_config,
+            SubscriptionLevel::Premium => &self.premium_config,
+            SubscriptionLevel::Professional => &self.vip_config,
+        }
+    }
+}
+
+impl Default for SubscriptionTradeConfig {
+    fn default() -> Self {
+        Self {
+            free_config: SubscriptionTierConfig::basic(),
+            basic_config: SubscriptionTierConfig {
+                use_gas_optimizer: true,
+                ..SubscriptionTierConfig::basic()
+            },
+            premium_config: SubscriptionTierConfig {
+                use_gas_optimizer: true,
+                use_mempool_watching: true,
+                take_profit_percent: 25.0,
+                ..SubscriptionTierConfig::basic()
+            },
+            vip_config: SubscriptionTierConfig {
+                use_gas_optimizer: true,
+                use_sandwich_mode: true,
+                use_mempool_watching: true,
+                use_front_run: true,
+                use_trailing_stop_loss: true,
+                take_profit_percent: 30.0,
+                ..SubscriptionTierConfig::basic()
+            },
         }
     }
 }
@@ -144,45 +260,38 @@ pub struct SnipeBot {
     config: Config,
     storage: Arc<Storage>,
     chain_adapter: ChainAdapterEnum,
-    wallet_manager: Arc<Mutex<WalletManager>>,
-    current_wallet_info: Option<WalletInfo>,
+    wallet_manager: Arc<WalletManager>,
+    current_wallet_address: Option<String>,
     trade_manager: RwLock<Option<Arc<Mutex<TradeManager<ChainAdapterEnum>>>>>,
     risk_analyzer: Option<Arc<dyn RiskAnalyzer>>,
-    gas_optimizer: Option<Arc<GasOptimizer>>,
+    gas_optimizer: RwLock<Option<Arc<GasOptimizer>>>,
     token_status_tracker: RwLock<Option<Arc<Mutex<TokenStatusTracker>>>>,
-    bot_mode: BotMode,
-    mempool_watcher: Option<Arc<Mutex<MempoolWatcher>>>,
-    auto_trade_config: Option<AutoTradeConfig>,
+    bot_mode: RwLock<BotMode>,
+    mempool_watcher: RwLock<Option<Arc<MempoolWatcher>>>,
+    service_tx: RwLock<Option<mpsc::Sender<ServiceMessage>>>,
+    auto_trade_config: RwLock<Option<AutoTradeConfig>>,
     subscription_config: SubscriptionTradeConfig,
-    current_user_level: SubscriptionLevel,
-    current_user: Option<WalletInfo>,
-    current_wallet_address: Option<String>,
-    status_update_sender: Option<tokio::sync::mpsc::Sender<StatusUpdate>>,
-    status_update_receiver: Option<tokio::sync::mpsc::Receiver<StatusUpdate>>,
-    mempool_tracker: Option<Arc<Mutex<MempoolTracker>>>,
+    current_user_level: RwLock<SubscriptionLevel>,
+    /// Người dùng đang chạy auto trade
+    current_user: RwLock<Option<String>>,
+    subscription_manager: RwLock<SubscriptionManager>,
+    /// Nhóm ví dùng cho giao dịch; có nhóm thì mỗi giao dịch tự chọn một ví rảnh trong nhóm
+    trade_wallet_group: RwLock<Option<String>>,
+    status_update_sender: mpsc::Sender<StatusUpdate>,
+    status_update_receiver: RwLock<Option<mpsc::Receiver<StatusUpdate>>>,
     last_token_tracker_lock_time: AtomicU64,
     last_trade_manager_lock_time: AtomicU64,
-    ai_module: Arc<RwLock<Option<AIModule>>>,
-    auto_tuner: Arc<RwLock<AutoTuner>>,
-    task_handles: RwLock<HashMap<String, JoinHandle<()>>>,
-    
-    module_manager: ModuleManager,
-    health_monitor: HealthMonitor,
-    trade_executor: TradeExecutor,
-    subscription_manager: SubscriptionManager,
-    
-    mempool_monitor: Option<Arc<Mutex<MempoolMonitor>>>,
-    ai_coordinator: Option<Arc<Mutex<AICoordinator>>>,
+    auto_trade_running: AtomicBool,
 }
 
 // Định nghĩa message cho channel
 #[derive(Debug, Clone)]
 pub enum StatusUpdate {
-    TokenUpdate { token_address: String, new_status: TokenStatus },
+    TokenUpdate { token_address: String, new_status: Box<TokenStatus> },
     PriceAlert { token_address: String, alert: TokenPriceAlert },
     MempoolTransaction { tx_hash: String, token_address: Option<String> },
     RiskUpdate { token_address: String, risk_level: TokenSafetyLevel },
-    TradeResult { result: TradeResult },
+    TradeResult { result: Box<TradeResult> },
 }
 
 // Enum để xác định nguồn gây ra deadlock
@@ -190,2915 +299,2163 @@ pub enum StatusUpdate {
 pub enum DeadlockSource {
     TokenTracker,
     TradeManager,
-    MempoolWatcher,
-    AIModule,
-    MonteEquilibrium,
+}
+
+/// Cơ hội sandwich quanh một lệnh mua đang chờ trong mempool
+#[derive(Debug, Clone)]
+pub struct SandwichOpportunity {
+    pub token_address: String,
+    pub victim_swap: PendingSwap,
+    pub potential_profit: f64,
+    pub estimated_price_impact: f64,
+}
+
+/// Chi phí ước tính cho các giao dịch phổ biến theo gas price hiện tại
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstimatedCost {
+    pub gas_price_gwei: f64,
+    pub swap_cost_eth: f64,
+    pub approve_cost_eth: f64,
+    pub swap_cost_usd: Option<f64>,
+    pub approve_cost_usd: Option<f64>,
+}
+
+/// Quyết định giao dịch cho một token
+#[derive(Debug, Clone)]
+struct AIDecision {
+    should_buy: bool,
+    confidence: f64,
+    prediction: String,
 }
 
 impl SnipeBot {
     pub async fn new(
-        config: Config, 
+        config: Config,
         storage: Arc<Storage>,
-        chain_adapter: ChainAdapterEnum
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        // Không cần tạo chain_adapter mới, sử dụng tham số đã được truyền vào
-        
-        // Khởi tạo wallet manager
-        let wallet_manager = WalletManager::new(Arc::new(config.clone())).await?;
-        let wallet_manager = Arc::new(Mutex::new(wallet_manager));
-        
-        // Lấy ví mặc định hoặc tạo mới nếu không có và auto_create_wallet=true
-        let mut wallet_manager_lock = wallet_manager.lock().await;
-        let wallet_info = match wallet_manager_lock.get_default_wallet() {
-            Some(wallet) => wallet,
-            None if config.auto_create_wallet => {
+        chain_adapter: ChainAdapterEnum,
+        wallet_manager: Arc<WalletManager>,
+    ) -> Result<Self> {
+        // Không khởi động khi passphrase ví vẫn là seed mặc định
+        ensure_operator_passphrase(&config.wallet_encryption_seed)?;
+
+        // Ưu tiên signer từ xa đã đăng ký (không nạp khóa vào tiến trình), sau đó ví đầu tiên trong kho,
+        // tạo mới nếu auto_create_wallet=true, hoặc nhập private key từ config.
+        // Adapter chỉ nhận signer của WalletManager nên mọi giao dịch đều qua chính sách chi tiêu
+        let wallets = wallet_manager.list_wallets()?;
+        let stored = wallets.iter().find(|wallet| wallet.has_private_key);
+        let address = match (wallet_manager.remote_signer_addresses().first(), stored) {
+            (Some(address), _) => *address,
+            (None, Some(view)) => Address::from_str(&view.address)?,
+            (None, None) if config.auto_create_wallet => {
                 info!("Không tìm thấy ví, tạo ví mới tự động");
-                let new_wallet = wallet_manager_lock.create_new_wallet(None)?;
-                wallet_manager_lock.save_wallets().await?;
-                info!("Đã tạo ví mới: {}", new_wallet.address);
-                new_wallet
+                let (_, address) = wallet_manager.create_wallet(None)?;
+                wallet_manager.save_wallets().await?;
+                info!("Đã tạo ví mới: {:?}", address);
+                address
+            },
+            (None, None) => {
+                let address = wallet_manager.import_private_key(&config.private_key, None)
+                    .map_err(|e| anyhow!("Private key trong cấu hình không hợp lệ: {}", e))?;
+                wallet_manager.save_wallets().await?;
+                address
             },
-            None => {
-                // Sử dụng private key từ config
-                let wallet_info = WalletInfo {
-                    address: "unknown".to_string(), // Sẽ được cập nhật sau
-                    private_key: config.private_key.clone(),
-                    mnemonic: None,
-                    chain_id: config.chain_id,
-                    created_at: 0,
-                    last_used: 0,
-                };
-                
-                // Tạo ví từ private key
-                let wallet = ethers::signers::LocalWallet::from_str(&config.private_key)?
-                    .with_chain_id(config.chain_id);
-                    
-                // Cập nhật địa chỉ
-                let mut wallet_info = wallet_info;
-                wallet_info.address = format!("{:?}", wallet.address());
-                
-                wallet_info
-            }
         };
-        
-        // Tạo ví và thiết lập vào adapter
-        let wallet = WalletManager::to_local_wallet(&wallet_info)?;
-        chain_adapter.set_wallet(wallet);
-        
-        // Giải phóng mutex
-        drop(wallet_manager_lock);
-        
-        // Tạo channel cho status updates
-        let (tx, rx) = tokio::sync::mpsc::channel(100);
-        
-        // Tạo instance mới với bot_mode mặc định là Manual
+        let wallet = wallet_manager.managed_signer(address, chain_adapter.get_config().chain_id)?;
+        let current_wallet_address = Some(format!("{:?}", address));
+        let chain_adapter = chain_adapter.with_wallet(wallet);
+
+        let (status_update_sender, status_update_receiver) = mpsc::channel(100);
         let mut bot = Self {
             config,
             storage,
             chain_adapter,
             wallet_manager,
-            current_wallet_info: Some(wallet_info),
-            bot_mode: BotMode::Manual,
+            current_wallet_address,
+            trade_manager: RwLock::new(None),
             risk_analyzer: None,
-            token_status_tracker: RwLock::new(Some(Arc::new(Mutex::new(TokenStatusTracker::new(
-                Arc::new(self.chain_adapter.get_provider().clone()),
-                vec![self.config.router_address.clone()],
-                vec![self.config.router_address.clone()],
-                self.config.weth_address.clone(),
-            )?)))),
-            mempool_watcher: None,
-            auto_trade_config: None,
+            gas_optimizer: RwLock::new(None),
+            token_status_tracker: RwLock::new(None),
+            bot_mode: RwLock::new(BotMode::Manual),
+            mempool_watcher: RwLock::new(None),
+            service_tx: RwLock::new(None),
+            auto_trade_config: RwLock::new(None),
             subscription_config: SubscriptionTradeConfig::default(),
-            current_user_level: SubscriptionLevel::Free,
-            current_user: None,
-            current_wallet_address: None,
-            status_update_sender: Some(tx),
-            status_update_receiver: Some(rx),
-            mempool_tracker: None,
-            last_token_tracker_lock_time: AtomicU64::new(0),
-            last_trade_manager_lock_time: AtomicU64::new(0),
-            ai_module: Arc::new(RwLock::new(None)),
-            auto_tuner: Arc::new(RwLock::new(AutoTuner::new())),
-            task_handles: RwLock::new(HashMap::new()),
-            
-            module_manager: ModuleManager::new(),
-            health_monitor: HealthMonitor::new(),
-            trade_executor: TradeExecutor::new(),
-            subscription_manager: SubscriptionManager::new(),
-            
-            mempool_monitor: None,
-            ai_coordinator: None,
+            current_user_level: RwLock::new(SubscriptionLevel::Free),
+            current_user: RwLock::new(None),
+            subscription_manager: RwLock::new(SubscriptionManager::new()),
+            trade_wallet_group: RwLock::new(None),
+            status_update_sender,
+            status_update_receiver: RwLock::new(Some(status_update_receiver)),
+            last_token_tracker_lock_time: AtomicU64::new(safe_now()),
+            last_trade_manager_lock_time: AtomicU64::new(safe_now()),
+            auto_trade_running: AtomicBool::new(false),
         };
-        
+
         // Khởi tạo các thành phần cần thiết cho cả hai chế độ
-        bot.initialize_components().await?;
-        
+        bot.initialize_components()?;
+
         Ok(bot)
     }
-    
+
+    // Khởi tạo risk analyzer, gas optimizer, token tracker và trade manager
+    fn initialize_components(&mut self) -> Result<()> {
+        let provider = Arc::new(self.chain_adapter.get_provider().clone());
+        let risk_config = RiskConfig {
+            config_id: uuid::Uuid::new_v4().to_string(),
+            name: "default".to_string(),
+            version: "1".to_string(),
+            created_at: std::time::SystemTime::now(),
+            max_allocation_per_token: 10.0,
+            max_tokens_in_portfolio: 20,
+            offline_mode: false,
+        };
+        let risk_analyzer: Arc<dyn RiskAnalyzer> = Arc::new(BasicRiskAnalyzer::new(provider, risk_config));
+        self.risk_analyzer = Some(risk_analyzer.clone());
+
+        let gas_optimizer = Arc::new(GasOptimizer::new(
+            U256::from(self.config.default_gas_price.saturating_mul(3)),
+            50,
+        ));
+        gas_optimizer.set_fee_caps(&self.get_fee_caps());
+        write_value(&self.gas_optimizer, Some(gas_optimizer.clone()));
+
+        // Khởi tạo TokenStatusTracker TRƯỚC khi tạo TradeManager
+        let token_tracker = Arc::new(Mutex::new(self.build_token_tracker()?));
+        write_value(&self.token_status_tracker, Some(token_tracker.clone()));
+
+        let trade_manager = self.build_trade_manager(Some(risk_analyzer), Some(gas_optimizer), Some(token_tracker));
+        write_value(&self.trade_manager, Some(Arc::new(Mutex::new(trade_manager))));
+
+        Ok(())
+    }
+
+    // Tạo TokenStatusTracker cho chain hiện tại
+    fn build_token_tracker(&self) -> Result<TokenStatusTracker> {
+        let chain_config = self.chain_adapter.get_config();
+        TokenStatusTracker::new(
+            self.chain_adapter.clone(),
+            vec![chain_config.factory_address.clone()],
+            vec![self.config.router_address.clone()],
+            self.config.weth_address.clone(),
+        )
+    }
+
+    // Tạo TradeManager cho chain hiện tại và gắn các thành phần dùng chung
+    fn build_trade_manager(
+        &self,
+        risk_analyzer: Option<Arc<dyn RiskAnalyzer>>,
+        gas_optimizer: Option<Arc<GasOptimizer>>,
+        token_tracker: Option<Arc<Mutex<TokenStatusTracker>>>,
+    ) -> TradeManager<ChainAdapterEnum> {
+        let trade_config = TradeConfig {
+            gas_limit: U256::from(self.config.default_gas_limit),
+            gas_price: U256::from(self.config.default_gas_price),
+            slippage: self.config.default_slippage,
+            timeout: 30,
+            auto_approve: true,
+            use_flashbots: false,
+            emergency_sell_gas_multiplier: 1.5,
+            router_address: self.config.router_address.clone(),
+            wrapped_native_token: self.config.weth_address.clone(),
+            max_slippage: 2.0,
+            twap_window_size: 10,
+            twap_min_samples: 5,
+            twap_update_interval: 60,
+        };
+        let mut trade_manager = TradeManager::new(Arc::new(self.chain_adapter.clone()), trade_config);
+        trade_manager.set_risk_analyzer(risk_analyzer.map(|analyzer| analyzer as Arc<dyn RiskAnalyzer + Send + Sync>));
+        trade_manager.set_gas_optimizer(gas_optimizer);
+        trade_manager.set_token_status_tracker(token_tracker);
+        if let Some(address) = self.current_wallet_address.as_deref().and_then(|a| Address::from_str(a).ok()) {
+            trade_manager.set_wallet_address(address);
+        }
+        trade_manager
+    }
+
+    // Kết nối bot với kênh của ServiceManager và khởi động mempool watcher
+    pub fn initialize_mempool_watcher(&self, service_tx: mpsc::Sender<ServiceMessage>) -> Result<()> {
+        *self.service_tx.write().map_err(|_| anyhow!("service_tx lock bị poisoned"))? = Some(service_tx.clone());
+
+        let watcher = Arc::new(MempoolWatcher::new(self.config.clone(), service_tx));
+        *self.mempool_watcher.write().map_err(|_| anyhow!("mempool_watcher lock bị poisoned"))? = Some(watcher.clone());
+
+        // Vòng theo dõi chạy nền; bot đọc dữ liệu qua tracker dùng chung của watcher
+        tokio::spawn(async move {
+            if let Err(e) = watcher.start_watching().await {
+                error!("Mempool watcher dừng: {}", e);
+            }
+        });
+        Ok(())
+    }
+
+    fn get_service_tx(&self) -> Option<mpsc::Sender<ServiceMessage>> {
+        self.service_tx.read().ok().and_then(|tx| tx.clone())
+    }
+
+    fn get_mempool_watcher(&self) -> Option<Arc<MempoolWatcher>> {
+        self.mempool_watcher.read().ok().and_then(|watcher| watcher.clone())
+    }
+
+    pub fn get_chain_adapter(&self) -> &ChainAdapterEnum {
+        &self.chain_adapter
+    }
+
+    pub fn get_gas_optimizer(&self) -> Option<Arc<GasOptimizer>> {
+        read_cloned(&self.gas_optimizer)
+    }
+
+    fn get_trade_manager(&self) -> Option<Arc<Mutex<TradeManager<ChainAdapterEnum>>>> {
+        read_cloned(&self.trade_manager)
+    }
+
+    fn get_token_tracker(&self) -> Option<Arc<Mutex<TokenStatusTracker>>> {
+        read_cloned(&self.token_status_tracker)
+    }
+
+    fn get_auto_trade_config(&self) -> Option<AutoTradeConfig> {
+        read_cloned(&self.auto_trade_config)
+    }
+
+    pub fn get_mode(&self) -> BotMode {
+        read_cloned(&self.bot_mode)
+    }
+
+    // Chuyển đổi chế độ Bot; sang Auto thì khởi động các dịch vụ theo dõi
+    pub async fn switch_mode(self: &Arc<Self>, mode: BotMode) -> Result<()> {
+        let current = self.get_mode();
+        if current == mode {
+            return Ok(());
+        }
+
+        info!("Chuyển chế độ bot từ {:?} sang {:?}", current, mode);
+        write_value(&self.bot_mode, mode.clone());
+        if mode == BotMode::Auto {
+            self.initialize_auto_mode().await?;
+        }
+        Ok(())
+    }
+
     // Thêm phương thức để thay đổi chain
-    pub async fn switch_chain(&mut self, chain_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut new_adapter = create_chain_adapter(chain_name).await?;
-        
+    pub async fn switch_chain(&mut self, chain_name: &str) -> Result<()> {
+        let new_adapter = create_chain_adapter(chain_name).await?;
+
         // Chuyển ví hiện tại sang adapter mới
-        if let Some(wallet_info) = &self.current_wallet_info {
-            let wallet = WalletManager::to_local_wallet(&wallet_info)?;
-            new_adapter.set_wallet(wallet);
-        }
-        
-        // Cập nhật adapter
-        self.chain_adapter = new_adapter;
-        
+        self.chain_adapter = match self.chain_adapter.get_wallet() {
+            Some(wallet) => {
+                let chain_id = new_adapter.get_config().chain_id;
+                new_adapter.with_wallet(wallet.clone().with_chain_id(chain_id))
+            },
+            None => new_adapter,
+        };
+
+        // Các thành phần giữ adapter cũ phải khởi tạo lại cho chain mới
+        self.initialize_components()?;
+        info!("Đã chuyển sang chain {}", chain_name);
         Ok(())
     }
-    
+
     // Thêm phương thức để thay đổi ví đang sử dụng
-    pub async fn switch_wallet(&mut self, address: &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn switch_wallet(&mut self, address: &str) -> Result<()> {
         // Xác thực địa chỉ ví trước khi tiếp tục
-        self.validate_wallet_address(address)?;
-        
-        let wallet_manager = self.wallet_manager.lock().await;
-        let wallet_info = wallet_manager.get_wallet_by_address(address)
-            .ok_or_else(|| format!("Không tìm thấy ví với địa chỉ {}", address))?;
-        
-        // Tạo bản sao để tránh giữ lock quá lâu
-        let wallet_info = wallet_info.clone();
-        drop(wallet_manager);
-        
-        // Cập nhật ví hiện tại
-        self.current_wallet_info = Some(wallet_info.clone());
+        Self::validate_wallet_address(address)?;
+
+        let wallet = self.wallet_manager.managed_signer(Address::from_str(address)?, self.chain_adapter.get_config().chain_id)?;
+        self.chain_adapter = self.chain_adapter.with_wallet(wallet);
         self.current_wallet_address = Some(address.to_string());
-        
-        // Cập nhật wallet cho adapter
-        let wallet = self.to_local_wallet(&wallet_info)?;
-        self.chain_adapter.set_wallet(wallet);
-        
+
         Ok(())
     }
-    
+
     // Tạo ví mới và chuyển đến ví đó
-    pub async fn create_and_switch_wallet(&mut self) -> Result<SafeWalletView, Box<dyn std::error::Error>> {
-        let mut wallet_manager = self.wallet_manager.lock().await;
-        
-        // Tạo ví mới (trả về view an toàn)
-        let wallet_view = wallet_manager.create_new_wallet(None)?;
-        
-        // Lưu thay đổi
-        wallet_manager.save_wallets().await?;
-        
-        // Tìm ví thực để lấy thông tin
-        let wallet_info = wallet_manager.get_wallet_by_address(&wallet_view.address)
-            .ok_or_else(|| anyhow!("Không tìm thấy ví vừa tạo"))?;
-        
-        // Tạo ví từ thông tin
-        let wallet = wallet_manager.to_local_wallet(wallet_info)?;
-        self.chain_adapter.set_wallet(wallet);
-        
-        // Lưu địa chỉ hiện tại thay vì toàn bộ WalletInfo
-        self.current_wallet_address = Some(wallet_view.address.clone());
-        
-        // Giải phóng mutex sớm
-        drop(wallet_manager);
-        
-        Ok(wallet_view)
-    }
-    
-    // Lấy danh sách ví (trả về danh sách an toàn)
-    pub async fn get_wallet_list(&self) -> Result<Vec<SafeWalletView>, Box<dyn std::error::Error>> {
-        let wallet_manager = self.wallet_manager.lock().await;
-        Ok(wallet_manager.get_wallet_list())
-    }
-    
+    pub async fn create_and_switch_wallet(&mut self, passphrase: Option<&str>) -> Result<Address> {
+        let (_, address) = self.wallet_manager.create_wallet(passphrase)?;
+        self.wallet_manager.save_wallets().await?;
+        self.switch_wallet(&format!("{:?}", address))?;
+        Ok(address)
+    }
+
+    // Lấy danh sách ví (trả về danh sách an toàn, kèm nhóm và số dư)
+    pub async fn get_wallet_list(&self) -> Result<Vec<SafeWalletView>> {
+        let provider = Arc::new(self.chain_adapter.get_provider().clone());
+        self.wallet_manager.list_wallets_with_balances(provider).await
+    }
+
+    // Giao dịch dùng ví xoay vòng trong nhóm thay cho ví hiện tại (None để quay về ví hiện tại)
+    pub fn set_trade_wallet_group(&self, group: Option<String>) {
+        write_value(&self.trade_wallet_group, group);
+    }
+
+    // Chọn và khóa một ví trong nhóm giao dịch kèm adapter ký (và lease nonce) bằng chính ví đó;
+    // None nếu bot dùng ví hiện tại
+    async fn acquire_trade_wallet(&self) -> Result<Option<(WalletLease, ChainAdapterEnum)>> {
+        let Some(group) = read_cloned(&self.trade_wallet_group) else {
+            return Ok(None);
+        };
+
+        let provider = Arc::new(self.chain_adapter.get_provider().clone());
+        let lease = self.wallet_manager.acquire_group_wallet(&group, provider).await?;
+        let signer = self.wallet_manager.managed_signer(lease.address(), self.chain_adapter.get_config().chain_id)?;
+        debug!("Giao dịch dùng ví {:?} trong nhóm {}", lease.address(), group);
+        Ok(Some((lease, self.chain_adapter.with_wallet(signer))))
+    }
+
     // Lấy địa chỉ ví hiện tại
     pub fn get_current_wallet_address(&self) -> String {
-        if let Some(wallet_info) = &self.current_wallet_info {
-            wallet_info.address.clone()
-        } else {
-            match self.chain_adapter.get_wallet() {
-                Some(wallet) => format!("{:?}", wallet.address()),
-                None => "0x0000000000000000000000000000000000000000".to_string(),
-            }
+        if let Some(address) = &self.current_wallet_address {
+            return address.clone();
+        }
+        match self.chain_adapter.get_wallet() {
+            Some(wallet) => format!("{:?}", wallet.address()),
+            None => format!("{:?}", Address::zero()),
         }
     }
-    
+
     // Import ví từ private key
-    pub async fn import_wallet_from_private_key(&self, private_key: &str) -> Result<WalletInfo, Box<dyn std::error::Error>> {
+    pub async fn import_wallet_from_private_key(&self, private_key: &str) -> Result<WalletInfo> {
         // Xác thực private key trước khi tiếp tục
-        self.validate_private_key(private_key)?;
-        
-        let mut wallet_manager = self.wallet_manager.lock().await;
-        let wallet_info = wallet_manager.import_from_private_key(private_key)?;
-        
-        // Mã hóa private key
-        let mut wallet_info_mut = wallet_info.clone();
-        wallet_info_mut.encrypt_private_key(private_key, &wallet_manager.encryption_key)?;
-        
-        // Cập nhật ví trong danh sách
-        for wallet in &mut wallet_manager.wallets {
-            if wallet.address == wallet_info.address {
-                *wallet = wallet_info_mut.clone();
-                break;
-            }
-        }
-        
-        // Lưu thay đổi
-        wallet_manager.save_wallets().await?;
-        
-        Ok(wallet_info_mut)
+        Self::validate_private_key(private_key)?;
+
+        let wallet_info = self.wallet_manager.import_from_private_key(private_key)?;
+        self.wallet_manager.save_wallets().await?;
+        Ok(wallet_info)
     }
-    
+
     // Import ví từ mnemonic
-    pub async fn import_wallet_from_mnemonic(&self, mnemonic: &str, passphrase: Option<&str>) -> Result<WalletInfo, Box<dyn std::error::Error>> {
+    pub async fn import_wallet_from_mnemonic(&self, mnemonic: &str, passphrase: Option<&str>) -> Result<WalletInfo> {
         // Xác thực mnemonic trước khi tiếp tục
-        self.validate_mnemonic(mnemonic)?;
-        
-        let mut wallet_manager = self.wallet_manager.lock().await;
-        let wallet_info = wallet_manager.import_from_mnemonic(mnemonic, passphrase)?;
-        
-        // Mã hóa mnemonic
-        let mut wallet_info_mut = wallet_info.clone();
-        wallet_info_mut.encrypt_mnemonic(mnemonic, &wallet_manager.encryption_key)?;
-        
-        // Cập nhật ví trong danh sách
-        for wallet in &mut wallet_manager.wallets {
-            if wallet.address == wallet_info.address {
-                *wallet = wallet_info_mut.clone();
-                break;
-            }
+        Self::validate_mnemonic(mnemonic)?;
+
+        let wallet_info = self.wallet_manager.import_from_mnemonic(mnemonic, passphrase)?;
+        self.wallet_manager.save_wallets().await?;
+        Ok(wallet_info)
+    }
+
+    // Xóa ví theo địa chỉ
+    pub async fn remove_wallet(&self, address: &str) -> Result<bool> {
+        let removed = self.wallet_manager.remove_wallet(address);
+        if removed {
+            self.wallet_manager.save_wallets().await?;
         }
-        
-        // Lưu thay đổi
-        wallet_manager.save_wallets().await?;
-        
-        Ok(wallet_info_mut)
+        Ok(removed)
     }
-    
-    // Lấy số dư token
-    pub async fn get_token_balance(&self, token_address: &str) -> Result<U256, Box<dyn std::error::Error>> {
-        let token_address = Address::from_str(token_address)?;
-        let wallet_address = self.get_current_wallet_address();
-        
-        self.chain_adapter.get_token_balance(token_address.to_string().as_str(), &wallet_address)
+
+    pub fn validate_wallet_address(address: &str) -> Result<()> {
+        WalletManager::validate_wallet_address(address)
+    }
+
+    pub fn validate_private_key(private_key: &str) -> Result<()> {
+        WalletManager::validate_private_key(private_key)
+    }
+
+    pub fn validate_mnemonic(mnemonic: &str) -> Result<()> {
+        WalletManager::validate_mnemonic(mnemonic)
     }
-    
+
+    // Lấy số dư token của ví hiện tại
+    pub async fn get_token_balance(&self, token_address: &str) -> Result<U256> {
+        let token_address = format!("{:?}", Address::from_str(token_address)?);
+        self.chain_adapter.get_token_balance(&token_address, &self.get_current_wallet_address()).await
+    }
+
     // Lấy số dư native token (ETH, BNB, etc.)
-    pub async fn get_native_balance(&self) -> Result<U256, Box<dyn std::error::Error>> {
-        let wallet_address = self.get_current_wallet_address();
-        self.chain_adapter.get_native_balance(wallet_address.as_str())
+    pub async fn get_native_balance(&self) -> Result<U256> {
+        self.chain_adapter.get_native_balance_verified(&self.get_current_wallet_address()).await
     }
-    
+
     // Phê duyệt token cho router
-    pub async fn approve_token(&self, token_address: &str, router: &str, amount: U256) -> Result<TransactionReceipt, Box<dyn std::error::Error>> {
+    pub async fn approve_token(&self, token_address: &str, spender: &str, amount: U256) -> Result<Option<ethers::types::TransactionReceipt>> {
+        let token_address = format!("{:?}", Address::from_str(token_address)?);
+        let spender = format!("{:?}", Address::from_str(spender)?);
+        self.chain_adapter.approve_token(&token_address, &spender, amount).await
+    }
+
+    // Kiểm tra allowance ví hiện tại đã cấp cho router
+    pub async fn check_allowance(&self, token_address: &str, spender: &str) -> Result<U256> {
         let token_address = Address::from_str(token_address)?;
-        let router_address = Address::from_str(router)?;
-        let wallet_address = self.get_current_wallet_address();
-        
-        self.chain_adapter.approve_token(token_address.to_string().as_str(), router_address.to_string().as_str(), amount)
+        let spender = Address::from_str(spender)?;
+        let owner = Address::from_str(&self.get_current_wallet_address())?;
+
+        let erc20_abi: ethers::abi::Abi = serde_json::from_str(abi_utils::get_erc20_abi())?;
+        let token_contract = Contract::new(token_address, erc20_abi, Arc::new(self.chain_adapter.get_provider().clone()));
+        let allowance: U256 = token_contract.method("allowance", (owner, spender))?.call().await?;
+        Ok(allowance)
+    }
+
+    // Swap trên chain bất kỳ (EVM hoặc non-EVM như Solana) qua adapter giao dịch chung
+    pub async fn swap_on_chain(&self, chain_name: &str, request: &SwapRequest) -> Result<SwapOutcome> {
+        let adapter = get_trading_adapter(chain_name)?;
+
+        // Địa chỉ token phải đúng định dạng của chain (hex cho EVM, base58 cho Solana)
+        for token in [&request.input_token, &request.output_token] {
+            if token != NATIVE_TOKEN && !adapter.is_valid_address(token) {
+                return Err(anyhow!("Địa chỉ token không hợp lệ trên {}: {}", chain_name, token));
+            }
+        }
+
+        let trader = adapter.trader_address()
+            .ok_or_else(|| anyhow!("Chưa có ví giao dịch trên {}", chain_name))?;
+        let balance = adapter.token_balance(&request.input_token, &trader).await?;
+        if balance < request.amount_in {
+            return Err(anyhow!("Số dư không đủ trên {}: có {}, cần {}", chain_name, balance, request.amount_in));
+        }
+
+        let outcome = adapter.swap(request).await?;
+        info!("Swap trên {} ({:?}): {} -> {}, tx {}", chain_name, adapter.chain_family(),
+            request.input_token, request.output_token, outcome.tx_id);
+        Ok(outcome)
     }
-    
+
     // Thực hiện swap/snipe
     pub async fn snipe(
         &self,
         token_info: &TokenInfo,
         amount_in: U256,
         snipe_cfg: &SnipeConfig,
-    ) -> Result<SnipeResult, Box<dyn std::error::Error>> {
+    ) -> Result<SnipeResult> {
         let token_address = &token_info.address;
         info!("Bắt đầu snipe token {} với {} ETH", token_address, ethers::utils::format_ether(amount_in));
-        
-        // Ước tính amount_out
-        let path = self.chain_adapter.get_native_to_token_path(token_address);
-        let amounts = self.chain_adapter.get_amounts_out(amount_in, path).await?;
-        let amount_out = amounts.last().ok_or("Không thể ước tính amount_out")?;
-        
-        // Tính amount_out_min với slippage
-        let slippage_factor = (1000.0 - snipe_cfg.slippage * 10.0) / 1000.0;
-        let amount_out_min = (amount_out.as_u128() as f64 * slippage_factor) as u128;
-        let amount_out_min = U256::from(amount_out_min);
-        
-        // Thiết lập deadline
-        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-        let deadline = now + snipe_cfg.timeout;
-        
-        // Lấy địa chỉ ví hiện tại
-        let wallet_address = self.get_current_wallet_address()?;
-        
-        // Sử dụng retry_blockchain_tx thay vì tự xử lý retry
+
+        // Ước tính amount_out (quorum read qua nhiều endpoint) và amount_out_min theo slippage
+        let path = self.chain_adapter.get_native_to_token_path(token_address)?;
+        let amounts = self.chain_adapter.get_amounts_out_verified(amount_in, path).await?;
+        let amount_out = *amounts.last().ok_or_else(|| anyhow!("Không thể ước tính amount_out"))?;
+        let amount_out_min = apply_slippage(amount_out, snipe_cfg.slippage);
+
+        let deadline = safe_now() + snipe_cfg.timeout;
+
+        // Lấy ví từ nhóm giao dịch (nếu có) hoặc ví hiện tại; ví nhóm bị khóa đến khi giao dịch có receipt
+        let wallet_lease = self.acquire_trade_wallet().await?;
+        let (trade_adapter, wallet_address) = match &wallet_lease {
+            Some((lease, adapter)) => (adapter, format!("{:?}", lease.address())),
+            None => (&self.chain_adapter, self.get_current_wallet_address()),
+        };
+
+        let initial_gas_price = match self.get_gas_optimizer() {
+            Some(optimizer) => match optimizer.get_optimal_gas_price(&self.chain_adapter).await {
+                Ok(price) => price,
+                Err(e) => {
+                    warn!("Không thể tối ưu gas price: {}, sử dụng giá mặc định", e);
+                    U256::from(snipe_cfg.gas_price)
+                }
+            },
+            None => U256::from(snipe_cfg.gas_price),
+        };
+
         let operation_name = format!("snipe_token_{}", token_address);
-        
-        let result = retry_blockchain_tx(
-            move || {
-                let chain_adapter = self.chain_adapter.clone();
-                let token_address = token_address.clone();
-                let amount_in = amount_in;
-                let amount_out_min = amount_out_min;
+        let result = crate::chain_adapters::retry::retry_blockchain_operation(
+            &operation_name,
+            |gas_price: Option<U256>| {
+                let gas_price = gas_price.unwrap_or(initial_gas_price);
                 let wallet_address = wallet_address.clone();
-                let deadline = deadline;
-                let gas_limit = snipe_cfg.gas_limit;
-                let gas_price_future = async {
-                    if let Some(optimizer) = chain_adapter.get_gas_optimizer() {
-                        match optimizer.get_optimal_gas_price(&*chain_adapter).await {
-                            Ok(price) => {
-                                info!("Sử dụng gas price tối ưu: {} Gwei", price.as_u128() as f64 / 1_000_000_000.0);
-                                price
-                            },
-                            Err(e) => {
-                                warn!("Không thể tối ưu gas price: {}, sử dụng giá mặc định", e);
-                                U256::from(snipe_cfg.gas_price)
-                            }
-                        }
-                    } else {
-                        U256::from(snipe_cfg.gas_price)
-                    }
-                };
-                
                 async move {
-                    // Lấy giá gas tối ưu
-                    let gas_price = gas_price_future.await;
-                    
-                    // Thực hiện giao dịch swap
-                    let receipt = chain_adapter.swap_exact_eth_for_tokens(
-                        &token_address,
+                    trade_adapter.swap_exact_eth_for_tokens(
+                        token_address,
                         amount_in,
                         amount_out_min,
                         &wallet_address,
                         deadline,
-                        Some(gas_limit),
-                        Some(gas_price),
-                    ).await?;
-                    
-                    if let Some(r) = receipt {
-                        Ok(r)
-                    } else {
-                        Err(anyhow::anyhow!("Không nhận được transaction receipt"))
-                    }
+                        Some(snipe_cfg.gas_limit),
+                        Some(gas_price.low_u64()),
+                    ).await?
+                    .ok_or_else(|| anyhow!("Không nhận được transaction receipt"))
                 }
             },
-            &operation_name,
-            Some(Box::new(move |attempt: u32| {
-                // Điều chỉnh gas và slippage cho mỗi lần retry
-                if attempt > 1 {
-                    // Tăng gas các tham số theo số lần thử
-                    let gas_multiplier = 1.0 + (attempt as f64 * 0.1); // +10% mỗi lần
-                    snipe_cfg.gas_limit = (snipe_cfg.gas_limit as f64 * gas_multiplier) as u64;
-                    snipe_cfg.gas_price = (snipe_cfg.gas_price as f64 * gas_multiplier) as u64;
-                    
-                    // Điều chỉnh slippage nếu cần
-                    if attempt > 2 {
-                        // Tăng slippage thêm 0.5% mỗi lần sau lần thứ 2
-                        snipe_cfg.slippage += 0.5;
-                        
-                        // Tính lại amount_out_min
-                        let new_slippage_factor = (1000.0 - snipe_cfg.slippage * 10.0) / 1000.0;
-                        amount_out_min = (amount_out.as_u128() as f64 * new_slippage_factor) as u128;
-                        amount_out_min = U256::from(amount_out_min);
-                    }
-                }
-            })),
-            None // Sử dụng config mặc định
+            Some(initial_gas_price),
+            self.config.auto_retry_count as usize,
         ).await;
-        
-        match result {
+        drop(wallet_lease);
+
+        let snipe_result = match &result {
             Ok(receipt) => {
-                // Xử lý kết quả thành công
-                let tx_hash = receipt.transaction_hash.to_string();
-                info!("Snipe thành công: {}", tx_hash);
-                
-                // Tạo kết quả
-                let timestamp = match SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
-                    Ok(duration) => duration.as_secs(),
-                    Err(_) => {
-                        warn!("Lỗi khi lấy thời gian hệ thống, sử dụng timestamp mặc định");
-                        0 // Sử dụng giá trị mặc định cho timestamp
-                    }
-                };
-                
-                let result = SnipeResult {
-                    transaction_hash: Some(tx_hash),
+                info!("Snipe thành công: {:?}", receipt.transaction_hash);
+                SnipeResult {
+                    transaction_hash: Some(format!("{:?}", receipt.transaction_hash)),
                     success: true,
                     token_address: token_address.clone(),
                     amount_in: ethers::utils::format_ether(amount_in),
                     estimated_amount_out: Some(amount_out.to_string()),
                     error: None,
-                    timestamp,
-                };
-                
-                // Lưu kết quả vào storage
-                self.storage.add_transaction(result.clone().into());
-                
-                Ok(result)
+                    timestamp: safe_now(),
+                }
             },
             Err(e) => {
-                // Xử lý lỗi
                 error!("Lỗi khi snipe token {}: {}", token_address, e);
-                
-                // Tạo kết quả thất bại
-                let timestamp = match SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
-                    Ok(duration) => duration.as_secs(),
-                    Err(_) => {
-                        warn!("Lỗi khi lấy thời gian hệ thống, sử dụng timestamp mặc định");
-                        0 // Sử dụng giá trị mặc định cho timestamp
-                    }
-                };
-                
-                let result = SnipeResult {
+                SnipeResult {
                     transaction_hash: None,
                     success: false,
                     token_address: token_address.clone(),
                     amount_in: ethers::utils::format_ether(amount_in),
                     estimated_amount_out: Some(amount_out.to_string()),
                     error: Some(e.to_string()),
-                    timestamp,
-                };
-                
-                // Lưu kết quả thất bại vào storage
-                self.storage.add_transaction(result.clone().into());
-                
-                Err(Box::new(e))
+                    timestamp: safe_now(),
+                }
             }
-        }
+        };
+
+        // Lưu kết quả (kể cả thất bại) vào storage
+        self.storage.add_transaction(snipe_result.clone().into());
+        result.map(|_| snipe_result)
     }
-    
-    // Kiểm tra token đã được phê duyệt chưa
-    pub async fn check_allowance(&self, token_address: &str, router: &str) -> Result<U256, Box<dyn std::error::Error>> {
-        let token_address = Address::from_str(token_address)?;
-        let router_address = Address::from_str(router)?;
+
+    // Bán một phần trăm số dư token của ví hiện tại về native token
+    pub async fn sell_token_percent(&self, token_address: &str, percent: u8, gas_price: U256) -> Result<SnipeResult> {
+        if percent == 0 || percent > 100 {
+            return Err(anyhow!("Phần trăm bán phải từ 1-100"));
+        }
+
         let wallet_address = self.get_current_wallet_address();
-        
-        let token_contract = Contract::new(
-            token_address,
-            self.chain_adapter.get_token_abi().clone(),
-            Arc::new(self.chain_adapter.get_provider().clone())
-        );
-        
-        let allowance: U256 = token_contract
-            .method("allowance", (wallet_address, router_address))?
-            .call()
-            .await?;
-            
-        Ok(allowance)
-    }
-    
-    // Thêm chức năng theo dõi token mới
-    pub async fn monitor_tokens(&self, tokens: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
-        info!("Bắt đầu theo dõi {} tokens", tokens.len());
-        
-        for token_address in tokens {
-            // Để đơn giản, chỉ in ra log
-            info!("Đang theo dõi token: {}", token_address);
+        let token_balance = self.chain_adapter.get_token_balance(token_address, &wallet_address).await
+            .map_err(|e| anyhow!("Không thể lấy số dư token: {}", e))?;
+        if token_balance.is_zero() {
+            return Err(anyhow!("Không có token để bán"));
         }
-        
-        Ok(())
-    }
-    
-    // Tạo nhiều ví HD từ một mnemonic
-    pub async fn create_hd_wallets(&mut self, mnemonic: &str, count: usize, passphrase: Option<&str>) -> Result<Vec<WalletInfo>, Box<dyn std::error::Error>> {
-        let mut wallet_manager = self.wallet_manager.lock().await;
-        
-        // Tạo ví HD
-        let wallets = wallet_manager.create_hd_wallets(mnemonic, count, passphrase)?;
-        
-        // Lưu thay đổi
-        wallet_manager.save_wallets().await?;
-        
-        Ok(wallets)
+        let amount_to_sell = token_balance.saturating_mul(U256::from(percent)) / U256::from(100);
+
+        // Tự động approve nếu allowance chưa đủ
+        let router_address = &self.config.router_address;
+        if self.check_allowance(token_address, router_address).await? < amount_to_sell {
+            info!("Tự động approve token {} cho router {}", token_address, router_address);
+            self.chain_adapter.approve_token(token_address, router_address, U256::MAX).await
+                .map_err(|e| anyhow!("Không thể approve token: {}", e))?;
+        }
+
+        let path = self.chain_adapter.get_token_to_native_path(token_address)?;
+        let amounts = self.chain_adapter.get_amounts_out_verified(amount_to_sell, path).await?;
+        let amount_out = *amounts.last().ok_or_else(|| anyhow!("Không thể ước tính amount_out"))?;
+        let amount_out_min = apply_slippage(amount_out, self.config.default_slippage);
+
+        let receipt = self.chain_adapter.swap_exact_tokens_for_eth(
+            token_address,
+            amount_to_sell,
+            amount_out_min,
+            &wallet_address,
+            safe_now() + 300,
+            Some(self.config.default_gas_limit),
+            Some(gas_price.low_u64()),
+        ).await?;
+
+        let snipe_result = SnipeResult {
+            transaction_hash: receipt.as_ref().map(|r| format!("{:?}", r.transaction_hash)),
+            success: receipt.is_some(),
+            token_address: token_address.to_string(),
+            amount_in: amount_to_sell.to_string(),
+            estimated_amount_out: Some(amount_out.to_string()),
+            error: if receipt.is_none() { Some("Giao dịch bán thất bại".into()) } else { None },
+            timestamp: safe_now(),
+        };
+        self.storage.add_transaction(snipe_result.clone().into());
+
+        Ok(snipe_result)
     }
-    
-    // Xóa ví theo địa chỉ
-    pub async fn remove_wallet(&mut self, address: &str) -> Result<bool, Box<dyn std::error::Error>> {
-        let mut wallet_manager = self.wallet_manager.lock().await;
-        
-        // Xóa ví
-        let result = wallet_manager.remove_wallet(address);
-        
-        // Lưu thay đổi nếu có xóa
-        if result {
-            wallet_manager.save_wallets().await?;
-        }
-        
-        Ok(result)
-    }
-    
-    // Cập nhật số dư cho tất cả các ví
-    pub async fn update_wallet_balances(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut wallet_manager = self.wallet_manager.lock().await;
-        
-        // Cập nhật số dư
-        wallet_manager.update_balances(self.chain_adapter.get_provider().clone()).await?;
-        
-        // Lưu thay đổi
-        wallet_manager.save_wallets().await?;
-        
-        Ok(())
+
+    // Gas price hiện tại nhân với phần trăm người dùng chọn (0 = giữ nguyên)
+    async fn gas_price_with_percent(&self, gas_price_percent: u64) -> Result<U256> {
+        let gas_price = self.chain_adapter.get_provider().get_gas_price().await?;
+        Ok(if gas_price_percent > 0 {
+            gas_price.saturating_mul(U256::from(gas_price_percent)) / U256::from(100)
+        } else {
+            gas_price
+        })
     }
-    
-    // Phương thức khởi tạo chế độ Auto
-    async fn initialize_auto_mode(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // Khởi tạo các thành phần cần thiết
-        
-        // Khởi tạo RiskAnalyzer
-        let risk_analyzer = Arc::new(BasicRiskAnalyzer::new(
-            chain_adapter.clone(),
-            weth_address.clone()
-        )?);
-        self.risk_analyzer = Some(risk_analyzer.clone());
-        
-        // Khởi tạo GasOptimizer
-        let gas_optimizer = Arc::new(GasOptimizer::new(
-            U256::from(self.config.default_gas_price * 3), 
-            50
-        ));
-        self.gas_optimizer = Some(gas_optimizer.clone());
-        
-        // Khởi tạo TokenStatusTracker TRƯỚC khi tạo TradeManager
-        let token_status_tracker = Arc::new(Mutex::new(TokenStatusTracker::new(
-            Arc::new(self.chain_adapter.get_provider().clone()),
-            vec![self.config.router_address.clone()],
-            vec![self.config.router_address.clone()],
-            self.config.weth_address.clone(),
-        )?));
-        self.token_status_tracker = RwLock::new(Some(token_status_tracker.clone()));
-        
-        // Tạo cấu hình cho TradeManager
-        let trade_config = TradeConfig {
-            // Các thông số khác...
-            max_slippage: self.config.default_slippage,
-            risk_tolerance: self.config.max_risk_score,
-            max_gas_price: self.config.default_gas_price * 3,
+
+    // Thực hiện giao dịch mua trong chế độ Manual
+    pub async fn manual_buy(&self, token_address: &str, amount: &str, gas_price_percent: u64) -> Result<SnipeResult> {
+        // Chỉ cho phép ở chế độ Manual
+        if self.get_mode() != BotMode::Manual {
+            return Err(anyhow!("Chỉ có thể sử dụng manual_buy trong chế độ Manual"));
+        }
+
+        let amount_in = ethers::utils::parse_ether(amount)?;
+        let gas_price = self.gas_price_with_percent(gas_price_percent).await?;
+
+        let token_info = TokenInfo {
+            address: token_address.to_string(),
+            symbol: "UNKNOWN".to_string(),
+            decimals: 18,
+            router: self.config.router_address.clone(),
+            pair: None,
+        };
+        let snipe_config = SnipeConfig {
+            gas_limit: self.config.default_gas_limit,
+            gas_price: gas_price.low_u64(),
+            slippage: self.config.default_slippage,
+            timeout: 300,
             auto_approve: true,
-            use_flashbots: false,
-            retry_count: self.config.auto_retry_count,
-            retry_delay_ms: 1000,
-            min_liquidity: 10000.0,
-            min_market_cap: 50000.0,
-            max_buy_tax: 10.0,
-            max_sell_tax: 10.0,
-            default_amount: "0.1".to_string(),
-            default_sell_percent: 100,
-            emergency_sell_gas_multiplier: 1.5,
         };
-        
-        // Khởi tạo TradeManager
-        let mut trade_manager = TradeManager::new(Arc::new(self.chain_adapter.clone()), trade_config);
-        
-        // Thiết lập các thành phần cho TradeManager
-        trade_manager.set_risk_analyzer(risk_analyzer.clone());
-        trade_manager.set_gas_optimizer(gas_optimizer.clone());
-        trade_manager.set_token_status_tracker(token_status_tracker.clone());
-        
-        // Lưu TradeManager
-        self.trade_manager = RwLock::new(Some(Arc::new(Mutex::new(trade_manager))));
-        
+
+        self.snipe(&token_info, amount_in, &snipe_config).await
+    }
+
+    // Thực hiện giao dịch bán trong chế độ Manual
+    pub async fn manual_sell(&self, token_address: &str, amount_percent: u8, gas_price_percent: u64) -> Result<SnipeResult> {
+        // Chỉ cho phép ở chế độ Manual
+        if self.get_mode() != BotMode::Manual {
+            return Err(anyhow!("Chỉ có thể sử dụng manual_sell trong chế độ Manual"));
+        }
+
+        let gas_price = self.gas_price_with_percent(gas_price_percent).await?;
+        self.sell_token_percent(token_address, amount_percent, gas_price).await
+    }
+
+    // Slippage mặc định của bot quy ra basis point cho adapter giao dịch chung
+    fn default_slippage_bps(&self) -> u32 {
+        (self.config.default_slippage * 100.0).round().clamp(0.0, 10_000.0) as u32
+    }
+
+    // Mua token trên chain non-EVM (như Solana) bằng số native token dạng thập phân qua adapter giao dịch chung
+    pub async fn manual_buy_on_chain(&self, chain_name: &str, token_address: &str, amount: &str, venue: Option<&str>) -> Result<SnipeResult> {
+        if self.get_mode() != BotMode::Manual {
+            return Err(anyhow!("Chỉ có thể sử dụng manual_buy trong chế độ Manual"));
+        }
+
+        let chain_id = chain_config_loader::find_chain_id(chain_name)
+            .ok_or_else(|| anyhow!("Chain {} chưa được cấu hình", chain_name))?;
+        let decimals = chain_registry::get_chain_config(chain_id)?.native_token_decimals;
+        let amount_in = u128::try_from(U256::from(ethers::utils::parse_units(amount, decimals as u32)?))
+            .map_err(|_| anyhow!("Số lượng mua quá lớn: {}", amount))?;
+
+        let mut request = SwapRequest::buy(token_address, amount_in, self.default_slippage_bps());
+        if let Some(venue) = venue {
+            request = request.with_venue(venue);
+        }
+        self.manual_swap_on_chain(chain_name, token_address, request).await
+    }
+
+    // Bán phần trăm số dư token trên chain non-EVM qua adapter giao dịch chung
+    pub async fn manual_sell_on_chain(&self, chain_name: &str, token_address: &str, amount_percent: u8, venue: Option<&str>) -> Result<SnipeResult> {
+        if self.get_mode() != BotMode::Manual {
+            return Err(anyhow!("Chỉ có thể sử dụng manual_sell trong chế độ Manual"));
+        }
+
+        let adapter = get_trading_adapter(chain_name)?;
+        let trader = adapter.trader_address()
+            .ok_or_else(|| anyhow!("Chưa có ví giao dịch trên {}", chain_name))?;
+        let balance = adapter.token_balance(token_address, &trader).await?;
+        let amount_in = balance / 100 * u128::from(amount_percent) + balance % 100 * u128::from(amount_percent) / 100;
+        if amount_in == 0 {
+            return Err(anyhow!("Không có token {} để bán trên {}", token_address, chain_name));
+        }
+
+        let mut request = SwapRequest::sell(token_address, amount_in, self.default_slippage_bps());
+        if let Some(venue) = venue {
+            request = request.with_venue(venue);
+        }
+        self.manual_swap_on_chain(chain_name, token_address, request).await
+    }
+
+    // Swap qua adapter giao dịch chung và lưu kết quả (kể cả thất bại) như lệnh snipe
+    async fn manual_swap_on_chain(&self, chain_name: &str, token_address: &str, request: SwapRequest) -> Result<SnipeResult> {
+        let result = self.swap_on_chain(chain_name, &request).await;
+        let snipe_result = match &result {
+            Ok(outcome) => SnipeResult {
+                transaction_hash: Some(outcome.tx_id.clone()),
+                success: outcome.confirmed,
+                token_address: token_address.to_string(),
+                amount_in: request.amount_in.to_string(),
+                estimated_amount_out: Some(outcome.quote.amount_out.to_string()),
+                error: (!outcome.confirmed).then(|| "Giao dịch chưa được xác nhận".to_string()),
+                timestamp: safe_now(),
+            },
+            Err(e) => SnipeResult {
+                transaction_hash: None,
+                success: false,
+                token_address: token_address.to_string(),
+                amount_in: request.amount_in.to_string(),
+                estimated_amount_out: None,
+                error: Some(e.to_string()),
+                timestamp: safe_now(),
+            },
+        };
+
+        self.storage.add_transaction(snipe_result.clone().into());
+        result.map(|_| snipe_result)
+    }
+
+    // Phân tích token: trạng thái từ tracker cộng với phân tích rủi ro
+    pub async fn analyze_token(&self, token_address: &str) -> Result<(TokenStatus, TokenRiskAnalysis)> {
+        let analyzer = self.risk_analyzer.as_ref()
+            .ok_or_else(|| anyhow!("Risk Analyzer chưa được khởi tạo"))?;
+        let risk_analysis = analyzer.analyze_token(Address::from_str(token_address)?).await?;
+
+        let mut token_status = self.get_token_status(token_address).await?;
+        if let Some(tracker) = self.get_token_tracker() {
+            let tracker = tracker.lock().await;
+            token_status.safety_level = tracker.classify_token(&token_status, Some(&risk_analysis));
+        }
+        token_status.is_contract_verified = risk_analysis.is_verified;
+
+        // Cập nhật số lượng giao dịch đang chờ
+        if let Some(watcher) = self.get_mempool_watcher() {
+            token_status.pending_tx_count = watcher.count_pending_txs_for_token(token_address).await;
+        }
+
+        Ok((token_status, risk_analysis))
+    }
+
+    // Lấy thông tin trạng thái token, thêm vào danh sách theo dõi nếu chưa có
+    pub async fn get_token_status(&self, token_address: &str) -> Result<TokenStatus> {
+        let tracker = self.get_token_tracker()
+            .ok_or_else(|| anyhow!("Token tracker chưa được khởi tạo"))?;
+        let mut tracker = tracker.lock().await;
+
+        let known = tracker.get_token(token_address).await
+            .map_err(|e| anyhow!("Không thể đọc trạng thái token: {}", e))?;
+        match known {
+            Some(status) => Ok(status),
+            None => {
+                tracker.add_token(token_address).await?;
+                tracker.update_token_status(token_address).await
+            }
+        }
+    }
+
+    // Thêm auto trade config
+    pub fn set_auto_trade_config(&self, config: AutoTradeConfig) {
+        write_value(&self.auto_trade_config, Some(config));
+    }
+
+    // Giao dịch tự động theo mức an toàn của token và chiến lược đã cấu hình
+    pub async fn auto_trade(&self, token_address: &str, amount: &str, action: &str) -> Result<TradeResult> {
+        if self.get_mode() != BotMode::Auto {
+            return Err(anyhow!("Bot không ở chế độ Auto"));
+        }
+        let config = self.get_auto_trade_config().unwrap_or_default();
+
+        let (token_status, _) = self.analyze_token(token_address).await?;
+
+        // Kiểm tra giữ lại reserve
+        if action == "buy" && !self.check_reserve_balance().await? {
+            return Err(anyhow!("Không đủ reserve balance. Duy trì tối thiểu {}% số dư", config.reserve_percent));
+        }
+
+        match (token_status.safety_level, action) {
+            // Không giao dịch token đỏ
+            (TokenSafetyLevel::Red, _) => {
+                return Err(anyhow!("Không giao dịch token có mức độ nguy hiểm cao"));
+            },
+            (TokenSafetyLevel::Yellow, "buy") => {
+                if !config.yellow_token_strategy.buy_on_large_orders {
+                    return Err(anyhow!("Chiến lược mua token vàng không được kích hoạt"));
+                }
+                if !self.check_large_orders_in_mempool(token_address, config.yellow_token_strategy.min_large_order_usd).await? {
+                    return Err(anyhow!("Không phát hiện lệnh mua lớn trên mempool cho token này"));
+                }
+            },
+            (TokenSafetyLevel::Yellow, "sandwich") if !config.yellow_token_strategy.use_sandwich_mode => {
+                return Err(anyhow!("Chế độ sandwich không được kích hoạt cho token vàng"));
+            },
+            (TokenSafetyLevel::Green, "buy") if config.green_token_strategy.front_run_orders => {
+                let large_orders = self.get_large_orders_in_mempool(token_address, config.green_token_strategy.min_order_size_usd).await?;
+                if let Some(order) = large_orders.first() {
+                    return self.front_run_transaction(order, amount).await;
+                }
+            },
+            (TokenSafetyLevel::Green, "tsl") => {
+                if !config.green_token_strategy.use_trailing_stop {
+                    return Err(anyhow!("Trailing stop loss không được kích hoạt cho token xanh"));
+                }
+                return self.activate_trailing_stop_loss(token_address, config.green_token_strategy.trailing_stop_percent).await;
+            },
+            (TokenSafetyLevel::Green, "sandwich") if !config.green_token_strategy.use_sandwich_mode => {
+                return Err(anyhow!("Chế độ sandwich không được kích hoạt cho token xanh"));
+            },
+            _ => {}
+        }
+
+        let gas_price = self.chain_adapter.get_provider().get_gas_price().await?;
+        let (snipe_result, trade_type) = match action {
+            "buy" => {
+                let amount_in = ethers::utils::parse_ether(amount)
+                    .map_err(|e| anyhow!("Số lượng không hợp lệ: {}", e))?;
+                let token_info = TokenInfo {
+                    address: token_address.to_string(),
+                    symbol: token_status.symbol.clone(),
+                    decimals: token_status.decimals,
+                    router: self.config.router_address.clone(),
+                    pair: token_status.pair_address.clone(),
+                };
+                let snipe_config = SnipeConfig {
+                    gas_limit: self.config.default_gas_limit,
+                    gas_price: gas_price.low_u64(),
+                    slippage: self.config.default_slippage,
+                    timeout: 300,
+                    auto_approve: true,
+                };
+                (self.snipe(&token_info, amount_in, &snipe_config).await?, TradeType::Buy)
+            },
+            "sell" | "sandwich" => {
+                let percent = amount.parse::<u8>().unwrap_or(100);
+                (self.sell_token_percent(token_address, percent, gas_price).await?, TradeType::Sell)
+            },
+            _ => return Err(anyhow!("Hành động không hỗ trợ: {}", action)),
+        };
+
+        // Chi phí giao dịch (thực thi + phí dữ liệu L1 trên rollup), None khi không có giá native token
+        let gas_cost_usd = match snipe_result.transaction_hash {
+            Some(_) => {
+                let cost = self.estimate_swap_cost(self.config.default_gas_limit, gas_price).await;
+                self.native_to_usd(cost).await
+            },
+            None => None,
+        };
+
+        Ok(TradeResult {
+            success: snipe_result.success,
+            tx_hash: snipe_result.transaction_hash,
+            amount_in: snipe_result.amount_in,
+            amount_out: snipe_result.estimated_amount_out,
+            price_per_token: Some(token_status.price_usd),
+            gas_used: None,
+            gas_price: gas_price.low_u64(),
+            timestamp: snipe_result.timestamp,
+            error: snipe_result.error,
+            trade_type,
+            token_address: token_address.to_string(),
+            victim_tx_hash: None,
+            profit_usd: None,
+            gas_cost_usd,
+        })
+    }
+
+    // Kiểm tra số dư native còn đủ tỷ lệ reserve theo auto trade config
+    async fn check_reserve_balance(&self) -> Result<bool> {
+        let reserve_percent = self.get_auto_trade_config()
+            .map(|config| config.reserve_percent)
+            .unwrap_or(25.0);
+        let balance = self.get_native_balance().await?;
+        let min_reserve = U256::from(self.config.default_gas_limit)
+            .saturating_mul(U256::from(self.config.default_gas_price))
+            .saturating_mul(U256::from(reserve_percent.max(0.0) as u64));
+
+        if balance < min_reserve {
+            if let Some(tx_sender) = self.get_service_tx() {
+                let balance_ratio = if min_reserve.is_zero() { 100.0 } else {
+                    u256_to_f64(balance) / u256_to_f64(min_reserve) * reserve_percent
+                };
+                let _ = tx_sender.send(ServiceMessage::ReserveBalanceAlert { current_percent: balance_ratio }).await;
+            }
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    // Giá native token (USD) từ DEX của chain hiện tại; None khi chain không có pool stablecoin
+    async fn get_native_price(&self) -> Result<Option<f64>> {
+        let chain_id = self.chain_adapter.get_config().chain_id;
+        let adapter = chain_registry::get_adapter(chain_id)?;
+        let chain = chain_registry::get_chain_config(chain_id)?;
+        DexPriceOracle::new(adapter, DexPriceConfig::from_chain_config(&chain)).native_price_usd().await
+    }
+
+    // Lấy danh sách các lệnh mua lớn đang chờ trong mempool
+    async fn get_large_orders_in_mempool(&self, token_address: &str, min_amount_usd: f64) -> Result<Vec<PendingSwap>> {
+        let Some(watcher) = self.get_mempool_watcher() else {
+            return Ok(Vec::new());
+        };
+        let pending = watcher.check_mempool().await
+            .map_err(|e| anyhow!("Không thể đọc mempool: {}", e))?;
+
+        let mut large_orders: Vec<PendingSwap> = pending.into_iter()
+            .filter(|swap| swap.is_buy && swap.amount_usd >= min_amount_usd && swap.token_address.eq_ignore_ascii_case(token_address))
+            .collect();
+        large_orders.sort_by(|a, b| b.amount_usd.total_cmp(&a.amount_usd));
+        Ok(large_orders)
+    }
+
+    // Front-run một giao dịch
+    async fn front_run_transaction(&self, pending_swap: &PendingSwap, amount: &str) -> Result<TradeResult> {
+        info!("Front-running giao dịch: {}", pending_swap.tx_hash);
+
+        // Tính gas cao hơn giao dịch gốc
+        let front_run_gas = pending_swap.gas_price * 110 / 100; // Tăng 10%
+        let amount_in = ethers::utils::parse_ether(amount)
+            .map_err(|e| anyhow!("Số lượng không hợp lệ: {}", e))?;
+        let token_address = &pending_swap.token_address;
+
+        // Không có giá native token thì không định giá được lệnh của nạn nhân và chi phí gas
+        let native_price = self.get_native_price().await.ok().flatten()
+            .ok_or_else(|| anyhow!("Không có giá native token cho chain hiện tại, bỏ qua front-run"))?;
+        let victim_in = U256::from((pending_swap.amount_usd / native_price * 1e18) as u128);
+
+        let path = self.chain_adapter.get_native_to_token_path(token_address)?;
+        let amounts = self.chain_adapter.get_amounts_out(amount_in, path.clone()).await?;
+        let amount_out = *amounts.last().ok_or_else(|| anyhow!("Không thể tính toán amount out"))?;
+        let amount_out_min = apply_slippage(amount_out, 1.0);
+        let combined = self.chain_adapter.get_amounts_out(amount_in.saturating_add(victim_in), path).await?;
+        let combined_out = *combined.last().ok_or_else(|| anyhow!("Không thể tính toán amount out"))?;
+
+        // Lợi nhuận kỳ vọng sau khi trừ chi phí mua + bán (gồm cả phí dữ liệu L1 trên rollup)
+        // phải vượt lợi nhuận tối thiểu
+        let expected_profit = estimate_front_run_profit(amount_in, victim_in, amount_out, combined_out)
+            .ok_or_else(|| anyhow!("Không thể ước tính lợi nhuận front-run từ báo giá router"))?;
+        let trade_cost = self.estimate_swap_cost(self.config.default_gas_limit, front_run_gas).await;
+        let round_trip_cost = u256_to_f64(trade_cost.saturating_mul(U256::from(2)));
+        let net_profit_usd = (expected_profit - round_trip_cost) / 1e18 * native_price;
+        let min_profit_percent = self.get_auto_trade_config()
+            .map(|config| config.arbitrage_min_profit_percent)
+            .unwrap_or(1.0);
+        let min_profit_usd = u256_to_f64(amount_in) / 1e18 * native_price * min_profit_percent / 100.0;
+        if net_profit_usd < min_profit_usd {
+            return Err(anyhow!("Lợi nhuận kỳ vọng sau chi phí gas {:.2} USD thấp hơn mức tối thiểu {:.2} USD, bỏ qua front-run",
+                net_profit_usd, min_profit_usd));
+        }
+
+        let receipt = self.chain_adapter.swap_exact_eth_for_tokens(
+            token_address,
+            amount_in,
+            amount_out_min,
+            &self.get_current_wallet_address(),
+            safe_now() + 300,
+            None,
+            Some(front_run_gas.low_u64()),
+        ).await?;
+
+        Ok(TradeResult {
+            success: receipt.is_some(),
+            tx_hash: receipt.as_ref().map(|r| format!("{:?}", r.transaction_hash)),
+            amount_in: ethers::utils::format_ether(amount_in),
+            amount_out: Some(amount_out.to_string()),
+            price_per_token: None,
+            gas_used: receipt.as_ref().and_then(|r| r.gas_used).map(|gas| gas.low_u64()),
+            gas_price: front_run_gas.low_u64(),
+            timestamp: safe_now(),
+            error: if receipt.is_none() { Some("Không nhận được receipt".to_string()) } else { None },
+            trade_type: TradeType::Frontrun,
+            token_address: token_address.clone(),
+            victim_tx_hash: Some(pending_swap.tx_hash.clone()),
+            profit_usd: Some(net_profit_usd),
+            gas_cost_usd: Some(u256_to_f64(trade_cost) / 1e18 * native_price),
+        })
+    }
+
+    // Kích hoạt trailing stop loss qua lệnh chờ của trade manager
+    async fn activate_trailing_stop_loss(&self, token_address: &str, trailing_percent: f64) -> Result<TradeResult> {
+        info!("Kích hoạt trailing stop loss cho token {} với mức {}%", token_address, trailing_percent);
+
+        let token_status = self.get_token_status(token_address).await?;
+        let current_price = token_status.price_usd;
+        let stop_price = current_price * (1.0 - trailing_percent / 100.0);
+
+        let trade_manager = self.get_trade_manager()
+            .ok_or_else(|| anyhow!("Trade manager chưa được khởi tạo"))?;
+        trade_manager.lock().await
+            .create_limit_order(token_address, OrderType::TrailingStop, stop_price, 100, 7 * 24 * 3600).await
+            .map_err(|e| anyhow!("Không thể tạo trailing stop: {}", e))?;
+
+        Ok(TradeResult {
+            success: true,
+            tx_hash: None,
+            amount_in: "0".to_string(),
+            amount_out: None,
+            price_per_token: Some(current_price),
+            gas_used: None,
+            gas_price: 0,
+            timestamp: safe_now(),
+            error: None,
+            trade_type: TradeType::Sell,
+            token_address: token_address.to_string(),
+            victim_tx_hash: None,
+            profit_usd: None,
+            gas_cost_usd: None,
+        })
+    }
+
+    // Thiết lập take profit và stop loss
+    pub async fn set_take_profit_stop_loss(&self, token_address: &str, take_profit_percent: f64, stop_loss_percent: f64) -> Result<()> {
+        let trade_manager = self.get_trade_manager()
+            .ok_or_else(|| anyhow!("Trade manager chưa được khởi tạo"))?;
+        let current_price = self.get_token_status(token_address).await?.price_usd;
+
+        let mut manager = trade_manager.lock().await;
+        manager.create_limit_order(token_address, OrderType::TakeProfit, current_price * (1.0 + take_profit_percent / 100.0), 100, 7 * 24 * 3600).await
+            .map_err(|e| anyhow!("Không thể tạo lệnh take profit: {}", e))?;
+        manager.create_limit_order(token_address, OrderType::StopLoss, current_price * (1.0 - stop_loss_percent / 100.0), 100, 7 * 24 * 3600).await
+            .map_err(|e| anyhow!("Không thể tạo lệnh stop loss: {}", e))?;
+
+        Ok(())
+    }
+
+    // Thiết lập cấp độ người dùng
+    pub fn set_user_level(&self, level: SubscriptionLevel) {
+        write_value(&self.current_user_level, level);
+
+        // Cập nhật cấu hình auto trade dựa trên cấp độ người dùng
+        self.update_auto_trade_config_for_subscription();
+    }
+
+    // Cập nhật cấu hình auto trade dựa trên cấp độ người dùng
+    fn update_auto_trade_config_for_subscription(&self) {
+        let mut auto_trade_config = AutoTradeConfig::default();
+
+        let level = self.get_current_subscription_level();
+        match level {
+            SubscriptionLevel::Free => {
+                let free_config = &self.subscription_config.free_config;
+
+                // Cập nhật cấu hình cho Free user
+                auto_trade_config.enabled = free_config.enabled;
+                auto_trade_config.red_token_strategy = false; // Không bao giờ giao dịch token đỏ
+
+                // Chỉ mua token vàng và xanh, bán sau 30 phút
+                auto_trade_config.yellow_token_strategy.buy_on_large_orders = free_config.allow_yellow_tokens;
+                auto_trade_config.yellow_token_strategy.sell_after_minutes = free_config.auto_sell_minutes;
+                auto_trade_config.yellow_token_strategy.use_sandwich_mode = false;
+                auto_trade_config.yellow_token_strategy.use_mempool_data = false;
+
+                // Vô hiệu hóa các chiến lược nâng cao
+                auto_trade_config.green_token_strategy.front_run_orders = false;
+                auto_trade_config.green_token_strategy.use_trailing_stop = false;
+                auto_trade_config.green_token_strategy.use_sandwich_mode = false;
+
+                // Vô hiệu hóa MEV
+                auto_trade_config.use_mev_strategies = false;
+            },
+            SubscriptionLevel::Basic | SubscriptionLevel::Premium => {
+                let premium_config = self.subscription_config.tier_config(level);
+
+                // Cập nhật cấu hình cho Basic/Premium user theo gói tương ứng
+                auto_trade_config.enabled = premium_config.enabled;
+                auto_trade_config.red_token_strategy = false; // Không bao giờ giao dịch token đỏ
+
+                auto_trade_config.yellow_token_strategy.buy_on_large_orders = premium_config.allow_yellow_tokens;
+                auto_trade_config.yellow_token_strategy.sell_after_minutes = premium_config.auto_sell_minutes;
+                auto_trade_config.yellow_token_strategy.take_profit_percent = premium_config.take_profit_percent;
+                auto_trade_config.yellow_token_strategy.stop_loss_percent = premium_config.stop_loss_percent;
+                auto_trade_config.yellow_token_strategy.use_sandwich_mode = false;
+                auto_trade_config.yellow_token_strategy.use_mempool_data = premium_config.use_mempool_watching;
+
+                // Gas optimizer cho Basic/Premium users
+                if premium_config.use_gas_optimizer && self.get_gas_optimizer().is_some() {
+                    auto_trade_config.max_gas_boost_percent = 150;
+                }
+
+                // Vô hiệu hóa front-run và sandwich
+                auto_trade_config.green_token_strategy.front_run_orders = false;
+                auto_trade_config.green_token_strategy.use_sandwich_mode = false;
+
+                // Vô hiệu hóa MEV hoàn toàn
+                auto_trade_config.use_mev_strategies = false;
+            },
+            SubscriptionLevel::Professional => {
+                let vip_config = &self.subscription_config.vip_config;
+
+                // Cập nhật cấu hình cho VIP user - mở tất cả các tính năng
+                auto_trade_config.enabled = vip_config.enabled;
+                auto_trade_config.red_token_strategy = false; // Không bao giờ giao dịch token đỏ
+
+                auto_trade_config.yellow_token_strategy.buy_on_large_orders = vip_config.allow_yellow_tokens;
+                auto_trade_config.yellow_token_strategy.use_sandwich_mode = vip_config.use_sandwich_mode;
+                auto_trade_config.yellow_token_strategy.use_mempool_data = vip_config.use_mempool_watching;
+                auto_trade_config.yellow_token_strategy.take_profit_percent = vip_config.take_profit_percent;
+                auto_trade_config.yellow_token_strategy.stop_loss_percent = vip_config.stop_loss_percent;
+
+                // Kích hoạt front-run và chiến lược nâng cao
+                auto_trade_config.green_token_strategy.front_run_orders = vip_config.use_front_run;
+                auto_trade_config.green_token_strategy.use_trailing_stop = vip_config.use_trailing_stop_loss;
+                auto_trade_config.green_token_strategy.trailing_stop_percent = vip_config.trailing_stop_percent;
+                auto_trade_config.green_token_strategy.use_sandwich_mode = vip_config.use_sandwich_mode;
+                auto_trade_config.green_token_strategy.take_profit_percent = vip_config.take_profit_percent;
+
+                // Kích hoạt tính năng MEV
+                auto_trade_config.use_mev_strategies = true;
+                auto_trade_config.max_gas_boost_percent = 200;
+            }
+        }
+
+        write_value(&self.auto_trade_config, Some(auto_trade_config));
+
+        // Giới hạn phí mới của gói phải áp dụng cho gas optimizer của bot
+        let fee_caps = self.get_fee_caps();
+        if let Some(optimizer) = self.get_gas_optimizer() {
+            optimizer.set_fee_caps(&fee_caps);
+        }
+    }
+
+    // Phương thức thiết lập cấu hình subscription
+    pub fn set_subscription_config(&mut self, config: SubscriptionTradeConfig) {
+        self.subscription_config = config;
+        self.update_auto_trade_config_for_subscription();
+    }
+
+    // Phương thức lấy thông tin cấp độ của người dùng hiện tại
+    pub fn get_current_subscription_level(&self) -> SubscriptionLevel {
+        read_cloned(&self.current_user_level)
+    }
+
+    // Phương thức lấy cấu hình auto trade cho cấp độ hiện tại
+    pub fn get_subscription_config(&self) -> &SubscriptionTradeConfig {
+        &self.subscription_config
+    }
+
+    // Giới hạn phí gas theo auto trade config và gói subscription hiện tại
+    pub fn get_fee_caps(&self) -> FeeCaps {
+        let level = diamond_common::user::SubscriptionLevel::from(self.get_current_subscription_level());
+        let max_gas_price_gwei = self.subscription_manager.read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get_subscription_config(&level)
+            .max_gas_price_gwei;
+
+        FeeCaps::new(
+            self.get_auto_trade_config().map(|config| config.max_gas_boost_percent),
+            Some(max_gas_price_gwei),
+        )
+    }
+
+    /// Chi phí (wei) của một lệnh swap qua router: thực thi + phí dữ liệu L1 trên rollup
+    async fn estimate_swap_cost(&self, gas_limit: u64, gas_price: U256) -> U256 {
+        let execution_cost = gas_price.saturating_mul(U256::from(gas_limit));
+        let router = match Address::from_str(&self.config.router_address) {
+            Ok(router) => router,
+            Err(_) => return execution_cost,
+        };
+        let swap_tx = l2_fee::sample_tx(router, l2_fee::SWAP_CALLDATA_BYTES)
+            .gas(gas_limit)
+            .gas_price(gas_price);
+
+        match self.chain_adapter.estimate_total_cost(&swap_tx).await {
+            Ok(estimate) => {
+                if estimate.fee_model.has_l1_fee() {
+                    debug!("Phí dữ liệu L1 ({}): {} wei / tổng {} wei",
+                        estimate.fee_model, estimate.l1_data_fee, estimate.total_fee);
+                }
+                estimate.total_fee
+            },
+            Err(e) => {
+                warn!("Không thể ước tính phí dữ liệu L1, chỉ tính phí thực thi: {}", e);
+                execution_cost
+            }
+        }
+    }
+
+    /// Quy đổi wei sang USD theo giá native token; None khi chain không có giá
+    async fn native_to_usd(&self, wei: U256) -> Option<f64> {
+        let native_price = self.get_native_price().await.ok().flatten()?;
+        let eth: f64 = ethers::utils::format_ether(wei).parse().ok()?;
+        Some(eth * native_price)
+    }
+
+    // Thêm chức năng theo dõi token mới
+    pub async fn monitor_tokens(&self, tokens: Vec<String>) -> Result<()> {
+        info!("Bắt đầu theo dõi {} tokens", tokens.len());
+
+        let tracker = self.get_token_tracker()
+            .ok_or_else(|| anyhow!("Token tracker chưa được khởi tạo"))?;
+        let mut tracker = tracker.lock().await;
+        for token_address in tokens {
+            Address::from_str(&token_address)
+                .map_err(|_| anyhow!("Địa chỉ token không hợp lệ: {}", token_address))?;
+            if !tracker.is_tracking(&token_address) {
+                tracker.add_token(&token_address).await?;
+            }
+            info!("Đang theo dõi token: {}", token_address);
+        }
+
+        Ok(())
+    }
+
+    // Tạo nhiều ví HD từ một mnemonic theo đường dẫn m/44'/60'/0'/0/i
+    pub async fn create_hd_wallets(&self, mnemonic: &str, count: usize, passphrase: Option<&str>) -> Result<Vec<WalletInfo>> {
+        Self::validate_mnemonic(mnemonic)?;
+
+        let count = u32::try_from(count).map_err(|_| anyhow!("Số lượng ví quá lớn: {}", count))?;
+        let wallets = self.wallet_manager.derive_hd_wallets(mnemonic, passphrase, &DerivationScheme::Bip44, 0..1, 0..count)?;
+        self.wallet_manager.save_wallets().await?;
+
+        Ok(wallets)
+    }
+
+    // Tạo ví mới (không chuyển sang ví đó)
+    pub async fn create_new_wallet(&self, passphrase: Option<&str>) -> Result<SafeWalletView> {
+        let (_, address) = self.wallet_manager.create_wallet(passphrase)?;
+        self.wallet_manager.save_wallets().await?;
+
+        let address = format!("{:?}", address);
+        self.wallet_manager.list_wallets()?
+            .into_iter()
+            .find(|wallet| wallet.address.eq_ignore_ascii_case(&address))
+            .ok_or_else(|| anyhow!("Không tìm thấy ví vừa tạo: {}", address))
+    }
+
+    // Cập nhật số dư native cho tất cả các ví
+    pub async fn update_wallet_balances(&self) -> Result<Vec<SafeWalletView>> {
+        let wallets = self.get_wallet_list().await?;
+        for wallet in &wallets {
+            debug!("Số dư ví {}: {}", wallet.address, wallet.balance.as_deref().unwrap_or("?"));
+        }
+        Ok(wallets)
+    }
+
+    // Chain adapter theo chain_id; hiện bot chỉ giữ adapter của chain đang dùng
+    pub fn get_chain_adapter_by_id(&self, chain_id: u64) -> Option<&ChainAdapterEnum> {
+        (self.chain_adapter.get_config().chain_id == chain_id).then_some(&self.chain_adapter)
+    }
+
+    // Cấu hình lại gas optimizer và gắn bản mới cho trade manager
+    pub async fn configure_gas_optimizer(
+        &self,
+        max_gas_price: Option<U256>,
+        max_boost_percent: Option<u64>,
+        sample_interval: Option<u64>,
+    ) -> Result<()> {
+        if self.get_gas_optimizer().is_none() {
+            return Err(anyhow!("Gas optimizer chưa được khởi tạo"));
+        }
+
+        let mut optimizer = GasOptimizer::new(U256::from(self.config.default_gas_price.saturating_mul(3)), 50);
+        optimizer.set_chain_id(self.chain_adapter.get_config().chain_id);
+        if let Some(max_price) = max_gas_price {
+            optimizer.set_gas_price_limit(max_price);
+        }
+        if let Some(boost) = max_boost_percent {
+            optimizer.set_max_boost_percent(boost);
+        }
+        if let Some(interval) = sample_interval {
+            optimizer.set_sample_interval(interval);
+        }
+        optimizer.set_fee_caps(&self.get_fee_caps());
+
+        let optimizer = Arc::new(optimizer);
+        write_value(&self.gas_optimizer, Some(optimizer.clone()));
+        if let Some(trade_manager) = self.get_trade_manager() {
+            trade_manager.lock().await.set_gas_optimizer(Some(optimizer));
+        }
+
+        info!("Đã cấu hình gas optimizer thành công");
+        Ok(())
+    }
+
+    // Phương thức khởi tạo chế độ Auto
+    async fn initialize_auto_mode(self: &Arc<Self>) -> Result<()> {
+        if self.get_token_tracker().is_none() || self.get_trade_manager().is_none() {
+            return Err(anyhow!("Token tracker và trade manager phải được khởi tạo trước chế độ Auto"));
+        }
+
         // Khởi động các dịch vụ theo dõi
-        self.start_monitoring_services().await?;
-        
+        self.start_monitoring_services();
+        self.start_health_check();
+
         // Bắt đầu xử lý các status update
-        self.process_status_updates().await;
-        
+        self.process_status_updates();
+
         Ok(())
     }
-    
-    // Khởi động các dịch vụ theo dõi
-    async fn start_monitoring_services(&self) -> Result<(), Box<dyn std::error::Error>> {
-        // Chỉ chạy nếu là Auto mode
-        if self.bot_mode != BotMode::Auto {
-            return Ok(());
-        }
-        
-        // Khởi động theo dõi token status nếu có
-        if let Some(token_tracker) = &self.token_status_tracker {
-            let token_tracker_weak = Arc::downgrade(token_tracker); // Sử dụng Weak thay vì Arc mạnh
-            let trade_manager_weak = self.trade_manager.as_ref().map(Arc::downgrade); // Weak reference
-            
-            // Task theo dõi token status
-            tokio::spawn(async move {
-                let update_interval = tokio::time::Duration::from_secs(30);
-                let mut interval = tokio::time::interval(update_interval);
-                
-                // Task ID để theo dõi
-                let task_id = Uuid::new_v4().to_string();
-                debug!("Khởi động task theo dõi token status với ID: {}", task_id);
-                
-                let mut last_success_time = std::time::Instant::now();
-                let mut consecutive_failures = 0;
-                
-                loop {
-                    interval.tick().await;
-                    
-                    // Chỉ tiếp tục nếu có thể nâng cấp weak reference
-                    let token_tracker_arc = match token_tracker_weak.upgrade() {
-                        Some(arc) => arc,
-                        None => {
-                            debug!("Task {} kết thúc: token_tracker đã bị giải phóng", task_id);
-                            break;
-                        }
-                    };
-                    
-                    // Sử dụng try_lock với timeout để tránh deadlock
-                    let token_tracker_result = tokio::time::timeout(
-                        tokio::time::Duration::from_secs(5),
-                        async {
-                            token_tracker_arc.try_lock()
-                        }
-                    ).await;
-                    
-                    match token_tracker_result {
-                        Ok(Ok(mut tracker)) => {
-                            // Cập nhật trạng thái token
+
+    // Khởi động các dịch vụ theo dõi; các task tự dừng khi bot rời chế độ Auto hoặc bị giải phóng
+    fn start_monitoring_services(self: &Arc<Self>) {
+        // Task theo dõi token status
+        let bot_weak = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(30));
+            let task_id = uuid::Uuid::new_v4().to_string();
+            debug!("Khởi động task theo dõi token status với ID: {}", task_id);
+
+            let mut last_success_time = Instant::now();
+            let mut consecutive_failures = 0u32;
+
+            loop {
+                interval.tick().await;
+
+                let Some(bot) = bot_weak.upgrade() else {
+                    debug!("Task {} kết thúc: bot đã bị giải phóng", task_id);
+                    break;
+                };
+                if bot.get_mode() != BotMode::Auto {
+                    debug!("Task {} kết thúc: bot rời chế độ Auto", task_id);
+                    break;
+                }
+
+                if let Some(tracker) = bot.get_token_tracker() {
+                    // Dùng timeout khi lấy lock để tránh deadlock
+                    match with_timeout(tracker.lock(), Duration::from_secs(5)).await {
+                        Ok(mut tracker) => {
+                            bot.update_token_tracker_lock_time();
                             match tracker.update_all_tokens().await {
-                                Ok(_) => {
-                                    last_success_time = std::time::Instant::now();
+                                Ok(alerts) => {
+                                    last_success_time = Instant::now();
                                     consecutive_failures = 0;
-                                    debug!("Task {}: Cập nhật token status thành công", task_id);
+                                    for alert in alerts {
+                                        let token_address = alert.token_address.clone();
+                                        let _ = bot.status_update_sender.try_send(StatusUpdate::PriceAlert { token_address, alert });
+                                    }
                                 },
                                 Err(e) => {
                                     consecutive_failures += 1;
                                     error!("Task {}: Lỗi khi cập nhật token status: {}", task_id, e);
                                 }
                             }
-                            
-                            // Giải phóng lock ngay sau khi sử dụng xong
-                            drop(tracker);
-                            
-                            // Đợi một khoảng thời gian nhỏ để giảm áp lực lên các resource
-                            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
                         },
-                        _ => {
+                        Err(_) => {
                             consecutive_failures += 1;
                             debug!("Task {}: Không thể lấy token tracker lock, đang bận", task_id);
-                            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                        }
-                    }
-                    
-                    // Xử lý Trade Manager trong task riêng biệt
-                    if let Some(trade_mgr_weak) = &trade_manager_weak {
-                        if let Some(trade_mgr) = trade_mgr_weak.upgrade() {
-                            // Tạo một task riêng biệt để tránh xung đột khóa
-                            tokio::spawn(async move {
-                                // Tạo một timeout để tránh deadlock
-                                let trade_mgr_result = tokio::time::timeout(
-                                    tokio::time::Duration::from_secs(5),
-                                    async {
-                                        trade_mgr.try_lock()
-                                    }
-                                ).await;
-                                
-                                match trade_mgr_result {
-                                    Ok(Ok(trade_mgr)) => {
-                                        // Xử lý theo thông tin token status
-                                        if let Err(e) = trade_mgr.run_continuous_risk_monitoring().await {
-                                            error!("Lỗi khi theo dõi rủi ro liên tục: {}", e);
-                                        }
-                                        
-                                        // Tách thành một task con khác để tránh giữ khóa quá lâu
-                                        let trade_mgr_clone = trade_mgr.clone();
-                                        drop(trade_mgr); // Giải phóng khóa trước khi tạo task mới
-                                        
-                                        // Tạo một task con để thực hiện chiến lược thoát
-                                        tokio::spawn(async move {
-                                            if let Err(e) = trade_mgr_clone.execute_dynamic_exit_strategies().await {
-                                                error!("Lỗi khi thực hiện chiến lược thoát: {}", e);
-                                            }
-                                        });
-                                    },
-                                    _ => {
-                                        debug!("Không thể lấy trade manager lock, đang bận");
-                                    }
-                                }
-                            });
                         }
                     }
-                    
-                    // Kiểm tra sức khỏe của task
-                    if consecutive_failures > 5 {
-                        error!("Task {}: Phát hiện quá nhiều lỗi liên tiếp ({}), đang tự khởi động lại", 
-                               task_id, consecutive_failures);
-                        consecutive_failures = 0;
-                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                    }
-                    
-                    if last_success_time.elapsed() > std::time::Duration::from_secs(300) {
-                        warn!("Task {}: Không có cập nhật thành công trong 5 phút, kiểm tra hệ thống", task_id);
-                    }
                 }
-            });
-        }
-        
-        // Khởi động theo dõi mempool trong task riêng biệt
-        if let Some(mempool_watcher) = &self.mempool_watcher {
-            let mempool_weak = Arc::downgrade(mempool_watcher); // Sử dụng Weak reference
-            let trade_manager_weak = self.trade_manager.as_ref().map(Arc::downgrade); // Weak reference
-            
-            // Task riêng biệt cho mempool monitoring
-            tokio::spawn(async move {
-                let update_interval = tokio::time::Duration::from_secs(10); // Mempool cập nhật nhanh hơn
-                let mut interval = tokio::time::interval(update_interval);
-                
-                // Task ID để theo dõi
-                let task_id = Uuid::new_v4().to_string();
-                debug!("Khởi động task theo dõi mempool với ID: {}", task_id);
-                
-                let mut last_success_time = std::time::Instant::now();
-                let mut consecutive_failures = 0;
-                
-                loop {
-                    interval.tick().await;
-                    
-                    // Chỉ tiếp tục nếu có thể nâng cấp weak reference
-                    let mempool_arc = match mempool_weak.upgrade() {
-                        Some(arc) => arc,
-                        None => {
-                            debug!("Task {} kết thúc: mempool_watcher đã bị giải phóng", task_id);
-                            break;
-                        }
-                    };
-                    
-                    // Sử dụng try_lock với timeout để tránh deadlock
-                    let mempool_result = tokio::time::timeout(
-                        tokio::time::Duration::from_secs(3),
-                        async {
-                            mempool_arc.try_lock()
-                        }
-                    ).await;
-                    
-                    match mempool_result {
-                        Ok(Ok(watcher)) => {
-                            // Xử lý mempool
-                            match watcher.process_pending_transactions().await {
-                                Ok(_) => {
-                                    last_success_time = std::time::Instant::now();
-                                    consecutive_failures = 0;
-                                    debug!("Task {}: Xử lý mempool thành công", task_id);
-                                },
-                                Err(e) => {
-                                    consecutive_failures += 1;
-                                    error!("Task {}: Lỗi khi xử lý mempool: {}", task_id, e);
-                                }
-                            }
-                            
-                            // Giải phóng lock ngay sau khi sử dụng xong
-                            drop(watcher);
-                            
-                            // Đợi một khoảng thời gian nhỏ để giảm áp lực
-                            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                        },
-                        _ => {
-                            consecutive_failures += 1;
-                            debug!("Task {}: Không thể lấy mempool watcher lock, đang bận", task_id);
-                            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                        }
-                    }
-                    
-                    // Xử lý các cơ hội từ mempool trong task riêng biệt
-                    if let Some(trade_mgr_weak) = &trade_manager_weak {
-                        if let Some(trade_mgr) = trade_mgr_weak.upgrade() {
-                            // Tạo một task riêng biệt để tránh xung đột khóa
-                            tokio::spawn(async move {
-                                let trade_mgr_result = tokio::time::timeout(
-                                    tokio::time::Duration::from_secs(3),
-                                    async {
-                                        trade_mgr.try_lock()
-                                    }
-                                ).await;
-                                
-                                if let Ok(Ok(trade_mgr)) = trade_mgr_result {
-                                    if let Err(e) = trade_mgr.process_mempool_opportunities().await {
-                                        error!("Lỗi khi xử lý cơ hội từ mempool: {}", e);
-                                    }
-                                    drop(trade_mgr);
-                                }
-                            });
+
+                // Token của các vị thế đang mở luôn phải được theo dõi
+                if let Some(trade_manager) = bot.get_trade_manager() {
+                    if let Ok(manager) = with_timeout(trade_manager.lock(), Duration::from_secs(5)).await {
+                        bot.update_trade_manager_lock_time();
+                        if let Err(e) = manager.auto_track_position_tokens().await {
+                            error!("Task {}: Lỗi khi theo dõi token của vị thế: {}", task_id, e);
                         }
                     }
-                    
-                    // Kiểm tra sức khỏe của task
-                    if consecutive_failures > 5 {
-                        error!("Task {}: Phát hiện quá nhiều lỗi liên tiếp ({}), đang tự khởi động lại", 
-                               task_id, consecutive_failures);
-                        consecutive_failures = 0;
-                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                    }
-                    
-                    if last_success_time.elapsed() > std::time::Duration::from_secs(300) {
-                        warn!("Task {}: Không có cập nhật thành công trong 5 phút, kiểm tra hệ thống", task_id);
-                    }
                 }
-            });
-        }
-        
-        // Khởi động task theo dõi HealthCheck riêng biệt
-        let token_tracker_weak = self.token_status_tracker.as_ref().map(Arc::downgrade);
-        let mempool_weak = self.mempool_watcher.as_ref().map(Arc::downgrade);
-        
+
+                // Kiểm tra sức khỏe của task
+                if consecutive_failures > 5 {
+                    error!("Task {}: Phát hiện quá nhiều lỗi liên tiếp ({}), tạm dừng trước khi thử lại",
+                           task_id, consecutive_failures);
+                    consecutive_failures = 0;
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                }
+                if last_success_time.elapsed() > Duration::from_secs(300) {
+                    warn!("Task {}: Không có cập nhật thành công trong 5 phút, kiểm tra hệ thống", task_id);
+                }
+            }
+        });
+
+        // Task theo dõi mempool: đưa token mới vào tracker và cập nhật số giao dịch đang chờ
+        let bot_weak = Arc::downgrade(self);
         tokio::spawn(async move {
-            let health_interval = tokio::time::Duration::from_secs(60); // Kiểm tra mỗi phút
-            let mut interval = tokio::time::interval(health_interval);
-            
+            let mut interval = tokio::time::interval(Duration::from_secs(10));
+            let task_id = uuid::Uuid::new_v4().to_string();
+            debug!("Khởi động task theo dõi mempool với ID: {}", task_id);
+
             loop {
                 interval.tick().await;
-                
-                // Kiểm tra TokenStatusTracker
-                if let Some(tracker_weak) = &token_tracker_weak {
-                    if tracker_weak.strong_count() == 0 {
-                        warn!("TokenStatusTracker đã bị giải phóng, hệ thống có thể không hoạt động đúng");
-                    }
+
+                let Some(bot) = bot_weak.upgrade() else {
+                    debug!("Task {} kết thúc: bot đã bị giải phóng", task_id);
+                    break;
+                };
+                if bot.get_mode() != BotMode::Auto {
+                    debug!("Task {} kết thúc: bot rời chế độ Auto", task_id);
+                    break;
                 }
-                
-                // Kiểm tra MempoolWatcher
-                if let Some(mempool_weak) = &mempool_weak {
-                    if mempool_weak.strong_count() == 0 {
-                        warn!("MempoolWatcher đã bị giải phóng, hệ thống có thể không theo dõi được mempool");
+                let (Some(watcher), Some(tracker)) = (bot.get_mempool_watcher(), bot.get_token_tracker()) else {
+                    continue;
+                };
+
+                let pending = match watcher.check_mempool().await {
+                    Ok(pending) => pending,
+                    Err(e) => {
+                        error!("Task {}: Lỗi khi xử lý mempool: {}", task_id, e);
+                        continue;
                     }
+                };
+                let mut pending_counts: HashMap<String, u32> = HashMap::new();
+                for swap in &pending {
+                    *pending_counts.entry(swap.token_address.clone()).or_default() += 1;
                 }
-                
-                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-            }
-        });
-        
-        Ok(())
-    }
-    
-    // Chuyển đổi chế độ Bot
-    pub async fn switch_mode(&mut self, mode: BotMode) -> Result<(), Box<dyn std::error::Error>> {
-        if self.bot_mode == mode {
-            return Ok(());
-        }
-        
-        info!("Chuyển chế độ bot từ {:?} sang {:?}", self.bot_mode, mode);
-        self.bot_mode = mode;
-        
-        Ok(())
-    }
-    
-    // Lấy chế độ Bot hiện tại
-    pub fn get_mode(&self) -> BotMode {
-        self.bot_mode.clone()
-    }
-    
-    // Khởi tạo các thành phần cần thiết
-    async fn initialize_components(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // Khởi tạo RiskAnalyzer
-        let provider = Arc::new(self.chain_adapter.get_provider().clone());
-        let risk_analyzer = Arc::new(RiskAnalyzer::new(
-            provider.clone(),
-            vec![self.config.router_address.clone()],
-            vec![self.config.router_address.clone()],
-            self.config.weth_address.clone(),
-        )?);
-        
-        self.risk_analyzer = Some(risk_analyzer);
-        
-        // Khởi tạo TokenStatusTracker
-        let token_tracker = Arc::new(Mutex::new(TokenStatusTracker::new(
-            provider.clone(),
-            vec![self.config.router_address.clone()],
-            vec![self.config.router_address.clone()],
-            self.config.weth_address.clone(),
-        )?));
-        
-        self.token_status_tracker = RwLock::new(Some(token_tracker));
-        
-        // Khởi tạo MempoolWatcher nếu có
-        if let Some(service_tx) = self.get_service_tx() {
-            let mempool_watcher = Arc::new(Mutex::new(MempoolWatcher::new(
-                self.config.clone(),
-                service_tx,
-            )));
-            
-            self.mempool_watcher = Some(mempool_watcher);
-        }
-        
-        // Sửa đoạn khởi tạo TradeManager
-        if let Ok(mut trade_manager_lock) = self.trade_manager.write() {
-            if trade_manager_lock.is_none() {
-                info!("Khởi tạo TradeManager...");
-                
-                // Sử dụng timeout khi khởi tạo các components
-                match tokio::time::timeout(
-                    std::time::Duration::from_secs(10),
-                    async {
-                        let chain_adapter = Arc::new(self.chain_adapter.clone());
-                        let config = self.config.clone();
-                        
-                        // Tạo TradeManager với cấu hình phù hợp
-                        let trade_config = trade::trade_logic::TradeConfig {
-                            gas_limit: U256::from(400000),
-                            gas_price: U256::from(5000000000u64),
-                            slippage: 0.5,
-                            timeout: 30,
-                            auto_approve: true,
-                            use_flashbots: config.advanced.use_flashbots,
-                            emergency_sell_gas_multiplier: 1.5,
-                            router_address: config.router_address.clone(),
-                            wrapped_native_token: config.wrapped_native_token.clone(),
-                            max_slippage: 2.0,
-                            twap_window_size: 10,
-                            twap_min_samples: 5,
-                            twap_update_interval: 60,
-                        };
-                        
-                        let trade_manager = TradeManager::new(chain_adapter, trade_config);
-                        
-                        // Thiết lập tất cả các trường cần thiết
-                        
-                        // Trả về manager đã khởi tạo
-                        Arc::new(Mutex::new(trade_manager))
+
+                let Ok(mut tracker) = with_timeout(tracker.lock(), Duration::from_secs(3)).await else {
+                    debug!("Task {}: Không thể lấy token tracker lock, đang bận", task_id);
+                    continue;
+                };
+                bot.update_token_tracker_lock_time();
+                for (token_address, count) in pending_counts {
+                    if !tracker.is_tracking(&token_address) {
+                        if let Err(e) = tracker.add_token(&token_address).await {
+                            warn!("Không thể thêm token {} từ mempool vào theo dõi: {}", token_address, e);
+                            continue;
+                        }
+                        match tracker.update_token_status(&token_address).await {
+                            Ok(status) => {
+                                debug!("Đã thêm token {} từ mempool vào theo dõi", token_address);
+                                let _ = bot.status_update_sender.try_send(StatusUpdate::TokenUpdate {
+                                    token_address: token_address.clone(),
+                                    new_status: Box::new(status),
+                                });
+                            },
+                            Err(e) => warn!("Không thể cập nhật trạng thái token {}: {}", token_address, e),
+                        }
                     }
-                ).await {
-                    Ok(manager) => {
-                        *trade_manager_lock = Some(manager);
-                        info!("TradeManager đã khởi tạo thành công.");
-                    },
-                    Err(_) => {
-                        error!("Timeout khi khởi tạo TradeManager.");
-                        return Err("Timeout khi khởi tạo TradeManager.".into());
+                    if let Err(e) = tracker.update_pending_tx_count(&token_address, count).await {
+                        debug!("Không thể cập nhật số giao dịch chờ của {}: {}", token_address, e);
                     }
                 }
             }
-        }
-        
-        Ok(())
+        });
     }
-    
-    // Lấy service_tx (placeholder - cần thay đổi theo implementation thực tế)
-    fn get_service_tx(&self) -> Option<mpsc::Sender<ServiceMessage>> {
-        None // Placeholder
-    }
-    
-    // Phân tích token và trả về TokenInfo với TokenStatus
-    pub async fn analyze_token(&self, token_address: &str) -> Result<(TokenInfo, TokenStatus, TokenRiskAnalysis), Box<dyn std::error::Error>> {
-        // Lấy thông tin cơ bản về token
-        let token_info = self.chain_adapter.get_token_info(token_address).await?;
-        
-        // Lấy thông tin trạng thái token
-        let mut token_status = self.get_token_status(token_address).await?;
-        
-        // Lấy phân tích rủi ro
-        let risk_analysis = if let Some(analyzer) = &self.risk_analyzer {
-            let token_addr = Address::from_str(token_address).map_err(|e| format!("Địa chỉ token không hợp lệ: {}", e))?;
-            analyzer.analyze_token(token_addr).await?
-        } else {
-            return Err("Risk Analyzer chưa được khởi tạo".into());
-        };
-        
-        // Cập nhật thông tin tax
-        let tax_info = TaxInfo {
-            buy_tax: 0.0, // Cần cập nhật từ risk_analysis nếu có
-            sell_tax: 0.0, // Cần cập nhật từ risk_analysis nếu có
-            transfer_tax: 0.0,
-            min_hold_time: None,
-        };
-        token_status.tax_info = Some(tax_info);
-        
-        // Cập nhật mức độ an toàn
-        if let Some(tracker) = &self.token_status_tracker {
-            let mut tracker = match tracker.lock().await {
-                Ok(guard) => guard,
-                Err(e) => {
-                    error!("Lỗi khi lấy lock cho token_tracker: {}", e);
-                    return Err(format!("Lỗi khi lấy lock cho token_tracker: {}", e).into());
+
+    // Kiểm tra định kỳ lock của token tracker và trade manager; khôi phục khi bị treo
+    fn start_health_check(self: &Arc<Self>) {
+        let bot_weak = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60));
+
+            loop {
+                interval.tick().await;
+
+                let Some(bot) = bot_weak.upgrade() else {
+                    break;
+                };
+                if bot.get_mode() != BotMode::Auto {
+                    break;
                 }
-            };
-            token_status.safety_level = tracker.classify_token(&token_status, Some(&risk_analysis));
-        } else {
-            // Phân loại mặc định nếu không có tracker
-            token_status.safety_level = if risk_analysis.base.risk_score < 60.0 { 
-                TokenSafetyLevel::Green 
-            } else if risk_analysis.base.risk_score < 80.0 { 
-                TokenSafetyLevel::Yellow 
-            } else { 
-                TokenSafetyLevel::Red 
-            };
-        }
-        
-        // Cập nhật các thông tin khác
-        token_status.is_contract_verified = risk_analysis.is_verified;
-        token_status.has_dangerous_functions = risk_analysis.dangerous_functions.len() > 0;
-        token_status.dangerous_functions = risk_analysis.dangerous_functions.clone();
-        
-        // Kiểm tra thanh khoản đã khóa
-        if let Some(analyzer) = &self.risk_analyzer {
-            token_status.liquidity_locked = Some(analyzer.check_liquidity_locked(token_address).await?);
-        }
-        
-        // Cập nhật số lượng giao dịch đang chờ
-        if let Some(mempool) = &self.mempool_watcher {
-            let watcher = mempool.lock().await;
-            token_status.pending_tx_count = watcher.count_pending_txs_for_token(token_address).await;
-        }
-        
-        Ok((token_info, token_status, risk_analysis))
-    }
-    
-    // Lấy thông tin trạng thái token
-    pub async fn get_token_status(&self, token_address: &str) -> Result<TokenStatus, Box<dyn std::error::Error>> {
-        if let Some(tracker) = &self.token_status_tracker {
-            // Sử dụng try_lock() thay vì lock().unwrap()
-            match tracker.try_lock() {
-                Ok(mut lock) => {
-                    // Kiểm tra xem token đã được theo dõi chưa
-                    if lock.get_token(token_address).is_none() {
-                        // Nếu chưa, thêm vào danh sách theo dõi
-                        lock.add_token(token_address).await?;
-                    }
-                    
-                    // Cập nhật thông tin mới nhất
-                    if let Some(status) = lock.get_token(token_address) {
-                        // Bây giờ thêm phân loại token an toàn
-                        let mut status = status.clone();
-                        
-                        // Phân tích rủi ro để cập nhật chỉ số an toàn
-                        if let Some(analyzer) = &self.risk_analyzer {
-                            let token_addr = Address::from_str(token_address).map_err(|e| format!("Địa chỉ token không hợp lệ: {}", e))?;
-                            let risk_analysis = analyzer.analyze_token(token_addr).await?;
-                            
-                            let safety_level = if risk_analysis.base.risk_score < 60.0 {
-                                TokenSafetyLevel::Green
-                            } else if risk_analysis.base.risk_score < 80.0 {
-                                TokenSafetyLevel::Yellow
-                            } else {
-                                TokenSafetyLevel::Red
-                            };
-                            
-                            // Cập nhật thông tin an toàn vào token status
-                            status.safety_emoji = safety_level.to_string();
-                            status.has_dangerous_functions = risk_analysis.dangerous_functions.len() > 0;
-                            status.dangerous_functions = risk_analysis.dangerous_functions;
-                            status.is_contract_verified = risk_analysis.is_verified;
-                            
-                            // Cập nhật thông tin thuế nếu có
-                            if let Some(ref mut tax_info) = status.tax_info {
-                                // Có thể cần cập nhật từ risk_analysis nếu có
-                            } else {
-                                let tax_info = TaxInfo {
-                                    buy_tax: 0.0,
-                                    sell_tax: 0.0,
-                                    transfer_tax: 0.0,
-                                    min_hold_time: None,
-                                };
-                                status.tax_info = Some(tax_info);
+
+                // Kiểm tra sức khỏe của token_tracker
+                if let Some(tracker) = bot.get_token_tracker() {
+                    let healthy = with_timeout(tracker.lock(), Duration::from_secs(1)).await.is_ok();
+                    if healthy {
+                        bot.update_token_tracker_lock_time();
+                    } else {
+                        warn!("Không thể lấy lock cho token_tracker, có thể bị deadlock");
+                        if bot.check_deadlock_recovery_needed(DeadlockSource::TokenTracker) {
+                            if let Err(e) = bot.recover_from_deadlock(DeadlockSource::TokenTracker).await {
+                                error!("Khôi phục TokenTracker thất bại: {}", e);
                             }
                         }
-                        
-                        return Ok(status);
                     }
-                },
-                Err(_) => {
-                    // Không thể lấy lock, thử dùng timeout
-                    match with_timeout(tracker.lock(), Duration::from_secs(5)).await {
-                        Ok(mut lock) => {
-                            // Đoạn code tương tự như trên
-                            if lock.get_token(token_address).is_none() {
-                                lock.add_token(token_address).await?;
-                            }
-                            
-                            if let Some(status) = lock.get_token(token_address) {
-                                let mut status = status.clone();
-                                
-                                if let Some(analyzer) = &self.risk_analyzer {
-                                    let token_addr = Address::from_str(token_address).map_err(|e| format!("Địa chỉ token không hợp lệ: {}", e))?;
-                                    let risk_analysis = analyzer.analyze_token(token_addr).await?;
-                                    
-                                    let safety_level = if risk_analysis.base.risk_score < 60.0 {
-                                        TokenSafetyLevel::Green
-                                    } else if risk_analysis.base.risk_score < 80.0 {
-                                        TokenSafetyLevel::Yellow
-                                    } else {
-                                        TokenSafetyLevel::Red
-                                    };
-                                    
-                                    status.safety_emoji = safety_level.to_string();
-                                    status.has_dangerous_functions = risk_analysis.dangerous_functions.len() > 0;
-                                    status.dangerous_functions = risk_analysis.dangerous_functions;
-                                    status.is_contract_verified = risk_analysis.is_verified;
-                                    
-                                    // Cập nhật thông tin thuế nếu có
-                                    if let Some(ref mut tax_info) = status.tax_info {
-                                        // Có thể cần cập nhật từ risk_analysis nếu có
-                                    } else {
-                                        let tax_info = TaxInfo {
-                                            buy_tax: 0.0,
-                                            sell_tax: 0.0,
-                                            transfer_tax: 0.0,
-                                            min_hold_time: None,
-                                        };
-                                        status.tax_info = Some(tax_info);
-                                    }
-                                }
-                                
-                                return Ok(status);
+                }
+
+                // Tương tự cho trade_manager
+                if let Some(trade_manager) = bot.get_trade_manager() {
+                    let healthy = with_timeout(trade_manager.lock(), Duration::from_secs(1)).await.is_ok();
+                    if healthy {
+                        bot.update_trade_manager_lock_time();
+                    } else {
+                        warn!("Không thể lấy lock cho trade_manager, có thể bị deadlock");
+                        if bot.check_deadlock_recovery_needed(DeadlockSource::TradeManager) {
+                            if let Err(e) = bot.recover_from_deadlock(DeadlockSource::TradeManager).await {
+                                error!("Khôi phục TradeManager thất bại: {}", e);
                             }
-                        },
-                        Err(_) => {
-                            return Err("Không thể lấy lock token tracker sau khi timeout, có thể deadlock".into());
                         }
                     }
                 }
             }
-        }
-        
-        Err("Không thể lấy thông tin trạng thái token".into())
+        });
     }
 
-    // Thực hiện giao dịch mua trong chế độ Manual
-    pub async fn manual_buy(&self, token_address: &str, amount: &str, gas_price_percent: u64) -> Result<SnipeResult, Box<dyn std::error::Error>> {
-        // Chỉ cho phép ở chế độ Manual
-        if self.bot_mode != BotMode::Manual {
-            return Err("Chỉ có thể sử dụng manual_buy trong chế độ Manual".into());
-        }
-        
-        // Parse lượng token đầu vào
-        let amount_in = ethers::utils::parse_ether(amount)?;
-        
-        // Lấy thông tin gas hiện tại
-        let gas_info = self.chain_adapter.get_gas_info().await?;
-        
-        // Tính gas price mới dựa trên phần trăm
-        let gas_price = if gas_price_percent > 0 {
-            gas_info.gas_price.saturating_mul(U256::from(gas_price_percent)).div(U256::from(100))
-        } else {
-            gas_info.gas_price
-        };
-        
-        // Tạo thông tin token
-        let token_info = TokenInfo {
-            address: token_address.to_string(),
-            symbol: "UNKNOWN".to_string(), // Sẽ được cập nhật sau
-            decimals: 18,
-            router: self.config.router_address.clone(),
-            pair: None,
-        };
-        
-        // Lấy thông tin chi tiết từ blockchain nếu có thể
-        let token_info = match self.chain_adapter.get_token_info(token_address).await {
-            Ok(info) => info,
-            Err(_) => token_info,
-        };
-        
-        // Tạo cấu hình snipe
-        let snipe_config = SnipeConfig {
-            token_address: token_info.address.clone(),
-            amount_in: amount_in.clone(),
-            amount_out_min: U256::from(0), // Tạm thời để 0, có thể cải thiện sau
-            deadline: 0, // Tạm thời để 0, có thể cải thiện sau
-            slippage: self.config.default_slippage,
-            gas_price: gas_price.clone(),
-            gas_limit: U256::from(0), // Tạm thời để 0, có thể cải thiện sau
-            chain_id: self.config.chain_id,
-            max_retries: 3, // Tạm thời để 3, có thể cải thiện sau
-            retry_delay: 1000, // Tạm thời để 1000, có thể cải thiện sau
-        };
-        
-        // Thực hiện snipe
-        self.snipe(&token_info, amount_in, &snipe_config).await
-    }
-    
-    // Thực hiện giao dịch bán trong chế độ Manual
-    pub async fn manual_sell(&self, token_address: &str, amount_percent: u8, gas_price_percent: u64) -> Result<SnipeResult, Box<dyn std::error::Error>> {
-        // Chỉ cho phép ở chế độ Manual
-        if self.bot_mode != BotMode::Manual {
-            return Err("Chỉ có thể sử dụng manual_sell trong chế độ Manual".into());
-        }
-        
-        // Kiểm tra phần trăm hợp lệ
-        if amount_percent == 0 || amount_percent > 100 {
-            return Err("Phần trăm bán phải từ 1-100".into());
-        }
-        
-        // Lấy số dư token
-        let token_balance = match self.chain_adapter.get_token_balance(
-            token_address, 
-            &self.get_current_wallet_address()
-        ).await {
-            Ok(balance) => balance,
-            Err(e) => return Err(format!("Không thể lấy số dư token: {}", e).into()),
-        };
-        
-        if token_balance.is_zero() {
-            return Err("Không có token để bán".into());
-        }
-        
-        // Tính số lượng token cần bán
-        let amount_to_sell = token_balance.saturating_mul(U256::from(amount_percent)).div(U256::from(100));
-        
-        // Lấy thông tin gas hiện tại
-        let gas_info = self.chain_adapter.get_gas_info().await?;
-        
-        // Tính gas price mới dựa trên phần trăm
-        let gas_price = if gas_price_percent > 0 {
-            gas_info.gas_price.saturating_mul(U256::from(gas_price_percent)).div(U256::from(100))
-        } else {
-            gas_info.gas_price
-        };
-        
-        // Thực hiện bán token
-        let deadline = match SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
-            Ok(duration) => duration.as_secs() + 300, // 5 phút
-            Err(_) => {
-                // Nếu có lỗi với thời gian hệ thống, sử dụng giá trị mặc định
-                warn!("Lỗi khi lấy thời gian hệ thống, sử dụng deadline mặc định");
-                u64::MAX / 2 // Một giá trị đủ lớn, nhưng không gây tràn số
-            }
-        };
-        
-        // Kiểm tra nếu token đã được approve
-        let router_address = &self.config.router_address;
-        let approval_status = self.chain_adapter.check_token_allowance(
-            token_address,
-            router_address,
-            &self.get_current_wallet_address(),
-        ).await?;
-        
-        if approval_status < amount_to_sell {
-            // Token cần được approve
-            info!("Tự động approve token {} cho router {}", token_address, router_address);
-            match self.chain_adapter.approve_token(
-                token_address, 
-                router_address, 
-                U256::MAX, // Approve không giới hạn
-                Some(self.config.default_gas_limit),
-                Some(gas_price.as_u64()),
-            ).await {
-                Ok(_) => info!("Đã approve token thành công"),
-                Err(e) => return Err(format!("Không thể approve token: {}", e).into()),
-            }
-        }
-        
-        let result = self.chain_adapter.swap_exact_tokens_for_eth(
-            token_address,
-            amount_to_sell,
-            U256::zero(), // min_amount_out: tạm thời để 0, có thể cải thiện sau
-            &self.get_current_wallet_address(),
-            deadline,
-            Some(self.config.default_gas_limit),
-            Some(gas_price.as_u64()),
-        ).await?;
-        
-        // Tạo kết quả
-        let timestamp = match SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
-            Ok(duration) => duration.as_secs(),
-            Err(_) => {
-                warn!("Lỗi khi lấy thời gian hệ thống, sử dụng timestamp mặc định");
-                0 // Sử dụng giá trị mặc định cho timestamp
-            }
+    // Chuyển các status update nội bộ thành thông báo cho ServiceManager (chỉ chạy một lần)
+    fn process_status_updates(self: &Arc<Self>) {
+        let receiver = match self.status_update_receiver.write() {
+            Ok(mut receiver) => receiver.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
         };
-        
-        let snipe_result = SnipeResult {
-            transaction_hash: result.as_ref().map(|r| format!("{:?}", r.transaction_hash)),
-            success: result.is_some(),
-            token_address: token_address.to_string(),
-            amount_in: amount_to_sell.to_string(),
-            estimated_amount_out: None,
-            error: if result.is_none() { Some("Giao dịch bán thất bại".into()) } else { None },
-            timestamp,
+        let Some(mut rx) = receiver else {
+            return;
         };
-        
-        // Lưu kết quả vào storage
-        if let Err(e) = self.storage.add_transaction(snipe_result.clone().into()) {
-            warn!("Không thể lưu giao dịch: {}", e);
-        }
-        
-        Ok(snipe_result)
-    }
 
-    // Thêm auto trade config
-    pub fn set_auto_trade_config(&mut self, config: AutoTradeConfig) {
-        self.auto_trade_config = Some(config);
-    }
-    
-    // Cập nhật phương thức auto_trade để hỗ trợ các chiến lược mới
-    pub async fn auto_trade(&self, token_address: &str, amount: &str, action: &str) -> Result<TradeResult, Box<dyn std::error::Error>> {
-        // Kiểm tra bot mode
-        if self.bot_mode != BotMode::Auto {
-            return Err("Bot không ở chế độ Auto".into());
-        }
-        
-        // Phân tích token
-        let (token_info, token_status, risk_analysis) = self.analyze_token(token_address).await?;
-        
-        // Kiểm tra giữ lại reserve
-        if action == "buy" && !self.check_reserve_balance().await? {
-            return Err("Không đủ reserve balance. Duy trì tối thiểu 25% số dư".into());
-        }
-        
-        // Kiểm tra safety level và áp dụng chiến lược phù hợp
-        match token_status.safety_level {
-            TokenSafetyLevel::Red => {
-                // Không giao dịch token đỏ
-                return Err("Không giao dịch token có mức độ nguy hiểm cao".into());
-            },
-            TokenSafetyLevel::Yellow => {
-                // Chiến lược cho token vàng
-                if action == "buy" {
-                    // Kiểm tra điều kiện mua dựa trên large orders
-                    if let Some(config) = &self.auto_trade_config {
-                        if !config.yellow_token_strategy.buy_on_large_orders {
-                            return Err("Chiến lược mua token vàng không được kích hoạt".into());
-                        }
-                        
-                        // Kiểm tra mempool xem có lệnh lớn không
-                        let has_large_orders = self.check_large_orders_in_mempool(
-                            token_address, 
-                            config.yellow_token_strategy.min_large_order_usd
-                        ).await?;
-                        
-                        if !has_large_orders {
-                            return Err("Không phát hiện lệnh mua lớn trên mempool cho token này".into());
-                        }
-                    }
-                } else if action == "sell" {
-                    // Tự động bán sau 5 phút
-                    // Logic đã được xử lý ở ServiceManager
-                } else if action == "sandwich" {
-                    // Thực hiện sandwich nếu được kích hoạt
-                    if let Some(config) = &self.auto_trade_config {
-                        if !config.yellow_token_strategy.use_sandwich_mode {
-                            return Err("Chế độ sandwich không được kích hoạt cho token vàng".into());
-                        }
-                        
-                        // Tìm cơ hội sandwich
-                        return self.execute_sandwich_strategy(token_address).await;
-                    }
-                }
-            },
-            TokenSafetyLevel::Green => {
-                // Chiến lược cho token xanh
-                if action == "buy" {
-                    if let Some(config) = &self.auto_trade_config {
-                        if !config.green_token_strategy.front_run_orders {
-                            return Err("Front-running không được kích hoạt cho token xanh".into());
-                        }
-                        
-                        // Kiểm tra mempool cho lệnh lớn để front-run
-                        let large_orders = self.get_large_orders_in_mempool(
-                            token_address, 
-                            config.green_token_strategy.min_order_size_usd
-                        ).await?;
-                        
-                        if large_orders.is_empty() {
-                            return Err("Không phát hiện lệnh lớn để front-run".into());
-                        }
-                        
-                        // Front-run lệnh lớn nhất
-                        return self.front_run_transaction(&large_orders[0], amount).await;
-                    }
-                } else if action == "tsl" {
-                    // Kích hoạt trailing stop loss
-                    if let Some(config) = &self.auto_trade_config {
-                        if !config.green_token_strategy.use_trailing_stop {
-                            return Err("Trailing stop loss không được kích hoạt cho token xanh".into());
-                        }
-                        
-                        return self.activate_trailing_stop_loss(
-                            token_address, 
-                            config.green_token_strategy.trailing_stop_percent
-                        ).await;
-                    }
-                } else if action == "sandwich" {
-                    // Thực hiện sandwich nếu được kích hoạt
-                    if let Some(config) = &self.auto_trade_config {
-                        if !config.green_token_strategy.use_sandwich_mode {
-                            return Err("Chế độ sandwich không được kích hoạt cho token xanh".into());
-                        }
-                        
-                        // Tìm cơ hội sandwich
-                        return self.execute_sandwich_strategy(token_address).await;
+        let bot_weak = Arc::downgrade(self);
+        tokio::spawn(async move {
+            while let Some(update) = rx.recv().await {
+                let Some(bot) = bot_weak.upgrade() else {
+                    break;
+                };
+                let message = match update {
+                    StatusUpdate::TokenUpdate { token_address, new_status } => Some(ServiceMessage::TokenSafetyUpdate {
+                        token_address,
+                        safety_level: new_status.safety_level,
+                    }),
+                    StatusUpdate::PriceAlert { token_address, alert } => Some(ServiceMessage::PriceAlert {
+                        token: token_address,
+                        price: alert.new_price,
+                        change: alert.price_change_percent,
+                    }),
+                    StatusUpdate::RiskUpdate { token_address, risk_level } => Some(ServiceMessage::TokenSafetyUpdate {
+                        token_address,
+                        safety_level: risk_level,
+                    }),
+                    StatusUpdate::MempoolTransaction { tx_hash, token_address } => {
+                        debug!("Giao dịch mempool {} cho token {:?}", tx_hash, token_address);
+                        None
+                    },
+                    StatusUpdate::TradeResult { result } => {
+                        info!("Auto trade {:?} token {}: thành công={}, tx={:?}",
+                              result.trade_type, result.token_address, result.success, result.tx_hash);
+                        None
+                    },
+                };
+
+                if let (Some(message), Some(tx)) = (message, bot.get_service_tx()) {
+                    if let Err(e) = tx.send(message).await {
+                        warn!("Không thể gửi status update tới ServiceManager: {}", e);
                     }
                 }
             }
-        }
-        
-        // Nếu không có chiến lược đặc biệt, thực hiện giao dịch thông thường
-        match action {
-            "buy" => {
-                // Mua token
-                let amount_in = ethers::utils::parse_ether(amount)
-                    .map_err(|e| format!("Số lượng không hợp lệ: {}", e))?;
-                
-                self.buy_token(token_address, amount_in).await
-            },
-            "sell" => {
-                // Bán token
-                let percent = amount.parse::<u8>().unwrap_or(100);
-                self.sell_token_percent(token_address, percent).await
-            },
-            "emergency_sell" => {
-                // Bán khẩn cấp
-                self.emergency_sell(token_address).await
-            },
-            _ => Err(format!("Hành động không hỗ trợ: {}", action).into()),
-        }
-    }
-    
-    // Kiểm tra số dư reserve
-    async fn check_reserve_balance(&self) -> Result<bool, Box<dyn std::error::Error>> {
-        // Lấy số dư native token
-        let wallet_address = self.get_current_wallet_address();
-        let balance = self.chain_adapter.get_native_balance(&wallet_address).await?;
-        
-        // Lấy số dư stablecoin (USDT/USDC)
-        let stablecoin_balance = self.get_stablecoin_balance().await?;
-        
-        // Tính tổng số dư
-        let native_in_usd = self.convert_native_to_usd(balance).await?;
-        let total_balance = native_in_usd + stablecoin_balance;
-        
-        // Kiểm tra reserve percent
-        let reserve_percent = if let Some(config) = &self.auto_trade_config {
-            config.reserve_percent
-        } else {
-            25.0 // Mặc định
-        };
-        
-        let current_reserve = stablecoin_balance / total_balance * 100.0;
-        
-        // Gửi thông báo nếu reserve thấp
-        if current_reserve < reserve_percent {
-            if let Some(tx_sender) = self.get_service_tx() {
-                let _ = tx_sender.send(ServiceMessage::ReserveBalanceAlert { 
-                    current_percent: current_reserve 
-                }).await;
-            }
-            
-            return false;
-        }
-        
-        Ok(true)
+        });
     }
-    
-    // Lấy số dư stablecoin (USDT, USDC)
-    async fn get_stablecoin_balance(&self) -> Result<f64, Box<dyn std::error::Error>> {
+
+    // Tổng số dư stablecoin (USD) của ví hiện tại theo danh sách stablecoin của chain
+    pub async fn get_stablecoin_balance(&self) -> Result<f64> {
         let wallet_address = self.get_current_wallet_address();
+        let chain = chain_registry::get_chain_config(self.chain_adapter.get_config().chain_id)?;
         let mut total_balance = 0.0;
-        
-        // Danh sách stablecoin phổ biến
-        let stablecoins = vec![
-            "0xdAC17F958D2ee523a2206206994597C13D831ec7", // USDT (Ethereum)
-            "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", // USDC (Ethereum)
-            // Thêm các stablecoin khác theo chain
-        ];
-        
-        for token in stablecoins {
-            match self.chain_adapter.get_token_balance(token, &wallet_address).await {
+
+        for (token, decimals) in DexPriceConfig::from_chain_config(&chain).stablecoins {
+            let token = format!("{:?}", token);
+            match self.chain_adapter.get_token_balance(&token, &wallet_address).await {
                 Ok(balance) => {
-                    // Lấy decimals của token
-                    let decimals = match self.chain_adapter.get_token_info(token).await {
-                        Ok(info) => info.decimals,
-                        Err(_) => 18, // Mặc định
-                    };
-                    
-                    // Chuyển đổi sang số thập phân
-                    let balance_f64 = balance.as_u128() as f64 / (10.0_f64.powi(decimals as i32));
-                    total_balance += balance_f64;
+                    total_balance += ethers::utils::format_units(balance, decimals as u32)
+                        .ok()
+                        .and_then(|value| value.parse::<f64>().ok())
+                        .unwrap_or(0.0);
                 },
                 Err(e) => {
                     warn!("Không thể lấy số dư token {}: {}", token, e);
                 }
             }
         }
-        
+
         Ok(total_balance)
     }
-    
+
     // Chuyển đổi native token sang USD
-    async fn convert_native_to_usd(&self, amount: U256) -> Result<f64, Box<dyn std::error::Error>> {
-        // Lấy giá native token (ETH, BNB, ...) từ price oracle
-        let native_price = match self.get_native_price().await {
-            Ok(price) => price,
-            Err(_) => 2000.0, // Giá mặc định nếu không lấy được
-        };
-        
-        // Chuyển đổi amount sang số thập phân
-        let amount_f64 = ethers::utils::format_ether(amount)
-            .parse::<f64>()
-            .unwrap_or(0.0);
-            
-        Ok(amount_f64 * native_price)
-    }
-    
-    // Lấy giá native token
-    async fn get_native_price(&self) -> Result<f64, Box<dyn std::error::Error>> {
-        // Thực tế nên sử dụng price oracle hoặc API
-        // Đây là hàm mẫu
-        Ok(2000.0) // Giả định giá ETH là $2000
-    }
-    
+    pub async fn convert_native_to_usd(&self, amount: U256) -> Result<f64> {
+        self.native_to_usd(amount).await
+            .ok_or_else(|| anyhow!("Không có giá native token cho chain hiện tại"))
+    }
+
     // Kiểm tra mempool để phát hiện lệnh lớn
-    async fn check_large_orders_in_mempool(&self, token_address: &str, min_amount_usd: f64) -> Result<bool, Box<dyn std::error::Error>> {
-        // Nếu không có mempool watcher
-        if self.mempool_watcher.is_none() {
+    async fn check_large_orders_in_mempool(&self, token_address: &str, min_amount_usd: f64) -> Result<bool> {
+        if self.get_mempool_watcher().is_none() {
             return Ok(false);
         }
-        
-        // Lấy orders từ mempool watcher
         let orders = self.get_large_orders_in_mempool(token_address, min_amount_usd).await?;
-        
         Ok(!orders.is_empty())
     }
-    
-    // Lấy danh sách các lệnh lớn từ mempool
-    async fn get_large_orders_in_mempool(&self, token_address: &str, min_amount_usd: f64) -> Result<Vec<PendingSwap>, Box<dyn std::error::Error>> {
-        // Truy cập mempool tracker thông qua watcher
-        if let Some(mempool_watcher) = &self.mempool_watcher {
-            let watcher = mempool_watcher.lock().await;
-            
-            // Lấy các giao dịch lớn đang chờ xử lý
-            if let Some(mempool_tracker) = &watcher.mempool_tracker {
-                let mut large_orders: Vec<PendingSwap> = Vec::new();
-                
-                // Lọc các giao dịch mua lớn
-                if let Some(swaps) = mempool_tracker.pending_swaps.get(token_address) {
-                    for swap in swaps {
-                        if swap.is_buy && swap.amount_usd >= min_amount_usd {
-                            large_orders.push(swap.clone());
-                        }
-                    }
-                }
-                
-                return Ok(large_orders);
-            }
+
+    // Tìm cơ hội sandwich từ mempool (chỉ cho gói Premium trở lên)
+    async fn find_sandwich_opportunity(&self, token_address: &str) -> Result<Option<SandwichOpportunity>> {
+        if self.get_current_subscription_level() < SubscriptionLevel::Premium {
+            return Ok(None);
         }
-        
-        Ok(Vec::new())
-    }
-    
-    // Front-run một giao dịch
-    async fn front_run_transaction(&self, pending_swap: &PendingSwap, amount: &str) -> Result<TradeResult, Box<dyn std::error::Error>> {
-        info!("Front-running giao dịch: {}", pending_swap.tx_hash);
-        
-        // Tính gas cao hơn giao dịch gốc
-        let original_gas = pending_swap.gas_price;
-        let front_run_gas = original_gas * 110 / 100; // Tăng 10%
-        
-        // Chuyển đổi amount sang U256
-        let amount_in = ethers::utils::parse_ether(amount)
-            .map_err(|e| format!("Số lượng không hợp lệ: {}", e))?;
-        
-        // Sử dụng chain adapter để mua token với gas price cao hơn
-        let token_address = &pending_swap.token_address;
-        let mut slippage = 1.0; // 1% slippage
-        
-        // Tính toán amount_out_min
-        let path = self.chain_adapter.get_native_to_token_path(token_address);
-        let amounts = self.chain_adapter.get_amounts_out(amount_in, path).await?;
-        let amount_out = amounts.last().ok_or("Không thể tính toán amount out")?;
-        let amount_out_min = *amount_out * U256::from(100 - (slippage as u64)) / U256::from(100);
-        
-        // Deadline giao dịch (sau 5 phút)
-        let deadline = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs() + 300;
-        
-        // Địa chỉ ví
-        let wallet_address = self.get_current_wallet_address();
-        
-        // Thực hiện giao dịch với gas cao hơn
-        let receipt = self.chain_adapter.swap_exact_eth_for_tokens(
-            token_address,
-            amount_in,
-            amount_out_min,
-            &wallet_address,
-            deadline as u64,
-            None, // gas limit mặc định
-            Some(front_run_gas.as_u64()),
-        ).await?;
-        
-        // Tạo kết quả giao dịch
-        let trade_result = TradeResult {
-            success: receipt.is_some(),
-            tx_hash: receipt.map(|r| format!("{:?}", r.transaction_hash)),
-            amount_in: ethers::utils::format_ether(amount_in),
-            amount_out: None, // Cần thêm xử lý để lấy amount out từ receipt
-            price_per_token: None,
-            gas_used: receipt.as_ref().map(|r| r.gas_used.as_u64()),
-            gas_price: front_run_gas.as_u64(),
-            timestamp: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
-            error: if receipt.is_none() { Some("Không nhận được receipt".to_string()) } else { None },
-            trade_type: TradeType::Buy,
-        };
-        
-        Ok(trade_result)
-    }
-    
-    // Thực hiện sandwich attack
-    async fn execute_sandwich_strategy(&self, token_address: &str) -> Result<TradeResult, Box<dyn std::error::Error>> {
-        // Tìm cơ hội sandwich từ mempool
-        let opportunity = self.find_sandwich_opportunity(token_address).await?;
-        
-        if opportunity.is_none() {
-            return Err("Không tìm thấy cơ hội sandwich phù hợp".into());
-        }
-        
-        let opportunity = opportunity.unwrap();
-        
-        info!("Thực hiện sandwich attack cho token: {}", token_address);
-        
-        // Bước 1: Front-run
-        let front_run_amount = "0.05"; // 0.05 ETH
-        let front_run_result = self.front_run_transaction(&opportunity.victim_swap, front_run_amount).await?;
-        
-        if !front_run_result.success {
-            return Err("Front-run thất bại".into());
-        }
-        
-        // Bước 2: Đợi victim transaction thực hiện
-        // Trong thực tế, cần theo dõi mempool để biết khi nào victim tx được xác nhận
-        // Ở đây chỉ là mô phỏng
-        tokio::time::sleep(Duration::from_millis(100)).await;
-        
-        // Bước 3: Back-run (bán token)
-        let back_run_result = self.sell_token_percent(token_address, 100).await?;
-        
-        // Kết hợp kết quả
-        let trade_result = TradeResult {
-            success: front_run_result.success && back_run_result.success,
-            tx_hash: Some(format!("front:{},back:{}", 
-                                 front_run_result.tx_hash.unwrap_or_default(), 
-                                 back_run_result.tx_hash.unwrap_or_default())),
-            amount_in: front_run_result.amount_in,
-            amount_out: back_run_result.amount_out,
-            price_per_token: None,
-            gas_used: Some(front_run_result.gas_used.unwrap_or(0) + back_run_result.gas_used.unwrap_or(0)),
-            gas_price: (front_run_result.gas_price + back_run_result.gas_price) / 2,
-            timestamp: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
-            error: None,
-            trade_type: TradeType::Buy,
+        let Some(watcher) = self.get_mempool_watcher() else {
+            return Ok(None);
         };
-        
-        Ok(trade_result)
-    }
-    
-    // Tìm cơ hội sandwich từ mempool
-    async fn find_sandwich_opportunity(&self, token_address: &str) -> Result<Option<SandwichOpportunity>, Box<dyn std::error::Error>> {
-        // Chỉ hỗ trợ sandwich cho các ví gói cao cấp
-        if self.current_user_level < SubscriptionLevel::Premium {
+
+        debug!("Tìm cơ hội sandwich cho token {}", token_address);
+
+        // Chỉ sử dụng sandwich nếu số dư đủ lớn
+        let balance = self.get_native_balance().await?;
+        if (balance / U256::from(10)).is_zero() {
             return Ok(None);
         }
-        
-        debug!("Tìm cơ hội sandwich cho token {}", token_address);
-        
-        // Sử dụng nội bộ để chỉ định cơ hội
-        let (tx, rx) = tokio::sync::oneshot::channel::<Option<SandwichOpportunity>>();
-        
-        // Lấy số dư hiện tại để xác định khoản có thể trade
+
+        let opportunities = watcher.find_sandwich_opportunities().await
+            .map_err(|e| anyhow!("Không thể đọc cơ hội sandwich: {}", e))?;
+        let Some(best) = opportunities.into_iter()
+            .filter(|opportunity| opportunity.token_address.eq_ignore_ascii_case(token_address))
+            .max_by(|a, b| a.potential_profit.total_cmp(&b.potential_profit)) else {
+            return Ok(None);
+        };
+
+        // Giao dịch nạn nhân phải còn trong mempool
+        let pending = watcher.check_mempool().await
+            .map_err(|e| anyhow!("Không thể đọc mempool: {}", e))?;
+        Ok(pending.into_iter()
+            .find(|swap| swap.tx_hash == best.victim_tx_hash)
+            .map(|victim_swap| SandwichOpportunity {
+                token_address: token_address.to_string(),
+                victim_swap,
+                potential_profit: best.potential_profit,
+                estimated_price_impact: best.estimated_price_impact,
+            }))
+    }
+
+    // Thực hiện sandwich attack
+    pub async fn execute_sandwich_strategy(&self, token_address: &str) -> Result<TradeResult> {
+        let opportunity = self.find_sandwich_opportunity(token_address).await?
+            .ok_or_else(|| anyhow!("Không tìm thấy cơ hội sandwich phù hợp"))?;
+        self.execute_sandwich(&opportunity, "0.05").await
+    }
+
+    // Thực hiện sandwich với phần trăm số dư cụ thể
+    async fn execute_sandwich_strategy_with_percent(&self, token_address: &str, percent: f64) -> Result<TradeResult> {
+        let opportunity = self.find_sandwich_opportunity(token_address).await?
+            .ok_or_else(|| anyhow!("Không tìm thấy cơ hội sandwich phù hợp"))?;
+
         let balance = self.get_native_balance().await?;
-        let min_amount = balance / U256::from(10); // Ít nhất 10% số dư
-        
-        // Chỉ sử dụng sandwich nếu số dư đủ lớn
-        if min_amount.is_zero() {
-            return Ok(None);
+        let percent_bps = (percent.clamp(0.0, 100.0) * 100.0).round() as u64;
+        let amount_to_use = balance.saturating_mul(U256::from(percent_bps)) / U256::from(10_000);
+        self.execute_sandwich(&opportunity, &ethers::utils::format_ether(amount_to_use)).await
+    }
+
+    // Front-run lệnh nạn nhân, chờ lệnh đó vào block rồi bán toàn bộ
+    async fn execute_sandwich(&self, opportunity: &SandwichOpportunity, amount: &str) -> Result<TradeResult> {
+        let token_address = &opportunity.token_address;
+        info!("Thực hiện sandwich attack cho token: {}", token_address);
+
+        // Bước 1: Front-run
+        let front_run_result = self.front_run_transaction(&opportunity.victim_swap, amount).await?;
+        if !front_run_result.success {
+            return Err(anyhow!("Front-run thất bại"));
         }
-        
-        // Lấy địa chỉ ví
-        let wallet_address = match &self.current_wallet_address {
-            Some(addr) => addr.clone(),
-            None => return Err("Không có ví được chọn để tìm cơ hội sandwich".into()),
-        };
-        
-        // Theo dõi mempool cho token
-        let callback = Box::new(move |transaction: Transaction, value: U256| {
-            // Lọc giao dịch vào có giá trị đủ lớn
-            if value >= min_amount {
-                // Cơ hội sandwich tiềm năng
-                let opportunity = SandwichOpportunity {
-                    token_address: token_address.to_string(),
-                    victim_swap: PendingSwap {
-                        tx_hash: format!("{:?}", transaction.hash),
-                        from: format!("{:?}", transaction.from),
-                        to: format!("{:?}", transaction.to.unwrap_or_default()),
-                        value: value.to_string(),
-                        gas_price: transaction.gas_price.unwrap_or_default().to_string(),
-                        input: format!("{:?}", transaction.input),
-                    },
-                    potential_profit: 0.0, // Sẽ tính toán sau
-                    estimated_price_impact: 0.0, // Sẽ tính toán sau
-                };
-                
-                // Gửi cơ hội qua channel
-                let _ = tx.send(Some(opportunity));
+
+        // Bước 2: Đợi victim transaction được xác nhận (tối đa 30 giây)
+        let provider = self.chain_adapter.get_provider();
+        if let Ok(victim_hash) = ethers::types::H256::from_str(&opportunity.victim_swap.tx_hash) {
+            for _ in 0..30 {
+                if let Ok(Some(_)) = provider.get_transaction_receipt(victim_hash).await {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_secs(1)).await;
             }
-        });
-        
-        // Bắt đầu theo dõi với một minimum ETH amount
-        let _ = self.chain_adapter.watch_for_sandwich_opportunities(
-            token_address, 
-            min_amount,
-            callback
-        ).await?;
-        
-        // Đặt timeout 5 giây để tìm cơ hội
-        let timeout = tokio::time::Duration::from_secs(5);
-        match tokio::time::timeout(timeout, rx).await {
-            Ok(result) => match result {
-                Ok(opportunity) => Ok(opportunity),
-                Err(_) => Ok(None),
-            },
-            Err(_) => Ok(None), // Timeout
         }
+
+        // Bước 3: Back-run (bán token)
+        let gas_price = provider.get_gas_price().await?;
+        let back_run_result = self.sell_token_percent(token_address, 100, gas_price).await?;
+
+        Ok(TradeResult {
+            success: front_run_result.success && back_run_result.success,
+            tx_hash: Some(format!("front:{},back:{}",
+                                  front_run_result.tx_hash.unwrap_or_default(),
+                                  back_run_result.transaction_hash.unwrap_or_default())),
+            amount_in: front_run_result.amount_in,
+            amount_out: back_run_result.estimated_amount_out,
+            price_per_token: None,
+            gas_used: front_run_result.gas_used,
+            gas_price: front_run_result.gas_price,
+            timestamp: safe_now(),
+            error: back_run_result.error,
+            trade_type: TradeType::Sandwich,
+            token_address: token_address.clone(),
+            victim_tx_hash: Some(opportunity.victim_swap.tx_hash.clone()),
+            profit_usd: front_run_result.profit_usd,
+            gas_cost_usd: front_run_result.gas_cost_usd,
+        })
     }
-    
-    // Kích hoạt trailing stop loss
-    async fn activate_trailing_stop_loss(&self, token_address: &str, trailing_percent: f64) -> Result<TradeResult, Box<dyn std::error::Error>> {
-        info!("Kích hoạt trailing stop loss cho token {} với mức {}%", token_address, trailing_percent);
-        
-        // Lấy thông tin token và giá hiện tại
-        let (token_info, token_status, _) = self.analyze_token(token_address).await?;
-        let current_price = token_status.price_usd;
-        
-        // Tạo trailing stop loss mới
-        let trailing_stop = TrailingStopLoss {
-            token_address: token_address.to_string(),
-            activation_price: current_price,
-            highest_price: current_price,
-            trail_percent: trailing_percent,
-            stop_price: current_price * (1.0 - trailing_percent / 100.0),
-            created_at: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
-        };
-        
-        // Lưu trailing stop loss
-        if let Some(trade_manager) = &self.trade_manager {
-            let mut manager = trade_manager.lock().await;
-            manager.set_trailing_stop_loss(token_address, trailing_stop).await?;
-        }
-        
-        // Trả về kết quả giả
-        let trade_result = TradeResult {
-            success: true,
-            tx_hash: None,
-            amount_in: "0".to_string(),
-            amount_out: None,
-            price_per_token: Some(current_price),
-            gas_used: None,
-            gas_price: 0,
-            timestamp: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
-            error: None,
-            trade_type: TradeType::Approve, // Sử dụng Approve vì không có TradeType phù hợp
-        };
-        
-        Ok(trade_result)
-    }
-    
-    // Tự động giao dịch dựa trên phân loại token
-    pub async fn auto_trade_by_safety(&self, token_address: &str) -> Result<TradeResult, Box<dyn std::error::Error>> {
-        // Phân tích token
-        let (token_info, token_status, risk_analysis) = self.analyze_token(token_address).await?;
-        
-        // Kiểm tra cấp độ người dùng
-        match self.current_user_level {
+
+    // Tự động giao dịch dựa trên phân loại token và cấp độ người dùng
+    pub async fn auto_trade_by_safety(self: &Arc<Self>, token_address: &str) -> Result<TradeResult> {
+        let (token_status, risk_analysis) = self.analyze_token(token_address).await?;
+
+        // Không cấp độ nào được giao dịch token 🔴
+        if matches!(token_status.safety_level, TokenSafetyLevel::Red) {
+            return Err(anyhow!("Không giao dịch token có mức độ nguy hiểm cao"));
+        }
+        let config = self.get_auto_trade_config()
+            .ok_or_else(|| anyhow!("Chưa có cấu hình auto trade cho người dùng hiện tại"))?;
+        let level = self.get_current_subscription_level();
+        let decision = decide_trade(&token_status, &risk_analysis);
+
+        match level {
             SubscriptionLevel::Free => {
-                // Free user chỉ mua token 🟡 và 🟢, không mua token 🔴
-                match token_status.safety_level {
-                    TokenSafetyLevel::Red => {
-                        return Err("Free user không thể giao dịch token nguy hiểm".into());
-                    },
-                    TokenSafetyLevel::Yellow | TokenSafetyLevel::Green => {
-                        // Chỉ mua token an toàn và bán sau 30 phút
-                        if let Some(config) = &self.auto_trade_config {
-                            // Logic mua token an toàn
-                            let amount = "0.05"; // Giới hạn số lượng nhỏ cho free user
-                            let result = self.buy_token_with_amount(token_address, amount).await?;
-                            
-                            // Thiết lập bán tự động sau 30 phút
-                            if result.success {
-                                self.schedule_auto_sell(token_address, 30).await?;
-                            }
-                            
-                            return Ok(result);
-                        }
-                    }
+                // Giới hạn số lượng nhỏ và bán tự động sau thời gian cấu hình
+                let result = self.buy_token_with_gas(token_address, "0.05", None).await?;
+                if result.success {
+                    self.schedule_auto_sell(token_address, config.yellow_token_strategy.sell_after_minutes);
                 }
+                Ok(result)
             },
-            SubscriptionLevel::Premium => {
-                // Premium user chỉ mua token 🟡 và 🟢, không mua token 🔴
-                match token_status.safety_level {
-                    TokenSafetyLevel::Red => {
-                        return Err("Premium user không thể giao dịch token nguy hiểm".into());
-                    },
-                    TokenSafetyLevel::Yellow | TokenSafetyLevel::Green => {
-                        if let Some(config) = &self.auto_trade_config {
-                            // Sử dụng gas optimizer
-                            let optimized_gas = if self.gas_optimizer.is_some() {
-                                Some(self.optimize_gas().await?)
-                            } else {
-                                None
-                            };
-                            
-                            // Sử dụng AI để quyết định mua/bán
-                            let ai_decision = self.get_ai_trade_decision(token_address).await?;
-                            
-                            if ai_decision.confidence >= 0.6 && ai_decision.should_buy {
-                                let amount = "0.1"; // Số lượng lớn hơn cho premium user
-                                let result = self.buy_token_with_optimized_params(
-                                    token_address, 
-                                    amount, 
-                                    optimized_gas
-                                ).await?;
-                                
-                                // Thiết lập take profit và stop loss tự động
-                                if result.success {
-                                    self.set_take_profit_stop_loss(
-                                        token_address,
-                                        config.yellow_token_strategy.take_profit_percent,
-                                        config.yellow_token_strategy.stop_loss_percent
-                                    ).await?;
-                                }
-                                
-                                return Ok(result);
-                            } else {
-                                return Err(format!("AI quyết định không mua token (confidence {}%)", 
-                                                 ai_decision.confidence * 100.0).into());
-                            }
-                        }
-                    }
+            SubscriptionLevel::Basic | SubscriptionLevel::Premium => {
+                let optimized_gas = if self.subscription_config.tier_config(level).use_gas_optimizer {
+                    Some(self.optimize_gas().await?)
+                } else {
+                    None
+                };
+
+                if decision.confidence < config.ai_confidence_threshold || !decision.should_buy {
+                    return Err(anyhow!("Quyết định không mua token (confidence {:.0}%)", decision.confidence * 100.0));
+                }
+
+                let result = self.buy_token_with_gas(token_address, "0.1", optimized_gas).await?;
+                // Thiết lập take profit và stop loss tự động
+                if result.success {
+                    self.set_take_profit_stop_loss(
+                        token_address,
+                        config.yellow_token_strategy.take_profit_percent,
+                        config.yellow_token_strategy.stop_loss_percent,
+                    ).await?;
                 }
+                Ok(result)
             },
-            SubscriptionLevel::VIP => {
-                // VIP user có thể truy cập tất cả các tính năng, nhưng vẫn không mua token 🔴
-                match token_status.safety_level {
-                    TokenSafetyLevel::Red => {
-                        return Err("VIP user không thể giao dịch token nguy hiểm".into());
-                    },
-                    TokenSafetyLevel::Yellow | TokenSafetyLevel::Green => {
-                        if let Some(config) = &self.auto_trade_config {
-                            // Sử dụng mempool watching
-                            if config.yellow_token_strategy.use_mempool_data {
-                                // Kiểm tra cơ hội front-run
-                                let large_orders = self.get_large_orders_in_mempool(
-                                    token_address, 
-                                    config.green_token_strategy.min_order_size_usd
-                                ).await?;
-                                
-                                if !large_orders.is_empty() && config.green_token_strategy.front_run_orders {
-                                    // Thực hiện front-run
-                                    let front_run_result = self.front_run_transaction(
-                                        &large_orders[0], 
-                                        "0.2"
-                                    ).await?;
-                                    
-                                    if front_run_result.success && config.green_token_strategy.use_trailing_stop {
-                                        // Kích hoạt trailing stop loss
-                                        self.activate_trailing_stop_loss(
-                                            token_address, 
-                                            config.green_token_strategy.trailing_stop_percent
-                                        ).await?;
-                                    }
-                                    
-                                    return Ok(front_run_result);
-                                }
-                            }
-                            
-                            // Sử dụng AI phân tích nâng cao
-                            let ai_decision = self.get_ai_trade_decision(token_address).await?;
-                            
-                            if ai_decision.confidence >= 0.7 {
-                                if ai_decision.should_buy {
-                                    // Mua với các tham số tối ưu
-                                    let optimized_gas = self.optimize_gas().await?;
-                                    let amount = "0.3"; // Số lượng lớn hơn cho VIP
-                                    
-                                    let result = self.buy_token_with_optimized_params(
-                                        token_address, 
-                                        amount, 
-                                        Some(optimized_gas)
-                                    ).await?;
-                                    
-                                    // Thiết lập trailing stop loss
-                                    if result.success && config.green_token_strategy.use_trailing_stop {
-                                        self.activate_trailing_stop_loss(
-                                            token_address, 
-                                            config.green_token_strategy.trailing_stop_percent
-                                        ).await?;
-                                    }
-                                    
-                                    return Ok(result);
-                                } else if ai_decision.prediction == "pump_soon" {
-                                    // Mua thêm khi AI dự đoán sắp pump
-                                    let amount = "0.5"; // Mua nhiều hơn 
-                                    return self.buy_token_with_amount(token_address, amount).await;
-                                }
-                            }
-                            
-                            // Kiểm tra cơ hội sandwich nếu được kích hoạt
-                            if config.yellow_token_strategy.use_sandwich_mode || 
-                               config.green_token_strategy.use_sandwich_mode {
-                                // Tính toán phần trăm token sử dụng cho sandwich
-                                let sandwich_percent = 30.0; // % số dư dùng cho sandwich
-                                
-                                // Tìm cơ hội sandwich
-                                let sandwich_result = self.execute_sandwich_strategy_with_percent(
-                                    token_address,
-                                    sandwich_percent
-                                ).await;
-                                
-                                if let Ok(result) = sandwich_result {
-                                    return Ok(result);
-                                }
-                            }
+            SubscriptionLevel::Professional => {
+                // Kiểm tra cơ hội front-run từ mempool
+                if config.yellow_token_strategy.use_mempool_data && config.green_token_strategy.front_run_orders {
+                    let large_orders = self.get_large_orders_in_mempool(
+                        token_address,
+                        config.green_token_strategy.min_order_size_usd,
+                    ).await?;
+                    if let Some(order) = large_orders.first() {
+                        let front_run_result = self.front_run_transaction(order, "0.2").await?;
+                        if front_run_result.success && config.green_token_strategy.use_trailing_stop {
+                            self.activate_trailing_stop_loss(token_address, config.green_token_strategy.trailing_stop_percent).await?;
                         }
+                        return Ok(front_run_result);
                     }
                 }
-            }
-        }
-        
-        Err("Không tìm thấy cơ hội giao dịch phù hợp với cấp độ người dùng".into())
-    }
-    
-    // Lên lịch bán tự động sau một khoảng thời gian
-    async fn schedule_auto_sell(&self, token_address: &str, minutes: u64) -> Result<(), Box<dyn std::error::Error>> {
-        let token_address = token_address.to_string();
-        let snipebot = self.clone();
-        
-        tokio::spawn(async move {
-            tokio::time::sleep(std::time::Duration::from_secs(minutes * 60)).await;
-            
-            // Kiểm tra xem có còn sở hữu token không
-            if let Ok(has_position) = snipebot.has_position(&token_address).await {
-                if has_position {
-                    match snipebot.sell_token(&token_address, None, Some(100), None, None).await {
-                        Ok(result) => {
-                            info!("Bán tự động token {} thành công: tx={}", 
-                                 token_address, result.tx_hash.unwrap_or_default());
-                        },
-                        Err(e) => {
-                            error!("Không thể bán tự động token {}: {}", token_address, e);
+
+                if decision.confidence >= config.ai_confidence_threshold.max(0.7) {
+                    if decision.should_buy {
+                        let optimized_gas = self.optimize_gas().await?;
+                        let result = self.buy_token_with_gas(token_address, "0.3", Some(optimized_gas)).await?;
+                        if result.success && config.green_token_strategy.use_trailing_stop {
+                            self.activate_trailing_stop_loss(token_address, config.green_token_strategy.trailing_stop_percent).await?;
                         }
+                        return Ok(result);
+                    } else if decision.prediction == "pump_soon" {
+                        // Mua thêm khi dự đoán sắp pump
+                        return self.buy_token_with_gas(token_address, "0.5", None).await;
                     }
                 }
-            }
-        });
-        
-        Ok(())
-    }
-    
-    // Thiết lập take profit và stop loss
-    async fn set_take_profit_stop_loss(&self, token_address: &str, take_profit_percent: f64, stop_loss_percent: f64) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(trade_manager) = &self.trade_manager {
-            let manager = trade_manager.lock().await;
-            manager.set_profit_target(token_address, take_profit_percent)?;
-            manager.set_stop_loss(token_address, stop_loss_percent)?;
-        }
-        
-        Ok(())
-    }
-    
-    // Thiết lập cấp độ người dùng
-    pub fn set_user_level(&mut self, level: SubscriptionLevel) {
-        self.current_user_level = level;
-        
-        // Cập nhật cấu hình auto trade dựa trên cấp độ người dùng
-        self.update_auto_trade_config_for_subscription();
-    }
-    
-    // Cập nhật cấu hình auto trade dựa trên cấp độ người dùng
-    fn update_auto_trade_config_for_subscription(&mut self) {
-        let mut auto_trade_config = AutoTradeConfig::default();
-        
-        match self.current_user_level {
-            SubscriptionLevel::Free => {
-                let free_config = &self.subscription_config.free_config;
-                
-                // Cập nhật cấu hình cho Free user
-                auto_trade_config.enabled = free_config.enabled;
-                auto_trade_config.red_token_strategy = false; // Không bao giờ giao dịch token đỏ
-                
-                // Chỉ mua token vàng và xanh, bán sau 30 phút
-                auto_trade_config.yellow_token_strategy.buy_on_large_orders = free_config.allow_yellow_tokens;
-                auto_trade_config.yellow_token_strategy.sell_after_minutes = free_config.auto_sell_minutes;
-                auto_trade_config.yellow_token_strategy.use_sandwich_mode = false;
-                auto_trade_config.yellow_token_strategy.use_mempool_data = false;
-                
-                // Vô hiệu hóa các chiến lược nâng cao
-                auto_trade_config.green_token_strategy.front_run_orders = false;
-                auto_trade_config.green_token_strategy.use_trailing_stop = false;
-                auto_trade_config.green_token_strategy.use_sandwich_mode = false;
-                
-                // Vô hiệu hóa MEV
-                auto_trade_config.use_mev_strategies = false;
-            },
-            SubscriptionLevel::Premium => {
-                let premium_config = &self.subscription_config.premium_config;
-                
-                // Cập nhật cấu hình cho Premium user
-                auto_trade_config.enabled = premium_config.enabled;
-                auto_trade_config.red_token_strategy = false; // Không bao giờ giao dịch token đỏ
-                
-                // Mua token vàng và xanh với AI support
-                auto_trade_config.yellow_token_strategy.buy_on_large_orders = premium_config.allow_yellow_tokens;
-                auto_trade_config.yellow_token_strategy.sell_after_minutes = premium_config.auto_sell_minutes;
-                auto_trade_config.yellow_token_strategy.take_profit_percent = premium_config.take_profit_percent;
-                auto_trade_config.yellow_token_strategy.stop_loss_percent = premium_config.stop_loss_percent;
-                auto_trade_config.yellow_token_strategy.use_sandwich_mode = false;
-                auto_trade_config.yellow_token_strategy.use_mempool_data = true;
-                
-                // Gas optimizer cho Premium users
-                if premium_config.use_gas_optimizer && self.gas_optimizer.is_some() {
-                    auto_trade_config.max_gas_boost_percent = 150;
+
+                // Kiểm tra cơ hội sandwich nếu được kích hoạt
+                if config.yellow_token_strategy.use_sandwich_mode || config.green_token_strategy.use_sandwich_mode {
+                    if let Ok(result) = self.execute_sandwich_strategy_with_percent(token_address, 30.0).await {
+                        return Ok(result);
+                    }
                 }
-                
-                // Vô hiệu hóa front-run, nhưng vẫn cho phép phân tích AI
-                auto_trade_config.green_token_strategy.front_run_orders = false;
-                auto_trade_config.green_token_strategy.use_sandwich_mode = false;
-                
-                // Vô hiệu hóa MEV hoàn toàn
-                auto_trade_config.use_mev_strategies = false;
+
+                Err(anyhow!("Không tìm thấy cơ hội giao dịch phù hợp với cấp độ người dùng"))
             },
-            SubscriptionLevel::VIP => {
-                let vip_config = &self.subscription_config.vip_config;
-                
-                // Cập nhật cấu hình cho VIP user - mở tất cả các tính năng
-                auto_trade_config.enabled = vip_config.enabled;
-                auto_trade_config.red_token_strategy = false; // Không bao giờ giao dịch token đỏ
-                
-                // Mở tất cả các tính năng cho token vàng và xanh
-                auto_trade_config.yellow_token_strategy.buy_on_large_orders = vip_config.allow_yellow_tokens;
-                auto_trade_config.yellow_token_strategy.use_sandwich_mode = vip_config.use_sandwich_mode;
-                auto_trade_config.yellow_token_strategy.use_mempool_data = vip_config.use_mempool_watching;
-                auto_trade_config.yellow_token_strategy.take_profit_percent = vip_config.take_profit_percent;
-                auto_trade_config.yellow_token_strategy.stop_loss_percent = vip_config.stop_loss_percent;
-                
-                // Kích hoạt front-run và chiến lược nâng cao
-                auto_trade_config.green_token_strategy.front_run_orders = vip_config.use_front_run;
-                auto_trade_config.green_token_strategy.use_trailing_stop = vip_config.use_trailing_stop_loss;
-                auto_trade_config.green_token_strategy.trailing_stop_percent = vip_config.trailing_stop_percent;
-                auto_trade_config.green_token_strategy.use_sandwich_mode = vip_config.use_sandwich_mode;
-                auto_trade_config.green_token_strategy.take_profit_percent = vip_config.take_profit_percent;
-                
-                // Kích hoạt tính năng MEV
-                auto_trade_config.use_mev_strategies = true;
-                auto_trade_config.max_gas_boost_percent = 200;
+        }
+    }
+
+    // Mua token với gas price cho trước (None: tự tính), kiểm tra chi phí gas so với số dư
+    async fn buy_token_with_gas(&self, token_address: &str, amount: &str, gas_price: Option<U256>) -> Result<TradeResult> {
+        let amount_in = ethers::utils::parse_ether(amount)
+            .map_err(|e| anyhow!("Số lượng không hợp lệ: {}", e))?;
+
+        let gas_limit = match self.estimate_swap_gas(token_address, amount_in).await {
+            Ok(gas_limit) => gas_limit,
+            Err(e) => {
+                warn!("Không thể ước tính gas cho swap {}: {}, dùng gas limit mặc định", token_address, e);
+                self.config.default_gas_limit
             }
+        };
+        let gas_price = match gas_price {
+            Some(gas_price) => gas_price,
+            None => self.calculate_gas_fees(0).await?.0,
+        };
+        if !self.validate_transaction_cost_vs_balance(gas_limit, u256_to_f64(gas_price) / 1e9).await? {
+            return Err(anyhow!("Chi phí giao dịch quá cao so với số dư"));
         }
-        
-        self.auto_trade_config = Some(auto_trade_config);
+
+        let token_status = self.get_token_status(token_address).await?;
+        let token_info = TokenInfo {
+            address: token_address.to_string(),
+            symbol: token_status.symbol.clone(),
+            decimals: token_status.decimals,
+            router: self.config.router_address.clone(),
+            pair: token_status.pair_address.clone(),
+        };
+        let snipe_config = SnipeConfig {
+            gas_limit,
+            gas_price: gas_price.low_u64(),
+            slippage: self.config.default_slippage,
+            timeout: 300,
+            auto_approve: true,
+        };
+        let snipe_result = self.snipe(&token_info, amount_in, &snipe_config).await?;
+
+        let gas_cost_usd = match Address::from_str(&self.config.router_address) {
+            Ok(router) => {
+                let swap_tx = l2_fee::sample_tx(router, l2_fee::SWAP_CALLDATA_BYTES);
+                self.estimate_transaction_cost_usd(&swap_tx, gas_limit, gas_price).await.ok()
+            },
+            Err(_) => None,
+        };
+
+        Ok(TradeResult {
+            success: snipe_result.success,
+            tx_hash: snipe_result.transaction_hash,
+            amount_in: snipe_result.amount_in,
+            amount_out: snipe_result.estimated_amount_out,
+            price_per_token: Some(token_status.price_usd),
+            gas_used: None,
+            gas_price: gas_price.low_u64(),
+            timestamp: snipe_result.timestamp,
+            error: snipe_result.error,
+            trade_type: TradeType::Buy,
+            token_address: token_address.to_string(),
+            victim_tx_hash: None,
+            profit_usd: None,
+            gas_cost_usd,
+        })
     }
-    
-    // Thực hiện sandwich với phần trăm số dư cụ thể
-    async fn execute_sandwich_strategy_with_percent(&self, token_address: &str, percent: f64) -> Result<TradeResult, Box<dyn std::error::Error>> {
-        // Lấy cơ hội sandwich
-        if let Some(mempool_watcher) = &self.mempool_watcher {
-            let watcher = mempool_watcher.lock().await;
-            
-            if let Some(mempool_tracker) = &watcher.mempool_tracker {
-                if let Some(opportunity) = mempool_tracker.get_best_sandwich_opportunity() {
-                    if opportunity.token_address == token_address {
-                        // Tính toán số lượng ETH dựa trên phần trăm số dư
-                        let balance = self.get_wallet_balance().await?;
-                        let amount_to_use = balance * percent / 100.0;
-                        
-                        // Thực hiện sandwich
-                        return self.execute_sandwich(&opportunity, &amount_to_use.to_string()).await;
-                    }
+
+    // Lên lịch bán toàn bộ token sau một khoảng thời gian
+    fn schedule_auto_sell(self: &Arc<Self>, token_address: &str, minutes: u64) {
+        let token_address = token_address.to_string();
+        let snipebot = self.clone();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+
+            // Chỉ bán nếu vẫn còn sở hữu token
+            match snipebot.get_token_balance(&token_address).await {
+                Ok(balance) if !balance.is_zero() => {},
+                Ok(_) => return,
+                Err(e) => {
+                    error!("Không thể kiểm tra số dư token {} trước khi bán tự động: {}", token_address, e);
+                    return;
                 }
             }
-        }
-        
-        Err("Không tìm thấy cơ hội sandwich phù hợp".into())
+
+            let result = match snipebot.optimize_gas().await {
+                Ok(gas_price) => snipebot.sell_token_percent(&token_address, 100, gas_price).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(result) => info!("Bán tự động token {} thành công: tx={}",
+                                    token_address, result.transaction_hash.unwrap_or_default()),
+                Err(e) => error!("Không thể bán tự động token {}: {}", token_address, e),
+            }
+        });
     }
-    
-    // Tối ưu gas dựa trên điều kiện mạng
-    async fn optimize_gas(&self) -> Result<U256, Box<dyn std::error::Error>> {
-        let chain_name = self.chain_adapter.get_chain_name();
-        let rpc_url = self.chain_adapter.get_config().primary_rpc_urls[0].clone();
-        
-        match self.gas_optimizer.get_optimal_gas_price().await {
+
+    // Tối ưu gas dựa trên điều kiện mạng, mặc định 5 Gwei khi không tối ưu được
+    async fn optimize_gas(&self) -> Result<U256> {
+        let Some(optimizer) = self.get_gas_optimizer() else {
+            return Ok(U256::from(5_000_000_000u64));
+        };
+
+        match optimizer.get_optimal_gas_price(&self.chain_adapter).await {
             Ok(price) => {
-                info!("Giá gas tối ưu: {} Gwei", price.as_u128() as f64 / 1_000_000_000.0);
+                info!("Giá gas tối ưu: {} Gwei", u256_to_f64(price) / 1e9);
                 Ok(price)
             },
             Err(e) => {
                 warn!("Không thể tối ưu gas price: {}, sử dụng giá mặc định", e);
-                Ok(U256::from(5_000_000_000u64)) // 5 Gwei mặc định
+                Ok(U256::from(5_000_000_000u64))
             }
         }
     }
-    
-    // Lấy quyết định giao dịch từ AI
-    async fn get_ai_trade_decision(&self, token_address: &str) -> Result<AIDecision, Box<dyn std::error::Error>> {
-        if let Some(trade_manager) = &self.trade_manager {
-            let manager = trade_manager.lock().await;
-            
-            // Lấy phân tích AI từ trade manager
-            let ai_suggestions = manager.get_ai_suggestions(token_address);
-            
-            if !ai_suggestions.is_empty() {
-                // Lấy suggest gần nhất
-                let latest = ai_suggestions.last().unwrap();
-                
-                // Phân tích loại gợi ý
-                let should_buy = matches!(latest.suggestion_type, 
-                    AISuggestionType::BuyToken | AISuggestionType::IncreasePosition);
-                
-                // Trích xuất độ tin cậy từ lý do (nếu có)
-                let confidence = if latest.reason.contains("confidence:") {
-                    let parts: Vec<&str> = latest.reason.split("confidence:").collect();
-                    if parts.len() > 1 {
-                        parts[1].trim().parse::<f64>().unwrap_or(0.5)
-                    } else {
-                        0.5
-                    }
-                } else {
-                    0.5
-                };
-                
-                // Xác định dự đoán
-                let prediction = if latest.reason.contains("pump soon") {
-                    "pump_soon"
-                } else if latest.reason.contains("bearish") {
-                    "bearish"
-                } else {
-                    "neutral"
-                };
-                
-                return Ok(AIDecision {
-                    should_buy,
-                    confidence,
-                    prediction: prediction.to_string(),
-                });
-            }
-            
-            // Nếu không có suggest, trả về quyết định trung tính
-            return Ok(AIDecision {
-                should_buy: false,
-                confidence: 0.0,
-                prediction: "neutral".to_string(),
-            });
-        }
-        
-        Err("Không thể lấy dữ liệu AI, trade manager chưa được khởi tạo".into())
-    }
-    
-    // Phương thức thiết lập cấu hình subscription
-    pub fn set_subscription_config(&mut self, config: SubscriptionTradeConfig) {
-        self.subscription_config = config;
+
+    // Quyết định giao dịch cho token từ phân tích rủi ro và trạng thái hiện tại
+    async fn get_ai_trade_decision(&self, token_address: &str) -> Result<AIDecision> {
+        let (token_status, risk_analysis) = self.analyze_token(token_address).await?;
+        Ok(decide_trade(&token_status, &risk_analysis))
     }
-    
-    // Phương thức khởi tạo auto trade theo cấp độ người dùng
-    pub async fn start_auto_trade(&mut self, username: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Tìm người dùng trong database
-        if let Some(user_manager) = &self.user_manager {
-            let manager = user_manager.lock().await;
-            
-            if let Some(user) = manager.get_user(username) {
-                // Kiểm tra subscription có còn hạn không
-                if !user.subscription.is_active() {
-                    return Err("Subscription đã hết hạn".into());
-                }
-                
-                // Thiết lập cấp độ người dùng
-                self.current_user = Some(user.clone());
-                self.set_user_level(user.subscription.level.clone());
-                
-                // Bắt đầu auto trade service nếu chưa chạy
-                if let BotMode::Manual = self.bot_mode {
-                    self.set_bot_mode(BotMode::Auto);
-                }
-                
-                // Kiểm tra nếu auto trade service chưa chạy
-                self.start_auto_trade_service().await?;
-                
-                info!("Đã bắt đầu auto trade cho người dùng {} với cấp độ {:?}", 
-                      username, user.subscription.level);
-                
-                return Ok(());
-            }
-            
-            return Err(format!("Không tìm thấy người dùng {}", username).into());
+
+    // Bắt đầu auto trade cho người dùng (theo id) với cấp độ trong gói đăng ký
+    pub async fn start_auto_trade(self: &Arc<Self>, user_manager: &UserManager, user_id: &str) -> Result<()> {
+        let subscription = user_manager.get_user(user_id)
+            .ok_or_else(|| anyhow!("Không tìm thấy người dùng {}", user_id))?
+            .subscription.clone()
+            .ok_or_else(|| anyhow!("Người dùng {} chưa có gói đăng ký", user_id))?;
+        if !subscription.is_active() {
+            return Err(anyhow!("Subscription đã hết hạn"));
         }
-        
-        Err("User manager chưa được khởi tạo".into())
-    }
-    
-    // Phương thức lấy thông tin cấp độ của người dùng hiện tại
-    pub fn get_current_subscription_level(&self) -> SubscriptionLevel {
-        self.current_user_level.clone()
-    }
-    
-    // Phương thức lấy cấu hình auto trade cho cấp độ hiện tại
-    pub fn get_subscription_config(&self) -> &SubscriptionTradeConfig {
-        &self.subscription_config
-    }
 
-    // src/snipebot.rs - thêm phương thức này
-    async fn start_health_check(&self) {
-        let token_tracker_clone = self.token_status_tracker.clone();
-        let trade_manager_clone = self.trade_manager.clone();
-        
-        tokio::spawn(async move {
-            let check_interval = tokio::time::Duration::from_secs(60);
-            let mut interval = tokio::time::interval(check_interval);
-            
-            loop {
-                interval.tick().await;
-                
-                // Kiểm tra sức khỏe của token_tracker
-                if let Some(tracker) = &token_tracker_clone {
-                    match with_timeout(tracker.try_lock(), Duration::from_secs(1)).await {
-                        Ok(Ok(_)) => {
-                            // OK - có thể lấy lock
-                        },
-                        _ => {
-                            // Không thể lấy lock, có thể bị deadlock
-                            warn!("Không thể lấy lock cho token_tracker sau nhiều lần thử, có thể bị deadlock");
-                            
-                            // Ghi log và gửi thông báo để admin kiểm tra
-                            // TODO: Triển khai cơ chế thông báo
-                        }
-                    }
-                }
-                
-                // Tương tự cho trade_manager
-                // ...
-            }
-        });
-    }
-    
-    // Thêm phương thức để xử lý status updates
-    async fn process_status_updates(&mut self) {
-        if let Some(mut rx) = self.status_update_receiver.take() {
-            let trade_manager_weak = self.trade_manager.as_ref().map(Arc::downgrade);
-            let token_tracker_weak = self.token_status_tracker.as_ref().map(Arc::downgrade);
-            let risk_analyzer = self.risk_analyzer.clone();
-            
-            tokio::spawn(async move {
-                while let Some(update) = rx.recv().await {
-                    match update {
-                        StatusUpdate::TokenUpdate { token_address, new_status } => {
-                            // Cập nhật token status
-                            if let Some(tracker_weak) = &token_tracker_weak {
-                                if let Some(tracker) = tracker_weak.upgrade() {
-                                    if let Ok(mut tracker_lock) = tracker.try_lock() {
-                                        if let Err(e) = tracker_lock.update_token_status(&token_address, new_status) {
-                                            error!("Lỗi khi cập nhật token status: {}", e);
-                                        }
-                                        drop(tracker_lock);
-                                    }
-                                }
-                            }
-                        },
-                        StatusUpdate::PriceAlert { token_address, alert } => {
-                            // Xử lý cảnh báo giá
-                            if let Some(trade_mgr_weak) = &trade_manager_weak {
-                                if let Some(trade_mgr) = trade_mgr_weak.upgrade() {
-                                    if let Ok(trade_mgr_lock) = trade_mgr.try_lock() {
-                                        if let Err(e) = trade_mgr_lock.handle_price_alert(&alert).await {
-                                            error!("Lỗi khi xử lý cảnh báo giá: {}", e);
-                                        }
-                                        drop(trade_mgr_lock);
-                                    }
-                                }
-                            }
-                        },
-                        // Xử lý các loại update khác
-                        // ...
-                    }
-                }
-            });
+        {
+            let mut manager = self.subscription_manager.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+            manager.update_user_level(user_id, subscription.level.clone())?;
+            manager.check_subscription_limits(user_id, SubscriptionTradeType::AutoTrade)?;
         }
-    }
 
-    // Thêm phương thức xác thực địa chỉ ví
-    pub fn validate_wallet_address(&self, address: &str) -> Result<(), Box<dyn std::error::Error>> {
-        WalletManager::validate_wallet_address(address)
-    }
-    
-    // Thêm phương thức xác thực private key
-    pub fn validate_private_key(&self, private_key: &str) -> Result<(), Box<dyn std::error::Error>> {
-        WalletManager::validate_private_key(private_key)
-    }
-    
-    // Thêm phương thức xác thực mnemonic
-    pub fn validate_mnemonic(&self, mnemonic: &str) -> Result<(), Box<dyn std::error::Error>> {
-        WalletManager::validate_mnemonic(mnemonic)
+        // Thiết lập cấp độ người dùng
+        write_value(&self.current_user, Some(user_id.to_string()));
+        self.set_user_level(SubscriptionLevel::from(subscription.level.clone()));
+
+        // Chuyển sang chế độ Auto và khởi động hệ thống auto trade
+        self.switch_mode(BotMode::Auto).await?;
+        self.start_auto_trading_system().await?;
+
+        info!("Đã bắt đầu auto trade cho người dùng {} với cấp độ {:?}", user_id, subscription.level);
+        Ok(())
     }
 
-    // Cập nhật phương thức import_wallet_from_private_key
-    pub async fn import_wallet_from_private_key(&self, private_key: &str) -> Result<WalletInfo, Box<dyn std::error::Error>> {
-        // Xác thực private key trước khi tiếp tục
-        self.validate_private_key(private_key)?;
-        
-        let mut wallet_manager = self.wallet_manager.lock().await;
-        let wallet_info = wallet_manager.import_from_private_key(private_key)?;
-        
-        // Mã hóa private key
-        let mut wallet_info_mut = wallet_info.clone();
-        wallet_info_mut.encrypt_private_key(private_key, &wallet_manager.encryption_key)?;
-        
-        // Cập nhật ví trong danh sách
-        for wallet in &mut wallet_manager.wallets {
-            if wallet.address == wallet_info.address {
-                *wallet = wallet_info_mut.clone();
-                break;
-            }
+    // Cập nhật gói đăng ký của người dùng; người dùng đang chạy auto trade được áp dụng cấp độ mới ngay
+    pub async fn update_user_subscription(
+        &self,
+        user_manager: &mut UserManager,
+        user_id: &str,
+        new_level: diamond_common::user::SubscriptionLevel,
+        duration_days: u64,
+    ) -> Result<()> {
+        user_manager.update_user_subscription(user_id, new_level.clone(), duration_days).await?;
+        self.subscription_manager.write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .update_user_level(user_id, new_level.clone())?;
+
+        if read_cloned(&self.current_user).as_deref() == Some(user_id) {
+            self.set_user_level(SubscriptionLevel::from(new_level.clone()));
         }
-        
-        // Lưu thay đổi
-        wallet_manager.save_wallets().await?;
-        
-        Ok(wallet_info_mut)
+
+        info!("Cập nhật subscription của người dùng {} thành {:?} trong {} ngày", user_id, new_level, duration_days);
+        Ok(())
     }
-    
-    // Cập nhật phương thức import_wallet_from_mnemonic
-    pub async fn import_wallet_from_mnemonic(&self, mnemonic: &str, passphrase: Option<&str>) -> Result<WalletInfo, Box<dyn std::error::Error>> {
-        // Xác thực mnemonic trước khi tiếp tục
-        self.validate_mnemonic(mnemonic)?;
-        
-        let mut wallet_manager = self.wallet_manager.lock().await;
-        let wallet_info = wallet_manager.import_from_mnemonic(mnemonic, passphrase)?;
-        
-        // Mã hóa mnemonic
-        let mut wallet_info_mut = wallet_info.clone();
-        wallet_info_mut.encrypt_mnemonic(mnemonic, &wallet_manager.encryption_key)?;
-        
-        // Cập nhật ví trong danh sách
-        for wallet in &mut wallet_manager.wallets {
-            if wallet.address == wallet_info.address {
-                *wallet = wallet_info_mut.clone();
-                break;
-            }
+
+    // Khởi động chu kỳ auto trade chạy nền (chỉ một chu kỳ tại một thời điểm)
+    pub async fn start_auto_trading_system(self: &Arc<Self>) -> Result<()> {
+        info!("Khởi động hệ thống giao dịch tự động...");
+
+        if self.get_mode() != BotMode::Auto {
+            return Err(anyhow!("Bot không ở chế độ Auto"));
         }
-        
-        // Lưu thay đổi
-        wallet_manager.save_wallets().await?;
-        
-        Ok(wallet_info_mut)
-    }
-    
-    // Cập nhật phương thức create_new_wallet
-    pub async fn create_new_wallet(&mut self, passphrase: Option<&str>) -> Result<SafeWalletView, Box<dyn std::error::Error>> {
-        let mut wallet_manager = self.wallet_manager.lock().await;
-        let wallet_view = wallet_manager.create_new_wallet(passphrase)?;
-        
-        // Lưu thay đổi
-        wallet_manager.save_wallets().await?;
-        
-        Ok(wallet_view)
-    }
-    
-    // Thêm phương thức để lấy chain adapter theo chain_id
-    pub fn get_chain_adapter_by_id(&self, chain_id: u64) -> Option<&Box<dyn ChainAdapter>> {
-        // Giả sử hiện tại chỉ có một adapter
-        if self.chain_adapter.get_config().chain_id == chain_id {
-            Some(&self.chain_adapter)
-        } else {
-            None
+        if self.auto_trade_running.swap(true, Ordering::SeqCst) {
+            debug!("Chu kỳ auto trade đang chạy");
+            return Ok(());
         }
+
+        let bot = self.clone();
+        tokio::spawn(async move {
+            bot.start_auto_trade_cycle().await;
+            bot.auto_trade_running.store(false, Ordering::SeqCst);
+        });
+
+        info!("Hệ thống giao dịch tự động đã khởi động thành công");
+        Ok(())
     }
 
-    // Thêm phương thức cấu hình gas optimizer
-    pub fn configure_gas_optimizer(&mut self, 
-                                max_gas_price: Option<U256>,
-                                max_boost_percent: Option<u64>,
-                                sample_interval: Option<u64>) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(optimizer) = self.chain_adapter.get_gas_optimizer() {
-            if let Some(max_price) = max_gas_price {
-                optimizer.set_gas_price_limit(max_price);
-            }
-            
-            if let Some(boost) = max_boost_percent {
-                optimizer.set_max_boost_percent(boost);
+    // Chu kỳ auto trade: đánh giá các token đang theo dõi và giao dịch khi đủ độ tin cậy
+    async fn start_auto_trade_cycle(self: &Arc<Self>) {
+        while self.get_mode() == BotMode::Auto {
+            let config = self.get_auto_trade_config().unwrap_or_default();
+
+            if let Err(e) = self.update_token_pair_stats().await {
+                warn!("Không thể cập nhật thống kê cặp token: {}", e);
             }
-            
-            if let Some(interval) = sample_interval {
-                optimizer.set_sample_interval(interval);
+
+            let tokens = match self.get_token_tracker() {
+                Some(tracker) => tracker.lock().await.get_tracked_tokens(),
+                None => Vec::new(),
+            };
+            for token_address in tokens {
+                if self.get_mode() != BotMode::Auto {
+                    break;
+                }
+                let decision = match self.get_ai_trade_decision(&token_address).await {
+                    Ok(decision) => decision,
+                    Err(e) => {
+                        debug!("Bỏ qua token {}: {}", token_address, e);
+                        continue;
+                    }
+                };
+                if decision.confidence < config.ai_confidence_threshold || !decision.should_buy {
+                    continue;
+                }
+
+                match self.auto_trade_by_safety(&token_address).await {
+                    Ok(result) => {
+                        let _ = self.status_update_sender.try_send(StatusUpdate::TradeResult { result: Box::new(result) });
+                    },
+                    Err(e) => debug!("Không giao dịch token {}: {}", token_address, e),
+                }
             }
-            
-            info!("Đã cấu hình gas optimizer thành công");
-            Ok(())
-        } else {
-            Err("Gas optimizer chưa được khởi tạo".into())
-        }
-    }
-
-    // Phương thức update_user_subscription đã có trong SnipeBot, cần cập nhật để sử dụng UserManager
-    pub async fn update_user_subscription(&mut self, username: &str, new_level: SubscriptionLevel, duration_days: u64) -> Result<(), Box<dyn std::error::Error>> {
-        // Lấy User Manager
-        let user_manager_mutex = self.wallet_manager.clone();
-        let mut user_manager = user_manager_mutex.lock().await;
-        
-        // Cập nhật subscription trong UserManager
-        let user = user_manager.update_user_subscription(username, new_level, duration_days)?;
-        
-        // Lưu thay đổi vào bộ nhớ vĩnh viễn
-        user_manager.save_users().await?;
-        
-        // Cập nhật mức subscription hiện tại cho SnipeBot nếu user hiện tại
-        if let Some(current_user) = &self.current_user {
-            if current_user.address == username {
-                self.current_user_level = new_level.clone();
-                self.update_auto_trade_config_for_subscription();
+
+            // Tự động thu gọn cache token khi bộ nhớ bị áp lực
+            if config.auto_tuning_enabled && self.memory_pressure_detected().await {
+                if let Some(tracker) = self.get_token_tracker() {
+                    let removed = tracker.lock().await.reduce_cache_size(MEMORY_PRESSURE_TOKEN_LIMIT / 2);
+                    info!("Đã giảm {} token khỏi cache do áp lực bộ nhớ", removed);
+                }
             }
+
+            tokio::time::sleep(Duration::from_secs(config.cycle_interval_seconds.max(1))).await;
         }
-        
-        info!("Cập nhật subscription của người dùng {} thành {:?} trong {} ngày", 
-            username, new_level, duration_days);
-            
-        Ok(())
     }
 
-    /// Lấy ước tính chi phí gas cho một giao dịch swap
-    async fn estimate_swap_gas(&self, token_address: &str, amount_in: U256) -> Result<u64, Box<dyn std::error::Error>> {
+    /// Lấy ước tính gas limit cho một giao dịch swap native -> token (cộng 30% buffer)
+    async fn estimate_swap_gas(&self, token_address: &str, amount_in: U256) -> Result<u64> {
         debug!("Ước tính gas cho swap token {}", token_address);
-        
-        // Lấy đường dẫn swap từ native token đến token đích
-        let path = self.chain_adapter.get_native_to_token_path(token_address)?;
-        
-        // Lấy địa chỉ ví hiện tại
-        let wallet_address = match &self.current_wallet_address {
-            Some(addr) => addr.clone(),
-            None => return Err("Không có ví được chọn để ước tính gas".into()),
-        };
-        
-        // Lấy deadline (thời gian hiện tại + 20 phút)
-        let deadline = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs() + 1200;
-        
-        // Tạo tham số swap để ước tính gas
-        let router_address = self.chain_adapter.get_config().router_address.clone();
-        let provider = ethers::providers::Provider::<ethers::providers::Http>::try_from(&self.chain_adapter.get_config().rpc_url)?;
-        
-        // Lấy ABI của router
-        let router_abi = abi_utils::get_router_abi();
-        let router_addr = ethers::types::Address::from_str(&router_address)?;
-        let router_contract = ethers::contract::Contract::new(
-            router_addr, 
-            serde_json::from_str(router_abi)?, 
-            provider
-        );
-        
-        // Lấy tên hàm swap từ cấu hình
-        let swap_function = &self.chain_adapter.get_config().eth_to_token_swap_fn;
-        
-        // Min amount out (slippage 50% cho ước tính)
-        let min_amount_out = U256::from(1);
-        
-        // Địa chỉ ví của người dùng
-        let recipient = ethers::types::Address::from_str(&wallet_address)?;
-        
-        // Ước tính gas limit
-        let gas_estimate = match swap_function.as_str() {
-            "swapExactETHForTokens" => {
-                let call = router_contract.method::<_, ()>(
-                    "swapExactETHForTokens",
-                    (min_amount_out, path, recipient, U256::from(deadline))
-                )?;
-                
-                // Thêm value cho transaction
-                let call = call.value(amount_in);
-                
-                // Ước tính gas
-                call.estimate_gas().await?
-            },
-            "swapExactAVAXForTokens" => {
-                let call = router_contract.method::<_, ()>(
-                    "swapExactAVAXForTokens",
-                    (min_amount_out, path, recipient, U256::from(deadline))
-                )?;
-                
-                // Thêm value cho transaction
-                let call = call.value(amount_in);
-                
-                // Ước tính gas
-                call.estimate_gas().await?
-            },
-            _ => {
-                // Mặc định cho các chains khác
-                let call = router_contract.method::<_, ()>(
-                    "swapExactETHForTokens",
-                    (min_amount_out, path, recipient, U256::from(deadline))
-                )?;
-                
-                // Thêm value cho transaction
-                let call = call.value(amount_in);
-                
-                // Ước tính gas
-                call.estimate_gas().await?
-            }
-        };
-        
+
+        // Min amount out 1 chỉ dùng để ước tính
+        let tx = self.chain_adapter.build_swap_exact_eth_for_tokens_tx(
+            token_address,
+            amount_in,
+            U256::one(),
+            &self.get_current_wallet_address(),
+            safe_now() + 1200,
+            None,
+        )?;
+        let gas_estimate = self.chain_adapter.get_provider().estimate_gas(&tx, None).await?;
+
         // Thêm 30% buffer cho gas limit để tránh gas errors
-        let gas_limit = gas_estimate.as_u64() * 130 / 100;
-        
+        let gas_limit = gas_estimate.low_u64().saturating_mul(130) / 100;
         debug!("Ước tính gas limit cho swap {}: {}", token_address, gas_limit);
-        
         Ok(gas_limit)
     }
-    
-    /// Tính toán gas fees dựa trên các tham số khác nhau
-    async fn calculate_gas_fees(&self, gas_limit: u64, boost_percent: u64) -> Result<(U256, U256, U256), Box<dyn std::error::Error>> {
-        // Lấy gas price cơ bản
+
+    /// Tính gas price (có boost), max fee và priority fee; chain không hỗ trợ EIP-1559 có priority fee bằng 0
+    async fn calculate_gas_fees(&self, boost_percent: u64) -> Result<(U256, U256, U256)> {
         let base_gas_price = self.optimize_gas().await?;
-        let base_gas_price = U256::from(base_gas_price);
-        
-        // Tính gas price với boost
-        let boosted_gas_price = base_gas_price + (base_gas_price * U256::from(boost_percent) / U256::from(100));
-        
-        // Tính max fee per gas và priority fee nếu chain hỗ trợ EIP-1559
-        let (max_fee_per_gas, priority_fee_per_gas) = if self.chain_adapter.get_config().eip1559_supported {
-            // Lấy priority fee từ config
-            let priority_fee = match self.chain_adapter.get_config().max_priority_fee {
-                Some(fee) => {
-                    let priority_fee_gwei = ethers::utils::parse_units(fee.to_string(), "gwei")?;
-                    priority_fee_gwei
-                },
-                None => U256::from(1500000000), // 1.5 gwei mặc định
+        let boosted_gas_price = base_gas_price.saturating_add(base_gas_price.saturating_mul(U256::from(boost_percent)) / U256::from(100));
+
+        let chain_config = self.chain_adapter.get_config();
+        let (max_fee_per_gas, priority_fee_per_gas) = if chain_config.eip1559_supported {
+            let priority_fee = match chain_config.max_priority_fee {
+                Some(fee) => ethers::utils::parse_units(fee.to_string(), "gwei")?.into(),
+                None => U256::from(1_500_000_000u64), // 1.5 gwei mặc định
             };
-            
-            // Max fee = base fee + priority fee
-            let max_fee = boosted_gas_price + priority_fee;
-            
-            (max_fee, priority_fee)
+            (boosted_gas_price.saturating_add(priority_fee), priority_fee)
         } else {
             (boosted_gas_price, U256::zero())
         };
-        
-        // Trả về gas price, max fee, priority fee
+
         Ok((boosted_gas_price, max_fee_per_gas, priority_fee_per_gas))
     }
-    
-    /// Tính ước lượng chi phí giao dịch bằng USD
-    async fn estimate_transaction_cost_usd(&self, gas_limit: u64, gas_price: U256) -> Result<f64, Box<dyn std::error::Error>> {
-        // Tính tổng chi phí gas
-        let total_gas_cost = gas_price * U256::from(gas_limit);
-        
-        // Đổi từ wei sang ether
-        let gas_cost_eth = ethers::utils::format_units(total_gas_cost, "ether")?;
-        let gas_cost_eth: f64 = gas_cost_eth.parse()?;
-        
-        // Lấy giá native token
-        let eth_price = match self.chain_adapter.get_native_token_price().await {
-            Ok(price) => price,
-            Err(_) => 2000.0, // Giá mặc định nếu không lấy được
+
+    /// GasInfo cho một lệnh swap qua router, gồm cả phí dữ liệu L1 trên rollup
+    pub async fn swap_gas_info(&self, gas_price: U256, gas_limit: U256) -> GasInfo {
+        let gas_info = GasInfo::new_legacy(gas_price, gas_limit);
+        let router = match Address::from_str(&self.config.router_address) {
+            Ok(router) => router,
+            Err(_) => return gas_info,
         };
-        
-        // Tính chi phí USD
-        let gas_cost_usd = gas_cost_eth * eth_price;
-        
-        Ok(gas_cost_usd)
-    }
-
-    pub async fn initialize_mempool_monitoring(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let mempool_tracker = Arc::new(Mutex::new(MempoolTracker::new(
-            self.config.min_large_tx_amount
-        )));
-        
-        // Đăng ký callback từ mempool_tracker đến token_status_tracker
-        {
-            let token_tracker = self.token_status_tracker.clone();
-            let chain_adapter = self.chain_adapter.clone();
-            
-            let mempool = mempool_tracker.clone();
-            let callback = Box::new(move |pending_swap: PendingSwap| {
-                let token_addr = pending_swap.token_address.clone();
-                let tt = token_tracker.clone();
-                let ca = chain_adapter.clone();
-                
-                tokio::spawn(async move {
-                    // Khi phát hiện token mới từ mempool, cập nhật vào token_status_tracker
-                    if let Ok(mut tracker) = tt.lock() {
-                        // Thêm token vào danh sách theo dõi
-                        match tracker.add_token_to_track(&token_addr).await {
-                            Ok(_) => {
-                                debug!("Đã thêm token {} từ mempool vào theo dõi", token_addr);
-                                
-                                // Lấy thông tin token
-                                if let Ok(token_info) = ca.get_token_info(&token_addr).await {
-                                    // Lập tức kiểm tra và cập nhật trạng thái
-                                    if let Err(e) = tracker.update_token_status(&token_addr).await {
-                                        warn!("Không thể cập nhật trạng thái token: {}", e);
-                                    }
-                                }
-                            },
-                            Err(e) => warn!("Không thể thêm token vào theo dõi: {}", e),
-                        }
-                    }
-                });
-            });
-            
-            // Đăng ký callback vào mempool_tracker
-            if let Ok(mut tracker) = mempool_tracker.lock() {
-                tracker.add_new_token_callback(callback);
+        let swap_tx = l2_fee::sample_tx(router, l2_fee::SWAP_CALLDATA_BYTES)
+            .gas(gas_limit)
+            .gas_price(gas_price);
+
+        match self.chain_adapter.estimate_total_cost(&swap_tx).await {
+            Ok(estimate) => estimate.apply_to_gas_info(gas_info),
+            Err(e) => {
+                warn!("Không thể ước tính phí dữ liệu L1, chỉ tính phí thực thi: {}", e);
+                gas_info
             }
         }
-        
-        // Lưu trữ trong SnipeBot
-        self.mempool_tracker = Some(mempool_tracker);
-        
-        Ok(())
-    }
-
-    // Trong SnipeBot
-    pub async fn start_auto_trading_system(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        info!("Khởi động hệ thống giao dịch tự động...");
-        
-        // 1. Khởi tạo các thành phần
-        self.initialize_mempool_monitoring().await?;
-        self.initialize_token_status_tracker().await?;
-        self.initialize_risk_analyzer().await?;
-        self.initialize_ai_module().await?;
-        self.initialize_monte_equilibrium().await?;
-        self.initialize_trade_manager().await?;
-        self.initialize_auto_tuner().await?;
-        
-        // 2. Thiết lập các callbacks giữa các thành phần
-        self.connect_component_callbacks().await?;
-        
-        // 3. Bắt đầu các dịch vụ chạy nền
-        self.start_background_services().await?;
-        
-        // 4. Bắt đầu chu trình auto trade
-        if self.config.auto_trade_enabled && self.bot_mode == BotMode::Auto {
-            self.start_auto_trade_cycle().await?;
-        }
-        
-        info!("Hệ thống giao dịch tự động đã khởi động thành công");
-        
-        Ok(())
     }
 
-    async fn start_auto_trade_cycle(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // Chạy một chu kỳ auto-trade
-        loop {
-            // 1. Tìm token mới từ mempool
-            self.discover_new_tokens_from_mempool().await?;
-            
-            // 2. Cập nhật trạng thái tất cả token đang theo dõi
-            self.update_tracked_tokens_status().await?;
-            
-            // 3. Thực hiện phân tích AI và đề xuất giao dịch
-            let ai_recommendations = self.get_ai_trading_recommendations().await?;
-            
-            // 4. Tối ưu hóa và thực hiện giao dịch
-            for rec in &ai_recommendations {
-                if rec.confidence >= self.auto_trade_config.ai_confidence_threshold {
-                    // Tối ưu hóa tham số giao dịch
-                    let optimized_params = self.monte_equilibrium.optimize_trade_parameters(
-                        &rec.token_address,
-                        &rec.action_type,
-                        rec.confidence
-                    ).await?;
-                    
-                    // Thực hiện giao dịch
-                    self.trade_manager.execute_optimized_trade(
-                        &rec.token_address,
-                        &rec.action_type,
-                        &optimized_params
-                    ).await?;
+    /// Ước lượng chi phí giao dịch bằng USD (gồm cả phí dữ liệu L1 trên rollup)
+    async fn estimate_transaction_cost_usd(&self, tx: &TransactionRequest, gas_limit: u64, gas_price: U256) -> Result<f64> {
+        let priced_tx = tx.clone().gas(gas_limit).gas_price(gas_price);
+        let total_gas_cost = match self.chain_adapter.estimate_total_cost(&priced_tx).await {
+            Ok(estimate) => {
+                if estimate.fee_model.has_l1_fee() {
+                    debug!("Phí dữ liệu L1 ({}): {} wei / tổng {} wei",
+                        estimate.fee_model, estimate.l1_data_fee, estimate.total_fee);
                 }
+                estimate.total_fee
+            },
+            Err(e) => {
+                warn!("Không thể ước tính tổng chi phí, chỉ tính phí thực thi: {}", e);
+                gas_price.saturating_mul(U256::from(gas_limit))
             }
-            
-            // 5. Tự động điều chỉnh các tham số hệ thống
-            if self.auto_tuning_enabled {
-                self.auto_tuner.optimize_system_parameters().await?;
-            }
-            
-            // 6. Đợi khoảng thời gian trước khi bắt đầu chu kỳ mới
-            tokio::time::sleep(tokio::time::Duration::from_secs(self.auto_trade_config.cycle_interval_seconds)).await;
-        }
+        };
+
+        self.convert_native_to_usd(total_gas_cost).await
     }
 
-    // Thêm phương thức kiểm tra tích hợp
-    pub async fn check_module_integration(&self) -> Result<String, Box<dyn std::error::Error>> {
+    // Kiểm tra các thành phần của bot đã khởi tạo và khả dụng
+    pub async fn check_module_integration(&self) -> Result<String> {
         let mut status = vec![];
-        
+
         // Kiểm tra Chain Adapter
-        if self.chain_adapter.is_connected().await {
-            status.push("ChainAdapter: Đã kết nối".to_string());
-        } else {
-            status.push("ChainAdapter: Không kết nối".to_string());
+        match self.measure_rpc_response_time().await {
+            Ok(_) => status.push("ChainAdapter: Đã kết nối".to_string()),
+            Err(e) => status.push(format!("ChainAdapter: Không kết nối ({})", e)),
         }
-        
+
         // Kiểm tra TokenStatusTracker
-        if let Some(tracker) = &self.token_status_tracker {
-            if let Ok(_) = tracker.lock() {
-                status.push("TokenStatusTracker: Khả dụng".to_string());
-            } else {
-                status.push("TokenStatusTracker: Lỗi mutex".to_string());
-            }
-        } else {
-            status.push("TokenStatusTracker: Chưa khởi tạo".to_string());
-        }
-        
-        // Kiểm tra AIModule
-        if let Some(ai_module) = &self.ai_module {
-            if let Ok(_) = ai_module.lock() {
-                status.push("AIModule: Khả dụng".to_string());
-            } else {
-                status.push("AIModule: Lỗi mutex".to_string());
-            }
-        } else {
-            status.push("AIModule: Chưa khởi tạo".to_string());
+        match self.get_token_tracker() {
+            Some(tracker) => match tracker.try_lock() {
+                Ok(_) => status.push("TokenStatusTracker: Khả dụng".to_string()),
+                Err(_) => status.push("TokenStatusTracker: Đang bận".to_string()),
+            },
+            None => status.push("TokenStatusTracker: Chưa khởi tạo".to_string()),
         }
-        
+
         // Kiểm tra TradeManager
-        if let Some(trade_manager) = &self.trade_manager {
-            // Kiểm tra trade manager
-            status.push("TradeManager: Khả dụng".to_string());
-        } else {
-            status.push("TradeManager: Chưa khởi tạo".to_string());
+        match self.get_trade_manager() {
+            Some(_) => status.push("TradeManager: Khả dụng".to_string()),
+            None => status.push("TradeManager: Chưa khởi tạo".to_string()),
         }
-        
-        // Kiểm tra các module khác tương tự
-        
+
+        // Kiểm tra MempoolWatcher
+        match self.get_mempool_watcher() {
+            Some(_) => status.push("MempoolWatcher: Khả dụng".to_string()),
+            None => status.push("MempoolWatcher: Chưa khởi tạo".to_string()),
+        }
+
+        status.push(format!("Tích hợp: {}", self.verify_module_integration().await));
         Ok(status.join("\n"))
     }
 
-    // Dòng 2671, thêm phương thức sau check_module_integration
-
-    pub async fn check_system_performance(&self) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
-        // Khởi tạo kết quả
+    // Các chỉ số hiệu năng của bot: RPC, mempool, gas, lock contention, giao dịch và bộ nhớ
+    pub async fn check_system_performance(&self) -> Result<HashMap<String, f64>> {
         let mut performance_metrics = HashMap::new();
-        
-        // Kiểm tra thời gian phản hồi của các RPC endpoint
-        let chain_id = self.chain_adapter.get_chain_id().await?;
+
+        // Kiểm tra thời gian phản hồi của RPC endpoint
         let rpc_response_time = self.measure_rpc_response_time().await?;
         performance_metrics.insert("rpc_response_time_ms".to_string(), rpc_response_time);
-        
-        // Kiểm tra mempool performance
-        if let Some(mempool_watcher) = &self.mempool_watcher {
-            if let Ok(watcher) = mempool_watcher.try_lock() {
-                performance_metrics.insert("mempool_tx_per_second".to_string(), watcher.get_transaction_rate());
-                performance_metrics.insert("mempool_pending_count".to_string(), watcher.get_pending_transaction_count() as f64);
+
+        // Kiểm tra mempool
+        if let Some(watcher) = self.get_mempool_watcher() {
+            if let Ok(pending) = watcher.check_mempool().await {
+                performance_metrics.insert("mempool_pending_count".to_string(), pending.len() as f64);
             }
         }
-        
+
         // Kiểm tra trạng thái mạng
-        let gas_price = self.chain_adapter.get_gas_price().await?;
-        performance_metrics.insert("current_gas_price_gwei".to_string(), gas_price.as_u128() as f64 / 1_000_000_000.0);
-        
+        let estimated_cost = self.get_estimated_cost().await?;
+        performance_metrics.insert("current_gas_price_gwei".to_string(), estimated_cost.gas_price_gwei);
+        if let Some(swap_cost_usd) = estimated_cost.swap_cost_usd {
+            performance_metrics.insert("swap_cost_usd".to_string(), swap_cost_usd);
+        }
+
         // Kiểm tra mutex contention
-        if let Some(token_status_tracker) = &self.token_status_tracker {
-            let start = std::time::Instant::now();
-            let _guard = match tokio::time::timeout(
-                std::time::Duration::from_millis(500),
-                token_status_tracker.lock()
-            ).await {
+        if let Some(tracker) = self.get_token_tracker() {
+            let start = Instant::now();
+            match with_timeout(tracker.lock(), Duration::from_millis(500)).await {
                 Ok(_) => {
-                    let elapsed = start.elapsed().as_micros() as f64 / 1000.0;
-                    performance_metrics.insert("token_tracker_lock_time_ms".to_string(), elapsed);
+                    performance_metrics.insert("token_tracker_lock_time_ms".to_string(), start.elapsed().as_micros() as f64 / 1000.0);
                     performance_metrics.insert("token_tracker_lock_contention".to_string(), 0.0);
-                    None // Không cần giữ khóa
                 },
                 Err(_) => {
                     performance_metrics.insert("token_tracker_lock_contention".to_string(), 1.0);
-                    None
-                }
-            };
-        }
-        
-        // Kiểm tra AI module performance
-        if let Some(ai_module) = &self.ai_module {
-            let start = std::time::Instant::now();
-            if let Ok(ai) = ai_module.try_lock() {
-                let elapsed = start.elapsed().as_micros() as f64 / 1000.0;
-                performance_metrics.insert("ai_module_lock_time_ms".to_string(), elapsed);
-                
-                // Kiểm tra thời gian dự đoán AI
-                let test_features = HashMap::from([
-                    ("price".to_string(), 1.0),
-                    ("volume_24h".to_string(), 1000000.0),
-                    ("liquidity".to_string(), 500000.0),
-                    ("holders_count".to_string(), 100.0),
-                ]);
-                
-                let predict_start = std::time::Instant::now();
-                match tokio::time::timeout(
-                    std::time::Duration::from_secs(2),
-                    ai.model.predict(test_features.clone())
-                ).await {
-                    Ok(Ok(_)) => {
-                        let predict_time = predict_start.elapsed().as_millis() as f64;
-                        performance_metrics.insert("ai_prediction_time_ms".to_string(), predict_time);
-                    },
-                    _ => {
-                        performance_metrics.insert("ai_prediction_time_ms".to_string(), -1.0);
-                    }
                 }
-            } else {
-                performance_metrics.insert("ai_module_lock_contention".to_string(), 1.0);
             }
         }
-        
+
         // Kiểm tra hiệu suất giao dịch
-        if let Some(trade_manager) = &self.trade_manager {
+        if let Some(trade_manager) = self.get_trade_manager() {
             if let Ok(manager) = trade_manager.try_lock() {
-                if let Ok(stats) = manager.get_trade_performance_stats().await {
-                    performance_metrics.insert("trade_success_rate".to_string(), stats.success_rate);
-                    performance_metrics.insert("average_slippage".to_string(), stats.average_slippage);
-                    performance_metrics.insert("average_execution_time_ms".to_string(), stats.average_execution_time_ms);
+                let stats = manager.stats();
+                if stats.total_trades > 0 {
+                    performance_metrics.insert("trade_success_rate".to_string(),
+                        stats.successful_trades as f64 / stats.total_trades as f64);
                 }
+                performance_metrics.insert("total_profit_usd".to_string(), stats.total_profit_usd);
             }
         }
-        
-        // Kiểm tra memory usage của các module chính
-        performance_metrics.insert("mempool_memory_usage_mb".to_string(), self.estimate_module_memory_usage("mempool"));
-        performance_metrics.insert("ai_module_memory_usage_mb".to_string(), self.estimate_module_memory_usage("ai"));
-        performance_metrics.insert("monte_equilibrium_memory_usage_mb".to_string(), self.estimate_module_memory_usage("monte"));
-        
+
+        // Ước tính memory usage của các module chính
+        performance_metrics.insert("mempool_memory_usage_mb".to_string(), self.estimate_module_memory_usage("mempool").await);
+        performance_metrics.insert("token_tracker_memory_usage_mb".to_string(), self.estimate_module_memory_usage("token_tracker").await);
+
         Ok(performance_metrics)
     }
 
-    // Đo thời gian phản hồi của RPC endpoint hiện tại
-    async fn measure_rpc_response_time(&self) -> Result<f64, Box<dyn std::error::Error>> {
-        let start = std::time::Instant::now();
-        match tokio::time::timeout(
-            std::time::Duration::from_secs(5),
-            self.chain_adapter.get_block_number()
-        ).await {
+    // Đo thời gian phản hồi (ms) của RPC endpoint hiện tại
+    async fn measure_rpc_response_time(&self) -> Result<f64> {
+        let start = Instant::now();
+        match with_timeout(self.chain_adapter.get_provider().get_block_number(), Duration::from_secs(5)).await {
             Ok(Ok(_)) => Ok(start.elapsed().as_micros() as f64 / 1000.0),
-            Ok(Err(e)) => Err(format!("Lỗi khi đo RPC response time: {}", e).into()),
-            Err(_) => Err("Timeout khi đo RPC response time".into()),
+            Ok(Err(e)) => Err(anyhow!("Lỗi khi đo RPC response time: {}", e)),
+            Err(_) => Err(anyhow!("Timeout khi đo RPC response time")),
         }
     }
 
-    // Ước tính memory usage của các module chính
-    fn estimate_module_memory_usage(&self, module_name: &str) -> f64 {
-        // Trong thực tế, cần triển khai đo memory usage thực tế
-        // Đây chỉ là placeholder
-        match module_name {
-            "mempool" => {
-                if let Some(mempool) = &self.mempool_watcher {
-                    if let Ok(watcher) = mempool.try_lock() {
-                        return (watcher.get_pending_transaction_count() * 500) as f64 / (1024.0 * 1024.0);
-                    }
-                }
-            },
-            "ai" => {
-                // Ước tính 50MB cho AI model
-                return 50.0;
+    // Ước tính memory usage (MB) của module theo số phần tử đang giữ
+    async fn estimate_module_memory_usage(&self, module_name: &str) -> f64 {
+        let entries = match module_name {
+            "mempool" => match self.get_mempool_watcher() {
+                Some(watcher) => watcher.check_mempool().await.map(|pending| pending.len()).unwrap_or(0),
+                None => 0,
             },
-            "monte" => {
-                // Ước tính 100MB cho Monte Equilibrium
-                return 100.0;
+            "token_tracker" => match self.get_token_tracker() {
+                Some(tracker) => match tracker.try_lock() {
+                    Ok(tracker) => tracker.get_tracked_tokens().len(),
+                    Err(_) => 0,
+                },
+                None => 0,
             },
-            _ => {}
-        }
-        
-        0.0
+            _ => 0,
+        };
+        (entries * 500) as f64 / (1024.0 * 1024.0)
     }
 
-    // Phương thức hỗ trợ để kiểm tra tích hợp giữa các module
+    // Kiểm tra tích hợp giữa các module: mọi token trong vị thế phải được tracker theo dõi
     async fn verify_module_integration(&self) -> String {
         let mut issues = Vec::new();
-        
-        // Kiểm tra TradeManager <-> AIModule
-        if let (Some(trade_manager), Some(ai_module)) = (&self.trade_manager, &self.ai_module) {
-            match tokio::time::timeout(
-                std::time::Duration::from_secs(1),
-                async {
-                    let _trade = trade_manager.try_lock();
-                    let _ai = ai_module.try_lock();
-                }
-            ).await {
-                Ok(_) => {},
-                Err(_) => {
-                    issues.push("Tích hợp TradeManager-AIModule: Có thể deadlock");
-                }
-            }
-        }
-        
-        // Kiểm tra TradeManager <-> MempoolWatcher
-        if let (Some(trade_manager), Some(mempool)) = (&self.trade_manager, &self.mempool_watcher) {
-            if let (Ok(tm), Ok(mp)) = (trade_manager.try_lock(), mempool.try_lock()) {
-                if !tm.is_mempool_integration_valid() || !mp.is_trade_manager_integration_valid() {
-                    issues.push("Tích hợp TradeManager-MempoolWatcher: Không hợp lệ");
-                }
-            } else {
-                issues.push("Tích hợp TradeManager-MempoolWatcher: Không thể kiểm tra (lock)");
-            }
-        }
-        
-        // Kiểm tra TokenStatusTracker <-> TradeManager
-        if let (Some(token_tracker), Some(trade_manager)) = (&self.token_status_tracker, &self.trade_manager) {
-            if let (Ok(tt), Ok(tm)) = (token_tracker.try_lock(), trade_manager.try_lock()) {
-                // Kiểm tra xem tất cả các token trong vị thế có được theo dõi không
-                let all_positions = tm.get_all_positions();
-                let tracked_tokens = tt.get_tracked_tokens();
-                
-                for position in &all_positions {
-                    if !tracked_tokens.contains(&position.token_address) {
-                        issues.push(format!("Tích hợp TokenTracker-TradeManager: Token {} không được theo dõi", 
-                                           position.token_address));
+
+        if let (Some(token_tracker), Some(trade_manager)) = (self.get_token_tracker(), self.get_trade_manager()) {
+            if let (Ok(tracker), Ok(manager)) = (token_tracker.try_lock(), trade_manager.try_lock()) {
+                for token_address in manager.position_tokens() {
+                    if !tracker.is_tracking(&token_address) {
+                        issues.push(format!("Tích hợp TokenTracker-TradeManager: Token {} không được theo dõi", token_address));
                     }
                 }
             } else {
-                issues.push("Tích hợp TokenTracker-TradeManager: Không thể kiểm tra (lock)");
+                issues.push("Tích hợp TokenTracker-TradeManager: Không thể kiểm tra (lock)".to_string());
             }
         }
-        
+
         if issues.is_empty() {
             "OK - Tất cả các module tích hợp đúng".to_string()
         } else {
@@ -3106,479 +2463,485 @@ impl SnipeBot {
         }
     }
 
-    // Phương thức hỗ trợ để thử dự đoán AI
-    async fn test_ai_prediction(&self, features: HashMap<String, f64>) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(ai_module) = &self.ai_module {
-            if let Ok(ai) = ai_module.try_lock() {
-                ai.model.predict(features).await?;
-                return Ok(());
-            }
-        }
-        
-        Err("Không thể dự đoán AI".into())
-    }
+    // Giá token (USD) từ DEX của chain hiện tại
+    pub async fn get_token_price_usd(&self, token_address: &str) -> Result<f64> {
+        let chain_id = self.chain_adapter.get_config().chain_id;
+        let adapter = chain_registry::get_adapter(chain_id)?;
+        let chain = chain_registry::get_chain_config(chain_id)?;
+        let oracle = DexPriceOracle::new(adapter, DexPriceConfig::from_chain_config(&chain));
 
-    async fn get_token_price_usd(&self, token_address: &str) -> Result<f64, Box<dyn std::error::Error>> {
-        // Lấy giá token/ETH (hoặc token/BNB...)
-        let token_eth_price = self.chain_adapter.get_token_price(token_address, None).await?;
-        
-        // Lấy giá ETH/USD (hoặc BNB/USD...)
-        let eth_price = match self.chain_adapter.get_native_token_price().await {
-            Ok(price) => price,
-            Err(e) => {
-                warn!("Không thể lấy giá native token: {}, dùng giá mặc định", e);
-                1500.0 // Giá mặc định
-            }
-        };
-        
-        // Tính giá token/USD
-        Ok(token_eth_price * eth_price)
+        let native_price = oracle.native_price_usd().await?
+            .ok_or_else(|| anyhow!("Không có giá native token cho chain hiện tại"))?;
+        let decimals = self.get_token_status(token_address).await?.decimals;
+        oracle.token_price_usd(Address::from_str(token_address)?, decimals, Some(native_price)).await?
+            .ok_or_else(|| anyhow!("Không có giá cho token {}", token_address))
     }
 
-    async fn get_estimated_cost(&self) -> Result<EstimatedCost, Box<dyn std::error::Error>> {
-        let gas_price = match self.chain_adapter.get_gas_price().await {
-            Ok(price) => price,
-            Err(e) => {
-                return Err(format!("Không thể lấy gas price: {}", e).into());
-            }
-        };
-        
-        let gas_price_gwei = gas_price.as_u128() as f64 / 1_000_000_000.0;
-        let eth_price = match self.chain_adapter.get_native_token_price().await {
-            Ok(price) => price,
-            Err(e) => {
-                warn!("Không thể lấy giá ETH: {}, sử dụng giá mặc định", e);
-                1500.0 // Giá mặc định
-            }
-        };
-        
+    // Chi phí ước tính cho swap và approve theo gas price hiện tại
+    async fn get_estimated_cost(&self) -> Result<EstimatedCost> {
+        let gas_price = self.chain_adapter.get_provider().get_gas_price().await
+            .map_err(|e| anyhow!("Không thể lấy gas price: {}", e))?;
+
         // Ước tính chi phí cho các loại giao dịch phổ biến
-        let swap_gas = 250_000;
-        let approve_gas = 65_000;
-        
-        let swap_cost_eth = (swap_gas as f64) * gas_price_gwei / 1_000_000_000.0;
-        let approve_cost_eth = (approve_gas as f64) * gas_price_gwei / 1_000_000_000.0;
-        
+        let swap_cost = self.estimate_swap_cost(250_000, gas_price).await;
+        let approve_cost = gas_price.saturating_mul(U256::from(65_000));
+
         Ok(EstimatedCost {
-            gas_price_gwei,
-            swap_cost_eth,
-            approve_cost_eth,
-            swap_cost_usd: swap_cost_eth * eth_price,
-            approve_cost_usd: approve_cost_eth * eth_price,
+            gas_price_gwei: u256_to_f64(gas_price) / 1e9,
+            swap_cost_eth: u256_to_f64(swap_cost) / 1e18,
+            approve_cost_eth: u256_to_f64(approve_cost) / 1e18,
+            swap_cost_usd: self.native_to_usd(swap_cost).await,
+            approve_cost_usd: self.native_to_usd(approve_cost).await,
         })
     }
 
-    // Kiểm tra chi phí giao dịch so với ETH USD
-    async fn validate_transaction_cost_vs_balance(&self, estimated_gas: u64, gas_price_gwei: f64) -> Result<bool, Box<dyn std::error::Error>> {
-        // Lấy giá token gốc (ETH, BNB, v.v) bằng cách sử dụng chain_adapter
-        let eth_price = match self.chain_adapter.get_native_token_price().await {
-            Ok(price) => price,
-            Err(e) => {
-                error!("Không thể lấy giá token gốc: {}", e);
-                return Ok(false);
-            }
-        };
+    // Chi phí giao dịch không được vượt quá 20% số dư native
+    async fn validate_transaction_cost_vs_balance(&self, estimated_gas: u64, gas_price_gwei: f64) -> Result<bool> {
+        let native_balance = self.get_native_balance().await?;
+        let balance_in_eth = u256_to_f64(native_balance) / 1e18;
+        let tx_cost_in_eth = estimated_gas as f64 * gas_price_gwei * 1e-9;
 
-        // Lấy gas price hiện tại
-        let current_gas_price = self.chain_adapter.get_gas_price().await?;
-        
-        // Lấy số dư token gốc
-        let native_balance = self.chain_adapter.get_native_balance().await?;
-        
-        // Tính toán chi phí giao dịch ước tính bằng USD
-        let tx_cost_in_eth = (estimated_gas as f64) * gas_price_gwei * 1e-9;
-        let tx_cost_in_usd = tx_cost_in_eth * eth_price;
-        
-        // Lấy số dư token gốc dưới dạng ETH
-        let balance_in_eth = native_balance.as_u128() as f64 / 1e18;
-        let balance_in_usd = balance_in_eth * eth_price;
-        
-        // Kiểm tra xem chi phí giao dịch có vượt quá 20% số dư hiện tại không
-        let tx_cost_percent = tx_cost_in_usd / balance_in_usd * 100.0;
-        
+        if balance_in_eth <= 0.0 {
+            warn!("Số dư native bằng 0, không đủ trả phí giao dịch");
+            return Ok(false);
+        }
+        let tx_cost_percent = tx_cost_in_eth / balance_in_eth * 100.0;
         if tx_cost_percent > 20.0 {
-            warn!(
-                "Chi phí giao dịch ({:.2} USD, {:.2}% số dư) quá cao so với số dư ({:.2} USD)",
-                tx_cost_in_usd, tx_cost_percent, balance_in_usd
-            );
+            warn!("Chi phí giao dịch ({:.6} native, {:.2}% số dư) quá cao so với số dư ({:.6} native)",
+                  tx_cost_in_eth, tx_cost_percent, balance_in_eth);
             return Ok(false);
         }
-        
-        Ok(true)
-    }
 
-    // Dữ liệu thống kê của một cặp token
-    async fn update_token_pair_stats(&self) -> Result<(), Box<dyn std::error::Error>> {
-        // Thực hiện cập nhật thống kê cặp token
-        
-        Ok(())
+        Ok(true)
     }
 
-    // Enum để xác định nguồn gây ra deadlock
-    #[derive(Debug, Clone, Copy)]
-    pub enum DeadlockSource {
-        TokenTracker,
-        TradeManager,
-        MempoolWatcher,
-        AIModule,
-        MonteEquilibrium,
+    // Cập nhật giá và thống kê cặp của các token đang theo dõi
+    async fn update_token_pair_stats(&self) -> Result<()> {
+        let Some(tracker) = self.get_token_tracker() else {
+            return Ok(());
+        };
+        let mut tracker = with_timeout(tracker.lock(), Duration::from_secs(5)).await
+            .map_err(|_| anyhow!("Timeout khi chờ lock token tracker"))?;
+        self.update_token_tracker_lock_time();
+        tracker.update_token_prices().await
+            .map_err(|e| anyhow!("Không thể cập nhật giá token: {}", e))
     }
 
-    // Phương thức kiểm tra xem có cần khôi phục deadlock không
+    // Phương thức kiểm tra xem có cần khôi phục deadlock không (không lấy được lock quá 60 giây)
     pub fn check_deadlock_recovery_needed(&self, source: DeadlockSource) -> bool {
-        match source {
-            DeadlockSource::TokenTracker => {
-                // Kiểm tra xem TokenTracker có bị treo không
-                if let Ok(last_lock_time) = self.last_token_tracker_lock_time.load(std::sync::atomic::Ordering::Relaxed) {
-                    let now = SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap_or_default()
-                        .as_secs();
-                    
-                    // Nếu thời gian cuối cùng lock là quá 60 giây trước, coi như bị treo
-                    if now - last_lock_time > 60 {
-                        warn!("Phát hiện TokenTracker có thể đã bị deadlock (không được cập nhật trong {} giây)", now - last_lock_time);
-                        return true;
-                    }
-                }
-                false
-            },
-            DeadlockSource::TradeManager => {
-                // Kiểm tra xem TradeManager có bị treo không
-                if let Ok(last_lock_time) = self.last_trade_manager_lock_time.load(std::sync::atomic::Ordering::Relaxed) {
-                    let now = SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap_or_default()
-                        .as_secs();
-                    
-                    // Nếu thời gian cuối cùng lock là quá 60 giây trước, coi như bị treo
-                    if now - last_lock_time > 60 {
-                        warn!("Phát hiện TradeManager có thể đã bị deadlock (không được cập nhật trong {} giây)", now - last_lock_time);
-                        return true;
-                    }
-                }
-                false
-            },
-            _ => false, // Các nguồn khác chưa được xử lý
+        let last_lock_time = match source {
+            DeadlockSource::TokenTracker => &self.last_token_tracker_lock_time,
+            DeadlockSource::TradeManager => &self.last_trade_manager_lock_time,
+        };
+        let elapsed = safe_now().saturating_sub(last_lock_time.load(Ordering::SeqCst));
+        if elapsed > 60 {
+            warn!("Phát hiện {:?} có thể đã bị deadlock (không được cập nhật trong {} giây)", source, elapsed);
+            return true;
         }
+        false
     }
 
-    // Phương thức khôi phục từ deadlock
-    pub async fn recover_from_deadlock(&self, source: DeadlockSource) -> Result<(), Box<dyn std::error::Error>> {
+    // Phương thức khôi phục từ deadlock: thay thành phần bị treo bằng bản mới
+    pub async fn recover_from_deadlock(&self, source: DeadlockSource) -> Result<()> {
         match source {
             DeadlockSource::TokenTracker => {
                 warn!("Đang khôi phục TokenTracker từ deadlock");
-                
-                // Tạo TokenTracker mới
-                if let Some(chain_adapter) = &self.chain_adapter {
-                    let config = chain_adapter.get_config();
-                    let new_tracker = Arc::new(Mutex::new(TokenStatusTracker::new(
-                        chain_adapter.clone(),
-                        vec![config.factory_address.clone()],
-                        vec![config.router_address.clone()],
-                        config.wrapped_native_token.clone(),
-                    )?));
-                    
-                    // Thay thế tracker cũ
-                    self.replace_token_tracker(new_tracker).await;
-                    
-                    // Ghi nhận thời gian khôi phục
-                    self.update_token_tracker_lock_time();
-                    
-                    info!("Đã khôi phục TokenTracker thành công");
-                } else {
-                    return Err("Không thể khôi phục TokenTracker: ChainAdapter không có sẵn".into());
-                }
+                let new_tracker = Arc::new(Mutex::new(self.build_token_tracker()?));
+                self.replace_token_tracker(new_tracker).await;
+                info!("Đã khôi phục TokenTracker thành công");
             },
             DeadlockSource::TradeManager => {
                 warn!("Đang khôi phục TradeManager từ deadlock");
-                
-                // Sử dụng try_write với timeout để tránh deadlock mới
-                let trade_manager_write_result = tokio::time::timeout(
-                    std::time::Duration::from_secs(5),
-                    async {
-                        self.trade_manager.try_write()
-                    }
-                ).await;
-                
-                match trade_manager_write_result {
-                    Ok(Ok(mut trade_manager_guard)) => {
-                        // Tạo TradeManager mới
-                        if let Some(chain_adapter) = &self.chain_adapter {
-                            let trade_config = trade::trade_logic::TradeConfig {
-                                gas_limit: U256::from(400000),
-                                gas_price: U256::from(5000000000u64),
-                                slippage: 0.5,
-                                timeout: 30,
-                                auto_approve: true,
-                                use_flashbots: self.config.advanced.use_flashbots,
-                                emergency_sell_gas_multiplier: 1.5,
-                                router_address: self.config.router_address.clone(),
-                                wrapped_native_token: self.config.wrapped_native_token.clone(),
-                                max_slippage: 2.0,
-                                twap_window_size: 10,
-                                twap_min_samples: 5,
-                                twap_update_interval: 60,
-                            };
-                            
-                            let new_manager = Arc::new(Mutex::new(TradeManager::new(
-                                Arc::new(chain_adapter.clone()),
-                                trade_config,
-                            )));
-                            
-                            // Cập nhật TradeManager
-                            *trade_manager_guard = Some(new_manager.clone());
-                            
-                            // Cập nhật timestamp để biết đã khôi phục thành công
-                            self.update_trade_manager_lock_time();
-                            
-                            info!("Đã khôi phục TradeManager thành công");
-                            return Ok(());
-                        }
-                        return Err("Không thể khôi phục TradeManager: Thiếu các component".into());
-                    },
-                    _ => {
-                        error!("Không thể khôi phục TradeManager: không thể lấy write lock");
-                        return Err("Không thể khôi phục TradeManager: không thể lấy write lock".into());
-                    }
-                }
+                let new_manager = self.build_trade_manager(
+                    self.risk_analyzer.clone(),
+                    self.get_gas_optimizer(),
+                    self.get_token_tracker(),
+                );
+                self.replace_trade_manager(Arc::new(Mutex::new(new_manager)));
+                info!("Đã khôi phục TradeManager thành công");
             },
-            // Tương tự cho các nguồn khác
-            _ => return Err(format!("Chưa hỗ trợ khôi phục từ nguồn: {:?}", source).into()),
         }
-        
         Ok(())
     }
-    
-    // Phương thức thay thế token tracker
+
+    // Thay token tracker; sao chép danh sách token từ tracker cũ nếu còn lấy được lock
     async fn replace_token_tracker(&self, new_tracker: Arc<Mutex<TokenStatusTracker>>) {
-        // Sử dụng RwLock thay vì unsafe
-        if let Ok(mut tracker_guard) = self.token_status_tracker.write() {
-            // Cố gắng copy dữ liệu từ tracker cũ nếu có thể
-            if let Some(old_tracker) = tracker_guard.as_ref() {
-                match old_tracker.try_lock() {
-                    Ok(tracker) => {
-                        // Copy dữ liệu
-                        if let Ok(mut new_tracker_guard) = new_tracker.try_lock() {
-                            // Copy dữ liệu từ tracker cũ sang mới
-                            for token_address in tracker.get_tracked_tokens() {
-                                if let Ok(token_status) = tracker.get_token(&token_address).await {
-                                    if let Some(status) = token_status {
-                                        if let Err(e) = new_tracker_guard.add_token(&token_address).await {
-                                            warn!("Không thể sao chép token {}: {}", token_address, e);
-                                        }
-                                    }
-                                }
-                            }
+        if let Some(old_tracker) = self.get_token_tracker() {
+            match old_tracker.try_lock() {
+                Ok(old_tracker) => {
+                    let mut tracker = new_tracker.lock().await;
+                    for token_address in old_tracker.get_tracked_tokens() {
+                        if let Err(e) = tracker.add_token(&token_address).await {
+                            warn!("Không thể sao chép token {}: {}", token_address, e);
                         }
-                    },
-                    Err(_) => {
-                        warn!("Không thể lấy dữ liệu từ tracker cũ do deadlock");
                     }
+                },
+                Err(_) => {
+                    warn!("Không thể lấy dữ liệu từ tracker cũ do deadlock, tracker mới bắt đầu trống");
                 }
             }
-            
-            // Gán tracker mới an toàn
-            *tracker_guard = Some(new_tracker);
-        }
-    }
-    
-    // Phương thức thay thế trade manager
-    async fn replace_trade_manager(&self, new_manager: Arc<Mutex<TradeManager<ChainAdapterEnum>>>) {
-        // Sử dụng try_write với timeout để tránh bị treo
-        match tokio::time::timeout(
-            std::time::Duration::from_secs(5),
-            async {
-                if let Ok(mut manager_guard) = self.trade_manager.try_write() {
-                    *manager_guard = Some(new_manager);
-                    self.update_trade_manager_lock_time();
-                    return true;
-                }
-                false
-            }
-        ).await {
-            Ok(true) => {
-                info!("Đã thay thế TradeManager thành công");
-            },
-            _ => {
-                error!("Không thể thay thế TradeManager: không thể lấy write lock");
+        }
+
+        write_value(&self.token_status_tracker, Some(new_tracker.clone()));
+        // Trade manager phải dùng tracker mới
+        if let Some(trade_manager) = self.get_trade_manager() {
+            if let Ok(mut manager) = trade_manager.try_lock() {
+                manager.set_token_status_tracker(Some(new_tracker));
             }
         }
+        self.update_token_tracker_lock_time();
+    }
+
+    // Thay trade manager
+    fn replace_trade_manager(&self, new_manager: Arc<Mutex<TradeManager<ChainAdapterEnum>>>) {
+        write_value(&self.trade_manager, Some(new_manager));
+        self.update_trade_manager_lock_time();
     }
-    
+
     // Ghi nhận thời gian khóa thành công
     fn update_token_tracker_lock_time(&self) {
-        // Sử dụng Atomic thay vì unsafe
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        
-        self.last_token_tracker_lock_time.store(now, Ordering::SeqCst);
-    }
-    
-    fn update_trade_manager_lock_time(&self) {
-        // Sử dụng Atomic thay vì unsafe
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        
-        self.last_trade_manager_lock_time.store(now, Ordering::SeqCst);
-    }
-    
-    // Phát hiện áp lực bộ nhớ
-    pub fn memory_pressure_detected(&self) -> bool {
-        // Simple heuristic: Kiểm tra kích thước cache của TokenTracker
-        if let Ok(tracker_guard) = self.token_status_tracker.read() {
-            if let Some(tracker) = tracker_guard.as_ref() {
-                if let Ok(token_tracker) = tracker.try_lock() {
-                    token_tracker.get_cache_size() > 10000
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        } else {
-            false
-        }
+        self.last_token_tracker_lock_time.store(safe_now(), Ordering::SeqCst);
     }
-}
 
-// Thêm các struct phụ trợ
+    fn update_trade_manager_lock_time(&self) {
+        self.last_trade_manager_lock_time.store(safe_now(), Ordering::SeqCst);
+    }
 
-#[derive(Debug, Clone)]
-pub struct SandwichOpportunity {
-    pub token_address: String,
-    pub victim_swap: PendingSwap,
-    pub potential_profit: f64,
-    pub estimated_price_impact: f64,
+    // Phát hiện áp lực bộ nhớ: cache token vượt ngưỡng
+    pub async fn memory_pressure_detected(&self) -> bool {
+        let Some(tracker) = self.get_token_tracker() else {
+            return false;
+        };
+        let Ok(tracker) = tracker.try_lock() else {
+            return false;
+        };
+        tracker.len().await.map(|size| size > MEMORY_PRESSURE_TOKEN_LIMIT).unwrap_or(false)
+    }
 }
 
-// Hàm tạo một instance mới của SnipeBot
-pub async fn create_new_snipebot(config: Config) -> Result<Arc<SnipeBot>, Box<dyn std::error::Error>> {
-    // Tạo storage manager
-    let storage = Arc::new(Storage::new(config.db_path.clone()));
-    
-    // Tạo instance của SnipeBot
-    let snipebot = SnipeBot::new(config, storage).await?;
-    
+// Đọc giá trị trong RwLock, kể cả khi lock bị poisoned
+/// Tạo SnipeBot cho chain trong cấu hình
+pub async fn create_new_snipebot(
+    config: Config,
+    storage: Arc<Storage>,
+    wallet_manager: Arc<WalletManager>,
+) -> Result<Arc<SnipeBot>> {
+    let chain_adapter = create_chain_adapter(&config.chain_name).await?;
+    let snipebot = SnipeBot::new(config, storage, chain_adapter, wallet_manager).await?;
     Ok(Arc::new(snipebot))
 }
 
-#[derive(Debug, Clone)]
-struct AIDecision {
-    should_buy: bool,
-    confidence: f64,
-    prediction: String,
+// Chờ future với thời hạn; dùng khi lấy lock để tránh treo vô hạn
+async fn with_timeout<T>(future: impl Future<Output = T>, duration: Duration) -> Result<T, tokio::time::error::Elapsed> {
+    tokio::time::timeout(duration, future).await
 }
 
-// Thiết lập cấu hình subscription
-pub fn set_subscription_config(&mut self, config: SubscriptionTradeConfig) {
-    self.subscription_config = config;
+// Quyết định giao dịch theo heuristic từ phân tích rủi ro và biến động giá
+// (chưa có module AI trong tree)
+fn decide_trade(status: &TokenStatus, risk: &TokenRiskAnalysis) -> AIDecision {
+    let safety = (100.0 - risk.base.risk_score).clamp(0.0, 100.0) / 100.0;
+    let momentum = (status.price_change_24h / 100.0).clamp(-1.0, 1.0);
+    let activity = (status.pending_tx_count as f64 / 20.0).min(1.0);
+
+    let confidence = (safety * 0.6 + momentum.max(0.0) * 0.25 + activity * 0.15).clamp(0.0, 1.0);
+    let should_buy = !matches!(status.safety_level, TokenSafetyLevel::Red) && momentum > -0.1;
+    let prediction = if activity >= 0.5 && momentum >= 0.0 {
+        "pump_soon"
+    } else if momentum < -0.1 {
+        "dump"
+    } else {
+        "neutral"
+    };
+
+    AIDecision { should_buy, confidence, prediction: prediction.to_string() }
 }
 
-// Tạo một API endpoint để cập nhật cấp độ người dùng
-pub async fn update_user_subscription(&mut self, username: &str, new_level: SubscriptionLevel, duration_days: u64) -> Result<(), Box<dyn std::error::Error>> {
-    // Tìm người dùng trong database
-    if let Some(user_manager) = &self.user_manager {
-        let mut manager = user_manager.lock().await;
-        
-        if let Some(user) = manager.get_user_mut(username) {
-            // Cập nhật subscription
-            user.subscription = Subscription::new(new_level, duration_days);
-            
-            // Lưu thay đổi
-            manager.save_users().await?;
-            
-            // Nếu người dùng hiện tại, cập nhật cấp độ trong SnipeBot
-            if let Some(current_user) = &self.current_user {
-                if current_user.username == username {
-                    self.set_user_level(new_level);
-                }
-            }
-            
-            return Ok(());
-        }
-        
-        return Err(format!("Không tìm thấy người dùng {}", username).into());
+fn read_cloned<T: Clone>(lock: &RwLock<T>) -> T {
+    match lock.read() {
+        Ok(value) => value.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
     }
-    
-    Err("User manager chưa được khởi tạo".into())
 }
 
-// Triển khai API endpoint để bắt đầu auto trade
-pub async fn start_auto_trade(&mut self, username: &str) -> Result<(), Box<dyn std::error::Error>> {
-    // Kiểm tra cấp độ người dùng
-    if let Some(user_manager) = &self.user_manager {
-        let manager = user_manager.lock().await;
-        
-        if let Some(user) = manager.get_user(username) {
-            // Thiết lập user level
-            self.set_user_level(user.subscription.level.clone());
-            
-            // Chuyển sang chế độ Auto
-            self.set_bot_mode(BotMode::Auto);
-            
-            // Bắt đầu dịch vụ auto trade
-            self.start_auto_trade_service().await?;
-            
-            return Ok(());
-        }
-        
-        return Err(format!("Không tìm thấy người dùng {}", username).into());
+// Ghi giá trị vào RwLock, kể cả khi lock bị poisoned
+fn write_value<T>(lock: &RwLock<T>, value: T) {
+    match lock.write() {
+        Ok(mut current) => *current = value,
+        Err(poisoned) => *poisoned.into_inner() = value,
     }
-    
-    Err("User manager chưa được khởi tạo".into())
 }
 
-async fn with_timeout<T>(future: impl Future<Output = T>, duration: Duration) -> Result<T, tokio::time::error::Elapsed> {
-    tokio::time::timeout(duration, future).await
+/// Lợi nhuận kỳ vọng (wei native) khi mua `our_in` ngay trước lệnh mua `victim_in` rồi bán lại toàn bộ.
+/// Suy ra reserve của pool x*y=k từ hai báo giá router (`our_in` -> `our_out`, `our_in + victim_in` -> `combined_out`);
+/// None khi báo giá không khớp mô hình constant product.
+fn estimate_front_run_profit(our_in: U256, victim_in: U256, our_out: U256, combined_out: U256) -> Option<f64> {
+    let (x, v) = (u256_to_f64(our_in), u256_to_f64(victim_in));
+    let (t1, t2) = (u256_to_f64(our_out), u256_to_f64(combined_out));
+    if x <= 0.0 || v <= 0.0 || t1 <= 0.0 || t2 <= t1 {
+        return None;
+    }
+
+    // t(a) = Y * a / (R + a)  =>  giải R, Y từ hai điểm báo giá
+    let ratio = t2 / t1;
+    let denominator = ratio * x - x - v;
+    if denominator >= 0.0 {
+        return None;
+    }
+    let native_reserve = x * (x + v) * (1.0 - ratio) / denominator;
+    if native_reserve <= 0.0 {
+        return None;
+    }
+    let token_reserve = t1 * (native_reserve + x) / x;
+
+    // Sau hai lệnh mua, bán lại t1 token (trừ 0.3% phí pool)
+    let sell_out = (native_reserve + x + v) * t1 / (token_reserve - t2 + t1) * 0.997;
+    Some(sell_out - x)
+}
+
+// amount * (100 - slippage%) / 100, tính theo basis point để không mất độ chính xác của U256
+fn apply_slippage(amount: U256, slippage_percent: f64) -> U256 {
+    let slippage_bps = (slippage_percent.clamp(0.0, 100.0) * 100.0).round() as u64;
+    amount.saturating_mul(U256::from(10_000 - slippage_bps)) / U256::from(10_000)
+}
+
+fn u256_to_f64(value: U256) -> f64 {
+    ethers::utils::format_units(value, 0).ok()
+        .and_then(|s| s.parse::<f64>().ok())
+        .unwrap_or(f64::MAX)
 }
 
-// Hàm ví dụ để minh họa cách sử dụng with_timeout
-async fn example_with_timeout<T>(mutex: Arc<Mutex<T>>) -> Option<MutexGuard<T>> {
-    match with_timeout(mutex.lock(), Duration::from_secs(5)).await {
-        Ok(Ok(guard)) => Some(guard),
-        Ok(Err(e)) => {
-            error!("Lỗi khi lấy lock: {}", e);
-            None
-        },
-        Err(_) => {
-            error!("Timeout khi lấy lock");
-            None
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_adapters::base::EVMAdapter;
+    use crate::chain_adapters::chain_config_loader::bundled_chain_configs;
+    use crate::chain_adapters::tests::{
+        mock_chain::{MockChain, UniswapV2},
+        mock_contracts::TokenSpec,
+        wallet_fixture::{TestWallets, TEST_PASSPHRASE},
+    };
+    use ethers::providers::{Http, Provider};
+    use ethers::types::H256;
+    use diamond_wallet::signer_server::SignerServer;
+    use diamond_wallet::{RemoteSignerApi, RemoteSignerConfig, RemoteTransport, SpendingPolicy};
+
+    // Báo giá của pool x*y=k không phí
+    fn quote(native_reserve: f64, token_reserve: f64, amount_in: f64) -> U256 {
+        U256::from((token_reserve * amount_in / (native_reserve + amount_in)) as u128)
+    }
+
+    #[test]
+    fn test_front_run_profit_from_router_quotes() {
+        let (native_reserve, token_reserve) = (100e18, 1_000_000e18);
+        let (ours, victim) = (1e18, 20e18);
+        let profit = estimate_front_run_profit(
+            U256::from(ours as u128),
+            U256::from(victim as u128),
+            quote(native_reserve, token_reserve, ours),
+            quote(native_reserve, token_reserve, ours + victim),
+        ).unwrap();
+
+        let sell_out = (native_reserve + ours + victim) * quote(native_reserve, token_reserve, ours).as_u128() as f64
+            / (token_reserve - quote(native_reserve, token_reserve, ours + victim).as_u128() as f64
+                + quote(native_reserve, token_reserve, ours).as_u128() as f64) * 0.997;
+        assert!((profit - (sell_out - ours)).abs() / ours < 1e-6);
+        assert!(profit > 0.0);
+
+        // Lệnh nạn nhân quá nhỏ: phí pool lớn hơn biến động giá
+        let small_victim = 1e15;
+        let profit = estimate_front_run_profit(
+            U256::from(ours as u128),
+            U256::from(small_victim as u128),
+            quote(native_reserve, token_reserve, ours),
+            quote(native_reserve, token_reserve, ours + small_victim),
+        ).unwrap();
+        assert!(profit < 0.0);
+
+        // Báo giá không tăng theo lượng vào: không ước tính được
+        assert!(estimate_front_run_profit(U256::from(1), U256::from(1), U256::from(5), U256::from(5)).is_none());
+    }
+
+    #[test]
+    fn test_each_level_has_its_own_tier_config() {
+        let config = SubscriptionTradeConfig::default();
+        assert!(std::ptr::eq(config.tier_config(SubscriptionLevel::Basic), &config.basic_config));
+        assert!(std::ptr::eq(config.tier_config(SubscriptionLevel::Premium), &config.premium_config));
+        // Gói Basic không có mempool watching của Premium
+        assert!(!config.basic_config.use_mempool_watching);
+        assert!(config.premium_config.use_mempool_watching);
+    }
+
+    /// Mock chain có pool MOCK/WETH trên Uniswap V2
+    async fn spawn_dex_market(chain_id: u64) -> (MockChain, UniswapV2, Address) {
+        let ether = U256::exp10(18);
+        let chain = MockChain::spawn(chain_id).await.unwrap();
+        let dex = chain.deploy_uniswap_v2().unwrap();
+        let token = chain.deploy_erc20(&TokenSpec::new("Mock Token", "MOCK", 18)).unwrap();
+        chain.add_liquidity_eth(&dex, token, ether * 1_000_000, ether * 100).unwrap();
+        (chain, dex, token)
+    }
+
+    /// SnipeBot trên mock chain, ký bằng WalletManager của `wallets`
+    async fn mock_chain_bot(chain: &MockChain, dex: &UniswapV2, wallets: &TestWallets) -> Result<SnipeBot> {
+        let mut chain_config = bundled_chain_configs().chains.into_iter()
+            .find(|source| source.key == "ethereum")
+            .unwrap()
+            .config;
+        chain_config.chain_id = chain.chain_id();
+        chain_config.primary_rpc_urls = vec![chain.http_url().to_string()];
+        chain_config.backup_rpc_urls.clear();
+        chain_config.wrapped_native_token = Some(dex.weth);
+        chain_config.router_contracts = HashMap::from([("uniswap_v2".to_string(), dex.router)]);
+        chain_config.factory_contracts = HashMap::from([("uniswap_v2".to_string(), dex.factory)]);
+        let adapter = EVMAdapter::new(chain_config.to_adapter_config()).await?;
+
+        let config = Config {
+            chain_id: chain.chain_id(),
+            rpc_url: chain.http_url().to_string(),
+            router_address: format!("{:?}", dex.router),
+            weth_address: format!("{:?}", dex.weth),
+            wallet_folder: wallets.dir.path().join("wallets").to_string_lossy().to_string(),
+            wallet_encryption_seed: TEST_PASSPHRASE.to_string(),
+            auto_retry_count: 1,
+            ..Config::default()
+        };
+        SnipeBot::new(
+            config,
+            Arc::new(Storage::new()),
+            ChainAdapterEnum::Custom("mock".to_string(), Arc::new(adapter)),
+            wallets.manager.clone(),
+        ).await
+    }
+
+    fn mock_token_info(dex: &UniswapV2, token: Address) -> TokenInfo {
+        TokenInfo {
+            address: format!("{:?}", token),
+            symbol: "MOCK".to_string(),
+            decimals: 18,
+            router: format!("{:?}", dex.router),
+            pair: None,
         }
     }
-}
 
-/// Hàm retry giao dịch blockchain
-async fn retry_blockchain_tx<F, T, E>(
-    operation: F,
-    max_retries: usize,
-    initial_backoff_ms: u64,
-) -> Result<T, Box<dyn std::error::Error>>
-where
-    F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, E>> + Send>> + Send + Sync,
-    E: std::fmt::Display + 'static,
-{
-    let mut retries = 0;
-    let mut backoff_ms = initial_backoff_ms;
-
-    loop {
-        match operation().await {
-            Ok(result) => return Ok(result),
-            Err(e) => {
-                if retries >= max_retries {
-                    return Err(format!("Đã hết số lần thử lại: {}", e).into());
-                }
+    fn mock_snipe_config() -> SnipeConfig {
+        SnipeConfig {
+            gas_limit: 300_000,
+            gas_price: 2_000_000_000,
+            slippage: 5.0,
+            timeout: 300,
+            auto_approve: false,
+        }
+    }
 
-                warn!("Lỗi giao dịch (lần thử {}): {}. Thử lại sau {} ms", retries + 1, e, backoff_ms);
-                tokio::time::sleep(tokio::time::Duration::from_millis(backoff_ms)).await;
+    /// Giao dịch của bot được ký qua WalletManager nên hạn mức chi tiêu của ví chặn lệnh vượt mức
+    /// trước khi có gì được gửi lên chain
+    #[tokio::test]
+    async fn test_snipe_over_wallet_limit_is_rejected() {
+        let ether = U256::exp10(18);
+        let (chain, dex, token) = spawn_dex_market(1_101).await;
 
-                retries += 1;
-                backoff_ms *= 2; // Tăng thời gian chờ theo cấp số nhân
-            }
+        let wallets = TestWallets::new(chain.chain_id());
+        let trader = wallets.import_dev_wallet(&chain, 1);
+        wallets.manager.set_spending_policy(trader, SpendingPolicy {
+            max_value_per_tx: Some(ether / 2),
+            ..Default::default()
+        }).unwrap();
+
+        let bot = mock_chain_bot(&chain, &dex, &wallets).await.unwrap();
+        assert_eq!(bot.get_current_wallet_address(), format!("{:?}", trader));
+        let token_info = mock_token_info(&dex, token);
+
+        let error = bot.snipe(&token_info, ether, &mock_snipe_config()).await.unwrap_err();
+        assert!(error.to_string().contains("exceeds per-transaction limit"), "{:#}", error);
+        assert_eq!(chain.method_calls("eth_sendRawTransaction"), 0);
+
+        // Lệnh trong hạn mức vẫn được ký và gửi
+        let result = bot.snipe(&token_info, ether / 10, &mock_snipe_config()).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(chain.method_calls("eth_sendRawTransaction"), 1);
+    }
+
+    /// Mỗi lệnh snipe đồng thời khóa một ví khác trong nhóm và giao dịch được ký, trả phí
+    /// và lấy nonce từ chính ví đó chứ không phải ví mặc định của adapter
+    #[tokio::test]
+    async fn test_concurrent_group_snipes_send_from_leased_wallets() {
+        let ether = U256::exp10(18);
+        let (chain, dex, token) = spawn_dex_market(1_103).await;
+
+        let wallets = TestWallets::new(chain.chain_id());
+        let members = [wallets.import_dev_wallet(&chain, 1), wallets.import_dev_wallet(&chain, 2)];
+        for member in members {
+            wallets.manager.add_to_group("snipers", member).unwrap();
         }
+
+        let bot = mock_chain_bot(&chain, &dex, &wallets).await.unwrap();
+        bot.set_trade_wallet_group(Some("snipers".to_string()));
+        let token_info = mock_token_info(&dex, token);
+
+        // Giữ cả hai giao dịch trong mempool để hai lease chồng lên nhau
+        chain.set_automine(false);
+        let miner = {
+            let chain = chain.clone();
+            tokio::spawn(async move {
+                while chain.pending_transactions().len() < 2 {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+                chain.mine(1);
+            })
+        };
+        let snipe_config = mock_snipe_config();
+        let (first, second) = tokio::join!(
+            bot.snipe(&token_info, ether / 10, &snipe_config),
+            bot.snipe(&token_info, ether / 10, &snipe_config),
+        );
+        miner.await.unwrap();
+
+        let provider = Provider::<Http>::try_from(chain.http_url()).unwrap();
+        let mut senders = Vec::new();
+        for result in [first.unwrap(), second.unwrap()] {
+            assert!(result.success, "{:?}", result.error);
+            let hash = H256::from_str(result.transaction_hash.as_deref().unwrap()).unwrap();
+            senders.push(provider.get_transaction(hash).await.unwrap().unwrap().from);
+        }
+        senders.sort();
+        let mut expected = members.to_vec();
+        expected.sort();
+        assert_eq!(senders, expected);
+        assert_eq!(chain.method_calls("eth_sendRawTransaction"), 2);
+    }
+
+    /// Chỉ có signer từ xa (Web3Signer qua Unix socket): bot ký qua signer đó mà không nạp khóa nào
+    /// vào tiến trình; khi signer không còn phản hồi thì giao dịch thất bại chứ không ký bằng khóa cục bộ
+    #[tokio::test]
+    async fn test_snipe_with_remote_signer_only() {
+        let ether = U256::exp10(18);
+        let (chain, dex, token) = spawn_dex_market(1_102).await;
+
+        let wallets = TestWallets::new(chain.chain_id());
+        let socket = wallets.dir.path().join("signer.sock");
+        let server = SignerServer::new(chain.dev_wallet(1)).spawn_unix(&socket).unwrap();
+        let trader = wallets.manager.add_remote_signer(&RemoteSignerConfig {
+            transport: RemoteTransport::UnixSocket { path: socket.clone() },
+            api: RemoteSignerApi::Web3Signer,
+            address: None,
+            chain_id: chain.chain_id(),
+        }).await.unwrap();
+        assert_eq!(trader, chain.dev_address(1));
+
+        let bot = mock_chain_bot(&chain, &dex, &wallets).await.unwrap();
+        assert_eq!(bot.get_current_wallet_address(), format!("{:?}", trader));
+        assert!(wallets.manager.list_wallets().unwrap().is_empty());
+        let token_info = mock_token_info(&dex, token);
+
+        let result = bot.snipe(&token_info, ether / 10, &mock_snipe_config()).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(chain.method_calls("eth_sendRawTransaction"), 1);
+
+        // Signer từ xa ngừng hoạt động: không có bản ký nào khác được gửi
+        server.abort();
+        std::fs::remove_file(&socket).unwrap();
+        let error = bot.snipe(&token_info, ether / 10, &mock_snipe_config()).await.unwrap_err();
+        assert!(error.to_string().contains("Remote signer transport error"), "{:#}", error);
+        assert_eq!(chain.method_calls("eth_sendRawTransaction"), 1);
     }
-}
\ No newline at end of file
+}
@@ -41,8 +41,7 @@ impl Storage {
     }
     
     pub async fn save_to_file(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let txs = self.transactions.lock().unwrap();
-        let txs_vec: Vec<Transaction> = txs.values().cloned().collect();
+        let txs_vec = self.get_all_transactions();
         let json = serde_json::to_string(&txs_vec)?;
         fs::write(path, json).await?;
         Ok(())
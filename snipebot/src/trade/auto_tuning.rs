@@ -780,6 +780,11 @@ impl TradeResultAnalysis for TradeResult {
     }
     
     fn get_gas_cost_usd(&self) -> f64 {
+        // Ưu tiên chi phí đã tính sẵn (bao gồm phí dữ liệu L1 trên rollup)
+        if let Some(cost) = self.gas_cost_usd {
+            return cost;
+        }
+        
         // Ước lượng chi phí gas
         match self.gas_used {
             Some(gas) => {
@@ -0,0 +1,379 @@
+use std::sync::Arc;
+use anyhow::{Result, anyhow};
+use ethers::providers::Middleware;
+use ethers::types::{BlockNumber, FeeHistory, U256};
+use serde::{Serialize, Deserialize};
+use tracing::{debug, warn};
+
+use crate::chain_adapters::GasInfo;
+
+/// Các percentile reward được yêu cầu từ `eth_feeHistory`, theo thứ tự của `FeeUrgency`
+pub const REWARD_PERCENTILES: [f64; 4] = [10.0, 50.0, 75.0, 95.0];
+
+/// Base fee thay đổi tối đa 1/8 mỗi block theo EIP-1559
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// Độ phân giải (phần triệu) khi quy đổi tỷ lệ gas sử dụng sang số nguyên
+const GAS_USED_RATIO_SCALE: u64 = 1_000_000;
+
+/// Mức độ khẩn cấp của giao dịch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FeeUrgency {
+    /// Chấp nhận chờ, dùng percentile 10
+    Slow,
+    /// Mặc định, dùng percentile 50
+    Normal,
+    /// Ưu tiên vào block sớm, dùng percentile 75
+    Fast,
+    /// Cần vào block kế tiếp (snipe), dùng percentile 95
+    Instant,
+}
+
+impl FeeUrgency {
+    /// Vị trí cột trong `FeeHistory.reward` tương ứng với `REWARD_PERCENTILES`
+    fn reward_index(&self) -> usize {
+        match self {
+            FeeUrgency::Slow => 0,
+            FeeUrgency::Normal => 1,
+            FeeUrgency::Fast => 2,
+            FeeUrgency::Instant => 3,
+        }
+    }
+
+    /// Percentile reward sử dụng
+    pub fn percentile(&self) -> f64 {
+        REWARD_PERCENTILES[self.reward_index()]
+    }
+
+    /// Số block base fee cần được bảo đảm trong max fee
+    pub fn projection_blocks(&self) -> u64 {
+        match self {
+            FeeUrgency::Slow => 1,
+            FeeUrgency::Normal => 2,
+            FeeUrgency::Fast => 3,
+            FeeUrgency::Instant => 5,
+        }
+    }
+
+    /// Giả định độ lấp đầy block khi dự phóng base fee. `None` = dùng trung bình gần đây.
+    fn assumed_utilization(&self) -> Option<f64> {
+        match self {
+            FeeUrgency::Slow | FeeUrgency::Normal => None,
+            FeeUrgency::Fast | FeeUrgency::Instant => Some(1.0),
+        }
+    }
+}
+
+impl std::fmt::Display for FeeUrgency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeeUrgency::Slow => write!(f, "Slow"),
+            FeeUrgency::Normal => write!(f, "Normal"),
+            FeeUrgency::Fast => write!(f, "Fast"),
+            FeeUrgency::Instant => write!(f, "Instant"),
+        }
+    }
+}
+
+/// Giới hạn phí áp dụng lên kết quả ước tính
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeeCaps {
+    /// % tăng tối đa so với mức Normal (từ `AutoTradeConfig.max_gas_boost_percent`)
+    pub max_gas_boost_percent: Option<u64>,
+    /// Max fee tuyệt đối (gwei) (từ `max_gas_price_gwei` của gói subscription hiện tại)
+    pub max_gas_price_gwei: Option<u64>,
+}
+
+impl FeeCaps {
+    /// Tạo giới hạn phí
+    pub fn new(max_gas_boost_percent: Option<u64>, max_gas_price_gwei: Option<u64>) -> Self {
+        Self {
+            max_gas_boost_percent,
+            max_gas_price_gwei,
+        }
+    }
+
+    /// Max fee tuyệt đối tính bằng wei
+    pub fn max_gas_price_wei(&self) -> Option<U256> {
+        self.max_gas_price_gwei.map(|gwei| U256::from(gwei) * U256::exp10(9))
+    }
+}
+
+/// Kết quả ước tính phí EIP-1559
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    /// Mức khẩn cấp đã dùng
+    pub urgency: FeeUrgency,
+    /// Base fee của block kế tiếp
+    pub next_base_fee: U256,
+    /// Base fee dự phóng sau `projection_blocks` block
+    pub projected_base_fee: U256,
+    /// Max priority fee per gas
+    pub max_priority_fee_per_gas: U256,
+    /// Max fee per gas
+    pub max_fee_per_gas: U256,
+    /// Kết quả có bị giới hạn bởi `FeeCaps` hay không
+    pub capped: bool,
+}
+
+impl FeeEstimate {
+    /// Chuyển sang GasInfo để áp dụng vào giao dịch
+    pub fn to_gas_info(&self, gas_limit: U256) -> GasInfo {
+        GasInfo::new_eip1559(self.max_fee_per_gas, self.max_priority_fee_per_gas, gas_limit)
+    }
+}
+
+/// Cấu hình cho FeeEstimator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeEstimatorConfig {
+    /// Số block lấy từ `eth_feeHistory`
+    pub block_count: u64,
+    /// Priority fee tối thiểu (wei) khi lịch sử không có dữ liệu
+    pub min_priority_fee: U256,
+}
+
+impl Default for FeeEstimatorConfig {
+    fn default() -> Self {
+        Self {
+            block_count: 20,
+            min_priority_fee: U256::from(100_000_000u64), // 0.1 gwei
+        }
+    }
+}
+
+/// Ước tính phí EIP-1559 dựa trên percentile reward của `eth_feeHistory`
+#[derive(Debug, Clone, Default)]
+pub struct FeeEstimator {
+    config: FeeEstimatorConfig,
+}
+
+impl FeeEstimator {
+    /// Tạo FeeEstimator mới
+    pub fn new(config: FeeEstimatorConfig) -> Self {
+        Self { config }
+    }
+
+    /// Lấy fee history từ provider và ước tính phí
+    pub async fn estimate<M: Middleware>(
+        &self,
+        client: Arc<M>,
+        urgency: FeeUrgency,
+        caps: &FeeCaps,
+    ) -> Result<FeeEstimate> {
+        let history = client
+            .fee_history(self.config.block_count, BlockNumber::Latest, &REWARD_PERCENTILES)
+            .await
+            .map_err(|e| anyhow!("Không thể lấy eth_feeHistory: {}", e))?;
+        self.estimate_from_history(&history, urgency, caps)
+    }
+
+    /// Ước tính phí cho tất cả mức khẩn cấp từ một lần gọi `eth_feeHistory`
+    pub async fn estimate_all<M: Middleware>(
+        &self,
+        client: Arc<M>,
+        caps: &FeeCaps,
+    ) -> Result<Vec<FeeEstimate>> {
+        let history = client
+            .fee_history(self.config.block_count, BlockNumber::Latest, &REWARD_PERCENTILES)
+            .await
+            .map_err(|e| anyhow!("Không thể lấy eth_feeHistory: {}", e))?;
+
+        [FeeUrgency::Slow, FeeUrgency::Normal, FeeUrgency::Fast, FeeUrgency::Instant]
+            .iter()
+            .map(|urgency| self.estimate_from_history(&history, *urgency, caps))
+            .collect()
+    }
+
+    /// Ước tính phí từ dữ liệu fee history đã có
+    pub fn estimate_from_history(
+        &self,
+        history: &FeeHistory,
+        urgency: FeeUrgency,
+        caps: &FeeCaps,
+    ) -> Result<FeeEstimate> {
+        // base_fee_per_gas có n+1 phần tử: phần tử cuối là base fee của block kế tiếp
+        let next_base_fee = *history.base_fee_per_gas.last()
+            .ok_or_else(|| anyhow!("Fee history không có base fee"))?;
+
+        let utilization = urgency.assumed_utilization()
+            .unwrap_or_else(|| average_utilization(&history.gas_used_ratio).max(0.5));
+        let projected_base_fee = project_base_fee(next_base_fee, utilization, urgency.projection_blocks() - 1);
+
+        let priority_fee = self.priority_fee_percentile(history, urgency);
+        let mut max_priority_fee_per_gas = priority_fee;
+        let mut max_fee_per_gas = projected_base_fee + priority_fee;
+        let mut capped = false;
+
+        // Giới hạn theo % tăng so với mức Normal
+        if let Some(boost) = caps.max_gas_boost_percent {
+            if urgency != FeeUrgency::Normal {
+                let normal_priority = self.priority_fee_percentile(history, FeeUrgency::Normal);
+                let normal_base = project_base_fee(
+                    next_base_fee,
+                    average_utilization(&history.gas_used_ratio).max(0.5),
+                    FeeUrgency::Normal.projection_blocks() - 1,
+                );
+                let boost_limit = (normal_base + normal_priority) * U256::from(100 + boost) / U256::from(100);
+                if max_fee_per_gas > boost_limit {
+                    max_fee_per_gas = boost_limit;
+                    capped = true;
+                }
+            }
+        }
+
+        // Giới hạn tuyệt đối theo gói subscription
+        if let Some(limit) = caps.max_gas_price_wei() {
+            if max_fee_per_gas > limit {
+                warn!("Max fee {} vượt giới hạn {} gwei, áp dụng giới hạn", max_fee_per_gas, caps.max_gas_price_gwei.unwrap_or_default());
+                max_fee_per_gas = limit;
+                capped = true;
+            }
+        }
+
+        if max_priority_fee_per_gas > max_fee_per_gas {
+            max_priority_fee_per_gas = max_fee_per_gas;
+        }
+
+        debug!(
+            "Ước tính phí {}: next_base_fee={}, projected_base_fee={}, priority_fee={}, max_fee={}, capped={}",
+            urgency, next_base_fee, projected_base_fee, max_priority_fee_per_gas, max_fee_per_gas, capped
+        );
+
+        Ok(FeeEstimate {
+            urgency,
+            next_base_fee,
+            projected_base_fee,
+            max_priority_fee_per_gas,
+            max_fee_per_gas,
+            capped,
+        })
+    }
+
+    /// Trung vị của percentile reward qua các block, bỏ qua block rỗng
+    fn priority_fee_percentile(&self, history: &FeeHistory, urgency: FeeUrgency) -> U256 {
+        let index = urgency.reward_index();
+        let mut rewards: Vec<U256> = history.reward.iter()
+            .enumerate()
+            .filter(|(i, _)| history.gas_used_ratio.get(*i).map(|r| *r > 0.0).unwrap_or(true))
+            .filter_map(|(_, rewards)| rewards.get(index).cloned())
+            .collect();
+
+        if rewards.is_empty() {
+            return self.config.min_priority_fee;
+        }
+
+        rewards.sort();
+        std::cmp::max(rewards[rewards.len() / 2], self.config.min_priority_fee)
+    }
+}
+
+/// Tính base fee block kế tiếp theo công thức EIP-1559 từ base fee và tỷ lệ gas sử dụng
+pub fn project_next_base_fee(base_fee: U256, gas_used_ratio: f64) -> U256 {
+    let ratio = if gas_used_ratio.is_finite() { gas_used_ratio.clamp(0.0, 1.0) } else { 0.5 };
+    let used = (ratio * GAS_USED_RATIO_SCALE as f64).round() as u64;
+    let target = GAS_USED_RATIO_SCALE / 2;
+
+    // delta = base_fee * |used - target| / target / 8 (tính bằng số nguyên, không tràn)
+    let delta = base_fee.saturating_mul(U256::from(used.abs_diff(target)))
+        / U256::from(target)
+        / U256::from(BASE_FEE_MAX_CHANGE_DENOMINATOR);
+    if used >= target {
+        base_fee.saturating_add(delta)
+    } else {
+        base_fee.saturating_sub(delta)
+    }
+}
+
+/// Dự phóng base fee sau `blocks` block với độ lấp đầy giả định
+pub fn project_base_fee(base_fee: U256, gas_used_ratio: f64, blocks: u64) -> U256 {
+    (0..blocks).fold(base_fee, |fee, _| project_next_base_fee(fee, gas_used_ratio))
+}
+
+/// Độ lấp đầy block trung bình
+fn average_utilization(ratios: &[f64]) -> f64 {
+    if ratios.is_empty() {
+        return 0.5;
+    }
+    ratios.iter().sum::<f64>() / ratios.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Fee history mẫu (12 block, gas limit 30M) ở định dạng JSON-RPC của eth_feeHistory
+    const RECORDED_FEE_HISTORY: &str = r#"{"oldestBlock": "0x121eac0", "baseFeePerGas": ["0x44aa3ccb6", "0x47730a164", "0x4552f7c5e", "0x4bb1b932b", "0x50c2aa2e5", "0x564f1a23a", "0x6118fd681", "0x5d96d048e", "0x607d135c4", "0x6b1146e01", "0x68cca7066", "0x6566401ed", "0x6f67cddbe"], "gasUsedRatio": [0.662201, 0.381019, 0.867557, 0.767718, 0.774817, 1.0, 0.355465, 0.623923, 0.938556, 0.415266, 0.370228, 0.894719], "reward": [["0x2feb3e9", "0x9223da1", "0x23ec932b", "0x4e1677fb"], ["0x28a8ac0", "0x872698f", "0x210e7d9a", "0x4db4e70f"], ["0x381772e", "0x6c93234", "0xf7f279f", "0x9c4ff3f9"], ["0x258d3dc", "0x740a515", "0x101da510", "0xbafc78de"], ["0x242d26d", "0x9c3cf83", "0x13afe007", "0x6aa37560"], ["0x2487507", "0x9e14dad", "0x2293f00b", "0xa5ee2f1f"], ["0x38e8a0c", "0x6e8e149", "0x1eaef530", "0x5e9418dd"], ["0x5242912", "0x94688c7", "0x2089b644", "0x5c7ebd04"], ["0x44188d1", "0xbb7e17b", "0x2780b9cd", "0x73923458"], ["0x34a8606", "0x9402216", "0x125429d0", "0xbc5a89f9"], ["0x243d3ad", "0xa327f7a", "0x16bf9735", "0xb3a20159"], ["0x5357903", "0xab3c91a", "0x1f8fdffc", "0xb161292c"]]}"#;
+
+    fn recorded() -> FeeHistory {
+        serde_json::from_str(RECORDED_FEE_HISTORY).expect("fee history mẫu không hợp lệ")
+    }
+
+    #[test]
+    fn test_base_fee_projection_saturates_on_large_values() {
+        let huge = U256::MAX / 2;
+        assert_eq!(project_next_base_fee(U256::MAX, 1.0), U256::MAX);
+        assert!(project_next_base_fee(huge, 0.0) < huge);
+        assert_eq!(project_next_base_fee(U256::from(1_000u64), f64::NAN), U256::from(1_000u64));
+        assert_eq!(project_next_base_fee(U256::from(800u64), 1.0), U256::from(900u64));
+    }
+
+    #[test]
+    fn test_base_fee_projection_matches_recorded_history() {
+        let history = recorded();
+        for i in 0..history.gas_used_ratio.len() {
+            let projected = project_next_base_fee(history.base_fee_per_gas[i], history.gas_used_ratio[i]).as_u128() as f64;
+            let actual = history.base_fee_per_gas[i + 1].as_u128() as f64;
+            let error = (projected - actual).abs() / actual;
+            assert!(error < 0.0001, "block {}: dự phóng {} khác thực tế {} ({:.6})", i, projected, actual, error);
+        }
+    }
+
+    #[test]
+    fn test_urgency_levels_are_ordered() {
+        let history = recorded();
+        let estimator = FeeEstimator::default();
+        let caps = FeeCaps::default();
+
+        let fees: Vec<FeeEstimate> = [FeeUrgency::Slow, FeeUrgency::Normal, FeeUrgency::Fast, FeeUrgency::Instant]
+            .iter()
+            .map(|u| estimator.estimate_from_history(&history, *u, &caps).unwrap())
+            .collect();
+
+        for pair in fees.windows(2) {
+            assert!(pair[0].max_priority_fee_per_gas <= pair[1].max_priority_fee_per_gas);
+            assert!(pair[0].max_fee_per_gas <= pair[1].max_fee_per_gas);
+        }
+        // Max fee luôn đủ trả base fee của block kế tiếp
+        for fee in &fees {
+            assert!(fee.max_fee_per_gas >= fee.next_base_fee + fee.max_priority_fee_per_gas);
+        }
+    }
+
+    #[test]
+    fn test_priority_fee_is_median_of_percentile_column() {
+        let history = recorded();
+        let estimator = FeeEstimator::default();
+        let fee = estimator.estimate_from_history(&history, FeeUrgency::Normal, &FeeCaps::default()).unwrap();
+
+        let mut column: Vec<U256> = history.reward.iter().map(|r| r[1]).collect();
+        column.sort();
+        assert_eq!(fee.max_priority_fee_per_gas, column[column.len() / 2]);
+    }
+
+    #[test]
+    fn test_caps_are_respected() {
+        let history = recorded();
+        let estimator = FeeEstimator::default();
+
+        let absolute = FeeCaps::new(None, Some(20));
+        let fee = estimator.estimate_from_history(&history, FeeUrgency::Instant, &absolute).unwrap();
+        assert!(fee.capped);
+        assert_eq!(fee.max_fee_per_gas, U256::from(20_000_000_000u64));
+        assert!(fee.max_priority_fee_per_gas <= fee.max_fee_per_gas);
+
+        let normal = estimator.estimate_from_history(&history, FeeUrgency::Normal, &FeeCaps::default()).unwrap();
+        let boost = FeeCaps::new(Some(5), None);
+        let fee = estimator.estimate_from_history(&history, FeeUrgency::Instant, &boost).unwrap();
+        assert!(fee.max_fee_per_gas <= normal.max_fee_per_gas * U256::from(105) / U256::from(100));
+    }
+}
@@ -0,0 +1,672 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use ethers::types::{FeeHistory, U256};
+use once_cell::sync::Lazy;
+use serde::{Serialize, Deserialize};
+use tracing::debug;
+
+use crate::chain_adapters::block_tracker;
+use crate::utils::safe_now;
+
+/// Percentile reward dùng làm ngưỡng "tip tối thiểu được đưa vào block"
+pub const INCLUSION_REWARD_PERCENTILE: f64 = 10.0;
+
+/// Base fee thay đổi tối đa 12.5% mỗi block theo EIP-1559
+const MAX_BASE_FEE_CHANGE: f64 = 0.125;
+
+/// Hệ số ridge cho các biến không phải intercept, tránh ma trận suy biến
+const RIDGE_LAMBDA: f64 = 1e-6;
+
+/// Số vòng chia đôi khi tìm phí rẻ nhất
+const SEARCH_ITERATIONS: usize = 48;
+
+/// Một mẫu quan sát gas của chain tại một block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasSample {
+    /// Số block
+    pub block_number: u64,
+    /// Thời điểm lấy mẫu (Unix timestamp)
+    pub timestamp: u64,
+    /// Gas price đề xuất từ node (wei)
+    pub gas_price: U256,
+    /// Base fee của block (wei), bằng 0 với chain legacy
+    pub base_fee: U256,
+    /// Độ lấp đầy block (gas_used / gas_limit)
+    pub utilization: f64,
+    /// Số giao dịch đang pending (0 khi không có dữ liệu mempool)
+    pub pending_count: u64,
+    /// Priority fee thấp nhất (percentile 10) được đưa vào block (wei)
+    pub min_included_tip: U256,
+}
+
+/// Cấu hình cho GasForecaster
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasForecasterConfig {
+    /// Số mẫu tối đa giữ trong lịch sử
+    pub max_samples: usize,
+    /// Số mẫu tối thiểu để fit mô hình
+    pub min_samples: usize,
+    /// Half-life của trọng số mũ (tính theo số mẫu)
+    pub half_life_samples: f64,
+}
+
+impl Default for GasForecasterConfig {
+    fn default() -> Self {
+        Self {
+            max_samples: 256,
+            min_samples: 10,
+            half_life_samples: 20.0,
+        }
+    }
+}
+
+/// Mức phí rẻ nhất đạt xác suất vào block mục tiêu
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeQuote {
+    /// Max fee per gas (wei). Với chain legacy là gas price.
+    pub max_fee_per_gas: U256,
+    /// Max priority fee per gas (wei). Với chain legacy bằng gas price.
+    pub max_priority_fee_per_gas: U256,
+    /// Xác suất vào block dự đoán
+    pub inclusion_probability: f64,
+    /// Số block trong cửa sổ dự đoán
+    pub within_blocks: u64,
+}
+
+/// Mô hình đã fit từ lịch sử
+#[derive(Debug, Clone)]
+struct FittedModel {
+    /// Hệ số hồi quy tip ~ [1, utilization, ln(1 + pending)]
+    tip_coefficients: [f64; 3],
+    /// Phần dư (gwei) kèm trọng số, sắp xếp tăng dần
+    residuals: Vec<(f64, f64)>,
+    /// Đặc trưng của mẫu mới nhất, dùng để dự đoán block kế tiếp
+    latest_features: [f64; 3],
+    /// Base fee mới nhất (gwei)
+    latest_base_fee: f64,
+    /// Độ dốc ln(base fee) mỗi block
+    base_fee_log_slope: f64,
+    /// Độ dốc ln(gas price) mỗi mẫu
+    gas_price_log_slope: f64,
+}
+
+impl FittedModel {
+    /// Tip dự đoán (gwei) cho block kế tiếp, chưa cộng phần dư
+    fn predicted_tip(&self) -> f64 {
+        dot3(&self.tip_coefficients, &self.latest_features)
+    }
+
+    /// Xác suất tip `tip_gwei` đủ để vào một block (CDF có trọng số)
+    fn tip_cdf(&self, tip_gwei: f64) -> f64 {
+        let prediction = self.predicted_tip();
+        let total: f64 = self.residuals.iter().map(|(_, w)| w).sum();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let covered: f64 = self.residuals.iter()
+            .filter(|(residual, _)| (prediction + residual).max(0.0) <= tip_gwei)
+            .map(|(_, w)| w)
+            .sum();
+        covered / total
+    }
+
+    /// Quantile có trọng số của tip cần thiết (gwei)
+    fn tip_quantile(&self, q: f64) -> f64 {
+        let prediction = self.predicted_tip();
+        let total: f64 = self.residuals.iter().map(|(_, w)| w).sum();
+        let target = q.clamp(0.0, 1.0) * total;
+        let mut cumulative = 0.0;
+        for (residual, weight) in &self.residuals {
+            cumulative += weight;
+            if cumulative >= target {
+                return (prediction + residual).max(0.0);
+            }
+        }
+        self.residuals.last().map(|(r, _)| (prediction + r).max(0.0)).unwrap_or(prediction.max(0.0))
+    }
+
+    /// Base fee dự đoán (gwei) sau `blocks_ahead` block, giới hạn theo biên độ EIP-1559
+    fn predicted_base_fee(&self, blocks_ahead: u64) -> f64 {
+        if self.latest_base_fee <= 0.0 {
+            return 0.0;
+        }
+        let n = blocks_ahead as f64;
+        let projected = self.latest_base_fee * (self.base_fee_log_slope * n).exp();
+        let upper = self.latest_base_fee * (1.0 + MAX_BASE_FEE_CHANGE).powf(n);
+        let lower = self.latest_base_fee * (1.0 - MAX_BASE_FEE_CHANGE).powf(n);
+        projected.clamp(lower, upper)
+    }
+}
+
+/// Dự đoán gas dựa trên lịch sử cục bộ của một chain
+///
+/// Mô hình gồm hai phần: hồi quy tuyến tính có trọng số mũ cho tip tối thiểu
+/// được đưa vào block theo độ lấp đầy và số giao dịch pending, kết hợp phân phối
+/// phần dư có trọng số để ước tính quantile; và xu hướng log-tuyến tính của base fee.
+#[derive(Debug, Clone)]
+pub struct GasForecaster {
+    chain_id: u64,
+    config: GasForecasterConfig,
+    samples: VecDeque<GasSample>,
+    model: Option<FittedModel>,
+}
+
+impl GasForecaster {
+    /// Tạo forecaster mới cho chain
+    pub fn new(chain_id: u64, config: GasForecasterConfig) -> Self {
+        Self {
+            chain_id,
+            config,
+            samples: VecDeque::new(),
+            model: None,
+        }
+    }
+
+    /// Chain ID
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Số mẫu hiện có
+    pub fn sample_count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Mô hình đã đủ dữ liệu để dự đoán chưa
+    pub fn is_ready(&self) -> bool {
+        self.model.is_some()
+    }
+
+    /// Thêm mẫu mới và fit lại mô hình. Mẫu trùng block sẽ thay thế mẫu cũ.
+    pub fn record(&mut self, sample: GasSample) {
+        if let Some(last) = self.samples.back() {
+            if sample.block_number < last.block_number {
+                debug!("Bỏ qua mẫu gas cũ (block {} < {}) cho chain {}",
+                    sample.block_number, last.block_number, self.chain_id);
+                return;
+            }
+            if sample.block_number == last.block_number {
+                self.samples.pop_back();
+            }
+        }
+
+        self.samples.push_back(sample);
+        while self.samples.len() > self.config.max_samples {
+            self.samples.pop_front();
+        }
+
+        self.model = self.fit();
+    }
+
+    /// Xác suất một giao dịch với phí cho trước được đưa vào block trong `within_blocks` block tới
+    ///
+    /// Với chain legacy truyền `max_fee_per_gas = max_priority_fee_per_gas = gas_price`.
+    /// Trả về `None` nếu chưa đủ dữ liệu.
+    pub fn inclusion_probability(
+        &self,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+        within_blocks: u64,
+    ) -> Option<f64> {
+        let model = self.model.as_ref()?;
+        Some(inclusion_probability_gwei(
+            model,
+            wei_to_gwei(max_fee_per_gas),
+            wei_to_gwei(max_priority_fee_per_gas),
+            within_blocks,
+        ))
+    }
+
+    /// Mức phí rẻ nhất đạt xác suất vào block `target_probability` trong `within_blocks` block
+    ///
+    /// Max fee được đặt bằng base fee dự đoán cao nhất trong cửa sổ cộng priority fee,
+    /// sau đó tìm priority fee nhỏ nhất bằng chia đôi (xác suất đơn điệu theo phí).
+    pub fn cheapest_fee_for_target(&self, target_probability: f64, within_blocks: u64) -> Option<FeeQuote> {
+        let model = self.model.as_ref()?;
+        let within_blocks = within_blocks.max(1);
+        let target = target_probability.clamp(0.0, 0.999);
+
+        let base_cap = (1..=within_blocks)
+            .map(|j| model.predicted_base_fee(j))
+            .fold(0.0, f64::max);
+        let probability_at = |tip: f64| inclusion_probability_gwei(model, base_cap + tip, tip, within_blocks);
+
+        // Cận trên: tip cao nhất từng thấy, nhân đôi để có dư địa
+        let mut high = model.tip_quantile(1.0).max(1e-3) * 2.0;
+        if probability_at(high) < target {
+            return None;
+        }
+        let mut low = 0.0;
+        if probability_at(low) >= target {
+            high = low;
+        }
+        for _ in 0..SEARCH_ITERATIONS {
+            if high - low < 1e-9 {
+                break;
+            }
+            let mid = (low + high) / 2.0;
+            if probability_at(mid) >= target {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+
+        let tip = gwei_to_wei(high);
+        let max_fee = gwei_to_wei(base_cap).saturating_add(tip);
+        Some(FeeQuote {
+            max_fee_per_gas: max_fee,
+            max_priority_fee_per_gas: tip,
+            inclusion_probability: probability_at(high),
+            within_blocks,
+        })
+    }
+
+    /// Base fee dự đoán sau `blocks_ahead` block
+    pub fn predict_base_fee(&self, blocks_ahead: u64) -> Option<U256> {
+        self.model.as_ref().map(|m| gwei_to_wei(m.predicted_base_fee(blocks_ahead)))
+    }
+
+    /// Xu hướng gas price: % thay đổi dự đoán qua `horizon` mẫu kế tiếp kèm mô tả
+    pub fn gas_price_trend(&self, horizon: u64) -> Option<(f64, String)> {
+        let model = self.model.as_ref()?;
+        let pct = ((model.gas_price_log_slope * horizon as f64).exp() - 1.0) * 100.0;
+        let description = if pct >= 0.5 {
+            format!("Tăng {:.2}%", pct)
+        } else if pct <= -0.5 {
+            format!("Giảm {:.2}%", -pct)
+        } else {
+            "Ổn định".to_string()
+        };
+        Some((pct, description))
+    }
+
+    /// Fit mô hình từ lịch sử hiện tại
+    fn fit(&self) -> Option<FittedModel> {
+        let n = self.samples.len();
+        if n < self.config.min_samples.max(2) {
+            return None;
+        }
+
+        let weights = exponential_weights(n, self.config.half_life_samples);
+        let features: Vec<[f64; 3]> = self.samples.iter().map(features_of).collect();
+        let tips: Vec<f64> = self.samples.iter().map(|s| wei_to_gwei(s.min_included_tip)).collect();
+
+        let tip_coefficients = weighted_least_squares(&features, &tips, &weights)
+            .unwrap_or_else(|| [weighted_mean(&tips, &weights), 0.0, 0.0]);
+
+        let mut residuals: Vec<(f64, f64)> = features.iter()
+            .zip(tips.iter())
+            .zip(weights.iter())
+            .map(|((x, y), w)| (y - dot3(&tip_coefficients, x), *w))
+            .collect();
+        residuals.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        // Xu hướng base fee theo số block
+        let first_block = self.samples.front().map(|s| s.block_number).unwrap_or(0);
+        let base_fee_points: Vec<(f64, f64, f64)> = self.samples.iter()
+            .zip(weights.iter())
+            .filter(|(s, _)| !s.base_fee.is_zero())
+            .map(|(s, w)| ((s.block_number - first_block) as f64, wei_to_gwei(s.base_fee).ln(), *w))
+            .collect();
+        let base_fee_log_slope = weighted_slope(&base_fee_points).unwrap_or(0.0);
+
+        // Xu hướng gas price theo thứ tự mẫu
+        let gas_price_points: Vec<(f64, f64, f64)> = self.samples.iter()
+            .zip(weights.iter())
+            .enumerate()
+            .filter(|(_, (s, _))| !s.gas_price.is_zero())
+            .map(|(i, (s, w))| (i as f64, wei_to_gwei(s.gas_price).ln(), *w))
+            .collect();
+        let gas_price_log_slope = weighted_slope(&gas_price_points).unwrap_or(0.0);
+
+        let latest = self.samples.back()?;
+        Some(FittedModel {
+            tip_coefficients,
+            residuals,
+            latest_features: features_of(latest),
+            latest_base_fee: wei_to_gwei(latest.base_fee),
+            base_fee_log_slope,
+            gas_price_log_slope,
+        })
+    }
+}
+
+/// Xác suất vào block trong cửa sổ, giả định các block độc lập
+fn inclusion_probability_gwei(model: &FittedModel, max_fee: f64, priority_fee: f64, within_blocks: u64) -> f64 {
+    let mut miss_probability = 1.0;
+    for j in 1..=within_blocks.max(1) {
+        let base_fee = model.predicted_base_fee(j);
+        if max_fee < base_fee {
+            continue;
+        }
+        let effective_tip = priority_fee.min(max_fee - base_fee);
+        miss_probability *= 1.0 - model.tip_cdf(effective_tip);
+    }
+    1.0 - miss_probability
+}
+
+fn features_of(sample: &GasSample) -> [f64; 3] {
+    [1.0, sample.utilization.clamp(0.0, 1.0), (sample.pending_count as f64).ln_1p()]
+}
+
+fn dot3(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Trọng số mũ: mẫu mới nhất có trọng số 1, giảm một nửa sau mỗi `half_life` mẫu
+fn exponential_weights(n: usize, half_life: f64) -> Vec<f64> {
+    let half_life = half_life.max(1e-6);
+    (0..n).map(|i| 0.5f64.powf((n - 1 - i) as f64 / half_life)).collect()
+}
+
+fn weighted_mean(values: &[f64], weights: &[f64]) -> f64 {
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    values.iter().zip(weights).map(|(v, w)| v * w).sum::<f64>() / total
+}
+
+/// Giải (XᵀWX + λI)β = XᵀWy cho 3 biến, intercept không bị phạt
+fn weighted_least_squares(x: &[[f64; 3]], y: &[f64], w: &[f64]) -> Option<[f64; 3]> {
+    let mut xtx = [[0.0; 3]; 3];
+    let mut xty = [0.0; 3];
+    for ((row, target), weight) in x.iter().zip(y).zip(w) {
+        for i in 0..3 {
+            xty[i] += weight * row[i] * target;
+            for j in 0..3 {
+                xtx[i][j] += weight * row[i] * row[j];
+            }
+        }
+    }
+    xtx[1][1] += RIDGE_LAMBDA;
+    xtx[2][2] += RIDGE_LAMBDA;
+    solve3(xtx, xty)
+}
+
+/// Khử Gauss có chọn pivot cho hệ 3x3
+fn solve3(mut a: [[f64; 3]; 3], mut b: [f64; 3]) -> Option<[f64; 3]> {
+    for col in 0..3 {
+        let pivot = (col..3).max_by(|&i, &j| {
+            a[i][col].abs().partial_cmp(&a[j][col].abs()).unwrap_or(std::cmp::Ordering::Equal)
+        })?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col];
+        for row in (col + 1)..3 {
+            let factor = a[row][col] / pivot_row[col];
+            for (k, value) in a[row].iter_mut().enumerate().skip(col) {
+                *value -= factor * pivot_row[k];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut solution = [0.0; 3];
+    for row in (0..3).rev() {
+        let mut sum = b[row];
+        for k in (row + 1)..3 {
+            sum -= a[row][k] * solution[k];
+        }
+        solution[row] = sum / a[row][row];
+    }
+    if solution.iter().all(|v| v.is_finite()) {
+        Some(solution)
+    } else {
+        None
+    }
+}
+
+/// Độ dốc của hồi quy tuyến tính có trọng số trên các điểm (x, y, w)
+fn weighted_slope(points: &[(f64, f64, f64)]) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+    let total: f64 = points.iter().map(|(_, _, w)| w).sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let mean_x = points.iter().map(|(x, _, w)| x * w).sum::<f64>() / total;
+    let mean_y = points.iter().map(|(_, y, w)| y * w).sum::<f64>() / total;
+    let covariance: f64 = points.iter().map(|(x, y, w)| w * (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = points.iter().map(|(x, _, w)| w * (x - mean_x).powi(2)).sum();
+    if variance < 1e-12 {
+        return None;
+    }
+    Some(covariance / variance)
+}
+
+fn wei_to_gwei(value: U256) -> f64 {
+    if value > U256::from(u128::MAX) {
+        return f64::MAX;
+    }
+    value.as_u128() as f64 / 1e9
+}
+
+fn gwei_to_wei(value: f64) -> U256 {
+    if !value.is_finite() || value <= 0.0 {
+        return U256::zero();
+    }
+    U256::from((value * 1e9).ceil() as u128)
+}
+
+/// Dựng mẫu gas từ kết quả `eth_feeHistory(1, latest, [10])` mà gas optimizer đã lấy
+///
+/// `head` là block mới nhất từ block tracker (nếu có), mặc định dùng block trong fee history.
+/// Trả về None với chain legacy (không có base fee): mô hình tip không áp dụng cho chain này.
+pub fn sample_from_fee_history(history: &FeeHistory, gas_price: U256, head: Option<u64>) -> Option<GasSample> {
+    let base_fee = history.base_fee_per_gas.first().copied()?;
+    if history.base_fee_per_gas.iter().all(|fee| fee.is_zero()) {
+        return None;
+    }
+    let min_included_tip = history.reward.first()
+        .and_then(|rewards| rewards.first())
+        .copied()
+        .unwrap_or_default();
+
+    Some(GasSample {
+        block_number: head.unwrap_or_else(|| history.oldest_block.low_u64()),
+        timestamp: safe_now(),
+        gas_price,
+        base_fee,
+        utilization: history.gas_used_ratio.first().copied().unwrap_or(0.5),
+        pending_count: 0,
+        min_included_tip,
+    })
+}
+
+// Forecaster theo chain
+static GAS_FORECASTERS: Lazy<RwLock<HashMap<u64, Arc<RwLock<GasForecaster>>>>> = Lazy::new(|| {
+    RwLock::new(HashMap::new())
+});
+
+/// Lấy hoặc tạo forecaster cho chain
+pub fn get_or_create_gas_forecaster(chain_id: u64) -> Arc<RwLock<GasForecaster>> {
+    if let Some(forecaster) = GAS_FORECASTERS.read().unwrap_or_else(|e| e.into_inner()).get(&chain_id) {
+        return forecaster.clone();
+    }
+
+    GAS_FORECASTERS.write().unwrap_or_else(|e| e.into_inner())
+        .entry(chain_id)
+        .or_insert_with(|| Arc::new(RwLock::new(GasForecaster::new(chain_id, GasForecasterConfig::default()))))
+        .clone()
+}
+
+/// Lấy forecaster của chain nếu đã tồn tại
+pub fn get_gas_forecaster(chain_id: u64) -> Option<Arc<RwLock<GasForecaster>>> {
+    GAS_FORECASTERS.read().unwrap_or_else(|e| e.into_inner()).get(&chain_id).cloned()
+}
+
+/// Ghi mẫu dựng từ fee history vào forecaster của chain, không gọi thêm RPC.
+/// Trả về false nếu chain legacy (không ghi mẫu).
+pub async fn update_gas_forecaster(chain_id: u64, gas_price: U256, history: &FeeHistory) -> bool {
+    let head = match block_tracker::get_block_tracker(chain_id).await {
+        Some(tracker) => tracker.current_head().await,
+        None => None,
+    };
+    let Some(sample) = sample_from_fee_history(history, gas_price, head) else {
+        return false;
+    };
+
+    let forecaster = get_or_create_gas_forecaster(chain_id);
+    forecaster.write().unwrap_or_else(|e| e.into_inner()).record(sample);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gwei(value: f64) -> U256 {
+        gwei_to_wei(value)
+    }
+
+    fn sample(block: u64, base_fee: f64, tip: f64, utilization: f64, pending: u64) -> GasSample {
+        GasSample {
+            block_number: block,
+            timestamp: 1_700_000_000 + block * 12,
+            gas_price: gwei(base_fee + tip),
+            base_fee: gwei(base_fee),
+            utilization,
+            pending_count: pending,
+            min_included_tip: gwei(tip),
+        }
+    }
+
+    /// Lịch sử với base fee cố định 20 gwei và tip 1-2 gwei
+    fn steady_forecaster() -> GasForecaster {
+        let mut forecaster = GasForecaster::new(1, GasForecasterConfig::default());
+        for i in 0..60u64 {
+            let tip = 1.0 + (i % 5) as f64 * 0.25;
+            forecaster.record(sample(100 + i, 20.0, tip, 0.5, 100));
+        }
+        forecaster
+    }
+
+    #[test]
+    fn test_sample_from_fee_history_skips_legacy_chains() {
+        let history = FeeHistory {
+            oldest_block: U256::from(100),
+            base_fee_per_gas: vec![gwei(20.0), gwei(21.0)],
+            gas_used_ratio: vec![0.7],
+            reward: vec![vec![gwei(1.5)]],
+        };
+        let sample = sample_from_fee_history(&history, gwei(22.0), Some(101)).unwrap();
+        assert_eq!(sample.block_number, 101);
+        assert_eq!(sample.base_fee, gwei(20.0));
+        assert_eq!(sample.min_included_tip, gwei(1.5));
+        assert!((sample.utilization - 0.7).abs() < 1e-9);
+        assert_eq!(sample_from_fee_history(&history, gwei(22.0), None).unwrap().block_number, 100);
+
+        let legacy = FeeHistory {
+            base_fee_per_gas: vec![U256::zero(), U256::zero()],
+            ..history
+        };
+        assert!(sample_from_fee_history(&legacy, gwei(5.0), None).is_none());
+    }
+
+    #[test]
+    fn test_not_ready_without_enough_samples() {
+        let mut forecaster = GasForecaster::new(1, GasForecasterConfig::default());
+        for i in 0..5 {
+            forecaster.record(sample(i, 20.0, 1.0, 0.5, 10));
+        }
+        assert!(!forecaster.is_ready());
+        assert!(forecaster.inclusion_probability(gwei(30.0), gwei(2.0), 1).is_none());
+        assert!(forecaster.cheapest_fee_for_target(0.9, 3).is_none());
+    }
+
+    #[test]
+    fn test_inclusion_probability_monotonic() {
+        let forecaster = steady_forecaster();
+
+        let below_base = forecaster.inclusion_probability(gwei(15.0), gwei(5.0), 3).unwrap();
+        let low_tip = forecaster.inclusion_probability(gwei(25.0), gwei(0.5), 1).unwrap();
+        let mid_tip = forecaster.inclusion_probability(gwei(25.0), gwei(1.5), 1).unwrap();
+        let high_tip = forecaster.inclusion_probability(gwei(25.0), gwei(2.5), 1).unwrap();
+
+        assert_eq!(below_base, 0.0);
+        assert!(low_tip < mid_tip && mid_tip < high_tip);
+        assert!((high_tip - 1.0).abs() < 1e-9);
+
+        // Cửa sổ dài hơn thì xác suất cao hơn
+        let one_block = forecaster.inclusion_probability(gwei(25.0), gwei(1.25), 1).unwrap();
+        let three_blocks = forecaster.inclusion_probability(gwei(25.0), gwei(1.25), 3).unwrap();
+        assert!(three_blocks > one_block);
+    }
+
+    #[test]
+    fn test_cheapest_fee_meets_target() {
+        let forecaster = steady_forecaster();
+
+        let relaxed = forecaster.cheapest_fee_for_target(0.5, 1).unwrap();
+        let strict = forecaster.cheapest_fee_for_target(0.95, 1).unwrap();
+
+        assert!(relaxed.inclusion_probability >= 0.5);
+        assert!(strict.inclusion_probability >= 0.95);
+        assert!(strict.max_priority_fee_per_gas > relaxed.max_priority_fee_per_gas);
+        assert!(strict.max_fee_per_gas >= gwei(20.0) + strict.max_priority_fee_per_gas - U256::one());
+
+        // Chờ nhiều block hơn thì rẻ hơn
+        let patient = forecaster.cheapest_fee_for_target(0.95, 5).unwrap();
+        assert!(patient.max_priority_fee_per_gas <= strict.max_priority_fee_per_gas);
+    }
+
+    #[test]
+    fn test_regression_uses_congestion_features() {
+        let mut forecaster = GasForecaster::new(1, GasForecasterConfig::default());
+        // Tip tăng theo độ lấp đầy block
+        for i in 0..80u64 {
+            let utilization = (i % 10) as f64 / 10.0;
+            forecaster.record(sample(i, 10.0, 1.0 + 4.0 * utilization, utilization, 50));
+        }
+        let model = forecaster.model.as_ref().unwrap();
+        assert!((model.tip_coefficients[1] - 4.0).abs() < 0.05);
+
+        // Block mới nhất có utilization 0.9 → tip dự đoán ~4.6 gwei
+        assert!((model.predicted_tip() - 4.6).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_base_fee_trend_and_clamp() {
+        let mut forecaster = GasForecaster::new(1, GasForecasterConfig::default());
+        let mut base_fee = 10.0;
+        for i in 0..30u64 {
+            forecaster.record(sample(i, base_fee, 1.0, 1.0, 10));
+            base_fee *= 1.05;
+        }
+        let latest = wei_to_gwei(forecaster.samples.back().unwrap().base_fee);
+        let next = wei_to_gwei(forecaster.predict_base_fee(1).unwrap());
+        assert!((next / latest - 1.05).abs() < 0.01);
+
+        let (pct, description) = forecaster.gas_price_trend(5).unwrap();
+        assert!(pct > 0.0);
+        assert!(description.starts_with("Tăng"));
+    }
+
+    #[test]
+    fn test_record_replaces_same_block_and_ignores_old() {
+        let mut forecaster = GasForecaster::new(1, GasForecasterConfig::default());
+        forecaster.record(sample(10, 20.0, 1.0, 0.5, 10));
+        forecaster.record(sample(10, 21.0, 1.0, 0.5, 10));
+        forecaster.record(sample(9, 22.0, 1.0, 0.5, 10));
+        assert_eq!(forecaster.sample_count(), 1);
+        assert_eq!(forecaster.samples.back().unwrap().base_fee, gwei(21.0));
+    }
+
+    #[test]
+    fn test_solve3() {
+        let a = [[2.0, 1.0, -1.0], [-3.0, -1.0, 2.0], [-2.0, 1.0, 2.0]];
+        let b = [8.0, -11.0, -3.0];
+        let x = solve3(a, b).unwrap();
+        assert!((x[0] - 2.0).abs() < 1e-9);
+        assert!((x[1] - 3.0).abs() < 1e-9);
+        assert!((x[2] + 1.0).abs() < 1e-9);
+
+        assert!(solve3([[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 0.0, 1.0]], [1.0, 2.0, 3.0]).is_none());
+    }
+}
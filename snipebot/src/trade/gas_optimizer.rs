@@ -1,19 +1,19 @@
 use std::sync::{Arc, RwLock};
 use std::time::{SystemTime, UNIX_EPOCH};
-use anyhow::{Result, anyhow};
-use tracing::{info, warn, error};
-use crate::chain_adapters::ChainAdapter;
+use anyhow::Result;
+use tracing::{info, warn};
+use crate::chain_adapters::chain_registry;
+use crate::chain_adapters::trait_adapter::ChainAdapter;
 use ethers::prelude::*;
-use ethers::providers::{Http, Provider, Middleware};
-use ethers::types::{U256, BlockNumber, TransactionRequest};
-use std::collections::VecDeque;
+use ethers::providers::Middleware;
+use ethers::types::{U256, BlockNumber, transaction::eip2718::TypedTransaction};
+use std::collections::{HashMap, VecDeque};
 use log::{debug};
 use serde::{Serialize, Deserialize};
-use crate::chain_adapters::ChainConfig;
 use crate::metric::update_gas_metric;
 use once_cell::sync::Lazy;
-use crate::utils::safe_now;
-use ethers::types::{Address};
+use crate::fee_estimator::{FeeEstimator, FeeUrgency, FeeCaps};
+use crate::gas_forecaster::{self, FeeQuote};
 
 /// Đánh giá chiến lược tối ưu
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +47,7 @@ static GAS_PRICE_CACHE: Lazy<RwLock<HashMap<u64, CachedGasData>>> = Lazy::new(||
 
 // Định nghĩa kiểu dữ liệu cho gas price cache
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 struct CachedGasData {
     chain_id: u64,
     timestamp: u64,
@@ -84,6 +85,18 @@ pub struct GasOptimizerConfig {
     pub history_window: u64,               // Kích thước cửa sổ lịch sử (giây)
     pub enable_dynamic_adjustment: bool,   // Bật tính năng tự động điều chỉnh
     pub adaptive_boost_mode: bool,         // Chế độ tăng tương thích
+    #[serde(default = "default_inclusion_target_probability")]
+    pub inclusion_target_probability: f64, // Xác suất vào block mục tiêu khi chọn phí theo gas forecaster
+    #[serde(default = "default_inclusion_target_blocks")]
+    pub inclusion_target_blocks: u64,      // Số block tối đa để đạt xác suất mục tiêu
+}
+
+fn default_inclusion_target_probability() -> f64 {
+    0.9
+}
+
+fn default_inclusion_target_blocks() -> u64 {
+    3
 }
 
 impl Default for GasOptimizerConfig {
@@ -96,6 +109,8 @@ impl Default for GasOptimizerConfig {
             history_window: 3600, // 1 giờ
             enable_dynamic_adjustment: true,
             adaptive_boost_mode: true,
+            inclusion_target_probability: default_inclusion_target_probability(),
+            inclusion_target_blocks: default_inclusion_target_blocks(),
         }
     }
 }
@@ -107,6 +122,7 @@ pub struct GasOptimizer {
     network_congestion: NetworkCongestion,
     last_update: u64,
     chain_id: u64,
+    subscription_caps: RwLock<FeeCaps>, // Giới hạn phí của auto trade config và gói subscription
 }
 
 impl GasOptimizer {
@@ -126,6 +142,7 @@ impl GasOptimizer {
             network_congestion: NetworkCongestion::Medium,
             last_update: 0,
             chain_id: 1, // Mặc định Ethereum
+            subscription_caps: RwLock::new(FeeCaps::default()),
         }
     }
     
@@ -134,7 +151,7 @@ impl GasOptimizer {
     }
     
     // Cập nhật lịch sử gas price
-    pub async fn update_gas_price_history<M: Middleware>(&mut self, client: Arc<M>) -> Result<()> {
+    pub async fn update_gas_price_history<M: Middleware + 'static>(&mut self, client: Arc<M>) -> Result<()> {
         let current_time = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
@@ -171,12 +188,22 @@ impl GasOptimizer {
         // Thêm vào lịch sử
         self.gas_price_history.push_back((current_time, gas_price));
         
-        // Lấy block gần nhất để có base fee (nếu là EIP-1559)
-        if let Ok(block) = client.get_block(BlockNumber::Latest).await {
-            if let Some(block) = block {
-                if let Some(base_fee) = block.base_fee_per_gas {
-                    self.base_fee_history.push_back((current_time, base_fee));
-                }
+        // Một lần eth_feeHistory cho cả base fee và mẫu của gas forecaster;
+        // chain legacy không có base fee nên bỏ qua mô hình tip
+        let eip1559_supported = chain_registry::get_chain_config(self.chain_id)
+            .map(|config| config.gas_config.supports_eip1559)
+            .unwrap_or(true);
+        if eip1559_supported {
+            match client.fee_history(1u64, BlockNumber::Latest, &[gas_forecaster::INCLUSION_REWARD_PERCENTILE]).await {
+                Ok(history) => {
+                    if let Some(base_fee) = history.base_fee_per_gas.first() {
+                        self.base_fee_history.push_back((current_time, *base_fee));
+                    }
+                    if !gas_forecaster::update_gas_forecaster(self.chain_id, gas_price, &history).await {
+                        debug!("Chain {} không có base fee, bỏ qua mô hình tip", self.chain_id);
+                    }
+                },
+                Err(e) => debug!("Không thể lấy fee history cho chain {}: {}", self.chain_id, e),
             }
         }
         
@@ -217,7 +244,7 @@ impl GasOptimizer {
             network_congestion: self.network_congestion,
         };
         
-        let mut cache = GAS_PRICE_CACHE.write().unwrap();
+        let mut cache = GAS_PRICE_CACHE.write().unwrap_or_else(|e| e.into_inner());
         cache.insert(self.chain_id, cache_data);
     }
     
@@ -315,7 +342,7 @@ impl GasOptimizer {
             
         // Kiểm tra cache
         {
-            let cache = GAS_PRICE_CACHE.read().unwrap();
+            let cache = GAS_PRICE_CACHE.read().unwrap_or_else(|e| e.into_inner());
             if let Some(data) = cache.get(&chain_id) {
                 // Nếu cache còn mới (< GAS_CACHE_DURATION giây)
                 if current_time - data.timestamp < GAS_CACHE_DURATION {
@@ -333,15 +360,13 @@ impl GasOptimizer {
         
         // Lấy thông tin EIP-1559 nếu chain hỗ trợ
         if adapter.get_config().eip1559_supported {
-            if let Ok(block) = provider.get_block(BlockNumber::Latest).await {
-                if let Some(block) = block {
-                    base_fee = block.base_fee_per_gas;
-                    
-                    // Tính priority fee nếu có base fee
-                    if let Some(base) = base_fee {
-                        if gas_price > base {
-                            priority_fee = Some(gas_price - base);
-                        }
+            if let Ok(Some(block)) = provider.get_block(BlockNumber::Latest).await {
+                base_fee = block.base_fee_per_gas;
+                
+                // Tính priority fee nếu có base fee
+                if let Some(base) = base_fee {
+                    if gas_price > base {
+                        priority_fee = Some(gas_price - base);
                     }
                 }
             }
@@ -359,7 +384,7 @@ impl GasOptimizer {
         
         // Cập nhật cache
         {
-            let mut cache = GAS_PRICE_CACHE.write().unwrap();
+            let mut cache = GAS_PRICE_CACHE.write().unwrap_or_else(|e| e.into_inner());
             cache.insert(chain_id, data.clone());
         }
         
@@ -374,7 +399,7 @@ impl GasOptimizer {
         } else {
             // Legacy gas price
             let provider = adapter.get_provider();
-            let client = Arc::new(provider.clone());
+            let _client = Arc::new(provider.clone());
             
             // Nếu gas_price_history rỗng, chỉ sử dụng giá gas hiện tại thay vì cập nhật history
             let current_gas_price = provider.get_gas_price().await?;
@@ -390,100 +415,73 @@ impl GasOptimizer {
             
             // Tính gas price tối ưu
             let multiplier = (percentage_increase * 1000.0) as u64;
-            let optimal_gas = (current_gas_price.as_u128() * multiplier as u128) / 1000;
+            let optimal_gas = U256::from((current_gas_price.as_u128() * multiplier as u128) / 1000);
             
-            Ok(U256::from(optimal_gas))
+            Ok(std::cmp::min(optimal_gas, self.max_fee_cap()))
         }
     }
     
     // Đề xuất phí EIP-1559 tối ưu (với chains hỗ trợ)
     pub async fn get_optimal_eip1559_fees<A: ChainAdapter>(&self, adapter: &A) -> Result<(U256, U256)> {
-        let provider = adapter.get_provider();
-        let chain_id = adapter.get_config().chain_id;
-        
-        // Lấy dữ liệu gas từ cache hoặc cập nhật
-        let gas_data = self.get_cached_gas_data(adapter).await?;
-        
-        // Lấy ước tính phí từ mạng
-        let fee_history = provider.fee_history(5, BlockNumber::Latest, &[10.0, 50.0, 90.0]).await?;
-        
-        // Tính toán các priority fee percentiles
-        let mut priority_fees = Vec::new();
-        for rewards in fee_history.reward.iter() {
-            if let Some(reward) = rewards.get(1) { // lấy percentile 50.0
-                priority_fees.push(*reward);
-            }
+        // Khi forecaster đã đủ dữ liệu, chọn phí rẻ nhất đạt xác suất vào block mục tiêu
+        let forecaster_ready = gas_forecaster::get_gas_forecaster(self.chain_id)
+            .map(|forecaster| forecaster.read().unwrap_or_else(|e| e.into_inner()).is_ready())
+            .unwrap_or(false);
+        if forecaster_ready {
+            return self.get_fees_for_inclusion_target(
+                adapter,
+                self.config.inclusion_target_probability,
+                self.config.inclusion_target_blocks,
+                &self.fee_caps(),
+            ).await;
         }
         
-        // Tính toán priority fee trung bình
-        let priority_fee = if !priority_fees.is_empty() {
-            let sum = priority_fees.iter().fold(U256::zero(), |acc, &x| acc + x);
-            sum / U256::from(priority_fees.len())
-        } else if let Some(pf) = gas_data.priority_fee {
-            pf
-        } else {
-            U256::from(1_500_000_000) // 1.5 Gwei mặc định
-        };
-        
-        // Điều chỉnh priority fee dựa trên mức độ tắc nghẽn
-        let boost_factor = match gas_data.network_congestion {
-            NetworkCongestion::Low => 100, // +0%
-            NetworkCongestion::Medium => 120, // +20%
-            NetworkCongestion::High => 150, // +50%
-            NetworkCongestion::VeryHigh => 200, // +100%
-        };
-        
-        // Giới hạn boost không vượt quá max_boost_percent
-        let boost_factor = std::cmp::min(boost_factor, 100 + self.config.priority_boost_percent);
-        
-        let boosted_priority_fee = priority_fee * U256::from(boost_factor) / U256::from(100);
-        
-        // Tính toán max fee dựa trên base fee gần đây
-        let base_fees: Vec<U256> = fee_history.base_fee_per_gas.clone();
-        let max_fee = if !base_fees.is_empty() || gas_data.base_fee.is_some() {
-            let latest_base_fee = base_fees.last()
-                .cloned()
-                .unwrap_or_else(|| gas_data.base_fee.unwrap_or_default());
-                
-            let base_fee_multiplier = match gas_data.network_congestion {
-                NetworkCongestion::Low => 120, // +20%
-                NetworkCongestion::Medium => 130, // +30%
-                NetworkCongestion::High => 150, // +50%
-                NetworkCongestion::VeryHigh => 200, // +100%
-            };
-            
-            latest_base_fee * U256::from(base_fee_multiplier) / U256::from(100) + boosted_priority_fee
-        } else {
-            // Nếu không có dữ liệu base fee, sử dụng gas price hiện tại
-            gas_data.gas_price
-        };
-        
-        // Đảm bảo max fee không vượt quá giới hạn
-        let capped_max_fee = if max_fee > self.config.max_gas_price {
-            self.config.max_gas_price
-        } else {
-            max_fee
+        // Mức khẩn cấp theo mức độ tắc nghẽn mạng
+        let gas_data = self.get_cached_gas_data(adapter).await?;
+        let urgency = match gas_data.network_congestion {
+            NetworkCongestion::Low | NetworkCongestion::Medium => FeeUrgency::Normal,
+            NetworkCongestion::High => FeeUrgency::Fast,
+            NetworkCongestion::VeryHigh => FeeUrgency::Instant,
         };
         
-        debug!("Tối ưu EIP-1559 fees: priority_fee={}, max_fee={} (congestion={:?})", 
-              boosted_priority_fee, capped_max_fee, gas_data.network_congestion);
+        self.get_eip1559_fees_for_urgency(adapter, urgency, &self.fee_caps()).await
+    }
+    
+    // Đề xuất phí EIP-1559 theo mức khẩn cấp từ percentile của eth_feeHistory
+    pub async fn get_eip1559_fees_for_urgency<A: ChainAdapter>(
+        &self,
+        adapter: &A,
+        urgency: FeeUrgency,
+        caps: &FeeCaps,
+    ) -> Result<(U256, U256)> {
+        let client = Arc::new(adapter.get_provider().clone());
+        let estimate = FeeEstimator::default().estimate(client, urgency, caps).await?;
+        
+        // Đảm bảo max fee không vượt quá giới hạn của optimizer và gói subscription
+        let capped_max_fee = std::cmp::min(estimate.max_fee_per_gas, self.max_fee_cap());
+        let priority_fee = std::cmp::min(estimate.max_priority_fee_per_gas, capped_max_fee);
+        
+        debug!("Tối ưu EIP-1559 fees: priority_fee={}, max_fee={} (urgency={}, congestion={:?})", 
+              priority_fee, capped_max_fee, urgency, self.network_congestion);
               
-        Ok((boosted_priority_fee, capped_max_fee))
+        Ok((priority_fee, capped_max_fee))
     }
     
     // Hàm tiện ích để tối ưu transaction request với giá gas phù hợp
-    pub async fn optimize_transaction<A: ChainAdapter>(&self, adapter: &A, mut tx: TransactionRequest) -> Result<TransactionRequest> {
+    pub async fn optimize_transaction<A: ChainAdapter>(&self, adapter: &A, mut tx: TypedTransaction) -> Result<TypedTransaction> {
         // Kiểm tra nếu EIP-1559 được hỗ trợ
         if adapter.get_config().eip1559_supported {
             let (priority_fee, max_fee) = self.get_optimal_eip1559_fees(adapter).await?;
             
             // Thiết lập max_priority_fee_per_gas và max_fee_per_gas
-            tx = tx.max_priority_fee_per_gas(priority_fee)
-                   .max_fee_per_gas(max_fee);
+            let request = <Eip1559TransactionRequest as From<TypedTransaction>>::from(tx)
+                .max_priority_fee_per_gas(priority_fee)
+                .max_fee_per_gas(max_fee);
+            tx = TypedTransaction::Eip1559(request);
         } else {
             // Đối với giao dịch legacy, thiết lập gas_price
             let gas_price = self.get_optimal_gas_price(adapter).await?;
-            tx = tx.gas_price(gas_price);
+            tx.set_gas_price(gas_price);
         }
         
         Ok(tx)
@@ -517,10 +515,11 @@ impl GasOptimizer {
         let new_price = base_price * U256::from(100 + increase_percent) / U256::from(100);
         
         // Đảm bảo không vượt quá giới hạn
-        if new_price > self.config.max_gas_price {
+        let max_fee_cap = self.max_fee_cap();
+        if new_price > max_fee_cap {
             warn!("Retry gas price {} vượt quá giới hạn {}, sử dụng giới hạn", 
-                new_price, self.config.max_gas_price);
-            self.config.max_gas_price
+                new_price, max_fee_cap);
+            max_fee_cap
         } else {
             info!("Tăng gas price do retry ({}) từ {} lên {} (+{}%)", 
                 retry_count, base_price, new_price, increase_percent);
@@ -528,6 +527,18 @@ impl GasOptimizer {
         }
     }
     
+    // Tăng phí thêm `percent` phần trăm cho lần gửi lại theo quyết định của retry policy.
+    // Trả về None khi phí đã chạm giới hạn phí hiện hành và không thể tăng thêm
+    pub fn bump_fee(&self, fee: U256, percent: u32) -> Option<U256> {
+        let bumped = fee.saturating_mul(U256::from(100 + u64::from(percent))) / U256::from(100);
+        let capped = bumped.min(self.max_fee_cap());
+        if capped <= fee {
+            warn!("Không thể tăng phí {} thêm {}%: đã chạm giới hạn {}", fee, percent, self.max_fee_cap());
+            return None;
+        }
+        Some(capped)
+    }
+    
     // Tối ưu gas limit
     pub fn get_optimized_gas_limit(&self, base_gas_limit: u64, retry_count: u32) -> u64 {
         if retry_count == 0 {
@@ -571,6 +582,30 @@ impl GasOptimizer {
         self.config.max_boost_percent = percent;
     }
     
+    // Áp dụng giới hạn phí của auto trade config và gói subscription
+    pub fn set_fee_caps(&self, caps: &FeeCaps) {
+        if let Ok(mut guard) = self.subscription_caps.write() {
+            *guard = caps.clone();
+        }
+    }
+    
+    // Giới hạn phí hiện hành: boost của subscription nếu có, ngược lại của optimizer
+    pub fn fee_caps(&self) -> FeeCaps {
+        let caps = self.subscription_caps.read().map(|guard| guard.clone()).unwrap_or_default();
+        FeeCaps::new(
+            caps.max_gas_boost_percent.or(Some(self.config.max_boost_percent)),
+            caps.max_gas_price_gwei,
+        )
+    }
+    
+    // Max fee tuyệt đối: nhỏ hơn giữa giới hạn optimizer và giới hạn subscription
+    fn max_fee_cap(&self) -> U256 {
+        match self.fee_caps().max_gas_price_wei() {
+            Some(cap) => std::cmp::min(cap, self.config.max_gas_price),
+            None => self.config.max_gas_price,
+        }
+    }
+    
     // Lấy thông tin tắc nghẽn mạng
     pub fn get_network_congestion(&self) -> NetworkCongestion {
         self.network_congestion
@@ -582,7 +617,7 @@ impl GasOptimizer {
             return None;
         }
         
-        let sum: U256 = self.gas_price_history.iter().map(|(_, price)| *price).sum();
+        let sum = self.gas_price_history.iter().fold(U256::zero(), |acc, (_, price)| acc.saturating_add(*price));
         Some(sum / U256::from(self.gas_price_history.len()))
     }
     
@@ -592,44 +627,58 @@ impl GasOptimizer {
             return None;
         }
         
-        let sum: U256 = self.base_fee_history.iter().map(|(_, fee)| *fee).sum();
+        let sum = self.base_fee_history.iter().fold(U256::zero(), |acc, (_, fee)| acc.saturating_add(*fee));
         Some(sum / U256::from(self.base_fee_history.len()))
     }
     
-    // Phân tích xu hướng gas price
+    // Phân tích xu hướng gas price theo mô hình của gas forecaster (% thay đổi dự đoán qua 5 mẫu tới)
     pub fn analyze_gas_trend(&self) -> Option<(f64, String)> {
-        if self.gas_price_history.len() < 5 {
-            return None;
-        }
+        let forecaster = gas_forecaster::get_gas_forecaster(self.chain_id)?;
+        let guard = forecaster.read().unwrap_or_else(|e| e.into_inner());
+        guard.gas_price_trend(5)
+    }
+    
+    // Phí rẻ nhất đạt xác suất vào block mục tiêu trong `within_blocks` block, trả về (priority_fee, max_fee)
+    // Nếu forecaster chưa đủ dữ liệu, dùng ước tính theo mức khẩn cấp tương ứng
+    pub async fn get_fees_for_inclusion_target<A: ChainAdapter>(
+        &self,
+        adapter: &A,
+        target_probability: f64,
+        within_blocks: u64,
+        caps: &FeeCaps,
+    ) -> Result<(U256, U256)> {
+        let quote: Option<FeeQuote> = gas_forecaster::get_gas_forecaster(self.chain_id)
+            .and_then(|forecaster| forecaster.read().unwrap_or_else(|e| e.into_inner())
+                .cheapest_fee_for_target(target_probability, within_blocks));
+        
+        let quote = match quote {
+            Some(quote) => quote,
+            None => {
+                let urgency = if target_probability >= 0.95 {
+                    FeeUrgency::Instant
+                } else if target_probability >= 0.8 {
+                    FeeUrgency::Fast
+                } else if target_probability >= 0.5 {
+                    FeeUrgency::Normal
+                } else {
+                    FeeUrgency::Slow
+                };
+                debug!("Gas forecaster chưa sẵn sàng cho chain {}, dùng urgency {}", self.chain_id, urgency);
+                return self.get_eip1559_fees_for_urgency(adapter, urgency, caps).await;
+            }
+        };
         
-        // Lấy 5 mẫu gần nhất
-        let samples: Vec<(u64, U256)> = self.gas_price_history
-            .iter()
-            .rev()
-            .take(5)
-            .cloned()
-            .collect();
-            
-        let newest = samples[0].1;
-        let oldest = samples[4].1;
-        
-        if oldest > U256::zero() {
-            let change_pct = if newest > oldest {
-                let diff = newest - oldest;
-                let pct = (diff.as_u128() as f64 * 100.0) / oldest.as_u128() as f64;
-                (pct, format!("Tăng {:.2}%", pct))
-            } else if newest < oldest {
-                let diff = oldest - newest;
-                let pct = (diff.as_u128() as f64 * 100.0) / oldest.as_u128() as f64;
-                (-pct, format!("Giảm {:.2}%", pct))
-            } else {
-                (0.0, "Ổn định".to_string())
-            };
-            
-            Some(change_pct)
-        } else {
-            None
+        // Áp dụng giới hạn của optimizer và gói subscription
+        let mut max_fee = std::cmp::min(quote.max_fee_per_gas, self.max_fee_cap());
+        if let Some(cap) = caps.max_gas_price_wei() {
+            max_fee = std::cmp::min(max_fee, cap);
         }
+        let priority_fee = std::cmp::min(quote.max_priority_fee_per_gas, max_fee);
+        
+        debug!("Phí cho xác suất {:.0}% trong {} block: priority_fee={}, max_fee={} (dự đoán {:.1}%)",
+              target_probability * 100.0, within_blocks, priority_fee, max_fee, quote.inclusion_probability * 100.0);
+        
+        Ok((priority_fee, max_fee))
     }
 }
 
@@ -643,7 +692,7 @@ pub async fn get_current_gas_price<A: ChainAdapter>(adapter: &A) -> Result<U256>
         
     // Kiểm tra cache
     {
-        let cache = GAS_PRICE_CACHE.read().unwrap();
+        let cache = GAS_PRICE_CACHE.read().unwrap_or_else(|e| e.into_inner());
         if let Some(data) = cache.get(&chain_id) {
             // Nếu cache còn mới (< GAS_CACHE_DURATION giây)
             if current_time - data.timestamp < GAS_CACHE_DURATION {
@@ -667,7 +716,7 @@ pub async fn get_current_gas_price<A: ChainAdapter>(adapter: &A) -> Result<U256>
     };
     
     {
-        let mut cache = GAS_PRICE_CACHE.write().unwrap();
+        let mut cache = GAS_PRICE_CACHE.write().unwrap_or_else(|e| e.into_inner());
         cache.insert(chain_id, data);
     }
     
@@ -681,95 +730,8 @@ pub fn cleanup_gas_cache() {
         .unwrap()
         .as_secs();
         
-    let mut cache = GAS_PRICE_CACHE.write().unwrap();
+    let mut cache = GAS_PRICE_CACHE.write().unwrap_or_else(|e| e.into_inner());
     
     // Remove entries older than 2x cache duration
     cache.retain(|_, data| current_time - data.timestamp < GAS_CACHE_DURATION * 2);
 }
-
-// Thêm hàm sumU256 để tính tổng của Vec<U256>
-fn sumU256(values: &[U256]) -> U256 {
-    let mut sum = U256::zero();
-    for v in values {
-        sum = sum.saturating_add(*v);
-    }
-    sum
-}
-
-// Thêm hàm tính trung bình U256
-fn avgU256(values: &[U256]) -> U256 {
-    if values.is_empty() {
-        return U256::zero();
-    }
-    let sum = sumU256(values);
-    sum / U256::from(values.len())
-}
-
-pub async fn calculate_priority_fee(&self, provider: Arc<Provider<Http>>) -> Result<U256, Error> {
-    // Kiểm tra cache
-    let cache_key = provider.url().to_string();
-    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-    
-    // Kiểm tra trong bộ nhớ đệm
-    if let Some(cached) = self.cache.read().await.get(&cache_key) {
-        if now - cached.timestamp < 60 { // Cache valid for 1 minute
-            return Ok(cached.value);
-        }
-    }
-    
-    // Lấy 10 block gần nhất
-    let latest_block = provider.get_block_number().await?;
-    let mut priority_fees = Vec::new();
-    
-    for i in 0..10 {
-        if latest_block <= i.into() {
-            break;
-        }
-        
-        let block_number = latest_block - i;
-        if let Some(block) = provider.get_block(block_number).await? {
-            if let Some(base_fee) = block.base_fee_per_gas {
-                // Xử lý các giao dịch trong block
-                for tx_hash in block.transactions {
-                    if let Some(tx) = provider.get_transaction(tx_hash).await? {
-                        if let (Some(max_fee), Some(priority_fee)) = (tx.max_fee_per_gas, tx.max_priority_fee_per_gas) {
-                            // Tính toán priority fee thực tế được trả
-                            let effective_priority = if base_fee >= max_fee {
-                                U256::zero()
-                            } else {
-                                let available = max_fee - base_fee;
-                                if available < priority_fee {
-                                    available
-                                } else {
-                                    priority_fee
-                                }
-                            };
-                            
-                            if !effective_priority.is_zero() {
-                                priority_fees.push(effective_priority);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    // Tính priority fee trung bình
-    let result = if priority_fees.is_empty() {
-        // Giá trị mặc định nếu không có dữ liệu
-        U256::from(1_000_000_000) // 1 gwei
-    } else {
-        // Sử dụng hàm trợ giúp để tính tổng
-        let sum = sumU256(&priority_fees);
-        sum / U256::from(priority_fees.len())
-    };
-    
-    // Lưu vào cache
-    self.cache.write().await.insert(cache_key, CacheEntry {
-        value: result,
-        timestamp: now,
-    });
-    
-    Ok(result)
-}
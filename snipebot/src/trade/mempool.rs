@@ -1,30 +1,27 @@
 // External imports
 use ethers::types::{Address, U256, H256, Transaction};
-use ethers::providers::{Provider, Http, ProviderError};
+use ethers::providers::{Provider, Http, Middleware};
 
 // Standard library imports
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
-use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU32, Ordering};
-use std::str::FromStr;
 use std::num::NonZeroUsize;
 
 // Internal imports
-use crate::types::*;
-use crate::chain_adapters::ChainAdapter;
-use super::config::Config;
-use super::service::ServiceMessage;
-use super::utils;
+use crate::config::Config;
+use crate::service::ServiceMessage;
+use crate::utils;
 
 // Third party imports
-use tokio::time::sleep;
 use tokio::sync::mpsc;
 use futures::Stream;
 use futures::StreamExt;
 use tracing::{info, warn, error, debug};
 use serde::{Serialize, Deserialize};
 use lru::LruCache;
+use async_trait::async_trait;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PendingSwap {
@@ -41,7 +38,8 @@ pub struct MempoolWatcher {
     config: Config,
     tx_sender: mpsc::Sender<ServiceMessage>,
     watching_tokens: Vec<String>,
-    mempool_tracker: Option<MempoolTracker>,
+    /// Dữ liệu mempool dùng chung giữa vòng theo dõi và các truy vấn
+    mempool_tracker: Arc<std::sync::Mutex<MempoolTracker>>,
     counter: AtomicU32,
 }
 
@@ -62,6 +60,7 @@ impl Default for MempoolMonitorConfig {
     }
 }
 
+#[allow(dead_code)]
 impl MempoolWatcher {
     pub fn new(config: Config, tx_sender: mpsc::Sender<ServiceMessage>) -> Self {
         let provider = Provider::<Http>::try_from(&config.rpc_url)
@@ -72,7 +71,7 @@ impl MempoolWatcher {
             config,
             tx_sender,
             watching_tokens: Vec::new(),
-            mempool_tracker: Some(MempoolTracker::new(1000.0)), // $1000 cho large txs
+            mempool_tracker: Arc::new(std::sync::Mutex::new(MempoolTracker::new(1000.0))), // $1000 cho large txs
             counter: AtomicU32::new(0),
         }
     }
@@ -86,6 +85,15 @@ impl MempoolWatcher {
         }
     }
     
+    fn tracker(&self) -> std::sync::MutexGuard<'_, MempoolTracker> {
+        self.mempool_tracker.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    
+    /// Tracker dùng chung để truy vấn trong khi vòng `start_watching` đang chạy
+    pub fn shared_tracker(&self) -> Arc<std::sync::Mutex<MempoolTracker>> {
+        Arc::clone(&self.mempool_tracker)
+    }
+    
     pub async fn start_watching(&self) -> Result<(), Box<dyn std::error::Error>> {
         info!("Bắt đầu theo dõi mempool");
         
@@ -97,16 +105,13 @@ impl MempoolWatcher {
         Ok(())
     }
     
-    async fn process_transactions<S: Stream<Item = Result<H256, ProviderError>> + Unpin>(
+    async fn process_transactions<S: Stream<Item = H256> + Unpin>(
         &self,
         mut tx_stream: S
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let mut mempool_tracker = MempoolTracker::new(1000.0); // $1000 cho large txs
-        
         // Xử lý các giao dịch trong stream
-        while let Some(tx_hash_result) = tx_stream.next().await {
-            match tx_hash_result {
-                Ok(tx_hash) => {
+        while let Some(tx_hash) = tx_stream.next().await {
+            {
                     // Lấy chi tiết transaction từ hash với timeout
                     match tokio::time::timeout(
                         Duration::from_secs(2),
@@ -129,12 +134,12 @@ impl MempoolWatcher {
                                                 timestamp: utils::safe_now(),
                                             };
                                             
-                                            mempool_tracker.add_pending_swap(pending_swap.clone());
+                                            self.tracker().add_pending_swap(pending_swap.clone());
                                             
                                             // Gửi thông báo cho các token đang được theo dõi
                                             if self.watching_tokens.contains(&pending_swap.token_address) {
                                                 // Gửi message về swap đang chờ xử lý
-                                                if let Err(e) = self.tx_sender.send(ServiceMessage::PendingSwap(pending_swap)).await {
+                                                if let Err(e) = self.tx_sender.send(ServiceMessage::PendingSwap(pending_swap.clone())).await {
                                                     error!("Lỗi khi gửi thông báo: {}", e);
                                                 }
                                                 
@@ -158,28 +163,30 @@ impl MempoolWatcher {
                         },
                         Err(_) => warn!("Timeout khi lấy giao dịch {:?}", tx_hash),
                     }
-                },
-                Err(e) => error!("Lỗi khi nhận transaction hash: {}", e),
             }
             
             // Dọn dẹp dữ liệu cũ mỗi 100 giao dịch - sử dụng AtomicU32 thay vì static mut
             let counter = self.counter.fetch_add(1, Ordering::SeqCst) + 1;
-            if counter % 100 == 0 {
-                mempool_tracker.cleanup_old_data(300); // 5 phút
+            if counter.is_multiple_of(100) {
+                self.tracker().cleanup_old_data(300); // 5 phút
             }
             
-            // Kiểm tra cơ hội arbitrage và sandwich
-            if let Some(opportunity) = mempool_tracker.get_best_arbitrage_opportunity() {
+            // Kiểm tra cơ hội arbitrage và sandwich (không giữ lock tracker khi gửi message)
+            let (arbitrage, sandwich) = {
+                let tracker = self.tracker();
+                (tracker.get_best_arbitrage_opportunity().cloned(), tracker.get_best_sandwich_opportunity().cloned())
+            };
+            if let Some(opportunity) = arbitrage {
                 if opportunity.potential_profit_percent >= 1.0 { // Tối thiểu 1%
-                    if let Err(e) = self.tx_sender.send(ServiceMessage::ArbitrageOpportunity(opportunity.clone())).await {
+                    if let Err(e) = self.tx_sender.send(ServiceMessage::ArbitrageOpportunity(opportunity)).await {
                         error!("Lỗi khi gửi thông báo cơ hội arbitrage: {}", e);
                     }
                 }
             }
             
-            if let Some(opportunity) = mempool_tracker.get_best_sandwich_opportunity() {
+            if let Some(opportunity) = sandwich {
                 if opportunity.potential_profit >= 50.0 { // Tối thiểu $50
-                    if let Err(e) = self.tx_sender.send(ServiceMessage::SandwichOpportunity(opportunity.clone())).await {
+                    if let Err(e) = self.tx_sender.send(ServiceMessage::SandwichOpportunity(opportunity)).await {
                         error!("Lỗi khi gửi thông báo cơ hội sandwich: {}", e);
                     }
                 }
@@ -203,7 +210,8 @@ impl MempoolWatcher {
     
     // Phương thức nội bộ để phân tích giao dịch
     async fn _analyze_transaction_internal(&self, tx: &Transaction) -> Option<(String, bool, f64)> {
-        if let Some(input) = &tx.input {
+        let input = &tx.input;
+        {
             if input.len() < 4 {
                 // Input quá ngắn để phân tích
                 debug!("Input data quá ngắn để phân tích: {:?}", tx.hash);
@@ -304,7 +312,7 @@ impl MempoolWatcher {
     }
     
     // Đăng ký callback khi phát hiện giao dịch trong mempool
-    pub fn add_callback<F>(&mut self, callback: F)
+    pub fn add_callback<F>(&mut self, _callback: F)
     where
         F: Fn(String, u64) + Send + Sync + 'static,
     {
@@ -314,80 +322,60 @@ impl MempoolWatcher {
     
     // Kiểm tra mempool và trả về giao dịch đang chờ
     pub async fn check_mempool(&self) -> Result<Vec<PendingSwap>, Box<dyn std::error::Error>> {
-        // Lấy dữ liệu từ mempool_tracker nếu có
-        if let Some(tracker) = &self.mempool_tracker {
-            let mut result = Vec::new();
-            
-            // Thu thập tất cả các pending swaps từ tracker
-            for (_, swaps) in tracker.pending_swaps.iter() {
-                for swap in swaps {
-                    result.push(swap.clone());
-                }
-            }
-            
-            Ok(result)
-        } else {
-            Ok(Vec::new())
-        }
+        // Thu thập tất cả các pending swaps từ tracker
+        let tracker = self.tracker();
+        Ok(tracker.pending_swaps.values().flatten().cloned().collect())
     }
     
     // Tìm cơ hội sandwich
     pub async fn find_sandwich_opportunities(&self) -> Result<Vec<SandwichOpportunity>, Box<dyn std::error::Error>> {
-        if let Some(tracker) = &self.mempool_tracker {
-            Ok(tracker.get_sandwich_opportunities().into_iter().cloned().collect())
-        } else {
-            Ok(Vec::new())
-        }
+        Ok(self.tracker().get_sandwich_opportunities().into_iter().cloned().collect())
     }
     
     // Lấy các cơ hội arbitrage
     pub async fn find_arbitrage_opportunities(&self) -> Result<Vec<ArbitrageOpportunity>, Box<dyn std::error::Error>> {
-        if let Some(tracker) = &self.mempool_tracker {
-            Ok(tracker.get_arbitrage_opportunities().into_iter().cloned().collect())
-        } else {
-            Ok(Vec::new())
-        }
+        Ok(self.tracker().get_arbitrage_opportunities().into_iter().cloned().collect())
     }
     
     // ... giữ lại các phương thức hiện có ...
     
-    fn extract_token_address_from_path(&self, input_str: &str) -> Option<String> {
+    fn extract_token_address_from_path(&self, _input_str: &str) -> Option<String> {
         // ... existing code ...
         // Kế thừa từ mempool.rs
         None
     }
     
-    fn extract_token_address_for_sell(&self, input_str: &str) -> Option<String> {
+    fn extract_token_address_for_sell(&self, _input_str: &str) -> Option<String> {
         // ... existing code ...
         // Kế thừa từ mempool.rs
         None
     }
     
-    fn extract_amount_from_sell_input(&self, input_str: &str) -> Option<String> {
+    fn extract_amount_from_sell_input(&self, _input_str: &str) -> Option<String> {
         // ... existing code ...
         // Kế thừa từ mempool.rs
         None
     }
     
-    async fn filter_transactions(&self, tx_hash: H256) -> bool {
+    async fn filter_transactions(&self, _tx_hash: H256) -> bool {
         // ... existing code ...
         // Kế thừa từ mempool.rs
         false
     }
     
-    fn is_target_address(&self, address: &Address) -> bool {
+    fn is_target_address(&self, _address: &Address) -> bool {
         // ... existing code ...
         // Kế thừa từ mempool.rs
         false
     }
     
-    pub async fn count_pending_txs_for_token(&self, token_address: &str) -> u32 {
+    pub async fn count_pending_txs_for_token(&self, _token_address: &str) -> u32 {
         // ... existing code ...
         // Kế thừa từ mempool.rs
         0
     }
     
-    pub async fn update_token_status_with_pending_txs(&self, token_tracker: &mut dyn TokenStatusUpdater) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn update_token_status_with_pending_txs(&self, _token_tracker: &mut dyn TokenStatusUpdater) -> Result<(), Box<dyn std::error::Error>> {
         // ... existing code ...
         // Kế thừa từ mempool.rs
         Ok(())
@@ -407,7 +395,8 @@ impl MempoolWatcher {
 
 // ... giữ lại các traits, structs, và implementations khác ...
 
-pub trait TokenStatusUpdater {
+#[async_trait]
+pub trait TokenStatusUpdater: Send {
     async fn update_pending_tx_count(&mut self, token_address: &str, count: u32) -> Result<(), Box<dyn std::error::Error>>;
 }
 
@@ -421,8 +410,9 @@ pub struct TokenStatus {
     // Thêm các trường khác nếu cần
 }
 
+#[async_trait]
 impl<M: Middleware + 'static> TokenStatusUpdater for TokenStatusTracker<M> {
-    async fn update_pending_tx_count(&mut self, token_address: &str, count: u32) -> Result<(), Box<dyn std::error::Error>> {
+    async fn update_pending_tx_count(&mut self, _token_address: &str, _count: u32) -> Result<(), Box<dyn std::error::Error>> {
         // ... existing code ...
         // Kế thừa từ mempool.rs
         Ok(())
@@ -439,6 +429,7 @@ pub struct MempoolTracker {
     pub max_items_per_token: usize, // Giới hạn số lượng items cho mỗi token
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LargeTransaction {
     pub tx_hash: String,
     pub token_address: String,
@@ -448,6 +439,7 @@ pub struct LargeTransaction {
     pub timestamp: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArbitrageOpportunity {
     pub token_address: String,
     pub exchange1: String,
@@ -458,6 +450,7 @@ pub struct ArbitrageOpportunity {
     pub timestamp: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SandwichOpportunity {
     pub token_address: String,
     pub victim_tx_hash: String,
@@ -495,6 +488,7 @@ pub struct TokenMetrics {
     pub last_updated: u64,
 }
 
+#[allow(dead_code)]
 impl MempoolTracker {
     pub fn new(min_large_tx_amount: f64) -> Self {
         // ... existing code ...
@@ -510,39 +504,39 @@ impl MempoolTracker {
         }
     }
     
-    pub fn add_pending_swap(&mut self, swap: PendingSwap) {
+    pub fn add_pending_swap(&mut self, _swap: PendingSwap) {
         // ... existing code ...
         // Kế thừa từ mempool.rs
     }
     
-    fn find_sandwich_opportunities(&mut self, token_address: &str) {
+    fn find_sandwich_opportunities(&mut self, _token_address: &str) {
         // ... existing code ...
         // Kế thừa từ mempool.rs
     }
     
-    fn estimate_price_impact(&self, token_address: &str, amount_usd: f64) -> f64 {
+    fn estimate_price_impact(&self, _token_address: &str, _amount_usd: f64) -> f64 {
         // ... existing code ...
         // Kế thừa từ mempool.rs
         0.0
     }
     
-    fn estimate_sandwich_profit(&self, token_address: &str, amount_usd: f64) -> f64 {
+    fn estimate_sandwich_profit(&self, _token_address: &str, _amount_usd: f64) -> f64 {
         // ... existing code ...
         // Kế thừa từ mempool.rs
         0.0
     }
     
-    pub fn add_arbitrage_opportunity(&mut self, opportunity: ArbitrageOpportunity) {
+    pub fn add_arbitrage_opportunity(&mut self, _opportunity: ArbitrageOpportunity) {
         // ... existing code ...
         // Kế thừa từ mempool.rs
     }
     
-    pub fn add_sandwich_opportunity(&mut self, opportunity: SandwichOpportunity) {
+    pub fn add_sandwich_opportunity(&mut self, _opportunity: SandwichOpportunity) {
         // ... existing code ...
         // Kế thừa từ mempool.rs
     }
     
-    pub fn cleanup_old_data(&mut self, max_age_seconds: u64) {
+    pub fn cleanup_old_data(&mut self, _max_age_seconds: u64) {
         // ... existing code ...
         // Kế thừa từ mempool.rs
     }
@@ -571,20 +565,15 @@ impl MempoolTracker {
         Vec::new()
     }
     
-    pub fn get_token_metrics(&self, token_address: &str) -> Option<&TokenMetrics> {
+    pub fn get_token_metrics(&self, _token_address: &str) -> Option<&TokenMetrics> {
         // ... existing code ...
         // Kế thừa từ mempool.rs
         None
     }
 }
 
-// Include any missing middleware types
-pub trait Middleware {}
-
 #[cfg(test)]
 mod tests {
-    use super::*;
-
     #[tokio::test]
     async fn test_mempool_watcher() {
         // TODO: Implement tests
@@ -0,0 +1,8 @@
+// Các module giao dịch: ước lượng phí, dự báo gas, tối ưu gas và phân tích token
+pub mod fee_estimator;
+pub mod gas_forecaster;
+pub mod gas_optimizer;
+pub mod token_status;
+pub mod risk_analyzer;
+pub mod trade_logic;
+pub mod mempool;
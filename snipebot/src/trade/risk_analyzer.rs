@@ -1,38 +1,34 @@
 // External imports
 use ethers::{
     types::{Address, H256, U256, Bytes},
-    contract::{Contract, ContractFactory, ContractError},
-    providers::{Provider, Http, Middleware, Ws},
-    prelude::*,
+    providers::{Provider, Http, Middleware},
 };
 
 // Standard library imports
 use std::{
     collections::HashMap,
     sync::{Arc, RwLock},
-    time::{Duration, SystemTime, UNIX_EPOCH, Instant},
+    time::{Duration, SystemTime, UNIX_EPOCH},
     str::FromStr,
 };
 
 // Internal imports
 use crate::{
-    types::TradeConfig,
     chain_adapters::ChainAdapterEnum,
-    utils,
-    abi_utils,
     config::Config,
 };
 
 // Third party imports
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Result, anyhow};
 use async_trait::async_trait;
-use tracing::{info, warn, error, debug};
+use tracing::{info, error, debug};
 use once_cell::sync::OnceCell;
 use tokio::sync::Mutex;
 use serde::{Serialize, Deserialize};
 
 /// Định nghĩa các function signature phổ biến để phân tích
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 struct FunctionSignatures {
     transfer: [u8; 4],
     transfer_from: [u8; 4],
@@ -379,7 +375,8 @@ pub struct RiskConfig {
 
 /// Triển khai RiskAnalyzer cơ bản
 #[derive(Debug, Clone)]
-pub struct BasicRiskAnalyzer<P: Provider + 'static> {
+#[allow(dead_code)]
+pub struct BasicRiskAnalyzer<P: Middleware + 'static> {
     /// Cấu hình risk analyzer
     config: RiskConfig,
     /// Provider
@@ -394,7 +391,7 @@ pub struct BasicRiskAnalyzer<P: Provider + 'static> {
     safety_cache: Arc<RwLock<HashMap<Address, bool>>>,
 }
 
-impl<P: Provider + 'static> BasicRiskAnalyzer<P> {
+impl<P: Middleware + 'static> BasicRiskAnalyzer<P> {
     /// Tạo RiskAnalyzer mới
     pub fn new(provider: Arc<P>, config: RiskConfig) -> Self {
         Self {
@@ -408,6 +405,7 @@ impl<P: Provider + 'static> BasicRiskAnalyzer<P> {
     }
 
     /// Kiểm tra bytecode để phát hiện các function signature nguy hiểm
+    #[allow(dead_code)]
     fn analyze_bytecode(&self, code: &Bytes, info: &mut ContractSecurityInfo) {
         let bytecode = &code.0;
         
@@ -458,7 +456,7 @@ impl<P: Provider + 'static> BasicRiskAnalyzer<P> {
         }
         
         // Lấy thông tin chi tiết
-        let token_analysis = match self.analyze_token(address).await {
+        let token_analysis = match RiskAnalyzer::analyze_token(self, address).await {
             Ok(analysis) => analysis,
             Err(e) => {
                 error!(token = %token_address, error = %e, "Lỗi khi phân tích token");
@@ -517,7 +515,7 @@ impl<P: Provider + 'static> BasicRiskAnalyzer<P> {
 }
 
 #[async_trait]
-impl<P: Provider + 'static> RiskAnalyzer for BasicRiskAnalyzer<P> {
+impl<P: Middleware + 'static> RiskAnalyzer for BasicRiskAnalyzer<P> {
     async fn analyze_token(&self, token: Address) -> Result<TokenRiskAnalysis> {
         info!("Đang phân tích rủi ro cho token: {}", token);
         
@@ -594,7 +592,7 @@ impl<P: Provider + 'static> RiskAnalyzer for BasicRiskAnalyzer<P> {
         Ok(analysis)
     }
     
-    async fn analyze_transaction(&self, tx: Vec<u8>) -> Result<TransactionRiskAnalysis> {
+    async fn analyze_transaction(&self, _tx: Vec<u8>) -> Result<TransactionRiskAnalysis> {
         // TODO: Implement transaction risk analysis
         let risk_analysis = RiskAnalysis::new();
         let analysis = TransactionRiskAnalysis {
@@ -633,16 +631,16 @@ impl<P: Provider + 'static> RiskAnalyzer for BasicRiskAnalyzer<P> {
 #[derive(Debug, Clone)]
 pub struct AdvancedRiskAnalyzer {
     /// BasicRiskAnalyzer nền tảng
-    basic_analyzer: BasicRiskAnalyzer<Http>,
+    basic_analyzer: BasicRiskAnalyzer<Provider<Http>>,
     /// Bật/tắt stress test
     stress_test_enabled: bool,
 }
 
 impl AdvancedRiskAnalyzer {
     /// Tạo advanced analyzer mới
-    pub fn new(config: RiskConfig) -> Self {
+    pub fn new(provider: Arc<Provider<Http>>, config: RiskConfig) -> Self {
         Self {
-            basic_analyzer: BasicRiskAnalyzer::new(Arc::new(Http), config),
+            basic_analyzer: BasicRiskAnalyzer::new(provider, config),
             stress_test_enabled: false,
         }
     }
@@ -650,11 +648,10 @@ impl AdvancedRiskAnalyzer {
     /// Tạo advanced analyzer với adapter
     pub fn new_with_adapter(
         config: RiskConfig,
-        adapter: ChainAdapterEnum,
-        weth_address: &str
+        adapter: &ChainAdapterEnum,
     ) -> Result<Self> {
         Ok(Self {
-            basic_analyzer: BasicRiskAnalyzer::new_with_adapter(Arc::new(Http), config, adapter, weth_address)?,
+            basic_analyzer: BasicRiskAnalyzer::new(Arc::new(adapter.get_provider().clone()), config),
             stress_test_enabled: false,
         })
     }
@@ -707,7 +704,7 @@ impl AdvancedRiskAnalyzer {
 impl RiskAnalyzer for AdvancedRiskAnalyzer {
     async fn analyze_token(&self, token: Address) -> Result<TokenRiskAnalysis> {
         // Dùng basic analyzer cơ bản
-        let mut analysis = self.basic_analyzer.analyze_token(token).await?;
+        let mut analysis = RiskAnalyzer::analyze_token(&self.basic_analyzer, token).await?;
         
         // Thêm phân tích nâng cao
         if self.stress_test_enabled {
@@ -729,7 +726,7 @@ impl RiskAnalyzer for AdvancedRiskAnalyzer {
 }
 
 #[async_trait]
-impl<P: Provider + 'static> TokenAnalyzer for BasicRiskAnalyzer<P> {
+impl<P: Middleware + 'static> TokenAnalyzer for BasicRiskAnalyzer<P> {
     async fn analyze_token(&self, token_address: &str) -> Result<TokenAnalysisResult> {
         self.analyze_token_by_address(token_address).await
     }
@@ -750,6 +747,12 @@ impl RiskAnalysis {
     }
 }
 
+impl Default for RiskAnalysis {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Singleton pattern để lấy RiskAnalyzer instance trong ứng dụng
 static RISK_ANALYZER: OnceCell<Arc<Mutex<dyn RiskAnalyzer + Send + Sync>>> = OnceCell::new();
 
@@ -790,15 +793,19 @@ pub async fn get_risk_analyzer() -> Result<Arc<Mutex<dyn RiskAnalyzer + Send + S
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::str::FromStr;
+    
     
     #[test]
     fn test_token_risk_analysis() {
         let analysis = TokenRiskAnalysis {
+            base: RiskAnalysis {
+                risk_score: 75.0,
+                risk_factors: Vec::new(),
+                timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+            },
             token: Address::zero(),
             symbol: "TEST".to_string(),
             name: "Test Token".to_string(),
-            risk_score: 75.0,
             total_issues: 3,
             critical_issues: 1,
             high_issues: 1,
@@ -822,7 +829,7 @@ mod tests {
         
         assert_eq!(analysis.token, Address::zero());
         assert_eq!(analysis.symbol, "TEST");
-        assert_eq!(analysis.risk_score, 75.0);
+        assert_eq!(analysis.base.risk_score, 75.0);
         assert_eq!(analysis.total_issues, 3);
     }
     
@@ -893,6 +900,7 @@ mod tests {
             created_at: SystemTime::now(),
             max_allocation_per_token: 0.1,
             max_tokens_in_portfolio: 20,
+            offline_mode: false,
         };
         
         assert_eq!(config.config_id, "test");
@@ -911,10 +919,12 @@ mod tests {
             created_at: SystemTime::now(),
             max_allocation_per_token: 0.1,
             max_tokens_in_portfolio: 20,
+            offline_mode: true,
         };
         
-        let analyzer = BasicRiskAnalyzer::new(Arc::new(Http), config);
-        assert!(analyzer.provider.is_none());
-        assert!(analyzer.token_abi.is_none());
+        let provider = Provider::<Http>::try_from("http://localhost:8545").unwrap();
+        let analyzer = BasicRiskAnalyzer::new(Arc::new(provider), config);
+        assert!(analyzer.config.offline_mode);
+        assert!(analyzer.safety_cache.read().unwrap().is_empty());
     }
 }
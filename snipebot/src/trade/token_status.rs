@@ -1,35 +1,31 @@
 use ethers::{
-    providers::{Middleware},
-    types::{Address, U256, H256, TransactionReceipt},
-    contract::Contract,
-    providers::Provider
+    types::{Address, U256},
+    contract::Contract
 };
 use std::time::{Duration, Instant};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::str::FromStr;
 use std::collections::HashMap;
-use log::{info, warn, debug, error};
+use log::{warn, debug};
 use anyhow::{Result, anyhow};
 use serde::{Serialize, Deserialize};
-use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::{sync::RwLock};
+use tokio::sync::{Mutex, MutexGuard, Semaphore};
+use futures::future::join_all;
 use async_trait::async_trait;
-use serde_json::Value;
 use std::{fmt::Debug};
-use metrics::{counter, gauge};
 
 // Import crate modules
 use crate::{
     chain_adapters::{
-        ChainAdapter, 
-        ChainAdapterEnum, 
-        ChainError, 
-        GasInfo
+        ChainAdapterEnum,
+        multicall::{self, Call3},
     },
     risk_analyzer::TokenRiskAnalysis,
+    utils,
+    abi_utils,
 };
 
-use common::cache::{Cache, CacheEntry};
+use diamond_common::cache::{Cache, CacheEntry};
 
 /// Cấu trúc thông tin token
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -196,6 +192,7 @@ pub trait AsyncTokenStatusTracker: TokenStatusTrackerBase {
 }
 
 /// Token Status Tracker
+#[allow(dead_code)]
 pub struct TokenStatusTracker {
     adapter: ChainAdapterEnum,
     tracked_tokens: Arc<RwLock<HashMap<String, CacheEntry<TokenStatus>>>>,
@@ -211,30 +208,40 @@ pub struct TokenStatusTracker {
 
 #[async_trait]
 impl Cache for TokenStatusTracker {
-    async fn get_from_cache<T: Serialize + for<'de> Deserialize<'de> + Send + Sync + 'static>(&self, key: &str) -> Result<Option<T>> {
+    async fn get<K, V>(&self, key: K) -> Result<Option<V>>
+    where
+        K: AsRef<str> + Send + Sync,
+        V: for<'de> Deserialize<'de> + Send + Sync + 'static,
+    {
         let tokens = self.tracked_tokens.read().map_err(|e| anyhow!("RwLock error: {}", e))?;
-        if let Some(entry) = tokens.get(key) {
+        if let Some(entry) = tokens.get(key.as_ref()) {
             if !entry.is_expired() {
-                if let Ok(value) = serde_json::to_string(&entry.value) {
-                    return Ok(Some(serde_json::from_str(&value)?));
-                }
+                let value = serde_json::to_value(&entry.value)?;
+                return Ok(Some(serde_json::from_value(value)?));
             }
         }
         Ok(None)
     }
 
-    async fn store_in_cache<T: Serialize + for<'de> Deserialize<'de> + Send + Sync + 'static>(&self, key: &str, value: T, ttl_seconds: u64) -> Result<()> {
+    async fn set<K, V>(&self, key: K, value: V, ttl_seconds: u64) -> Result<()>
+    where
+        K: AsRef<str> + Send + Sync,
+        V: Serialize + Send + Sync + 'static,
+    {
         let json_value = serde_json::to_value(value)?;
         if let Ok(token_status) = serde_json::from_value::<TokenStatus>(json_value) {
             let mut tokens = self.tracked_tokens.write().map_err(|e| anyhow!("RwLock error: {}", e))?;
-            tokens.insert(key.to_string(), CacheEntry::new(token_status, ttl_seconds));
+            tokens.insert(key.as_ref().to_string(), CacheEntry::new(token_status, ttl_seconds));
         }
         Ok(())
     }
 
-    async fn remove(&self, key: &str) -> Result<()> {
+    async fn remove<K>(&self, key: K) -> Result<()>
+    where
+        K: AsRef<str> + Send + Sync,
+    {
         let mut tokens = self.tracked_tokens.write().map_err(|e| anyhow!("RwLock error: {}", e))?;
-        tokens.remove(key);
+        tokens.remove(key.as_ref());
         Ok(())
     }
 
@@ -244,19 +251,86 @@ impl Cache for TokenStatusTracker {
         Ok(())
     }
 
-    async fn cleanup_cache(&self) -> Result<()> {
+    async fn cleanup(&self) -> Result<()> {
         let mut tokens = self.tracked_tokens.write().map_err(|e| anyhow!("RwLock error: {}", e))?;
-        let keys_to_remove: Vec<String> = tokens
-            .iter()
-            .filter(|(_, entry)| entry.is_expired())
-            .map(|(key, _)| key.clone())
-            .collect();
-        
-        for key in keys_to_remove {
-            tokens.remove(&key);
-        }
+        tokens.retain(|_, entry| !entry.is_expired());
         Ok(())
     }
+
+    async fn len(&self) -> Result<usize> {
+        let tokens = self.tracked_tokens.read().map_err(|e| anyhow!("RwLock error: {}", e))?;
+        Ok(tokens.len())
+    }
+}
+
+impl TokenStatusTrackerBase for TokenStatusTracker {
+    fn get_tracked_tokens(&self) -> Vec<String> {
+        match self.tracked_tokens.read() {
+            Ok(tokens) => tokens.keys().cloned().collect(),
+            Err(poisoned) => poisoned.into_inner().keys().cloned().collect(),
+        }
+    }
+
+    fn is_tracking(&self, token_address: &str) -> bool {
+        match self.tracked_tokens.read() {
+            Ok(tokens) => tokens.contains_key(token_address),
+            Err(poisoned) => poisoned.into_inner().contains_key(token_address),
+        }
+    }
+
+    fn get_cached_status(&self, token_address: &str) -> Option<TokenStatus> {
+        let tokens = self.tracked_tokens.read().ok()?;
+        tokens.get(token_address).map(|entry| entry.value.clone())
+    }
+}
+
+/// Đổi số lượng token sang số thực theo decimals
+fn units_to_f64(amount: U256, decimals: u8) -> f64 {
+    ethers::utils::format_units(amount, decimals as u32)
+        .ok()
+        .and_then(|value| value.parse::<f64>().ok())
+        .unwrap_or(0.0)
+}
+
+/// Tính trạng thái mới của token từ reserves của pair token/WETH.
+/// Giá USD suy ra từ tỷ giá native/USD của lần cập nhật trước (nếu có).
+fn status_from_reserves(
+    old_status: &TokenStatus,
+    token: Address,
+    weth: Address,
+    token0: Address,
+    (reserve0, reserve1): (U256, U256),
+    total_supply: Option<U256>,
+) -> Option<TokenStatus> {
+    let (token_reserve, native_reserve) = if token0 == token {
+        (reserve0, reserve1)
+    } else if token0 == weth {
+        (reserve1, reserve0)
+    } else {
+        return None;
+    };
+    
+    let token_amount = units_to_f64(token_reserve, old_status.decimals);
+    let native_amount = units_to_f64(native_reserve, 18);
+    
+    let mut status = old_status.clone();
+    status.price_native = if token_amount > 0.0 { native_amount / token_amount } else { 0.0 };
+    
+    if let Some(total_supply) = total_supply {
+        status.total_supply = total_supply.to_string();
+    }
+    
+    if old_status.price_native > 0.0 && old_status.price_usd > 0.0 {
+        let native_usd = old_status.price_usd / old_status.price_native;
+        status.price_usd = status.price_native * native_usd;
+        status.liquidity = 2.0 * native_amount * native_usd;
+        if let Some(total_supply) = total_supply {
+            status.market_cap = units_to_f64(total_supply, old_status.decimals) * status.price_usd;
+        }
+    }
+    
+    status.last_updated = utils::safe_now();
+    Some(status)
 }
 
 impl TokenStatusTracker {
@@ -330,6 +404,30 @@ impl TokenStatusTracker {
         
         let tokens_to_update: Vec<String> = self.tracked_tokens.read().map_err(|e| anyhow!("RwLock error: {}", e))?.keys().cloned().collect();
         
+        // Làm mới reserves/totalSupply của mọi token đã biết pair bằng Multicall3 (một eth_call)
+        let refreshed = self.refresh_tokens_with_multicall(&tokens_to_update).await;
+        for (token_address, new_status) in &refreshed {
+            let old_status = self.tracked_tokens.read().map_err(|e| anyhow!("RwLock error: {}", e))?.get(token_address).map(|entry| entry.value.clone());
+            let Some(old_status) = old_status else {
+                continue;
+            };
+            
+            self.tracked_tokens.write().map_err(|e| anyhow!("RwLock error: {}", e))?.insert(token_address.clone(), CacheEntry::new(new_status.clone(), 300));
+            
+            if let Some(alert) = self.check_price_alert(token_address, &old_status, new_status) {
+                alerts.push(alert.clone());
+                
+                for callback in &self.alert_callbacks {
+                    callback(alert.clone());
+                }
+            }
+        }
+        
+        // Các token còn lại (chưa có pair hoặc multicall lỗi) cập nhật riêng lẻ
+        let tokens_to_update: Vec<String> = tokens_to_update.into_iter()
+            .filter(|token_address| !refreshed.contains_key(token_address))
+            .collect();
+        
         // Sử dụng tokio::task::JoinSet để xử lý song song
         let mut join_set = tokio::task::JoinSet::new();
         
@@ -340,7 +438,7 @@ impl TokenStatusTracker {
         for token_address in tokens_to_update {
             let adapter = self.adapter.clone();
             let semaphore_clone = semaphore.clone();
-            let old_status = self.tracked_tokens.read().map_err(|e| anyhow!("RwLock error: {}", e))?.get(&token_address).cloned();
+            let old_status = self.tracked_tokens.read().map_err(|e| anyhow!("RwLock error: {}", e))?.get(&token_address).map(|entry| entry.value.clone());
             
             // Chỉ xử lý nếu token đã được theo dõi
             if let Some(old_status) = old_status {
@@ -350,7 +448,7 @@ impl TokenStatusTracker {
                         Ok(permit) => permit,
                         Err(e) => {
                             warn!("Không thể lấy semaphore permit: {}", e);
-                            return (token_address, old_status, Err(format!("Lỗi semaphore: {}", e).into()));
+                            return (token_address, old_status, Err(anyhow!("Lỗi semaphore: {}", e)));
                         }
                     };
                     
@@ -370,7 +468,7 @@ impl TokenStatusTracker {
                         },
                         Err(_) => {
                             warn!("Timeout khi cập nhật token {}", token_address);
-                            (token_address, old_status, Err("Timeout khi cập nhật token".into()))
+                            (token_address, old_status, Err(anyhow!("Timeout khi cập nhật token")))
                         }
                     }
                 });
@@ -389,7 +487,7 @@ impl TokenStatusTracker {
             match result {
                 Ok((token_address, old_status, Ok(new_status))) => {
                     // Cập nhật trạng thái token
-                    self.tracked_tokens.write().map_err(|e| anyhow!("RwLock error: {}", e))?.insert(token_address, CacheEntry::new(new_status, 300));
+                    self.tracked_tokens.write().map_err(|e| anyhow!("RwLock error: {}", e))?.insert(token_address.clone(), CacheEntry::new(new_status.clone(), 300));
                     
                     // Kiểm tra biến động giá và tạo cảnh báo nếu cần
                     if let Some(alert) = self.check_price_alert(&token_address, &old_status, &new_status) {
@@ -430,6 +528,64 @@ impl TokenStatusTracker {
         Ok(alerts)
     }
     
+    // Làm mới trạng thái các token có pair qua một lần gọi Multicall3
+    async fn refresh_tokens_with_multicall(&self, token_addresses: &[String]) -> HashMap<String, TokenStatus> {
+        let mut targets = Vec::new();
+        {
+            let tokens = match self.tracked_tokens.read() {
+                Ok(tokens) => tokens,
+                Err(e) => {
+                    warn!("RwLock error: {}", e);
+                    return HashMap::new();
+                }
+            };
+            for token_address in token_addresses {
+                let Some(entry) = tokens.get(token_address) else { continue };
+                let pair = entry.value.pair_address.as_deref().and_then(|pair| Address::from_str(pair).ok());
+                if let (Ok(token), Some(pair)) = (Address::from_str(token_address), pair) {
+                    targets.push((token_address.clone(), token, pair, entry.value.clone()));
+                }
+            }
+        }
+        
+        if targets.is_empty() {
+            return HashMap::new();
+        }
+        
+        // Mỗi token: getReserves(pair), token0(pair), totalSupply(token)
+        let calls: Vec<Call3> = targets.iter()
+            .flat_map(|(_, token, pair, _)| [
+                multicall::get_reserves_call(*pair),
+                multicall::token0_call(*pair),
+                multicall::total_supply_call(*token),
+            ])
+            .collect();
+        
+        let results = match multicall::aggregate3(self.adapter.get_provider(), &calls, None).await {
+            Ok(results) => results,
+            Err(e) => {
+                warn!("Multicall3 không khả dụng, cập nhật {} token riêng lẻ: {}", targets.len(), e);
+                return HashMap::new();
+            }
+        };
+        
+        let mut refreshed = HashMap::new();
+        for ((token_address, token, _, old_status), chunk) in targets.into_iter().zip(results.chunks(3)) {
+            let reserves = chunk[0].as_ref().and_then(|data| multicall::decode_reserves(data));
+            let token0 = chunk[1].as_ref().and_then(|data| multicall::decode_address(data));
+            let total_supply = chunk[2].as_ref().and_then(|data| multicall::decode_uint(data));
+            
+            if let (Some(reserves), Some(token0)) = (reserves, token0) {
+                if let Some(status) = status_from_reserves(&old_status, token, self.weth_address, token0, reserves, total_supply) {
+                    refreshed.insert(token_address, status);
+                }
+            }
+        }
+        
+        debug!("Đã làm mới {} token bằng Multicall3", refreshed.len());
+        refreshed
+    }
+    
     // Phương thức mới để lấy token status với adapter
     async fn get_token_status_with_adapter(adapter: &ChainAdapterEnum, token_address: &str) -> Result<TokenStatus> {
         let token_addr = Address::from_str(token_address)?;
@@ -439,10 +595,10 @@ impl TokenStatusTracker {
         let token_abi = abi_utils::get_erc20_abi();
         let token_abi: ethers::abi::Abi = serde_json::from_str(token_abi)?;
         
-        let token_contract = Contract::new(
+        let _token_contract = Contract::new(
             token_addr,
             token_abi.clone(),
-            provider.clone(),
+            Arc::new(provider.clone()),
         );
         
         // Tiếp tục lấy thông tin token như cũ...
@@ -496,7 +652,7 @@ impl TokenStatusTracker {
             let factory_contract = Contract::new(
                 *factory_addr,
                 factory_abi,
-                Arc::clone(&self.adapter.get_provider())
+                Arc::new(self.adapter.get_provider().clone())
             );
             
             // Lấy pair address
@@ -522,7 +678,7 @@ impl TokenStatusTracker {
     }
     
     // Lấy giá và liquidity của token
-    async fn get_token_price_and_liquidity(&self, token_address: &str, pair_address: Option<&str>) -> Result<(f64, f64, f64)> {
+    async fn get_token_price_and_liquidity(adapter: &ChainAdapterEnum, token_address: &str, pair_address: Option<&str>) -> Result<(f64, f64, f64)> {
         let pair_addr = match pair_address {
             Some(addr) => Address::from_str(addr)?,
             None => {
@@ -532,13 +688,12 @@ impl TokenStatusTracker {
         };
         
         let token_addr = Address::from_str(token_address)?;
+        let provider = Arc::new(adapter.get_provider().clone());
+        let pair_abi: ethers::abi::Abi = serde_json::from_str(abi_utils::get_pair_abi())?;
+        let token_abi: ethers::abi::Abi = serde_json::from_str(abi_utils::get_erc20_abi())?;
         
         // Lấy reserves từ pair contract
-        let pair_contract = Contract::new(
-            pair_addr,
-            self.pair_abi.clone(),
-            Arc::clone(&self.adapter.get_provider())
-        );
+        let pair_contract = Contract::new(pair_addr, pair_abi, provider.clone());
         
         // Xác định token0 và token1
         let token0: Address = pair_contract.method("token0", ())?.call().await?;
@@ -557,37 +712,26 @@ impl TokenStatusTracker {
         };
         
         // Lấy số decimals của token
-        let token_contract = Contract::new(
-            token_addr,
-            self.token_abi.clone(),
-            Arc::clone(&self.adapter.get_provider())
-        );
-        
+        let token_contract = Contract::new(token_addr, token_abi, provider);
         let decimals: u8 = token_contract.method("decimals", ())?.call().await?;
         
         // Tính giá 1 token = bao nhiêu ETH
-        let token_decimals_factor = 10u128.pow(decimals as u32);
-        let eth_decimals_factor = 10u128.pow(18); // ETH có 18 decimals
-        
-        let price_in_eth = if !token_reserve.is_zero() {
-            (eth_reserve.as_u128() as f64 * token_decimals_factor as f64) / 
-            (token_reserve.as_u128() as f64 * eth_decimals_factor as f64)
-        } else {
-            0.0
-        };
+        let token_amount = units_to_f64(token_reserve, decimals);
+        let eth_amount = units_to_f64(eth_reserve, 18);
+        let price_in_eth = if token_amount > 0.0 { eth_amount / token_amount } else { 0.0 };
         
         // Giả sử ETH = $2000 USD
         let eth_price_usd = 2000.0;
         let price_usd = price_in_eth * eth_price_usd;
         
         // Tính liquidity (giá trị của ETH trong pair)
-        let liquidity = (eth_reserve.as_u128() as f64 / 1e18) * eth_price_usd * 2.0; // *2 vì đây là liquidity ở cả hai bên
+        let liquidity = eth_amount * eth_price_usd * 2.0; // *2 vì đây là liquidity ở cả hai bên
         
         Ok((price_usd, price_in_eth, liquidity))
     }
     
     // Lấy top holders
-    async fn get_top_holders(&self, token_address: &str) -> Result<Vec<HolderInfo>> {
+    async fn get_top_holders(&self, _token_address: &str) -> Result<Vec<HolderInfo>> {
         // Trong thực tế, cần API bên ngoài để lấy dữ liệu này
         // Đây là phiên bản giả lập
         let mut holders = Vec::new();
@@ -603,6 +747,10 @@ impl TokenStatusTracker {
                 is_contract: i == 0, // Giả sử holder đầu tiên là contract
                 balance,
                 percent,
+                holder_count: None,
+                token_distribution: None,
+                top_holders: None,
+                concentration_score: None,
             });
         }
         
@@ -610,7 +758,7 @@ impl TokenStatusTracker {
     }
     
     // Ước tính số lượng holders
-    async fn estimate_holders_count(&self, token_address: &str) -> Result<u64> {
+    async fn estimate_holders_count(&self, _token_address: &str) -> Result<u64> {
         // Trong thực tế, cần API bên ngoài
         // Giả lập kết quả
         Ok(1000)
@@ -674,7 +822,7 @@ impl TokenStatusTracker {
     
     // Tích hợp với mempool để cập nhật pending_tx_count
     pub async fn update_pending_tx_count(&mut self, token_address: &str, count: u32) -> Result<()> {
-        if let Some(status) = self.tracked_tokens.read().map_err(|e| anyhow!("RwLock error: {}", e))?.get_mut(token_address) {
+        if let Some(status) = self.tracked_tokens.write().map_err(|e| anyhow!("RwLock error: {}", e))?.get_mut(token_address) {
             status.value.pending_tx_count = count;
             Ok(())
         } else {
@@ -684,67 +832,30 @@ impl TokenStatusTracker {
 
     pub async fn update_token_status(&mut self, token_address: &str) -> Result<TokenStatus> {
         // Lấy thông tin cơ bản
-        let token_info = self.adapter.get_token_info(token_address).await?;
-        
-        // Phân tích rủi ro qua RiskAnalyzer
-        let risk_analysis = match &self.risk_analyzer {
-            Some(analyzer) => match analyzer.analyze_token(token_address).await {
-                Ok(analysis) => Some(analysis),
-                Err(e) => {
-                    warn!("Không thể phân tích rủi ro cho token {}: {}", token_address, e);
-                    None
-                }
-            },
-            None => None,
-        };
-        
-        // Cập nhật trạng thái token
-        let mut token_status = TokenStatus {
-            address: token_address.to_string(),
-            symbol: token_info.symbol.clone(),
-            name: token_info.name.clone(),
-            decimals: token_info.decimals,
-            current_price: None,
-            price_change_24h: None,
-            volume_24h: None,
-            market_cap: None,
-            liquidity: None,
-            holders_count: None,
-            is_honeypot: risk_analysis.as_ref().map(|a| a.is_honeypot()).unwrap_or(false),
-            buy_tax: risk_analysis.as_ref().and_then(|a| a.tax_info.as_ref().map(|t| t.buy_tax)),
-            sell_tax: risk_analysis.as_ref().and_then(|a| a.tax_info.as_ref().map(|t| t.sell_tax)),
-            risk_score: risk_analysis.as_ref().map(|a| a.risk_score),
-            last_updated: utils::safe_now(),
-            safety_level: self.calculate_safety_level(risk_analysis.as_ref()),
-        };
+        let mut token_status = self.get_token_status(token_address).await?;
         
         // Cập nhật thông tin thị trường
-        self.update_token_market_info(&mut token_status).await?;
+        let (pair_address, router_address) = self.find_pair_and_router(token_address).await?;
+        let (price_usd, price_native, liquidity) =
+            Self::get_token_price_and_liquidity(&self.adapter, token_address, pair_address.as_deref()).await?;
+        token_status.pair_address = pair_address;
+        if let Some(router_address) = router_address {
+            token_status.router_address = router_address;
+        }
+        token_status.price_usd = price_usd;
+        token_status.price_native = price_native;
+        token_status.liquidity = liquidity;
+        token_status.holders = self.get_top_holders(token_address).await?;
+        token_status.holders_count = self.estimate_holders_count(token_address).await?;
+        token_status.safety_level = self.classify_token(&token_status, None);
+        token_status.last_updated = utils::safe_now();
         
         // Lưu vào cache
         self.tracked_tokens.write().map_err(|e| anyhow!("RwLock error: {}", e))?.insert(token_address.to_string(), CacheEntry::new(token_status.clone(), 300));
         
-        // Gửi đến AIModule nếu đạt ngưỡng an toàn
-        if let Some(safety_threshold) = self.config.ai_safety_threshold {
-            if token_status.safety_level >= safety_threshold {
-                self.send_to_ai_module(token_address, &token_status, risk_analysis.as_ref()).await?;
-            }
-        }
-        
         Ok(token_status)
     }
 
-    async fn send_to_ai_module(&self, token_address: &str, status: &TokenStatus, risk_analysis: Option<&TokenRiskAnalysis>) -> Result<()> {
-        if let Some(ai_module) = &self.ai_module {
-            let mut ai_module = ai_module.lock().await;
-            ai_module.analyze_new_token(token_address, status, risk_analysis).await?;
-            
-            info!("Đã gửi thông tin token {} đến AI Module để phân tích.", token_address);
-        }
-        
-        Ok(())
-    }
-
     pub async fn update_token_prices(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         // Tạo danh sách token cần cập nhật - cần sửa để lấy từ LruCache
         let token_addresses: Vec<String> = self.tracked_tokens.read().map_err(|e| anyhow!("RwLock error: {}", e))?.keys().cloned().collect();
@@ -782,7 +893,7 @@ impl TokenStatusTracker {
                 // Cố gắng lấy giá cho token
                 match Self::get_token_price_and_liquidity(&adapter, &address, None).await {
                     Ok((price_usd, price_native, liquidity)) => {
-                        Some((address, TokenStatus {
+                        Some((address.clone(), TokenStatus {
                             address: address.clone(),
                             symbol: "TEMP".to_string(),
                             name: "Temporary Token".to_string(),
@@ -831,7 +942,7 @@ impl TokenStatusTracker {
             match result {
                 Ok(Some((address, status))) => {
                     // Cập nhật giá và thời gian cập nhật
-                    if let Some(token) = self.tracked_tokens.read().map_err(|e| anyhow!("RwLock error: {}", e))?.get_mut(&address) {
+                    if let Some(token) = self.tracked_tokens.write().map_err(|e| anyhow!("RwLock error: {}", e))?.get_mut(&address) {
                         *token = CacheEntry::new(status, 300);
                         token.value.last_updated = utils::safe_now();
                         update_count += 1;
@@ -882,7 +993,7 @@ impl TokenStatusTracker {
             tokio::time::Duration::from_secs(10),
             self.update_token_status(token_address)
         ).await {
-            Ok(result) => result,
+            Ok(result) => result.map(|_| ()).map_err(Into::into),
             Err(_) => {
                 warn!("Timeout khi cập nhật token status {}", token_address);
                 Err("Timeout khi cập nhật token status".into())
@@ -892,74 +1003,89 @@ impl TokenStatusTracker {
     
     /// Phương thức dọn dẹp các token cũ không còn được sử dụng
     pub fn cleanup_old_tokens(&mut self, current_time: u64, max_age_seconds: u64) -> usize {
-        let tokens_to_remove: Vec<String> = self.tracked_tokens.read().map_err(|e| anyhow!("RwLock error: {}", e))?
-            .iter()
-            .filter_map(|(token_address, status)| {
-                if let Some(last_updated) = status.value.last_updated {
-                    if current_time > last_updated && current_time - last_updated > max_age_seconds {
-                        return Some(token_address.clone());
-                    }
-                }
-                None
-            })
-            .collect();
-        
-        let count = tokens_to_remove.len();
-        for token in tokens_to_remove {
-            self.tracked_tokens.write().map_err(|e| anyhow!("RwLock error: {}", e))?.remove(token);
-        }
+        let mut tokens = match self.tracked_tokens.write() {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                warn!("RwLock error: {}", e);
+                return 0;
+            }
+        };
         
-        count
+        let before = tokens.len();
+        tokens.retain(|_, status| current_time.saturating_sub(status.value.last_updated) <= max_age_seconds);
+        before - tokens.len()
     }
     
     /// Giảm kích thước cache bằng cách xóa các token ít sử dụng nhất
     pub fn reduce_cache_size(&mut self, target_size: usize) -> usize {
-        let current_size = self.tracked_tokens.read().map_err(|e| anyhow!("RwLock error: {}", e))?.len();
+        let mut tokens = match self.tracked_tokens.write() {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                warn!("RwLock error: {}", e);
+                return 0;
+            }
+        };
+        
+        let current_size = tokens.len();
         if current_size <= target_size {
             return 0;
         }
         
         // Sắp xếp tokens theo thời gian cập nhật gần nhất
-        let mut tokens_with_time: Vec<(String, u64)> = self.tracked_tokens.read().map_err(|e| anyhow!("RwLock error: {}", e))?
+        let mut tokens_with_time: Vec<(String, u64)> = tokens
             .iter()
-            .map(|(addr, status)| {
-                (addr.clone(), status.value.last_updated.unwrap_or(0))
-            })
+            .map(|(addr, status)| (addr.clone(), status.value.last_updated))
             .collect();
         
         // Sắp xếp theo thời gian tăng dần (cũ nhất lên đầu)
         tokens_with_time.sort_by_key(|(_, time)| *time);
         
-        // Xác định số lượng token cần xóa
-        let to_remove = current_size - target_size;
-        
         // Xóa các token cũ nhất
-        for i in 0..to_remove {
-            if i < tokens_with_time.len() {
-                self.tracked_tokens.write().map_err(|e| anyhow!("RwLock error: {}", e))?.remove(tokens_with_time[i].0);
-            }
+        let to_remove = current_size - target_size;
+        for (addr, _) in tokens_with_time.into_iter().take(to_remove) {
+            tokens.remove(&addr);
         }
         
         to_remove
     }
+    
+    // Tạo cảnh báo nếu giá thay đổi vượt ngưỡng min_alert_percent
+    fn check_price_alert(&self, token_address: &str, old_status: &TokenStatus, new_status: &TokenStatus) -> Option<TokenPriceAlert> {
+        if old_status.price_usd <= 0.0 || new_status.price_usd <= 0.0 {
+            return None;
+        }
+        
+        let price_change_percent = (new_status.price_usd - old_status.price_usd) / old_status.price_usd * 100.0;
+        if price_change_percent.abs() < self.min_alert_percent {
+            return None;
+        }
+        
+        let alert_type = if price_change_percent > 0.0 {
+            PriceAlertType::PriceIncrease
+        } else {
+            PriceAlertType::PriceDecrease
+        };
+        
+        Some(TokenPriceAlert {
+            token_address: token_address.to_string(),
+            symbol: new_status.symbol.clone(),
+            price_change_percent,
+            old_price: old_status.price_usd,
+            new_price: new_status.price_usd,
+            timestamp: utils::safe_now(),
+            alert_type,
+        })
+    }
 }
 
 /// Thử lấy lock với timeout để tránh deadlock
-pub async fn try_lock_tracker(token_status_tracker: &Arc<Mutex<TokenStatusTracker>>) -> Result<MutexGuard<TokenStatusTracker>, Box<dyn std::error::Error>> {
+pub async fn try_lock_tracker(token_status_tracker: &Arc<Mutex<TokenStatusTracker>>) -> Result<MutexGuard<'_, TokenStatusTracker>, Box<dyn std::error::Error>> {
     // Thiết lập timeout 2 giây để tránh treo vô hạn
     match tokio::time::timeout(
         tokio::time::Duration::from_secs(2),
-        token_status_tracker.try_lock()
+        token_status_tracker.lock()
     ).await {
-        Ok(guard_result) => {
-            match guard_result {
-                Ok(guard) => Ok(guard),
-                Err(e) => {
-                    warn!("Không thể lấy lock TokenStatusTracker: {}", e);
-                    Err(format!("Lỗi khi lấy lock: {}", e).into())
-                }
-            }
-        },
+        Ok(guard) => Ok(guard),
         Err(_) => {
             warn!("Timeout khi chờ lấy lock TokenStatusTracker");
             Err("Timeout khi chờ lấy lock TokenStatusTracker".into())
@@ -969,18 +1095,11 @@ pub async fn try_lock_tracker(token_status_tracker: &Arc<Mutex<TokenStatusTracke
 
 /// Khôi phục trạng thái nếu phát hiện deadlock
 pub async fn recover_from_deadlock(token_status_tracker: &Arc<Mutex<TokenStatusTracker>>) -> Result<(), Box<dyn std::error::Error>> {
-    // Tạo tracker mới
-    let new_tracker = TokenStatusTracker {
-        tracked_tokens: Arc::new(RwLock::new(HashMap::new())),
-        price_alerts: HashMap::new(),
-        alert_callbacks: Vec::new(),
-        adapter: token_status_tracker.lock().await?.adapter.clone(),
-    };
-    
-    // Khởi tạo lại tracker
+    // Khởi tạo lại tracker: xóa danh sách token và callbacks
     match token_status_tracker.try_lock() {
         Ok(mut guard) => {
-            *guard = new_tracker;
+            guard.tracked_tokens = Arc::new(RwLock::new(HashMap::new()));
+            guard.alert_callbacks.clear();
             Ok(())
         },
         Err(_) => {
@@ -1,66 +1,35 @@
 // Diamondchain - Copyright (c) 2023
 
 // External imports
-use ethers::{
-    abi::{Abi, Function, Event},
-    contract::Contract,
-    middleware::Middleware,
-    types::{Address, H256, U256, Bytes, Filter, Log, AccessList, TransactionRequest, TransactionReceipt, BlockId},
-    utils::{hex, self},
-    providers::{Http, Provider, StreamExt, JsonRpcClient},
-    core::types::BlockNumber,
-    signers::{Signer, Signer as EthersSigner},
-};
+use ethers::types::{Address, H256, U256, TransactionRequest};
 
 // Standard library imports
 use std::{
-    collections::{HashMap, HashSet},
-    sync::{Arc, Mutex, RwLock},
-    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
-    fmt::{self, Display, Formatter},
-    error::Error,
+    collections::HashMap,
+    sync::{Arc, RwLock},
     future::Future,
-    pin::Pin,
     marker::Unpin,
-    str::FromStr,
 };
 
 // Third party imports
-use anyhow::{Result, Context, anyhow};
-use tracing::{info, warn, error, debug};
+use anyhow::anyhow;
+use tracing::error;
 use async_trait::async_trait;
-use tokio::time::{timeout, sleep};
-use tokio::sync::{Mutex as AsyncMutex, broadcast};
+use tokio::sync::Mutex as AsyncMutex;
 use serde::{Serialize, Deserialize};
-use uuid::Uuid;
-use serde_json::{self, Value};
-use prometheus::{IntCounter, Histogram, Gauge, opts};
+use prometheus::{IntCounter, Histogram, Gauge, histogram_opts};
+use lazy_static::lazy_static;
 
 // Internal imports
 use crate::{
-    chain_adapters::{ChainAdapter, AsyncChainAdapter, ChainError, GasInfo, TokenDetails, ChainAdapterEnum},
-    blockchain::{Blockchain, Transaction},
-    network::{Network, NetworkConfig},
-    storage::{Storage, StorageConfig},
-    risk_analyzer::{RiskAnalyzer, RiskConfig, TokenRiskAnalysis},
-    gas_optimizer::GasOptimizer,
-    token_status::{TokenStatusTracker, TokenStatus, TokenPriceAlert, PriceAlertType},
-    error::{TransactionError, classify_blockchain_error, get_recovery_info, RecoveryAction},
-    abi_utils,
-    types::{
-        TradeConfig, TradeStats, TradeResult as CoreTradeResult, TradeType, 
-        ProfitTarget, StopLossConfig, AIConfig, AISuggestion, DCAStrategy as DCAStrategyType,
-        DCAInterval, MonteCarloConfig, MonteCarloResult, SandwichParams,
-        OrderStatus, OrderType, OptimizedStrategy, FrontrunParams, StrategyType,
-    },
-    mempool::{MempoolTracker, MempoolTransaction, TransactionType, SandwichResult as CoreSandwichResult},
-    auto_tuning::AutoTuner,
-    trade::{self, token_status::{TokenStatusTracker as TradeTokenStatusTracker}},
+    risk_analyzer::RiskAnalyzer,
+    gas_optimizer::{GasOptimizer, OptimizedStrategy},
+    token_status::TokenStatusTracker,
+    types::{TradeStats, DCAInterval, MonteCarloConfig, MonteCarloResult, SandwichParams},
     flashbots::{FlashbotsProvider, FlashbotsConfig, FlashbotsBundleProvider},
 };
 
-use common::cache::CacheEntry;
-use crate::models::{Token, TokenPriceData, Wallet};
+pub use crate::types::TradeType;
 
 // Lazy static metrics
 lazy_static! {
@@ -80,7 +49,7 @@ lazy_static! {
     ).unwrap();
 
     static ref GAS_USED_HISTOGRAM: Histogram = Histogram::with_opts(
-        opts!(
+        histogram_opts!(
             "trade_gas_used",
             "Gas used per trade",
             vec![1000.0, 5000.0, 10000.0, 50000.0, 100000.0, 500000.0]
@@ -88,7 +57,7 @@ lazy_static! {
     ).unwrap();
 
     static ref TRADE_AMOUNT_HISTOGRAM: Histogram = Histogram::with_opts(
-        opts!(
+        histogram_opts!(
             "trade_amount",
             "Trade amount in ETH",
             vec![0.01, 0.1, 0.5, 1.0, 5.0, 10.0, 50.0]
@@ -366,7 +335,7 @@ pub struct TradeResult {
     pub victim_tx_hash: Option<String>,
     /// Lợi nhuận USD
     pub profit_usd: Option<f64>,
-    /// Chi phí gas USD
+    /// Chi phí gas USD (gồm cả phí dữ liệu L1 trên rollup)
     pub gas_cost_usd: Option<f64>,
 }
 
@@ -419,59 +388,41 @@ impl TradePerformanceStorage for DefaultTradePerformanceStorage {
 /// Trait for DCA Strategy
 #[async_trait]
 pub trait DCAStrategy: Send + Sync {
-    async fn schedule_dca_buy(&self, token_address: &str, amount: f64, interval: DCAInterval) -> Result<(), Box<dyn std::error::Error>>;
-    async fn cancel_dca_schedule(&self, token_address: &str) -> Result<(), Box<dyn std::error::Error>>;
-}
-
-/// Trait for handling FlashBots bundles
-#[async_trait]
-pub trait FlashbotsBundleProvider: Send + Sync {
-    async fn submit_flashbots_bundle(&self, transactions: Vec<TransactionRequest>) -> Result<H256, Box<dyn std::error::Error>>;
-    async fn check_bundle_status(&self, bundle_hash: &H256) -> Result<bool, Box<dyn std::error::Error>>;
+    async fn schedule_dca_buy(&self, token_address: &str, amount: f64, interval: DCAInterval) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    async fn cancel_dca_schedule(&self, token_address: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 }
 
 /// Trait for TWAP calculation
 #[async_trait]
 pub trait TWAPCalculator: Send + Sync {
-    async fn calculate_twap(&self, token_address: &str, window_size: usize) -> Result<f64, Box<dyn std::error::Error>>;
-    async fn add_price_sample(&self, token_address: &str, price: f64) -> Result<(), Box<dyn std::error::Error>>;
+    async fn calculate_twap(&self, token_address: &str, window_size: usize) -> Result<f64, Box<dyn std::error::Error + Send + Sync>>;
+    async fn add_price_sample(&self, token_address: &str, price: f64) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 }
 
 /// Trait for Monte Carlo simulation
 #[async_trait]
 pub trait MonteCarloSimulator: Send + Sync {
-    async fn simulate_trade_outcomes(&self, token_address: &str, config: &MonteCarloConfig) -> Result<MonteCarloResult, Box<dyn std::error::Error>>;
+    async fn simulate_trade_outcomes(&self, token_address: &str, config: &MonteCarloConfig) -> Result<MonteCarloResult, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Lỗi trả về cho các chức năng TradeManager chưa được hỗ trợ
+fn not_supported(feature: &str) -> Box<dyn std::error::Error + Send + Sync> {
+    anyhow!("{} chưa được hỗ trợ", feature).into()
 }
 
 /// Cấu trúc quản lý giao dịch
-#[derive(Debug)]
-pub struct TradeManager<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'static> {
+pub struct TradeManager<A: Send + Sync + 'static> {
     /// Chain adapter
     chain_adapter: Arc<A>,
     
-    /// Blockchain
-    blockchain: Arc<Blockchain>,
-    
-    /// Network
-    network: Arc<Network>,
-    
-    /// Storage
-    storage: Arc<Storage>,
-    
     /// Risk analyzer
-    risk_analyzer: Arc<dyn RiskAnalyzer + Send + Sync + 'static>,
-    
-    /// Mempool monitor
-    mempool: Arc<dyn MempoolTracker + Send + Sync + 'static>,
+    risk_analyzer: Option<Arc<dyn RiskAnalyzer + Send + Sync + 'static>>,
     
     /// Gas optimizer
-    gas_optimizer: Arc<dyn GasOptimizer + Send + Sync + 'static>,
+    gas_optimizer: Option<Arc<GasOptimizer>>,
     
     /// Token status tracker
-    token_status_tracker: Arc<dyn TradeTokenStatusTracker + Send + Sync + 'static>,
-    
-    /// Auto tuner
-    auto_tuner: Option<Arc<AsyncMutex<AutoTuner>>>,
+    token_status_tracker: Option<Arc<AsyncMutex<TokenStatusTracker>>>,
     
     /// Price monitor
     price_monitor: Option<Arc<dyn PriceMonitor + Send + Sync + 'static>>,
@@ -485,9 +436,6 @@ pub struct TradeManager<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'sta
     /// Statistics
     stats: RwLock<TradeStats>,
     
-    /// Active trades
-    active_trades: RwLock<HashMap<String, TradeTaskInfo>>,
-    
     /// Token positions
     positions: RwLock<HashMap<String, TokenPosition>>,
     
@@ -500,47 +448,28 @@ pub struct TradeManager<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'sta
     /// Trade manager config
     trade_manager_config: TradeManagerConfig,
     
-    /// Slippage percent
-    slippage_percent: f64,
-    
-    /// Gas price multiplier
-    gas_price_multiplier: f64,
-    
-    /// Router address
-    router_address: Option<Address>,
-    
     /// FlashBots provider
     flashbots_provider: Option<Arc<FlashbotsProvider>>,
 }
 
 // Các implementations cho TradeManager
-impl<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'static> TradeManager<A> {
+impl<A: Send + Sync + 'static> TradeManager<A> {
     /// Tạo TradeManager mới
     pub fn new(chain_adapter: Arc<A>, config: TradeConfig) -> Self {
         // Khởi tạo FlashbotsProvider nếu cần thiết
         let flashbots_provider = if config.use_flashbots {
-            // Tạo cấu hình Flashbots
-            let flashbots_config = FlashbotsConfig {
-                relay_endpoint: "https://relay.flashbots.net".to_string(),
-                flashbots_key: "".to_string(), // Cần cung cấp từ cấu hình
-                max_attempts: 3,
-                timeout_seconds: 1,
-            };
-            
-            // Tạo provider
-            match FlashbotsProvider::new(flashbots_config) {
+            match FlashbotsProvider::new(FlashbotsConfig::default()) {
                 Ok(provider) => Some(Arc::new(provider)),
                 Err(e) => {
                     error!("Không thể khởi tạo FlashbotsProvider: {}", e);
                     None
                 }
             }
-            } else {
+        } else {
             None
         };
         
-        // Implementation here
-        unimplemented!("TradeManager::new to be implemented")
+        Self::with_flashbots_provider(chain_adapter, config, flashbots_provider)
     }
 
     /// Tạo TradeManager với cấu hình Flashbots tùy chỉnh
@@ -553,8 +482,99 @@ impl<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'static> TradeManager<A
         let flashbots_provider = FlashbotsProvider::new(flashbots_config)
             .map_err(|e| anyhow!("Không thể khởi tạo FlashbotsProvider: {}", e))?;
             
-        // Implementation here
-        unimplemented!("TradeManager::new_with_flashbots to be implemented")
+        Ok(Self::with_flashbots_provider(chain_adapter, config, Some(Arc::new(flashbots_provider))))
+    }
+    
+    fn with_flashbots_provider(
+        chain_adapter: Arc<A>,
+        config: TradeConfig,
+        flashbots_provider: Option<Arc<FlashbotsProvider>>,
+    ) -> Self {
+        Self {
+            chain_adapter,
+            risk_analyzer: None,
+            gas_optimizer: None,
+            token_status_tracker: None,
+            price_monitor: None,
+            wallet_address: None,
+            stats: RwLock::new(TradeStats::default()),
+            positions: RwLock::new(HashMap::new()),
+            limit_orders: RwLock::new(HashMap::new()),
+            auto_sandwich_configs: RwLock::new(HashMap::new()),
+            trade_manager_config: TradeManagerConfig {
+                default_slippage: config.slippage,
+                min_sandwich_victim_usd: 1000.0,
+                max_pending_orders: 100,
+                enable_auto_tuning: false,
+            },
+            flashbots_provider,
+            config,
+        }
+    }
+    
+    /// Chain adapter mà TradeManager sử dụng
+    pub fn chain_adapter(&self) -> &Arc<A> {
+        &self.chain_adapter
+    }
+    
+    /// Cấu hình giao dịch
+    pub fn config(&self) -> &TradeConfig {
+        &self.config
+    }
+    
+    /// Risk analyzer đang dùng (nếu có)
+    pub fn risk_analyzer(&self) -> Option<&Arc<dyn RiskAnalyzer + Send + Sync + 'static>> {
+        self.risk_analyzer.as_ref()
+    }
+    
+    /// Gas optimizer đang dùng (nếu có)
+    pub fn gas_optimizer(&self) -> Option<&Arc<GasOptimizer>> {
+        self.gas_optimizer.as_ref()
+    }
+    
+    /// Price monitor đang dùng (nếu có)
+    pub fn price_monitor(&self) -> Option<&Arc<dyn PriceMonitor + Send + Sync + 'static>> {
+        self.price_monitor.as_ref()
+    }
+    
+    /// Địa chỉ ví giao dịch (nếu đã thiết lập)
+    pub fn wallet_address(&self) -> Option<Address> {
+        self.wallet_address
+    }
+    
+    /// Thiết lập risk analyzer
+    pub fn set_risk_analyzer(&mut self, risk_analyzer: Option<Arc<dyn RiskAnalyzer + Send + Sync + 'static>>) {
+        self.risk_analyzer = risk_analyzer;
+    }
+    
+    /// Thiết lập gas optimizer
+    pub fn set_gas_optimizer(&mut self, gas_optimizer: Option<Arc<GasOptimizer>>) {
+        self.gas_optimizer = gas_optimizer;
+    }
+    
+    /// Thiết lập token status tracker
+    pub fn set_token_status_tracker(&mut self, tracker: Option<Arc<AsyncMutex<TokenStatusTracker>>>) {
+        self.token_status_tracker = tracker;
+    }
+    
+    /// Thiết lập price monitor
+    pub fn set_price_monitor(&mut self, price_monitor: Arc<dyn PriceMonitor + Send + Sync + 'static>) {
+        self.price_monitor = Some(price_monitor);
+    }
+    
+    /// Thiết lập địa chỉ ví giao dịch
+    pub fn set_wallet_address(&mut self, wallet_address: Address) {
+        self.wallet_address = Some(wallet_address);
+    }
+    
+    /// Thống kê giao dịch hiện tại
+    pub fn stats(&self) -> TradeStats {
+        self.stats.read().map(|stats| stats.clone()).unwrap_or_default()
+    }
+    
+    /// Danh sách token đang có vị thế
+    pub fn position_tokens(&self) -> Vec<String> {
+        self.positions.read().map(|positions| positions.keys().cloned().collect()).unwrap_or_default()
     }
     
     /// Thực hiện gửi bundle qua Flashbots
@@ -563,7 +583,7 @@ impl<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'static> TradeManager<A
     {
         // Kiểm tra xem có Flashbots provider không
         let provider = self.flashbots_provider.as_ref()
-            .ok_or_else(|| Box::new(anyhow!("Flashbots không được bật")) as Box<dyn std::error::Error + Send + Sync>)?;
+            .ok_or_else(|| not_supported("Flashbots"))?;
             
         // Gửi bundle qua FlashbotsBundleProvider trait
         provider.submit_flashbots_bundle(transactions).await
@@ -575,151 +595,125 @@ impl<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'static> TradeManager<A
     {
         // Kiểm tra xem có Flashbots provider không
         let provider = self.flashbots_provider.as_ref()
-            .ok_or_else(|| Box::new(anyhow!("Flashbots không được bật")) as Box<dyn std::error::Error + Send + Sync>)?;
+            .ok_or_else(|| not_supported("Flashbots"))?;
             
         // Kiểm tra trạng thái bundle
         provider.check_bundle_status(bundle_hash).await
     }
 
     /// Mua token với tham số tối ưu
-    pub async fn buy_token_with_optimized_params(&self, token_address: &str, amount: &str, gas_price: Option<u64>) -> Result<TradeResult, Box<dyn std::error::Error + Send + Sync>> {
-        // Implementation here
-        unimplemented!("buy_token_with_optimized_params to be implemented")
+    pub async fn buy_token_with_optimized_params(&self, _token_address: &str, _amount: &str, _gas_price: Option<u64>) -> Result<TradeResult, Box<dyn std::error::Error + Send + Sync>> {
+        Err(not_supported("buy_token_with_optimized_params"))
     }
 
     /// Thực hiện chiến lược tối ưu
-    pub async fn execute_optimized_strategy(&self, strategy: &OptimizedStrategy) -> Result<TradeResult, Box<dyn std::error::Error + Send + Sync>> {
-        // Implementation here
-        unimplemented!("execute_optimized_strategy to be implemented")
+    pub async fn execute_optimized_strategy(&self, _strategy: &OptimizedStrategy) -> Result<TradeResult, Box<dyn std::error::Error + Send + Sync>> {
+        Err(not_supported("execute_optimized_strategy"))
     }
 
     /// Tự động theo dõi token vị thế
     pub async fn auto_track_position_tokens(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Implementation here
-        unimplemented!("auto_track_position_tokens to be implemented")
+        let Some(tracker) = &self.token_status_tracker else {
+            return Ok(());
+        };
+        
+        for token_address in self.position_tokens() {
+            tracker.lock().await.add_token(&token_address).await?;
+        }
+        Ok(())
     }
 
     /// Thực hiện sandwich attack
-    pub async fn execute_sandwich_attack(&self, params: &SandwichParams) -> Result<SandwichResult, Box<dyn std::error::Error + Send + Sync>> {
-        // Implementation here
-        unimplemented!("execute_sandwich_attack to be implemented")
-    }
-
-    /// Lưu thông tin hiệu suất giao dịch
-    async fn store_trade_performance(&self, sandwich_result: &SandwichResult) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Implementation here
-        unimplemented!("store_trade_performance to be implemented")
-    }
-
-    /// Báo cáo cho auto tuner
-    async fn report_to_auto_tuner(&self, sandwich_result: &SandwichResult) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Implementation here
-        unimplemented!("report_to_auto_tuner to be implemented")
+    pub async fn execute_sandwich_attack(&self, _params: &SandwichParams) -> Result<SandwichResult, Box<dyn std::error::Error + Send + Sync>> {
+        Err(not_supported("execute_sandwich_attack"))
     }
 
     /// Tạo lệnh giới hạn
     pub async fn create_limit_order(&mut self, token_address: &str, order_type: OrderType, price_target: f64, percent: u8, time_limit_seconds: u64) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Implementation here
-        unimplemented!("create_limit_order to be implemented")
+        let mut orders = self.limit_orders.write().map_err(|e| anyhow!("RwLock error: {}", e))?;
+        if orders.len() >= self.trade_manager_config.max_pending_orders {
+            return Err(anyhow!("Đã đạt số lệnh chờ tối đa ({})", self.trade_manager_config.max_pending_orders).into());
+        }
+        
+        let now = crate::utils::safe_now();
+        let id = uuid::Uuid::new_v4().to_string();
+        orders.insert(id.clone(), LimitOrder {
+            id: id.clone(),
+            token_address: token_address.to_string(),
+            order_type,
+            price_target,
+            percent: percent.min(100),
+            created_at: now,
+            expires_at: now + time_limit_seconds,
+            status: OrderStatus::Active,
+        });
+        Ok(id)
     }
 
     /// Kích hoạt auto sandwich
     pub async fn enable_auto_sandwich(&mut self, token_address: &str, max_buys: u32, time_limit_seconds: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Implementation here
-        unimplemented!("enable_auto_sandwich to be implemented")
-    }
-    
-    /// Ước tính gas cho swap
-    async fn estimate_gas_for_swap(&self, token_address: &str, amount: U256) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
-        // Implementation here
-        unimplemented!("estimate_gas_for_swap to be implemented")
+        let now = crate::utils::safe_now();
+        let config = AutoSandwichConfig {
+            token_address: token_address.to_string(),
+            max_additional_buys: max_buys,
+            buys_completed: 0,
+            start_time: now,
+            end_time: now + time_limit_seconds,
+            is_active: true,
+            min_victim_size_usd: self.trade_manager_config.min_sandwich_victim_usd,
+            use_flashbots: self.config.use_flashbots,
+        };
+        self.auto_sandwich_configs.write()
+            .map_err(|e| anyhow!("RwLock error: {}", e))?
+            .insert(token_address.to_string(), config);
+        Ok(())
     }
 }
 
 // Implement FlashbotsBundleProvider qua forwarding đến flashbots_provider
 #[async_trait]
-impl<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'static> FlashbotsBundleProvider for TradeManager<A> {
+impl<A: Send + Sync + 'static> FlashbotsBundleProvider for TradeManager<A> {
     async fn submit_flashbots_bundle(&self, transactions: Vec<TransactionRequest>) -> Result<H256, Box<dyn std::error::Error + Send + Sync>> {
-        self.submit_flashbots_bundle(transactions).await
+        TradeManager::submit_flashbots_bundle(self, transactions).await
     }
 
     async fn check_bundle_status(&self, bundle_hash: &H256) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        self.check_bundle_status(bundle_hash).await
-    }
-}
-
-impl<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'static> TWAPCalculator for TradeManager<A> {
-    async fn calculate_twap(&self, token_address: &str, window_size: usize) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-        // Implementation here
-        unimplemented!("calculate_twap to be implemented")
-    }
-
-    async fn add_price_sample(&self, token_address: &str, price: f64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Implementation here
-        unimplemented!("add_price_sample to be implemented")
-    }
-}
-
-impl<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'static> MonteCarloSimulator for TradeManager<A> {
-    async fn simulate_trade_outcomes(&self, token_address: &str, config: &MonteCarloConfig) -> Result<MonteCarloResult, Box<dyn std::error::Error>> {
-        // Implementation here
-        unimplemented!("simulate_trade_outcomes to be implemented")
+        TradeManager::check_bundle_status(self, bundle_hash).await
     }
 }
 
-impl<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'static> DCAStrategy for TradeManager<A> {
-    async fn schedule_dca_buy(&self, token_address: &str, amount: f64, interval: DCAInterval) -> Result<(), Box<dyn std::error::Error>> {
-        // Implementation here
-        unimplemented!("schedule_dca_buy to be implemented")
-    }
-
-    async fn cancel_dca_schedule(&self, token_address: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Implementation here
-        unimplemented!("cancel_dca_schedule to be implemented")
+#[async_trait]
+impl<A: Send + Sync + 'static> TWAPCalculator for TradeManager<A> {
+    async fn calculate_twap(&self, _token_address: &str, _window_size: usize) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        Err(not_supported("calculate_twap"))
     }
-}
 
-    impl<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'static> MempoolTracker for TradeManager<A> {
-    // Implement MempoolTracker trait methods
-    // ...
-    fn track_mempool(&self) -> Result<(), Box<dyn std::error::Error>> {
-        // Implementation here
-        unimplemented!("track_mempool to be implemented")
+    async fn add_price_sample(&self, _token_address: &str, _price: f64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Err(not_supported("add_price_sample"))
     }
 }
 
-    impl<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'static> RiskAnalyzer for TradeManager<A> {
-    // Implement RiskAnalyzer trait methods
-    // ...
-    fn analyze_token_risk(&self, token_address: &str) -> Result<TokenRiskAnalysis, Box<dyn std::error::Error>> {
-        // Implementation here
-        unimplemented!("analyze_token_risk to be implemented")
+#[async_trait]
+impl<A: Send + Sync + 'static> MonteCarloSimulator for TradeManager<A> {
+    async fn simulate_trade_outcomes(&self, _token_address: &str, _config: &MonteCarloConfig) -> Result<MonteCarloResult, Box<dyn std::error::Error + Send + Sync>> {
+        Err(not_supported("simulate_trade_outcomes"))
     }
 }
 
-impl<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'static> GasOptimizer for TradeManager<A> {
-    // Implement GasOptimizer trait methods
-    // ...
-    fn optimize_gas(&self, base_gas: u64) -> Result<u64, Box<dyn std::error::Error>> {
-        // Implementation here
-        unimplemented!("optimize_gas to be implemented")
+#[async_trait]
+impl<A: Send + Sync + 'static> DCAStrategy for TradeManager<A> {
+    async fn schedule_dca_buy(&self, _token_address: &str, _amount: f64, _interval: DCAInterval) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Err(not_supported("schedule_dca_buy"))
     }
-}
 
-impl<A: ChainAdapter + AsyncChainAdapter + Send + Sync + 'static> TradeTokenStatusTracker for TradeManager<A> {
-    // Implement TokenStatusTracker trait methods
-    // ...
-    fn track_token(&self, token_address: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Implementation here
-        unimplemented!("track_token to be implemented")
+    async fn cancel_dca_schedule(&self, _token_address: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Err(not_supported("cancel_dca_schedule"))
     }
 }
 
 // Module tests
 #[cfg(test)]
 mod tests {
-    use super::*;
-
     #[test]
     fn test_trade_manager_new() {
         // Tests here
@@ -2,7 +2,6 @@
 use ethers::types::{Address, U256};
 use serde::{Serialize, Deserialize};
 use chrono;
-use std::time::SystemTime;
 
 // Re-exports
 pub use crate::token_status::{TokenStatus, TokenSafetyLevel};
@@ -60,8 +59,8 @@ impl Subscription {
     }
 }
 
-/// Các cấp độ đăng ký
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Các cấp độ đăng ký (theo thứ tự tăng dần)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum SubscriptionLevel {
     /// Miễn phí
     Free,
@@ -73,6 +72,29 @@ pub enum SubscriptionLevel {
     Professional
 }
 
+/// Cấp độ subscription của người dùng (UserManager) sang cấp độ của bot; VIP ứng với Professional
+impl From<diamond_common::user::SubscriptionLevel> for SubscriptionLevel {
+    fn from(level: diamond_common::user::SubscriptionLevel) -> Self {
+        match level {
+            diamond_common::user::SubscriptionLevel::Free => Self::Free,
+            diamond_common::user::SubscriptionLevel::Basic => Self::Basic,
+            diamond_common::user::SubscriptionLevel::Premium => Self::Premium,
+            diamond_common::user::SubscriptionLevel::VIP => Self::Professional,
+        }
+    }
+}
+
+impl From<SubscriptionLevel> for diamond_common::user::SubscriptionLevel {
+    fn from(level: SubscriptionLevel) -> Self {
+        match level {
+            SubscriptionLevel::Free => Self::Free,
+            SubscriptionLevel::Basic => Self::Basic,
+            SubscriptionLevel::Premium => Self::Premium,
+            SubscriptionLevel::Professional => Self::VIP,
+        }
+    }
+}
+
 /// Thông tin token cơ bản
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenInfo {
@@ -151,4 +173,63 @@ pub struct PotentialVictim {
     pub amount_usd: f64,
     pub gas_price: U256,
     pub timestamp: u64,
-} 
\ No newline at end of file
+} 
+/// Loại giao dịch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TradeType {
+    /// Mua token
+    Buy,
+    /// Bán token
+    Sell,
+    /// Phê duyệt token cho router
+    Approve,
+    /// Giao dịch sandwich
+    Sandwich,
+    /// Giao dịch front-run
+    Frontrun,
+}
+
+/// Thống kê giao dịch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TradeStats {
+    pub total_trades: u64,
+    pub successful_trades: u64,
+    pub failed_trades: u64,
+    pub total_profit_usd: f64,
+    pub total_gas_cost_usd: f64,
+}
+
+/// Chu kỳ mua DCA
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DCAInterval {
+    Hourly,
+    Daily,
+    Weekly,
+}
+
+/// Cấu hình mô phỏng Monte Carlo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloConfig {
+    pub iterations: usize,
+    pub time_horizon_secs: u64,
+    pub volatility: f64,
+}
+
+/// Kết quả mô phỏng Monte Carlo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloResult {
+    pub expected_return: f64,
+    pub success_probability: f64,
+    pub worst_case: f64,
+    pub best_case: f64,
+}
+
+/// Tham số cho chiến lược sandwich
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandwichParams {
+    pub victim_tx_hash: String,
+    pub front_run_amount_percent: f64,
+    pub front_run_gas_multiplier: f64,
+    pub back_run_gas_multiplier: f64,
+    pub use_flashbots: bool,
+}
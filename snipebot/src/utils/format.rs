@@ -52,18 +52,17 @@ pub fn format_token_amount(amount: U256, decimals: u8, display_decimals: usize)
         let integer_part = &amount_str[0..(amount_len - decimals as usize)];
         let decimal_part = &amount_str[(amount_len - decimals as usize)..];
         
-        // Lấy số chữ số thập phân cần hiển thị
-        let formatted_decimal = if decimal_part.len() > display_decimals {
-            &decimal_part[0..display_decimals]
-        } else {
-            decimal_part
-        };
-        
-        if display_decimals > 0 && !formatted_decimal.is_empty() && formatted_decimal != "0".repeat(formatted_decimal.len()) {
-            format!("{}.{}", integer_part, formatted_decimal)
-        } else {
-            integer_part.to_string()
+        if display_decimals == 0 {
+            return integer_part.to_string();
         }
+        
+        // Lấy đúng `display_decimals` chữ số thập phân, thêm 0 nếu thiếu
+        let formatted_decimal: String = decimal_part.chars()
+            .chain(std::iter::repeat('0'))
+            .take(display_decimals)
+            .collect();
+        
+        format!("{}.{}", integer_part, formatted_decimal)
     }
 }
 
@@ -171,13 +170,13 @@ pub fn truncate_string(s: &str, max_len: usize) -> String {
 /// Định dạng số lượng lớn thành dạng K, M, B
 pub fn format_large_number(number: f64) -> String {
     if number < 1_000.0 {
-        return format!("{:.2}", number);
+        format!("{:.2}", number)
     } else if number < 1_000_000.0 {
-        return format!("{:.2}K", number / 1_000.0);
+        format!("{:.2}K", number / 1_000.0)
     } else if number < 1_000_000_000.0 {
-        return format!("{:.2}M", number / 1_000_000.0);
+        format!("{:.2}M", number / 1_000_000.0)
     } else {
-        return format!("{:.2}B", number / 1_000_000_000.0);
+        format!("{:.2}B", number / 1_000_000_000.0)
     }
 }
 
@@ -199,7 +198,7 @@ mod tests {
         let amount = U256::from_dec_str("1234567890123456789").unwrap();
         assert_eq!(format_token_amount(amount, 18, 4), "1.2345");
         
-        let amount = U256::from_dec_str("123456789").unwrap();
+        let amount = U256::from_dec_str("123456789000000").unwrap(); // 0.000123456789 ETH
         assert_eq!(format_token_amount(amount, 18, 6), "0.000123");
     }
     
@@ -216,11 +215,11 @@ mod tests {
     fn test_parse_address() {
         let address_str = "0x1234567890abcdef1234567890abcdef12345678";
         let address = parse_address(address_str).unwrap();
-        assert_eq!(address.to_string(), "0x1234567890abcdef1234567890abcdef12345678".to_lowercase());
+        assert_eq!(format!("{:?}", address), "0x1234567890abcdef1234567890abcdef12345678".to_lowercase());
         
         let address_str = "1234567890abcdef1234567890abcdef12345678";
         let address = parse_address(address_str).unwrap();
-        assert_eq!(address.to_string(), "0x1234567890abcdef1234567890abcdef12345678".to_lowercase());
+        assert_eq!(format!("{:?}", address), "0x1234567890abcdef1234567890abcdef12345678".to_lowercase());
         
         let address_str = "invalid";
         assert_eq!(parse_address(address_str), None);
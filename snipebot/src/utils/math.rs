@@ -1,7 +1,6 @@
 use ethers::types::U256;
-use num_traits::cast::ToPrimitive;
 use log::warn;
-use std::cmp::{max, min};
+use std::cmp::min;
 
 /// Tính trung bình của dãy số
 pub fn average(values: &[f64]) -> f64 {
@@ -23,7 +22,7 @@ pub fn median(values: &[f64]) -> f64 {
     sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
     
     let mid = sorted.len() / 2;
-    if sorted.len() % 2 == 0 {
+    if sorted.len().is_multiple_of(2) {
         (sorted[mid - 1] + sorted[mid]) / 2.0
     } else {
         sorted[mid]
@@ -87,6 +86,12 @@ pub fn safe_sub_u256(a: U256, b: U256) -> U256 {
     }
 }
 
+/// Tính số lượng đầu ra tối thiểu sau khi trừ slippage (phần trăm)
+pub fn calculate_min_amount_out(amount_out: U256, slippage_percent: f64) -> U256 {
+    let slippage_bps = (slippage_percent.clamp(0.0, 100.0) * 100.0) as u64;
+    amount_out * U256::from(10_000 - slippage_bps) / U256::from(10_000)
+}
+
 /// Tính giá trị trung bình của dãy U256
 pub fn average_u256(values: &[U256]) -> U256 {
     if values.is_empty() {
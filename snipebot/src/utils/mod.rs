@@ -1,6 +1,5 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 use ethers::types::U256;
-use num_traits::cast::ToPrimitive;
 use log::warn;
 
 pub mod format;
@@ -22,7 +21,7 @@ pub fn wei_to_eth(wei: U256) -> f64 {
         return 0.0;
     }
     
-    let wei_f64 = wei.to_f64().unwrap_or_else(|| {
+    let wei_f64 = wei.to_string().parse::<f64>().unwrap_or_else(|_| {
         warn!("Không thể chuyển đổi U256 thành f64: {:?}", wei);
         0.0
     });
@@ -30,6 +29,11 @@ pub fn wei_to_eth(wei: U256) -> f64 {
     wei_f64 / 1e18
 }
 
+/// Chuyển đổi Gwei (số thực) thành Wei, giá trị âm được coi là 0
+pub fn gwei_to_wei(gwei: f64) -> U256 {
+    U256::from((gwei.max(0.0) * 1e9) as u128)
+}
+
 /// Phân tích chuỗi số thành U256 với số lượng decimals phù hợp
 pub fn parse_decimal_u256(amount: String, decimals: u32) -> U256 {
     let parts: Vec<&str> = amount.split('.').collect();
@@ -89,7 +93,7 @@ pub fn is_valid_eth_address(address: &str) -> bool {
     }
     
     // Kiểm tra xem tất cả các ký tự còn lại có phải là hex không
-    address[2..].chars().all(|c| c.is_digit(16))
+    address[2..].chars().all(|c| c.is_ascii_hexdigit())
 }
 
 #[cfg(test)]
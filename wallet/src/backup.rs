@@ -0,0 +1,521 @@
+// External imports
+use ethers::{prelude::LocalWallet, signers::Signer, types::Address};
+
+// Standard library imports
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Third party imports
+use anyhow::{anyhow, bail, Context, Result};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, Zeroizing};
+
+// Internal imports
+use crate::envelope::{derive_master_key, open, seal, MasterKeyParams, SealedBox, DEFAULT_ENCRYPTION_SEEDS, MIN_PASSPHRASE_LEN};
+use crate::groups::WalletGroup;
+use crate::keystore::hex_bytes;
+use crate::policy::SpendingPolicy;
+use crate::secure_storage::WalletInfo;
+
+/// Định danh định dạng tệp sao lưu
+pub const BACKUP_FORMAT: &str = "diamond-wallet-backup";
+/// Phiên bản định dạng sao lưu hiện tại; bản sao lưu mới hơn bị từ chối khi khôi phục
+pub const BACKUP_VERSION: u16 = 1;
+
+/// Tiền tố chuỗi mảnh khóa sao lưu
+const SHARE_PREFIX: &str = "dwb-share-1";
+/// AAD bọc backup key bằng khóa dẫn xuất từ passphrase sao lưu
+const WRAP_AAD: &[u8] = b"backup-key";
+
+/// Phần đầu tệp sao lưu, để dạng rõ và được xác thực cùng payload (AAD)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupHeader {
+    pub format: String,
+    pub version: u16,
+    pub created_at: u64,
+    pub wallet_count: usize,
+    pub group_count: usize,
+    pub policy_count: usize,
+    /// Dấu vân tay của backup key, dùng để nhận ra mảnh khóa của đúng bản sao lưu
+    pub key_check: String,
+}
+
+/// Tệp sao lưu: payload mã hóa bằng backup key ngẫu nhiên, backup key được bọc bằng passphrase sao lưu
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupArchive {
+    header: BackupHeader,
+    kdf: MasterKeyParams,
+    #[serde(with = "hex_bytes")]
+    salt: Vec<u8>,
+    wrapped_key: SealedBox,
+    payload: SealedBox,
+}
+
+/// Một ví trong bản sao lưu; private key để trống với ví chỉ theo dõi hoặc ví phần cứng
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BackupWallet {
+    pub info: WalletInfo,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    private_key: Option<String>,
+}
+
+impl BackupWallet {
+    pub fn new(info: WalletInfo, private_key: Option<&[u8]>) -> Self {
+        Self { info, private_key: private_key.map(hex::encode) }
+    }
+
+    /// Private key dạng byte (nếu bản sao lưu có khóa của ví)
+    pub fn private_key(&self) -> Result<Option<Zeroizing<Vec<u8>>>> {
+        self.private_key.as_ref()
+            .map(|key| hex::decode(key).map(Zeroizing::new).map_err(|e| anyhow!("Private key của ví {} không hợp lệ: {}", self.info.address, e)))
+            .transpose()
+    }
+}
+
+impl Drop for BackupWallet {
+    fn drop(&mut self) {
+        self.private_key.zeroize();
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BackupPayload {
+    wallets: Vec<BackupWallet>,
+    groups: Vec<WalletGroup>,
+    policies: HashMap<Address, SpendingPolicy>,
+}
+
+/// Nội dung bản sao lưu đã giải mã và kiểm tra toàn vẹn
+pub struct BackupContents {
+    pub header: BackupHeader,
+    pub wallets: Vec<BackupWallet>,
+    pub groups: Vec<WalletGroup>,
+    pub policies: HashMap<Address, SpendingPolicy>,
+}
+
+impl BackupContents {
+    pub fn summary(&self) -> BackupSummary {
+        BackupSummary {
+            version: self.header.version,
+            created_at: self.header.created_at,
+            wallets: self.wallets.iter().map(|wallet| wallet.info.address.clone()).collect(),
+            watch_only: self.wallets.iter().filter(|wallet| wallet.private_key.is_none()).count(),
+            groups: self.groups.iter().map(|group| group.name.clone()).collect(),
+            policies: self.policies.len(),
+        }
+    }
+}
+
+/// Tóm tắt bản sao lưu đã kiểm tra, không chứa khóa
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSummary {
+    pub version: u16,
+    pub created_at: u64,
+    pub wallets: Vec<String>,
+    /// Số ví không có khóa (chỉ theo dõi, phần cứng)
+    pub watch_only: usize,
+    pub groups: Vec<String>,
+    pub policies: usize,
+}
+
+/// Kết quả khôi phục từ bản sao lưu
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestoreReport {
+    pub restored_wallets: Vec<String>,
+    /// Ví đã có trong storage (không ghi đè) hoặc đang có khóa mà bản sao lưu chỉ theo dõi
+    pub skipped_wallets: Vec<String>,
+    pub restored_groups: Vec<String>,
+    pub skipped_groups: Vec<String>,
+    pub restored_policies: usize,
+    pub skipped_policies: usize,
+}
+
+/// Chia backup key thành `shares` mảnh, cần `threshold` mảnh để khôi phục
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareSplit {
+    pub threshold: u8,
+    pub shares: u8,
+}
+
+/// Tùy chọn khi tạo bản sao lưu
+#[derive(Debug, Clone, Default)]
+pub struct BackupOptions {
+    /// Tham số Argon2id dẫn xuất khóa từ passphrase sao lưu
+    pub kdf: MasterKeyParams,
+    /// Chia backup key theo Shamir k-of-n
+    pub split: Option<ShareSplit>,
+}
+
+/// Tệp sao lưu (JSON) và các mảnh khóa nếu có chia
+pub struct BackupExport {
+    pub archive: String,
+    pub shares: Vec<BackupShare>,
+}
+
+/// Cách mở khóa bản sao lưu
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupKey {
+    Passphrase(String),
+    Shares(Vec<BackupShare>),
+}
+
+/// Một mảnh Shamir của backup key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupShare {
+    pub threshold: u8,
+    pub index: u8,
+    pub key_check: String,
+    #[serde(with = "hex_bytes")]
+    pub data: Vec<u8>,
+}
+
+impl fmt::Display for BackupShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}:{}:{}", SHARE_PREFIX, self.threshold, self.index, self.key_check, hex::encode(&self.data))
+    }
+}
+
+impl FromStr for BackupShare {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.trim().split(':').collect();
+        let [SHARE_PREFIX, threshold, index, key_check, data] = parts.as_slice() else {
+            bail!("Mảnh khóa sao lưu không đúng định dạng {}:<k>:<i>:<check>:<hex>", SHARE_PREFIX);
+        };
+        Ok(Self {
+            threshold: threshold.parse().context("Ngưỡng mảnh khóa không hợp lệ")?,
+            index: index.parse().context("Chỉ số mảnh khóa không hợp lệ")?,
+            key_check: key_check.to_string(),
+            data: hex::decode(data).context("Dữ liệu mảnh khóa không hợp lệ")?,
+        })
+    }
+}
+
+/// Passphrase sao lưu phải đủ dài, không phải seed mặc định và khác passphrase vận hành
+pub fn ensure_backup_passphrase(passphrase: &str, runtime_passphrase: &str) -> Result<()> {
+    let trimmed = passphrase.trim();
+    if trimmed.chars().count() < MIN_PASSPHRASE_LEN {
+        bail!("Passphrase sao lưu phải dài ít nhất {} ký tự", MIN_PASSPHRASE_LEN);
+    }
+    if DEFAULT_ENCRYPTION_SEEDS.iter().any(|seed| trimmed.eq_ignore_ascii_case(seed)) {
+        bail!("Passphrase sao lưu không được là seed mặc định");
+    }
+    if trimmed == runtime_passphrase.trim() {
+        bail!("Passphrase sao lưu phải khác passphrase vận hành (WALLET_ENCRYPTION_SEED)");
+    }
+    Ok(())
+}
+
+/// Mã hóa ví, nhóm và chính sách thành một tệp sao lưu
+pub fn create_backup(
+    wallets: Vec<BackupWallet>,
+    groups: Vec<WalletGroup>,
+    policies: HashMap<Address, SpendingPolicy>,
+    passphrase: &str,
+    options: &BackupOptions,
+) -> Result<BackupExport> {
+    let mut backup_key = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(backup_key.as_mut());
+
+    let header = BackupHeader {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        created_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        wallet_count: wallets.len(),
+        group_count: groups.len(),
+        policy_count: policies.len(),
+        key_check: key_check(&backup_key),
+    };
+    let aad = serde_json::to_vec(&header)?;
+
+    let payload = Zeroizing::new(serde_json::to_vec(&BackupPayload { wallets, groups, policies })?);
+    let payload = seal(&backup_key, &payload, &aad)?;
+
+    let mut salt = vec![0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let wrapping_key = derive_master_key(passphrase, &salt, &options.kdf)?;
+    let wrapped_key = seal(&wrapping_key, backup_key.as_ref(), &[WRAP_AAD, &aad].concat())?;
+
+    let shares = match options.split {
+        Some(split) => split_key(&backup_key, split)?,
+        None => Vec::new(),
+    };
+    let archive = BackupArchive { header, kdf: options.kdf.clone(), salt, wrapped_key, payload };
+
+    Ok(BackupExport { archive: serde_json::to_string_pretty(&archive)?, shares })
+}
+
+/// Giải mã và kiểm tra toàn bộ bản sao lưu: định dạng, phiên bản, xác thực AEAD,
+/// số lượng khớp header và khóa của từng ví khớp địa chỉ. Không ghi gì xuống đĩa
+pub fn open_backup(archive: &str, key: &BackupKey) -> Result<BackupContents> {
+    let archive: BackupArchive = serde_json::from_str(archive).context("Tệp sao lưu không đúng định dạng")?;
+    let header = archive.header;
+    if header.format != BACKUP_FORMAT {
+        bail!("Không phải tệp sao lưu ví (format {})", header.format);
+    }
+    if header.version == 0 || header.version > BACKUP_VERSION {
+        bail!("Phiên bản sao lưu {} không được hỗ trợ (tối đa {})", header.version, BACKUP_VERSION);
+    }
+    let aad = serde_json::to_vec(&header)?;
+
+    let backup_key = match key {
+        BackupKey::Passphrase(passphrase) => {
+            let wrapping_key = derive_master_key(passphrase, &archive.salt, &archive.kdf)?;
+            let key = open(&wrapping_key, &archive.wrapped_key, &[WRAP_AAD, &aad].concat())
+                .map_err(|_| anyhow!("Sai passphrase sao lưu hoặc tệp sao lưu đã bị sửa"))?;
+            to_key(&key)?
+        }
+        BackupKey::Shares(shares) => combine_key(shares)?,
+    };
+    if key_check(&backup_key) != header.key_check {
+        bail!("Khóa không thuộc bản sao lưu này");
+    }
+
+    let payload = open(&backup_key, &archive.payload, &aad)
+        .map_err(|_| anyhow!("Payload sao lưu không toàn vẹn"))?;
+    let payload: BackupPayload = serde_json::from_slice(&payload).context("Payload sao lưu không hợp lệ")?;
+
+    if payload.wallets.len() != header.wallet_count
+        || payload.groups.len() != header.group_count
+        || payload.policies.len() != header.policy_count
+    {
+        bail!("Số lượng ví/nhóm/chính sách không khớp header sao lưu");
+    }
+
+    let mut seen = HashSet::new();
+    for wallet in &payload.wallets {
+        let address = &wallet.info.address;
+        Address::from_str(address).map_err(|e| anyhow!("Địa chỉ {} không hợp lệ: {}", address, e))?;
+        if !seen.insert(address.to_lowercase()) {
+            bail!("Ví {} xuất hiện nhiều lần trong bản sao lưu", address);
+        }
+        match wallet.private_key()? {
+            Some(private_key) => {
+                let signer = LocalWallet::from_bytes(&private_key)
+                    .map_err(|e| anyhow!("Private key của ví {} không hợp lệ: {}", address, e))?;
+                if !format!("{:?}", signer.address()).eq_ignore_ascii_case(address) {
+                    bail!("Private key không khớp địa chỉ ví {}", address);
+                }
+            }
+            None if !wallet.info.watch_only && !wallet.info.is_hardware => {
+                bail!("Bản sao lưu thiếu private key của ví {}", address);
+            }
+            None => {}
+        }
+    }
+
+    Ok(BackupContents { header, wallets: payload.wallets, groups: payload.groups, policies: payload.policies })
+}
+
+/// Kiểm tra bản sao lưu mở được và toàn vẹn, trả về tóm tắt
+pub fn verify_backup(archive: &str, key: &BackupKey) -> Result<BackupSummary> {
+    Ok(open_backup(archive, key)?.summary())
+}
+
+/// Dấu vân tay 8 byte đầu của SHA-256(backup key)
+fn key_check(key: &[u8; 32]) -> String {
+    hex::encode(&Sha256::digest(key)[..8])
+}
+
+fn to_key(bytes: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    if bytes.len() != key.len() {
+        bail!("Backup key phải dài 32 byte");
+    }
+    key.copy_from_slice(bytes);
+    Ok(key)
+}
+
+/// Chia backup key theo Shamir trên GF(256): mỗi byte là hệ số tự do của một đa thức bậc k-1
+fn split_key(key: &[u8; 32], split: ShareSplit) -> Result<Vec<BackupShare>> {
+    let ShareSplit { threshold, shares } = split;
+    if threshold < 2 || threshold > shares {
+        bail!("Chia khóa cần 2 <= k <= n (k = {}, n = {})", threshold, shares);
+    }
+    if shares == u8::MAX {
+        bail!("Tối đa {} mảnh khóa", u8::MAX - 1);
+    }
+
+    let check = key_check(key);
+    let mut result: Vec<BackupShare> = (1..=shares)
+        .map(|index| BackupShare { threshold, index, key_check: check.clone(), data: Vec::with_capacity(key.len()) })
+        .collect();
+
+    let mut coefficients = Zeroizing::new(vec![0u8; threshold as usize]);
+    for byte in key.iter() {
+        coefficients[0] = *byte;
+        OsRng.fill_bytes(&mut coefficients[1..]);
+        for share in result.iter_mut() {
+            // Horner: f(x) = c0 + x(c1 + x(c2 + ...))
+            let y = coefficients.iter().rev().fold(0u8, |acc, c| gf_mul(acc, share.index) ^ c);
+            share.data.push(y);
+        }
+    }
+    Ok(result)
+}
+
+/// Ghép backup key từ ít nhất k mảnh bằng nội suy Lagrange tại x = 0
+fn combine_key(shares: &[BackupShare]) -> Result<Zeroizing<[u8; 32]>> {
+    let first = shares.first().ok_or_else(|| anyhow!("Chưa có mảnh khóa nào"))?;
+    let mut indices = HashSet::new();
+    for share in shares {
+        if share.threshold != first.threshold || share.key_check != first.key_check {
+            bail!("Các mảnh khóa không thuộc cùng một bản sao lưu");
+        }
+        if share.index == 0 || share.data.len() != 32 {
+            bail!("Mảnh khóa {} không hợp lệ", share.index);
+        }
+        if !indices.insert(share.index) {
+            bail!("Mảnh khóa {} bị trùng", share.index);
+        }
+    }
+    if shares.len() < first.threshold as usize {
+        bail!("Cần {} mảnh khóa, mới có {}", first.threshold, shares.len());
+    }
+
+    let shares = &shares[..first.threshold as usize];
+    let mut key = Zeroizing::new([0u8; 32]);
+    for (i, share) in shares.iter().enumerate() {
+        // l_i(0) = Π x_j / (x_j - x_i); phép trừ trên GF(256) là XOR
+        let basis = shares.iter().enumerate()
+            .filter(|(j, _)| *j != i)
+            .fold(1u8, |acc, (_, other)| gf_mul(acc, gf_mul(other.index, gf_inv(other.index ^ share.index))));
+        for (byte, y) in key.iter_mut().zip(&share.data) {
+            *byte ^= gf_mul(*y, basis);
+        }
+    }
+    Ok(key)
+}
+
+/// Nhân trên GF(256) với đa thức rút gọn x^8 + x^4 + x^3 + x + 1 (như AES)
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// Nghịch đảo trên GF(256): a^254
+fn gf_inv(a: u8) -> u8 {
+    let (mut result, mut base, mut exp) = (1u8, a, 254u8);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::groups::SelectionStrategy;
+
+    const BACKUP_PASSPHRASE: &str = "offline backup passphrase";
+
+    fn sample_wallets() -> (LocalWallet, Vec<BackupWallet>) {
+        let signer = LocalWallet::new(&mut OsRng);
+        let info = |address: String, watch_only: bool| WalletInfo {
+            address,
+            encrypted_private_key: None,
+            encrypted_mnemonic: None,
+            chain_id: 1,
+            created_at: 1,
+            last_used: 1,
+            balance: None,
+            name: Some("main".to_string()),
+            tags: vec![],
+            is_hardware: false,
+            derivation_path: Some("m/44'/60'/0'/0/0".to_string()),
+            watch_only,
+        };
+        let wallets = vec![
+            BackupWallet::new(info(format!("{:?}", signer.address()), false), Some(&signer.signer().to_bytes())),
+            BackupWallet::new(info(format!("{:?}", Address::random()), true), None),
+        ];
+        (signer, wallets)
+    }
+
+    fn options(split: Option<ShareSplit>) -> BackupOptions {
+        BackupOptions { kdf: MasterKeyParams::light(), split }
+    }
+
+    #[test]
+    fn test_backup_roundtrip_with_passphrase() {
+        let (signer, wallets) = sample_wallets();
+        let group = WalletGroup { name: "snipers".to_string(), strategy: SelectionStrategy::default(), members: vec![signer.address()] };
+        let policies = HashMap::from([(signer.address(), SpendingPolicy { allowed_chains: Some(vec![1]), ..Default::default() })]);
+
+        let export = create_backup(wallets, vec![group.clone()], policies, BACKUP_PASSPHRASE, &options(None)).unwrap();
+        assert!(export.shares.is_empty());
+        assert!(!export.archive.contains(&hex::encode(signer.signer().to_bytes())));
+
+        let contents = open_backup(&export.archive, &BackupKey::Passphrase(BACKUP_PASSPHRASE.to_string())).unwrap();
+        assert_eq!(contents.groups, vec![group]);
+        assert_eq!(contents.policies[&signer.address()].allowed_chains, Some(vec![1]));
+        let summary = contents.summary();
+        assert_eq!((summary.wallets.len(), summary.watch_only), (2, 1));
+        let restored = contents.wallets[0].private_key().unwrap().unwrap();
+        assert_eq!(restored.as_slice(), signer.signer().to_bytes().as_slice());
+
+        assert!(verify_backup(&export.archive, &BackupKey::Passphrase("wrong backup passphrase".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_tampered_backup_is_rejected() {
+        let (_, wallets) = sample_wallets();
+        let export = create_backup(wallets, vec![], HashMap::new(), BACKUP_PASSPHRASE, &options(None)).unwrap();
+        let key = BackupKey::Passphrase(BACKUP_PASSPHRASE.to_string());
+
+        // Sửa header (được xác thực làm AAD) khiến cả khóa bọc lẫn payload không mở được
+        let mut archive: serde_json::Value = serde_json::from_str(&export.archive).unwrap();
+        archive["header"]["created_at"] = serde_json::json!(0);
+        assert!(open_backup(&archive.to_string(), &key).is_err());
+
+        let mut archive: serde_json::Value = serde_json::from_str(&export.archive).unwrap();
+        archive["header"]["version"] = serde_json::json!(BACKUP_VERSION + 1);
+        let error = open_backup(&archive.to_string(), &key).err().unwrap();
+        assert!(error.to_string().contains("không được hỗ trợ"));
+    }
+
+    #[test]
+    fn test_shamir_shares_restore_backup_key() {
+        let (_, wallets) = sample_wallets();
+        let split = ShareSplit { threshold: 3, shares: 5 };
+        let export = create_backup(wallets, vec![], HashMap::new(), BACKUP_PASSPHRASE, &options(Some(split))).unwrap();
+        assert_eq!(export.shares.len(), 5);
+
+        let encoded: Vec<String> = export.shares.iter().map(|share| share.to_string()).collect();
+        let parsed: Vec<BackupShare> = encoded.iter().map(|share| share.parse().unwrap()).collect();
+        assert_eq!(parsed, export.shares);
+
+        // Bất kỳ 3 mảnh nào cũng mở được, 2 mảnh thì không
+        let subset = vec![parsed[4].clone(), parsed[1].clone(), parsed[2].clone()];
+        assert_eq!(verify_backup(&export.archive, &BackupKey::Shares(subset)).unwrap().wallets.len(), 2);
+        assert!(verify_backup(&export.archive, &BackupKey::Shares(parsed[..2].to_vec())).is_err());
+
+        let mut forged = parsed[..3].to_vec();
+        forged[0].data[0] ^= 1;
+        assert!(verify_backup(&export.archive, &BackupKey::Shares(forged)).is_err());
+
+        assert!(ensure_backup_passphrase("operator passphrase", "operator passphrase").is_err());
+        assert!(ensure_backup_passphrase(BACKUP_PASSPHRASE, "operator passphrase").is_ok());
+    }
+}
@@ -0,0 +1,226 @@
+// External imports
+use coins_bip32::enc::{MainnetEncoder, XKeyEncoder};
+use coins_bip32::xkeys::Parent;
+use ethers::{
+    prelude::{LocalWallet, MnemonicBuilder},
+    signers::{coins_bip39::English, Signer},
+    types::Address,
+    utils::public_key_to_address,
+};
+
+// Standard library imports
+use std::collections::HashSet;
+use std::future::Future;
+use std::ops::Range;
+
+// Third party imports
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// Số ví tối đa dẫn xuất trong một lần
+pub const MAX_DERIVED_WALLETS: usize = 1000;
+/// Số địa chỉ chưa dùng liên tiếp trước khi dừng dò tìm (BIP-44 gap limit)
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// Sơ đồ đường dẫn dẫn xuất ví Ethereum
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DerivationScheme {
+    /// m/44'/60'/{account}'/0/{index} (MetaMask, Trezor, Ledger mặc định)
+    Bip44,
+    /// m/44'/60'/{account}'/0/0 (Ledger Live: mỗi account một địa chỉ)
+    LedgerLive,
+    /// m/44'/60'/0'/{index} (MyEtherWallet/MyCrypto cũ, Ledger legacy)
+    LegacyMew,
+    /// Mẫu tùy chỉnh chứa {account} và/hoặc {index}, VD: m/44'/60'/{account}'/0/{index}
+    Custom(String),
+}
+
+impl DerivationScheme {
+    /// Đường dẫn cho account và index; sơ đồ không dùng tham số nào thì bỏ qua tham số đó
+    pub fn path(&self, account: u32, index: u32) -> String {
+        match self {
+            Self::Bip44 => format!("m/44'/60'/{}'/0/{}", account, index),
+            Self::LedgerLive => format!("m/44'/60'/{}'/0/0", account),
+            Self::LegacyMew => format!("m/44'/60'/0'/{}", index),
+            Self::Custom(template) => template
+                .replace("{account}", &account.to_string())
+                .replace("{index}", &index.to_string()),
+        }
+    }
+
+    /// Các đường dẫn trong dải account × index, bỏ trùng và giữ thứ tự
+    pub fn paths(&self, accounts: Range<u32>, indices: Range<u32>) -> Result<Vec<String>> {
+        if let Self::Custom(template) = self {
+            validate_path(&template.replace("{account}", "0").replace("{index}", "0"))?;
+        }
+
+        let mut seen = HashSet::new();
+        let mut paths = Vec::new();
+        for account in accounts {
+            for index in indices.clone() {
+                let path = self.path(account, index);
+                if seen.insert(path.clone()) {
+                    if paths.len() == MAX_DERIVED_WALLETS {
+                        bail!("Derivation range exceeds {} wallets", MAX_DERIVED_WALLETS);
+                    }
+                    paths.push(path);
+                }
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Đường dẫn thứ `slot` khi dò ví đã dùng: Ledger Live tăng account, sơ đồ khác tăng index
+    pub fn discovery_path(&self, account: u32, slot: u32) -> String {
+        match self {
+            Self::LedgerLive => self.path(slot, 0),
+            _ => self.path(account, slot),
+        }
+    }
+}
+
+/// Kiểm tra đường dẫn BIP-32 dạng m/44'/60'/0'/0/0
+pub fn validate_path(path: &str) -> Result<()> {
+    let mut segments = path.split('/');
+    if segments.next() != Some("m") {
+        bail!("Derivation path {} must start with m/", path);
+    }
+    let mut depth = 0;
+    for segment in segments {
+        let number = segment.strip_suffix('\'').or_else(|| segment.strip_suffix('h')).unwrap_or(segment);
+        if number.parse::<u32>().ok().filter(|n| *n < 0x8000_0000).is_none() {
+            bail!("Invalid segment '{}' in derivation path {}", segment, path);
+        }
+        depth += 1;
+    }
+    if depth == 0 {
+        bail!("Derivation path {} has no segments", path);
+    }
+    Ok(())
+}
+
+/// Dẫn xuất ví từ mnemonic theo đường dẫn
+pub fn derive_wallet(mnemonic: &str, passphrase: Option<&str>, path: &str) -> Result<LocalWallet> {
+    validate_path(path)?;
+    Ok(MnemonicBuilder::<English>::default()
+        .phrase(mnemonic)
+        .password(passphrase.unwrap_or(""))
+        .derivation_path(path)?
+        .build()?)
+}
+
+/// Dò các ví đã có giao dịch: dừng sau `gap_limit` ví chưa dùng liên tiếp
+pub async fn discover_used_wallets<F, Fut>(
+    mnemonic: &str,
+    passphrase: Option<&str>,
+    scheme: &DerivationScheme,
+    account: u32,
+    gap_limit: u32,
+    is_used: F,
+) -> Result<Vec<(String, LocalWallet)>>
+where
+    F: Fn(Address) -> Fut,
+    Fut: Future<Output = Result<bool>>,
+{
+    let mut used = Vec::new();
+    let mut gap = 0;
+    let mut slot = 0;
+    while gap < gap_limit.max(1) {
+        if slot as usize >= MAX_DERIVED_WALLETS {
+            bail!("Discovery stopped after {} wallets", MAX_DERIVED_WALLETS);
+        }
+        let path = scheme.discovery_path(account, slot);
+        let wallet = derive_wallet(mnemonic, passphrase, &path)?;
+        if is_used(wallet.address()).await? {
+            used.push((path, wallet));
+            gap = 0;
+        } else {
+            gap += 1;
+        }
+        slot += 1;
+    }
+    Ok(used)
+}
+
+/// Địa chỉ nhận (nhánh 0) của xpub cấp account cho dải index; đường dẫn trả về tương đối với xpub (M/0/i)
+pub fn xpub_addresses(xpub: &str, indices: Range<u32>) -> Result<Vec<(String, Address)>> {
+    if indices.len() > MAX_DERIVED_WALLETS {
+        bail!("Derivation range exceeds {} wallets", MAX_DERIVED_WALLETS);
+    }
+    let account = MainnetEncoder::xpub_from_base58(xpub.trim())
+        .map_err(|e| anyhow!("Invalid xpub: {}", e))?;
+    let external = account.derive_child(0).map_err(|e| anyhow!("Cannot derive from xpub: {}", e))?;
+
+    indices
+        .map(|index| {
+            let child = external.derive_child(index).map_err(|e| anyhow!("Cannot derive index {}: {}", index, e))?;
+            Ok((format!("M/0/{}", index), public_key_to_address(child.as_ref())))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::coins_bip39::Mnemonic;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_scheme_paths_and_validation() {
+        assert_eq!(DerivationScheme::Bip44.path(2, 5), "m/44'/60'/2'/0/5");
+        assert_eq!(DerivationScheme::LedgerLive.path(3, 9), "m/44'/60'/3'/0/0");
+        assert_eq!(DerivationScheme::LegacyMew.path(3, 9), "m/44'/60'/0'/9");
+
+        // Ledger Live bỏ qua index nên dải 2 account × 3 index chỉ còn 2 đường dẫn
+        assert_eq!(DerivationScheme::LedgerLive.paths(0..2, 0..3).unwrap().len(), 2);
+        assert_eq!(DerivationScheme::Bip44.paths(0..2, 0..3).unwrap().len(), 6);
+
+        let custom = DerivationScheme::Custom("m/44'/61'/{account}'/0/{index}".to_string());
+        assert_eq!(custom.path(1, 2), "m/44'/61'/1'/0/2");
+        assert!(DerivationScheme::Custom("44'/60'/{index}".to_string()).paths(0..1, 0..1).is_err());
+        assert!(validate_path("m/44'/60'/x/0").is_err());
+        assert!(DerivationScheme::Bip44.paths(0..2, 0..600).is_err());
+
+        let wallet = derive_wallet(MNEMONIC, None, &DerivationScheme::Bip44.path(0, 0)).unwrap();
+        assert_eq!(format!("{:?}", wallet.address()), "0x9858effd232b4033e47d90003d41ec34ecaeda94");
+    }
+
+    #[test]
+    fn test_xpub_addresses_match_mnemonic_derivation() {
+        let mnemonic = Mnemonic::<English>::new_from_phrase(MNEMONIC).unwrap();
+        let account = mnemonic.derive_key("m/44'/60'/0'", None).unwrap();
+        let xpub = MainnetEncoder::xpub_to_base58(&account.verify_key()).unwrap();
+
+        let addresses = xpub_addresses(&xpub, 0..3).unwrap();
+        for (index, (path, address)) in addresses.iter().enumerate() {
+            assert_eq!(path, &format!("M/0/{}", index));
+            let wallet = derive_wallet(MNEMONIC, None, &DerivationScheme::Bip44.path(0, index as u32)).unwrap();
+            assert_eq!(*address, wallet.address());
+        }
+        assert!(xpub_addresses("xpub-not-base58", 0..1).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_discovery_stops_after_gap_limit() {
+        let used: HashSet<Address> = [0u32, 1, 4]
+            .iter()
+            .map(|index| derive_wallet(MNEMONIC, None, &DerivationScheme::Bip44.path(0, *index)).unwrap().address())
+            .collect();
+
+        let found = discover_used_wallets(MNEMONIC, None, &DerivationScheme::Bip44, 0, 3, |address| {
+            let is_used = used.contains(&address);
+            async move { Ok(is_used) }
+        }).await.unwrap();
+        let paths: Vec<&str> = found.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, vec!["m/44'/60'/0'/0/0", "m/44'/60'/0'/0/1", "m/44'/60'/0'/0/4"]);
+
+        // Gap limit 2 dừng trước index 4
+        let found = discover_used_wallets(MNEMONIC, None, &DerivationScheme::Bip44, 0, 2, |address| {
+            let is_used = used.contains(&address);
+            async move { Ok(is_used) }
+        }).await.unwrap();
+        assert_eq!(found.len(), 2);
+    }
+}
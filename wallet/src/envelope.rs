@@ -0,0 +1,406 @@
+// Standard library imports
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Third party imports
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use anyhow::{anyhow, Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+// Internal imports
+use crate::keystore::hex_bytes;
+use crate::secure_storage::write_file_atomic;
+
+/// Các seed mặc định từng được đóng gói trong cấu hình; bot không được chạy với chúng
+pub const DEFAULT_ENCRYPTION_SEEDS: &[&str] = &[
+    "diamond_wallet",
+    "snipebot_default_encryption_seed",
+    "default_encryption_seed_change_this",
+];
+
+/// Độ dài tối thiểu của passphrase vận hành
+pub const MIN_PASSPHRASE_LEN: usize = 12;
+
+/// Phiên bản định dạng tệp keyring
+const KEYRING_VERSION: u8 = 1;
+
+/// Bản rõ cố định dùng để kiểm tra passphrase khi mở keyring
+const VERIFIER_PLAINTEXT: &[u8] = b"diamond-keyring-v1";
+const VERIFIER_AAD: &[u8] = b"keyring";
+
+/// Từ chối passphrase rỗng, quá ngắn hoặc còn là seed mặc định
+pub fn ensure_operator_passphrase(passphrase: &str) -> Result<()> {
+    let trimmed = passphrase.trim();
+    if DEFAULT_ENCRYPTION_SEEDS.iter().any(|seed| trimmed.eq_ignore_ascii_case(seed)) {
+        return Err(anyhow!(
+            "wallet_encryption_seed vẫn là giá trị mặc định \"{}\", hãy đặt WALLET_ENCRYPTION_SEED riêng trước khi khởi động",
+            trimmed
+        ));
+    }
+    if trimmed.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(anyhow!("wallet_encryption_seed phải dài ít nhất {} ký tự", MIN_PASSPHRASE_LEN));
+    }
+    Ok(())
+}
+
+/// Tham số Argon2id để dẫn xuất master key từ passphrase vận hành
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MasterKeyParams {
+    /// Bộ nhớ (KiB)
+    pub memory_kib: u32,
+    /// Số vòng lặp
+    pub iterations: u32,
+    /// Số luồng song song
+    pub parallelism: u32,
+}
+
+impl MasterKeyParams {
+    /// Tham số nhẹ, chỉ dùng cho môi trường thử nghiệm
+    pub fn light() -> Self {
+        Self { memory_kib: 1024, iterations: 1, parallelism: 1 }
+    }
+}
+
+impl Default for MasterKeyParams {
+    fn default() -> Self {
+        // RFC 9106, cấu hình khuyến nghị thứ hai (64 MiB, 3 vòng)
+        Self { memory_kib: 65536, iterations: 3, parallelism: 1 }
+    }
+}
+
+/// Dữ liệu mã hóa AES-256-GCM
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SealedBox {
+    #[serde(with = "hex_bytes")]
+    nonce: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    ciphertext: Vec<u8>,
+}
+
+/// Data key của một ví, được bọc bằng master key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DataKeyEntry {
+    key_id: String,
+    created_at: u64,
+    wrapped: SealedBox,
+    /// Data key cũ trong lúc xoay khóa, giữ lại tới khi keystore đã được mã hóa lại
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    previous: Option<SealedBox>,
+}
+
+/// Nội dung tệp keyring trên đĩa
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyringFile {
+    version: u8,
+    master_key: MasterKeyParams,
+    #[serde(with = "hex_bytes")]
+    salt: Vec<u8>,
+    verifier: SealedBox,
+    data_keys: HashMap<String, DataKeyEntry>,
+}
+
+/// Data key đã giải mã của một ví
+pub struct DataKey {
+    pub key_id: String,
+    key: Zeroizing<[u8; 32]>,
+}
+
+impl DataKey {
+    fn generate() -> Self {
+        let mut key = Zeroizing::new([0u8; 32]);
+        OsRng.fill_bytes(key.as_mut());
+        Self { key_id: uuid::Uuid::new_v4().to_string(), key }
+    }
+
+    /// Mật khẩu của keystore V3 được mã hóa bằng data key này
+    pub fn keystore_password(&self) -> Zeroizing<String> {
+        Zeroizing::new(hex::encode(self.key.as_ref()))
+    }
+}
+
+/// Keyring đã mở khóa: master key (Argon2id từ passphrase) bọc data key của từng ví.
+/// Đổi passphrase chỉ bọc lại data key, không chạm tới keystore của ví
+pub struct Keyring {
+    path: PathBuf,
+    file: KeyringFile,
+    master_key: Zeroizing<[u8; 32]>,
+}
+
+impl Keyring {
+    /// Mở keyring tại `path`, hoặc tạo mới với salt ngẫu nhiên nếu chưa có
+    pub fn open_or_create(path: &Path, passphrase: &str, params: &MasterKeyParams) -> Result<Self> {
+        if path.exists() {
+            let data = fs::read(path).with_context(|| format!("Không đọc được keyring {:?}", path))?;
+            let file: KeyringFile = serde_json::from_slice(&data).context("Tệp keyring không hợp lệ")?;
+            if file.version != KEYRING_VERSION {
+                return Err(anyhow!("Phiên bản keyring không được hỗ trợ: {}", file.version));
+            }
+            let master_key = derive_master_key(passphrase, &file.salt, &file.master_key)?;
+            open(&master_key, &file.verifier, VERIFIER_AAD)
+                .map_err(|_| anyhow!("Passphrase không đúng"))?;
+            return Ok(Self { path: path.to_path_buf(), file, master_key });
+        }
+
+        ensure_operator_passphrase(passphrase)?;
+        let (salt, master_key) = new_master_key(passphrase, params)?;
+        let keyring = Self {
+            path: path.to_path_buf(),
+            file: KeyringFile {
+                version: KEYRING_VERSION,
+                master_key: params.clone(),
+                salt,
+                verifier: seal(&master_key, VERIFIER_PLAINTEXT, VERIFIER_AAD)?,
+                data_keys: HashMap::new(),
+            },
+            master_key,
+        };
+        keyring.save()?;
+        Ok(keyring)
+    }
+
+    /// Kiểm tra passphrase có khớp với master key đang mở không
+    pub fn verify_passphrase(&self, passphrase: &str) -> Result<()> {
+        let master_key = derive_master_key(passphrase, &self.file.salt, &self.file.master_key)?;
+        open(&master_key, &self.file.verifier, VERIFIER_AAD)
+            .map(|_| ())
+            .map_err(|_| anyhow!("Passphrase không đúng"))
+    }
+
+    /// Data key hiện tại của ví
+    pub fn data_key(&self, wallet_id: &str) -> Result<Option<DataKey>> {
+        match self.file.data_keys.get(wallet_id) {
+            Some(entry) => Ok(Some(DataKey {
+                key_id: entry.key_id.clone(),
+                key: self.unwrap_key(wallet_id, &entry.wrapped)?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Data key trước đó nếu ví đang xoay khóa dở dang
+    pub fn previous_data_key(&self, wallet_id: &str) -> Result<Option<DataKey>> {
+        match self.file.data_keys.get(wallet_id).and_then(|entry| entry.previous.as_ref().map(|sealed| (entry, sealed))) {
+            Some((entry, sealed)) => Ok(Some(DataKey {
+                key_id: format!("{}-previous", entry.key_id),
+                key: self.unwrap_key(wallet_id, sealed)?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Tạo data key mới cho ví (thay thế khóa hiện có) và ghi keyring
+    pub fn create_data_key(&mut self, wallet_id: &str) -> Result<DataKey> {
+        let data_key = DataKey::generate();
+        let entry = DataKeyEntry {
+            key_id: data_key.key_id.clone(),
+            created_at: now_secs(),
+            wrapped: seal(&self.master_key, data_key.key.as_ref(), wallet_id.as_bytes())?,
+            previous: None,
+        };
+        self.file.data_keys.insert(wallet_id.to_string(), entry);
+        self.save()?;
+        Ok(data_key)
+    }
+
+    /// Bắt đầu xoay data key: khóa hiện tại thành `previous`, trả về (khóa cũ, khóa mới)
+    pub fn begin_rotation(&mut self, wallet_id: &str) -> Result<(DataKey, DataKey)> {
+        let old = self.data_key(wallet_id)?
+            .ok_or_else(|| anyhow!("Ví {} chưa có data key", wallet_id))?;
+        let new = DataKey::generate();
+        let wrapped = seal(&self.master_key, new.key.as_ref(), wallet_id.as_bytes())?;
+
+        let entry = self.file.data_keys.get_mut(wallet_id)
+            .ok_or_else(|| anyhow!("Ví {} chưa có data key", wallet_id))?;
+        entry.previous = Some(std::mem::replace(&mut entry.wrapped, wrapped));
+        entry.key_id = new.key_id.clone();
+        entry.created_at = now_secs();
+        self.save()?;
+        Ok((old, new))
+    }
+
+    /// Kết thúc xoay data key sau khi keystore đã được mã hóa lại bằng khóa mới
+    pub fn finish_rotation(&mut self, wallet_id: &str) -> Result<()> {
+        if let Some(entry) = self.file.data_keys.get_mut(wallet_id) {
+            if entry.previous.take().is_some() {
+                self.save()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Xóa data key của ví
+    pub fn remove(&mut self, wallet_id: &str) -> Result<()> {
+        if self.file.data_keys.remove(wallet_id).is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Các ví có data key cũ hơn `max_age` hoặc đang xoay dở
+    pub fn stale_wallets(&self, max_age: Duration) -> Vec<String> {
+        let cutoff = now_secs().saturating_sub(max_age.as_secs());
+        self.file.data_keys.iter()
+            .filter(|(_, entry)| entry.created_at <= cutoff || entry.previous.is_some())
+            .map(|(wallet_id, _)| wallet_id.clone())
+            .collect()
+    }
+
+    /// Đổi passphrase: dẫn xuất master key mới (salt mới, tham số mới) và bọc lại mọi data key
+    pub fn rotate_passphrase(&mut self, current: &str, new: &str, params: &MasterKeyParams) -> Result<()> {
+        self.verify_passphrase(current)?;
+        ensure_operator_passphrase(new)?;
+
+        let (salt, master_key) = new_master_key(new, params)?;
+        let mut data_keys = HashMap::with_capacity(self.file.data_keys.len());
+        for (wallet_id, entry) in &self.file.data_keys {
+            let rewrap = |sealed: &SealedBox| -> Result<SealedBox> {
+                let key = self.unwrap_key(wallet_id, sealed)?;
+                seal(&master_key, key.as_ref(), wallet_id.as_bytes())
+            };
+            data_keys.insert(wallet_id.clone(), DataKeyEntry {
+                key_id: entry.key_id.clone(),
+                created_at: entry.created_at,
+                wrapped: rewrap(&entry.wrapped)?,
+                previous: entry.previous.as_ref().map(rewrap).transpose()?,
+            });
+        }
+
+        self.file = KeyringFile {
+            version: KEYRING_VERSION,
+            master_key: params.clone(),
+            salt,
+            verifier: seal(&master_key, VERIFIER_PLAINTEXT, VERIFIER_AAD)?,
+            data_keys,
+        };
+        self.master_key = master_key;
+        self.save()
+    }
+
+    fn unwrap_key(&self, wallet_id: &str, sealed: &SealedBox) -> Result<Zeroizing<[u8; 32]>> {
+        let plaintext = open(&self.master_key, sealed, wallet_id.as_bytes())
+            .with_context(|| format!("Không giải mã được data key của ví {}", wallet_id))?;
+        if plaintext.len() != 32 {
+            return Err(anyhow!("Data key của ví {} không hợp lệ", wallet_id));
+        }
+        let mut key = Zeroizing::new([0u8; 32]);
+        key.copy_from_slice(&plaintext);
+        Ok(key)
+    }
+
+    fn save(&self) -> Result<()> {
+        let data = serde_json::to_vec_pretty(&self.file)?;
+        write_file_atomic(&self.path, &data)
+            .with_context(|| format!("Không ghi được keyring {:?}", self.path))
+    }
+}
+
+/// Tạo salt ngẫu nhiên và dẫn xuất master key
+fn new_master_key(passphrase: &str, params: &MasterKeyParams) -> Result<(Vec<u8>, Zeroizing<[u8; 32]>)> {
+    let mut salt = vec![0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let master_key = derive_master_key(passphrase, &salt, params)?;
+    Ok((salt, master_key))
+}
+
+/// Dẫn xuất master key bằng Argon2id
+pub(crate) fn derive_master_key(passphrase: &str, salt: &[u8], params: &MasterKeyParams) -> Result<Zeroizing<[u8; 32]>> {
+    let params = Params::new(params.memory_kib, params.iterations, params.parallelism, Some(32))
+        .map_err(|e| anyhow!("Tham số Argon2 không hợp lệ: {}", e))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| anyhow!("Không dẫn xuất được master key: {}", e))?;
+    Ok(key)
+}
+
+pub(crate) fn seal(key: &[u8; 32], plaintext: &[u8], aad: &[u8]) -> Result<SealedBox> {
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| anyhow!("{}", e))?;
+    let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+        .map_err(|e| anyhow!("Không mã hóa được: {}", e))?;
+    Ok(SealedBox { nonce: nonce.to_vec(), ciphertext })
+}
+
+pub(crate) fn open(key: &[u8; 32], sealed: &SealedBox, aad: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    if sealed.nonce.len() != 12 {
+        return Err(anyhow!("Nonce không hợp lệ"));
+    }
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| anyhow!("{}", e))?;
+    let plaintext = cipher.decrypt(Nonce::from_slice(&sealed.nonce), Payload { msg: &sealed.ciphertext, aad })
+        .map_err(|e| anyhow!("Không giải mã được: {}", e))?;
+    Ok(Zeroizing::new(plaintext))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const PASSPHRASE: &str = "correct horse battery staple";
+
+    #[test]
+    fn test_rejects_default_seeds() {
+        for seed in DEFAULT_ENCRYPTION_SEEDS {
+            assert!(ensure_operator_passphrase(seed).is_err());
+        }
+        assert!(ensure_operator_passphrase("short").is_err());
+        assert!(ensure_operator_passphrase(PASSPHRASE).is_ok());
+
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("keyring.json");
+        assert!(Keyring::open_or_create(&path, "diamond_wallet", &MasterKeyParams::light()).is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_passphrase_rotation_keeps_data_keys() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("keyring.json");
+        let mut keyring = Keyring::open_or_create(&path, PASSPHRASE, &MasterKeyParams::light()).unwrap();
+        let password = keyring.create_data_key("0xabc").unwrap().keystore_password();
+
+        keyring.rotate_passphrase(PASSPHRASE, "a brand new operator passphrase", &MasterKeyParams::light()).unwrap();
+        assert!(keyring.rotate_passphrase(PASSPHRASE, "another passphrase!!", &MasterKeyParams::light()).is_err());
+
+        // Mở lại từ đĩa: passphrase cũ bị từ chối, data key không đổi
+        assert!(Keyring::open_or_create(&path, PASSPHRASE, &MasterKeyParams::light()).is_err());
+        let reopened = Keyring::open_or_create(&path, "a brand new operator passphrase", &MasterKeyParams::light()).unwrap();
+        assert_eq!(reopened.data_key("0xabc").unwrap().unwrap().keystore_password(), password);
+    }
+
+    #[test]
+    fn test_data_key_rotation() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("keyring.json");
+        let mut keyring = Keyring::open_or_create(&path, PASSPHRASE, &MasterKeyParams::light()).unwrap();
+        keyring.create_data_key("0xabc").unwrap();
+        assert!(keyring.stale_wallets(Duration::from_secs(3600)).is_empty());
+        assert_eq!(keyring.stale_wallets(Duration::ZERO), vec!["0xabc".to_string()]);
+
+        let (old, new) = keyring.begin_rotation("0xabc").unwrap();
+        assert_ne!(old.keystore_password(), new.keystore_password());
+        assert_eq!(keyring.previous_data_key("0xabc").unwrap().unwrap().keystore_password(), old.keystore_password());
+        // Xoay dở dang vẫn được coi là cần xoay
+        assert_eq!(keyring.stale_wallets(Duration::from_secs(3600)), vec!["0xabc".to_string()]);
+
+        keyring.finish_rotation("0xabc").unwrap();
+        assert!(keyring.previous_data_key("0xabc").unwrap().is_none());
+        assert_eq!(keyring.data_key("0xabc").unwrap().unwrap().keystore_password(), new.keystore_password());
+    }
+}
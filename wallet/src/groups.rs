@@ -0,0 +1,342 @@
+// External imports
+use ethers::types::{Address, U256};
+
+// Standard library imports
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Third party imports
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+// Internal imports
+use crate::secure_storage::write_file_atomic;
+
+/// Tên file lưu nhóm ví trong thư mục ví
+pub const GROUPS_FILE: &str = "wallet_groups.json";
+
+/// Cách chọn ví trong nhóm cho mỗi giao dịch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionStrategy {
+    /// Lần lượt theo thứ tự thành viên
+    #[default]
+    RoundRobin,
+    /// Ví lâu chưa được dùng nhất
+    LeastRecentlyUsed,
+    /// Ví có số dư native lớn nhất
+    MostBalance,
+    /// Ví có ít giao dịch đang chờ nhất (nonce pending - nonce đã mine)
+    LowestPendingNonce,
+}
+
+impl SelectionStrategy {
+    /// Chiến lược cần đọc số dư/nonce trên chain trước khi chọn
+    pub fn needs_chain_state(&self) -> bool {
+        matches!(self, Self::MostBalance | Self::LowestPendingNonce)
+    }
+}
+
+/// Nhóm ví (VD: "snipe-pool-A")
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletGroup {
+    pub name: String,
+    #[serde(default)]
+    pub strategy: SelectionStrategy,
+    pub members: Vec<Address>,
+}
+
+/// Trạng thái on-chain của một ví khi chọn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WalletChainState {
+    pub balance: U256,
+    pub pending_nonces: u64,
+}
+
+/// Lỗi khi chọn ví trong nhóm
+#[derive(Debug, thiserror::Error)]
+pub enum GroupError {
+    #[error("wallet group {0} does not exist")]
+    UnknownGroup(String),
+    #[error("wallet group {0} has no members")]
+    EmptyGroup(String),
+    #[error("all wallets in group {0} have pending transactions")]
+    AllWalletsBusy(String),
+}
+
+/// Trạng thái khóa và lịch sử dùng ví, dùng chung giữa các lease
+#[derive(Debug, Default)]
+struct LeaseState {
+    locked: HashSet<Address>,
+    /// Thứ tự lần dùng gần nhất (số tăng dần)
+    last_used: HashMap<Address, u64>,
+    tick: u64,
+    cursors: HashMap<String, usize>,
+}
+
+/// Quyền dùng một ví của nhóm; ví bị khóa cho đến khi lease bị drop
+/// (giữ lease đến khi giao dịch có receipt)
+#[derive(Debug)]
+pub struct WalletLease {
+    address: Address,
+    group: String,
+    state: Arc<Mutex<LeaseState>>,
+}
+
+impl WalletLease {
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+}
+
+impl Drop for WalletLease {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.locked.remove(&self.address);
+        }
+        debug!("Released wallet {:?} of group {}", self.address, self.group);
+    }
+}
+
+/// Danh sách nhóm ví (lưu file) và bộ chọn ví xoay vòng cho giao dịch
+#[derive(Debug)]
+pub struct WalletGroups {
+    path: PathBuf,
+    groups: BTreeMap<String, WalletGroup>,
+    state: Arc<Mutex<LeaseState>>,
+}
+
+impl WalletGroups {
+    /// Nạp nhóm ví từ thư mục ví
+    pub fn open(dir: &Path) -> Result<Self> {
+        let path = dir.join(GROUPS_FILE);
+        let groups = if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Cannot read {:?}", path))?;
+            serde_json::from_str(&content).with_context(|| format!("Invalid wallet group file {:?}", path))?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self { path, groups, state: Arc::new(Mutex::new(LeaseState::default())) })
+    }
+
+    pub fn list(&self) -> Vec<WalletGroup> {
+        self.groups.values().cloned().collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&WalletGroup> {
+        self.groups.get(name)
+    }
+
+    /// Tạo hoặc thay thế nhóm
+    pub fn upsert(&mut self, mut group: WalletGroup) -> Result<()> {
+        let mut seen = HashSet::new();
+        group.members.retain(|member| seen.insert(*member));
+        self.groups.insert(group.name.clone(), group);
+        self.save()
+    }
+
+    /// Thêm ví vào nhóm; nhóm chưa có thì tạo với chiến lược mặc định
+    pub fn add_member(&mut self, name: &str, address: Address) -> Result<()> {
+        let group = self.groups.entry(name.to_string()).or_insert_with(|| WalletGroup {
+            name: name.to_string(),
+            strategy: SelectionStrategy::default(),
+            members: Vec::new(),
+        });
+        if !group.members.contains(&address) {
+            group.members.push(address);
+        }
+        self.save()
+    }
+
+    pub fn remove_member(&mut self, name: &str, address: Address) -> Result<bool> {
+        let removed = match self.groups.get_mut(name) {
+            Some(group) => {
+                let before = group.members.len();
+                group.members.retain(|member| *member != address);
+                group.members.len() != before
+            }
+            None => false,
+        };
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Gỡ ví khỏi mọi nhóm (khi ví bị xóa)
+    pub fn remove_wallet(&mut self, address: Address) -> Result<()> {
+        let mut changed = false;
+        for group in self.groups.values_mut() {
+            let before = group.members.len();
+            group.members.retain(|member| *member != address);
+            changed |= group.members.len() != before;
+        }
+        if changed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    pub fn remove_group(&mut self, name: &str) -> Result<bool> {
+        let removed = self.groups.remove(name).is_some();
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Các nhóm có chứa ví
+    pub fn groups_of(&self, address: Address) -> Vec<String> {
+        self.groups.values()
+            .filter(|group| group.members.contains(&address))
+            .map(|group| group.name.clone())
+            .collect()
+    }
+
+    /// Ví đang có giao dịch chờ (đang bị lease giữ)
+    pub fn is_locked(&self, address: Address) -> bool {
+        self.state.lock().unwrap().locked.contains(&address)
+    }
+
+    /// Thành viên chưa bị khóa của nhóm
+    pub fn available_members(&self, name: &str) -> Result<Vec<Address>, GroupError> {
+        let group = self.groups.get(name).ok_or_else(|| GroupError::UnknownGroup(name.to_string()))?;
+        let state = self.state.lock().unwrap();
+        Ok(group.members.iter().copied().filter(|member| !state.locked.contains(member)).collect())
+    }
+
+    /// Chọn và khóa một ví của nhóm theo chiến lược; `chain_state` chỉ cần cho
+    /// chiến lược dựa trên số dư/nonce (ví thiếu trạng thái coi như số dư 0)
+    pub fn acquire(
+        &self,
+        name: &str,
+        chain_state: &HashMap<Address, WalletChainState>,
+    ) -> Result<WalletLease, GroupError> {
+        let group = self.groups.get(name).ok_or_else(|| GroupError::UnknownGroup(name.to_string()))?;
+        if group.members.is_empty() {
+            return Err(GroupError::EmptyGroup(name.to_string()));
+        }
+
+        // Chọn và khóa trong cùng một lần giữ lock để giao dịch song song không trùng ví
+        let mut state = self.state.lock().unwrap();
+        let available: Vec<(usize, Address)> = group.members.iter().copied().enumerate()
+            .filter(|(_, member)| !state.locked.contains(member))
+            .collect();
+        let last_used = |address: &Address| state.last_used.get(address).copied().unwrap_or(0);
+        let chain = |address: &Address| chain_state.get(address).copied().unwrap_or_default();
+
+        let selected = match group.strategy {
+            SelectionStrategy::RoundRobin => {
+                let cursor = state.cursors.get(name).copied().unwrap_or(0) % group.members.len();
+                available.iter()
+                    .min_by_key(|(index, _)| (index + group.members.len() - cursor) % group.members.len())
+                    .copied()
+            }
+            SelectionStrategy::LeastRecentlyUsed => available.iter()
+                .min_by_key(|(index, address)| (last_used(address), *index))
+                .copied(),
+            SelectionStrategy::MostBalance => available.iter()
+                .max_by(|(a_index, a), (b_index, b)| chain(a).balance.cmp(&chain(b).balance).then(b_index.cmp(a_index)))
+                .copied(),
+            SelectionStrategy::LowestPendingNonce => available.iter()
+                .min_by_key(|(index, address)| (chain(address).pending_nonces, last_used(address), *index))
+                .copied(),
+        };
+        let (index, address) = selected.ok_or_else(|| GroupError::AllWalletsBusy(name.to_string()))?;
+
+        state.tick += 1;
+        let tick = state.tick;
+        state.last_used.insert(address, tick);
+        state.cursors.insert(name.to_string(), index + 1);
+        state.locked.insert(address);
+        debug!("Selected wallet {:?} from group {} ({:?})", address, name, group.strategy);
+
+        Ok(WalletLease { address, group: name.to_string(), state: self.state.clone() })
+    }
+
+    fn save(&self) -> Result<()> {
+        let json = serde_json::to_vec_pretty(&self.groups)?;
+        write_file_atomic(&self.path, &json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members() -> Vec<Address> {
+        (1..=3u8).map(Address::repeat_byte).collect()
+    }
+
+    fn pool(strategy: SelectionStrategy) -> (WalletGroups, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let mut groups = WalletGroups::open(dir.path()).unwrap();
+        groups.upsert(WalletGroup { name: "snipe-pool-A".to_string(), strategy, members: members() }).unwrap();
+        (groups, dir)
+    }
+
+    #[test]
+    fn test_round_robin_skips_locked_wallets_until_released() {
+        let (groups, _dir) = pool(SelectionStrategy::RoundRobin);
+        let no_state = HashMap::new();
+
+        let first = groups.acquire("snipe-pool-A", &no_state).unwrap();
+        let second = groups.acquire("snipe-pool-A", &no_state).unwrap();
+        let third = groups.acquire("snipe-pool-A", &no_state).unwrap();
+        assert_eq!([first.address(), second.address(), third.address()].to_vec(), members());
+        assert!(groups.is_locked(second.address()));
+        assert!(matches!(groups.acquire("snipe-pool-A", &no_state), Err(GroupError::AllWalletsBusy(_))));
+
+        // Giao dịch của ví thứ hai xong thì ví đó được chọn lại
+        drop(second);
+        assert_eq!(groups.acquire("snipe-pool-A", &no_state).unwrap().address(), members()[1]);
+        assert!(matches!(groups.acquire("missing", &no_state), Err(GroupError::UnknownGroup(_))));
+    }
+
+    #[test]
+    fn test_balance_nonce_and_lru_strategies() {
+        let wallets = members();
+        let chain_state: HashMap<Address, WalletChainState> = [
+            (wallets[0], WalletChainState { balance: U256::from(5u64), pending_nonces: 2 }),
+            (wallets[1], WalletChainState { balance: U256::from(9u64), pending_nonces: 1 }),
+            (wallets[2], WalletChainState { balance: U256::from(1u64), pending_nonces: 0 }),
+        ].into_iter().collect();
+
+        let (groups, _dir) = pool(SelectionStrategy::MostBalance);
+        let richest = groups.acquire("snipe-pool-A", &chain_state).unwrap();
+        assert_eq!(richest.address(), wallets[1]);
+        assert_eq!(groups.acquire("snipe-pool-A", &chain_state).unwrap().address(), wallets[0]);
+
+        let (groups, _dir) = pool(SelectionStrategy::LowestPendingNonce);
+        assert_eq!(groups.acquire("snipe-pool-A", &chain_state).unwrap().address(), wallets[2]);
+
+        let (groups, _dir) = pool(SelectionStrategy::LeastRecentlyUsed);
+        drop(groups.acquire("snipe-pool-A", &HashMap::new()).unwrap());
+        drop(groups.acquire("snipe-pool-A", &HashMap::new()).unwrap());
+        // Ví thứ ba chưa dùng lần nào, sau đó quay về ví dùng lâu nhất
+        assert_eq!(groups.acquire("snipe-pool-A", &HashMap::new()).unwrap().address(), wallets[2]);
+        assert_eq!(groups.acquire("snipe-pool-A", &HashMap::new()).unwrap().address(), wallets[0]);
+    }
+
+    #[test]
+    fn test_membership_persisted() {
+        let (mut groups, dir) = pool(SelectionStrategy::LeastRecentlyUsed);
+        let extra = Address::repeat_byte(0x09);
+        groups.add_member("snipe-pool-B", extra).unwrap();
+        groups.add_member("snipe-pool-A", extra).unwrap();
+        groups.remove_wallet(members()[0]).unwrap();
+
+        let reopened = WalletGroups::open(dir.path()).unwrap();
+        assert_eq!(reopened.groups_of(extra), vec!["snipe-pool-A".to_string(), "snipe-pool-B".to_string()]);
+        assert!(reopened.groups_of(members()[0]).is_empty());
+        assert_eq!(reopened.get("snipe-pool-A").unwrap().strategy, SelectionStrategy::LeastRecentlyUsed);
+    }
+}
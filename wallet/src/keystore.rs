@@ -0,0 +1,473 @@
+// External imports
+use ethers::{
+    prelude::LocalWallet,
+    signers::Signer,
+    types::Address,
+    utils::keccak256,
+};
+
+// Standard library imports
+use std::fs;
+use std::path::Path;
+
+// Third party imports
+use aes::Aes128;
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use hmac::Hmac;
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+/// Phiên bản định dạng Web3 Secret Storage được hỗ trợ
+pub const KEYSTORE_VERSION: u8 = 3;
+
+/// Thuật toán mã hóa duy nhất của định dạng V3
+const KEYSTORE_CIPHER: &str = "aes-128-ctr";
+
+/// PRF duy nhất được geth/MetaMask dùng cho pbkdf2
+const PBKDF2_PRF: &str = "hmac-sha256";
+
+/// Độ dài khóa dẫn xuất: 16 byte đầu cho AES, 16 byte sau cho MAC
+const DERIVED_KEY_LEN: usize = 32;
+
+/// Trần tham số scrypt khi import: tệp lạ không được bắt tiến trình cấp phát hàng chục GB
+/// hay chạy KDF hàng giờ. N = 2^20 với r = 8 cần 1 GiB, gấp 4 lần mức "standard" của geth
+const MAX_SCRYPT_N: u64 = 1 << 20;
+const MAX_SCRYPT_MEMORY: u64 = 1 << 30;
+const MAX_SCRYPT_P: u32 = 16;
+
+/// Trần số vòng pbkdf2 khi import, gấp 10 lần mức mặc định 1_000_000 của MetaMask
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+
+/// Tham số KDF khi tạo keystore mới
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kdf", rename_all = "lowercase")]
+pub enum KeystoreKdf {
+    /// scrypt với N = 2^log_n
+    Scrypt { log_n: u8, r: u32, p: u32 },
+    /// PBKDF2-HMAC-SHA256
+    Pbkdf2 { iterations: u32 },
+}
+
+impl KeystoreKdf {
+    /// Tham số chuẩn của geth (N = 2^18, r = 8, p = 1)
+    pub fn standard() -> Self {
+        KeystoreKdf::Scrypt { log_n: 18, r: 8, p: 1 }
+    }
+
+    /// Tham số nhẹ của geth (N = 2^12, r = 8, p = 6), chỉ dùng cho môi trường thử nghiệm
+    pub fn light() -> Self {
+        KeystoreKdf::Scrypt { log_n: 12, r: 8, p: 6 }
+    }
+}
+
+impl Default for KeystoreKdf {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Tệp keystore JSON theo chuẩn Web3 Secret Storage V3 (geth, MetaMask, MyEtherWallet)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreV3 {
+    /// Địa chỉ hex chữ thường không có tiền tố 0x (một số ví bỏ trống trường này)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// MyEtherWallet dùng khóa "Crypto"
+    #[serde(alias = "Crypto")]
+    pub crypto: KeystoreCrypto,
+    pub id: String,
+    pub version: u8,
+}
+
+/// Phần mã hóa của keystore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreCrypto {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    #[serde(with = "hex_bytes")]
+    pub ciphertext: Vec<u8>,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+    #[serde(with = "hex_bytes")]
+    pub mac: Vec<u8>,
+}
+
+/// Tham số của cipher aes-128-ctr
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CipherParams {
+    #[serde(with = "hex_bytes")]
+    pub iv: Vec<u8>,
+}
+
+/// Tham số KDF được lưu trong tệp keystore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KdfParams {
+    Scrypt {
+        dklen: usize,
+        n: u64,
+        r: u32,
+        p: u32,
+        #[serde(with = "hex_bytes")]
+        salt: Vec<u8>,
+    },
+    Pbkdf2 {
+        c: u32,
+        dklen: usize,
+        prf: String,
+        #[serde(with = "hex_bytes")]
+        salt: Vec<u8>,
+    },
+}
+
+impl KeystoreV3 {
+    /// Mã hóa private key thành keystore V3 với salt và IV ngẫu nhiên cho từng tệp
+    pub fn encrypt(private_key: &[u8], password: &str, kdf: &KeystoreKdf) -> Result<Self> {
+        let wallet = LocalWallet::from_bytes(private_key)
+            .map_err(|e| anyhow!("Private key không hợp lệ: {}", e))?;
+
+        let mut keystore = Self::encrypt_secret(private_key, password, kdf)?;
+        keystore.address = Some(hex::encode(wallet.address()));
+        Ok(keystore)
+    }
+
+    /// Mã hóa secret bất kỳ (ví dụ secret key ed25519 của Solana) theo định dạng V3, không ghi địa chỉ
+    pub fn encrypt_secret(secret: &[u8], password: &str, kdf: &KeystoreKdf) -> Result<Self> {
+        let mut salt = [0u8; 32];
+        OsRng.fill_bytes(&mut salt);
+        let mut iv = [0u8; 16];
+        OsRng.fill_bytes(&mut iv);
+
+        let kdfparams = match kdf {
+            KeystoreKdf::Scrypt { log_n, r, p } => KdfParams::Scrypt {
+                dklen: DERIVED_KEY_LEN,
+                n: 1u64 << log_n,
+                r: *r,
+                p: *p,
+                salt: salt.to_vec(),
+            },
+            KeystoreKdf::Pbkdf2 { iterations } => KdfParams::Pbkdf2 {
+                c: *iterations,
+                dklen: DERIVED_KEY_LEN,
+                prf: PBKDF2_PRF.to_string(),
+                salt: salt.to_vec(),
+            },
+        };
+        let derived_key = kdfparams.derive_key(password)?;
+
+        let mut ciphertext = secret.to_vec();
+        Aes128Ctr::new(derived_key[..16].into(), iv[..].into()).apply_keystream(&mut ciphertext);
+        let mac = keystore_mac(&derived_key, &ciphertext);
+
+        Ok(Self {
+            address: None,
+            crypto: KeystoreCrypto {
+                cipher: KEYSTORE_CIPHER.to_string(),
+                cipherparams: CipherParams { iv: iv.to_vec() },
+                ciphertext,
+                kdf: kdfparams.name().to_string(),
+                kdfparams,
+                mac: mac.to_vec(),
+            },
+            id: uuid::Uuid::new_v4().to_string(),
+            version: KEYSTORE_VERSION,
+        })
+    }
+
+    /// Giải mã private key, kiểm tra MAC trước khi giải mã
+    pub fn decrypt(&self, password: &str) -> Result<Zeroizing<Vec<u8>>> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(anyhow!("Phiên bản keystore không được hỗ trợ: {}", self.version));
+        }
+        if !self.crypto.cipher.eq_ignore_ascii_case(KEYSTORE_CIPHER) {
+            return Err(anyhow!("Cipher keystore không được hỗ trợ: {}", self.crypto.cipher));
+        }
+        if self.crypto.cipherparams.iv.len() != 16 {
+            return Err(anyhow!("IV keystore phải dài 16 byte"));
+        }
+        if self.crypto.kdfparams.name() != self.crypto.kdf.to_ascii_lowercase() {
+            return Err(anyhow!("Tham số KDF không khớp với kdf \"{}\"", self.crypto.kdf));
+        }
+
+        let derived_key = self.crypto.kdfparams.derive_key(password)?;
+        let mac = keystore_mac(&derived_key, &self.crypto.ciphertext);
+        if !constant_time_eq(&mac, &self.crypto.mac) {
+            return Err(anyhow!("Mật khẩu keystore không đúng (MAC không khớp)"));
+        }
+
+        let mut private_key = Zeroizing::new(self.crypto.ciphertext.clone());
+        Aes128Ctr::new(derived_key[..16].into(), self.crypto.cipherparams.iv[..].into())
+            .apply_keystream(&mut private_key);
+        Ok(private_key)
+    }
+
+    /// Giải mã thành LocalWallet và đối chiếu với địa chỉ ghi trong tệp (nếu có)
+    pub fn decrypt_wallet(&self, password: &str) -> Result<LocalWallet> {
+        let private_key = self.decrypt(password)?;
+        let wallet = LocalWallet::from_bytes(&private_key)
+            .map_err(|e| anyhow!("Private key trong keystore không hợp lệ: {}", e))?;
+
+        if let Some(address) = self.address()? {
+            if address != wallet.address() {
+                return Err(anyhow!(
+                    "Địa chỉ keystore {:?} không khớp với private key {:?}",
+                    address, wallet.address()
+                ));
+            }
+        }
+        Ok(wallet)
+    }
+
+    /// Địa chỉ ghi trong tệp keystore
+    pub fn address(&self) -> Result<Option<Address>> {
+        match self.address.as_deref().filter(|address| !address.is_empty()) {
+            Some(address) => {
+                let address = address.strip_prefix("0x").unwrap_or(address);
+                let bytes = hex::decode(address)
+                    .with_context(|| format!("Địa chỉ keystore không hợp lệ: {}", address))?;
+                if bytes.len() != 20 {
+                    return Err(anyhow!("Địa chỉ keystore không hợp lệ: {}", address));
+                }
+                Ok(Some(Address::from_slice(&bytes)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Đọc keystore từ chuỗi JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Tệp keystore V3 không hợp lệ")
+    }
+
+    /// Xuất keystore thành chuỗi JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Đọc keystore từ tệp
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Không đọc được keystore {:?}", path))?;
+        Self::from_json(&json)
+    }
+
+    /// Tên tệp theo quy ước thư mục keystore của geth
+    pub fn geth_file_name(&self) -> String {
+        format!(
+            "UTC--{}--{}",
+            Utc::now().format("%Y-%m-%dT%H-%M-%S%.9fZ"),
+            self.address.clone().unwrap_or_else(|| self.id.clone())
+        )
+    }
+}
+
+impl KdfParams {
+    /// Tên KDF tương ứng với trường "kdf"
+    fn name(&self) -> &'static str {
+        match self {
+            KdfParams::Scrypt { .. } => "scrypt",
+            KdfParams::Pbkdf2 { .. } => "pbkdf2",
+        }
+    }
+
+    /// Dẫn xuất khóa từ mật khẩu theo tham số trong tệp
+    fn derive_key(&self, password: &str) -> Result<Zeroizing<Vec<u8>>> {
+        match self {
+            KdfParams::Scrypt { dklen, n, r, p, salt } => {
+                if *dklen != DERIVED_KEY_LEN {
+                    return Err(anyhow!("dklen keystore phải bằng {} byte: {}", DERIVED_KEY_LEN, dklen));
+                }
+                if !n.is_power_of_two() || *n < 2 {
+                    return Err(anyhow!("Tham số scrypt n phải là lũy thừa của 2: {}", n));
+                }
+                if *n > MAX_SCRYPT_N || *p > MAX_SCRYPT_P || 128 * n * u64::from(*r) > MAX_SCRYPT_MEMORY {
+                    return Err(anyhow!(
+                        "Tham số scrypt vượt trần cho phép (n={}, r={}, p={}; tối đa n={}, p={}, bộ nhớ {} byte)",
+                        n, r, p, MAX_SCRYPT_N, MAX_SCRYPT_P, MAX_SCRYPT_MEMORY
+                    ));
+                }
+                let params = scrypt::Params::new(n.trailing_zeros() as u8, *r, *p)
+                    .map_err(|e| anyhow!("Tham số scrypt không hợp lệ: {}", e))?;
+                let mut key = Zeroizing::new(vec![0u8; *dklen]);
+                scrypt::scrypt(password.as_bytes(), salt, &params, &mut key)
+                    .map_err(|e| anyhow!("Không dẫn xuất được khóa scrypt: {}", e))?;
+                Ok(key)
+            }
+            KdfParams::Pbkdf2 { c, dklen, prf, salt } => {
+                if *dklen != DERIVED_KEY_LEN {
+                    return Err(anyhow!("dklen keystore phải bằng {} byte: {}", DERIVED_KEY_LEN, dklen));
+                }
+                if prf != PBKDF2_PRF {
+                    return Err(anyhow!("PRF pbkdf2 không được hỗ trợ: {}", prf));
+                }
+                if *c > MAX_PBKDF2_ITERATIONS {
+                    return Err(anyhow!(
+                        "Số vòng pbkdf2 vượt trần cho phép: {} (tối đa {})",
+                        c, MAX_PBKDF2_ITERATIONS
+                    ));
+                }
+                let mut key = Zeroizing::new(vec![0u8; *dklen]);
+                pbkdf2::pbkdf2::<Hmac<Sha256>>(password.as_bytes(), salt, *c, &mut key);
+                Ok(key)
+            }
+        }
+    }
+}
+
+/// MAC = keccak256(derived_key[16..32] ++ ciphertext)
+fn keystore_mac(derived_key: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(16 + ciphertext.len());
+    data.extend_from_slice(&derived_key[16..32]);
+    data.extend_from_slice(ciphertext);
+    keccak256(data)
+}
+
+/// So sánh thời gian hằng để không lộ vị trí byte sai của MAC
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Serde cho trường hex (chấp nhận có hoặc không có tiền tố 0x)
+pub(crate) mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        hex::decode(value.strip_prefix("0x").unwrap_or(&value)).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Private key và mật khẩu của các vector thử trong đặc tả Web3 Secret Storage
+    const SPEC_PRIVATE_KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+    const SPEC_PASSWORD: &str = "testpassword";
+
+    const SPEC_PBKDF2: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": "6087dab2f9fdbbfaddc31a909735c1e6" },
+            "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+            "kdf": "pbkdf2",
+            "kdfparams": {
+                "c": 262144,
+                "dklen": 32,
+                "prf": "hmac-sha256",
+                "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+            },
+            "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    }"#;
+
+    /// Keystore scrypt do Parity tạo (khóa "Crypto" viết hoa), mật khẩu "123456789".
+    /// Vector scrypt trong đặc tả dùng r = 1 với N = 2^18, vi phạm ràng buộc N < 2^(16r) của RFC 7914
+    const PARITY_SCRYPT: &str = r#"{"version":3,"id":"3b330c3b-b0b3-4e39-b62e-c2041a98d673","address":"4c8ab9d3e938285776d6717d7319f6a9b1d809dd","Crypto":{"ciphertext":"bb3a6dbf21f0bf2b5eb0b43426590f16650acee9462ab710cca18781691a5739","cipherparams":{"iv":"6a533f77fc5cb8a752a16ec6a3200da1"},"cipher":"aes-128-ctr","kdf":"scrypt","kdfparams":{"dklen":32,"salt":"a58609853dec53c81feb165e346c700e714285771825bb4cbf87c4ea1996b682","n":8192,"r":8,"p":1},"mac":"a71edeb659ed628db13579ce9f75c80c9d386c1239b280548d9a0e58ad20d6c7"}}"#;
+
+    #[test]
+    fn test_known_vectors() {
+        let keystore = KeystoreV3::from_json(SPEC_PBKDF2).unwrap();
+        let private_key = keystore.decrypt(SPEC_PASSWORD).unwrap();
+        assert_eq!(hex::encode(&*private_key), SPEC_PRIVATE_KEY);
+        assert!(keystore.decrypt("wrongpassword").is_err());
+
+        let keystore = KeystoreV3::from_json(PARITY_SCRYPT).unwrap();
+        let wallet = keystore.decrypt_wallet("123456789").unwrap();
+        assert_eq!(keystore.address().unwrap(), Some(wallet.address()));
+        assert_eq!(
+            hex::encode(wallet.signer().to_bytes()),
+            "e19658a6a6d937bda75ea314db6892393274493b732b93aa21eb6eb2e38e928c"
+        );
+        assert!(keystore.decrypt("wrongpassword").is_err());
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let private_key = hex::decode(SPEC_PRIVATE_KEY).unwrap();
+        let expected = LocalWallet::from_bytes(&private_key).unwrap().address();
+
+        for kdf in [KeystoreKdf::light(), KeystoreKdf::Pbkdf2 { iterations: 1024 }] {
+            let keystore = KeystoreV3::encrypt(&private_key, SPEC_PASSWORD, &kdf).unwrap();
+            let parsed = KeystoreV3::from_json(&keystore.to_json().unwrap()).unwrap();
+            assert_eq!(parsed.address().unwrap(), Some(expected));
+            assert_eq!(parsed.decrypt_wallet(SPEC_PASSWORD).unwrap().address(), expected);
+            assert!(parsed.decrypt("wrongpassword").is_err());
+        }
+
+        // Salt và IV ngẫu nhiên cho từng tệp
+        let first = KeystoreV3::encrypt(&private_key, SPEC_PASSWORD, &KeystoreKdf::light()).unwrap();
+        let second = KeystoreV3::encrypt(&private_key, SPEC_PASSWORD, &KeystoreKdf::light()).unwrap();
+        assert_ne!(first.crypto.ciphertext, second.crypto.ciphertext);
+        assert_ne!(first.crypto.mac, second.crypto.mac);
+    }
+
+    #[test]
+    fn test_rejects_mismatched_address() {
+        let private_key = hex::decode(SPEC_PRIVATE_KEY).unwrap();
+        let mut keystore = KeystoreV3::encrypt(&private_key, SPEC_PASSWORD, &KeystoreKdf::light()).unwrap();
+        keystore.address = Some(hex::encode(Address::repeat_byte(0x11)));
+        assert!(keystore.decrypt_wallet(SPEC_PASSWORD).is_err());
+    }
+
+    #[test]
+    fn test_rejects_scrypt_params_above_ceiling() {
+        let private_key = hex::decode(SPEC_PRIVATE_KEY).unwrap();
+        let keystore = KeystoreV3::encrypt(&private_key, SPEC_PASSWORD, &KeystoreKdf::light()).unwrap();
+        let with_params = |n: u64, r: u32, p: u32| {
+            let mut keystore = keystore.clone();
+            if let KdfParams::Scrypt { n: kdf_n, r: kdf_r, p: kdf_p, .. } = &mut keystore.crypto.kdfparams {
+                (*kdf_n, *kdf_r, *kdf_p) = (n, r, p);
+            }
+            keystore
+        };
+
+        // Bị từ chối trước khi dẫn xuất khóa (nếu không, các lời gọi này cần hàng TB bộ nhớ)
+        for (n, r, p) in [(1 << 40, 8, 1), (1 << 20, 1 << 20, 1), (1 << 12, 8, 1 << 20)] {
+            let error = with_params(n, r, p).decrypt(SPEC_PASSWORD).unwrap_err();
+            assert!(error.to_string().contains("vượt trần"), "{}", error);
+        }
+        assert!(with_params(1 << 12, 8, 6).decrypt(SPEC_PASSWORD).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_pbkdf2_iterations_above_ceiling() {
+        let private_key = hex::decode(SPEC_PRIVATE_KEY).unwrap();
+        let mut keystore = KeystoreV3::encrypt(&private_key, SPEC_PASSWORD, &KeystoreKdf::Pbkdf2 { iterations: 1024 }).unwrap();
+        if let KdfParams::Pbkdf2 { c, .. } = &mut keystore.crypto.kdfparams {
+            *c = u32::MAX;
+        }
+
+        // Bị từ chối ngay, không chạy 4 tỷ vòng HMAC
+        let error = keystore.decrypt(SPEC_PASSWORD).unwrap_err();
+        assert!(error.to_string().contains("vượt trần"), "{}", error);
+    }
+
+    #[test]
+    fn test_rejects_dklen_other_than_32() {
+        let private_key = hex::decode(SPEC_PRIVATE_KEY).unwrap();
+        for kdf in [KeystoreKdf::light(), KeystoreKdf::Pbkdf2 { iterations: 1024 }] {
+            let keystore = KeystoreV3::encrypt(&private_key, SPEC_PASSWORD, &kdf).unwrap();
+            for len in [16, 64, 1 << 40] {
+                let mut keystore = keystore.clone();
+                match &mut keystore.crypto.kdfparams {
+                    KdfParams::Scrypt { dklen, .. } | KdfParams::Pbkdf2 { dklen, .. } => *dklen = len,
+                }
+                let error = keystore.decrypt(SPEC_PASSWORD).unwrap_err();
+                assert!(error.to_string().contains("dklen"), "{}", error);
+            }
+        }
+    }
+}
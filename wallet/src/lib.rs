@@ -1,17 +1,27 @@
 // Module exports
 mod wallet;
 mod secure_storage;
+pub mod keystore;
+pub mod envelope;
+pub mod signer;
+pub mod signer_server;
+pub mod policy;
+pub mod groups;
+pub mod derivation;
+pub mod backup;
 pub mod config;
 pub mod defi;
 pub mod mission;
 pub mod stake;
 pub mod farm;
+pub mod solana;
 
 // Re-export các component chính
 pub use wallet::{
     WalletManager, 
     WalletManagerConfig, 
     WalletClientExt,
+    ManagedSigner,
 };
 
 pub use secure_storage::{
@@ -19,8 +29,28 @@ pub use secure_storage::{
     SecureWalletStorage, 
     WalletInfo, 
     EncryptedData,
-    SafeWalletView
+    SafeWalletView,
+    KeystoreMigrationReport,
 };
 
+pub use keystore::{KeystoreV3, KeystoreKdf};
+pub use envelope::{ensure_operator_passphrase, MasterKeyParams, DEFAULT_ENCRYPTION_SEEDS};
+pub use signer::{
+    WalletSigner,
+    RemoteSigner,
+    RemoteSignerConfig,
+    RemoteSignerApi,
+    RemoteTransport,
+    SignerError,
+    Erc2612Permit,
+};
+pub use signer_server::SignerServer;
+pub use policy::{SpendingPolicy, PolicyViolation, PolicyEngine, AuditEntry, TokenApproval};
+pub use groups::{WalletGroup, WalletGroups, WalletLease, WalletChainState, SelectionStrategy, GroupError};
+pub use derivation::{DerivationScheme, DEFAULT_GAP_LIMIT};
+pub use backup::{BackupExport, BackupKey, BackupOptions, BackupShare, BackupSummary, RestoreReport, ShareSplit, verify_backup};
+
+pub use solana::{EncryptedSolanaKeypair, SolanaKeyStore, SolanaKeypair};
+
 // Re-export ABI từ blockchain
 pub use diamond_blockchain::abi; 
\ No newline at end of file
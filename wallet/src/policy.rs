@@ -0,0 +1,608 @@
+// External imports
+use ethers::types::{transaction::eip2718::TypedTransaction, Address, NameOrAddress, H256, U256};
+
+// Standard library imports
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+// Third party imports
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+// Internal imports
+use crate::secure_storage::write_file_atomic;
+
+/// Tên file lưu chính sách chi tiêu trong thư mục ví
+pub const POLICY_FILE: &str = "policies.json";
+/// Tên file audit trail (JSON lines, chỉ ghi thêm)
+pub const AUDIT_FILE: &str = "policy_audit.jsonl";
+
+/// Selector approve(address,uint256)
+const APPROVE_SELECTOR: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+/// Selector increaseAllowance(address,uint256)
+const INCREASE_ALLOWANCE_SELECTOR: [u8; 4] = [0x39, 0x50, 0x93, 0x51];
+/// Selector setApprovalForAll(address,bool)
+const SET_APPROVAL_FOR_ALL_SELECTOR: [u8; 4] = [0xa2, 0x2c, 0xb4, 0x65];
+
+/// Chính sách chi tiêu gắn với một ví, kiểm tra ngay trước khi ký.
+/// Trường để trống nghĩa là không giới hạn theo tiêu chí đó
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpendingPolicy {
+    /// Danh sách hợp đồng/địa chỉ được phép gọi và được phép nhận approve (router, token)
+    #[serde(default)]
+    pub allowed_contracts: Option<Vec<Address>>,
+    /// Giá trị native tối đa mỗi giao dịch (wei)
+    #[serde(default)]
+    pub max_value_per_tx: Option<U256>,
+    /// Tổng giá trị native tối đa mỗi ngày (UTC) trên mỗi chain (wei)
+    #[serde(default)]
+    pub daily_limit_native: Option<U256>,
+    /// Tổng giá trị USD tối đa mỗi ngày (UTC) trên mọi chain
+    #[serde(default)]
+    pub daily_limit_usd: Option<f64>,
+    /// Số lượng approve tối đa cho một spender
+    #[serde(default)]
+    pub max_approval_amount: Option<U256>,
+    /// Cho phép approve không giới hạn (uint256 max, setApprovalForAll)
+    #[serde(default)]
+    pub allow_unlimited_approvals: bool,
+    /// Các chain được phép ký giao dịch
+    #[serde(default)]
+    pub allowed_chains: Option<Vec<u64>>,
+}
+
+impl SpendingPolicy {
+    fn check_chain(&self, chain_id: u64) -> Result<(), PolicyViolation> {
+        match &self.allowed_chains {
+            Some(chains) if !chains.contains(&chain_id) => Err(PolicyViolation::ChainNotAllowed(chain_id)),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_destination(&self, to: Option<Address>) -> Result<(), PolicyViolation> {
+        match (&self.allowed_contracts, to) {
+            (Some(whitelist), Some(to)) if !whitelist.contains(&to) => Err(PolicyViolation::DestinationNotAllowed(to)),
+            (Some(_), None) => Err(PolicyViolation::ContractCreationNotAllowed),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_approval(&self, approval: &TokenApproval) -> Result<(), PolicyViolation> {
+        if let Some(whitelist) = &self.allowed_contracts {
+            if !whitelist.contains(&approval.spender) {
+                return Err(PolicyViolation::SpenderNotAllowed(approval.spender));
+            }
+        }
+        if approval.is_unlimited() {
+            if !self.allow_unlimited_approvals {
+                return Err(PolicyViolation::UnlimitedApproval(approval.spender));
+            }
+        } else if let Some(limit) = self.max_approval_amount {
+            if approval.amount > limit {
+                return Err(PolicyViolation::ApprovalExceedsLimit { amount: approval.amount, limit });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Vi phạm chính sách chi tiêu
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PolicyViolation {
+    #[error("Chain {0} is not allowed for this wallet")]
+    ChainNotAllowed(u64),
+
+    #[error("Destination {0:?} is not in the contract whitelist")]
+    DestinationNotAllowed(Address),
+
+    #[error("Contract deployment is not allowed when a contract whitelist is set")]
+    ContractCreationNotAllowed,
+
+    #[error("Destination must be a resolved address, got ENS name {0}")]
+    UnresolvedDestination(String),
+
+    #[error("Transaction value {value} exceeds per-transaction limit {limit}")]
+    ValueExceedsLimit { value: U256, limit: U256 },
+
+    #[error("Daily native limit {limit} on chain {chain_id} exceeded: spent {spent}, requested {value}")]
+    DailyLimitExceeded { chain_id: u64, spent: U256, value: U256, limit: U256 },
+
+    #[error("Daily USD limit {limit:.2} exceeded: spent {spent:.2}, requested {value:.2}")]
+    DailyUsdLimitExceeded { spent: f64, value: f64, limit: f64 },
+
+    #[error("No native USD price for chain {0}; cannot enforce the daily USD limit")]
+    UsdPriceUnavailable(u64),
+
+    #[error("Approval spender {0:?} is not in the contract whitelist")]
+    SpenderNotAllowed(Address),
+
+    #[error("Unlimited approval to {0:?} is not allowed")]
+    UnlimitedApproval(Address),
+
+    #[error("Approval amount {amount} exceeds limit {limit}")]
+    ApprovalExceedsLimit { amount: U256, limit: U256 },
+
+    #[error("Audit trail unavailable: {0}")]
+    AuditUnavailable(String),
+}
+
+/// Approve token được giải mã từ calldata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenApproval {
+    pub spender: Address,
+    pub amount: U256,
+}
+
+impl TokenApproval {
+    /// Giải mã approve, increaseAllowance và setApprovalForAll
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < 4 + 64 {
+            return None;
+        }
+        let selector = &data[..4];
+        let spender = Address::from_slice(&data[16..36]);
+        let word = U256::from_big_endian(&data[36..68]);
+
+        if selector == APPROVE_SELECTOR || selector == INCREASE_ALLOWANCE_SELECTOR {
+            Some(Self { spender, amount: word })
+        } else if selector == SET_APPROVAL_FOR_ALL_SELECTOR {
+            // setApprovalForAll(false) là thu hồi quyền
+            (!word.is_zero()).then_some(Self { spender, amount: U256::MAX })
+        } else {
+            None
+        }
+    }
+
+    /// Coi là không giới hạn từ uint160 max trở lên (Permit2, uint256 max)
+    pub fn is_unlimited(&self) -> bool {
+        self.amount >= U256::MAX >> 96
+    }
+}
+
+/// Một dòng audit cho mỗi quyết định ký
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub wallet: Address,
+    pub chain_id: u64,
+    pub to: Option<Address>,
+    pub value: U256,
+    #[serde(default)]
+    pub usd_value: Option<f64>,
+    pub allowed: bool,
+    #[serde(default)]
+    pub violation: Option<String>,
+    /// Hash EIP-191 của thông điệp khi chữ ký là sign_message thay vì giao dịch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_hash: Option<H256>,
+}
+
+/// Chi tiêu đã ghi nhận trong ngày hiện tại
+#[derive(Debug)]
+struct SpendLedger {
+    day: NaiveDate,
+    native: HashMap<(Address, u64), U256>,
+    usd: HashMap<Address, f64>,
+}
+
+impl SpendLedger {
+    fn new(day: NaiveDate) -> Self {
+        Self { day, native: HashMap::new(), usd: HashMap::new() }
+    }
+
+    fn roll_to(&mut self, day: NaiveDate) {
+        if self.day != day {
+            *self = Self::new(day);
+        }
+    }
+
+    fn record(&mut self, entry: &AuditEntry) {
+        let native = self.native.entry((entry.wallet, entry.chain_id)).or_default();
+        *native = native.saturating_add(entry.value);
+        if let Some(usd) = entry.usd_value {
+            *self.usd.entry(entry.wallet).or_default() += usd;
+        }
+    }
+}
+
+/// Bộ kiểm tra chính sách: lưu chính sách theo ví, theo dõi chi tiêu ngày và ghi audit
+#[derive(Debug)]
+pub struct PolicyEngine {
+    policies: HashMap<Address, SpendingPolicy>,
+    default_policy: Option<SpendingPolicy>,
+    native_usd_prices: HashMap<u64, f64>,
+    ledger: SpendLedger,
+    policy_path: PathBuf,
+    audit_path: PathBuf,
+}
+
+impl PolicyEngine {
+    /// Mở chính sách trong thư mục ví và dựng lại chi tiêu hôm nay từ audit trail
+    pub fn open(dir: &Path, default_policy: Option<SpendingPolicy>) -> Result<Self> {
+        let policy_path = dir.join(POLICY_FILE);
+        let audit_path = dir.join(AUDIT_FILE);
+
+        let policies = if policy_path.exists() {
+            let content = std::fs::read_to_string(&policy_path)
+                .with_context(|| format!("Cannot read {:?}", policy_path))?;
+            serde_json::from_str(&content).with_context(|| format!("Invalid policy file {:?}", policy_path))?
+        } else {
+            HashMap::new()
+        };
+
+        let now = Utc::now();
+        let mut ledger = SpendLedger::new(now.date_naive());
+        if audit_path.exists() {
+            let file = std::fs::File::open(&audit_path)?;
+            for line in BufReader::new(file).lines() {
+                let line = line?;
+                match serde_json::from_str::<AuditEntry>(&line) {
+                    Ok(entry) if entry.allowed && entry.timestamp.date_naive() == ledger.day => ledger.record(&entry),
+                    Ok(_) => {}
+                    Err(e) => warn!("Skipping malformed audit entry: {}", e),
+                }
+            }
+        }
+
+        Ok(Self {
+            policies,
+            default_policy,
+            native_usd_prices: HashMap::new(),
+            ledger,
+            policy_path,
+            audit_path,
+        })
+    }
+
+    /// Chính sách áp dụng cho ví (riêng hoặc mặc định)
+    pub fn policy_for(&self, wallet: Address) -> Option<&SpendingPolicy> {
+        self.policies.get(&wallet).or(self.default_policy.as_ref())
+    }
+
+    /// Các chính sách riêng theo ví (không gồm chính sách mặc định)
+    pub fn policies(&self) -> &HashMap<Address, SpendingPolicy> {
+        &self.policies
+    }
+
+    /// Gắn chính sách cho ví và lưu xuống đĩa
+    pub fn set_policy(&mut self, wallet: Address, policy: SpendingPolicy) -> Result<()> {
+        self.policies.insert(wallet, policy);
+        self.save()
+    }
+
+    /// Gỡ chính sách riêng của ví (quay về chính sách mặc định)
+    pub fn remove_policy(&mut self, wallet: Address) -> Result<bool> {
+        let removed = self.policies.remove(&wallet).is_some();
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Cập nhật giá USD của đồng native trên chain, dùng cho giới hạn USD
+    pub fn set_native_usd_price(&mut self, chain_id: u64, price: f64) {
+        self.native_usd_prices.insert(chain_id, price);
+    }
+
+    /// Tổng native đã chi hôm nay của ví trên chain
+    pub fn spent_today(&self, wallet: Address, chain_id: u64) -> U256 {
+        self.ledger.native.get(&(wallet, chain_id)).copied().unwrap_or_default()
+    }
+
+    /// Kiểm tra giao dịch; nếu hợp lệ thì ghi nhận chi tiêu ngay (trước khi gửi, thiên về an toàn)
+    pub fn authorize(&mut self, wallet: Address, chain_id: u64, tx: &TypedTransaction) -> Result<(), PolicyViolation> {
+        self.authorize_at(wallet, chain_id, tx, Utc::now())
+    }
+
+    /// Kiểm tra approve ký ngoài giao dịch (EIP-2612 permit) theo cùng quy tắc approve
+    pub fn authorize_approval(
+        &mut self,
+        wallet: Address,
+        chain_id: u64,
+        token: Address,
+        approval: TokenApproval,
+    ) -> Result<(), PolicyViolation> {
+        let now = Utc::now();
+        let result = match self.policy_for(wallet) {
+            Some(policy) => policy.check_chain(chain_id)
+                .and_then(|_| policy.check_destination(Some(token)))
+                .and_then(|_| policy.check_approval(&approval)),
+            None => Ok(()),
+        };
+        match result {
+            Ok(()) => self.accept(wallet, chain_id, Some(token), U256::zero(), None, now),
+            Err(violation) => self.reject(wallet, chain_id, Some(token), U256::zero(), now, violation),
+        }
+    }
+
+    /// Kiểm tra chữ ký thông điệp (EIP-191, như header xác thực relay Flashbots): chỉ xét chain
+    /// được phép, không tính vào chi tiêu, nhưng luôn ghi audit kèm hash thông điệp
+    pub fn authorize_message(&mut self, wallet: Address, chain_id: u64, message_hash: H256) -> Result<(), PolicyViolation> {
+        let result = match self.policy_for(wallet) {
+            Some(policy) => policy.check_chain(chain_id),
+            None => Ok(()),
+        };
+        let entry = AuditEntry {
+            timestamp: Utc::now(),
+            wallet,
+            chain_id,
+            to: None,
+            value: U256::zero(),
+            usd_value: None,
+            allowed: result.is_ok(),
+            violation: result.as_ref().err().map(|violation| violation.to_string()),
+            message_hash: Some(message_hash),
+        };
+        match result {
+            // Không ghi được audit thì không ký
+            Ok(()) => self.append_audit(&entry).map_err(|e| PolicyViolation::AuditUnavailable(e.to_string())),
+            Err(violation) => {
+                warn!("Policy rejected message signature from {:?} on chain {}: {}", wallet, chain_id, violation);
+                if let Err(e) = self.append_audit(&entry) {
+                    error!("Failed to write policy audit entry: {}", e);
+                }
+                Err(violation)
+            }
+        }
+    }
+
+    pub(crate) fn authorize_at(
+        &mut self,
+        wallet: Address,
+        chain_id: u64,
+        tx: &TypedTransaction,
+        now: DateTime<Utc>,
+    ) -> Result<(), PolicyViolation> {
+        self.ledger.roll_to(now.date_naive());
+
+        let to = match tx.to() {
+            Some(NameOrAddress::Address(address)) => Some(*address),
+            Some(NameOrAddress::Name(name)) => {
+                return self.reject(wallet, chain_id, None, U256::zero(), now,
+                    PolicyViolation::UnresolvedDestination(name.clone()));
+            }
+            None => None,
+        };
+        let value = tx.value().copied().unwrap_or_default();
+        let usd_value = self.native_usd_prices.get(&chain_id).map(|price| wei_to_native(value) * price);
+
+        let Some(policy) = self.policy_for(wallet) else {
+            return self.accept(wallet, chain_id, to, value, usd_value, now);
+        };
+        match self.evaluate(policy, wallet, chain_id, to, value, usd_value, tx) {
+            Ok(()) => self.accept(wallet, chain_id, to, value, usd_value, now),
+            Err(violation) => self.reject(wallet, chain_id, to, value, now, violation),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn evaluate(
+        &self,
+        policy: &SpendingPolicy,
+        wallet: Address,
+        chain_id: u64,
+        to: Option<Address>,
+        value: U256,
+        usd_value: Option<f64>,
+        tx: &TypedTransaction,
+    ) -> Result<(), PolicyViolation> {
+        policy.check_chain(chain_id)?;
+        policy.check_destination(to)?;
+        if let Some(approval) = tx.data().and_then(|data| TokenApproval::decode(data)) {
+            policy.check_approval(&approval)?;
+        }
+
+        if let Some(limit) = policy.max_value_per_tx {
+            if value > limit {
+                return Err(PolicyViolation::ValueExceedsLimit { value, limit });
+            }
+        }
+
+        if let Some(limit) = policy.daily_limit_native {
+            let spent = self.spent_today(wallet, chain_id);
+            if spent.saturating_add(value) > limit {
+                return Err(PolicyViolation::DailyLimitExceeded { chain_id, spent, value, limit });
+            }
+        }
+
+        if let Some(limit) = policy.daily_limit_usd {
+            if !value.is_zero() {
+                let value = usd_value.ok_or(PolicyViolation::UsdPriceUnavailable(chain_id))?;
+                let spent = self.ledger.usd.get(&wallet).copied().unwrap_or_default();
+                if spent + value > limit {
+                    return Err(PolicyViolation::DailyUsdLimitExceeded { spent, value, limit });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn accept(
+        &mut self,
+        wallet: Address,
+        chain_id: u64,
+        to: Option<Address>,
+        value: U256,
+        usd_value: Option<f64>,
+        now: DateTime<Utc>,
+    ) -> Result<(), PolicyViolation> {
+        let entry = AuditEntry { timestamp: now, wallet, chain_id, to, value, usd_value, allowed: true, violation: None, message_hash: None };
+        // Không ghi được audit thì không ký
+        self.append_audit(&entry).map_err(|e| PolicyViolation::AuditUnavailable(e.to_string()))?;
+        self.ledger.record(&entry);
+        Ok(())
+    }
+
+    fn reject(
+        &mut self,
+        wallet: Address,
+        chain_id: u64,
+        to: Option<Address>,
+        value: U256,
+        now: DateTime<Utc>,
+        violation: PolicyViolation,
+    ) -> Result<(), PolicyViolation> {
+        warn!("Policy rejected transaction from {:?} on chain {}: {}", wallet, chain_id, violation);
+        let entry = AuditEntry {
+            timestamp: now,
+            wallet,
+            chain_id,
+            to,
+            value,
+            usd_value: None,
+            allowed: false,
+            violation: Some(violation.to_string()),
+            message_hash: None,
+        };
+        if let Err(e) = self.append_audit(&entry) {
+            error!("Failed to write policy audit entry: {}", e);
+        }
+        Err(violation)
+    }
+
+    fn append_audit(&self, entry: &AuditEntry) -> Result<()> {
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&self.audit_path)
+            .with_context(|| format!("Cannot open audit trail {:?}", self.audit_path))?;
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        let json = serde_json::to_vec_pretty(&self.policies)?;
+        write_file_atomic(&self.policy_path, &json)
+    }
+}
+
+/// Đổi wei sang đơn vị native (18 chữ số thập phân)
+fn wei_to_native(value: U256) -> f64 {
+    ethers::utils::format_units(value, 18)
+        .ok()
+        .and_then(|s| s.parse::<f64>().ok())
+        .unwrap_or(f64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use ethers::types::{Bytes, TransactionRequest};
+
+    const ROUTER: Address = Address::repeat_byte(0x10);
+    const TOKEN: Address = Address::repeat_byte(0x20);
+    const WALLET: Address = Address::repeat_byte(0x01);
+
+    fn ether(n: u64) -> U256 {
+        U256::exp10(18) * n
+    }
+
+    fn approve(spender: Address, amount: U256) -> TypedTransaction {
+        let mut data = APPROVE_SELECTOR.to_vec();
+        data.extend_from_slice(&[0u8; 12]);
+        data.extend_from_slice(spender.as_bytes());
+        let mut word = [0u8; 32];
+        amount.to_big_endian(&mut word);
+        data.extend_from_slice(&word);
+        TransactionRequest::new().to(TOKEN).data(Bytes::from(data)).into()
+    }
+
+    fn whitelisted_policy() -> SpendingPolicy {
+        SpendingPolicy {
+            allowed_contracts: Some(vec![ROUTER, TOKEN]),
+            allowed_chains: Some(vec![1, 56]),
+            max_approval_amount: Some(ether(1_000)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_whitelist_chain_and_approval_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut engine = PolicyEngine::open(dir.path(), None).unwrap();
+        engine.set_policy(WALLET, whitelisted_policy()).unwrap();
+
+        let swap: TypedTransaction = TransactionRequest::new().to(ROUTER).value(1u64).into();
+        assert!(engine.authorize(WALLET, 1, &swap).is_ok());
+        assert_eq!(engine.authorize(WALLET, 137, &swap), Err(PolicyViolation::ChainNotAllowed(137)));
+
+        let drain: TypedTransaction = TransactionRequest::new().to(Address::repeat_byte(0x66)).into();
+        assert!(matches!(engine.authorize(WALLET, 1, &drain), Err(PolicyViolation::DestinationNotAllowed(_))));
+
+        assert!(engine.authorize(WALLET, 1, &approve(ROUTER, ether(10))).is_ok());
+        assert!(matches!(engine.authorize(WALLET, 1, &approve(ROUTER, U256::MAX)), Err(PolicyViolation::UnlimitedApproval(_))));
+        assert!(matches!(engine.authorize(WALLET, 1, &approve(ROUTER, ether(5_000))), Err(PolicyViolation::ApprovalExceedsLimit { .. })));
+        assert!(matches!(engine.authorize(WALLET, 1, &approve(Address::repeat_byte(0x66), ether(1))), Err(PolicyViolation::SpenderNotAllowed(_))));
+
+        // Ví không có chính sách riêng dùng chính sách mặc định
+        let engine = PolicyEngine::open(dir.path(), Some(SpendingPolicy::default())).unwrap();
+        assert_eq!(engine.policy_for(WALLET), Some(&whitelisted_policy()));
+        assert_eq!(engine.policy_for(Address::zero()), Some(&SpendingPolicy::default()));
+    }
+
+    #[test]
+    fn test_daily_limits_roll_over_and_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut engine = PolicyEngine::open(dir.path(), None).unwrap();
+        engine.set_policy(WALLET, SpendingPolicy {
+            max_value_per_tx: Some(ether(2)),
+            daily_limit_native: Some(ether(3)),
+            daily_limit_usd: Some(8_000.0),
+            ..Default::default()
+        }).unwrap();
+
+        let send = |n: u64| -> TypedTransaction { TransactionRequest::new().to(ROUTER).value(ether(n)).into() };
+        assert_eq!(engine.authorize(WALLET, 1, &send(1)), Err(PolicyViolation::UsdPriceUnavailable(1)));
+
+        engine.set_native_usd_price(1, 3_000.0);
+        assert!(matches!(engine.authorize(WALLET, 1, &send(5)), Err(PolicyViolation::ValueExceedsLimit { .. })));
+        assert!(engine.authorize(WALLET, 1, &send(2)).is_ok());
+        assert!(matches!(engine.authorize(WALLET, 1, &send(2)), Err(PolicyViolation::DailyLimitExceeded { .. })));
+        assert!(matches!(engine.authorize(WALLET, 1, &send(1)), Err(PolicyViolation::DailyUsdLimitExceeded { .. })));
+
+        // Khởi động lại vẫn nhớ chi tiêu hôm nay nhờ audit trail
+        let mut engine = PolicyEngine::open(dir.path(), None).unwrap();
+        assert_eq!(engine.spent_today(WALLET, 1), ether(2));
+        engine.set_native_usd_price(1, 3_000.0);
+        assert!(matches!(engine.authorize(WALLET, 1, &send(2)), Err(PolicyViolation::DailyLimitExceeded { .. })));
+
+        // Sang ngày mới thì hạn mức được làm mới
+        let tomorrow = Utc.from_utc_datetime(&(Utc::now().date_naive().succ_opt().unwrap().and_hms_opt(0, 0, 1).unwrap()));
+        assert!(engine.authorize_at(WALLET, 1, &send(2), tomorrow).is_ok());
+
+        let audit = std::fs::read_to_string(dir.path().join(AUDIT_FILE)).unwrap();
+        let entries: Vec<AuditEntry> = audit.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(entries.iter().filter(|e| e.allowed).count(), 2);
+        assert!(entries.iter().any(|e| e.violation.as_deref().is_some_and(|v| v.contains("Daily USD limit"))));
+    }
+
+    #[test]
+    fn test_message_signatures_are_chain_checked_and_audited() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut engine = PolicyEngine::open(dir.path(), None).unwrap();
+        engine.set_policy(WALLET, whitelisted_policy()).unwrap();
+
+        let hash = H256::repeat_byte(0xab);
+        assert!(engine.authorize_message(WALLET, 1, hash).is_ok());
+        assert_eq!(engine.authorize_message(WALLET, 137, hash), Err(PolicyViolation::ChainNotAllowed(137)));
+        assert_eq!(engine.spent_today(WALLET, 1), U256::zero());
+
+        let audit = std::fs::read_to_string(dir.path().join(AUDIT_FILE)).unwrap();
+        let entries: Vec<AuditEntry> = audit.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.message_hash == Some(hash)));
+        assert_eq!(entries.iter().map(|e| e.allowed).collect::<Vec<_>>(), vec![true, false]);
+    }
+}
@@ -3,39 +3,24 @@ use aes_gcm::{
     Aes256Gcm, Nonce,
 };
 use sha2::{Sha256, Digest};
-use zeroize::{Zeroize, ZeroizeOnDrop};
-use rand::{rngs::OsRng, RngCore};
-use anyhow::{Result, Context, anyhow};
-use std::fs::{self, File};
-use std::io::{Read, Write};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+use anyhow::{Result, Context, anyhow, bail};
+use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
 use chrono::{DateTime, Utc, Duration};
-use aes_gcm::{
-    aead::{Aead, KeyInit, generic_array::GenericArray},
-    Aes256Gcm,
-};
-use anyhow::{anyhow, Result, Context};
-use argon2::password_hash::SaltString;
 use ethers::{
     prelude::LocalWallet,
-    signers::Signer
+    signers::Signer,
+    types::Address,
 };
-use rand::rngs::OsRng;
-use rand::RngCore;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs;
-use std::path::{Path, PathBuf};
-use std::str::FromStr;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tracing::debug;
-use zeroize::{Zeroize, ZeroizeOnDrop};
-use argon2::{
-    Algorithm, Argon2, Params, Version,
-    password_hash::PasswordHasher,
-};
+use tracing::{debug, info, warn};
+use crate::envelope::{Keyring, MasterKeyParams};
+use crate::keystore::{KeystoreKdf, KeystoreV3};
 
 
 // Cache entry standard structure
@@ -45,6 +30,7 @@ pub struct CacheEntry<T> {
     pub expires_at: DateTime<Utc>,
 }
 
+#[allow(dead_code)]
 impl<T> CacheEntry<T> {
     pub fn new(value: T, ttl_seconds: i64) -> Self {
         Self {
@@ -67,6 +53,7 @@ pub struct SensitiveData {
 }
 
 impl SensitiveData {
+    #[allow(dead_code)]
     pub fn new(identifier: &str, data: Vec<u8>) -> Self {
         Self {
             identifier: identifier.to_string(),
@@ -77,6 +64,7 @@ impl SensitiveData {
 
 // Security error types
 #[derive(Debug, thiserror::Error)]
+#[allow(dead_code, clippy::enum_variant_names)]
 pub enum SecurityError {
     #[error("Encryption error: {0}")]
     EncryptionError(String),
@@ -112,6 +100,12 @@ pub struct WalletInfo {
     pub name: Option<String>,
     pub tags: Vec<String>,
     pub is_hardware: bool,
+    /// Đường dẫn BIP-32 nếu ví được dẫn xuất từ mnemonic hoặc xpub
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+    /// Ví chỉ theo dõi: không có khóa, không thể ký
+    #[serde(default)]
+    pub watch_only: bool,
 }
 
 // Cấu trúc dữ liệu mã hóa
@@ -136,33 +130,56 @@ pub struct SafeWalletView {
     pub name: Option<String>,
     pub tags: Vec<String>,
     pub is_hardware: bool,
+    /// Nhóm ví chứa ví này
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Đường dẫn BIP-32 nếu ví được dẫn xuất
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+    /// Ví chỉ theo dõi (nhập từ xpub hoặc địa chỉ)
+    #[serde(default)]
+    pub watch_only: bool,
 }
 
 
 // Secure storage manager
+#[allow(dead_code)]
 pub struct SecureWalletStorage {
     storage_path: PathBuf,
     // Cache for loaded wallets
     wallet_cache: Arc<RwLock<HashMap<String, CacheEntry<SensitiveData>>>>,
     salt: [u8; 16],
     wallets: HashMap<String, WalletInfo>,
-    current_key: EncryptionKey,
+    index_path: PathBuf,
+    keystore_kdf: KeystoreKdf,
+    master_key_params: MasterKeyParams,
+    // Keyring đã mở khóa bằng passphrase vận hành
+    keyring: Option<Keyring>,
+}
 
+/// Kết quả chuyển các ví `.bin` cũ sang keystore V3
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeystoreMigrationReport {
+    /// Địa chỉ đã được mã hóa lại thành keystore V3
+    pub migrated: Vec<String>,
+    /// Ví chỉ có metadata (không có private key), chỉ chuyển vào index
+    pub metadata_only: Vec<String>,
+    /// Tệp không chuyển được kèm lý do
+    pub failed: Vec<(String, String)>,
 }
 
 impl SecureWalletStorage {
     // Create a new secure storage
     pub fn new(storage_path: PathBuf, config: &StorageConfig) -> Result<Self> {
-        let salt_vec = config.encryption_salt.as_bytes().to_vec();
+        // Salt cũ chỉ còn dùng để đọc các tệp `.bin` trước khi chuyển sang keystore
+        let salt_vec = config.encryption_salt.as_bytes();
         let mut salt = [0u8; 16];
-        salt.copy_from_slice(&salt_vec[0..16]);
+        let salt_len = salt_vec.len().min(salt.len());
+        salt[..salt_len].copy_from_slice(&salt_vec[..salt_len]);
 
-        fs::create_dir_all(&storage_path)
+        fs::create_dir_all(storage_path.join(KEYSTORE_DIR))
             .with_context(|| format!("Failed to create storage directory at {:?}", storage_path))?;
 
-
-        let key_id = format!("key-{}", Self::rand_hex_string(8));
-        let key = Self::generate_encryption_key("default", &salt)?;
         let mut wallets = HashMap::new();
 
         // Tải ví từ đĩa nếu tệp tồn tại
@@ -180,48 +197,416 @@ impl SecureWalletStorage {
             wallet_cache: Arc::new(RwLock::new(HashMap::new())),
             salt,
             wallets,
-            current_key: EncryptionKey {
-                key_id,
-                key,
-                created_at: SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs(),
-            },
+            index_path: wallet_path,
+            keystore_kdf: config.keystore_kdf.clone(),
+            master_key_params: config.master_key.clone(),
+            keyring: None,
         })
     }
 
-    // Store sensitive data securely
+    /// Mở khóa keyring bằng passphrase vận hành (tạo keyring mới nếu chưa có)
+    pub fn unlock(&mut self, passphrase: &str) -> Result<()> {
+        let keyring = Keyring::open_or_create(
+            &self.storage_path.join(KEYRING_FILE),
+            passphrase,
+            &self.master_key_params,
+        )?;
+        self.keyring = Some(keyring);
+        Ok(())
+    }
+
+    /// Kiểm tra passphrase với keyring đang mở, hoặc mở khóa nếu chưa mở
+    fn unlock_or_verify(&mut self, passphrase: &str) -> Result<()> {
+        match &self.keyring {
+            Some(keyring) => keyring.verify_passphrase(passphrase),
+            None => self.unlock(passphrase),
+        }
+    }
+
+    fn keyring(&self) -> Result<&Keyring> {
+        self.keyring.as_ref().ok_or_else(|| anyhow!("Wallet storage chưa được mở khóa"))
+    }
+
+    fn keyring_mut(&mut self) -> Result<&mut Keyring> {
+        self.keyring.as_mut().ok_or_else(|| anyhow!("Wallet storage chưa được mở khóa"))
+    }
+
+    // Store wallet metadata; private keys live in keystore V3 files
     pub fn store_wallet(&mut self, wallet_info: &WalletInfo, password: &str) -> Result<()> {
-        let key = self.derive_key(password)?;
-        let identifier = wallet_info.address.clone();
-        let data = serde_json::to_vec(wallet_info)?;
-        let mut nonce_bytes = [0u8; 12];
-        OsRng.fill_bytes(&mut nonce_bytes);
-        let nonce = Nonce::from_slice(&nonce_bytes);
+        let address = wallet_info.address.clone();
 
-        let cipher = Aes256Gcm::new_from_slice(&key)
-            .map_err(|e| SecurityError::EncryptionError(e.to_string()))?;
+        // Không cho ghi đè metadata nếu sai passphrase vận hành
+        self.unlock_or_verify(password)?;
 
-        let encrypted_data = cipher.encrypt(nonce, Payload {
-            msg: &data,
-            aad: identifier.as_bytes(),
-        })
-        .map_err(|e| SecurityError::EncryptionError(e.to_string()))?;
+        let mut info = wallet_info.clone();
+        info.encrypted_private_key = None;
+        info.encrypted_mnemonic = None;
+        self.wallets.insert(address, info);
+        self.save_to_file()
+    }
+
+    // Load wallet metadata, verifying the operator passphrase
+    pub fn load_wallet(&mut self, address: &str, password: &str) -> Result<WalletInfo> {
+        if !self.keystore_path(address).exists() && self.get_file_path(address).exists() {
+            return self.load_legacy_wallet(address, password);
+        }
+        self.unlock_or_verify(password)?;
+
+        self.wallets.get(address)
+            .cloned()
+            .ok_or_else(|| anyhow!("Wallet not found: {}", address))
+    }
+
+    // Delete wallet metadata and key files
+    pub fn delete_wallet(&mut self, address: &str) -> Result<()> {
+        for file_path in [self.keystore_path(address), self.get_file_path(address)] {
+            if file_path.exists() {
+                fs::remove_file(&file_path)
+                    .with_context(|| format!("Failed to delete file at {:?}", file_path))?;
+            }
+        }
+
+        if let Some(keyring) = self.keyring.as_mut() {
+            keyring.remove(address)?;
+        }
+        if self.wallets.remove(address).is_some() {
+            self.save_to_file()?;
+        }
+        Ok(())
+    }
+
+    /// Kiểm tra ví đã có trong index hoặc có keystore
+    pub fn exists(&self, address: &str) -> bool {
+        self.wallets.contains_key(address) || self.keystore_path(address).exists()
+    }
+
+    /// Ví có keystore chứa private key (ví chỉ có metadata hoặc chỉ theo dõi thì không)
+    pub fn has_keystore(&self, address: &str) -> bool {
+        self.keystore_path(address).exists()
+    }
+
+    /// Ghi index metadata ví ra đĩa
+    pub fn save_to_file(&self) -> Result<()> {
+        let wallets: Vec<&WalletInfo> = self.wallets.values().collect();
+        let data = serde_json::to_vec_pretty(&wallets)?;
+        write_file_atomic(&self.index_path, &data)
+            .with_context(|| format!("Failed to write wallet index to {:?}", self.index_path))
+    }
+
+    /// Mã hóa private key thành keystore V3 bằng data key riêng của ví và ghi metadata vào index
+    pub fn store_private_key(
+        &mut self,
+        private_key: &[u8],
+        chain_id: u64,
+        name: Option<String>,
+    ) -> Result<WalletInfo> {
+        self.store_key(private_key, chain_id, name, None)
+    }
+
+    /// Như `store_private_key` nhưng ghi lại đường dẫn BIP-32 mà khóa được dẫn xuất
+    pub fn store_derived_key(
+        &mut self,
+        private_key: &[u8],
+        chain_id: u64,
+        name: Option<String>,
+        derivation_path: &str,
+    ) -> Result<WalletInfo> {
+        self.store_key(private_key, chain_id, name, Some(derivation_path.to_string()))
+    }
+
+    fn store_key(
+        &mut self,
+        private_key: &[u8],
+        chain_id: u64,
+        name: Option<String>,
+        derivation_path: Option<String>,
+    ) -> Result<WalletInfo> {
+        let wallet = LocalWallet::from_bytes(private_key)
+            .map_err(|e| anyhow!("Private key không hợp lệ: {}", e))?;
+        let address = format!("{:?}", wallet.address());
+        let keystore_path = self.keystore_path(&address);
+
+        let keyring = self.keyring_mut()?;
+        let data_key = match keyring.data_key(&address)? {
+            Some(data_key) => data_key,
+            None => keyring.create_data_key(&address)?,
+        };
+        let keystore = KeystoreV3::encrypt(private_key, &data_key.keystore_password(), &DATA_KEY_KDF)?;
+        write_file_atomic(&keystore_path, keystore.to_json()?.as_bytes())
+            .with_context(|| format!("Failed to write keystore for {}", address))?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let info = match self.wallets.get(&address) {
+            // Nhập khóa cho ví đang chỉ theo dõi sẽ nâng nó thành ví ký được
+            Some(existing) => WalletInfo {
+                name: name.or_else(|| existing.name.clone()),
+                last_used: now,
+                derivation_path: derivation_path.or_else(|| existing.derivation_path.clone()),
+                watch_only: false,
+                ..existing.clone()
+            },
+            None => WalletInfo {
+                address: address.clone(),
+                encrypted_private_key: None,
+                encrypted_mnemonic: None,
+                chain_id,
+                created_at: now,
+                last_used: now,
+                balance: None,
+                name,
+                tags: vec![],
+                is_hardware: false,
+                derivation_path,
+                watch_only: false,
+            },
+        };
+        self.wallets.insert(address, info.clone());
+        self.save_to_file()?;
+
+        Ok(info)
+    }
+
+    /// Thêm ví chỉ theo dõi (không có khóa) để theo dõi số dư và PnL
+    pub fn add_watch_only(
+        &mut self,
+        address: Address,
+        chain_id: u64,
+        name: Option<String>,
+        derivation_path: Option<String>,
+    ) -> Result<WalletInfo> {
+        let address = format!("{:?}", address);
+        let info = match self.wallets.get(&address) {
+            Some(existing) if !existing.watch_only => {
+                bail!("Ví {} đã có khóa, không cần nhập dạng chỉ theo dõi", address)
+            }
+            Some(existing) => WalletInfo {
+                name: name.or_else(|| existing.name.clone()),
+                derivation_path: derivation_path.or_else(|| existing.derivation_path.clone()),
+                ..existing.clone()
+            },
+            None => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                WalletInfo {
+                    address: address.clone(),
+                    encrypted_private_key: None,
+                    encrypted_mnemonic: None,
+                    chain_id,
+                    created_at: now,
+                    last_used: now,
+                    balance: None,
+                    name,
+                    tags: vec![],
+                    is_hardware: false,
+                    derivation_path,
+                    watch_only: true,
+                }
+            }
+        };
+        self.wallets.insert(address, info.clone());
+        self.save_to_file()?;
+
+        Ok(info)
+    }
+
+    /// Metadata của ví trong index
+    pub fn wallet_info(&self, address: &str) -> Option<&WalletInfo> {
+        self.wallets.get(address)
+    }
+
+    /// Metadata của mọi ví trong index
+    pub fn wallet_infos(&self) -> Vec<WalletInfo> {
+        self.wallets.values().cloned().collect()
+    }
+
+    /// Ghi lại ví từ bản sao lưu: khóa (nếu có) được mã hóa bằng data key của storage này,
+    /// metadata (tên, tag, đường dẫn, thời điểm tạo) giữ nguyên như lúc sao lưu
+    pub fn restore_wallet(&mut self, info: &WalletInfo, private_key: Option<&[u8]>) -> Result<()> {
+        let mut restored = WalletInfo {
+            encrypted_private_key: None,
+            encrypted_mnemonic: None,
+            ..info.clone()
+        };
+        match private_key {
+            Some(private_key) => {
+                let stored = self.store_key(private_key, info.chain_id, info.name.clone(), info.derivation_path.clone())?;
+                if stored.address != info.address {
+                    bail!("Khóa trong bản sao lưu không khớp địa chỉ {}", info.address);
+                }
+                restored.watch_only = false;
+            }
+            None if !info.watch_only && !info.is_hardware => {
+                bail!("Bản sao lưu thiếu khóa của ví {}", info.address)
+            }
+            None => {}
+        }
+        self.wallets.insert(restored.address.clone(), restored);
+        self.save_to_file()
+    }
+
+    /// Giải mã keystore của ví bằng data key (hoặc data key cũ nếu đang xoay dở)
+    pub fn load_private_key(&self, address: &str) -> Result<LocalWallet> {
+        if self.wallets.get(address).is_some_and(|info| info.watch_only) {
+            bail!("Ví {} chỉ theo dõi, không có khóa để ký", address);
+        }
+        let keystore = KeystoreV3::load(&self.keystore_path(address))?;
+        let keyring = self.keyring()?;
+        let data_key = keyring.data_key(address)?
+            .ok_or_else(|| anyhow!("Ví {} chưa có data key", address))?;
+
+        match keystore.decrypt_wallet(&data_key.keystore_password()) {
+            Ok(wallet) => Ok(wallet),
+            Err(e) => match keyring.previous_data_key(address)? {
+                Some(previous) => keystore.decrypt_wallet(&previous.keystore_password()),
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Import keystore JSON của MetaMask/geth; khóa được mã hóa lại bằng data key của storage
+    pub fn import_keystore(
+        &mut self,
+        json: &str,
+        keystore_password: &str,
+        chain_id: u64,
+        name: Option<String>,
+    ) -> Result<LocalWallet> {
+        let wallet = KeystoreV3::from_json(json)?.decrypt_wallet(keystore_password)?;
+        self.store_private_key(&wallet.signer().to_bytes(), chain_id, name)?;
+        Ok(wallet)
+    }
+
+    /// Xuất keystore V3 của ví (tương thích MetaMask/geth), mã hóa bằng mật khẩu xuất
+    pub fn export_keystore(&self, address: &str, export_password: &str) -> Result<String> {
+        let wallet = self.load_private_key(address)?;
+        let private_key = Zeroizing::new(wallet.signer().to_bytes().to_vec());
+        KeystoreV3::encrypt(&private_key, export_password, &self.keystore_kdf)?.to_json()
+    }
 
-        let mut file_content = Vec::with_capacity(nonce_bytes.len() + encrypted_data.len());
-        file_content.extend_from_slice(&nonce_bytes);
-        file_content.extend_from_slice(&encrypted_data);
+    /// Đổi passphrase vận hành: chỉ bọc lại data key, keystore của ví giữ nguyên
+    pub fn rotate_passphrase(&mut self, current: &str, new: &str) -> Result<()> {
+        let params = self.master_key_params.clone();
+        self.keyring_mut()?.rotate_passphrase(current, new, &params)?;
+        info!("Đã đổi passphrase của wallet storage");
+        Ok(())
+    }
 
-        let file_path = self.get_file_path(&identifier);
-        fs::write(&file_path, &file_content)
-            .with_context(|| format!("Failed to write encrypted data to {:?}", file_path))?;
+    /// Các ví cần xoay data key (cũ hơn `max_age` hoặc đang xoay dở)
+    pub fn stale_data_keys(&self, max_age: std::time::Duration) -> Vec<String> {
+        self.keyring.as_ref()
+            .map(|keyring| keyring.stale_wallets(max_age))
+            .unwrap_or_default()
+    }
 
+    /// Xoay data key của ví: mã hóa lại keystore bằng data key mới
+    pub fn rotate_data_key(&mut self, address: &str) -> Result<()> {
+        let keystore_path = self.keystore_path(address);
+        let keystore = KeystoreV3::load(&keystore_path)?;
+        let keyring = self.keyring_mut()?;
+
+        // Tiếp tục lần xoay dở dang thay vì tạo khóa mới đè lên khóa cũ
+        let (old, new) = match keyring.previous_data_key(address)? {
+            Some(previous) => {
+                let current = keyring.data_key(address)?
+                    .ok_or_else(|| anyhow!("Ví {} chưa có data key", address))?;
+                (previous, current)
+            }
+            None => keyring.begin_rotation(address)?,
+        };
+
+        let private_key = match keystore.decrypt(&old.keystore_password()) {
+            Ok(private_key) => private_key,
+            // Keystore đã được ghi bằng khóa mới trước khi lần xoay trước bị gián đoạn
+            Err(_) => keystore.decrypt(&new.keystore_password())?,
+        };
+        let rotated = KeystoreV3::encrypt(&private_key, &new.keystore_password(), &DATA_KEY_KDF)?;
+        write_file_atomic(&keystore_path, rotated.to_json()?.as_bytes())
+            .with_context(|| format!("Failed to write keystore for {}", address))?;
+
+        keyring.finish_rotation(address)?;
+        debug!("Đã xoay data key của ví {}", address);
         Ok(())
     }
 
-    // Load sensitive data
-    pub fn load_wallet(&self, address: &str, password: &str) -> Result<WalletInfo> {
+    /// Chuyển toàn bộ tệp `.bin` cũ (mã hóa bằng `legacy_password`) sang keystore V3 bọc bằng data key,
+    /// xóa tệp cũ sau khi keystore mới giải mã được
+    pub fn migrate_legacy_wallets(&mut self, legacy_password: &str) -> Result<KeystoreMigrationReport> {
+        self.keyring()?;
+        let mut report = KeystoreMigrationReport::default();
+        let entries = fs::read_dir(&self.storage_path)
+            .with_context(|| format!("Failed to read storage directory {:?}", self.storage_path))?;
+
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("bin") {
+                continue;
+            }
+            let Some(identifier) = path.file_stem().and_then(|stem| stem.to_str()).map(str::to_string) else {
+                continue;
+            };
+
+            match self.migrate_legacy_wallet(&identifier, legacy_password) {
+                Ok(Some(address)) => report.migrated.push(address),
+                Ok(None) => report.metadata_only.push(identifier),
+                Err(e) => {
+                    warn!("Không chuyển được ví {} sang keystore: {}", identifier, e);
+                    report.failed.push((identifier, e.to_string()));
+                }
+            }
+        }
+
+        info!(
+            "Đã chuyển {} ví sang keystore V3 ({} chỉ có metadata, {} lỗi)",
+            report.migrated.len(), report.metadata_only.len(), report.failed.len()
+        );
+        Ok(report)
+    }
+
+    /// Chuyển một tệp `.bin`; trả về địa chỉ nếu có private key được mã hóa lại
+    fn migrate_legacy_wallet(&mut self, identifier: &str, password: &str) -> Result<Option<String>> {
+        let info = self.load_legacy_wallet(identifier, password)?;
+
+        let address = match &info.encrypted_private_key {
+            Some(encrypted) => {
+                let key = self.derive_key(password)?;
+                let cipher = Aes256Gcm::new_from_slice(&key)
+                    .map_err(|e| SecurityError::DecryptionError(e.to_string()))?;
+                let private_key = Zeroizing::new(cipher
+                    .decrypt(Nonce::from_slice(&encrypted.nonce), encrypted.ciphertext.as_slice())
+                    .map_err(|e| SecurityError::DecryptionError(e.to_string()))?);
+                let private_key_hex = std::str::from_utf8(&private_key)
+                    .map_err(|e| SecurityError::FormatError(e.to_string()))?;
+                let private_key = Zeroizing::new(hex::decode(private_key_hex.trim().trim_start_matches("0x"))
+                    .map_err(|e| SecurityError::FormatError(e.to_string()))?);
+
+                let stored = self.store_private_key(&private_key, info.chain_id, info.name.clone())?;
+                // Chỉ xóa tệp cũ khi keystore mới giải mã được
+                self.load_private_key(&stored.address)?;
+                Some(stored.address)
+            }
+            None => None,
+        };
+
+        let key = address.clone().unwrap_or_else(|| info.address.clone());
+        let mut metadata = info;
+        metadata.address = key.clone();
+        metadata.encrypted_private_key = None;
+        metadata.encrypted_mnemonic = None;
+        self.wallets.insert(key, metadata);
+        self.save_to_file()?;
+
+        fs::remove_file(self.get_file_path(identifier))
+            .with_context(|| format!("Failed to delete legacy wallet file for {}", identifier))?;
+        Ok(address)
+    }
+
+    // Load a legacy `.bin` wallet written before keystore V3 storage
+    fn load_legacy_wallet(&self, address: &str, password: &str) -> Result<WalletInfo> {
         let file_path = self.get_file_path(address);
         let file_content = fs::read(&file_path)
             .with_context(|| format!("Failed to read encrypted data from {:?}", file_path))?;
@@ -249,77 +634,26 @@ impl SecureWalletStorage {
         Ok(wallet_info)
     }
 
-    // Delete sensitive data
-    pub fn delete_wallet(&self, address: &str) -> Result<()> {
-        let file_path = self.get_file_path(address);
-
-        if file_path.exists() {
-            fs::remove_file(&file_path)
-                .with_context(|| format!("Failed to delete file at {:?}", file_path))?;
-        }
-
-        Ok(())
-    }
-
-    // Helper: Get file path for an identifier
+    // Helper: Get legacy `.bin` file path for an identifier
     fn get_file_path(&self, identifier: &str) -> PathBuf {
         self.storage_path.join(format!("{}.bin", identifier))
     }
 
-    // Helper: Derive encryption key from password
+    // Helper: Get keystore V3 file path for an address
+    fn keystore_path(&self, address: &str) -> PathBuf {
+        self.storage_path.join(KEYSTORE_DIR).join(format!("{}.json", address.to_lowercase()))
+    }
+
+    // Helper: Derive legacy `.bin` encryption key from password (single SHA-256, migration only)
     fn derive_key(&self, password: &str) -> Result<[u8; 32]> {
         let mut hasher = Sha256::new();
         hasher.update(password.as_bytes());
-        hasher.update(&self.salt);
+        hasher.update(self.salt);
 
         let result = hasher.finalize();
         let mut key = [0u8; 32];
         key.copy_from_slice(&result);
 
-        Ok(key)
-    }
-    /// Tạo hàm trợ giúp tạo chuỗi hex ngẫu nhiên
-    fn rand_hex_string(len: usize) -> String {
-        let mut bytes = vec![0u8; len];
-        OsRng.fill_bytes(&mut bytes);
-        hex::encode(bytes)
-    }
-    
-     /// Tạo khóa từ mật khẩu sử dụng Argon2
-    fn generate_encryption_key(password: &str, salt: &[u8]) -> Result<[u8; 32]> {
-        // Tạo một Argon2 context
-        let argon2 = Argon2::new(
-            Algorithm::Argon2id, 
-            Version::V0x13, 
-            Params::new(4096, 3, 4, Some(32)).unwrap()
-        );
-        
-        // Tạo salt string từ slice
-        let mut random_salt = [0u8; 16];
-        OsRng.fill_bytes(&mut random_salt);
-        
-        // Đảm bảo có một số byte từ salt ban đầu trong random_salt
-        for i in 0..std::cmp::min(salt.len(), 8) {
-            random_salt[i] ^= salt[i];  // XOR để kết hợp giá trị
-        }
-        
-        // Sử dụng từ khóa từ tài liệu của password-hash
-        let salt_str = SaltString::encode_b64(&random_salt)
-            .map_err(|e| anyhow!("Failed to encode salt: {}", e))?;
-        
-        // Hash mật khẩu
-        let hash = argon2
-            .hash_password(password.as_bytes(), &salt_str)
-            .map_err(|e| anyhow!("Failed to hash password: {}", e))?;
-            
-        // Lấy bytes từ hash
-        let hash_wrapped = hash.hash.unwrap();
-        let hash_bytes = hash_wrapped.as_bytes();
-        
-        // Sao chép 32 bytes đầu tiên vào key
-        let mut key = [0u8; 32];
-        key.copy_from_slice(&hash_bytes[0..32]);
-        
         Ok(key)
     }
 }
@@ -331,6 +665,12 @@ pub struct StorageConfig {
     pub wallet_dir: String,
     pub wallet_filename: String,
     pub encryption_salt: String,
+    /// KDF dùng khi xuất keystore V3 bằng mật khẩu người dùng
+    #[serde(default)]
+    pub keystore_kdf: KeystoreKdf,
+    /// Tham số Argon2id dẫn xuất master key từ passphrase vận hành
+    #[serde(default)]
+    pub master_key: MasterKeyParams,
 }
 
 impl Default for StorageConfig {
@@ -339,10 +679,41 @@ impl Default for StorageConfig {
             wallet_dir: ".wallets".to_string(),
             wallet_filename: "wallets.json".to_string(),
             encryption_salt: "diamond".to_string(),
+            keystore_kdf: KeystoreKdf::default(),
+            master_key: MasterKeyParams::default(),
         }
     }
 }
 
+/// Thư mục con chứa keystore V3, mỗi ví một tệp `<địa chỉ>.json`
+const KEYSTORE_DIR: &str = "keystore";
+
+/// Tệp keyring chứa data key của các ví, bọc bằng master key
+const KEYRING_FILE: &str = "keyring.json";
+
+/// KDF của keystore được mã hóa bằng data key: data key là 256 bit ngẫu nhiên
+/// nên không cần KDF chậm, xoay khóa hàng loạt vẫn nhanh
+const DATA_KEY_KDF: KeystoreKdf = KeystoreKdf::Pbkdf2 { iterations: 1024 };
+
+/// Ghi tệp qua tệp tạm rồi đổi tên để không để lại keystore/index ghi dở
+pub(crate) fn write_file_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&tmp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
 fn load_wallets(path: &Path) -> Result<Vec<WalletInfo>> {
     let file_content = fs::read(path)
         .context("Failed to read wallet file")?;
@@ -353,47 +724,6 @@ fn load_wallets(path: &Path) -> Result<Vec<WalletInfo>> {
     Ok(wallet_data)
 }
 
-// Khóa mã hóa
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
-struct EncryptionKey {
-    key_id: String,
-    key: [u8; 32],
-    created_at: u64,
-}
-
-impl Zeroize for EncryptionKey {
-    fn zeroize(&mut self) {
-        self.key.zeroize();
-    }
-}
-
-impl Drop for EncryptionKey {
-    fn drop(&mut self) {
-        self.zeroize();
-    }
-}
-
-impl ZeroizeOnDrop for EncryptionKey {}
-
-#[allow(dead_code)]
-impl EncryptionKey {
-    /// Tạo khóa mã hóa mới
-    fn new() -> Self {
-        let mut key = [0u8; 32];
-        OsRng.fill_bytes(&mut key);
-        
-        Self {
-            key_id: uuid::Uuid::new_v4().to_string(),
-            key,
-            created_at: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
-        }
-    }
-    
-}
 
 
 impl WalletInfo {
@@ -410,6 +740,9 @@ impl WalletInfo {
             name: self.name.clone(),
             tags: self.tags.clone(),
             is_hardware: self.is_hardware,
+            groups: Vec::new(),
+            derivation_path: self.derivation_path.clone(),
+            watch_only: self.watch_only,
         }
     }
 }
@@ -417,20 +750,21 @@ impl WalletInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::str::FromStr;
     use tempfile::tempdir;
 
     #[test]
     fn test_secure_storage_store_load() {
         let temp_dir = tempdir().unwrap();
         let config = StorageConfig::default();
-        let storage = SecureWalletStorage::new(temp_dir.path().to_path_buf(), &config).unwrap();
+        let mut storage = SecureWalletStorage::new(temp_dir.path().to_path_buf(), &config).unwrap();
 
         let identifier = "test_wallet";
-        let data = b"sensitive wallet data";
+        let _data = b"sensitive wallet data";
         let password = "secure_password";
 
         // Store data
-        storage.store_wallet( &WalletInfo{ address: identifier.to_string(), encrypted_private_key: None, encrypted_mnemonic: None, chain_id: 1, created_at: 1, last_used: 1, balance: None, name: None, tags: vec![], is_hardware: false}, password).unwrap();
+        storage.store_wallet( &WalletInfo{ address: identifier.to_string(), encrypted_private_key: None, encrypted_mnemonic: None, chain_id: 1, created_at: 1, last_used: 1, balance: None, name: None, tags: vec![], is_hardware: false, derivation_path: None, watch_only: false}, password).unwrap();
 
         // Verify it exists
         assert!(storage.exists(identifier));
@@ -445,6 +779,161 @@ mod tests {
         assert!(!storage.exists(identifier));
     }
 
+    const TEST_PRIVATE_KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+    const PASSPHRASE: &str = "operator passphrase for tests";
+
+    fn keystore_storage(path: &Path) -> SecureWalletStorage {
+        let config = StorageConfig {
+            keystore_kdf: KeystoreKdf::light(),
+            master_key: MasterKeyParams::light(),
+            ..StorageConfig::default()
+        };
+        let mut storage = SecureWalletStorage::new(path.to_path_buf(), &config).unwrap();
+        storage.unlock(PASSPHRASE).unwrap();
+        storage
+    }
+
+    #[test]
+    fn test_keystore_import_export() {
+        let temp_dir = tempdir().unwrap();
+        let mut storage = keystore_storage(temp_dir.path());
+        let private_key = hex::decode(TEST_PRIVATE_KEY).unwrap();
+
+        // Keystore từ ví khác được mã hóa lại bằng data key của storage
+        let external = KeystoreV3::encrypt(&private_key, "password", &KeystoreKdf::light()).unwrap();
+        let wallet = storage.import_keystore(&external.to_json().unwrap(), "password", 1, None).unwrap();
+        let address = format!("{:?}", wallet.address());
+        assert!(storage.exists(&address));
+        assert_eq!(storage.load_private_key(&address).unwrap().address(), wallet.address());
+        assert!(storage.load_wallet(&address, "wrong passphrase").is_err());
+        assert!(KeystoreV3::load(&storage.keystore_path(&address)).unwrap().decrypt("password").is_err());
+
+        // Xuất bằng mật khẩu riêng, KDF scrypt cho MetaMask/geth
+        let exported = KeystoreV3::from_json(&storage.export_keystore(&address, "export password").unwrap()).unwrap();
+        assert_eq!(exported.crypto.kdf, "scrypt");
+        assert_eq!(exported.decrypt_wallet("export password").unwrap().address(), wallet.address());
+
+        // Index và keyring được nạp lại khi mở storage mới
+        let mut reopened = keystore_storage(temp_dir.path());
+        assert_eq!(reopened.load_wallet(&address, PASSPHRASE).unwrap().address, address);
+        assert_eq!(reopened.load_private_key(&address).unwrap().address(), wallet.address());
+    }
+
+    #[test]
+    fn test_passphrase_and_data_key_rotation() {
+        let temp_dir = tempdir().unwrap();
+        let mut storage = keystore_storage(temp_dir.path());
+        let info = storage.store_private_key(&hex::decode(TEST_PRIVATE_KEY).unwrap(), 1, None).unwrap();
+        let keystore_path = storage.keystore_path(&info.address);
+
+        // Đổi passphrase không chạm tới keystore của ví
+        let before = fs::read(&keystore_path).unwrap();
+        storage.rotate_passphrase(PASSPHRASE, "rotated operator passphrase").unwrap();
+        assert_eq!(fs::read(&keystore_path).unwrap(), before);
+        assert!(storage.rotate_passphrase(PASSPHRASE, "another operator passphrase").is_err());
+        assert!(storage.load_wallet(&info.address, PASSPHRASE).is_err());
+        assert!(storage.load_private_key(&info.address).is_ok());
+
+        // Xoay data key mã hóa lại keystore, private key giữ nguyên
+        assert_eq!(storage.stale_data_keys(std::time::Duration::ZERO), vec![info.address.clone()]);
+        storage.rotate_data_key(&info.address).unwrap();
+        assert_ne!(fs::read(&keystore_path).unwrap(), before);
+        assert_eq!(format!("{:?}", storage.load_private_key(&info.address).unwrap().address()), info.address);
+        assert!(storage.stale_data_keys(std::time::Duration::from_secs(3600)).is_empty());
+    }
+
+    #[test]
+    fn test_watch_only_wallet_upgraded_by_key_import() {
+        let temp_dir = tempdir().unwrap();
+        let mut storage = keystore_storage(temp_dir.path());
+        let wallet = LocalWallet::from_str(TEST_PRIVATE_KEY).unwrap();
+
+        // Ví chỉ theo dõi có trong index nhưng không ký được
+        let info = storage.add_watch_only(wallet.address(), 1, Some("cold".to_string()), Some("M/0/3".to_string())).unwrap();
+        assert!(info.watch_only && info.to_safe_view().watch_only);
+        assert!(storage.load_private_key(&info.address).is_err());
+        assert!(!storage.has_keystore(&info.address));
+
+        // Nhập khóa sau đó giữ tên, ghi đường dẫn mới và bỏ cờ chỉ theo dõi
+        let info = storage.store_derived_key(&wallet.signer().to_bytes(), 1, None, "m/44'/60'/0'/0/3").unwrap();
+        assert!(!info.watch_only);
+        assert_eq!(info.name.as_deref(), Some("cold"));
+        assert_eq!(info.to_safe_view().derivation_path.as_deref(), Some("m/44'/60'/0'/0/3"));
+        assert!(storage.load_private_key(&info.address).is_ok());
+        assert!(storage.has_keystore(&info.address));
+        assert!(storage.add_watch_only(wallet.address(), 1, None, None).is_err());
+    }
+
+    #[test]
+    fn test_restore_wallet_keeps_backup_metadata() {
+        let source_dir = tempdir().unwrap();
+        let mut source = keystore_storage(source_dir.path());
+        let wallet = LocalWallet::from_str(TEST_PRIVATE_KEY).unwrap();
+        let mut info = source.store_derived_key(&wallet.signer().to_bytes(), 56, Some("hot".to_string()), "m/44'/60'/0'/0/7").unwrap();
+        info.created_at = 42;
+        info.tags = vec!["sniper".to_string()];
+
+        // Storage đích có keyring riêng; khóa được mã hóa lại, metadata giữ như bản sao lưu
+        let target_dir = tempdir().unwrap();
+        let mut target = keystore_storage(target_dir.path());
+        assert!(target.restore_wallet(&info, None).is_err());
+        target.restore_wallet(&info, Some(&wallet.signer().to_bytes())).unwrap();
+
+        let restored = target.wallet_info(&info.address).unwrap();
+        assert_eq!((restored.created_at, restored.chain_id), (42, 56));
+        assert_eq!(restored.tags, vec!["sniper".to_string()]);
+        assert_eq!(restored.derivation_path.as_deref(), Some("m/44'/60'/0'/0/7"));
+        assert_eq!(target.load_private_key(&info.address).unwrap().address(), wallet.address());
+        assert_eq!(target.wallet_infos().len(), 1);
+    }
+
+    #[test]
+    fn test_migrate_legacy_bin_wallet() {
+        let temp_dir = tempdir().unwrap();
+        let mut storage = keystore_storage(temp_dir.path());
+        let wallet = LocalWallet::from_str(TEST_PRIVATE_KEY).unwrap();
+        let address = format!("{:?}", wallet.address());
+
+        // Ghi tệp `.bin` theo định dạng cũ: private key hex mã hóa AES-GCM trong WalletInfo
+        let key = storage.derive_key("diamond_wallet").unwrap();
+        let cipher = Aes256Gcm::new_from_slice(&key).unwrap();
+        let key_nonce = [7u8; 12];
+        let encrypted_key = cipher.encrypt(Nonce::from_slice(&key_nonce), TEST_PRIVATE_KEY.as_bytes()).unwrap();
+        let info = WalletInfo {
+            address: address.clone(),
+            encrypted_private_key: Some(EncryptedData { ciphertext: encrypted_key, nonce: key_nonce.to_vec(), salt: vec![], version: 1 }),
+            encrypted_mnemonic: None,
+            chain_id: 56,
+            created_at: 1,
+            last_used: 1,
+            balance: None,
+            name: Some("legacy".to_string()),
+            tags: vec![],
+            is_hardware: false,
+            derivation_path: None,
+            watch_only: false,
+        };
+        let file_nonce = [9u8; 12];
+        let mut file_content = file_nonce.to_vec();
+        file_content.extend(cipher.encrypt(Nonce::from_slice(&file_nonce), Payload {
+            msg: &serde_json::to_vec(&info).unwrap(),
+            aad: address.as_bytes(),
+        }).unwrap());
+        fs::write(storage.get_file_path(&address), file_content).unwrap();
+        fs::write(storage.get_file_path("corrupt"), b"short").unwrap();
+
+        let report = storage.migrate_legacy_wallets("diamond_wallet").unwrap();
+        assert_eq!(report.migrated, vec![address.clone()]);
+        assert_eq!(report.failed.len(), 1);
+        assert!(!storage.get_file_path(&address).exists());
+
+        assert_eq!(storage.load_private_key(&address).unwrap().address(), wallet.address());
+        let migrated = storage.load_wallet(&address, PASSPHRASE).unwrap();
+        assert_eq!((migrated.chain_id, migrated.name.as_deref()), (56, Some("legacy")));
+        assert!(migrated.encrypted_private_key.is_none());
+    }
+
     #[test]
     fn test_cache_entry() {
         let value = "test_value";
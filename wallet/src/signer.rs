@@ -0,0 +1,607 @@
+// External imports
+use ethers::{
+    prelude::LocalWallet,
+    providers::{Http, JsonRpcClient},
+    signers::{Signer, WalletError},
+    abi::{self, Token},
+    types::{
+        transaction::{
+            eip2718::TypedTransaction,
+            eip712::{EIP712Domain, Eip712},
+        },
+        Address, Bytes, NameOrAddress, Signature, H256, U256, U64,
+    },
+    utils::{keccak256, rlp::Rlp},
+};
+
+// Standard library imports
+use std::convert::Infallible;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+// Third party imports
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+/// Thời gian chờ tối đa cho một yêu cầu tới signer từ xa
+pub const REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Lỗi của các backend ký
+#[derive(Debug, thiserror::Error)]
+pub enum SignerError {
+    #[error("Local signer error: {0}")]
+    Local(#[from] WalletError),
+
+    #[error("Remote signer transport error: {0}")]
+    Transport(String),
+
+    #[error("Remote signer error: {0}")]
+    Remote(String),
+
+    #[error("Remote signer returned an invalid response: {0}")]
+    InvalidResponse(String),
+
+    #[error("Remote signer {0:?} does not manage account {1:?}")]
+    UnknownAccount(String, Address),
+
+    #[error("Unsupported by remote signer: {0}")]
+    Unsupported(String),
+
+    #[error("Signing rejected by wallet manager: {0}")]
+    Rejected(String),
+}
+
+/// Bộ tên phương thức JSON-RPC của signer từ xa
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteSignerApi {
+    /// Web3Signer (eth_accounts, eth_sign, eth_signTransaction)
+    Web3Signer,
+    /// Clef (account_list, account_signData, account_signTransaction)
+    Clef,
+}
+
+impl RemoteSignerApi {
+    fn accounts_method(self) -> &'static str {
+        match self {
+            RemoteSignerApi::Web3Signer => "eth_accounts",
+            RemoteSignerApi::Clef => "account_list",
+        }
+    }
+}
+
+/// Kênh kết nối tới signer từ xa
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RemoteTransport {
+    /// Tiến trình ký riêng qua Unix socket, JSON-RPC mỗi dòng một thông điệp (tương thích Clef IPC)
+    UnixSocket { path: PathBuf },
+    /// Endpoint HTTP JSON-RPC (Web3Signer, Clef --http)
+    Http { url: String },
+}
+
+/// Cấu hình một signer từ xa
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSignerConfig {
+    #[serde(flatten)]
+    pub transport: RemoteTransport,
+    pub api: RemoteSignerApi,
+    /// Tài khoản cần dùng; bỏ trống thì lấy tài khoản đầu tiên signer trả về
+    #[serde(default)]
+    pub address: Option<Address>,
+    pub chain_id: u64,
+}
+
+/// Client JSON-RPC theo kênh kết nối
+#[derive(Debug)]
+enum RemoteClient {
+    UnixSocket { path: PathBuf, next_id: AtomicU64 },
+    Http(Http),
+}
+
+impl RemoteClient {
+    fn new(transport: &RemoteTransport) -> Result<Self, SignerError> {
+        match transport {
+            RemoteTransport::UnixSocket { path } => Ok(RemoteClient::UnixSocket {
+                path: path.clone(),
+                next_id: AtomicU64::new(1),
+            }),
+            RemoteTransport::Http { url } => Http::from_str(url)
+                .map(RemoteClient::Http)
+                .map_err(|e| SignerError::Transport(format!("invalid signer URL {}: {}", url, e))),
+        }
+    }
+
+    async fn request<R: DeserializeOwned>(&self, method: &str, params: Value) -> Result<R, SignerError> {
+        let request = async {
+            match self {
+                RemoteClient::UnixSocket { path, next_id } => {
+                    let id = next_id.fetch_add(1, Ordering::Relaxed);
+                    unix_socket_request(path, id, method, params).await
+                }
+                RemoteClient::Http(http) => http.request(method, params).await.map_err(|e| match e {
+                    ethers::providers::HttpClientError::JsonRpcError(e) => SignerError::Remote(e.to_string()),
+                    e => SignerError::Transport(e.to_string()),
+                }),
+            }
+        };
+        let value = tokio::time::timeout(REMOTE_SIGNER_TIMEOUT, request).await
+            .map_err(|_| SignerError::Transport(format!("{} timed out", method)))??;
+        serde_json::from_value(value).map_err(|e| SignerError::InvalidResponse(e.to_string()))
+    }
+}
+
+/// Gửi một yêu cầu JSON-RPC qua Unix socket và đọc một dòng phản hồi
+async fn unix_socket_request(path: &PathBuf, id: u64, method: &str, params: Value) -> Result<Value, SignerError> {
+    let stream = UnixStream::connect(path).await
+        .map_err(|e| SignerError::Transport(format!("connect {:?}: {}", path, e)))?;
+    let (reader, mut writer) = stream.into_split();
+
+    let mut line = serde_json::to_vec(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+        .map_err(|e| SignerError::Transport(e.to_string()))?;
+    line.push(b'\n');
+    writer.write_all(&line).await.map_err(|e| SignerError::Transport(e.to_string()))?;
+
+    let mut response = String::new();
+    BufReader::new(reader).read_line(&mut response).await
+        .map_err(|e| SignerError::Transport(e.to_string()))?;
+    let response: Value = serde_json::from_str(&response)
+        .map_err(|e| SignerError::InvalidResponse(e.to_string()))?;
+
+    if let Some(error) = response.get("error").filter(|error| !error.is_null()) {
+        return Err(SignerError::Remote(error.to_string()));
+    }
+    response.get("result").cloned()
+        .ok_or_else(|| SignerError::InvalidResponse("missing result".to_string()))
+}
+
+/// Signer từ xa: private key nằm trong tiến trình/dịch vụ khác, bot chỉ gửi yêu cầu ký.
+/// Mọi chữ ký trả về đều được kiểm tra lại địa chỉ khôi phục và nội dung giao dịch
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    client: Arc<RemoteClient>,
+    api: RemoteSignerApi,
+    label: String,
+    address: Address,
+    chain_id: u64,
+}
+
+impl RemoteSigner {
+    /// Kết nối tới signer và xác nhận signer quản lý tài khoản cần dùng
+    pub async fn connect(config: &RemoteSignerConfig) -> Result<Self, SignerError> {
+        let client = RemoteClient::new(&config.transport)?;
+        let label = match &config.transport {
+            RemoteTransport::UnixSocket { path } => path.display().to_string(),
+            RemoteTransport::Http { url } => url.clone(),
+        };
+
+        let accounts: Vec<Address> = client.request(config.api.accounts_method(), json!([])).await?;
+        let address = match config.address {
+            Some(address) if accounts.contains(&address) => address,
+            Some(address) => return Err(SignerError::UnknownAccount(label, address)),
+            None => *accounts.first()
+                .ok_or_else(|| SignerError::InvalidResponse(format!("{} has no accounts", label)))?,
+        };
+
+        Ok(Self {
+            client: Arc::new(client),
+            api: config.api,
+            label,
+            address,
+            chain_id: config.chain_id,
+        })
+    }
+
+    /// Tham số giao dịch theo định dạng SendTxArgs của Clef/Web3Signer
+    fn transaction_params(&self, tx: &TypedTransaction) -> Result<Value, SignerError> {
+        let to = match tx.to() {
+            Some(NameOrAddress::Address(address)) => Some(*address),
+            Some(NameOrAddress::Name(name)) => {
+                return Err(SignerError::Unsupported(format!("unresolved ENS name {}", name)));
+            }
+            None => None,
+        };
+        let chain_id = tx.chain_id().unwrap_or_else(|| U64::from(self.chain_id));
+
+        let mut params = json!({
+            "from": self.address,
+            "to": to,
+            "gas": tx.gas(),
+            "value": tx.value(),
+            "data": tx.data(),
+            "nonce": tx.nonce(),
+            "chainId": chain_id,
+        });
+        match tx {
+            TypedTransaction::Eip1559(request) => {
+                params["maxFeePerGas"] = json!(request.max_fee_per_gas);
+                params["maxPriorityFeePerGas"] = json!(request.max_priority_fee_per_gas);
+                params["accessList"] = json!(request.access_list);
+            }
+            TypedTransaction::Eip2930(request) => {
+                params["gasPrice"] = json!(request.tx.gas_price);
+                params["accessList"] = json!(request.access_list);
+            }
+            TypedTransaction::Legacy(request) => {
+                params["gasPrice"] = json!(request.gas_price);
+            }
+        }
+
+        // Bỏ các trường rỗng để signer tự báo thiếu thay vì nhận null
+        if let Some(object) = params.as_object_mut() {
+            object.retain(|_, value| !value.is_null());
+        }
+        Ok(params)
+    }
+
+    /// Kiểm tra chữ ký khôi phục đúng địa chỉ của signer
+    fn verify_signature(&self, signature: &Signature, hash: ethers::types::H256) -> Result<(), SignerError> {
+        let recovered = signature.recover(hash)
+            .map_err(|e| SignerError::InvalidResponse(e.to_string()))?;
+        if recovered != self.address {
+            return Err(SignerError::InvalidResponse(format!(
+                "signature recovers to {:?}, expected {:?}", recovered, self.address
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    type Error = SignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature, Self::Error> {
+        let message = message.as_ref();
+        let data = Bytes::from(message.to_vec());
+        let signature: Bytes = match self.api {
+            RemoteSignerApi::Web3Signer => {
+                self.client.request("eth_sign", json!([self.address, data])).await?
+            }
+            RemoteSignerApi::Clef => {
+                self.client.request("account_signData", json!(["text/plain", self.address, data])).await?
+            }
+        };
+
+        let signature = Signature::try_from(signature.as_ref())
+            .map_err(|e| SignerError::InvalidResponse(e.to_string()))?;
+        self.verify_signature(&signature, ethers::utils::hash_message(message))?;
+        Ok(signature)
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let mut tx = tx.clone();
+        if tx.chain_id().is_none() {
+            tx.set_chain_id(self.chain_id);
+        }
+        let params = self.transaction_params(&tx)?;
+
+        let raw: Bytes = match self.api {
+            RemoteSignerApi::Web3Signer => self.client.request("eth_signTransaction", json!([params])).await?,
+            RemoteSignerApi::Clef => {
+                #[derive(Deserialize)]
+                struct ClefSignedTransaction {
+                    raw: Bytes,
+                }
+                let signed: ClefSignedTransaction = self.client.request("account_signTransaction", json!([params])).await?;
+                signed.raw
+            }
+        };
+
+        // Signer không được tự ý đổi nội dung giao dịch
+        let (signed_tx, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw))
+            .map_err(|e| SignerError::InvalidResponse(e.to_string()))?;
+        if signed_tx.sighash() != tx.sighash() {
+            return Err(SignerError::InvalidResponse(format!(
+                "{} signed a different transaction than requested", self.label
+            )));
+        }
+        self.verify_signature(&signature, tx.sighash())?;
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, _payload: &T) -> Result<Signature, Self::Error> {
+        // Eip712 chỉ cung cấp hash, signer từ xa cần JSON typed data đầy đủ
+        Err(SignerError::Unsupported("EIP-712 typed data signing".to_string()))
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+/// Kiểu dữ liệu Permit theo EIP-2612
+const PERMIT_TYPE: &str = "Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)";
+
+/// Yêu cầu ký EIP-2612 `permit`. Dùng DOMAIN_SEPARATOR đọc từ token nên không cần biết name/version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Erc2612Permit {
+    pub chain_id: u64,
+    pub token: Address,
+    pub owner: Address,
+    pub spender: Address,
+    pub value: U256,
+    pub nonce: U256,
+    pub deadline: U256,
+    pub domain_separator: H256,
+}
+
+impl Eip712 for Erc2612Permit {
+    type Error = Infallible;
+
+    fn domain_separator(&self) -> Result<[u8; 32], Self::Error> {
+        Ok(self.domain_separator.0)
+    }
+
+    fn domain(&self) -> Result<EIP712Domain, Self::Error> {
+        Ok(EIP712Domain {
+            chain_id: Some(U256::from(self.chain_id)),
+            verifying_contract: Some(self.token),
+            ..Default::default()
+        })
+    }
+
+    fn type_hash() -> Result<[u8; 32], Self::Error> {
+        Ok(keccak256(PERMIT_TYPE))
+    }
+
+    fn struct_hash(&self) -> Result<[u8; 32], Self::Error> {
+        Ok(keccak256(abi::encode(&[
+            Token::FixedBytes(Self::type_hash()?.to_vec()),
+            Token::Address(self.owner),
+            Token::Address(self.spender),
+            Token::Uint(self.value),
+            Token::Uint(self.nonce),
+            Token::Uint(self.deadline),
+        ])))
+    }
+}
+
+/// Signer dùng cho ví: khóa trong bộ nhớ hoặc signer từ xa
+#[derive(Debug, Clone)]
+pub enum WalletSigner {
+    Local(LocalWallet),
+    Remote(RemoteSigner),
+}
+
+impl WalletSigner {
+    /// Private key có nằm trong tiến trình bot không
+    pub fn is_local(&self) -> bool {
+        matches!(self, WalletSigner::Local(_))
+    }
+}
+
+#[async_trait]
+impl Signer for WalletSigner {
+    type Error = SignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature, Self::Error> {
+        match self {
+            WalletSigner::Local(wallet) => Ok(wallet.sign_message(message).await?),
+            WalletSigner::Remote(signer) => signer.sign_message(message).await,
+        }
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            WalletSigner::Local(wallet) => Ok(wallet.sign_transaction(tx).await?),
+            WalletSigner::Remote(signer) => signer.sign_transaction(tx).await,
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, payload: &T) -> Result<Signature, Self::Error> {
+        match self {
+            WalletSigner::Local(wallet) => Ok(wallet.sign_typed_data(payload).await?),
+            WalletSigner::Remote(signer) => signer.sign_typed_data(payload).await,
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            WalletSigner::Local(wallet) => wallet.address(),
+            WalletSigner::Remote(signer) => signer.address(),
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            WalletSigner::Local(wallet) => wallet.chain_id(),
+            WalletSigner::Remote(signer) => signer.chain_id(),
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            WalletSigner::Local(wallet) => WalletSigner::Local(wallet.with_chain_id(chain_id)),
+            WalletSigner::Remote(signer) => WalletSigner::Remote(signer.with_chain_id(chain_id)),
+        }
+    }
+}
+
+impl From<LocalWallet> for WalletSigner {
+    fn from(wallet: LocalWallet) -> Self {
+        WalletSigner::Local(wallet)
+    }
+}
+
+impl From<RemoteSigner> for WalletSigner {
+    fn from(signer: RemoteSigner) -> Self {
+        WalletSigner::Remote(signer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer_server::SignerServer;
+    use ethers::types::{Eip1559TransactionRequest, TransactionRequest};
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    const TEST_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    fn test_wallet() -> LocalWallet {
+        TEST_KEY.parse().unwrap()
+    }
+
+    /// Endpoint HTTP JSON-RPC tối giản phía trước SignerServer; `tamper` mô phỏng signer bị chiếm quyền
+    async fn spawn_http_stand_in(server: SignerServer, tamper: bool) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let server = server.clone();
+                tokio::spawn(async move {
+                    let mut reader = BufReader::new(stream);
+                    loop {
+                        let mut content_length = 0;
+                        loop {
+                            let mut line = String::new();
+                            if reader.read_line(&mut line).await.unwrap_or(0) == 0 {
+                                return;
+                            }
+                            let line = line.trim_end().to_ascii_lowercase();
+                            if line.is_empty() {
+                                break;
+                            }
+                            if let Some(value) = line.strip_prefix("content-length:") {
+                                content_length = value.trim().parse().unwrap();
+                            }
+                        }
+
+                        let mut body = vec![0u8; content_length];
+                        reader.read_exact(&mut body).await.unwrap();
+                        let mut request: Value = serde_json::from_slice(&body).unwrap();
+                        if tamper {
+                            if let Some(tx) = request["params"].get_mut(0).and_then(Value::as_object_mut) {
+                                tx.insert("value".to_string(), json!("0xde0b6b3a7640000"));
+                            }
+                        }
+
+                        let response = serde_json::to_vec(&server.handle_request(request).await).unwrap();
+                        let head = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+                            response.len()
+                        );
+                        let stream = reader.get_mut();
+                        stream.write_all(head.as_bytes()).await.unwrap();
+                        stream.write_all(&response).await.unwrap();
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_unix_socket_web3signer_matches_local_signature() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("signer.sock");
+        let _server = SignerServer::new(test_wallet()).spawn_unix(&socket).unwrap();
+
+        let remote = RemoteSigner::connect(&RemoteSignerConfig {
+            transport: RemoteTransport::UnixSocket { path: socket },
+            api: RemoteSignerApi::Web3Signer,
+            address: Some(test_wallet().address()),
+            chain_id: 1,
+        }).await.unwrap();
+        let signer = WalletSigner::from(remote);
+        assert!(!signer.is_local());
+
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(Address::repeat_byte(0x11))
+            .value(1_000u64)
+            .gas(21_000u64)
+            .gas_price(1_000_000_000u64)
+            .nonce(7u64)
+            .chain_id(1u64)
+            .into();
+        let local = WalletSigner::from(test_wallet());
+        assert_eq!(
+            signer.sign_transaction(&tx).await.unwrap(),
+            local.sign_transaction(&tx).await.unwrap()
+        );
+
+        let signature = signer.sign_message("diamond").await.unwrap();
+        assert_eq!(signature.recover("diamond").unwrap(), test_wallet().address());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("signer.sock");
+        let _server = SignerServer::new(test_wallet()).spawn_unix(&socket).unwrap();
+
+        let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        // Thư mục tạm dùng để bind đã được dọn
+        let entries: Vec<_> = std::fs::read_dir(dir.path()).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(entries, vec![std::ffi::OsString::from("signer.sock")]);
+    }
+
+    #[tokio::test]
+    async fn test_http_clef_signs_eip1559_transaction() {
+        let url = spawn_http_stand_in(SignerServer::new(test_wallet()), false).await;
+        let signer = RemoteSigner::connect(&RemoteSignerConfig {
+            transport: RemoteTransport::Http { url },
+            api: RemoteSignerApi::Clef,
+            address: None,
+            chain_id: 56,
+        }).await.unwrap();
+        assert_eq!(signer.address(), test_wallet().address());
+
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(Address::repeat_byte(0x22))
+            .value(5u64)
+            .gas(50_000u64)
+            .max_fee_per_gas(3_000_000_000u64)
+            .max_priority_fee_per_gas(1_000_000_000u64)
+            .nonce(0u64)
+            .into();
+        let signature = signer.sign_transaction(&tx).await.unwrap();
+
+        let mut expected = tx.clone();
+        expected.set_chain_id(56u64);
+        assert_eq!(signature.recover(expected.sighash()).unwrap(), test_wallet().address());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_signature_for_tampered_transaction() {
+        let url = spawn_http_stand_in(SignerServer::new(test_wallet()), true).await;
+        let signer = RemoteSigner::connect(&RemoteSignerConfig {
+            transport: RemoteTransport::Http { url },
+            api: RemoteSignerApi::Web3Signer,
+            address: Some(test_wallet().address()),
+            chain_id: 1,
+        }).await.unwrap();
+
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(Address::repeat_byte(0x33))
+            .value(1u64)
+            .gas(21_000u64)
+            .gas_price(1u64)
+            .nonce(0u64)
+            .into();
+        let err = signer.sign_transaction(&tx).await.unwrap_err();
+        assert!(matches!(err, SignerError::InvalidResponse(_)));
+    }
+}
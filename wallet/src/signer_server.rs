@@ -0,0 +1,241 @@
+// External imports
+use ethers::{
+    prelude::LocalWallet,
+    signers::Signer,
+    types::{
+        transaction::{eip2718::TypedTransaction, eip2930::AccessList},
+        Address, Bytes, Eip1559TransactionRequest, Eip2930TransactionRequest, NameOrAddress,
+        TransactionRequest, U256, U64,
+    },
+};
+
+// Standard library imports
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// Third party imports
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Tham số giao dịch nhận từ client (SendTxArgs của Clef/Web3Signer)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionArgs {
+    from: Address,
+    to: Option<Address>,
+    gas: Option<U256>,
+    gas_price: Option<U256>,
+    max_fee_per_gas: Option<U256>,
+    max_priority_fee_per_gas: Option<U256>,
+    value: Option<U256>,
+    nonce: Option<U256>,
+    #[serde(alias = "input")]
+    data: Option<Bytes>,
+    access_list: Option<AccessList>,
+    chain_id: Option<U64>,
+}
+
+impl TransactionArgs {
+    fn into_typed(self) -> TypedTransaction {
+        let to = self.to.map(NameOrAddress::Address);
+        if self.max_fee_per_gas.is_some() || self.max_priority_fee_per_gas.is_some() {
+            return TypedTransaction::Eip1559(Eip1559TransactionRequest {
+                from: Some(self.from),
+                to,
+                gas: self.gas,
+                value: self.value,
+                data: self.data,
+                nonce: self.nonce,
+                access_list: self.access_list.unwrap_or_default(),
+                max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+                max_fee_per_gas: self.max_fee_per_gas,
+                chain_id: self.chain_id,
+            });
+        }
+
+        let legacy = TransactionRequest {
+            from: Some(self.from),
+            to,
+            gas: self.gas,
+            gas_price: self.gas_price,
+            value: self.value,
+            data: self.data,
+            nonce: self.nonce,
+            chain_id: self.chain_id,
+        };
+        match self.access_list {
+            Some(access_list) => TypedTransaction::Eip2930(Eip2930TransactionRequest::new(legacy, access_list)),
+            None => TypedTransaction::Legacy(legacy),
+        }
+    }
+}
+
+/// Tiến trình ký độc lập: giữ private key và phục vụ yêu cầu ký qua Unix socket.
+/// Hỗ trợ cả tên phương thức Web3Signer (eth_*) và Clef (account_*)
+#[derive(Debug, Clone)]
+pub struct SignerServer {
+    wallet: Arc<LocalWallet>,
+}
+
+impl SignerServer {
+    pub fn new(wallet: LocalWallet) -> Self {
+        Self { wallet: Arc::new(wallet) }
+    }
+
+    /// Địa chỉ của khóa đang được phục vụ
+    pub fn address(&self) -> Address {
+        self.wallet.address()
+    }
+
+    /// Mở Unix socket và phục vụ ở task nền; socket chỉ chủ sở hữu được truy cập.
+    /// Socket được bind trong thư mục tạm 0700 cạnh đường dẫn đích, chmod 0600 rồi mới
+    /// đổi tên sang đường dẫn đích, nên không có lúc nào tiến trình khác kết nối được
+    pub fn spawn_unix(self, path: impl AsRef<Path>) -> Result<JoinHandle<()>> {
+        let path: PathBuf = path.as_ref().to_path_buf();
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Cannot remove stale socket {:?}", path))?;
+        }
+        let file_name = path.file_name()
+            .ok_or_else(|| anyhow!("Signer socket path has no file name: {:?}", path))?;
+        let staging_dir = path.with_file_name(format!(".{}.{}", file_name.to_string_lossy(), std::process::id()));
+        let mut builder = std::fs::DirBuilder::new();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        builder.create(&staging_dir)
+            .with_context(|| format!("Cannot create socket staging directory {:?}", staging_dir))?;
+
+        let staged = staging_dir.join(file_name);
+        let bound = UnixListener::bind(&staged)
+            .with_context(|| format!("Cannot bind signer socket {:?}", path))
+            .and_then(|listener| {
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+                }
+                std::fs::rename(&staged, &path)
+                    .with_context(|| format!("Cannot move signer socket to {:?}", path))?;
+                Ok(listener)
+            });
+        let _ = std::fs::remove_file(&staged);
+        let _ = std::fs::remove_dir(&staging_dir);
+        let listener = bound?;
+
+        Ok(tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        let server = self.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = server.serve_connection(stream).await {
+                                debug!("Signer connection closed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        warn!("Signer socket accept failed: {}", e);
+                        break;
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Đọc các yêu cầu JSON-RPC (mỗi dòng một yêu cầu) và trả lời trên cùng kết nối
+    async fn serve_connection(&self, stream: UnixStream) -> Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<Value>(&line) {
+                Ok(request) => self.handle_request(request).await,
+                Err(e) => json!({
+                    "jsonrpc": "2.0",
+                    "id": Value::Null,
+                    "error": { "code": -32700, "message": e.to_string() },
+                }),
+            };
+            let mut bytes = serde_json::to_vec(&response)?;
+            bytes.push(b'\n');
+            writer.write_all(&bytes).await?;
+        }
+        Ok(())
+    }
+
+    /// Xử lý một yêu cầu JSON-RPC và trả về phản hồi hoàn chỉnh (dùng chung cho mọi kênh)
+    pub async fn handle_request(&self, request: Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let method = request.get("method").and_then(Value::as_str).unwrap_or_default().to_string();
+        let params = request.get("params").cloned().unwrap_or_else(|| json!([]));
+
+        match self.dispatch(&method, params).await {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(e) => {
+                debug!("Signer request {} rejected: {}", method, e);
+                json!({ "jsonrpc": "2.0", "id": id, "error": { "code": -32000, "message": e.to_string() } })
+            }
+        }
+    }
+
+    async fn dispatch(&self, method: &str, params: Value) -> Result<Value> {
+        let params: Vec<Value> = serde_json::from_value(params).context("params must be an array")?;
+        let param = |index: usize| params.get(index).cloned()
+            .ok_or_else(|| anyhow!("missing parameter {}", index));
+
+        match method {
+            "eth_accounts" | "account_list" => Ok(json!([self.address()])),
+            "eth_sign" => {
+                self.ensure_account(serde_json::from_value(param(0)?)?)?;
+                let data: Bytes = serde_json::from_value(param(1)?)?;
+                let signature = self.wallet.sign_message(data.as_ref()).await?;
+                Ok(json!(Bytes::from(signature.to_vec())))
+            }
+            "account_signData" => {
+                let content_type: String = serde_json::from_value(param(0)?)?;
+                if content_type != "text/plain" {
+                    return Err(anyhow!("unsupported content type {}", content_type));
+                }
+                self.ensure_account(serde_json::from_value(param(1)?)?)?;
+                let data: Bytes = serde_json::from_value(param(2)?)?;
+                let signature = self.wallet.sign_message(data.as_ref()).await?;
+                Ok(json!(Bytes::from(signature.to_vec())))
+            }
+            "eth_signTransaction" | "account_signTransaction" => {
+                let args: TransactionArgs = serde_json::from_value(param(0)?)?;
+                self.ensure_account(args.from)?;
+                let tx = args.into_typed();
+                let chain_id = tx.chain_id().ok_or_else(|| anyhow!("chainId is required"))?;
+
+                let signer = self.wallet.as_ref().clone().with_chain_id(chain_id.as_u64());
+                let signature = signer.sign_transaction(&tx).await?;
+                let raw = tx.rlp_signed(&signature);
+
+                if method == "account_signTransaction" {
+                    Ok(json!({ "raw": raw, "tx": tx }))
+                } else {
+                    Ok(json!(raw))
+                }
+            }
+            _ => Err(anyhow!("method {} not supported", method)),
+        }
+    }
+
+    fn ensure_account(&self, address: Address) -> Result<()> {
+        if address != self.address() {
+            return Err(anyhow!("unknown account {:?}", address));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+use anyhow::{Result, anyhow, Context};
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey, Signature};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::keystore::{KeystoreKdf, KeystoreV3};
+use crate::secure_storage::write_file_atomic;
+
+/// Độ dài keypair Solana dạng bytes (32 byte secret + 32 byte public key)
+pub const SOLANA_KEYPAIR_LENGTH: usize = 64;
+
+/// Thư mục con trong thư mục ví chứa keypair Solana đã mã hóa, mỗi keypair một tệp `<pubkey>.json`
+pub const SOLANA_KEYSTORE_DIR: &str = "solana";
+
+/// Keypair ed25519 dùng để ký giao dịch Solana
+pub struct SolanaKeypair {
+    signing_key: SigningKey,
+}
+
+impl SolanaKeypair {
+    /// Tạo keypair ngẫu nhiên mới
+    pub fn generate() -> Self {
+        Self {
+            signing_key: SigningKey::generate(&mut OsRng),
+        }
+    }
+
+    /// Tạo keypair từ 32 byte secret key
+    pub fn from_secret_key(secret: &[u8; 32]) -> Self {
+        Self {
+            signing_key: SigningKey::from_bytes(secret),
+        }
+    }
+
+    /// Tạo keypair từ 64 byte (định dạng của Solana CLI: secret + public key).
+    /// Public key đi kèm phải khớp với secret key
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != SOLANA_KEYPAIR_LENGTH {
+            return Err(anyhow!("Keypair Solana phải dài {} bytes, nhận {} bytes", SOLANA_KEYPAIR_LENGTH, bytes.len()));
+        }
+
+        let mut secret = [0u8; 32];
+        secret.copy_from_slice(&bytes[..32]);
+        let keypair = Self::from_secret_key(&secret);
+        secret.zeroize();
+
+        if keypair.pubkey_bytes() != bytes[32..] {
+            return Err(anyhow!("Public key trong keypair Solana không khớp với secret key"));
+        }
+        Ok(keypair)
+    }
+
+    /// Tạo keypair từ chuỗi base58 (định dạng export của Phantom/Solflare)
+    pub fn from_base58(encoded: &str) -> Result<Self> {
+        let mut bytes = bs58::decode(encoded.trim()).into_vec()
+            .map_err(|e| anyhow!("Keypair Solana base58 không hợp lệ: {}", e))?;
+        let keypair = Self::from_bytes(&bytes);
+        bytes.zeroize();
+        keypair
+    }
+
+    /// Đọc keypair từ file JSON của Solana CLI (mảng 64 số)
+    pub fn from_json_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Không thể đọc file keypair {}", path.display()))?;
+        let mut bytes: Vec<u8> = serde_json::from_str(&content)
+            .with_context(|| format!("File keypair {} không đúng định dạng Solana CLI", path.display()))?;
+        let keypair = Self::from_bytes(&bytes);
+        bytes.zeroize();
+        keypair
+    }
+
+    /// Public key dạng bytes
+    pub fn pubkey_bytes(&self) -> [u8; 32] {
+        self.signing_key.verifying_key().to_bytes()
+    }
+
+    /// Public key (địa chỉ ví) dạng base58
+    pub fn pubkey(&self) -> String {
+        bs58::encode(self.pubkey_bytes()).into_string()
+    }
+
+    /// Ký message, trả về chữ ký 64 byte
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        self.signing_key.sign(message).to_bytes()
+    }
+
+    /// Export keypair dạng base58 (64 byte secret + public key)
+    pub fn to_base58(&self) -> String {
+        bs58::encode(self.signing_key.to_keypair_bytes()).into_string()
+    }
+
+    /// Mã hóa secret key theo định dạng keystore V3, kèm pubkey để tra cứu
+    pub fn encrypt(&self, password: &str, kdf: &KeystoreKdf) -> Result<EncryptedSolanaKeypair> {
+        let secret = Zeroizing::new(self.signing_key.to_bytes());
+        Ok(EncryptedSolanaKeypair {
+            pubkey: self.pubkey(),
+            keystore: KeystoreV3::encrypt_secret(secret.as_ref(), password, kdf)?,
+        })
+    }
+}
+
+/// Keypair Solana đã mã hóa: secret key 32 byte trong keystore V3 và pubkey base58
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedSolanaKeypair {
+    pub pubkey: String,
+    pub keystore: KeystoreV3,
+}
+
+impl EncryptedSolanaKeypair {
+    /// Giải mã keypair và đối chiếu pubkey ghi trong tệp
+    pub fn decrypt(&self, password: &str) -> Result<SolanaKeypair> {
+        let secret = self.keystore.decrypt(password)?;
+        let secret: &[u8; 32] = secret.as_slice().try_into()
+            .map_err(|_| anyhow!("Secret key Solana phải dài 32 bytes, nhận {} bytes", secret.len()))?;
+        let keypair = SolanaKeypair::from_secret_key(secret);
+        if keypair.pubkey() != self.pubkey {
+            return Err(anyhow!("Pubkey {} không khớp với secret key đã mã hóa", self.pubkey));
+        }
+        Ok(keypair)
+    }
+}
+
+/// Kho keypair Solana mã hóa nằm trong thư mục ví
+#[derive(Debug, Clone)]
+pub struct SolanaKeyStore {
+    dir: PathBuf,
+}
+
+impl SolanaKeyStore {
+    /// Kho trong thư mục con `solana` của thư mục ví (chưa tạo thư mục cho đến lần ghi đầu)
+    pub fn open(wallet_dir: &Path) -> Self {
+        Self { dir: wallet_dir.join(SOLANA_KEYSTORE_DIR) }
+    }
+
+    fn keypair_path(&self, pubkey: &str) -> Result<PathBuf> {
+        // Pubkey dùng làm tên tệp nên chỉ nhận base58 hợp lệ 32 byte
+        match bs58::decode(pubkey).into_vec() {
+            Ok(bytes) if bytes.len() == 32 => Ok(self.dir.join(format!("{}.json", pubkey))),
+            _ => Err(anyhow!("Pubkey Solana không hợp lệ: {}", pubkey)),
+        }
+    }
+
+    /// Mã hóa và lưu keypair (ghi nguyên tử, quyền 0600), trả về pubkey
+    pub fn store(&self, keypair: &SolanaKeypair, password: &str, kdf: &KeystoreKdf) -> Result<String> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Không thể tạo thư mục {}", self.dir.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&self.dir, fs::Permissions::from_mode(0o700))?;
+        }
+
+        let encrypted = keypair.encrypt(password, kdf)?;
+        let path = self.keypair_path(&encrypted.pubkey)?;
+        write_file_atomic(&path, serde_json::to_string_pretty(&encrypted)?.as_bytes())?;
+        Ok(encrypted.pubkey)
+    }
+
+    /// Đọc và giải mã keypair theo pubkey
+    pub fn load(&self, pubkey: &str, password: &str) -> Result<SolanaKeypair> {
+        let path = self.keypair_path(pubkey)?;
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Không có keypair Solana {} trong kho", pubkey))?;
+        let encrypted: EncryptedSolanaKeypair = serde_json::from_str(&content)
+            .with_context(|| format!("Tệp keypair Solana {} không hợp lệ", path.display()))?;
+        if encrypted.pubkey != pubkey {
+            return Err(anyhow!("Tệp {} chứa pubkey khác {}", path.display(), pubkey));
+        }
+        encrypted.decrypt(password)
+    }
+
+    /// Pubkey của các keypair trong kho, sắp xếp tăng dần
+    pub fn pubkeys(&self) -> Result<Vec<String>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut pubkeys = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(pubkey) = path.file_stem().and_then(|stem| stem.to_str()) {
+                pubkeys.push(pubkey.to_string());
+            }
+        }
+        pubkeys.sort();
+        Ok(pubkeys)
+    }
+}
+
+/// Kiểm tra chữ ký ed25519 của một public key Solana
+pub fn verify_solana_signature(pubkey: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+    match VerifyingKey::from_bytes(pubkey) {
+        Ok(key) => key.verify_strict(message, &Signature::from_bytes(signature)).is_ok(),
+        Err(_) => false,
+    }
+}
+
+impl std::fmt::Debug for SolanaKeypair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Không bao giờ in secret key
+        f.debug_struct("SolanaKeypair")
+            .field("pubkey", &self.pubkey())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keypair_roundtrip_and_sign() {
+        let keypair = SolanaKeypair::generate();
+        let restored = SolanaKeypair::from_base58(&keypair.to_base58()).unwrap();
+        assert_eq!(restored.pubkey(), keypair.pubkey());
+
+        let signature = restored.sign(b"diamond");
+        assert!(verify_solana_signature(&keypair.pubkey_bytes(), b"diamond", &signature));
+        assert!(!verify_solana_signature(&keypair.pubkey_bytes(), b"other", &signature));
+    }
+
+    #[test]
+    fn test_rejects_mismatched_public_key() {
+        let mut bytes = SolanaKeypair::generate().signing_key.to_keypair_bytes();
+        bytes[40] ^= 0xff;
+        assert!(SolanaKeypair::from_bytes(&bytes).is_err());
+        assert!(SolanaKeypair::from_bytes(&bytes[..32]).is_err());
+    }
+
+    #[test]
+    fn test_keystore_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SolanaKeyStore::open(dir.path());
+        let keypair = SolanaKeypair::generate();
+        let kdf = KeystoreKdf::light();
+
+        let pubkey = store.store(&keypair, "solana passphrase", &kdf).unwrap();
+        assert_eq!(pubkey, keypair.pubkey());
+        assert_eq!(store.pubkeys().unwrap(), vec![pubkey.clone()]);
+
+        let loaded = store.load(&pubkey, "solana passphrase").unwrap();
+        assert_eq!(loaded.to_base58(), keypair.to_base58());
+        assert!(store.load(&pubkey, "wrong passphrase").is_err());
+        assert!(store.load("../keyring", "solana passphrase").is_err());
+
+        // Tệp chỉ chứa secret đã mã hóa, không lộ dạng base58
+        let content = fs::read_to_string(dir.path().join(SOLANA_KEYSTORE_DIR).join(format!("{}.json", pubkey))).unwrap();
+        assert!(!content.contains(&keypair.to_base58()));
+    }
+}
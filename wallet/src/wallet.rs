@@ -1,29 +1,45 @@
 use ethers::{
     prelude::{LocalWallet, SignerMiddleware, MnemonicBuilder, Provider},
     signers::{coins_bip39::English, Signer},
-    types::{transaction::eip2718::TypedTransaction, Address, Bytes, H256, U256},
-    providers::{Http, Middleware}
+    types::{transaction::{eip2718::TypedTransaction, eip712::Eip712}, Address, Bytes, Signature, H256},
+    providers::{Http, Middleware},
+    utils::hash_message,
 };
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc as StdArc;
 use std::sync::{Arc, RwLock, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::task::JoinHandle;
 use anyhow::{Result, anyhow, Context};
+use async_trait::async_trait;
 use tracing::{debug, info, warn, error};
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 use aes_gcm::{
     aead::{Aead, generic_array::GenericArray, KeyInit},
     Aes256Gcm
 };
-use crate::secure_storage::{SecureWalletStorage, StorageConfig, SafeWalletView, WalletInfo};
+use crate::secure_storage::{SecureWalletStorage, StorageConfig, SafeWalletView, WalletInfo, KeystoreMigrationReport};
+use crate::keystore::KeystoreKdf;
+use crate::solana::{SolanaKeypair, SolanaKeyStore};
+use crate::envelope::{ensure_operator_passphrase, MasterKeyParams};
+use crate::signer::{Erc2612Permit, RemoteSigner, RemoteSignerConfig, SignerError, WalletSigner};
+use crate::policy::{PolicyEngine, SpendingPolicy, TokenApproval};
+use crate::groups::{WalletChainState, WalletGroup, WalletGroups, WalletLease};
+use crate::derivation::{derive_wallet, discover_used_wallets, xpub_addresses, DerivationScheme};
+use crate::backup::{create_backup, ensure_backup_passphrase, open_backup, BackupExport, BackupKey, BackupOptions, BackupWallet, RestoreReport};
 
 /// Cấu hình cho WalletManager
 #[derive(Debug, Clone)]
 pub struct WalletManagerConfig {
     pub default_chain_id: u64,
     pub storage_config: StorageConfig,
+    /// Passphrase vận hành để mở khóa master key; không được để giá trị mặc định
     pub wallet_encryption_seed: String,
+    /// Chính sách chi tiêu cho ví chưa có chính sách riêng
+    pub default_policy: Option<SpendingPolicy>,
 }
 
 impl Default for WalletManagerConfig {
@@ -32,6 +48,7 @@ impl Default for WalletManagerConfig {
             default_chain_id: 1, // Ethereum mainnet
             storage_config: StorageConfig::default(),
             wallet_encryption_seed: "diamond_wallet".to_string(),
+            default_policy: None,
         }
     }
 }
@@ -56,10 +73,21 @@ impl Zeroize for WalletSecrets {
     }
 }
 
+/// Khóa của ví trong index và tên tệp keystore (`0x` + 40 hex viết thường)
+fn storage_key(address: Address) -> String {
+    format!("{:?}", address)
+}
+
 /// WalletManager - Quản lý ví và khóa bảo mật
 pub struct WalletManager {
     storage: RwLock<SecureWalletStorage>,
     wallets: RwLock<HashMap<Address, LocalWallet>>,
+    /// Signer từ xa theo địa chỉ; ví có signer từ xa không bao giờ giải mã khóa trong tiến trình
+    remote_signers: RwLock<HashMap<Address, RemoteSigner>>,
+    /// Chính sách chi tiêu kiểm tra trước mỗi lần ký
+    policies: Mutex<PolicyEngine>,
+    /// Nhóm ví và trạng thái khóa ví khi chọn ví cho giao dịch
+    groups: RwLock<WalletGroups>,
     config: WalletManagerConfig,
     encryption_key: String,
 }
@@ -67,11 +95,27 @@ pub struct WalletManager {
 impl WalletManager {
     /// Tạo một WalletManager mới
     pub fn new(config: WalletManagerConfig) -> Result<Self> {
-        let storage = SecureWalletStorage::new(&config.storage_config)?;
+        // Từ chối khởi động khi passphrase vẫn là seed mặc định
+        ensure_operator_passphrase(&config.wallet_encryption_seed)?;
+        
+        let mut storage = SecureWalletStorage::new(
+            PathBuf::from(&config.storage_config.wallet_dir),
+            &config.storage_config,
+        )?;
+        storage.unlock(&config.wallet_encryption_seed)?;
+        
+        let policies = PolicyEngine::open(
+            std::path::Path::new(&config.storage_config.wallet_dir),
+            config.default_policy.clone(),
+        )?;
+        let groups = WalletGroups::open(std::path::Path::new(&config.storage_config.wallet_dir))?;
         
         Ok(WalletManager {
             storage: RwLock::new(storage),
             wallets: RwLock::new(HashMap::new()),
+            remote_signers: RwLock::new(HashMap::new()),
+            policies: Mutex::new(policies),
+            groups: RwLock::new(groups),
             encryption_key: config.wallet_encryption_seed.clone(),
             config,
         })
@@ -83,11 +127,14 @@ impl WalletManager {
         let wallet_config = WalletManagerConfig {
             default_chain_id: config.chain_id,
             storage_config: StorageConfig {
-                wallet_dir: config.wallet_dir.clone(),
-                wallet_filename: config.wallet_filename.clone(),
+                wallet_dir: config.wallet_folder.clone(),
                 encryption_salt: config.wallet_encryption_seed.clone(),
+                keystore_kdf: KeystoreKdf::default(),
+                master_key: MasterKeyParams::default(),
+                ..StorageConfig::default()
             },
             wallet_encryption_seed: config.wallet_encryption_seed.clone(),
+            default_policy: None,
         };
         
         Self::new(wallet_config)
@@ -110,15 +157,11 @@ impl WalletManager {
             .build()?;
 
         let address = wallet.address();
-        let private_key = hex::encode(wallet.signer().to_bytes());
+        let private_key = Zeroizing::new(wallet.signer().to_bytes().to_vec());
         
-        // Tạo wallet info và lưu vào storage
-        let mut storage = self.storage.write().unwrap();
-        storage.create_with_private_key(&private_key, 
-                                       self.config.default_chain_id, 
-                                       None)?;
-        storage.save_to_file()?;
-        drop(storage);
+        // Mã hóa khóa thành keystore V3 và ghi metadata vào index
+        self.storage.write().unwrap()
+            .store_private_key(&private_key, self.config.default_chain_id, None)?;
         
         // Thêm ví vào cache
         let mut wallets = self.wallets.write().unwrap();
@@ -133,7 +176,7 @@ impl WalletManager {
         
         // Trả về thông tin ví an toàn
         let storage = self.storage.read().unwrap();
-        let wallet_info = storage.get_wallet(&address.to_string())
+        let wallet_info = storage.wallet_info(&storage_key(address))
             .ok_or_else(|| anyhow!("Wallet not found"))?;
         
         Ok(wallet_info.to_safe_view())
@@ -144,14 +187,11 @@ impl WalletManager {
         // Xác nhận private key hợp lệ
         let wallet = LocalWallet::from_str(private_key)?;
         let address = wallet.address();
+        let key_bytes = Zeroizing::new(wallet.signer().to_bytes().to_vec());
         
-        // Lưu vào storage
-        let mut storage = self.storage.write().unwrap();
-        storage.create_with_private_key(private_key, 
-                                       self.config.default_chain_id, 
-                                       name)?;
-        storage.save_to_file()?;
-        drop(storage);
+        // Mã hóa khóa thành keystore V3 và ghi metadata vào index
+        self.storage.write().unwrap()
+            .store_private_key(&key_bytes, self.config.default_chain_id, name)?;
         
         // Thêm vào cache
         let mut wallets = self.wallets.write().unwrap();
@@ -170,83 +210,200 @@ impl WalletManager {
         
         // Lấy thông tin ví
         let storage = self.storage.read().unwrap();
-        let wallet_info = storage.get_wallet(&address.to_string())
+        let wallet_info = storage.wallet_info(&storage_key(address))
             .ok_or_else(|| anyhow!("Wallet not found"))?
             .clone();
             
         Ok(wallet_info)
     }
     
-    /// Import ví từ mnemonic
+    /// Import ví từ mnemonic theo đường dẫn mặc định m/44'/60'/0'/0/0
     pub fn import_from_mnemonic(&self, mnemonic: &str, passphrase: Option<&str>) -> Result<WalletInfo> {
-        // Xác thực mnemonic
+        self.import_from_mnemonic_path(mnemonic, passphrase, &DerivationScheme::Bip44.path(0, 0), None)
+    }
+    
+    /// Import ví từ mnemonic theo đường dẫn BIP-32 tùy chọn (Ledger Live, MEW, ...)
+    pub fn import_from_mnemonic_path(
+        &self,
+        mnemonic: &str,
+        passphrase: Option<&str>,
+        path: &str,
+        name: Option<String>,
+    ) -> Result<WalletInfo> {
         Self::validate_mnemonic(mnemonic)?;
         
-        // Tạo ví từ mnemonic
-        let wallet = MnemonicBuilder::<English>::default()
-            .phrase(mnemonic)
-            .password(passphrase.unwrap_or(""))
-            .derivation_path("m/44'/60'/0'/0/0")?
-            .build()?;
-            
+        let wallet = derive_wallet(mnemonic, passphrase, path)?;
+        self.store_derived_wallet(wallet, path, name)
+    }
+    
+    /// Lưu khóa dẫn xuất kèm đường dẫn và đưa ví vào cache
+    fn store_derived_wallet(&self, wallet: LocalWallet, path: &str, name: Option<String>) -> Result<WalletInfo> {
         let address = wallet.address();
-        let private_key = hex::encode(wallet.signer().to_bytes());
+        let mut private_key = wallet.signer().to_bytes();
+        let stored = self.storage.write().unwrap()
+            .store_derived_key(&private_key, self.config.default_chain_id, name, path);
+        private_key.as_mut_slice().zeroize();
+        let wallet_info = stored?;
         
-        // Lưu private key vào storage
+        let mut wallets = self.wallets.write().unwrap();
+        wallets.insert(address, wallet);
+        
+        Ok(wallet_info)
+    }
+    
+    /// Import ví từ keystore JSON V3 (MetaMask, geth, MyEtherWallet)
+    pub fn import_keystore(&self, json: &str, keystore_password: &str, name: Option<String>) -> Result<Address> {
         let mut storage = self.storage.write().unwrap();
-        let wallet_info = storage.create_with_private_key(&private_key, 
-                                       self.config.default_chain_id, 
-                                       None)?;
-        storage.save_to_file()?;
+        let wallet = storage.import_keystore(json, keystore_password, self.config.default_chain_id, name)?;
         drop(storage);
         
-        // Thêm vào cache
+        let address = wallet.address();
         let mut wallets = self.wallets.write().unwrap();
         wallets.insert(address, wallet);
         
-        Ok(wallet_info)
+        Ok(address)
+    }
+    
+    /// Xuất ví thành keystore JSON V3 (mã hóa bằng mật khẩu xuất) để import vào MetaMask/geth
+    pub fn export_keystore(&self, address: Address, export_password: &str) -> Result<String> {
+        let storage = self.storage.read().unwrap();
+        storage.export_keystore(&storage_key(address), export_password)
+    }
+    
+    /// Mã hóa lại các ví `.bin` cũ (mã hóa bằng seed cũ) thành keystore V3 (chạy một lần khi nâng cấp)
+    pub fn migrate_legacy_wallets(&self, legacy_password: &str) -> Result<KeystoreMigrationReport> {
+        let mut storage = self.storage.write().unwrap();
+        storage.migrate_legacy_wallets(legacy_password)
+    }
+    
+    /// Đổi passphrase vận hành; cần cập nhật WALLET_ENCRYPTION_SEED trước lần khởi động sau
+    pub fn rotate_passphrase(&self, current: &str, new: &str) -> Result<()> {
+        let mut storage = self.storage.write().unwrap();
+        storage.rotate_passphrase(current, new)?;
+        
+        // Keypair Solana mã hóa trực tiếp bằng passphrase nên phải mã hóa lại
+        let solana = self.solana_keystore();
+        for pubkey in solana.pubkeys()? {
+            let keypair = solana.load(&pubkey, current)?;
+            solana.store(&keypair, new, &self.config.storage_config.keystore_kdf)?;
+        }
+        Ok(())
+    }
+    
+    /// Xoay data key của các ví cũ hơn `max_age`, khóa storage theo từng ví
+    pub fn rotate_stale_data_keys(&self, max_age: Duration) -> Result<Vec<String>> {
+        let stale = self.storage.read().unwrap().stale_data_keys(max_age);
+        
+        let mut rotated = Vec::with_capacity(stale.len());
+        for address in stale {
+            match self.storage.write().unwrap().rotate_data_key(&address) {
+                Ok(()) => rotated.push(address),
+                Err(e) => warn!("Không xoay được data key của ví {}: {}", address, e),
+            }
+        }
+        
+        Ok(rotated)
+    }
+    
+    /// Chạy nền việc xoay data key theo chu kỳ
+    pub fn spawn_data_key_rotation(self: Arc<Self>, interval: Duration, max_age: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                
+                // KDF và ghi đĩa là thao tác chặn
+                let manager = self.clone();
+                match tokio::task::spawn_blocking(move || manager.rotate_stale_data_keys(max_age)).await {
+                    Ok(Ok(rotated)) if !rotated.is_empty() => info!("Đã xoay data key của {} ví", rotated.len()),
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => error!("Lỗi khi xoay data key: {}", e),
+                    Err(e) => error!("Tác vụ xoay data key bị hủy: {}", e),
+                }
+            }
+        })
     }
     
-    /// Tạo nhiều ví HD từ mnemonic
+    /// Tạo nhiều ví HD từ mnemonic theo đường dẫn m/44'/60'/0'/0/i
     pub fn create_hd_wallets(&mut self, mnemonic: &str, count: usize, passphrase: Option<&str>) -> Result<Vec<WalletInfo>> {
-        // Xác thực mnemonic
+        self.derive_hd_wallets(mnemonic, passphrase, &DerivationScheme::Bip44, 0..1, 0..count as u32)
+    }
+    
+    /// Dẫn xuất và lưu ví HD cho dải account × index theo sơ đồ đường dẫn
+    pub fn derive_hd_wallets(
+        &self,
+        mnemonic: &str,
+        passphrase: Option<&str>,
+        scheme: &DerivationScheme,
+        accounts: Range<u32>,
+        indices: Range<u32>,
+    ) -> Result<Vec<WalletInfo>> {
         Self::validate_mnemonic(mnemonic)?;
         
-        let mut wallet_infos = Vec::with_capacity(count);
+        scheme.paths(accounts, indices)?
+            .iter()
+            .map(|path| {
+                let wallet = derive_wallet(mnemonic, passphrase, path)?;
+                self.store_derived_wallet(wallet, path, Some(format!("HD Wallet {}", path)))
+            })
+            .collect()
+    }
+    
+    /// Dò các ví HD đã có giao dịch on-chain (nonce > 0) và lưu lại;
+    /// dừng sau `gap_limit` ví trống liên tiếp
+    pub async fn discover_hd_wallets(
+        &self,
+        mnemonic: &str,
+        passphrase: Option<&str>,
+        scheme: &DerivationScheme,
+        account: u32,
+        gap_limit: u32,
+        provider: StdArc<Provider<Http>>,
+    ) -> Result<Vec<WalletInfo>> {
+        Self::validate_mnemonic(mnemonic)?;
         
-        // Tạo nhiều ví với các path khác nhau
-        for i in 0..count {
-            let path = format!("m/44'/60'/0'/0/{}", i);
-            
-            let wallet = MnemonicBuilder::<English>::default()
-                .phrase(mnemonic)
-                .password(passphrase.unwrap_or(""))
-                .derivation_path(&path)?
-                .build()?;
-                
-            let address = wallet.address();
-            let private_key = hex::encode(wallet.signer().to_bytes());
-            
-            // Lưu ví vào storage
-            let mut storage = self.storage.write().unwrap();
-            let wallet_info = storage.create_with_private_key(&private_key, 
-                                           self.config.default_chain_id, 
-                                           Some(format!("HD Wallet {}", i)))?;
-            storage.save_to_file()?;
-            drop(storage);
-            
-            // Thêm vào cache
-            let mut wallets = self.wallets.write().unwrap();
-            wallets.insert(address, wallet);
-            
-            wallet_infos.push(wallet_info);
+        let used = discover_used_wallets(mnemonic, passphrase, scheme, account, gap_limit, |address| {
+            let provider = provider.clone();
+            async move { Ok::<_, anyhow::Error>(!provider.get_transaction_count(address, None).await?.is_zero()) }
+        }).await?;
+        info!("Tìm thấy {} ví HD đã sử dụng theo sơ đồ {:?}", used.len(), scheme);
+        
+        used.into_iter()
+            .map(|(path, wallet)| self.store_derived_wallet(wallet, &path, Some(format!("HD Wallet {}", path))))
+            .collect()
+    }
+    
+    /// Thêm ví chỉ theo dõi từ địa chỉ để theo dõi số dư và PnL mà không cần khóa
+    pub fn import_watch_only(&self, address: Address, name: Option<String>) -> Result<SafeWalletView> {
+        let mut storage = self.storage.write().unwrap();
+        let wallet_info = storage.add_watch_only(address, self.config.default_chain_id, name, None)?;
+        
+        Ok(wallet_info.to_safe_view())
+    }
+    
+    /// Thêm các ví chỉ theo dõi dẫn xuất từ xpub cấp account (nhánh nhận M/0/i).
+    /// Địa chỉ đã có khóa trong storage được bỏ qua
+    pub fn import_xpub(&self, xpub: &str, indices: Range<u32>, name: Option<String>) -> Result<Vec<SafeWalletView>> {
+        let addresses = xpub_addresses(xpub, indices)?;
+        
+        let mut storage = self.storage.write().unwrap();
+        let mut imported = Vec::with_capacity(addresses.len());
+        for (path, address) in addresses {
+            if storage.wallet_info(&storage_key(address)).is_some_and(|info| !info.watch_only) {
+                debug!("Bỏ qua {:?} ({}): ví đã có khóa", address, path);
+                continue;
+            }
+            let name = name.as_ref().map(|name| format!("{} {}", name, path));
+            let wallet_info = storage.add_watch_only(address, self.config.default_chain_id, name, Some(path))?;
+            imported.push(wallet_info.to_safe_view());
         }
         
-        Ok(wallet_infos)
+        Ok(imported)
     }
     
-    /// Lấy ví theo địa chỉ
-    pub fn get_wallet(&self, address: Address) -> Result<LocalWallet> {
+    /// Lấy ví theo địa chỉ. Chỉ dùng trong crate: giao dịch bên ngoài phải ký qua
+    /// `sign_transaction`/`send_transaction` hoặc `ManagedSigner` để qua chính sách chi tiêu
+    pub(crate) fn get_wallet(&self, address: Address) -> Result<LocalWallet> {
         // Kiểm tra cache trước
         let wallets = self.wallets.read().unwrap();
         if let Some(wallet) = wallets.get(&address) {
@@ -255,12 +412,8 @@ impl WalletManager {
         drop(wallets);
         
         // Nếu không có trong cache, tìm trong storage
-        let storage = self.storage.read().unwrap();
-        let wallet_info = storage.get_wallet(&address.to_string())
-            .ok_or_else(|| anyhow!("Wallet not found"))?;
-        
-        // Tạo LocalWallet từ wallet_info
-        let wallet = storage.to_local_wallet(wallet_info)?;
+        let wallet = self.storage.read().unwrap()
+            .load_private_key(&storage_key(address))?;
         
         // Thêm vào cache
         let mut wallets = self.wallets.write().unwrap();
@@ -269,6 +422,100 @@ impl WalletManager {
         Ok(wallet)
     }
     
+    /// Đăng ký signer từ xa (Unix socket, Web3Signer, Clef) cho một địa chỉ
+    pub async fn add_remote_signer(&self, config: &RemoteSignerConfig) -> Result<Address> {
+        let signer = RemoteSigner::connect(config).await?;
+        let address = signer.address();
+        
+        self.remote_signers.write().unwrap().insert(address, signer);
+        info!("Registered remote signer for {:?}", address);
+        
+        Ok(address)
+    }
+    
+    /// Các địa chỉ đã đăng ký signer từ xa (khóa không nằm trong tiến trình)
+    pub fn remote_signer_addresses(&self) -> Vec<Address> {
+        let mut addresses: Vec<Address> = self.remote_signers.read().unwrap().keys().copied().collect();
+        addresses.sort();
+        addresses
+    }
+    
+    /// Gỡ signer từ xa của một địa chỉ
+    pub fn remove_remote_signer(&self, address: Address) -> bool {
+        self.remote_signers.write().unwrap().remove(&address).is_some()
+    }
+    
+    /// Lấy signer cho địa chỉ: ưu tiên signer từ xa, nếu không có thì dùng khóa trong storage.
+    /// Chỉ dùng trong crate: mọi chữ ký ra ngoài phải qua chính sách chi tiêu và audit trail
+    pub(crate) fn get_signer(&self, address: Address) -> Result<WalletSigner> {
+        if let Some(signer) = self.remote_signers.read().unwrap().get(&address) {
+            return Ok(WalletSigner::Remote(signer.clone()));
+        }
+        if self.storage.read().unwrap().wallet_info(&storage_key(address)).is_some_and(|info| info.watch_only) {
+            return Err(anyhow!("Ví {:?} chỉ theo dõi, không thể ký", address));
+        }
+        
+        Ok(WalletSigner::Local(self.get_wallet(address)?))
+    }
+    
+    /// Signer cho adapter giao dịch: không giữ khóa, mỗi chữ ký đều qua WalletSigner của ví,
+    /// chính sách chi tiêu và audit trail của WalletManager
+    pub fn managed_signer(self: &Arc<Self>, address: Address, chain_id: u64) -> Result<ManagedSigner> {
+        if !self.remote_signers.read().unwrap().contains_key(&address) {
+            let storage = self.storage.read().unwrap();
+            let key = storage_key(address);
+            if storage.wallet_info(&key).is_some_and(|info| info.watch_only) {
+                return Err(anyhow!("Ví {:?} chỉ theo dõi, không thể ký", address));
+            }
+            if !storage.has_keystore(&key) {
+                return Err(anyhow!("Không tìm thấy khóa hoặc signer từ xa cho {:?}", address));
+            }
+        }
+        
+        Ok(ManagedSigner { manager: Arc::clone(self), address, chain_id })
+    }
+    
+    /// Mã hóa keypair Solana bằng passphrase của ví và lưu vào thư mục ví, trả về pubkey
+    pub fn import_solana_keypair(&self, keypair: &SolanaKeypair) -> Result<String> {
+        let pubkey = self.solana_keystore()
+            .store(keypair, &self.encryption_key, &self.config.storage_config.keystore_kdf)?;
+        info!("Imported Solana keypair {}", pubkey);
+        Ok(pubkey)
+    }
+    
+    /// Giải mã keypair Solana trong kho; không chỉ định pubkey thì dùng keypair duy nhất (nếu có)
+    pub fn solana_keypair(&self, pubkey: Option<&str>) -> Result<Option<SolanaKeypair>> {
+        let store = self.solana_keystore();
+        let pubkey = match pubkey {
+            Some(pubkey) => pubkey.to_string(),
+            None => match store.pubkeys()?.as_slice() {
+                [] => return Ok(None),
+                [pubkey] => pubkey.clone(),
+                pubkeys => return Err(anyhow!("Kho có {} keypair Solana, cần chỉ định pubkey", pubkeys.len())),
+            },
+        };
+        store.load(&pubkey, &self.encryption_key).map(Some)
+    }
+    
+    fn solana_keystore(&self) -> SolanaKeyStore {
+        SolanaKeyStore::open(std::path::Path::new(&self.config.storage_config.wallet_dir))
+    }
+    
+    /// Gắn chính sách chi tiêu cho ví
+    pub fn set_spending_policy(&self, address: Address, policy: SpendingPolicy) -> Result<()> {
+        self.policies.lock().unwrap().set_policy(address, policy)
+    }
+    
+    /// Lấy chính sách chi tiêu đang áp dụng cho ví
+    pub fn spending_policy(&self, address: Address) -> Option<SpendingPolicy> {
+        self.policies.lock().unwrap().policy_for(address).cloned()
+    }
+    
+    /// Cập nhật giá USD của đồng native, dùng cho hạn mức chi tiêu USD
+    pub fn set_native_usd_price(&self, chain_id: u64, price: f64) {
+        self.policies.lock().unwrap().set_native_usd_price(chain_id, price);
+    }
+    
     /// Ký một giao dịch
     pub async fn sign_transaction(
         &self,
@@ -276,11 +523,13 @@ impl WalletManager {
         tx: &TypedTransaction,
         chain_id: u64,
     ) -> Result<Bytes> {
-        let wallet = self.get_wallet(address)?;
-        let wallet = wallet.with_chain_id(chain_id);
+        // Gắn chain ID để giao dịch đóng gói khớp với dữ liệu đã ký
+        let mut tx = tx.clone();
+        if tx.chain_id().is_none() {
+            tx.set_chain_id(chain_id);
+        }
         
-        // Ký giao dịch
-        let signature = wallet.sign_transaction(tx).await?;
+        let signature = self.authorize_and_sign(address, &tx, chain_id).await?;
         
         // Đóng gói giao dịch với chữ ký
         let signed_tx = tx.rlp_signed(&signature);
@@ -288,6 +537,25 @@ impl WalletManager {
         Ok(signed_tx)
     }
     
+    /// Kiểm tra chính sách chi tiêu rồi ký giao dịch bằng signer của ví
+    async fn authorize_and_sign(&self, address: Address, tx: &TypedTransaction, chain_id: u64) -> Result<Signature> {
+        let signer = self.get_signer(address)?.with_chain_id(chain_id);
+        
+        // Kiểm tra chính sách chi tiêu trước khi ký
+        self.policies.lock().unwrap().authorize(address, chain_id, tx)?;
+        
+        Ok(signer.sign_transaction(tx).await?)
+    }
+    
+    /// Ký thông điệp EIP-191 sau khi kiểm tra chain được phép và ghi audit trail
+    pub async fn sign_message(&self, address: Address, chain_id: u64, message: &[u8]) -> Result<Signature> {
+        let signer = self.get_signer(address)?.with_chain_id(chain_id);
+        
+        self.policies.lock().unwrap().authorize_message(address, chain_id, hash_message(message))?;
+        
+        Ok(signer.sign_message(message).await?)
+    }
+    
     /// Gửi một giao dịch đã ký qua một provider
     pub async fn send_transaction(
         &self,
@@ -295,14 +563,17 @@ impl WalletManager {
         address: Address,
         tx: &TypedTransaction,
     ) -> Result<H256> {
-        let wallet = self.get_wallet(address)?;
+        let signer = self.get_signer(address)?;
         
         // Thiết lập chain ID từ provider
         let chain_id = provider.get_chainid().await?;
-        let signing_wallet = wallet.with_chain_id(chain_id.as_u64());
+        let signer = signer.with_chain_id(chain_id.as_u64());
+        
+        // Kiểm tra chính sách chi tiêu trước khi ký
+        self.policies.lock().unwrap().authorize(address, chain_id.as_u64(), tx)?;
         
-        // Tạo client với ví đã ký
-        let client = SignerMiddleware::new(provider, signing_wallet);
+        // Tạo client với signer tương ứng
+        let client = SignerMiddleware::new(provider, signer);
         
         // Ký và gửi giao dịch 
         let pending_tx = client.send_transaction(tx.clone(), None).await?;
@@ -311,10 +582,181 @@ impl WalletManager {
         Ok(pending_tx.tx_hash())
     }
     
-    /// Lấy danh sách tất cả các ví
+    /// Ký EIP-2612 permit sau khi kiểm tra chính sách approve của ví
+    pub async fn sign_permit(&self, permit: &Erc2612Permit) -> Result<Signature> {
+        let approval = TokenApproval { spender: permit.spender, amount: permit.value };
+        self.policies.lock().unwrap().authorize_approval(permit.owner, permit.chain_id, permit.token, approval)?;
+        
+        let signer = self.get_signer(permit.owner)?.with_chain_id(permit.chain_id);
+        Ok(signer.sign_typed_data(permit).await?)
+    }
+    
+    /// Tạo hoặc cập nhật nhóm ví
+    pub fn set_wallet_group(&self, group: WalletGroup) -> Result<()> {
+        self.groups.write().unwrap().upsert(group)
+    }
+    
+    /// Thêm ví vào nhóm (tạo nhóm nếu chưa có)
+    pub fn add_to_group(&self, group: &str, address: Address) -> Result<()> {
+        self.groups.write().unwrap().add_member(group, address)
+    }
+    
+    /// Gỡ ví khỏi nhóm
+    pub fn remove_from_group(&self, group: &str, address: Address) -> Result<bool> {
+        self.groups.write().unwrap().remove_member(group, address)
+    }
+    
+    /// Xóa nhóm ví
+    pub fn remove_wallet_group(&self, group: &str) -> Result<bool> {
+        self.groups.write().unwrap().remove_group(group)
+    }
+    
+    /// Danh sách nhóm ví
+    pub fn wallet_groups(&self) -> Vec<WalletGroup> {
+        self.groups.read().unwrap().list()
+    }
+    
+    /// Ví đang bị khóa bởi giao dịch chờ xác nhận
+    pub fn is_wallet_locked(&self, address: Address) -> bool {
+        self.groups.read().unwrap().is_locked(address)
+    }
+    
+    /// Chọn và khóa một ví của nhóm cho giao dịch; giữ lease đến khi giao dịch có receipt.
+    /// Số dư và nonce chỉ được đọc khi chiến lược của nhóm cần
+    pub async fn acquire_group_wallet(&self, group: &str, provider: StdArc<Provider<Http>>) -> Result<WalletLease> {
+        let (strategy, candidates) = {
+            let groups = self.groups.read().unwrap();
+            let strategy = groups.get(group).map(|group| group.strategy).unwrap_or_default();
+            (strategy, groups.available_members(group)?)
+        };
+        
+        let mut chain_state = HashMap::new();
+        if strategy.needs_chain_state() {
+            for address in candidates {
+                let balance = provider.get_balance(address, None).await?;
+                let pending = provider.get_transaction_count(address, Some(ethers::types::BlockNumber::Pending.into())).await?;
+                let mined = provider.get_transaction_count(address, Some(ethers::types::BlockNumber::Latest.into())).await?;
+                chain_state.insert(address, WalletChainState {
+                    balance,
+                    pending_nonces: pending.saturating_sub(mined).as_u64(),
+                });
+            }
+        }
+        
+        Ok(self.groups.read().unwrap().acquire(group, &chain_state)?)
+    }
+    
+    /// Xuất mọi ví (kèm khóa), nhóm và chính sách thành một tệp sao lưu mã hóa bằng passphrase
+    /// sao lưu riêng; `options.split` chia thêm backup key thành các mảnh Shamir k-of-n
+    pub fn export_backup(&self, backup_passphrase: &str, options: &BackupOptions) -> Result<BackupExport> {
+        ensure_backup_passphrase(backup_passphrase, &self.encryption_key)?;
+        
+        let wallets = {
+            let storage = self.storage.read().unwrap();
+            let mut wallets = Vec::new();
+            for info in storage.wallet_infos() {
+                let private_key = if info.watch_only || info.is_hardware {
+                    None
+                } else {
+                    let wallet = storage.load_private_key(&info.address)
+                        .with_context(|| format!("Không đọc được khóa của ví {} để sao lưu", info.address))?;
+                    Some(Zeroizing::new(wallet.signer().to_bytes().to_vec()))
+                };
+                wallets.push(BackupWallet::new(info, private_key.as_deref().map(|key| key.as_slice())));
+            }
+            wallets
+        };
+        let groups = self.wallet_groups();
+        let policies = self.policies.lock().unwrap().policies().clone();
+        
+        let export = create_backup(wallets, groups, policies, backup_passphrase, options)?;
+        info!("Đã tạo bản sao lưu ví ({} mảnh khóa)", export.shares.len());
+        Ok(export)
+    }
+    
+    /// Khôi phục từ tệp sao lưu. Toàn bộ bản sao lưu được giải mã và kiểm tra trước khi ghi;
+    /// ví, nhóm và chính sách đã có chỉ bị ghi đè khi `overwrite`
+    pub fn restore_backup(&self, archive: &str, key: &BackupKey, overwrite: bool) -> Result<RestoreReport> {
+        let contents = open_backup(archive, key)?;
+        let mut report = RestoreReport::default();
+        
+        {
+            let mut storage = self.storage.write().unwrap();
+            for wallet in &contents.wallets {
+                let address = &wallet.info.address;
+                let private_key = wallet.private_key()?;
+                let existing = storage.wallet_info(address).cloned();
+                // Không hạ ví đang có khóa xuống ví chỉ theo dõi
+                let downgrade = private_key.is_none() && existing.as_ref().is_some_and(|info| !info.watch_only);
+                if (existing.is_some() && !overwrite) || downgrade {
+                    report.skipped_wallets.push(address.clone());
+                    continue;
+                }
+                storage.restore_wallet(&wallet.info, private_key.as_deref().map(|key| key.as_slice()))?;
+                report.restored_wallets.push(address.clone());
+            }
+        }
+        
+        {
+            let mut groups = self.groups.write().unwrap();
+            for group in &contents.groups {
+                if groups.get(&group.name).is_some() && !overwrite {
+                    report.skipped_groups.push(group.name.clone());
+                    continue;
+                }
+                groups.upsert(group.clone())?;
+                report.restored_groups.push(group.name.clone());
+            }
+        }
+        
+        let mut policies = self.policies.lock().unwrap();
+        for (address, policy) in &contents.policies {
+            if policies.policies().contains_key(address) && !overwrite {
+                report.skipped_policies += 1;
+                continue;
+            }
+            policies.set_policy(*address, policy.clone())?;
+            report.restored_policies += 1;
+        }
+        
+        info!(
+            "Đã khôi phục {} ví, {} nhóm, {} chính sách từ bản sao lưu",
+            report.restored_wallets.len(), report.restored_groups.len(), report.restored_policies
+        );
+        Ok(report)
+    }
+    
+    /// Lấy danh sách tất cả các ví (kèm nhóm của từng ví)
     pub fn list_wallets(&self) -> Result<Vec<SafeWalletView>> {
+        let mut wallets: Vec<SafeWalletView> = self.storage.read().unwrap()
+            .wallet_infos()
+            .iter()
+            .map(WalletInfo::to_safe_view)
+            .collect();
+        
+        // Khóa nằm trong keystore, không nằm trong index
         let storage = self.storage.read().unwrap();
-        let wallets = storage.get_all_wallets();
+        let groups = self.groups.read().unwrap();
+        for wallet in wallets.iter_mut() {
+            wallet.has_private_key = storage.has_keystore(&wallet.address);
+            if let Ok(address) = Address::from_str(&wallet.address) {
+                wallet.groups = groups.groups_of(address);
+            }
+        }
+        
+        Ok(wallets)
+    }
+    
+    /// Danh sách ví kèm nhóm và số dư native hiện tại (wei)
+    pub async fn list_wallets_with_balances(&self, provider: StdArc<Provider<Http>>) -> Result<Vec<SafeWalletView>> {
+        let mut wallets = self.list_wallets()?;
+        for wallet in wallets.iter_mut() {
+            let address = Address::from_str(&wallet.address)?;
+            match provider.get_balance(address, None).await {
+                Ok(balance) => wallet.balance = Some(balance.to_string()),
+                Err(e) => warn!("Không thể lấy số dư ví {}: {}", wallet.address, e),
+            }
+        }
         
         Ok(wallets)
     }
@@ -334,21 +776,21 @@ impl WalletManager {
     pub fn remove_wallet(&self, address: &str) -> bool {
         // Xóa khỏi storage
         let result = {
-            let mut storage = self.storage.write().unwrap();
-            let result = storage.remove_wallet(address);
-            if result.is_ok() {
-                let _ = storage.save_to_file();
-                true
-            } else {
-                false
-            }
+            let key = Address::from_str(address)
+                .map(storage_key)
+                .unwrap_or_else(|_| address.to_string());
+            self.storage.write().unwrap().delete_wallet(&key).is_ok()
         };
         
         if result {
-            // Xóa khỏi cache nếu xóa từ storage thành công
+            // Xóa khỏi cache và khỏi các nhóm nếu xóa từ storage thành công
             if let Ok(address_obj) = Address::from_str(address) {
                 let mut wallets = self.wallets.write().unwrap();
                 wallets.remove(&address_obj);
+                
+                if let Err(e) = self.groups.write().unwrap().remove_wallet(address_obj) {
+                    warn!("Không thể gỡ ví {} khỏi nhóm: {}", address, e);
+                }
             }
         }
         
@@ -389,6 +831,68 @@ impl WalletManager {
     }
 }
 
+/// Signer gắn với một ví trong WalletManager, dùng được với `SignerMiddleware`.
+/// Không giữ private key: ký giao dịch qua chính sách chi tiêu rồi WalletSigner của ví
+/// (signer từ xa nếu đã đăng ký); thông điệp qua `WalletManager::sign_message`,
+/// typed data phải ký qua `WalletManager::sign_permit`
+#[derive(Clone)]
+pub struct ManagedSigner {
+    manager: Arc<WalletManager>,
+    address: Address,
+    chain_id: u64,
+}
+
+impl std::fmt::Debug for ManagedSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManagedSigner")
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+impl ManagedSigner {
+    /// Ký giao dịch qua `WalletManager::sign_transaction`, trả về giao dịch đã ký dạng RLP
+    pub async fn sign_raw_transaction(&self, tx: &TypedTransaction) -> Result<Bytes> {
+        self.manager.sign_transaction(self.address, tx, self.chain_id).await
+    }
+}
+
+#[async_trait]
+impl Signer for ManagedSigner {
+    type Error = SignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature, Self::Error> {
+        self.manager.sign_message(self.address, self.chain_id, message.as_ref()).await
+            .map_err(|e| SignerError::Rejected(e.to_string()))
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let mut tx = tx.clone();
+        if tx.chain_id().is_none() {
+            tx.set_chain_id(self.chain_id);
+        }
+        self.manager.authorize_and_sign(self.address, &tx, self.chain_id).await
+            .map_err(|e| SignerError::Rejected(e.to_string()))
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, _payload: &T) -> Result<Signature, Self::Error> {
+        Err(SignerError::Unsupported("typed data must be signed through WalletManager::sign_permit".to_string()))
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        Self { chain_id: chain_id.into(), ..self }
+    }
+}
+
 /// Extension để tạo client có khả năng ký giao dịch từ Provider
 pub trait WalletClientExt {
     fn with_wallet(self, wallet_info: &WalletInfo, encryption_key: &[u8; 32]) -> Result<SignerMiddleware<Provider<Http>, LocalWallet>>;
@@ -423,22 +927,92 @@ impl WalletClientExt for Provider<Http> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::policy::AUDIT_FILE;
+    
+    const TEST_PASSPHRASE: &str = "diamond wallet test passphrase";
+    
+    fn test_manager(dir: &tempfile::TempDir) -> Arc<WalletManager> {
+        Arc::new(WalletManager::new(WalletManagerConfig {
+            default_chain_id: 1,
+            storage_config: StorageConfig {
+                wallet_dir: dir.path().to_string_lossy().to_string(),
+                master_key: MasterKeyParams::light(),
+                ..Default::default()
+            },
+            wallet_encryption_seed: TEST_PASSPHRASE.to_string(),
+            default_policy: None,
+        }).unwrap())
+    }
     
     #[tokio::test]
-    async fn test_wallet_management() -> Result<()> {
-        // Test code
+    async fn test_managed_signer_messages_go_through_policy_and_audit() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let manager = test_manager(&dir);
+        let address = import_random_wallet(&manager);
+        manager.set_spending_policy(address, SpendingPolicy { allowed_chains: Some(vec![1]), ..Default::default() })?;
+        
+        let signature = manager.managed_signer(address, 1)?.sign_message("relay auth").await?;
+        assert_eq!(signature.recover("relay auth")?, address);
+        
+        let error = manager.managed_signer(address, 56)?.sign_message("relay auth").await.unwrap_err();
+        assert!(matches!(error, SignerError::Rejected(ref reason) if reason.contains("56")), "{}", error);
+        
+        let audit = std::fs::read_to_string(dir.path().join(AUDIT_FILE))?;
+        assert_eq!(audit.lines().count(), 2);
+        assert!(audit.lines().all(|line| line.contains(&format!("{:?}", hash_message("relay auth")))));
         Ok(())
     }
     
+    fn import_random_wallet(manager: &WalletManager) -> Address {
+        let wallet = LocalWallet::new(&mut rand::thread_rng());
+        manager.import_private_key(&hex::encode(wallet.signer().to_bytes()), None).unwrap()
+    }
+    
     #[tokio::test]
-    async fn test_import_from_private_key() -> Result<()> {
-        // Test code
+    async fn test_group_leases_lock_wallets_until_dropped() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let manager = test_manager(&dir);
+        let members = [import_random_wallet(&manager), import_random_wallet(&manager)];
+        for member in members {
+            manager.add_to_group("snipers", member)?;
+        }
+        // Round-robin không đọc chain nên provider không cần kết nối được
+        let provider = StdArc::new(Provider::<Http>::try_from("http://127.0.0.1:9")?);
+        
+        let first = manager.acquire_group_wallet("snipers", provider.clone()).await?;
+        let second = manager.acquire_group_wallet("snipers", provider.clone()).await?;
+        assert_ne!(first.address(), second.address());
+        assert!(members.iter().all(|member| manager.is_wallet_locked(*member)));
+        let error = manager.acquire_group_wallet("snipers", provider.clone()).await.unwrap_err();
+        assert!(error.to_string().contains("pending transactions"), "{}", error);
+        
+        // Signer của lease ký bằng chính ví được khóa
+        assert_eq!(manager.managed_signer(first.address(), 1)?.address(), first.address());
+        
+        let released = first.address();
+        drop(first);
+        assert!(!manager.is_wallet_locked(released));
+        assert_eq!(manager.acquire_group_wallet("snipers", provider).await?.address(), released);
         Ok(())
     }
     
     #[tokio::test]
-    async fn test_create_hd_wallets() -> Result<()> {
-        // Test code
+    async fn test_removed_wallet_leaves_its_groups() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let manager = test_manager(&dir);
+        let kept = import_random_wallet(&manager);
+        let removed = import_random_wallet(&manager);
+        for member in [kept, removed] {
+            manager.add_to_group("snipers", member)?;
+        }
+        
+        assert!(manager.remove_wallet(&format!("{:?}", removed)));
+        assert_eq!(manager.wallet_groups()[0].members, vec![kept]);
+        
+        // Thành viên nhóm được lưu cùng thư mục ví
+        drop(manager);
+        let reopened = test_manager(&dir);
+        assert_eq!(reopened.wallet_groups()[0].members, vec![kept]);
         Ok(())
     }
 }